@@ -0,0 +1,12 @@
+//! Contact summaries and reply drafts shared with the backend
+//!
+//! The backend's summary and reply code compiled into this crate, so MCP
+//! clients get the same summary, next-best-action and template replies as
+//! the REST API.
+
+#[path = "../../src/ai/ai_summary.rs"]
+pub mod ai_summary;
+
+#[path = "../../src/ai/ai_reply.rs"]
+#[allow(dead_code)]
+pub mod ai_reply;
@@ -0,0 +1,87 @@
+//! Bearer-token authentication for the HTTP transport
+//!
+//! Tokens are configured as `token:scope` pairs, e.g.
+//! `MCP_AUTH_TOKENS="s3cret:read_write,dashb0ard:read_only"`. Stdio runs as
+//! the local user and always has full access.
+
+use crate::error::McpError;
+use crate::tools::is_read_only;
+
+/// What a caller may do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Only tools that leave the CRM untouched
+    ReadOnly,
+    /// Every tool
+    ReadWrite,
+}
+
+impl Scope {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "read_only" | "read" => Some(Scope::ReadOnly),
+            "read_write" | "write" => Some(Scope::ReadWrite),
+            _ => None,
+        }
+    }
+
+    /// Whether a tool may be called with this scope
+    pub fn allows_tool(&self, name: &str) -> bool {
+        match self {
+            Scope::ReadOnly => is_read_only(name),
+            Scope::ReadWrite => true,
+        }
+    }
+}
+
+/// A bearer token and the scope it grants
+#[derive(Clone)]
+pub struct ApiToken {
+    token: String,
+    pub scope: Scope,
+}
+
+impl std::fmt::Debug for ApiToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never log the secret itself
+        f.debug_struct("ApiToken").field("scope", &self.scope).finish_non_exhaustive()
+    }
+}
+
+/// Parse a comma-separated list of `token:scope` pairs
+pub fn parse_tokens(spec: &str) -> Result<Vec<ApiToken>, McpError> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (token, scope) = entry
+                .rsplit_once(':')
+                .ok_or_else(|| McpError::Config("auth token entries must look like token:scope".into()))?;
+            let scope = Scope::parse(scope.trim()).ok_or_else(|| {
+                McpError::Config(format!("unknown token scope '{}', use read_only or read_write", scope))
+            })?;
+            if token.trim().is_empty() {
+                return Err(McpError::Config("auth token must not be empty".into()));
+            }
+            Ok(ApiToken {
+                token: token.trim().to_string(),
+                scope,
+            })
+        })
+        .collect()
+}
+
+/// Scope granted by an `Authorization: Bearer <token>` header value, if any
+pub fn authenticate(tokens: &[ApiToken], authorization: Option<&str>) -> Option<Scope> {
+    let presented = authorization?.strip_prefix("Bearer ")?.trim();
+
+    tokens
+        .iter()
+        .find(|t| constant_time_eq(t.token.as_bytes(), presented.as_bytes()))
+        .map(|t| t.scope)
+}
+
+/// Compare without bailing out at the first differing byte
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
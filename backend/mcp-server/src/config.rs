@@ -1,5 +1,7 @@
 //! Configuration for MCP server
 
+use crate::auth::ApiToken;
+
 /// MCP Server configuration
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -9,6 +11,12 @@ pub struct Config {
     pub db_namespace: String,
     /// Database name
     pub db_name: String,
+    /// Workspace (tenant) every tool call is scoped to
+    pub workspace_id: String,
+    /// Bearer tokens accepted by the HTTP transport
+    pub auth_tokens: Vec<ApiToken>,
+    /// Stdio requests allowed to run against the database at once
+    pub max_concurrency: usize,
 }
 
 impl Default for Config {
@@ -17,6 +25,9 @@ impl Default for Config {
             db_url: "ws://localhost:8000".into(),
             db_namespace: "crm".into(),
             db_name: "main".into(),
+            workspace_id: "default".into(),
+            auth_tokens: Vec::new(),
+            max_concurrency: 8,
         }
     }
 }
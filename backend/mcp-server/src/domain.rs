@@ -0,0 +1,28 @@
+//! Contact and interaction rules shared with the backend
+//!
+//! These are the backend's own domain files, compiled into this crate, so
+//! MCP tools enforce exactly the validation the REST API does.
+
+#[path = "../../src/domain/errors.rs"]
+pub mod errors;
+
+#[path = "../../src/domain/validation.rs"]
+#[allow(dead_code)]
+pub mod validation;
+
+#[path = "../../src/domain/churn.rs"]
+#[allow(dead_code)]
+pub mod churn;
+
+#[path = "../../src/domain/contact.rs"]
+#[allow(dead_code)]
+pub mod contact;
+
+#[path = "../../src/domain/interaction.rs"]
+#[allow(dead_code)]
+pub mod interaction;
+
+pub use contact::{ContactBuilder, ContactStatus};
+pub use errors::{DomainError, DomainResult};
+pub use interaction::{CallMetadata, EmailMetadata, MeetingMetadata};
+pub use validation::{validate_email, validate_linkedin_url, validate_name, validate_phone, validate_tag};
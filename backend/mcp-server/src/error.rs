@@ -1,7 +1,28 @@
 //! Error types for MCP server
 
+use serde::Serialize;
 use thiserror::Error;
 
+use crate::domain::DomainError;
+
+/// One rejected tool argument
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldViolation {
+    pub field: String,
+    pub code: String,
+    pub message: String,
+}
+
+impl FieldViolation {
+    pub fn new(field: impl Into<String>, code: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum McpError {
     #[error("Database error: {0}")]
@@ -22,6 +43,15 @@ pub enum McpError {
     #[error("Invalid parameters: {0}")]
     InvalidParams(String),
 
+    #[error("Validation failed: {}", summarize(.0))]
+    Validation(Vec<FieldViolation>),
+
+    #[error("Not allowed: {0}")]
+    Forbidden(String),
+
+    #[error("Configuration error: {0}")]
+    Config(String),
+
     #[error("Not implemented: {0}")]
     NotImplemented(String),
 
@@ -35,10 +65,38 @@ impl McpError {
         match self {
             McpError::InvalidRequest(_) => -32600,
             McpError::ToolNotFound(_) => -32601,
-            McpError::InvalidParams(_) => -32602,
+            McpError::InvalidParams(_) | McpError::Validation(_) => -32602,
             McpError::Internal(_) => -32603,
+            McpError::Forbidden(_) => -32003,
             McpError::Json(_) => -32700,
             _ => -32000, // Server error
         }
     }
 }
+
+impl From<DomainError> for McpError {
+    fn from(error: DomainError) -> Self {
+        McpError::Validation(
+            error
+                .violations()
+                .into_iter()
+                .map(|v| {
+                    let field = v.field().unwrap_or(match v {
+                        DomainError::DuplicateEmail { .. } => "email",
+                        DomainError::InvalidStateTransition { .. } => "status",
+                        _ => "contact",
+                    });
+                    FieldViolation::new(field, v.code(), v.to_string())
+                })
+                .collect(),
+        )
+    }
+}
+
+fn summarize(violations: &[FieldViolation]) -> String {
+    violations
+        .iter()
+        .map(|v| format!("{}: {}", v.field, v.message))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
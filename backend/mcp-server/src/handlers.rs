@@ -2,15 +2,26 @@
 //!
 //! Handles JSON-RPC requests and dispatches to appropriate tool implementations.
 
+use futures::stream::{self, StreamExt};
+use serde::de::DeserializeOwned;
 use serde_json::{json, Value};
 use surrealdb::engine::remote::ws::{Client, Ws};
+use surrealdb::sql::Thing;
 use surrealdb::Surreal;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
+use crate::ai::{ai_reply, ai_summary};
+use crate::auth::Scope;
 use crate::config::Config;
-use crate::error::McpError;
+use crate::domain::{
+    validate_email, validate_linkedin_url, validate_name, validate_phone, validate_tag, ContactBuilder,
+    ContactStatus, DomainError,
+};
+use crate::error::{FieldViolation, McpError};
+use crate::models::{Sentiment, TimelineEntry, TimelineEntryType};
+use crate::prompts::{self, get_prompt_definitions};
 use crate::protocol::*;
-use crate::tools::get_tool_definitions;
+use crate::tools::{get_tool_definitions, is_read_only};
 
 /// Initialize database connection
 pub async fn init_db(config: &Config) -> Result<Surreal<Client>, McpError> {
@@ -23,21 +34,136 @@ pub async fn init_db(config: &Config) -> Result<Surreal<Client>, McpError> {
         .await
         .map_err(|e| McpError::Database(e.to_string()))?;
 
+    // Every query below filters on the session-level $workspace parameter
+    db.set("workspace", Thing::from(("workspace", config.workspace_id.as_str())))
+        .await
+        .map_err(|e| McpError::Database(e.to_string()))?;
+
     info!("Connected to database: {}", config.db_url);
     Ok(db)
 }
 
+/// Handle one raw message from a transport: a single request or a batch array.
+///
+/// Returns the serialized reply, or None when nothing is owed because the
+/// message held only notifications. `scope` is what the caller authenticated as.
+pub async fn handle_message(db: &Surreal<Client>, scope: Scope, message: &str) -> Option<String> {
+    match serde_json::from_str(message) {
+        Ok(value) => handle_json(db, scope, value).await,
+        Err(e) => Some(parse_error(e)),
+    }
+}
+
+/// Reply to a message that isn't valid JSON
+pub fn parse_error(e: serde_json::Error) -> String {
+    let response = JsonRpcResponse::error(None, -32700, format!("Parse error: {}", e));
+    serde_json::to_string(&response).unwrap()
+}
+
+/// Most requests one batch may hold
+const MAX_BATCH_LEN: usize = 100;
+
+/// Most reads of one batch that run at once
+const BATCH_READ_CONCURRENCY: usize = 8;
+
+/// Handle an already parsed message, see [`handle_message`]
+pub async fn handle_json(db: &Surreal<Client>, scope: Scope, value: Value) -> Option<String> {
+    match value {
+        Value::Array(items) if items.is_empty() => {
+            let response = JsonRpcResponse::error(None, -32600, "Invalid Request: empty batch".into());
+            Some(serde_json::to_string(&response).unwrap())
+        }
+        Value::Array(items) if items.len() > MAX_BATCH_LEN => {
+            let response = JsonRpcResponse::error(
+                None,
+                -32600,
+                format!("Invalid Request: a batch holds at most {} requests", MAX_BATCH_LEN),
+            );
+            Some(serde_json::to_string(&response).unwrap())
+        }
+        Value::Array(items) => {
+            let responses = handle_batch(db, scope, items).await;
+            (!responses.is_empty()).then(|| serde_json::to_string(&responses).unwrap())
+        }
+        value => handle_value(db, scope, value)
+            .await
+            .map(|response| serde_json::to_string(&response).unwrap()),
+    }
+}
+
+/// Handle a JSON-RPC batch, answering in request order.
+///
+/// Reads run concurrently, at most `BATCH_READ_CONCURRENCY` at a time. A
+/// tool call that writes waits for the reads before it and runs on its
+/// own, so writes land in the order they were sent.
+async fn handle_batch(db: &Surreal<Client>, scope: Scope, items: Vec<Value>) -> Vec<JsonRpcResponse> {
+    debug!("Handling batch of {} requests", items.len());
+
+    let mut responses = Vec::with_capacity(items.len());
+    let mut reads = Vec::new();
+
+    for item in items {
+        if writes(&item) {
+            responses.extend(run_reads(std::mem::take(&mut reads)).await);
+            responses.extend(handle_value(db, scope, item).await);
+        } else {
+            reads.push(handle_value(db, scope, item));
+        }
+    }
+    responses.extend(run_reads(reads).await);
+
+    responses
+}
+
+/// Run a batch's pending reads, keeping their order
+async fn run_reads<F>(reads: Vec<F>) -> Vec<JsonRpcResponse>
+where
+    F: std::future::Future<Output = Option<JsonRpcResponse>>,
+{
+    stream::iter(reads)
+        .buffered(BATCH_READ_CONCURRENCY)
+        .filter_map(|response| async move { response })
+        .collect()
+        .await
+}
+
+/// Whether a batch item is a call to a tool that writes to the CRM
+fn writes(item: &Value) -> bool {
+    item.get("method").and_then(|v| v.as_str()) == Some("tools/call")
+        && !item
+            .pointer("/params/name")
+            .and_then(|v| v.as_str())
+            .is_some_and(is_read_only)
+}
+
+/// Handle a single request object; notifications get no reply
+async fn handle_value(db: &Surreal<Client>, scope: Scope, value: Value) -> Option<JsonRpcResponse> {
+    let request: JsonRpcRequest = match serde_json::from_value(value) {
+        Ok(request) => request,
+        Err(e) => {
+            return Some(JsonRpcResponse::error(None, -32600, format!("Invalid Request: {}", e)));
+        }
+    };
+
+    let is_notification = request.id.is_none();
+    let response = handle_request(db, scope, request).await;
+    (!is_notification).then_some(response)
+}
+
 /// Handle incoming JSON-RPC request
-pub async fn handle_request(db: &Surreal<Client>, request: JsonRpcRequest) -> JsonRpcResponse {
+pub async fn handle_request(db: &Surreal<Client>, scope: Scope, request: JsonRpcRequest) -> JsonRpcResponse {
     debug!("Handling request: {}", request.method);
 
     match request.method.as_str() {
         "initialize" => handle_initialize(request.id),
         "initialized" => JsonRpcResponse::success(request.id, json!({})),
-        "tools/list" => handle_list_tools(request.id),
-        "tools/call" => handle_call_tool(db, request.id, request.params).await,
+        "tools/list" => handle_list_tools(request.id, scope),
+        "tools/call" => handle_call_tool(db, scope, request.id, request.params).await,
         "resources/list" => handle_list_resources(request.id),
+        "resources/templates/list" => handle_list_resource_templates(request.id),
         "resources/read" => handle_read_resource(db, request.id, request.params).await,
+        "prompts/list" => handle_list_prompts(request.id),
+        "prompts/get" => handle_get_prompt(db, request.id, request.params).await,
         "ping" => JsonRpcResponse::success(request.id, json!({})),
         _ => {
             error!("Unknown method: {}", request.method);
@@ -59,6 +185,7 @@ fn handle_initialize(id: Option<Value>) -> JsonRpcResponse {
                 subscribe: false,
                 list_changed: false,
             },
+            prompts: PromptsCapability { list_changed: false },
         },
         server_info: ServerInfo {
             name: "crm-mcp-server".into(),
@@ -68,13 +195,18 @@ fn handle_initialize(id: Option<Value>) -> JsonRpcResponse {
     JsonRpcResponse::success(id, serde_json::to_value(result).unwrap())
 }
 
-fn handle_list_tools(id: Option<Value>) -> JsonRpcResponse {
-    let tools = get_tool_definitions();
+fn handle_list_tools(id: Option<Value>, scope: Scope) -> JsonRpcResponse {
+    // Only advertise what the caller is allowed to call
+    let tools: Vec<_> = get_tool_definitions()
+        .into_iter()
+        .filter(|tool| scope.allows_tool(&tool.name))
+        .collect();
     JsonRpcResponse::success(id, json!({ "tools": tools }))
 }
 
 async fn handle_call_tool(
     db: &Surreal<Client>,
+    scope: Scope,
     id: Option<Value>,
     params: Option<Value>,
 ) -> JsonRpcResponse {
@@ -88,26 +220,46 @@ async fn handle_call_tool(
     let tool_name = params.get("name").and_then(|v| v.as_str()).unwrap_or("");
     let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
 
+    if !scope.allows_tool(tool_name) {
+        warn!("Rejected out-of-scope call to {}", tool_name);
+        let e = McpError::Forbidden(format!("token is read-only and cannot call {}", tool_name));
+        return JsonRpcResponse::error(id, e.error_code(), e.to_string());
+    }
+
     info!("Calling tool: {} with args: {}", tool_name, arguments);
 
+    // Most tools answer with one text item; a summary also carries its data as JSON
     let result = match tool_name {
-        "search_contacts" => search_contacts(db, arguments).await,
-        "get_contact_details" => get_contact_details(db, arguments).await,
-        "create_contact" => create_contact(db, arguments).await,
-        "update_contact" => update_contact(db, arguments).await,
-        "log_interaction" => log_interaction(db, arguments).await,
-        "suggest_campaign_contacts" => suggest_campaign_contacts(db, arguments).await,
-        "draft_campaign_content" => draft_campaign_content(arguments).await,
-        "get_pipeline_summary" => get_pipeline_summary(db, arguments).await,
-        "get_engagement_insights" => get_engagement_insights(db, arguments).await,
-        _ => Err(McpError::ToolNotFound(tool_name.into())),
+        "get_contact_summary" => get_contact_summary(db, arguments).await,
+        _ => call_tool(db, tool_name, arguments).await.map(|text| vec![text]),
     };
 
     match result {
         Ok(content) => JsonRpcResponse::success(
             id,
             json!({
-                "content": [{ "type": "text", "text": content }]
+                "content": content
+                    .into_iter()
+                    .map(|text| json!({ "type": "text", "text": text }))
+                    .collect::<Vec<_>>()
+            }),
+        ),
+        // Field-level detail so the client can fix the arguments and retry
+        Err(McpError::Validation(violations)) => JsonRpcResponse::success(
+            id,
+            json!({
+                "content": [
+                    { "type": "text", "text": format!("Error: {}", McpError::Validation(violations.clone())) },
+                    {
+                        "type": "text",
+                        "text": serde_json::to_string_pretty(&json!({
+                            "error": "validation_failed",
+                            "errors": violations
+                        }))
+                        .unwrap()
+                    }
+                ],
+                "isError": true
             }),
         ),
         Err(e) => JsonRpcResponse::success(
@@ -120,6 +272,22 @@ async fn handle_call_tool(
     }
 }
 
+async fn call_tool(db: &Surreal<Client>, tool_name: &str, arguments: Value) -> Result<String, McpError> {
+    match tool_name {
+        "search_contacts" => search_contacts(db, arguments).await,
+        "get_contact_details" => get_contact_details(db, arguments).await,
+        "create_contact" => create_contact(db, arguments).await,
+        "update_contact" => update_contact(db, arguments).await,
+        "log_interaction" => log_interaction(db, arguments).await,
+        "draft_reply" => draft_reply(db, arguments).await,
+        "suggest_campaign_contacts" => suggest_campaign_contacts(db, arguments).await,
+        "draft_campaign_content" => draft_campaign_content(arguments).await,
+        "get_pipeline_summary" => get_pipeline_summary(db, arguments).await,
+        "get_engagement_insights" => get_engagement_insights(db, arguments).await,
+        _ => Err(McpError::ToolNotFound(tool_name.into())),
+    }
+}
+
 fn handle_list_resources(id: Option<Value>) -> JsonRpcResponse {
     let resources = vec![
         ResourceDefinition {
@@ -138,6 +306,30 @@ fn handle_list_resources(id: Option<Value>) -> JsonRpcResponse {
     JsonRpcResponse::success(id, json!({ "resources": resources }))
 }
 
+fn handle_list_resource_templates(id: Option<Value>) -> JsonRpcResponse {
+    let templates = vec![
+        ResourceTemplateDefinition {
+            uri_template: "crm://contact/{id}".into(),
+            name: "Contact".into(),
+            description: "A contact with its recent interactions and deals".into(),
+            mime_type: "application/json".into(),
+        },
+        ResourceTemplateDefinition {
+            uri_template: "crm://company/{id}".into(),
+            name: "Company".into(),
+            description: "A company with its contacts and deals".into(),
+            mime_type: "application/json".into(),
+        },
+        ResourceTemplateDefinition {
+            uri_template: "crm://campaign/{id}".into(),
+            name: "Campaign".into(),
+            description: "A campaign with its assets and recipient counts by status".into(),
+            mime_type: "application/json".into(),
+        },
+    ];
+    JsonRpcResponse::success(id, json!({ "resourceTemplates": templates }))
+}
+
 async fn handle_read_resource(
     db: &Surreal<Client>,
     id: Option<Value>,
@@ -152,7 +344,12 @@ async fn handle_read_resource(
     let result = match uri {
         "crm://contacts/recent" => get_recent_contacts(db).await,
         "crm://pipeline/summary" => get_pipeline_summary(db, json!({})).await,
-        _ => Err(McpError::InvalidRequest(format!("Unknown resource: {}", uri))),
+        _ => match uri.strip_prefix("crm://").and_then(|rest| rest.split_once('/')) {
+            Some(("contact", record_id)) if !record_id.is_empty() => get_contact_resource(db, record_id).await,
+            Some(("company", record_id)) if !record_id.is_empty() => get_company_resource(db, record_id).await,
+            Some(("campaign", record_id)) if !record_id.is_empty() => get_campaign_resource(db, record_id).await,
+            _ => Err(McpError::InvalidRequest(format!("Unknown resource: {}", uri))),
+        },
     };
 
     match result {
@@ -170,6 +367,123 @@ async fn handle_read_resource(
     }
 }
 
+fn handle_list_prompts(id: Option<Value>) -> JsonRpcResponse {
+    let prompts = get_prompt_definitions();
+    JsonRpcResponse::success(id, json!({ "prompts": prompts }))
+}
+
+async fn handle_get_prompt(
+    db: &Surreal<Client>,
+    id: Option<Value>,
+    params: Option<Value>,
+) -> JsonRpcResponse {
+    let params: GetPromptParams = match params.map(serde_json::from_value) {
+        Some(Ok(p)) => p,
+        _ => return JsonRpcResponse::error(id, -32602, "Missing or invalid params".into()),
+    };
+    let args = params.arguments.unwrap_or(json!({}));
+
+    let result = match params.name.as_str() {
+        "weekly_pipeline_review" => weekly_pipeline_review_prompt(db, args).await,
+        "draft_follow_up" => draft_follow_up_prompt(db, args).await,
+        "meeting_prep" => meeting_prep_prompt(db, args).await,
+        _ => Err(McpError::InvalidParams(format!("Unknown prompt: {}", params.name))),
+    };
+
+    match result {
+        Ok(result) => JsonRpcResponse::success(id, serde_json::to_value(result).unwrap()),
+        Err(e) => JsonRpcResponse::error(id, e.error_code(), e.to_string()),
+    }
+}
+
+// =============================================================================
+// Prompt Implementations
+// =============================================================================
+
+/// Prompt arguments arrive as strings; numbers are parsed here
+fn prompt_arg<'a>(args: &'a Value, name: &str) -> Option<&'a str> {
+    args.get(name).and_then(|v| v.as_str()).filter(|v| !v.trim().is_empty())
+}
+
+async fn weekly_pipeline_review_prompt(db: &Surreal<Client>, args: Value) -> Result<GetPromptResult, McpError> {
+    let stale_days = prompt_arg(&args, "stale_after_days")
+        .map(|d| {
+            d.trim()
+                .parse::<u64>()
+                .map_err(|_| McpError::InvalidParams("stale_after_days must be a whole number".into()))
+        })
+        .transpose()?
+        .unwrap_or(14);
+
+    let pipeline = get_pipeline_summary(db, json!({})).await?;
+    let hot = get_engagement_insights(db, json!({ "insight_type": "hot_prospects", "limit": 10 })).await?;
+    let stale = get_engagement_insights(
+        db,
+        json!({ "insight_type": "stale_leads", "days_threshold": stale_days, "limit": 10 }),
+    )
+    .await?;
+
+    Ok(GetPromptResult {
+        description: "Weekly pipeline review".into(),
+        messages: vec![PromptMessage::user(prompts::weekly_pipeline_review(
+            &pipeline, &hot, &stale, stale_days,
+        ))],
+    })
+}
+
+async fn draft_follow_up_prompt(db: &Surreal<Client>, args: Value) -> Result<GetPromptResult, McpError> {
+    let contact_id = prompt_arg(&args, "contact_id")
+        .ok_or_else(|| McpError::InvalidParams("contact_id is required".into()))?;
+
+    let contact = get_contact_details(db, json!({ "contact_id": contact_id, "timeline_limit": 10 })).await?;
+
+    Ok(GetPromptResult {
+        description: format!("Follow-up draft for contact {}", contact_id),
+        messages: vec![PromptMessage::user(prompts::draft_follow_up(
+            &contact,
+            prompt_arg(&args, "goal"),
+        ))],
+    })
+}
+
+async fn meeting_prep_prompt(db: &Surreal<Client>, args: Value) -> Result<GetPromptResult, McpError> {
+    let contact = prompt_arg(&args, "contact")
+        .ok_or_else(|| McpError::InvalidParams("contact is required".into()))?;
+
+    let contact_id = resolve_contact_id(db, contact).await?;
+    let details = get_contact_details(db, json!({ "contact_id": contact_id, "timeline_limit": 25 })).await?;
+
+    Ok(GetPromptResult {
+        description: format!("Meeting brief for {}", contact),
+        messages: vec![PromptMessage::user(prompts::meeting_prep(
+            &details,
+            prompt_arg(&args, "agenda"),
+        ))],
+    })
+}
+
+/// Find a contact by ID, email or name; the best-engaged match wins
+async fn resolve_contact_id(db: &Surreal<Client>, contact: &str) -> Result<String, McpError> {
+    let contact = contact.trim();
+
+    let mut result = db
+        .query(
+            "SELECT VALUE id FROM contact WHERE workspace = $workspace AND deleted_at IS NONE AND \
+                (id = type::thing('contact', $contact) OR email = string::lowercase($contact) \
+                 OR string::lowercase(first_name + ' ' + last_name) = string::lowercase($contact)) \
+             ORDER BY engagement_score DESC LIMIT 1",
+        )
+        .bind(("contact", contact))
+        .await
+        .map_err(|e| McpError::Database(e.to_string()))?;
+    let ids: Vec<Thing> = result.take(0).map_err(|e| McpError::Database(e.to_string()))?;
+
+    ids.into_iter()
+        .next()
+        .map(|t| t.id.to_raw())
+        .ok_or_else(|| McpError::InvalidParams(format!("No contact matches '{}'", contact)))
+}
+
 // =============================================================================
 // Tool Implementations
 // =============================================================================
@@ -185,7 +499,7 @@ async fn search_contacts(db: &Surreal<Client>, args: Value) -> Result<String, Mc
     let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20);
 
     // Build SurrealQL query
-    let mut conditions = Vec::new();
+    let mut conditions = vec!["workspace = $workspace", "deleted_at IS NONE"];
     let mut bindings: Vec<(&str, Value)> = Vec::new();
 
     if let Some(q) = query {
@@ -208,11 +522,7 @@ async fn search_contacts(db: &Surreal<Client>, args: Value) -> Result<String, Mc
         bindings.push(("min_engagement", json!(e)));
     }
 
-    let where_clause = if conditions.is_empty() {
-        String::new()
-    } else {
-        format!("WHERE {}", conditions.join(" AND "))
-    };
+    let where_clause = format!("WHERE {}", conditions.join(" AND "));
 
     let sql = format!(
         "SELECT id, first_name, last_name, email, status, tags, engagement_score, company FROM contact {} ORDER BY engagement_score DESC LIMIT {}",
@@ -261,10 +571,12 @@ async fn get_contact_details(db: &Surreal<Client>, args: Value) -> Result<String
         .unwrap_or(10);
 
     // Get contact
-    let contact: Option<Value> = db
-        .select(("contact", contact_id))
+    let mut result = db
+        .query("SELECT * FROM type::thing('contact', $id) WHERE workspace = $workspace AND deleted_at IS NONE")
+        .bind(("id", contact_id))
         .await
         .map_err(|e| McpError::Database(e.to_string()))?;
+    let contact: Option<Value> = result.take(0).map_err(|e| McpError::Database(e.to_string()))?;
 
     let contact = contact.ok_or_else(|| McpError::InvalidParams("Contact not found".into()))?;
 
@@ -273,11 +585,12 @@ async fn get_contact_details(db: &Surreal<Client>, args: Value) -> Result<String
     // Get timeline if requested
     if include_timeline {
         let sql = format!(
-            "SELECT * FROM timeline_entry WHERE contact = contact:{} ORDER BY timestamp DESC LIMIT {}",
-            contact_id, timeline_limit
+            "SELECT * FROM timeline_entry WHERE workspace = $workspace AND contact = type::thing('contact', $id) ORDER BY timestamp DESC LIMIT {}",
+            timeline_limit
         );
         let mut result = db
             .query(&sql)
+            .bind(("id", contact_id))
             .await
             .map_err(|e| McpError::Database(e.to_string()))?;
         let timeline: Vec<Value> = result.take(0).map_err(|e| McpError::Database(e.to_string()))?;
@@ -287,61 +600,163 @@ async fn get_contact_details(db: &Surreal<Client>, args: Value) -> Result<String
     Ok(serde_json::to_string_pretty(&response).unwrap())
 }
 
-async fn create_contact(db: &Surreal<Client>, args: Value) -> Result<String, McpError> {
-    let first_name = args
-        .get("first_name")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| McpError::InvalidParams("first_name is required".into()))?;
-    let last_name = args
-        .get("last_name")
+async fn get_contact_summary(db: &Surreal<Client>, args: Value) -> Result<Vec<String>, McpError> {
+    let contact_id = args
+        .get("contact_id")
         .and_then(|v| v.as_str())
-        .ok_or_else(|| McpError::InvalidParams("last_name is required".into()))?;
-
-    let contact = json!({
-        "first_name": first_name,
-        "last_name": last_name,
-        "email": args.get("email"),
-        "phone": args.get("phone"),
-        "company": args.get("company"),
-        "linkedin_url": args.get("linkedin_url"),
-        "status": args.get("status").and_then(|v| v.as_str()).unwrap_or("lead"),
-        "tags": args.get("tags").unwrap_or(&json!([])),
-        "engagement_score": 0.0,
-        "created_at": chrono::Utc::now().to_rfc3339(),
-        "updated_at": chrono::Utc::now().to_rfc3339()
-    });
+        .ok_or_else(|| McpError::InvalidParams("contact_id is required".into()))?;
+    let timeline_limit = args
+        .get("timeline_limit")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(100);
 
-    let created: Vec<Value> = db
-        .create("contact")
-        .content(contact)
+    let sql = r#"
+        SELECT * FROM type::thing('contact', $id) WHERE workspace = $workspace AND deleted_at IS NONE;
+        SELECT * FROM timeline_entry WHERE workspace = $workspace AND contact = type::thing('contact', $id)
+            ORDER BY timestamp DESC LIMIT $limit;
+    "#;
+    let mut result = db
+        .query(sql)
+        .bind(("id", contact_id))
+        .bind(("limit", timeline_limit))
         .await
         .map_err(|e| McpError::Database(e.to_string()))?;
+    let contact: Option<Value> = result.take(0).map_err(|e| McpError::Database(e.to_string()))?;
+    let contact = contact.ok_or_else(|| McpError::InvalidParams("Contact not found".into()))?;
+    let rows: Vec<Value> = result.take(1).map_err(|e| McpError::Database(e.to_string()))?;
 
-    let created = created.first().cloned().unwrap_or(json!(null));
+    let entries: Vec<TimelineEntry> = rows.into_iter().filter_map(timeline_entry).collect();
+    let score = contact.get("engagement_score").and_then(|v| v.as_f64()).unwrap_or(0.0);
 
-    // Log initial note if provided
-    if let Some(notes) = args.get("notes").and_then(|v| v.as_str()) {
-        if !notes.is_empty() {
-            if let Some(id) = created.get("id") {
-                let timeline_entry = json!({
-                    "contact": id,
-                    "type": "note",
-                    "content": notes,
-                    "timestamp": chrono::Utc::now().to_rfc3339()
-                });
-                let _: Vec<Value> = db
-                    .create("timeline_entry")
-                    .content(timeline_entry)
-                    .await
-                    .map_err(|e| McpError::Database(e.to_string()))?;
-            }
+    // Same summary and next-best-action the REST API serves
+    let summary = ai_summary::summarize_timeline(&entries).await;
+    let insights = ai_summary::generate_engagement_insights(&entries, score).await;
+
+    let name = format!(
+        "{} {}",
+        contact["first_name"].as_str().unwrap_or_default(),
+        contact["last_name"].as_str().unwrap_or_default()
+    );
+    let text = format!(
+        "{}\n\n{}\n\nRecommendation: {}\nNext best action: {}",
+        name.trim(),
+        summary,
+        insights.recommendation,
+        insights.next_best_action
+    );
+    let data = json!({
+        "contact_id": contact_id,
+        "name": name.trim(),
+        "status": contact.get("status"),
+        "summary": summary,
+        "insights": insights,
+        "interactions_considered": entries.len(),
+    });
+
+    Ok(vec![text, serde_json::to_string_pretty(&data).unwrap()])
+}
+
+/// Read a stored timeline entry as the backend's model.
+///
+/// Entries logged before `log_interaction` stored backend types may carry
+/// its shorthand ones.
+fn timeline_entry(mut row: Value) -> Option<TimelineEntry> {
+    let kind = row.get("type").and_then(|v| v.as_str()).map(|t| stored_type(t).to_string());
+    if let Some(kind) = kind {
+        row["type"] = json!(kind);
+    }
+
+    serde_json::from_value(row)
+        .map_err(|e| warn!("Skipping unreadable timeline entry: {}", e))
+        .ok()
+}
+
+/// The backend timeline entry type for one of `log_interaction`'s types
+fn stored_type(interaction_type: &str) -> &str {
+    match interaction_type {
+        "meeting" => "meeting_attended",
+        "event" => "event_attend",
+        other => other,
+    }
+}
+
+async fn create_contact(db: &Surreal<Client>, args: Value) -> Result<String, McpError> {
+    // Same rules as POST /api/contacts: every field is checked and all
+    // violations come back together
+    let mut builder = ContactBuilder::new()
+        .first_name(str_arg(&args, "first_name").unwrap_or_default())
+        .last_name(str_arg(&args, "last_name").unwrap_or_default())
+        .email(str_arg(&args, "email").unwrap_or_default());
+    if let Some(phone) = str_arg(&args, "phone") {
+        builder = builder.phone(phone);
+    }
+    if let Some(url) = str_arg(&args, "linkedin_url") {
+        builder = builder.linkedin_url(url);
+    }
+    builder = builder.tags(string_list_arg(&args, "tags")?);
+    if let Some(status) = status_arg(&args)? {
+        builder = builder.status(status);
+    }
+    let contact = builder.build()?;
+
+    if let Some(existing_id) = find_contact_id_by_email(db, &contact.email, None).await? {
+        return Err(DomainError::DuplicateEmail {
+            email: contact.email,
+            existing_id,
         }
+        .into());
     }
 
+    let notes = str_arg(&args, "notes").map(str::trim).filter(|n| !n.is_empty());
+
+    // Created with SET rather than CONTENT so the workspace record link comes
+    // straight from the session parameter; the contact and its initial note
+    // are written together
+    let sql = r#"
+        BEGIN TRANSACTION;
+        LET $created = (CREATE contact SET
+            workspace = $workspace,
+            first_name = $first_name,
+            last_name = $last_name,
+            email = $email,
+            phone = $phone,
+            company = $company,
+            linkedin_url = $linkedin_url,
+            status = $status,
+            tags = $tags,
+            engagement_score = 0.0,
+            created_at = time::now(),
+            updated_at = time::now());
+        IF $notes != NONE {
+            CREATE timeline_entry SET workspace = $workspace, contact = $created[0].id, type = 'note', content = $notes, timestamp = time::now();
+        };
+        RETURN $created;
+        COMMIT TRANSACTION;
+    "#;
+
+    let mut result = db
+        .query(sql)
+        .bind(("first_name", contact.first_name.clone()))
+        .bind(("last_name", contact.last_name.clone()))
+        .bind(("email", contact.email.clone()))
+        .bind(("phone", contact.phone.clone()))
+        .bind(("company", args.get("company").cloned()))
+        .bind(("linkedin_url", contact.linkedin_url.clone()))
+        .bind(("status", contact.status))
+        .bind(("tags", contact.tags.clone()))
+        .bind(("notes", notes))
+        .await
+        .map_err(|e| McpError::Database(e.to_string()))?;
+
+    let last = result.num_statements() - 1;
+    let created: Vec<Value> = result.take(last).map_err(|e| McpError::Database(e.to_string()))?;
+
+    let created = created.first().cloned().unwrap_or(json!(null));
+
     Ok(serde_json::to_string_pretty(&json!({
         "success": true,
         "contact": created,
-        "message": format!("Created contact: {} {}", first_name, last_name)
+        "message": format!("Created contact: {}", contact.full_name())
     }))
     .unwrap())
 }
@@ -352,36 +767,114 @@ async fn update_contact(db: &Surreal<Client>, args: Value) -> Result<String, Mcp
         .and_then(|v| v.as_str())
         .ok_or_else(|| McpError::InvalidParams("contact_id is required".into()))?;
 
-    // Build update object
+    let mut result = db
+        .query("SELECT status, tags FROM type::thing('contact', $id) WHERE workspace = $workspace AND deleted_at IS NONE")
+        .bind(("id", contact_id))
+        .await
+        .map_err(|e| McpError::Database(e.to_string()))?;
+    let current: Option<Value> = result.take(0).map_err(|e| McpError::Database(e.to_string()))?;
+    let current = current.ok_or_else(|| McpError::InvalidParams("Contact not found".into()))?;
+
+    // Build update object, validating every field the same way the REST
+    // API's ContactUpdater does and collecting all violations
     let mut updates = json!({
         "updated_at": chrono::Utc::now().to_rfc3339()
     });
+    let mut violations: Vec<DomainError> = Vec::new();
+
+    for field in ["first_name", "last_name"] {
+        if let Some(name) = str_arg(&args, field) {
+            let name = name.trim();
+            match validate_name(name, field) {
+                Ok(()) => updates[field] = json!(name),
+                Err(e) => violations.push(e),
+            }
+        }
+    }
 
-    for field in [
-        "first_name",
-        "last_name",
-        "email",
-        "phone",
-        "company",
-        "linkedin_url",
-        "status",
-    ] {
-        if let Some(value) = args.get(field) {
-            updates[field] = value.clone();
+    if let Some(email) = str_arg(&args, "email") {
+        let email = email.trim().to_lowercase();
+        match validate_email(&email) {
+            Ok(()) => {
+                if let Some(existing_id) = find_contact_id_by_email(db, &email, Some(contact_id)).await? {
+                    return Err(DomainError::DuplicateEmail { email, existing_id }.into());
+                }
+                updates["email"] = json!(email);
+            }
+            Err(e) => violations.push(e),
         }
     }
 
-    // Handle tag operations
-    if let Some(tags) = args.get("tags") {
-        updates["tags"] = tags.clone();
+    // Empty strings clear the optional fields
+    if let Some(phone) = str_arg(&args, "phone") {
+        let phone = Some(phone.trim()).filter(|p| !p.is_empty());
+        match validate_phone(phone) {
+            Ok(()) => updates["phone"] = json!(phone),
+            Err(e) => violations.push(e),
+        }
+    }
+    if let Some(url) = str_arg(&args, "linkedin_url") {
+        let url = Some(url.trim()).filter(|u| !u.is_empty());
+        match validate_linkedin_url(url) {
+            Ok(()) => updates["linkedin_url"] = json!(url),
+            Err(e) => violations.push(e),
+        }
+    }
+    if let Some(company) = args.get("company") {
+        updates["company"] = company.clone();
     }
-    // TODO: Handle add_tags and remove_tags with MERGE operations
 
-    let updated: Option<Value> = db
-        .update(("contact", contact_id))
-        .merge(updates)
+    if let Some(status) = status_arg(&args)? {
+        let from: ContactStatus = serde_json::from_value(current["status"].clone()).unwrap_or(ContactStatus::Lead);
+        if from.can_transition_to(status) {
+            updates["status"] = json!(status);
+        } else {
+            violations.push(DomainError::InvalidStateTransition {
+                from: from.to_string(),
+                to: status.to_string(),
+                reason: from.transition_explanation(status).to_string(),
+            });
+        }
+    }
+
+    // Tags: `tags` replaces, then `add_tags` and `remove_tags` apply on top
+    let tag_args = ["tags", "add_tags", "remove_tags"];
+    if tag_args.iter().any(|name| args.get(*name).is_some()) {
+        let mut tags: Vec<String> = match args.get("tags") {
+            Some(_) => Vec::new(),
+            None => serde_json::from_value(current["tags"].clone()).unwrap_or_default(),
+        };
+
+        for name in ["tags", "add_tags"] {
+            for (i, tag) in string_list_arg(&args, name)?.iter().enumerate() {
+                match validate_tag(tag) {
+                    Ok(tag) if !tags.contains(&tag) => tags.push(tag),
+                    Ok(_) => {}
+                    Err(e) => violations.push(e.at(format!("{}[{}]", name, i))),
+                }
+            }
+        }
+        let removed: Vec<String> = string_list_arg(&args, "remove_tags")?
+            .iter()
+            .map(|t| t.trim().to_lowercase())
+            .collect();
+        tags.retain(|t| !removed.contains(t));
+
+        updates["tags"] = json!(tags);
+    }
+
+    if let Some(error) = DomainError::from_violations(violations) {
+        return Err(error.into());
+    }
+
+    let mut result = db
+        .query("UPDATE type::thing('contact', $id) MERGE $updates WHERE workspace = $workspace AND deleted_at IS NONE")
+        .bind(("id", contact_id))
+        .bind(("updates", updates))
         .await
         .map_err(|e| McpError::Database(e.to_string()))?;
+    let updated: Option<Value> = result.take(0).map_err(|e| McpError::Database(e.to_string()))?;
+    let updated = updated.ok_or_else(|| McpError::InvalidParams("Contact not found".into()))?;
 
     Ok(serde_json::to_string_pretty(&json!({
         "success": true,
@@ -391,6 +884,63 @@ async fn update_contact(db: &Surreal<Client>, args: Value) -> Result<String, Mcp
     .unwrap())
 }
 
+fn str_arg<'a>(args: &'a Value, name: &str) -> Option<&'a str> {
+    args.get(name).and_then(|v| v.as_str())
+}
+
+/// A list of strings; anything else is a field violation
+fn string_list_arg(args: &Value, name: &str) -> Result<Vec<String>, McpError> {
+    match args.get(name) {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(value) => serde_json::from_value(value.clone()).map_err(|_| {
+            McpError::Validation(vec![FieldViolation::new(name, "invalid", "Must be a list of strings")])
+        }),
+    }
+}
+
+fn status_arg(args: &Value) -> Result<Option<ContactStatus>, McpError> {
+    match args.get("status") {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => serde_json::from_value(value.clone()).map(Some).map_err(|_| {
+            McpError::Validation(vec![FieldViolation::new(
+                "status",
+                "invalid",
+                "Must be one of lead, customer, partner, investor, other",
+            )])
+        }),
+    }
+}
+
+/// One of a tool's enum values, or the enum's default when not given
+fn enum_arg<T: DeserializeOwned + Default>(args: &Value, name: &str) -> Result<T, McpError> {
+    match args.get(name) {
+        None | Some(Value::Null) => Ok(T::default()),
+        Some(value) => serde_json::from_value(value.clone()).map_err(|_| {
+            McpError::Validation(vec![FieldViolation::new(name, "invalid", "Must be one of the listed values")])
+        }),
+    }
+}
+
+/// Another contact in the workspace already using this email, trashed ones included
+async fn find_contact_id_by_email(
+    db: &Surreal<Client>,
+    email: &str,
+    exclude_id: Option<&str>,
+) -> Result<Option<String>, McpError> {
+    let mut result = db
+        .query(
+            "SELECT VALUE id FROM contact WHERE workspace = $workspace AND email = $email \
+             AND ($exclude = NONE OR id != type::thing('contact', $exclude)) LIMIT 1",
+        )
+        .bind(("email", email.to_string()))
+        .bind(("exclude", exclude_id.map(str::to_string)))
+        .await
+        .map_err(|e| McpError::Database(e.to_string()))?;
+    let ids: Vec<Thing> = result.take(0).map_err(|e| McpError::Database(e.to_string()))?;
+
+    Ok(ids.into_iter().next().map(|t| t.id.to_raw()))
+}
+
 async fn log_interaction(db: &Surreal<Client>, args: Value) -> Result<String, McpError> {
     let contact_id = args
         .get("contact_id")
@@ -405,25 +955,51 @@ async fn log_interaction(db: &Surreal<Client>, args: Value) -> Result<String, Mc
         .and_then(|v| v.as_str())
         .ok_or_else(|| McpError::InvalidParams("content is required".into()))?;
 
-    let entry = json!({
-        "contact": format!("contact:{}", contact_id),
-        "type": interaction_type,
-        "content": content,
-        "metadata": args.get("metadata").unwrap_or(&json!({})),
-        "timestamp": chrono::Utc::now().to_rfc3339()
-    });
+    // Same rules as POST /api/timeline: the type must be one the backend
+    // knows, and calls, meetings and email have their metadata checked
+    let entry_type: TimelineEntryType = serde_json::from_value(json!(stored_type(interaction_type)))
+        .map_err(|_| McpError::InvalidParams(format!("Unknown interaction type: {}", interaction_type)))?;
+    let metadata = match args.get("metadata") {
+        None | Some(Value::Null) => json!({}),
+        Some(metadata @ Value::Object(_)) => metadata.clone(),
+        Some(_) => return Err(McpError::InvalidParams("metadata must be an object".into())),
+    };
+    entry_type.validate_metadata(&metadata)?;
+    // Stored on the entry too, where the REST API and insights read it
+    let sentiment: Option<Sentiment> = match metadata.get("sentiment") {
+        Some(value) => Some(
+            serde_json::from_value(value.clone())
+                .map_err(|_| McpError::InvalidParams("sentiment must be positive, neutral or negative".into()))?,
+        ),
+        None => None,
+    };
 
-    let created: Vec<Value> = db
-        .create("timeline_entry")
-        .content(entry)
+    // Refuse to log against a contact from another workspace
+    let mut existing = db
+        .query("SELECT id FROM type::thing('contact', $id) WHERE workspace = $workspace AND deleted_at IS NONE")
+        .bind(("id", contact_id))
         .await
         .map_err(|e| McpError::Database(e.to_string()))?;
+    let existing: Option<Value> = existing.take(0).map_err(|e| McpError::Database(e.to_string()))?;
+    if existing.is_none() {
+        return Err(McpError::InvalidParams("Contact not found".into()));
+    }
+
+    let mut result = db
+        .query("CREATE timeline_entry SET workspace = $workspace, contact = type::thing('contact', $id), type = $type, content = $content, metadata = $metadata, sentiment = $sentiment, timestamp = time::now()")
+        .bind(("id", contact_id))
+        .bind(("type", entry_type))
+        .bind(("content", content))
+        .bind(("metadata", metadata))
+        .bind(("sentiment", sentiment))
+        .await
+        .map_err(|e| McpError::Database(e.to_string()))?;
+    let created: Vec<Value> = result.take(0).map_err(|e| McpError::Database(e.to_string()))?;
 
     // Update contact's engagement score (simple increment)
     let _: Option<Value> = db
-        .query("UPDATE contact SET engagement_score += 1, updated_at = $now WHERE id = $id")
-        .bind(("id", format!("contact:{}", contact_id)))
-        .bind(("now", chrono::Utc::now().to_rfc3339()))
+        .query("UPDATE type::thing('contact', $id) SET engagement_score += 1, updated_at = time::now() WHERE workspace = $workspace AND deleted_at IS NONE")
+        .bind(("id", contact_id))
         .await
         .map_err(|e| McpError::Database(e.to_string()))?
         .take::<Option<Value>>(0)
@@ -438,6 +1014,59 @@ async fn log_interaction(db: &Surreal<Client>, args: Value) -> Result<String, Mc
     .unwrap())
 }
 
+async fn draft_reply(db: &Surreal<Client>, args: Value) -> Result<String, McpError> {
+    let contact_id = str_arg(&args, "contact_id")
+        .ok_or_else(|| McpError::InvalidParams("contact_id is required".into()))?;
+    let email_text = str_arg(&args, "email_text")
+        .filter(|t| !t.trim().is_empty())
+        .ok_or_else(|| McpError::InvalidParams("email_text is required".into()))?;
+    let tone: ai_reply::ReplyTone = enum_arg(&args, "tone")?;
+    let length: ai_reply::ReplyLength = enum_arg(&args, "length")?;
+
+    let sql = r#"
+        SELECT * FROM type::thing('contact', $id) WHERE workspace = $workspace AND deleted_at IS NONE;
+        SELECT * FROM timeline_entry WHERE workspace = $workspace AND contact = type::thing('contact', $id)
+            ORDER BY timestamp DESC LIMIT 20;
+    "#;
+    let mut result = db
+        .query(sql)
+        .bind(("id", contact_id))
+        .await
+        .map_err(|e| McpError::Database(e.to_string()))?;
+    let contact: Option<Value> = result.take(0).map_err(|e| McpError::Database(e.to_string()))?;
+    let contact = contact.ok_or_else(|| McpError::InvalidParams("Contact not found".into()))?;
+    let rows: Vec<Value> = result.take(1).map_err(|e| McpError::Database(e.to_string()))?;
+    let history: Vec<TimelineEntry> = rows.into_iter().filter_map(timeline_entry).collect();
+
+    let name = format!(
+        "{} {}",
+        contact["first_name"].as_str().unwrap_or_default(),
+        contact["last_name"].as_str().unwrap_or_default()
+    );
+
+    // The REST API's draft when no AI provider is configured; the client's
+    // own model can take it from there
+    let draft = ai_reply::template_reply(ai_reply::ReplyFacts {
+        contact_name: name.trim(),
+        subject: str_arg(&args, "subject"),
+        email_text,
+        history: &history,
+        tone,
+        length,
+    });
+
+    Ok(serde_json::to_string_pretty(&json!({
+        "contact_id": contact_id,
+        "subject": draft.subject,
+        "body": draft.body,
+        "tone": tone,
+        "length": length,
+        "history_considered": history.len(),
+        "note": "This is a draft. Review and customize before sending."
+    }))
+    .unwrap())
+}
+
 async fn suggest_campaign_contacts(db: &Surreal<Client>, args: Value) -> Result<String, McpError> {
     let objective = args
         .get("objective")
@@ -454,20 +1083,22 @@ async fn suggest_campaign_contacts(db: &Surreal<Client>, args: Value) -> Result<
         _ => (None, 0.0),
     };
 
-    let mut conditions = vec!["engagement_score >= $threshold".to_string()];
-    if let Some(status) = status_filter {
-        conditions.push(format!("status = '{}'", status));
+    // Every value is bound; only fixed condition text goes into the query
+    let mut conditions = vec!["workspace = $workspace", "deleted_at IS NONE", "engagement_score >= $threshold"];
+    if status_filter.is_some() {
+        conditions.push("status = $status");
     }
 
     let sql = format!(
-        "SELECT id, first_name, last_name, email, status, tags, engagement_score FROM contact WHERE {} ORDER BY engagement_score DESC LIMIT {}",
-        conditions.join(" AND "),
-        limit
+        "SELECT id, first_name, last_name, email, status, tags, engagement_score FROM contact WHERE {} ORDER BY engagement_score DESC LIMIT $limit",
+        conditions.join(" AND ")
     );
 
     let mut result = db
         .query(&sql)
         .bind(("threshold", engagement_threshold))
+        .bind(("status", status_filter))
+        .bind(("limit", limit))
         .await
         .map_err(|e| McpError::Database(e.to_string()))?;
 
@@ -551,6 +1182,7 @@ async fn get_pipeline_summary(db: &Surreal<Client>, _args: Value) -> Result<Stri
     let sql = r#"
         SELECT status, count() as count
         FROM contact
+        WHERE workspace = $workspace AND deleted_at IS NONE
         GROUP BY status
     "#;
 
@@ -562,7 +1194,7 @@ async fn get_pipeline_summary(db: &Surreal<Client>, _args: Value) -> Result<Stri
     let counts: Vec<Value> = result.take(0).map_err(|e| McpError::Database(e.to_string()))?;
 
     // Get total count
-    let sql_total = "SELECT count() as total FROM contact";
+    let sql_total = "SELECT count() as total FROM contact WHERE workspace = $workspace AND deleted_at IS NONE GROUP ALL";
     let mut total_result = db
         .query(sql_total)
         .await
@@ -594,23 +1226,23 @@ async fn get_engagement_insights(db: &Surreal<Client>, args: Value) -> Result<St
 
     let sql = match insight_type {
         "hot_prospects" => format!(
-            "SELECT * FROM contact WHERE engagement_score >= 70 ORDER BY engagement_score DESC LIMIT {}",
+            "SELECT * FROM contact WHERE workspace = $workspace AND deleted_at IS NONE AND engagement_score >= 70 ORDER BY engagement_score DESC LIMIT {}",
             limit
         ),
         "stale_leads" => format!(
-            "SELECT * FROM contact WHERE status = 'lead' AND updated_at < time::now() - {}d ORDER BY updated_at ASC LIMIT {}",
+            "SELECT * FROM contact WHERE workspace = $workspace AND deleted_at IS NONE AND status = 'lead' AND updated_at < time::now() - {}d ORDER BY updated_at ASC LIMIT {}",
             days, limit
         ),
         "needs_followup" => format!(
-            "SELECT * FROM contact WHERE updated_at < time::now() - 7d AND engagement_score > 30 ORDER BY engagement_score DESC LIMIT {}",
+            "SELECT * FROM contact WHERE workspace = $workspace AND deleted_at IS NONE AND updated_at < time::now() - 7d AND engagement_score > 30 ORDER BY engagement_score DESC LIMIT {}",
             limit
         ),
         "recent_activity" => format!(
-            "SELECT * FROM contact ORDER BY updated_at DESC LIMIT {}",
+            "SELECT * FROM contact WHERE workspace = $workspace AND deleted_at IS NONE ORDER BY updated_at DESC LIMIT {}",
             limit
         ),
         "at_risk" => format!(
-            "SELECT * FROM contact WHERE status = 'customer' AND updated_at < time::now() - {}d ORDER BY updated_at ASC LIMIT {}",
+            "SELECT * FROM contact WHERE workspace = $workspace AND deleted_at IS NONE AND status = 'customer' AND updated_at < time::now() - {}d ORDER BY updated_at ASC LIMIT {}",
             days, limit
         ),
         _ => {
@@ -641,7 +1273,7 @@ async fn get_engagement_insights(db: &Surreal<Client>, args: Value) -> Result<St
 }
 
 async fn get_recent_contacts(db: &Surreal<Client>) -> Result<String, McpError> {
-    let sql = "SELECT * FROM contact WHERE created_at > time::now() - 7d ORDER BY created_at DESC LIMIT 50";
+    let sql = "SELECT * FROM contact WHERE workspace = $workspace AND deleted_at IS NONE AND created_at > time::now() - 7d ORDER BY created_at DESC LIMIT 50";
 
     let mut result = db
         .query(sql)
@@ -657,3 +1289,82 @@ async fn get_recent_contacts(db: &Surreal<Client>) -> Result<String, McpError> {
     }))
     .unwrap())
 }
+
+async fn get_contact_resource(db: &Surreal<Client>, contact_id: &str) -> Result<String, McpError> {
+    let mut result = db
+        .query("SELECT * FROM type::thing('contact', $id) WHERE workspace = $workspace AND deleted_at IS NONE")
+        .query(
+            "SELECT * FROM timeline_entry WHERE workspace = $workspace AND contact = type::thing('contact', $id) \
+             ORDER BY timestamp DESC LIMIT 20",
+        )
+        .query("SELECT * FROM deal WHERE workspace = $workspace AND contact = type::thing('contact', $id)")
+        .bind(("id", contact_id))
+        .await
+        .map_err(|e| McpError::Database(e.to_string()))?;
+
+    let contact: Option<Value> = result.take(0).map_err(|e| McpError::Database(e.to_string()))?;
+    let contact = contact.ok_or_else(|| McpError::InvalidParams(format!("Contact {} not found", contact_id)))?;
+    let timeline: Vec<Value> = result.take(1).map_err(|e| McpError::Database(e.to_string()))?;
+    let deals: Vec<Value> = result.take(2).map_err(|e| McpError::Database(e.to_string()))?;
+
+    Ok(serde_json::to_string_pretty(&json!({
+        "contact": contact,
+        "recent_timeline": timeline,
+        "deals": deals
+    }))
+    .unwrap())
+}
+
+async fn get_company_resource(db: &Surreal<Client>, company_id: &str) -> Result<String, McpError> {
+    let mut result = db
+        .query("SELECT * FROM type::thing('company', $id) WHERE workspace = $workspace AND deleted_at IS NONE")
+        .query(
+            "SELECT id, first_name, last_name, email, status, tags, engagement_score FROM contact \
+             WHERE workspace = $workspace AND company = type::thing('company', $id) AND deleted_at IS NONE \
+             ORDER BY engagement_score DESC LIMIT 100",
+        )
+        .query("SELECT * FROM deal WHERE workspace = $workspace AND company = type::thing('company', $id)")
+        .bind(("id", company_id))
+        .await
+        .map_err(|e| McpError::Database(e.to_string()))?;
+
+    let company: Option<Value> = result.take(0).map_err(|e| McpError::Database(e.to_string()))?;
+    let company = company.ok_or_else(|| McpError::InvalidParams(format!("Company {} not found", company_id)))?;
+    let contacts: Vec<Value> = result.take(1).map_err(|e| McpError::Database(e.to_string()))?;
+    let deals: Vec<Value> = result.take(2).map_err(|e| McpError::Database(e.to_string()))?;
+
+    Ok(serde_json::to_string_pretty(&json!({
+        "company": company,
+        "contacts": contacts,
+        "deals": deals
+    }))
+    .unwrap())
+}
+
+async fn get_campaign_resource(db: &Surreal<Client>, campaign_id: &str) -> Result<String, McpError> {
+    let mut result = db
+        .query("SELECT * FROM type::thing('campaign', $id) WHERE workspace = $workspace")
+        .query(
+            "SELECT id, type, url, created_at FROM campaign_asset \
+             WHERE workspace = $workspace AND campaign = type::thing('campaign', $id) ORDER BY created_at DESC",
+        )
+        .query(
+            "SELECT status, count() AS count FROM campaign_recipient \
+             WHERE workspace = $workspace AND campaign = type::thing('campaign', $id) GROUP BY status",
+        )
+        .bind(("id", campaign_id))
+        .await
+        .map_err(|e| McpError::Database(e.to_string()))?;
+
+    let campaign: Option<Value> = result.take(0).map_err(|e| McpError::Database(e.to_string()))?;
+    let campaign = campaign.ok_or_else(|| McpError::InvalidParams(format!("Campaign {} not found", campaign_id)))?;
+    let assets: Vec<Value> = result.take(1).map_err(|e| McpError::Database(e.to_string()))?;
+    let recipients: Vec<Value> = result.take(2).map_err(|e| McpError::Database(e.to_string()))?;
+
+    Ok(serde_json::to_string_pretty(&json!({
+        "campaign": campaign,
+        "assets": assets,
+        "recipients_by_status": recipients
+    }))
+    .unwrap())
+}
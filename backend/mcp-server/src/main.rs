@@ -3,20 +3,34 @@
 //! Model Context Protocol server enabling LLM integration with the CRM.
 //! Supports stdio transport for Claude Desktop/Code and HTTP+SSE for web clients.
 
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::Router;
 use clap::Parser;
-use std::io::{self, BufRead, Write};
+use std::io;
+use std::sync::Arc;
+use surrealdb::engine::remote::ws::Client;
+use surrealdb::Surreal;
 use tracing::{info, warn, Level};
 use tracing_subscriber::FmtSubscriber;
 
+mod ai;
+mod auth;
 mod config;
+mod domain;
 mod error;
 mod handlers;
+mod models;
+mod prompts;
 mod protocol;
+mod stdio;
 mod tools;
 
+use auth::ApiToken;
 use config::Config;
 use error::McpError;
-use protocol::{JsonRpcRequest, JsonRpcResponse, McpMessage};
 
 #[derive(Parser, Debug)]
 #[command(name = "crm-mcp-server")]
@@ -30,6 +44,10 @@ struct Args {
     #[arg(long, default_value = "3001", env = "MCP_HTTP_PORT")]
     port: u16,
 
+    /// Maximum stdio requests running against the database at once
+    #[arg(long, default_value = "8", env = "MCP_MAX_CONCURRENCY", value_parser = clap::value_parser!(u16).range(1..))]
+    max_concurrency: u16,
+
     /// Database URL
     #[arg(long, default_value = "ws://localhost:8000", env = "CRM__DATABASE__URL")]
     db_url: String,
@@ -42,6 +60,15 @@ struct Args {
     #[arg(long, default_value = "main", env = "CRM__DATABASE__DATABASE")]
     db_name: String,
 
+    /// Bearer tokens for the HTTP transport, as comma-separated token:scope
+    /// pairs where scope is read_only or read_write
+    #[arg(long, env = "MCP_AUTH_TOKENS", hide_env_values = true)]
+    auth_tokens: Option<String>,
+
+    /// Workspace ID the server operates on
+    #[arg(long, env = "CRM_WORKSPACE_ID")]
+    workspace_id: String,
+
     /// Log level
     #[arg(long, default_value = "info", env = "RUST_LOG")]
     log_level: String,
@@ -62,11 +89,15 @@ async fn main() -> Result<(), McpError> {
     info!("Starting CRM MCP Server");
     info!("Transport: {}", args.transport);
     info!("Database: {}", args.db_url);
+    info!("Workspace: {}", args.workspace_id);
 
     let config = Config {
         db_url: args.db_url,
         db_namespace: args.db_namespace,
         db_name: args.db_name,
+        workspace_id: args.workspace_id,
+        auth_tokens: auth::parse_tokens(args.auth_tokens.as_deref().unwrap_or_default())?,
+        max_concurrency: args.max_concurrency.into(),
     };
 
     match args.transport.as_str() {
@@ -86,50 +117,95 @@ async fn run_stdio_transport(config: Config) -> Result<(), McpError> {
     // Initialize database connection
     let db = handlers::init_db(&config).await?;
 
-    let stdin = io::stdin();
-    let mut stdout = io::stdout();
+    stdio::run(db, config.max_concurrency, shutdown_signal()).await?;
+    info!("Stdio transport stopped");
+    Ok(())
+}
+
+/// Run MCP server over HTTP+SSE (for web clients)
+///
+/// `POST /mcp/messages` takes a single request or a batch, same as a stdio
+/// line, and requires a bearer token from `MCP_AUTH_TOKENS`. Server-sent
+/// events (`GET /mcp/sse`) are not implemented yet.
+async fn run_http_transport(config: Config, port: u16) -> Result<(), McpError> {
+    info!("Running in HTTP mode on port {}", port);
+
+    // Without tokens the server would be an open door to the CRM database
+    if config.auth_tokens.is_empty() {
+        return Err(McpError::Config("HTTP transport requires MCP_AUTH_TOKENS".into()));
+    }
+
+    let db = handlers::init_db(&config).await?;
+    let state = HttpState {
+        db,
+        tokens: Arc::new(config.auth_tokens),
+    };
 
-    for line in stdin.lock().lines() {
-        let line = line.map_err(|e| McpError::Io(e.to_string()))?;
+    let app = Router::new()
+        .route("/mcp/messages", post(handle_http_message))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(("0.0.0.0", port))
+        .await
+        .map_err(|e| McpError::Io(e.to_string()))?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await
+        .map_err(|e| McpError::Io(e.to_string()))
+}
 
-        if line.trim().is_empty() {
-            continue;
+/// Resolves on Ctrl-C or SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
         }
+    };
 
-        // Parse JSON-RPC request
-        let request: JsonRpcRequest = match serde_json::from_str(&line) {
-            Ok(req) => req,
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
             Err(e) => {
-                let error_response = JsonRpcResponse::error(
-                    None,
-                    -32700,
-                    format!("Parse error: {}", e),
-                );
-                writeln!(stdout, "{}", serde_json::to_string(&error_response).unwrap())
-                    .map_err(|e| McpError::Io(e.to_string()))?;
-                stdout.flush().map_err(|e| McpError::Io(e.to_string()))?;
-                continue;
+                warn!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
             }
-        };
-
-        // Handle the request
-        let response = handlers::handle_request(&db, request).await;
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
 
-        // Write response
-        writeln!(stdout, "{}", serde_json::to_string(&response).unwrap())
-            .map_err(|e| McpError::Io(e.to_string()))?;
-        stdout.flush().map_err(|e| McpError::Io(e.to_string()))?;
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
     }
+}
 
-    Ok(())
+#[derive(Clone)]
+struct HttpState {
+    db: Surreal<Client>,
+    tokens: Arc<Vec<ApiToken>>,
 }
 
-/// Run MCP server over HTTP+SSE (for web clients)
-async fn run_http_transport(_config: Config, port: u16) -> Result<(), McpError> {
-    info!("HTTP transport not yet implemented, port: {}", port);
-    // TODO: Implement HTTP+SSE transport using axum
-    // This would expose endpoints like:
-    // - POST /mcp/messages - for tool calls
-    // - GET /mcp/sse - for server-sent events
-    Err(McpError::NotImplemented("HTTP transport".into()))
+/// POST /mcp/messages
+async fn handle_http_message(State(state): State<HttpState>, headers: HeaderMap, body: String) -> Response {
+    let authorization = headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok());
+    let Some(scope) = auth::authenticate(&state.tokens, authorization) else {
+        warn!("Rejected HTTP request without a valid bearer token");
+        return (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            "missing or invalid bearer token",
+        )
+            .into_response();
+    };
+
+    match handlers::handle_message(&state.db, scope, &body).await {
+        Some(reply) => ([(header::CONTENT_TYPE, "application/json")], reply).into_response(),
+        // Only notifications: accepted, nothing to return
+        None => StatusCode::ACCEPTED.into_response(),
+    }
 }
@@ -0,0 +1,12 @@
+//! Backend models the shared AI code reads
+
+#[path = "../../src/models/attachment.rs"]
+#[allow(dead_code)]
+mod attachment;
+
+#[path = "../../src/models/timeline.rs"]
+#[allow(dead_code)]
+mod timeline;
+
+pub use attachment::AttachmentSummary;
+pub use timeline::{Sentiment, TimelineEntry, TimelineEntryType};
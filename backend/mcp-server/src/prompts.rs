@@ -0,0 +1,109 @@
+//! MCP Prompt templates for CRM workflows
+//!
+//! Each prompt is a ready-made request the user can pick from their client.
+//! The handler loads live CRM data for the prompt and the functions here
+//! wrap it in instructions for the LLM.
+
+use crate::protocol::{PromptArgument, PromptDefinition};
+
+/// Get all available prompt definitions
+pub fn get_prompt_definitions() -> Vec<PromptDefinition> {
+    vec![
+        weekly_pipeline_review_prompt(),
+        draft_follow_up_prompt(),
+        meeting_prep_prompt(),
+    ]
+}
+
+fn weekly_pipeline_review_prompt() -> PromptDefinition {
+    PromptDefinition {
+        name: "weekly_pipeline_review".into(),
+        description: "Review the pipeline: where contacts stand, who is heating up, \
+            and which leads are going stale.".into(),
+        arguments: vec![PromptArgument {
+            name: "stale_after_days".into(),
+            description: "Days without activity before a lead counts as stale (default 14)".into(),
+            required: false,
+        }],
+    }
+}
+
+fn draft_follow_up_prompt() -> PromptDefinition {
+    PromptDefinition {
+        name: "draft_follow_up".into(),
+        description: "Draft a follow-up message to a contact based on their recent interactions.".into(),
+        arguments: vec![
+            PromptArgument {
+                name: "contact_id".into(),
+                description: "Contact to follow up with".into(),
+                required: true,
+            },
+            PromptArgument {
+                name: "goal".into(),
+                description: "What the follow-up should achieve, e.g. 'book a demo'".into(),
+                required: false,
+            },
+        ],
+    }
+}
+
+fn meeting_prep_prompt() -> PromptDefinition {
+    PromptDefinition {
+        name: "meeting_prep".into(),
+        description: "Prepare a short brief before meeting someone: who they are, \
+            the history with them, and what to bring up.".into(),
+        arguments: vec![
+            PromptArgument {
+                name: "contact".into(),
+                description: "Contact ID, email address or name".into(),
+                required: true,
+            },
+            PromptArgument {
+                name: "agenda".into(),
+                description: "What the meeting is about".into(),
+                required: false,
+            },
+        ],
+    }
+}
+
+/// Prompt text for `weekly_pipeline_review`
+pub fn weekly_pipeline_review(pipeline: &str, hot_prospects: &str, stale_leads: &str, stale_days: u64) -> String {
+    format!(
+        "Give me a weekly review of my CRM pipeline.\n\n\
+         Cover, briefly:\n\
+         1. How contacts are spread across pipeline stages, and anything unusual.\n\
+         2. The hottest prospects and the single next step for each.\n\
+         3. Leads with no activity for {stale_days}+ days: which to re-engage and which to let go.\n\
+         4. The three most important things to do this week.\n\n\
+         Pipeline by status:\n{pipeline}\n\n\
+         Hot prospects:\n{hot_prospects}\n\n\
+         Stale leads:\n{stale_leads}"
+    )
+}
+
+/// Prompt text for `draft_follow_up`
+pub fn draft_follow_up(contact: &str, goal: Option<&str>) -> String {
+    let goal = goal.map(|g| format!("The goal of the follow-up: {}.\n", g)).unwrap_or_default();
+
+    format!(
+        "Draft a short follow-up email to this contact.\n\
+         {goal}\
+         Refer to our most recent interaction, keep it personal and under 150 words, \
+         and end with one clear next step. Give a subject line too.\n\n\
+         Contact and recent interactions:\n{contact}"
+    )
+}
+
+/// Prompt text for `meeting_prep`
+pub fn meeting_prep(contact: &str, agenda: Option<&str>) -> String {
+    let agenda = agenda.map(|a| format!("The meeting is about: {}.\n", a)).unwrap_or_default();
+
+    format!(
+        "I'm about to meet this contact. Prepare a one-page brief.\n\
+         {agenda}\
+         Include: who they are and where they are in our pipeline, a summary of our history, \
+         open threads or promises to follow up on, and three talking points or questions.\n\n\
+         Contact and interaction history:\n{contact}"
+    )
+}
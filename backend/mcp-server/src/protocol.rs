@@ -119,6 +119,7 @@ pub struct ReadResourceParams {
 pub struct ServerCapabilities {
     pub tools: ToolsCapability,
     pub resources: ResourcesCapability,
+    pub prompts: PromptsCapability,
 }
 
 #[derive(Debug, Serialize)]
@@ -132,6 +133,11 @@ pub struct ResourcesCapability {
     pub list_changed: bool,
 }
 
+#[derive(Debug, Serialize)]
+pub struct PromptsCapability {
+    pub list_changed: bool,
+}
+
 /// Server info
 #[derive(Debug, Serialize)]
 pub struct ServerInfo {
@@ -191,3 +197,58 @@ pub struct ResourceDefinition {
     #[serde(rename = "mimeType")]
     pub mime_type: String,
 }
+
+/// Parameterized resource, e.g. `crm://contact/{id}` (RFC 6570 URI template)
+#[derive(Debug, Serialize)]
+pub struct ResourceTemplateDefinition {
+    #[serde(rename = "uriTemplate")]
+    pub uri_template: String,
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+}
+
+/// Prompt template definition
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptDefinition {
+    pub name: String,
+    pub description: String,
+    pub arguments: Vec<PromptArgument>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptArgument {
+    pub name: String,
+    pub description: String,
+    pub required: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetPromptParams {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: Option<Value>,
+}
+
+/// prompts/get result
+#[derive(Debug, Serialize)]
+pub struct GetPromptResult {
+    pub description: String,
+    pub messages: Vec<PromptMessage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PromptMessage {
+    pub role: String,
+    pub content: ToolContent,
+}
+
+impl PromptMessage {
+    pub fn user(text: String) -> Self {
+        Self {
+            role: "user".into(),
+            content: ToolContent::Text { text },
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Stdio transport: newline-delimited JSON-RPC on stdin/stdout
+//!
+//! Each message runs in its own task, so a slow query doesn't hold up the
+//! rest and a `notifications/cancelled` read while it is running can abort
+//! it. At most `max_concurrency` messages touch the database at once; when
+//! all of them are busy, stdin isn't read until one finishes, so a client
+//! can't pile up work faster than it is done. A batch is one message, and
+//! `handlers` caps both its length and the reads it runs at once. Pings and listings answer
+//! from memory and are handled as they are read. Responses carry their
+//! request id, so they may be written in any order.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use serde_json::Value;
+use surrealdb::engine::remote::ws::Client;
+use surrealdb::Surreal;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::{mpsc, Mutex, Semaphore};
+use tokio::task::{AbortHandle, JoinSet};
+use tracing::{debug, info};
+
+use crate::auth::Scope;
+use crate::error::McpError;
+use crate::handlers;
+
+/// Requests not yet answered, by JSON-RPC id, so they can be cancelled
+type InFlight = Arc<Mutex<HashMap<String, AbortHandle>>>;
+
+/// Serve stdin until EOF or `shutdown` resolves.
+///
+/// Either way, messages already read are finished and their responses
+/// written before this returns.
+pub async fn run(
+    db: Surreal<Client>,
+    max_concurrency: usize,
+    shutdown: impl Future<Output = ()>,
+) -> Result<(), McpError> {
+    let (responses, rx) = mpsc::unbounded_channel();
+    let writer = tokio::spawn(write_responses(rx));
+
+    let permits = Arc::new(Semaphore::new(max_concurrency));
+    let in_flight: InFlight = Arc::default();
+    let mut tasks = JoinSet::new();
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    tokio::pin!(shutdown);
+
+    loop {
+        let line = tokio::select! {
+            line = lines.next_line() => line.map_err(|e| McpError::Io(e.to_string()))?,
+            _ = &mut shutdown => {
+                info!("Shutdown requested");
+                break;
+            }
+            // Reap finished tasks so the set doesn't grow over a long session
+            Some(_) = tasks.join_next(), if !tasks.is_empty() => continue,
+        };
+        let Some(line) = line else {
+            info!("stdin closed");
+            break;
+        };
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let message: Value = match serde_json::from_str(&line) {
+            Ok(message) => message,
+            Err(e) => {
+                let _ = responses.send(handlers::parse_error(e));
+                continue;
+            }
+        };
+
+        if message.get("method").and_then(|v| v.as_str()) == Some("notifications/cancelled") {
+            cancel(&in_flight, &message).await;
+            continue;
+        }
+
+        // Stdio is the local user's own process, so it gets full access
+        if is_lightweight(&message) {
+            if let Some(response) = handlers::handle_json(&db, Scope::ReadWrite, message).await {
+                let _ = responses.send(response);
+            }
+            continue;
+        }
+
+        // Taken before the task exists, so waiting messages stay unread on stdin
+        let permit = permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|e| McpError::Internal(e.to_string()))?;
+
+        // Batches are tracked as a whole and can't be cancelled by id
+        let key = message.get("id").filter(|id| !id.is_null()).map(|id| id.to_string());
+
+        let db = db.clone();
+        let responses = responses.clone();
+        let task_in_flight = in_flight.clone();
+        let task_key = key.clone();
+
+        // Held across the spawn so the task can't finish and deregister
+        // before it has been registered
+        let mut registry = in_flight.lock().await;
+        let handle = tasks.spawn(async move {
+            let _permit = permit;
+            let response = handlers::handle_json(&db, Scope::ReadWrite, message).await;
+
+            if let Some(key) = task_key {
+                task_in_flight.lock().await.remove(&key);
+            }
+            if let Some(response) = response {
+                let _ = responses.send(response);
+            }
+        });
+        if let Some(key) = key {
+            registry.insert(key, handle);
+        }
+    }
+
+    // Finish what was already read, then let the writer drain and exit
+    while tasks.join_next().await.is_some() {}
+    drop(responses);
+    writer.await.map_err(|e| McpError::Internal(e.to_string()))?
+}
+
+/// Requests answered without touching the database
+fn is_lightweight(message: &Value) -> bool {
+    matches!(
+        message.get("method").and_then(|v| v.as_str()),
+        Some("ping" | "initialize" | "tools/list" | "resources/list" | "resources/templates/list" | "prompts/list")
+    )
+}
+
+/// Abort the request named by a `notifications/cancelled`; no response is
+/// sent for it. Unknown or already answered ids are ignored.
+async fn cancel(in_flight: &InFlight, message: &Value) {
+    let Some(id) = message.pointer("/params/requestId") else {
+        return;
+    };
+
+    let handle = in_flight.lock().await.remove(&id.to_string());
+    match handle {
+        Some(handle) => {
+            let reason = message.pointer("/params/reason").and_then(|v| v.as_str());
+            info!("Cancelling request {} ({})", id, reason.unwrap_or("no reason given"));
+            handle.abort();
+        }
+        None => debug!("Cancellation for request {} that is not in flight", id),
+    }
+}
+
+/// Write responses to stdout one per line, flushing each
+async fn write_responses(mut rx: mpsc::UnboundedReceiver<String>) -> Result<(), McpError> {
+    let mut stdout = tokio::io::stdout();
+
+    while let Some(response) = rx.recv().await {
+        stdout
+            .write_all(format!("{}\n", response).as_bytes())
+            .await
+            .map_err(|e| McpError::Io(e.to_string()))?;
+        stdout.flush().await.map_err(|e| McpError::Io(e.to_string()))?;
+    }
+
+    Ok(())
+}
@@ -12,9 +12,11 @@ pub fn get_tool_definitions() -> Vec<ToolDefinition> {
         // Contact tools
         search_contacts_tool(),
         get_contact_details_tool(),
+        get_contact_summary_tool(),
         create_contact_tool(),
         update_contact_tool(),
         log_interaction_tool(),
+        draft_reply_tool(),
         // Campaign tools
         suggest_campaign_contacts_tool(),
         draft_campaign_content_tool(),
@@ -24,6 +26,26 @@ pub fn get_tool_definitions() -> Vec<ToolDefinition> {
     ]
 }
 
+/// Tools known to leave the CRM untouched
+///
+/// Anything not listed here, including tools added later, counts as a
+/// write and needs a read-write token.
+const READ_ONLY_TOOLS: &[&str] = &[
+    "search_contacts",
+    "get_contact_details",
+    "get_contact_summary",
+    "draft_reply",
+    "suggest_campaign_contacts",
+    "draft_campaign_content",
+    "get_pipeline_summary",
+    "get_engagement_insights",
+];
+
+/// Whether a tool leaves the CRM untouched
+pub fn is_read_only(name: &str) -> bool {
+    READ_ONLY_TOOLS.contains(&name)
+}
+
 fn search_contacts_tool() -> ToolDefinition {
     ToolDefinition {
         name: "search_contacts".into(),
@@ -89,11 +111,36 @@ fn get_contact_details_tool() -> ToolDefinition {
     }
 }
 
+fn get_contact_summary_tool() -> ToolDefinition {
+    ToolDefinition {
+        name: "get_contact_summary".into(),
+        description: "Summarize a contact's interaction history and suggest the next best action. \
+            Use before reaching out to someone or when asked how a relationship is going. \
+            Returns a short summary plus the same data as JSON (trend, recommendation, next action).".into(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "contact_id": {
+                    "type": "string",
+                    "description": "Contact ID"
+                },
+                "timeline_limit": {
+                    "type": "integer",
+                    "default": 100,
+                    "description": "Most recent interactions to consider"
+                }
+            },
+            "required": ["contact_id"]
+        }),
+    }
+}
+
 fn create_contact_tool() -> ToolDefinition {
     ToolDefinition {
         name: "create_contact".into(),
         description: "Add a new contact to the CRM. Use when you learn about a new person \
-            the user wants to track. At minimum requires first and last name.".into(),
+            the user wants to track. Requires first name, last name and email; invalid fields \
+            are reported back individually.".into(),
         input_schema: json!({
             "type": "object",
             "properties": {
@@ -123,7 +170,7 @@ fn create_contact_tool() -> ToolDefinition {
                 },
                 "status": {
                     "type": "string",
-                    "enum": ["lead", "customer", "partner", "investor"],
+                    "enum": ["lead", "customer", "partner", "investor", "other"],
                     "default": "lead",
                     "description": "Initial pipeline status"
                 },
@@ -137,7 +184,7 @@ fn create_contact_tool() -> ToolDefinition {
                     "description": "Initial notes about the contact"
                 }
             },
-            "required": ["first_name", "last_name"]
+            "required": ["first_name", "last_name", "email"]
         }),
     }
 }
@@ -212,15 +259,27 @@ fn log_interaction_tool() -> ToolDefinition {
                     "type": "object",
                     "description": "Additional structured data (e.g., meeting duration, topics discussed, location)",
                     "properties": {
-                        "duration_minutes": { "type": "integer" },
+                        "duration_minutes": { "type": "integer", "description": "Length of a call, at most 1440" },
+                        "outcome": {
+                            "type": "string",
+                            "enum": ["connected", "voicemail", "no_answer", "busy"],
+                            "description": "How a call went"
+                        },
+                        "attendees": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Who was at a meeting"
+                        },
                         "location": { "type": "string" },
+                        "subject": { "type": "string", "description": "Subject of an email" },
                         "topics": {
                             "type": "array",
                             "items": { "type": "string" }
                         },
                         "sentiment": {
                             "type": "string",
-                            "enum": ["positive", "neutral", "negative"]
+                            "enum": ["positive", "neutral", "negative"],
+                            "description": "How the contact came across; feeds the contact's sentiment trend"
                         },
                         "follow_up_needed": { "type": "boolean" }
                     }
@@ -231,6 +290,45 @@ fn log_interaction_tool() -> ToolDefinition {
     }
 }
 
+fn draft_reply_tool() -> ToolDefinition {
+    ToolDefinition {
+        name: "draft_reply".into(),
+        description: "Draft a reply to an email a contact sent, taking their interaction history into account. \
+            Use when asked to answer someone's email. Returns a subject and body to review and edit; \
+            nothing is sent or logged.".into(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "contact_id": {
+                    "type": "string",
+                    "description": "Contact who sent the email"
+                },
+                "email_text": {
+                    "type": "string",
+                    "description": "Text of the email being answered"
+                },
+                "subject": {
+                    "type": "string",
+                    "description": "Subject of the email being answered"
+                },
+                "tone": {
+                    "type": "string",
+                    "enum": ["professional", "friendly", "casual", "formal"],
+                    "default": "professional",
+                    "description": "Tone of the reply"
+                },
+                "length": {
+                    "type": "string",
+                    "enum": ["short", "medium", "long"],
+                    "default": "medium",
+                    "description": "Length of the reply"
+                }
+            },
+            "required": ["contact_id", "email_text"]
+        }),
+    }
+}
+
 fn suggest_campaign_contacts_tool() -> ToolDefinition {
     ToolDefinition {
         name: "suggest_campaign_contacts".into(),
@@ -0,0 +1,173 @@
+//! Contact summaries written by the AI provider
+//!
+//! Built on the rule-based summary in `ai_summary`, which stays the answer
+//! when there is no provider, nothing to describe, or the provider fails.
+
+use serde::{Deserialize, Serialize};
+
+use crate::ai::ai_summary::{generate_engagement_insights, summarize_timeline, EngagementInsights};
+use crate::ai::{ContentGenerator, GenerationContext};
+use crate::domain::snippet;
+use crate::models::TimelineEntry;
+
+/// Timeline entries described to the AI provider, newest first
+const BRIEF_ENTRIES: usize = 40;
+
+/// What the AI provider writes about a contact
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratedContactSummary {
+    pub summary: String,
+    pub recommendation: String,
+    pub next_best_action: String,
+}
+
+/// A contact's summary and insights
+#[derive(Debug, Clone)]
+pub struct ContactSummary {
+    pub summary: String,
+    pub insights: EngagementInsights,
+    /// Written by the AI provider rather than the built-in rules
+    pub ai_generated: bool,
+}
+
+/// Summarize a contact, with the AI provider when one is configured
+///
+/// The score and trend always come from the rules.
+pub async fn summarize_contact(
+    generator: &ContentGenerator,
+    ctx: GenerationContext<'_>,
+    contact_name: &str,
+    entries: &[TimelineEntry],
+    engagement_score: f64,
+) -> ContactSummary {
+    let summary = summarize_timeline(entries).await;
+    let mut insights = generate_engagement_insights(entries, engagement_score).await;
+
+    if generator.uses_templates() || entries.is_empty() {
+        return ContactSummary {
+            summary,
+            insights,
+            ai_generated: false,
+        };
+    }
+
+    let activity = activity_brief(contact_name, entries, &insights);
+    match generator.generate_contact_summary(ctx, &activity).await {
+        Ok(generated) => {
+            insights.recommendation = generated.recommendation;
+            insights.next_best_action = generated.next_best_action;
+            ContactSummary {
+                summary: generated.summary,
+                insights,
+                ai_generated: true,
+            }
+        }
+        Err(e) => {
+            tracing::warn!("AI contact summary failed, using the rule-based one: {}", e);
+            ContactSummary {
+                summary,
+                insights,
+                ai_generated: false,
+            }
+        }
+    }
+}
+
+/// The contact's activity written out as the brief for the AI provider
+fn activity_brief(contact_name: &str, entries: &[TimelineEntry], insights: &EngagementInsights) -> String {
+    let mut brief = format!(
+        "Contact: {}\nEngagement score: {:.0} out of 100, activity trend: {:?}, sentiment trend: {:?}\n\n\
+         Timeline, newest first:\n",
+        contact_name, insights.score, insights.trend, insights.sentiment_trend
+    );
+
+    for entry in entries.iter().take(BRIEF_ENTRIES) {
+        let sentiment = entry.sentiment.map(|s| format!(" ({:?})", s)).unwrap_or_default();
+        brief.push_str(&format!(
+            "- {} {:?}: {}{}\n",
+            entry.timestamp.format("%Y-%m-%d"),
+            entry.entry_type,
+            snippet(&entry.content),
+            sentiment
+        ));
+    }
+    if entries.len() > BRIEF_ENTRIES {
+        brief.push_str(&format!("({} older entries left out)\n", entries.len() - BRIEF_ENTRIES));
+    }
+
+    brief
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::ai_summary::ActivityTrend;
+    use crate::ai::provider::{AiProvider, Completion, CompletionRequest, MockProvider};
+    use crate::error::AppResult;
+    use crate::models::TimelineEntryType;
+    use chrono::{Duration, Utc};
+    use futures::future::BoxFuture;
+    use std::sync::Arc;
+    use surrealdb::sql::Thing;
+
+    /// Always answers with the same summary
+    struct FixedProvider;
+
+    impl AiProvider for FixedProvider {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+
+        fn complete<'a>(&'a self, request: &'a CompletionRequest) -> BoxFuture<'a, AppResult<Completion>> {
+            assert!(request.prompt.contains("Contact: Ada Lovelace"));
+            Box::pin(async move {
+                Ok(Completion {
+                    text: r#"{"summary": "Ada is evaluating us.", "recommendation": "Keep it short.", "next_best_action": "Book a demo"}"#.into(),
+                    provider: "fixed",
+                    model: "fixed".into(),
+                    input_tokens: 0,
+                    output_tokens: 0,
+                    latency: std::time::Duration::ZERO,
+                })
+            })
+        }
+    }
+
+    fn entry(days_ago: i64) -> TimelineEntry {
+        TimelineEntry {
+            id: None,
+            workspace: Thing::from(("workspace", "acme")),
+            contact: Thing::from(("contact", "ada")),
+            company: None,
+            campaign: None,
+            entry_type: TimelineEntryType::EmailOpen,
+            content: "Opened the launch email".into(),
+            metadata: serde_json::json!({}),
+            attachments: Vec::new(),
+            sentiment: None,
+            timestamp: Utc::now() - Duration::days(days_ago),
+        }
+    }
+
+    const CTX: GenerationContext<'static> = GenerationContext {
+        workspace_id: "acme",
+        campaign_id: None,
+    };
+
+    #[tokio::test]
+    async fn test_summarize_contact_uses_the_provider_when_configured() {
+        let entries: Vec<_> = [1, 2, 3].into_iter().map(entry).collect();
+
+        let generator = ContentGenerator::new(Arc::new(FixedProvider));
+        let summary = summarize_contact(&generator, CTX, "Ada Lovelace", &entries, 55.0).await;
+        assert!(summary.ai_generated);
+        assert_eq!(summary.summary, "Ada is evaluating us.");
+        assert_eq!(summary.insights.next_best_action, "Book a demo");
+        assert_eq!(summary.insights.trend, ActivityTrend::New);
+
+        let templates = ContentGenerator::new(Arc::new(MockProvider));
+        let summary = summarize_contact(&templates, CTX, "Ada Lovelace", &entries, 55.0).await;
+        assert!(!summary.ai_generated);
+        assert!(summary.summary.starts_with("This contact has 3 total interactions"));
+    }
+}
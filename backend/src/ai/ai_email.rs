@@ -1,18 +1,82 @@
 use serde::{Deserialize, Serialize};
 
+use crate::domain::{render, render_email, DomainError, DomainResult, EmailDocument, EmailSection, Escape, MergeFields};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeneratedEmail {
     pub subject: String,
     pub preview_text: String,
+    /// Compiled from `document` when the email has one
+    #[serde(default)]
     pub body_html: String,
+    #[serde(default)]
     pub body_text: String,
     pub cta_text: String,
     pub cta_url: String,
+    /// Sections the bodies are compiled from; emails written as raw HTML have none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document: Option<EmailDocument>,
+}
+
+impl GeneratedEmail {
+    /// Compile the document, when there is one, into the HTML and text bodies
+    ///
+    /// An email without a document must bring its own HTML.
+    pub fn compile(mut self) -> DomainResult<GeneratedEmail> {
+        match &self.document {
+            Some(document) => {
+                document.validate()?;
+                let rendered = render_email(document, &self.subject, &self.preview_text);
+                self.body_html = rendered.html;
+                self.body_text = rendered.text;
+            }
+            None if self.body_html.trim().is_empty() => {
+                return Err(DomainError::InvalidField {
+                    field: "body_html".into(),
+                    reason: "Required when the email has no document".into(),
+                });
+            }
+            None => {}
+        }
+        Ok(self)
+    }
+
+    /// Fill in the merge variables for one recipient
+    ///
+    /// Returns the personalized email and the variables that could not be
+    /// resolved, which are left as written.
+    pub fn personalize(&self, fields: &MergeFields) -> (GeneratedEmail, Vec<String>) {
+        let mut unresolved: Vec<String> = Vec::new();
+        let mut fill = |template: &str, escape: Escape| {
+            let rendered = render(template, fields, escape);
+            for name in rendered.unresolved {
+                if !unresolved.contains(&name) {
+                    unresolved.push(name);
+                }
+            }
+            rendered.text
+        };
+
+        let email = GeneratedEmail {
+            subject: fill(&self.subject, Escape::None),
+            preview_text: fill(&self.preview_text, Escape::None),
+            body_html: fill(&self.body_html, Escape::Html),
+            body_text: fill(&self.body_text, Escape::None),
+            cta_text: fill(&self.cta_text, Escape::None),
+            cta_url: fill(&self.cta_url, Escape::Url),
+            // What goes out is the compiled bodies
+            document: None,
+        };
+        (email, unresolved)
+    }
 }
 
 /// Generate an email from a prompt
-/// This is a stub that returns template-based mock data
-/// In production, this would call an AI service (e.g., Claude API)
+/// Keyword-matched templates, served by `MockProvider` when no AI provider
+/// is configured
+///
+/// The templates are documents; `ContentGenerator` compiles them like any
+/// provider's answer.
 pub async fn generate_email(prompt: &str) -> GeneratedEmail {
     // Extract key themes from prompt for personalization
     let is_product_launch = prompt.to_lowercase().contains("launch")
@@ -23,127 +87,98 @@ pub async fn generate_email(prompt: &str) -> GeneratedEmail {
         || prompt.to_lowercase().contains("update");
 
     if is_event {
-        GeneratedEmail {
-            subject: "You're Invited: Exclusive Event Just for You".to_string(),
-            preview_text: "Join us for an exciting event that you won't want to miss".to_string(),
-            body_html: format!(
-                r##"<html>
-<body style="font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; max-width: 600px; margin: 0 auto; padding: 20px;">
-    <h1 style="color: #1a1a1a;">You're Invited!</h1>
-    <p style="color: #4a4a4a; line-height: 1.6;">
-        Based on your prompt: "{}"
-    </p>
-    <p style="color: #4a4a4a; line-height: 1.6;">
-        We're hosting an exclusive event and would love for you to join us. This is your chance to connect with industry leaders, learn from experts, and be part of something special.
-    </p>
-    <div style="margin: 30px 0;">
-        <a href="#" style="background-color: #0066ff; color: white; padding: 12px 24px; text-decoration: none; border-radius: 6px; font-weight: 600;">
-            Reserve Your Spot
-        </a>
-    </div>
-    <p style="color: #666; font-size: 14px;">
-        Space is limited, so don't wait!
-    </p>
-</body>
-</html>"##,
-                prompt
-            ),
-            body_text: format!(
-                "You're Invited!\n\nBased on your prompt: \"{}\"\n\nWe're hosting an exclusive event and would love for you to join us.\n\nReserve your spot now!",
-                prompt
-            ),
-            cta_text: "Reserve Your Spot".to_string(),
-            cta_url: "https://crm.hey.sh/events/register".to_string(),
-        }
+        template_email(
+            "You're Invited: Exclusive Event Just for You",
+            "Join us for an exciting event that you won't want to miss",
+            vec![
+                heading("You're Invited!"),
+                text(format!("Based on your prompt: \"{}\"", prompt)),
+                text(
+                    "We're hosting an exclusive event and would love for you to join us. This is your chance to \
+                     connect with industry leaders, learn from experts, and be part of something special.",
+                ),
+                button("Reserve Your Spot", "https://crm.hey.sh/events/register"),
+                text("Space is limited, so don't wait!"),
+            ],
+        )
     } else if is_product_launch {
-        GeneratedEmail {
-            subject: "Introducing Something New".to_string(),
-            preview_text: "Be the first to experience our latest innovation".to_string(),
-            body_html: format!(
-                r##"<html>
-<body style="font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; max-width: 600px; margin: 0 auto; padding: 20px;">
-    <h1 style="color: #1a1a1a;">Something Big is Here</h1>
-    <p style="color: #4a4a4a; line-height: 1.6;">
-        Based on your prompt: "{}"
-    </p>
-    <p style="color: #4a4a4a; line-height: 1.6;">
-        We've been working hard to bring you something amazing, and today we're thrilled to share it with you. This is more than just an update - it's a leap forward.
-    </p>
-    <div style="margin: 30px 0;">
-        <a href="#" style="background-color: #0066ff; color: white; padding: 12px 24px; text-decoration: none; border-radius: 6px; font-weight: 600;">
-            Learn More
-        </a>
-    </div>
-</body>
-</html>"##,
-                prompt
-            ),
-            body_text: format!(
-                "Something Big is Here\n\nBased on your prompt: \"{}\"\n\nWe've been working hard to bring you something amazing.\n\nLearn more now!",
-                prompt
-            ),
-            cta_text: "Learn More".to_string(),
-            cta_url: "https://crm.hey.sh/product".to_string(),
-        }
+        template_email(
+            "Introducing Something New",
+            "Be the first to experience our latest innovation",
+            vec![
+                heading("Something Big is Here"),
+                text(format!("Based on your prompt: \"{}\"", prompt)),
+                text(
+                    "We've been working hard to bring you something amazing, and today we're thrilled to share it \
+                     with you. This is more than just an update - it's a leap forward.",
+                ),
+                button("Learn More", "https://crm.hey.sh/product"),
+            ],
+        )
     } else if is_newsletter {
-        GeneratedEmail {
-            subject: "Your Weekly Update".to_string(),
-            preview_text: "Here's what you need to know this week".to_string(),
-            body_html: format!(
-                r##"<html>
-<body style="font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; max-width: 600px; margin: 0 auto; padding: 20px;">
-    <h1 style="color: #1a1a1a;">This Week's Highlights</h1>
-    <p style="color: #4a4a4a; line-height: 1.6;">
-        Based on your prompt: "{}"
-    </p>
-    <p style="color: #4a4a4a; line-height: 1.6;">
-        Here's a quick roundup of everything that happened this week and what's coming up next.
-    </p>
-    <div style="margin: 30px 0;">
-        <a href="#" style="background-color: #0066ff; color: white; padding: 12px 24px; text-decoration: none; border-radius: 6px; font-weight: 600;">
-            Read Full Update
-        </a>
-    </div>
-</body>
-</html>"##,
-                prompt
-            ),
-            body_text: format!(
-                "This Week's Highlights\n\nBased on your prompt: \"{}\"\n\nHere's a quick roundup of everything that happened this week.",
-                prompt
-            ),
-            cta_text: "Read Full Update".to_string(),
-            cta_url: "https://crm.hey.sh/blog".to_string(),
-        }
+        template_email(
+            "Your Weekly Update",
+            "Here's what you need to know this week",
+            vec![
+                heading("This Week's Highlights"),
+                text(format!("Based on your prompt: \"{}\"", prompt)),
+                text("Here's a quick roundup of everything that happened this week and what's coming up next."),
+                button("Read Full Update", "https://crm.hey.sh/blog"),
+            ],
+        )
     } else {
-        GeneratedEmail {
-            subject: "A Quick Note for You".to_string(),
-            preview_text: "We have something to share".to_string(),
-            body_html: format!(
-                r##"<html>
-<body style="font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; max-width: 600px; margin: 0 auto; padding: 20px;">
-    <h1 style="color: #1a1a1a;">Hello!</h1>
-    <p style="color: #4a4a4a; line-height: 1.6;">
-        Based on your prompt: "{}"
-    </p>
-    <p style="color: #4a4a4a; line-height: 1.6;">
-        We wanted to reach out and share something with you. Your engagement means a lot to us, and we're always looking for ways to provide value.
-    </p>
-    <div style="margin: 30px 0;">
-        <a href="#" style="background-color: #0066ff; color: white; padding: 12px 24px; text-decoration: none; border-radius: 6px; font-weight: 600;">
-            Learn More
-        </a>
-    </div>
-</body>
-</html>"##,
-                prompt
-            ),
-            body_text: format!(
-                "Hello!\n\nBased on your prompt: \"{}\"\n\nWe wanted to reach out and share something with you.",
-                prompt
-            ),
-            cta_text: "Learn More".to_string(),
-            cta_url: "https://crm.hey.sh".to_string(),
-        }
+        template_email(
+            "A Quick Note for You",
+            "We have something to share",
+            vec![
+                heading("Hello!"),
+                text(format!("Based on your prompt: \"{}\"", prompt)),
+                text(
+                    "We wanted to reach out and share something with you. Your engagement means a lot to us, and \
+                     we're always looking for ways to provide value.",
+                ),
+                button("Learn More", "https://crm.hey.sh"),
+            ],
+        )
+    }
+}
+
+/// A template's email, with its call to action taken from its button
+fn template_email(subject: &str, preview_text: &str, sections: Vec<EmailSection>) -> GeneratedEmail {
+    let (cta_text, cta_url) = sections
+        .iter()
+        .find_map(|s| match s {
+            EmailSection::Button { text, url } => Some((text.clone(), url.clone())),
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    GeneratedEmail {
+        subject: subject.to_string(),
+        preview_text: preview_text.to_string(),
+        body_html: String::new(),
+        body_text: String::new(),
+        cta_text,
+        cta_url,
+        document: Some(EmailDocument {
+            sections,
+            accent_color: None,
+            footer: None,
+        }),
+    }
+}
+
+fn heading(text: &str) -> EmailSection {
+    EmailSection::Heading { text: text.to_string() }
+}
+
+fn text(text: impl Into<String>) -> EmailSection {
+    EmailSection::Text { text: text.into() }
+}
+
+fn button(text: &str, url: &str) -> EmailSection {
+    EmailSection::Button {
+        text: text.to_string(),
+        url: url.to_string(),
     }
 }
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::domain::FormSchema;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeneratedLandingPage {
     pub title: String,
@@ -10,6 +12,9 @@ pub struct GeneratedLandingPage {
     pub testimonials: Vec<Testimonial>,
     pub faq: Vec<FaqItem>,
     pub footer: FooterSection,
+    /// Fields of the page's signup form; generated pages get the standard form
+    #[serde(default)]
+    pub form: FormSchema,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -64,8 +69,8 @@ pub struct FooterLink {
 }
 
 /// Generate a landing page from a prompt
-/// This is a stub that returns template-based mock data
-/// In production, this would call an AI service
+/// Keyword-matched templates, served by `MockProvider` when no AI provider
+/// is configured
 pub async fn generate_landing_page(prompt: &str) -> GeneratedLandingPage {
     let is_product = prompt.to_lowercase().contains("product");
     let is_event = prompt.to_lowercase().contains("event");
@@ -157,5 +162,6 @@ pub async fn generate_landing_page(prompt: &str) -> GeneratedLandingPage {
                 FooterLink { text: "Terms".to_string(), url: "/terms".to_string() },
             ],
         },
+        form: FormSchema::default(),
     }
 }
@@ -0,0 +1,252 @@
+//! Meeting prep briefs: what to know before a call with a contact
+//!
+//! The facts the CRM has are written out as Markdown first. With an AI
+//! provider configured they become the brief for a one-pager; without one
+//! (or when the provider fails) the facts are the prep brief.
+
+use serde::{Deserialize, Serialize};
+
+use crate::ai::{ContentGenerator, GenerationContext};
+use crate::domain::{snippet, Contact};
+use crate::models::{Company, TimelineEntry, TimelineEntryType};
+
+/// Email threads listed, most recent first
+const MAX_THREADS: usize = 3;
+
+/// Messages listed per thread, most recent first
+const MAX_THREAD_MESSAGES: usize = 3;
+
+/// What the CRM knows about a contact ahead of a call
+#[derive(Debug, Clone, Copy)]
+pub struct PrepBriefFacts<'a> {
+    pub contact: &'a Contact,
+    pub company: Option<&'a Company>,
+    /// Newest first
+    pub recent_activity: &'a [TimelineEntry],
+    pub open_tasks: &'a [TimelineEntry],
+    /// Sent and received email, newest first
+    pub emails: &'a [TimelineEntry],
+}
+
+/// What the AI provider writes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratedPrepBrief {
+    pub markdown: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct PrepBrief {
+    pub markdown: String,
+    /// Written by the AI provider rather than assembled from the facts
+    pub ai_generated: bool,
+}
+
+/// Write a prep brief, with the AI provider when one is configured
+pub async fn prep_brief(
+    generator: &ContentGenerator,
+    ctx: GenerationContext<'_>,
+    facts: PrepBriefFacts<'_>,
+) -> PrepBrief {
+    let markdown = render_facts(facts);
+
+    if generator.uses_templates() {
+        return PrepBrief {
+            markdown,
+            ai_generated: false,
+        };
+    }
+
+    match generator.generate_prep_brief(ctx, &markdown).await {
+        Ok(generated) => PrepBrief {
+            markdown: generated.markdown,
+            ai_generated: true,
+        },
+        Err(e) => {
+            tracing::warn!("AI prep brief failed, using the CRM facts as they are: {}", e);
+            PrepBrief {
+                markdown,
+                ai_generated: false,
+            }
+        }
+    }
+}
+
+/// The facts as a Markdown one-pager
+fn render_facts(facts: PrepBriefFacts<'_>) -> String {
+    let contact = facts.contact;
+    let mut md = format!("# What to know before this call: {}\n\n## Contact\n\n", contact.full_name());
+
+    md.push_str(&format!("- Email: {}\n", contact.email));
+    if let Some(phone) = &contact.phone {
+        md.push_str(&format!("- Phone: {}\n", phone));
+    }
+    if let Some(linkedin) = &contact.linkedin_url {
+        md.push_str(&format!("- LinkedIn: {}\n", linkedin));
+    }
+    md.push_str(&format!(
+        "- Status: {}, engagement score {:.0}\n",
+        contact.status.as_str(),
+        contact.engagement_score
+    ));
+    if !contact.tags.is_empty() {
+        md.push_str(&format!("- Tags: {}\n", contact.tags.join(", ")));
+    }
+    for (field, value) in &contact.custom_fields {
+        md.push_str(&format!("- {}: {}\n", field, value));
+    }
+
+    if let Some(company) = facts.company {
+        md.push_str(&format!("\n## Company\n\n- {}\n", company.name));
+        for (label, value) in [
+            ("Domain", &company.domain),
+            ("Industry", &company.industry),
+            ("Size", &company.size),
+        ] {
+            if let Some(value) = value {
+                md.push_str(&format!("- {}: {}\n", label, value));
+            }
+        }
+    }
+
+    md.push_str("\n## Open tasks\n\n");
+    if facts.open_tasks.is_empty() {
+        md.push_str("None.\n");
+    }
+    for task in facts.open_tasks {
+        md.push_str(&format!("- {} (added {})\n", snippet(&task.content), task.timestamp.format("%Y-%m-%d")));
+    }
+
+    md.push_str("\n## Recent email threads\n\n");
+    let threads = email_threads(facts.emails);
+    if threads.is_empty() {
+        md.push_str("None.\n");
+    }
+    for (subject, messages) in threads {
+        md.push_str(&format!("### {}\n\n", subject));
+        for message in messages.iter().take(MAX_THREAD_MESSAGES) {
+            let direction = match message.entry_type {
+                TimelineEntryType::EmailReceived => "From them",
+                _ => "From us",
+            };
+            let text = message.metadata["snippet"].as_str().unwrap_or(&message.content);
+            md.push_str(&format!(
+                "- {}, {}: {}\n",
+                direction,
+                message.timestamp.format("%Y-%m-%d"),
+                snippet(text)
+            ));
+        }
+        md.push('\n');
+    }
+
+    md.push_str("## Recent activity\n\n");
+    if facts.recent_activity.is_empty() {
+        md.push_str("No interactions recorded yet.\n");
+    }
+    for entry in facts.recent_activity {
+        md.push_str(&format!(
+            "- {} {:?}: {}\n",
+            entry.timestamp.format("%Y-%m-%d"),
+            entry.entry_type,
+            snippet(&entry.content)
+        ));
+    }
+
+    md
+}
+
+/// Group email by subject, ignoring reply and forward prefixes
+///
+/// Threads are ordered by their latest message; `emails` must be newest first.
+fn email_threads(emails: &[TimelineEntry]) -> Vec<(String, Vec<&TimelineEntry>)> {
+    let mut threads: Vec<(String, Vec<&TimelineEntry>)> = Vec::new();
+
+    for email in emails {
+        let subject = thread_subject(email.metadata["subject"].as_str().unwrap_or(&email.content));
+        let key = subject.to_lowercase();
+        match threads.iter().position(|(s, _)| s.to_lowercase() == key) {
+            Some(i) => threads[i].1.push(email),
+            None if threads.len() < MAX_THREADS => threads.push((subject, vec![email])),
+            None => {}
+        }
+    }
+
+    threads
+}
+
+/// A subject without its `Re:` / `Fwd:` prefixes
+fn thread_subject(subject: &str) -> String {
+    let mut subject = subject.trim();
+    loop {
+        let lower = subject.to_lowercase();
+        let Some(prefix) = ["re:", "fwd:", "fw:"].into_iter().find(|p| lower.starts_with(p)) else {
+            break;
+        };
+        subject = subject[prefix.len()..].trim_start();
+    }
+
+    if subject.is_empty() {
+        "(no subject)".to_string()
+    } else {
+        subject.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ContactBuilder;
+    use chrono::{Duration, Utc};
+    use surrealdb::sql::Thing;
+
+    fn email(entry_type: TimelineEntryType, subject: &str, days_ago: i64) -> TimelineEntry {
+        TimelineEntry {
+            id: None,
+            workspace: Thing::from(("workspace", "acme")),
+            contact: Thing::from(("contact", "ada")),
+            company: None,
+            campaign: None,
+            entry_type,
+            content: format!("Email \"{}\"", subject),
+            metadata: serde_json::json!({ "subject": subject, "snippet": "Sounds good" }),
+            attachments: Vec::new(),
+            sentiment: None,
+            timestamp: Utc::now() - Duration::days(days_ago),
+        }
+    }
+
+    #[test]
+    fn test_thread_subject_strips_reply_prefixes() {
+        assert_eq!(thread_subject("Re: RE: Fwd: Pricing"), "Pricing");
+        assert_eq!(thread_subject("Re:"), "(no subject)");
+        assert_eq!(thread_subject("Renewal"), "Renewal");
+    }
+
+    #[test]
+    fn test_render_facts_groups_email_into_threads() {
+        let contact = ContactBuilder::new()
+            .first_name("Ada")
+            .last_name("Lovelace")
+            .email("ada@example.com")
+            .build()
+            .unwrap();
+        let emails = vec![
+            email(TimelineEntryType::EmailReceived, "Re: Pricing", 1),
+            email(TimelineEntryType::EmailSent, "Pricing", 2),
+            email(TimelineEntryType::EmailSent, "Welcome", 9),
+        ];
+        let facts = PrepBriefFacts {
+            contact: &contact,
+            company: None,
+            recent_activity: &[],
+            open_tasks: &[],
+            emails: &emails,
+        };
+
+        let md = render_facts(facts);
+        assert!(md.starts_with("# What to know before this call: Ada Lovelace"));
+        assert!(md.contains("### Pricing\n\n- From them"));
+        assert!(md.contains("### Welcome"));
+        assert!(md.contains("## Open tasks\n\nNone."));
+    }
+}
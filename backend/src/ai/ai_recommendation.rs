@@ -0,0 +1,165 @@
+//! Next best actions worded by the AI provider
+//!
+//! The rules in `domain::recommendation` pick each contact's action and its
+//! priority; the provider only rewrites the action and reason of the top
+//! few into something specific to the contact. Without a provider, or when
+//! it fails, the rules' wording stays.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ai::{ContentGenerator, GenerationContext};
+use crate::models::RecommendationResponse;
+
+/// Recommendations, most urgent first, sent to the AI provider in one request
+const AI_RECOMMENDATIONS: usize = 20;
+
+/// What the AI provider writes about one contact's action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratedNextAction {
+    pub contact_id: String,
+    pub action: String,
+    pub reason: String,
+}
+
+/// Reword the most urgent recommendations with the AI provider, when one is configured
+pub async fn word_recommendations(
+    generator: &ContentGenerator,
+    ctx: GenerationContext<'_>,
+    recommendations: &mut [RecommendationResponse],
+) {
+    if generator.uses_templates() || recommendations.is_empty() {
+        return;
+    }
+
+    let top = recommendations.len().min(AI_RECOMMENDATIONS);
+    let recommendations = &mut recommendations[..top];
+
+    let generated = match generator.generate_next_actions(ctx, &actions_brief(recommendations)).await {
+        Ok(generated) => generated,
+        Err(e) => {
+            tracing::warn!("AI next actions failed, using the rule-based wording: {}", e);
+            return;
+        }
+    };
+
+    let mut by_contact: HashMap<String, GeneratedNextAction> =
+        generated.into_iter().map(|g| (g.contact_id.clone(), g)).collect();
+    for recommendation in recommendations {
+        if let Some(generated) = by_contact.remove(&recommendation.contact_id) {
+            recommendation.action = generated.action;
+            recommendation.reason = generated.reason;
+            recommendation.ai_generated = true;
+        }
+    }
+}
+
+/// The recommendations and their signals written out as the brief for the AI provider
+fn actions_brief(recommendations: &[RecommendationResponse]) -> String {
+    let mut brief = String::new();
+
+    for r in recommendations {
+        let last_interaction = match (r.last_interaction_type, r.last_interaction_at) {
+            (Some(interaction_type), Some(at)) => format!("{:?} on {}", interaction_type, at.format("%Y-%m-%d")),
+            _ => "none".to_string(),
+        };
+        let deal = r.deal_stage.map(|s| s.to_string()).unwrap_or_else(|| "none".to_string());
+
+        brief.push_str(&format!(
+            "- contact_id {}: {}\n  Action: {:?}, {:?} priority: {} ({})\n  \
+             Engagement {:.0} out of 100 ({:?}, trend {:?}, velocity {:.1}); last interaction: {}; \
+             open tasks: {}; open deal stage: {}\n",
+            r.contact_id,
+            r.contact_name,
+            r.kind,
+            r.priority,
+            r.action,
+            r.reason,
+            r.engagement_score,
+            r.level,
+            r.trend,
+            r.velocity,
+            last_interaction,
+            r.open_tasks,
+            deal
+        ));
+    }
+
+    brief
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::provider::{AiProvider, Completion, CompletionRequest, MockProvider};
+    use crate::domain::{ActionPriority, EngagementLevel, EngagementTrend, NextActionKind};
+    use crate::error::AppResult;
+    use futures::future::BoxFuture;
+    use std::sync::Arc;
+
+    /// Only rewords Ada's action
+    struct FixedProvider;
+
+    impl AiProvider for FixedProvider {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+
+        fn complete<'a>(&'a self, request: &'a CompletionRequest) -> BoxFuture<'a, AppResult<Completion>> {
+            assert!(request.prompt.contains("contact_id ada: Ada Lovelace"));
+            Box::pin(async move {
+                Ok(Completion {
+                    text: r#"{"actions": [{"contact_id": "ada", "action": "Send Ada the pricing sheet", "reason": "Asked about pricing last week."}]}"#.into(),
+                    provider: "fixed",
+                    model: "fixed".into(),
+                    input_tokens: 0,
+                    output_tokens: 0,
+                    latency: std::time::Duration::ZERO,
+                })
+            })
+        }
+    }
+
+    fn recommendation(contact_id: &str, name: &str) -> RecommendationResponse {
+        RecommendationResponse {
+            contact_id: contact_id.into(),
+            contact_name: name.into(),
+            email: format!("{}@example.com", contact_id),
+            kind: NextActionKind::Reply,
+            priority: ActionPriority::High,
+            action: "Answer their message".into(),
+            reason: "Emailed us today and hasn't heard back".into(),
+            engagement_score: 40.0,
+            level: EngagementLevel::Warming,
+            trend: EngagementTrend::Stable,
+            velocity: 0.0,
+            last_interaction_type: None,
+            last_interaction_at: None,
+            open_tasks: 0,
+            deal_stage: None,
+            ai_generated: false,
+        }
+    }
+
+    const CTX: GenerationContext<'static> = GenerationContext {
+        workspace_id: "acme",
+        campaign_id: None,
+    };
+
+    #[tokio::test]
+    async fn test_word_recommendations_keeps_the_rules_where_the_provider_is_silent() {
+        let mut recommendations = vec![recommendation("ada", "Ada Lovelace"), recommendation("alan", "Alan Turing")];
+
+        word_recommendations(&ContentGenerator::new(Arc::new(FixedProvider)), CTX, &mut recommendations).await;
+        assert!(recommendations[0].ai_generated);
+        assert_eq!(recommendations[0].action, "Send Ada the pricing sheet");
+        assert_eq!(recommendations[0].kind, NextActionKind::Reply);
+        assert!(!recommendations[1].ai_generated);
+        assert_eq!(recommendations[1].action, "Answer their message");
+
+        let mut untouched = vec![recommendation("ada", "Ada Lovelace")];
+        word_recommendations(&ContentGenerator::new(Arc::new(MockProvider)), CTX, &mut untouched).await;
+        assert!(!untouched[0].ai_generated);
+    }
+}
@@ -0,0 +1,265 @@
+//! Reply drafts for email a contact sent us
+//!
+//! The MCP server compiles this file too, so it only depends on the
+//! timeline model. With an AI provider configured, `reply_brief` is what it
+//! writes the reply from; `template_reply` is the draft without one.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::{TimelineEntry, TimelineEntryType};
+
+/// History entries described to the AI provider, newest first
+const BRIEF_HISTORY: usize = 20;
+
+/// Characters of the inbound email and each history entry kept in a brief
+const BRIEF_CHARS: usize = 2000;
+const HISTORY_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplyTone {
+    #[default]
+    Professional,
+    Friendly,
+    Casual,
+    Formal,
+}
+
+impl ReplyTone {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReplyTone::Professional => "professional",
+            ReplyTone::Friendly => "friendly",
+            ReplyTone::Casual => "casual",
+            ReplyTone::Formal => "formal",
+        }
+    }
+
+    fn greeting(&self, first_name: &str) -> String {
+        match self {
+            ReplyTone::Formal => format!("Dear {},", first_name),
+            ReplyTone::Casual => format!("Hey {},", first_name),
+            ReplyTone::Professional | ReplyTone::Friendly => format!("Hi {},", first_name),
+        }
+    }
+
+    fn sign_off(&self) -> &'static str {
+        match self {
+            ReplyTone::Formal => "Kind regards,",
+            ReplyTone::Professional => "Best regards,",
+            ReplyTone::Friendly => "All the best,",
+            ReplyTone::Casual => "Cheers,",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplyLength {
+    Short,
+    #[default]
+    Medium,
+    Long,
+}
+
+impl ReplyLength {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReplyLength::Short => "short",
+            ReplyLength::Medium => "medium",
+            ReplyLength::Long => "long",
+        }
+    }
+
+    /// How long the body should be, in the AI provider's terms
+    fn guidance(&self) -> &'static str {
+        match self {
+            ReplyLength::Short => "two or three sentences",
+            ReplyLength::Medium => "one or two short paragraphs",
+            ReplyLength::Long => "three or four paragraphs",
+        }
+    }
+}
+
+/// What a reply is drafted from
+#[derive(Debug, Clone, Copy)]
+pub struct ReplyFacts<'a> {
+    pub contact_name: &'a str,
+    /// Subject of the email being answered
+    pub subject: Option<&'a str>,
+    pub email_text: &'a str,
+    /// The contact's timeline, newest first
+    pub history: &'a [TimelineEntry],
+    pub tone: ReplyTone,
+    pub length: ReplyLength,
+}
+
+/// A reply ready to review and send
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DraftReply {
+    pub subject: String,
+    pub body: String,
+}
+
+/// The email, the history and the controls written out for the AI provider
+pub fn reply_brief(facts: ReplyFacts<'_>) -> String {
+    let mut brief = format!(
+        "Contact: {}\nTone: {}\nLength: {}\n\nTheir email",
+        facts.contact_name,
+        facts.tone.as_str(),
+        facts.length.guidance()
+    );
+    if let Some(subject) = facts.subject {
+        brief.push_str(&format!(" (subject \"{}\")", subject.trim()));
+    }
+    brief.push_str(&format!(":\n\n{}\n\nHistory with them, newest first:\n", clip(facts.email_text, BRIEF_CHARS)));
+
+    if facts.history.is_empty() {
+        brief.push_str("None recorded.\n");
+    }
+    for entry in facts.history.iter().take(BRIEF_HISTORY) {
+        brief.push_str(&format!(
+            "- {} {:?}: {}\n",
+            entry.timestamp.format("%Y-%m-%d"),
+            entry.entry_type,
+            clip(&entry.content, HISTORY_CHARS)
+        ));
+    }
+
+    brief
+}
+
+/// A reply built from the tone and length alone, for when there is no AI provider
+///
+/// It acknowledges the email, promises an answer when it asks questions,
+/// and at the longest suggests a call; the rest is for the user to fill in.
+pub fn template_reply(facts: ReplyFacts<'_>) -> DraftReply {
+    let first_name = facts.contact_name.split_whitespace().next().unwrap_or("there");
+    let wrote_before = facts
+        .history
+        .iter()
+        .any(|e| matches!(e.entry_type, TimelineEntryType::EmailSent));
+
+    let thanks = match (facts.tone, wrote_before) {
+        (ReplyTone::Formal, _) => "Thank you for your email.",
+        (ReplyTone::Casual, true) => "Thanks for getting back to me!",
+        (ReplyTone::Casual, false) => "Thanks for reaching out!",
+        (_, true) => "Thank you for getting back to me.",
+        (_, false) => "Thank you for reaching out.",
+    };
+    let mut paragraphs = vec![thanks.to_string()];
+
+    if facts.length != ReplyLength::Short {
+        let questions = facts.email_text.matches('?').count();
+        paragraphs[0].push_str(if questions > 0 {
+            " I'm looking into your questions and will come back to you with answers shortly."
+        } else {
+            " I've read through your note and will follow up on the details shortly."
+        });
+    }
+
+    if facts.length == ReplyLength::Long {
+        paragraphs.push(
+            "In the meantime, would you have time for a short call this week? \
+             It may be quicker to go through everything together."
+                .to_string(),
+        );
+        paragraphs.push("Let me know if there is anything else I can help with.".to_string());
+    }
+
+    DraftReply {
+        subject: reply_subject(facts.subject),
+        body: format!(
+            "{}\n\n{}\n\n{}",
+            facts.tone.greeting(first_name),
+            paragraphs.join("\n\n"),
+            facts.tone.sign_off()
+        ),
+    }
+}
+
+/// `Re:` and the original subject, without stacking prefixes
+pub fn reply_subject(subject: Option<&str>) -> String {
+    let subject = subject.map(str::trim).filter(|s| !s.is_empty());
+    match subject {
+        Some(s) if s.to_lowercase().starts_with("re:") => s.to_string(),
+        Some(s) => format!("Re: {}", s),
+        None => "Re: your email".to_string(),
+    }
+}
+
+/// Collapse whitespace and cut text down to `max` characters
+fn clip(text: &str, max: usize) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= max {
+        return collapsed;
+    }
+
+    let cut: String = collapsed.chars().take(max).collect();
+    format!("{}…", cut.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use surrealdb::sql::Thing;
+
+    fn sent(content: &str) -> TimelineEntry {
+        TimelineEntry {
+            id: None,
+            workspace: Thing::from(("workspace", "acme")),
+            contact: Thing::from(("contact", "ada")),
+            company: None,
+            campaign: None,
+            entry_type: TimelineEntryType::EmailSent,
+            content: content.into(),
+            metadata: serde_json::json!({}),
+            attachments: Vec::new(),
+            sentiment: None,
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn facts<'a>(history: &'a [TimelineEntry], tone: ReplyTone, length: ReplyLength) -> ReplyFacts<'a> {
+        ReplyFacts {
+            contact_name: "Ada Lovelace",
+            subject: Some("Pricing"),
+            email_text: "Could you send over the pricing for ten seats?",
+            history,
+            tone,
+            length,
+        }
+    }
+
+    #[test]
+    fn test_template_reply_follows_tone_and_length() {
+        let history = vec![sent("Email \"Welcome\"")];
+
+        let short = template_reply(facts(&history, ReplyTone::Formal, ReplyLength::Short));
+        assert_eq!(short.subject, "Re: Pricing");
+        assert_eq!(short.body, "Dear Ada,\n\nThank you for your email.\n\nKind regards,");
+
+        let long = template_reply(facts(&[], ReplyTone::Casual, ReplyLength::Long));
+        assert!(long.body.starts_with("Hey Ada,\n\nThanks for reaching out! I'm looking into your questions"));
+        assert!(long.body.contains("short call this week"));
+        assert!(long.body.ends_with("Cheers,"));
+
+        let medium = template_reply(facts(&history, ReplyTone::Professional, ReplyLength::Medium));
+        assert!(medium.body.contains("Thank you for getting back to me."));
+        assert!(!medium.body.contains("call"));
+    }
+
+    #[test]
+    fn test_reply_subject_and_brief() {
+        assert_eq!(reply_subject(Some("RE: Pricing")), "RE: Pricing");
+        assert_eq!(reply_subject(Some("  ")), "Re: your email");
+
+        let history = vec![sent("Email \"Welcome\"")];
+        let brief = reply_brief(facts(&history, ReplyTone::Friendly, ReplyLength::Short));
+        assert!(brief.starts_with("Contact: Ada Lovelace\nTone: friendly\nLength: two or three sentences"));
+        assert!(brief.contains("(subject \"Pricing\"):\n\nCould you send over the pricing"));
+        assert!(brief.contains("EmailSent: Email \"Welcome\""));
+    }
+}
@@ -0,0 +1,123 @@
+//! Sentiment of new timeline entries, rated by the AI provider
+//!
+//! Only notes and received email are rated: they say how the contact feels
+//! about us, while email we sent, opens and clicks don't. Rating is off
+//! unless `ai.infer_sentiment` is set, and never holds up logging: entries
+//! are queued when written and rated by a background job, and an entry the
+//! provider fails to rate stays unrated.
+
+use crate::ai::{ContentGenerator, GenerationContext};
+use crate::models::{TimelineEntry, TimelineEntryType};
+
+/// Characters of an entry sent to the AI provider
+const MAX_TEXT_CHARS: usize = 2000;
+
+/// Whether an entry is one to rate: a note or received email not given a sentiment, with inference on
+pub fn awaits_sentiment(generator: &ContentGenerator, entry: &TimelineEntry) -> bool {
+    entry.sentiment.is_none()
+        && generator.infers_sentiment()
+        && matches!(entry.entry_type, TimelineEntryType::Note | TimelineEntryType::EmailReceived)
+}
+
+/// Fill in the sentiment of an entry that wasn't given one, where inference is on
+pub async fn infer_sentiment(generator: &ContentGenerator, entry: &mut TimelineEntry) {
+    if !awaits_sentiment(generator, entry) {
+        return;
+    }
+
+    let workspace_id = entry.workspace.id.to_raw();
+    let ctx = GenerationContext {
+        workspace_id: &workspace_id,
+        campaign_id: None,
+    };
+
+    match generator.generate_sentiment(ctx, &entry_text(entry)).await {
+        Ok(sentiment) => entry.sentiment = Some(sentiment),
+        Err(e) => tracing::warn!("Sentiment inference failed, leaving the entry unrated: {}", e),
+    }
+}
+
+/// The entry's content, with the email snippet the content leaves out
+fn entry_text(entry: &TimelineEntry) -> String {
+    let mut text = entry.content.clone();
+    if let Some(snippet) = entry.metadata["snippet"].as_str() {
+        text.push_str("\n\n");
+        text.push_str(snippet);
+    }
+    text.chars().take(MAX_TEXT_CHARS).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::provider::{AiProvider, Completion, CompletionRequest};
+    use crate::error::AppResult;
+    use crate::models::Sentiment;
+    use chrono::Utc;
+    use futures::future::BoxFuture;
+    use std::sync::Arc;
+    use surrealdb::sql::Thing;
+
+    /// Rates everything negative
+    struct GrumpyProvider;
+
+    impl AiProvider for GrumpyProvider {
+        fn name(&self) -> &'static str {
+            "grumpy"
+        }
+
+        fn complete<'a>(&'a self, request: &'a CompletionRequest) -> BoxFuture<'a, AppResult<Completion>> {
+            assert!(request.prompt.contains("Not happy with the onboarding"));
+            Box::pin(async move {
+                Ok(Completion {
+                    text: r#"{"sentiment": "negative"}"#.into(),
+                    provider: "grumpy",
+                    model: "grumpy".into(),
+                    input_tokens: 0,
+                    output_tokens: 0,
+                    latency: std::time::Duration::ZERO,
+                })
+            })
+        }
+    }
+
+    fn entry(entry_type: TimelineEntryType) -> TimelineEntry {
+        TimelineEntry {
+            id: None,
+            workspace: Thing::from(("workspace", "acme")),
+            contact: Thing::from(("contact", "ada")),
+            company: None,
+            campaign: None,
+            entry_type,
+            content: "Received \"Onboarding\"".into(),
+            metadata: serde_json::json!({ "snippet": "Not happy with the onboarding so far" }),
+            attachments: Vec::new(),
+            sentiment: None,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_infer_sentiment_rates_notes_and_received_email_only() {
+        let generator = ContentGenerator::new(Arc::new(GrumpyProvider)).with_sentiment_inference(true);
+
+        let mut email = entry(TimelineEntryType::EmailReceived);
+        assert!(awaits_sentiment(&generator, &email));
+        infer_sentiment(&generator, &mut email).await;
+        assert_eq!(email.sentiment, Some(Sentiment::Negative));
+
+        let mut open = entry(TimelineEntryType::EmailOpen);
+        assert!(!awaits_sentiment(&generator, &open));
+        infer_sentiment(&generator, &mut open).await;
+        assert_eq!(open.sentiment, None);
+
+        let mut rated = entry(TimelineEntryType::Note);
+        rated.sentiment = Some(Sentiment::Positive);
+        infer_sentiment(&generator, &mut rated).await;
+        assert_eq!(rated.sentiment, Some(Sentiment::Positive));
+
+        let mut off = entry(TimelineEntryType::Note);
+        infer_sentiment(&ContentGenerator::new(Arc::new(GrumpyProvider)), &mut off).await;
+        assert_eq!(off.sentiment, None);
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeneratedPost {
@@ -9,7 +10,7 @@ pub struct GeneratedPost {
     pub character_count: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum SocialPlatform {
     Twitter,
@@ -19,8 +20,8 @@ pub enum SocialPlatform {
 }
 
 /// Generate social media posts from a prompt
-/// This is a stub that returns template-based mock data
-/// In production, this would call an AI service
+/// Keyword-matched templates, served by `MockProvider` when no AI provider
+/// is configured
 pub async fn generate_social_posts(prompt: &str) -> Vec<GeneratedPost> {
     let base_content = if prompt.len() > 50 {
         &prompt[..50]
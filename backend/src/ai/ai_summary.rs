@@ -1,4 +1,23 @@
-use crate::models::TimelineEntry;
+//! Rule-based contact summaries and engagement insights
+//!
+//! The MCP server compiles this file too, so it only depends on the
+//! timeline model. `ai_contact_summary` has the AI provider on top.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::{Sentiment, TimelineEntry};
+
+/// Width of the windows compared for the activity trend
+pub const TREND_WINDOW_DAYS: i64 = 30;
+
+/// How far back the sentiment before the last 30 days is averaged; rated
+/// interactions are sparser than activity, so the baseline reaches further
+pub const SENTIMENT_BASELINE_DAYS: i64 = 90;
+
+/// Change in average sentiment, on the -1 to 1 scale, that counts as a shift
+const SENTIMENT_SHIFT: f64 = 0.25;
 
 /// Summarize a contact's timeline entries
 /// This is a stub that returns template-based mock data
@@ -16,6 +35,7 @@ pub async fn summarize_timeline(entries: &[TimelineEntry]) -> String {
     let mut notes = 0;
     let mut events = 0;
     let mut calls = 0;
+    let mut meetings = 0;
     let mut landing_pages = 0;
 
     for entry in entries {
@@ -24,9 +44,16 @@ pub async fn summarize_timeline(entries: &[TimelineEntry]) -> String {
             crate::models::TimelineEntryType::EmailOpen => emails_opened += 1,
             crate::models::TimelineEntryType::EmailClick => emails_opened += 1,
             crate::models::TimelineEntryType::Note => notes += 1,
-            crate::models::TimelineEntryType::EventInvite | crate::models::TimelineEntryType::EventAttend => events += 1,
+            crate::models::TimelineEntryType::EventInvite
+            | crate::models::TimelineEntryType::EventRegistration
+            | crate::models::TimelineEntryType::EventAttend => events += 1,
             crate::models::TimelineEntryType::Call => calls += 1,
-            crate::models::TimelineEntryType::LandingPageVisit => landing_pages += 1,
+            crate::models::TimelineEntryType::MeetingScheduled | crate::models::TimelineEntryType::MeetingAttended => {
+                meetings += 1
+            }
+            crate::models::TimelineEntryType::LandingPageVisit | crate::models::TimelineEntryType::FormSubmission => {
+                landing_pages += 1
+            }
             _ => {}
         }
     }
@@ -48,6 +75,10 @@ pub async fn summarize_timeline(entries: &[TimelineEntry]) -> String {
         summary_parts.push(format!("{} calls logged", calls));
     }
 
+    if meetings > 0 {
+        summary_parts.push(format!("{} meetings", meetings));
+    }
+
     if events > 0 {
         summary_parts.push(format!("{} event interactions", events));
     }
@@ -74,25 +105,17 @@ pub async fn summarize_timeline(entries: &[TimelineEntry]) -> String {
 
 /// Generate engagement insights for a contact
 pub async fn generate_engagement_insights(entries: &[TimelineEntry], engagement_score: f64) -> EngagementInsights {
-    let trend = if entries.len() < 5 {
-        EngagementTrend::New
-    } else {
-        // Simple heuristic based on recent vs older activity
-        let recent_count = entries.iter().take(entries.len() / 2).count();
-        let older_count = entries.len() - recent_count;
-
-        if recent_count > older_count {
-            EngagementTrend::Increasing
-        } else if recent_count < older_count {
-            EngagementTrend::Decreasing
-        } else {
-            EngagementTrend::Stable
-        }
-    };
+    let now = Utc::now();
+    let trend = activity_trend(entries, now);
+    let (recent, previous) = sentiment_windows(entries.iter().filter_map(|e| Some((e.timestamp, e.sentiment?))), now);
+    let sentiment_trend = sentiment_trend(recent, previous);
 
     let recommendation = match (engagement_score as i32, &trend) {
+        _ if sentiment_trend == SentimentTrend::Worsening => {
+            "Recent interactions have turned more negative. Reach out personally to understand their concerns."
+        }
         (0..=30, _) => "Consider reaching out with a personalized message to re-engage this contact.",
-        (31..=60, EngagementTrend::Decreasing) => "Engagement is declining. Schedule a check-in call or send relevant content.",
+        (31..=60, ActivityTrend::Decreasing) => "Engagement is declining. Schedule a check-in call or send relevant content.",
         (31..=60, _) => "Moderate engagement. Continue nurturing with valuable content.",
         (61..=80, _) => "Good engagement level. Consider inviting to exclusive events or early access programs.",
         (81..=100, _) => "Highly engaged! This contact may be ready for a sales conversation or partnership discussion.",
@@ -102,11 +125,70 @@ pub async fn generate_engagement_insights(entries: &[TimelineEntry], engagement_
     EngagementInsights {
         score: engagement_score,
         trend,
+        sentiment_trend,
         recommendation: recommendation.to_string(),
         next_best_action: determine_next_action(entries, engagement_score),
     }
 }
 
+/// Activity in the last 30 days compared with the 30 days before
+fn activity_trend(entries: &[TimelineEntry], now: DateTime<Utc>) -> ActivityTrend {
+    if entries.len() < 5 {
+        return ActivityTrend::New;
+    }
+
+    let recent_start = now - Duration::days(TREND_WINDOW_DAYS);
+    let older_start = recent_start - Duration::days(TREND_WINDOW_DAYS);
+    let recent = entries.iter().filter(|e| e.timestamp >= recent_start).count();
+    let older = entries
+        .iter()
+        .filter(|e| e.timestamp >= older_start && e.timestamp < recent_start)
+        .count();
+
+    match recent.cmp(&older) {
+        std::cmp::Ordering::Greater => ActivityTrend::Increasing,
+        std::cmp::Ordering::Less => ActivityTrend::Decreasing,
+        std::cmp::Ordering::Equal => ActivityTrend::Stable,
+    }
+}
+
+/// Average sentiment, -1 to 1, of the last 30 days and of the 60 days before
+///
+/// A window without rated interactions has no average.
+pub fn sentiment_windows(
+    ratings: impl IntoIterator<Item = (DateTime<Utc>, Sentiment)>,
+    now: DateTime<Utc>,
+) -> (Option<f64>, Option<f64>) {
+    let recent_start = now - Duration::days(TREND_WINDOW_DAYS);
+    let baseline_start = now - Duration::days(SENTIMENT_BASELINE_DAYS);
+    let (mut recent, mut previous) = ((0.0, 0u32), (0.0, 0u32));
+
+    for (timestamp, sentiment) in ratings {
+        let window = if timestamp >= recent_start {
+            &mut recent
+        } else if timestamp >= baseline_start {
+            &mut previous
+        } else {
+            continue;
+        };
+        window.0 += sentiment.score();
+        window.1 += 1;
+    }
+
+    let average = |(sum, count): (f64, u32)| if count > 0 { Some(sum / f64::from(count)) } else { None };
+    (average(recent), average(previous))
+}
+
+/// Compare the two averages from `sentiment_windows`
+pub fn sentiment_trend(recent: Option<f64>, previous: Option<f64>) -> SentimentTrend {
+    match (recent, previous) {
+        (Some(recent), Some(previous)) if recent - previous >= SENTIMENT_SHIFT => SentimentTrend::Improving,
+        (Some(recent), Some(previous)) if previous - recent >= SENTIMENT_SHIFT => SentimentTrend::Worsening,
+        (Some(_), Some(_)) => SentimentTrend::Stable,
+        _ => SentimentTrend::Unknown,
+    }
+}
+
 fn determine_next_action(entries: &[TimelineEntry], score: f64) -> String {
     if entries.is_empty() {
         return "Send an introductory email".to_string();
@@ -124,19 +206,93 @@ fn determine_next_action(entries: &[TimelineEntry], score: f64) -> String {
     }
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct EngagementInsights {
     pub score: f64,
-    pub trend: EngagementTrend,
+    pub trend: ActivityTrend,
+    pub sentiment_trend: SentimentTrend,
     pub recommendation: String,
     pub next_best_action: String,
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
+/// Whether a contact's activity is picking up or tailing off; `new` below five entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
-pub enum EngagementTrend {
+pub enum ActivityTrend {
     Increasing,
     Stable,
     Decreasing,
     New,
 }
+
+/// Whether the contact's last 30 days of rated interactions are warmer or
+/// cooler than before; `unknown` when either period has none
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SentimentTrend {
+    Improving,
+    Stable,
+    Worsening,
+    Unknown,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TimelineEntryType;
+    use surrealdb::sql::Thing;
+
+    fn entry(days_ago: i64) -> TimelineEntry {
+        TimelineEntry {
+            id: None,
+            workspace: Thing::from(("workspace", "acme")),
+            contact: Thing::from(("contact", "ada")),
+            company: None,
+            campaign: None,
+            entry_type: TimelineEntryType::EmailOpen,
+            content: "Opened the launch email".into(),
+            metadata: serde_json::json!({}),
+            attachments: Vec::new(),
+            sentiment: None,
+            timestamp: Utc::now() - Duration::days(days_ago),
+        }
+    }
+
+    #[test]
+    fn test_activity_trend_compares_the_last_two_months() {
+        let now = Utc::now();
+        let picking_up: Vec<_> = [1, 2, 3, 40, 50].into_iter().map(entry).collect();
+        assert_eq!(activity_trend(&picking_up, now), ActivityTrend::Increasing);
+
+        let tailing_off: Vec<_> = [5, 35, 40, 45, 50].into_iter().map(entry).collect();
+        assert_eq!(activity_trend(&tailing_off, now), ActivityTrend::Decreasing);
+
+        assert_eq!(activity_trend(&picking_up[..4], now), ActivityTrend::New);
+    }
+
+    #[tokio::test]
+    async fn test_sentiment_trend_compares_the_last_month_with_before() {
+        let now = Utc::now();
+        let rated = |days_ago: i64, sentiment| (now - Duration::days(days_ago), sentiment);
+
+        let ratings = [
+            rated(2, Sentiment::Negative),
+            rated(10, Sentiment::Neutral),
+            rated(40, Sentiment::Positive),
+            rated(60, Sentiment::Neutral),
+            rated(200, Sentiment::Negative),
+        ];
+        let (recent, previous) = sentiment_windows(ratings, now);
+        assert_eq!((recent, previous), (Some(-0.5), Some(0.5)));
+        assert_eq!(sentiment_trend(recent, previous), SentimentTrend::Worsening);
+        assert_eq!(sentiment_trend(Some(0.0), Some(0.2)), SentimentTrend::Stable);
+        assert_eq!(sentiment_trend(Some(-1.0), None), SentimentTrend::Unknown);
+
+        let mut entries: Vec<_> = [2, 40].into_iter().map(entry).collect();
+        entries[0].sentiment = Some(Sentiment::Negative);
+        entries[1].sentiment = Some(Sentiment::Positive);
+        let insights = generate_engagement_insights(&entries, 90.0).await;
+        assert_eq!(insights.sentiment_trend, SentimentTrend::Worsening);
+        assert!(insights.recommendation.starts_with("Recent interactions have turned more negative"));
+    }
+}
@@ -0,0 +1,183 @@
+//! Text embeddings for finding similar records
+//!
+//! `Embedder` turns texts into vectors whose cosine similarity says how
+//! alike the texts are. `LocalEmbedder` hashes character trigrams into a
+//! fixed-size vector with no network calls, which is good at spotting
+//! typos, nicknames and reordered names. `OpenAiEmbedder` calls the OpenAI
+//! embeddings API. Vectors from different models can't be compared, so
+//! each is stored with `Embedder::model`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::config::DedupeConfig;
+use crate::error::{AppError, AppResult};
+use crate::secrets::SecretsManager;
+
+/// Dimensions of a `LocalEmbedder` vector
+const LOCAL_DIMENSIONS: usize = 512;
+
+pub trait Embedder: Send + Sync {
+    /// Model the vectors come from, e.g. `local-trigram-512`
+    fn model(&self) -> &str;
+
+    /// One vector per text, in order
+    fn embed<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, AppResult<Vec<Vec<f32>>>>;
+}
+
+/// Build the embedder selected in config
+///
+/// The OpenAI API key comes from the secrets manager under `dedupe.api_key_secret`.
+pub fn build_embedder(config: &DedupeConfig, secrets: &SecretsManager) -> AppResult<Arc<dyn Embedder>> {
+    let embedder: Arc<dyn Embedder> = match config.embedder.as_str() {
+        "local" => Arc::new(LocalEmbedder),
+        "openai" => Arc::new(OpenAiEmbedder {
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(config.request_timeout_secs))
+                .build()
+                .map_err(|e| AppError::Internal(format!("Failed to build embeddings client: {}", e)))?,
+            api_key: secrets
+                .get_secret(&config.api_key_secret)
+                .map_err(|e| AppError::Internal(format!("Embedder 'openai' needs an API key: {}", e)))?,
+            model: config.model.clone().unwrap_or_else(|| "text-embedding-3-small".into()),
+        }),
+        other => {
+            return Err(AppError::Internal(format!(
+                "Unknown embedder '{}', expected local or openai",
+                other
+            )));
+        }
+    };
+
+    tracing::info!("Contact embeddings: {}", embedder.model());
+    Ok(embedder)
+}
+
+/// Hashed character trigrams of each word, L2-normalized
+pub struct LocalEmbedder;
+
+impl LocalEmbedder {
+    fn vector(text: &str) -> Vec<f32> {
+        let normalized: String = text
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+            .collect();
+
+        let mut vector = vec![0.0f32; LOCAL_DIMENSIONS];
+        for word in normalized.split_whitespace() {
+            let padded: Vec<char> = format!(" {} ", word).chars().collect();
+            for trigram in padded.windows(3) {
+                let trigram: String = trigram.iter().collect();
+                vector[(fnv1a(trigram.as_bytes()) % LOCAL_DIMENSIONS as u64) as usize] += 1.0;
+            }
+        }
+
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|x| *x /= norm);
+        }
+        vector
+    }
+}
+
+impl Embedder for LocalEmbedder {
+    fn model(&self) -> &str {
+        "local-trigram-512"
+    }
+
+    fn embed<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, AppResult<Vec<Vec<f32>>>> {
+        Box::pin(async move { Ok(texts.iter().map(|t| Self::vector(t)).collect()) })
+    }
+}
+
+/// 64-bit FNV-1a; stable across builds, unlike the std hasher
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// OpenAI embeddings API
+struct OpenAiEmbedder {
+    http: reqwest::Client,
+    api_key: String,
+    model: String,
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbeddings {
+    data: Vec<OpenAiEmbedding>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+impl Embedder for OpenAiEmbedder {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn embed<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, AppResult<Vec<Vec<f32>>>> {
+        Box::pin(async move {
+            let response = self
+                .http
+                .post("https://api.openai.com/v1/embeddings")
+                .bearer_auth(&self.api_key)
+                .json(&json!({ "model": self.model, "input": texts }))
+                .send()
+                .await
+                .map_err(|e| AppError::Upstream(format!("Embeddings request failed: {}", e)))?;
+
+            let status = response.status();
+            if !status.is_success() {
+                let text = response.text().await.unwrap_or_default();
+                return Err(AppError::Upstream(format!("Embeddings API returned {}: {}", status, text)));
+            }
+
+            let mut embeddings: OpenAiEmbeddings = response
+                .json()
+                .await
+                .map_err(|e| AppError::Upstream(format!("Unreadable embeddings response: {}", e)))?;
+            if embeddings.data.len() != texts.len() {
+                return Err(AppError::Upstream(format!(
+                    "Embeddings API returned {} vectors for {} texts",
+                    embeddings.data.len(),
+                    texts.len()
+                )));
+            }
+
+            embeddings.data.sort_by_key(|e| e.index);
+            Ok(embeddings.data.into_iter().map(|e| e.embedding).collect())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::cosine_similarity;
+
+    #[tokio::test]
+    async fn test_local_embeddings_rank_the_same_person_closest() {
+        let texts: Vec<String> = ["Jane Doe jane.doe Acme", "Jane Doe jdoe", "John Smith john.smith Acme"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let vectors = LocalEmbedder.embed(&texts).await.unwrap();
+
+        assert_eq!(vectors[0].len(), LOCAL_DIMENSIONS);
+        assert!((cosine_similarity(&vectors[0], &vectors[0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&vectors[0], &vectors[1]) > 0.78);
+        assert!(cosine_similarity(&vectors[0], &vectors[2]) < 0.5);
+        // Same text, same vector, run after run
+        assert_eq!(LocalEmbedder::vector("Jane Doe"), LocalEmbedder::vector("jane  DOE"));
+    }
+}
@@ -0,0 +1,292 @@
+//! Content generation for campaign assets
+//!
+//! Renders the prompt template for an asset type, asks the configured
+//! provider, and parses the answer into the same structs the templates
+//! produce. Every completion is recorded in `ai_usage` with its tokens,
+//! latency and cost.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use surrealdb::sql::Thing;
+
+use crate::ai::ai_email::GeneratedEmail;
+use crate::ai::ai_landing_page::GeneratedLandingPage;
+use crate::ai::ai_prep_brief::GeneratedPrepBrief;
+use crate::ai::ai_recommendation::GeneratedNextAction;
+use crate::ai::ai_reply::DraftReply;
+use crate::ai::ai_social::GeneratedPost;
+use crate::ai::ai_contact_summary::GeneratedContactSummary;
+use crate::ai::prompts;
+use crate::ai::provider::{AiProvider, Completion, ContentKind, Pricing};
+use crate::db::workspace_thing;
+use crate::error::{AppError, AppResult};
+use crate::models::{AiUsage, Sentiment};
+use crate::repositories::AiUsageRepository;
+
+/// Who a generation is billed to
+#[derive(Debug, Clone, Copy)]
+pub struct GenerationContext<'a> {
+    pub workspace_id: &'a str,
+    pub campaign_id: Option<&'a str>,
+}
+
+pub struct ContentGenerator {
+    provider: Arc<dyn AiProvider>,
+    usage: Option<(AiUsageRepository, Pricing)>,
+    infer_sentiment: bool,
+}
+
+impl ContentGenerator {
+    pub fn new(provider: Arc<dyn AiProvider>) -> Self {
+        Self {
+            provider,
+            usage: None,
+            infer_sentiment: false,
+        }
+    }
+
+    /// Record every generation in `ai_usage`, costed at `pricing`
+    pub fn with_usage_tracking(mut self, repo: AiUsageRepository, pricing: Pricing) -> Self {
+        self.usage = Some((repo, pricing));
+        self
+    }
+
+    /// Generate an email and compile its sections into HTML and text bodies
+    pub async fn generate_email(&self, ctx: GenerationContext<'_>, brief: &str) -> AppResult<GeneratedEmail> {
+        let completion = self.complete(ctx, ContentKind::Email, brief).await?;
+        parse_content::<GeneratedEmail>(&completion)?.compile().map_err(|e| {
+            AppError::Upstream(format!("{} returned an email that can't be rendered: {}", completion.provider, e))
+        })
+    }
+
+    pub async fn generate_social_posts(
+        &self,
+        ctx: GenerationContext<'_>,
+        brief: &str,
+    ) -> AppResult<Vec<GeneratedPost>> {
+        #[derive(Deserialize)]
+        struct Posts {
+            posts: Vec<GeneratedPost>,
+        }
+
+        let completion = self.complete(ctx, ContentKind::SocialPosts, brief).await?;
+        parse_content::<Posts>(&completion).map(|p| p.posts)
+    }
+
+    pub async fn generate_landing_page(
+        &self,
+        ctx: GenerationContext<'_>,
+        brief: &str,
+    ) -> AppResult<GeneratedLandingPage> {
+        let completion = self.complete(ctx, ContentKind::LandingPage, brief).await?;
+        parse_content(&completion)
+    }
+
+    /// Have the provider rate the sentiment of new notes and received email
+    pub fn with_sentiment_inference(mut self, enabled: bool) -> Self {
+        self.infer_sentiment = enabled;
+        self
+    }
+
+    /// Whether sentiment inference is on and there is an AI provider to do it
+    pub fn infers_sentiment(&self) -> bool {
+        self.infer_sentiment && !self.uses_templates()
+    }
+
+    /// Whether answers come from the built-in templates rather than an AI provider
+    pub fn uses_templates(&self) -> bool {
+        self.provider.is_template()
+    }
+
+    /// Summarize a contact from a description of their activity
+    pub async fn generate_contact_summary(
+        &self,
+        ctx: GenerationContext<'_>,
+        activity: &str,
+    ) -> AppResult<GeneratedContactSummary> {
+        let completion = self.complete(ctx, ContentKind::ContactSummary, activity).await?;
+        parse_content(&completion)
+    }
+
+    /// Turn the facts about a contact into a prep brief for a call
+    pub async fn generate_prep_brief(&self, ctx: GenerationContext<'_>, facts: &str) -> AppResult<GeneratedPrepBrief> {
+        let completion = self.complete(ctx, ContentKind::PrepBrief, facts).await?;
+        parse_content(&completion)
+    }
+
+    /// Draft a reply to a contact's email from `ai_reply::reply_brief`
+    pub async fn generate_reply(&self, ctx: GenerationContext<'_>, brief: &str) -> AppResult<DraftReply> {
+        let completion = self.complete(ctx, ContentKind::DraftReply, brief).await?;
+        parse_content(&completion)
+    }
+
+    /// Rate how the contact comes across in the text of a timeline entry
+    pub async fn generate_sentiment(&self, ctx: GenerationContext<'_>, text: &str) -> AppResult<Sentiment> {
+        #[derive(Deserialize)]
+        struct Rated {
+            sentiment: Sentiment,
+        }
+
+        let completion = self.complete(ctx, ContentKind::Sentiment, text).await?;
+        parse_content::<Rated>(&completion).map(|r| r.sentiment)
+    }
+
+    /// Reword the rule-based next actions described by `ai_recommendation::actions_brief`
+    pub async fn generate_next_actions(
+        &self,
+        ctx: GenerationContext<'_>,
+        brief: &str,
+    ) -> AppResult<Vec<GeneratedNextAction>> {
+        #[derive(Deserialize)]
+        struct Actions {
+            actions: Vec<GeneratedNextAction>,
+        }
+
+        let completion = self.complete(ctx, ContentKind::NextActions, brief).await?;
+        parse_content::<Actions>(&completion).map(|a| a.actions)
+    }
+
+    async fn complete(&self, ctx: GenerationContext<'_>, kind: ContentKind, brief: &str) -> AppResult<Completion> {
+        let request = prompts::request(kind, brief);
+        let completion = self.provider.complete(&request).await?;
+
+        // The tokens are spent even if the content turns out unusable, so
+        // record before parsing; a failed write only costs us the row
+        if let Some((repo, pricing)) = &self.usage {
+            let usage = usage_record(ctx, kind, &completion, pricing);
+            if let Err(e) = repo.insert(usage).await {
+                tracing::warn!("Failed to record AI usage: {}", e);
+            }
+        }
+
+        tracing::debug!(
+            "Generated {:?} with {} ({}, {} in / {} out tokens, {:?})",
+            kind,
+            completion.provider,
+            completion.model,
+            completion.input_tokens,
+            completion.output_tokens,
+            completion.latency
+        );
+        Ok(completion)
+    }
+}
+
+fn usage_record(ctx: GenerationContext<'_>, kind: ContentKind, completion: &Completion, pricing: &Pricing) -> AiUsage {
+    let now = Utc::now();
+
+    AiUsage {
+        id: None,
+        workspace: workspace_thing(ctx.workspace_id),
+        campaign: ctx.campaign_id.map(|id| Thing::from(("campaign", id))),
+        content_kind: kind.as_str().to_string(),
+        provider: completion.provider.to_string(),
+        model: completion.model.clone(),
+        input_tokens: completion.input_tokens,
+        output_tokens: completion.output_tokens,
+        latency_ms: completion.latency.as_millis() as u64,
+        cost_usd: pricing.cost_usd(completion),
+        month: now.format("%Y-%m").to_string(),
+        created_at: now,
+    }
+}
+
+/// Parse a completion as JSON, tolerating a Markdown code fence around it
+fn parse_content<T: DeserializeOwned>(completion: &Completion) -> AppResult<T> {
+    let text = completion.text.trim();
+    let json = text
+        .strip_prefix("```json")
+        .or_else(|| text.strip_prefix("```"))
+        .and_then(|t| t.strip_suffix("```"))
+        .unwrap_or(text);
+
+    serde_json::from_str(json.trim()).map_err(|e| {
+        AppError::Upstream(format!(
+            "{} returned content in an unexpected shape: {}",
+            completion.provider, e
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::provider::MockProvider;
+    use std::time::Duration;
+
+    fn completion(text: &str) -> Completion {
+        Completion {
+            text: text.into(),
+            provider: "test",
+            model: "test".into(),
+            input_tokens: 0,
+            output_tokens: 0,
+            latency: Duration::ZERO,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_provider_round_trips_every_asset_type() {
+        let generator = ContentGenerator::new(Arc::new(MockProvider));
+        let ctx = GenerationContext {
+            workspace_id: "acme",
+            campaign_id: None,
+        };
+
+        let email = generator.generate_email(ctx, "Invite to our webinar").await.unwrap();
+        assert!(email.subject.contains("Invited"));
+        // Compiled from the template's sections
+        assert!(email.body_html.contains("Reserve Your Spot"));
+        assert!(email.body_text.contains("Reserve Your Spot: https://crm.hey.sh/events/register"));
+
+        let posts = generator.generate_social_posts(ctx, "Product launch").await.unwrap();
+        assert!(!posts.is_empty());
+
+        let page = generator.generate_landing_page(ctx, "Product launch").await.unwrap();
+        assert!(!page.title.is_empty());
+    }
+
+    #[test]
+    fn test_parse_content_strips_code_fences() {
+        #[derive(Deserialize)]
+        struct Subject {
+            subject: String,
+        }
+
+        let fenced = completion("```json\n{\"subject\": \"Hi\"}\n```");
+        assert_eq!(parse_content::<Subject>(&fenced).unwrap().subject, "Hi");
+
+        let bare = completion("{\"subject\": \"Hello\"}");
+        assert_eq!(parse_content::<Subject>(&bare).unwrap().subject, "Hello");
+    }
+
+    #[test]
+    fn test_usage_record_is_costed_and_bucketed_by_month() {
+        let mut done = completion("{}");
+        done.input_tokens = 1_000_000;
+        done.output_tokens = 100_000;
+        let pricing = Pricing {
+            input_per_mtok: 3.0,
+            output_per_mtok: 15.0,
+        };
+        let ctx = GenerationContext {
+            workspace_id: "acme",
+            campaign_id: Some("launch"),
+        };
+
+        let usage = usage_record(ctx, ContentKind::Email, &done, &pricing);
+        assert_eq!(usage.content_kind, "email");
+        assert!((usage.cost_usd - 4.5).abs() < 1e-9);
+        assert_eq!(usage.campaign.unwrap().id.to_raw(), "launch");
+        assert_eq!(usage.month, usage.created_at.format("%Y-%m").to_string());
+    }
+
+    #[test]
+    fn test_parse_content_rejects_the_wrong_shape() {
+        let err = parse_content::<GeneratedEmail>(&completion("Sure! Here is your email:")).unwrap_err();
+        assert!(matches!(err, AppError::Upstream(_)));
+    }
+}
@@ -1,4 +1,15 @@
+pub mod ai_contact_summary;
 pub mod ai_email;
 pub mod ai_social;
 pub mod ai_landing_page;
+pub mod ai_prep_brief;
+pub mod ai_recommendation;
+pub mod ai_reply;
+pub mod ai_sentiment;
 pub mod ai_summary;
+pub mod embedding;
+pub mod generator;
+pub mod prompts;
+pub mod provider;
+
+pub use generator::{ContentGenerator, GenerationContext};
@@ -0,0 +1,160 @@
+//! Prompt templates for AI content generation
+//!
+//! Each asset type asks for one JSON object matching the struct it is parsed
+//! into, so the generated content stores exactly like the template output.
+//! Contact summaries, prep briefs, reply drafts and next actions work the
+//! same way, with what the CRM knows about the contacts as the brief.
+
+use crate::ai::provider::{CompletionRequest, ContentKind};
+
+const SYSTEM: &str = "You write marketing content for a small company's CRM campaigns. \
+    Write in a clear, friendly and specific voice; avoid hype and filler. \
+    Respond with a single JSON object and nothing else: no prose, no code fences.";
+
+const ANALYST_SYSTEM: &str = "You help salespeople and marketers keep track of their relationships in a CRM. \
+    Be factual and concise: only state what the activity shows, and never invent interactions. \
+    Respond with a single JSON object and nothing else: no prose, no code fences.";
+
+const CORRESPONDENT_SYSTEM: &str = "You draft email replies for a salesperson or marketer, written as them. \
+    Answer what the email asks, refer to past interactions only where the history shows them, \
+    and never make commitments, prices or dates up: leave a [placeholder] for the user to fill in. \
+    Respond with a single JSON object and nothing else: no prose, no code fences.";
+
+const EMAIL: &str = r#"Write a marketing email for this brief:
+
+{brief}
+
+Return JSON with these fields:
+{
+  "subject": "under 60 characters",
+  "preview_text": "under 90 characters, shown after the subject in inboxes",
+  "document": {
+    "sections": [
+      { "type": "heading", "text": "the email's title" },
+      { "type": "text", "text": "one or more paragraphs, separated by blank lines" },
+      { "type": "list", "items": ["short point"] },
+      { "type": "button", "text": "button label", "url": "https:// URL" },
+      { "type": "image", "src": "https:// image URL", "alt": "what the image shows" },
+      { "type": "divider" }
+    ]
+  },
+  "cta_text": "the button's label",
+  "cta_url": "the button's URL; use https://crm.hey.sh if the brief names none"
+}
+Use the section types in any order and number, with exactly one button. Add images only when the brief gives
+their URLs. Write plain text in every section: no HTML or Markdown; the sections are rendered into the email.
+Personalize with merge variables, each with a fallback for contacts missing the value:
+{{first_name|there}}, {{company|your team}}. Use no other variables unless the brief names them."#;
+
+const SOCIAL_POSTS: &str = r#"Write social media posts for this brief:
+
+{brief}
+
+Write one post each for twitter, linked_in, facebook and instagram, each in the style of its platform.
+Twitter posts must stay under 280 characters including hashtags.
+
+Return JSON of the form:
+{
+  "posts": [
+    {
+      "platform": "twitter" | "linked_in" | "facebook" | "instagram",
+      "content": "post text without hashtags",
+      "hashtags": ["WithoutHashSign"],
+      "suggested_image_prompt": "description of an image to accompany the post",
+      "character_count": number of characters in content
+    }
+  ]
+}"#;
+
+const LANDING_PAGE: &str = r#"Write the content of a landing page for this brief:
+
+{brief}
+
+Return JSON with these fields:
+{
+  "title": "page title",
+  "subtitle": "one sentence",
+  "hero_section": { "headline": "", "subheadline": "", "cta_text": "", "cta_url": "", "image_prompt": "" },
+  "features": [{ "title": "", "description": "", "icon": "single emoji" }],
+  "cta_section": { "headline": "", "description": "", "button_text": "", "button_url": "" },
+  "testimonials": [{ "quote": "", "author": "", "role": "", "company": "" }],
+  "faq": [{ "question": "", "answer": "" }],
+  "footer": { "company_name": "", "tagline": "", "links": [{ "text": "", "url": "" }] }
+}
+Give three or four features, two testimonials marked as examples to replace, and three FAQ items."#;
+
+const CONTACT_SUMMARY: &str = r#"Summarize our relationship with this contact from their CRM activity:
+
+{brief}
+
+Return JSON with these fields:
+{
+  "summary": "two to four sentences on who they are to us, how they have engaged and what happened most recently",
+  "recommendation": "one sentence on how to approach them now",
+  "next_best_action": "one concrete next step, under 12 words"
+}"#;
+
+const PREP_BRIEF: &str = r#"Someone is about to get on a call with this contact. Here is everything the CRM knows:
+
+{brief}
+
+Return JSON with this field:
+{
+  "markdown": "a one-page Markdown brief titled 'What to know before this call': who they are, where the relationship stands, open tasks, what recent emails were about, and two or three suggested talking points"
+}
+Use only the facts given, and leave out sections the facts say nothing about."#;
+
+const DRAFT_REPLY: &str = r#"Draft a reply to this contact's email:
+
+{brief}
+
+Write in the tone given and keep the body to the length given.
+Return JSON with these fields:
+{
+  "subject": "the reply's subject line, normally Re: and the original subject",
+  "body": "plain-text reply body with greeting and sign-off, without a signature block"
+}"#;
+
+const SENTIMENT: &str = r#"How does the contact come across in this CRM entry, towards us and our product?
+
+{brief}
+
+Return JSON with this field:
+{
+  "sentiment": "positive, neutral or negative"
+}
+Answer neutral when the entry is purely factual or says nothing about how they feel."#;
+
+const NEXT_ACTIONS: &str = r#"Our rules picked a next action for each of these contacts from their CRM activity:
+
+{brief}
+
+Reword each contact's action as one concrete step specific to them, and its reason as one sentence from the facts given.
+Keep the kind of action the rules picked; don't suggest a different one.
+Return JSON of the form:
+{
+  "actions": [
+    { "contact_id": "as given", "action": "under 12 words", "reason": "one sentence" }
+  ]
+}"#;
+
+/// The completion request for generating `kind` from the user's brief
+pub fn request(kind: ContentKind, brief: &str) -> CompletionRequest {
+    let (system, template) = match kind {
+        ContentKind::Email => (SYSTEM, EMAIL),
+        ContentKind::SocialPosts => (SYSTEM, SOCIAL_POSTS),
+        ContentKind::LandingPage => (SYSTEM, LANDING_PAGE),
+        ContentKind::ContactSummary => (ANALYST_SYSTEM, CONTACT_SUMMARY),
+        ContentKind::PrepBrief => (ANALYST_SYSTEM, PREP_BRIEF),
+        ContentKind::DraftReply => (CORRESPONDENT_SYSTEM, DRAFT_REPLY),
+        ContentKind::Sentiment => (ANALYST_SYSTEM, SENTIMENT),
+        ContentKind::NextActions => (ANALYST_SYSTEM, NEXT_ACTIONS),
+    };
+
+    CompletionRequest {
+        kind,
+        brief: brief.to_string(),
+        system: system.to_string(),
+        prompt: template.replace("{brief}", brief.trim()),
+    }
+}
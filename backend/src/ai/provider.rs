@@ -0,0 +1,444 @@
+//! LLM providers behind AI content generation
+//!
+//! `AiProvider` turns a prompt into text. `AnthropicProvider` and
+//! `OpenAiProvider` call the vendors' HTTP APIs with a per-request timeout
+//! and retry rate limits, server errors and timeouts with exponential
+//! backoff. `MockProvider` answers from the built-in templates, so
+//! development and tests need no API key.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future::BoxFuture;
+use serde_json::{json, Value};
+
+use crate::ai::{ai_email, ai_landing_page, ai_social};
+use crate::config::AiConfig;
+use crate::error::{AppError, AppResult};
+use crate::secrets::SecretsManager;
+
+/// What a completion is asked to produce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentKind {
+    Email,
+    SocialPosts,
+    LandingPage,
+    ContactSummary,
+    PrepBrief,
+    DraftReply,
+    Sentiment,
+    NextActions,
+}
+
+impl ContentKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentKind::Email => "email",
+            ContentKind::SocialPosts => "social_posts",
+            ContentKind::LandingPage => "landing_page",
+            ContentKind::ContactSummary => "contact_summary",
+            ContentKind::PrepBrief => "prep_brief",
+            ContentKind::DraftReply => "draft_reply",
+            ContentKind::Sentiment => "sentiment",
+            ContentKind::NextActions => "next_actions",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CompletionRequest {
+    pub kind: ContentKind,
+    /// The user's own description of what they want
+    pub brief: String,
+    pub system: String,
+    /// Full prompt, `brief` wrapped in the template for `kind`
+    pub prompt: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub text: String,
+    pub provider: &'static str,
+    pub model: String,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub latency: Duration,
+}
+
+pub trait AiProvider: Send + Sync {
+    /// Short name, e.g. `anthropic`
+    fn name(&self) -> &'static str;
+
+    /// Whether answers are the built-in templates rather than generated
+    fn is_template(&self) -> bool {
+        false
+    }
+
+    fn complete<'a>(&'a self, request: &'a CompletionRequest) -> BoxFuture<'a, AppResult<Completion>>;
+}
+
+/// Build the provider selected in config
+///
+/// API keys come from the secrets manager under `ai.api_key_secret`.
+pub fn build_provider(config: &AiConfig, secrets: &SecretsManager) -> AppResult<Arc<dyn AiProvider>> {
+    let api_key = || {
+        secrets
+            .get_secret(&config.api_key_secret)
+            .map_err(|e| AppError::Internal(format!("AI provider '{}' needs an API key: {}", config.provider, e)))
+    };
+
+    let provider: Arc<dyn AiProvider> = match config.provider.as_str() {
+        "mock" => Arc::new(MockProvider),
+        "anthropic" => Arc::new(AnthropicProvider {
+            http: HttpApi::new(config)?,
+            api_key: api_key()?,
+            model: config.model.clone().unwrap_or_else(|| "claude-sonnet-4-5".into()),
+            max_tokens: config.max_tokens,
+        }),
+        "openai" => Arc::new(OpenAiProvider {
+            http: HttpApi::new(config)?,
+            api_key: api_key()?,
+            model: config.model.clone().unwrap_or_else(|| "gpt-4o-mini".into()),
+            max_tokens: config.max_tokens,
+        }),
+        other => {
+            return Err(AppError::Internal(format!(
+                "Unknown AI provider '{}', expected mock, anthropic or openai",
+                other
+            )));
+        }
+    };
+
+    tracing::info!("AI content provider: {}", provider.name());
+    Ok(provider)
+}
+
+/// Token prices used to cost each generation
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Pricing {
+    /// USD per million input tokens
+    pub input_per_mtok: f64,
+    /// USD per million output tokens
+    pub output_per_mtok: f64,
+}
+
+impl Pricing {
+    /// Configured prices, falling back to the list price of each provider's default model
+    pub fn from_config(config: &AiConfig) -> Self {
+        let (input, output) = match config.provider.as_str() {
+            "anthropic" => (3.0, 15.0),
+            "openai" => (0.15, 0.60),
+            _ => (0.0, 0.0),
+        };
+
+        Self {
+            input_per_mtok: config.input_cost_per_mtok.unwrap_or(input),
+            output_per_mtok: config.output_cost_per_mtok.unwrap_or(output),
+        }
+    }
+
+    pub fn cost_usd(&self, completion: &Completion) -> f64 {
+        (completion.input_tokens as f64 * self.input_per_mtok
+            + completion.output_tokens as f64 * self.output_per_mtok)
+            / 1_000_000.0
+    }
+}
+
+/// HTTP client shared by the real providers, with timeout and retries
+struct HttpApi {
+    client: reqwest::Client,
+    max_retries: u32,
+    initial_backoff: Duration,
+}
+
+impl HttpApi {
+    fn new(config: &AiConfig) -> AppResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_secs))
+            .build()
+            .map_err(|e| AppError::Internal(format!("Failed to build AI client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            max_retries: config.max_retries,
+            initial_backoff: Duration::from_millis(config.initial_backoff_ms),
+        })
+    }
+
+    /// POST `body` and return the JSON response, retrying transient failures
+    async fn post(&self, url: &str, headers: &[(&str, &str)], body: &Value) -> AppResult<Value> {
+        let mut attempt = 0;
+
+        loop {
+            let mut request = self.client.post(url).json(body);
+            for (name, value) in headers {
+                request = request.header(*name, *value);
+            }
+
+            let error = match request.send().await {
+                Ok(response) if response.status().is_success() => {
+                    return response
+                        .json()
+                        .await
+                        .map_err(|e| AppError::Upstream(format!("Unreadable AI response: {}", e)));
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let text = response.text().await.unwrap_or_default();
+                    let error = AppError::Upstream(format!("AI provider returned {}: {}", status, text));
+                    if !is_retryable_status(status) {
+                        return Err(error);
+                    }
+                    error
+                }
+                Err(e) if e.is_timeout() || e.is_connect() => {
+                    AppError::Upstream(format!("AI provider unreachable: {}", e))
+                }
+                Err(e) => return Err(AppError::Upstream(format!("AI request failed: {}", e))),
+            };
+
+            if attempt >= self.max_retries {
+                return Err(error);
+            }
+            attempt += 1;
+            let delay = self.initial_backoff * 2u32.saturating_pow(attempt - 1);
+            tracing::warn!("{}; retry {} of {} in {:?}", error, attempt, self.max_retries, delay);
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Anthropic Messages API
+pub struct AnthropicProvider {
+    http: HttpApi,
+    api_key: String,
+    model: String,
+    max_tokens: u32,
+}
+
+impl AiProvider for AnthropicProvider {
+    fn name(&self) -> &'static str {
+        "anthropic"
+    }
+
+    fn complete<'a>(&'a self, request: &'a CompletionRequest) -> BoxFuture<'a, AppResult<Completion>> {
+        Box::pin(async move {
+            let started = Instant::now();
+            let body = json!({
+                "model": self.model,
+                "max_tokens": self.max_tokens,
+                "system": request.system,
+                "messages": [{ "role": "user", "content": request.prompt }],
+            });
+            let headers = [("x-api-key", self.api_key.as_str()), ("anthropic-version", "2023-06-01")];
+            let response = self.http.post("https://api.anthropic.com/v1/messages", &headers, &body).await?;
+
+            parse_anthropic_response(&response, &self.model, started.elapsed())
+        })
+    }
+}
+
+fn parse_anthropic_response(response: &Value, model: &str, latency: Duration) -> AppResult<Completion> {
+    let text = response["content"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|block| block["text"].as_str())
+        .collect::<String>();
+    if text.is_empty() {
+        return Err(AppError::Upstream("AI provider returned no text".into()));
+    }
+
+    Ok(Completion {
+        text,
+        provider: "anthropic",
+        model: response["model"].as_str().unwrap_or(model).to_string(),
+        input_tokens: response["usage"]["input_tokens"].as_u64().unwrap_or(0) as u32,
+        output_tokens: response["usage"]["output_tokens"].as_u64().unwrap_or(0) as u32,
+        latency,
+    })
+}
+
+/// OpenAI Chat Completions API
+pub struct OpenAiProvider {
+    http: HttpApi,
+    api_key: String,
+    model: String,
+    max_tokens: u32,
+}
+
+impl AiProvider for OpenAiProvider {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn complete<'a>(&'a self, request: &'a CompletionRequest) -> BoxFuture<'a, AppResult<Completion>> {
+        Box::pin(async move {
+            let started = Instant::now();
+            let body = json!({
+                "model": self.model,
+                "max_tokens": self.max_tokens,
+                "response_format": { "type": "json_object" },
+                "messages": [
+                    { "role": "system", "content": request.system },
+                    { "role": "user", "content": request.prompt },
+                ],
+            });
+            let authorization = format!("Bearer {}", self.api_key);
+            let headers = [("authorization", authorization.as_str())];
+            let response = self
+                .http
+                .post("https://api.openai.com/v1/chat/completions", &headers, &body)
+                .await?;
+
+            parse_openai_response(&response, &self.model, started.elapsed())
+        })
+    }
+}
+
+fn parse_openai_response(response: &Value, model: &str, latency: Duration) -> AppResult<Completion> {
+    let text = response["choices"][0]["message"]["content"]
+        .as_str()
+        .filter(|t| !t.is_empty())
+        .ok_or_else(|| AppError::Upstream("AI provider returned no text".into()))?;
+
+    Ok(Completion {
+        text: text.to_string(),
+        provider: "openai",
+        model: response["model"].as_str().unwrap_or(model).to_string(),
+        input_tokens: response["usage"]["prompt_tokens"].as_u64().unwrap_or(0) as u32,
+        output_tokens: response["usage"]["completion_tokens"].as_u64().unwrap_or(0) as u32,
+        latency,
+    })
+}
+
+/// Answers from the built-in keyword templates, with no network calls
+pub struct MockProvider;
+
+impl AiProvider for MockProvider {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn is_template(&self) -> bool {
+        true
+    }
+
+    fn complete<'a>(&'a self, request: &'a CompletionRequest) -> BoxFuture<'a, AppResult<Completion>> {
+        Box::pin(async move {
+            let started = Instant::now();
+            let content = match request.kind {
+                ContentKind::Email => serde_json::to_string(&ai_email::generate_email(&request.brief).await),
+                ContentKind::SocialPosts => {
+                    let posts = ai_social::generate_social_posts(&request.brief).await;
+                    serde_json::to_string(&json!({ "posts": posts }))
+                }
+                ContentKind::LandingPage => {
+                    serde_json::to_string(&ai_landing_page::generate_landing_page(&request.brief).await)
+                }
+                // These are written from CRM data rather than a user's brief,
+                // and the ai modules fall back to their own rules instead
+                ContentKind::ContactSummary
+                | ContentKind::PrepBrief
+                | ContentKind::DraftReply
+                | ContentKind::Sentiment
+                | ContentKind::NextActions => {
+                    return Err(AppError::Internal(format!(
+                        "The template provider has no {} template",
+                        request.kind.as_str()
+                    )));
+                }
+            };
+
+            Ok(Completion {
+                text: content.map_err(|e| AppError::Internal(e.to_string()))?,
+                provider: "mock",
+                model: "templates".into(),
+                input_tokens: 0,
+                output_tokens: 0,
+                latency: started.elapsed(),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_the_mock_provider_answers_from_templates() {
+        assert!(MockProvider.is_template());
+
+        let config = AiConfig {
+            provider: "openai".into(),
+            ..AiConfig::default()
+        };
+        let openai = OpenAiProvider {
+            http: HttpApi::new(&config).unwrap(),
+            api_key: "key".into(),
+            model: "gpt-4o".into(),
+            max_tokens: 100,
+        };
+        assert!(!openai.is_template());
+    }
+
+    #[test]
+    fn test_parse_anthropic_response() {
+        let response = json!({
+            "model": "claude-sonnet-4-5",
+            "content": [{ "type": "text", "text": "{\"subject\":" }, { "type": "text", "text": "\"Hi\"}" }],
+            "usage": { "input_tokens": 120, "output_tokens": 45 }
+        });
+
+        let completion = parse_anthropic_response(&response, "fallback", Duration::ZERO).unwrap();
+        assert_eq!(completion.text, "{\"subject\":\"Hi\"}");
+        assert_eq!(completion.model, "claude-sonnet-4-5");
+        assert_eq!((completion.input_tokens, completion.output_tokens), (120, 45));
+    }
+
+    #[test]
+    fn test_parse_openai_response() {
+        let response = json!({
+            "choices": [{ "message": { "role": "assistant", "content": "{}" } }],
+            "usage": { "prompt_tokens": 80, "completion_tokens": 2 }
+        });
+
+        let completion = parse_openai_response(&response, "gpt-4o-mini", Duration::ZERO).unwrap();
+        assert_eq!(completion.model, "gpt-4o-mini");
+        assert_eq!(completion.output_tokens, 2);
+
+        assert!(parse_openai_response(&json!({ "choices": [] }), "gpt-4o-mini", Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_pricing_costs_both_token_kinds() {
+        let pricing = Pricing {
+            input_per_mtok: 3.0,
+            output_per_mtok: 15.0,
+        };
+        let completion = Completion {
+            text: String::new(),
+            provider: "anthropic",
+            model: "claude-sonnet-4-5".into(),
+            input_tokens: 2_000,
+            output_tokens: 1_000,
+            latency: Duration::ZERO,
+        };
+
+        assert!((pricing.cost_usd(&completion) - 0.021).abs() < 1e-12);
+        assert_eq!(Pricing::from_config(&AiConfig::default()).cost_usd(&completion), 0.0);
+    }
+
+    #[test]
+    fn test_only_rate_limits_and_server_errors_are_retried() {
+        assert!(is_retryable_status(reqwest::StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(reqwest::StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable_status(reqwest::StatusCode::UNAUTHORIZED));
+        assert!(!is_retryable_status(reqwest::StatusCode::BAD_REQUEST));
+    }
+}
@@ -0,0 +1,136 @@
+//! Workspace backups from the command line
+//!
+//! The same archives GET /api/workspace/backup downloads and POST
+//! /api/workspace/restore reads (see `services::backup_service`), written
+//! to and read from files:
+//!
+//! ```text
+//! crm-server backup WORKSPACE_ID FILE   write the workspace to FILE
+//! crm-server restore FILE               replace the workspace FILE was taken from with it
+//! ```
+//!
+//! A restore clears the shared read cache when Redis is used; servers with
+//! the in-memory cache catch up within its TTL.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use futures::stream::{self, StreamExt};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::config::Config;
+use crate::db::Database;
+use crate::error::AppError;
+use crate::secrets;
+use crate::services::read_cache::build_read_cache;
+use crate::services::BackupService;
+
+/// Bytes read from the archive at a time during a restore
+const READ_CHUNK_BYTES: usize = 64 * 1024;
+
+/// What `crm-server backup` or `crm-server restore` was asked to do
+#[derive(Debug, PartialEq, Eq)]
+pub enum BackupCommand {
+    Backup { workspace_id: String, path: PathBuf },
+    Restore { path: PathBuf },
+}
+
+impl BackupCommand {
+    /// Parse the arguments, starting with `backup` or `restore`
+    pub fn parse(args: &[String]) -> Result<Self> {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+        match args.as_slice() {
+            ["backup", workspace_id, path] => Ok(Self::Backup {
+                workspace_id: workspace_id.to_string(),
+                path: PathBuf::from(path),
+            }),
+            ["restore", path] => Ok(Self::Restore { path: PathBuf::from(path) }),
+            _ => bail!("Usage: crm-server backup WORKSPACE_ID FILE | crm-server restore FILE"),
+        }
+    }
+}
+
+pub async fn run(db: Arc<Database>, config: &Config, command: BackupCommand) -> Result<()> {
+    let read_cache = build_read_cache(&config.cache, &secrets::init_secrets_manager()).await?;
+    let service = BackupService::new(db, Arc::new(read_cache));
+
+    match command {
+        BackupCommand::Backup { workspace_id, path } => {
+            if let Err(e) = write_backup(&service, &workspace_id, &path).await {
+                // A partial archive won't restore; don't leave one behind
+                let _ = tokio::fs::remove_file(&path).await;
+                return Err(e);
+            }
+            tracing::info!("Backed up workspace {} to {}", workspace_id, path.display());
+        }
+        BackupCommand::Restore { path } => {
+            let file = tokio::fs::File::open(&path)
+                .await
+                .with_context(|| format!("Failed to open {}", path.display()))?;
+            let chunks = stream::unfold(file, |mut file| async move {
+                let mut chunk = vec![0u8; READ_CHUNK_BYTES];
+                match file.read(&mut chunk).await {
+                    Ok(0) => None,
+                    Ok(n) => {
+                        chunk.truncate(n);
+                        Some((Ok(chunk), file))
+                    }
+                    Err(e) => Some((Err(AppError::Internal(format!("Failed to read the backup: {}", e))), file)),
+                }
+            });
+
+            let restored = service.restore(None, chunks).await?;
+            for table in &restored.tables {
+                println!("{:<24} {}", table.table, table.records);
+            }
+            println!(
+                "Restored workspace {} as of {} ({} records)",
+                restored.workspace_id,
+                restored.backed_up_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                restored.records
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn write_backup(service: &BackupService, workspace_id: &str, path: &Path) -> Result<()> {
+    let mut file = tokio::fs::File::create(path)
+        .await
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+
+    let mut chunks = Box::pin(service.export(workspace_id));
+    while let Some(chunk) = chunks.next().await {
+        file.write_all(chunk?.as_bytes()).await?;
+    }
+    file.flush().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_backup_command() {
+        let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+
+        assert_eq!(
+            BackupCommand::parse(&args("backup acme acme.ndjson")).unwrap(),
+            BackupCommand::Backup {
+                workspace_id: "acme".into(),
+                path: PathBuf::from("acme.ndjson")
+            }
+        );
+        assert_eq!(
+            BackupCommand::parse(&args("restore acme.ndjson")).unwrap(),
+            BackupCommand::Restore { path: PathBuf::from("acme.ndjson") }
+        );
+        assert!(BackupCommand::parse(&args("backup acme")).is_err());
+        assert!(BackupCommand::parse(&args("restore")).is_err());
+    }
+}
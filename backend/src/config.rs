@@ -1,52 +1,891 @@
+//! Server configuration
+//!
+//! Layered, later sources winning: `config/base.yaml`, then
+//! `config/{RUN_MODE}.yaml`, then `CRM__SECTION__KEY` environment variables.
+//! The deployment variables `JWT_SECRET` and `SURREALDB_*` are honoured when
+//! their `CRM__` equivalents are unset. `Config::validate` runs at startup.
+
 use config::{Config as ConfigLoader, ConfigError, Environment, File};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::env;
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
+    /// From `RUN_MODE`; not read from the files
+    #[serde(default)]
+    pub run_mode: RunMode,
     pub server: ServerConfig,
     pub database: DatabaseConfig,
     pub jwt: JwtConfig,
+    pub tracking: TrackingConfig,
+    #[serde(default)]
+    pub webhooks: WebhookConfig,
+    #[serde(default)]
+    pub engagement: EngagementJobConfig,
+    #[serde(default)]
+    pub segments: SegmentConfig,
+    #[serde(default)]
+    pub scheduler: SchedulerConfig,
+    #[serde(default)]
+    pub sequences: SequenceConfig,
+    #[serde(default)]
+    pub events: EventConfig,
+    #[serde(default)]
+    pub digest: DigestConfig,
+    #[serde(default)]
+    pub social: SocialConfig,
+    #[serde(default)]
+    pub landing_pages: LandingPageConfig,
+    #[serde(default)]
+    pub inbox: InboxConfig,
+    #[serde(default)]
+    pub email_events: EmailEventsConfig,
+    #[serde(default)]
+    pub senders: SendersConfig,
+    #[serde(default)]
+    pub enrichment: EnrichmentConfig,
+    #[serde(default)]
+    pub attachments: AttachmentsConfig,
+    #[serde(default)]
+    pub avatars: AvatarsConfig,
+    #[serde(default)]
+    pub trash: TrashConfig,
+    #[serde(default)]
+    pub ai: AiConfig,
+    #[serde(default)]
+    pub dedupe: DedupeConfig,
+    #[serde(default)]
+    pub cache: CacheConfig,
+    #[serde(default)]
+    pub limits: LimitsConfig,
+    #[serde(default)]
+    pub credentials: CredentialsConfig,
+    #[serde(default)]
+    pub cors: CorsConfig,
+    #[serde(default)]
+    pub security_headers: SecurityHeadersConfig,
     pub logging: LoggingConfig,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+/// Which `config/{mode}.yaml` is layered on the base, and how strictly the
+/// result is checked
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RunMode {
+    #[default]
+    Development,
+    Test,
+    Staging,
+    Production,
+}
+
+impl RunMode {
+    /// Insecure settings are errors rather than warnings
+    pub fn is_strict(self) -> bool {
+        matches!(self, Self::Staging | Self::Production)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ServerConfig {
     pub port: u16,
     pub host: String,
+    /// Seconds in-flight requests, and then background jobs, get to finish
+    /// after SIGTERM or Ctrl-C
+    #[serde(default = "default_shutdown_timeout_secs")]
+    pub shutdown_timeout_secs: u64,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+fn default_shutdown_timeout_secs() -> u64 {
+    25
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DatabaseConfig {
     pub surrealdb: SurrealDbConfig,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SurrealDbConfig {
+    /// `remote` (a SurrealDB server at `url`) or `rocksdb` (embedded in this
+    /// process, on `data_dir`; needs the `embedded` feature)
+    #[serde(default = "default_engine")]
+    pub engine: String,
+    /// Directory the embedded engine keeps its data in
+    #[serde(default = "default_data_dir")]
+    pub data_dir: String,
     pub url: String,
     pub namespace: String,
     pub database: String,
     pub username: String,
     pub password: String,
+    /// Connection timeout in seconds
     pub timeout: Option<u64>,
+    /// Timeout for a single query in seconds
+    #[serde(default = "default_query_timeout_secs")]
+    pub query_timeout_secs: u64,
+    #[serde(default)]
+    pub retry: DbRetryConfig,
+}
+
+fn default_engine() -> String {
+    "remote".into()
+}
+
+fn default_data_dir() -> String {
+    "data/crm.db".into()
+}
+
+fn default_query_timeout_secs() -> u64 {
+    30
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct DbRetryConfig {
+    /// Attempts to connect at startup before giving up
+    pub connect_attempts: u32,
+    /// Retries of a read after a dropped connection or timeout
+    pub max_retries: u32,
+    /// Delay before the first retry in milliseconds; doubles on each retry
+    pub initial_backoff_ms: u64,
+    /// Upper bound on the delay between retries in milliseconds
+    pub max_backoff_ms: u64,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+impl Default for DbRetryConfig {
+    fn default() -> Self {
+        Self {
+            connect_attempts: 10,
+            max_retries: 2,
+            initial_backoff_ms: 200,
+            max_backoff_ms: 5000,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct JwtConfig {
     pub secret: String,
+    /// Lifetime of access tokens in minutes
+    #[serde(default = "default_access_token_ttl")]
+    pub access_token_ttl_minutes: i64,
+    /// Lifetime of refresh tokens in days
+    #[serde(default = "default_refresh_token_ttl")]
+    pub refresh_token_ttl_days: i64,
+}
+
+fn default_access_token_ttl() -> i64 {
+    15
+}
+
+fn default_refresh_token_ttl() -> i64 {
+    30
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrackingConfig {
+    /// Key used to sign email open/click tracking tokens
+    pub secret: String,
+    /// Public base URL that tracking links in outgoing emails point at
+    pub base_url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// Attempts per delivery before it is marked failed
+    pub max_attempts: u32,
+    /// Delay before the first retry in seconds; doubles on each retry
+    pub initial_backoff_secs: u64,
+    /// How often the dispatcher polls for due deliveries, in seconds
+    pub poll_interval_secs: u64,
+    /// Timeout for a single delivery request, in seconds
+    pub request_timeout_secs: u64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 6,
+            initial_backoff_secs: 30,
+            poll_interval_secs: 5,
+            request_timeout_secs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct EngagementJobConfig {
+    /// How often every contact's engagement score is recomputed, in seconds
+    pub recalculate_interval_secs: u64,
+    /// Contacts loaded per page while recomputing
+    pub batch_size: u32,
+}
+
+impl Default for EngagementJobConfig {
+    fn default() -> Self {
+        Self {
+            recalculate_interval_secs: 3600,
+            batch_size: 200,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SegmentConfig {
+    /// How often saved segment member counts are refreshed, in seconds
+    pub count_refresh_interval_secs: u64,
+}
+
+impl Default for SegmentConfig {
+    fn default() -> Self {
+        Self {
+            count_refresh_interval_secs: 900,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    /// How often due campaigns and open send windows are checked, in seconds
+    pub poll_interval_secs: u64,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self { poll_interval_secs: 60 }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SequenceConfig {
+    /// How often due sequence enrollments are advanced, in seconds
+    pub run_interval_secs: u64,
+    /// Most enrollments advanced per run; the rest wait for the next one
+    pub batch_size: u32,
+}
+
+impl Default for SequenceConfig {
+    fn default() -> Self {
+        Self {
+            run_interval_secs: 300,
+            batch_size: 500,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct EventConfig {
+    /// How often due event reminders and follow-ups are sent, in seconds
+    pub reminder_interval_secs: u64,
+    /// Key used to sign the tokens in check-in QR codes
+    pub checkin_secret: String,
+}
+
+impl Default for EventConfig {
+    fn default() -> Self {
+        Self {
+            reminder_interval_secs: 300,
+            checkin_secret: "change-this-checkin-secret-in-production".to_string(),
+        }
+    }
+}
+
+/// Weekly digest email to workspace members
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct DigestConfig {
+    /// How often members with a digest due are looked for, in seconds
+    pub check_interval_secs: u64,
+    /// Days of activity a digest covers, and how far ahead it looks for events
+    pub period_days: i64,
+    /// Days without activity after which a lead counts as going cold
+    pub stale_after_days: i64,
+    /// Most entries listed per section
+    pub section_limit: u32,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_secs: 900,
+            period_days: 7,
+            stale_after_days: 14,
+            section_limit: 10,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SocialConfig {
+    /// How often due social posts are published, in seconds
+    pub publish_interval_secs: u64,
+    /// Attempts per post before it is marked failed
+    pub max_attempts: u32,
+    /// Delay before the first retry in seconds; doubles on each retry
+    pub initial_backoff_secs: u64,
+    /// Timeout for a single platform request, in seconds
+    pub request_timeout_secs: u64,
+    pub linkedin: LinkedInConfig,
+    pub twitter: TwitterConfig,
+}
+
+impl Default for SocialConfig {
+    fn default() -> Self {
+        Self {
+            publish_interval_secs: 60,
+            max_attempts: 3,
+            initial_backoff_secs: 300,
+            request_timeout_secs: 30,
+            linkedin: LinkedInConfig::default(),
+            twitter: TwitterConfig::default(),
+        }
+    }
+}
+
+/// Access tokens are per workspace; see `SocialAccount`
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct LinkedInConfig {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct TwitterConfig {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct LandingPageConfig {
+    /// Length of the submission rate limit window, in seconds
+    pub rate_limit_window_secs: u64,
+    /// Submissions one client (by IP) may make to a page per window
+    pub max_submissions_per_client: u32,
+    /// Submissions a page accepts per window; pages can set their own
+    pub max_submissions_per_page: u32,
+    /// Proxies in front of the server that append to `X-Forwarded-For`; the
+    /// client IP is the hop the outermost of them saw. 0 uses the socket address
+    pub trusted_proxies: usize,
+    /// Reject emails from known throwaway providers
+    pub block_disposable_emails: bool,
+    /// Further email domains to reject, e.g. competitors' or test domains
+    pub blocked_email_domains: Vec<String>,
+    pub captcha: CaptchaConfig,
+}
+
+impl Default for LandingPageConfig {
+    fn default() -> Self {
+        Self {
+            rate_limit_window_secs: 3600,
+            max_submissions_per_client: 5,
+            max_submissions_per_page: 1000,
+            trusted_proxies: 0,
+            block_disposable_emails: true,
+            blocked_email_domains: Vec::new(),
+            captcha: CaptchaConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CaptchaConfig {
+    /// `none`, `hcaptcha` or `turnstile`
+    pub provider: String,
+    /// Public key pages render the widget with
+    pub site_key: String,
+    /// Name of the secret holding the provider's secret key
+    pub secret_key_secret: String,
+    /// Timeout for a verification request, in seconds
+    pub request_timeout_secs: u64,
+}
+
+impl Default for CaptchaConfig {
+    fn default() -> Self {
+        Self {
+            provider: "none".into(),
+            site_key: String::new(),
+            secret_key_secret: "CAPTCHA_SECRET_KEY".into(),
+            request_timeout_secs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct InboxConfig {
+    /// How often connected mailboxes are checked for new email, in seconds
+    pub sync_interval_secs: u64,
+    /// Most messages read from one mailbox per sync; the rest wait for the next
+    pub max_messages_per_sync: u32,
+    /// How far back the first sync of a mailbox looks, in days
+    pub initial_sync_days: u32,
+    /// Timeout for a single mail server or API request, in seconds
+    pub request_timeout_secs: u64,
+    pub gmail: GmailConfig,
+}
+
+impl Default for InboxConfig {
+    fn default() -> Self {
+        Self {
+            sync_interval_secs: 300,
+            max_messages_per_sync: 200,
+            initial_sync_days: 7,
+            request_timeout_secs: 30,
+            gmail: GmailConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct GmailConfig {
+    /// OAuth client ID; Gmail can't be connected while empty
+    pub client_id: String,
+    /// Name of the secret holding the OAuth client secret
+    pub client_secret_secret: String,
+    /// Must match a redirect URI registered for the OAuth client
+    pub redirect_uri: String,
+}
+
+impl Default for GmailConfig {
+    fn default() -> Self {
+        Self {
+            client_id: String::new(),
+            client_secret_secret: "GMAIL_CLIENT_SECRET".to_string(),
+            redirect_uri: "http://localhost:8080/api/integrations/gmail/callback".to_string(),
+        }
+    }
+}
+
+/// Bounce and complaint callbacks from the email provider
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct EmailEventsConfig {
+    /// The callback endpoints answer 404 while disabled
+    pub enabled: bool,
+    /// Name of the secret holding the token the callback URLs carry
+    pub webhook_token_secret: String,
+    /// Timeout for confirming an SNS subscription, in seconds
+    pub request_timeout_secs: u64,
+}
+
+impl Default for EmailEventsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            webhook_token_secret: "EMAIL_EVENTS_TOKEN".to_string(),
+            request_timeout_secs: 10,
+        }
+    }
+}
+
+/// Verifying the domains campaign email is sent from
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SendersConfig {
+    /// DNS-over-HTTPS resolver answering JSON queries
+    pub dns_url: String,
+    /// Domain the email provider publishes its SPF record at, e.g.
+    /// `sendgrid.net`; empty accepts any SPF record
+    pub spf_include: String,
+    /// Selectors the email provider signs with; one must have a DKIM key
+    pub dkim_selectors: Vec<String>,
+    /// Timeout for a single DNS query, in seconds
+    pub request_timeout_secs: u64,
+}
+
+impl Default for SendersConfig {
+    fn default() -> Self {
+        Self {
+            dns_url: "https://cloudflare-dns.com/dns-query".to_string(),
+            spf_include: "sendgrid.net".to_string(),
+            dkim_selectors: vec!["s1".to_string(), "s2".to_string()],
+            request_timeout_secs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct TrashConfig {
+    /// Days a deleted contact or company stays restorable
+    pub retention_days: u32,
+    /// How often expired trash is purged, in seconds
+    pub purge_interval_secs: u64,
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self {
+            retention_days: 30,
+            purge_interval_secs: 3600,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AiConfig {
+    /// `mock` (built-in templates), `anthropic` or `openai`
+    pub provider: String,
+    /// Model name; each provider has its own default
+    pub model: Option<String>,
+    /// Name of the secret holding the provider's API key
+    pub api_key_secret: String,
+    /// Upper bound on generated tokens per request
+    pub max_tokens: u32,
+    /// Timeout for a single provider request, in seconds
+    pub request_timeout_secs: u64,
+    /// Retries after a rate limit, server error or timeout
+    pub max_retries: u32,
+    /// Delay before the first retry in milliseconds; doubles on each retry
+    pub initial_backoff_ms: u64,
+    /// USD per million input tokens; defaults to the provider's list price
+    pub input_cost_per_mtok: Option<f64>,
+    /// USD per million output tokens; defaults to the provider's list price
+    pub output_cost_per_mtok: Option<f64>,
+    /// Rate the sentiment of new notes and received email that weren't given one
+    pub infer_sentiment: bool,
+    /// How often entries queued for a sentiment rating are rated, in seconds
+    pub sentiment_interval_secs: u64,
+    /// Most queued entries rated per pass
+    pub sentiment_batch_size: u32,
+    /// Most contact summaries a workspace has the provider write per hour
+    pub summaries_per_hour: u32,
+    /// Most recommendation lists a workspace has the provider reword per hour
+    pub recommendations_per_hour: u32,
+}
+
+impl Default for AiConfig {
+    fn default() -> Self {
+        Self {
+            provider: "mock".into(),
+            model: None,
+            api_key_secret: "AI_API_KEY".into(),
+            max_tokens: 4096,
+            request_timeout_secs: 60,
+            max_retries: 2,
+            initial_backoff_ms: 1000,
+            input_cost_per_mtok: None,
+            output_cost_per_mtok: None,
+            infer_sentiment: false,
+            sentiment_interval_secs: 30,
+            sentiment_batch_size: 20,
+            summaries_per_hour: 60,
+            recommendations_per_hour: 30,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct DedupeConfig {
+    /// How often contacts are embedded and compared, in seconds
+    pub check_interval_secs: u64,
+    /// `local` (hashed character trigrams, no API key) or `openai`
+    pub embedder: String,
+    /// Embedding model; `text-embedding-3-small` for openai
+    pub model: Option<String>,
+    /// Name of the secret holding the OpenAI API key
+    pub api_key_secret: String,
+    /// Timeout for a single embeddings request, in seconds
+    pub request_timeout_secs: u64,
+    /// Contacts embedded per request
+    pub batch_size: usize,
+    /// Smallest similarity, 0-1, that is suggested; depends on the embedder
+    pub min_confidence: f64,
+    /// Most recently updated contacts compared per workspace; every pair is compared
+    pub max_contacts: u32,
+}
+
+impl Default for DedupeConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_secs: 3600,
+            embedder: "local".into(),
+            model: None,
+            api_key_secret: "AI_API_KEY".into(),
+            request_timeout_secs: 30,
+            batch_size: 100,
+            min_confidence: 0.78,
+            max_contacts: 5000,
+        }
+    }
+}
+
+/// Read cache for pipeline summaries, tag lists and segment counts
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// `memory`, `redis` (needs the `redis` feature) or `none`
+    pub backend: String,
+    /// Longest a value is served before it is computed again, in seconds
+    pub ttl_secs: u64,
+    /// Most values kept by the `memory` backend
+    pub max_entries: u64,
+    /// Name of the secret holding the Redis URL
+    pub redis_url_secret: String,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            backend: "memory".into(),
+            ttl_secs: 60,
+            max_entries: 10_000,
+            redis_url_secret: "REDIS_URL".into(),
+        }
+    }
+}
+
+/// Encryption of credentials stored for connected accounts
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CredentialsConfig {
+    /// Name of the secret holding the encryption key
+    pub key_secret: String,
+}
+
+impl Default for CredentialsConfig {
+    fn default() -> Self {
+        Self {
+            key_secret: "CREDENTIALS_KEY".into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct EnrichmentConfig {
+    /// `none` (enrichment disabled) or `proxycurl`
+    pub provider: String,
+    /// Name of the secret holding the provider's API key
+    pub api_key_secret: String,
+    /// Timeout for a single provider request, in seconds
+    pub request_timeout_secs: u64,
+}
+
+impl Default for EnrichmentConfig {
+    fn default() -> Self {
+        Self {
+            provider: "none".into(),
+            api_key_secret: "ENRICHMENT_API_KEY".into(),
+            request_timeout_secs: 20,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AttachmentsConfig {
+    /// Largest file accepted, in bytes
+    pub max_size_bytes: usize,
+    /// Content types files may have
+    pub allowed_content_types: Vec<String>,
+    pub storage: StorageConfig,
+}
+
+impl Default for AttachmentsConfig {
+    fn default() -> Self {
+        Self {
+            max_size_bytes: 25 * 1024 * 1024,
+            allowed_content_types: [
+                "application/pdf",
+                "application/msword",
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+                "application/vnd.ms-powerpoint",
+                "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+                "application/vnd.ms-excel",
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+                "text/plain",
+                "text/csv",
+                "image/png",
+                "image/jpeg",
+                "image/gif",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+            storage: StorageConfig::default(),
+        }
+    }
+}
+
+/// Contact avatars; kept in the attachments' object storage
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AvatarsConfig {
+    /// Largest image accepted, in bytes
+    pub max_size_bytes: usize,
+}
+
+impl Default for AvatarsConfig {
+    fn default() -> Self {
+        Self {
+            max_size_bytes: 5 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// `local` (files on disk) or `s3`
+    pub backend: String,
+    /// Directory files are kept in with the `local` backend
+    pub local_dir: String,
+    pub s3_bucket: String,
+    pub s3_region: String,
+    /// Set for S3-compatible services (MinIO, R2); AWS when empty
+    pub s3_endpoint: String,
+    /// Names of the secrets holding the S3 access key pair
+    pub s3_access_key_secret: String,
+    pub s3_secret_key_secret: String,
+    /// Timeout for a single S3 request, in seconds
+    pub request_timeout_secs: u64,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            backend: "local".into(),
+            local_dir: "data/attachments".into(),
+            s3_bucket: String::new(),
+            s3_region: "us-east-1".into(),
+            s3_endpoint: String::new(),
+            s3_access_key_secret: "S3_ACCESS_KEY_ID".into(),
+            s3_secret_key_secret: "S3_SECRET_ACCESS_KEY".into(),
+            request_timeout_secs: 60,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct LimitsConfig {
+    /// Largest request body on authenticated routes, in bytes
+    pub api_body_bytes: usize,
+    /// Largest request body on public routes (sign-in, forms, inbound
+    /// webhooks), in bytes
+    pub public_body_bytes: usize,
+    /// Largest CRM export accepted by an import, in bytes
+    pub import_body_bytes: usize,
+    /// Deepest nesting of arrays and objects in a JSON body
+    pub json_max_depth: usize,
+    /// Most items in any one JSON array
+    pub json_max_array_items: usize,
+    /// Most keys in any one JSON object
+    pub json_max_object_keys: usize,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            api_body_bytes: 2 * 1024 * 1024,
+            public_body_bytes: 64 * 1024,
+            import_body_bytes: 50 * 1024 * 1024,
+            json_max_depth: 32,
+            json_max_array_items: 5_000,
+            json_max_object_keys: 500,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// Allow any origin, method and header; for development and test only
+    pub permissive: bool,
+    /// Origins browsers may call the API from, e.g. `https://crm.example.com`
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    /// How long browsers may cache a preflight response, in seconds
+    pub max_age_secs: u64,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            permissive: false,
+            allowed_origins: vec!["http://localhost:3000".into()],
+            allowed_methods: ["GET", "POST", "PUT", "PATCH", "DELETE"].map(String::from).to_vec(),
+            allowed_headers: ["authorization", "content-type", "idempotency-key", "x-request-id"]
+                .map(String::from)
+                .to_vec(),
+            max_age_secs: 600,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SecurityHeadersConfig {
+    /// `Strict-Transport-Security` max-age in seconds; 0 leaves the header off
+    pub hsts_max_age_secs: u64,
+    /// `X-Frame-Options` value; empty leaves the header off
+    pub frame_options: String,
+    /// `Referrer-Policy` value; empty leaves the header off
+    pub referrer_policy: String,
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        Self {
+            hsts_max_age_secs: 31_536_000,
+            frame_options: "DENY".into(),
+            referrer_policy: "no-referrer".into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LoggingConfig {
     pub level: String,
+    /// `text`, or `json` for one object per line with the request ID
     pub format: String,
 }
 
+/// Deployment variables read when the matching `CRM__` variable is unset
+const LEGACY_ENV: &[(&str, &str)] = &[
+    ("JWT_SECRET", "jwt.secret"),
+    ("SURREALDB_URL", "database.surrealdb.url"),
+    ("SURREALDB_NAMESPACE", "database.surrealdb.namespace"),
+    ("SURREALDB_DATABASE", "database.surrealdb.database"),
+    ("SURREALDB_USER", "database.surrealdb.username"),
+    ("SURREALDB_PASS", "database.surrealdb.password"),
+];
+
+/// Values shipped in `config/base.yaml`, `.env.example` and the defaults above
+const DEFAULT_SECRETS: &[&str] = &[
+    "change-this-in-production",
+    "change-this-tracking-secret-in-production",
+    "change-this-checkin-secret-in-production",
+    "your-super-secret-jwt-key-change-in-production",
+];
+
+/// Shortest signing secret accepted in staging and production
+const MIN_SECRET_LEN: usize = 32;
+
+/// Keys whose values are replaced in `Config::redacted`. The `*_secret` keys
+/// elsewhere name entries in the secrets manager and are shown.
+const REDACTED_KEYS: &[&str] = &["secret", "checkin_secret", "password"];
+
 impl Config {
     pub fn from_env() -> Result<Self, ConfigError> {
         let environment = env::var("RUN_MODE").unwrap_or_else(|_| "development".into());
 
-        let mut config_loader = ConfigLoader::builder()
+        let mut builder = ConfigLoader::builder()
             // Start with the base configuration
             .add_source(File::with_name("config/base"))
             // Add environment-specific configuration
@@ -57,11 +896,198 @@ impl Config {
                     .separator("__")
                     .try_parsing(true)
                     .list_separator(",")
-                    .with_list_parse_key("database.surrealdb.tags"),
+                    .with_list_parse_key("database.surrealdb.tags")
+                    .with_list_parse_key("cors.allowed_origins")
+                    .with_list_parse_key("cors.allowed_methods")
+                    .with_list_parse_key("cors.allowed_headers"),
             )
-            // Add secret overrides if available
-            .build()?;
+            .set_override("run_mode", environment.as_str())?;
+
+        for (var, key) in LEGACY_ENV {
+            let crm_var = format!("CRM__{}", key.replace('.', "__").to_uppercase());
+            if env::var(&crm_var).is_err() {
+                builder = builder.set_override_option(*key, env::var(var).ok())?;
+            }
+        }
+
+        builder.build()?.try_deserialize()
+    }
+
+    /// Check the loaded configuration, returning warnings to log.
+    ///
+    /// Default or short secrets, the default database credentials and a
+    /// localhost tracking URL are warnings in development and test, and
+    /// errors in staging and production.
+    pub fn validate(&self) -> Result<Vec<String>, ConfigError> {
+        let mut errors = Vec::new();
+        let mut insecure = Vec::new();
+
+        let secrets = [
+            ("jwt.secret", &self.jwt.secret),
+            ("tracking.secret", &self.tracking.secret),
+            ("events.checkin_secret", &self.events.checkin_secret),
+        ];
+        for (key, secret) in secrets {
+            if secret.trim().is_empty() {
+                errors.push(format!("{} is empty", key));
+            } else if secret.contains("${") {
+                errors.push(format!("{} holds an unexpanded placeholder; set it from the environment", key));
+            } else if DEFAULT_SECRETS.contains(&secret.as_str()) {
+                insecure.push(format!("{} is the shipped default", key));
+            } else if secret.len() < MIN_SECRET_LEN {
+                insecure.push(format!("{} is shorter than {} characters", key, MIN_SECRET_LEN));
+            }
+        }
+
+        let surrealdb = &self.database.surrealdb;
+        if surrealdb.password.contains("${") {
+            errors.push("database.surrealdb.password holds an unexpanded placeholder".to_string());
+        }
+        if surrealdb.engine == "remote" && surrealdb.username == "root" && surrealdb.password == "root" {
+            insecure.push("database.surrealdb uses the default root/root credentials".to_string());
+        }
+
+        if self.jwt.access_token_ttl_minutes <= 0 || self.jwt.refresh_token_ttl_days <= 0 {
+            errors.push("jwt token lifetimes must be positive".to_string());
+        }
+
+        let base_url = &self.tracking.base_url;
+        if !base_url.starts_with("http://") && !base_url.starts_with("https://") {
+            errors.push(format!("tracking.base_url '{}' is not an http(s) URL", base_url));
+        } else if base_url.contains("://localhost") || base_url.contains("://127.0.0.1") {
+            insecure.push(format!("tracking.base_url '{}' is not reachable by email recipients", base_url));
+        }
+
+        if self.cors.permissive && self.run_mode.is_strict() {
+            errors.push("cors.permissive is for development and test only; list cors.allowed_origins".to_string());
+        }
+        if self.cors.allowed_origins.iter().any(|origin| origin.contains('*')) {
+            errors.push("cors.allowed_origins takes exact origins; set cors.permissive to allow any".to_string());
+        }
+
+        if !matches!(self.logging.format.as_str(), "text" | "json") {
+            errors.push(format!("logging.format '{}' is neither text nor json", self.logging.format));
+        }
+
+        if self.run_mode.is_strict() {
+            errors.append(&mut insecure);
+        }
+        if !errors.is_empty() {
+            return Err(ConfigError::Message(format!(
+                "Refusing to start in {:?} mode: {}",
+                self.run_mode,
+                errors.join("; ")
+            )));
+        }
+
+        Ok(insecure)
+    }
+
+    /// The configuration as JSON with secrets and passwords replaced
+    pub fn redacted(&self) -> serde_json::Value {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        redact(&mut value);
+        value
+    }
+}
+
+fn redact(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if REDACTED_KEYS.contains(&key.as_str()) && value.is_string() {
+                    *value = serde_json::Value::String("<redacted>".into());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::FileFormat;
+
+    fn base_config(run_mode: &str) -> Config {
+        ConfigLoader::builder()
+            .add_source(File::from_str(include_str!("../config/base.yaml"), FileFormat::Yaml))
+            .set_override("run_mode", run_mode)
+            .unwrap()
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_base_config_warns_in_development() {
+        let config = base_config("development");
+        assert_eq!(config.run_mode, RunMode::Development);
+
+        let warnings = config.validate().unwrap();
+        assert!(warnings.iter().any(|w| w.starts_with("jwt.secret")));
+        assert!(warnings.iter().any(|w| w.contains("root/root")));
+    }
+
+    #[test]
+    fn test_production_refuses_default_secrets() {
+        let mut config = base_config("production");
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("jwt.secret is the shipped default"));
+
+        config.jwt.secret = "a".repeat(MIN_SECRET_LEN);
+        config.tracking.secret = "b".repeat(MIN_SECRET_LEN);
+        config.events.checkin_secret = "c".repeat(MIN_SECRET_LEN);
+        config.database.surrealdb.username = "crm".into();
+        config.database.surrealdb.password = "d".repeat(MIN_SECRET_LEN);
+        config.tracking.base_url = "https://crm.example.com".into();
+        assert_eq!(config.validate().unwrap(), Vec::<String>::new());
+
+        config.jwt.secret = "short".into();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_placeholders_are_rejected_in_every_mode() {
+        let mut config = base_config("development");
+        config.database.surrealdb.password = "${SURREALDB_PASSWORD}".into();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_permissive_cors_only_outside_production() {
+        let mut config = base_config("development");
+        config.cors.permissive = true;
+        assert!(config.validate().is_ok());
+
+        config.run_mode = RunMode::Production;
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("cors.permissive"));
+    }
+
+    #[test]
+    fn test_unknown_log_format_is_rejected() {
+        let mut config = base_config("development");
+        config.logging.format = "json".into();
+        assert!(config.validate().is_ok());
+
+        config.logging.format = "logfmt".into();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_redacted_hides_secret_values_but_not_secret_names() {
+        let redacted = base_config("development").redacted();
 
-        config_loader.try_deserialize()
+        assert_eq!(redacted["jwt"]["secret"], "<redacted>");
+        assert_eq!(redacted["tracking"]["secret"], "<redacted>");
+        assert_eq!(redacted["database"]["surrealdb"]["password"], "<redacted>");
+        assert_eq!(redacted["database"]["surrealdb"]["username"], "root");
+        assert_eq!(redacted["cache"]["redis_url_secret"], "REDIS_URL");
+        assert_eq!(redacted["run_mode"], "development");
     }
 }
@@ -1,35 +1,541 @@
 use anyhow::Result;
-use surrealdb::engine::remote::http::{Client, Http};
+use futures::Stream;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::future::Future;
+use std::time::Duration;
+use surrealdb::engine::any::{self, Any};
+use surrealdb::method::Query;
 use surrealdb::opt::auth::Root;
-use surrealdb::Surreal;
-use crate::config::Config;
+use surrealdb::sql::{Id, Thing, Value};
+use surrealdb::{Notification, Surreal};
+use crate::config::{Config, DbRetryConfig, SurrealDbConfig};
 
+/// Connect timeout when the config doesn't set one, in seconds
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 30;
+
+/// Connections to SurrealDB
+///
+/// With the `remote` engine, queries go to a SurrealDB server over HTTP:
+/// each is its own request over reqwest's pool of keep-alive connections,
+/// so a dropped connection only fails the queries in flight; reads run
+/// through `retry` are sent again. Live queries need a WebSocket, which
+/// reconnects on its own, but live queries end with the connection and
+/// have to be started again; `ping_live` tells whether it still answers.
+///
+/// With the `rocksdb` engine (built with the `embedded` feature) the
+/// database runs inside this process on a data directory, and one
+/// connection serves both.
 pub struct Database {
-    pub client: Surreal<Client>,
+    pub client: Surreal<Any>,
+    /// Connection for live queries; HTTP cannot push notifications
+    pub live: Surreal<Any>,
+    query_timeout: Duration,
+    retry: DbRetryConfig,
 }
 
 impl Database {
+    /// Connect and sign in, retrying with backoff while the database is unreachable
     pub async fn new(config: &Config) -> Result<Self> {
         let db_config = &config.database.surrealdb;
+        match db_config.engine.as_str() {
+            "remote" => {}
+            "rocksdb" if cfg!(feature = "embedded") => {}
+            "rocksdb" => anyhow::bail!("The rocksdb engine needs the server built with the `embedded` feature"),
+            other => anyhow::bail!("Unknown database engine '{}'", other),
+        }
+
+        let retry = db_config.retry.clone();
+        let mut attempt = 0;
 
-        let client = Surreal::new::<Http>(&db_config.url).await?;
+        loop {
+            attempt += 1;
+            let error = match Self::connect(db_config).await {
+                Ok((client, live)) => {
+                    return Ok(Self {
+                        client,
+                        live,
+                        query_timeout: Duration::from_secs(db_config.query_timeout_secs),
+                        retry,
+                    });
+                }
+                Err(e) => e,
+            };
+
+            if attempt >= retry.connect_attempts {
+                return Err(error.context(format!(
+                    "Failed to open the {} database at {} after {} attempts",
+                    db_config.engine,
+                    location(db_config),
+                    attempt
+                )));
+            }
+            let delay = backoff_delay(attempt, &retry);
+            tracing::warn!(
+                "Failed to connect to SurrealDB: {}; attempt {} of {}, retrying in {:?}",
+                error,
+                attempt,
+                retry.connect_attempts,
+                delay
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    async fn connect(db_config: &SurrealDbConfig) -> Result<(Surreal<Any>, Surreal<Any>)> {
+        let timeout = Duration::from_secs(db_config.timeout.unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS));
+
+        tokio::time::timeout(timeout, Self::open_engine(db_config))
+            .await
+            .map_err(|_| anyhow::anyhow!("Connecting timed out after {:?}", timeout))?
+    }
 
-        client
-            .signin(Root {
-                username: &db_config.username,
-                password: &db_config.password,
+    /// The query connection and the live query connection for the configured engine
+    async fn open_engine(db_config: &SurrealDbConfig) -> Result<(Surreal<Any>, Surreal<Any>)> {
+        if db_config.engine == "rocksdb" {
+            // Nothing to sign in to: the embedded database only answers this process
+            let db = any::connect(format!("rocksdb://{}", db_config.data_dir)).await?;
+            db.use_ns(&db_config.namespace).use_db(&db_config.database).await?;
+            tracing::info!("Using the embedded database in {}", db_config.data_dir);
+            return Ok((db.clone(), db));
+        }
+
+        let client = Self::open_remote(db_config, "http").await?;
+        let live = Self::open_remote(db_config, "ws").await?;
+        Ok((client, live))
+    }
+
+    async fn open_remote(db_config: &SurrealDbConfig, scheme: &str) -> Result<Surreal<Any>> {
+        let db = any::connect(format!("{}://{}", scheme, db_config.url)).await?;
+
+        db.signin(Root {
+            username: &db_config.username,
+            password: &db_config.password,
+        })
+        .await?;
+        db.use_ns(&db_config.namespace).use_db(&db_config.database).await?;
+
+        Ok(db)
+    }
+
+    /// Run a trivial query, to check the database answers and our session still works
+    pub async fn ping(&self) -> Result<(), surrealdb::Error> {
+        self.timed(async {
+            self.client.query("RETURN true").await?.check()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Run a trivial query on the live query connection
+    pub async fn ping_live(&self) -> Result<(), surrealdb::Error> {
+        self.timed(async {
+            self.live.query("RETURN true").await?.check()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Run a read, retrying it after a dropped connection or a timeout
+    ///
+    /// Every attempt gets the configured query timeout. Only reads belong
+    /// here: a write whose response was lost may already have been applied.
+    pub async fn retry<T, F, Fut>(&self, mut query: F) -> Result<T, surrealdb::Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, surrealdb::Error>>,
+    {
+        let mut attempt = 0;
+
+        loop {
+            let error = match self.timed(query()).await {
+                Ok(value) => return Ok(value),
+                Err(e) if is_transient(&e) => e,
+                Err(e) => return Err(e),
+            };
+
+            if attempt >= self.retry.max_retries {
+                return Err(error);
+            }
+            attempt += 1;
+            let delay = backoff_delay(attempt, &self.retry);
+            tracing::warn!("{}; retry {} of {} in {:?}", error, attempt, self.retry.max_retries, delay);
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Fail a query that runs past the configured query timeout
+    async fn timed<T>(
+        &self,
+        query: impl Future<Output = Result<T, surrealdb::Error>>,
+    ) -> Result<T, surrealdb::Error> {
+        tokio::time::timeout(self.query_timeout, query)
+            .await
+            .unwrap_or_else(|_| Err(surrealdb::Error::Db(surrealdb::error::Db::QueryTimedout)))
+    }
+
+    /// Start collecting statements that must succeed or fail together
+    pub fn transaction(&self) -> Transaction<'_> {
+        Transaction {
+            query: self.client.query("BEGIN TRANSACTION"),
+            statements: 0,
+        }
+    }
+
+    /// `LIVE SELECT * FROM contact`, across every workspace
+    ///
+    /// The stream ends if the live query is killed or the connection drops;
+    /// callers are expected to start a new one.
+    pub async fn live_contacts(
+        &self,
+    ) -> Result<impl Stream<Item = Result<Notification<Value>, surrealdb::Error>>, surrealdb::Error> {
+        self.live.select::<Vec<Value>>("contact").live().await
+    }
+
+    /// Select a record by ID, but only if it belongs to the given workspace
+    ///
+    /// A record from another workspace is indistinguishable from a missing
+    /// one, and so is a record in the trash (`deleted_at` set).
+    pub async fn select_scoped<T: DeserializeOwned>(
+        &self,
+        table: &str,
+        id: &str,
+        workspace_id: &str,
+    ) -> Result<Option<T>, surrealdb::Error> {
+        let records: Vec<T> = self
+            .retry(move || async move {
+                self.client
+                    .query("SELECT * FROM $record WHERE workspace = $workspace AND deleted_at IS NONE")
+                    .bind(("record", Thing::from((table, id))))
+                    .bind(("workspace", workspace_thing(workspace_id)))
+                    .await?
+                    .take(0)
             })
             .await?;
 
-        client.use_ns(&db_config.namespace).use_db(&db_config.database).await?;
+        Ok(records.into_iter().next())
+    }
+
+    /// Delete a record by ID if it belongs to the given workspace
+    ///
+    /// Returns whether a record was actually deleted.
+    pub async fn delete_scoped(
+        &self,
+        table: &str,
+        id: &str,
+        workspace_id: &str,
+    ) -> Result<bool, surrealdb::Error> {
+        let deleted: Vec<serde_json::Value> = self
+            .client
+            .query("DELETE $record WHERE workspace = $workspace RETURN BEFORE")
+            .bind(("record", Thing::from((table, id))))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(!deleted.is_empty())
+    }
+
+    /// Move a record to the trash by stamping `deleted_at`
+    ///
+    /// Returns whether a live record was found and trashed.
+    pub async fn soft_delete_scoped(
+        &self,
+        table: &str,
+        id: &str,
+        workspace_id: &str,
+    ) -> Result<bool, surrealdb::Error> {
+        let trashed: Vec<serde_json::Value> = self
+            .client
+            .query(
+                "UPDATE $record SET deleted_at = time::now() \
+                 WHERE workspace = $workspace AND deleted_at IS NONE RETURN BEFORE",
+            )
+            .bind(("record", Thing::from((table, id))))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(!trashed.is_empty())
+    }
+
+    /// Take a record back out of the trash, returning it as restored
+    pub async fn restore_scoped<T: DeserializeOwned>(
+        &self,
+        table: &str,
+        id: &str,
+        workspace_id: &str,
+    ) -> Result<Option<T>, surrealdb::Error> {
+        let restored: Vec<T> = self
+            .client
+            .query(
+                "UPDATE $record SET deleted_at = NONE, updated_at = time::now() \
+                 WHERE workspace = $workspace AND deleted_at IS NOT NONE RETURN AFTER",
+            )
+            .bind(("record", Thing::from((table, id))))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(restored.into_iter().next())
+    }
+}
+
+/// Statements that commit together or not at all
+///
+/// The HTTP engine keeps no session between requests, so a transaction
+/// can't be held open across round trips. Instead the statements are
+/// collected here and sent as one `BEGIN TRANSACTION; ...; COMMIT
+/// TRANSACTION;` batch. Bind names are shared by every statement in the
+/// batch. Records that refer to each other should get their IDs up front
+/// from `new_thing`, and anything the caller needs back is read after
+/// `commit`.
+pub struct Transaction<'a> {
+    query: Query<'a, Any>,
+    statements: usize,
+}
+
+impl<'a> Transaction<'a> {
+    /// Add a statement to the batch
+    pub fn query(mut self, statement: impl Into<String>) -> Self {
+        self.query = self.query.query(statement.into());
+        self.statements += 1;
+        self
+    }
 
-        Ok(Self { client })
+    /// Bind a parameter for every statement in the batch
+    pub fn bind(mut self, binding: impl Serialize) -> Self {
+        self.query = self.query.bind(binding);
+        self
     }
 
-    pub async fn init_schema(&self) -> Result<()> {
-        let schema = include_str!("../schema/init.surql");
-        self.client.query(schema).await?;
-        tracing::info!("Database schema initialized");
+    pub fn is_empty(&self) -> bool {
+        self.statements == 0
+    }
+
+    /// Run the batch; if any statement fails, none of them take effect
+    pub async fn commit(self) -> Result<(), surrealdb::Error> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        self.query.query("COMMIT TRANSACTION").await?.check()?;
         Ok(())
     }
 }
+
+/// Where the configured database lives, for messages
+fn location(db_config: &SurrealDbConfig) -> &str {
+    if db_config.engine == "rocksdb" {
+        &db_config.data_dir
+    } else {
+        &db_config.url
+    }
+}
+
+/// A new random record ID, for records created inside a transaction
+pub fn new_thing(table: &str) -> Thing {
+    Thing::from((table, Id::rand()))
+}
+
+/// Record link for a workspace ID
+pub fn workspace_thing(workspace_id: &str) -> Thing {
+    Thing::from(("workspace", workspace_id))
+}
+
+/// Delay before the given retry (counting from 1): doubles each time, up to the configured maximum
+fn backoff_delay(retry: u32, config: &DbRetryConfig) -> Duration {
+    let factor = 2u64.saturating_pow(retry.saturating_sub(1));
+    Duration::from_millis(config.initial_backoff_ms.saturating_mul(factor).min(config.max_backoff_ms))
+}
+
+/// Whether a query failed on the way to or from the database, rather than in it
+///
+/// HTTP errors also cover error responses from the server; a read failing
+/// that way again only costs the backoff.
+fn is_transient(error: &surrealdb::Error) -> bool {
+    use surrealdb::error::{Api, Db};
+
+    matches!(
+        error,
+        surrealdb::Error::Api(Api::Http(_) | Api::Ws(_) | Api::ConnectionUninitialised)
+            | surrealdb::Error::Db(Db::QueryTimedout)
+    )
+}
+
+/// Whether a write failed because a UNIQUE index already holds the value
+///
+/// Remote engines only pass the server's message on, so that is matched too.
+pub fn is_unique_violation(error: &surrealdb::Error) -> bool {
+    use surrealdb::error::{Api, Db};
+
+    match error {
+        surrealdb::Error::Db(Db::IndexExists { .. }) => true,
+        surrealdb::Error::Api(Api::Query(message)) => message.contains("already contains"),
+        _ => false,
+    }
+}
+
+/// Whether a transaction was rolled back because a concurrent one wrote the same records
+///
+/// Each storage engine words this its own way, so the message is matched.
+pub fn is_write_conflict(error: &surrealdb::Error) -> bool {
+    let message = error.to_string().to_lowercase();
+    message.contains("conflict") || message.contains("resource busy")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_backoff_delay_doubles_up_to_the_maximum() {
+        let config = DbRetryConfig {
+            initial_backoff_ms: 200,
+            max_backoff_ms: 1000,
+            ..Default::default()
+        };
+
+        assert_eq!(backoff_delay(1, &config), Duration::from_millis(200));
+        assert_eq!(backoff_delay(2, &config), Duration::from_millis(400));
+        assert_eq!(backoff_delay(3, &config), Duration::from_millis(800));
+        assert_eq!(backoff_delay(4, &config), Duration::from_millis(1000));
+        assert_eq!(backoff_delay(64, &config), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_only_connection_errors_and_timeouts_are_transient() {
+        use surrealdb::error::{Api, Db};
+
+        assert!(is_transient(&surrealdb::Error::Api(Api::Http("connection refused".into()))));
+        assert!(is_transient(&surrealdb::Error::Api(Api::Ws("connection reset".into()))));
+        assert!(is_transient(&surrealdb::Error::Db(Db::QueryTimedout)));
+        assert!(!is_transient(&surrealdb::Error::Api(Api::Query("Parse error".into()))));
+        assert!(!is_transient(&surrealdb::Error::Db(Db::QueryCancelled)));
+    }
+
+    #[test]
+    fn test_unique_violation_is_recognised() {
+        use surrealdb::error::Api;
+
+        let remote = "Database index `segment_name` already contains [workspace:a, 'VIP'], with record `segment:b`";
+        assert!(is_unique_violation(&surrealdb::Error::Api(Api::Query(remote.into()))));
+        assert!(!is_unique_violation(&surrealdb::Error::Api(Api::Query("Parse error".into()))));
+    }
+
+    #[test]
+    fn test_write_conflict_is_recognised() {
+        use surrealdb::error::Api;
+
+        let rocksdb = "The query was not executed due to a failed transaction. \
+                       There was a problem with a datastore transaction: Resource busy: ";
+        assert!(is_write_conflict(&surrealdb::Error::Api(Api::Query(rocksdb.into()))));
+        let tikv = "The query was not executed due to a failed transaction. Write conflict";
+        assert!(is_write_conflict(&surrealdb::Error::Api(Api::Query(tikv.into()))));
+        assert!(!is_write_conflict(&surrealdb::Error::Api(Api::Query("Parse error".into()))));
+    }
+
+    /// A stand-in for SurrealDB's HTTP endpoint
+    ///
+    /// Records the SQL of every request and answers each statement of it,
+    /// leaving out BEGIN and COMMIT as the server does. With `fail` set it
+    /// answers like a transaction that was rolled back.
+    async fn fake_database(fail: bool) -> (Database, Arc<Mutex<Vec<String>>>) {
+        use axum::routing::{get, post};
+
+        #[derive(serde::Serialize)]
+        enum Status {
+            Ok,
+            Err,
+        }
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Arc::clone(&received);
+        let app = axum::Router::new()
+            .route("/health", get(|| async {}))
+            .route("/version", get(|| async { "surrealdb-1.5.6" }))
+            .route(
+                "/sql",
+                post(move |body: String| async move {
+                    let statements = body
+                        .split(';')
+                        .map(str::trim)
+                        .filter(|s| !s.is_empty() && !s.ends_with("TRANSACTION"))
+                        .count();
+                    recorder.lock().unwrap().push(body);
+
+                    let results: Vec<(String, Status, Value)> = (0..statements)
+                        .map(|_| match fail {
+                            false => ("1ms".to_string(), Status::Ok, Value::None),
+                            true => (
+                                "1ms".to_string(),
+                                Status::Err,
+                                Value::from("The query was not executed due to a failed transaction"),
+                            ),
+                        })
+                        .collect();
+                    surrealdb::sql::serde::serialize(&results).unwrap()
+                }),
+            );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = any::connect(format!("http://{}", address)).await.unwrap();
+        let database = Database {
+            live: client.clone(),
+            client,
+            query_timeout: Duration::from_secs(5),
+            retry: DbRetryConfig::default(),
+        };
+        (database, received)
+    }
+
+    #[tokio::test]
+    async fn test_transaction_commits_every_statement_in_one_batch() {
+        let (db, received) = fake_database(false).await;
+
+        db.transaction()
+            .query("CREATE contact:ada CONTENT { email: $email }")
+            .query("CREATE timeline_entry CONTENT { contact: contact:ada }")
+            .bind(("email", "ada@example.com"))
+            .commit()
+            .await
+            .unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        let batch = &received[0];
+        assert!(batch.starts_with("BEGIN TRANSACTION"));
+        assert!(batch.trim_end().trim_end_matches(';').ends_with("COMMIT TRANSACTION"));
+        assert!(batch.find("CREATE contact:ada").unwrap() < batch.find("CREATE timeline_entry").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_failed_transaction_is_an_error() {
+        let (db, _) = fake_database(true).await;
+
+        let result = db
+            .transaction()
+            .query("CREATE contact:ada CONTENT { email: 'ada@example.com' }")
+            .query("CREATE contact:ada CONTENT { email: 'ada@example.com' }")
+            .commit()
+            .await;
+
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("failed transaction"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_empty_transaction_sends_nothing() {
+        let (db, received) = fake_database(false).await;
+
+        let tx = db.transaction();
+        assert!(tx.is_empty());
+        tx.commit().await.unwrap();
+
+        assert!(received.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,128 @@
+//! Attachment Domain - files attached to contacts and timeline entries
+//!
+//! Uploads are checked against the configured size limit and allowed
+//! content types. Filenames are reduced to something safe to send back in
+//! a `Content-Disposition` header and to show in a list.
+
+use super::errors::{DomainError, DomainResult};
+
+/// Longest filename kept, in characters
+const MAX_FILENAME_LEN: usize = 200;
+
+/// Check an upload and return its cleaned-up filename and content type
+pub fn validate_upload(
+    filename: &str,
+    content_type: &str,
+    size: usize,
+    max_size: usize,
+    allowed_content_types: &[String],
+) -> DomainResult<(String, String)> {
+    let mut violations = Vec::new();
+
+    let filename = sanitize_filename(filename);
+    if filename.is_empty() {
+        violations.push(DomainError::RequiredFieldMissing {
+            field: "filename".to_string(),
+        });
+    }
+
+    // Parameters such as `; charset=utf-8` don't change what the file is
+    let content_type = content_type.split(';').next().unwrap_or_default().trim().to_lowercase();
+    if !allowed_content_types.iter().any(|t| t.eq_ignore_ascii_case(&content_type)) {
+        violations.push(DomainError::InvalidField {
+            field: "content_type".to_string(),
+            reason: format!("Files of type '{}' can't be attached", content_type),
+        });
+    }
+
+    if size == 0 {
+        violations.push(DomainError::InvalidField {
+            field: "file".to_string(),
+            reason: "File is empty".to_string(),
+        });
+    } else if size > max_size {
+        violations.push(DomainError::InvalidField {
+            field: "file".to_string(),
+            reason: format!("Must be at most {} bytes", max_size),
+        });
+    }
+
+    match DomainError::from_violations(violations) {
+        Some(error) => Err(error),
+        None => Ok((filename, content_type)),
+    }
+}
+
+/// The last path segment of a filename, without control characters,
+/// quotes or backslashes
+pub fn sanitize_filename(filename: &str) -> String {
+    let name = filename.rsplit(['/', '\\']).next().unwrap_or_default();
+    let name: String = name
+        .chars()
+        .filter(|c| !c.is_control() && *c != '"')
+        .take(MAX_FILENAME_LEN)
+        .collect();
+    name.trim().trim_start_matches('.').trim().to_string()
+}
+
+/// `Content-Disposition` for downloading a file under its original name
+///
+/// Header values must be ASCII, so non-ASCII names also go in the RFC 6266
+/// `filename*` parameter, percent-encoded, next to an ASCII fallback.
+pub fn content_disposition(filename: &str) -> String {
+    if filename.is_ascii() {
+        return format!("attachment; filename=\"{}\"", filename);
+    }
+
+    let fallback: String = filename.chars().map(|c| if c.is_ascii() { c } else { '_' }).collect();
+    let encoded: String = filename
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'.' | b'-' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect();
+    format!("attachment; filename=\"{}\"; filename*=UTF-8''{}", fallback, encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowed() -> Vec<String> {
+        vec!["application/pdf".to_string(), "image/png".to_string()]
+    }
+
+    #[test]
+    fn test_validate_upload() {
+        let (filename, content_type) =
+            validate_upload("proposal.pdf", "Application/PDF; name=x", 1024, 2048, &allowed()).unwrap();
+
+        assert_eq!(filename, "proposal.pdf");
+        assert_eq!(content_type, "application/pdf");
+    }
+
+    #[test]
+    fn test_validate_upload_reports_every_problem() {
+        let err = validate_upload("", "application/x-msdownload", 4096, 2048, &allowed()).unwrap_err();
+
+        assert_eq!(err.violations().len(), 3);
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(sanitize_filename("../../etc/passwd"), "passwd");
+        assert_eq!(sanitize_filename("C:\\decks\\Q3 \"final\".pptx"), "Q3 final.pptx");
+        assert_eq!(sanitize_filename(".env"), "env");
+        assert_eq!(sanitize_filename("   "), "");
+    }
+
+    #[test]
+    fn test_content_disposition() {
+        assert_eq!(content_disposition("deck.pdf"), "attachment; filename=\"deck.pdf\"");
+        assert_eq!(
+            content_disposition("Offert å.pdf"),
+            "attachment; filename=\"Offert _.pdf\"; filename*=UTF-8''Offert%20%C3%A5.pdf"
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! Audit - what changed on a record, field by field
+//!
+//! Every create, update and delete of a tenant record is logged with the
+//! actor and a list of field changes. The diffing here is pure: it works
+//! on JSON snapshots of the record before and after.
+//!
+//! - create: every field goes from null to its value
+//! - update: only the fields whose value actually changed
+//! - delete: every field goes from its value to null
+//! - restore: as create, for a record brought back from the trash
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+/// Kinds of record that are audited
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEntity {
+    Contact,
+    Company,
+    Campaign,
+    Event,
+    Deal,
+}
+
+impl AuditEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditEntity::Contact => "contact",
+            AuditEntity::Company => "company",
+            AuditEntity::Campaign => "campaign",
+            AuditEntity::Event => "event",
+            AuditEntity::Deal => "deal",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    /// Brought back out of the trash
+    Restore,
+}
+
+/// One field's value before and after a change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// Bookkeeping fields that are never worth reporting as a change
+const IGNORED_FIELDS: [&str; 4] = ["id", "workspace", "created_at", "updated_at"];
+
+/// Diff the given fields of two snapshots
+///
+/// Fields listed but unchanged (e.g. set to the value they already had)
+/// are left out.
+pub fn diff_fields(before: &Value, after: &Value, fields: &[String]) -> Vec<FieldChange> {
+    fields
+        .iter()
+        .filter_map(|field| change(field, before, after))
+        .collect()
+}
+
+/// Diff every top-level field of two snapshots
+///
+/// Either side may be `{}` (or null) for a create or delete.
+pub fn diff_all(before: &Value, after: &Value) -> Vec<FieldChange> {
+    let mut fields: Vec<&String> = before
+        .as_object()
+        .into_iter()
+        .chain(after.as_object())
+        .flat_map(|o| o.keys())
+        .filter(|k| !IGNORED_FIELDS.contains(&k.as_str()))
+        .collect();
+    fields.sort();
+    fields.dedup();
+
+    fields
+        .into_iter()
+        .filter_map(|field| change(field, before, after))
+        .collect()
+}
+
+fn change(field: &str, before: &Value, after: &Value) -> Option<FieldChange> {
+    let old = before.get(field).cloned().unwrap_or(Value::Null);
+    let new = after.get(field).cloned().unwrap_or(Value::Null);
+
+    (old != new).then(|| FieldChange {
+        field: field.to_string(),
+        old,
+        new,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_fields_skips_unchanged() {
+        let before = json!({ "email": "a@example.com", "phone": null });
+        let after = json!({ "email": "b@example.com", "phone": null });
+
+        let changes = diff_fields(&before, &after, &["email".into(), "phone".into()]);
+
+        assert_eq!(
+            changes,
+            vec![FieldChange {
+                field: "email".into(),
+                old: json!("a@example.com"),
+                new: json!("b@example.com"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_all_for_create() {
+        let after = json!({ "id": "x", "name": "Acme", "updated_at": "now", "tags": [] });
+
+        let changes = diff_all(&json!({}), &after);
+
+        let fields: Vec<&str> = changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["name", "tags"]);
+        assert_eq!(changes[0].old, Value::Null);
+    }
+
+    #[test]
+    fn test_diff_all_for_delete() {
+        let before = json!({ "name": "Acme" });
+
+        let changes = diff_all(&before, &Value::Null);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].new, Value::Null);
+    }
+
+    #[test]
+    fn test_diff_all_ignores_bookkeeping() {
+        let before = json!({ "name": "Acme", "updated_at": "t1" });
+        let after = json!({ "name": "Acme", "updated_at": "t2" });
+
+        assert!(diff_all(&before, &after).is_empty());
+    }
+}
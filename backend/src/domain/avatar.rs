@@ -0,0 +1,113 @@
+//! Avatar Domain - contact pictures
+//!
+//! An uploaded image is cropped to a square and resized to each of the
+//! standard sizes, so clients never download more than they show. Contacts
+//! without an uploaded avatar get the Gravatar registered for their email.
+
+use std::io::Cursor;
+
+use image::imageops::FilterType;
+use image::io::{Limits, Reader};
+use image::ImageOutputFormat;
+use sha2::{Digest, Sha256};
+
+use super::errors::{DomainError, DomainResult};
+
+/// Square sizes, in pixels, every avatar is stored at
+pub const AVATAR_SIZES: [u32; 3] = [64, 128, 256];
+
+/// Largest width or height of an image accepted for resizing
+const MAX_SOURCE_DIMENSION: u32 = 8000;
+
+/// The standard size to serve for a requested one: the smallest at least
+/// as large, or the largest there is
+pub fn avatar_size(requested: Option<u32>) -> u32 {
+    let largest = AVATAR_SIZES[AVATAR_SIZES.len() - 1];
+    match requested {
+        Some(requested) => AVATAR_SIZES.into_iter().find(|&s| s >= requested).unwrap_or(largest),
+        None => largest,
+    }
+}
+
+/// Gravatar for an email, at the largest standard size
+///
+/// Emails without a Gravatar get its generic silhouette rather than a 404.
+pub fn gravatar_url(email: &str) -> String {
+    let hash = hex::encode(Sha256::digest(email.trim().to_lowercase().as_bytes()));
+    format!(
+        "https://gravatar.com/avatar/{}?s={}&d=mp",
+        hash,
+        AVATAR_SIZES[AVATAR_SIZES.len() - 1]
+    )
+}
+
+/// Crop a PNG, JPEG, GIF or WebP image to a square and encode it as PNG at
+/// each standard size
+pub fn resize_avatar(data: &[u8]) -> DomainResult<Vec<(u32, Vec<u8>)>> {
+    let invalid = |reason: String| DomainError::InvalidField {
+        field: "file".to_string(),
+        reason,
+    };
+
+    let mut reader = Reader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|e| invalid(format!("Unreadable image: {}", e)))?;
+    if reader.format().is_none() {
+        return Err(invalid("Must be a PNG, JPEG, GIF or WebP image".to_string()));
+    }
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_SOURCE_DIMENSION);
+    limits.max_image_height = Some(MAX_SOURCE_DIMENSION);
+    reader.limits(limits);
+
+    let image = reader
+        .decode()
+        .map_err(|e| invalid(format!("Unreadable image: {}", e)))?;
+
+    AVATAR_SIZES
+        .into_iter()
+        .map(|size| {
+            let mut png = Vec::new();
+            image
+                .resize_to_fill(size, size, FilterType::Lanczos3)
+                .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+                .map_err(|e| invalid(format!("Failed to resize image: {}", e)))?;
+            Ok((size, png))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, GenericImageView, RgbImage};
+
+    #[test]
+    fn test_resize_avatar_crops_to_every_standard_size() {
+        let mut source = Vec::new();
+        DynamicImage::ImageRgb8(RgbImage::new(300, 200))
+            .write_to(&mut Cursor::new(&mut source), ImageOutputFormat::Png)
+            .unwrap();
+
+        let resized = resize_avatar(&source).unwrap();
+
+        assert_eq!(resized.iter().map(|(size, _)| *size).collect::<Vec<_>>(), AVATAR_SIZES);
+        for (size, png) in resized {
+            assert_eq!(image::load_from_memory(&png).unwrap().dimensions(), (size, size));
+        }
+        assert!(resize_avatar(b"%PDF-1.7 not an image").is_err());
+    }
+
+    #[test]
+    fn test_gravatar_url_and_sizes() {
+        assert_eq!(
+            gravatar_url("  Jane@Example.com "),
+            "https://gravatar.com/avatar/8c87b489ce35cf2e2f39f80e282cb2e804932a56a213983eeeb428407d43b52d?s=256&d=mp"
+        );
+
+        assert_eq!(avatar_size(None), 256);
+        assert_eq!(avatar_size(Some(32)), 64);
+        assert_eq!(avatar_size(Some(100)), 128);
+        assert_eq!(avatar_size(Some(1024)), 256);
+    }
+}
@@ -0,0 +1,123 @@
+//! Churn Domain - customers who stopped being customers
+//!
+//! Churning a customer moves them back to Lead and tags them `churned`, so
+//! they can be won back through the usual pipeline. The reason and the day
+//! they left are kept on their timeline, which the churn report reads.
+
+use chrono::{DateTime, Utc};
+
+use super::errors::{DomainError, DomainResult};
+
+/// Tag every churned contact carries
+pub const CHURNED_TAG: &str = "churned";
+
+/// Longest churn reason
+pub const MAX_CHURN_REASON_LEN: usize = 1000;
+
+/// Why and when a customer left
+#[derive(Debug, Clone, PartialEq)]
+pub struct Churn {
+    pub reason: String,
+    pub churned_at: DateTime<Utc>,
+}
+
+impl Churn {
+    /// A churn with a reason, on the given day or now
+    ///
+    /// The day may be in the past, to record a churn noticed late, but not
+    /// in the future, nor before `not_before`: when the contact was created
+    /// or last changed status.
+    pub fn new(
+        reason: &str,
+        churned_at: Option<DateTime<Utc>>,
+        not_before: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> DomainResult<Self> {
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(DomainError::InvalidField {
+                field: "reason".into(),
+                reason: "is required".into(),
+            });
+        }
+        if reason.chars().count() > MAX_CHURN_REASON_LEN {
+            return Err(DomainError::InvalidField {
+                field: "reason".into(),
+                reason: format!("must be at most {} characters", MAX_CHURN_REASON_LEN),
+            });
+        }
+
+        let churned_at = churned_at.unwrap_or(now);
+        if churned_at > now {
+            return Err(DomainError::InvalidField {
+                field: "churned_at".into(),
+                reason: "must not be in the future".into(),
+            });
+        }
+        if churned_at < not_before {
+            return Err(DomainError::InvalidField {
+                field: "churned_at".into(),
+                reason: "must not be before the contact was created or last changed status".into(),
+            });
+        }
+
+        Ok(Self {
+            reason: reason.to_string(),
+            churned_at,
+        })
+    }
+}
+
+/// Customers at the start of a period, from those at its end and the moves
+/// during it
+pub fn customers_at_start(customers_now: u64, churned: u64, new_customers: u64) -> u64 {
+    (customers_now + churned).saturating_sub(new_customers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_churn_needs_a_reason_and_a_past_date() {
+        let now = Utc::now();
+        let last_month = now - Duration::days(30);
+
+        let churn = Churn::new("  Moved to a competitor ", None, last_month, now).unwrap();
+        assert_eq!(churn.reason, "Moved to a competitor");
+        assert_eq!(churn.churned_at, now);
+
+        let last_week = now - Duration::days(7);
+        assert_eq!(Churn::new("Budget cut", Some(last_week), last_month, now).unwrap().churned_at, last_week);
+
+        assert_eq!(Churn::new(" ", None, last_month, now).unwrap_err().field(), Some("reason"));
+        let long = "x".repeat(MAX_CHURN_REASON_LEN + 1);
+        assert_eq!(Churn::new(&long, None, last_month, now).unwrap_err().field(), Some("reason"));
+        let tomorrow = now + Duration::days(1);
+        assert_eq!(
+            Churn::new("Budget cut", Some(tomorrow), last_month, now).unwrap_err().field(),
+            Some("churned_at")
+        );
+    }
+
+    #[test]
+    fn test_churn_is_not_dated_before_the_last_status_change() {
+        let now = Utc::now();
+        let became_customer = now - Duration::days(7);
+
+        let before = became_customer - Duration::days(1);
+        assert_eq!(
+            Churn::new("Budget cut", Some(before), became_customer, now).unwrap_err().field(),
+            Some("churned_at")
+        );
+        assert!(Churn::new("Budget cut", Some(became_customer), became_customer, now).is_ok());
+    }
+
+    #[test]
+    fn test_customers_at_start() {
+        // 40 now, 10 left and 15 joined during the period: 35 at its start
+        assert_eq!(customers_at_start(40, 10, 15), 35);
+        assert_eq!(customers_at_start(0, 0, 3), 0);
+    }
+}
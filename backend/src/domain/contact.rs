@@ -3,18 +3,21 @@
 //! This module defines:
 //! 1. The Contact entity (what data it holds)
 //! 2. Contact status and transitions (state machine)
-//! 3. Factory functions for creating valid contacts
-//! 4. Business rules for contact operations
+//! 3. Email subscription status (who may receive campaign email)
+//! 4. Factory functions for creating valid contacts
+//! 5. Business rules for contact operations
 //!
 //! IMPORTANT: No database code here. No IDs from storage.
 //! This is the IDEAL contact as the business sees it.
 
 use super::errors::{DomainError, DomainResult};
 use super::validation::{
-    validate_email, validate_linkedin_url, validate_name, validate_phone, validate_tags,
+    validate_country, validate_custom_field, validate_custom_field_count, validate_email, validate_linkedin_url,
+    validate_name, validate_phone, validate_place, validate_tag, validate_tags, validate_timezone, MAX_TAGS,
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 // ============================================================================
 // Contact Status - A State Machine
@@ -63,6 +66,15 @@ pub enum ContactStatus {
 }
 
 impl ContactStatus {
+    /// Every status, in the order the pipeline board shows them
+    pub const ALL: [ContactStatus; 5] = [
+        ContactStatus::Lead,
+        ContactStatus::Customer,
+        ContactStatus::Partner,
+        ContactStatus::Investor,
+        ContactStatus::Other,
+    ];
+
     /// Check if a status transition is valid
     ///
     /// # Business Rules:
@@ -124,6 +136,16 @@ impl ContactStatus {
             _ => "This status transition is not allowed by business rules",
         }
     }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContactStatus::Lead => "lead",
+            ContactStatus::Customer => "customer",
+            ContactStatus::Partner => "partner",
+            ContactStatus::Investor => "investor",
+            ContactStatus::Other => "other",
+        }
+    }
 }
 
 impl Default for ContactStatus {
@@ -144,6 +166,54 @@ impl std::fmt::Display for ContactStatus {
     }
 }
 
+// ============================================================================
+// Subscription Status - Email consent
+// ============================================================================
+
+/// Whether a contact wants to receive campaign email
+///
+/// Independent of the lifecycle status: a customer can unsubscribe from
+/// marketing without stopping being a customer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionStatus {
+    #[default]
+    Subscribed,
+    Unsubscribed,
+    /// The address bounced for good, as reported by the email provider
+    Bounced,
+    /// The contact marked our email as spam
+    Complained,
+}
+
+impl SubscriptionStatus {
+    /// Business rule: suppressed contacts never get campaign email and are
+    /// left out when a campaign segment is resolved
+    pub fn is_suppressed(&self) -> bool {
+        *self != SubscriptionStatus::Subscribed
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubscriptionStatus::Subscribed => "subscribed",
+            SubscriptionStatus::Unsubscribed => "unsubscribed",
+            SubscriptionStatus::Bounced => "bounced",
+            SubscriptionStatus::Complained => "complained",
+        }
+    }
+}
+
+impl std::fmt::Display for SubscriptionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubscriptionStatus::Subscribed => write!(f, "Subscribed"),
+            SubscriptionStatus::Unsubscribed => write!(f, "Unsubscribed"),
+            SubscriptionStatus::Bounced => write!(f, "Bounced"),
+            SubscriptionStatus::Complained => write!(f, "Complained"),
+        }
+    }
+}
+
 // ============================================================================
 // Contact Entity
 // ============================================================================
@@ -162,16 +232,40 @@ pub struct Contact {
     // Optional fields
     pub phone: Option<String>,
     pub linkedin_url: Option<String>,
+    /// IANA timezone, used to send campaign email at a sensible local time
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// ISO 3166-1 alpha-2 code, upper case
+    #[serde(default)]
+    pub country: Option<String>,
+    /// State, county or sales territory
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub city: Option<String>,
+    /// Uploaded picture; contacts without one show their Gravatar
+    #[serde(default)]
+    pub avatar_url: Option<String>,
+    /// Manual order on the pipeline board; `None` until first moved there
+    #[serde(default)]
+    pub pipeline_position: Option<f64>,
 
     // Classification
     pub tags: Vec<String>,
     pub status: ContactStatus,
+    pub subscription_status: SubscriptionStatus,
+
+    /// Workspace-defined data, e.g. `plan: "pro"`; usable as merge variables
+    #[serde(default)]
+    pub custom_fields: BTreeMap<String, String>,
 
     // Metrics
     pub engagement_score: f64,
 
     // Relationships (IDs, resolved by repository layer)
     pub company_id: Option<String>,
+    /// Workspace user responsible for the contact
+    pub owner_id: Option<String>,
 
     // Audit
     pub created_at: DateTime<Utc>,
@@ -204,6 +298,24 @@ impl Contact {
         self.status == ContactStatus::Customer && self.engagement_score < 30.0
     }
 
+    /// Check if the contact may be sent campaign email
+    pub fn can_receive_campaign_email(&self) -> bool {
+        !self.subscription_status.is_suppressed()
+    }
+
+    /// Opt the contact out of campaign email
+    ///
+    /// Returns false if they were already unsubscribed.
+    pub fn unsubscribe(&mut self) -> bool {
+        if self.subscription_status.is_suppressed() {
+            return false;
+        }
+
+        self.subscription_status = SubscriptionStatus::Unsubscribed;
+        self.updated_at = Utc::now();
+        true
+    }
+
     /// Attempt to transition to a new status
     pub fn transition_status(&mut self, new_status: ContactStatus) -> DomainResult<()> {
         if !self.status.can_transition_to(new_status) {
@@ -224,6 +336,12 @@ impl Contact {
         let validated = super::validation::validate_tag(tag)?;
 
         if !self.tags.contains(&validated) {
+            if self.tags.len() >= MAX_TAGS {
+                return Err(DomainError::InvalidField {
+                    field: "tags".to_string(),
+                    reason: format!("At most {} tags are allowed", MAX_TAGS),
+                });
+            }
             self.tags.push(validated);
             self.updated_at = Utc::now();
         }
@@ -290,9 +408,14 @@ pub struct ContactBuilder {
     email: Option<String>,
     phone: Option<String>,
     linkedin_url: Option<String>,
+    timezone: Option<String>,
+    country: Option<String>,
+    region: Option<String>,
+    city: Option<String>,
     tags: Vec<String>,
     status: ContactStatus,
     company_id: Option<String>,
+    custom_fields: Vec<(String, String)>,
 }
 
 impl ContactBuilder {
@@ -331,6 +454,38 @@ impl ContactBuilder {
         self
     }
 
+    pub fn timezone(mut self, timezone: &str) -> Self {
+        let trimmed = timezone.trim();
+        if !trimmed.is_empty() {
+            self.timezone = Some(trimmed.to_string());
+        }
+        self
+    }
+
+    pub fn country(mut self, country: &str) -> Self {
+        let trimmed = country.trim();
+        if !trimmed.is_empty() {
+            self.country = Some(trimmed.to_ascii_uppercase());
+        }
+        self
+    }
+
+    pub fn region(mut self, region: &str) -> Self {
+        let trimmed = region.trim();
+        if !trimmed.is_empty() {
+            self.region = Some(trimmed.to_string());
+        }
+        self
+    }
+
+    pub fn city(mut self, city: &str) -> Self {
+        let trimmed = city.trim();
+        if !trimmed.is_empty() {
+            self.city = Some(trimmed.to_string());
+        }
+        self
+    }
+
     pub fn tag(mut self, tag: &str) -> Self {
         self.tags.push(tag.to_string());
         self
@@ -351,29 +506,79 @@ impl ContactBuilder {
         self
     }
 
+    /// Set a custom field; empty values are left out
+    pub fn custom_field(mut self, key: &str, value: &str) -> Self {
+        self.custom_fields.push((key.to_string(), value.trim().to_string()));
+        self
+    }
+
     /// Build the Contact, validating all fields
+    ///
+    /// Every field is checked; when more than one is bad the error is
+    /// `DomainError::Violations` listing each of them.
     pub fn build(self) -> DomainResult<Contact> {
+        let mut violations = Vec::new();
+
         // Validate required fields
-        let first_name = self.first_name.ok_or_else(|| DomainError::RequiredFieldMissing {
-            field: "first_name".to_string(),
-        })?;
-        validate_name(&first_name, "first_name")?;
-
-        let last_name = self.last_name.ok_or_else(|| DomainError::RequiredFieldMissing {
-            field: "last_name".to_string(),
-        })?;
-        validate_name(&last_name, "last_name")?;
-
-        let email = self.email.ok_or_else(|| DomainError::RequiredFieldMissing {
-            field: "email".to_string(),
-        })?;
-        validate_email(&email)?;
+        let first_name = self.first_name.unwrap_or_default();
+        if let Err(e) = validate_name(&first_name, "first_name") {
+            violations.push(e);
+        }
+
+        let last_name = self.last_name.unwrap_or_default();
+        if let Err(e) = validate_name(&last_name, "last_name") {
+            violations.push(e);
+        }
+
+        let email = self.email.unwrap_or_default();
+        if let Err(e) = validate_email(&email) {
+            violations.push(e);
+        }
 
         // Validate optional fields
-        validate_phone(self.phone.as_deref())?;
-        validate_linkedin_url(self.linkedin_url.as_deref())?;
+        if let Err(e) = validate_phone(self.phone.as_deref()) {
+            violations.push(e);
+        }
+        if let Err(e) = validate_linkedin_url(self.linkedin_url.as_deref()) {
+            violations.push(e);
+        }
+        if let Err(e) = validate_timezone(self.timezone.as_deref()) {
+            violations.push(e);
+        }
+        if let Err(e) = validate_country(self.country.as_deref()) {
+            violations.push(e);
+        }
+        if let Err(e) = validate_place(self.region.as_deref(), "region") {
+            violations.push(e);
+        }
+        if let Err(e) = validate_place(self.city.as_deref(), "city") {
+            violations.push(e);
+        }
+
+        // Validate and normalize tags, reporting each bad tag by position
+        for (i, tag) in self.tags.iter().enumerate() {
+            if let Err(e) = validate_tag(tag) {
+                violations.push(e.at(format!("tags[{}]", i)));
+            }
+        }
+
+        let mut custom_fields = BTreeMap::new();
+        for (key, value) in &self.custom_fields {
+            match validate_custom_field(key, value) {
+                Ok(key) if !value.is_empty() => {
+                    custom_fields.insert(key, value.clone());
+                }
+                Ok(_) => {}
+                Err(e) => violations.push(e.at(format!("custom_fields.{}", key.trim()))),
+            }
+        }
+        if let Err(e) = validate_custom_field_count(custom_fields.len()) {
+            violations.push(e);
+        }
 
-        // Validate and normalize tags
+        if let Some(error) = DomainError::from_violations(violations) {
+            return Err(error);
+        }
         let tags = validate_tags(&self.tags)?;
 
         let now = Utc::now();
@@ -384,10 +589,19 @@ impl ContactBuilder {
             email,
             phone: self.phone,
             linkedin_url: self.linkedin_url,
+            timezone: self.timezone,
+            country: self.country,
+            region: self.region,
+            city: self.city,
+            avatar_url: None,
+            pipeline_position: None,
             tags,
             status: self.status,
+            subscription_status: SubscriptionStatus::Subscribed,
+            custom_fields,
             engagement_score: 0.0, // New contacts start at 0
             company_id: self.company_id,
+            owner_id: None,
             created_at: now,
             updated_at: now,
         })
@@ -395,19 +609,20 @@ impl ContactBuilder {
 }
 
 // ============================================================================
-// YOUR TURN: Implement ContactUpdater
+// Contact Updater - The safe way to change contacts
 // ============================================================================
 
 /// Updater for modifying existing contacts
 ///
 /// Unlike ContactBuilder, this takes an existing contact and applies
-/// partial updates to it.
+/// partial updates to it. Every setter validates its input, and the names
+/// of the fields that actually changed are collected for audit logging.
 ///
 /// # Example
 /// ```
 /// let updated = ContactUpdater::new(existing_contact)
-///     .email("new@example.com")
-///     .add_tag("priority")
+///     .email("new@example.com")?
+///     .add_tag("priority")?
 ///     .apply()?;
 /// ```
 pub struct ContactUpdater {
@@ -424,48 +639,227 @@ impl ContactUpdater {
         }
     }
 
+    pub fn first_name(mut self, name: &str) -> DomainResult<Self> {
+        let name = name.trim();
+        validate_name(name, "first_name")?;
+        if self.contact.first_name != name {
+            self.contact.first_name = name.to_string();
+            self.touch("first_name");
+        }
+        Ok(self)
+    }
+
+    pub fn last_name(mut self, name: &str) -> DomainResult<Self> {
+        let name = name.trim();
+        validate_name(name, "last_name")?;
+        if self.contact.last_name != name {
+            self.contact.last_name = name.to_string();
+            self.touch("last_name");
+        }
+        Ok(self)
+    }
+
     /// Update email address
     ///
-    /// YOUR IMPLEMENTATION:
+    /// Uniqueness is not checked here - that needs the database.
     pub fn email(mut self, email: &str) -> DomainResult<Self> {
-        // TODO: Implement this
-        //
-        // Steps:
-        // 1. Validate the new email
-        // 2. Update self.contact.email
-        // 3. Add "email" to modified_fields
-        // 4. Update updated_at timestamp
-        // 5. Return self for chaining
-
-        todo!("Implement email update")
+        let email = email.trim().to_lowercase();
+        validate_email(&email)?;
+        if self.contact.email != email {
+            self.contact.email = email;
+            self.touch("email");
+        }
+        Ok(self)
     }
 
-    /// Update phone number
-    ///
-    /// YOUR IMPLEMENTATION:
+    /// Update phone number; `None` or an empty string clears it
     pub fn phone(mut self, phone: Option<&str>) -> DomainResult<Self> {
-        // TODO: Implement this
+        let phone = phone.map(str::trim).filter(|p| !p.is_empty());
+        validate_phone(phone)?;
+        if self.contact.phone.as_deref() != phone {
+            self.contact.phone = phone.map(str::to_string);
+            self.touch("phone");
+        }
+        Ok(self)
+    }
+
+    /// Update LinkedIn URL; `None` or an empty string clears it
+    pub fn linkedin_url(mut self, url: Option<&str>) -> DomainResult<Self> {
+        let url = url.map(str::trim).filter(|u| !u.is_empty());
+        validate_linkedin_url(url)?;
+        if self.contact.linkedin_url.as_deref() != url {
+            self.contact.linkedin_url = url.map(str::to_string);
+            self.touch("linkedin_url");
+        }
+        Ok(self)
+    }
+
+    /// Update timezone; `None` or an empty string clears it
+    pub fn timezone(mut self, timezone: Option<&str>) -> DomainResult<Self> {
+        let timezone = timezone.map(str::trim).filter(|t| !t.is_empty());
+        validate_timezone(timezone)?;
+        if self.contact.timezone.as_deref() != timezone {
+            self.contact.timezone = timezone.map(str::to_string);
+            self.touch("timezone");
+        }
+        Ok(self)
+    }
+
+    /// Update country; `None` or an empty string clears it
+    pub fn country(mut self, country: Option<&str>) -> DomainResult<Self> {
+        let country = country.map(str::trim).filter(|c| !c.is_empty()).map(str::to_ascii_uppercase);
+        validate_country(country.as_deref())?;
+        if self.contact.country != country {
+            self.contact.country = country;
+            self.touch("country");
+        }
+        Ok(self)
+    }
+
+    /// Update region; `None` or an empty string clears it
+    pub fn region(mut self, region: Option<&str>) -> DomainResult<Self> {
+        let region = region.map(str::trim).filter(|r| !r.is_empty());
+        validate_place(region, "region")?;
+        if self.contact.region.as_deref() != region {
+            self.contact.region = region.map(str::to_string);
+            self.touch("region");
+        }
+        Ok(self)
+    }
 
-        todo!("Implement phone update")
+    /// Update city; `None` or an empty string clears it
+    pub fn city(mut self, city: Option<&str>) -> DomainResult<Self> {
+        let city = city.map(str::trim).filter(|c| !c.is_empty());
+        validate_place(city, "city")?;
+        if self.contact.city.as_deref() != city {
+            self.contact.city = city.map(str::to_string);
+            self.touch("city");
+        }
+        Ok(self)
+    }
+
+    /// Place the contact on the pipeline board
+    pub fn pipeline_position(mut self, position: f64) -> Self {
+        if self.contact.pipeline_position != Some(position) {
+            self.contact.pipeline_position = Some(position);
+            self.touch("pipeline_position");
+        }
+        self
+    }
+
+    /// Set or clear the uploaded avatar
+    pub fn avatar_url(mut self, url: Option<&str>) -> Self {
+        if self.contact.avatar_url.as_deref() != url {
+            self.contact.avatar_url = url.map(str::to_string);
+            self.touch("avatar_url");
+        }
+        self
     }
 
     /// Add a tag
-    ///
-    /// YOUR IMPLEMENTATION:
     pub fn add_tag(mut self, tag: &str) -> DomainResult<Self> {
-        // TODO: Implement this
-        // Hint: Use self.contact.add_tag()
+        let before = self.contact.tags.len();
+        self.contact.add_tag(tag)?;
+        if self.contact.tags.len() != before {
+            self.touch("tags");
+        }
+        Ok(self)
+    }
 
-        todo!("Implement add_tag")
+    /// Replace all tags
+    pub fn tags(mut self, tags: &[String]) -> DomainResult<Self> {
+        let tags = validate_tags(tags)?;
+        if self.contact.tags != tags {
+            self.contact.tags = tags;
+            self.touch("tags");
+        }
+        Ok(self)
     }
 
     /// Change status
-    ///
     pub fn status(mut self, new_status: ContactStatus) -> DomainResult<Self> {
-        // TODO: Implement this
-        // Hint: Use self.contact.transition_status()
+        if self.contact.status != new_status {
+            self.contact.transition_status(new_status)?;
+            self.touch("status");
+        }
+        Ok(self)
+    }
+
+    pub fn engagement_score(mut self, score: f64) -> DomainResult<Self> {
+        let before = self.contact.engagement_score;
+        self.contact.update_engagement(score)?;
+        if self.contact.engagement_score != before {
+            self.touch("engagement_score");
+        }
+        Ok(self)
+    }
+
+    /// Update the company; `None` or an empty string detaches it
+    pub fn company_id(mut self, company_id: Option<&str>) -> Self {
+        let company_id = company_id.filter(|id| !id.is_empty());
+        if self.contact.company_id.as_deref() != company_id {
+            self.contact.company_id = company_id.map(str::to_string);
+            self.touch("company_id");
+        }
+        self
+    }
 
-        todo!("Implement status change")
+    /// Set or clear custom fields; `None` or an empty value removes the field,
+    /// fields not mentioned are kept
+    pub fn custom_fields(mut self, changes: &BTreeMap<String, Option<String>>) -> DomainResult<Self> {
+        let mut fields = self.contact.custom_fields.clone();
+
+        for (key, value) in changes {
+            let value = value.as_deref().map(str::trim).unwrap_or_default();
+            let key = validate_custom_field(key, value)?;
+            if value.is_empty() {
+                fields.remove(&key);
+            } else {
+                fields.insert(key, value.to_string());
+            }
+        }
+
+        validate_custom_field_count(fields.len())?;
+
+        if self.contact.custom_fields != fields {
+            self.contact.custom_fields = fields;
+            self.touch("custom_fields");
+        }
+        Ok(self)
+    }
+
+    /// Remove a tag; removing one the contact doesn't carry is a no-op
+    pub fn remove_tag(mut self, tag: &str) -> Self {
+        if self.contact.remove_tag(tag) {
+            self.touch("tags");
+        }
+        self
+    }
+
+    /// Churn a customer: back to Lead, tagged `churned`
+    pub fn churn(self) -> DomainResult<Self> {
+        if self.contact.status != ContactStatus::Customer {
+            return Err(DomainError::InvalidStateTransition {
+                from: self.contact.status.to_string(),
+                to: ContactStatus::Lead.to_string(),
+                reason: "Only customers can churn".into(),
+            });
+        }
+
+        self.status(ContactStatus::Lead)?.add_tag(super::churn::CHURNED_TAG)
+    }
+
+    /// Hand the contact to another user; `None` or an empty string clears the owner
+    ///
+    /// Whether the user belongs to the workspace is not checked here - that
+    /// needs the database.
+    pub fn owner_id(mut self, owner_id: Option<&str>) -> Self {
+        let owner_id = owner_id.filter(|id| !id.is_empty());
+        if self.contact.owner_id.as_deref() != owner_id {
+            self.contact.owner_id = owner_id.map(str::to_string);
+            self.touch("owner_id");
+        }
+        self
     }
 
     /// Apply all changes and return the updated contact
@@ -478,6 +872,13 @@ impl ContactUpdater {
     pub fn modified_fields(&self) -> &[String] {
         &self.modified_fields
     }
+
+    fn touch(&mut self, field: &str) {
+        if !self.modified_fields.iter().any(|f| f == field) {
+            self.modified_fields.push(field.to_string());
+        }
+        self.contact.updated_at = Utc::now();
+    }
 }
 
 // ============================================================================
@@ -552,6 +953,32 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_build_reports_every_violation() {
+        let err = ContactBuilder::new()
+            .first_name("John")
+            .email("invalid-email")
+            .tag("ok")
+            .tag("not ok!")
+            .build()
+            .unwrap_err();
+
+        let fields: Vec<&str> = err.violations().iter().filter_map(|e| e.field()).collect();
+        assert_eq!(fields, vec!["last_name", "email", "tags[1]"]);
+        assert!(matches!(err, DomainError::Violations(_)));
+    }
+
+    #[test]
+    fn test_build_single_violation_is_not_wrapped() {
+        let err = ContactBuilder::new()
+            .first_name("John")
+            .last_name("Doe")
+            .build()
+            .unwrap_err();
+
+        assert_eq!(err, DomainError::RequiredFieldMissing { field: "email".into() });
+    }
+
     // ---- Status Transition Tests ----
 
     #[test]
@@ -695,10 +1122,47 @@ mod tests {
         assert_eq!(contact.engagement_score, 0.0);
     }
 
+    // ---- Subscription Tests ----
+
+    #[test]
+    fn test_new_contact_is_subscribed() {
+        let contact = ContactBuilder::new()
+            .first_name("John")
+            .last_name("Doe")
+            .email("john@example.com")
+            .build()
+            .unwrap();
+
+        assert_eq!(contact.subscription_status, SubscriptionStatus::Subscribed);
+        assert!(contact.can_receive_campaign_email());
+    }
+
+    #[test]
+    fn test_unsubscribe_suppresses_campaign_email() {
+        let mut contact = ContactBuilder::new()
+            .first_name("John")
+            .last_name("Doe")
+            .email("john@example.com")
+            .build()
+            .unwrap();
+
+        assert!(contact.unsubscribe());
+        assert!(!contact.can_receive_campaign_email());
+
+        // Unsubscribing twice is a no-op
+        assert!(!contact.unsubscribe());
+    }
+
+    #[test]
+    fn test_bounced_and_complained_are_suppressed() {
+        assert!(!SubscriptionStatus::Subscribed.is_suppressed());
+        assert!(SubscriptionStatus::Bounced.is_suppressed());
+        assert!(SubscriptionStatus::Complained.is_suppressed());
+    }
+
     // ---- YOUR TESTS: ContactUpdater ----
 
     #[test]
-    #[ignore] // Remove this line after implementing ContactUpdater
     fn test_contact_updater_email() {
         let contact = ContactBuilder::new()
             .first_name("John")
@@ -717,7 +1181,6 @@ mod tests {
     }
 
     #[test]
-    #[ignore] // Remove this line after implementing ContactUpdater
     fn test_contact_updater_status_transition() {
         let contact = ContactBuilder::new()
             .first_name("John")
@@ -734,4 +1197,125 @@ mod tests {
 
         assert_eq!(updated.status, ContactStatus::Customer);
     }
+
+    #[test]
+    fn test_contact_updater_tracks_modified_fields() {
+        let contact = ContactBuilder::new()
+            .first_name("John")
+            .last_name("Doe")
+            .email("john@example.com")
+            .build()
+            .unwrap();
+
+        let updater = ContactUpdater::new(contact)
+            .first_name("John") // unchanged
+            .unwrap()
+            .email("johnny@example.com")
+            .unwrap()
+            .add_tag("vip")
+            .unwrap()
+            .add_tag("VIP") // duplicate
+            .unwrap();
+
+        assert_eq!(updater.modified_fields(), ["email", "tags"]);
+    }
+
+    #[test]
+    fn test_contact_updater_rejects_invalid_input() {
+        let contact = ContactBuilder::new()
+            .first_name("John")
+            .last_name("Doe")
+            .email("john@example.com")
+            .build()
+            .unwrap();
+
+        assert!(ContactUpdater::new(contact).email("not-an-email").is_err());
+    }
+
+    #[test]
+    fn test_location_is_normalized_and_checked() {
+        let contact = ContactBuilder::new()
+            .first_name("Ada")
+            .last_name("Berg")
+            .email("ada@example.com")
+            .country(" se ")
+            .region("Stockholm County")
+            .city(" ")
+            .build()
+            .unwrap();
+        assert_eq!(contact.country.as_deref(), Some("SE"));
+        assert_eq!(contact.region.as_deref(), Some("Stockholm County"));
+        assert_eq!(contact.city, None);
+
+        let updater = ContactUpdater::new(contact.clone())
+            .country(Some("SE"))
+            .unwrap()
+            .city(Some("Stockholm"))
+            .unwrap()
+            .region(Some(""))
+            .unwrap();
+        assert_eq!(updater.modified_fields(), ["city", "region"]);
+
+        assert!(ContactUpdater::new(contact).country(Some("Sweden")).is_err());
+    }
+
+    #[test]
+    fn test_only_customers_churn() {
+        let customer = ContactBuilder::new()
+            .first_name("John")
+            .last_name("Doe")
+            .email("john@example.com")
+            .status(ContactStatus::Customer)
+            .build()
+            .unwrap();
+
+        let updater = ContactUpdater::new(customer.clone()).churn().unwrap();
+        assert_eq!(updater.modified_fields(), ["status", "tags"]);
+        let churned = updater.apply().unwrap();
+        assert_eq!(churned.status, ContactStatus::Lead);
+        assert_eq!(churned.tags, vec![crate::domain::churn::CHURNED_TAG]);
+
+        // A churned lead can't churn again
+        assert!(matches!(
+            ContactUpdater::new(churned).churn(),
+            Err(DomainError::InvalidStateTransition { .. })
+        ));
+    }
+
+    #[test]
+    fn test_custom_fields_are_normalized_and_merged() {
+        let contact = ContactBuilder::new()
+            .first_name("John")
+            .last_name("Doe")
+            .email("john@example.com")
+            .custom_field("Plan", "pro")
+            .custom_field("region", "  ")
+            .build()
+            .unwrap();
+        assert_eq!(contact.custom_fields.get("plan").map(String::as_str), Some("pro"));
+        assert!(!contact.custom_fields.contains_key("region"));
+
+        let changes = BTreeMap::from([
+            ("plan".to_string(), None),
+            ("seats".to_string(), Some("12".to_string())),
+        ]);
+        let updater = ContactUpdater::new(contact).custom_fields(&changes).unwrap();
+        assert_eq!(updater.modified_fields(), ["custom_fields"]);
+
+        let updated = updater.apply().unwrap();
+        assert_eq!(updated.custom_fields, BTreeMap::from([("seats".to_string(), "12".to_string())]));
+    }
+
+    #[test]
+    fn test_build_rejects_bad_custom_field_names() {
+        let err = ContactBuilder::new()
+            .first_name("John")
+            .last_name("Doe")
+            .email("john@example.com")
+            .custom_field("plan tier", "pro")
+            .build()
+            .unwrap_err();
+
+        assert_eq!(err.field(), Some("custom_fields.plan tier"));
+    }
 }
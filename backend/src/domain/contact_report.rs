@@ -0,0 +1,365 @@
+//! Contact Report - a printable one-pager about a contact
+//!
+//! Meant to be shared before a board meeting or when handing a contact
+//! over: their profile, engagement with a weekly activity chart, the
+//! timeline entries worth knowing about, and the notes kept on them. The
+//! report is a standalone HTML page with its styles and chart inline, laid
+//! out for the browser's "Save as PDF".
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+
+use super::engagement::{EngagementLevel, EngagementTrend};
+use super::personalization::escape_html;
+
+/// Size of the activity chart, in SVG user units
+const CHART_WIDTH: f64 = 640.0;
+const CHART_HEIGHT: f64 = 160.0;
+/// Room below the bars for week labels
+const CHART_LABEL_HEIGHT: f64 = 18.0;
+
+/// Who the report is about
+#[derive(Debug, Clone)]
+pub struct ReportProfile {
+    pub name: String,
+    pub email: String,
+    pub phone: Option<String>,
+    pub linkedin_url: Option<String>,
+    pub company: Option<String>,
+    pub status: String,
+    /// City, region and country, as far as known
+    pub location: Option<String>,
+    pub tags: Vec<String>,
+    pub owner: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportEngagement {
+    pub score: f64,
+    pub level: EngagementLevel,
+    pub trend: EngagementTrend,
+}
+
+/// Timeline entries in the week starting on `week_start`, a Monday
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeeklyActivity {
+    pub week_start: NaiveDate,
+    pub count: u64,
+}
+
+/// A timeline entry or note as the report lists it
+#[derive(Debug, Clone)]
+pub struct ReportEntry {
+    pub timestamp: DateTime<Utc>,
+    /// What kind of entry it is, e.g. "Meeting attended"
+    pub label: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ContactReport {
+    pub workspace_name: String,
+    pub profile: ReportProfile,
+    pub engagement: ReportEngagement,
+    /// Oldest week first
+    pub activity: Vec<WeeklyActivity>,
+    /// Newest first
+    pub highlights: Vec<ReportEntry>,
+    /// Newest first
+    pub notes: Vec<ReportEntry>,
+}
+
+/// Sum daily entry counts into the last `weeks` weeks up to `today`
+///
+/// Weeks start on Monday; the last one is the week of `today`. Weeks
+/// without entries are kept, with a count of zero, so the chart shows the
+/// gaps. Days outside the range are ignored.
+pub fn weekly_activity(daily: &[(NaiveDate, u64)], weeks: u32, today: NaiveDate) -> Vec<WeeklyActivity> {
+    let this_week = week_start(today);
+    let mut activity: Vec<WeeklyActivity> = (0..weeks)
+        .rev()
+        .map(|back| WeeklyActivity {
+            week_start: this_week - Duration::weeks(i64::from(back)),
+            count: 0,
+        })
+        .collect();
+
+    let Some(first) = activity.first().map(|w| w.week_start) else {
+        return activity;
+    };
+    for (day, count) in daily {
+        if *day < first || *day > today {
+            continue;
+        }
+        let index = ((week_start(*day) - first).num_days() / 7) as usize;
+        if let Some(week) = activity.get_mut(index) {
+            week.count += count;
+        }
+    }
+
+    activity
+}
+
+fn week_start(day: NaiveDate) -> NaiveDate {
+    day - Duration::days(i64::from(day.weekday().num_days_from_monday()))
+}
+
+/// Render a report as a standalone, printable HTML page
+pub fn render_contact_report(report: &ContactReport, now: DateTime<Utc>) -> String {
+    let profile = &report.profile;
+    let title = format!("{} - contact report", profile.name);
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n", escape_html(&title), STYLES));
+
+    html.push_str("<header>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", escape_html(&profile.name)));
+    let subtitle = match &profile.company {
+        Some(company) => format!("{} at {}", profile.status, company),
+        None => profile.status.clone(),
+    };
+    html.push_str(&format!("<p class=\"subtitle\">{}</p>\n", escape_html(&subtitle)));
+    html.push_str(&format!(
+        "<p class=\"meta\">{} &middot; generated {}</p>\n",
+        escape_html(&report.workspace_name),
+        now.format("%-d %b %Y %H:%M UTC")
+    ));
+    html.push_str("<p class=\"no-print\">Use your browser's Print to save this report as a PDF.</p>\n");
+    html.push_str("</header>\n");
+
+    // Profile
+    let location = profile.location.clone();
+    let tags = (!profile.tags.is_empty()).then(|| profile.tags.join(", "));
+    let rows = [
+        ("Email", Some(profile.email.clone())),
+        ("Phone", profile.phone.clone()),
+        ("LinkedIn", profile.linkedin_url.clone()),
+        ("Company", profile.company.clone()),
+        ("Location", location),
+        ("Tags", tags),
+        ("Owner", profile.owner.clone()),
+        ("In the CRM since", Some(profile.created_at.format("%-d %b %Y").to_string())),
+    ];
+    html.push_str("<section>\n<h2>Profile</h2>\n<table class=\"profile\">\n");
+    for (label, value) in rows {
+        if let Some(value) = value {
+            html.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", label, escape_html(&value)));
+        }
+    }
+    html.push_str("</table>\n</section>\n");
+
+    // Engagement
+    let total: u64 = report.activity.iter().map(|w| w.count).sum();
+    html.push_str("<section>\n<h2>Engagement</h2>\n");
+    html.push_str(&format!(
+        "<p class=\"score\"><strong>{:.0}</strong>/100 &middot; {} &middot; {}</p>\n",
+        report.engagement.score,
+        level_label(report.engagement.level),
+        trend_label(report.engagement.trend)
+    ));
+    html.push_str(&format!(
+        "<p>{} in the last {}.</p>\n",
+        plural(total, "timeline entry", "timeline entries"),
+        plural(report.activity.len() as u64, "week", "weeks")
+    ));
+    html.push_str(&activity_chart(&report.activity));
+    html.push_str("</section>\n");
+
+    html.push_str(&entry_section("Timeline highlights", &report.highlights, "No highlights yet."));
+    html.push_str(&entry_section("Notes", &report.notes, "No notes yet."));
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Bar chart of weekly activity, as inline SVG
+fn activity_chart(activity: &[WeeklyActivity]) -> String {
+    if activity.is_empty() {
+        return String::new();
+    }
+
+    let max = activity.iter().map(|w| w.count).max().unwrap_or(0).max(1) as f64;
+    let slot = CHART_WIDTH / activity.len() as f64;
+    let bar_width = (slot * 0.8).max(1.0);
+    let plot_height = CHART_HEIGHT - CHART_LABEL_HEIGHT;
+    // At most eight week labels, so they don't overlap
+    let label_every = activity.len().div_ceil(8).max(1);
+
+    let mut svg = format!(
+        "<svg class=\"chart\" viewBox=\"0 0 {w} {h}\" width=\"{w}\" height=\"{h}\" role=\"img\" \
+         aria-label=\"Timeline entries per week\" xmlns=\"http://www.w3.org/2000/svg\">\n",
+        w = CHART_WIDTH,
+        h = CHART_HEIGHT
+    );
+    svg.push_str(&format!(
+        "<line x1=\"0\" y1=\"{y:.1}\" x2=\"{w}\" y2=\"{y:.1}\" stroke=\"#d0d5dd\" stroke-width=\"1\"/>\n",
+        y = plot_height,
+        w = CHART_WIDTH
+    ));
+
+    for (i, week) in activity.iter().enumerate() {
+        let height = week.count as f64 / max * (plot_height - 4.0);
+        let x = i as f64 * slot + (slot - bar_width) / 2.0;
+        svg.push_str(&format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#4f46e5\">\
+             <title>Week of {}: {}</title></rect>\n",
+            x,
+            plot_height - height,
+            bar_width,
+            height,
+            week.week_start.format("%-d %b %Y"),
+            week.count
+        ));
+        if i % label_every == 0 {
+            svg.push_str(&format!(
+                "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"10\" fill=\"#667085\">{}</text>\n",
+                x,
+                CHART_HEIGHT - 4.0,
+                week.week_start.format("%-d %b")
+            ));
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn entry_section(heading: &str, entries: &[ReportEntry], empty: &str) -> String {
+    let mut html = format!("<section>\n<h2>{}</h2>\n", heading);
+    if entries.is_empty() {
+        html.push_str(&format!("<p class=\"empty\">{}</p>\n</section>\n", empty));
+        return html;
+    }
+
+    html.push_str("<ol class=\"entries\">\n");
+    for entry in entries {
+        html.push_str(&format!(
+            "<li><div class=\"when\">{} &middot; {}</div><div>{}</div></li>\n",
+            entry.timestamp.format("%-d %b %Y"),
+            escape_html(&entry.label),
+            escape_html(entry.content.trim()).replace('\n', "<br>")
+        ));
+    }
+    html.push_str("</ol>\n</section>\n");
+    html
+}
+
+fn level_label(level: EngagementLevel) -> &'static str {
+    match level {
+        EngagementLevel::Cold => "Cold",
+        EngagementLevel::Warming => "Warming",
+        EngagementLevel::Engaged => "Engaged",
+        EngagementLevel::Hot => "Hot",
+        EngagementLevel::Champion => "Champion",
+    }
+}
+
+fn trend_label(trend: EngagementTrend) -> &'static str {
+    match trend {
+        EngagementTrend::Declining => "declining",
+        EngagementTrend::Stable => "stable",
+        EngagementTrend::Improving => "improving",
+    }
+}
+
+/// "1 week", "3 weeks"
+fn plural(count: u64, one: &str, many: &str) -> String {
+    format!("{} {}", count, if count == 1 { one } else { many })
+}
+
+const STYLES: &str = "\
+body{font-family:-apple-system,'Segoe UI',Helvetica,Arial,sans-serif;color:#101828;max-width:760px;\
+margin:32px auto;padding:0 24px;line-height:1.45;font-size:14px}\
+h1{font-size:26px;margin:0}h2{font-size:16px;margin:28px 0 8px;border-bottom:1px solid #eaecf0;padding-bottom:4px}\
+.subtitle{font-size:16px;color:#344054;margin:4px 0}.meta,.when,.empty{color:#667085;font-size:12px}\
+table.profile th{text-align:left;font-weight:600;padding:2px 16px 2px 0;color:#344054;vertical-align:top}\
+.score{font-size:16px}.chart{max-width:100%;height:auto}\
+ol.entries{list-style:none;padding:0;margin:0}ol.entries li{padding:6px 0;border-bottom:1px solid #f2f4f7;\
+break-inside:avoid}\
+@page{size:A4;margin:18mm}@media print{body{margin:0;max-width:none}.no-print{display:none}}";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    fn report() -> ContactReport {
+        ContactReport {
+            workspace_name: "Acme".into(),
+            profile: ReportProfile {
+                name: "Jane <Doe>".into(),
+                email: "jane@example.com".into(),
+                phone: None,
+                linkedin_url: None,
+                company: Some("Initech".into()),
+                status: "Customer".into(),
+                location: Some("Stockholm, SE".into()),
+                tags: vec!["board".into()],
+                owner: None,
+                created_at: "2026-01-05T10:00:00Z".parse().unwrap(),
+            },
+            engagement: ReportEngagement {
+                score: 72.4,
+                level: EngagementLevel::Hot,
+                trend: EngagementTrend::Improving,
+            },
+            activity: weekly_activity(&[(day("2026-03-18"), 3)], 4, day("2026-03-20")),
+            highlights: vec![ReportEntry {
+                timestamp: "2026-03-18T14:00:00Z".parse().unwrap(),
+                label: "Meeting attended".into(),
+                content: "Quarterly review".into(),
+            }],
+            notes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_weekly_activity_buckets_by_monday() {
+        let daily = [
+            (day("2026-02-01"), 9), // before the range
+            (day("2026-03-02"), 1), // Monday
+            (day("2026-03-08"), 2), // Sunday, same week
+            (day("2026-03-18"), 4),
+        ];
+
+        let weeks = weekly_activity(&daily, 3, day("2026-03-20"));
+
+        assert_eq!(
+            weeks,
+            vec![
+                WeeklyActivity {
+                    week_start: day("2026-03-02"),
+                    count: 3,
+                },
+                WeeklyActivity {
+                    week_start: day("2026-03-09"),
+                    count: 0,
+                },
+                WeeklyActivity {
+                    week_start: day("2026-03-16"),
+                    count: 4,
+                },
+            ]
+        );
+        assert!(weekly_activity(&daily, 0, day("2026-03-20")).is_empty());
+    }
+
+    #[test]
+    fn test_render_contact_report() {
+        let html = render_contact_report(&report(), "2026-03-20T09:00:00Z".parse().unwrap());
+
+        // Contact data is escaped
+        assert!(html.contains("<h1>Jane &lt;Doe&gt;</h1>"));
+        assert!(html.contains("Customer at Initech"));
+        assert!(html.contains("<strong>72</strong>/100 &middot; Hot &middot; improving"));
+        assert!(html.contains("3 timeline entries in the last 4 weeks"));
+        assert_eq!(html.matches("<rect ").count(), 4);
+        assert!(html.contains("18 Mar 2026 &middot; Meeting attended"));
+        assert!(html.contains("No notes yet."));
+        assert!(!html.contains("<th>Phone</th>"));
+    }
+}
@@ -0,0 +1,208 @@
+//! Deal Domain - Sales opportunities and their pipeline stages
+//!
+//! A deal moves through the pipeline the same way a contact moves through
+//! its lifecycle: only some stage changes make business sense, and the
+//! rules live here rather than in the handlers.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::errors::{DomainError, DomainResult};
+
+/// Where a deal sits in the sales pipeline
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DealStage {
+    /// Identified, not yet qualified
+    #[default]
+    Prospecting,
+    /// Budget, need and timing confirmed
+    Qualification,
+    /// Proposal or quote sent
+    Proposal,
+    /// Working out terms
+    Negotiation,
+    /// Signed
+    ClosedWon,
+    /// Gone elsewhere or dropped
+    ClosedLost,
+}
+
+impl DealStage {
+    /// Every stage in pipeline order
+    pub const ALL: [DealStage; 6] = [
+        DealStage::Prospecting,
+        DealStage::Qualification,
+        DealStage::Proposal,
+        DealStage::Negotiation,
+        DealStage::ClosedWon,
+        DealStage::ClosedLost,
+    ];
+
+    pub fn is_closed(&self) -> bool {
+        matches!(self, DealStage::ClosedWon | DealStage::ClosedLost)
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.is_closed()
+    }
+
+    /// Likelihood of closing, in percent, used for the weighted pipeline
+    pub fn win_probability(&self) -> f64 {
+        match self {
+            DealStage::Prospecting => 10.0,
+            DealStage::Qualification => 25.0,
+            DealStage::Proposal => 50.0,
+            DealStage::Negotiation => 75.0,
+            DealStage::ClosedWon => 100.0,
+            DealStage::ClosedLost => 0.0,
+        }
+    }
+
+    /// Check if a stage transition is valid
+    ///
+    /// # Business Rules:
+    /// - Open deals can move freely between open stages (forward or back)
+    /// - Any open deal can be closed as won or lost
+    /// - A won deal is final
+    /// - A lost deal can only be reopened from the start (Prospecting)
+    pub fn can_transition_to(&self, new_stage: DealStage) -> bool {
+        use DealStage::*;
+
+        if *self == new_stage {
+            return true;
+        }
+
+        match (self, new_stage) {
+            (ClosedWon, _) => false,
+            (ClosedLost, Prospecting) => true,
+            (ClosedLost, _) => false,
+            _ => true,
+        }
+    }
+
+    /// Validate a transition, explaining why it was refused
+    pub fn transition_to(&self, new_stage: DealStage) -> DomainResult<DealStage> {
+        if self.can_transition_to(new_stage) {
+            return Ok(new_stage);
+        }
+
+        let reason = match self {
+            DealStage::ClosedWon => "Won deals are final; create a new deal for follow-up business",
+            _ => "Lost deals can only be reopened to prospecting",
+        };
+
+        Err(DomainError::InvalidStateTransition {
+            from: self.as_str().to_string(),
+            to: new_stage.as_str().to_string(),
+            reason: reason.to_string(),
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DealStage::Prospecting => "prospecting",
+            DealStage::Qualification => "qualification",
+            DealStage::Proposal => "proposal",
+            DealStage::Negotiation => "negotiation",
+            DealStage::ClosedWon => "closed_won",
+            DealStage::ClosedLost => "closed_lost",
+        }
+    }
+}
+
+impl std::fmt::Display for DealStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Business rule: a deal needs a name and a non-negative, finite value
+pub fn validate_deal(name: &str, value: f64) -> DomainResult<()> {
+    if name.trim().is_empty() {
+        return Err(DomainError::RequiredFieldMissing {
+            field: "name".to_string(),
+        });
+    }
+
+    if !value.is_finite() || value < 0.0 {
+        return Err(DomainError::InvalidField {
+            field: "value".to_string(),
+            reason: "Deal value must be zero or more".to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Closing stamps the time, reopening clears it, moves between open stages keep it
+pub fn closed_at(
+    from: DealStage,
+    to: DealStage,
+    current: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    match (from.is_closed(), to.is_closed()) {
+        (false, true) => Some(now),
+        (_, false) => None,
+        (true, true) => current,
+    }
+}
+
+/// Expected revenue: value weighted by the stage's win probability
+pub fn weighted_value(value: f64, stage: DealStage) -> f64 {
+    value * stage.win_probability() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_stages_move_freely() {
+        assert!(DealStage::Prospecting.can_transition_to(DealStage::Negotiation));
+        assert!(DealStage::Negotiation.can_transition_to(DealStage::Qualification));
+        assert!(DealStage::Proposal.can_transition_to(DealStage::ClosedLost));
+    }
+
+    #[test]
+    fn test_won_is_final() {
+        assert!(!DealStage::ClosedWon.can_transition_to(DealStage::Negotiation));
+        assert!(!DealStage::ClosedWon.can_transition_to(DealStage::ClosedLost));
+
+        let err = DealStage::ClosedWon.transition_to(DealStage::Prospecting).unwrap_err();
+        assert!(matches!(err, DomainError::InvalidStateTransition { .. }));
+    }
+
+    #[test]
+    fn test_lost_reopens_to_prospecting_only() {
+        assert!(DealStage::ClosedLost.can_transition_to(DealStage::Prospecting));
+        assert!(!DealStage::ClosedLost.can_transition_to(DealStage::Proposal));
+        assert!(!DealStage::ClosedLost.can_transition_to(DealStage::ClosedWon));
+    }
+
+    #[test]
+    fn test_validate_deal() {
+        assert!(validate_deal("Acme renewal", 12_000.0).is_ok());
+        assert!(validate_deal("  ", 100.0).is_err());
+        assert!(validate_deal("Acme", -1.0).is_err());
+        assert!(validate_deal("Acme", f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_closed_at_follows_closing_and_reopening() {
+        let then = Utc::now() - chrono::Duration::days(3);
+        let now = Utc::now();
+
+        assert_eq!(closed_at(DealStage::Proposal, DealStage::ClosedWon, None, now), Some(now));
+        assert_eq!(closed_at(DealStage::ClosedLost, DealStage::Prospecting, Some(then), now), None);
+        assert_eq!(closed_at(DealStage::Prospecting, DealStage::Proposal, None, now), None);
+    }
+
+    #[test]
+    fn test_weighted_value() {
+        assert_eq!(weighted_value(10_000.0, DealStage::Proposal), 5_000.0);
+        assert_eq!(weighted_value(10_000.0, DealStage::ClosedLost), 0.0);
+    }
+}
@@ -0,0 +1,135 @@
+//! Contact Dedupe - Duplicate suggestions from contact embeddings
+//!
+//! Each contact is embedded from its name, mailbox name and company. Pairs
+//! whose vectors are close enough become suggestions in a review queue,
+//! where a person accepts (merges) or dismisses them. A pair is suggested
+//! once: a dismissed pair is never raised again.
+//!
+//! This complements the rule-based checks in `merge`, which only catch
+//! exact or near-exact matches on one field; the rules' reasons are still
+//! attached to each suggestion so the reviewer can see what matched.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::contact::Contact;
+use super::errors::{DomainError, DomainResult};
+
+/// Where a suggestion is in the review queue
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DedupeStatus {
+    #[default]
+    Pending,
+    /// Merged into one contact
+    Accepted,
+    /// Not the same person
+    Dismissed,
+}
+
+impl DedupeStatus {
+    /// Accept or dismiss a pending suggestion
+    pub fn resolve(self, to: DedupeStatus) -> DomainResult<DedupeStatus> {
+        match self {
+            DedupeStatus::Pending if to != DedupeStatus::Pending => Ok(to),
+            _ => Err(DomainError::InvalidStateTransition {
+                from: self.as_str().to_string(),
+                to: to.as_str().to_string(),
+                reason: "Accepted and dismissed suggestions are final".to_string(),
+            }),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DedupeStatus::Pending => "pending",
+            DedupeStatus::Accepted => "accepted",
+            DedupeStatus::Dismissed => "dismissed",
+        }
+    }
+}
+
+/// The text a contact is embedded from
+///
+/// Email domains are left out: a personal and a work address for the same
+/// person differ there, while colleagues share it.
+pub fn embedding_text(contact: &Contact, company_name: Option<&str>) -> String {
+    let mailbox = contact.email.split('@').next().unwrap_or_default();
+    let mut text = format!("{} {} {}", contact.first_name.trim(), contact.last_name.trim(), mailbox);
+    if let Some(company) = company_name {
+        text.push(' ');
+        text.push_str(company.trim());
+    }
+    text
+}
+
+/// Cosine similarity of two vectors, 0 when either is empty or zero
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f64, 0.0f64, 0.0f64);
+    for (x, y) in a.iter().zip(b) {
+        dot += f64::from(*x) * f64::from(*y);
+        norm_a += f64::from(*x) * f64::from(*x);
+        norm_b += f64::from(*y) * f64::from(*y);
+    }
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    (dot / (norm_a.sqrt() * norm_b.sqrt())).clamp(0.0, 1.0)
+}
+
+/// Every pair of vectors at least `min_similarity` alike, most alike first
+///
+/// Compares all pairs, so callers cap how many vectors they pass in.
+/// Returned indices refer to positions in `vectors`, lower index first.
+pub fn similar_pairs(vectors: &[Vec<f32>], min_similarity: f64) -> Vec<(usize, usize, f64)> {
+    let mut pairs = Vec::new();
+
+    for (i, a) in vectors.iter().enumerate() {
+        for (j, b) in vectors.iter().enumerate().skip(i + 1) {
+            let similarity = cosine_similarity(a, b);
+            if similarity >= min_similarity {
+                pairs.push((i, j, similarity));
+            }
+        }
+    }
+
+    pairs.sort_by(|a, b| b.2.total_cmp(&a.2).then((a.0, a.1).cmp(&(b.0, b.1))));
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ContactBuilder;
+
+    #[test]
+    fn test_embedding_text_leaves_out_the_email_domain() {
+        let contact = ContactBuilder::new()
+            .first_name("Ada")
+            .last_name("Lovelace")
+            .email("ada.l@example.com")
+            .build()
+            .unwrap();
+
+        assert_eq!(embedding_text(&contact, Some("Analytical Engines")), "Ada Lovelace ada.l Analytical Engines");
+        assert_eq!(embedding_text(&contact, None), "Ada Lovelace ada.l");
+    }
+
+    #[test]
+    fn test_similar_pairs_most_alike_first() {
+        let vectors = vec![vec![1.0, 0.0], vec![0.0, 1.0], vec![1.0, 0.1], vec![1.0, 0.5], vec![]];
+
+        let pairs = similar_pairs(&vectors, 0.8);
+        assert_eq!(pairs.iter().map(|p| (p.0, p.1)).collect::<Vec<_>>(), vec![(0, 2), (2, 3), (0, 3)]);
+        assert!((pairs[0].2 - 0.995).abs() < 1e-3);
+        assert_eq!(cosine_similarity(&vectors[0], &vectors[4]), 0.0);
+    }
+
+    #[test]
+    fn test_resolved_suggestions_are_final() {
+        assert_eq!(DedupeStatus::Pending.resolve(DedupeStatus::Dismissed).unwrap(), DedupeStatus::Dismissed);
+        assert!(DedupeStatus::Accepted.resolve(DedupeStatus::Dismissed).is_err());
+        assert!(DedupeStatus::Pending.resolve(DedupeStatus::Pending).is_err());
+    }
+}
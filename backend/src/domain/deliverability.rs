@@ -0,0 +1,393 @@
+//! Deliverability Domain - bounces and spam complaints reported by the email provider
+//!
+//! Providers call back with what happened to the email after it left us.
+//! Two formats are understood:
+//! - SendGrid's event webhook: a JSON array of events
+//! - Amazon SES notifications, delivered through SNS: an SNS envelope whose
+//!   `Message` is the SES notification as a JSON string
+//!
+//! Only bounces and complaints are kept; deliveries, opens and the rest are
+//! ignored (opens and clicks come from our own tracking links). A hard
+//! bounce or a complaint suppresses the address; a soft bounce is only
+//! recorded, as the provider retries those itself.
+//!
+//! Emails can carry the workspace and campaign they were sent for, as
+//! SendGrid custom args or SES message tags named `crm_workspace_id` and
+//! `crm_campaign_id`; events that do are attributed to them.
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use super::contact::SubscriptionStatus;
+use super::errors::{DomainError, DomainResult};
+use super::validation::validate_email;
+
+/// Name of the custom arg or message tag naming the workspace an email was sent for
+pub const WORKSPACE_TAG: &str = "crm_workspace_id";
+/// Name of the custom arg or message tag naming the campaign an email was sent for
+pub const CAMPAIGN_TAG: &str = "crm_campaign_id";
+
+/// Most events one callback may carry
+pub const MAX_FEEDBACK_EVENTS: usize = 1000;
+
+/// Longest bounce reason kept
+const MAX_REASON_LEN: usize = 500;
+
+/// Email providers whose callbacks are understood
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailProvider {
+    Ses,
+    Sendgrid,
+}
+
+/// What the provider reported about one email
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackKind {
+    /// The address doesn't exist or refuses mail for good
+    HardBounce,
+    /// A temporary failure, such as a full mailbox
+    SoftBounce,
+    /// The recipient marked the email as spam
+    Complaint,
+}
+
+impl FeedbackKind {
+    /// What the contact's subscription becomes, for the kinds that stop all further campaign email
+    pub fn suppression(&self) -> Option<SubscriptionStatus> {
+        match self {
+            FeedbackKind::HardBounce => Some(SubscriptionStatus::Bounced),
+            FeedbackKind::Complaint => Some(SubscriptionStatus::Complained),
+            FeedbackKind::SoftBounce => None,
+        }
+    }
+
+    pub fn is_bounce(&self) -> bool {
+        matches!(self, FeedbackKind::HardBounce | FeedbackKind::SoftBounce)
+    }
+}
+
+/// One bounce or complaint, for one address
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliveryFeedback {
+    pub kind: FeedbackKind,
+    /// Lowercased
+    pub email: String,
+    /// The provider's explanation, such as the SMTP diagnostic
+    pub reason: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+    pub workspace_id: Option<String>,
+    pub campaign_id: Option<String>,
+}
+
+/// What an SES callback asks of us
+#[derive(Debug, Clone, PartialEq)]
+pub enum SesNotification {
+    /// SNS wants the subscription confirmed by fetching this URL
+    SubscriptionConfirmation { subscribe_url: String },
+    Feedback(Vec<DeliveryFeedback>),
+}
+
+/// Read a SendGrid event webhook payload
+///
+/// Events for addresses that aren't valid are dropped rather than failing
+/// the batch, so one bad event doesn't make SendGrid retry the rest.
+pub fn parse_sendgrid(payload: &Value, now: DateTime<Utc>) -> DomainResult<Vec<DeliveryFeedback>> {
+    let events = payload.as_array().ok_or_else(|| invalid("payload", "Expected an array of events"))?;
+    check_batch(events.len())?;
+
+    Ok(events
+        .iter()
+        .filter_map(|event| {
+            let kind = match (text(event, "event")?, text(event, "type")) {
+                ("bounce", Some("blocked")) => FeedbackKind::SoftBounce,
+                ("bounce", _) => FeedbackKind::HardBounce,
+                ("spamreport", _) => FeedbackKind::Complaint,
+                _ => return None,
+            };
+            let occurred_at = event
+                .get("timestamp")
+                .and_then(Value::as_i64)
+                .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+                .unwrap_or(now);
+
+            feedback(
+                kind,
+                text(event, "email")?,
+                text(event, "reason"),
+                occurred_at,
+                text(event, WORKSPACE_TAG),
+                text(event, CAMPAIGN_TAG),
+            )
+        })
+        .collect())
+}
+
+/// Read an SES notification, with or without its SNS envelope
+pub fn parse_ses(payload: &Value, now: DateTime<Utc>) -> DomainResult<SesNotification> {
+    let notification = match text(payload, "Type") {
+        Some("SubscriptionConfirmation") => {
+            let subscribe_url = text(payload, "SubscribeURL")
+                .filter(|url| is_sns_url(url))
+                .ok_or_else(|| invalid("SubscribeURL", "Expected an Amazon SNS URL"))?;
+            return Ok(SesNotification::SubscriptionConfirmation {
+                subscribe_url: subscribe_url.to_string(),
+            });
+        }
+        Some("Notification") => {
+            let message = text(payload, "Message").ok_or_else(|| invalid("Message", "Missing"))?;
+            serde_json::from_str(message).map_err(|_| invalid("Message", "Expected an SES notification"))?
+        }
+        Some(_) => return Ok(SesNotification::Feedback(Vec::new())),
+        None => payload.clone(),
+    };
+
+    let tag = |name: &str| notification.pointer(&format!("/mail/tags/{}/0", name)).and_then(Value::as_str);
+    let workspace_id = tag(WORKSPACE_TAG);
+    let campaign_id = tag(CAMPAIGN_TAG);
+
+    let notification_type = text(&notification, "notificationType").or_else(|| text(&notification, "eventType"));
+    let (details, recipients, kind) = match notification_type {
+        Some("Bounce") => {
+            let bounce = notification.get("bounce").unwrap_or(&Value::Null);
+            let kind = match text(bounce, "bounceType") {
+                Some("Permanent") => FeedbackKind::HardBounce,
+                _ => FeedbackKind::SoftBounce,
+            };
+            (bounce, "bouncedRecipients", kind)
+        }
+        Some("Complaint") => (
+            notification.get("complaint").unwrap_or(&Value::Null),
+            "complainedRecipients",
+            FeedbackKind::Complaint,
+        ),
+        _ => return Ok(SesNotification::Feedback(Vec::new())),
+    };
+
+    let occurred_at = text(details, "timestamp")
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        .map_or(now, |at| at.with_timezone(&Utc));
+    let recipients = details.get(recipients).and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+    check_batch(recipients.len())?;
+
+    Ok(SesNotification::Feedback(
+        recipients
+            .iter()
+            .filter_map(|recipient| {
+                let reason = text(recipient, "diagnosticCode").or_else(|| text(details, "complaintFeedbackType"));
+                feedback(
+                    kind,
+                    text(recipient, "emailAddress")?,
+                    reason,
+                    occurred_at,
+                    workspace_id,
+                    campaign_id,
+                )
+            })
+            .collect(),
+    ))
+}
+
+/// Whether a URL is an Amazon SNS subscription confirmation, the only kind
+/// a subscription is confirmed at:
+/// `https://sns.<region>.amazonaws.com/?Action=ConfirmSubscription&...`
+///
+/// The region must be one label: other hosts under `amazonaws.com`, such
+/// as S3 website buckets, can belong to anyone.
+pub fn is_sns_url(url: &str) -> bool {
+    let Some((host, query)) = url.strip_prefix("https://").and_then(|rest| rest.split_once("/?")) else {
+        return false;
+    };
+    let Some(region) = host.strip_prefix("sns.").and_then(|h| h.strip_suffix(".amazonaws.com")) else {
+        return false;
+    };
+
+    is_aws_region(region) && query.split('&').any(|param| param == "Action=ConfirmSubscription")
+}
+
+/// An AWS region name, such as `eu-west-1` or `us-gov-east-1`
+fn is_aws_region(region: &str) -> bool {
+    let parts: Vec<&str> = region.split('-').collect();
+    parts.len() >= 3
+        && parts.iter().all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()))
+        && parts.last().is_some_and(|p| p.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn feedback(
+    kind: FeedbackKind,
+    email: &str,
+    reason: Option<&str>,
+    occurred_at: DateTime<Utc>,
+    workspace_id: Option<&str>,
+    campaign_id: Option<&str>,
+) -> Option<DeliveryFeedback> {
+    // SES may give the address with a display name: "Jane <jane@example.com>"
+    let email = email.rsplit('<').next().unwrap_or(email).trim_end_matches('>').trim().to_lowercase();
+    validate_email(&email).ok()?;
+
+    Some(DeliveryFeedback {
+        kind,
+        email,
+        reason: reason
+            .map(|r| r.trim().chars().take(MAX_REASON_LEN).collect::<String>())
+            .filter(|r| !r.is_empty()),
+        occurred_at,
+        workspace_id: workspace_id.filter(|id| !id.is_empty()).map(str::to_string),
+        campaign_id: campaign_id.filter(|id| !id.is_empty()).map(str::to_string),
+    })
+}
+
+fn check_batch(len: usize) -> DomainResult<()> {
+    if len > MAX_FEEDBACK_EVENTS {
+        return Err(invalid(
+            "payload",
+            &format!("At most {} events can be sent at once", MAX_FEEDBACK_EVENTS),
+        ));
+    }
+    Ok(())
+}
+
+fn text<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(Value::as_str)
+}
+
+fn invalid(field: &str, reason: &str) -> DomainError {
+    DomainError::InvalidField {
+        field: field.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_sendgrid_events() {
+        let payload = json!([
+            { "email": "Jane@Example.com", "event": "bounce", "type": "bounce", "reason": "550 5.1.1 unknown user",
+              "timestamp": 1772442000, "crm_workspace_id": "w1", "crm_campaign_id": "c1" },
+            { "email": "full@example.com", "event": "bounce", "type": "blocked" },
+            { "email": "angry@example.com", "event": "spamreport" },
+            { "email": "fine@example.com", "event": "delivered" },
+            { "email": "not-an-address", "event": "bounce" },
+        ]);
+
+        let events = parse_sendgrid(&payload, now()).unwrap();
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].kind, FeedbackKind::HardBounce);
+        assert_eq!(events[0].email, "jane@example.com");
+        assert_eq!(events[0].reason.as_deref(), Some("550 5.1.1 unknown user"));
+        assert_eq!(events[0].occurred_at.timestamp(), 1772442000);
+        assert_eq!(events[0].campaign_id.as_deref(), Some("c1"));
+        assert_eq!(events[1].kind, FeedbackKind::SoftBounce);
+        assert_eq!(events[1].occurred_at, now());
+        assert_eq!(events[2].kind, FeedbackKind::Complaint);
+        assert_eq!(events[2].workspace_id, None);
+
+        assert!(parse_sendgrid(&json!({ "event": "bounce" }), now()).is_err());
+    }
+
+    #[test]
+    fn test_parse_ses_bounce_in_sns_envelope() {
+        let message = json!({
+            "notificationType": "Bounce",
+            "bounce": {
+                "bounceType": "Permanent",
+                "timestamp": "2026-03-01T12:00:00.000Z",
+                "bouncedRecipients": [
+                    { "emailAddress": "Jane <jane@example.com>", "diagnosticCode": "smtp; 550 no such user" },
+                    { "emailAddress": "joe@example.com" }
+                ]
+            },
+            "mail": { "tags": { "crm_workspace_id": ["w1"], "crm_campaign_id": ["c1"] } }
+        });
+        let payload = json!({ "Type": "Notification", "Message": message.to_string() });
+
+        let SesNotification::Feedback(events) = parse_ses(&payload, now()).unwrap() else {
+            panic!("expected feedback");
+        };
+
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| e.kind == FeedbackKind::HardBounce));
+        assert_eq!(events[0].email, "jane@example.com");
+        assert_eq!(events[0].reason.as_deref(), Some("smtp; 550 no such user"));
+        assert_eq!(events[0].workspace_id.as_deref(), Some("w1"));
+        assert_eq!(events[1].occurred_at, Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_parse_ses_complaint_and_transient_bounce() {
+        let complaint = json!({
+            "eventType": "Complaint",
+            "complaint": {
+                "complaintFeedbackType": "abuse",
+                "complainedRecipients": [{ "emailAddress": "a@example.com" }]
+            }
+        });
+        let SesNotification::Feedback(events) = parse_ses(&complaint, now()).unwrap() else {
+            panic!("expected feedback");
+        };
+        assert_eq!(events[0].kind, FeedbackKind::Complaint);
+        assert_eq!(events[0].reason.as_deref(), Some("abuse"));
+
+        let transient = json!({
+            "notificationType": "Bounce",
+            "bounce": { "bounceType": "Transient", "bouncedRecipients": [{ "emailAddress": "b@example.com" }] }
+        });
+        let SesNotification::Feedback(events) = parse_ses(&transient, now()).unwrap() else {
+            panic!("expected feedback");
+        };
+        assert_eq!(events[0].kind, FeedbackKind::SoftBounce);
+
+        let delivery = json!({ "notificationType": "Delivery" });
+        assert_eq!(parse_ses(&delivery, now()).unwrap(), SesNotification::Feedback(Vec::new()));
+    }
+
+    #[test]
+    fn test_parse_ses_subscription_confirmation() {
+        let url = "https://sns.eu-west-1.amazonaws.com/?Action=ConfirmSubscription&Token=abc";
+        let payload = json!({ "Type": "SubscriptionConfirmation", "SubscribeURL": url });
+        assert_eq!(
+            parse_ses(&payload, now()).unwrap(),
+            SesNotification::SubscriptionConfirmation {
+                subscribe_url: url.to_string()
+            }
+        );
+
+        let spoofed = json!({ "Type": "SubscriptionConfirmation", "SubscribeURL": "https://evil.example.com/" });
+        assert!(parse_ses(&spoofed, now()).is_err());
+    }
+
+    #[test]
+    fn test_is_sns_url() {
+        assert!(is_sns_url("https://sns.us-east-1.amazonaws.com/?Action=ConfirmSubscription"));
+        assert!(!is_sns_url("http://sns.us-east-1.amazonaws.com/"));
+        assert!(!is_sns_url("https://sns.us-east-1.amazonaws.com.evil.com/"));
+        assert!(!is_sns_url("https://evil.com/sns.us-east-1.amazonaws.com"));
+        assert!(!is_sns_url("https://user@sns.us-east-1.amazonaws.com/"));
+        assert!(is_sns_url("https://sns.us-gov-west-1.amazonaws.com/?Action=ConfirmSubscription&Token=abc"));
+        assert!(!is_sns_url(
+            "https://sns.evil.s3-website-us-east-1.amazonaws.com/?Action=ConfirmSubscription"
+        ));
+        assert!(!is_sns_url("https://sns.us-east-1.amazonaws.com:8443/?Action=ConfirmSubscription"));
+        assert!(!is_sns_url("https://sns.us-east-1.amazonaws.com/redirect?Action=ConfirmSubscription"));
+        assert!(!is_sns_url("https://sns.us-east-1.amazonaws.com/?Action=Unsubscribe"));
+    }
+
+    #[test]
+    fn test_only_hard_bounces_and_complaints_suppress() {
+        assert_eq!(FeedbackKind::HardBounce.suppression(), Some(SubscriptionStatus::Bounced));
+        assert_eq!(FeedbackKind::Complaint.suppression(), Some(SubscriptionStatus::Complained));
+        assert_eq!(FeedbackKind::SoftBounce.suppression(), None);
+    }
+}
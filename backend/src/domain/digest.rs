@@ -0,0 +1,322 @@
+//! Digest Domain - the weekly summary email for workspace members
+//!
+//! Each member picks the weekday and local hour their digest arrives, and
+//! which sections it has. A digest goes out once per scheduled slot; a slot
+//! missed by more than a day (say the server was down) is skipped rather
+//! than delivered late.
+
+use chrono::{DateTime, Datelike, Duration, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::errors::{DomainError, DomainResult};
+use super::personalization::escape_html;
+use super::validation::validate_timezone;
+
+/// How long after its slot a digest may still be sent
+const SEND_GRACE_HOURS: i64 = 24;
+
+/// A member's digest settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct DigestPreferences {
+    pub enabled: bool,
+    /// Day the digest arrives, e.g. `"Mon"`
+    #[schema(value_type = String)]
+    pub weekday: Weekday,
+    /// Local hour it arrives, 0-23
+    pub hour: u32,
+    /// IANA timezone for `hour`; UTC if not set
+    pub timezone: Option<String>,
+    pub new_leads: bool,
+    pub engagement_movers: bool,
+    pub stale_leads: bool,
+    pub upcoming_events: bool,
+}
+
+impl Default for DigestPreferences {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            weekday: Weekday::Mon,
+            hour: 8,
+            timezone: None,
+            new_leads: true,
+            engagement_movers: true,
+            stale_leads: true,
+            upcoming_events: true,
+        }
+    }
+}
+
+impl DigestPreferences {
+    pub fn validate(&self) -> DomainResult<()> {
+        if self.hour > 23 {
+            return Err(DomainError::InvalidField {
+                field: "hour".to_string(),
+                reason: "Hour must be between 0 and 23".to_string(),
+            });
+        }
+
+        validate_timezone(self.timezone.as_deref()).map_err(|e| e.at("timezone"))
+    }
+
+    /// The latest scheduled slot at or before `now`
+    pub fn last_slot(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let tz: Tz = self.timezone.as_deref().and_then(|tz| tz.parse().ok()).unwrap_or(Tz::UTC);
+        let today = now.with_timezone(&tz).date_naive();
+        let days_back = (today.weekday().num_days_from_monday() + 7 - self.weekday.num_days_from_monday()) % 7;
+        let time = NaiveTime::from_hms_opt(self.hour.min(23), 0, 0).unwrap_or_default();
+
+        let slot_on = |days_back: i64| {
+            let local = (today - Duration::days(days_back)).and_time(time);
+            // A slot in a DST gap moves to the same UTC wall time
+            tz.from_local_datetime(&local)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc))
+                .unwrap_or_else(|| Utc.from_utc_datetime(&local))
+        };
+
+        let slot = slot_on(i64::from(days_back));
+        if slot <= now {
+            slot
+        } else {
+            slot_on(i64::from(days_back) + 7)
+        }
+    }
+
+    /// The first scheduled slot after `now`
+    pub fn next_slot(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        // Eight days on is past the next slot even across a DST change
+        self.last_slot(self.last_slot(now) + Duration::days(8))
+    }
+
+    /// Whether a digest should go out at `now`
+    pub fn is_due(&self, last_sent_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+        if !self.enabled {
+            return false;
+        }
+
+        let slot = self.last_slot(now);
+        now - slot < Duration::hours(SEND_GRACE_HOURS) && last_sent_at.is_none_or(|sent| sent < slot)
+    }
+}
+
+/// A lead created during the period
+#[derive(Debug, Clone)]
+pub struct DigestLead {
+    pub name: String,
+    pub email: String,
+}
+
+/// A contact with the most inbound interactions during the period
+#[derive(Debug, Clone)]
+pub struct DigestMover {
+    pub name: String,
+    pub email: String,
+    pub interactions: u64,
+    pub engagement_score: f64,
+}
+
+/// A lead nobody has touched in a while
+#[derive(Debug, Clone)]
+pub struct DigestStaleLead {
+    pub name: String,
+    pub email: String,
+    /// Last timeline entry, or when the lead was created if there is none
+    pub last_activity: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DigestEvent {
+    pub name: String,
+    pub start_time: DateTime<Utc>,
+    pub registered: u64,
+}
+
+/// What goes into one member's digest; `None` sections are turned off
+#[derive(Debug, Clone)]
+pub struct DigestContent {
+    pub workspace_name: String,
+    pub recipient_name: String,
+    pub period_days: i64,
+    /// All leads created in the period; `new_leads` lists only the newest
+    pub new_lead_count: u64,
+    pub new_leads: Option<Vec<DigestLead>>,
+    pub engagement_movers: Option<Vec<DigestMover>>,
+    pub stale_leads: Option<Vec<DigestStaleLead>>,
+    pub upcoming_events: Option<Vec<DigestEvent>>,
+}
+
+/// A digest ready to send
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedDigest {
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+/// Render a digest as an email, with HTML and plain text bodies
+pub fn render_digest(digest: &DigestContent, now: DateTime<Utc>) -> RenderedDigest {
+    let mut highlights = Vec::new();
+    if digest.new_leads.is_some() && digest.new_lead_count > 0 {
+        highlights.push(plural(digest.new_lead_count, "new lead"));
+    }
+    if let Some(events) = digest.upcoming_events.as_ref().filter(|e| !e.is_empty()) {
+        highlights.push(plural(events.len() as u64, "upcoming event"));
+    }
+    let subject = if highlights.is_empty() {
+        format!("Your weekly {} digest", digest.workspace_name)
+    } else {
+        format!("Your weekly {} digest: {}", digest.workspace_name, highlights.join(", "))
+    };
+
+    // (heading, lines) per enabled section
+    let mut sections: Vec<(String, Vec<String>)> = Vec::new();
+    if let Some(leads) = &digest.new_leads {
+        let heading = format!("New leads ({})", digest.new_lead_count);
+        sections.push((heading, leads.iter().map(|l| format!("{} <{}>", l.name, l.email)).collect()));
+    }
+    if let Some(movers) = &digest.engagement_movers {
+        let lines = movers
+            .iter()
+            .map(|m| {
+                format!(
+                    "{} <{}>: {}, engagement score {:.0}",
+                    m.name,
+                    m.email,
+                    plural(m.interactions, "interaction"),
+                    m.engagement_score
+                )
+            })
+            .collect();
+        sections.push(("Engagement movers".to_string(), lines));
+    }
+    if let Some(leads) = &digest.stale_leads {
+        let lines = leads
+            .iter()
+            .map(|l| {
+                let idle = (now - l.last_activity).num_days().max(0) as u64;
+                format!("{} <{}>: no activity for {}", l.name, l.email, plural(idle, "day"))
+            })
+            .collect();
+        sections.push(("Leads going cold".to_string(), lines));
+    }
+    if let Some(events) = &digest.upcoming_events {
+        let lines = events
+            .iter()
+            .map(|e| {
+                format!(
+                    "{} on {}: {} registered",
+                    e.name,
+                    e.start_time.format("%a %-d %b %H:%M UTC"),
+                    e.registered
+                )
+            })
+            .collect();
+        sections.push(("Upcoming events".to_string(), lines));
+    }
+
+    let intro = format!(
+        "Hi {}, here is what happened in {} over the last {}.",
+        digest.recipient_name,
+        digest.workspace_name,
+        plural(digest.period_days.max(0) as u64, "day")
+    );
+
+    let mut text = format!("{}\n", intro);
+    let mut html = format!("<p>{}</p>\n", escape_html(&intro));
+    for (heading, lines) in &sections {
+        text.push_str(&format!("\n{}\n", heading));
+        html.push_str(&format!("<h2>{}</h2>\n", escape_html(heading)));
+
+        if lines.is_empty() {
+            text.push_str("Nothing this week.\n");
+            html.push_str("<p>Nothing this week.</p>\n");
+            continue;
+        }
+
+        html.push_str("<ul>\n");
+        for line in lines {
+            text.push_str(&format!("- {}\n", line));
+            html.push_str(&format!("<li>{}</li>\n", escape_html(line)));
+        }
+        html.push_str("</ul>\n");
+    }
+
+    RenderedDigest { subject, html, text }
+}
+
+/// "1 day", "3 days"
+fn plural(count: u64, noun: &str) -> String {
+    format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_due_once_per_slot_within_grace() {
+        let prefs = DigestPreferences::default(); // Mondays 08:00 UTC
+
+        // 2026-10-12 is a Monday
+        assert_eq!(prefs.last_slot(at("2026-10-14T12:00:00Z")), at("2026-10-12T08:00:00Z"));
+        assert_eq!(prefs.last_slot(at("2026-10-12T07:59:00Z")), at("2026-10-05T08:00:00Z"));
+        assert_eq!(prefs.next_slot(at("2026-10-14T12:00:00Z")), at("2026-10-19T08:00:00Z"));
+
+        assert!(prefs.is_due(None, at("2026-10-12T08:05:00Z")));
+        assert!(prefs.is_due(Some(at("2026-10-05T08:01:00Z")), at("2026-10-12T08:05:00Z")));
+        assert!(!prefs.is_due(Some(at("2026-10-12T08:01:00Z")), at("2026-10-12T09:00:00Z")));
+        // Missed by more than a day: wait for next week
+        assert!(!prefs.is_due(None, at("2026-10-14T12:00:00Z")));
+
+        let off = DigestPreferences { enabled: false, ..prefs };
+        assert!(!off.is_due(None, at("2026-10-12T08:05:00Z")));
+    }
+
+    #[test]
+    fn test_slot_follows_the_members_timezone() {
+        let prefs = DigestPreferences {
+            weekday: Weekday::Fri,
+            hour: 9,
+            timezone: Some("Europe/Stockholm".into()),
+            ..Default::default()
+        };
+
+        // 09:00 in Stockholm is 07:00 UTC in October (CEST)
+        assert_eq!(prefs.last_slot(at("2026-10-16T07:30:00Z")), at("2026-10-16T07:00:00Z"));
+        // ...and 08:00 UTC once summer time ends on the 25th
+        assert_eq!(prefs.next_slot(at("2026-10-24T00:00:00Z")), at("2026-10-30T08:00:00Z"));
+        assert!(DigestPreferences { hour: 24, ..Default::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_render_escapes_and_skips_disabled_sections() {
+        let digest = DigestContent {
+            workspace_name: "Acme".into(),
+            recipient_name: "Ada".into(),
+            period_days: 7,
+            new_lead_count: 2,
+            new_leads: Some(vec![DigestLead {
+                name: "Bob <Builder>".into(),
+                email: "bob@example.com".into(),
+            }]),
+            engagement_movers: None,
+            stale_leads: Some(Vec::new()),
+            upcoming_events: None,
+        };
+
+        let email = render_digest(&digest, Utc::now());
+        assert_eq!(email.subject, "Your weekly Acme digest: 2 new leads");
+        assert!(email.html.contains("Bob &lt;Builder&gt;"));
+        assert!(email.text.contains("- Bob <Builder> <bob@example.com>"));
+        assert!(email.text.contains("Leads going cold\nNothing this week."));
+        assert!(!email.text.contains("Engagement movers"));
+    }
+}
@@ -0,0 +1,470 @@
+//! Email Layout - compiling structured email documents into sendable HTML and text
+//!
+//! An email is written as a list of sections (headings, paragraphs, lists,
+//! buttons, images and dividers) and compiled into the HTML mail clients
+//! actually render, the way MJML does:
+//! - nested tables with inline styles rather than CSS layout, which Outlook
+//!   and Gmail drop
+//! - a 600px column that goes full width on phones through one media query,
+//!   with a fixed-width ghost table so Outlook on Windows keeps the column
+//! - buttons as padded table cells, so they stay clickable everywhere
+//! - a hidden preheader, so inboxes preview the preview text rather than
+//!   the first heading
+//!
+//! The plain-text alternative is written from the same sections. Merge
+//! variables like `{{first_name|there}}` pass through untouched, to be
+//! filled in per recipient afterwards.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::errors::{DomainError, DomainResult};
+use super::personalization::escape_html;
+
+/// Most sections one email may have
+const MAX_SECTIONS: usize = 50;
+
+/// Longest text of any one section, or list item
+const MAX_TEXT_LEN: usize = 5000;
+
+/// Most items in one list
+const MAX_LIST_ITEMS: usize = 50;
+
+/// Width of the email's column, in pixels
+const CONTAINER_WIDTH: u32 = 600;
+
+/// Horizontal padding inside the column, in pixels
+const GUTTER: u32 = 32;
+
+/// Button color when the document sets none
+const DEFAULT_ACCENT: &str = "#0066ff";
+
+const FONT_STACK: &str = "-apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, Helvetica, Arial, sans-serif";
+
+/// Resets and the small-screen rules; inline styles carry everything else
+const HEAD_STYLE: &str = "body{margin:0;padding:0;width:100%!important;\
+    -webkit-text-size-adjust:100%;-ms-text-size-adjust:100%;}\
+    table,td{border-collapse:collapse;mso-table-lspace:0pt;mso-table-rspace:0pt;}\
+    img{border:0;height:auto;line-height:100%;outline:none;text-decoration:none;-ms-interpolation-mode:bicubic;}\
+    @media only screen and (max-width:620px){\
+    .email-container{width:100%!important;}\
+    .email-pad{padding-left:16px!important;padding-right:16px!important;}\
+    .email-fluid{width:100%!important;max-width:100%!important;height:auto!important;}\
+    .email-button{width:100%!important;}}";
+
+/// An email as sections, compiled by `render_email`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct EmailDocument {
+    pub sections: Vec<EmailSection>,
+    /// Button color as `#rrggbb`; a blue by default
+    #[serde(default)]
+    pub accent_color: Option<String>,
+    /// Small print under the content, such as the sender's address
+    #[serde(default)]
+    pub footer: Option<String>,
+}
+
+/// One block of an email, top to bottom
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EmailSection {
+    /// The first heading is the email's title; later ones head their part
+    Heading { text: String },
+    /// Paragraphs, separated by blank lines
+    Text { text: String },
+    List { items: Vec<String> },
+    Button { text: String, url: String },
+    Image {
+        /// An absolute http(s) URL
+        src: String,
+        alt: String,
+        /// Where clicking the image goes
+        #[serde(default)]
+        href: Option<String>,
+    },
+    Divider,
+}
+
+/// A compiled email: HTML and its plain-text alternative
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedEmail {
+    pub html: String,
+    pub text: String,
+}
+
+impl EmailDocument {
+    pub fn validate(&self) -> DomainResult<()> {
+        if self.sections.is_empty() {
+            return Err(invalid("sections", "An email needs at least one section"));
+        }
+        if self.sections.len() > MAX_SECTIONS {
+            return Err(invalid("sections", &format!("At most {} sections", MAX_SECTIONS)));
+        }
+        if let Some(color) = &self.accent_color {
+            if !is_hex_color(color) {
+                return Err(invalid("accent_color", "Expected a color like #0066ff"));
+            }
+        }
+        if let Some(footer) = &self.footer {
+            check_text("footer", footer)?;
+        }
+
+        for (i, section) in self.sections.iter().enumerate() {
+            let field = |name: &str| format!("sections[{}].{}", i, name);
+            match section {
+                EmailSection::Heading { text } | EmailSection::Text { text } => check_text(&field("text"), text)?,
+                EmailSection::List { items } => {
+                    if items.is_empty() || items.len() > MAX_LIST_ITEMS {
+                        return Err(invalid(&field("items"), &format!("Expected 1 to {} items", MAX_LIST_ITEMS)));
+                    }
+                    for item in items {
+                        check_text(&field("items"), item)?;
+                    }
+                }
+                EmailSection::Button { text, url } => {
+                    check_text(&field("text"), text)?;
+                    if !is_link(url) {
+                        return Err(invalid(&field("url"), "Expected an http(s) or mailto: link"));
+                    }
+                }
+                EmailSection::Image { src, alt, href } => {
+                    if !is_web_url(src) {
+                        return Err(invalid(&field("src"), "Expected an absolute http(s) URL"));
+                    }
+                    if alt.len() > MAX_TEXT_LEN {
+                        return Err(invalid(&field("alt"), "Too long"));
+                    }
+                    if href.as_deref().is_some_and(|href| !is_link(href)) {
+                        return Err(invalid(&field("href"), "Expected an http(s) or mailto: link"));
+                    }
+                }
+                EmailSection::Divider => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Compile a document into responsive HTML and plain text
+///
+/// The document is expected to be valid; an accent color that isn't falls
+/// back to the default.
+pub fn render_email(document: &EmailDocument, subject: &str, preview_text: &str) -> RenderedEmail {
+    let accent = document
+        .accent_color
+        .as_deref()
+        .filter(|c| is_hex_color(c))
+        .unwrap_or(DEFAULT_ACCENT);
+
+    let mut rows = String::new();
+    let mut chunks: Vec<String> = Vec::new();
+    let mut seen_heading = false;
+    for (i, section) in document.sections.iter().enumerate() {
+        let top = if i == 0 { GUTTER } else { 0 };
+        let (cell, text) = match section {
+            EmailSection::Heading { text } => {
+                let (tag, size, line) = if seen_heading { ("h2", 20, 28) } else { ("h1", 26, 34) };
+                seen_heading = true;
+                let html = format!(
+                    "<{tag} style=\"margin:0;font-family:{font};font-size:{size}px;line-height:{line}px;\
+                     font-weight:700;color:#1a1a1a;\">{text}</{tag}>",
+                    tag = tag,
+                    font = FONT_STACK,
+                    size = size,
+                    line = line,
+                    text = escape_html(text.trim()),
+                );
+                (html, text.trim().to_string())
+            }
+            EmailSection::Text { text } => {
+                let paragraphs = paragraphs(text);
+                let html = paragraphs
+                    .iter()
+                    .enumerate()
+                    .map(|(n, p)| {
+                        let bottom = if n + 1 < paragraphs.len() { 16 } else { 0 };
+                        format!(
+                            "<p style=\"margin:0 0 {bottom}px;font-family:{font};font-size:16px;line-height:24px;\
+                             color:#4a4a4a;\">{text}</p>",
+                            bottom = bottom,
+                            font = FONT_STACK,
+                            text = escape_html(p).replace('\n', "<br>"),
+                        )
+                    })
+                    .collect::<String>();
+                (html, paragraphs.join("\n\n"))
+            }
+            EmailSection::List { items } => {
+                let html = format!(
+                    "<ul style=\"margin:0;padding:0 0 0 20px;font-family:{font};font-size:16px;line-height:24px;\
+                     color:#4a4a4a;\">{items}</ul>",
+                    font = FONT_STACK,
+                    items = items
+                        .iter()
+                        .map(|item| format!(r#"<li style="margin:0 0 8px;">{}</li>"#, escape_html(item.trim())))
+                        .collect::<String>(),
+                );
+                let text = items.iter().map(|item| format!("- {}", item.trim())).collect::<Vec<_>>().join("\n");
+                (html, text)
+            }
+            EmailSection::Button { text, url } => {
+                let html = format!(
+                    "<table role=\"presentation\" class=\"email-button\" \
+                     cellpadding=\"0\" cellspacing=\"0\" border=\"0\"><tr>\
+                     <td align=\"center\" bgcolor=\"{accent}\" style=\"border-radius:6px;background-color:{accent};\">\
+                     <a href=\"{url}\" target=\"_blank\" style=\"display:inline-block;padding:12px 24px;\
+                     font-family:{font};font-size:16px;font-weight:600;line-height:20px;color:#ffffff;\
+                     text-decoration:none;border-radius:6px;\">{text}</a></td></tr></table>",
+                    accent = accent,
+                    url = escape_html(url.trim()),
+                    font = FONT_STACK,
+                    text = escape_html(text.trim()),
+                );
+                (html, format!("{}: {}", text.trim(), url.trim()))
+            }
+            EmailSection::Image { src, alt, href } => {
+                let width = CONTAINER_WIDTH - 2 * GUTTER;
+                let img = format!(
+                    "<img src=\"{src}\" alt=\"{alt}\" width=\"{width}\" class=\"email-fluid\" \
+                     style=\"display:block;width:100%;max-width:{width}px;height:auto;border:0;\">",
+                    src = escape_html(src.trim()),
+                    alt = escape_html(alt.trim()),
+                    width = width,
+                );
+                let alt = alt.trim();
+                match href {
+                    Some(href) => (
+                        format!(r#"<a href="{}" target="_blank">{}</a>"#, escape_html(href.trim()), img),
+                        if alt.is_empty() { href.trim().to_string() } else { format!("{}: {}", alt, href.trim()) },
+                    ),
+                    None => (img, alt.to_string()),
+                }
+            }
+            EmailSection::Divider => (
+                "<table role=\"presentation\" width=\"100%\" cellpadding=\"0\" cellspacing=\"0\" border=\"0\">\
+                 <tr><td style=\"border-top:1px solid #e4e4e7;font-size:0;line-height:0;\">&nbsp;</td></tr></table>"
+                    .to_string(),
+                "---".to_string(),
+            ),
+        };
+
+        rows.push_str(&row(&cell, top, 24));
+        if !text.is_empty() {
+            chunks.push(text);
+        }
+    }
+
+    if let Some(footer) = document.footer.as_deref().map(str::trim).filter(|f| !f.is_empty()) {
+        let cell = format!(
+            "<p style=\"margin:0;font-family:{font};font-size:12px;line-height:18px;color:#8a8a8a;\">{text}</p>",
+            font = FONT_STACK,
+            text = escape_html(footer).replace('\n', "<br>"),
+        );
+        rows.push_str(&row(&cell, 8, GUTTER));
+        chunks.push(footer.to_string());
+    }
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html lang="en" xmlns="http://www.w3.org/1999/xhtml" xmlns:o="urn:schemas-microsoft-com:office:office">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta http-equiv="X-UA-Compatible" content="IE=edge">
+<meta name="x-apple-disable-message-reformatting">
+<meta name="format-detection" content="telephone=no, date=no, address=no, email=no">
+<title>{title}</title>
+<!--[if mso]><noscript><xml><o:OfficeDocumentSettings><o:PixelsPerInch>96</o:PixelsPerInch>
+</o:OfficeDocumentSettings></xml></noscript><![endif]-->
+<style>{style}</style>
+</head>
+<body style="margin:0;padding:0;background-color:#f4f4f5;">
+<div style="display:none;max-height:0;max-width:0;overflow:hidden;opacity:0;mso-hide:all;">{preheader}{filler}</div>
+<table role="presentation" width="100%" cellpadding="0" cellspacing="0" border="0" style="background-color:#f4f4f5;">
+<tr><td align="center" style="padding:24px 0;">
+<!--[if mso]><table role="presentation" width="{width}" cellpadding="0" cellspacing="0" border="0"><tr><td><![endif]-->
+<table role="presentation" class="email-container" width="{width}" cellpadding="0" cellspacing="0" border="0"
+ style="width:100%;max-width:{width}px;background-color:#ffffff;border-radius:8px;">
+{rows}</table>
+<!--[if mso]></td></tr></table><![endif]-->
+</td></tr>
+</table>
+</body>
+</html>
+"#,
+        title = escape_html(subject.trim()),
+        style = HEAD_STYLE,
+        preheader = escape_html(preview_text.trim()),
+        // Keeps inboxes from filling the rest of the preview with the body
+        filler = "&#847;&zwnj;&nbsp;".repeat(40),
+        width = CONTAINER_WIDTH,
+        rows = rows,
+    );
+
+    RenderedEmail {
+        html,
+        text: chunks.join("\n\n"),
+    }
+}
+
+/// One section's row of the email's column
+fn row(cell: &str, top: u32, bottom: u32) -> String {
+    format!(
+        "<tr><td class=\"email-pad\" style=\"padding:{top}px {gutter}px {bottom}px {gutter}px;\">{cell}</td></tr>\n",
+        top = top,
+        bottom = bottom,
+        gutter = GUTTER,
+        cell = cell,
+    )
+}
+
+/// Non-blank paragraphs of a text section, trimmed
+fn paragraphs(text: &str) -> Vec<&str> {
+    text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()).collect()
+}
+
+fn check_text(field: &str, text: &str) -> DomainResult<()> {
+    if text.trim().is_empty() {
+        return Err(invalid(field, "Must not be blank"));
+    }
+    if text.len() > MAX_TEXT_LEN {
+        return Err(invalid(field, &format!("At most {} characters", MAX_TEXT_LEN)));
+    }
+    Ok(())
+}
+
+fn is_web_url(url: &str) -> bool {
+    let lower = url.trim().to_lowercase();
+    (lower.starts_with("https://") || lower.starts_with("http://")) && !lower.contains(char::is_whitespace)
+}
+
+/// A link a button or image may point to; a merge variable holding one counts
+fn is_link(url: &str) -> bool {
+    let url = url.trim();
+    is_web_url(url) || url.to_lowercase().starts_with("mailto:") || (url.starts_with("{{") && url.ends_with("}}"))
+}
+
+fn is_hex_color(color: &str) -> bool {
+    color.len() == 7 && color.starts_with('#') && color[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn invalid(field: &str, reason: &str) -> DomainError {
+    DomainError::InvalidField {
+        field: field.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(sections: Vec<EmailSection>) -> EmailDocument {
+        EmailDocument {
+            sections,
+            accent_color: None,
+            footer: None,
+        }
+    }
+
+    fn text(text: &str) -> EmailSection {
+        EmailSection::Text { text: text.into() }
+    }
+
+    #[test]
+    fn test_render_builds_responsive_html_and_text() {
+        let doc = EmailDocument {
+            sections: vec![
+                EmailSection::Heading {
+                    text: "Hi {{first_name|there}}".into(),
+                },
+                text("We're launching <today>.\n\nSecond paragraph"),
+                EmailSection::List {
+                    items: vec!["Faster".into(), "Cheaper".into()],
+                },
+                EmailSection::Button {
+                    text: "Learn more".into(),
+                    url: "https://example.com/launch?a=1&b=2".into(),
+                },
+                EmailSection::Divider,
+            ],
+            accent_color: Some("#ff5500".into()),
+            footer: Some("Acme, 1 Main St".into()),
+        };
+        assert!(doc.validate().is_ok());
+
+        let email = render_email(&doc, "Launch", "Something new");
+
+        assert!(email.html.starts_with("<!DOCTYPE html>"));
+        assert!(email.html.contains("@media only screen and (max-width:620px)"));
+        assert!(email.html.contains("<!--[if mso]>"));
+        assert!(email.html.contains(">Something new&#847;"));
+        assert!(email.html.contains("<h1 "));
+        assert!(email.html.contains("We&#39;re launching &lt;today&gt;."));
+        assert!(email.html.contains(r#"href="https://example.com/launch?a=1&amp;b=2""#));
+        assert!(email.html.contains("background-color:#ff5500;"));
+        // Merge variables survive for per-recipient personalization
+        assert!(email.html.contains("Hi {{first_name|there}}"));
+        assert!(email.html.contains("</body>"));
+
+        assert_eq!(
+            email.text,
+            "Hi {{first_name|there}}\n\nWe're launching <today>.\n\nSecond paragraph\n\n- Faster\n- Cheaper\n\n\
+             Learn more: https://example.com/launch?a=1&b=2\n\n---\n\nAcme, 1 Main St"
+        );
+    }
+
+    #[test]
+    fn test_later_headings_and_linked_images() {
+        let doc = document(vec![
+            EmailSection::Heading { text: "Title".into() },
+            EmailSection::Image {
+                src: "https://cdn.example.com/hero.png".into(),
+                alt: "Our new app".into(),
+                href: Some("https://example.com".into()),
+            },
+            EmailSection::Heading { text: "Part two".into() },
+        ]);
+
+        let email = render_email(&doc, "", "");
+
+        assert!(email.html.contains("<h2 "));
+        assert!(email
+            .html
+            .contains(r#"<a href="https://example.com" target="_blank"><img src="https://cdn.example.com/hero.png""#));
+        assert_eq!(email.text, "Title\n\nOur new app: https://example.com\n\nPart two");
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_sections() {
+        assert!(document(Vec::new()).validate().is_err());
+        assert!(document(vec![text("  ")]).validate().is_err());
+        assert!(document(vec![EmailSection::List { items: Vec::new() }]).validate().is_err());
+
+        let button = |url: &str| {
+            document(vec![EmailSection::Button {
+                text: "Go".into(),
+                url: url.into(),
+            }])
+        };
+        assert!(button("https://example.com").validate().is_ok());
+        assert!(button("mailto:sales@example.com").validate().is_ok());
+        assert!(button("{{event_url}}").validate().is_ok());
+        assert!(button("javascript:alert(1)").validate().is_err());
+
+        let image = document(vec![EmailSection::Image {
+            src: "data:image/png;base64,AAAA".into(),
+            alt: String::new(),
+            href: None,
+        }]);
+        assert!(image.validate().is_err());
+
+        let colored = EmailDocument {
+            accent_color: Some("blue".into()),
+            ..document(vec![text("Hi")])
+        };
+        let err = colored.validate().unwrap_err();
+        assert!(matches!(err, DomainError::InvalidField { ref field, .. } if field == "accent_color"));
+    }
+}
@@ -0,0 +1,273 @@
+//! Email Thread Domain - grouping email into conversations and timing the replies
+//!
+//! Email entries are threaded by their headers: a message joins the thread
+//! of any message its `In-Reply-To` or `References` names. A message whose
+//! headers name nothing on record (mail logged without headers, or a reply
+//! to mail that never reached the CRM) falls back to its subject, with any
+//! `Re:` or `Fwd:` prefixes dropped.
+//!
+//! A response time runs from the first message of one side that is still
+//! unanswered to the next message from the other side, so a follow-up sent
+//! while waiting doesn't restart the clock.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Prefixes mail clients put before the subject of replies and forwards
+const REPLY_PREFIXES: [&str; 6] = ["re:", "fw:", "fwd:", "aw:", "sv:", "vs:"];
+
+/// Which way an email went
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailDirection {
+    /// From us to the contact
+    Sent,
+    /// From the contact to us
+    Received,
+}
+
+/// What threading needs to know about one email
+#[derive(Debug, Clone)]
+pub struct ThreadEmail<'a> {
+    /// IDs the message is known by: its `Message-ID`, and any ID the mailbox gave it
+    pub ids: Vec<&'a str>,
+    /// The IDs in its `In-Reply-To` and `References` headers
+    pub parents: Vec<&'a str>,
+    pub subject: &'a str,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// The subject without reply and forward prefixes, lowercased
+pub fn normalize_subject(subject: &str) -> String {
+    let mut rest = subject.trim();
+    'strip: loop {
+        for prefix in REPLY_PREFIXES {
+            if rest.get(..prefix.len()).is_some_and(|head| head.eq_ignore_ascii_case(prefix)) {
+                rest = rest[prefix.len()..].trim_start();
+                continue 'strip;
+            }
+        }
+        break;
+    }
+    rest.to_lowercase()
+}
+
+/// Group emails into threads, as indexes into `emails`
+///
+/// Each thread lists its emails oldest first; the threads come most recent
+/// activity first.
+pub fn group_threads(emails: &[ThreadEmail]) -> Vec<Vec<usize>> {
+    let mut parent: Vec<usize> = (0..emails.len()).collect();
+
+    let mut by_id: HashMap<&str, usize> = HashMap::new();
+    for (i, email) in emails.iter().enumerate() {
+        for id in &email.ids {
+            match by_id.get(id) {
+                Some(&other) => union(&mut parent, i, other),
+                None => {
+                    by_id.insert(id, i);
+                }
+            }
+        }
+    }
+
+    let mut by_subject: HashMap<String, usize> = HashMap::new();
+    for (i, email) in emails.iter().enumerate() {
+        let mut linked = false;
+        for id in &email.parents {
+            if let Some(&other) = by_id.get(id) {
+                union(&mut parent, i, other);
+                linked = true;
+            }
+        }
+
+        let subject = normalize_subject(email.subject);
+        if !linked && !subject.is_empty() {
+            match by_subject.get(&subject) {
+                Some(&other) => union(&mut parent, i, other),
+                None => {
+                    by_subject.insert(subject, i);
+                }
+            }
+        }
+    }
+
+    let mut threads: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..emails.len() {
+        let root = find(&mut parent, i);
+        threads.entry(root).or_default().push(i);
+    }
+
+    let mut threads: Vec<Vec<usize>> = threads.into_values().collect();
+    for thread in &mut threads {
+        thread.sort_by_key(|&i| (emails[i].timestamp, i));
+    }
+    threads.sort_by_key(|thread| {
+        let last = thread[thread.len() - 1];
+        std::cmp::Reverse((emails[last].timestamp, last))
+    });
+    threads
+}
+
+fn find(parent: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    let mut node = i;
+    while parent[node] != root {
+        let next = parent[node];
+        parent[node] = root;
+        node = next;
+    }
+    root
+}
+
+fn union(parent: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parent, a), find(parent, b));
+    if a != b {
+        parent[a.max(b)] = a.min(b);
+    }
+}
+
+/// How long each side of a thread took to answer the other, in minutes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResponseTimes {
+    /// From their message to our reply
+    pub ours: Vec<f64>,
+    /// From our message to their reply
+    pub theirs: Vec<f64>,
+}
+
+impl ResponseTimes {
+    /// Response times of one thread; `emails` must be oldest first
+    pub fn of_thread(emails: &[(EmailDirection, DateTime<Utc>)]) -> Self {
+        let mut times = Self::default();
+        let mut waiting: Option<(EmailDirection, DateTime<Utc>)> = None;
+
+        for &(direction, at) in emails {
+            match waiting {
+                Some((from, since)) if from != direction => {
+                    let minutes = (at - since).num_seconds().max(0) as f64 / 60.0;
+                    match direction {
+                        EmailDirection::Sent => times.ours.push(minutes),
+                        EmailDirection::Received => times.theirs.push(minutes),
+                    }
+                    waiting = Some((direction, at));
+                }
+                Some(_) => {}
+                None => waiting = Some((direction, at)),
+            }
+        }
+        times
+    }
+
+    pub fn extend(&mut self, other: ResponseTimes) {
+        self.ours.extend(other.ours);
+        self.theirs.extend(other.theirs);
+    }
+}
+
+/// Mean of some response times, `None` when there are none
+pub fn average_minutes(minutes: &[f64]) -> Option<f64> {
+    (!minutes.is_empty()).then(|| minutes.iter().sum::<f64>() / minutes.len() as f64)
+}
+
+/// Median of some response times, `None` when there are none
+pub fn median_minutes(minutes: &[f64]) -> Option<f64> {
+    if minutes.is_empty() {
+        return None;
+    }
+
+    let mut sorted = minutes.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    Some(if sorted.len() % 2 == 1 {
+        sorted[mid]
+    } else {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap() + Duration::minutes(minutes)
+    }
+
+    fn email<'a>(ids: &[&'a str], parents: &[&'a str], subject: &'a str, minutes: i64) -> ThreadEmail<'a> {
+        ThreadEmail {
+            ids: ids.to_vec(),
+            parents: parents.to_vec(),
+            subject,
+            timestamp: at(minutes),
+        }
+    }
+
+    #[test]
+    fn test_normalize_subject() {
+        assert_eq!(normalize_subject("Re: RE: Fwd:  Pricing "), "pricing");
+        assert_eq!(normalize_subject("AW: Angebot"), "angebot");
+        assert_eq!(normalize_subject("Resources"), "resources");
+        assert_eq!(normalize_subject("  "), "");
+    }
+
+    #[test]
+    fn test_threads_by_headers() {
+        let emails = vec![
+            email(&["<1@us>"], &[], "Pricing", 0),
+            email(&["<2@them>"], &["<1@us>"], "Re: Pricing", 60),
+            // Different subject, but replies to the pricing thread
+            email(&["<3@us>"], &["<1@us>", "<2@them>"], "Contract draft", 90),
+            email(&["<4@us>"], &[], "Pricing", 30),
+            email(&["<5@them>"], &["<9@elsewhere>"], "Intro", 10),
+        ];
+
+        let threads = group_threads(&emails);
+        // <4@us> has no headers pointing anywhere, so it joins by subject
+        assert_eq!(threads, vec![vec![0, 3, 1, 2], vec![4]]);
+    }
+
+    #[test]
+    fn test_threads_without_headers_by_subject() {
+        let emails = vec![
+            email(&[], &[], "Welcome", 0),
+            email(&[], &[], "Re: welcome", 5),
+            email(&[], &[], "", 6),
+            email(&[], &[], "", 7),
+        ];
+
+        // Emails without a subject can't be matched and stay on their own
+        assert_eq!(group_threads(&emails), vec![vec![3], vec![2], vec![0, 1]]);
+    }
+
+    #[test]
+    fn test_response_times() {
+        use EmailDirection::*;
+        let times = ResponseTimes::of_thread(&[
+            (Sent, at(0)),
+            // A follow-up doesn't restart their clock
+            (Sent, at(30)),
+            (Received, at(120)),
+            (Sent, at(150)),
+            (Sent, at(160)),
+        ]);
+
+        assert_eq!(times.theirs, vec![120.0]);
+        assert_eq!(times.ours, vec![30.0]);
+    }
+
+    #[test]
+    fn test_average_and_median() {
+        assert_eq!(average_minutes(&[]), None);
+        assert_eq!(average_minutes(&[10.0, 20.0, 60.0]), Some(30.0));
+        assert_eq!(median_minutes(&[60.0, 10.0, 20.0]), Some(20.0));
+        assert_eq!(median_minutes(&[10.0, 20.0]), Some(15.0));
+    }
+}
@@ -11,14 +11,16 @@
 
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 /// Types of interactions that affect engagement
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum InteractionType {
     EmailSent,
     EmailOpen,
     EmailClick,
+    EmailReceived,
     LandingPageVisit,
     FormSubmission,
     EventRegistration,
@@ -44,6 +46,7 @@ impl InteractionType {
             InteractionType::EmailOpen => 3.0,
             InteractionType::EmailClick => 5.0,
             InteractionType::LandingPageVisit => 4.0,
+            InteractionType::EmailReceived => 6.0,
             InteractionType::SocialInteraction => 3.0,
 
             // Active (they took action)
@@ -64,6 +67,7 @@ impl InteractionType {
             self,
             InteractionType::EmailOpen
                 | InteractionType::EmailClick
+                | InteractionType::EmailReceived
                 | InteractionType::LandingPageVisit
                 | InteractionType::FormSubmission
                 | InteractionType::EventRegistration
@@ -80,6 +84,8 @@ impl InteractionType {
 pub struct Interaction {
     pub interaction_type: InteractionType,
     pub occurred_at: DateTime<Utc>,
+    /// Multiplier on the base score, e.g. from a call's outcome and length
+    pub weight: f64,
 }
 
 impl Interaction {
@@ -87,8 +93,14 @@ impl Interaction {
         Self {
             interaction_type,
             occurred_at,
+            weight: 1.0,
         }
     }
+
+    pub fn with_weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
 }
 
 /// Configuration for the engagement scoring algorithm
@@ -124,7 +136,7 @@ impl Default for EngagementConfig {
 /// # Algorithm
 ///
 /// 1. For each interaction:
-///    - Start with base_score for the interaction type
+///    - Start with base_score for the interaction type, times its weight
 ///    - Apply time decay: score * 0.5^(days_ago / half_life)
 ///
 /// 2. Sum all decayed scores
@@ -155,20 +167,12 @@ pub fn calculate_engagement_score(
     }
 
     let now = Utc::now();
-    let half_life_seconds = config.half_life_days * 24.0 * 60.0 * 60.0;
 
     // Calculate time-decayed score for each interaction
-    let mut raw_score = 0.0;
-
-    for interaction in interactions {
-        let base = interaction.interaction_type.base_score();
-
-        // Calculate time decay
-        let seconds_ago = (now - interaction.occurred_at).num_seconds().max(0) as f64;
-        let decay_factor = 0.5_f64.powf(seconds_ago / half_life_seconds);
-
-        raw_score += base * decay_factor;
-    }
+    let mut raw_score: f64 = interactions
+        .iter()
+        .map(|interaction| decayed_score(interaction, now, config))
+        .sum();
 
     // Apply consistency bonus
     let consistency = calculate_consistency_factor(interactions, config);
@@ -179,6 +183,15 @@ pub fn calculate_engagement_score(
     normalized.clamp(0.0, 100.0)
 }
 
+/// Base score of one interaction after time decay
+fn decayed_score(interaction: &Interaction, now: DateTime<Utc>, config: &EngagementConfig) -> f64 {
+    let half_life_seconds = config.half_life_days * 24.0 * 60.0 * 60.0;
+    let seconds_ago = (now - interaction.occurred_at).num_seconds().max(0) as f64;
+    let decay_factor = 0.5_f64.powf(seconds_ago / half_life_seconds);
+
+    interaction.interaction_type.base_score() * interaction.weight * decay_factor
+}
+
 /// Calculate a consistency factor based on interaction distribution
 ///
 /// Contacts who engage regularly get a bonus vs those with sporadic activity
@@ -219,7 +232,7 @@ fn calculate_consistency_factor(
 // ============================================================================
 
 /// Engagement level categories
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum EngagementLevel {
     Cold,      // 0-20
@@ -253,7 +266,7 @@ impl EngagementLevel {
 }
 
 /// Trend direction for engagement
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum EngagementTrend {
     Declining,
@@ -300,7 +313,7 @@ pub fn calculate_engagement_trend(
 }
 
 // ============================================================================
-// YOUR TURN: Implement these functions
+// Velocity and drivers
 // ============================================================================
 
 /// Calculate engagement velocity - how fast is engagement changing?
@@ -310,51 +323,110 @@ pub fn calculate_engagement_trend(
 /// - Zero: engagement is stable
 /// - Negative: engagement is decelerating
 ///
-/// # Algorithm Hint:
-/// Compare the score change in the last 15 days vs the prior 15 days.
-/// Velocity = recent_change - older_change
-///
-/// YOUR IMPLEMENTATION:
+/// Scores the last 15 days, 15-30 days ago and 30-45 days ago separately,
+/// then compares the most recent change with the one before it:
+/// `(s0_15 - s15_30) - (s15_30 - s30_45)`.
 pub fn calculate_engagement_velocity(
-    _interactions: &[Interaction],
-    _config: &EngagementConfig,
+    interactions: &[Interaction],
+    config: &EngagementConfig,
 ) -> f64 {
-    // TODO: Implement this function
-    //
-    // Steps:
-    // 1. Split interactions into 3 periods: last 15 days, 15-30 days, 30-45 days
-    // 2. Calculate score for each period
-    // 3. recent_change = score_0_15 - score_15_30
-    // 4. older_change = score_15_30 - score_30_45
-    // 5. velocity = recent_change - older_change
-    //
-    // Test by running: cargo test test_engagement_velocity
-
-    todo!("Implement calculate_engagement_velocity")
+    let now = Utc::now();
+
+    let period_score = |from_days: i64, to_days: i64| {
+        let newest = now - Duration::days(from_days);
+        let oldest = now - Duration::days(to_days);
+        let in_period: Vec<_> = interactions
+            .iter()
+            .filter(|i| i.occurred_at > oldest && i.occurred_at <= newest)
+            .cloned()
+            .collect();
+        calculate_engagement_score(&in_period, config)
+    };
+
+    let recent = period_score(0, 15);
+    let middle = period_score(15, 30);
+    let oldest = period_score(30, 45);
+
+    let recent_change = recent - middle;
+    let older_change = middle - oldest;
+
+    recent_change - older_change
 }
 
 /// Identify the most impactful interaction types for a contact
 ///
-/// Returns interaction types sorted by their contribution to the total score.
-/// Useful for understanding what's driving engagement.
-///
-/// YOUR IMPLEMENTATION:
+/// Returns interaction types sorted by their time-decayed contribution to
+/// the raw score, highest first. Useful for understanding what's driving
+/// engagement.
 pub fn identify_top_interaction_types(
-    _interactions: &[Interaction],
-    _config: &EngagementConfig,
-    _top_n: usize,
+    interactions: &[Interaction],
+    config: &EngagementConfig,
+    top_n: usize,
 ) -> Vec<(InteractionType, f64)> {
-    // TODO: Implement this function
-    //
-    // Steps:
-    // 1. Group interactions by type
-    // 2. Calculate the decayed score contribution for each type
-    // 3. Sort by contribution descending
-    // 4. Return top N
-    //
-    // Test by running: cargo test test_top_interaction_types
-
-    todo!("Implement identify_top_interaction_types")
+    decayed_totals(interactions, config)
+        .into_iter()
+        .take(top_n)
+        .map(|(interaction_type, _, raw)| (interaction_type, raw))
+        .collect()
+}
+
+/// One interaction type's share of an engagement score
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ScoreContribution {
+    pub interaction_type: InteractionType,
+    pub count: usize,
+    /// Points of the final 0-100 score; contributions sum to the score
+    pub points: f64,
+    /// Share of the score, 0-100
+    pub percentage: f64,
+}
+
+/// Split a contact's engagement score by interaction type
+///
+/// Each type gets its share of the decayed raw score, scaled so the points
+/// add up to `calculate_engagement_score` (consistency bonus and the
+/// 100 cap included). Sorted by points, highest first.
+pub fn engagement_breakdown(interactions: &[Interaction], config: &EngagementConfig) -> Vec<ScoreContribution> {
+    let totals = decayed_totals(interactions, config);
+    let raw_total: f64 = totals.iter().map(|(_, _, raw)| raw).sum();
+    if raw_total <= 0.0 {
+        return Vec::new();
+    }
+
+    let score = calculate_engagement_score(interactions, config);
+
+    totals
+        .into_iter()
+        .map(|(interaction_type, count, raw)| {
+            let share = raw / raw_total;
+            ScoreContribution {
+                interaction_type,
+                count,
+                points: score * share,
+                percentage: share * 100.0,
+            }
+        })
+        .collect()
+}
+
+/// Decayed score and count per interaction type, highest score first
+fn decayed_totals(interactions: &[Interaction], config: &EngagementConfig) -> Vec<(InteractionType, usize, f64)> {
+    let now = Utc::now();
+    let mut totals: Vec<(InteractionType, usize, f64)> = Vec::new();
+
+    for interaction in interactions {
+        let score = decayed_score(interaction, now, config);
+        match totals.iter_mut().find(|(t, _, _)| *t == interaction.interaction_type) {
+            Some((_, count, total)) => {
+                *count += 1;
+                *total += score;
+            }
+            None => totals.push((interaction.interaction_type, 1, score)),
+        }
+    }
+
+    totals.sort_by(|a, b| b.2.total_cmp(&a.2));
+    totals
 }
 
 // ============================================================================
@@ -422,6 +494,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_weight_scales_an_interaction() {
+        let config = EngagementConfig::default();
+
+        let call = vec![make_interaction(InteractionType::CallCompleted, 0)];
+        let missed = vec![make_interaction(InteractionType::CallCompleted, 0).with_weight(0.1)];
+
+        let call_score = calculate_engagement_score(&call, &config);
+        let missed_score = calculate_engagement_score(&missed, &config);
+
+        assert!((missed_score - call_score * 0.1).abs() < 0.001);
+    }
+
     #[test]
     fn test_score_is_clamped_to_100() {
         let config = EngagementConfig::default();
@@ -497,10 +582,9 @@ mod tests {
         assert!(!InteractionType::NoteAdded.is_inbound());
     }
 
-    // ---- YOUR TESTS ----
+    // ---- Velocity and Driver Tests ----
 
     #[test]
-    #[ignore] // Remove after implementing calculate_engagement_velocity
     fn test_engagement_velocity() {
         let config = EngagementConfig::default();
 
@@ -536,7 +620,12 @@ mod tests {
     }
 
     #[test]
-    #[ignore] // Remove after implementing identify_top_interaction_types
+    fn test_no_activity_has_zero_velocity() {
+        let velocity = calculate_engagement_velocity(&[], &EngagementConfig::default());
+        assert_eq!(velocity, 0.0);
+    }
+
+    #[test]
     fn test_top_interaction_types() {
         let config = EngagementConfig::default();
 
@@ -554,4 +643,30 @@ mod tests {
         assert_eq!(top.len(), 2);
         assert_eq!(top[0].0, InteractionType::MeetingAttended);
     }
+
+    #[test]
+    fn test_breakdown_sums_to_score() {
+        let config = EngagementConfig::default();
+
+        let interactions = vec![
+            make_interaction(InteractionType::EmailOpen, 1),
+            make_interaction(InteractionType::EmailOpen, 3),
+            make_interaction(InteractionType::FormSubmission, 2),
+            make_interaction(InteractionType::EmailSent, 10),
+        ];
+
+        let breakdown = engagement_breakdown(&interactions, &config);
+        let score = calculate_engagement_score(&interactions, &config);
+        let total: f64 = breakdown.iter().map(|c| c.points).sum();
+
+        assert_eq!(breakdown[0].interaction_type, InteractionType::FormSubmission);
+        assert_eq!(breakdown[1].count, 2);
+        assert!((total - score).abs() < 1e-9);
+        assert!((breakdown.iter().map(|c| c.percentage).sum::<f64>() - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_breakdown_of_nothing_is_empty() {
+        assert!(engagement_breakdown(&[], &EngagementConfig::default()).is_empty());
+    }
 }
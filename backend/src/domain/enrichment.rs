@@ -0,0 +1,102 @@
+//! Enrichment Domain - filling in contacts from their LinkedIn profile
+//!
+//! Enrichment only ever fills gaps. A field is written when it is empty,
+//! or when it still holds exactly what the previous enrichment put there;
+//! a value someone typed in by hand is never replaced.
+
+use serde::{Deserialize, Serialize};
+
+/// Custom field holding the contact's LinkedIn headline
+pub const HEADLINE_FIELD: &str = "headline";
+
+/// Custom field holding where the contact is based
+pub const LOCATION_FIELD: &str = "location";
+
+/// What a profile says about a contact; also what a contact currently has
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EnrichedProfile {
+    pub headline: Option<String>,
+    /// Name of the current employer
+    pub company: Option<String>,
+    pub location: Option<String>,
+}
+
+impl EnrichedProfile {
+    /// Trim every value and drop the blank ones
+    pub fn normalized(self) -> Self {
+        let clean = |v: Option<String>| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        Self {
+            headline: clean(self.headline),
+            company: clean(self.company),
+            location: clean(self.location),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.headline.is_none() && self.company.is_none() && self.location.is_none()
+    }
+}
+
+/// The fields of `found` that may be written to a contact
+///
+/// `current` is what the contact has now and `previous` what the last
+/// enrichment filled in. A field is kept when the contact has a value of
+/// its own, i.e. one that differs from the previous enrichment's.
+pub fn fields_to_fill(current: &EnrichedProfile, previous: &EnrichedProfile, found: &EnrichedProfile) -> EnrichedProfile {
+    fn pick(current: &Option<String>, previous: &Option<String>, found: &Option<String>) -> Option<String> {
+        let found = found.as_ref()?;
+        let untouched = match current {
+            None => true,
+            Some(value) => previous.as_ref() == Some(value),
+        };
+        (untouched && current.as_ref() != Some(found)).then(|| found.clone())
+    }
+
+    EnrichedProfile {
+        headline: pick(&current.headline, &previous.headline, &found.headline),
+        company: pick(&current.company, &previous.company, &found.company),
+        location: pick(&current.location, &previous.location, &found.location),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(headline: Option<&str>, company: Option<&str>, location: Option<&str>) -> EnrichedProfile {
+        EnrichedProfile {
+            headline: headline.map(str::to_string),
+            company: company.map(str::to_string),
+            location: location.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_fills_empty_fields_only() {
+        let current = profile(None, Some("Acme"), None);
+        let found = profile(Some("CTO at Globex"), Some("Globex"), Some("Stockholm"));
+
+        let fill = fields_to_fill(&current, &EnrichedProfile::default(), &found);
+
+        assert_eq!(fill, profile(Some("CTO at Globex"), None, Some("Stockholm")));
+    }
+
+    #[test]
+    fn test_refreshes_values_enrichment_set() {
+        // Headline untouched since the last run; location edited by hand
+        let previous = profile(Some("Engineer"), None, Some("Berlin"));
+        let current = profile(Some("Engineer"), None, Some("Hamburg"));
+        let found = profile(Some("Staff Engineer"), None, Some("Munich"));
+
+        let fill = fields_to_fill(&current, &previous, &found);
+
+        assert_eq!(fill, profile(Some("Staff Engineer"), None, None));
+    }
+
+    #[test]
+    fn test_normalized() {
+        let p = profile(Some("  "), Some(" Acme "), None).normalized();
+        assert_eq!(p, profile(None, Some("Acme"), None));
+        assert!(EnrichedProfile::default().is_empty());
+    }
+}
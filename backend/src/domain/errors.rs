@@ -20,6 +20,64 @@ pub enum DomainError {
 
     /// A business rule was violated
     BusinessRuleViolation { rule: String, details: String },
+
+    /// Several fields failed validation at once
+    Violations(Vec<DomainError>),
+
+    /// Another contact in the workspace already has this email
+    DuplicateEmail { email: String, existing_id: String },
+}
+
+impl DomainError {
+    /// Collapse collected violations: None when there are none, the error
+    /// itself when there is exactly one
+    pub fn from_violations(mut violations: Vec<DomainError>) -> Option<DomainError> {
+        match violations.len() {
+            0 => None,
+            1 => violations.pop(),
+            _ => Some(DomainError::Violations(violations)),
+        }
+    }
+
+    /// Report a field error under a different field path, e.g. `tags[2]`
+    pub fn at(self, path: impl Into<String>) -> Self {
+        match self {
+            DomainError::RequiredFieldMissing { .. } => DomainError::RequiredFieldMissing { field: path.into() },
+            DomainError::InvalidField { reason, .. } => DomainError::InvalidField {
+                field: path.into(),
+                reason,
+            },
+            other => other,
+        }
+    }
+
+    /// Field the error is about, for field-level errors
+    pub fn field(&self) -> Option<&str> {
+        match self {
+            DomainError::RequiredFieldMissing { field } | DomainError::InvalidField { field, .. } => Some(field),
+            _ => None,
+        }
+    }
+
+    /// Stable machine-readable code
+    pub fn code(&self) -> &'static str {
+        match self {
+            DomainError::RequiredFieldMissing { .. } => "required",
+            DomainError::InvalidField { .. } => "invalid",
+            DomainError::InvalidStateTransition { .. } => "invalid_transition",
+            DomainError::BusinessRuleViolation { .. } => "business_rule",
+            DomainError::Violations(_) => "invalid",
+            DomainError::DuplicateEmail { .. } => "duplicate_email",
+        }
+    }
+
+    /// The individual errors, flattening `Violations`
+    pub fn violations(&self) -> Vec<&DomainError> {
+        match self {
+            DomainError::Violations(errors) => errors.iter().flat_map(|e| e.violations()).collect(),
+            other => vec![other],
+        }
+    }
 }
 
 impl fmt::Display for DomainError {
@@ -37,6 +95,13 @@ impl fmt::Display for DomainError {
             DomainError::BusinessRuleViolation { rule, details } => {
                 write!(f, "Business rule '{}' violated: {}", rule, details)
             }
+            DomainError::Violations(errors) => {
+                let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                write!(f, "{}", messages.join("; "))
+            }
+            DomainError::DuplicateEmail { email, .. } => {
+                write!(f, "A contact with email '{}' already exists", email)
+            }
         }
     }
 }
@@ -60,8 +125,3 @@ pub type DomainResult<T> = Result<T, DomainError>;
 //
 // A2: _______________________________________________________________
 //     _______________________________________________________________
-//
-// Q3: Add a new error variant for "DuplicateEmail" below:
-//
-// YOUR CODE HERE:
-//
@@ -0,0 +1,516 @@
+//! Event Domain - Who gets a seat at an event
+//!
+//! An event can cap how many contacts attend and close registration at a
+//! deadline. Registrations beyond the cap go on a waitlist, which is worked
+//! through in order as registered contacts cancel.
+//!
+//! Contacts are checked in at the door, or their attendance is synced
+//! afterwards.
+//!
+//! Registered contacts are reminded ahead of the start, and attendees and
+//! no-shows get a follow-up once the event is over.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::errors::{DomainError, DomainResult};
+
+/// Furthest ahead of an event a reminder may go out: 30 days, in minutes
+pub const MAX_REMINDER_OFFSET_MINUTES: u32 = 30 * 24 * 60;
+
+/// Most reminders one event may send before it starts
+const MAX_REMINDERS: usize = 5;
+
+/// How long before an event starts check-in opens, in minutes
+const CHECK_IN_OPENS_MINUTES: i64 = 60;
+
+/// How long after it is due a follow-up still goes out, e.g. when
+/// attendance is recorded late
+pub const FOLLOW_UP_WINDOW_DAYS: i64 = 7;
+
+/// Where a contact stands with an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RsvpStatus {
+    Invited,
+    Registered,
+    /// Asked to register while the event was full; moved up as seats free
+    Waitlisted,
+    Attended,
+    NoShow,
+    Cancelled,
+}
+
+impl RsvpStatus {
+    /// Whether the contact holds one of the event's seats
+    pub fn takes_seat(&self) -> bool {
+        matches!(self, RsvpStatus::Registered | RsvpStatus::Attended)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RsvpStatus::Invited => "invited",
+            RsvpStatus::Registered => "registered",
+            RsvpStatus::Waitlisted => "waitlisted",
+            RsvpStatus::Attended => "attended",
+            RsvpStatus::NoShow => "no_show",
+            RsvpStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl std::fmt::Display for RsvpStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Capacity and registration deadline of an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegistrationRules {
+    /// None means unlimited
+    pub max_attendees: Option<u32>,
+    /// None means registration stays open
+    pub registration_deadline: Option<DateTime<Utc>>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+}
+
+impl RegistrationRules {
+    /// Business rules for creating an event
+    ///
+    /// # Rules:
+    /// - The event ends after it starts
+    /// - A capacity, if set, is at least 1
+    /// - Registration closes no later than the event starts
+    pub fn validate(&self) -> DomainResult<()> {
+        let mut violations = Vec::new();
+
+        if self.end_time <= self.start_time {
+            violations.push(DomainError::InvalidField {
+                field: "end_time".to_string(),
+                reason: "The event must end after it starts".to_string(),
+            });
+        }
+
+        if self.max_attendees == Some(0) {
+            violations.push(DomainError::InvalidField {
+                field: "max_attendees".to_string(),
+                reason: "Capacity must be at least 1".to_string(),
+            });
+        }
+
+        if self.registration_deadline.is_some_and(|d| d > self.start_time) {
+            violations.push(DomainError::InvalidField {
+                field: "registration_deadline".to_string(),
+                reason: "Registration must close before the event starts".to_string(),
+            });
+        }
+
+        match DomainError::from_violations(violations) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// The status an RSVP request results in
+    ///
+    /// `seats_taken` counts seats held by other contacts.
+    ///
+    /// # Business Rules:
+    /// - Registering after the deadline is refused
+    /// - Registering for a full event puts the contact on the waitlist
+    /// - Nobody asks to be waitlisted; it only happens when the event is full
+    /// - Attendance is final: attended or no-show RSVPs can't be cancelled
+    pub fn resolve(
+        &self,
+        current: Option<RsvpStatus>,
+        requested: RsvpStatus,
+        seats_taken: u64,
+        now: DateTime<Utc>,
+    ) -> DomainResult<RsvpStatus> {
+        match requested {
+            RsvpStatus::Waitlisted => Err(DomainError::BusinessRuleViolation {
+                rule: "waitlist".to_string(),
+                details: "Contacts are waitlisted automatically when the event is full".to_string(),
+            }),
+            RsvpStatus::Registered => {
+                if current == Some(RsvpStatus::Registered) {
+                    return Ok(RsvpStatus::Registered);
+                }
+                if let Some(deadline) = self.registration_deadline.filter(|d| now > *d) {
+                    return Err(DomainError::BusinessRuleViolation {
+                        rule: "registration_closed".to_string(),
+                        details: format!("Registration closed at {}", deadline.to_rfc3339()),
+                    });
+                }
+                match self.open_seats(seats_taken) {
+                    Some(0) => Ok(RsvpStatus::Waitlisted),
+                    _ => Ok(RsvpStatus::Registered),
+                }
+            }
+            RsvpStatus::Cancelled => match current {
+                Some(from @ (RsvpStatus::Attended | RsvpStatus::NoShow)) => {
+                    Err(DomainError::InvalidStateTransition {
+                        from: from.to_string(),
+                        to: requested.to_string(),
+                        reason: "Attendance has already been recorded".to_string(),
+                    })
+                }
+                _ => Ok(RsvpStatus::Cancelled),
+            },
+            other => Ok(other),
+        }
+    }
+
+    /// Whether checking a contact in changes their RSVP to attended
+    ///
+    /// # Business Rules:
+    /// - Check-in opens an hour before the start; attendance can be synced
+    ///   any time after
+    /// - Anyone can be checked in, including walk-ins without an RSVP and
+    ///   contacts who cancelled or were waitlisted
+    /// - Checking in twice changes nothing
+    pub fn check_in(&self, current: Option<RsvpStatus>, now: DateTime<Utc>) -> DomainResult<bool> {
+        if now < self.start_time - chrono::Duration::minutes(CHECK_IN_OPENS_MINUTES) {
+            return Err(DomainError::BusinessRuleViolation {
+                rule: "check_in_not_open".to_string(),
+                details: format!(
+                    "Check-in opens {} minutes before the event starts",
+                    CHECK_IN_OPENS_MINUTES
+                ),
+            });
+        }
+
+        Ok(current != Some(RsvpStatus::Attended))
+    }
+
+    /// Whether a change of RSVP gives a seat back to the waitlist
+    ///
+    /// # Business Rules:
+    /// - Only a contact who held a seat and no longer does frees one
+    /// - Seats aren't handed out once the event has started, so recording
+    ///   attendance afterwards (attended to no-show) frees nothing
+    pub fn frees_seat(&self, previous: Option<RsvpStatus>, status: RsvpStatus, now: DateTime<Utc>) -> bool {
+        now < self.start_time && previous.is_some_and(|p| p.takes_seat()) && !status.takes_seat()
+    }
+
+    /// Seats still free, or None when the event has no cap
+    pub fn open_seats(&self, seats_taken: u64) -> Option<u64> {
+        self.max_attendees
+            .map(|max| u64::from(max).saturating_sub(seats_taken))
+    }
+}
+
+/// When an event's reminder and follow-up emails go out
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct EventReminders {
+    /// Minutes before the start a reminder goes to registered contacts
+    pub before_start_minutes: Vec<u32>,
+    /// Minutes after the end a follow-up goes to attendees and no-shows;
+    /// null sends none
+    pub follow_up_after_minutes: Option<u32>,
+}
+
+impl Default for EventReminders {
+    /// A day and an hour before, and a follow-up an hour after
+    fn default() -> Self {
+        Self {
+            before_start_minutes: vec![24 * 60, 60],
+            follow_up_after_minutes: Some(60),
+        }
+    }
+}
+
+/// An email the reminder scheduler sends for an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventEmail {
+    Reminder { minutes_before: u32 },
+    FollowUp { attended: bool },
+}
+
+impl EventEmail {
+    /// How a sent email is remembered on the RSVP
+    pub fn key(&self) -> String {
+        match self {
+            EventEmail::Reminder { minutes_before } => format!("reminder_{}", minutes_before),
+            EventEmail::FollowUp { .. } => "follow_up".to_string(),
+        }
+    }
+}
+
+impl EventReminders {
+    /// Business rules for an event's reminder schedule
+    ///
+    /// # Rules:
+    /// - At most 5 reminders, each at a different offset
+    /// - Offsets are between one minute and 30 days
+    pub fn validate(&self) -> DomainResult<()> {
+        let mut violations = Vec::new();
+
+        if self.before_start_minutes.len() > MAX_REMINDERS {
+            violations.push(DomainError::InvalidField {
+                field: "reminders.before_start_minutes".to_string(),
+                reason: format!("At most {} reminders can be scheduled", MAX_REMINDERS),
+            });
+        }
+
+        for (i, &minutes) in self.before_start_minutes.iter().enumerate() {
+            if self.before_start_minutes[..i].contains(&minutes) {
+                violations.push(DomainError::InvalidField {
+                    field: format!("reminders.before_start_minutes[{}]", i),
+                    reason: format!("A reminder {} minutes before is already scheduled", minutes),
+                });
+            }
+        }
+
+        let offsets = self
+            .before_start_minutes
+            .iter()
+            .enumerate()
+            .map(|(i, &m)| (format!("reminders.before_start_minutes[{}]", i), m))
+            .chain(
+                self.follow_up_after_minutes
+                    .map(|m| ("reminders.follow_up_after_minutes".to_string(), m)),
+            );
+        for (field, minutes) in offsets {
+            if minutes == 0 || minutes > MAX_REMINDER_OFFSET_MINUTES {
+                violations.push(DomainError::InvalidField {
+                    field,
+                    reason: format!("Must be between 1 and {} minutes", MAX_REMINDER_OFFSET_MINUTES),
+                });
+            }
+        }
+
+        match DomainError::from_violations(violations) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// The email due for an RSVP, if any
+    ///
+    /// `sent` holds the keys of the emails the RSVP has already had.
+    ///
+    /// # Business Rules:
+    /// - Registered contacts are reminded until the event starts; when
+    ///   several reminders are due at once, e.g. after a late registration,
+    ///   only the one closest to the start goes out
+    /// - Attendees and no-shows get one follow-up after the event ends, for
+    ///   up to a week after it is due
+    /// - Nothing is sent twice
+    pub fn due(
+        &self,
+        status: RsvpStatus,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        sent: &[String],
+        now: DateTime<Utc>,
+    ) -> Option<EventEmail> {
+        let email = match status {
+            RsvpStatus::Registered if now < start_time => self
+                .before_start_minutes
+                .iter()
+                .filter(|&&m| start_time - chrono::Duration::minutes(i64::from(m)) <= now)
+                .min()
+                .map(|&minutes_before| EventEmail::Reminder { minutes_before })?,
+            RsvpStatus::Attended | RsvpStatus::NoShow => {
+                let due_at = end_time + chrono::Duration::minutes(i64::from(self.follow_up_after_minutes?));
+                if now < due_at || now >= due_at + chrono::Duration::days(FOLLOW_UP_WINDOW_DAYS) {
+                    return None;
+                }
+                EventEmail::FollowUp {
+                    attended: status == RsvpStatus::Attended,
+                }
+            }
+            _ => return None,
+        };
+
+        (!sent.contains(&email.key())).then_some(email)
+    }
+
+    /// Keys to remember once `email` is sent: a reminder also stands in for
+    /// the earlier ones that were skipped
+    pub fn settled_by(&self, email: EventEmail) -> Vec<String> {
+        match email {
+            EventEmail::Reminder { minutes_before } => self
+                .before_start_minutes
+                .iter()
+                .filter(|&&m| m >= minutes_before)
+                .map(|&m| EventEmail::Reminder { minutes_before: m }.key())
+                .collect(),
+            EventEmail::FollowUp { .. } => vec![email.key()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn rules(max_attendees: Option<u32>) -> RegistrationRules {
+        let start_time = Utc::now() + Duration::days(7);
+        RegistrationRules {
+            max_attendees,
+            registration_deadline: Some(start_time - Duration::days(1)),
+            start_time,
+            end_time: start_time + Duration::hours(1),
+        }
+    }
+
+    #[test]
+    fn test_validate_rules() {
+        assert!(rules(Some(50)).validate().is_ok());
+        assert!(rules(Some(0)).validate().is_err());
+
+        let mut late = rules(None);
+        late.registration_deadline = Some(late.start_time + Duration::minutes(5));
+        assert!(late.validate().is_err());
+
+        let mut backwards = rules(None);
+        backwards.end_time = backwards.start_time;
+        assert!(backwards.validate().is_err());
+    }
+
+    #[test]
+    fn test_full_event_waitlists() {
+        let now = Utc::now();
+        let event = rules(Some(2));
+
+        assert_eq!(event.resolve(None, RsvpStatus::Registered, 1, now).unwrap(), RsvpStatus::Registered);
+        assert_eq!(event.resolve(None, RsvpStatus::Registered, 2, now).unwrap(), RsvpStatus::Waitlisted);
+        assert_eq!(
+            event.resolve(Some(RsvpStatus::Registered), RsvpStatus::Registered, 2, now).unwrap(),
+            RsvpStatus::Registered
+        );
+        assert_eq!(rules(None).resolve(None, RsvpStatus::Registered, 10_000, now).unwrap(), RsvpStatus::Registered);
+        assert!(event.resolve(None, RsvpStatus::Waitlisted, 0, now).is_err());
+    }
+
+    #[test]
+    fn test_registration_deadline() {
+        let event = rules(Some(10));
+        let after = event.registration_deadline.unwrap() + Duration::seconds(1);
+
+        let err = event.resolve(Some(RsvpStatus::Invited), RsvpStatus::Registered, 0, after).unwrap_err();
+        assert!(matches!(err, DomainError::BusinessRuleViolation { .. }));
+        // Recording attendance isn't registration
+        assert!(event.resolve(None, RsvpStatus::Attended, 10, after).is_ok());
+    }
+
+    #[test]
+    fn test_attendance_is_final() {
+        let event = rules(None);
+        let now = Utc::now();
+
+        assert!(event.resolve(Some(RsvpStatus::Waitlisted), RsvpStatus::Cancelled, 0, now).is_ok());
+        let err = event.resolve(Some(RsvpStatus::Attended), RsvpStatus::Cancelled, 0, now).unwrap_err();
+        assert!(matches!(err, DomainError::InvalidStateTransition { .. }));
+    }
+
+    #[test]
+    fn test_check_in() {
+        let event = rules(Some(1));
+
+        let early = event.start_time - Duration::hours(2);
+        assert!(event.check_in(Some(RsvpStatus::Registered), early).is_err());
+
+        let at_door = event.start_time - Duration::minutes(10);
+        assert!(event.check_in(Some(RsvpStatus::Registered), at_door).unwrap());
+        assert!(event.check_in(None, at_door).unwrap());
+        assert!(event.check_in(Some(RsvpStatus::Cancelled), at_door).unwrap());
+        assert!(!event.check_in(Some(RsvpStatus::Attended), at_door).unwrap());
+
+        // Attendance synced after the event
+        assert!(event.check_in(Some(RsvpStatus::NoShow), event.end_time + Duration::days(2)).unwrap());
+    }
+
+    #[test]
+    fn test_seats_are_only_freed_before_the_event() {
+        let event = rules(Some(3));
+        let before = event.start_time - Duration::hours(1);
+        let after = event.end_time + Duration::hours(1);
+
+        assert!(event.frees_seat(Some(RsvpStatus::Registered), RsvpStatus::Cancelled, before));
+        assert!(!event.frees_seat(Some(RsvpStatus::Waitlisted), RsvpStatus::Cancelled, before));
+        assert!(!event.frees_seat(Some(RsvpStatus::Registered), RsvpStatus::Registered, before));
+        assert!(!event.frees_seat(Some(RsvpStatus::Attended), RsvpStatus::NoShow, after));
+        assert!(!event.frees_seat(Some(RsvpStatus::Registered), RsvpStatus::Cancelled, event.start_time));
+    }
+
+    #[test]
+    fn test_open_seats() {
+        assert_eq!(rules(Some(3)).open_seats(1), Some(2));
+        assert_eq!(rules(Some(3)).open_seats(5), Some(0));
+        assert_eq!(rules(None).open_seats(5), None);
+    }
+
+    #[test]
+    fn test_validate_reminders() {
+        assert!(EventReminders::default().validate().is_ok());
+
+        let invalid = EventReminders {
+            before_start_minutes: vec![60, 0, 60],
+            follow_up_after_minutes: Some(MAX_REMINDER_OFFSET_MINUTES + 1),
+        };
+        let err = invalid.validate().unwrap_err();
+        assert_eq!(err.violations().len(), 3);
+    }
+
+    #[test]
+    fn test_reminders_due_before_start() {
+        let reminders = EventReminders::default();
+        let start = Utc::now() + Duration::days(3);
+        let end = start + Duration::hours(1);
+
+        let early = start - Duration::days(2);
+        assert_eq!(reminders.due(RsvpStatus::Registered, start, end, &[], early), None);
+
+        let day_before = start - Duration::hours(23);
+        let email = reminders.due(RsvpStatus::Registered, start, end, &[], day_before);
+        assert_eq!(email, Some(EventEmail::Reminder { minutes_before: 1440 }));
+        assert_eq!(reminders.due(RsvpStatus::Invited, start, end, &[], day_before), None);
+
+        // Registered late: only the last reminder goes out, and covers the first
+        let last_minute = start - Duration::minutes(30);
+        let email = reminders.due(RsvpStatus::Registered, start, end, &[], last_minute).unwrap();
+        assert_eq!(email, EventEmail::Reminder { minutes_before: 60 });
+        let sent = reminders.settled_by(email);
+        assert_eq!(sent, vec!["reminder_1440", "reminder_60"]);
+        assert_eq!(reminders.due(RsvpStatus::Registered, start, end, &sent, last_minute), None);
+
+        assert_eq!(reminders.due(RsvpStatus::Registered, start, end, &[], start), None);
+    }
+
+    #[test]
+    fn test_follow_up_after_end() {
+        let reminders = EventReminders::default();
+        let start = Utc::now() - Duration::days(1);
+        let end = start + Duration::hours(1);
+        let after = end + Duration::hours(2);
+
+        assert_eq!(
+            reminders.due(RsvpStatus::NoShow, start, end, &[], after),
+            Some(EventEmail::FollowUp { attended: false })
+        );
+        assert_eq!(reminders.due(RsvpStatus::Registered, start, end, &[], after), None);
+        assert_eq!(
+            reminders.due(RsvpStatus::Attended, start, end, &["follow_up".to_string()], after),
+            None
+        );
+        assert_eq!(reminders.due(RsvpStatus::Attended, start, end, &[], end), None);
+        assert_eq!(
+            reminders.due(RsvpStatus::Attended, start, end, &[], after + Duration::days(FOLLOW_UP_WINDOW_DAYS)),
+            None
+        );
+
+        let none = EventReminders {
+            follow_up_after_minutes: None,
+            ..EventReminders::default()
+        };
+        assert_eq!(none.due(RsvpStatus::Attended, start, end, &[], after), None);
+    }
+}
@@ -0,0 +1,445 @@
+//! Landing Page Forms - What a landing page asks its visitors
+//!
+//! A landing page carries a form schema: the fields it shows, their types
+//! and which are required. Submissions are checked against the schema, and
+//! every field that isn't a built-in contact field (name, email, phone,
+//! company, message) lands in the contact's custom fields.
+//!
+//! Hidden fields carry values the page fills in itself, typically the UTM
+//! parameters of the link the visitor arrived through. A honeypot field is
+//! rendered out of sight of people; bots that fill it in give themselves
+//! away.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::errors::{DomainError, DomainResult};
+use super::validation::{validate_custom_field, validate_email, validate_phone};
+
+/// Most fields a form may have
+const MAX_FIELDS: usize = 30;
+
+/// Longest value a free-text field accepts
+const MAX_TEXT_LEN: usize = 5000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FormFieldType {
+    Text,
+    Textarea,
+    Email,
+    Phone,
+    Number,
+    Checkbox,
+    Select,
+    /// Not shown; filled in by the page, e.g. from `utm_*` query parameters
+    Hidden,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FormField {
+    /// Key in the submission; also the custom field it's stored as
+    pub name: String,
+    #[serde(default)]
+    pub label: String,
+    #[serde(rename = "type")]
+    pub field_type: FormFieldType,
+    #[serde(default)]
+    pub required: bool,
+    /// Allowed values of a `select` field
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+}
+
+impl FormField {
+    fn new(name: &str, label: &str, field_type: FormFieldType, required: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            label: label.to_string(),
+            field_type,
+            required,
+            options: Vec::new(),
+        }
+    }
+}
+
+/// The fields of a landing page form, in display order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FormSchema {
+    pub fields: Vec<FormField>,
+    /// Name of a decoy field people never see; submissions filling it are dropped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub honeypot: Option<String>,
+    /// Submissions the page accepts per rate limit window, instead of the configured default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_submissions: Option<u32>,
+}
+
+/// The form pages had before forms were configurable
+impl Default for FormSchema {
+    fn default() -> Self {
+        Self {
+            fields: vec![
+                FormField::new("first_name", "First name", FormFieldType::Text, true),
+                FormField::new("last_name", "Last name", FormFieldType::Text, true),
+                FormField::new("email", "Email", FormFieldType::Email, true),
+                FormField::new("company", "Company", FormFieldType::Text, false),
+                FormField::new("message", "Message", FormFieldType::Textarea, false),
+            ],
+            honeypot: None,
+            max_submissions: None,
+        }
+    }
+}
+
+/// A submission that passed its form's schema
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FormSubmission {
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+    pub phone: Option<String>,
+    pub company: Option<String>,
+    pub message: Option<String>,
+    /// Every other submitted field, keyed by field name
+    pub custom_fields: BTreeMap<String, String>,
+}
+
+impl FormSubmission {
+    /// The submitted custom fields an existing contact doesn't have yet
+    ///
+    /// Anyone can submit a public form with someone's email, so a submission
+    /// fills gaps but never overwrites what the contact already has.
+    pub fn unset_custom_fields(&self, existing: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+        self.custom_fields
+            .iter()
+            .filter(|(key, _)| existing.get(*key).is_none_or(|value| value.trim().is_empty()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
+
+impl FormSchema {
+    /// Check the schema can be rendered and its submissions stored
+    ///
+    /// # Rules:
+    /// - 1-30 fields with unique names usable as custom field keys
+    /// - A required `email` field of type `email`, since submissions create contacts
+    /// - `select` fields list their options
+    pub fn validate(&self) -> DomainResult<()> {
+        let mut violations = Vec::new();
+
+        if self.fields.is_empty() || self.fields.len() > MAX_FIELDS {
+            violations.push(DomainError::InvalidField {
+                field: "form.fields".to_string(),
+                reason: format!("A form must have 1-{} fields", MAX_FIELDS),
+            });
+        }
+
+        let mut seen = HashSet::new();
+        for (i, field) in self.fields.iter().enumerate() {
+            let path = format!("form.fields[{}]", i);
+
+            match validate_custom_field(&field.name, "") {
+                Ok(name) if name != field.name => violations.push(DomainError::InvalidField {
+                    field: format!("{}.name", path),
+                    reason: "Field names must be lowercase".to_string(),
+                }),
+                Ok(_) => {}
+                Err(e) => violations.push(e.at(format!("{}.name", path))),
+            }
+
+            if !seen.insert(field.name.as_str()) {
+                violations.push(DomainError::InvalidField {
+                    field: format!("{}.name", path),
+                    reason: format!("Duplicate field '{}'", field.name),
+                });
+            }
+
+            if field.field_type == FormFieldType::Select && field.options.is_empty() {
+                violations.push(DomainError::RequiredFieldMissing {
+                    field: format!("{}.options", path),
+                });
+            }
+
+            if field.name == "email" && (field.field_type != FormFieldType::Email || !field.required) {
+                violations.push(DomainError::InvalidField {
+                    field: format!("{}.type", path),
+                    reason: "The email field must be a required field of type email".to_string(),
+                });
+            }
+        }
+
+        if let Some(honeypot) = &self.honeypot {
+            let usable = validate_custom_field(honeypot, "").is_ok_and(|name| &name == honeypot);
+            if !usable || seen.contains(honeypot.as_str()) {
+                violations.push(DomainError::InvalidField {
+                    field: "form.honeypot".to_string(),
+                    reason: "The honeypot must be a lowercase name no form field uses".to_string(),
+                });
+            }
+        }
+
+        if self.max_submissions == Some(0) {
+            violations.push(DomainError::InvalidField {
+                field: "form.max_submissions".to_string(),
+                reason: "Must be at least 1".to_string(),
+            });
+        }
+
+        if !seen.contains("email") {
+            violations.push(DomainError::BusinessRuleViolation {
+                rule: "form_requires_email".to_string(),
+                details: "A form needs an email field to create contacts".to_string(),
+            });
+        }
+
+        match DomainError::from_violations(violations) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether a submission filled in the honeypot, i.e. came from a bot
+    pub fn honeypot_filled(&self, values: &serde_json::Map<String, serde_json::Value>) -> bool {
+        let Some(honeypot) = &self.honeypot else { return false };
+
+        match values.get(honeypot) {
+            None | Some(serde_json::Value::Null) => false,
+            Some(serde_json::Value::String(s)) => !s.trim().is_empty(),
+            Some(_) => true,
+        }
+    }
+
+    /// Check a submission against the schema
+    ///
+    /// Values are normalized to strings; keys the schema doesn't define are
+    /// ignored. Every failing field is reported at once.
+    pub fn validate_submission(
+        &self,
+        values: &serde_json::Map<String, serde_json::Value>,
+    ) -> DomainResult<FormSubmission> {
+        let mut violations = Vec::new();
+        let mut submission = FormSubmission::default();
+
+        for field in &self.fields {
+            let value = match normalize_value(field, values.get(&field.name)) {
+                Ok(value) => value,
+                Err(e) => {
+                    violations.push(e);
+                    continue;
+                }
+            };
+
+            let Some(value) = value else {
+                if field.required {
+                    violations.push(DomainError::RequiredFieldMissing {
+                        field: field.name.clone(),
+                    });
+                }
+                continue;
+            };
+
+            match field.name.as_str() {
+                "first_name" => submission.first_name = value,
+                "last_name" => submission.last_name = value,
+                "email" => submission.email = value.to_lowercase(),
+                "phone" => submission.phone = Some(value),
+                "company" => submission.company = Some(value),
+                "message" => submission.message = Some(value),
+                name => match validate_custom_field(name, &value) {
+                    Ok(key) => {
+                        submission.custom_fields.insert(key, value);
+                    }
+                    Err(e) => violations.push(e.at(name)),
+                },
+            }
+        }
+
+        match DomainError::from_violations(violations) {
+            Some(err) => Err(err),
+            None => Ok(submission),
+        }
+    }
+}
+
+/// A submitted value as a string, None when it's missing or blank
+fn normalize_value(field: &FormField, value: Option<&serde_json::Value>) -> DomainResult<Option<String>> {
+    let invalid = |reason: &str| DomainError::InvalidField {
+        field: field.name.clone(),
+        reason: reason.to_string(),
+    };
+
+    let text = match value {
+        None | Some(serde_json::Value::Null) => return Ok(None),
+        Some(serde_json::Value::String(s)) => s.trim().to_string(),
+        Some(serde_json::Value::Bool(b)) => b.to_string(),
+        Some(serde_json::Value::Number(n)) => n.to_string(),
+        Some(_) => return Err(invalid("Must be a single value")),
+    };
+    if text.is_empty() {
+        return Ok(None);
+    }
+    if text.chars().count() > MAX_TEXT_LEN {
+        return Err(invalid("Value is too long"));
+    }
+
+    let value = match field.field_type {
+        FormFieldType::Email => {
+            validate_email(&text).map_err(|e| e.at(field.name.clone()))?;
+            text
+        }
+        FormFieldType::Phone => {
+            validate_phone(Some(&text)).map_err(|e| e.at(field.name.clone()))?;
+            text
+        }
+        FormFieldType::Number => match text.parse::<f64>() {
+            Ok(n) if n.is_finite() => text,
+            _ => return Err(invalid("Must be a number")),
+        },
+        FormFieldType::Checkbox => match text.to_lowercase().as_str() {
+            "true" | "on" | "yes" | "1" => "true".to_string(),
+            // An unticked required checkbox counts as missing
+            "false" | "off" | "no" | "0" => return Ok(None),
+            _ => return Err(invalid("Must be true or false")),
+        },
+        FormFieldType::Select => {
+            if !field.options.contains(&text) {
+                return Err(invalid("Not one of the allowed options"));
+            }
+            text
+        }
+        FormFieldType::Text | FormFieldType::Textarea | FormFieldType::Hidden => text,
+    };
+
+    Ok(Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn values(value: serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    fn custom_schema() -> FormSchema {
+        let mut plan = FormField::new("plan", "Plan", FormFieldType::Select, true);
+        plan.options = vec!["starter".into(), "pro".into()];
+
+        FormSchema {
+            fields: vec![
+                FormField::new("email", "Email", FormFieldType::Email, true),
+                FormField::new("first_name", "First name", FormFieldType::Text, false),
+                plan,
+                FormField::new("team_size", "Team size", FormFieldType::Number, false),
+                FormField::new("consent", "I agree", FormFieldType::Checkbox, true),
+                FormField::new("utm_source", "", FormFieldType::Hidden, false),
+            ],
+            honeypot: Some("website".into()),
+            max_submissions: Some(50),
+        }
+    }
+
+    #[test]
+    fn test_default_schema_is_valid() {
+        assert!(FormSchema::default().validate().is_ok());
+        assert!(custom_schema().validate().is_ok());
+    }
+
+    #[test]
+    fn test_schema_rules() {
+        let mut schema = custom_schema();
+        schema.fields.push(FormField::new("plan", "Again", FormFieldType::Text, false));
+        schema.fields.push(FormField::new("Bad Name", "", FormFieldType::Text, false));
+        schema.fields[2].options.clear();
+
+        let err = schema.validate().unwrap_err();
+        let fields: Vec<_> = err.violations().iter().filter_map(|e| e.field()).map(String::from).collect();
+        assert_eq!(
+            fields,
+            vec!["form.fields[2].options", "form.fields[6].name", "form.fields[7].name"]
+        );
+
+        let no_email = FormSchema {
+            fields: vec![FormField::new("first_name", "", FormFieldType::Text, true)],
+            ..FormSchema::default()
+        };
+        assert!(no_email.validate().is_err());
+
+        let mut trap_is_a_field = custom_schema();
+        trap_is_a_field.honeypot = Some("plan".into());
+        assert!(trap_is_a_field.validate().is_err());
+    }
+
+    #[test]
+    fn test_honeypot_filled() {
+        let schema = custom_schema();
+
+        assert!(!schema.honeypot_filled(&values(json!({ "email": "ada@example.com" }))));
+        assert!(!schema.honeypot_filled(&values(json!({ "website": " " }))));
+        assert!(schema.honeypot_filled(&values(json!({ "website": "https://spam.example" }))));
+        assert!(!FormSchema::default().honeypot_filled(&values(json!({ "website": "x" }))));
+    }
+
+    #[test]
+    fn test_submission_only_fills_unset_custom_fields() {
+        let submission = FormSubmission {
+            custom_fields: BTreeMap::from([
+                ("plan".to_string(), "free".to_string()),
+                ("team_size".to_string(), "12".to_string()),
+                ("source".to_string(), "linkedin".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let existing = BTreeMap::from([
+            ("plan".to_string(), "enterprise".to_string()),
+            ("source".to_string(), " ".to_string()),
+        ]);
+
+        let added = submission.unset_custom_fields(&existing);
+        assert_eq!(added.get("team_size").map(String::as_str), Some("12"));
+        assert_eq!(added.get("source").map(String::as_str), Some("linkedin"));
+        assert!(!added.contains_key("plan"));
+    }
+
+    #[test]
+    fn test_submission_maps_custom_fields() {
+        let submission = custom_schema()
+            .validate_submission(&values(json!({
+                "email": " Ada@Example.com ",
+                "plan": "pro",
+                "team_size": 12,
+                "consent": "on",
+                "utm_source": "linkedin",
+                "unknown": "ignored"
+            })))
+            .unwrap();
+
+        assert_eq!(submission.email, "ada@example.com");
+        assert_eq!(submission.first_name, "");
+        assert_eq!(submission.custom_fields.get("plan").map(String::as_str), Some("pro"));
+        assert_eq!(submission.custom_fields.get("team_size").map(String::as_str), Some("12"));
+        assert_eq!(submission.custom_fields.get("consent").map(String::as_str), Some("true"));
+        assert_eq!(submission.custom_fields.get("utm_source").map(String::as_str), Some("linkedin"));
+        assert!(!submission.custom_fields.contains_key("unknown"));
+    }
+
+    #[test]
+    fn test_submission_reports_every_failing_field() {
+        let err = custom_schema()
+            .validate_submission(&values(json!({
+                "email": "not-an-email",
+                "plan": "enterprise",
+                "team_size": "a dozen",
+                "consent": false
+            })))
+            .unwrap_err();
+
+        let fields: Vec<_> = err.violations().iter().filter_map(|e| e.field()).map(String::from).collect();
+        assert_eq!(fields, vec!["email", "plan", "team_size", "consent"]);
+    }
+}
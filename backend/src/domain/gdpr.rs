@@ -0,0 +1,66 @@
+//! GDPR Domain - erasing a data subject
+//!
+//! Erasure anonymizes rather than deletes. The contact record and its links
+//! (RSVPs, campaign and sequence membership, deals) stay, so event counts
+//! and campaign reports keep adding up, but every field that identifies the
+//! person is replaced or cleared:
+//!
+//! - Name and email become placeholders; phone, LinkedIn, timezone, location,
+//!   avatar, tags and custom fields are cleared, and the contact is unsubscribed
+//! - Timeline entries lose their content, attachments and sentiment; their
+//!   metadata keeps only the references reports rely on
+//! - Attached files and the audit history of the contact's fields are deleted
+//! - Status changes keep their statuses and dates but lose their reasons
+//! - Relationships stay but lose their notes
+//! - Tracked short links are deleted, so later clicks aren't recorded
+//! - Webhook deliveries keep only the contact's ID, and stored responses to
+//!   retried requests that mention the contact are deleted
+//! - The contact's embedding and duplicate suggestions are deleted, and
+//!   duplicate detection leaves erased contacts out from then on
+
+/// First name every erased contact gets
+pub const ERASED_FIRST_NAME: &str = "Erased";
+
+/// Last name every erased contact gets
+pub const ERASED_LAST_NAME: &str = "Contact";
+
+/// What an erased timeline entry says instead of its content
+pub const ERASED_CONTENT: &str = "[erased]";
+
+/// Timeline metadata kept on erasure: references, never personal data
+pub const KEPT_METADATA_KEYS: [&str; 5] = ["action", "campaign_id", "event_id", "landing_page_id", "status"];
+
+/// Placeholder email of an erased contact
+///
+/// Unique per contact, so the workspace's unique email index still holds,
+/// and on the reserved `.invalid` domain, so nothing can ever be sent to it.
+pub fn erased_email(contact_id: &str) -> String {
+    format!("erased-{}@erased.invalid", contact_id)
+}
+
+/// Whether a contact has been erased, judging by its placeholder email
+pub fn is_erased(contact_id: &str, email: &str) -> bool {
+    email == erased_email(contact_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::validate_email;
+
+    #[test]
+    fn test_erased_email_is_valid_and_unique_per_contact() {
+        let email = erased_email("8xk2pq0v");
+
+        assert_eq!(email, "erased-8xk2pq0v@erased.invalid");
+        assert!(validate_email(&email).is_ok());
+        assert_ne!(email, erased_email("other"));
+    }
+
+    #[test]
+    fn test_is_erased() {
+        assert!(is_erased("8xk2pq0v", &erased_email("8xk2pq0v")));
+        assert!(!is_erased("8xk2pq0v", &erased_email("other")));
+        assert!(!is_erased("8xk2pq0v", "ada@example.com"));
+    }
+}
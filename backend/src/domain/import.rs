@@ -0,0 +1,128 @@
+//! Import Domain - records brought over from another CRM
+//!
+//! An importer turns the other CRM's contacts, companies, deals and notes
+//! into an `ImportBatch`, keeping each record's ID over there so that a
+//! second run finds what the first one created. Companies are matched by
+//! domain and contacts by email when nothing was imported for them yet.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::contact::ContactStatus;
+use super::deal::DealStage;
+
+/// Custom field holding an imported contact's job title
+pub const JOB_TITLE_FIELD: &str = "job_title";
+
+/// Metadata key marking a timeline entry as brought over by an import
+pub const IMPORT_SOURCE_KEY: &str = "import_source";
+
+static HTML_TAG_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<style.*?</style>|<[^>]*>").unwrap());
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedCompany {
+    pub external_id: String,
+    pub name: String,
+    pub domain: Option<String>,
+    pub industry: Option<String>,
+    pub size: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedContact {
+    pub external_id: String,
+    /// Contacts without an email can't be imported
+    pub email: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub phone: Option<String>,
+    pub job_title: Option<String>,
+    pub status: ContactStatus,
+    pub company_external_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedDeal {
+    pub external_id: String,
+    pub name: String,
+    pub value: f64,
+    pub stage: DealStage,
+    pub close_date: Option<DateTime<Utc>>,
+    pub contact_external_id: Option<String>,
+    pub company_external_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedNote {
+    pub external_id: String,
+    /// Plain text
+    pub body: String,
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Notes land on the timeline of every contact they're attached to
+    pub contact_external_ids: Vec<String>,
+}
+
+/// Everything one import run brings over
+#[derive(Debug, Clone, Default)]
+pub struct ImportBatch {
+    pub companies: Vec<ImportedCompany>,
+    pub contacts: Vec<ImportedContact>,
+    pub deals: Vec<ImportedDeal>,
+    pub notes: Vec<ImportedNote>,
+}
+
+/// Bare, lowercase host of a company website, e.g. `acme.com` for
+/// `https://www.Acme.com/about`
+pub fn normalize_domain(website: &str) -> Option<String> {
+    let host = website.trim().to_lowercase();
+    let host = host
+        .split_once("://")
+        .map_or(host.as_str(), |(_, rest)| rest)
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default();
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    let host = host.split(':').next().unwrap_or_default();
+
+    (host.contains('.') && !host.starts_with('.') && !host.ends_with('.')).then(|| host.to_string())
+}
+
+/// Plain text of a note written in another CRM's rich text editor
+pub fn note_text(html: &str) -> String {
+    let text = HTML_TAG_REGEX
+        .replace_all(&html.replace("<br>", "\n").replace("</p>", "\n"), " ")
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+
+    text.lines().map(str::trim).filter(|l| !l.is_empty()).collect::<Vec<_>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_domain() {
+        assert_eq!(normalize_domain("https://www.Acme.com/about"), Some("acme.com".into()));
+        assert_eq!(normalize_domain("globex.io"), Some("globex.io".into()));
+        assert_eq!(normalize_domain("http://shop.initech.co.uk:8080?x=1"), Some("shop.initech.co.uk".into()));
+    }
+
+    #[test]
+    fn test_note_text() {
+        assert_eq!(note_text("<p>Called &amp; left a message</p><p>Follow up</p>"), "Called & left a message\nFollow up");
+        assert_eq!(note_text("Plain<br>text"), "Plain\ntext");
+    }
+
+    #[test]
+    fn test_normalize_domain_rejects_non_hosts() {
+        assert_eq!(normalize_domain(""), None);
+        assert_eq!(normalize_domain("Acme Inc"), None);
+        assert_eq!(normalize_domain("https://"), None);
+    }
+}
@@ -0,0 +1,225 @@
+//! Inbound Webhooks - turning JSON from external tools into contacts
+//!
+//! Each inbound source has a mapping from contact fields to paths in the
+//! payloads it receives. A path is a dot-separated list of object keys,
+//! with numeric segments indexing into arrays: `data.attributes.email`,
+//! `form_response.answers.0.email`.
+//!
+//! Only the email is required; it is what finds an existing contact.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use super::errors::{DomainError, DomainResult};
+use super::validation::{validate_custom_field, validate_email, validate_tag};
+
+/// Most custom fields one mapping may fill
+const MAX_MAPPED_FIELDS: usize = 30;
+
+/// Longest value taken from a payload for any one field
+const MAX_VALUE_LEN: usize = 500;
+
+/// Where a source's payloads keep each contact field
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct InboundMapping {
+    /// Path to the contact's email
+    pub email: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub phone: Option<String>,
+    /// Custom field key to path
+    #[serde(default)]
+    pub custom_fields: BTreeMap<String, String>,
+    /// Added to every contact the source creates or updates
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Path to a short description of what happened, e.g. the event type;
+    /// becomes the timeline entry's text
+    pub summary: Option<String>,
+}
+
+/// Contact fields read out of one payload
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MappedContact {
+    /// Lowercased
+    pub email: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub phone: Option<String>,
+    pub custom_fields: BTreeMap<String, String>,
+    pub summary: Option<String>,
+}
+
+impl InboundMapping {
+    /// Check a mapping before it is stored
+    ///
+    /// Every path must be well-formed, custom field keys and tags must be
+    /// ones a contact can have.
+    pub fn validate(&self) -> DomainResult<()> {
+        let mut violations = Vec::new();
+
+        if self.email.trim().is_empty() {
+            violations.push(DomainError::RequiredFieldMissing {
+                field: "mapping.email".to_string(),
+            });
+        }
+
+        let optional = [
+            ("first_name", &self.first_name),
+            ("last_name", &self.last_name),
+            ("phone", &self.phone),
+            ("summary", &self.summary),
+        ];
+        let paths = std::iter::once(("email".to_string(), self.email.as_str()))
+            .chain(optional.iter().filter_map(|(f, p)| p.as_deref().map(|p| (f.to_string(), p))))
+            .chain(self.custom_fields.iter().map(|(k, p)| (format!("custom_fields.{}", k), p.as_str())));
+        for (field, path) in paths {
+            if !path.trim().is_empty() && !is_valid_path(path) {
+                violations.push(DomainError::InvalidField {
+                    field: format!("mapping.{}", field),
+                    reason: "Must be a dot-separated path, like data.email".to_string(),
+                });
+            }
+        }
+
+        if self.custom_fields.len() > MAX_MAPPED_FIELDS {
+            violations.push(DomainError::InvalidField {
+                field: "mapping.custom_fields".to_string(),
+                reason: format!("At most {} custom fields can be mapped", MAX_MAPPED_FIELDS),
+            });
+        }
+        for key in self.custom_fields.keys() {
+            if let Err(e) = validate_custom_field(key, "") {
+                violations.push(e.at(format!("mapping.custom_fields.{}", key)));
+            }
+        }
+
+        for (i, tag) in self.tags.iter().enumerate() {
+            if let Err(e) = validate_tag(tag) {
+                violations.push(e.at(format!("mapping.tags[{}]", i)));
+            }
+        }
+
+        match DomainError::from_violations(violations) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Read the contact fields out of a payload
+    ///
+    /// Fails when the payload has no valid email where the mapping says;
+    /// any other field that is missing is simply left out.
+    pub fn apply(&self, payload: &Value) -> DomainResult<MappedContact> {
+        let email = lookup(payload, &self.email)
+            .map(|e| e.to_lowercase())
+            .ok_or_else(|| DomainError::RequiredFieldMissing {
+                field: "email".to_string(),
+            })?;
+        validate_email(&email)?;
+
+        let field = |path: &Option<String>| path.as_deref().and_then(|p| lookup(payload, p));
+
+        Ok(MappedContact {
+            email,
+            first_name: field(&self.first_name),
+            last_name: field(&self.last_name),
+            phone: field(&self.phone),
+            custom_fields: self
+                .custom_fields
+                .iter()
+                .filter_map(|(key, path)| lookup(payload, path).map(|v| (key.trim().to_lowercase(), v)))
+                .collect(),
+            summary: field(&self.summary),
+        })
+    }
+}
+
+/// The value at a path, as text
+///
+/// Strings, numbers and booleans are returned; anything else, and blank
+/// strings, count as missing.
+fn lookup(payload: &Value, path: &str) -> Option<String> {
+    let value = path.split('.').try_fold(payload, |value, segment| match value {
+        Value::Object(map) => map.get(segment),
+        Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => None,
+    })?;
+
+    let text = match value {
+        Value::String(s) => s.trim().to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        _ => return None,
+    };
+    (!text.is_empty()).then(|| text.chars().take(MAX_VALUE_LEN).collect())
+}
+
+fn is_valid_path(path: &str) -> bool {
+    path.split('.').all(|segment| !segment.is_empty() && !segment.contains(char::is_whitespace))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn calendly_mapping() -> InboundMapping {
+        InboundMapping {
+            email: "payload.invitee.email".to_string(),
+            first_name: Some("payload.invitee.first_name".to_string()),
+            last_name: Some("payload.invitee.last_name".to_string()),
+            phone: None,
+            custom_fields: BTreeMap::from([("meeting".to_string(), "payload.questions.0.answer".to_string())]),
+            tags: vec!["calendly".to_string()],
+            summary: Some("event".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_apply_mapping() {
+        let payload = json!({
+            "event": "invitee.created",
+            "payload": {
+                "invitee": { "email": " Jane@Example.com ", "first_name": "Jane", "last_name": "" },
+                "questions": [{ "answer": 30 }]
+            }
+        });
+
+        let mapped = calendly_mapping().apply(&payload).unwrap();
+
+        assert_eq!(mapped.email, "jane@example.com");
+        assert_eq!(mapped.first_name.as_deref(), Some("Jane"));
+        assert_eq!(mapped.last_name, None);
+        assert_eq!(mapped.custom_fields.get("meeting").map(String::as_str), Some("30"));
+        assert_eq!(mapped.summary.as_deref(), Some("invitee.created"));
+    }
+
+    #[test]
+    fn test_apply_requires_email() {
+        let mapping = calendly_mapping();
+
+        assert!(matches!(
+            mapping.apply(&json!({ "payload": {} })),
+            Err(DomainError::RequiredFieldMissing { .. })
+        ));
+        assert!(mapping
+            .apply(&json!({ "payload": { "invitee": { "email": "not-an-email" } } }))
+            .is_err());
+    }
+
+    #[test]
+    fn test_validate_mapping() {
+        assert!(calendly_mapping().validate().is_ok());
+
+        let mapping = InboundMapping {
+            email: "data..email".to_string(),
+            tags: vec!["".to_string()],
+            ..Default::default()
+        };
+        assert!(matches!(mapping.validate(), Err(DomainError::Violations(v)) if v.len() == 2));
+    }
+}
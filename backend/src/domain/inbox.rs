@@ -0,0 +1,189 @@
+//! Inbox Domain - turning received email into CRM activity
+//!
+//! Messages pulled from a connected mailbox are matched to contacts by the
+//! sender's address. What ends up on the timeline is the subject and a
+//! short snippet of the body, never the full message.
+
+use chrono::{DateTime, Utc};
+
+use super::errors::{DomainError, DomainResult};
+use super::network::validate_public_host;
+
+/// Longest snippet kept from a message body, in characters
+pub const SNIPPET_CHARS: usize = 200;
+
+/// A message as read from a connected mailbox
+#[derive(Debug, Clone, PartialEq)]
+pub struct InboundEmail {
+    /// Unique per mailbox; used to skip messages already synced
+    pub message_id: String,
+    /// The `Message-ID` header, when the mailbox's own ID is something else
+    pub header_message_id: Option<String>,
+    /// The first ID in the `In-Reply-To` header
+    pub in_reply_to: Option<String>,
+    /// The IDs in the `References` header, oldest first
+    pub references: Vec<String>,
+    /// Lowercased sender address
+    pub from_email: String,
+    pub from_name: Option<String>,
+    pub subject: String,
+    pub snippet: String,
+    pub received_at: DateTime<Utc>,
+}
+
+/// Check the login for an IMAP mailbox before it is stored
+///
+/// Host, username and password are required; the host must be a bare,
+/// public name without scheme or port.
+pub fn validate_imap_login(host: &str, port: u16, username: &str, password: &str) -> DomainResult<()> {
+    let mut violations = Vec::new();
+
+    for (field, value) in [("host", host), ("username", username), ("password", password)] {
+        if value.trim().is_empty() {
+            violations.push(DomainError::RequiredFieldMissing { field: field.to_string() });
+        }
+    }
+
+    if host.contains(['/', ':']) || host.trim().contains(char::is_whitespace) {
+        violations.push(DomainError::InvalidField {
+            field: "host".to_string(),
+            reason: "Must be a host name, without scheme or port".to_string(),
+        });
+    } else if !host.trim().is_empty() {
+        if let Err(e) = validate_public_host("host", host.trim()) {
+            violations.push(e);
+        }
+    }
+
+    if port == 0 {
+        violations.push(DomainError::InvalidField {
+            field: "port".to_string(),
+            reason: "Must be a valid port".to_string(),
+        });
+    }
+
+    match DomainError::from_violations(violations) {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Collapse whitespace and cut a message body down to a snippet
+pub fn snippet(body: &str) -> String {
+    let collapsed = body.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= SNIPPET_CHARS {
+        return collapsed;
+    }
+
+    let cut: String = collapsed.chars().take(SNIPPET_CHARS).collect();
+    // Don't end halfway through a word when there is a word boundary to use
+    let cut = match cut.rfind(' ') {
+        Some(i) if i > SNIPPET_CHARS / 2 => &cut[..i],
+        _ => cut.as_str(),
+    };
+    format!("{}…", cut.trim_end())
+}
+
+/// The message IDs in an `In-Reply-To` or `References` header
+///
+/// IDs are whitespace separated; anything between them, such as the
+/// comments some old clients add, is dropped.
+pub fn header_message_ids(header: &str) -> Vec<String> {
+    header
+        .split_whitespace()
+        .filter(|id| id.starts_with('<') && id.ends_with('>') && id.len() > 2)
+        .map(str::to_string)
+        .collect()
+}
+
+/// First and last name for a contact created from a sender
+///
+/// Uses the display name when there is one, otherwise the address's local
+/// part split on dots, dashes and underscores. A single name leaves the
+/// last name as `-`, since contacts need both.
+pub fn sender_name(from_name: Option<&str>, from_email: &str) -> (String, String) {
+    let display = from_name
+        .map(|n| n.trim().trim_matches('"').trim())
+        .filter(|n| !n.is_empty() && !n.contains('@'));
+
+    let parts: Vec<String> = match display {
+        Some(name) => name.split_whitespace().map(str::to_string).collect(),
+        None => from_email
+            .split('@')
+            .next()
+            .unwrap_or_default()
+            .split(['.', '-', '_', '+'])
+            .filter(|p| !p.is_empty())
+            .map(capitalize)
+            .collect(),
+    };
+
+    match parts.split_first() {
+        Some((first, [])) => (first.clone(), "-".to_string()),
+        Some((first, rest)) => (first.clone(), rest.join(" ")),
+        None => (from_email.to_string(), "-".to_string()),
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_imap_login() {
+        assert!(validate_imap_login("imap.example.com", 993, "jane", "secret").is_ok());
+        assert!(matches!(
+            validate_imap_login("imaps://imap.example.com", 993, "jane", "secret"),
+            Err(DomainError::InvalidField { .. })
+        ));
+        assert!(matches!(
+            validate_imap_login("10.0.0.5", 993, "jane", "secret"),
+            Err(DomainError::InvalidField { .. })
+        ));
+        assert!(matches!(
+            validate_imap_login("", 0, "jane", ""),
+            Err(DomainError::Violations(v)) if v.len() == 3
+        ));
+    }
+
+    #[test]
+    fn test_header_message_ids() {
+        assert_eq!(header_message_ids(" <1@a>\r\n <2@b> (comment) <>"), vec!["<1@a>", "<2@b>"]);
+        assert!(header_message_ids("").is_empty());
+    }
+
+    #[test]
+    fn test_snippet() {
+        assert_eq!(snippet("  Hi there,\r\n\r\n  see you   soon "), "Hi there, see you soon");
+
+        let long = "word ".repeat(100);
+        let cut = snippet(&long);
+        assert!(cut.ends_with("word…"));
+        assert!(cut.chars().count() <= SNIPPET_CHARS + 1);
+    }
+
+    #[test]
+    fn test_sender_name() {
+        assert_eq!(
+            sender_name(Some("\"Jane van Dyke\""), "jane@example.com"),
+            ("Jane".to_string(), "van Dyke".to_string())
+        );
+        assert_eq!(
+            sender_name(None, "jane.doe@example.com"),
+            ("Jane".to_string(), "Doe".to_string())
+        );
+        assert_eq!(sender_name(Some("  "), "info@example.com"), ("Info".to_string(), "-".to_string()));
+        assert_eq!(
+            sender_name(Some("jane@example.com"), "jane@example.com"),
+            ("Jane".to_string(), "-".to_string())
+        );
+    }
+}
@@ -0,0 +1,311 @@
+//! Interaction Domain - What calls, meetings and email record
+//!
+//! Timeline metadata is stored as a JSON object. For calls, meetings and
+//! email the keys below are checked when an entry is logged; any other keys
+//! are kept as given. A call's outcome and length decide how much it counts
+//! towards engagement: a missed call is barely an interaction, a long
+//! conversation is worth more than a quick check-in.
+//!
+//! Entries stored before these checks may not match; they are read
+//! leniently and score as plain calls.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::errors::{DomainError, DomainResult};
+
+/// Longest call that can be logged, in minutes
+pub const MAX_CALL_DURATION_MINUTES: u32 = 24 * 60;
+
+/// Most attendees one meeting may list
+pub const MAX_ATTENDEES: usize = 100;
+
+/// Longest meeting location or attendee name
+const MAX_TEXT_LEN: usize = 500;
+
+/// Longest email subject or message ID (the RFC 5322 line limit)
+const MAX_SUBJECT_LEN: usize = 998;
+
+/// Most message IDs an email's `references` may list
+pub const MAX_REFERENCES: usize = 100;
+
+/// Calls shorter than this, in minutes, count half
+const SHORT_CALL_MINUTES: u32 = 2;
+
+/// Calls at least this long, in minutes, count one and a half times
+const LONG_CALL_MINUTES: u32 = 30;
+
+/// How a call went
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallOutcome {
+    Connected,
+    Voicemail,
+    NoAnswer,
+    Busy,
+}
+
+/// Metadata of a `call` entry
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CallMetadata {
+    pub duration_minutes: Option<u32>,
+    pub outcome: Option<CallOutcome>,
+}
+
+impl CallMetadata {
+    pub fn from_metadata(metadata: &Value) -> DomainResult<Self> {
+        let call = Self {
+            duration_minutes: field(metadata, "duration_minutes")?,
+            outcome: field(metadata, "outcome")?,
+        };
+
+        if call.duration_minutes.is_some_and(|minutes| minutes > MAX_CALL_DURATION_MINUTES) {
+            return Err(invalid(
+                "duration_minutes",
+                format!("must be at most {} minutes", MAX_CALL_DURATION_MINUTES),
+            ));
+        }
+        if call.duration_minutes.is_some_and(|minutes| minutes > 0)
+            && matches!(call.outcome, Some(CallOutcome::NoAnswer | CallOutcome::Busy))
+        {
+            return Err(invalid("duration_minutes", "must be 0 or left out for an unanswered call"));
+        }
+
+        Ok(call)
+    }
+
+    /// Multiplier on the base score of a call
+    ///
+    /// Calls with neither outcome nor duration count as an ordinary call.
+    pub fn engagement_weight(&self) -> f64 {
+        match self.outcome {
+            Some(CallOutcome::NoAnswer | CallOutcome::Busy) => 0.1,
+            Some(CallOutcome::Voicemail) => 0.25,
+            Some(CallOutcome::Connected) | None => match self.duration_minutes {
+                Some(minutes) if minutes < SHORT_CALL_MINUTES => 0.5,
+                Some(minutes) if minutes >= LONG_CALL_MINUTES => 1.5,
+                _ => 1.0,
+            },
+        }
+    }
+}
+
+/// Metadata of a `meeting_scheduled` or `meeting_attended` entry
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MeetingMetadata {
+    /// Names or email addresses of everyone taking part
+    pub attendees: Vec<String>,
+    pub location: Option<String>,
+}
+
+impl MeetingMetadata {
+    pub fn from_metadata(metadata: &Value) -> DomainResult<Self> {
+        let meeting = Self {
+            attendees: field(metadata, "attendees")?.unwrap_or_default(),
+            location: field(metadata, "location")?,
+        };
+
+        if meeting.attendees.len() > MAX_ATTENDEES {
+            return Err(invalid("attendees", format!("at most {} are allowed", MAX_ATTENDEES)));
+        }
+        if meeting.attendees.iter().any(|a| a.trim().is_empty() || a.len() > MAX_TEXT_LEN) {
+            return Err(invalid(
+                "attendees",
+                format!("each must be given and at most {} characters", MAX_TEXT_LEN),
+            ));
+        }
+        if meeting.location.as_ref().is_some_and(|l| l.len() > MAX_TEXT_LEN) {
+            return Err(invalid("location", format!("must be at most {} characters", MAX_TEXT_LEN)));
+        }
+
+        Ok(meeting)
+    }
+}
+
+/// Metadata of an email entry
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EmailMetadata {
+    pub subject: Option<String>,
+    /// The `Message-ID` header, which threads replies to the message
+    pub message_id: Option<String>,
+    /// The `Message-ID` header of synced mail the mailbox knows by another ID
+    pub header_message_id: Option<String>,
+    /// The message this one replies to
+    pub in_reply_to: Option<String>,
+    /// The earlier messages of the thread, oldest first
+    #[serde(default)]
+    pub references: Vec<String>,
+}
+
+impl EmailMetadata {
+    pub fn from_metadata(metadata: &Value) -> DomainResult<Self> {
+        let email = Self {
+            subject: field(metadata, "subject")?,
+            message_id: field(metadata, "message_id")?,
+            header_message_id: field(metadata, "header_message_id")?,
+            in_reply_to: field(metadata, "in_reply_to")?,
+            references: field(metadata, "references")?.unwrap_or_default(),
+        };
+
+        if email.subject.as_ref().is_some_and(|s| s.len() > MAX_SUBJECT_LEN) {
+            return Err(invalid("subject", format!("must be at most {} characters", MAX_SUBJECT_LEN)));
+        }
+        for (key, id) in [
+            ("message_id", &email.message_id),
+            ("header_message_id", &email.header_message_id),
+            ("in_reply_to", &email.in_reply_to),
+        ] {
+            if let Some(id) = id {
+                check_message_id(key, id)?;
+            }
+        }
+        if email.references.len() > MAX_REFERENCES {
+            return Err(invalid("references", format!("at most {} are allowed", MAX_REFERENCES)));
+        }
+        for id in &email.references {
+            check_message_id("references", id)?;
+        }
+
+        Ok(email)
+    }
+
+    /// Read the metadata of an entry stored before these checks, or with keys they would refuse
+    ///
+    /// Whatever doesn't parse is left out rather than failing the whole read.
+    pub fn read_leniently(metadata: &Value) -> Self {
+        let text = |key: &str| {
+            metadata
+                .get(key)
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+        };
+
+        Self {
+            subject: text("subject"),
+            message_id: text("message_id"),
+            header_message_id: text("header_message_id"),
+            in_reply_to: text("in_reply_to"),
+            references: metadata
+                .get("references")
+                .and_then(Value::as_array)
+                .map(|ids| ids.iter().filter_map(Value::as_str).map(String::from).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+fn check_message_id(key: &str, id: &str) -> DomainResult<()> {
+    if id.trim().is_empty() {
+        return Err(invalid(key, "must not be empty"));
+    }
+    if id.len() > MAX_SUBJECT_LEN {
+        return Err(invalid(key, format!("must be at most {} characters", MAX_SUBJECT_LEN)));
+    }
+    Ok(())
+}
+
+/// Read one key of the metadata; missing and null are `None`
+fn field<T: DeserializeOwned>(metadata: &Value, key: &str) -> DomainResult<Option<T>> {
+    if !metadata.is_object() {
+        return Err(DomainError::InvalidField {
+            field: "metadata".into(),
+            reason: "must be an object".into(),
+        });
+    }
+
+    match metadata.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => serde_json::from_value(value.clone())
+            .map(Some)
+            .map_err(|e| invalid(key, e.to_string())),
+    }
+}
+
+fn invalid(key: &str, reason: impl Into<String>) -> DomainError {
+    DomainError::InvalidField {
+        field: format!("metadata.{}", key),
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_call_metadata_is_checked() {
+        let metadata = json!({ "duration_minutes": 20, "outcome": "connected", "notes": "x" });
+        let call = CallMetadata::from_metadata(&metadata);
+        assert_eq!(
+            call.unwrap(),
+            CallMetadata {
+                duration_minutes: Some(20),
+                outcome: Some(CallOutcome::Connected),
+            }
+        );
+        assert_eq!(CallMetadata::from_metadata(&json!({})).unwrap(), CallMetadata::default());
+
+        let field_of = |metadata: Value| CallMetadata::from_metadata(&metadata).unwrap_err().field().map(String::from);
+        assert_eq!(field_of(json!({ "duration_minutes": "long" })).as_deref(), Some("metadata.duration_minutes"));
+        assert_eq!(field_of(json!({ "duration_minutes": -5 })).as_deref(), Some("metadata.duration_minutes"));
+        assert_eq!(field_of(json!({ "duration_minutes": 2000 })).as_deref(), Some("metadata.duration_minutes"));
+        assert_eq!(field_of(json!({ "outcome": "hung_up" })).as_deref(), Some("metadata.outcome"));
+        assert_eq!(
+            field_of(json!({ "duration_minutes": 5, "outcome": "no_answer" })).as_deref(),
+            Some("metadata.duration_minutes")
+        );
+        assert_eq!(field_of(json!(["call"])).as_deref(), Some("metadata"));
+    }
+
+    #[test]
+    fn test_call_weight_follows_outcome_and_duration() {
+        let weight = |duration_minutes, outcome| {
+            CallMetadata {
+                duration_minutes,
+                outcome,
+            }
+            .engagement_weight()
+        };
+
+        assert_eq!(weight(None, None), 1.0);
+        assert_eq!(weight(Some(1), Some(CallOutcome::Connected)), 0.5);
+        assert_eq!(weight(Some(10), None), 1.0);
+        assert_eq!(weight(Some(45), Some(CallOutcome::Connected)), 1.5);
+        assert!(weight(None, Some(CallOutcome::Voicemail)) < weight(Some(1), Some(CallOutcome::Connected)));
+        assert!(weight(Some(0), Some(CallOutcome::NoAnswer)) < weight(None, Some(CallOutcome::Voicemail)));
+    }
+
+    #[test]
+    fn test_meeting_and_email_metadata_are_checked() {
+        let meeting = MeetingMetadata::from_metadata(&json!({ "attendees": ["ada@example.com"], "location": "Zoom" }));
+        assert_eq!(meeting.unwrap().attendees, vec!["ada@example.com".to_string()]);
+        assert!(MeetingMetadata::from_metadata(&json!({ "attendees": "ada@example.com" })).is_err());
+        assert!(MeetingMetadata::from_metadata(&json!({ "attendees": [" "] })).is_err());
+        assert!(MeetingMetadata::from_metadata(&json!({ "attendees": vec!["a"; MAX_ATTENDEES + 1] })).is_err());
+
+        let email = EmailMetadata::from_metadata(&json!({ "subject": "Pricing", "message_id": "<1@mail>" }));
+        assert_eq!(email.unwrap().subject.as_deref(), Some("Pricing"));
+        assert!(EmailMetadata::from_metadata(&json!({ "subject": 42 })).is_err());
+        assert!(EmailMetadata::from_metadata(&json!({ "message_id": "" })).is_err());
+
+        let reply = EmailMetadata::from_metadata(&json!({ "in_reply_to": "<1@mail>", "references": ["<1@mail>"] }));
+        assert_eq!(reply.unwrap().references, vec!["<1@mail>".to_string()]);
+        assert!(EmailMetadata::from_metadata(&json!({ "in_reply_to": " " })).is_err());
+        assert!(EmailMetadata::from_metadata(&json!({ "references": "<1@mail>" })).is_err());
+        assert!(EmailMetadata::from_metadata(&json!({ "references": vec!["<x>"; MAX_REFERENCES + 1] })).is_err());
+    }
+
+    #[test]
+    fn test_email_metadata_read_leniently() {
+        let metadata = json!({ "subject": " Pricing ", "message_id": 7, "references": null });
+        let email = EmailMetadata::read_leniently(&metadata);
+        assert_eq!(email.subject.as_deref(), Some("Pricing"));
+        assert_eq!(email.message_id, None);
+        assert!(email.references.is_empty());
+        assert_eq!(EmailMetadata::read_leniently(&json!("not an object")), EmailMetadata::default());
+    }
+}
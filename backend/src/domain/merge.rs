@@ -0,0 +1,465 @@
+//! Contact Merge - Combining duplicate contacts and finding likely duplicates
+//!
+//! Merging keeps the primary contact and folds the duplicate into it:
+//!
+//! - Identity (name, email) always comes from the primary
+//! - Optional fields (phone, LinkedIn, timezone, avatar, company) are filled
+//!   from the duplicate when the primary has none
+//! - Tags are unioned; custom fields are filled like optional fields
+//! - A relationship status (customer, partner, investor) beats lead/other
+//! - Unsubscribed wins over subscribed: consent is never silently restored
+//! - The higher engagement score and the earlier creation date are kept
+//!
+//! Every field where both contacts had a different value is reported as a
+//! conflict, so the caller can show what was discarded.
+//!
+//! Duplicate detection compares contacts pairwise on canonical email,
+//! name similarity and phone digits. A contact about to be created is held
+//! to a stricter standard: a shared name only counts together with a
+//! shared company, since two people can easily have the same name.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::contact::{Contact, ContactStatus};
+
+// ============================================================================
+// Merging
+// ============================================================================
+
+/// A field where the primary and duplicate disagreed
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct MergeConflict {
+    pub field: String,
+    pub kept: String,
+    pub discarded: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct MergeResult {
+    pub merged: Contact,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// Fold `duplicate` into `primary` following the rules above
+pub fn merge_contacts(primary: &Contact, duplicate: &Contact, now: DateTime<Utc>) -> MergeResult {
+    let mut merged = primary.clone();
+    let mut conflicts = Vec::new();
+
+    let mut note = |field: &str, kept: &str, discarded: &str| {
+        if kept != discarded {
+            conflicts.push(MergeConflict {
+                field: field.to_string(),
+                kept: kept.to_string(),
+                discarded: discarded.to_string(),
+            });
+        }
+    };
+
+    note("first_name", &primary.first_name, &duplicate.first_name);
+    note("last_name", &primary.last_name, &duplicate.last_name);
+    note("email", &primary.email, &duplicate.email);
+
+    merged.phone = merge_optional("phone", &primary.phone, &duplicate.phone, &mut note);
+    merged.linkedin_url = merge_optional("linkedin_url", &primary.linkedin_url, &duplicate.linkedin_url, &mut note);
+    merged.timezone = merge_optional("timezone", &primary.timezone, &duplicate.timezone, &mut note);
+    merged.avatar_url = merge_optional("avatar_url", &primary.avatar_url, &duplicate.avatar_url, &mut note);
+    merged.company_id = merge_optional("company_id", &primary.company_id, &duplicate.company_id, &mut note);
+    merged.owner_id = merge_optional("owner_id", &primary.owner_id, &duplicate.owner_id, &mut note);
+
+    for tag in &duplicate.tags {
+        if !merged.tags.contains(tag) {
+            merged.tags.push(tag.clone());
+        }
+    }
+
+    for (key, value) in &duplicate.custom_fields {
+        match primary.custom_fields.get(key) {
+            Some(kept) => note(&format!("custom_fields.{}", key), kept, value),
+            None => {
+                merged.custom_fields.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    if !has_relationship(primary.status) && has_relationship(duplicate.status) {
+        merged.status = duplicate.status;
+        note("status", &duplicate.status.to_string(), &primary.status.to_string());
+    } else {
+        note("status", &primary.status.to_string(), &duplicate.status.to_string());
+    }
+
+    if duplicate.subscription_status.is_suppressed() {
+        merged.subscription_status = duplicate.subscription_status;
+    }
+
+    merged.engagement_score = primary.engagement_score.max(duplicate.engagement_score);
+    merged.created_at = primary.created_at.min(duplicate.created_at);
+    merged.updated_at = now;
+
+    MergeResult { merged, conflicts }
+}
+
+fn merge_optional(
+    field: &str,
+    primary: &Option<String>,
+    duplicate: &Option<String>,
+    note: &mut impl FnMut(&str, &str, &str),
+) -> Option<String> {
+    match (primary, duplicate) {
+        (Some(kept), Some(discarded)) => {
+            note(field, kept, discarded);
+            Some(kept.clone())
+        }
+        (Some(kept), None) => Some(kept.clone()),
+        (None, other) => other.clone(),
+    }
+}
+
+/// Whether the status records an actual relationship rather than a prospect
+fn has_relationship(status: ContactStatus) -> bool {
+    matches!(
+        status,
+        ContactStatus::Customer | ContactStatus::Partner | ContactStatus::Investor
+    )
+}
+
+// ============================================================================
+// Duplicate detection
+// ============================================================================
+
+/// Minimum combined score for a pair to be reported
+pub const DUPLICATE_THRESHOLD: f64 = 0.6;
+
+/// Why two contacts look like the same person
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateReason {
+    /// Same address once case, `+tags` and Gmail dots are ignored
+    SameEmail,
+    /// Same or nearly the same mailbox name at different domains
+    SimilarEmail,
+    SameName,
+    /// Full names within a couple of typos of each other
+    SimilarName,
+    SamePhone,
+    /// Same company; only considered for contacts about to be created
+    SameCompany,
+}
+
+impl DuplicateReason {
+    fn weight(&self) -> f64 {
+        match self {
+            DuplicateReason::SameEmail => 1.0,
+            DuplicateReason::SamePhone => 0.7,
+            DuplicateReason::SameName => 0.6,
+            DuplicateReason::SimilarEmail => 0.4,
+            DuplicateReason::SimilarName => 0.4,
+            DuplicateReason::SameCompany => 0.2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DuplicateMatch {
+    /// 0-1, higher is more certain
+    pub score: f64,
+    pub reasons: Vec<DuplicateReason>,
+}
+
+/// Compare two contacts; `None` when they don't look alike enough
+pub fn match_duplicate(a: &Contact, b: &Contact) -> Option<DuplicateMatch> {
+    let reasons = duplicate_reasons(a, b);
+    let score = score(&reasons);
+    (score >= DUPLICATE_THRESHOLD).then_some(DuplicateMatch { score, reasons })
+}
+
+/// Compare a contact about to be created with an existing one
+///
+/// It is a likely duplicate when the two share an address or a phone
+/// number, have a nearly identical mailbox name and a similar name or the
+/// same company, or have the same name at the same company.
+pub fn match_new_contact(new: &Contact, existing: &Contact) -> Option<DuplicateMatch> {
+    let mut reasons = duplicate_reasons(new, existing);
+    if new.company_id.is_some() && new.company_id == existing.company_id {
+        reasons.push(DuplicateReason::SameCompany);
+    }
+
+    let has = |reason| reasons.contains(&reason);
+    let same_person = has(DuplicateReason::SameName) || has(DuplicateReason::SimilarName);
+    let likely = has(DuplicateReason::SameEmail)
+        || has(DuplicateReason::SamePhone)
+        || (has(DuplicateReason::SimilarEmail) && (same_person || has(DuplicateReason::SameCompany)))
+        || (has(DuplicateReason::SameName) && has(DuplicateReason::SameCompany));
+
+    likely.then(|| DuplicateMatch {
+        score: score(&reasons),
+        reasons,
+    })
+}
+
+fn score(reasons: &[DuplicateReason]) -> f64 {
+    reasons.iter().map(DuplicateReason::weight).sum::<f64>().min(1.0)
+}
+
+/// Every rule two contacts match on, however weakly
+pub fn duplicate_reasons(a: &Contact, b: &Contact) -> Vec<DuplicateReason> {
+    let mut reasons = Vec::new();
+
+    let (a_local, a_domain) = canonical_email(&a.email);
+    let (b_local, b_domain) = canonical_email(&b.email);
+    if a_local == b_local && a_domain == b_domain {
+        reasons.push(DuplicateReason::SameEmail);
+    } else if a_local.len() >= 5 && levenshtein(&a_local, &b_local) <= 1 {
+        reasons.push(DuplicateReason::SimilarEmail);
+    }
+
+    let a_name = normalized_name(a);
+    let b_name = normalized_name(b);
+    if a_name == b_name {
+        reasons.push(DuplicateReason::SameName);
+    } else if a_name.len() >= 6 && levenshtein(&a_name, &b_name) <= 2 {
+        reasons.push(DuplicateReason::SimilarName);
+    }
+
+    if let (Some(a_phone), Some(b_phone)) = (phone_digits(a), phone_digits(b))
+        && a_phone == b_phone
+    {
+        reasons.push(DuplicateReason::SamePhone);
+    }
+
+    reasons
+}
+
+/// Find likely duplicate pairs among `contacts`, most certain first
+///
+/// Only contacts sharing a blocking key (mailbox name, phone digits, or
+/// name prefix) are compared, which keeps this far below n² in practice.
+/// Returned indices refer to positions in `contacts`.
+pub fn find_duplicate_pairs(contacts: &[Contact]) -> Vec<(usize, usize, DuplicateMatch)> {
+    let mut blocks: std::collections::HashMap<String, Vec<usize>> = std::collections::HashMap::new();
+    for (i, contact) in contacts.iter().enumerate() {
+        for key in blocking_keys(contact) {
+            blocks.entry(key).or_default().push(i);
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    let mut pairs = Vec::new();
+
+    for members in blocks.values() {
+        for (n, &i) in members.iter().enumerate() {
+            for &j in &members[n + 1..] {
+                if !seen.insert((i, j)) {
+                    continue;
+                }
+                if let Some(m) = match_duplicate(&contacts[i], &contacts[j]) {
+                    pairs.push((i, j, m));
+                }
+            }
+        }
+    }
+
+    pairs.sort_by(|a, b| b.2.score.total_cmp(&a.2.score).then((a.0, a.1).cmp(&(b.0, b.1))));
+    pairs
+}
+
+fn blocking_keys(contact: &Contact) -> Vec<String> {
+    let (local, _) = canonical_email(&contact.email);
+    let mut keys = vec![format!("email:{}", local)];
+
+    if let Some(phone) = phone_digits(contact) {
+        keys.push(format!("phone:{}", phone));
+    }
+
+    let first: String = contact.first_name.to_lowercase().chars().take(1).collect();
+    let last: String = contact.last_name.to_lowercase().chars().take(3).collect();
+    keys.push(format!("name:{}{}", first, last));
+
+    keys
+}
+
+/// Split an email into a canonical mailbox name and domain
+///
+/// Lowercased, `+tag` suffix dropped, and dots removed for Gmail, which
+/// ignores them.
+pub fn canonical_email(email: &str) -> (String, String) {
+    let email = email.trim().to_lowercase();
+    let (local, domain) = email.rsplit_once('@').unwrap_or((email.as_str(), ""));
+    let local = local.split('+').next().unwrap_or(local);
+
+    let local = if domain == "gmail.com" || domain == "googlemail.com" {
+        local.replace('.', "")
+    } else {
+        local.to_string()
+    };
+
+    (local, domain.to_string())
+}
+
+fn normalized_name(contact: &Contact) -> String {
+    format!("{} {}", contact.first_name.trim(), contact.last_name.trim()).to_lowercase()
+}
+
+/// Phone number digits, if there are enough to be meaningful
+fn phone_digits(contact: &Contact) -> Option<String> {
+    let digits: String = contact.phone.as_deref()?.chars().filter(char::is_ascii_digit).collect();
+    (digits.len() >= 7).then_some(digits)
+}
+
+/// Edit distance between two strings
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            current[j + 1] = (previous[j] + cost).min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{ContactBuilder, SubscriptionStatus};
+
+    fn contact(first: &str, last: &str, email: &str) -> Contact {
+        ContactBuilder::new()
+            .first_name(first)
+            .last_name(last)
+            .email(email)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_merge_keeps_primary_identity_and_fills_gaps() {
+        let primary = contact("John", "Smith", "john@acme.com");
+        let mut duplicate = contact("Johnny", "Smith", "jsmith@gmail.com");
+        duplicate.phone = Some("+1 555 123 4567".into());
+
+        let result = merge_contacts(&primary, &duplicate, Utc::now());
+
+        assert_eq!(result.merged.first_name, "John");
+        assert_eq!(result.merged.email, "john@acme.com");
+        assert_eq!(result.merged.phone.as_deref(), Some("+1 555 123 4567"));
+        assert!(result.conflicts.iter().any(|c| c.field == "first_name" && c.discarded == "Johnny"));
+        assert!(!result.conflicts.iter().any(|c| c.field == "phone"));
+    }
+
+    #[test]
+    fn test_merge_fills_custom_fields_from_duplicate() {
+        let mut primary = contact("Ada", "Lovelace", "ada@example.com");
+        primary.custom_fields.insert("plan".into(), "pro".into());
+        let mut duplicate = contact("Ada", "Lovelace", "ada.l@example.com");
+        duplicate.custom_fields.insert("plan".into(), "free".into());
+        duplicate.custom_fields.insert("seats".into(), "3".into());
+
+        let result = merge_contacts(&primary, &duplicate, Utc::now());
+
+        assert_eq!(result.merged.custom_fields["plan"], "pro");
+        assert_eq!(result.merged.custom_fields["seats"], "3");
+        assert!(result.conflicts.iter().any(|c| c.field == "custom_fields.plan" && c.discarded == "free"));
+    }
+
+    #[test]
+    fn test_merge_unions_tags_and_keeps_best_signals() {
+        let mut primary = contact("Ada", "Lovelace", "ada@example.com");
+        primary.tags = vec!["vip".into()];
+        primary.engagement_score = 20.0;
+
+        let mut duplicate = contact("Ada", "Lovelace", "ada.l@example.com");
+        duplicate.tags = vec!["vip".into(), "speaker".into()];
+        duplicate.engagement_score = 65.0;
+        duplicate.status = ContactStatus::Customer;
+        duplicate.subscription_status = SubscriptionStatus::Unsubscribed;
+
+        let result = merge_contacts(&primary, &duplicate, Utc::now());
+
+        assert_eq!(result.merged.tags, vec!["vip".to_string(), "speaker".to_string()]);
+        assert_eq!(result.merged.engagement_score, 65.0);
+        assert_eq!(result.merged.status, ContactStatus::Customer);
+        assert_eq!(result.merged.subscription_status, SubscriptionStatus::Unsubscribed);
+    }
+
+    #[test]
+    fn test_canonical_email() {
+        assert_eq!(canonical_email("J.Doe+news@Gmail.com"), ("jdoe".into(), "gmail.com".into()));
+        assert_eq!(canonical_email("j.doe@acme.com"), ("j.doe".into(), "acme.com".into()));
+    }
+
+    #[test]
+    fn test_match_duplicate() {
+        let a = contact("Jane", "Doe", "jane.doe+crm@gmail.com");
+        let b = contact("Jane", "Doe", "janedoe@gmail.com");
+        let m = match_duplicate(&a, &b).unwrap();
+        assert_eq!(m.score, 1.0);
+        assert!(m.reasons.contains(&DuplicateReason::SameEmail));
+
+        let c = contact("Jayne", "Doe", "jdoe@work.io");
+        assert!(match_duplicate(&a, &c).is_none());
+
+        let d = contact("Bob", "Stone", "bob@one.com");
+        let e = contact("Robert", "Stone", "bob@two.com");
+        assert!(match_duplicate(&d, &e).is_none());
+    }
+
+    #[test]
+    fn test_shared_phone_and_similar_name() {
+        let mut a = contact("Katherine", "Johnson", "kj@nasa.gov");
+        let mut b = contact("Katharine", "Johnson", "katherine@home.net");
+        a.phone = Some("+1 (555) 010-2030".into());
+        b.phone = Some("15550102030".into());
+
+        let m = match_duplicate(&a, &b).unwrap();
+        assert!(m.reasons.contains(&DuplicateReason::SamePhone));
+        assert!(m.reasons.contains(&DuplicateReason::SimilarName));
+    }
+
+    #[test]
+    fn test_match_new_contact_needs_more_than_a_name() {
+        let mut new = contact("John", "Smith", "john.smith@acme.com");
+        new.company_id = Some("acme".into());
+
+        let mut namesake = contact("John", "Smith", "js@other.org");
+        assert!(match_new_contact(&new, &namesake).is_none());
+
+        namesake.company_id = Some("acme".into());
+        let m = match_new_contact(&new, &namesake).unwrap();
+        assert_eq!(m.reasons, vec![DuplicateReason::SameName, DuplicateReason::SameCompany]);
+
+        let typo = contact("Jon", "Smyth", "jhn.smith@other.org");
+        assert!(match_new_contact(&new, &typo).unwrap().reasons.contains(&DuplicateReason::SimilarEmail));
+
+        // A similar mailbox name alone is a coincidence
+        let stranger = contact("Alice", "Brown", "jhn.smith@other.org");
+        assert!(match_new_contact(&new, &stranger).is_none());
+    }
+
+    #[test]
+    fn test_find_duplicate_pairs() {
+        let contacts = vec![
+            contact("Jane", "Doe", "jane@acme.com"),
+            contact("Sam", "Lee", "sam@lee.dev"),
+            contact("Jane", "Doe", "JANE+x@acme.com"),
+        ];
+
+        let pairs = find_duplicate_pairs(&contacts);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!((pairs[0].0, pairs[0].1), (0, 2));
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("same", "same"), 0);
+    }
+}
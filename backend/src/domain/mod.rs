@@ -10,12 +10,78 @@
 //! The domain layer defines WHAT the business rules are.
 //! Other layers (handlers, repositories) define HOW to execute them.
 
+pub mod attachment;
+pub mod audit;
+pub mod avatar;
+pub mod churn;
 pub mod contact;
+pub mod contact_report;
+pub mod deal;
+pub mod dedupe;
+pub mod deliverability;
+pub mod email_layout;
+pub mod email_thread;
+pub mod digest;
 pub mod validation;
 pub mod engagement;
+pub mod enrichment;
+pub mod event;
+pub mod form;
+pub mod gdpr;
+pub mod import;
+pub mod inbound;
+pub mod inbox;
+pub mod interaction;
+pub mod merge;
+pub mod network;
+pub mod personalization;
+pub mod pipeline;
+pub mod recommendation;
+pub mod relationship;
+pub mod schedule;
+pub mod sender;
+pub mod sequence;
+pub mod short_link;
 pub mod errors;
+pub mod tracking;
+pub mod user_agent;
+pub mod webhook;
+pub mod win_loss;
 
+pub use attachment::*;
+pub use audit::*;
+pub use avatar::*;
+pub use churn::*;
 pub use contact::*;
+pub use contact_report::*;
+pub use deal::*;
+pub use dedupe::*;
+pub use deliverability::*;
+pub use email_layout::*;
+pub use email_thread::*;
+pub use digest::*;
 pub use validation::*;
 pub use engagement::*;
+pub use enrichment::*;
+pub use event::*;
+pub use form::*;
+pub use gdpr::*;
+pub use import::*;
+pub use inbound::*;
+pub use inbox::*;
+pub use interaction::*;
+pub use merge::*;
+pub use network::*;
+pub use personalization::*;
+pub use pipeline::*;
+pub use recommendation::*;
+pub use relationship::*;
+pub use schedule::*;
+pub use sender::*;
+pub use sequence::*;
+pub use short_link::*;
 pub use errors::*;
+pub use tracking::*;
+pub use user_agent::*;
+pub use webhook::*;
+pub use win_loss::*;
@@ -0,0 +1,112 @@
+//! Network Domain - which hosts outgoing connections may reach
+//!
+//! Webhooks and mailboxes connect to hosts a workspace chooses. Those must
+//! be on the public internet: loopback, private, link-local (which holds
+//! cloud metadata endpoints) and other internal addresses would let a
+//! workspace reach the server's own network.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use super::errors::{DomainError, DomainResult};
+
+/// Host names that only resolve inside a network
+const INTERNAL_SUFFIXES: &[&str] = &[".localhost", ".local", ".internal", ".localdomain", ".home.arpa"];
+
+/// Whether an address is reachable on the public internet
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public_ipv4(mapped),
+            None => is_public_ipv6(ip),
+        },
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        // Shared address space (carrier-grade NAT)
+        || (a == 100 && (64..128).contains(&b))
+        // "This network" and reserved
+        || a == 0
+        || a >= 240)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local, fc00::/7
+        || (first & 0xfe00) == 0xfc00
+        // Link-local, fe80::/10
+        || (first & 0xffc0) == 0xfe80)
+}
+
+/// Whether a host name or address literal points inside a network
+pub fn is_internal_host(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']').trim_end_matches('.');
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return !is_public_ip(ip);
+    }
+
+    let host = host.to_lowercase();
+    host.is_empty()
+        || host == "localhost"
+        || !host.contains('.')
+        || INTERNAL_SUFFIXES.iter().any(|suffix| host.ends_with(suffix))
+}
+
+/// Check a host a workspace asked us to connect to is a public one
+///
+/// Only the name is checked here; what it resolves to is checked again
+/// when connecting.
+pub fn validate_public_host(field: &str, host: &str) -> DomainResult<()> {
+    if is_internal_host(host) {
+        return Err(DomainError::InvalidField {
+            field: field.to_string(),
+            reason: "Must be a public host, not a local, private or internal one".to_string(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_internal_hosts() {
+        for host in [
+            "localhost",
+            "api.localhost",
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "[::1]",
+            "[fd00::1]",
+            "[fe80::1]",
+            "[::ffff:127.0.0.1]",
+            "metadata.google.internal",
+            "printer.local",
+            "intranet",
+        ] {
+            assert!(is_internal_host(host), "{} should be internal", host);
+        }
+
+        for host in ["example.com", "hooks.slack.com", "8.8.8.8", "[2606:4700::1111]"] {
+            assert!(!is_internal_host(host), "{} should be public", host);
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! Personalization - Merge variables in campaign email
+//!
+//! Templates reference recipient data as `{{first_name}}`, `{{company}}` or
+//! a contact's custom field, e.g. `{{plan}}`. A variable may carry a
+//! fallback for contacts without a value: `{{first_name|there}}`.
+//!
+//! Built-in variables are `first_name`, `last_name`, `full_name`, `email`
+//! and `company`; they take precedence over custom fields of the same name.
+//!
+//! Rendering never fails: a variable with neither a value nor a fallback is
+//! left in the text as written and reported, so callers decide whether that
+//! blocks a send (it does for campaign execution) or is just shown (preview).
+
+use std::collections::BTreeMap;
+
+/// The values merge variables resolve to for one recipient
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MergeFields {
+    values: BTreeMap<String, String>,
+}
+
+impl MergeFields {
+    pub fn new(first_name: &str, last_name: &str, email: &str) -> Self {
+        let full_name = format!("{} {}", first_name.trim(), last_name.trim());
+
+        let mut fields = Self::default();
+        fields.set("first_name", first_name);
+        fields.set("last_name", last_name);
+        fields.set("full_name", full_name.trim());
+        fields.set("email", email);
+        fields
+    }
+
+    /// Name of the contact's company
+    pub fn company(mut self, company: Option<&str>) -> Self {
+        if let Some(company) = company {
+            self.set("company", company);
+        }
+        self
+    }
+
+    /// Custom fields; built-in variables are never overwritten
+    pub fn custom_fields(mut self, fields: &BTreeMap<String, String>) -> Self {
+        for (key, value) in fields {
+            if !self.values.contains_key(key) && !is_builtin(key) {
+                self.set(key, value);
+            }
+        }
+        self
+    }
+
+    /// Value of a variable; blank values count as missing
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    fn set(&mut self, name: &str, value: &str) {
+        let value = value.trim();
+        if !value.is_empty() {
+            self.values.insert(name.to_string(), value.to_string());
+        }
+    }
+}
+
+fn is_builtin(name: &str) -> bool {
+    matches!(name, "first_name" | "last_name" | "full_name" | "email" | "company")
+}
+
+/// How substituted values are encoded for the text they land in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Escape {
+    /// Plain text: values go in as they are
+    None,
+    /// HTML: `<`, `>`, `&` and quotes are escaped
+    Html,
+    /// A URL: values are percent-encoded
+    Url,
+}
+
+/// A rendered template and the variables that could not be resolved
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rendered {
+    pub text: String,
+    /// Names without a value or fallback, in order of first appearance
+    pub unresolved: Vec<String>,
+}
+
+impl Rendered {
+    pub fn is_complete(&self) -> bool {
+        self.unresolved.is_empty()
+    }
+}
+
+/// Substitute every `{{variable}}` in `template`
+///
+/// Names are case-insensitive and may be padded with spaces
+/// (`{{ First_Name }}`). An unclosed `{{` is left as literal text.
+pub fn render(template: &str, fields: &MergeFields, escape: Escape) -> Rendered {
+    let mut text = String::with_capacity(template.len());
+    let mut unresolved: Vec<String> = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let tag = &rest[start..start + 2 + len + 2];
+        let (name, fallback) = parse_tag(&rest[start + 2..start + 2 + len]);
+
+        text.push_str(&rest[..start]);
+        match fields.get(&name).or(fallback) {
+            Some(value) => text.push_str(&encode(value, escape)),
+            None => {
+                text.push_str(tag);
+                if !unresolved.contains(&name) {
+                    unresolved.push(name);
+                }
+            }
+        }
+        rest = &rest[start + tag.len()..];
+    }
+    text.push_str(rest);
+
+    Rendered { text, unresolved }
+}
+
+/// Names of the variables a template uses, in order of first appearance
+pub fn variables(template: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let (name, _) = parse_tag(&rest[start + 2..start + 2 + len]);
+        if !names.contains(&name) {
+            names.push(name);
+        }
+        rest = &rest[start + 2 + len + 2..];
+    }
+
+    names
+}
+
+/// Split `name|fallback`; an empty fallback counts as none
+fn parse_tag(inner: &str) -> (String, Option<&str>) {
+    let (name, fallback) = match inner.split_once('|') {
+        Some((name, fallback)) => (name, Some(fallback.trim()).filter(|f| !f.is_empty())),
+        None => (inner, None),
+    };
+    (name.trim().to_lowercase(), fallback)
+}
+
+/// Make text safe to put in HTML
+pub fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn encode(value: &str, escape: Escape) -> String {
+    match escape {
+        Escape::None => value.to_string(),
+        Escape::Html => escape_html(value),
+        Escape::Url => value
+            .bytes()
+            .map(|b| match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+                _ => format!("%{:02X}", b),
+            })
+            .collect(),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ada() -> MergeFields {
+        MergeFields::new("Ada", "Lovelace", "ada@example.com")
+            .company(Some("Analytical Engines"))
+            .custom_fields(&BTreeMap::from([
+                ("plan".to_string(), "pro".to_string()),
+                ("first_name".to_string(), "Not Ada".to_string()),
+            ]))
+    }
+
+    #[test]
+    fn test_render_builtin_and_custom_fields() {
+        let rendered = render("Hi {{first_name}} at {{ Company }}, your {{plan}} plan", &ada(), Escape::None);
+
+        assert_eq!(rendered.text, "Hi Ada at Analytical Engines, your pro plan");
+        assert!(rendered.is_complete());
+    }
+
+    #[test]
+    fn test_render_uses_fallback_only_when_missing() {
+        let fields = MergeFields::new("", "Lovelace", "ada@example.com");
+
+        assert_eq!(render("Hi {{first_name|there}}", &fields, Escape::None).text, "Hi there");
+        assert_eq!(render("Hi {{first_name|there}}", &ada(), Escape::None).text, "Hi Ada");
+    }
+
+    #[test]
+    fn test_render_reports_unresolved_variables_once() {
+        let rendered = render("{{seats}} seats, {{seats}} again, {{company}}", &ada(), Escape::None);
+
+        assert_eq!(rendered.unresolved, vec!["seats"]);
+        assert_eq!(rendered.text, "{{seats}} seats, {{seats}} again, Analytical Engines");
+    }
+
+    #[test]
+    fn test_render_escapes_for_html_and_urls() {
+        let fields = MergeFields::new("Bob & <Co>", "Smith", "bob@example.com");
+
+        assert_eq!(render("<b>{{first_name}}</b>", &fields, Escape::Html).text, "<b>Bob &amp; &lt;Co&gt;</b>");
+        assert_eq!(
+            render("https://x.io/?n={{first_name}}", &fields, Escape::Url).text,
+            "https://x.io/?n=Bob%20%26%20%3CCo%3E"
+        );
+    }
+
+    #[test]
+    fn test_unclosed_tag_is_literal() {
+        assert_eq!(render("Hi {{first_name", &ada(), Escape::None).text, "Hi {{first_name");
+    }
+
+    #[test]
+    fn test_variables() {
+        assert_eq!(variables("{{a}} {{ B|x }} {{a}}"), vec!["a", "b"]);
+    }
+}
@@ -0,0 +1,131 @@
+//! Pipeline Domain - manual card order on the kanban board
+//!
+//! Cards in a column are ordered by a fractional position, so dropping a
+//! card between two others only rewrites the moved card. Cards never placed
+//! by hand sort first; a card dropped among them gives just them positions,
+//! below the first placed card. Only when there is no room left between the
+//! neighbours is the whole column renumbered.
+
+/// Gap between cards after a column is renumbered
+pub const POSITION_STEP: f64 = 1024.0;
+
+/// Position for a card dropped at `index` in a column
+///
+/// `column` holds the positions of the other cards in board order; `None`
+/// marks a card that was never moved. An index past the end appends.
+/// Returns `None` when the column has to be renumbered first.
+pub fn drop_position(column: &[Option<f64>], index: usize) -> Option<f64> {
+    let index = index.min(column.len());
+    let before = index.checked_sub(1).map(|i| column[i]);
+    let after = column.get(index).copied();
+
+    match (before, after) {
+        (None, None) => Some(POSITION_STEP),
+        (None, Some(Some(next))) => Some(next - POSITION_STEP),
+        (Some(Some(previous)), None) => Some(previous + POSITION_STEP),
+        (Some(Some(previous)), Some(Some(next))) => {
+            let middle = previous + (next - previous) / 2.0;
+            (previous < middle && middle < next).then_some(middle)
+        }
+        // An unplaced neighbour has nothing to be ordered against
+        _ => None,
+    }
+}
+
+/// Positions for a renumbered column of `len` cards, in board order
+pub fn renumbered_positions(len: usize) -> impl Iterator<Item = f64> {
+    (1..=len).map(|i| i as f64 * POSITION_STEP)
+}
+
+/// Where a dropped card goes, and the other cards it needs placed first
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placement {
+    /// Position of the dropped card
+    pub position: f64,
+    /// `(index in the column, position)` of other cards; their order stays the same
+    pub others: Vec<(usize, f64)>,
+}
+
+/// Place a card dropped at `index` in a column, as for `drop_position`
+pub fn place_card(column: &[Option<f64>], index: usize) -> Placement {
+    let index = index.min(column.len());
+    let unplaced = column.iter().take_while(|p| p.is_none()).count();
+
+    // Slot `j` of `slots` cards, the dropped one at `index`, counted so the last ends at `end`
+    let slots = |count: usize, end: f64| {
+        let slot = move |j: usize| end - (count - 1 - j) as f64 * POSITION_STEP;
+        let others = (0..count - 1)
+            .map(|i| (i, slot(if i < index { i } else { i + 1 })))
+            .collect();
+        Placement {
+            position: slot(index),
+            others,
+        }
+    };
+
+    if unplaced > 0 && index <= unplaced {
+        let first_placed = column.get(unplaced).copied().flatten();
+        let end = first_placed.map_or((unplaced + 1) as f64 * POSITION_STEP, |p| p - POSITION_STEP);
+        return slots(unplaced + 1, end);
+    }
+
+    match drop_position(column, index) {
+        Some(position) => Placement {
+            position,
+            others: Vec::new(),
+        },
+        None => slots(column.len() + 1, (column.len() + 1) as f64 * POSITION_STEP),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drop_between_placed_cards() {
+        let column = [Some(1024.0), Some(2048.0), Some(3072.0)];
+
+        assert_eq!(drop_position(&column, 0), Some(0.0));
+        assert_eq!(drop_position(&column, 1), Some(1536.0));
+        assert_eq!(drop_position(&column, 3), Some(4096.0));
+        assert_eq!(drop_position(&column, 99), Some(4096.0));
+        assert_eq!(drop_position(&[], 0), Some(POSITION_STEP));
+    }
+
+    #[test]
+    fn test_renumber_when_there_is_no_room() {
+        assert_eq!(drop_position(&[None, Some(1024.0)], 1), None);
+        assert_eq!(drop_position(&[Some(5.0), Some(5.0)], 1), None);
+
+        let crowded = [Some(1.0), Some(1.0 + f64::EPSILON)];
+        assert_eq!(drop_position(&crowded, 1), None);
+
+        assert_eq!(renumbered_positions(3).collect::<Vec<_>>(), vec![1024.0, 2048.0, 3072.0]);
+    }
+
+    #[test]
+    fn test_place_between_placed_cards_moves_only_the_card() {
+        let placement = place_card(&[None, Some(1024.0), Some(2048.0)], 2);
+        assert_eq!(placement.position, 1536.0);
+        assert!(placement.others.is_empty());
+    }
+
+    #[test]
+    fn test_place_among_unplaced_cards_places_only_them() {
+        let placement = place_card(&[None, None, Some(1024.0), Some(2048.0)], 1);
+        assert_eq!(placement.position, -1024.0);
+        assert_eq!(placement.others, vec![(0, -2048.0), (1, 0.0)]);
+
+        let placement = place_card(&[None, None], 2);
+        assert_eq!(placement.position, 3072.0);
+        assert_eq!(placement.others, vec![(0, 1024.0), (1, 2048.0)]);
+    }
+
+    #[test]
+    fn test_place_renumbers_a_crowded_column() {
+        let placement = place_card(&[Some(1.0), Some(1.0 + f64::EPSILON), Some(3.0)], 1);
+        assert_eq!(placement.position, 2048.0);
+        assert_eq!(placement.others, vec![(0, 1024.0), (1, 3072.0), (2, 4096.0)]);
+    }
+}
@@ -0,0 +1,257 @@
+//! Next Best Action - what to do about a contact, and how urgently
+//!
+//! Generalizes the rule of thumb behind `ai::ai_summary`'s next best action
+//! (introduce, re-engage or call) to every signal the CRM has about a
+//! contact: engagement level, trend and velocity, their last interaction,
+//! open tasks and their furthest open deal.
+//!
+//! The rules are checked in order and the first match wins, so the most
+//! time-sensitive situations come first: someone waiting for an answer,
+//! then a late-stage deal going quiet, then work already on the list.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::deal::DealStage;
+use super::engagement::{EngagementLevel, EngagementTrend, Interaction, InteractionType};
+
+/// Days a message from the contact counts as waiting for an answer
+pub const REPLY_WINDOW_DAYS: i64 = 14;
+
+/// Days without interactions after which a proposal or negotiation has stalled
+pub const STALLED_DEAL_DAYS: i64 = 14;
+
+/// Days without interactions after which a contact needs re-engaging
+pub const DORMANT_DAYS: i64 = 30;
+
+/// Engagement velocity below this counts as dropping off fast
+pub const VELOCITY_DROP: f64 = -10.0;
+
+/// The kind of action recommended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NextActionKind {
+    Reply,
+    AdvanceDeal,
+    CompleteTasks,
+    CheckIn,
+    BookMeeting,
+    AskForReferral,
+    Nurture,
+    ReEngage,
+    Introduce,
+}
+
+/// How soon an action should be taken; sorts most urgent first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionPriority {
+    High,
+    Medium,
+    Low,
+}
+
+/// Everything the rules look at for one contact
+#[derive(Debug, Clone)]
+pub struct RecommendationSignals {
+    pub engagement_score: f64,
+    pub trend: EngagementTrend,
+    pub velocity: f64,
+    /// Most recent interaction that counts towards engagement
+    pub last_interaction: Option<Interaction>,
+    /// Task entries not marked completed
+    pub open_tasks: u32,
+    /// Furthest stage among the contact's open deals
+    pub deal_stage: Option<DealStage>,
+}
+
+/// A recommended action and why
+#[derive(Debug, Clone, PartialEq)]
+pub struct NextAction {
+    pub kind: NextActionKind,
+    pub priority: ActionPriority,
+    /// What to do, in a few words
+    pub action: String,
+    /// The signals behind it
+    pub reason: String,
+}
+
+impl NextAction {
+    fn new(kind: NextActionKind, priority: ActionPriority, action: &str, reason: impl Into<String>) -> Self {
+        Self {
+            kind,
+            priority,
+            action: action.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Pick the next best action for a contact
+pub fn recommend_next_action(signals: &RecommendationSignals, now: DateTime<Utc>) -> NextAction {
+    use ActionPriority::*;
+    use NextActionKind::*;
+
+    let level = EngagementLevel::from_score(signals.engagement_score);
+    let last = signals.last_interaction.as_ref();
+    let days_since = last.map(|i| (now - i.occurred_at).num_days());
+    let cooling = signals.trend == EngagementTrend::Declining || signals.velocity < VELOCITY_DROP;
+
+    if let Some(last) = last {
+        let days = (now - last.occurred_at).num_days();
+        if awaits_answer(last.interaction_type) && days <= REPLY_WINDOW_DAYS {
+            return NextAction::new(
+                Reply,
+                High,
+                "Answer their message",
+                format!("{} {} and hasn't heard back", describe(last.interaction_type), ago(days)),
+            );
+        }
+    }
+
+    if let Some(stage @ (DealStage::Proposal | DealStage::Negotiation)) = signals.deal_stage {
+        let quiet = !matches!(days_since, Some(days) if days <= STALLED_DEAL_DAYS);
+        if quiet || cooling {
+            let reason = match days_since {
+                Some(days) if quiet => format!("Deal in {} with no interactions for {} days", stage, days),
+                None => format!("Deal in {} with no interactions recorded", stage),
+                _ => format!("Deal in {} while engagement is declining", stage),
+            };
+            return NextAction::new(AdvanceDeal, High, "Follow up on the deal", reason);
+        }
+    }
+
+    if signals.open_tasks > 0 {
+        let priority = if signals.deal_stage.is_some() { High } else { Medium };
+        let reason = match signals.open_tasks {
+            1 => "1 open task".to_string(),
+            n => format!("{} open tasks", n),
+        };
+        return NextAction::new(CompleteTasks, priority, "Work through the open tasks", reason);
+    }
+
+    if let Some(stage) = signals.deal_stage {
+        return NextAction::new(
+            AdvanceDeal,
+            Medium,
+            "Move the deal to its next stage",
+            format!("Open deal in {}", stage),
+        );
+    }
+
+    let Some(days) = days_since else {
+        return NextAction::new(Introduce, Low, "Send an introductory email", "No interactions recorded yet");
+    };
+
+    if days > DORMANT_DAYS {
+        let priority = if level == EngagementLevel::Cold { Low } else { Medium };
+        return NextAction::new(
+            ReEngage,
+            priority,
+            "Re-engage with a check-in message",
+            format!("No interactions for {} days", days),
+        );
+    }
+
+    if cooling && matches!(level, EngagementLevel::Engaged | EngagementLevel::Hot | EngagementLevel::Champion) {
+        return NextAction::new(
+            CheckIn,
+            High,
+            "Check in before they drift away",
+            format!("{:?} contact whose engagement is declining", level),
+        );
+    }
+
+    let reason = format!("{:?} contact, engagement {:?}", level, signals.trend).to_lowercase();
+    match level {
+        EngagementLevel::Champion => NextAction::new(AskForReferral, Medium, "Ask for a referral or case study", reason),
+        EngagementLevel::Hot => NextAction::new(BookMeeting, High, "Schedule a call or meeting", reason),
+        EngagementLevel::Engaged => NextAction::new(Nurture, Medium, "Invite to an event or offer a demo", reason),
+        EngagementLevel::Warming => NextAction::new(Nurture, Low, "Share relevant content", reason),
+        EngagementLevel::Cold => NextAction::new(ReEngage, Low, "Re-engage with a personalized message", reason),
+    }
+}
+
+/// Interactions where the contact is waiting for us
+fn awaits_answer(interaction_type: InteractionType) -> bool {
+    matches!(interaction_type, InteractionType::EmailReceived | InteractionType::FormSubmission)
+}
+
+fn describe(interaction_type: InteractionType) -> &'static str {
+    match interaction_type {
+        InteractionType::FormSubmission => "Submitted a form",
+        _ => "Emailed us",
+    }
+}
+
+fn ago(days: i64) -> String {
+    match days {
+        0 => "today".to_string(),
+        1 => "yesterday".to_string(),
+        n => format!("{} days ago", n),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn signals(score: f64, last: Option<(InteractionType, i64)>) -> RecommendationSignals {
+        let now = Utc::now();
+        RecommendationSignals {
+            engagement_score: score,
+            trend: EngagementTrend::Stable,
+            velocity: 0.0,
+            last_interaction: last.map(|(t, days)| Interaction::new(t, now - Duration::days(days))),
+            open_tasks: 0,
+            deal_stage: None,
+        }
+    }
+
+    #[test]
+    fn test_waiting_messages_come_before_deals_and_tasks() {
+        let mut waiting = signals(30.0, Some((InteractionType::EmailReceived, 2)));
+        waiting.open_tasks = 3;
+        waiting.deal_stage = Some(DealStage::Negotiation);
+
+        let action = recommend_next_action(&waiting, Utc::now());
+        assert_eq!(action.kind, NextActionKind::Reply);
+        assert_eq!(action.priority, ActionPriority::High);
+        assert_eq!(action.reason, "Emailed us 2 days ago and hasn't heard back");
+
+        // Once we've answered, the quiet negotiation is next, then the tasks
+        waiting.last_interaction = Some(Interaction::new(InteractionType::EmailSent, Utc::now() - Duration::days(20)));
+        assert_eq!(recommend_next_action(&waiting, Utc::now()).kind, NextActionKind::AdvanceDeal);
+
+        waiting.deal_stage = Some(DealStage::Prospecting);
+        let action = recommend_next_action(&waiting, Utc::now());
+        assert_eq!(action.kind, NextActionKind::CompleteTasks);
+        assert_eq!(action.priority, ActionPriority::High);
+    }
+
+    #[test]
+    fn test_engagement_decides_without_deals_or_tasks() {
+        let now = Utc::now();
+        let kind = |s: &RecommendationSignals| recommend_next_action(s, now).kind;
+
+        assert_eq!(kind(&signals(0.0, None)), NextActionKind::Introduce);
+        assert_eq!(kind(&signals(50.0, Some((InteractionType::EmailOpen, 45)))), NextActionKind::ReEngage);
+        assert_eq!(kind(&signals(70.0, Some((InteractionType::MeetingAttended, 3)))), NextActionKind::BookMeeting);
+        assert_eq!(kind(&signals(90.0, Some((InteractionType::EventAttendance, 3)))), NextActionKind::AskForReferral);
+
+        let mut cooling = signals(70.0, Some((InteractionType::EmailOpen, 3)));
+        cooling.velocity = -15.0;
+        let action = recommend_next_action(&cooling, now);
+        assert_eq!(action.kind, NextActionKind::CheckIn);
+        assert_eq!(action.priority, ActionPriority::High);
+    }
+
+    #[test]
+    fn test_priorities_sort_most_urgent_first() {
+        let mut priorities = vec![ActionPriority::Low, ActionPriority::High, ActionPriority::Medium];
+        priorities.sort();
+        assert_eq!(priorities, vec![ActionPriority::High, ActionPriority::Medium, ActionPriority::Low]);
+    }
+}
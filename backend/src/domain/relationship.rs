@@ -0,0 +1,272 @@
+//! Relationship Domain - who knows whom, as graph edges between contacts and companies
+//!
+//! Each kind of relationship is an edge table of its own and reads from the
+//! edge's `in` to its `out`: a contact was introduced by another contact, a
+//! contact works with a contact or a company, and a contact or company
+//! invested in a company.
+//!
+//! The network of a contact is everything within two hops of it, whichever
+//! way the edges point. The records two hops away are the warm
+//! introductions, and the records in between are who can make them.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::errors::{DomainError, DomainResult};
+
+/// Longest note a relationship can carry
+pub const MAX_RELATIONSHIP_NOTE_LEN: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationshipKind {
+    /// A contact was introduced by another contact
+    IntroducedBy,
+    /// A contact works with another contact, or at a company
+    WorksWith,
+    /// A contact or company invested in a company
+    InvestedIn,
+}
+
+impl RelationshipKind {
+    pub const ALL: [RelationshipKind; 3] = [Self::IntroducedBy, Self::WorksWith, Self::InvestedIn];
+
+    /// The edge table holding relationships of this kind
+    pub fn table(&self) -> &'static str {
+        match self {
+            Self::IntroducedBy => "introduced_by",
+            Self::WorksWith => "works_with",
+            Self::InvestedIn => "invested_in",
+        }
+    }
+
+    pub fn from_table(table: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.table() == table)
+    }
+
+    /// Whether a relationship of this kind may go from one type of record to another
+    pub fn allows(&self, from: NodeType, to: NodeType) -> bool {
+        match self {
+            Self::IntroducedBy => from == NodeType::Contact && to == NodeType::Contact,
+            Self::WorksWith => from == NodeType::Contact,
+            Self::InvestedIn => to == NodeType::Company,
+        }
+    }
+}
+
+/// What a relationship connects: a contact or a company
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeType {
+    Contact,
+    Company,
+}
+
+impl NodeType {
+    pub fn table(&self) -> &'static str {
+        match self {
+            Self::Contact => "contact",
+            Self::Company => "company",
+        }
+    }
+
+    pub fn from_table(table: &str) -> Option<Self> {
+        match table {
+            "contact" => Some(Self::Contact),
+            "company" => Some(Self::Company),
+            _ => None,
+        }
+    }
+}
+
+/// One contact or company in the relationship graph
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeRef {
+    pub node_type: NodeType,
+    pub id: String,
+}
+
+impl NodeRef {
+    pub fn new(node_type: NodeType, id: impl Into<String>) -> Self {
+        Self {
+            node_type,
+            id: id.into(),
+        }
+    }
+}
+
+/// Check a new relationship and clean up its note
+pub fn validate_relationship(
+    kind: RelationshipKind,
+    from: &NodeRef,
+    to: &NodeRef,
+    note: Option<&str>,
+) -> DomainResult<Option<String>> {
+    if from == to {
+        return Err(DomainError::InvalidField {
+            field: "to_id".into(),
+            reason: "a record can't be related to itself".into(),
+        });
+    }
+    if !kind.allows(from.node_type, to.node_type) {
+        return Err(DomainError::InvalidField {
+            field: "kind".into(),
+            reason: format!(
+                "{} can't relate a {} to a {}",
+                kind.table(),
+                from.node_type.table(),
+                to.node_type.table()
+            ),
+        });
+    }
+
+    let note = note.map(str::trim).filter(|n| !n.is_empty());
+    if let Some(note) = note
+        && note.chars().count() > MAX_RELATIONSHIP_NOTE_LEN
+    {
+        return Err(DomainError::InvalidField {
+            field: "note".into(),
+            reason: format!("must be at most {} characters", MAX_RELATIONSHIP_NOTE_LEN),
+        });
+    }
+    Ok(note.map(String::from))
+}
+
+/// A record in someone's network, and how to get to it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkReach {
+    pub node: NodeRef,
+    /// 1 for direct relationships, 2 for relationships of those
+    pub hops: u8,
+    /// For two hops, the direct relationships that lead here
+    pub via: Vec<NodeRef>,
+}
+
+/// Everything within two hops of `root`, following edges either way
+///
+/// Closest first; among records two hops away, those reachable through the
+/// most direct relationships come first, since they have the most ways in.
+pub fn network_reach(root: &NodeRef, edges: &[(NodeRef, NodeRef)]) -> Vec<NetworkReach> {
+    let mut neighbours: BTreeMap<&NodeRef, BTreeSet<&NodeRef>> = BTreeMap::new();
+    for (from, to) in edges {
+        if from != to {
+            neighbours.entry(from).or_default().insert(to);
+            neighbours.entry(to).or_default().insert(from);
+        }
+    }
+
+    let Some(direct) = neighbours.get(root) else {
+        return Vec::new();
+    };
+
+    let mut second: BTreeMap<&NodeRef, Vec<NodeRef>> = BTreeMap::new();
+    for &first in direct {
+        for &node in neighbours.get(first).into_iter().flatten() {
+            if node != root && !direct.contains(node) {
+                second.entry(node).or_default().push(first.clone());
+            }
+        }
+    }
+
+    let mut second: Vec<NetworkReach> = second
+        .into_iter()
+        .map(|(node, via)| NetworkReach {
+            node: node.clone(),
+            hops: 2,
+            via,
+        })
+        .collect();
+    second.sort_by(|a, b| b.via.len().cmp(&a.via.len()).then_with(|| a.node.cmp(&b.node)));
+
+    direct
+        .iter()
+        .map(|&node| NetworkReach {
+            node: node.clone(),
+            hops: 1,
+            via: Vec::new(),
+        })
+        .chain(second)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact(id: &str) -> NodeRef {
+        NodeRef::new(NodeType::Contact, id)
+    }
+
+    fn company(id: &str) -> NodeRef {
+        NodeRef::new(NodeType::Company, id)
+    }
+
+    #[test]
+    fn test_kinds_round_trip_through_tables() {
+        for kind in RelationshipKind::ALL {
+            assert_eq!(RelationshipKind::from_table(kind.table()), Some(kind));
+        }
+        assert_eq!(RelationshipKind::from_table("contact"), None);
+        assert_eq!(NodeType::from_table("company"), Some(NodeType::Company));
+        assert_eq!(NodeType::from_table("deal"), None);
+    }
+
+    #[test]
+    fn test_allowed_endpoints() {
+        use NodeType::*;
+        assert!(RelationshipKind::IntroducedBy.allows(Contact, Contact));
+        assert!(!RelationshipKind::IntroducedBy.allows(Contact, Company));
+        assert!(RelationshipKind::WorksWith.allows(Contact, Company));
+        assert!(!RelationshipKind::WorksWith.allows(Company, Contact));
+        assert!(RelationshipKind::InvestedIn.allows(Company, Company));
+        assert!(!RelationshipKind::InvestedIn.allows(Company, Contact));
+    }
+
+    #[test]
+    fn test_validate_relationship() {
+        let note = validate_relationship(RelationshipKind::WorksWith, &contact("a"), &company("x"), Some("  CTO "));
+        assert_eq!(note.unwrap(), Some("CTO".to_string()));
+
+        let blank = validate_relationship(RelationshipKind::IntroducedBy, &contact("a"), &contact("b"), Some(" "));
+        assert_eq!(blank.unwrap(), None);
+
+        let err = validate_relationship(RelationshipKind::IntroducedBy, &contact("a"), &contact("a"), None);
+        assert_eq!(err.unwrap_err().field(), Some("to_id"));
+
+        let err = validate_relationship(RelationshipKind::InvestedIn, &contact("a"), &contact("b"), None);
+        assert_eq!(err.unwrap_err().field(), Some("kind"));
+
+        let long = "x".repeat(MAX_RELATIONSHIP_NOTE_LEN + 1);
+        let err = validate_relationship(RelationshipKind::WorksWith, &contact("a"), &contact("b"), Some(&long));
+        assert_eq!(err.unwrap_err().field(), Some("note"));
+    }
+
+    #[test]
+    fn test_network_reach() {
+        let edges = vec![
+            // Ann was introduced by Bob, and works with Cat
+            (contact("ann"), contact("bob")),
+            (contact("ann"), contact("cat")),
+            // Bob and Cat both work at Acme, which Dan invested in
+            (contact("bob"), company("acme")),
+            (contact("cat"), company("acme")),
+            (contact("dan"), company("acme")),
+            // Eve was introduced by Bob; Fay is three hops away
+            (contact("eve"), contact("bob")),
+            (contact("fay"), contact("eve")),
+        ];
+
+        let reach = network_reach(&contact("ann"), &edges);
+        let summary: Vec<(&str, u8, usize)> = reach.iter().map(|r| (r.node.id.as_str(), r.hops, r.via.len())).collect();
+        assert_eq!(summary, vec![("bob", 1, 0), ("cat", 1, 0), ("acme", 2, 2), ("eve", 2, 1)]);
+        assert_eq!(reach[2].via, vec![contact("bob"), contact("cat")]);
+    }
+
+    #[test]
+    fn test_network_of_an_unrelated_record_is_empty() {
+        let edges = vec![(contact("bob"), contact("cat"))];
+        assert!(network_reach(&contact("ann"), &edges).is_empty());
+    }
+}
@@ -0,0 +1,191 @@
+//! Campaign Scheduling - When campaign email may go out
+//!
+//! A send window limits delivery to certain hours (and optionally certain
+//! weekdays) in each recipient's own timezone, so a 9-17 window reaches
+//! Stockholm and New York contacts during their respective office hours.
+//! Recipients outside the window stay pending until it opens for them.
+//!
+//! Contacts without a timezone use their company's, then the window's
+//! timezone, or UTC.
+
+use chrono::{DateTime, Datelike, Duration, NaiveTime, TimeZone, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::errors::{DomainError, DomainResult};
+use super::validation::validate_timezone;
+
+/// Local hours (and days) during which campaign email may be sent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct SendWindow {
+    /// First hour of the window, 0-23, in the recipient's local time
+    pub start_hour: u32,
+    /// Hour the window closes, 1-24; must be after `start_hour`
+    pub end_hour: u32,
+    /// Weekdays the window is open, e.g. `["Mon", "Tue"]`; empty means every day
+    #[serde(default)]
+    #[schema(value_type = Vec<String>)]
+    pub days: Vec<Weekday>,
+    /// IANA timezone for recipients without one; UTC if not set
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+impl SendWindow {
+    pub fn validate(&self) -> DomainResult<()> {
+        if self.start_hour > 23 || self.end_hour > 24 || self.end_hour <= self.start_hour {
+            return Err(DomainError::InvalidField {
+                field: "send_window".to_string(),
+                reason: "Hours must satisfy 0 <= start_hour < end_hour <= 24".to_string(),
+            });
+        }
+
+        validate_timezone(self.timezone.as_deref()).map_err(|e| e.at("send_window.timezone"))
+    }
+
+    /// The timezone sending to a recipient follows
+    ///
+    /// Unknown names fall back like missing ones, so one bad stored value
+    /// can't block a campaign.
+    pub fn timezone_for(&self, contact_timezone: Option<&str>) -> Tz {
+        contact_timezone
+            .and_then(|tz| tz.parse().ok())
+            .or_else(|| self.timezone.as_deref().and_then(|tz| tz.parse().ok()))
+            .unwrap_or(Tz::UTC)
+    }
+
+    /// Whether the window is open at `now` for a recipient in `tz`
+    pub fn is_open(&self, now: DateTime<Utc>, tz: Tz) -> bool {
+        let local = now.with_timezone(&tz);
+        self.is_open_on(local.weekday()) && local.hour() >= self.start_hour && local.hour() < self.end_hour
+    }
+
+    /// The first moment at or after `now` when the window is open in `tz`
+    pub fn next_open(&self, now: DateTime<Utc>, tz: Tz) -> DateTime<Utc> {
+        if self.is_open(now, tz) {
+            return now;
+        }
+
+        let today = now.with_timezone(&tz).date_naive();
+        let start = NaiveTime::from_hms_opt(self.start_hour, 0, 0).unwrap_or(NaiveTime::MIN);
+
+        // A week ahead always contains an open day; a day's start can fall in
+        // a DST gap, in which case the window opens an hour later
+        (0..=7)
+            .map(|offset| today + Duration::days(offset))
+            .filter(|day| self.is_open_on(day.weekday()))
+            .filter_map(|day| {
+                let naive = day.and_time(start);
+                tz.from_local_datetime(&naive)
+                    .earliest()
+                    .or_else(|| tz.from_local_datetime(&(naive + Duration::hours(1))).earliest())
+            })
+            .map(|opens| opens.with_timezone(&Utc))
+            .find(|opens| *opens > now)
+            .unwrap_or(now)
+    }
+
+    fn is_open_on(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+}
+
+/// Check a requested send time for a campaign
+///
+/// # Business Rules:
+/// - It must not be in the past (a minute of clock skew is tolerated)
+/// - It must be within a year, to catch typos in the year
+pub fn validate_scheduled_at(scheduled_at: DateTime<Utc>, now: DateTime<Utc>) -> DomainResult<()> {
+    if scheduled_at < now - Duration::minutes(1) {
+        return Err(DomainError::InvalidField {
+            field: "scheduled_at".to_string(),
+            reason: "Scheduled time is in the past".to_string(),
+        });
+    }
+    if scheduled_at > now + Duration::days(365) {
+        return Err(DomainError::InvalidField {
+            field: "scheduled_at".to_string(),
+            reason: "Campaigns can be scheduled at most a year ahead".to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn office_hours() -> SendWindow {
+        SendWindow {
+            start_hour: 9,
+            end_hour: 17,
+            days: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+            timezone: Some("Europe/Stockholm".into()),
+        }
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_window_follows_each_recipients_timezone() {
+        let window = office_hours();
+        // Wednesday 14:00 UTC: 16:00 in Stockholm, 10:00 in New York, 23:00 in Tokyo
+        let now = utc("2026-03-18T14:00:00Z");
+
+        assert!(window.is_open(now, window.timezone_for(None)));
+        assert!(window.is_open(now, window.timezone_for(Some("America/New_York"))));
+        assert!(!window.is_open(now, window.timezone_for(Some("Asia/Tokyo"))));
+    }
+
+    #[test]
+    fn test_next_open_skips_closed_days() {
+        let window = office_hours();
+        // Friday 18:00 in Stockholm: next opening is Monday 09:00 local (08:00 UTC)
+        let now = utc("2026-03-20T17:00:00Z");
+
+        assert_eq!(window.next_open(now, window.timezone_for(None)), utc("2026-03-23T08:00:00Z"));
+    }
+
+    #[test]
+    fn test_next_open_is_now_inside_the_window() {
+        let window = office_hours();
+        let now = utc("2026-03-18T10:30:00Z");
+
+        assert_eq!(window.next_open(now, Tz::UTC), now);
+    }
+
+    #[test]
+    fn test_unknown_contact_timezone_falls_back_to_window() {
+        assert_eq!(office_hours().timezone_for(Some("Nowhere/Special")), Tz::Europe__Stockholm);
+    }
+
+    #[test]
+    fn test_window_validation() {
+        let mut window = office_hours();
+        assert!(window.validate().is_ok());
+
+        window.end_hour = 9;
+        assert!(window.validate().is_err());
+
+        window.end_hour = 17;
+        window.timezone = Some("Europe/Atlantis".into());
+        assert_eq!(window.validate().unwrap_err().field(), Some("send_window.timezone"));
+    }
+
+    #[test]
+    fn test_scheduled_at_bounds() {
+        let now = utc("2026-03-18T10:00:00Z");
+
+        assert!(validate_scheduled_at(now + Duration::hours(2), now).is_ok());
+        assert!(validate_scheduled_at(now - Duration::hours(2), now).is_err());
+        assert!(validate_scheduled_at(now + Duration::days(400), now).is_err());
+    }
+}
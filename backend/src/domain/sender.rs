@@ -0,0 +1,335 @@
+//! Sender Identities - who campaign email comes from
+//!
+//! A sender identity is the From name and address, Reply-To and signature
+//! a campaign's email goes out with. The address's domain must publish an
+//! SPF record that lets our email provider send for it, a DKIM key under
+//! one of the provider's selectors and a TXT record holding the
+//! identity's own verification token; campaigns only send from identities
+//! whose domain passes all three. SPF and DKIM are the same for every
+//! customer of the provider, so only the token shows the domain is the
+//! workspace's.
+//!
+//! These functions judge TXT records; looking them up is the caller's job.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{insert_before_body_close, validate_email, validate_name, DomainError, DomainResult};
+
+/// Longest signature accepted, in bytes of HTML
+const MAX_SIGNATURE_LEN: usize = 10_000;
+
+/// What an ownership TXT record holds before the identity's token
+const VERIFICATION_PREFIX: &str = "crm-hey-sh-verification=";
+
+static TAG_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<[^>]*>").unwrap());
+
+static LINE_BREAK_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)<br\s*/?>|</(p|div|tr|li)\s*>").unwrap());
+
+/// Outcome of one DNS check
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DnsCheckStatus {
+    #[default]
+    Unchecked,
+    Pass,
+    Fail,
+}
+
+/// Result of checking one kind of record for a sender's domain
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct DnsCheck {
+    pub status: DnsCheckStatus,
+    /// What was found, or what is missing
+    pub detail: Option<String>,
+}
+
+impl DnsCheck {
+    fn pass(detail: impl Into<String>) -> Self {
+        Self {
+            status: DnsCheckStatus::Pass,
+            detail: Some(detail.into()),
+        }
+    }
+
+    fn fail(detail: impl Into<String>) -> Self {
+        Self {
+            status: DnsCheckStatus::Fail,
+            detail: Some(detail.into()),
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.status == DnsCheckStatus::Pass
+    }
+}
+
+/// Validate a sender identity's fields
+///
+/// # Rules:
+/// - The from name is a name: required, at most 100 characters
+/// - The from and reply-to addresses are valid email addresses
+/// - The signature is at most 10,000 bytes
+pub fn validate_sender(
+    from_name: &str,
+    from_email: &str,
+    reply_to: Option<&str>,
+    signature_html: Option<&str>,
+) -> DomainResult<()> {
+    let mut violations = Vec::new();
+
+    if let Err(e) = validate_name(from_name, "from_name") {
+        violations.push(e);
+    }
+    if let Err(e) = validate_email(from_email) {
+        violations.push(e.at("from_email"));
+    }
+    if let Some(Err(e)) = reply_to.map(validate_email) {
+        violations.push(e.at("reply_to"));
+    }
+    if signature_html.is_some_and(|s| s.len() > MAX_SIGNATURE_LEN) {
+        violations.push(DomainError::InvalidField {
+            field: "signature_html".to_string(),
+            reason: format!("Signature cannot exceed {} characters", MAX_SIGNATURE_LEN),
+        });
+    }
+
+    match DomainError::from_violations(violations) {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// The domain an address sends from, lowercased
+pub fn sender_domain(email: &str) -> Option<String> {
+    let (_, domain) = email.trim().rsplit_once('@')?;
+    let domain = domain.trim_end_matches('.').to_lowercase();
+    (!domain.is_empty()).then_some(domain)
+}
+
+/// A mailbox for an email header, e.g. `Jane Doe <jane@example.com>`
+///
+/// Names with characters that mean something in a header are quoted.
+pub fn format_mailbox(name: &str, email: &str) -> String {
+    let name = name.trim();
+    if name.is_empty() {
+        return email.to_string();
+    }
+    if name.chars().any(|c| "()<>[]:;@\\,.\"".contains(c)) {
+        let escaped = name.replace('\\', "\\\\").replace('"', "\\\"");
+        format!("\"{}\" <{}>", escaped, email)
+    } else {
+        format!("{} <{}>", name, email)
+    }
+}
+
+/// Judge the TXT records of a sender's domain for SPF
+///
+/// # Rules:
+/// - The domain has exactly one SPF record; receivers treat several as an
+///   error
+/// - The record includes the provider's SPF domain, e.g.
+///   `include:amazonses.com`; with no provider domain any record passes
+pub fn check_spf(records: &[String], provider_include: &str) -> DnsCheck {
+    let spf: Vec<&String> = records
+        .iter()
+        .filter(|r| {
+            let lower = r.trim().to_lowercase();
+            lower == "v=spf1" || lower.starts_with("v=spf1 ")
+        })
+        .collect();
+
+    let record = match spf.as_slice() {
+        [] => return DnsCheck::fail("No SPF record"),
+        [record] => record.trim(),
+        _ => return DnsCheck::fail(format!("{} SPF records; there must be exactly one", spf.len())),
+    };
+
+    let wanted = format!("include:{}", provider_include.to_lowercase());
+    let included = provider_include.is_empty()
+        || record
+            .split_whitespace()
+            .any(|term| term.trim_start_matches('+').eq_ignore_ascii_case(&wanted));
+    if included {
+        DnsCheck::pass(record)
+    } else {
+        DnsCheck::fail(format!("SPF record does not include {}: {}", wanted, record))
+    }
+}
+
+/// Name an identity's ownership TXT record is published at, e.g.
+/// `_crm-hey-sh.example.com`
+pub fn verification_name(domain: &str) -> String {
+    format!("_crm-hey-sh.{}", domain)
+}
+
+/// Value of an identity's ownership TXT record
+pub fn verification_record(token: &str) -> String {
+    format!("{}{}", VERIFICATION_PREFIX, token)
+}
+
+/// Judge the TXT records at an identity's verification name
+///
+/// The check passes when one of them is exactly the identity's
+/// `verification_record`. An identity without a token never passes.
+pub fn check_ownership(records: &[String], token: &str) -> DnsCheck {
+    let wanted = verification_record(token);
+    if !token.is_empty() && records.iter().any(|r| r.trim() == wanted) {
+        DnsCheck::pass("Verification token found")
+    } else {
+        DnsCheck::fail(format!("No TXT record {}", wanted))
+    }
+}
+
+/// Judge the TXT records found at each DKIM selector's name
+///
+/// `lookups` pairs a name like `s1._domainkey.example.com` with its
+/// records. The check passes when any of them holds a DKIM key; a key
+/// with an empty `p=` has been revoked and doesn't count.
+pub fn check_dkim(lookups: &[(String, Vec<String>)]) -> DnsCheck {
+    for (name, records) in lookups {
+        if records.iter().any(|r| is_dkim_key(r)) {
+            return DnsCheck::pass(format!("DKIM key at {}", name));
+        }
+    }
+
+    let names: Vec<&str> = lookups.iter().map(|(name, _)| name.as_str()).collect();
+    DnsCheck::fail(format!("No DKIM key at {}", names.join(", ")))
+}
+
+fn is_dkim_key(record: &str) -> bool {
+    let tags: Vec<(&str, &str)> = record
+        .split(';')
+        .filter_map(|tag| tag.split_once('='))
+        .map(|(name, value)| (name.trim(), value.trim()))
+        .collect();
+
+    // v=, when present, comes first
+    let version_ok = match tags.iter().position(|(name, _)| *name == "v") {
+        None => true,
+        Some(0) => tags[0].1.eq_ignore_ascii_case("DKIM1"),
+        Some(_) => false,
+    };
+    version_ok && tags.iter().any(|(name, value)| *name == "p" && !value.is_empty())
+}
+
+/// Add a signature to an HTML body, before `</body>` when there is one
+pub fn append_signature_html(html: &str, signature_html: &str) -> String {
+    let signature = format!(r#"<div class="signature" style="margin-top:24px;">{}</div>"#, signature_html);
+    insert_before_body_close(html, &signature)
+}
+
+/// Add a signature to a text body, after the conventional `-- ` delimiter
+pub fn append_signature_text(text: &str, signature_html: &str) -> String {
+    format!("{}\n\n-- \n{}", text.trim_end(), signature_text(signature_html))
+}
+
+/// Plain-text rendering of an HTML signature
+fn signature_text(html: &str) -> String {
+    let broken = LINE_BREAK_REGEX.replace_all(html, "\n");
+    let stripped = TAG_REGEX.replace_all(&broken, "");
+    let text = stripped
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_sender_domain() {
+        assert_eq!(sender_domain("Jane@Mail.Example.com").as_deref(), Some("mail.example.com"));
+        assert_eq!(sender_domain("nobody"), None);
+        assert_eq!(sender_domain("nobody@"), None);
+    }
+
+    #[test]
+    fn test_format_mailbox_quotes_special_names() {
+        assert_eq!(format_mailbox("Jane Doe", "jane@example.com"), "Jane Doe <jane@example.com>");
+        assert_eq!(format_mailbox("Doe, Jane", "jane@example.com"), "\"Doe, Jane\" <jane@example.com>");
+        assert_eq!(format_mailbox("Jane \"JD\" Doe", "jane@example.com"), "\"Jane \\\"JD\\\" Doe\" <jane@example.com>");
+        assert_eq!(format_mailbox(" ", "jane@example.com"), "jane@example.com");
+    }
+
+    #[test]
+    fn test_validate_sender_reports_every_field() {
+        assert!(validate_sender("Jane", "jane@example.com", Some("replies@example.com"), Some("<p>Jane</p>")).is_ok());
+
+        let err = validate_sender("", "not-an-email", Some("nope"), None).unwrap_err();
+        let fields: Vec<&str> = err.violations().iter().filter_map(|e| e.field()).collect();
+        assert_eq!(fields, vec!["from_name", "from_email", "reply_to"]);
+    }
+
+    #[test]
+    fn test_check_spf() {
+        let pass = check_spf(
+            &records(&["google-site-verification=abc", "v=spf1 include:_spf.google.com include:amazonses.com ~all"]),
+            "amazonses.com",
+        );
+        assert!(pass.passed());
+
+        assert_eq!(check_spf(&records(&["hello"]), "amazonses.com").status, DnsCheckStatus::Fail);
+        assert!(!check_spf(&records(&["v=spf1 include:sendgrid.net -all"]), "amazonses.com").passed());
+        assert!(!check_spf(&records(&["v=spf1 -all", "v=spf1 include:amazonses.com -all"]), "amazonses.com").passed());
+        assert!(check_spf(&records(&["v=spf1 -all"]), "").passed());
+    }
+
+    #[test]
+    fn test_check_ownership() {
+        assert_eq!(verification_name("example.com"), "_crm-hey-sh.example.com");
+
+        let pass = check_ownership(&records(&["other", " crm-hey-sh-verification=abc123 "]), "abc123");
+        assert!(pass.passed());
+
+        // Another identity's token, or none at all, doesn't count
+        assert!(!check_ownership(&records(&["crm-hey-sh-verification=xyz789"]), "abc123").passed());
+        assert!(!check_ownership(&records(&["crm-hey-sh-verification=abc1234"]), "abc123").passed());
+        assert!(!check_ownership(&records(&["crm-hey-sh-verification="]), "").passed());
+        assert_eq!(check_ownership(&[], "abc123").status, DnsCheckStatus::Fail);
+    }
+
+    #[test]
+    fn test_check_dkim() {
+        let name = |s: &str| format!("{}._domainkey.example.com", s);
+
+        let pass = check_dkim(&[
+            (name("s1"), Vec::new()),
+            (name("s2"), records(&["v=DKIM1; k=rsa; p=MIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQC"])),
+        ]);
+        assert!(pass.passed());
+        assert_eq!(pass.detail.as_deref(), Some("DKIM key at s2._domainkey.example.com"));
+
+        // Revoked keys and misplaced versions don't count
+        assert!(!check_dkim(&[(name("s1"), records(&["v=DKIM1; p="]))]).passed());
+        assert!(!check_dkim(&[(name("s1"), records(&["p=abc; v=DKIM1"]))]).passed());
+        assert!(!check_dkim(&[]).passed());
+    }
+
+    #[test]
+    fn test_append_signature() {
+        let signature = "<p>Jane Doe<br>Founder &amp; CEO</p>";
+
+        let html = append_signature_html("<html><body><p>Hi</p></body></html>", signature);
+        assert!(html.ends_with(&format!(r#"{}</div></body></html>"#, signature)));
+
+        let text = append_signature_text("Hi\n", signature);
+        assert_eq!(text, "Hi\n\n-- \nJane Doe\nFounder & CEO");
+    }
+}
@@ -0,0 +1,293 @@
+//! Drip Sequences - Multi-step email flows
+//!
+//! A sequence is an ordered list of steps a contact is walked through:
+//! send an email, wait some days, or branch on whether they opened or
+//! clicked the last email. Each enrollment remembers the step it is at;
+//! `advance` decides, from that position and the contact's activity, what
+//! to send now and where (and when) to pick up next.
+//!
+//! Branches may only jump forward, so every run through a sequence ends.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::errors::{DomainError, DomainResult};
+
+/// Longest wait a single step may ask for
+pub const MAX_WAIT_DAYS: u32 = 365;
+
+/// One step of a sequence
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SequenceStep {
+    /// Send an email; subject and body may use merge variables
+    Email { subject: String, body_html: String },
+    /// Pause before the next step
+    Wait { days: u32 },
+    /// Go to `then_step` if the contact engaged with the last email, to
+    /// `else_step` otherwise; a step index equal to the number of steps
+    /// ends the sequence
+    Branch {
+        condition: BranchCondition,
+        then_step: usize,
+        else_step: usize,
+    },
+}
+
+/// Engagement a branch checks for since the last email was sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BranchCondition {
+    Opened,
+    Clicked,
+}
+
+/// What a contact did with the last email they got from the sequence
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EmailActivity {
+    pub opened: bool,
+    pub clicked: bool,
+}
+
+impl EmailActivity {
+    /// A click counts as an open: blocked tracking pixels are common, but
+    /// nobody clicks an email they didn't open
+    pub fn satisfies(&self, condition: BranchCondition) -> bool {
+        match condition {
+            BranchCondition::Opened => self.opened || self.clicked,
+            BranchCondition::Clicked => self.clicked,
+        }
+    }
+}
+
+/// The result of advancing an enrollment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Advance {
+    /// Indices of the email steps to send now, in order
+    pub emails: Vec<usize>,
+    /// Step to continue from next time
+    pub position: usize,
+    /// When to continue; `None` when the sequence is done
+    pub resume_at: Option<DateTime<Utc>>,
+}
+
+impl Advance {
+    pub fn is_complete(&self) -> bool {
+        self.resume_at.is_none()
+    }
+}
+
+/// Validate the steps of a sequence
+///
+/// # Business Rules:
+/// - A sequence has at least one email step
+/// - Emails need a subject and a body
+/// - Waits are 1 to 365 days
+/// - Branches jump forward, to a later step or to the end
+pub fn validate_steps(steps: &[SequenceStep]) -> DomainResult<()> {
+    let mut violations = Vec::new();
+
+    if !steps.iter().any(|s| matches!(s, SequenceStep::Email { .. })) {
+        violations.push(DomainError::InvalidField {
+            field: "steps".to_string(),
+            reason: "A sequence needs at least one email step".to_string(),
+        });
+    }
+
+    for (i, step) in steps.iter().enumerate() {
+        match step {
+            SequenceStep::Email { subject, body_html } => {
+                if subject.trim().is_empty() {
+                    violations.push(DomainError::RequiredFieldMissing {
+                        field: format!("steps[{}].subject", i),
+                    });
+                }
+                if body_html.trim().is_empty() {
+                    violations.push(DomainError::RequiredFieldMissing {
+                        field: format!("steps[{}].body_html", i),
+                    });
+                }
+            }
+            SequenceStep::Wait { days } => {
+                if *days == 0 || *days > MAX_WAIT_DAYS {
+                    violations.push(DomainError::InvalidField {
+                        field: format!("steps[{}].days", i),
+                        reason: format!("Waits must be between 1 and {} days", MAX_WAIT_DAYS),
+                    });
+                }
+            }
+            SequenceStep::Branch { then_step, else_step, .. } => {
+                for (name, target) in [("then_step", then_step), ("else_step", else_step)] {
+                    if *target <= i || *target > steps.len() {
+                        violations.push(DomainError::InvalidField {
+                            field: format!("steps[{}].{}", i, name),
+                            reason: format!("Must be a later step (up to {} to end the sequence)", steps.len()),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    match DomainError::from_violations(violations) {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Walk from `position` until the sequence waits or ends
+///
+/// `activity` is what the contact did since their last email. A branch
+/// right after an email sent in this same run sees no activity, since
+/// nobody can have opened it yet.
+pub fn advance(steps: &[SequenceStep], position: usize, activity: EmailActivity, now: DateTime<Utc>) -> Advance {
+    let mut emails = Vec::new();
+    let mut i = position;
+
+    while let Some(step) = steps.get(i) {
+        match step {
+            SequenceStep::Email { .. } => {
+                emails.push(i);
+                i += 1;
+            }
+            SequenceStep::Wait { days } => {
+                return Advance {
+                    emails,
+                    position: i + 1,
+                    resume_at: Some(now + Duration::days(i64::from(*days))),
+                };
+            }
+            SequenceStep::Branch {
+                condition,
+                then_step,
+                else_step,
+            } => {
+                let engaged = emails.is_empty() && activity.satisfies(*condition);
+                let next = if engaged { *then_step } else { *else_step };
+                // Validation guarantees this; stop rather than loop on bad data
+                if next <= i {
+                    break;
+                }
+                i = next;
+            }
+        }
+    }
+
+    Advance {
+        emails,
+        position: steps.len(),
+        resume_at: None,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email(subject: &str) -> SequenceStep {
+        SequenceStep::Email {
+            subject: subject.into(),
+            body_html: "<p>Hi {{first_name|there}}</p>".into(),
+        }
+    }
+
+    /// Welcome, wait 3 days, then a nudge for those who didn't open
+    fn onboarding() -> Vec<SequenceStep> {
+        vec![
+            email("Welcome"),
+            SequenceStep::Wait { days: 3 },
+            SequenceStep::Branch {
+                condition: BranchCondition::Opened,
+                then_step: 4,
+                else_step: 3,
+            },
+            email("Did you miss this?"),
+            email("Getting started"),
+        ]
+    }
+
+    fn now() -> DateTime<Utc> {
+        "2026-03-18T10:00:00Z".parse().unwrap()
+    }
+
+    #[test]
+    fn test_advance_sends_until_a_wait() {
+        let step = advance(&onboarding(), 0, EmailActivity::default(), now());
+
+        assert_eq!(step.emails, vec![0]);
+        assert_eq!(step.position, 2);
+        assert_eq!(step.resume_at, Some(now() + Duration::days(3)));
+    }
+
+    #[test]
+    fn test_branch_follows_activity() {
+        let opened = EmailActivity {
+            opened: true,
+            clicked: false,
+        };
+
+        let engaged = advance(&onboarding(), 2, opened, now());
+        assert_eq!(engaged.emails, vec![4]);
+        assert!(engaged.is_complete());
+
+        let ignored = advance(&onboarding(), 2, EmailActivity::default(), now());
+        assert_eq!(ignored.emails, vec![3, 4]);
+        assert!(ignored.is_complete());
+    }
+
+    #[test]
+    fn test_click_counts_as_open() {
+        let clicked = EmailActivity {
+            opened: false,
+            clicked: true,
+        };
+
+        assert!(clicked.satisfies(BranchCondition::Opened));
+        assert!(!EmailActivity { opened: true, clicked: false }.satisfies(BranchCondition::Clicked));
+    }
+
+    #[test]
+    fn test_branch_right_after_an_email_sees_no_activity() {
+        let steps = vec![
+            email("Hello"),
+            SequenceStep::Branch {
+                condition: BranchCondition::Opened,
+                then_step: 3,
+                else_step: 2,
+            },
+            email("Follow-up"),
+        ];
+        let opened = EmailActivity {
+            opened: true,
+            clicked: true,
+        };
+
+        assert_eq!(advance(&steps, 0, opened, now()).emails, vec![0, 2]);
+    }
+
+    #[test]
+    fn test_validate_steps() {
+        assert!(validate_steps(&onboarding()).is_ok());
+
+        let err = validate_steps(&[SequenceStep::Wait { days: 2 }]).unwrap_err();
+        assert_eq!(err.field(), Some("steps"));
+
+        let backwards = vec![
+            email("Hello"),
+            SequenceStep::Branch {
+                condition: BranchCondition::Clicked,
+                then_step: 0,
+                else_step: 2,
+            },
+        ];
+        assert_eq!(validate_steps(&backwards).unwrap_err().field(), Some("steps[1].then_step"));
+
+        let never = vec![email("Hello"), SequenceStep::Wait { days: 0 }];
+        assert_eq!(validate_steps(&never).unwrap_err().field(), Some("steps[1].days"));
+    }
+}
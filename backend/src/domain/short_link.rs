@@ -0,0 +1,81 @@
+//! Short Links - codes for per-recipient tracked URLs
+//!
+//! A short link stands for one destination URL sent to one contact through
+//! one channel. Unlike tracking tokens the code carries nothing; the link
+//! is looked up when it is followed.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::InteractionType;
+
+/// Characters a code is made of
+const ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Characters per code; 62^8 leaves guessing a live code hopeless
+pub const SHORT_CODE_LEN: usize = 8;
+
+/// Where a short link was shared
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkChannel {
+    #[default]
+    Email,
+    Social,
+}
+
+impl LinkChannel {
+    /// The interaction a click through the link counts as
+    pub fn interaction(&self) -> InteractionType {
+        match self {
+            LinkChannel::Email => InteractionType::EmailClick,
+            LinkChannel::Social => InteractionType::SocialInteraction,
+        }
+    }
+}
+
+/// Build a code from random bits
+pub fn short_code(mut random: u128) -> String {
+    let base = ALPHABET.len() as u128;
+    (0..SHORT_CODE_LEN)
+        .map(|_| {
+            let c = ALPHABET[(random % base) as usize] as char;
+            random /= base;
+            c
+        })
+        .collect()
+}
+
+/// Whether a string could be a code, checked before looking one up
+pub fn is_short_code(code: &str) -> bool {
+    code.len() == SHORT_CODE_LEN && code.bytes().all(|b| b.is_ascii_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_codes_are_valid() {
+        for random in [0, 1, 61, 62, u128::MAX, 0x1234_5678_9abc_def0] {
+            let code = short_code(random);
+            assert!(is_short_code(&code), "{}", code);
+        }
+        assert_ne!(short_code(1), short_code(2));
+    }
+
+    #[test]
+    fn test_is_short_code_rejects_junk() {
+        assert!(!is_short_code(""));
+        assert!(!is_short_code("abc"));
+        assert!(!is_short_code("abcdefgh1"));
+        assert!(!is_short_code("abc/efgh"));
+        assert!(!is_short_code("abcdéfg"));
+    }
+
+    #[test]
+    fn test_channel_interaction() {
+        assert_eq!(LinkChannel::Email.interaction(), InteractionType::EmailClick);
+        assert_eq!(LinkChannel::Social.interaction(), InteractionType::SocialInteraction);
+    }
+}
@@ -0,0 +1,130 @@
+//! Email Tracking - instrumenting outgoing email HTML
+//!
+//! Pure string transformations used before an email is sent:
+//! - every trackable link is rewritten to go through the click tracker
+//! - a 1x1 pixel is added so opens can be recorded
+//! - an unsubscribe link is added to the footer
+//!
+//! Building the tracking URLs (and signing them) is the caller's job;
+//! these functions only decide WHERE they go.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static HREF_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)href\s*=\s*(["'])(.*?)(["'])"#).unwrap());
+
+static BODY_CLOSE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)</body\s*>").unwrap());
+
+/// Whether a link should be routed through the click tracker
+///
+/// Only absolute http(s) links are tracked - mailto:, tel:, anchors and
+/// template placeholders are left alone.
+pub fn is_trackable_link(url: &str) -> bool {
+    let lower = url.trim().to_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+/// Rewrite every trackable `href` in the HTML using `track`
+///
+/// `track` receives the original URL and returns the tracking URL to use.
+pub fn rewrite_links<F>(html: &str, mut track: F) -> String
+where
+    F: FnMut(&str) -> String,
+{
+    HREF_REGEX
+        .replace_all(html, |caps: &regex::Captures| {
+            let url = &caps[2];
+            if is_trackable_link(url) {
+                format!("href={}{}{}", &caps[1], track(url), &caps[3])
+            } else {
+                caps[0].to_string()
+            }
+        })
+        .into_owned()
+}
+
+/// Add an invisible open-tracking pixel to the HTML
+///
+/// The pixel goes right before `</body>` when there is one, otherwise at
+/// the end of the document.
+pub fn append_tracking_pixel(html: &str, pixel_url: &str) -> String {
+    let pixel = format!(
+        r#"<img src="{}" width="1" height="1" alt="" style="display:none;border:0;" />"#,
+        pixel_url
+    );
+
+    insert_before_body_close(html, &pixel)
+}
+
+/// Add an unsubscribe footer to the HTML
+///
+/// Must run after `rewrite_links` - the unsubscribe link is not a click to
+/// track.
+pub fn append_unsubscribe_link(html: &str, unsubscribe_url: &str) -> String {
+    let footer = format!(
+        r#"<p style="font-size:12px;color:#888;">Don't want these emails? <a href="{}">Unsubscribe</a></p>"#,
+        unsubscribe_url
+    );
+
+    insert_before_body_close(html, &footer)
+}
+
+pub(crate) fn insert_before_body_close(html: &str, snippet: &str) -> String {
+    match BODY_CLOSE_REGEX.find(html) {
+        Some(m) => format!("{}{}{}", &html[..m.start()], snippet, &html[m.start()..]),
+        None => format!("{}{}", html, snippet),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trackable_links() {
+        assert!(is_trackable_link("https://example.com"));
+        assert!(is_trackable_link("HTTP://example.com/path"));
+        assert!(!is_trackable_link("mailto:hi@example.com"));
+        assert!(!is_trackable_link("tel:+123"));
+        assert!(!is_trackable_link("#"));
+    }
+
+    #[test]
+    fn test_rewrite_links() {
+        let html = r##"<a href="https://example.com/a">A</a> <a href='#'>B</a> <a href="mailto:x@y.z">C</a>"##;
+
+        let rewritten = rewrite_links(html, |url| format!("https://t.example/click?u={}", url.len()));
+
+        assert!(rewritten.contains(r#"href="https://t.example/click?u=21""#));
+        assert!(rewritten.contains("href='#'"));
+        assert!(rewritten.contains(r#"href="mailto:x@y.z""#));
+    }
+
+    #[test]
+    fn test_append_tracking_pixel_before_body_close() {
+        let html = "<html><body><p>Hi</p></body></html>";
+
+        let tracked = append_tracking_pixel(html, "https://t.example/open");
+
+        assert!(tracked.contains(r#"<img src="https://t.example/open""#));
+        assert!(tracked.ends_with("</body></html>"));
+    }
+
+    #[test]
+    fn test_append_tracking_pixel_without_body() {
+        let tracked = append_tracking_pixel("<p>Hi</p>", "https://t.example/open");
+
+        assert!(tracked.starts_with("<p>Hi</p><img"));
+    }
+
+    #[test]
+    fn test_append_unsubscribe_link() {
+        let html = "<html><body><p>Hi</p></body></html>";
+
+        let with_footer = append_unsubscribe_link(html, "https://t.example/unsubscribe/abc");
+
+        assert!(with_footer.contains(r#"<a href="https://t.example/unsubscribe/abc">Unsubscribe</a>"#));
+        assert!(with_footer.ends_with("</p></body></html>"));
+    }
+}
@@ -0,0 +1,53 @@
+//! User Agents - telling people from crawlers, link previewers and scripts
+//!
+//! Matching is on well-known substrings only; a bot that poses as a browser
+//! gets through, which only costs an extra view in the counts.
+
+/// Substrings of the lowercased User-Agent that mark an automated client
+const BOT_MARKERS: &[&str] = &[
+    "bot",
+    "crawler",
+    "spider",
+    "slurp",
+    "facebookexternalhit",
+    "preview",
+    "headless",
+    "lighthouse",
+    "embedly",
+    "whatsapp",
+    "curl/",
+    "wget/",
+    "python-requests",
+    "go-http-client",
+    "okhttp",
+];
+
+/// Whether a request comes from a bot rather than a person's browser
+///
+/// Browsers always send a User-Agent, so a missing or empty one counts as a bot.
+pub fn is_bot(user_agent: Option<&str>) -> bool {
+    let Some(agent) = user_agent.map(str::trim).filter(|a| !a.is_empty()) else {
+        return true;
+    };
+    let agent = agent.to_lowercase();
+    BOT_MARKERS.iter().any(|marker| agent.contains(marker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_bot() {
+        let browser = "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_4) AppleWebKit/605.1.15 \
+                       (KHTML, like Gecko) Version/17.4 Safari/605.1.15";
+        assert!(!is_bot(Some(browser)));
+
+        assert!(is_bot(Some("Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)")));
+        assert!(is_bot(Some("Slackbot-LinkExpanding 1.0 (+https://api.slack.com/robots)")));
+        assert!(is_bot(Some("facebookexternalhit/1.1")));
+        assert!(is_bot(Some("curl/8.4.0")));
+        assert!(is_bot(Some("  ")));
+        assert!(is_bot(None));
+    }
+}
@@ -20,6 +20,28 @@ static PHONE_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"^[\d\s\-\(\)\+\.]{7,20}$").unwrap()
 });
 
+/// Throwaway email providers rejected on public forms
+const DISPOSABLE_EMAIL_DOMAINS: &[&str] = &[
+    "10minutemail.com",
+    "dispostable.com",
+    "emailondeck.com",
+    "fakeinbox.com",
+    "getnada.com",
+    "guerrillamail.com",
+    "guerrillamail.net",
+    "mailinator.com",
+    "maildrop.cc",
+    "mailnesia.com",
+    "mintemail.com",
+    "mohmal.com",
+    "sharklasers.com",
+    "temp-mail.org",
+    "tempmail.com",
+    "throwawaymail.com",
+    "trashmail.com",
+    "yopmail.com",
+];
+
 static LINKEDIN_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"^https?://(www\.)?linkedin\.com/in/[\w\-]+/?$").unwrap());
 
@@ -65,6 +87,31 @@ pub fn validate_email(email: &str) -> DomainResult<()> {
     Ok(())
 }
 
+/// Reject emails from throwaway providers or other blocked domains
+///
+/// # Rules:
+/// - The domain, or a domain it is a subdomain of, must not be a known
+///   disposable email provider when `block_disposable` is set
+/// - Nor any of `blocked`
+pub fn validate_email_domain(email: &str, block_disposable: bool, blocked: &[String]) -> DomainResult<()> {
+    let domain = email.rsplit_once('@').map_or("", |(_, d)| d).trim().to_lowercase();
+
+    let matches = |listed: &str| {
+        let listed = listed.trim().to_lowercase();
+        !listed.is_empty() && (domain == listed || domain.ends_with(&format!(".{}", listed)))
+    };
+
+    let disposable = block_disposable && DISPOSABLE_EMAIL_DOMAINS.iter().any(|d| matches(d));
+    if disposable || blocked.iter().any(|d| matches(d)) {
+        return Err(DomainError::InvalidField {
+            field: "email".to_string(),
+            reason: "Please use a permanent email address".to_string(),
+        });
+    }
+
+    Ok(())
+}
+
 /// Validate a phone number format
 ///
 /// # Rules:
@@ -178,7 +225,15 @@ pub fn validate_tag(tag: &str) -> DomainResult<String> {
     Ok(normalized)
 }
 
+/// Most tags a contact can carry
+pub const MAX_TAGS: usize = 100;
+
+/// Most custom fields a contact can carry
+pub const MAX_CUSTOM_FIELDS: usize = 100;
+
 /// Validate a list of tags
+///
+/// At most `MAX_TAGS` remain once duplicates are removed.
 pub fn validate_tags(tags: &[String]) -> DomainResult<Vec<String>> {
     let mut validated = Vec::with_capacity(tags.len());
 
@@ -190,9 +245,132 @@ pub fn validate_tags(tags: &[String]) -> DomainResult<Vec<String>> {
     let mut seen = std::collections::HashSet::new();
     validated.retain(|t| seen.insert(t.clone()));
 
+    if validated.len() > MAX_TAGS {
+        return Err(DomainError::InvalidField {
+            field: "tags".to_string(),
+            reason: format!("At most {} tags are allowed", MAX_TAGS),
+        });
+    }
+
     Ok(validated)
 }
 
+/// Check the number of custom fields a contact would end up with
+pub fn validate_custom_field_count(count: usize) -> DomainResult<()> {
+    if count > MAX_CUSTOM_FIELDS {
+        return Err(DomainError::InvalidField {
+            field: "custom_fields".to_string(),
+            reason: format!("At most {} custom fields are allowed", MAX_CUSTOM_FIELDS),
+        });
+    }
+    Ok(())
+}
+
+/// Validate a custom field, returning the normalized key
+///
+/// # Rules:
+/// - Key: 1-50 characters, lowercase letters, digits and underscores,
+///   starting with a letter (so it can be used as a `{{merge_variable}}`)
+/// - Value: at most 500 characters
+///
+/// # Examples
+/// ```
+/// use crm_backend::domain::validation::validate_custom_field;
+///
+/// assert_eq!(validate_custom_field("Plan", "pro").unwrap(), "plan");
+/// assert!(validate_custom_field("2nd-plan", "pro").is_err());
+/// ```
+pub fn validate_custom_field(key: &str, value: &str) -> DomainResult<String> {
+    let normalized = key.trim().to_lowercase();
+
+    if normalized.is_empty() || normalized.len() > 50 {
+        return Err(DomainError::InvalidField {
+            field: "custom_fields".to_string(),
+            reason: "Custom field names must be 1-50 characters".to_string(),
+        });
+    }
+
+    let starts_with_letter = normalized.starts_with(|c: char| c.is_ascii_lowercase());
+    if !starts_with_letter
+        || !normalized
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Err(DomainError::InvalidField {
+            field: "custom_fields".to_string(),
+            reason: "Custom field names can only contain letters, numbers, and underscores, and must start with a letter"
+                .to_string(),
+        });
+    }
+
+    if value.chars().count() > 500 {
+        return Err(DomainError::InvalidField {
+            field: format!("custom_fields.{}", normalized),
+            reason: "Custom field values cannot exceed 500 characters".to_string(),
+        });
+    }
+
+    Ok(normalized)
+}
+
+/// Validate an IANA timezone name such as `Europe/Stockholm`
+///
+/// # Rules:
+/// - Optional (None is valid)
+/// - Must be a name from the tz database; abbreviations like `CET` that
+///   the database also lists are accepted, offsets like `+02:00` are not
+pub fn validate_timezone(timezone: Option<&str>) -> DomainResult<()> {
+    if let Some(tz) = timezone
+        && tz.parse::<chrono_tz::Tz>().is_err()
+    {
+        return Err(DomainError::InvalidField {
+            field: "timezone".to_string(),
+            reason: format!("Unknown timezone '{}', use an IANA name like Europe/Stockholm", tz),
+        });
+    }
+
+    Ok(())
+}
+
+/// Validate a country as an ISO 3166-1 alpha-2 code such as `SE`
+///
+/// # Rules:
+/// - Optional (None is valid)
+/// - Exactly two ASCII letters; callers store it upper case
+pub fn validate_country(country: Option<&str>) -> DomainResult<()> {
+    if let Some(code) = country
+        && (code.len() != 2 || !code.chars().all(|c| c.is_ascii_alphabetic()))
+    {
+        return Err(DomainError::InvalidField {
+            field: "country".to_string(),
+            reason: format!("Unknown country '{}', use a two-letter ISO code like SE", code),
+        });
+    }
+
+    Ok(())
+}
+
+/// Longest region or city name
+pub const MAX_PLACE_LEN: usize = 100;
+
+/// Validate a region or city name
+///
+/// # Rules:
+/// - Optional (None is valid)
+/// - At most 100 characters, with no control characters
+pub fn validate_place(place: Option<&str>, field_name: &str) -> DomainResult<()> {
+    if let Some(place) = place
+        && (place.chars().count() > MAX_PLACE_LEN || place.chars().any(char::is_control))
+    {
+        return Err(DomainError::InvalidField {
+            field: field_name.to_string(),
+            reason: format!("Must be at most {} characters, without line breaks", MAX_PLACE_LEN),
+        });
+    }
+
+    Ok(())
+}
+
 /// Validate engagement score
 ///
 /// # Rules:
@@ -252,6 +430,38 @@ fn validate_company_domain_protocol(d: &str) -> Option<Result<(), DomainError>>
     None
 }
 
+/// Validate a user password
+///
+/// # Rules:
+/// - Must be 8-128 characters
+/// - Must contain at least one letter and one digit
+pub fn validate_password(password: &str) -> DomainResult<()> {
+    if password.is_empty() {
+        return Err(DomainError::RequiredFieldMissing {
+            field: "password".to_string(),
+        });
+    }
+
+    if password.chars().count() < 8 || password.chars().count() > 128 {
+        return Err(DomainError::InvalidField {
+            field: "password".to_string(),
+            reason: "Password must be between 8 and 128 characters".to_string(),
+        });
+    }
+
+    let has_letter = password.chars().any(|c| c.is_alphabetic());
+    let has_digit = password.chars().any(|c| c.is_ascii_digit());
+
+    if !has_letter || !has_digit {
+        return Err(DomainError::InvalidField {
+            field: "password".to_string(),
+            reason: "Password must contain at least one letter and one digit".to_string(),
+        });
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -301,6 +511,16 @@ mod tests {
 
     // ---- Phone Validation Tests ----
 
+    #[test]
+    fn test_email_domain_validation() {
+        assert!(validate_email_domain("ada@example.com", true, &[]).is_ok());
+        assert!(validate_email_domain("bot@mailinator.com", true, &[]).is_err());
+        assert!(validate_email_domain("bot@eu.Mailinator.com", true, &[]).is_err());
+        assert!(validate_email_domain("bot@mailinator.com", false, &[]).is_ok());
+        assert!(validate_email_domain("ada@notmailinator.com", true, &[]).is_ok());
+        assert!(validate_email_domain("rival@competitor.io", true, &["competitor.io".into()]).is_err());
+    }
+
     #[test]
     fn test_valid_phones() {
         let valid_phones = [
@@ -375,8 +595,57 @@ mod tests {
         assert_eq!(result, vec!["vip", "early-adopter"]);
     }
 
+    #[test]
+    fn test_tag_and_custom_field_counts_are_capped() {
+        let tags: Vec<String> = (0..MAX_TAGS).map(|i| format!("tag{}", i)).collect();
+        assert_eq!(validate_tags(&tags).unwrap().len(), MAX_TAGS);
+
+        // Duplicates don't count
+        let mut repeated = tags.clone();
+        repeated.push("tag0".into());
+        assert!(validate_tags(&repeated).is_ok());
+
+        let mut too_many = tags;
+        too_many.push("one-more".into());
+        assert!(validate_tags(&too_many).is_err());
+
+        assert!(validate_custom_field_count(MAX_CUSTOM_FIELDS).is_ok());
+        assert!(validate_custom_field_count(MAX_CUSTOM_FIELDS + 1).is_err());
+    }
+
     // ---- LinkedIn URL Tests ----
 
+    #[test]
+    fn test_timezone_validation() {
+        assert!(validate_timezone(None).is_ok());
+        assert!(validate_timezone(Some("Europe/Stockholm")).is_ok());
+        assert!(validate_timezone(Some("America/New_York")).is_ok());
+        assert!(validate_timezone(Some("Mars/Olympus_Mons")).is_err());
+        assert!(validate_timezone(Some("+02:00")).is_err());
+    }
+
+    #[test]
+    fn test_country_and_place_validation() {
+        assert!(validate_country(None).is_ok());
+        assert!(validate_country(Some("SE")).is_ok());
+        assert!(validate_country(Some("Sweden")).is_err());
+        assert!(validate_country(Some("S1")).is_err());
+
+        assert!(validate_place(Some("Västra Götaland"), "region").is_ok());
+        assert!(validate_place(Some("Stock\nholm"), "city").is_err());
+        let err = validate_place(Some(&"x".repeat(MAX_PLACE_LEN + 1)), "city").unwrap_err();
+        assert_eq!(err.field(), Some("city"));
+    }
+
+    #[test]
+    fn test_custom_field_validation() {
+        assert_eq!(validate_custom_field(" Plan_Tier ", "pro").unwrap(), "plan_tier");
+        assert!(validate_custom_field("", "pro").is_err());
+        assert!(validate_custom_field("plan-tier", "pro").is_err());
+        assert!(validate_custom_field("2fa", "on").is_err());
+        assert!(validate_custom_field("notes", &"x".repeat(501)).is_err());
+    }
+
     #[test]
     fn test_valid_linkedin_urls() {
         let valid_urls = [
@@ -438,4 +707,15 @@ mod tests {
         assert!(validate_company_domain(Some("example")).is_err());
         assert!(validate_company_domain(Some("example.")).is_err());
     }
+
+    #[test]
+    fn test_password_validation() {
+        assert!(validate_password("correct-horse-1").is_ok());
+
+        assert!(validate_password("").is_err());
+        assert!(validate_password("short1").is_err());
+        assert!(validate_password("nodigitsatall").is_err());
+        assert!(validate_password("1234567890").is_err());
+        assert!(validate_password(&format!("a1{}", "x".repeat(127))).is_err());
+    }
 }
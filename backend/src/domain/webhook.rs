@@ -0,0 +1,155 @@
+//! Webhooks - the events we publish and the rules for delivering them
+//!
+//! A workspace registers URLs for the events it cares about. Every event
+//! is delivered as a signed JSON POST; failed deliveries are retried with
+//! exponential backoff until they succeed or run out of attempts.
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::errors::{DomainError, DomainResult};
+use super::network::validate_public_host;
+
+/// Events a webhook can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub enum WebhookEvent {
+    #[serde(rename = "contact.created")]
+    ContactCreated,
+    #[serde(rename = "contact.status_changed")]
+    ContactStatusChanged,
+    #[serde(rename = "campaign.executed")]
+    CampaignExecuted,
+    #[serde(rename = "form.submitted")]
+    FormSubmitted,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::ContactCreated => "contact.created",
+            WebhookEvent::ContactStatusChanged => "contact.status_changed",
+            WebhookEvent::CampaignExecuted => "campaign.executed",
+            WebhookEvent::FormSubmitted => "form.submitted",
+        }
+    }
+}
+
+impl std::fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Validate a webhook target URL
+///
+/// # Rules:
+/// - Must be an absolute http(s) URL with a host
+/// - The host must be public: no localhost, private, link-local or
+///   internal names and addresses
+/// - At most 2048 characters
+pub fn validate_webhook_url(url: &str) -> DomainResult<()> {
+    let url = url.trim();
+
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(DomainError::InvalidField {
+            field: "url".to_string(),
+            reason: "Must be an http:// or https:// URL".to_string(),
+        });
+    }
+
+    // Parsed the way the dispatcher's HTTP client will parse it, so the
+    // host checked is the host connected to
+    let parsed = reqwest::Url::parse(url).ok();
+    let Some(host) = parsed.as_ref().and_then(|u| u.host_str()).filter(|h| !h.is_empty()) else {
+        return Err(DomainError::InvalidField {
+            field: "url".to_string(),
+            reason: "Must include a valid host".to_string(),
+        });
+    };
+    validate_public_host("url", host)?;
+
+    if url.len() > 2048 {
+        return Err(DomainError::InvalidField {
+            field: "url".to_string(),
+            reason: "Must be at most 2048 characters".to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Validate the subscribed events
+///
+/// # Rules:
+/// - At least one event
+pub fn validate_webhook_events(events: &[WebhookEvent]) -> DomainResult<()> {
+    if events.is_empty() {
+        return Err(DomainError::RequiredFieldMissing {
+            field: "events".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Longest we ever wait between two attempts
+const MAX_RETRY_DELAY_SECS: i64 = 6 * 60 * 60;
+
+/// How long to wait before retrying after `attempts` failed attempts
+///
+/// Doubles with every attempt: initial, 2x, 4x, ... capped at six hours.
+pub fn retry_delay(attempts: u32, initial_secs: u64) -> Duration {
+    let exponent = attempts.saturating_sub(1).min(30);
+    let secs = (initial_secs as i64).saturating_mul(1_i64 << exponent);
+    Duration::seconds(secs.min(MAX_RETRY_DELAY_SECS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_names() {
+        assert_eq!(WebhookEvent::ContactCreated.as_str(), "contact.created");
+        assert_eq!(
+            serde_json::to_string(&WebhookEvent::ContactStatusChanged).unwrap(),
+            "\"contact.status_changed\""
+        );
+        assert_eq!(
+            serde_json::from_str::<WebhookEvent>("\"form.submitted\"").unwrap(),
+            WebhookEvent::FormSubmitted
+        );
+    }
+
+    #[test]
+    fn test_validate_webhook_url() {
+        assert!(validate_webhook_url("https://example.com/hooks/crm").is_ok());
+        assert!(validate_webhook_url("http://localhost:9000").is_err());
+        assert!(validate_webhook_url("http://169.254.169.254/latest/meta-data").is_err());
+        assert!(validate_webhook_url("https://10.0.0.5/hooks").is_err());
+        assert!(validate_webhook_url("https://example.com@127.0.0.1/").is_err());
+        // `\` ends the authority for http(s), so the host here is the metadata address
+        assert!(validate_webhook_url("http://169.254.169.254\\@example.com/").is_err());
+        assert!(validate_webhook_url("ftp://example.com").is_err());
+        assert!(validate_webhook_url("https://").is_err());
+        assert!(validate_webhook_url("example.com").is_err());
+    }
+
+    #[test]
+    fn test_validate_webhook_events() {
+        assert!(validate_webhook_events(&[]).is_err());
+        assert!(validate_webhook_events(&[WebhookEvent::CampaignExecuted]).is_ok());
+    }
+
+    #[test]
+    fn test_retry_delay_doubles() {
+        assert_eq!(retry_delay(1, 30), Duration::seconds(30));
+        assert_eq!(retry_delay(2, 30), Duration::seconds(60));
+        assert_eq!(retry_delay(3, 30), Duration::seconds(120));
+    }
+
+    #[test]
+    fn test_retry_delay_is_capped() {
+        assert_eq!(retry_delay(40, 30), Duration::seconds(MAX_RETRY_DELAY_SECS));
+    }
+}
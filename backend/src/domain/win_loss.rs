@@ -0,0 +1,108 @@
+//! Win/Loss Domain - why deals and leads were won or lost
+//!
+//! A deal is won or lost when it closes. A lead is won when it becomes a
+//! customer and lost when it is moved to Other. Either transition may carry
+//! one of a fixed set of reasons, so the win/loss report can group them; a
+//! reason on any other transition is refused. Transitions made without a
+//! reason are still counted, as unspecified.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::contact::ContactStatus;
+use super::deal::DealStage;
+use super::errors::{DomainError, DomainResult};
+
+/// Whether a deal or lead was won or lost
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WinLossOutcome {
+    Won,
+    Lost,
+}
+
+/// Why a deal or lead was won or lost
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WinLossReason {
+    /// Chose us over a competitor, or a competitor over us
+    Competitor,
+    Price,
+    Timing,
+    NoBudget,
+    /// The product did or didn't do what they needed
+    ProductFit,
+    Other,
+}
+
+/// The outcome of a deal in this stage, if it is closed
+pub fn deal_outcome(stage: DealStage) -> Option<WinLossOutcome> {
+    match stage {
+        DealStage::ClosedWon => Some(WinLossOutcome::Won),
+        DealStage::ClosedLost => Some(WinLossOutcome::Lost),
+        _ => None,
+    }
+}
+
+/// The outcome of a lead moving from one status to another, if it was one
+pub fn lead_outcome(from: ContactStatus, to: ContactStatus) -> Option<WinLossOutcome> {
+    match (from, to) {
+        (ContactStatus::Lead, ContactStatus::Customer) => Some(WinLossOutcome::Won),
+        (ContactStatus::Lead, ContactStatus::Other) => Some(WinLossOutcome::Lost),
+        _ => None,
+    }
+}
+
+/// The reason to store for a transition with this outcome
+///
+/// Transitions that don't win or lose anything store no reason, which also
+/// clears the reason of a reopened deal.
+pub fn win_loss_reason(
+    outcome: Option<WinLossOutcome>,
+    reason: Option<WinLossReason>,
+) -> DomainResult<Option<WinLossReason>> {
+    match (outcome, reason) {
+        (None, Some(_)) => Err(DomainError::InvalidField {
+            field: "win_loss_reason".into(),
+            reason: "can only be given when a deal or lead is won or lost".into(),
+        }),
+        (None, None) => Ok(None),
+        (Some(_), reason) => Ok(reason),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcomes() {
+        assert_eq!(deal_outcome(DealStage::ClosedWon), Some(WinLossOutcome::Won));
+        assert_eq!(deal_outcome(DealStage::ClosedLost), Some(WinLossOutcome::Lost));
+        assert_eq!(deal_outcome(DealStage::Negotiation), None);
+
+        assert_eq!(lead_outcome(ContactStatus::Lead, ContactStatus::Customer), Some(WinLossOutcome::Won));
+        assert_eq!(lead_outcome(ContactStatus::Lead, ContactStatus::Other), Some(WinLossOutcome::Lost));
+        assert_eq!(lead_outcome(ContactStatus::Lead, ContactStatus::Partner), None);
+        // Churned customers going back to Lead aren't leads won or lost
+        assert_eq!(lead_outcome(ContactStatus::Customer, ContactStatus::Lead), None);
+        assert_eq!(lead_outcome(ContactStatus::Partner, ContactStatus::Customer), None);
+    }
+
+    #[test]
+    fn test_reason_only_on_a_win_or_loss() {
+        let lost = Some(WinLossOutcome::Lost);
+        assert_eq!(win_loss_reason(lost, Some(WinLossReason::Price)).unwrap(), Some(WinLossReason::Price));
+        assert_eq!(win_loss_reason(lost, None).unwrap(), None);
+        assert_eq!(win_loss_reason(None, None).unwrap(), None);
+
+        let err = win_loss_reason(None, Some(WinLossReason::Timing)).unwrap_err();
+        assert_eq!(err.field(), Some("win_loss_reason"));
+    }
+
+    #[test]
+    fn test_reasons_serialize_snake_case() {
+        assert_eq!(serde_json::to_value(WinLossReason::NoBudget).unwrap(), "no_budget");
+        assert_eq!(serde_json::to_value(WinLossReason::ProductFit).unwrap(), "product_fit");
+    }
+}
@@ -9,6 +9,8 @@ use thiserror::Error;
 use utoipa::ToSchema;
 
 use crate::domain::errors::DomainError;
+use crate::middleware::current_request_id;
+use crate::models::PossibleDuplicate;
 
 #[derive(Error, Debug)]
 pub enum AppError {
@@ -21,34 +23,123 @@ pub enum AppError {
     #[error("Validation error: {0}")]
     Validation(String),
 
+    /// One or more fields failed domain validation
+    #[error("Validation error: {}", summarize(.0))]
+    InvalidFields(Vec<FieldError>),
+
     #[error("Conflict: {0}")]
     Conflict(String),
 
+    /// Another contact already has this email; clients can offer a merge
+    #[error("Conflict: a contact with email '{email}' already exists")]
+    DuplicateEmail { email: String, existing_id: String },
+
+    /// A new contact looks like existing ones; clients can retry with
+    /// `allow_duplicates` or pick one of the candidates
+    #[error("Conflict: the contact looks like {} existing contact(s)", .0.len())]
+    PossibleDuplicates(Vec<PossibleDuplicate>),
+
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
 
+    /// Signed in, but the user's role doesn't allow this
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    /// The request body is over the route's size limit
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    /// A client sent more requests than it is allowed to
+    #[error("Too many requests: {0}")]
+    TooManyRequests(String),
+
     #[error("Internal server error: {0}")]
     Internal(String),
 
+    /// An external service (e.g. the AI provider) failed or answered badly
+    #[error("Upstream error: {0}")]
+    Upstream(String),
+
+    /// Boxed: the SurrealDB error is large enough to bloat every `AppResult`
     #[error("Database error: {0}")]
-    Database(#[from] surrealdb::Error),
+    Database(Box<surrealdb::Error>),
+}
+
+impl From<surrealdb::Error> for AppError {
+    fn from(err: surrealdb::Error) -> Self {
+        AppError::Database(Box::new(err))
+    }
 }
 
 #[derive(utoipa::ToSchema, Serialize)]
 pub struct ErrorResponse {
     pub error: String,
     pub status: u16,
+    /// Machine-readable reason, for errors clients are expected to handle
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// The contact that caused a `duplicate_email` conflict
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub existing_contact_id: Option<String>,
+    /// The `X-Request-Id` of the failed request, to find it in the logs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Per-field problems, so clients can highlight the offending inputs
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+    /// Existing contacts behind a `possible_duplicate` conflict, most likely first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<PossibleDuplicate>,
+}
+
+/// One invalid field in a validation error
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct FieldError {
+    /// Path of the field in the request, e.g. `email` or `tags[2]`
+    pub field: String,
+    /// `required`, `invalid`, or `unresolved_variables` for campaign
+    /// recipients whose email would go out incomplete
+    pub code: String,
+    pub message: String,
+}
+
+impl FieldError {
+    fn from_domain(err: &DomainError) -> Self {
+        let message = match err {
+            DomainError::RequiredFieldMissing { field } => format!("{} is required", field),
+            DomainError::InvalidField { reason, .. } => reason.clone(),
+            other => other.to_string(),
+        };
+
+        Self {
+            field: err.field().unwrap_or_default().to_string(),
+            code: err.code().to_string(),
+            message,
+        }
+    }
+}
+
+/// Flat message for the `error` field, as it read before per-field errors
+fn summarize(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|e| match e.code.as_str() {
+            "required" => e.message.clone(),
+            _ => format!("{}: {}", e.field, e.message),
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 impl From<DomainError> for AppError {
     fn from(err: DomainError) -> Self {
         match err {
-            DomainError::RequiredFieldMissing { field } => {
-                AppError::Validation(format!("{} is required", field))
-            }
-            DomainError::InvalidField { field, reason } => {
-                AppError::Validation(format!("{}: {}", field, reason))
-            }
+            DomainError::RequiredFieldMissing { .. }
+            | DomainError::InvalidField { .. }
+            | DomainError::Violations(_) => AppError::InvalidFields(
+                err.violations().into_iter().map(FieldError::from_domain).collect(),
+            ),
             DomainError::InvalidStateTransition { from, to, reason } => {
                 AppError::BadRequest(format!(
                     "Cannot transition from {} to {}: {}",
@@ -58,25 +149,59 @@ impl From<DomainError> for AppError {
             DomainError::BusinessRuleViolation { rule, details } => {
                 AppError::BadRequest(format!("{}: {}", rule, details))
             }
+            DomainError::DuplicateEmail { email, existing_id } => AppError::DuplicateEmail { email, existing_id },
         }
     }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        let errors = match &self {
+            AppError::InvalidFields(errors) => errors.clone(),
+            _ => Vec::new(),
+        };
+        let (code, existing_contact_id) = match &self {
+            AppError::DuplicateEmail { existing_id, .. } => {
+                (Some("duplicate_email".to_string()), Some(existing_id.clone()))
+            }
+            AppError::PossibleDuplicates(_) => (Some("possible_duplicate".to_string()), None),
+            _ => (None, None),
+        };
+
         let (status, error_message) = match &self {
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.clone()),
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
             AppError::Validation(msg) => (StatusCode::UNPROCESSABLE_ENTITY, msg.clone()),
+            AppError::InvalidFields(errors) => (StatusCode::UNPROCESSABLE_ENTITY, summarize(errors)),
             AppError::Conflict(msg) => (StatusCode::CONFLICT, msg.clone()),
+            AppError::DuplicateEmail { email, .. } => (
+                StatusCode::CONFLICT,
+                format!("A contact with email '{}' already exists", email),
+            ),
+            AppError::PossibleDuplicates(candidates) => (
+                StatusCode::CONFLICT,
+                format!("The contact looks like {} existing contact(s)", candidates.len()),
+            ),
             AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg.clone()),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg.clone()),
+            AppError::PayloadTooLarge(msg) => (StatusCode::PAYLOAD_TOO_LARGE, msg.clone()),
+            AppError::TooManyRequests(msg) => (StatusCode::TOO_MANY_REQUESTS, msg.clone()),
             AppError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg.clone()),
+            AppError::Upstream(msg) => (StatusCode::BAD_GATEWAY, msg.clone()),
             AppError::Database(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
         };
 
         let body = Json(ErrorResponse {
             error: error_message,
             status: status.as_u16(),
+            code,
+            existing_contact_id,
+            request_id: current_request_id(),
+            errors,
+            candidates: match self {
+                AppError::PossibleDuplicates(candidates) => candidates,
+                _ => Vec::new(),
+            },
         });
 
         (status, body).into_response()
@@ -84,3 +209,48 @@ impl IntoResponse for AppError {
 }
 
 pub type AppResult<T> = Result<T, AppError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_errors_from_domain_violations() {
+        let err = AppError::from(DomainError::Violations(vec![
+            DomainError::RequiredFieldMissing { field: "last_name".into() },
+            DomainError::InvalidField {
+                field: "email".into(),
+                reason: "Invalid email format".into(),
+            },
+        ]));
+
+        let AppError::InvalidFields(errors) = &err else {
+            panic!("expected field errors, got {:?}", err);
+        };
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].code, "required");
+        assert_eq!(errors[1].field, "email");
+        assert_eq!(summarize(errors), "last_name is required; email: Invalid email format");
+    }
+
+    #[test]
+    fn test_rule_violations_stay_bad_requests() {
+        let err = AppError::from(DomainError::BusinessRuleViolation {
+            rule: "merge".into(),
+            details: "cannot merge a contact into itself".into(),
+        });
+
+        assert!(matches!(err, AppError::BadRequest(_)));
+    }
+
+    #[test]
+    fn test_duplicate_email_is_a_409_with_code() {
+        let err = AppError::from(DomainError::DuplicateEmail {
+            email: "ada@example.com".into(),
+            existing_id: "ada".into(),
+        });
+
+        assert!(matches!(&err, AppError::DuplicateEmail { existing_id, .. } if existing_id == "ada"));
+        assert_eq!(err.into_response().status(), StatusCode::CONFLICT);
+    }
+}
@@ -0,0 +1,37 @@
+//! AI Handlers - drafting with the configured AI provider
+
+use axum::{extract::State, Json};
+
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{DraftReplyRequest, DraftReplyResponse};
+use crate::AppState;
+
+/// Draft a reply to an email from a contact
+///
+/// POST /api/ai/draft-reply
+/// Body: { contact_id, email_text, subject?, tone?, length? }
+///
+/// Takes the contact's recent timeline into account. Written by the AI
+/// provider when one is configured, otherwise from a built-in template.
+/// Nothing is sent or saved.
+#[utoipa::path(
+    post,
+    path = "/api/ai/draft-reply",
+    tag = "ai",
+    request_body = DraftReplyRequest,
+    responses(
+        (status = 200, description = "Reply draft", body = DraftReplyResponse),
+        (status = 400, description = "No email text", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse)
+    )
+)]
+pub async fn draft_reply(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(req): Json<DraftReplyRequest>,
+) -> AppResult<Json<DraftReplyResponse>> {
+    let draft = state.assistant_service.draft_reply(&user.workspace_id, req).await?;
+    Ok(Json(draft))
+}
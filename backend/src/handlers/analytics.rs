@@ -1,140 +1,346 @@
+//! Analytics Handlers - campaign, event, contact, funnel, churn, win/loss, sentiment and AI usage reports
+//!
+//! Every report but the event and sentiment ones accepts `?time_range=7d|30d|90d|365d|all` (default 30d).
+
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     Json,
 };
 
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult};
+use crate::middleware::CurrentUser;
+use crate::models::{
+    AiUsageReport, AnalyticsQuery, Campaign, CampaignAnalytics, ChurnAnalytics, ContactsAnalytics,
+    DeliverabilityAnalytics, Event, EventAnalytics, FunnelAnalytics, PipelineAnalytics, PipelineRegionAnalytics,
+    ResponseTimeAnalytics, SentimentAnalytics, WinLossAnalytics,
+};
 use crate::AppState;
 
-#[derive(serde::Serialize)]
-pub struct CampaignAnalytics {
-    pub campaign_id: String,
-    pub total_contacts: u64,
-    pub emails_sent: u64,
-    pub emails_opened: u64,
-    pub emails_clicked: u64,
-    pub landing_page_visits: u64,
-    pub conversions: u64,
-    pub open_rate: f64,
-    pub click_rate: f64,
-    pub conversion_rate: f64,
-}
-
+/// Email, landing page and conversion metrics for one campaign
+///
+/// GET /api/analytics/campaign/:id
+#[utoipa::path(
+    get,
+    path = "/api/analytics/campaign/{id}",
+    tag = "analytics",
+    params(
+        ("id" = String, Path, description = "Campaign ID"),
+        AnalyticsQuery
+    ),
+    responses(
+        (status = 200, description = "Campaign analytics", body = CampaignAnalytics),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Campaign not found", body = ErrorResponse)
+    )
+)]
 pub async fn campaign_analytics(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Path(id): Path<String>,
+    Query(query): Query<AnalyticsQuery>,
 ) -> AppResult<Json<CampaignAnalytics>> {
-    // Mock analytics data - in production, this would aggregate from timeline entries
-    Ok(Json(CampaignAnalytics {
-        campaign_id: id,
-        total_contacts: 1250,
-        emails_sent: 1200,
-        emails_opened: 480,
-        emails_clicked: 96,
-        landing_page_visits: 72,
-        conversions: 18,
-        open_rate: 40.0,
-        click_rate: 8.0,
-        conversion_rate: 1.5,
-    }))
+    let campaign: Option<Campaign> = state
+        .db
+        .select_scoped("campaign", &id, &user.workspace_id)
+        .await?;
+    campaign.ok_or_else(|| AppError::NotFound("Campaign not found".into()))?;
+
+    let analytics = state
+        .analytics_service
+        .campaign(&user.workspace_id, &id, query.time_range.unwrap_or_default())
+        .await?;
+    Ok(Json(analytics))
 }
 
-#[derive(serde::Serialize)]
-pub struct ContactsAnalytics {
-    pub total_contacts: u64,
-    pub leads: u64,
-    pub customers: u64,
-    pub partners: u64,
-    pub investors: u64,
-    pub other: u64,
-    pub avg_engagement_score: f64,
-    pub new_this_month: u64,
-    pub top_engaged: Vec<TopEngagedContact>,
+/// Bounces and spam complaints the email provider reported for one campaign
+///
+/// GET /api/analytics/campaign/:id/deliverability
+#[utoipa::path(
+    get,
+    path = "/api/analytics/campaign/{id}/deliverability",
+    tag = "analytics",
+    params(
+        ("id" = String, Path, description = "Campaign ID"),
+        AnalyticsQuery
+    ),
+    responses(
+        (status = 200, description = "Campaign deliverability", body = DeliverabilityAnalytics),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Campaign not found", body = ErrorResponse)
+    )
+)]
+pub async fn campaign_deliverability(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Query(query): Query<AnalyticsQuery>,
+) -> AppResult<Json<DeliverabilityAnalytics>> {
+    let campaign: Option<Campaign> = state
+        .db
+        .select_scoped("campaign", &id, &user.workspace_id)
+        .await?;
+    campaign.ok_or_else(|| AppError::NotFound("Campaign not found".into()))?;
+
+    let analytics = state
+        .analytics_service
+        .deliverability(&user.workspace_id, &id, query.time_range.unwrap_or_default())
+        .await?;
+    Ok(Json(analytics))
 }
 
-#[derive(serde::Serialize)]
-pub struct TopEngagedContact {
-    pub id: String,
-    pub name: String,
-    pub engagement_score: f64,
+/// RSVP funnel, registrations over time and top attendees of one event
+///
+/// GET /api/analytics/event/:id
+#[utoipa::path(
+    get,
+    path = "/api/analytics/event/{id}",
+    tag = "analytics",
+    params(("id" = String, Path, description = "Event ID")),
+    responses(
+        (status = 200, description = "Event analytics", body = EventAnalytics),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Event not found", body = ErrorResponse)
+    )
+)]
+pub async fn event_analytics(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<EventAnalytics>> {
+    let event: Option<Event> = state.db.select_scoped("event", &id, &user.workspace_id).await?;
+    event.ok_or_else(|| AppError::NotFound("Event not found".into()))?;
+
+    let analytics = state.analytics_service.event(&user.workspace_id, &id).await?;
+    Ok(Json(analytics))
 }
 
+/// Status distribution, engagement and growth of the contact base
+///
+/// GET /api/analytics/contacts
+#[utoipa::path(
+    get,
+    path = "/api/analytics/contacts",
+    tag = "analytics",
+    params(AnalyticsQuery),
+    responses(
+        (status = 200, description = "Contact analytics", body = ContactsAnalytics),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
 pub async fn contacts_analytics(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<AnalyticsQuery>,
 ) -> AppResult<Json<ContactsAnalytics>> {
-    // Mock analytics data
-    Ok(Json(ContactsAnalytics {
-        total_contacts: 3500,
-        leads: 2100,
-        customers: 850,
-        partners: 320,
-        investors: 180,
-        other: 50,
-        avg_engagement_score: 42.5,
-        new_this_month: 145,
-        top_engaged: vec![
-            TopEngagedContact {
-                id: "1".to_string(),
-                name: "John Smith".to_string(),
-                engagement_score: 98.5,
-            },
-            TopEngagedContact {
-                id: "2".to_string(),
-                name: "Jane Doe".to_string(),
-                engagement_score: 95.2,
-            },
-            TopEngagedContact {
-                id: "3".to_string(),
-                name: "Bob Johnson".to_string(),
-                engagement_score: 92.8,
-            },
-        ],
-    }))
+    let analytics = state
+        .analytics_service
+        .contacts(&user.workspace_id, query.time_range.unwrap_or_default())
+        .await?;
+    Ok(Json(analytics))
+}
+
+/// Conversion funnel for contacts created in the time range
+///
+/// GET /api/analytics/funnel
+#[utoipa::path(
+    get,
+    path = "/api/analytics/funnel",
+    tag = "analytics",
+    params(AnalyticsQuery),
+    responses(
+        (status = 200, description = "Conversion funnel", body = FunnelAnalytics),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn funnel_analytics(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<AnalyticsQuery>,
+) -> AppResult<Json<FunnelAnalytics>> {
+    let analytics = state
+        .analytics_service
+        .funnel(&user.workspace_id, query.time_range.unwrap_or_default())
+        .await?;
+    Ok(Json(analytics))
+}
+
+/// Customers churned in the time range, the churn rate and the most given reasons
+///
+/// GET /api/analytics/churn
+#[utoipa::path(
+    get,
+    path = "/api/analytics/churn",
+    tag = "analytics",
+    params(AnalyticsQuery),
+    responses(
+        (status = 200, description = "Churn report", body = ChurnAnalytics),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn churn_analytics(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<AnalyticsQuery>,
+) -> AppResult<Json<ChurnAnalytics>> {
+    let analytics = state
+        .analytics_service
+        .churn(&user.workspace_id, query.time_range.unwrap_or_default())
+        .await?;
+    Ok(Json(analytics))
+}
+
+/// How fast we and our contacts answer each other's email in the time range
+///
+/// GET /api/analytics/response-times
+#[utoipa::path(
+    get,
+    path = "/api/analytics/response-times",
+    tag = "analytics",
+    params(AnalyticsQuery),
+    responses(
+        (status = 200, description = "Email response times", body = ResponseTimeAnalytics),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn response_time_analytics(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<AnalyticsQuery>,
+) -> AppResult<Json<ResponseTimeAnalytics>> {
+    let analytics = state
+        .analytics_service
+        .response_times(&user.workspace_id, query.time_range.unwrap_or_default())
+        .await?;
+    Ok(Json(analytics))
+}
+
+/// Deals and leads won and lost in the time range, by reason and month
+///
+/// GET /api/analytics/win-loss
+#[utoipa::path(
+    get,
+    path = "/api/analytics/win-loss",
+    tag = "analytics",
+    params(AnalyticsQuery),
+    responses(
+        (status = 200, description = "Win/loss report", body = WinLossAnalytics),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn win_loss_analytics(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<AnalyticsQuery>,
+) -> AppResult<Json<WinLossAnalytics>> {
+    let analytics = state
+        .analytics_service
+        .win_loss(&user.workspace_id, query.time_range.unwrap_or_default())
+        .await?;
+    Ok(Json(analytics))
+}
+
+/// Open deal value by stage, and deals won and lost in the time range
+///
+/// GET /api/analytics/pipeline
+#[utoipa::path(
+    get,
+    path = "/api/analytics/pipeline",
+    tag = "analytics",
+    params(AnalyticsQuery),
+    responses(
+        (status = 200, description = "Deal pipeline", body = PipelineAnalytics),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn pipeline_analytics(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<AnalyticsQuery>,
+) -> AppResult<Json<PipelineAnalytics>> {
+    let analytics = state
+        .analytics_service
+        .pipeline(&user.workspace_id, query.time_range.unwrap_or_default())
+        .await?;
+    Ok(Json(analytics))
 }
 
-#[derive(serde::Serialize)]
-pub struct FunnelAnalytics {
-    pub stages: Vec<FunnelStage>,
-    pub overall_conversion_rate: f64,
+/// The deal pipeline by country and region, from each deal's company or contact
+///
+/// GET /api/analytics/pipeline/regions
+#[utoipa::path(
+    get,
+    path = "/api/analytics/pipeline/regions",
+    tag = "analytics",
+    params(AnalyticsQuery),
+    responses(
+        (status = 200, description = "Deal pipeline by region", body = PipelineRegionAnalytics),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn pipeline_region_analytics(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<AnalyticsQuery>,
+) -> AppResult<Json<PipelineRegionAnalytics>> {
+    let analytics = state
+        .analytics_service
+        .pipeline_by_region(&user.workspace_id, query.time_range.unwrap_or_default())
+        .await?;
+    Ok(Json(analytics))
 }
 
-#[derive(serde::Serialize)]
-pub struct FunnelStage {
-    pub name: String,
-    pub count: u64,
-    pub percentage: f64,
+/// Tokens, latency and cost of AI content generation, per campaign and month
+///
+/// GET /api/analytics/ai-usage
+#[utoipa::path(
+    get,
+    path = "/api/analytics/ai-usage",
+    tag = "analytics",
+    params(AnalyticsQuery),
+    responses(
+        (status = 200, description = "AI usage and cost", body = AiUsageReport),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn ai_usage_analytics(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<AnalyticsQuery>,
+) -> AppResult<Json<AiUsageReport>> {
+    let report = state
+        .analytics_service
+        .ai_usage(&user.workspace_id, query.time_range.unwrap_or_default())
+        .await?;
+    Ok(Json(report))
 }
 
-pub async fn funnel_analytics(State(_state): State<AppState>) -> AppResult<Json<FunnelAnalytics>> {
-    // Mock funnel data
-    Ok(Json(FunnelAnalytics {
-        stages: vec![
-            FunnelStage {
-                name: "Visitors".to_string(),
-                count: 10000,
-                percentage: 100.0,
-            },
-            FunnelStage {
-                name: "Leads".to_string(),
-                count: 2100,
-                percentage: 21.0,
-            },
-            FunnelStage {
-                name: "Qualified".to_string(),
-                count: 840,
-                percentage: 8.4,
-            },
-            FunnelStage {
-                name: "Opportunities".to_string(),
-                count: 252,
-                percentage: 2.52,
-            },
-            FunnelStage {
-                name: "Customers".to_string(),
-                count: 126,
-                percentage: 1.26,
-            },
-        ],
-        overall_conversion_rate: 1.26,
-    }))
+/// Accounts turning negative: companies whose contacts came across worse in
+/// the last 30 days than in the 60 before, from the sentiment of their interactions
+///
+/// GET /api/analytics/sentiment
+#[utoipa::path(
+    get,
+    path = "/api/analytics/sentiment",
+    tag = "analytics",
+    responses(
+        (status = 200, description = "Accounts turning negative, biggest drop first", body = SentimentAnalytics),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn sentiment_analytics(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> AppResult<Json<SentimentAnalytics>> {
+    let report = state.analytics_service.sentiment(&user.workspace_id).await?;
+    Ok(Json(report))
 }
@@ -0,0 +1,171 @@
+//! Attachment Handlers - upload, list, download and delete attached files
+//!
+//! Uploads send the file itself as the request body, with its type in
+//! `Content-Type` and its name in the `filename` query parameter.
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::domain::content_disposition;
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{AttachmentResponse, UploadAttachmentQuery};
+use crate::AppState;
+
+/// List the files attached to a contact and its timeline entries
+///
+/// GET /api/contacts/:id/attachments
+#[utoipa::path(
+    get,
+    path = "/api/contacts/{id}/attachments",
+    tag = "attachments",
+    params(("id" = String, Path, description = "Contact ID")),
+    responses(
+        (status = 200, description = "Attachments, newest first", body = Vec<AttachmentResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse)
+    )
+)]
+pub async fn list_contact_attachments(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<Vec<AttachmentResponse>>> {
+    let attachments = state.attachment_service.list_for_contact(&user.workspace_id, &id).await?;
+    Ok(Json(attachments))
+}
+
+/// Attach a file to a contact
+///
+/// POST /api/contacts/:id/attachments?filename=proposal.pdf
+/// Body: the file, with its `Content-Type`
+#[utoipa::path(
+    post,
+    path = "/api/contacts/{id}/attachments",
+    tag = "attachments",
+    params(("id" = String, Path, description = "Contact ID"), UploadAttachmentQuery),
+    request_body(content = Vec<u8>, description = "The file", content_type = "application/octet-stream"),
+    responses(
+        (status = 200, description = "File attached", body = AttachmentResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse),
+        (status = 413, description = "File over the size limit", body = ErrorResponse),
+        (status = 422, description = "Empty file, file too large or type not allowed", body = ErrorResponse)
+    )
+)]
+pub async fn upload_contact_attachment(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Query(query): Query<UploadAttachmentQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> AppResult<Json<AttachmentResponse>> {
+    let attachment = state
+        .attachment_service
+        .attach_to_contact(&user, &id, &query.filename, content_type(&headers), body.to_vec())
+        .await?;
+    Ok(Json(attachment))
+}
+
+/// Attach a file to a timeline entry
+///
+/// POST /api/timeline/:id/attachments?filename=deck.pptx
+/// Body: the file, with its `Content-Type`
+#[utoipa::path(
+    post,
+    path = "/api/timeline/{id}/attachments",
+    tag = "attachments",
+    params(("id" = String, Path, description = "Timeline entry ID"), UploadAttachmentQuery),
+    request_body(content = Vec<u8>, description = "The file", content_type = "application/octet-stream"),
+    responses(
+        (status = 200, description = "File attached", body = AttachmentResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Timeline entry not found", body = ErrorResponse),
+        (status = 413, description = "File over the size limit", body = ErrorResponse),
+        (status = 422, description = "Empty file, file too large or type not allowed", body = ErrorResponse)
+    )
+)]
+pub async fn upload_timeline_attachment(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Query(query): Query<UploadAttachmentQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> AppResult<Json<AttachmentResponse>> {
+    let attachment = state
+        .attachment_service
+        .attach_to_entry(&user, &id, &query.filename, content_type(&headers), body.to_vec())
+        .await?;
+    Ok(Json(attachment))
+}
+
+/// Download an attached file
+///
+/// GET /api/attachments/:id/download
+#[utoipa::path(
+    get,
+    path = "/api/attachments/{id}/download",
+    tag = "attachments",
+    params(("id" = String, Path, description = "Attachment ID")),
+    responses(
+        (status = 200, description = "The file, with its original name and type", body = Vec<u8>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Attachment not found", body = ErrorResponse)
+    )
+)]
+pub async fn download_attachment(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Response> {
+    let (attachment, data) = state.attachment_service.download(&user.workspace_id, &id).await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, attachment.content_type),
+            (header::CONTENT_DISPOSITION, content_disposition(&attachment.filename)),
+            // Never let a browser render an uploaded file as something else
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        data,
+    )
+        .into_response())
+}
+
+/// Delete an attachment and its file
+///
+/// DELETE /api/attachments/:id
+#[utoipa::path(
+    delete,
+    path = "/api/attachments/{id}",
+    tag = "attachments",
+    params(("id" = String, Path, description = "Attachment ID")),
+    responses(
+        (status = 200, description = "Attachment deleted", body = serde_json::Value),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Attachment not found", body = ErrorResponse)
+    )
+)]
+pub async fn delete_attachment(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    state.attachment_service.delete(&user.workspace_id, &id).await?;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+fn content_type(headers: &HeaderMap) -> &str {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/octet-stream")
+}
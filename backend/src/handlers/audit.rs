@@ -0,0 +1,69 @@
+//! Audit Handlers - read access to the audit log
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+
+use crate::domain::AuditEntity;
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{AuditLogResponse, AuditQuery};
+use crate::AppState;
+
+/// List audit log entries, newest first
+///
+/// GET /api/audit?entity_type=contact&entity_id=abc&action=update&actor_id=u1&limit=50&offset=0
+#[utoipa::path(
+    get,
+    path = "/api/audit",
+    tag = "audit",
+    params(AuditQuery),
+    responses(
+        (status = 200, description = "Audit log entries", body = Vec<AuditLogResponse>),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn list_audit_log(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<AuditQuery>,
+) -> AppResult<Json<Vec<AuditLogResponse>>> {
+    let entries = state.audit_service.list(&user.workspace_id, &query).await?;
+    Ok(Json(entries))
+}
+
+/// Full change history of a single record
+///
+/// GET /api/audit/:entity_type/:entity_id
+#[utoipa::path(
+    get,
+    path = "/api/audit/{entity_type}/{entity_id}",
+    tag = "audit",
+    params(
+        ("entity_type" = AuditEntity, Path, description = "Kind of record"),
+        ("entity_id" = String, Path, description = "Record ID"),
+        AuditQuery
+    ),
+    responses(
+        (status = 200, description = "Change history of the record", body = Vec<AuditLogResponse>),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn get_entity_history(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path((entity_type, entity_id)): Path<(AuditEntity, String)>,
+    Query(query): Query<AuditQuery>,
+) -> AppResult<Json<Vec<AuditLogResponse>>> {
+    let query = AuditQuery {
+        entity_type: Some(entity_type),
+        entity_id: Some(entity_id),
+        ..query
+    };
+
+    let entries = state.audit_service.list(&user.workspace_id, &query).await?;
+    Ok(Json(entries))
+}
@@ -0,0 +1,112 @@
+//! Auth Handlers - registration, login and token refresh
+//!
+//! These routes are public; every other /api route requires the
+//! access token they hand out.
+
+use axum::{extract::State, Json};
+
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{AuthResponse, LoginRequest, RefreshRequest, RegisterRequest, UserResponse};
+use crate::AppState;
+
+/// Register a new user
+///
+/// POST /api/auth/register
+/// Body: { email, name, password, workspace_name? }
+#[utoipa::path(
+    post,
+    path = "/api/auth/register",
+    tag = "auth",
+    request_body = RegisterRequest,
+    responses(
+        (status = 200, description = "Registered and logged in", body = AuthResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 409, description = "Conflict", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    ),
+    security(())
+)]
+pub async fn register(
+    State(state): State<AppState>,
+    Json(req): Json<RegisterRequest>,
+) -> AppResult<Json<AuthResponse>> {
+    let response = state
+        .auth_service
+        .register(
+            &req.email,
+            &req.name,
+            &req.password,
+            req.workspace_name.as_deref(),
+        )
+        .await?;
+
+    Ok(Json(response))
+}
+
+/// Log in with email and password
+///
+/// POST /api/auth/login
+/// Body: { email, password }
+#[utoipa::path(
+    post,
+    path = "/api/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Logged in", body = AuthResponse)
+    ),
+    security(())
+)]
+pub async fn login(
+    State(state): State<AppState>,
+    Json(req): Json<LoginRequest>,
+) -> AppResult<Json<AuthResponse>> {
+    let response = state.auth_service.login(&req.email, &req.password).await?;
+
+    Ok(Json(response))
+}
+
+/// Exchange a refresh token for a new token pair
+///
+/// POST /api/auth/refresh
+/// Body: { refresh_token }
+#[utoipa::path(
+    post,
+    path = "/api/auth/refresh",
+    tag = "auth",
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, description = "New token pair", body = AuthResponse)
+    ),
+    security(())
+)]
+pub async fn refresh(
+    State(state): State<AppState>,
+    Json(req): Json<RefreshRequest>,
+) -> AppResult<Json<AuthResponse>> {
+    let response = state.auth_service.refresh(&req.refresh_token).await?;
+
+    Ok(Json(response))
+}
+
+/// Get the currently authenticated user
+///
+/// GET /api/auth/me
+#[utoipa::path(
+    get,
+    path = "/api/auth/me",
+    tag = "auth",
+    responses(
+        (status = 200, description = "The authenticated user", body = UserResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn me(
+    State(state): State<AppState>,
+    CurrentUser(identity): CurrentUser,
+) -> AppResult<Json<UserResponse>> {
+    let user = state.auth_service.current_user(&identity).await?;
+
+    Ok(Json(user.into()))
+}
@@ -0,0 +1,102 @@
+//! Avatar Handlers - upload, remove and serve contact pictures
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{AvatarQuery, ContactResponse};
+use crate::AppState;
+
+/// Upload a contact's avatar
+///
+/// PUT /api/contacts/:id/avatar
+/// Body: a PNG, JPEG, GIF or WebP image
+///
+/// The image is cropped to a square and stored at 64, 128 and 256 pixels.
+#[utoipa::path(
+    put,
+    path = "/api/contacts/{id}/avatar",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Contact ID")),
+    request_body(content = Vec<u8>, description = "The image", content_type = "image/*"),
+    responses(
+        (status = 200, description = "Avatar replaced", body = ContactResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse),
+        (status = 413, description = "Image over the size limit", body = ErrorResponse),
+        (status = 422, description = "Not a readable image, or too large", body = ErrorResponse)
+    )
+)]
+pub async fn upload_contact_avatar(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    body: Bytes,
+) -> AppResult<Json<ContactResponse>> {
+    let contact = state.avatar_service.upload(&user, &id, body.to_vec()).await?;
+    Ok(Json(contact))
+}
+
+/// Remove a contact's uploaded avatar; its Gravatar is shown instead
+///
+/// DELETE /api/contacts/:id/avatar
+#[utoipa::path(
+    delete,
+    path = "/api/contacts/{id}/avatar",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Contact ID")),
+    responses(
+        (status = 200, description = "Avatar removed", body = ContactResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse)
+    )
+)]
+pub async fn delete_contact_avatar(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<ContactResponse>> {
+    let contact = state.avatar_service.remove(&user, &id).await?;
+    Ok(Json(contact))
+}
+
+/// Serve an uploaded avatar (public, linked from `avatar_url`)
+///
+/// GET /avatars/:workspace_id/:avatar_id?size=128
+#[utoipa::path(
+    get,
+    path = "/avatars/{workspace_id}/{avatar_id}",
+    tag = "contacts",
+    params(
+        ("workspace_id" = String, Path, description = "Workspace ID"),
+        ("avatar_id" = String, Path, description = "Avatar ID"),
+        AvatarQuery
+    ),
+    responses(
+        (status = 200, description = "The avatar as PNG", content_type = "image/png", body = Vec<u8>),
+        (status = 404, description = "Avatar not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_avatar(
+    State(state): State<AppState>,
+    Path((workspace_id, avatar_id)): Path<(String, String)>,
+    Query(query): Query<AvatarQuery>,
+) -> AppResult<Response> {
+    let png = state.avatar_service.image(&workspace_id, &avatar_id, query.size).await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "image/png"),
+            // A new upload gets a new URL, so this one never changes
+            (header::CACHE_CONTROL, "public, max-age=31536000, immutable"),
+        ],
+        png,
+    )
+        .into_response())
+}
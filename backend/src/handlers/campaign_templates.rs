@@ -0,0 +1,106 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+use crate::domain::AuditEntity;
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{CampaignResponse, CampaignTemplateResponse, InstantiateTemplateRequest};
+use crate::AppState;
+
+#[utoipa::path(
+    get,
+    path = "/api/campaign-templates",
+    tag = "campaigns",
+    responses(
+        (status = 200, description = "Campaign templates in the workspace", body = Vec<CampaignTemplateResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn list_templates(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> AppResult<Json<Vec<CampaignTemplateResponse>>> {
+    let templates = state.campaign_template_service.list(&user.workspace_id).await?;
+    Ok(Json(templates))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/campaign-templates/{id}",
+    tag = "campaigns",
+    params(("id" = String, Path, description = "Template ID")),
+    responses(
+        (status = 200, description = "Campaign template", body = CampaignTemplateResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Template not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_template(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<CampaignTemplateResponse>> {
+    let template = state.campaign_template_service.get(&user.workspace_id, &id).await?;
+    Ok(Json(template.into()))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/campaign-templates/{id}",
+    tag = "campaigns",
+    params(("id" = String, Path, description = "Template ID")),
+    responses(
+        (status = 200, description = "Template deleted", body = serde_json::Value),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Template not found", body = ErrorResponse)
+    )
+)]
+pub async fn delete_template(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    state.campaign_template_service.delete(&user.workspace_id, &id).await?;
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// Start a new draft campaign from a template
+///
+/// POST /api/campaign-templates/:id/instantiate
+/// Body (optional): { "name": "..." }
+#[utoipa::path(
+    post,
+    path = "/api/campaign-templates/{id}/instantiate",
+    tag = "campaigns",
+    params(("id" = String, Path, description = "Template ID")),
+    request_body = InstantiateTemplateRequest,
+    responses(
+        (status = 200, description = "The new draft campaign", body = CampaignResponse),
+        (status = 400, description = "The template's saved segment no longer exists", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Template not found", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn instantiate_template(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    req: Option<Json<InstantiateTemplateRequest>>,
+) -> AppResult<Json<CampaignResponse>> {
+    let req = req.map(|Json(req)| req).unwrap_or_default();
+    let campaign = state
+        .campaign_template_service
+        .instantiate(&user.workspace_id, &id, req)
+        .await?;
+
+    let response = CampaignResponse::from(campaign.clone());
+    state
+        .audit_service
+        .record_create(&user, AuditEntity::Campaign, &response.id, &campaign)
+        .await;
+
+    Ok(Json(response))
+}
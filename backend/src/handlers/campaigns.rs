@@ -1,23 +1,42 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     Json,
 };
 use chrono::Utc;
 use surrealdb::sql::Thing;
 
-use crate::ai::{ai_email, ai_landing_page, ai_social};
+use crate::db::workspace_thing;
+use crate::domain::{AuditEntity, MergeFields};
 use crate::error::{AppError, AppResult};
+use crate::middleware::CurrentUser;
+use crate::services::validate_definition;
 use crate::models::{
-    AssetType, Campaign, CampaignAsset, CampaignAssetResponse, CampaignResponse, CampaignStatus,
-    CreateCampaignRequest, GenerateAssetsRequest, UpdateCampaignRequest,
+    AssetQuery, Campaign, CampaignAssetResponse, CampaignRecipientResponse,
+    CampaignResponse, CampaignStatus, CampaignTemplateResponse, CloneCampaignRequest, Company, CreateCampaignRequest,
+    CreateShortLinksRequest, EmailPreviewQuery, EmailPreviewResponse, GenerateAssetsRequest, RecipientQuery,
+    RegenerateAssetRequest, SaveTemplateRequest, ScheduleCampaignRequest, ScheduleSocialPostRequest, Segment,
+    ShortLinkResponse, SocialPostResponse, UpdateAssetRequest, UpdateCampaignRequest,
 };
 use crate::AppState;
 
-pub async fn list_campaigns(State(state): State<AppState>) -> AppResult<Json<Vec<CampaignResponse>>> {
+#[utoipa::path(
+    get,
+    path = "/api/campaigns",
+    tag = "campaigns",
+    responses(
+        (status = 200, description = "Campaigns in the workspace", body = Vec<CampaignResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn list_campaigns(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> AppResult<Json<Vec<CampaignResponse>>> {
     let campaigns: Vec<Campaign> = state
         .db
         .client
-        .query("SELECT * FROM campaign ORDER BY created_at DESC")
+        .query("SELECT * FROM campaign WHERE workspace = $workspace ORDER BY created_at DESC")
+        .bind(("workspace", workspace_thing(&user.workspace_id)))
         .await?
         .take(0)?;
 
@@ -25,10 +44,46 @@ pub async fn list_campaigns(State(state): State<AppState>) -> AppResult<Json<Vec
     Ok(Json(responses))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/campaigns",
+    tag = "campaigns",
+    request_body = CreateCampaignRequest,
+    responses(
+        (status = 200, description = "Campaign created", body = CampaignResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
 pub async fn create_campaign(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Json(req): Json<CreateCampaignRequest>,
 ) -> AppResult<Json<CampaignResponse>> {
+    if let Some(ref segment_definition) = req.segment_definition {
+        validate_definition(segment_definition)?;
+    }
+    let segment = match req.segment_id {
+        Some(ref segment_id) => {
+            if req.segment_definition.is_some() {
+                return Err(AppError::BadRequest(
+                    "Give either segment_id or segment_definition, not both".into(),
+                ));
+            }
+            Some(saved_segment(&state, &user.workspace_id, segment_id).await?)
+        }
+        None => None,
+    };
+    let sender = match req.sender_id {
+        Some(ref sender_id) => Some(
+            state
+                .sender_service
+                .selectable(&user.workspace_id, &user.user_id, sender_id)
+                .await?,
+        ),
+        None => None,
+    };
+
     let now = Utc::now();
 
     let campaigns: Vec<Campaign> = state
@@ -37,47 +92,91 @@ pub async fn create_campaign(
         .create("campaign")
         .content(Campaign {
             id: None,
+            workspace: workspace_thing(&user.workspace_id),
             name: req.name,
             objective: req.objective,
             status: CampaignStatus::Draft,
             channels: req.channels,
             prompt: req.prompt,
             segment_definition: req.segment_definition.unwrap_or(serde_json::json!({})),
+            segment,
+            sender,
+            scheduled_at: None,
+            send_window: None,
             created_at: now,
             updated_at: now,
         })
         .await?;
 
     let campaign = campaigns.into_iter().next().ok_or_else(|| AppError::Internal("Failed to create campaign".into()))?;
-    Ok(Json(campaign.into()))
+    let response = CampaignResponse::from(campaign.clone());
+    state
+        .audit_service
+        .record_create(&user, AuditEntity::Campaign, &response.id, &campaign)
+        .await;
+
+    Ok(Json(response))
 }
 
+/// Record link to a saved segment, checked to exist in the workspace
+async fn saved_segment(state: &AppState, workspace_id: &str, segment_id: &str) -> AppResult<Thing> {
+    let segment: Option<Segment> = state.db.select_scoped("segment", segment_id, workspace_id).await?;
+    segment
+        .and_then(|s| s.id)
+        .ok_or_else(|| AppError::BadRequest(format!("Segment {} not found", segment_id)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/campaigns/{id}",
+    tag = "campaigns",
+    params(("id" = String, Path, description = "Campaign ID")),
+    responses(
+        (status = 200, description = "Campaign", body = CampaignResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Campaign not found", body = ErrorResponse)
+    )
+)]
 pub async fn get_campaign(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Path(id): Path<String>,
 ) -> AppResult<Json<CampaignResponse>> {
     let campaign: Option<Campaign> = state
         .db
-        .client
-        .select(("campaign", id.as_str()))
+        .select_scoped("campaign", &id, &user.workspace_id)
         .await?;
 
     let campaign = campaign.ok_or_else(|| AppError::NotFound("Campaign not found".into()))?;
     Ok(Json(campaign.into()))
 }
 
+#[utoipa::path(
+    patch,
+    path = "/api/campaigns/{id}",
+    tag = "campaigns",
+    params(("id" = String, Path, description = "Campaign ID")),
+    request_body = UpdateCampaignRequest,
+    responses(
+        (status = 200, description = "Campaign updated", body = CampaignResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Campaign not found", body = ErrorResponse)
+    )
+)]
 pub async fn update_campaign(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Path(id): Path<String>,
     Json(req): Json<UpdateCampaignRequest>,
 ) -> AppResult<Json<CampaignResponse>> {
     let existing: Option<Campaign> = state
         .db
-        .client
-        .select(("campaign", id.as_str()))
+        .select_scoped("campaign", &id, &user.workspace_id)
         .await?;
 
-    let mut campaign = existing.ok_or_else(|| AppError::NotFound("Campaign not found".into()))?;
+    let before = existing.ok_or_else(|| AppError::NotFound("Campaign not found".into()))?;
+    let mut campaign = before.clone();
 
     if let Some(name) = req.name {
         campaign.name = name;
@@ -95,7 +194,20 @@ pub async fn update_campaign(
         campaign.prompt = Some(prompt);
     }
     if let Some(segment_definition) = req.segment_definition {
+        validate_definition(&segment_definition)?;
         campaign.segment_definition = segment_definition;
+        campaign.segment = None;
+    }
+    if let Some(segment_id) = req.segment_id {
+        campaign.segment = Some(saved_segment(&state, &user.workspace_id, &segment_id).await?);
+    }
+    if let Some(sender_id) = req.sender_id {
+        campaign.sender = Some(
+            state
+                .sender_service
+                .selectable(&user.workspace_id, &user.user_id, &sender_id)
+                .await?,
+        );
     }
 
     campaign.updated_at = Utc::now();
@@ -108,94 +220,530 @@ pub async fn update_campaign(
         .await?;
 
     let campaign = updated.ok_or_else(|| AppError::Internal("Failed to update campaign".into()))?;
+    state
+        .audit_service
+        .record_update(&user, AuditEntity::Campaign, &id, &before, &campaign)
+        .await;
+
     Ok(Json(campaign.into()))
 }
 
+/// Copy a campaign, with its audience and generated assets, into a new draft
+///
+/// POST /api/campaigns/:id/clone
+/// Body (optional): { "name": "..." }
+#[utoipa::path(
+    post,
+    path = "/api/campaigns/{id}/clone",
+    tag = "campaigns",
+    params(("id" = String, Path, description = "Campaign ID")),
+    request_body = CloneCampaignRequest,
+    responses(
+        (status = 200, description = "The new draft campaign", body = CampaignResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Campaign not found", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn clone_campaign(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    req: Option<Json<CloneCampaignRequest>>,
+) -> AppResult<Json<CampaignResponse>> {
+    let req = req.map(|Json(req)| req).unwrap_or_default();
+    let campaign = state
+        .campaign_template_service
+        .clone_campaign(&user.workspace_id, &id, req)
+        .await?;
+
+    let response = CampaignResponse::from(campaign.clone());
+    state
+        .audit_service
+        .record_create(&user, AuditEntity::Campaign, &response.id, &campaign)
+        .await;
+
+    Ok(Json(response))
+}
+
+/// Save a campaign's setup and assets to the template library
+///
+/// POST /api/campaigns/:id/template
+/// Body: { "name": "...", "description": "..." }
+#[utoipa::path(
+    post,
+    path = "/api/campaigns/{id}/template",
+    tag = "campaigns",
+    params(("id" = String, Path, description = "Campaign ID")),
+    request_body = SaveTemplateRequest,
+    responses(
+        (status = 200, description = "Template saved", body = CampaignTemplateResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Campaign not found", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn save_campaign_template(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Json(req): Json<SaveTemplateRequest>,
+) -> AppResult<Json<CampaignTemplateResponse>> {
+    let template = state
+        .campaign_template_service
+        .save_template(&user.workspace_id, &id, req)
+        .await?;
+    Ok(Json(template))
+}
+
+/// List a campaign's assets
+///
+/// GET /api/campaigns/:id/assets?include_history=true
+///
+/// Only the active version of each asset type unless `include_history` is set.
+#[utoipa::path(
+    get,
+    path = "/api/campaigns/{id}/assets",
+    tag = "campaigns",
+    params(
+        ("id" = String, Path, description = "Campaign ID"),
+        AssetQuery
+    ),
+    responses(
+        (status = 200, description = "Generated assets", body = Vec<CampaignAssetResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Campaign not found", body = ErrorResponse)
+    )
+)]
 pub async fn list_campaign_assets(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Path(id): Path<String>,
+    Query(query): Query<AssetQuery>,
 ) -> AppResult<Json<Vec<CampaignAssetResponse>>> {
-    let assets: Vec<CampaignAsset> = state
-        .db
-        .client
-        .query("SELECT * FROM campaign_asset WHERE campaign = $campaign ORDER BY created_at DESC")
-        .bind(("campaign", Thing::from(("campaign", id.as_str()))))
-        .await?
-        .take(0)?;
+    let assets = state
+        .campaign_asset_service
+        .list(&user.workspace_id, &id, query.include_history.unwrap_or(false))
+        .await?;
 
-    let responses: Vec<CampaignAssetResponse> = assets.into_iter().map(Into::into).collect();
-    Ok(Json(responses))
+    // Newest first
+    Ok(Json(assets.into_iter().rev().map(Into::into).collect()))
 }
 
+/// Generate assets; each becomes the active version of its type
+///
+/// POST /api/campaigns/:id/assets
+/// Body: { "prompt": "...", "asset_types": ["email", "social_post"] }
+#[utoipa::path(
+    post,
+    path = "/api/campaigns/{id}/assets",
+    tag = "campaigns",
+    params(("id" = String, Path, description = "Campaign ID")),
+    request_body = GenerateAssetsRequest,
+    responses(
+        (status = 200, description = "Assets generated", body = Vec<CampaignAssetResponse>),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Campaign not found", body = ErrorResponse),
+        (status = 502, description = "AI provider failed", body = ErrorResponse)
+    )
+)]
 pub async fn generate_campaign_assets(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Path(id): Path<String>,
     Json(req): Json<GenerateAssetsRequest>,
 ) -> AppResult<Json<Vec<CampaignAssetResponse>>> {
-    let campaign_thing = Thing::from(("campaign", id.as_str()));
-    let mut created_assets = Vec::new();
-
-    for asset_type in req.asset_types {
-        let generated_content = match asset_type {
-            AssetType::Email => {
-                let email = ai_email::generate_email(&req.prompt).await;
-                serde_json::to_value(email).unwrap_or(serde_json::json!({}))
-            }
-            AssetType::SocialPost => {
-                let posts = ai_social::generate_social_posts(&req.prompt).await;
-                serde_json::to_value(posts).unwrap_or(serde_json::json!({}))
-            }
-            AssetType::LandingPage => {
-                let page = ai_landing_page::generate_landing_page(&req.prompt).await;
-                serde_json::to_value(page).unwrap_or(serde_json::json!({}))
-            }
-            AssetType::EventInvite => {
-                let email = ai_email::generate_email(&format!("Event invitation: {}", req.prompt)).await;
-                serde_json::to_value(email).unwrap_or(serde_json::json!({}))
-            }
-        };
+    let assets = state.campaign_asset_service.generate(&user.workspace_id, &id, req).await?;
+    Ok(Json(assets.into_iter().map(Into::into).collect()))
+}
 
-        let assets: Vec<CampaignAsset> = state
-            .db
-            .client
-            .create("campaign_asset")
-            .content(CampaignAsset {
-                id: None,
-                campaign: campaign_thing.clone(),
-                asset_type: asset_type.clone(),
-                generated_content,
-                url: None,
-                created_at: Utc::now(),
-            })
-            .await?;
-
-        if let Some(a) = assets.into_iter().next() {
-            created_assets.push(a.into());
-        }
-    }
+/// Generate a new version of an asset
+///
+/// POST /api/campaigns/:id/assets/:asset_id/regenerate
+/// Body: { "prompt": "..." } (optional; defaults to the campaign's prompt)
+///
+/// The new version becomes active; the old one is kept as its parent.
+#[utoipa::path(
+    post,
+    path = "/api/campaigns/{id}/assets/{asset_id}/regenerate",
+    tag = "campaigns",
+    params(
+        ("id" = String, Path, description = "Campaign ID"),
+        ("asset_id" = String, Path, description = "Asset ID")
+    ),
+    request_body = RegenerateAssetRequest,
+    responses(
+        (status = 200, description = "New version generated", body = CampaignAssetResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Campaign or asset not found", body = ErrorResponse),
+        (status = 422, description = "No prompt given and the campaign has none", body = ErrorResponse),
+        (status = 502, description = "AI provider failed", body = ErrorResponse)
+    )
+)]
+pub async fn regenerate_campaign_asset(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path((id, asset_id)): Path<(String, String)>,
+    req: Option<Json<RegenerateAssetRequest>>,
+) -> AppResult<Json<CampaignAssetResponse>> {
+    let req = req.map(|Json(r)| r).unwrap_or_default();
+    let asset = state
+        .campaign_asset_service
+        .regenerate(&user.workspace_id, &id, &asset_id, req)
+        .await?;
+    Ok(Json(asset.into()))
+}
 
-    Ok(Json(created_assets))
+/// Edit an asset's generated content before the campaign runs
+///
+/// PATCH /api/campaigns/:id/assets/:asset_id
+/// Body: { "generated_content": { ... } }
+///
+/// Stored as a new active version; the edited one is kept as its parent.
+/// An email with a `document` has its HTML and text bodies compiled from
+/// the document's sections.
+#[utoipa::path(
+    patch,
+    path = "/api/campaigns/{id}/assets/{asset_id}",
+    tag = "campaigns",
+    params(
+        ("id" = String, Path, description = "Campaign ID"),
+        ("asset_id" = String, Path, description = "Asset ID")
+    ),
+    request_body = UpdateAssetRequest,
+    responses(
+        (status = 200, description = "Edited version", body = CampaignAssetResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Campaign or asset not found", body = ErrorResponse),
+        (status = 409, description = "Campaign has already run", body = ErrorResponse),
+        (status = 422, description = "Content doesn't match the asset type", body = ErrorResponse)
+    )
+)]
+pub async fn update_campaign_asset(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path((id, asset_id)): Path<(String, String)>,
+    Json(req): Json<UpdateAssetRequest>,
+) -> AppResult<Json<CampaignAssetResponse>> {
+    let asset = state
+        .campaign_asset_service
+        .edit(&user.workspace_id, &id, &asset_id, req)
+        .await?;
+    Ok(Json(asset.into()))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/campaigns/{id}/execute",
+    tag = "campaigns",
+    params(
+        ("id" = String, Path, description = "Campaign ID"),
+        ("Idempotency-Key" = Option<String>, Header, description = "Replay the first response for retries")
+    ),
+    responses(
+        (status = 200, description = "Execution started; retries with the same Idempotency-Key return the first result", body = serde_json::Value),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Campaign not found", body = ErrorResponse),
+        (status = 409, description = "Conflict", body = ErrorResponse),
+        (status = 422, description = "Recipients with merge variables that have no value, one error per contact", body = ErrorResponse)
+    )
+)]
 pub async fn execute_campaign(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Path(id): Path<String>,
 ) -> AppResult<Json<serde_json::Value>> {
-    // Update campaign status to running
-    let _: Option<Campaign> = state
+    let campaign: Campaign = state
         .db
-        .client
-        .query("UPDATE campaign SET status = 'running', updated_at = $now WHERE id = $id")
-        .bind(("id", Thing::from(("campaign", id.as_str()))))
-        .bind(("now", Utc::now()))
+        .select_scoped("campaign", &id, &user.workspace_id)
         .await?
-        .take(0)?;
+        .ok_or_else(|| AppError::NotFound("Campaign not found".into()))?;
+
+    let summary = state.campaign_scheduler.execute(&user.workspace_id, &campaign).await?;
 
     // In a real implementation, this would trigger background jobs
     // For now, we just return success
     Ok(Json(serde_json::json!({
         "status": "execution_started",
         "campaign_id": id,
+        "recipients_added": summary.recipients_added,
+        "recipients_sent": summary.recipients_sent,
+        "recipients_suppressed": summary.recipients_suppressed,
+        "recipients_waiting": summary.recipients_waiting,
+        "next_send_at": summary.next_send_at,
+        "social_posts_queued": summary.social_posts_queued,
         "message": "Campaign execution has been triggered. Assets will be distributed according to channel configuration."
     })))
 }
+
+/// Schedule a campaign to execute at a given time
+///
+/// POST /api/campaigns/:id/schedule
+/// Body: { "scheduled_at": "...", "send_window": { "start_hour": 9, "end_hour": 17, "days": ["Mon"], "timezone": "Europe/Stockholm" } }
+///
+/// With a send window, email only goes out while the window is open in
+/// each contact's timezone. Rescheduling a scheduled campaign replaces
+/// its time and window.
+#[utoipa::path(
+    post,
+    path = "/api/campaigns/{id}/schedule",
+    tag = "campaigns",
+    params(("id" = String, Path, description = "Campaign ID")),
+    request_body = ScheduleCampaignRequest,
+    responses(
+        (status = 200, description = "Campaign scheduled", body = CampaignResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Campaign not found", body = ErrorResponse),
+        (status = 409, description = "Campaign is neither a draft nor scheduled", body = ErrorResponse),
+        (status = 422, description = "Invalid time or send window", body = ErrorResponse)
+    )
+)]
+pub async fn schedule_campaign(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Json(req): Json<ScheduleCampaignRequest>,
+) -> AppResult<Json<CampaignResponse>> {
+    let campaign = state.campaign_scheduler.schedule(&user.workspace_id, &id, req).await?;
+    Ok(Json(campaign.into()))
+}
+
+/// Cancel a scheduled campaign, returning it to draft
+///
+/// POST /api/campaigns/:id/cancel
+#[utoipa::path(
+    post,
+    path = "/api/campaigns/{id}/cancel",
+    tag = "campaigns",
+    params(("id" = String, Path, description = "Campaign ID")),
+    responses(
+        (status = 200, description = "Schedule cancelled", body = CampaignResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Campaign not found", body = ErrorResponse),
+        (status = 409, description = "Campaign is not scheduled", body = ErrorResponse)
+    )
+)]
+pub async fn cancel_campaign(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<CampaignResponse>> {
+    let campaign = state.campaign_scheduler.cancel(&user.workspace_id, &id).await?;
+    Ok(Json(campaign.into()))
+}
+
+/// Render the campaign's latest email for one contact
+///
+/// POST /api/campaigns/:id/preview-email?contact_id=...
+#[utoipa::path(
+    post,
+    path = "/api/campaigns/{id}/preview-email",
+    tag = "campaigns",
+    params(
+        ("id" = String, Path, description = "Campaign ID"),
+        EmailPreviewQuery
+    ),
+    responses(
+        (status = 200, description = "Personalized email", body = EmailPreviewResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Campaign, contact or email asset not found", body = ErrorResponse)
+    )
+)]
+pub async fn preview_campaign_email(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Query(query): Query<EmailPreviewQuery>,
+) -> AppResult<Json<EmailPreviewResponse>> {
+    let _campaign: Campaign = state
+        .db
+        .select_scoped("campaign", &id, &user.workspace_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Campaign not found".into()))?;
+
+    let email = state
+        .campaign_scheduler
+        .latest_email(&user.workspace_id, &id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Campaign has no email asset to preview".into()))?;
+
+    let contact = state.contact_service.get(&user.workspace_id, &query.contact_id).await?.contact;
+    let company = match &contact.company_id {
+        Some(company_id) => state
+            .db
+            .select_scoped::<Company>("company", company_id, &user.workspace_id)
+            .await?
+            .map(|c| c.name),
+        None => None,
+    };
+
+    let fields = MergeFields::new(&contact.first_name, &contact.last_name, &contact.email)
+        .company(company.as_deref())
+        .custom_fields(&contact.custom_fields);
+    let (personalized, unresolved_variables) = email.personalize(&fields);
+
+    Ok(Json(EmailPreviewResponse {
+        campaign_id: id,
+        contact_id: query.contact_id,
+        to: contact.email,
+        subject: personalized.subject,
+        preview_text: personalized.preview_text,
+        body_html: personalized.body_html,
+        body_text: personalized.body_text,
+        cta_text: personalized.cta_text,
+        cta_url: personalized.cta_url,
+        unresolved_variables,
+    }))
+}
+
+/// List the resolved recipients of a campaign
+///
+/// GET /api/campaigns/:id/recipients?status=pending&limit=100&offset=0
+#[utoipa::path(
+    get,
+    path = "/api/campaigns/{id}/recipients",
+    tag = "campaigns",
+    params(
+        ("id" = String, Path, description = "Campaign ID"),
+        RecipientQuery
+    ),
+    responses(
+        (status = 200, description = "Recipients", body = Vec<CampaignRecipientResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Campaign not found", body = ErrorResponse)
+    )
+)]
+pub async fn list_campaign_recipients(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Query(query): Query<RecipientQuery>,
+) -> AppResult<Json<Vec<CampaignRecipientResponse>>> {
+    let _campaign: Campaign = state
+        .db
+        .select_scoped("campaign", &id, &user.workspace_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Campaign not found".into()))?;
+
+    let recipients = state
+        .segment_service
+        .recipients(
+            &user.workspace_id,
+            &id,
+            query.status,
+            query.limit.unwrap_or(100).min(1000),
+            query.offset.unwrap_or(0),
+        )
+        .await?;
+
+    Ok(Json(recipients.into_iter().map(Into::into).collect()))
+}
+
+/// Schedule one of a campaign's generated social posts
+///
+/// POST /api/campaigns/:id/social-posts
+/// Body: { "asset_id": "...", "platform": "linked_in", "publish_at": "...", "contact_ids": ["..."] }
+///
+/// Tagged contacts get a social touch on their timeline once the post is live.
+#[utoipa::path(
+    post,
+    path = "/api/campaigns/{id}/social-posts",
+    tag = "campaigns",
+    params(("id" = String, Path, description = "Campaign ID")),
+    request_body = ScheduleSocialPostRequest,
+    responses(
+        (status = 200, description = "Post scheduled", body = SocialPostResponse),
+        (status = 400, description = "Platform not configured or no post for it", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Campaign, asset or contact not found", body = ErrorResponse),
+        (status = 422, description = "Post too long for the platform", body = ErrorResponse)
+    )
+)]
+pub async fn schedule_social_post(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Json(req): Json<ScheduleSocialPostRequest>,
+) -> AppResult<Json<SocialPostResponse>> {
+    let post = state.social_service.schedule(&user.workspace_id, &id, req).await?;
+    Ok(Json(post))
+}
+
+/// List a campaign's social posts with their publishing status and URLs
+///
+/// GET /api/campaigns/:id/social-posts
+#[utoipa::path(
+    get,
+    path = "/api/campaigns/{id}/social-posts",
+    tag = "campaigns",
+    params(("id" = String, Path, description = "Campaign ID")),
+    responses(
+        (status = 200, description = "Social posts", body = Vec<SocialPostResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Campaign not found", body = ErrorResponse)
+    )
+)]
+pub async fn list_social_posts(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<Vec<SocialPostResponse>>> {
+    let posts = state.social_service.list(&user.workspace_id, &id).await?;
+    Ok(Json(posts))
+}
+
+/// Shorten a URL for each of the contacts, for sharing outside campaign email
+///
+/// POST /api/campaigns/:id/short-links
+/// Body: { "url": "https://...", "channel": "social", "contact_ids": ["..."] }
+///
+/// Following a link redirects through /r/:code and logs an email click or a
+/// social touch on the contact's timeline. Contacts that already have a
+/// link to the URL get the same one back.
+#[utoipa::path(
+    post,
+    path = "/api/campaigns/{id}/short-links",
+    tag = "campaigns",
+    params(("id" = String, Path, description = "Campaign ID")),
+    request_body = CreateShortLinksRequest,
+    responses(
+        (status = 200, description = "One short link per contact", body = Vec<ShortLinkResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Campaign or contact not found", body = ErrorResponse),
+        (status = 422, description = "Not an http(s) URL, or too many contacts", body = ErrorResponse)
+    )
+)]
+pub async fn create_short_links(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Json(req): Json<CreateShortLinksRequest>,
+) -> AppResult<Json<Vec<ShortLinkResponse>>> {
+    let links = state.tracking_service.shorten(&user.workspace_id, &id, req).await?;
+    Ok(Json(links))
+}
+
+/// List a campaign's short links with their click counts
+///
+/// GET /api/campaigns/:id/short-links
+#[utoipa::path(
+    get,
+    path = "/api/campaigns/{id}/short-links",
+    tag = "campaigns",
+    params(("id" = String, Path, description = "Campaign ID")),
+    responses(
+        (status = 200, description = "Short links", body = Vec<ShortLinkResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Campaign not found", body = ErrorResponse)
+    )
+)]
+pub async fn list_short_links(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<Vec<ShortLinkResponse>>> {
+    let links = state.tracking_service.short_links(&user.workspace_id, &id).await?;
+    Ok(Json(links))
+}
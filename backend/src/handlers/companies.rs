@@ -4,14 +4,28 @@ use axum::{
 };
 use chrono::Utc;
 
+use crate::db::workspace_thing;
+use crate::domain::{validate_country, validate_place, validate_timezone, AuditEntity, DomainResult};
 use crate::error::{AppError, AppResult};
+use crate::middleware::CurrentUser;
 use crate::models::{
     Company, CompanyQuery, CompanyResponse, CreateCompanyRequest, UpdateCompanyRequest,
 };
 use crate::AppState;
 
+#[utoipa::path(
+    get,
+    path = "/api/companies",
+    tag = "companies",
+    params(CompanyQuery),
+    responses(
+        (status = 200, description = "List of companies", body = Vec<CompanyResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
 pub async fn list_companies(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Query(query): Query<CompanyQuery>,
 ) -> AppResult<Json<Vec<CompanyResponse>>> {
     let limit = query.limit.unwrap_or(50);
@@ -20,7 +34,11 @@ pub async fn list_companies(
     let companies: Vec<Company> = state
         .db
         .client
-        .query("SELECT * FROM company ORDER BY created_at DESC LIMIT $limit START $offset")
+        .query(
+            "SELECT * FROM company WHERE workspace = $workspace AND deleted_at IS NONE \
+             ORDER BY created_at DESC LIMIT $limit START $offset",
+        )
+        .bind(("workspace", workspace_thing(&user.workspace_id)))
         .bind(("limit", limit))
         .bind(("offset", offset))
         .await?
@@ -30,58 +48,110 @@ pub async fn list_companies(
     Ok(Json(responses))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/companies",
+    tag = "companies",
+    request_body = CreateCompanyRequest,
+    responses(
+        (status = 200, description = "Company created", body = CompanyResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
 pub async fn create_company(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Json(req): Json<CreateCompanyRequest>,
 ) -> AppResult<Json<CompanyResponse>> {
     let now = Utc::now();
+    let company = Company {
+        id: None,
+        workspace: workspace_thing(&user.workspace_id),
+        name: req.name,
+        domain: req.domain,
+        industry: req.industry,
+        size: req.size,
+        tags: req.tags.unwrap_or_default(),
+        country: non_blank(req.country).map(|c| c.to_ascii_uppercase()),
+        region: non_blank(req.region),
+        city: non_blank(req.city),
+        timezone: non_blank(req.timezone),
+        created_at: now,
+        updated_at: now,
+    };
+    validate_location(&company)?;
 
     let companies: Vec<Company> = state
         .db
         .client
         .create("company")
-        .content(Company {
-            id: None,
-            name: req.name,
-            domain: req.domain,
-            industry: req.industry,
-            size: req.size,
-            tags: req.tags.unwrap_or_default(),
-            created_at: now,
-            updated_at: now,
-        })
+        .content(company)
         .await?;
 
     let company = companies.into_iter().next().ok_or_else(|| AppError::Internal("Failed to create company".into()))?;
-    Ok(Json(company.into()))
+    let response = CompanyResponse::from(company.clone());
+    state
+        .audit_service
+        .record_create(&user, AuditEntity::Company, &response.id, &company)
+        .await;
+
+    Ok(Json(response))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/companies/{id}",
+    tag = "companies",
+    params(("id" = String, Path, description = "Company ID")),
+    responses(
+        (status = 200, description = "Company", body = CompanyResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Company not found", body = ErrorResponse)
+    )
+)]
 pub async fn get_company(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Path(id): Path<String>,
 ) -> AppResult<Json<CompanyResponse>> {
     let company: Option<Company> = state
         .db
-        .client
-        .select(("company", id.as_str()))
+        .select_scoped("company", &id, &user.workspace_id)
         .await?;
 
     let company = company.ok_or_else(|| AppError::NotFound("Company not found".into()))?;
     Ok(Json(company.into()))
 }
 
+#[utoipa::path(
+    patch,
+    path = "/api/companies/{id}",
+    tag = "companies",
+    params(("id" = String, Path, description = "Company ID")),
+    request_body = UpdateCompanyRequest,
+    responses(
+        (status = 200, description = "Company updated", body = CompanyResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Company not found", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
 pub async fn update_company(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Path(id): Path<String>,
     Json(req): Json<UpdateCompanyRequest>,
 ) -> AppResult<Json<CompanyResponse>> {
     let existing: Option<Company> = state
         .db
-        .client
-        .select(("company", id.as_str()))
+        .select_scoped("company", &id, &user.workspace_id)
         .await?;
 
-    let mut company = existing.ok_or_else(|| AppError::NotFound("Company not found".into()))?;
+    let before = existing.ok_or_else(|| AppError::NotFound("Company not found".into()))?;
+    let mut company = before.clone();
 
     if let Some(name) = req.name {
         company.name = name;
@@ -98,7 +168,20 @@ pub async fn update_company(
     if let Some(tags) = req.tags {
         company.tags = tags;
     }
+    if let Some(country) = req.country {
+        company.country = non_blank(Some(country)).map(|c| c.to_ascii_uppercase());
+    }
+    if let Some(region) = req.region {
+        company.region = non_blank(Some(region));
+    }
+    if let Some(city) = req.city {
+        company.city = non_blank(Some(city));
+    }
+    if let Some(timezone) = req.timezone {
+        company.timezone = non_blank(Some(timezone));
+    }
 
+    validate_location(&company)?;
     company.updated_at = Utc::now();
 
     let updated: Option<Company> = state
@@ -109,18 +192,98 @@ pub async fn update_company(
         .await?;
 
     let company = updated.ok_or_else(|| AppError::Internal("Failed to update company".into()))?;
+    state
+        .audit_service
+        .record_update(&user, AuditEntity::Company, &id, &before, &company)
+        .await;
+
     Ok(Json(company.into()))
 }
 
+/// Move a company to the trash
+///
+/// DELETE /api/companies/:id
+#[utoipa::path(
+    delete,
+    path = "/api/companies/{id}",
+    tag = "companies",
+    params(("id" = String, Path, description = "Company ID")),
+    responses(
+        (status = 200, description = "Company moved to the trash", body = serde_json::Value),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Company not found", body = ErrorResponse)
+    )
+)]
 pub async fn delete_company(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Path(id): Path<String>,
 ) -> AppResult<Json<serde_json::Value>> {
-    let _: Option<Company> = state
+    let existing: Company = state
         .db
-        .client
-        .delete(("company", id.as_str()))
+        .select_scoped("company", &id, &user.workspace_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Company not found".into()))?;
+
+    let deleted = state
+        .db
+        .soft_delete_scoped("company", &id, &user.workspace_id)
         .await?;
 
+    if !deleted {
+        return Err(AppError::NotFound("Company not found".into()));
+    }
+
+    state
+        .audit_service
+        .record_delete(&user, AuditEntity::Company, &id, &existing)
+        .await;
+
     Ok(Json(serde_json::json!({ "deleted": true })))
 }
+
+/// Restore a company from the trash
+///
+/// POST /api/companies/:id/restore
+#[utoipa::path(
+    post,
+    path = "/api/companies/{id}/restore",
+    tag = "companies",
+    params(("id" = String, Path, description = "Company ID")),
+    responses(
+        (status = 200, description = "Company restored", body = CompanyResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Company not in the trash", body = ErrorResponse)
+    )
+)]
+pub async fn restore_company(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<CompanyResponse>> {
+    let company: Company = state
+        .db
+        .restore_scoped("company", &id, &user.workspace_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Company not found in trash".into()))?;
+
+    state
+        .audit_service
+        .record_restore(&user, AuditEntity::Company, &id, &company)
+        .await;
+
+    Ok(Json(company.into()))
+}
+
+/// Check a company's location by the same rules as a contact's
+fn validate_location(company: &Company) -> DomainResult<()> {
+    validate_country(company.country.as_deref())?;
+    validate_place(company.region.as_deref(), "region")?;
+    validate_place(company.city.as_deref(), "city")?;
+    validate_timezone(company.timezone.as_deref())
+}
+
+/// Trimmed, with blank as none
+fn non_blank(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
@@ -8,26 +8,46 @@
 //! Business logic lives in the service and domain layers.
 
 use axum::{
+    body::Body,
     extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
     Json,
 };
 
 use crate::domain::ContactStatus as DomainStatus;
-use crate::error::AppResult;
-use crate::models::{ContactQuery, ContactResponse, CreateContactRequest, UpdateContactRequest};
+use crate::error::{AppError, AppResult};
+use crate::middleware::CurrentUser;
+use crate::models::{
+    AcceptDedupeRequest, BatchGetContactsRequest, BatchGetContactsResponse, BulkContactsRequest, BulkContactsResponse, BulkOperation, ChurnContactRequest, ContactCountResponse, ContactEngagementResponse, ContactExportParams,
+    ContactQuery, ContactReportQuery, ContactResponse, ContactSummaryResponse, CreateContactRequest,
+    DedupeSuggestionQuery, DedupeSuggestionResponse, DuplicateCandidate, DuplicateQuery, EnrichContactResponse,
+    EngagementBreakdownResponse, EngagementRecalculationResponse, MergeContactsRequest, MergeContactsResponse,
+    PrepBriefResponse, StatusHistoryResponse, TagCount, UpdateContactRequest,
+};
 use crate::repositories::ContactQuery as RepoContactQuery;
-use crate::services::{CreateContactInput, UpdateContactInput};
+use crate::services::{
+    parse_definition, parse_tag_filter, BulkChange, CreateContactInput, UpdateContactInput, MAX_BULK_CONTACTS,
+};
 use crate::AppState;
 
+/// Total number of matches of a paged list
+const TOTAL_COUNT: &str = "x-total-count";
+
 /// List contacts with optional filters
 ///
-/// GET /api/contacts?limit=50&offset=0&status=lead&search=john
+/// GET /api/contacts?limit=50&offset=0&status=lead&search=john&tags_all=vip,beta&tags_any=speaker,sponsor&exclude_tags=churned&company_id=acme&country=SE&region=Stockholm&min_engagement=20&max_engagement=80&created_after=2024-06-03T00:00:00Z&created_before=2024-06-10T00:00:00Z&sort=engagement_score&order=desc
+///
+/// The `X-Total-Count` header holds the number of contacts matching the
+/// filters across all pages.
 #[utoipa::path(
     get,
     path = "/api/contacts",
+    tag = "contacts",
     params(ContactQuery),
     responses(
-        (status = 200, description = "List of contacts", body = Vec<ContactResponse>),
+        (status = 200, description = "List of contacts", body = Vec<ContactResponse>,
+            headers(("X-Total-Count" = u64, description = "Contacts matching the filters across all pages"))),
         (status = 400, description = "Bad request", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
@@ -35,41 +55,148 @@ use crate::AppState;
 )]
 pub async fn list_contacts(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Query(query): Query<ContactQuery>,
-) -> AppResult<Json<Vec<ContactResponse>>> {
-    // Convert API query params to repository query
-    let repo_query = RepoContactQuery::new()
-        .with_limit(query.limit.unwrap_or(50))
-        .with_offset(query.offset.unwrap_or(0));
+) -> AppResult<([(&'static str, String); 1], Json<Vec<ContactResponse>>)> {
+    let repo_query = repo_query(query)?;
 
-    let contacts = state.contact_service.list(repo_query).await?;
+    let (contacts, total) = tokio::try_join!(
+        state.contact_service.list(&user.workspace_id, repo_query.clone()),
+        state.contact_service.count(&user.workspace_id, &repo_query),
+    )?;
 
     let responses: Vec<ContactResponse> = contacts
         .into_iter()
         .map(|stored| ContactResponse::from_stored(stored))
         .collect();
 
-    Ok(Json(responses))
+    Ok(([(TOTAL_COUNT, total.to_string())], Json(responses)))
+}
+
+/// Number of contacts matching the filters
+///
+/// GET /api/contacts/count?status=lead&tags_any=vip,beta
+///
+/// Takes the same filters as the list; `limit`, `offset`, `sort` and
+/// `order` are ignored.
+#[utoipa::path(
+    get,
+    path = "/api/contacts/count",
+    tag = "contacts",
+    params(ContactQuery),
+    responses(
+        (status = 200, description = "Number of matching contacts", body = ContactCountResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn count_contacts(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<ContactQuery>,
+) -> AppResult<Json<ContactCountResponse>> {
+    let repo_query = repo_query(query)?;
+    let count = state.contact_service.count(&user.workspace_id, &repo_query).await?;
+
+    Ok(Json(ContactCountResponse { count }))
+}
+
+/// Every tag on the workspace's contacts, with how many carry it
+///
+/// GET /api/contacts/tags
+///
+/// Most used first; trashed contacts don't count.
+#[utoipa::path(
+    get,
+    path = "/api/contacts/tags",
+    tag = "contacts",
+    responses(
+        (status = 200, description = "Tags in use, most used first", body = Vec<TagCount>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn list_tags(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> AppResult<Json<Vec<TagCount>>> {
+    let tags = state.contact_service.tags(&user.workspace_id).await?;
+
+    Ok(Json(tags))
+}
+
+/// Export contacts matching the filters as a file download
+///
+/// GET /api/contacts/export?format=csv|json&status=lead&search=john
+///
+/// The body is streamed page by page, so large workspaces are never
+/// buffered in memory. `limit` and `offset` are ignored.
+#[utoipa::path(
+    get,
+    path = "/api/contacts/export",
+    tag = "contacts",
+    params(
+        ContactQuery,
+        ContactExportParams
+    ),
+    responses(
+        (status = 200, description = "Matching contacts as a CSV or JSON download", body = String),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn export_contacts(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<ContactQuery>,
+    Query(params): Query<ContactExportParams>,
+) -> Response {
+    let format = params.format.unwrap_or_default();
+
+    let repo_query = match repo_query(query) {
+        Ok(repo_query) => repo_query,
+        Err(e) => return e.into_response(),
+    };
+
+    let stream = state
+        .contact_service
+        .export(&user.workspace_id, repo_query, format);
+
+    let disposition = format!(
+        "attachment; filename=\"contacts.{}\"",
+        format.file_extension()
+    );
+
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        Body::from_stream(stream),
+    )
+        .into_response()
 }
 
 /// Create a new contact
 ///
 /// POST /api/contacts
-/// Body: { first_name, last_name, email, phone?, linkedin_url?, tags?, status?, company_id? }
+/// Body: { first_name, last_name, email, phone?, linkedin_url?, timezone?, country?, region?, city?, tags?, status?, company_id?, custom_fields?, note?, allow_duplicates? }
 #[utoipa::path(
     post,
     path = "/api/contacts",
+    tag = "contacts",
+    params(("Idempotency-Key" = Option<String>, Header, description = "Replay the first response for retries")),
     request_body = CreateContactRequest,
     responses(
-        (status = 201, description = "Contact created", body = ContactResponse),
+        (status = 200, description = "Contact created", body = ContactResponse),
         (status = 400, description = "Bad request", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 409, description = "Conflict", body = ErrorResponse),
+        (status = 409, description = "Email already used by another contact (code `duplicate_email`), or likely duplicates found (code `possible_duplicate`, listed in `candidates`)", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     )
 )]
 pub async fn create_contact(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Json(req): Json<CreateContactRequest>,
 ) -> AppResult<Json<ContactResponse>> {
     let input = CreateContactInput {
@@ -78,12 +205,22 @@ pub async fn create_contact(
         email: req.email,
         phone: req.phone,
         linkedin_url: req.linkedin_url,
+        timezone: req.timezone,
+        country: req.country,
+        region: req.region,
+        city: req.city,
         tags: req.tags.unwrap_or_default(),
         status: req.status.map(|s| api_status_to_domain(s)),
         company_id: req.company_id,
+        custom_fields: req.custom_fields.unwrap_or_default(),
+        note: req.note,
+        allow_duplicates: req.allow_duplicates,
     };
 
-    let stored = state.contact_service.create(input).await?;
+    let stored = state
+        .contact_service
+        .create(&user, input)
+        .await?;
 
     Ok(Json(ContactResponse::from_stored(stored)))
 }
@@ -91,11 +228,23 @@ pub async fn create_contact(
 /// Get a single contact by ID
 ///
 /// GET /api/contacts/:id
+#[utoipa::path(
+    get,
+    path = "/api/contacts/{id}",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Contact ID")),
+    responses(
+        (status = 200, description = "Contact", body = ContactResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse)
+    )
+)]
 pub async fn get_contact(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Path(id): Path<String>,
 ) -> AppResult<Json<ContactResponse>> {
-    let stored = state.contact_service.get(&id).await?;
+    let stored = state.contact_service.get(&user.workspace_id, &id).await?;
 
     Ok(Json(ContactResponse::from_stored(stored)))
 }
@@ -103,9 +252,25 @@ pub async fn get_contact(
 /// Update an existing contact
 ///
 /// PATCH /api/contacts/:id
-/// Body: { first_name?, last_name?, email?, phone?, linkedin_url?, tags?, status?, engagement_score?, company_id? }
+/// Body: { first_name?, last_name?, email?, phone?, linkedin_url?, timezone?, country?, region?, city?, tags?, status?, status_reason?, win_loss_reason?, engagement_score?, company_id?, custom_fields? }
+#[utoipa::path(
+    patch,
+    path = "/api/contacts/{id}",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Contact ID")),
+    request_body = UpdateContactRequest,
+    responses(
+        (status = 200, description = "Contact updated", body = ContactResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse),
+        (status = 409, description = "Email already used by another contact (code `duplicate_email`)", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
 pub async fn update_contact(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Path(id): Path<String>,
     Json(req): Json<UpdateContactRequest>,
 ) -> AppResult<Json<ContactResponse>> {
@@ -115,30 +280,626 @@ pub async fn update_contact(
         email: req.email,
         phone: req.phone,
         linkedin_url: req.linkedin_url,
+        timezone: req.timezone,
+        country: req.country,
+        region: req.region,
+        city: req.city,
         tags: req.tags,
         status: req.status.map(|s| api_status_to_domain(s)),
+        status_reason: req.status_reason,
+        win_loss_reason: req.win_loss_reason,
         engagement_score: req.engagement_score,
         company_id: req.company_id,
+        pipeline_position: None,
+        custom_fields: req.custom_fields,
     };
 
-    let stored = state.contact_service.update(&id, input).await?;
+    let stored = state
+        .contact_service
+        .update(&user, &id, input)
+        .await?;
 
     Ok(Json(ContactResponse::from_stored(stored)))
 }
 
-/// Delete a contact
+/// Move a contact to the trash
 ///
 /// DELETE /api/contacts/:id
+#[utoipa::path(
+    delete,
+    path = "/api/contacts/{id}",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Contact ID")),
+    responses(
+        (status = 200, description = "Contact moved to the trash", body = serde_json::Value),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse)
+    )
+)]
 pub async fn delete_contact(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Path(id): Path<String>,
 ) -> AppResult<Json<serde_json::Value>> {
-    state.contact_service.delete(&id).await?;
+    state.contact_service.delete(&user, &id).await?;
 
     Ok(Json(serde_json::json!({ "deleted": true })))
 }
 
+/// Restore a contact from the trash
+///
+/// POST /api/contacts/:id/restore
+#[utoipa::path(
+    post,
+    path = "/api/contacts/{id}/restore",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Contact ID")),
+    responses(
+        (status = 200, description = "Contact restored", body = ContactResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not in the trash", body = ErrorResponse)
+    )
+)]
+pub async fn restore_contact(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<ContactResponse>> {
+    let stored = state.contact_service.restore(&user, &id).await?;
+
+    Ok(Json(ContactResponse::from_stored(stored)))
+}
+
+/// Churn a customer: back to Lead, tagged `churned`, with the reason on their timeline
+///
+/// POST /api/contacts/:id/churn
+/// Body: { reason, churned_at? }
+#[utoipa::path(
+    post,
+    path = "/api/contacts/{id}/churn",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Contact ID")),
+    request_body = ChurnContactRequest,
+    responses(
+        (status = 200, description = "Contact churned", body = ContactResponse),
+        (status = 400, description = "The contact is not a customer", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse),
+        (status = 422, description = "Missing reason or a churn date in the future", body = ErrorResponse)
+    )
+)]
+pub async fn churn_contact(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Json(req): Json<ChurnContactRequest>,
+) -> AppResult<Json<ContactResponse>> {
+    let stored = state
+        .contact_service
+        .churn(&user, &id, &req.reason, req.churned_at)
+        .await?;
+
+    Ok(Json(ContactResponse::from_stored(stored)))
+}
+
+/// Merge a duplicate contact into a primary contact
+///
+/// POST /api/contacts/merge
+/// Body: { primary_id, duplicate_id }
+#[utoipa::path(
+    post,
+    path = "/api/contacts/merge",
+    tag = "contacts",
+    request_body = MergeContactsRequest,
+    responses(
+        (status = 200, description = "Contacts merged", body = MergeContactsResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse)
+    )
+)]
+pub async fn merge_contacts(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(req): Json<MergeContactsRequest>,
+) -> AppResult<Json<MergeContactsResponse>> {
+    let result = state
+        .contact_service
+        .merge(&user, &req.primary_id, &req.duplicate_id)
+        .await?;
+
+    Ok(Json(result))
+}
+
+/// Fill in a contact's headline, company and location from its LinkedIn profile
+///
+/// POST /api/contacts/:id/enrich
+///
+/// Only empty fields, or ones still holding what the last enrichment found,
+/// are written; values edited by hand are kept.
+#[utoipa::path(
+    post,
+    path = "/api/contacts/{id}/enrich",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Contact ID")),
+    responses(
+        (status = 200, description = "Contact enriched", body = EnrichContactResponse),
+        (status = 400, description = "Enrichment not configured, or the contact has no LinkedIn URL", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact or LinkedIn profile not found", body = ErrorResponse),
+        (status = 502, description = "The enrichment provider failed", body = ErrorResponse)
+    )
+)]
+pub async fn enrich_contact(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<EnrichContactResponse>> {
+    let result = state.enrichment_service.enrich(&user, &id).await?;
+
+    Ok(Json(result))
+}
+
+/// Load many contacts by ID in one request
+///
+/// POST /api/contacts/batch-get
+/// Body: { ids: [...] }
+///
+/// Contacts come back in the order asked for; IDs that don't match a
+/// contact are listed in `not_found` rather than failing the request.
+#[utoipa::path(
+    post,
+    path = "/api/contacts/batch-get",
+    tag = "contacts",
+    request_body = BatchGetContactsRequest,
+    responses(
+        (status = 200, description = "The contacts found", body = BatchGetContactsResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 422, description = "More than 500 IDs", body = ErrorResponse)
+    )
+)]
+pub async fn batch_get_contacts(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(req): Json<BatchGetContactsRequest>,
+) -> AppResult<Json<BatchGetContactsResponse>> {
+    let (contacts, not_found) = state.contact_service.get_many(&user.workspace_id, req.ids).await?;
+
+    Ok(Json(BatchGetContactsResponse {
+        contacts: contacts.into_iter().map(ContactResponse::from_stored).collect(),
+        not_found,
+    }))
+}
+
+/// Apply tag, status and owner changes to many contacts at once
+///
+/// POST /api/contacts/bulk
+/// Body: { contact_ids? | segment_definition?, operations: [{ op: "add_tags", tags }, ...] }
+#[utoipa::path(
+    post,
+    path = "/api/contacts/bulk",
+    tag = "contacts",
+    request_body = BulkContactsRequest,
+    responses(
+        (status = 200, description = "Per-contact results", body = BulkContactsResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn bulk_update_contacts(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(req): Json<BulkContactsRequest>,
+) -> AppResult<Json<BulkContactsResponse>> {
+    let ids = match (req.contact_ids, req.segment_definition) {
+        (Some(ids), None) => ids,
+        (None, Some(definition)) => {
+            let definition = parse_definition(&definition)?;
+            // One past the cap, so the service can refuse oversized segments
+            state
+                .segment_service
+                .matching_ids(&user.workspace_id, &definition, MAX_BULK_CONTACTS + 1)
+                .await?
+        }
+        _ => return Err(AppError::BadRequest("Give either contact_ids or segment_definition".into())),
+    };
+
+    let changes = req
+        .operations
+        .into_iter()
+        .map(|op| match op {
+            BulkOperation::AddTags { tags } => BulkChange::AddTags(tags),
+            BulkOperation::RemoveTags { tags } => BulkChange::RemoveTags(tags),
+            BulkOperation::SetStatus { status } => BulkChange::SetStatus(api_status_to_domain(status)),
+            BulkOperation::SetOwner { owner_id } => BulkChange::SetOwner(owner_id),
+        })
+        .collect();
+
+    let result = state.contact_service.bulk_update(&user, ids, changes).await?;
+
+    Ok(Json(result))
+}
+
+/// Likely duplicate contacts by email, name and phone similarity
+///
+/// GET /api/contacts/duplicates?limit=50
+#[utoipa::path(
+    get,
+    path = "/api/contacts/duplicates",
+    tag = "contacts",
+    params(DuplicateQuery),
+    responses(
+        (status = 200, description = "Likely duplicate pairs", body = Vec<DuplicateCandidate>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn find_duplicates(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<DuplicateQuery>,
+) -> AppResult<Json<Vec<DuplicateCandidate>>> {
+    let duplicates = state
+        .contact_service
+        .find_duplicates(&user.workspace_id, query.limit.unwrap_or(50).min(500))
+        .await?;
+
+    Ok(Json(duplicates))
+}
+
+/// Duplicate suggestions queued by the background embedding job
+///
+/// GET /api/contacts/dedupe-suggestions?status=pending&limit=50
+#[utoipa::path(
+    get,
+    path = "/api/contacts/dedupe-suggestions",
+    tag = "contacts",
+    params(DedupeSuggestionQuery),
+    responses(
+        (status = 200, description = "Suggestions, most certain first", body = Vec<DedupeSuggestionResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn list_dedupe_suggestions(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<DedupeSuggestionQuery>,
+) -> AppResult<Json<Vec<DedupeSuggestionResponse>>> {
+    let suggestions = state.dedupe_service.suggestions(&user.workspace_id, query).await?;
+
+    Ok(Json(suggestions))
+}
+
+/// Accept a suggestion by merging the two contacts
+///
+/// POST /api/contacts/dedupe-suggestions/:id/accept
+/// Body (optional): { primary_id? }
+#[utoipa::path(
+    post,
+    path = "/api/contacts/dedupe-suggestions/{id}/accept",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Suggestion ID")),
+    request_body = AcceptDedupeRequest,
+    responses(
+        (status = 200, description = "Contacts merged", body = MergeContactsResponse),
+        (status = 400, description = "Suggestion already resolved, or primary_id not in the pair", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Suggestion or contact not found", body = ErrorResponse),
+        (status = 409, description = "Another reviewer resolved the suggestion first", body = ErrorResponse)
+    )
+)]
+pub async fn accept_dedupe_suggestion(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    req: Option<Json<AcceptDedupeRequest>>,
+) -> AppResult<Json<MergeContactsResponse>> {
+    let req = req.map(|Json(r)| r).unwrap_or_default();
+    let result = state.dedupe_service.accept(&user, &id, req).await?;
+
+    Ok(Json(result))
+}
+
+/// Dismiss a suggestion; the pair won't be suggested again
+///
+/// POST /api/contacts/dedupe-suggestions/:id/dismiss
+#[utoipa::path(
+    post,
+    path = "/api/contacts/dedupe-suggestions/{id}/dismiss",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Suggestion ID")),
+    responses(
+        (status = 200, description = "Suggestion dismissed", body = DedupeSuggestionResponse),
+        (status = 400, description = "Suggestion already resolved", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Suggestion not found", body = ErrorResponse),
+        (status = 409, description = "Another reviewer resolved the suggestion first", body = ErrorResponse)
+    )
+)]
+pub async fn dismiss_dedupe_suggestion(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<DedupeSuggestionResponse>> {
+    let suggestion = state.dedupe_service.dismiss(&user, &id).await?;
+
+    Ok(Json(suggestion))
+}
+
+/// Engagement level, trend, velocity and top interaction types
+///
+/// GET /api/contacts/:id/engagement
+#[utoipa::path(
+    get,
+    path = "/api/contacts/{id}/engagement",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Contact ID")),
+    responses(
+        (status = 200, description = "Engagement overview", body = ContactEngagementResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_contact_engagement(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<ContactEngagementResponse>> {
+    let engagement = state
+        .engagement_service
+        .overview(&user.workspace_id, &id)
+        .await?;
+
+    Ok(Json(engagement))
+}
+
+/// Score contribution per interaction type
+///
+/// GET /api/contacts/:id/engagement/breakdown
+#[utoipa::path(
+    get,
+    path = "/api/contacts/{id}/engagement/breakdown",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Contact ID")),
+    responses(
+        (status = 200, description = "Score contribution per interaction type", body = EngagementBreakdownResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_engagement_breakdown(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<EngagementBreakdownResponse>> {
+    let breakdown = state
+        .engagement_service
+        .breakdown(&user.workspace_id, &id)
+        .await?;
+
+    Ok(Json(breakdown))
+}
+
+/// Every status change of a contact, oldest first
+///
+/// GET /api/contacts/:id/status-history
+#[utoipa::path(
+    get,
+    path = "/api/contacts/{id}/status-history",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Contact ID")),
+    responses(
+        (status = 200, description = "Status changes, oldest first", body = Vec<StatusHistoryResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_status_history(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<Vec<StatusHistoryResponse>>> {
+    let history = state
+        .contact_service
+        .status_history(&user.workspace_id, &id)
+        .await?;
+
+    Ok(Json(history.into_iter().map(StatusHistoryResponse::from).collect()))
+}
+
+/// Summary of a contact's timeline with trend and next best action
+///
+/// GET /api/contacts/:id/summary
+///
+/// Written by the AI provider when one is configured, otherwise by the
+/// built-in rules. Cached until the workspace's timeline changes.
+#[utoipa::path(
+    get,
+    path = "/api/contacts/{id}/summary",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Contact ID")),
+    responses(
+        (status = 200, description = "Contact summary", body = ContactSummaryResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse),
+        (status = 429, description = "The workspace's hourly AI summaries are used up", body = ErrorResponse)
+    )
+)]
+pub async fn get_contact_summary(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<ContactSummaryResponse>> {
+    let summary = state.assistant_service.summary(&user.workspace_id, &id).await?;
+    Ok(Json(summary))
+}
+
+/// A printable report on a contact, to share before a meeting or a handoff
+///
+/// GET /api/contacts/:id/report?weeks=26&download=false
+///
+/// A standalone HTML page with the profile, engagement and a weekly
+/// activity chart, timeline highlights and notes; print it from the
+/// browser to get a PDF.
+#[utoipa::path(
+    get,
+    path = "/api/contacts/{id}/report",
+    tag = "contacts",
+    params(
+        ("id" = String, Path, description = "Contact ID"),
+        ContactReportQuery
+    ),
+    responses(
+        (status = 200, description = "Contact report", content_type = "text/html", body = String),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn get_contact_report(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Query(query): Query<ContactReportQuery>,
+) -> AppResult<Response> {
+    let html = state
+        .contact_report_service
+        .render(&user.workspace_id, &id, query.weeks)
+        .await?;
+
+    // The ID is the contact's, but keep the header safe whatever it holds
+    let file_id: String = id.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_').collect();
+    let disposition = format!(
+        "{}; filename=\"contact-{}-report.html\"",
+        if query.download { "attachment" } else { "inline" },
+        file_id
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/html; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        html,
+    )
+        .into_response())
+}
+
+/// Write a "what to know before this call" brief for a contact
+///
+/// POST /api/contacts/:id/prep-brief
+///
+/// Covers contact and company details, open tasks, recent email threads and
+/// recent activity. The brief is saved on the contact's timeline as a
+/// `prep_brief` entry, which doesn't count towards engagement.
+#[utoipa::path(
+    post,
+    path = "/api/contacts/{id}/prep-brief",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Contact ID")),
+    responses(
+        (status = 200, description = "Prep brief and the timeline entry it was saved as", body = PrepBriefResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse)
+    )
+)]
+pub async fn create_prep_brief(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<PrepBriefResponse>> {
+    let brief = state.assistant_service.prep_brief(&user.workspace_id, &id).await?;
+    Ok(Json(brief))
+}
+
+/// Recompute a contact's engagement score from its timeline now
+///
+/// POST /api/contacts/:id/recalculate-engagement
+#[utoipa::path(
+    post,
+    path = "/api/contacts/{id}/recalculate-engagement",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Contact ID")),
+    responses(
+        (status = 200, description = "Score recalculated", body = EngagementRecalculationResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse)
+    )
+)]
+pub async fn recalculate_engagement(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<EngagementRecalculationResponse>> {
+    let result = state
+        .engagement_service
+        .recalculate(&user.workspace_id, &id)
+        .await?;
+
+    Ok(Json(result))
+}
+
 // Helper function to convert API status to domain status
+/// Convert API query params to a repository query
+fn repo_query(query: ContactQuery) -> AppResult<RepoContactQuery> {
+    if let (Some(min), Some(max)) = (query.min_engagement, query.max_engagement) {
+        if min > max {
+            return Err(AppError::Validation(
+                "min_engagement must not be greater than max_engagement".into(),
+            ));
+        }
+    }
+    for (after, before, field) in [
+        (query.created_after, query.created_before, "created"),
+        (query.updated_after, query.updated_before, "updated"),
+    ] {
+        if let (Some(after), Some(before)) = (after, before) {
+            if after >= before {
+                return Err(AppError::Validation(format!("{field}_after must be before {field}_before")));
+            }
+        }
+    }
+
+    let mut repo_query = RepoContactQuery::new()
+        .with_limit(query.limit.unwrap_or(50))
+        .with_offset(query.offset.unwrap_or(0))
+        .with_sort(query.sort.unwrap_or_default(), query.order);
+    repo_query.search = query.search.filter(|s| !s.trim().is_empty());
+    repo_query.status = query.status.map(api_status_to_domain);
+    repo_query.tags_all = tag_filter(query.tags.iter().chain(&query.tags_all));
+    repo_query.tags_any = tag_filter(&query.tags_any);
+    repo_query.exclude_tags = tag_filter(&query.exclude_tags);
+    repo_query.company_id = query.company_id;
+    repo_query.country = query.country.map(|c| c.trim().to_ascii_uppercase()).filter(|c| !c.is_empty());
+    repo_query.region = query.region.filter(|r| !r.trim().is_empty());
+    repo_query.city = query.city.filter(|c| !c.trim().is_empty());
+    repo_query.timezone = query.timezone.filter(|t| !t.trim().is_empty());
+    repo_query.min_engagement = query.min_engagement;
+    repo_query.max_engagement = query.max_engagement;
+    repo_query.created_after = query.created_after;
+    repo_query.created_before = query.created_before;
+    repo_query.updated_after = query.updated_after;
+    repo_query.updated_before = query.updated_before;
+
+    let required: Vec<&String> = repo_query.tags_all.iter().chain(&repo_query.tags_any).flatten().collect();
+    if let Some(tag) = repo_query.exclude_tags.iter().flatten().find(|tag| required.contains(tag)) {
+        return Err(AppError::Validation(format!("Tag '{}' can't be both included and excluded", tag)));
+    }
+
+    Ok(repo_query)
+}
+
+/// Parse comma-separated tag lists into one normalized list; `None` when no tags are given
+fn tag_filter<'a>(raw: impl IntoIterator<Item = &'a String>) -> Option<Vec<String>> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in raw.into_iter().flat_map(|r| parse_tag_filter(r.as_str())) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    (!tags.is_empty()).then_some(tags)
+}
+
 fn api_status_to_domain(status: crate::models::ContactStatus) -> DomainStatus {
     match status {
         crate::models::ContactStatus::Lead => DomainStatus::Lead,
@@ -148,3 +909,69 @@ fn api_status_to_domain(status: crate::models::ContactStatus) -> DomainStatus {
         crate::models::ContactStatus::Other => DomainStatus::Other,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::filter_conditions;
+    use axum::http::Uri;
+
+    /// The repository query a `GET /api/contacts` request turns into
+    fn parse(query: &str) -> AppResult<RepoContactQuery> {
+        let uri: Uri = format!("/api/contacts?{}", query).parse().unwrap();
+        repo_query(Query::<ContactQuery>::try_from_uri(&uri).unwrap().0)
+    }
+
+    #[test]
+    fn test_every_filter_reaches_the_query() {
+        let query = parse(
+            "search=ada&status=customer&tags=VIP,%20beta&tags_any=press&exclude_tags=churned\
+             &company_id=acme&min_engagement=10&max_engagement=80&limit=20&offset=40",
+        )
+        .unwrap();
+
+        let (conditions, bindings) = filter_conditions(&query);
+        for condition in [
+            "status = $status",
+            "(first_name CONTAINS $search OR last_name CONTAINS $search OR email CONTAINS $search)",
+            "tags CONTAINSALL $tags_all",
+            "tags CONTAINSANY $tags_any",
+            "tags CONTAINSNONE $exclude_tags",
+            "company = type::thing('company', $company_id)",
+            "engagement_score >= $min_engagement",
+            "engagement_score <= $max_engagement",
+        ] {
+            assert!(conditions.contains(&condition), "missing {}", condition);
+        }
+
+        let bound = |name: &str| bindings.iter().find(|(n, _)| *n == name).map(|(_, v)| v.clone());
+        assert_eq!(bound("status"), Some(serde_json::json!("customer")));
+        assert_eq!(bound("tags_all"), Some(serde_json::json!(["vip", "beta"])));
+        assert_eq!(bound("tags_any"), Some(serde_json::json!(["press"])));
+        assert_eq!(bound("exclude_tags"), Some(serde_json::json!(["churned"])));
+        assert_eq!(bound("company_id"), Some(serde_json::json!("acme")));
+        assert_eq!(bound("min_engagement"), Some(serde_json::json!(10.0)));
+    }
+
+    #[test]
+    fn test_tags_and_tags_all_are_combined() {
+        let query = parse("tags=vip&tags_all=beta,VIP").unwrap();
+
+        assert_eq!(query.tags_all, Some(vec!["vip".to_string(), "beta".to_string()]));
+    }
+
+    #[test]
+    fn test_blank_filters_are_ignored() {
+        let query = parse("search=%20&tags=,%20,&region=").unwrap();
+
+        let (conditions, bindings) = filter_conditions(&query);
+        assert_eq!(conditions, vec!["workspace = $workspace", "deleted_at IS NONE"]);
+        assert!(bindings.is_empty());
+    }
+
+    #[test]
+    fn test_contradictory_filters_are_rejected() {
+        assert!(matches!(parse("min_engagement=50&max_engagement=10"), Err(AppError::Validation(_))));
+        assert!(matches!(parse("tags=vip&exclude_tags=VIP"), Err(AppError::Validation(_))));
+    }
+}
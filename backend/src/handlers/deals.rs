@@ -0,0 +1,158 @@
+//! Deal Handlers - sales pipeline CRUD and stage changes
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{CreateDealRequest, DealQuery, DealResponse, DealStageRequest, UpdateDealRequest};
+use crate::AppState;
+
+/// List deals, optionally filtered by stage, contact or company
+///
+/// GET /api/deals?stage=proposal&contact_id=...&company_id=...&limit=50&offset=0
+///
+/// `limit` defaults to 50 and is capped at 200.
+#[utoipa::path(
+    get,
+    path = "/api/deals",
+    tag = "deals",
+    params(DealQuery),
+    responses(
+        (status = 200, description = "List of deals", body = Vec<DealResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn list_deals(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<DealQuery>,
+) -> AppResult<Json<Vec<DealResponse>>> {
+    Ok(Json(state.deal_service.list(&user.workspace_id, &query).await?))
+}
+
+/// Create a deal
+///
+/// POST /api/deals
+/// Body: { name, value, stage?, expected_close_date?, contact_id?, company_id?, notes? }
+#[utoipa::path(
+    post,
+    path = "/api/deals",
+    tag = "deals",
+    request_body = CreateDealRequest,
+    responses(
+        (status = 200, description = "Deal created", body = DealResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn create_deal(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(req): Json<CreateDealRequest>,
+) -> AppResult<Json<DealResponse>> {
+    Ok(Json(state.deal_service.create(&user, req).await?))
+}
+
+/// Get a single deal
+///
+/// GET /api/deals/:id
+#[utoipa::path(
+    get,
+    path = "/api/deals/{id}",
+    tag = "deals",
+    params(("id" = String, Path, description = "Deal ID")),
+    responses(
+        (status = 200, description = "Deal", body = DealResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Deal not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_deal(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<DealResponse>> {
+    Ok(Json(state.deal_service.get(&user.workspace_id, &id).await?))
+}
+
+/// Update a deal's details
+///
+/// PATCH /api/deals/:id
+/// Body: { name?, value?, expected_close_date?, contact_id?, company_id?, notes? }
+#[utoipa::path(
+    patch,
+    path = "/api/deals/{id}",
+    tag = "deals",
+    params(("id" = String, Path, description = "Deal ID")),
+    request_body = UpdateDealRequest,
+    responses(
+        (status = 200, description = "Deal updated", body = DealResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Deal not found", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn update_deal(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Json(req): Json<UpdateDealRequest>,
+) -> AppResult<Json<DealResponse>> {
+    Ok(Json(state.deal_service.update(&user, &id, req).await?))
+}
+
+/// Move a deal to another pipeline stage
+///
+/// POST /api/deals/:id/stage
+/// Body: { stage: "proposal" | "closed_won" | ..., win_loss_reason? }
+#[utoipa::path(
+    post,
+    path = "/api/deals/{id}/stage",
+    tag = "deals",
+    params(("id" = String, Path, description = "Deal ID")),
+    request_body = DealStageRequest,
+    responses(
+        (status = 200, description = "Deal moved to the new stage", body = DealResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Deal not found", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn update_deal_stage(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Json(req): Json<DealStageRequest>,
+) -> AppResult<Json<DealResponse>> {
+    Ok(Json(state.deal_service.change_stage(&user, &id, req).await?))
+}
+
+/// Delete a deal
+///
+/// DELETE /api/deals/:id
+#[utoipa::path(
+    delete,
+    path = "/api/deals/{id}",
+    tag = "deals",
+    params(("id" = String, Path, description = "Deal ID")),
+    responses(
+        (status = 200, description = "Deal deleted", body = serde_json::Value),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Deal not found", body = ErrorResponse)
+    )
+)]
+pub async fn delete_deal(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    state.deal_service.delete(&user, &id).await?;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
@@ -0,0 +1,73 @@
+//! Digest Handlers - the current user's weekly digest email
+
+use axum::{extract::State, Json};
+
+use crate::domain::DigestPreferences;
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{DigestPreferencesResponse, DigestPreview};
+use crate::AppState;
+
+/// The current user's digest preferences, or the defaults if never saved
+///
+/// GET /api/digest/preferences
+#[utoipa::path(
+    get,
+    path = "/api/digest/preferences",
+    tag = "digest",
+    responses(
+        (status = 200, description = "Digest preferences and schedule", body = DigestPreferencesResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn get_preferences(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> AppResult<Json<DigestPreferencesResponse>> {
+    let preferences = state.digest_service.preferences(&user).await?;
+    Ok(Json(preferences))
+}
+
+/// Replace the current user's digest preferences
+///
+/// PUT /api/digest/preferences
+/// Body: { enabled, weekday, hour, timezone, new_leads, engagement_movers, stale_leads, upcoming_events }
+#[utoipa::path(
+    put,
+    path = "/api/digest/preferences",
+    tag = "digest",
+    request_body = DigestPreferences,
+    responses(
+        (status = 200, description = "Saved preferences and schedule", body = DigestPreferencesResponse),
+        (status = 400, description = "Invalid hour or timezone", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn update_preferences(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(preferences): Json<DigestPreferences>,
+) -> AppResult<Json<DigestPreferencesResponse>> {
+    let preferences = state.digest_service.update_preferences(&user, preferences).await?;
+    Ok(Json(preferences))
+}
+
+/// The digest the current user would get if it went out now
+///
+/// GET /api/digest/preview
+#[utoipa::path(
+    get,
+    path = "/api/digest/preview",
+    tag = "digest",
+    responses(
+        (status = 200, description = "Rendered digest email", body = DigestPreview),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn preview(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> AppResult<Json<DigestPreview>> {
+    let preview = state.digest_service.preview(&user).await?;
+    Ok(Json(preview))
+}
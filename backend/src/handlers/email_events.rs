@@ -0,0 +1,46 @@
+//! Email Event Handlers - bounce and complaint callbacks from the email provider
+//!
+//! These routes are unauthenticated; the shared token in the path is the
+//! only credential, and a wrong one gets the same 404 as disabled callbacks.
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+use crate::domain::EmailProvider;
+use crate::error::AppResult;
+use crate::models::EmailEventsResponse;
+use crate::AppState;
+
+/// Receive bounces and complaints from SES (through SNS) or SendGrid's event webhook
+///
+/// POST /api/email-events/:provider/:token
+/// Body: an SNS message or SES notification for `ses`, an array of events for `sendgrid`
+///
+/// Hard bounces and complaints suppress the contacts with the address; an
+/// SNS subscription confirmation is confirmed.
+#[utoipa::path(
+    post,
+    path = "/api/email-events/{provider}/{token}",
+    tag = "email-events",
+    params(
+        ("provider" = EmailProvider, Path, description = "Provider sending the callback: ses or sendgrid"),
+        ("token" = String, Path, description = "Callback token from the secrets manager")
+    ),
+    request_body = serde_json::Value,
+    security(()),
+    responses(
+        (status = 200, description = "Reports recorded", body = EmailEventsResponse),
+        (status = 404, description = "Callbacks disabled, or wrong token", body = ErrorResponse),
+        (status = 422, description = "Payload not in the provider's format", body = ErrorResponse)
+    )
+)]
+pub async fn receive(
+    State(state): State<AppState>,
+    Path((provider, token)): Path<(EmailProvider, String)>,
+    Json(payload): Json<serde_json::Value>,
+) -> AppResult<Json<EmailEventsResponse>> {
+    let result = state.deliverability_service.ingest(provider, &token, payload).await?;
+    Ok(Json(result))
+}
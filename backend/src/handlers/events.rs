@@ -5,18 +5,34 @@ use axum::{
 use chrono::Utc;
 use surrealdb::sql::Thing;
 
+use crate::db::workspace_thing;
+use crate::domain::{AuditEntity, RegistrationRules};
 use crate::error::{AppError, AppResult};
+use crate::middleware::CurrentUser;
 use crate::models::{
-    CreateEventRequest, Event, EventResponse, InviteRequest, Rsvp, RsvpRequest, RsvpResponse,
-    RsvpStatus, TimelineEntry, TimelineEntryType,
+    BulkCheckinRequest, BulkCheckinResponse, CheckinRequest, CheckinTokenResponse, Contact, CreateEventRequest, Event,
+    EventResponse, InviteRequest, RsvpRequest, RsvpResponse,
 };
 use crate::AppState;
 
-pub async fn list_events(State(state): State<AppState>) -> AppResult<Json<Vec<EventResponse>>> {
+#[utoipa::path(
+    get,
+    path = "/api/events",
+    tag = "events",
+    responses(
+        (status = 200, description = "Events in the workspace", body = Vec<EventResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn list_events(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> AppResult<Json<Vec<EventResponse>>> {
     let events: Vec<Event> = state
         .db
         .client
-        .query("SELECT * FROM event ORDER BY start_time ASC")
+        .query("SELECT * FROM event WHERE workspace = $workspace ORDER BY start_time ASC")
+        .bind(("workspace", workspace_thing(&user.workspace_id)))
         .await?
         .take(0)?;
 
@@ -24,10 +40,33 @@ pub async fn list_events(State(state): State<AppState>) -> AppResult<Json<Vec<Ev
     Ok(Json(responses))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/events",
+    tag = "events",
+    request_body = CreateEventRequest,
+    responses(
+        (status = 200, description = "Event created", body = EventResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 422, description = "Invalid schedule, capacity or reminders", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
 pub async fn create_event(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Json(req): Json<CreateEventRequest>,
 ) -> AppResult<Json<EventResponse>> {
+    RegistrationRules {
+        max_attendees: req.max_attendees,
+        registration_deadline: req.registration_deadline,
+        start_time: req.start_time,
+        end_time: req.end_time,
+    }
+    .validate()?;
+    let reminders = req.reminders.unwrap_or_default();
+    reminders.validate()?;
+
     let campaign = req.campaign_id.map(|id| Thing::from(("campaign", id.as_str())));
 
     let events: Vec<Event> = state
@@ -36,6 +75,7 @@ pub async fn create_event(
         .create("event")
         .content(Event {
             id: None,
+            workspace: workspace_thing(&user.workspace_id),
             campaign,
             name: req.name,
             event_type: req.event_type,
@@ -43,151 +83,182 @@ pub async fn create_event(
             start_time: req.start_time,
             end_time: req.end_time,
             location: req.location,
+            max_attendees: req.max_attendees,
+            registration_deadline: req.registration_deadline,
+            reminders,
             created_at: Utc::now(),
         })
         .await?;
 
     let event = events.into_iter().next().ok_or_else(|| AppError::Internal("Failed to create event".into()))?;
-    Ok(Json(event.into()))
+    let response = EventResponse::from(event.clone());
+    state
+        .audit_service
+        .record_create(&user, AuditEntity::Event, &response.id, &event)
+        .await;
+
+    Ok(Json(response))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/events/{id}",
+    tag = "events",
+    params(("id" = String, Path, description = "Event ID")),
+    responses(
+        (status = 200, description = "Event", body = EventResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Event not found", body = ErrorResponse)
+    )
+)]
 pub async fn get_event(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Path(id): Path<String>,
 ) -> AppResult<Json<EventResponse>> {
     let event: Option<Event> = state
         .db
-        .client
-        .select(("event", id.as_str()))
+        .select_scoped("event", &id, &user.workspace_id)
         .await?;
 
     let event = event.ok_or_else(|| AppError::NotFound("Event not found".into()))?;
     Ok(Json(event.into()))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/events/{id}/invite",
+    tag = "events",
+    params(("id" = String, Path, description = "Event ID")),
+    request_body = InviteRequest,
+    responses(
+        (status = 200, description = "New invitations; contacts that already have an RSVP are left as they are", body = Vec<RsvpResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Event or contact not found; nobody is invited", body = ErrorResponse),
+        (status = 409, description = "Another request gave one of the contacts an RSVP first", body = ErrorResponse),
+        (status = 422, description = "No contacts, or more than 1000", body = ErrorResponse)
+    )
+)]
 pub async fn invite_to_event(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Path(event_id): Path<String>,
     Json(req): Json<InviteRequest>,
 ) -> AppResult<Json<Vec<RsvpResponse>>> {
-    let event_thing = Thing::from(("event", event_id.as_str()));
-    let mut rsvps = Vec::new();
-
-    for contact_id in req.contact_ids {
-        let contact_thing = Thing::from(("contact", contact_id.as_str()));
-
-        // Create RSVP with invited status
-        let rsvp_results: Vec<Rsvp> = state
-            .db
-            .client
-            .create("rsvp")
-            .content(Rsvp {
-                id: None,
-                event: event_thing.clone(),
-                contact: contact_thing.clone(),
-                status: RsvpStatus::Invited,
-                timestamp: Utc::now(),
-            })
-            .await?;
-
-        if let Some(r) = rsvp_results.into_iter().next() {
-            rsvps.push(r.into());
-        }
-
-        // Create timeline entry
-        let _: Vec<TimelineEntry> = state
-            .db
-            .client
-            .create("timeline_entry")
-            .content(TimelineEntry {
-                id: None,
-                contact: contact_thing,
-                company: None,
-                entry_type: TimelineEntryType::EventInvite,
-                content: format!("Invited to event {}", event_id),
-                metadata: serde_json::json!({ "event_id": event_id }),
-                timestamp: Utc::now(),
-            })
-            .await?;
-    }
-
-    Ok(Json(rsvps))
+    let rsvps = state
+        .event_service
+        .invite(&user.workspace_id, &event_id, req.contact_ids)
+        .await?;
+    Ok(Json(rsvps.into_iter().map(RsvpResponse::from).collect()))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/events/{id}/rsvp",
+    tag = "events",
+    params(("id" = String, Path, description = "Event ID")),
+    request_body = RsvpRequest,
+    responses(
+        (status = 200, description = "RSVP recorded; `registered` comes back `waitlisted` when the event is full", body = RsvpResponse),
+        (status = 400, description = "Registration closed or status change not allowed", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Event not found", body = ErrorResponse)
+    )
+)]
 pub async fn rsvp_event(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Path(event_id): Path<String>,
     Json(req): Json<RsvpRequest>,
 ) -> AppResult<Json<RsvpResponse>> {
-    let event_thing = Thing::from(("event", event_id.as_str()));
-    let contact_thing = Thing::from(("contact", req.contact_id.as_str()));
+    ensure_contact_exists(&state, &user.workspace_id, &req.contact_id).await?;
 
-    // Find existing RSVP
-    let existing: Vec<Rsvp> = state
-        .db
-        .client
-        .query("SELECT * FROM rsvp WHERE event = $event AND contact = $contact LIMIT 1")
-        .bind(("event", event_thing.clone()))
-        .bind(("contact", contact_thing.clone()))
-        .await?
-        .take(0)?;
+    let rsvp = state
+        .event_service
+        .rsvp(&user.workspace_id, &event_id, &req.contact_id, req.status)
+        .await?;
 
-    let rsvp = if let Some(existing_rsvp) = existing.first() {
-        // Update existing RSVP
-        let updated: Option<Rsvp> = state
-            .db
-            .client
-            .update(existing_rsvp.id.clone().unwrap())
-            .merge(serde_json::json!({
-                "status": req.status,
-                "timestamp": Utc::now()
-            }))
-            .await?;
-
-        updated.ok_or_else(|| AppError::Internal("Failed to update RSVP".into()))?
-    } else {
-        // Create new RSVP
-        let new_rsvps: Vec<Rsvp> = state
-            .db
-            .client
-            .create("rsvp")
-            .content(Rsvp {
-                id: None,
-                event: event_thing,
-                contact: contact_thing.clone(),
-                status: req.status.clone(),
-                timestamp: Utc::now(),
-            })
-            .await?;
-
-        new_rsvps.into_iter().next().ok_or_else(|| AppError::Internal("Failed to create RSVP".into()))?
-    };
-
-    // Create timeline entry if registered or attended
-    if matches!(req.status, RsvpStatus::Registered | RsvpStatus::Attended) {
-        let entry_type = match req.status {
-            RsvpStatus::Attended => TimelineEntryType::EventAttend,
-            _ => TimelineEntryType::EventInvite,
-        };
-
-        let _: Vec<TimelineEntry> = state
-            .db
-            .client
-            .create("timeline_entry")
-            .content(TimelineEntry {
-                id: None,
-                contact: contact_thing,
-                company: None,
-                entry_type,
-                content: format!("RSVP status updated for event {}", event_id),
-                metadata: serde_json::json!({
-                    "event_id": event_id,
-                    "status": req.status
-                }),
-                timestamp: Utc::now(),
-            })
-            .await?;
-    }
+    Ok(Json(rsvp.into()))
+}
 
+#[utoipa::path(
+    post,
+    path = "/api/events/{id}/checkin",
+    tag = "events",
+    params(("id" = String, Path, description = "Event ID")),
+    request_body = CheckinRequest,
+    responses(
+        (status = 200, description = "Contact checked in; already checked-in contacts come back unchanged", body = RsvpResponse),
+        (status = 400, description = "Invalid check-in code or check-in not open yet", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Event or contact not found", body = ErrorResponse)
+    )
+)]
+pub async fn check_in(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(event_id): Path<String>,
+    Json(req): Json<CheckinRequest>,
+) -> AppResult<Json<RsvpResponse>> {
+    let rsvp = state.event_service.check_in(&user.workspace_id, &event_id, req).await?;
     Ok(Json(rsvp.into()))
 }
+
+#[utoipa::path(
+    post,
+    path = "/api/events/{id}/checkin/bulk",
+    tag = "events",
+    params(("id" = String, Path, description = "Event ID")),
+    request_body = BulkCheckinRequest,
+    responses(
+        (status = 200, description = "Per-contact results, in request order", body = BulkCheckinResponse),
+        (status = 400, description = "Check-in not open yet", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Event not found", body = ErrorResponse)
+    )
+)]
+pub async fn bulk_check_in(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(event_id): Path<String>,
+    Json(req): Json<BulkCheckinRequest>,
+) -> AppResult<Json<BulkCheckinResponse>> {
+    let response = state
+        .event_service
+        .check_in_many(&user.workspace_id, &event_id, req.checkins)
+        .await?;
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/events/{id}/checkin/token/{contact_id}",
+    tag = "events",
+    params(
+        ("id" = String, Path, description = "Event ID"),
+        ("contact_id" = String, Path, description = "Contact ID")
+    ),
+    responses(
+        (status = 200, description = "Token for the contact's check-in QR code", body = CheckinTokenResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Event or contact not found", body = ErrorResponse)
+    )
+)]
+pub async fn checkin_token(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path((event_id, contact_id)): Path<(String, String)>,
+) -> AppResult<Json<CheckinTokenResponse>> {
+    let token = state
+        .event_service
+        .checkin_token(&user.workspace_id, &event_id, &contact_id)
+        .await?;
+    Ok(Json(token))
+}
+
+async fn ensure_contact_exists(state: &AppState, workspace_id: &str, contact_id: &str) -> AppResult<()> {
+    let contact: Option<Contact> = state.db.select_scoped("contact", contact_id, workspace_id).await?;
+    contact
+        .map(|_| ())
+        .ok_or_else(|| AppError::NotFound(format!("Contact '{}' not found", contact_id)))
+}
@@ -0,0 +1,46 @@
+//! Activity Feed Handlers - live workspace activity over server-sent events
+
+use std::convert::Infallible;
+
+use axum::{
+    extract::State,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::{Stream, StreamExt};
+
+use crate::middleware::CurrentUser;
+use crate::AppState;
+
+/// Stream new timeline entries, contacts, contact changes and RSVP changes as they happen
+///
+/// GET /api/feed/stream
+///
+/// Each SSE event is named after its kind (`timeline_entry_created`,
+/// `contact_created`, `contact_changed`, `rsvp_changed`) and carries
+/// `{ type, data }` as JSON. After a `resync` event, or after reconnecting,
+/// clients should reload over the REST API.
+#[utoipa::path(
+    get,
+    path = "/api/feed/stream",
+    tag = "feed",
+    responses(
+        (status = 200, description = "Server-sent events, one per workspace activity", content_type = "text/event-stream"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn stream_feed(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    // Ends on shutdown, so open streams don't hold up draining connections
+    let stream = state
+        .feed_service
+        .subscribe(&user.workspace_id)
+        .take_until(state.shutdown.triggered())
+        .map(|event| {
+            let data = serde_json::to_string(&event).unwrap_or_default();
+            Ok(Event::default().event(event.name()).data(data))
+        });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
@@ -0,0 +1,80 @@
+//! GDPR Handlers - data subject export and erasure
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{GdprErasureResponse, GdprExport};
+use crate::AppState;
+
+/// Export everything stored about a contact
+///
+/// POST /api/contacts/:id/gdpr-export
+#[utoipa::path(
+    post,
+    path = "/api/contacts/{id}/gdpr-export",
+    tag = "gdpr",
+    params(("id" = String, Path, description = "Contact ID")),
+    responses(
+        (status = 200, description = "The contact's data", body = GdprExport),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse)
+    )
+)]
+pub async fn export_contact_data(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<GdprExport>> {
+    let export = state.gdpr_service.export(&user.workspace_id, &id).await?;
+    Ok(Json(export))
+}
+
+/// Erase a contact's personal data
+///
+/// POST /api/contacts/:id/gdpr-erase
+///
+/// The contact is anonymized rather than deleted, so its links stay intact.
+/// This cannot be undone.
+#[utoipa::path(
+    post,
+    path = "/api/contacts/{id}/gdpr-erase",
+    tag = "gdpr",
+    params(("id" = String, Path, description = "Contact ID")),
+    responses(
+        (status = 200, description = "Contact erased", body = GdprErasureResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse)
+    )
+)]
+pub async fn erase_contact_data(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<GdprErasureResponse>> {
+    let erasure = state.gdpr_service.erase(&user, &id).await?;
+    Ok(Json(erasure))
+}
+
+/// List past erasures
+///
+/// GET /api/gdpr/erasures
+#[utoipa::path(
+    get,
+    path = "/api/gdpr/erasures",
+    tag = "gdpr",
+    responses(
+        (status = 200, description = "Erasure log, newest first", body = Vec<GdprErasureResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn list_erasures(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> AppResult<Json<Vec<GdprErasureResponse>>> {
+    let erasures = state.gdpr_service.list_erasures(&user.workspace_id).await?;
+    Ok(Json(erasures))
+}
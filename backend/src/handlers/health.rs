@@ -1,24 +1,48 @@
-use axum::Json;
+use axum::{extract::State, http::StatusCode, Json};
 use serde::Serialize;
 use utoipa::ToSchema;
 
+use crate::AppState;
+
 #[derive(Serialize, ToSchema)]
 pub struct HealthResponse {
     pub status: String,
     pub version: String,
+    /// "ok", or "unavailable" when the database couldn't be queried; the cause is logged
+    pub database: String,
+    /// The running configuration, with secrets and passwords redacted
+    pub config: serde_json::Value,
 }
 
 /// Health check endpoint
+///
+/// Runs a query against the database, so a server that can't reach it
+/// reports unhealthy.
 #[utoipa::path(
     get,
     path = "/health",
+    tag = "health",
     responses(
-        (status = 200, description = "Health status", body = HealthResponse)
-    )
+        (status = 200, description = "Health status", body = HealthResponse),
+        (status = 503, description = "The database can't be queried", body = HealthResponse)
+    ),
+    security(())
 )]
-pub async fn health_check() -> Json<HealthResponse> {
-    Json(HealthResponse {
-        status: "healthy".to_string(),
+pub async fn health_check(State(state): State<AppState>) -> (StatusCode, Json<HealthResponse>) {
+    let (status, database) = match state.db.ping().await {
+        Ok(()) => (StatusCode::OK, "ok".to_string()),
+        Err(e) => {
+            tracing::warn!("Health check failed to query the database: {}", e);
+            (StatusCode::SERVICE_UNAVAILABLE, "unavailable".to_string())
+        }
+    };
+
+    let response = HealthResponse {
+        status: if status.is_success() { "healthy" } else { "unhealthy" }.to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
-    })
+        database,
+        config: state.config.redacted(),
+    };
+
+    (status, Json(response))
 }
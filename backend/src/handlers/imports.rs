@@ -0,0 +1,108 @@
+//! Import Handlers - migrate contacts, companies, deals and notes from another CRM
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{HubspotImportRequest, ImportJobResponse, PipedriveImportRequest};
+use crate::AppState;
+
+/// Start importing from HubSpot
+///
+/// POST /api/import/hubspot
+/// Body: { api_key?, export?, mode?: "create" | "upsert" }
+///
+/// The import runs in the background; poll the returned job for progress.
+#[utoipa::path(
+    post,
+    path = "/api/import/hubspot",
+    tag = "import",
+    request_body = HubspotImportRequest,
+    responses(
+        (status = 200, description = "Import started", body = ImportJobResponse),
+        (status = 400, description = "Neither or both of api_key and export, or an unreadable export", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 409, description = "A HubSpot import is already running", body = ErrorResponse)
+    )
+)]
+pub async fn import_hubspot(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(req): Json<HubspotImportRequest>,
+) -> AppResult<Json<ImportJobResponse>> {
+    let job = state.import_service.start_hubspot(&user, req).await?;
+    Ok(Json(job))
+}
+
+/// Start importing from Pipedrive
+///
+/// POST /api/import/pipedrive
+/// Body: { api_token?, export?, mode?: "create" | "upsert" }
+///
+/// The import runs in the background; poll the returned job for progress.
+#[utoipa::path(
+    post,
+    path = "/api/import/pipedrive",
+    tag = "import",
+    request_body = PipedriveImportRequest,
+    responses(
+        (status = 200, description = "Import started", body = ImportJobResponse),
+        (status = 400, description = "Neither or both of api_token and export, or an unreadable export", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 409, description = "A Pipedrive import is already running", body = ErrorResponse)
+    )
+)]
+pub async fn import_pipedrive(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(req): Json<PipedriveImportRequest>,
+) -> AppResult<Json<ImportJobResponse>> {
+    let job = state.import_service.start_pipedrive(&user, req).await?;
+    Ok(Json(job))
+}
+
+/// List recent import jobs
+///
+/// GET /api/import/jobs
+#[utoipa::path(
+    get,
+    path = "/api/import/jobs",
+    tag = "import",
+    responses(
+        (status = 200, description = "The last 50 import jobs, newest first", body = Vec<ImportJobResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn list_import_jobs(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> AppResult<Json<Vec<ImportJobResponse>>> {
+    let jobs = state.import_service.list(&user.workspace_id).await?;
+    Ok(Json(jobs))
+}
+
+/// Get an import job and its progress
+///
+/// GET /api/import/jobs/:id
+#[utoipa::path(
+    get,
+    path = "/api/import/jobs/{id}",
+    tag = "import",
+    params(("id" = String, Path, description = "Import job ID")),
+    responses(
+        (status = 200, description = "Import job", body = ImportJobResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Import job not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_import_job(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<ImportJobResponse>> {
+    let job = state.import_service.get(&user.workspace_id, &id).await?;
+    Ok(Json(job))
+}
@@ -0,0 +1,136 @@
+//! Inbound Handlers - let external tools push contacts and activity in over HTTP
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{
+    CreateInboundSourceRequest, InboundIngestResponse, InboundSourceResponse, UpdateInboundSourceRequest,
+};
+use crate::AppState;
+
+/// List inbound sources
+///
+/// GET /api/inbound-sources
+#[utoipa::path(
+    get,
+    path = "/api/inbound-sources",
+    tag = "inbound",
+    responses(
+        (status = 200, description = "Inbound sources", body = Vec<InboundSourceResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn list_inbound_sources(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> AppResult<Json<Vec<InboundSourceResponse>>> {
+    let sources = state.inbound_service.list(&user.workspace_id).await?;
+    Ok(Json(sources))
+}
+
+/// Create an inbound source
+///
+/// POST /api/inbound-sources
+/// Body: { name, mapping: { email, first_name?, last_name?, phone?, custom_fields?, tags?, summary? } }
+///
+/// Mapping values are dot-separated paths into the payloads the tool sends.
+#[utoipa::path(
+    post,
+    path = "/api/inbound-sources",
+    tag = "inbound",
+    request_body = CreateInboundSourceRequest,
+    responses(
+        (status = 200, description = "Source created, with the token of its ingestion URL", body = InboundSourceResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn create_inbound_source(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(req): Json<CreateInboundSourceRequest>,
+) -> AppResult<Json<InboundSourceResponse>> {
+    let source = state.inbound_service.create(&user.workspace_id, req).await?;
+    Ok(Json(source))
+}
+
+/// Update an inbound source's name, mapping or whether it accepts payloads
+///
+/// PATCH /api/inbound-sources/:id
+#[utoipa::path(
+    patch,
+    path = "/api/inbound-sources/{id}",
+    tag = "inbound",
+    params(("id" = String, Path, description = "Inbound source ID")),
+    request_body = UpdateInboundSourceRequest,
+    responses(
+        (status = 200, description = "Source updated", body = InboundSourceResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Inbound source not found", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn update_inbound_source(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Json(req): Json<UpdateInboundSourceRequest>,
+) -> AppResult<Json<InboundSourceResponse>> {
+    let source = state.inbound_service.update(&user.workspace_id, &id, req).await?;
+    Ok(Json(source))
+}
+
+/// Delete an inbound source
+///
+/// DELETE /api/inbound-sources/:id
+#[utoipa::path(
+    delete,
+    path = "/api/inbound-sources/{id}",
+    tag = "inbound",
+    params(("id" = String, Path, description = "Inbound source ID")),
+    responses(
+        (status = 200, description = "Source deleted", body = serde_json::Value),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Inbound source not found", body = ErrorResponse)
+    )
+)]
+pub async fn delete_inbound_source(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    state.inbound_service.delete(&user.workspace_id, &id).await?;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// Ingest a payload from an external tool (public; the token is the credential)
+///
+/// POST /api/inbound/:source_token
+/// Body: any JSON object
+#[utoipa::path(
+    post,
+    path = "/api/inbound/{source_token}",
+    tag = "inbound",
+    params(("source_token" = String, Path, description = "Token of the inbound source")),
+    request_body = serde_json::Value,
+    security(()),
+    responses(
+        (status = 200, description = "Contact created or updated, with a timeline entry", body = InboundIngestResponse),
+        (status = 404, description = "Unknown source", body = ErrorResponse),
+        (status = 409, description = "Source disabled, or the contact is in the trash", body = ErrorResponse),
+        (status = 422, description = "No valid email where the mapping says", body = ErrorResponse)
+    )
+)]
+pub async fn ingest(
+    State(state): State<AppState>,
+    Path(source_token): Path<String>,
+    Json(payload): Json<serde_json::Value>,
+) -> AppResult<Json<InboundIngestResponse>> {
+    let result = state.inbound_service.ingest(&source_token, payload).await?;
+    Ok(Json(result))
+}
@@ -0,0 +1,267 @@
+//! Integration Handlers - connect mailboxes whose inbound email lands on contact timelines,
+//! and the social accounts campaign posts are published with
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{
+    ConnectSocialAccountRequest, CreateGmailIntegrationRequest, CreateImapIntegrationRequest,
+    GmailAuthorizationResponse, InboxSyncResponse, IntegrationResponse, OAuthCallbackQuery, SocialAccountResponse,
+    SocialPlatform, UpdateIntegrationRequest,
+};
+use crate::AppState;
+
+/// List connected mailboxes
+///
+/// GET /api/integrations
+#[utoipa::path(
+    get,
+    path = "/api/integrations",
+    tag = "integrations",
+    responses(
+        (status = 200, description = "Connected mailboxes", body = Vec<IntegrationResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn list_integrations(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> AppResult<Json<Vec<IntegrationResponse>>> {
+    let integrations = state.inbox_service.list(&user.workspace_id).await?;
+    Ok(Json(integrations))
+}
+
+/// Connect an IMAP mailbox
+///
+/// POST /api/integrations/imap
+/// Body: { host, port?, username, password, mailbox?, create_unknown_contacts? }
+///
+/// The login is tried against the server before it is saved.
+#[utoipa::path(
+    post,
+    path = "/api/integrations/imap",
+    tag = "integrations",
+    request_body = CreateImapIntegrationRequest,
+    responses(
+        (status = 200, description = "Mailbox connected", body = IntegrationResponse),
+        (status = 400, description = "The server refused the login", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn create_imap_integration(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(req): Json<CreateImapIntegrationRequest>,
+) -> AppResult<Json<IntegrationResponse>> {
+    let integration = state.inbox_service.create_imap(&user.workspace_id, req).await?;
+    Ok(Json(integration))
+}
+
+/// Start connecting a Gmail account
+///
+/// POST /api/integrations/gmail
+/// Body: { create_unknown_contacts? }
+///
+/// Send the user to the returned URL; Google redirects back to the callback.
+#[utoipa::path(
+    post,
+    path = "/api/integrations/gmail",
+    tag = "integrations",
+    request_body = CreateGmailIntegrationRequest,
+    responses(
+        (status = 200, description = "Pending integration and Google's consent URL", body = GmailAuthorizationResponse),
+        (status = 400, description = "Gmail sync is not configured", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn create_gmail_integration(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(req): Json<CreateGmailIntegrationRequest>,
+) -> AppResult<Json<GmailAuthorizationResponse>> {
+    let authorization = state.inbox_service.start_gmail(&user.workspace_id, req).await?;
+    Ok(Json(authorization))
+}
+
+/// OAuth callback for Gmail (public; Google redirects the browser here)
+///
+/// GET /api/integrations/gmail/callback?state=...&code=...
+#[utoipa::path(
+    get,
+    path = "/api/integrations/gmail/callback",
+    tag = "integrations",
+    params(OAuthCallbackQuery),
+    security(()),
+    responses(
+        (status = 200, description = "Gmail account connected", body = IntegrationResponse),
+        (status = 400, description = "Access was not granted", body = ErrorResponse),
+        (status = 404, description = "No pending connection for this state", body = ErrorResponse),
+        (status = 502, description = "Google could not be reached", body = ErrorResponse)
+    )
+)]
+pub async fn gmail_callback(
+    State(state): State<AppState>,
+    Query(query): Query<OAuthCallbackQuery>,
+) -> AppResult<Json<IntegrationResponse>> {
+    let integration = state.inbox_service.complete_gmail(query).await?;
+    Ok(Json(integration))
+}
+
+/// Update a connected mailbox
+///
+/// PATCH /api/integrations/:id
+/// Body: { create_unknown_contacts? }
+#[utoipa::path(
+    patch,
+    path = "/api/integrations/{id}",
+    tag = "integrations",
+    params(("id" = String, Path, description = "Integration ID")),
+    request_body = UpdateIntegrationRequest,
+    responses(
+        (status = 200, description = "Integration updated", body = IntegrationResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Integration not found", body = ErrorResponse)
+    )
+)]
+pub async fn update_integration(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Json(req): Json<UpdateIntegrationRequest>,
+) -> AppResult<Json<IntegrationResponse>> {
+    let integration = state.inbox_service.update(&user.workspace_id, &id, req).await?;
+    Ok(Json(integration))
+}
+
+/// Disconnect a mailbox
+///
+/// DELETE /api/integrations/:id
+#[utoipa::path(
+    delete,
+    path = "/api/integrations/{id}",
+    tag = "integrations",
+    params(("id" = String, Path, description = "Integration ID")),
+    responses(
+        (status = 200, description = "Integration deleted", body = serde_json::Value),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Integration not found", body = ErrorResponse)
+    )
+)]
+pub async fn delete_integration(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    state.inbox_service.delete(&user.workspace_id, &id).await?;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// Sync a mailbox now instead of waiting for the background sync
+///
+/// POST /api/integrations/:id/sync
+#[utoipa::path(
+    post,
+    path = "/api/integrations/{id}/sync",
+    tag = "integrations",
+    params(("id" = String, Path, description = "Integration ID")),
+    responses(
+        (status = 200, description = "What the sync recorded", body = InboxSyncResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Integration not found", body = ErrorResponse),
+        (status = 409, description = "The mailbox has not finished connecting", body = ErrorResponse),
+        (status = 502, description = "The mail server could not be read", body = ErrorResponse)
+    )
+)]
+pub async fn sync_integration(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<InboxSyncResponse>> {
+    let result = state.inbox_service.sync(&user.workspace_id, &id).await?;
+    Ok(Json(result))
+}
+
+/// List the social accounts posts are published with
+///
+/// GET /api/integrations/social
+#[utoipa::path(
+    get,
+    path = "/api/integrations/social",
+    tag = "integrations",
+    responses(
+        (status = 200, description = "Connected social accounts", body = Vec<SocialAccountResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn list_social_accounts(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> AppResult<Json<Vec<SocialAccountResponse>>> {
+    let accounts = state.social_service.list_accounts(&user.workspace_id).await?;
+    Ok(Json(accounts))
+}
+
+/// Connect the workspace's account for a social platform
+///
+/// PUT /api/integrations/social/:platform
+/// Body: { access_token, author_urn? }
+///
+/// Replaces the platform's earlier account, if any.
+#[utoipa::path(
+    put,
+    path = "/api/integrations/social/{platform}",
+    tag = "integrations",
+    params(("platform" = SocialPlatform, Path, description = "Platform to publish to")),
+    request_body = ConnectSocialAccountRequest,
+    responses(
+        (status = 200, description = "Account connected", body = SocialAccountResponse),
+        (status = 400, description = "Publishing to the platform is not configured", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn connect_social_account(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(platform): Path<SocialPlatform>,
+    Json(req): Json<ConnectSocialAccountRequest>,
+) -> AppResult<Json<SocialAccountResponse>> {
+    let account = state
+        .social_service
+        .connect_account(&user.workspace_id, platform, req)
+        .await?;
+    Ok(Json(account))
+}
+
+/// Disconnect a social account
+///
+/// DELETE /api/integrations/social/:platform
+#[utoipa::path(
+    delete,
+    path = "/api/integrations/social/{platform}",
+    tag = "integrations",
+    params(("platform" = SocialPlatform, Path, description = "Platform to disconnect")),
+    responses(
+        (status = 200, description = "Account disconnected", body = serde_json::Value),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "No account is connected", body = ErrorResponse)
+    )
+)]
+pub async fn disconnect_social_account(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(platform): Path<SocialPlatform>,
+) -> AppResult<Json<serde_json::Value>> {
+    state
+        .social_service
+        .disconnect_account(&user.workspace_id, platform)
+        .await?;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
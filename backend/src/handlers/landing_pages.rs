@@ -1,33 +1,57 @@
 use axum::{
-    extract::{Path, State},
+    extract::{ConnectInfo, Path, Query, State},
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
     Json,
 };
 use chrono::Utc;
+use std::net::SocketAddr;
 use surrealdb::sql::Thing;
 
-use crate::ai::ai_landing_page;
+use crate::ai::GenerationContext;
+use crate::db::workspace_thing;
+use crate::domain::is_bot;
 use crate::error::{AppError, AppResult};
-use crate::models::{AssetType, CampaignAsset, Contact, ContactStatus, TimelineEntry, TimelineEntryType};
+use crate::middleware::CurrentUser;
+use crate::models::{AssetType, CampaignAsset, LandingPageQuery};
+use crate::services::{new_visitor_id, visitor_cookie, visitor_from_cookies, Screening};
 use crate::AppState;
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::ToSchema)]
 pub struct GenerateLandingPageRequest {
     pub prompt: String,
     pub campaign_id: Option<String>,
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, utoipa::ToSchema)]
 pub struct LandingPageResponse {
     pub id: String,
     pub content: serde_json::Value,
     pub url: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/landing-pages/generate",
+    tag = "landing_pages",
+    request_body = GenerateLandingPageRequest,
+    responses(
+        (status = 200, description = "Landing page generated", body = LandingPageResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 502, description = "AI provider failed", body = ErrorResponse)
+    )
+)]
 pub async fn generate_landing_page(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Json(req): Json<GenerateLandingPageRequest>,
 ) -> AppResult<Json<LandingPageResponse>> {
-    let generated = ai_landing_page::generate_landing_page(&req.prompt).await;
+    let ctx = GenerationContext {
+        workspace_id: &user.workspace_id,
+        campaign_id: req.campaign_id.as_deref(),
+    };
+    let generated = state.content_generator.generate_landing_page(ctx, &req.prompt).await?;
     let content = serde_json::to_value(&generated).unwrap_or(serde_json::json!({}));
 
     let campaign = req.campaign_id.map(|id| Thing::from(("campaign", id.as_str())));
@@ -38,10 +62,14 @@ pub async fn generate_landing_page(
         .create("campaign_asset")
         .content(CampaignAsset {
             id: None,
+            workspace: workspace_thing(&user.workspace_id),
             campaign: campaign.unwrap_or_else(|| Thing::from(("campaign", "standalone"))),
             asset_type: AssetType::LandingPage,
             generated_content: content.clone(),
             url: None,
+            version: 1,
+            parent: None,
+            active: true,
             created_at: Utc::now(),
         })
         .await?;
@@ -56,10 +84,33 @@ pub async fn generate_landing_page(
     }))
 }
 
+/// Serve a landing page to a visitor
+///
+/// GET /lp/:id?utm_source=...&utm_medium=...&utm_campaign=...
+///
+/// The visitor's first view of the page is recorded with its UTM
+/// parameters. Visitors without a cookie are given one, so repeat views
+/// aren't counted again. Bots are served the page without either.
+#[utoipa::path(
+    get,
+    path = "/lp/{id}",
+    tag = "landing_pages",
+    params(
+        ("id" = String, Path, description = "Landing page ID"),
+        LandingPageQuery
+    ),
+    responses(
+        (status = 200, description = "Landing page content", body = serde_json::Value),
+        (status = 404, description = "Landing page not found", body = ErrorResponse)
+    ),
+    security(())
+)]
 pub async fn get_landing_page(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> AppResult<Json<serde_json::Value>> {
+    Query(query): Query<LandingPageQuery>,
+    headers: HeaderMap,
+) -> AppResult<Response> {
     let asset: Option<CampaignAsset> = state
         .db
         .client
@@ -68,87 +119,113 @@ pub async fn get_landing_page(
 
     let asset = asset.ok_or_else(|| AppError::NotFound("Landing page not found".into()))?;
 
-    Ok(Json(asset.generated_content))
+    // Crawlers and link previewers are served the page but neither counted nor given a cookie
+    let bot = is_bot(headers.get(header::USER_AGENT).and_then(|v| v.to_str().ok()));
+    let known_visitor = cookie_visitor(&headers);
+    let visitor_id = known_visitor.clone().unwrap_or_else(new_visitor_id);
+    let referrer = headers
+        .get(header::REFERER)
+        .and_then(|v| v.to_str().ok())
+        .map(|r| r.chars().take(500).collect());
+
+    // Serving the page matters more than counting the view
+    if !bot {
+        if let Err(e) = state
+            .landing_page_service
+            .record_visit(&asset, &visitor_id, query.utm(), referrer)
+            .await
+        {
+            tracing::warn!("Landing page visit not recorded for {}: {}", id, e);
+        }
+    }
+
+    // Pages render the CAPTCHA widget from this when submissions need one
+    let mut content = asset.generated_content;
+    if let (Some(page), Some(captcha)) = (content.as_object_mut(), state.spam_guard.captcha_widget()) {
+        page.insert("captcha".into(), captcha);
+    }
+
+    let body = Json(content);
+    Ok(if known_visitor.is_some() || bot {
+        body.into_response()
+    } else {
+        ([(header::SET_COOKIE, visitor_cookie(&visitor_id))], body).into_response()
+    })
 }
 
-#[derive(serde::Deserialize)]
-pub struct LandingPageSubmission {
-    pub first_name: String,
-    pub last_name: String,
-    pub email: String,
-    pub company: Option<String>,
-    pub message: Option<String>,
+/// The visitor ID from the request's cookies
+fn cookie_visitor(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .find_map(visitor_from_cookies)
 }
 
+/// Submit a landing page's form
+///
+/// POST /lp/:id/submit
+/// Body: { <field name>: value, ... } as defined by the page's form schema
+///
+/// Fields other than the contact's own (name, email, phone, company,
+/// message) are stored as contact custom fields; an existing contact only
+/// gains the ones it doesn't have yet. Submissions are screened
+/// for spam first: honeypot, rate limits, CAPTCHA and throwaway emails.
+#[utoipa::path(
+    post,
+    path = "/lp/{id}/submit",
+    tag = "landing_pages",
+    params(
+        ("id" = String, Path, description = "Landing page ID"),
+        ("Idempotency-Key" = Option<String>, Header, description = "Replay the first response for retries")
+    ),
+    request_body(content = serde_json::Value, description = "Field values keyed by form field name"),
+    responses(
+        (status = 200, description = "Submission recorded; retries with the same Idempotency-Key return the first result", body = serde_json::Value),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 422, description = "Submission does not match the form", body = ErrorResponse),
+        (status = 429, description = "Too many submissions", body = ErrorResponse),
+        (status = 404, description = "Landing page not found, or not the version its campaign uses", body = ErrorResponse),
+        (status = 409, description = "Conflict", body = ErrorResponse)
+    ),
+    security(())
+)]
 pub async fn submit_landing_page_form(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    Json(submission): Json<LandingPageSubmission>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(values): Json<serde_json::Map<String, serde_json::Value>>,
 ) -> AppResult<Json<serde_json::Value>> {
-    // Create or find contact
-    let existing: Vec<Contact> = state
-        .db
-        .client
-        .query("SELECT * FROM contact WHERE email = $email LIMIT 1")
-        .bind(("email", &submission.email))
-        .await?
-        .take(0)?;
-
-    let contact_id = if let Some(contact) = existing.first() {
-        contact.id.clone().unwrap()
-    } else {
-        let now = Utc::now();
-        let new_contacts: Vec<Contact> = state
-            .db
-            .client
-            .create("contact")
-            .content(Contact {
-                id: None,
-                first_name: submission.first_name.clone(),
-                last_name: submission.last_name.clone(),
-                email: submission.email.clone(),
-                phone: None,
-                linkedin_url: None,
-                tags: vec!["landing_page_lead".to_string()],
-                status: ContactStatus::Lead,
-                engagement_score: 10.0,
-                company: None,
-                created_at: now,
-                updated_at: now,
-            })
-            .await?;
-
-        new_contacts
-            .into_iter()
-            .next()
-            .ok_or_else(|| AppError::Internal("Failed to create contact".into()))?
-            .id
-            .unwrap()
-    };
-
-    // Create timeline entry for the landing page visit/submission
-    let _: Vec<TimelineEntry> = state
-        .db
-        .client
-        .create("timeline_entry")
-        .content(TimelineEntry {
-            id: None,
-            contact: contact_id.clone(),
-            company: None,
-            entry_type: TimelineEntryType::LandingPageVisit,
-            content: format!("Submitted form on landing page {}", id),
-            metadata: serde_json::json!({
-                "landing_page_id": id,
-                "message": submission.message,
-                "company": submission.company,
-            }),
-            timestamp: Utc::now(),
-        })
+    // Public route: the landing page itself determines the owning workspace
+    let (page, form) = state.landing_page_service.submission_form(&id).await?;
+
+    let client_ip = state.spam_guard.client_ip(&headers, peer);
+    if state.spam_guard.screen(&id, &form, &values, &client_ip).await? == Screening::Discard {
+        // Bots get the same answer as people, minus a contact
+        return Ok(Json(serde_json::json!({
+            "success": true,
+            "message": "Thank you for your submission!"
+        })));
+    }
+
+    let submission = form.validate_submission(&values)?;
+    state.spam_guard.check_email(&submission.email)?;
+
+    let contact_id = state
+        .landing_page_service
+        .submit(&page, submission, cookie_visitor(&headers))
         .await?;
 
-    Ok(Json(serde_json::json!({
-        "success": true,
-        "contact_id": contact_id.id.to_string(),
-        "message": "Thank you for your submission!"
-    })))
+    Ok(Json(match contact_id {
+        Some(contact_id) => serde_json::json!({
+            "success": true,
+            "contact_id": contact_id,
+            "message": "Thank you for your submission!"
+        }),
+        None => serde_json::json!({
+            "success": true,
+            "message": "Thank you for your submission!"
+        }),
+    }))
 }
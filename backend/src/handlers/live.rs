@@ -0,0 +1,121 @@
+//! Live Handlers - WebSocket change streams
+//!
+//! The socket route sits outside the bearer-token middleware: a browser
+//! cannot send an Authorization header with the handshake, so it opens the
+//! socket with a one-time ticket instead, checked here. Other clients may
+//! still send the header.
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::HeaderMap,
+    response::Response,
+    Json,
+};
+use futures::stream::BoxStream;
+use futures::{SinkExt, StreamExt};
+
+use crate::error::{AppError, AppResult};
+use crate::middleware::{bearer_token_from, CurrentUser};
+use crate::models::{ContactStreamMessage, LiveSocketQuery, LiveTicketResponse};
+use crate::shutdown::Shutdown;
+use crate::AppState;
+
+/// Get a one-time ticket for opening /ws/contacts
+///
+/// POST /api/live/tickets
+///
+/// The ticket works once, within 30 seconds.
+#[utoipa::path(
+    post,
+    path = "/api/live/tickets",
+    tag = "live",
+    responses(
+        (status = 200, description = "Ticket issued", body = LiveTicketResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn create_live_ticket(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> AppResult<Json<LiveTicketResponse>> {
+    Ok(Json(state.contact_live_service.issue_ticket(&user).await?))
+}
+
+/// Push contact changes in the caller's workspace as they happen
+///
+/// GET /ws/contacts?ticket=<ticket>&after=<cursor>
+///
+/// Messages are `{ "type": "change", "cursor", "action": "created|updated|deleted", "contact": {...} }`
+/// or `{ "type": "resync", "cursor" }`. On reconnecting, pass the last
+/// cursor received as `after` to be sent the changes missed in between.
+/// After a resync clients should reload contacts over the REST API.
+#[utoipa::path(
+    get,
+    path = "/ws/contacts",
+    tag = "live",
+    params(LiveSocketQuery),
+    responses(
+        (status = 101, description = "Switching to a WebSocket of contact changes"),
+        (status = 401, description = "Missing, invalid or used ticket", body = ErrorResponse)
+    ),
+    security(())
+)]
+pub async fn contacts_socket(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<LiveSocketQuery>,
+    ws: WebSocketUpgrade,
+) -> AppResult<Response> {
+    let user = match (bearer_token_from(&headers), query.ticket.as_deref()) {
+        (Some(token), _) => state.auth_service.verify_access_token(token)?,
+        (None, Some(ticket)) => state.contact_live_service.redeem_ticket(ticket).await?,
+        (None, None) => return Err(AppError::Unauthorized("Missing bearer token or ticket".into())),
+    };
+    let changes = state
+        .contact_live_service
+        .subscribe(&user.workspace_id, query.after.as_deref())
+        .await;
+    let shutdown = state.shutdown.clone();
+
+    Ok(ws.on_upgrade(move |socket| forward(socket, changes, shutdown)))
+}
+
+/// Send every change to the client until either side goes away or the
+/// server shuts down
+async fn forward(socket: WebSocket, mut changes: BoxStream<'static, ContactStreamMessage>, shutdown: Shutdown) {
+    let (mut sender, mut receiver) = socket.split();
+    let mut stopping = Box::pin(shutdown.triggered());
+
+    loop {
+        tokio::select! {
+            change = changes.next() => {
+                let Some(change) = change else { break };
+                let text = match serde_json::to_string(&change) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        tracing::warn!("Failed to encode contact change: {}", e);
+                        continue;
+                    }
+                };
+                if sender.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+            incoming = receiver.next() => {
+                // Clients only listen; anything but a close is ignored
+                match incoming {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                }
+            }
+            _ = &mut stopping => {
+                // Clients reconnect, to another instance if need be, and resume
+                let _ = sender.send(Message::Close(None)).await;
+                break;
+            }
+        }
+    }
+}
@@ -1,8 +1,34 @@
 pub mod health;
+pub mod auth;
 pub mod contacts;
 pub mod companies;
+pub mod deals;
 pub mod timeline;
 pub mod campaigns;
+pub mod campaign_templates;
 pub mod landing_pages;
+pub mod live;
+pub mod pipeline;
+pub mod recommendations;
+pub mod relationships;
+pub mod digest;
+pub mod email_events;
 pub mod events;
+pub mod feed;
+pub mod gdpr;
+pub mod imports;
+pub mod inbound;
+pub mod integrations;
+pub mod ai;
 pub mod analytics;
+pub mod attachments;
+pub mod audit;
+pub mod avatars;
+pub mod search;
+pub mod segments;
+pub mod senders;
+pub mod sequences;
+pub mod tracking;
+pub mod trash;
+pub mod webhooks;
+pub mod workspaces;
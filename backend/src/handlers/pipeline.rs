@@ -0,0 +1,65 @@
+//! Pipeline Handlers - kanban board of contacts or deals
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{PipelineBoard, PipelineBoardQuery, PipelineCard, PipelineMoveRequest};
+use crate::AppState;
+
+/// The board: contacts grouped by status, or deals grouped by stage
+///
+/// GET /api/pipeline/board?kind=contacts|deals&limit=50
+#[utoipa::path(
+    get,
+    path = "/api/pipeline/board",
+    tag = "pipeline",
+    params(PipelineBoardQuery),
+    responses(
+        (status = 200, description = "Board columns in pipeline order", body = PipelineBoard),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn get_board(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<PipelineBoardQuery>,
+) -> AppResult<Json<PipelineBoard>> {
+    let board = state
+        .pipeline_service
+        .board(&user.workspace_id, query.kind.unwrap_or_default(), query.limit.unwrap_or(50))
+        .await?;
+
+    Ok(Json(board))
+}
+
+/// Drag a card to a column and a place in it
+///
+/// PATCH /api/pipeline/move
+/// Body: { kind, id, status, index? }
+///
+/// A move to another column changes the contact's status or the deal's
+/// stage, and is refused if that transition isn't allowed.
+#[utoipa::path(
+    patch,
+    path = "/api/pipeline/move",
+    tag = "pipeline",
+    request_body = PipelineMoveRequest,
+    responses(
+        (status = 200, description = "The moved card", body = PipelineCard),
+        (status = 400, description = "Unknown status or disallowed transition", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact or deal not found", body = ErrorResponse)
+    )
+)]
+pub async fn move_card(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(req): Json<PipelineMoveRequest>,
+) -> AppResult<Json<PipelineCard>> {
+    let card = state.pipeline_service.move_card(&user, req).await?;
+    Ok(Json(card))
+}
@@ -0,0 +1,43 @@
+//! Recommendation Handlers - next best actions across contacts
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{RecommendationQuery, RecommendationResponse};
+use crate::AppState;
+
+/// The next best action for each contact, most urgent first
+///
+/// GET /api/recommendations?priority=high&limit=50
+///
+/// Actions are picked and prioritized by rules over engagement, recent
+/// activity, open tasks and open deals. When an AI provider is configured,
+/// it rewords the most urgent ones for the contact at hand, up to
+/// `ai.recommendations_per_hour` times per workspace. Results are cached
+/// until the workspace's timeline changes.
+#[utoipa::path(
+    get,
+    path = "/api/recommendations",
+    tag = "recommendations",
+    params(RecommendationQuery),
+    responses(
+        (status = 200, description = "Recommendations, most urgent first", body = Vec<RecommendationResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn list_recommendations(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<RecommendationQuery>,
+) -> AppResult<Json<Vec<RecommendationResponse>>> {
+    let recommendations = state
+        .recommendation_service
+        .recommendations(&user.workspace_id, query)
+        .await?;
+
+    Ok(Json(recommendations))
+}
@@ -0,0 +1,136 @@
+//! Relationship Handlers - relate contacts and companies, and walk the network for warm introductions
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+use crate::domain::{NodeType, RelationshipKind};
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{CreateRelationshipRequest, NetworkResponse, RelationshipResponse};
+use crate::AppState;
+
+/// Relate two contacts or companies
+///
+/// POST /api/relationships
+/// Body: { "kind": "introduced_by", "from_type": "contact", "from_id": "...", "to_type": "contact", "to_id": "...", "note": "..." }
+#[utoipa::path(
+    post,
+    path = "/api/relationships",
+    tag = "relationships",
+    request_body = CreateRelationshipRequest,
+    responses(
+        (status = 200, description = "Relationship created", body = RelationshipResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact or company not found", body = ErrorResponse),
+        (status = 409, description = "The two are already related this way", body = ErrorResponse),
+        (status = 422, description = "Kind not allowed between these records, or note too long", body = ErrorResponse)
+    )
+)]
+pub async fn create_relationship(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(req): Json<CreateRelationshipRequest>,
+) -> AppResult<Json<RelationshipResponse>> {
+    let relationship = state.relationship_service.create(&user, req).await?;
+    Ok(Json(relationship))
+}
+
+/// Remove a relationship
+///
+/// DELETE /api/relationships/:kind/:id
+#[utoipa::path(
+    delete,
+    path = "/api/relationships/{kind}/{id}",
+    tag = "relationships",
+    params(
+        ("kind" = RelationshipKind, Path, description = "Kind of relationship"),
+        ("id" = String, Path, description = "Relationship ID")
+    ),
+    responses(
+        (status = 200, description = "Relationship removed", body = serde_json::Value),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Relationship not found", body = ErrorResponse)
+    )
+)]
+pub async fn delete_relationship(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path((kind, id)): Path<(RelationshipKind, String)>,
+) -> AppResult<Json<serde_json::Value>> {
+    state.relationship_service.delete(&user.workspace_id, kind, &id).await?;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// List a contact's relationships, either way round
+///
+/// GET /api/contacts/:id/relationships
+#[utoipa::path(
+    get,
+    path = "/api/contacts/{id}/relationships",
+    tag = "relationships",
+    params(("id" = String, Path, description = "Contact ID")),
+    responses(
+        (status = 200, description = "Relationships, newest first", body = Vec<RelationshipResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse)
+    )
+)]
+pub async fn list_contact_relationships(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<Vec<RelationshipResponse>>> {
+    let relationships = state.relationship_service.list(&user.workspace_id, NodeType::Contact, &id).await?;
+    Ok(Json(relationships))
+}
+
+/// List a company's relationships, either way round
+///
+/// GET /api/companies/:id/relationships
+#[utoipa::path(
+    get,
+    path = "/api/companies/{id}/relationships",
+    tag = "relationships",
+    params(("id" = String, Path, description = "Company ID")),
+    responses(
+        (status = 200, description = "Relationships, newest first", body = Vec<RelationshipResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Company not found", body = ErrorResponse)
+    )
+)]
+pub async fn list_company_relationships(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<Vec<RelationshipResponse>>> {
+    let relationships = state.relationship_service.list(&user.workspace_id, NodeType::Company, &id).await?;
+    Ok(Json(relationships))
+}
+
+/// Everything within two hops of a contact: its relationships, and theirs
+///
+/// GET /api/contacts/:id/network
+///
+/// Records two hops away are warm introductions; `via` lists who can make them.
+#[utoipa::path(
+    get,
+    path = "/api/contacts/{id}/network",
+    tag = "relationships",
+    params(("id" = String, Path, description = "Contact ID")),
+    responses(
+        (status = 200, description = "The contact's two-hop network", body = NetworkResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_contact_network(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<NetworkResponse>> {
+    let network = state.relationship_service.network(&user.workspace_id, &id).await?;
+    Ok(Json(network))
+}
@@ -0,0 +1,42 @@
+//! Search Handlers - unified full-text search
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{SearchQuery, SearchResponse};
+use crate::AppState;
+
+/// Search contacts, companies and timeline entries
+///
+/// GET /api/search?q=acme&limit=20
+#[utoipa::path(
+    get,
+    path = "/api/search",
+    tag = "search",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "Matching contacts, companies and timeline entries", body = SearchResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn search(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<SearchQuery>,
+) -> AppResult<Json<SearchResponse>> {
+    let results = state
+        .search_service
+        .search(&user.workspace_id, &query.q, query.limit)
+        .await?;
+
+    Ok(Json(SearchResponse {
+        query: query.q,
+        total: results.len(),
+        results,
+    }))
+}
@@ -0,0 +1,191 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{
+    CreateSegmentRequest, SegmentPreviewQuery, SegmentPreviewRequest, SegmentPreviewResponse, SegmentResponse,
+    UpdateSegmentRequest,
+};
+use crate::services::parse_definition;
+use crate::AppState;
+
+/// How many contacts a preview returns unless asked otherwise
+const DEFAULT_PREVIEW_LIMIT: u32 = 20;
+const MAX_PREVIEW_LIMIT: u32 = 200;
+
+#[utoipa::path(
+    get,
+    path = "/api/segments",
+    tag = "segments",
+    responses(
+        (status = 200, description = "Saved segments", body = Vec<SegmentResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn list_segments(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> AppResult<Json<Vec<SegmentResponse>>> {
+    let segments = state.segment_service.list_saved(&user.workspace_id).await?;
+    Ok(Json(segments))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/segments",
+    tag = "segments",
+    request_body = CreateSegmentRequest,
+    responses(
+        (status = 200, description = "Segment saved", body = SegmentResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 409, description = "A segment with this name already exists", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn create_segment(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(req): Json<CreateSegmentRequest>,
+) -> AppResult<Json<SegmentResponse>> {
+    let segment = state.segment_service.create_saved(&user.workspace_id, req).await?;
+    Ok(Json(segment))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/segments/{id}",
+    tag = "segments",
+    params(("id" = String, Path, description = "Segment ID")),
+    responses(
+        (status = 200, description = "Saved segment", body = SegmentResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Segment not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_segment(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<SegmentResponse>> {
+    let segment = state.segment_service.get_saved(&user.workspace_id, &id).await?;
+    Ok(Json(segment.into()))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/segments/{id}",
+    tag = "segments",
+    params(("id" = String, Path, description = "Segment ID")),
+    request_body = UpdateSegmentRequest,
+    responses(
+        (status = 200, description = "Segment updated", body = SegmentResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Segment not found", body = ErrorResponse),
+        (status = 409, description = "A segment with this name already exists", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn update_segment(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Json(req): Json<UpdateSegmentRequest>,
+) -> AppResult<Json<SegmentResponse>> {
+    let segment = state
+        .segment_service
+        .update_saved(&user.workspace_id, &id, req)
+        .await?;
+    Ok(Json(segment))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/segments/{id}",
+    tag = "segments",
+    params(("id" = String, Path, description = "Segment ID")),
+    responses(
+        (status = 200, description = "Segment deleted", body = serde_json::Value),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Segment not found", body = ErrorResponse),
+        (status = 409, description = "Conflict", body = ErrorResponse)
+    )
+)]
+pub async fn delete_segment(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    state.segment_service.delete_saved(&user.workspace_id, &id).await?;
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// Contacts currently matching a saved segment
+///
+/// GET /api/segments/:id/preview?limit=20
+#[utoipa::path(
+    get,
+    path = "/api/segments/{id}/preview",
+    tag = "segments",
+    params(
+        ("id" = String, Path, description = "Segment ID"),
+        SegmentPreviewQuery
+    ),
+    responses(
+        (status = 200, description = "Contacts matching the segment", body = SegmentPreviewResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Segment not found", body = ErrorResponse)
+    )
+)]
+pub async fn preview_segment(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Query(query): Query<SegmentPreviewQuery>,
+) -> AppResult<Json<SegmentPreviewResponse>> {
+    let segment = state.segment_service.get_saved(&user.workspace_id, &id).await?;
+    let definition = parse_definition(&segment.definition)?;
+
+    let preview = state
+        .segment_service
+        .preview(&user.workspace_id, &definition, preview_limit(query.limit))
+        .await?;
+    Ok(Json(preview))
+}
+
+/// Contacts that would match a definition, without saving it
+///
+/// POST /api/segments/preview
+#[utoipa::path(
+    post,
+    path = "/api/segments/preview",
+    tag = "segments",
+    request_body = SegmentPreviewRequest,
+    responses(
+        (status = 200, description = "Contacts matching the definition", body = SegmentPreviewResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn preview_definition(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(req): Json<SegmentPreviewRequest>,
+) -> AppResult<Json<SegmentPreviewResponse>> {
+    let definition = parse_definition(&req.definition)?;
+
+    let preview = state
+        .segment_service
+        .preview(&user.workspace_id, &definition, preview_limit(req.limit))
+        .await?;
+    Ok(Json(preview))
+}
+
+fn preview_limit(limit: Option<u32>) -> u32 {
+    limit.unwrap_or(DEFAULT_PREVIEW_LIMIT).min(MAX_PREVIEW_LIMIT)
+}
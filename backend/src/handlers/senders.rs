@@ -0,0 +1,145 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{CreateSenderRequest, SenderResponse, UpdateSenderRequest};
+use crate::AppState;
+
+/// Sender identities shared with the workspace, plus the caller's own
+#[utoipa::path(
+    get,
+    path = "/api/senders",
+    tag = "senders",
+    responses(
+        (status = 200, description = "Sender identities", body = Vec<SenderResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn list_senders(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> AppResult<Json<Vec<SenderResponse>>> {
+    let senders = state.sender_service.list(&user.workspace_id, &user.user_id).await?;
+    Ok(Json(senders))
+}
+
+/// Add a sender identity; its domain's verification, SPF and DKIM records are checked right away
+///
+/// POST /api/senders
+/// Body: { "from_name": "Jane Doe", "from_email": "jane@example.com", "reply_to": null, "signature_html": "<p>Jane</p>", "personal": false }
+#[utoipa::path(
+    post,
+    path = "/api/senders",
+    tag = "senders",
+    request_body = CreateSenderRequest,
+    responses(
+        (status = 200, description = "Sender identity created", body = SenderResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn create_sender(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(req): Json<CreateSenderRequest>,
+) -> AppResult<Json<SenderResponse>> {
+    let sender = state
+        .sender_service
+        .create(&user.workspace_id, &user.user_id, req)
+        .await?;
+    Ok(Json(sender))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/senders/{id}",
+    tag = "senders",
+    params(("id" = String, Path, description = "Sender identity ID")),
+    responses(
+        (status = 200, description = "Sender identity", body = SenderResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Sender identity not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_sender(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<SenderResponse>> {
+    let sender = state.sender_service.get(&user.workspace_id, &user.user_id, &id).await?;
+    Ok(Json(sender.into()))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/senders/{id}",
+    tag = "senders",
+    params(("id" = String, Path, description = "Sender identity ID")),
+    request_body = UpdateSenderRequest,
+    responses(
+        (status = 200, description = "Sender identity updated", body = SenderResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Sender identity not found", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn update_sender(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Json(req): Json<UpdateSenderRequest>,
+) -> AppResult<Json<SenderResponse>> {
+    let sender = state
+        .sender_service
+        .update(&user.workspace_id, &user.user_id, &id, req)
+        .await?;
+    Ok(Json(sender))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/senders/{id}",
+    tag = "senders",
+    params(("id" = String, Path, description = "Sender identity ID")),
+    responses(
+        (status = 200, description = "Sender identity deleted", body = serde_json::Value),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Sender identity not found", body = ErrorResponse),
+        (status = 409, description = "Used by a campaign that hasn't finished", body = ErrorResponse)
+    )
+)]
+pub async fn delete_sender(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    state.sender_service.delete(&user.workspace_id, &user.user_id, &id).await?;
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// Look up the identity's domain records again, e.g. after publishing them
+///
+/// POST /api/senders/:id/verify
+#[utoipa::path(
+    post,
+    path = "/api/senders/{id}/verify",
+    tag = "senders",
+    params(("id" = String, Path, description = "Sender identity ID")),
+    responses(
+        (status = 200, description = "Sender identity with fresh verification, SPF and DKIM results", body = SenderResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Sender identity not found", body = ErrorResponse),
+        (status = 502, description = "DNS lookup failed", body = ErrorResponse)
+    )
+)]
+pub async fn verify_sender(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<SenderResponse>> {
+    let sender = state.sender_service.verify(&user.workspace_id, &user.user_id, &id).await?;
+    Ok(Json(sender))
+}
@@ -0,0 +1,187 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{
+    CreateSequenceRequest, EnrollmentChangeResponse, EnrollmentQuery, EnrollmentRequest, EnrollmentResponse,
+    SequenceResponse,
+};
+use crate::AppState;
+
+#[utoipa::path(
+    get,
+    path = "/api/sequences",
+    tag = "sequences",
+    responses(
+        (status = 200, description = "Sequences in the workspace", body = Vec<SequenceResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn list_sequences(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> AppResult<Json<Vec<SequenceResponse>>> {
+    let sequences = state.sequence_service.list(&user.workspace_id).await?;
+    Ok(Json(sequences))
+}
+
+/// Create a drip sequence
+///
+/// POST /api/sequences
+/// Body: { "name": "...", "steps": [{ "type": "email", "subject": "...", "body_html": "..." },
+///        { "type": "wait", "days": 3 },
+///        { "type": "branch", "condition": "opened", "then_step": 4, "else_step": 3 }, ...] }
+#[utoipa::path(
+    post,
+    path = "/api/sequences",
+    tag = "sequences",
+    request_body = CreateSequenceRequest,
+    responses(
+        (status = 200, description = "Sequence created", body = SequenceResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 422, description = "Invalid steps", body = ErrorResponse)
+    )
+)]
+pub async fn create_sequence(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(req): Json<CreateSequenceRequest>,
+) -> AppResult<Json<SequenceResponse>> {
+    let sequence = state.sequence_service.create(&user.workspace_id, req).await?;
+    Ok(Json(sequence))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/sequences/{id}",
+    tag = "sequences",
+    params(("id" = String, Path, description = "Sequence ID")),
+    responses(
+        (status = 200, description = "Sequence", body = SequenceResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Sequence not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_sequence(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<SequenceResponse>> {
+    let sequence = state.sequence_service.get(&user.workspace_id, &id).await?;
+    Ok(Json(sequence.into()))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/sequences/{id}",
+    tag = "sequences",
+    params(("id" = String, Path, description = "Sequence ID")),
+    responses(
+        (status = 200, description = "Sequence deleted", body = serde_json::Value),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Sequence not found", body = ErrorResponse),
+        (status = 409, description = "Sequence still has active enrollments", body = ErrorResponse)
+    )
+)]
+pub async fn delete_sequence(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    state.sequence_service.delete(&user.workspace_id, &id).await?;
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// List the contacts enrolled in a sequence
+///
+/// GET /api/sequences/:id/enrollments?status=active&limit=100&offset=0
+#[utoipa::path(
+    get,
+    path = "/api/sequences/{id}/enrollments",
+    tag = "sequences",
+    params(
+        ("id" = String, Path, description = "Sequence ID"),
+        EnrollmentQuery
+    ),
+    responses(
+        (status = 200, description = "Enrollments", body = Vec<EnrollmentResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Sequence not found", body = ErrorResponse)
+    )
+)]
+pub async fn list_enrollments(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Query(query): Query<EnrollmentQuery>,
+) -> AppResult<Json<Vec<EnrollmentResponse>>> {
+    let enrollments = state.sequence_service.enrollments(&user.workspace_id, &id, &query).await?;
+    Ok(Json(enrollments))
+}
+
+/// Enroll contacts in a sequence
+///
+/// POST /api/sequences/:id/enroll
+/// Body: { "contact_ids": ["..."] }
+///
+/// Contacts start at the first step. Those who finished or left the
+/// sequence start over; those still in it are left where they are.
+#[utoipa::path(
+    post,
+    path = "/api/sequences/{id}/enroll",
+    tag = "sequences",
+    params(("id" = String, Path, description = "Sequence ID")),
+    request_body = EnrollmentRequest,
+    responses(
+        (status = 200, description = "Contacts enrolled", body = EnrollmentChangeResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Sequence or contact not found", body = ErrorResponse)
+    )
+)]
+pub async fn enroll_contacts(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Json(req): Json<EnrollmentRequest>,
+) -> AppResult<Json<EnrollmentChangeResponse>> {
+    let result = state
+        .sequence_service
+        .enroll(&user.workspace_id, &id, req.contact_ids)
+        .await?;
+    Ok(Json(result))
+}
+
+/// Take contacts out of a sequence
+///
+/// POST /api/sequences/:id/unenroll
+/// Body: { "contact_ids": ["..."] }
+#[utoipa::path(
+    post,
+    path = "/api/sequences/{id}/unenroll",
+    tag = "sequences",
+    params(("id" = String, Path, description = "Sequence ID")),
+    request_body = EnrollmentRequest,
+    responses(
+        (status = 200, description = "Contacts unenrolled", body = EnrollmentChangeResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Sequence not found", body = ErrorResponse)
+    )
+)]
+pub async fn unenroll_contacts(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Json(req): Json<EnrollmentRequest>,
+) -> AppResult<Json<EnrollmentChangeResponse>> {
+    let result = state
+        .sequence_service
+        .unenroll(&user.workspace_id, &id, req.contact_ids)
+        .await?;
+    Ok(Json(result))
+}
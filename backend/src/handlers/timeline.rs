@@ -2,59 +2,148 @@ use axum::{
     extract::{Path, Query, State},
     Json,
 };
-use chrono::Utc;
 use surrealdb::sql::Thing;
 
-use crate::error::{AppError, AppResult};
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
 use crate::models::{
-    CreateTimelineEntryRequest, TimelineEntry, TimelineEntryResponse, TimelineQuery,
+    CreateTimelineEntryRequest, DailyActivityCount, EmailThreadResponse, TimelineEntryResponse, TimelineQuery,
 };
+use crate::services::new_entry;
 use crate::AppState;
 
+/// Timeline of one contact, with the same filters as `list_timeline`
+///
+/// GET /api/contacts/:id/timeline
+#[utoipa::path(
+    get,
+    path = "/api/contacts/{id}/timeline",
+    tag = "timeline",
+    params(
+        ("id" = String, Path, description = "Contact ID"),
+        TimelineQuery
+    ),
+    responses(
+        (status = 200, description = "Timeline of the contact, newest first", body = Vec<TimelineEntryResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
 pub async fn get_contact_timeline(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Path(contact_id): Path<String>,
+    Query(mut query): Query<TimelineQuery>,
+) -> AppResult<Json<Vec<TimelineEntryResponse>>> {
+    query.contact_id = Some(contact_id);
+
+    let entries = state.timeline_service.list(&user.workspace_id, &query).await?;
+
+    let responses: Vec<TimelineEntryResponse> = entries.into_iter().map(Into::into).collect();
+    Ok(Json(responses))
+}
+
+/// A contact's sent and received email, collapsed into conversations
+///
+/// GET /api/contacts/:id/threads
+///
+/// Email is threaded by its `Message-ID`, `In-Reply-To` and `References`
+/// headers, falling back to the subject for email logged without them.
+#[utoipa::path(
+    get,
+    path = "/api/contacts/{id}/threads",
+    tag = "timeline",
+    params(("id" = String, Path, description = "Contact ID")),
+    responses(
+        (status = 200, description = "Threads, most recent activity first", body = Vec<EmailThreadResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_contact_threads(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(contact_id): Path<String>,
+) -> AppResult<Json<Vec<EmailThreadResponse>>> {
+    let threads = state.timeline_service.threads(&user.workspace_id, &contact_id).await?;
+    Ok(Json(threads))
+}
+
+/// Workspace-wide timeline, newest first
+///
+/// GET /api/timeline?contact_id=&company_id=&campaign_id=&entry_type=&from=&to=&search=&limit=&offset=
+#[utoipa::path(
+    get,
+    path = "/api/timeline",
+    tag = "timeline",
+    params(TimelineQuery),
+    responses(
+        (status = 200, description = "Timeline entries, newest first", body = Vec<TimelineEntryResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn list_timeline(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Query(query): Query<TimelineQuery>,
 ) -> AppResult<Json<Vec<TimelineEntryResponse>>> {
-    let limit = query.limit.unwrap_or(50);
-    let offset = query.offset.unwrap_or(0);
-
-    let entries: Vec<TimelineEntry> = state
-        .db
-        .client
-        .query("SELECT * FROM timeline_entry WHERE contact = $contact ORDER BY timestamp DESC LIMIT $limit START $offset")
-        .bind(("contact", Thing::from(("contact", contact_id.as_str()))))
-        .bind(("limit", limit))
-        .bind(("offset", offset))
-        .await?
-        .take(0)?;
+    let entries = state.timeline_service.list(&user.workspace_id, &query).await?;
 
     let responses: Vec<TimelineEntryResponse> = entries.into_iter().map(Into::into).collect();
     Ok(Json(responses))
 }
 
+/// Entries per day for an activity heatmap; takes the `list_timeline` filters
+///
+/// GET /api/timeline/daily
+#[utoipa::path(
+    get,
+    path = "/api/timeline/daily",
+    tag = "timeline",
+    params(TimelineQuery),
+    responses(
+        (status = 200, description = "Entry counts per UTC day", body = Vec<DailyActivityCount>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn daily_activity(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<TimelineQuery>,
+) -> AppResult<Json<Vec<DailyActivityCount>>> {
+    let counts = state.timeline_service.daily_counts(&user.workspace_id, &query).await?;
+    Ok(Json(counts))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/timeline",
+    tag = "timeline",
+    request_body = CreateTimelineEntryRequest,
+    responses(
+        (status = 200, description = "Timeline entry created", body = TimelineEntryResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Contact not found", body = ErrorResponse),
+        (status = 422, description = "Metadata doesn't fit the entry type", body = ErrorResponse)
+    )
+)]
 pub async fn create_timeline_entry(
     State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
     Json(req): Json<CreateTimelineEntryRequest>,
 ) -> AppResult<Json<TimelineEntryResponse>> {
-    let contact = Thing::from(("contact", req.contact_id.as_str()));
-    let company = req.company_id.map(|id| Thing::from(("company", id.as_str())));
-
-    let entries: Vec<TimelineEntry> = state
-        .db
-        .client
-        .create("timeline_entry")
-        .content(TimelineEntry {
-            id: None,
-            contact,
-            company,
-            entry_type: req.entry_type,
-            content: req.content,
-            metadata: req.metadata.unwrap_or(serde_json::json!({})),
-            timestamp: Utc::now(),
-        })
-        .await?;
-
-    let entry = entries.into_iter().next().ok_or_else(|| AppError::Internal("Failed to create timeline entry".into()))?;
+    let mut entry = new_entry(
+        &user.workspace_id,
+        &req.contact_id,
+        req.entry_type,
+        req.content,
+        req.metadata.unwrap_or(serde_json::Value::Null),
+    );
+    entry.company = req.company_id.map(|id| Thing::from(("company", id.as_str())));
+    entry.campaign = req.campaign_id.map(|id| Thing::from(("campaign", id.as_str())));
+    entry.sentiment = req.sentiment;
+
+    let entry = state.timeline_service.record(&user.workspace_id, entry).await?;
+
     Ok(Json(entry.into()))
 }
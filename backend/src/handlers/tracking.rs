@@ -0,0 +1,157 @@
+//! Tracking Handlers - public endpoints hit from inside sent emails
+//!
+//! These routes are unauthenticated; the signed token or short link code
+//! in the path is the only credential.
+
+use axum::{
+    extract::{Path, State},
+    http::header,
+    response::{Html, IntoResponse, Redirect, Response},
+};
+
+use crate::domain::escape_html;
+use crate::error::AppResult;
+use crate::AppState;
+
+/// Transparent 1x1 GIF
+const TRACKING_PIXEL: &[u8] = &[
+    0x47, 0x49, 0x46, 0x38, 0x39, 0x61, 0x01, 0x00, 0x01, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0xff, 0xff, 0xff, 0x21, 0xf9, 0x04, 0x01, 0x00, 0x00, 0x00, 0x00, 0x2c, 0x00, 0x00,
+    0x00, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00, 0x02, 0x02, 0x44, 0x01, 0x00, 0x3b,
+];
+
+/// Record an email open
+///
+/// GET /t/open/:token
+///
+/// Always answers with the pixel - a broken image in the recipient's
+/// inbox helps nobody, even when the token is bad.
+#[utoipa::path(
+    get,
+    path = "/t/open/{token}",
+    tag = "tracking",
+    params(("token" = String, Path, description = "Signed tracking token")),
+    responses(
+        (status = 200, description = "Transparent 1x1 GIF", content_type = "image/gif")
+    ),
+    security(())
+)]
+pub async fn track_open(State(state): State<AppState>, Path(token): Path<String>) -> Response {
+    if let Err(e) = state.tracking_service.record_open(&token).await {
+        tracing::debug!("Email open not recorded: {}", e);
+    }
+
+    (
+        [
+            (header::CONTENT_TYPE, "image/gif"),
+            (header::CACHE_CONTROL, "no-store, no-cache, must-revalidate, max-age=0"),
+        ],
+        TRACKING_PIXEL,
+    )
+        .into_response()
+}
+
+/// Record a link click and redirect to the original URL
+///
+/// GET /t/click/:token
+#[utoipa::path(
+    get,
+    path = "/t/click/{token}",
+    tag = "tracking",
+    params(("token" = String, Path, description = "Signed tracking token")),
+    responses(
+        (status = 307, description = "Redirect to the original link"),
+        (status = 400, description = "Bad request", body = ErrorResponse)
+    ),
+    security(())
+)]
+pub async fn track_click(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> AppResult<Redirect> {
+    let url = state.tracking_service.record_click(&token).await?;
+
+    Ok(Redirect::temporary(&url))
+}
+
+/// Record a click through a short link and redirect to its URL
+///
+/// GET /r/:code
+#[utoipa::path(
+    get,
+    path = "/r/{code}",
+    tag = "tracking",
+    params(("code" = String, Path, description = "Short link code")),
+    responses(
+        (status = 307, description = "Redirect to the link's URL"),
+        (status = 404, description = "Unknown link", body = ErrorResponse)
+    ),
+    security(())
+)]
+pub async fn follow_short_link(
+    State(state): State<AppState>,
+    Path(code): Path<String>,
+) -> AppResult<Redirect> {
+    let url = state.tracking_service.follow_short_link(&code).await?;
+
+    Ok(Redirect::temporary(&url))
+}
+
+/// Ask the recipient to confirm they want to unsubscribe
+///
+/// GET /unsubscribe/:token
+///
+/// Changes nothing: mail scanners and link prefetchers follow links in
+/// email, so only the confirmation form's POST unsubscribes.
+#[utoipa::path(
+    get,
+    path = "/unsubscribe/{token}",
+    tag = "tracking",
+    params(("token" = String, Path, description = "Signed unsubscribe token")),
+    responses(
+        (status = 200, description = "Confirmation page with a form that POSTs back", content_type = "text/html"),
+        (status = 400, description = "Bad request", body = ErrorResponse)
+    ),
+    security(())
+)]
+pub async fn unsubscribe_page(State(state): State<AppState>, Path(token): Path<String>) -> AppResult<Html<String>> {
+    state.tracking_service.check_unsubscribe_token(&token)?;
+
+    Ok(Html(format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Unsubscribe</title></head>\
+         <body><h1>Unsubscribe from campaign email?</h1>\
+         <form method=\"post\" action=\"/unsubscribe/{}\">\
+         <button type=\"submit\">Unsubscribe</button></form></body></html>",
+        escape_html(&token)
+    )))
+}
+
+/// Unsubscribe the recipient from campaign email
+///
+/// POST /unsubscribe/:token
+///
+/// Sent by the confirmation page, and by mail clients as an RFC 8058
+/// one-click unsubscribe.
+#[utoipa::path(
+    post,
+    path = "/unsubscribe/{token}",
+    tag = "tracking",
+    params(("token" = String, Path, description = "Signed unsubscribe token")),
+    responses(
+        (status = 200, description = "Unsubscribed", content_type = "text/html"),
+        (status = 400, description = "Bad request", body = ErrorResponse)
+    ),
+    security(())
+)]
+pub async fn unsubscribe(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> AppResult<Html<&'static str>> {
+    state.tracking_service.unsubscribe(&token).await?;
+
+    Ok(Html(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Unsubscribed</title></head>\
+         <body><h1>You have been unsubscribed</h1>\
+         <p>You will no longer receive campaign emails from us.</p></body></html>",
+    ))
+}
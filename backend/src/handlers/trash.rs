@@ -0,0 +1,32 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{TrashItem, TrashQuery};
+use crate::AppState;
+
+/// Deleted contacts and companies that can still be restored
+///
+/// GET /api/trash?entity_type=contact|company&limit=50
+#[utoipa::path(
+    get,
+    path = "/api/trash",
+    tag = "trash",
+    params(TrashQuery),
+    responses(
+        (status = 200, description = "Trashed contacts and companies, most recently deleted first", body = Vec<TrashItem>),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn list_trash(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<TrashQuery>,
+) -> AppResult<Json<Vec<TrashItem>>> {
+    let items = state.trash_service.list(&user.workspace_id, &query).await?;
+    Ok(Json(items))
+}
@@ -0,0 +1,175 @@
+//! Webhook Handlers - register endpoints for CRM events and inspect deliveries
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+
+use crate::error::AppResult;
+use crate::middleware::CurrentUser;
+use crate::models::{
+    CreateWebhookRequest, DeliveryQuery, WebhookDeliveryDetailResponse, WebhookDeliveryResponse,
+    WebhookResponse,
+};
+use crate::AppState;
+
+/// List webhooks
+///
+/// GET /api/webhooks
+#[utoipa::path(
+    get,
+    path = "/api/webhooks",
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "Registered webhooks", body = Vec<WebhookResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> AppResult<Json<Vec<WebhookResponse>>> {
+    let webhooks = state.webhook_service.list(&user.workspace_id).await?;
+    Ok(Json(webhooks))
+}
+
+/// Register a webhook
+///
+/// POST /api/webhooks
+/// Body: { url, events: ["contact.created", ...], description? }
+///
+/// The response includes the signing secret; it is not shown again.
+#[utoipa::path(
+    post,
+    path = "/api/webhooks",
+    tag = "webhooks",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 200, description = "Webhook registered; the signing secret is only returned here", body = WebhookResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(req): Json<CreateWebhookRequest>,
+) -> AppResult<Json<WebhookResponse>> {
+    let webhook = state.webhook_service.register(&user.workspace_id, req).await?;
+    Ok(Json(webhook))
+}
+
+/// Get a webhook
+///
+/// GET /api/webhooks/:id
+#[utoipa::path(
+    get,
+    path = "/api/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = String, Path, description = "Webhook ID")),
+    responses(
+        (status = 200, description = "Webhook", body = WebhookResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Webhook not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_webhook(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<WebhookResponse>> {
+    let webhook = state.webhook_service.get(&user.workspace_id, &id).await?;
+    Ok(Json(webhook))
+}
+
+/// Delete a webhook
+///
+/// DELETE /api/webhooks/:id
+#[utoipa::path(
+    delete,
+    path = "/api/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = String, Path, description = "Webhook ID")),
+    responses(
+        (status = 200, description = "Webhook deleted", body = serde_json::Value),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Webhook not found", body = ErrorResponse)
+    )
+)]
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    state.webhook_service.delete(&user.workspace_id, &id).await?;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// List deliveries of a webhook
+///
+/// GET /api/webhooks/:id/deliveries?status=failed&limit=50&offset=0
+#[utoipa::path(
+    get,
+    path = "/api/webhooks/{id}/deliveries",
+    tag = "webhooks",
+    params(
+        ("id" = String, Path, description = "Webhook ID"),
+        DeliveryQuery
+    ),
+    responses(
+        (status = 200, description = "Deliveries, newest first", body = Vec<WebhookDeliveryResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Webhook not found", body = ErrorResponse)
+    )
+)]
+pub async fn list_deliveries(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<String>,
+    Query(query): Query<DeliveryQuery>,
+) -> AppResult<Json<Vec<WebhookDeliveryResponse>>> {
+    let deliveries = state
+        .webhook_service
+        .deliveries(
+            &user.workspace_id,
+            &id,
+            query.status,
+            query.limit.unwrap_or(50).min(500),
+            query.offset.unwrap_or(0),
+        )
+        .await?;
+
+    Ok(Json(deliveries))
+}
+
+/// Get a delivery with every attempt made for it
+///
+/// GET /api/webhooks/:id/deliveries/:delivery_id
+#[utoipa::path(
+    get,
+    path = "/api/webhooks/{id}/deliveries/{delivery_id}",
+    tag = "webhooks",
+    params(
+        ("id" = String, Path, description = "Webhook ID"),
+        ("delivery_id" = String, Path, description = "Delivery ID")
+    ),
+    responses(
+        (status = 200, description = "Delivery with its attempt log", body = WebhookDeliveryDetailResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Delivery not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_delivery(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path((id, delivery_id)): Path<(String, String)>,
+) -> AppResult<Json<WebhookDeliveryDetailResponse>> {
+    let delivery = state
+        .webhook_service
+        .delivery(&user.workspace_id, &id, &delivery_id)
+        .await?;
+
+    Ok(Json(delivery))
+}
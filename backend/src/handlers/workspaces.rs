@@ -0,0 +1,164 @@
+//! Workspace Handlers - the caller's own tenant
+//!
+//! A user only ever sees the workspace baked into their access token.
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
+use futures::TryStreamExt;
+use std::sync::Arc;
+
+use crate::error::{AppError, AppResult};
+use crate::middleware::CurrentUser;
+use crate::models::{RestoreResponse, UpdateWorkspaceRequest, WorkspaceResponse};
+use crate::repositories::WorkspaceRepository;
+use crate::AppState;
+
+/// Get the current workspace
+///
+/// GET /api/workspace
+#[utoipa::path(
+    get,
+    path = "/api/workspace",
+    tag = "workspace",
+    responses(
+        (status = 200, description = "The caller's workspace", body = WorkspaceResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    )
+)]
+pub async fn get_workspace(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> AppResult<Json<WorkspaceResponse>> {
+    let repo = WorkspaceRepository::new(Arc::clone(&state.db));
+    let workspace = repo
+        .find_by_id(&user.workspace_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Workspace not found".into()))?;
+
+    Ok(Json(workspace.into()))
+}
+
+/// Rename the current workspace
+///
+/// PATCH /api/workspace
+/// Body: { name? }
+#[utoipa::path(
+    patch,
+    path = "/api/workspace",
+    tag = "workspace",
+    request_body = UpdateWorkspaceRequest,
+    responses(
+        (status = 200, description = "Workspace updated", body = WorkspaceResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 422, description = "Validation error", body = ErrorResponse)
+    )
+)]
+pub async fn update_workspace(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(req): Json<UpdateWorkspaceRequest>,
+) -> AppResult<Json<WorkspaceResponse>> {
+    let repo = WorkspaceRepository::new(Arc::clone(&state.db));
+    let mut workspace = repo
+        .find_by_id(&user.workspace_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Workspace not found".into()))?;
+
+    if let Some(name) = req.name {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::Validation("Workspace name cannot be empty".into()));
+        }
+        workspace.name = name.to_string();
+    }
+    workspace.updated_at = Utc::now();
+
+    let updated = repo.update(&user.workspace_id, workspace).await?;
+    Ok(Json(updated.into()))
+}
+
+/// Download a backup of the whole workspace
+///
+/// GET /api/workspace/backup
+///
+/// Every record of every table, streamed as newline-delimited JSON; see
+/// `services::backup_service` for the format. Credentials and attachment
+/// files are not included. Owners and admins only.
+#[utoipa::path(
+    get,
+    path = "/api/workspace/backup",
+    tag = "workspace",
+    responses(
+        (status = 200, description = "The workspace as a backup archive", body = String, content_type = "application/x-ndjson"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Not a workspace owner or admin", body = ErrorResponse)
+    )
+)]
+pub async fn backup_workspace(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> AppResult<Response> {
+    state.auth_service.require_admin(&user).await?;
+
+    let stream = state.backup_service.export(&user.workspace_id);
+    let disposition = format!(
+        "attachment; filename=\"crm-backup-{}-{}.ndjson\"",
+        user.workspace_id,
+        Utc::now().format("%Y%m%d%H%M%S")
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        Body::from_stream(stream),
+    )
+        .into_response())
+}
+
+/// Replace the workspace with a backup of it
+///
+/// POST /api/workspace/restore
+/// Body: a backup archive from GET /api/workspace/backup
+///
+/// The whole archive is checked before anything changes; then everything
+/// in the workspace is replaced with it in one transaction. Credentials
+/// aren't restored from the archive. Owners and admins only.
+#[utoipa::path(
+    post,
+    path = "/api/workspace/restore",
+    tag = "workspace",
+    request_body(content = String, description = "Backup archive", content_type = "application/x-ndjson"),
+    responses(
+        (status = 200, description = "Workspace restored", body = RestoreResponse),
+        (status = 400, description = "Not a backup of this workspace, or a truncated or malformed one", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Not a workspace owner or admin", body = ErrorResponse)
+    )
+)]
+pub async fn restore_workspace(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    body: Body,
+) -> AppResult<Json<RestoreResponse>> {
+    state.auth_service.require_admin(&user).await?;
+    tracing::info!("User {} is restoring workspace {} from a backup", user.user_id, user.workspace_id);
+
+    let chunks = body
+        .into_data_stream()
+        .map_err(|e| AppError::BadRequest(format!("Failed to read the backup: {}", e)));
+    let restored = state
+        .backup_service
+        .restore(Some(&user.workspace_id), chunks)
+        .await?;
+
+    Ok(Json(restored))
+}
@@ -1,60 +1,102 @@
 use anyhow::Result;
 use axum::{
-    routing::{get, post, patch, delete},
+    extract::DefaultBodyLimit,
+    routing::{get, post, put, patch, delete},
     Router,
 };
+use std::future::IntoFuture;
+use std::net::SocketAddr;
 use std::sync::Arc;
-use tower_http::cors::{Any, CorsLayer};
+use std::time::Duration;
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-
-// OpenAPI imports
-use utoipa::{OpenApi, Modify};
+use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 mod ai;
+mod backup;
 mod config;
 mod db;
 mod domain;
 mod error;
 mod handlers;
+mod middleware;
+mod migrations;
 mod models;
+mod openapi;
 mod repositories;
 mod secrets;
+mod seed;
 mod services;
+mod shutdown;
 
 // Re-export domain types for use in library context
 pub use domain::*;
 
+use ai::ContentGenerator;
 use db::Database;
-use services::ContactService;
-
-// OpenAPI Documentation
-#[derive(OpenApi)]
-#[openapi(
-    paths(
-        handlers::health::health_check,
-        handlers::contacts::list_contacts,
-        handlers::contacts::create_contact,
-    ),
-    components(
-        schemas(
-            models::ContactResponse,
-            models::CreateContactRequest,
-            models::ContactQuery,
-            error::ErrorResponse,
-        )
-    ),
-    tags(
-        (name = "CRM API", description = "CRM.HEY.SH API for contact and company management")
-    )
-)]
-struct ApiDoc;
+use services::{
+    AnalyticsService, AssistantService, AttachmentService, AuditService, AuthService, AvatarService, BackupService, CampaignAssetService, CampaignScheduler, CampaignTemplateService, ContactLiveService, ContactReportService,
+    ContactService, DealService, DedupeService, DeliverabilityService, DigestService, EngagementService, EnrichmentService, EventService, FeedService, GdprService, IdempotencyService, ImportService, InboundService, InboxService, LandingPageService, PipelineService, RecommendationService, RelationshipService, SearchService, SegmentService, SenderService, SequenceService, SocialService, SpamGuard, TimelineService, TrackingService, TrashService, WebhookDispatcher,
+    WebhookService,
+};
+use services::read_cache::ReadCache;
 
 #[derive(Clone)]
 pub struct AppState {
+    pub config: Arc<config::Config>,
+    /// Triggered on SIGTERM or Ctrl-C; live streams end on it
+    pub shutdown: shutdown::Shutdown,
     pub db: Arc<Database>,
+    pub read_cache: Arc<ReadCache>,
+    pub content_generator: Arc<ContentGenerator>,
     pub contact_service: Arc<ContactService>,
+    pub contact_live_service: Arc<ContactLiveService>,
+    pub contact_report_service: Arc<ContactReportService>,
+    pub analytics_service: Arc<AnalyticsService>,
+    pub assistant_service: Arc<AssistantService>,
+    pub attachment_service: Arc<AttachmentService>,
+    pub audit_service: Arc<AuditService>,
+    pub auth_service: Arc<AuthService>,
+    pub avatar_service: Arc<AvatarService>,
+    pub backup_service: Arc<BackupService>,
+    pub campaign_asset_service: Arc<CampaignAssetService>,
+    pub campaign_scheduler: Arc<CampaignScheduler>,
+    pub campaign_template_service: Arc<CampaignTemplateService>,
+    pub deal_service: Arc<DealService>,
+    pub dedupe_service: Arc<DedupeService>,
+    pub deliverability_service: Arc<DeliverabilityService>,
+    pub digest_service: Arc<DigestService>,
+    pub engagement_service: Arc<EngagementService>,
+    pub enrichment_service: Arc<EnrichmentService>,
+    pub event_service: Arc<EventService>,
+    pub feed_service: Arc<FeedService>,
+    pub gdpr_service: Arc<GdprService>,
+    pub idempotency_service: Arc<IdempotencyService>,
+    pub import_service: Arc<ImportService>,
+    pub inbound_service: Arc<InboundService>,
+    pub inbox_service: Arc<InboxService>,
+    pub landing_page_service: Arc<LandingPageService>,
+    pub pipeline_service: Arc<PipelineService>,
+    pub recommendation_service: Arc<RecommendationService>,
+    pub relationship_service: Arc<RelationshipService>,
+    pub search_service: Arc<SearchService>,
+    pub segment_service: Arc<SegmentService>,
+    pub sender_service: Arc<SenderService>,
+    pub sequence_service: Arc<SequenceService>,
+    pub social_service: Arc<SocialService>,
+    pub spam_guard: Arc<SpamGuard>,
+    pub timeline_service: Arc<TimelineService>,
+    pub tracking_service: Arc<TrackingService>,
+    pub trash_service: Arc<TrashService>,
+    pub webhook_service: Arc<WebhookService>,
+}
+
+/// A one-off command given on the command line
+enum Command {
+    Migrate(migrations::MigrateCommand),
+    Backup(backup::BackupCommand),
+    Seed(seed::SeedCommand),
 }
 
 #[tokio::main]
@@ -66,51 +108,467 @@ async fn main() -> Result<()> {
     let app_config = config::Config::from_env()
         .map_err(|e| anyhow::anyhow!("Failed to load configuration: {}", e))?;
 
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
+    // Initialize tracing; `json` puts one object per line for log shippers
+    let logs = tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::try_from_default_env()
-            .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(&app_config.logging.level)))
-        .init();
+            .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(&app_config.logging.level)));
+    if app_config.logging.format == "json" {
+        logs.with(tracing_subscriber::fmt::layer().json().flatten_event(true).with_span_list(false))
+            .init();
+    } else {
+        logs.with(tracing_subscriber::fmt::layer()).init();
+    }
+
+    // Refuse to start on settings that aren't safe to run with
+    for warning in app_config.validate()? {
+        tracing::warn!("Configuration: {}", warning);
+    }
+
+    // `crm-server migrate|backup|restore|seed ...` runs once instead of serving
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = match args.first().map(String::as_str) {
+        Some("migrate") => Some(Command::Migrate(migrations::MigrateCommand::parse(&args[1..])?)),
+        Some("backup") | Some("restore") => Some(Command::Backup(backup::BackupCommand::parse(&args)?)),
+        Some("seed") => Some(Command::Seed(seed::SeedCommand::parse(&args[1..])?)),
+        Some(other) => anyhow::bail!("Unknown command '{}'; use `migrate`, `backup`, `restore` or `seed`", other),
+        None => None,
+    };
 
     // Initialize database
     let db = Database::new(&app_config).await?;
-    db.init_schema().await?;
+    if let Some(Command::Migrate(command)) = command {
+        return migrations::run(&db, command).await;
+    }
+    migrations::migrate_up(&db).await?;
     let db = Arc::new(db);
+    match command {
+        Some(Command::Backup(command)) => return backup::run(db, &app_config, command).await,
+        Some(Command::Seed(command)) => return seed::run(db, &app_config, command).await,
+        _ => {}
+    }
+
+    // Read cache for hot lookups; a Redis URL, if used, comes from the secrets manager
+    let secrets = secrets::init_secrets_manager();
+    let read_cache = Arc::new(services::read_cache::build_read_cache(&app_config.cache, &secrets).await?);
+
+    // AI content generation; the provider's API key comes from the secrets manager
+    let content_generator = Arc::new(
+        ContentGenerator::new(ai::provider::build_provider(&app_config.ai, &secrets)?).with_usage_tracking(
+            repositories::AiUsageRepository::new(Arc::clone(&db)),
+            ai::provider::Pricing::from_config(&app_config.ai),
+        )
+        .with_sentiment_inference(app_config.ai.infer_sentiment),
+    );
 
     // Initialize services
-    let contact_service = Arc::new(ContactService::new(Arc::clone(&db)));
+    let feed_service = Arc::new(FeedService::new());
+    let engagement_service = Arc::new(EngagementService::new(Arc::clone(&db)));
+    let timeline_service = Arc::new(TimelineService::new(
+        Arc::clone(&db),
+        Arc::clone(&content_generator),
+        Arc::clone(&feed_service),
+        Arc::clone(&engagement_service),
+        Arc::clone(&read_cache),
+    ));
+    let contact_service = Arc::new(ContactService::new(
+        Arc::clone(&db),
+        Arc::clone(&feed_service),
+        Arc::clone(&timeline_service),
+        Arc::clone(&read_cache),
+    ));
+    let contact_live_service = Arc::new(ContactLiveService::new(Arc::clone(&db), Arc::clone(&feed_service)));
+    let analytics_service = Arc::new(AnalyticsService::new(Arc::clone(&db), Arc::clone(&read_cache)));
+    let audit_service = Arc::new(AuditService::new(Arc::clone(&db)));
+    let auth_service = Arc::new(AuthService::new(Arc::clone(&db), &app_config.jwt));
+    let backup_service = Arc::new(BackupService::new(Arc::clone(&db), Arc::clone(&read_cache)));
+    let contact_report_service = Arc::new(ContactReportService::new(Arc::clone(&db), Arc::clone(&engagement_service)));
+    let event_service = Arc::new(EventService::new(
+        Arc::clone(&db),
+        Arc::clone(&feed_service),
+        Arc::clone(&timeline_service),
+        &app_config.events,
+    ));
+    let idempotency_service = Arc::new(IdempotencyService::new(Arc::clone(&db)));
+    let import_service = Arc::new(ImportService::new(
+        Arc::clone(&db),
+        Arc::clone(&feed_service),
+        Arc::clone(&timeline_service),
+    ));
+    let inbound_service = Arc::new(InboundService::new(
+        Arc::clone(&db),
+        Arc::clone(&feed_service),
+        Arc::clone(&timeline_service),
+    ));
+    let search_service = Arc::new(SearchService::new(Arc::clone(&db)));
+    let relationship_service = Arc::new(RelationshipService::new(Arc::clone(&db)));
+    let segment_service = Arc::new(SegmentService::new(Arc::clone(&db), Arc::clone(&read_cache)));
+    let campaign_template_service = Arc::new(CampaignTemplateService::new(Arc::clone(&db)));
+    let sequence_service = Arc::new(SequenceService::new(Arc::clone(&db), Arc::clone(&timeline_service)));
+    let tracking_service = Arc::new(TrackingService::new(
+        Arc::clone(&db),
+        &app_config.tracking,
+        Arc::clone(&timeline_service),
+    ));
+    let webhook_service = Arc::new(WebhookService::new(Arc::clone(&db)));
+    let landing_page_service = Arc::new(LandingPageService::new(
+        Arc::clone(&db),
+        Arc::clone(&timeline_service),
+        Arc::clone(&feed_service),
+        Arc::clone(&webhook_service),
+    ));
+
+    let campaign_asset_service = Arc::new(CampaignAssetService::new(Arc::clone(&db), Arc::clone(&content_generator)));
+    let assistant_service = Arc::new(AssistantService::new(
+        Arc::clone(&db),
+        Arc::clone(&content_generator),
+        Arc::clone(&timeline_service),
+        Arc::clone(&read_cache),
+        &app_config.ai,
+    ));
+    let recommendation_service = Arc::new(RecommendationService::new(
+        Arc::clone(&db),
+        Arc::clone(&content_generator),
+        Arc::clone(&read_cache),
+        &app_config.ai,
+    ));
+
+    // Connected accounts' credentials are encrypted with a key from the secrets manager
+    let credential_cipher =
+        services::credential_cipher::CredentialCipher::from_config(&app_config.credentials, &secrets).map(Arc::new);
+    // Social publishing; each workspace connects its own platform accounts
+    let social_service = Arc::new(SocialService::new(
+        Arc::clone(&db),
+        Arc::clone(&timeline_service),
+        services::social_publisher::build_publishers(&app_config.social)?,
+        credential_cipher.clone(),
+        &app_config.social,
+    ));
+    // LinkedIn enrichment; the provider's API key comes from the secrets manager
+    let enrichment_service = Arc::new(EnrichmentService::new(
+        Arc::clone(&db),
+        Arc::clone(&timeline_service),
+        services::enrichment_provider::build_enrichment_provider(&app_config.enrichment, &secrets)?,
+    ));
+    // Inbound email sync; the Gmail OAuth client secret comes from the secrets manager
+    let inbox_service = Arc::new(InboxService::new(
+        Arc::clone(&db),
+        Arc::clone(&timeline_service),
+        services::mailbox::GmailClient::from_config(&app_config.inbox, &secrets)?,
+        credential_cipher.clone(),
+        &app_config.inbox,
+    ));
+    // Attachment files and avatars; S3 access keys come from the secrets manager
+    let object_storage = services::object_storage::build_object_storage(&app_config.attachments.storage, &secrets)?;
+    let attachment_service = Arc::new(AttachmentService::new(
+        Arc::clone(&db),
+        Arc::clone(&object_storage),
+        &app_config.attachments,
+    ));
+    let avatar_service = Arc::new(AvatarService::new(
+        Arc::clone(&db),
+        Arc::clone(&object_storage),
+        &app_config.tracking.base_url,
+        &app_config.avatars,
+    ));
+    let trash_service = Arc::new(TrashService::new(
+        Arc::clone(&db),
+        Arc::clone(&object_storage),
+        Arc::clone(&avatar_service),
+        &app_config.trash,
+    ));
+    let gdpr_service = Arc::new(GdprService::new(Arc::clone(&db), object_storage, Arc::clone(&avatar_service)));
+    let pipeline_service = Arc::new(PipelineService::new(
+        Arc::clone(&db),
+        Arc::clone(&contact_service),
+        Arc::clone(&audit_service),
+        Arc::clone(&read_cache),
+    ));
+    let deal_service = Arc::new(DealService::new(
+        Arc::clone(&db),
+        Arc::clone(&audit_service),
+        Arc::clone(&read_cache),
+    ));
+    let digest_service = Arc::new(DigestService::new(Arc::clone(&db), &app_config.digest));
+    // Contact embeddings for duplicate suggestions; an OpenAI key, if used, comes from the secrets manager
+    let dedupe_service = Arc::new(DedupeService::new(
+        Arc::clone(&db),
+        ai::embedding::build_embedder(&app_config.dedupe, &secrets)?,
+        Arc::clone(&contact_service),
+        &app_config.dedupe,
+    ));
+    // Landing page spam protection; the CAPTCHA secret key comes from the secrets manager
+    let spam_guard = Arc::new(SpamGuard::new(&app_config.landing_pages, &secrets)?);
+    // Bounce and complaint callbacks; their shared token comes from the secrets manager
+    let deliverability_service = Arc::new(DeliverabilityService::new(
+        Arc::clone(&db),
+        Arc::clone(&timeline_service),
+        &app_config.email_events,
+        &secrets,
+    )?);
+    // Sender identities; their domains' SPF and DKIM records are read over DNS-over-HTTPS
+    let sender_service = Arc::new(SenderService::new(Arc::clone(&db), &app_config.senders)?);
+    let campaign_scheduler = Arc::new(CampaignScheduler::new(
+        Arc::clone(&db),
+        Arc::clone(&segment_service),
+        Arc::clone(&sender_service),
+        Arc::clone(&social_service),
+        Arc::clone(&timeline_service),
+    ));
+
+    // Background jobs stop at the end of their current pass once `jobs` is triggered
+    let jobs = shutdown::Shutdown::default();
+    let job_handles = vec![
+        // Background webhook delivery
+        WebhookDispatcher::new(Arc::clone(&db), &app_config.webhooks)?.spawn(jobs.clone()),
+        // Periodic engagement score recalculation
+        Arc::clone(&engagement_service).spawn_recalculation(&app_config.engagement, jobs.clone()),
+        // Periodic saved segment member counts
+        Arc::clone(&segment_service).spawn_count_refresh(&app_config.segments, jobs.clone()),
+        // Contact changes pushed to activity feed subscribers
+        Arc::clone(&contact_live_service).spawn(jobs.clone()),
+        // Permanently remove trashed records past retention
+        Arc::clone(&trash_service).spawn_purge(&app_config.trash, jobs.clone()),
+        // Forget expired Idempotency-Keys
+        Arc::clone(&idempotency_service).spawn_cleanup(jobs.clone()),
+        // Start scheduled campaigns and send as recipients' send windows open
+        Arc::clone(&campaign_scheduler).spawn(&app_config.scheduler, jobs.clone()),
+        // Advance drip sequence enrollments whose next step is due
+        Arc::clone(&sequence_service).spawn_runner(&app_config.sequences, jobs.clone()),
+        // Remind registered contacts of upcoming events and follow up afterwards
+        Arc::clone(&event_service).spawn_reminders(&app_config.events, jobs.clone()),
+        // Publish social posts whose time has come
+        Arc::clone(&social_service).spawn(jobs.clone()),
+        // Pull new email from connected mailboxes onto contact timelines
+        Arc::clone(&inbox_service).spawn(&app_config.inbox, jobs.clone()),
+        // Send members their weekly digest at the day and hour they picked
+        Arc::clone(&digest_service).spawn(jobs.clone()),
+        // Embed contacts and queue likely duplicates for review
+        Arc::clone(&dedupe_service).spawn(jobs.clone()),
+        // Rate the sentiment of notes and received email queued when they were recorded
+        Arc::clone(&timeline_service).spawn_sentiment(&app_config.ai, jobs.clone()),
+    ];
+
+    // Stop serving on SIGTERM or Ctrl-C
+    let stopping = shutdown::Shutdown::default();
+    tokio::spawn({
+        let stopping = stopping.clone();
+        async move {
+            shutdown::signal().await;
+            stopping.trigger();
+        }
+    });
 
     let state = AppState {
+        config: Arc::new(app_config.clone()),
+        shutdown: stopping.clone(),
         db,
+        read_cache,
+        content_generator,
         contact_service,
+        contact_live_service,
+        contact_report_service,
+        analytics_service,
+        assistant_service,
+        attachment_service,
+        audit_service,
+        auth_service,
+        avatar_service,
+        backup_service,
+        campaign_asset_service,
+        campaign_scheduler,
+        campaign_template_service,
+        deal_service,
+        dedupe_service,
+        deliverability_service,
+        digest_service,
+        engagement_service,
+        enrichment_service,
+        event_service,
+        feed_service,
+        gdpr_service,
+        idempotency_service,
+        import_service,
+        inbound_service,
+        inbox_service,
+        landing_page_service,
+        pipeline_service,
+        recommendation_service,
+        relationship_service,
+        search_service,
+        segment_service,
+        sender_service,
+        sequence_service,
+        social_service,
+        spam_guard,
+        timeline_service,
+        tracking_service,
+        trash_service,
+        webhook_service,
     };
 
-    // CORS configuration
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+    // CORS and security headers, per environment
+    let cors = middleware::cors_layer(&app_config.cors, app_config.run_mode.is_strict())?;
+    let security_headers = middleware::SecurityHeaders::new(&app_config.security_headers)?;
 
-    // Build router
-    let app = Router::new()
+    // Retried POSTs carrying an Idempotency-Key get the first response back
+    let idempotent = || axum::middleware::from_fn_with_state(state.clone(), middleware::idempotency);
+
+    // Public routes - no authentication required
+    let public_routes = Router::new()
         // Health check
         .route("/health", get(handlers::health::health_check))
+        // Auth
+        .route("/api/auth/register", post(handlers::auth::register))
+        .route("/api/auth/login", post(handlers::auth::login))
+        .route("/api/auth/refresh", post(handlers::auth::refresh))
+        // Landing Pages (served to visitors)
+        .route("/lp/:id", get(handlers::landing_pages::get_landing_page))
+        .route("/lp/:id/submit", post(handlers::landing_pages::submit_landing_page_form).layer(idempotent()))
+        // Email tracking (signed tokens, hit from recipients' inboxes)
+        .route("/t/open/:token", get(handlers::tracking::track_open))
+        .route("/t/click/:token", get(handlers::tracking::track_click))
+        .route("/unsubscribe/:token", get(handlers::tracking::unsubscribe_page))
+        .route("/unsubscribe/:token", post(handlers::tracking::unsubscribe))
+        // Short links (the code is the credential, shared in emails and social posts)
+        .route("/r/:code", get(handlers::tracking::follow_short_link))
+        // Contact avatars (the random avatar ID in the path keeps them private)
+        .route("/avatars/:workspace_id/:avatar_id", get(handlers::avatars::get_avatar))
+        // Inbound webhooks (the source token in the path is the credential)
+        .route("/api/inbound/:source_token", post(handlers::inbound::ingest))
+        // Bounce and complaint callbacks (the shared token in the path is the credential)
+        .route("/api/email-events/:provider/:token", post(handlers::email_events::receive))
+        // Gmail OAuth callback (Google redirects the browser here)
+        .route("/api/integrations/gmail/callback", get(handlers::integrations::gmail_callback))
+        // Live updates (a one-time ticket or bearer token, checked by the handler)
+        .route("/ws/contacts", get(handlers::live::contacts_socket))
+        .route_layer(axum::middleware::from_fn_with_state(
+            middleware::PayloadLimits::new(app_config.limits.public_body_bytes, &app_config.limits),
+            middleware::payload_limits,
+        ))
+        .layer(DefaultBodyLimit::max(app_config.limits.public_body_bytes));
+
+    // Protected routes - require a valid access token
+    let api_routes = Router::new()
+        .route("/api/auth/me", get(handlers::auth::me))
+        // Workspace
+        .route("/api/workspace", get(handlers::workspaces::get_workspace))
+        .route("/api/workspace", patch(handlers::workspaces::update_workspace))
+        .route("/api/workspace/backup", get(handlers::workspaces::backup_workspace))
+        .route("/api/workspace/restore", post(handlers::workspaces::restore_workspace))
+        // Search
+        .route("/api/search", get(handlers::search::search))
+        // Activity feed
+        .route("/api/feed/stream", get(handlers::feed::stream_feed))
+        .route("/api/live/tickets", post(handlers::live::create_live_ticket))
         // Contacts
         .route("/api/contacts", get(handlers::contacts::list_contacts))
-        .route("/api/contacts", post(handlers::contacts::create_contact))
+        .route("/api/contacts", post(handlers::contacts::create_contact).layer(idempotent()))
+        .route("/api/contacts/count", get(handlers::contacts::count_contacts))
+        .route("/api/contacts/tags", get(handlers::contacts::list_tags))
+        .route("/api/contacts/export", get(handlers::contacts::export_contacts))
+        .route("/api/contacts/duplicates", get(handlers::contacts::find_duplicates))
+        .route("/api/contacts/dedupe-suggestions", get(handlers::contacts::list_dedupe_suggestions))
+        .route("/api/contacts/dedupe-suggestions/:id/accept", post(handlers::contacts::accept_dedupe_suggestion))
+        .route("/api/contacts/dedupe-suggestions/:id/dismiss", post(handlers::contacts::dismiss_dedupe_suggestion))
+        .route("/api/contacts/batch-get", post(handlers::contacts::batch_get_contacts))
+        .route("/api/contacts/bulk", post(handlers::contacts::bulk_update_contacts))
+        .route("/api/contacts/merge", post(handlers::contacts::merge_contacts))
         .route("/api/contacts/:id", get(handlers::contacts::get_contact))
         .route("/api/contacts/:id", patch(handlers::contacts::update_contact))
         .route("/api/contacts/:id", delete(handlers::contacts::delete_contact))
+        .route("/api/contacts/:id/restore", post(handlers::contacts::restore_contact))
+        .route("/api/contacts/:id/churn", post(handlers::contacts::churn_contact))
         .route("/api/contacts/:id/timeline", get(handlers::timeline::get_contact_timeline))
+        .route("/api/contacts/:id/threads", get(handlers::timeline::get_contact_threads))
+        .route("/api/contacts/:id/engagement", get(handlers::contacts::get_contact_engagement))
+        .route("/api/contacts/:id/engagement/breakdown", get(handlers::contacts::get_engagement_breakdown))
+        .route("/api/contacts/:id/status-history", get(handlers::contacts::get_status_history))
+        .route("/api/contacts/:id/summary", get(handlers::contacts::get_contact_summary))
+        .route("/api/contacts/:id/report", get(handlers::contacts::get_contact_report))
+        .route("/api/contacts/:id/prep-brief", post(handlers::contacts::create_prep_brief))
+        .route("/api/contacts/:id/recalculate-engagement", post(handlers::contacts::recalculate_engagement))
+        .route("/api/contacts/:id/enrich", post(handlers::contacts::enrich_contact))
+        .route(
+            "/api/contacts/:id/avatar",
+            put(handlers::avatars::upload_contact_avatar).layer(DefaultBodyLimit::max(app_config.avatars.max_size_bytes)),
+        )
+        .route("/api/contacts/:id/avatar", delete(handlers::avatars::delete_contact_avatar))
+        // AI drafting
+        .route("/api/ai/draft-reply", post(handlers::ai::draft_reply))
+        // Next best actions
+        .route("/api/recommendations", get(handlers::recommendations::list_recommendations))
+        // GDPR data subject requests
+        .route("/api/contacts/:id/gdpr-export", post(handlers::gdpr::export_contact_data))
+        .route("/api/contacts/:id/gdpr-erase", post(handlers::gdpr::erase_contact_data))
+        .route("/api/gdpr/erasures", get(handlers::gdpr::list_erasures))
         // Companies
         .route("/api/companies", get(handlers::companies::list_companies))
         .route("/api/companies", post(handlers::companies::create_company))
         .route("/api/companies/:id", get(handlers::companies::get_company))
         .route("/api/companies/:id", patch(handlers::companies::update_company))
         .route("/api/companies/:id", delete(handlers::companies::delete_company))
+        .route("/api/companies/:id/restore", post(handlers::companies::restore_company))
+        // Trash
+        .route("/api/trash", get(handlers::trash::list_trash))
         // Timeline
+        .route("/api/timeline", get(handlers::timeline::list_timeline))
         .route("/api/timeline", post(handlers::timeline::create_timeline_entry))
+        .route("/api/timeline/daily", get(handlers::timeline::daily_activity))
+        // Attachments; uploads are the raw file, up to the configured size
+        .route("/api/contacts/:id/attachments", get(handlers::attachments::list_contact_attachments))
+        .route(
+            "/api/contacts/:id/attachments",
+            post(handlers::attachments::upload_contact_attachment)
+                .layer(DefaultBodyLimit::max(app_config.attachments.max_size_bytes)),
+        )
+        .route(
+            "/api/timeline/:id/attachments",
+            post(handlers::attachments::upload_timeline_attachment)
+                .layer(DefaultBodyLimit::max(app_config.attachments.max_size_bytes)),
+        )
+        .route("/api/attachments/:id/download", get(handlers::attachments::download_attachment))
+        .route("/api/attachments/:id", delete(handlers::attachments::delete_attachment))
+        // Relationships
+        .route("/api/relationships", post(handlers::relationships::create_relationship))
+        .route("/api/relationships/:kind/:id", delete(handlers::relationships::delete_relationship))
+        .route("/api/contacts/:id/relationships", get(handlers::relationships::list_contact_relationships))
+        .route("/api/companies/:id/relationships", get(handlers::relationships::list_company_relationships))
+        .route("/api/contacts/:id/network", get(handlers::relationships::get_contact_network))
+        // Deals
+        .route("/api/deals", get(handlers::deals::list_deals))
+        .route("/api/deals", post(handlers::deals::create_deal))
+        .route("/api/deals/:id", get(handlers::deals::get_deal))
+        .route("/api/deals/:id", patch(handlers::deals::update_deal))
+        .route("/api/deals/:id", delete(handlers::deals::delete_deal))
+        .route("/api/deals/:id/stage", post(handlers::deals::update_deal_stage))
+        // Pipeline board
+        .route("/api/pipeline/board", get(handlers::pipeline::get_board))
+        .route("/api/pipeline/move", patch(handlers::pipeline::move_card))
+        // Digest
+        .route("/api/digest/preferences", get(handlers::digest::get_preferences))
+        .route("/api/digest/preferences", put(handlers::digest::update_preferences))
+        .route("/api/digest/preview", get(handlers::digest::preview))
+        // Segments
+        .route("/api/segments", get(handlers::segments::list_segments))
+        .route("/api/segments", post(handlers::segments::create_segment))
+        .route("/api/segments/preview", post(handlers::segments::preview_definition))
+        .route("/api/segments/:id", get(handlers::segments::get_segment))
+        .route("/api/segments/:id", patch(handlers::segments::update_segment))
+        .route("/api/segments/:id", delete(handlers::segments::delete_segment))
+        .route("/api/segments/:id/preview", get(handlers::segments::preview_segment))
+        .route("/api/senders", get(handlers::senders::list_senders))
+        .route("/api/senders", post(handlers::senders::create_sender))
+        .route("/api/senders/:id", get(handlers::senders::get_sender))
+        .route("/api/senders/:id", patch(handlers::senders::update_sender))
+        .route("/api/senders/:id", delete(handlers::senders::delete_sender))
+        .route("/api/senders/:id/verify", post(handlers::senders::verify_sender))
+        .route("/api/sequences", get(handlers::sequences::list_sequences))
+        .route("/api/sequences", post(handlers::sequences::create_sequence))
+        .route("/api/sequences/:id", get(handlers::sequences::get_sequence))
+        .route("/api/sequences/:id", delete(handlers::sequences::delete_sequence))
+        .route("/api/sequences/:id/enrollments", get(handlers::sequences::list_enrollments))
+        .route("/api/sequences/:id/enroll", post(handlers::sequences::enroll_contacts))
+        .route("/api/sequences/:id/unenroll", post(handlers::sequences::unenroll_contacts))
         // Campaigns
         .route("/api/campaigns", get(handlers::campaigns::list_campaigns))
         .route("/api/campaigns", post(handlers::campaigns::create_campaign))
@@ -118,31 +576,133 @@ async fn main() -> Result<()> {
         .route("/api/campaigns/:id", patch(handlers::campaigns::update_campaign))
         .route("/api/campaigns/:id/assets", get(handlers::campaigns::list_campaign_assets))
         .route("/api/campaigns/:id/assets", post(handlers::campaigns::generate_campaign_assets))
-        .route("/api/campaigns/:id/execute", post(handlers::campaigns::execute_campaign))
+        .route("/api/campaigns/:id/assets/:asset_id", patch(handlers::campaigns::update_campaign_asset))
+        .route("/api/campaigns/:id/assets/:asset_id/regenerate", post(handlers::campaigns::regenerate_campaign_asset))
+        .route("/api/campaigns/:id/execute", post(handlers::campaigns::execute_campaign).layer(idempotent()))
+        .route("/api/campaigns/:id/schedule", post(handlers::campaigns::schedule_campaign))
+        .route("/api/campaigns/:id/cancel", post(handlers::campaigns::cancel_campaign))
+        .route("/api/campaigns/:id/clone", post(handlers::campaigns::clone_campaign))
+        .route("/api/campaigns/:id/template", post(handlers::campaigns::save_campaign_template))
+        .route("/api/campaign-templates", get(handlers::campaign_templates::list_templates))
+        .route("/api/campaign-templates/:id", get(handlers::campaign_templates::get_template))
+        .route("/api/campaign-templates/:id", delete(handlers::campaign_templates::delete_template))
+        .route("/api/campaign-templates/:id/instantiate", post(handlers::campaign_templates::instantiate_template))
+        .route("/api/campaigns/:id/recipients", get(handlers::campaigns::list_campaign_recipients))
+        .route("/api/campaigns/:id/preview-email", post(handlers::campaigns::preview_campaign_email))
+        .route("/api/campaigns/:id/social-posts", get(handlers::campaigns::list_social_posts))
+        .route("/api/campaigns/:id/social-posts", post(handlers::campaigns::schedule_social_post))
+        .route("/api/campaigns/:id/short-links", get(handlers::campaigns::list_short_links))
+        .route("/api/campaigns/:id/short-links", post(handlers::campaigns::create_short_links))
         // Landing Pages
         .route("/api/landing-pages/generate", post(handlers::landing_pages::generate_landing_page))
-        .route("/lp/:id", get(handlers::landing_pages::get_landing_page))
-        .route("/lp/:id/submit", post(handlers::landing_pages::submit_landing_page_form))
         // Events
         .route("/api/events", get(handlers::events::list_events))
         .route("/api/events", post(handlers::events::create_event))
         .route("/api/events/:id", get(handlers::events::get_event))
         .route("/api/events/:id/invite", post(handlers::events::invite_to_event))
         .route("/api/events/:id/rsvp", post(handlers::events::rsvp_event))
+        .route("/api/events/:id/checkin", post(handlers::events::check_in))
+        .route("/api/events/:id/checkin/bulk", post(handlers::events::bulk_check_in))
+        .route(
+            "/api/events/:id/checkin/token/:contact_id",
+            get(handlers::events::checkin_token),
+        )
+        // Webhooks
+        .route("/api/webhooks", get(handlers::webhooks::list_webhooks))
+        .route("/api/webhooks", post(handlers::webhooks::create_webhook))
+        .route("/api/webhooks/:id", get(handlers::webhooks::get_webhook))
+        .route("/api/webhooks/:id", delete(handlers::webhooks::delete_webhook))
+        .route("/api/webhooks/:id/deliveries", get(handlers::webhooks::list_deliveries))
+        .route("/api/webhooks/:id/deliveries/:delivery_id", get(handlers::webhooks::get_delivery))
+        // Inbound sources
+        .route("/api/inbound-sources", get(handlers::inbound::list_inbound_sources))
+        .route("/api/inbound-sources", post(handlers::inbound::create_inbound_source))
+        .route("/api/inbound-sources/:id", patch(handlers::inbound::update_inbound_source))
+        .route("/api/inbound-sources/:id", delete(handlers::inbound::delete_inbound_source))
+        .route("/api/import/jobs", get(handlers::imports::list_import_jobs))
+        .route("/api/import/jobs/:id", get(handlers::imports::get_import_job))
+        // Integrations
+        .route("/api/integrations", get(handlers::integrations::list_integrations))
+        .route("/api/integrations/imap", post(handlers::integrations::create_imap_integration))
+        .route("/api/integrations/gmail", post(handlers::integrations::create_gmail_integration))
+        .route("/api/integrations/:id", patch(handlers::integrations::update_integration))
+        .route("/api/integrations/:id", delete(handlers::integrations::delete_integration))
+        .route("/api/integrations/:id/sync", post(handlers::integrations::sync_integration))
+        .route("/api/integrations/social", get(handlers::integrations::list_social_accounts))
+        .route("/api/integrations/social/:platform", put(handlers::integrations::connect_social_account))
+        .route("/api/integrations/social/:platform", delete(handlers::integrations::disconnect_social_account))
+        // Audit log
+        .route("/api/audit", get(handlers::audit::list_audit_log))
+        .route("/api/audit/:entity_type/:entity_id", get(handlers::audit::get_entity_history))
         // Analytics
         .route("/api/analytics/campaign/:id", get(handlers::analytics::campaign_analytics))
+        .route("/api/analytics/campaign/:id/deliverability", get(handlers::analytics::campaign_deliverability))
+        .route("/api/analytics/event/:id", get(handlers::analytics::event_analytics))
         .route("/api/analytics/contacts", get(handlers::analytics::contacts_analytics))
         .route("/api/analytics/funnel", get(handlers::analytics::funnel_analytics))
-        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
+        .route("/api/analytics/churn", get(handlers::analytics::churn_analytics))
+        .route("/api/analytics/win-loss", get(handlers::analytics::win_loss_analytics))
+        .route("/api/analytics/response-times", get(handlers::analytics::response_time_analytics))
+        .route("/api/analytics/pipeline", get(handlers::analytics::pipeline_analytics))
+        .route("/api/analytics/pipeline/regions", get(handlers::analytics::pipeline_region_analytics))
+        .route("/api/analytics/ai-usage", get(handlers::analytics::ai_usage_analytics))
+        .route("/api/analytics/sentiment", get(handlers::analytics::sentiment_analytics))
+        .route_layer(axum::middleware::from_fn_with_state(
+            middleware::PayloadLimits::new(app_config.limits.api_body_bytes, &app_config.limits),
+            middleware::payload_limits,
+        ))
+        // One-time migration from another CRM; exports are large, so this is
+        // added after the payload limits and only has a size limit of its own
+        .route(
+            "/api/import/hubspot",
+            post(handlers::imports::import_hubspot)
+                .layer(DefaultBodyLimit::max(app_config.limits.import_body_bytes)),
+        )
+        .route(
+            "/api/import/pipedrive",
+            post(handlers::imports::import_pipedrive)
+                .layer(DefaultBodyLimit::max(app_config.limits.import_body_bytes)),
+        )
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::require_auth,
+        ))
+        .layer(DefaultBodyLimit::max(app_config.limits.api_body_bytes));
+
+    // Build router
+    let app = Router::new()
+        .merge(public_routes)
+        .merge(api_routes)
+        .merge(SwaggerUi::new("/docs").url("/api/openapi.json", openapi::ApiDoc::openapi()))
         .layer(cors)
-        .layer(TraceLayer::new_for_http())
+        .layer(axum::middleware::from_fn_with_state(security_headers, middleware::security_headers))
+        .layer(TraceLayer::new_for_http().make_span_with(middleware::request_span))
+        .layer(axum::middleware::from_fn(middleware::request_id))
         .with_state(state);
 
     let addr = format!("{}:{}", app_config.server.host, app_config.server.port);
     tracing::info!("Starting CRM server on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    // Peer addresses feed landing page rate limits
+    let server = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(stopping.triggered());
+
+    // In-flight requests get the grace period to finish, then background jobs do
+    let grace = Duration::from_secs(app_config.server.shutdown_timeout_secs);
+    tokio::select! {
+        result = server.into_future() => result?,
+        _ = async {
+            stopping.triggered().await;
+            tokio::time::sleep(grace).await;
+        } => tracing::warn!("Requests still running after {}s; closing their connections", grace.as_secs()),
+    }
+
+    jobs.trigger();
+    if !shutdown::join(job_handles, grace).await {
+        tracing::warn!("Background jobs still running after {}s; stopping them mid-pass", grace.as_secs());
+    }
+    tracing::info!("Shut down");
 
     Ok(())
 }
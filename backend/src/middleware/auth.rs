@@ -0,0 +1,104 @@
+//! Authentication middleware
+//!
+//! `require_auth` validates the bearer token on every protected route and
+//! stores the caller's identity in the request extensions. Handlers read it
+//! back with the `CurrentUser` extractor:
+//!
+//! ```ignore
+//! pub async fn handler(CurrentUser(user): CurrentUser) -> ... {
+//!     tracing::info!("called by {}", user.user_id);
+//! }
+//! ```
+
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request, State},
+    http::{header, request::Parts, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::error::AppError;
+use crate::services::AuthenticatedUser;
+use crate::AppState;
+
+/// Extractor for the authenticated user of the current request
+#[derive(Debug, Clone)]
+pub struct CurrentUser(pub AuthenticatedUser);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for CurrentUser
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<AuthenticatedUser>()
+            .cloned()
+            .map(CurrentUser)
+            .ok_or_else(|| AppError::Unauthorized("Authentication required".into()))
+    }
+}
+
+/// Reject requests without a valid access token
+pub async fn require_auth(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let token = bearer_token(&request)
+        .ok_or_else(|| AppError::Unauthorized("Missing bearer token".into()))?;
+
+    let user = state.auth_service.verify_access_token(token)?;
+    request.extensions_mut().insert(user);
+
+    Ok(next.run(request).await)
+}
+
+fn bearer_token(request: &Request) -> Option<&str> {
+    bearer_token_from(request.headers())
+}
+
+/// The token of an `Authorization: Bearer ...` header, for routes that
+/// authenticate outside `require_auth`
+pub fn bearer_token_from(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    fn request(authorization: Option<&str>) -> Request {
+        let mut request = axum::http::Request::get("/");
+        if let Some(value) = authorization {
+            request = request.header(header::AUTHORIZATION, value);
+        }
+        request.body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn test_bearer_token() {
+        assert_eq!(bearer_token(&request(Some("Bearer abc.def"))), Some("abc.def"));
+        assert_eq!(bearer_token(&request(Some("Bearer  abc "))), Some("abc"));
+        assert_eq!(bearer_token(&request(Some("Bearer "))), None);
+        assert_eq!(bearer_token(&request(Some("Basic dXNlcg=="))), None);
+        assert_eq!(bearer_token(&request(None)), None);
+    }
+
+    #[tokio::test]
+    async fn test_current_user_requires_auth() {
+        let (mut parts, _) = request(None).into_parts();
+        let result = CurrentUser::from_request_parts(&mut parts, &()).await;
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    }
+}
@@ -0,0 +1,101 @@
+//! Idempotency middleware
+//!
+//! Layered onto individual POST routes. Requests without an
+//! `Idempotency-Key` header pass straight through; with one, the first
+//! response (unless it is a client error) is stored and replayed for
+//! retries, marked with `Idempotent-Replayed: true`.
+//!
+//! On protected routes the layer must sit inside `require_auth`, so keys
+//! are scoped to the caller's workspace.
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::error::AppError;
+use crate::repositories::workspace_scope;
+use crate::services::{fingerprint, AuthenticatedUser, IdempotencyOutcome, StoredResponse};
+use crate::AppState;
+
+pub const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+pub const IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+/// Largest request body accepted on an idempotent route
+const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+
+/// Run a request at most once per Idempotency-Key
+pub async fn idempotency(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let Some(key) = request.headers().get(&IDEMPOTENCY_KEY) else {
+        return Ok(next.run(request).await);
+    };
+    let key = key
+        .to_str()
+        .map_err(|_| AppError::BadRequest("Idempotency-Key must be visible ASCII".into()))?
+        .trim()
+        .to_string();
+
+    let scope = match request.extensions().get::<AuthenticatedUser>() {
+        Some(user) => workspace_scope(&user.workspace_id),
+        None => "public".to_string(),
+    };
+
+    let (parts, body) = request.into_parts();
+    let body = to_bytes(body, MAX_REQUEST_BYTES)
+        .await
+        .map_err(|_| AppError::BadRequest("Request body too large".into()))?;
+    let request_hash = fingerprint(parts.method.as_str(), parts.uri.path(), &body);
+
+    let service = &state.idempotency_service;
+    if let IdempotencyOutcome::Replay(stored) = service.begin(&scope, &key, &request_hash).await? {
+        return Ok(replay(stored));
+    }
+
+    let response = service
+        .hold(&scope, &key, next.run(Request::from_parts(parts, Body::from(body))))
+        .await;
+
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            service.release(&scope, &key).await?;
+            return Err(AppError::Internal(format!("Failed to read response body: {}", e)));
+        }
+    };
+
+    let stored = StoredResponse {
+        status: parts.status.as_u16(),
+        body: String::from_utf8_lossy(&body).into_owned(),
+        content_type: parts
+            .headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+    };
+    if let Err(e) = service.finish(&scope, &key, stored).await {
+        tracing::error!("Failed to store idempotent response for key {}: {}", key, e);
+    }
+
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+fn replay(stored: StoredResponse) -> Response {
+    let mut response = Response::new(Body::from(stored.body));
+    *response.status_mut() = StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK);
+
+    let headers = response.headers_mut();
+    if let Some(content_type) = stored.content_type.and_then(|ct| HeaderValue::from_str(&ct).ok()) {
+        headers.insert(header::CONTENT_TYPE, content_type);
+    }
+    headers.insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+
+    response
+}
@@ -0,0 +1,17 @@
+//! Middleware - Cross-cutting HTTP concerns
+//!
+//! Layers applied to the router (authentication, idempotency, payload limits,
+//! request IDs, CORS and security headers) and the extractors that expose
+//! what they attach to each request.
+
+pub mod auth;
+pub mod idempotency;
+pub mod payload;
+pub mod request_id;
+pub mod security;
+
+pub use auth::*;
+pub use idempotency::*;
+pub use payload::*;
+pub use request_id::*;
+pub use security::*;
@@ -0,0 +1,196 @@
+//! Payload limits
+//!
+//! Layered onto each route group with that group's body size. JSON bodies
+//! are read up to the limit (413 past it) and their shape is checked before
+//! any extractor sees them: nesting, array lengths and object key counts
+//! past `limits.json_*` are a 422 naming the offending field. Malformed JSON
+//! is left for the extractor to reject; other bodies pass through, bounded
+//! by `DefaultBodyLimit`.
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+use serde_json::Value;
+
+use crate::config::LimitsConfig;
+use crate::error::{AppError, AppResult};
+
+/// The limits of one route group
+#[derive(Debug, Clone)]
+pub struct PayloadLimits {
+    max_bytes: usize,
+    max_depth: usize,
+    max_array_items: usize,
+    max_object_keys: usize,
+}
+
+impl PayloadLimits {
+    pub fn new(max_bytes: usize, config: &LimitsConfig) -> Self {
+        Self {
+            max_bytes,
+            max_depth: config.json_max_depth,
+            max_array_items: config.json_max_array_items,
+            max_object_keys: config.json_max_object_keys,
+        }
+    }
+
+    /// Check every array and object in `value`; `path` names it in errors
+    fn check(&self, value: &Value, path: &str, depth: usize) -> AppResult<()> {
+        let (len, max, unit) = match value {
+            Value::Array(items) => (items.len(), self.max_array_items, "items"),
+            Value::Object(fields) => (fields.len(), self.max_object_keys, "keys"),
+            _ => return Ok(()),
+        };
+        let name = if path.is_empty() { "The request body" } else { path };
+
+        if depth >= self.max_depth {
+            return Err(AppError::Validation(format!(
+                "{} is nested more than {} levels deep",
+                name, self.max_depth
+            )));
+        }
+        if len > max {
+            return Err(AppError::Validation(format!(
+                "{} has {} {}; at most {} are allowed",
+                name, len, unit, max
+            )));
+        }
+
+        match value {
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate().filter(|(_, item)| is_container(item)) {
+                    self.check(item, &format!("{}[{}]", path, i), depth + 1)?;
+                }
+            }
+            Value::Object(fields) => {
+                for (key, field) in fields.iter().filter(|(_, field)| is_container(field)) {
+                    let path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                    self.check(field, &path, depth + 1)?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// Enforce the route group's size and JSON shape limits
+pub async fn payload_limits(
+    State(limits): State<PayloadLimits>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if !is_json(request.headers()) {
+        return Ok(next.run(request).await);
+    }
+
+    let too_large = || {
+        AppError::PayloadTooLarge(format!(
+            "The request body is larger than the {} KiB allowed here",
+            limits.max_bytes / 1024
+        ))
+    };
+    let declared = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+    if declared.is_some_and(|length| length > limits.max_bytes) {
+        return Err(too_large());
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = to_bytes(body, limits.max_bytes).await.map_err(|_| too_large())?;
+    if let Ok(value) = serde_json::from_slice::<Value>(&bytes) {
+        limits.check(&value, "", 0)?;
+    }
+
+    Ok(next.run(Request::from_parts(parts, Body::from(bytes))).await)
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok()) else {
+        return false;
+    };
+    let mime = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+
+    mime == "application/json" || mime.ends_with("+json")
+}
+
+fn is_container(value: &Value) -> bool {
+    matches!(value, Value::Array(_) | Value::Object(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, middleware::from_fn_with_state, routing::post, Router};
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    fn limits() -> PayloadLimits {
+        let config = LimitsConfig {
+            json_max_depth: 3,
+            json_max_array_items: 2,
+            json_max_object_keys: 2,
+            ..LimitsConfig::default()
+        };
+        PayloadLimits::new(64, &config)
+    }
+
+    async fn send(content_type: &str, body: &str) -> StatusCode {
+        let app = Router::new()
+            .route("/", post(|| async { "ok" }))
+            .layer(from_fn_with_state(limits(), payload_limits));
+        let request = axum::http::Request::post("/")
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(body.to_string()))
+            .unwrap();
+
+        app.oneshot(request).await.unwrap().status()
+    }
+
+    #[test]
+    fn test_check_names_the_offending_field() {
+        let limits = limits();
+
+        assert!(limits.check(&json!({"a": [1, 2]}), "", 0).is_ok());
+        let err = limits.check(&json!({"a": {"b": [1, 2, 3]}}), "", 0).unwrap_err();
+        assert!(matches!(err, AppError::Validation(msg) if msg.starts_with("a.b has 3 items")));
+        let err = limits.check(&json!({"a": [{"b": [[1]]}]}), "", 0).unwrap_err();
+        assert!(matches!(err, AppError::Validation(msg) if msg.starts_with("a[0].b is nested")));
+        let err = limits.check(&json!({"a": 1, "b": 2, "c": 3}), "", 0).unwrap_err();
+        assert!(matches!(err, AppError::Validation(msg) if msg.starts_with("The request body has 3 keys")));
+    }
+
+    #[test]
+    fn test_is_json() {
+        let headers = |value: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::CONTENT_TYPE, value.parse().unwrap());
+            headers
+        };
+
+        assert!(is_json(&headers("application/json")));
+        assert!(is_json(&headers("Application/JSON; charset=utf-8")));
+        assert!(is_json(&headers("application/merge-patch+json")));
+        assert!(!is_json(&headers("text/plain")));
+        assert!(!is_json(&HeaderMap::new()));
+    }
+
+    #[tokio::test]
+    async fn test_payload_limits() {
+        assert_eq!(send("application/json", r#"{"a": [1, 2]}"#).await, StatusCode::OK);
+        assert_eq!(send("application/json", r#"{"a": [1, 2, 3]}"#).await, StatusCode::UNPROCESSABLE_ENTITY);
+        let large = format!(r#"{{"a": "{}"}}"#, "x".repeat(100));
+        assert_eq!(send("application/json", &large).await, StatusCode::PAYLOAD_TOO_LARGE);
+        // Malformed JSON is left for the extractor, other bodies pass through
+        assert_eq!(send("application/json", "{").await, StatusCode::OK);
+        assert_eq!(send("text/plain", "[1, 2, 3]").await, StatusCode::OK);
+    }
+}
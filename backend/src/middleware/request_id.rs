@@ -0,0 +1,126 @@
+//! Request IDs
+//!
+//! Every request gets an ID: the caller's `X-Request-Id` when it is a sane
+//! one, so IDs set by a load balancer carry through, or else a new UUID. It
+//! is sent back in the `X-Request-Id` response header, recorded on the
+//! request's tracing span (so every log line written while handling the
+//! request carries it), and included in error bodies.
+//!
+//! The layer must sit outside `TraceLayer`, whose span is made with
+//! `request_span`.
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Span;
+use uuid::Uuid;
+
+pub const REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest caller-supplied ID kept; longer ones are replaced
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: String;
+}
+
+/// The ID of a request, in its extensions
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Give the request an ID and echo it on the response
+pub async fn request_id(mut request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(&REQUEST_ID)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid(id))
+        .map(String::from)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    request.extensions_mut().insert(RequestId(id.clone()));
+
+    let mut response = CURRENT_REQUEST_ID.scope(id.clone(), next.run(request)).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID, value);
+    }
+
+    response
+}
+
+/// The ID of the request being handled on this task, if any
+pub fn current_request_id() -> Option<String> {
+    CURRENT_REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// The span a request is handled in, for `TraceLayer::make_span_with`
+///
+/// Only the path is recorded: query strings can carry tokens.
+pub fn request_span<B>(request: &axum::http::Request<B>) -> Span {
+    let id = request
+        .extensions()
+        .get::<RequestId>()
+        .map(|id| id.0.as_str())
+        .unwrap_or("-");
+
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        path = %request.uri().path(),
+        request_id = %id,
+    )
+}
+
+fn is_valid(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware::from_fn, routing::get, Router};
+    use tower::ServiceExt;
+
+    async fn send(id: Option<&str>) -> (String, String) {
+        let app = Router::new()
+            .route("/", get(|| async { current_request_id().unwrap_or_default() }))
+            .layer(from_fn(request_id));
+        let mut request = axum::http::Request::get("/");
+        if let Some(id) = id {
+            request = request.header(REQUEST_ID, id);
+        }
+
+        let response = app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+        let header = response.headers()[REQUEST_ID].to_str().unwrap().to_string();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (header, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[test]
+    fn test_is_valid() {
+        assert!(is_valid("lb-1234.abc_def:9"));
+        assert!(!is_valid(""));
+        assert!(!is_valid("has space"));
+        assert!(!is_valid("<script>"));
+        assert!(!is_valid(&"a".repeat(MAX_REQUEST_ID_LEN + 1)));
+    }
+
+    #[tokio::test]
+    async fn test_request_id() {
+        let (header, body) = send(Some("lb-1234")).await;
+        assert_eq!(header, "lb-1234");
+        assert_eq!(body, "lb-1234");
+
+        let (header, body) = send(Some("not valid")).await;
+        assert!(Uuid::parse_str(&header).is_ok());
+        assert_eq!(body, header);
+
+        let (header, _) = send(None).await;
+        assert!(Uuid::parse_str(&header).is_ok());
+        assert_eq!(current_request_id(), None);
+    }
+}
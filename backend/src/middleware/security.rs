@@ -0,0 +1,177 @@
+//! Security middleware - CORS and response security headers
+//!
+//! Both are built from configuration at startup, so a bad origin, method
+//! or header value stops the server instead of failing per request.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue, Method},
+    middleware::Next,
+    response::Response,
+};
+use config::ConfigError;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer};
+
+use crate::config::{CorsConfig, SecurityHeadersConfig};
+use crate::middleware::REQUEST_ID;
+
+/// The CORS policy from `config`; `permissive` allows everything
+///
+/// With `strict` (staging and production), localhost origins are dropped:
+/// the base config lists the local frontend for development.
+pub fn cors_layer(config: &CorsConfig, strict: bool) -> Result<CorsLayer, ConfigError> {
+    // Let browser clients read the total of paged lists and the request ID
+    let exposed = [HeaderName::from_static("x-total-count"), REQUEST_ID];
+
+    if config.permissive {
+        return Ok(CorsLayer::new()
+            .allow_origin(Any)
+            .allow_methods(Any)
+            .allow_headers(Any)
+            .expose_headers(exposed));
+    }
+
+    let origins = config
+        .allowed_origins
+        .iter()
+        .filter(|origin| !(strict && is_local_origin(origin)))
+        .map(|origin| HeaderValue::from_str(origin.trim_end_matches('/')).map_err(|_| invalid("origin", origin)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let methods = config
+        .allowed_methods
+        .iter()
+        .map(|method| Method::from_bytes(method.to_uppercase().as_bytes()).map_err(|_| invalid("method", method)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let headers = config
+        .allowed_headers
+        .iter()
+        .map(|header| HeaderName::from_bytes(header.as_bytes()).map_err(|_| invalid("header", header)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods(AllowMethods::list(methods))
+        .allow_headers(AllowHeaders::list(headers))
+        .expose_headers(exposed)
+        .max_age(Duration::from_secs(config.max_age_secs)))
+}
+
+/// An origin on this machine, which only a developer's browser calls from
+fn is_local_origin(origin: &str) -> bool {
+    let host = origin.split_once("://").map_or(origin, |(_, rest)| rest);
+    let host = host.trim_end_matches('/');
+    let host = match host.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    host.eq_ignore_ascii_case("localhost") || host == "::1" || host.starts_with("127.")
+}
+
+fn invalid(kind: &str, value: &str) -> ConfigError {
+    ConfigError::Message(format!("'{}' is not a valid CORS {}", value, kind))
+}
+
+/// Headers added to every response that doesn't set its own
+#[derive(Clone)]
+pub struct SecurityHeaders(Arc<Vec<(HeaderName, HeaderValue)>>);
+
+impl SecurityHeaders {
+    pub fn new(config: &SecurityHeadersConfig) -> Result<Self, ConfigError> {
+        let mut headers = vec![(
+            HeaderName::from_static("x-content-type-options"),
+            HeaderValue::from_static("nosniff"),
+        )];
+
+        if config.hsts_max_age_secs > 0 {
+            headers.push((
+                HeaderName::from_static("strict-transport-security"),
+                HeaderValue::from_str(&format!("max-age={}", config.hsts_max_age_secs))
+                    .map_err(|e| ConfigError::Message(e.to_string()))?,
+            ));
+        }
+        let optional = [
+            ("x-frame-options", "security_headers.frame_options", &config.frame_options),
+            ("referrer-policy", "security_headers.referrer_policy", &config.referrer_policy),
+        ];
+        for (name, key, value) in optional {
+            if value.is_empty() {
+                continue;
+            }
+            let value = HeaderValue::from_str(value)
+                .map_err(|_| ConfigError::Message(format!("{} '{}' is not a valid header value", key, value)))?;
+            headers.push((HeaderName::from_static(name), value));
+        }
+
+        Ok(Self(Arc::new(headers)))
+    }
+}
+
+/// Add the configured security headers to the response
+pub async fn security_headers(State(headers): State<SecurityHeaders>, request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+
+    for (name, value) in headers.0.iter() {
+        response.headers_mut().entry(name.clone()).or_insert_with(|| value.clone());
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware::from_fn_with_state, routing::get, Router};
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_is_local_origin() {
+        assert!(is_local_origin("http://localhost:3000"));
+        assert!(is_local_origin("http://LOCALHOST"));
+        assert!(is_local_origin("http://127.0.0.1:8080/"));
+        assert!(is_local_origin("http://[::1]:3000"));
+        assert!(!is_local_origin("https://crm.example.com"));
+        assert!(!is_local_origin("https://localhost.example.com"));
+    }
+
+    #[tokio::test]
+    async fn test_security_headers() {
+        let config = SecurityHeadersConfig {
+            frame_options: String::new(),
+            ..SecurityHeadersConfig::default()
+        };
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .route("/framed", get(|| async { ([("referrer-policy", "origin")], "ok") }))
+            .layer(from_fn_with_state(SecurityHeaders::new(&config).unwrap(), security_headers));
+
+        let response = app.clone().oneshot(Request::get("/").body(Body::empty()).unwrap()).await.unwrap();
+        let headers = response.headers();
+        assert_eq!(headers["x-content-type-options"], "nosniff");
+        assert_eq!(headers["strict-transport-security"], "max-age=31536000");
+        assert_eq!(headers["referrer-policy"], "no-referrer");
+        assert!(!headers.contains_key("x-frame-options"));
+
+        // A header the handler set is kept
+        let response = app.oneshot(Request::get("/framed").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.headers()["referrer-policy"], "origin");
+    }
+
+    #[test]
+    fn test_invalid_config_is_rejected() {
+        let config = SecurityHeadersConfig {
+            frame_options: "DENY\n".into(),
+            ..SecurityHeadersConfig::default()
+        };
+        assert!(SecurityHeaders::new(&config).is_err());
+
+        let config = CorsConfig {
+            permissive: false,
+            allowed_methods: vec!["NOT A METHOD".into()],
+            ..CorsConfig::default()
+        };
+        assert!(cors_layer(&config, false).is_err());
+    }
+}
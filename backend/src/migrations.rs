@@ -0,0 +1,440 @@
+//! Schema migrations
+//!
+//! The schema evolves through numbered migrations in `schema/migrations`:
+//! `NNNN_name.up.surql` makes a change and `NNNN_name.down.surql` undoes
+//! it. They are compiled into the binary and listed in `MIGRATIONS`, oldest
+//! first. Each applied migration is recorded in the `migration` table with
+//! a checksum of its up script, so only the ones a database hasn't seen are
+//! applied, and editing one that already ran is reported. A migration and
+//! its record are written in one transaction.
+//!
+//! The server applies pending migrations when it starts. `crm-server
+//! migrate` applies them without starting it, lists what is applied, or
+//! rolls back:
+//!
+//! ```text
+//! crm-server migrate [up]          apply every pending migration
+//! crm-server migrate down [VERSION] roll back to VERSION; the latest one only by default
+//! crm-server migrate status        list migrations and whether they are applied
+//! ```
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::db::Database;
+
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    up: &'static str,
+    down: &'static str,
+}
+
+impl Migration {
+    fn checksum(&self) -> String {
+        hex::encode(Sha256::digest(self.up.as_bytes()))
+    }
+}
+
+/// Every migration, oldest first; add new ones at the end
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "init",
+        up: include_str!("../schema/migrations/0001_init.up.surql"),
+        down: include_str!("../schema/migrations/0001_init.down.surql"),
+    },
+    Migration {
+        version: 2,
+        name: "interaction_entry_types",
+        up: include_str!("../schema/migrations/0002_interaction_entry_types.up.surql"),
+        down: include_str!("../schema/migrations/0002_interaction_entry_types.down.surql"),
+    },
+    Migration {
+        version: 3,
+        name: "timeline_campaign",
+        up: include_str!("../schema/migrations/0003_timeline_campaign.up.surql"),
+        down: include_str!("../schema/migrations/0003_timeline_campaign.down.surql"),
+    },
+    Migration {
+        version: 4,
+        name: "status_history",
+        up: include_str!("../schema/migrations/0004_status_history.up.surql"),
+        down: include_str!("../schema/migrations/0004_status_history.down.surql"),
+    },
+    Migration {
+        version: 5,
+        name: "churned_entries",
+        up: include_str!("../schema/migrations/0005_churned_entries.up.surql"),
+        down: include_str!("../schema/migrations/0005_churned_entries.down.surql"),
+    },
+    Migration {
+        version: 6,
+        name: "win_loss_reasons",
+        up: include_str!("../schema/migrations/0006_win_loss_reasons.up.surql"),
+        down: include_str!("../schema/migrations/0006_win_loss_reasons.down.surql"),
+    },
+    Migration {
+        version: 7,
+        name: "locations",
+        up: include_str!("../schema/migrations/0007_locations.up.surql"),
+        down: include_str!("../schema/migrations/0007_locations.down.surql"),
+    },
+    Migration {
+        version: 8,
+        name: "relationships",
+        up: include_str!("../schema/migrations/0008_relationships.up.surql"),
+        down: include_str!("../schema/migrations/0008_relationships.down.surql"),
+    },
+    Migration {
+        version: 9,
+        name: "reply_detection",
+        up: include_str!("../schema/migrations/0009_reply_detection.up.surql"),
+        down: include_str!("../schema/migrations/0009_reply_detection.down.surql"),
+    },
+    Migration {
+        version: 10,
+        name: "deliverability",
+        up: include_str!("../schema/migrations/0010_deliverability.up.surql"),
+        down: include_str!("../schema/migrations/0010_deliverability.down.surql"),
+    },
+    Migration {
+        version: 11,
+        name: "short_links",
+        up: include_str!("../schema/migrations/0011_short_links.up.surql"),
+        down: include_str!("../schema/migrations/0011_short_links.down.surql"),
+    },
+    Migration {
+        version: 12,
+        name: "sender_identities",
+        up: include_str!("../schema/migrations/0012_sender_identities.up.surql"),
+        down: include_str!("../schema/migrations/0012_sender_identities.down.surql"),
+    },
+    Migration {
+        version: 13,
+        name: "prep_brief_entries",
+        up: include_str!("../schema/migrations/0013_prep_brief_entries.up.surql"),
+        down: include_str!("../schema/migrations/0013_prep_brief_entries.down.surql"),
+    },
+    Migration {
+        version: 14,
+        name: "unsubscribe_entries",
+        up: include_str!("../schema/migrations/0014_unsubscribe_entries.up.surql"),
+        down: include_str!("../schema/migrations/0014_unsubscribe_entries.down.surql"),
+    },
+    Migration {
+        version: 15,
+        name: "idempotency_lease",
+        up: include_str!("../schema/migrations/0015_idempotency_lease.up.surql"),
+        down: include_str!("../schema/migrations/0015_idempotency_lease.down.surql"),
+    },
+    Migration {
+        version: 16,
+        name: "social_accounts",
+        up: include_str!("../schema/migrations/0016_social_accounts.up.surql"),
+        down: include_str!("../schema/migrations/0016_social_accounts.down.surql"),
+    },
+    Migration {
+        version: 17,
+        name: "asset_versions",
+        up: include_str!("../schema/migrations/0017_asset_versions.up.surql"),
+        down: include_str!("../schema/migrations/0017_asset_versions.down.surql"),
+    },
+    Migration {
+        version: 18,
+        name: "event_seats",
+        up: include_str!("../schema/migrations/0018_event_seats.up.surql"),
+        down: include_str!("../schema/migrations/0018_event_seats.down.surql"),
+    },
+    Migration {
+        version: 19,
+        name: "synced_messages",
+        up: include_str!("../schema/migrations/0019_synced_messages.up.surql"),
+        down: include_str!("../schema/migrations/0019_synced_messages.down.surql"),
+    },
+    Migration {
+        version: 20,
+        name: "import_leases",
+        up: include_str!("../schema/migrations/0020_import_leases.up.surql"),
+        down: include_str!("../schema/migrations/0020_import_leases.down.surql"),
+    },
+    Migration {
+        version: 21,
+        name: "sentiment_queue",
+        up: include_str!("../schema/migrations/0021_sentiment_queue.up.surql"),
+        down: include_str!("../schema/migrations/0021_sentiment_queue.down.surql"),
+    },
+    Migration {
+        version: 22,
+        name: "user_roles",
+        up: include_str!("../schema/migrations/0022_user_roles.up.surql"),
+        down: include_str!("../schema/migrations/0022_user_roles.down.surql"),
+    },
+    Migration {
+        version: 23,
+        name: "live_tickets",
+        up: include_str!("../schema/migrations/0023_live_tickets.up.surql"),
+        down: include_str!("../schema/migrations/0023_live_tickets.down.surql"),
+    },
+    Migration {
+        version: 24,
+        name: "backup_staging",
+        up: include_str!("../schema/migrations/0024_backup_staging.up.surql"),
+        down: include_str!("../schema/migrations/0024_backup_staging.down.surql"),
+    },
+    Migration {
+        version: 25,
+        name: "sender_ownership",
+        up: include_str!("../schema/migrations/0025_sender_ownership.up.surql"),
+        down: include_str!("../schema/migrations/0025_sender_ownership.down.surql"),
+    },
+];
+
+const MIGRATION_TABLE: &str = "DEFINE TABLE migration SCHEMAFULL;
+    DEFINE FIELD version ON TABLE migration TYPE int;
+    DEFINE FIELD name ON TABLE migration TYPE string;
+    DEFINE FIELD checksum ON TABLE migration TYPE string;
+    DEFINE FIELD applied_at ON TABLE migration TYPE datetime DEFAULT time::now();";
+
+#[derive(Debug, Deserialize)]
+struct AppliedMigration {
+    version: u32,
+    name: String,
+    checksum: String,
+    applied_at: DateTime<Utc>,
+}
+
+/// What `crm-server migrate` was asked to do
+#[derive(Debug, PartialEq, Eq)]
+pub enum MigrateCommand {
+    Up,
+    /// Roll back every migration after `to`; only the latest one when unset
+    Down { to: Option<u32> },
+    Status,
+}
+
+impl MigrateCommand {
+    /// Parse the arguments after `migrate`
+    pub fn parse(args: &[String]) -> Result<Self> {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+        match args.as_slice() {
+            [] | ["up"] => Ok(Self::Up),
+            ["down"] => Ok(Self::Down { to: None }),
+            ["down", version] => match version.parse() {
+                Ok(to) => Ok(Self::Down { to: Some(to) }),
+                Err(_) => bail!("'{}' is not a migration version", version),
+            },
+            ["status"] => Ok(Self::Status),
+            _ => bail!("Usage: crm-server migrate [up | down [VERSION] | status]"),
+        }
+    }
+}
+
+pub async fn run(db: &Database, command: MigrateCommand) -> Result<()> {
+    match command {
+        MigrateCommand::Up => {
+            migrate_up(db).await?;
+        }
+        MigrateCommand::Down { to } => {
+            migrate_down(db, to).await?;
+        }
+        MigrateCommand::Status => {
+            let applied = applied(db).await?;
+            for migration in MIGRATIONS {
+                match applied.iter().find(|a| a.version == migration.version) {
+                    Some(a) => println!(
+                        "{:04} {:<32} applied {}",
+                        migration.version,
+                        migration.name,
+                        a.applied_at.format("%Y-%m-%d %H:%M:%S UTC")
+                    ),
+                    None => println!("{:04} {:<32} pending", migration.version, migration.name),
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Apply every pending migration, oldest first; returns how many were applied
+pub async fn migrate_up(db: &Database) -> Result<usize> {
+    let applied = applied(db).await?;
+    warn_on_edited(&applied);
+
+    let pending = pending(&applied)?;
+    for migration in &pending {
+        db.transaction()
+            .query(migration.up)
+            .query(
+                "CREATE type::thing('migration', $version) \
+                 CONTENT { version: $version, name: $name, checksum: $checksum }",
+            )
+            .bind(("version", migration.version))
+            .bind(("name", migration.name))
+            .bind(("checksum", migration.checksum()))
+            .commit()
+            .await?;
+        tracing::info!("Applied migration {:04}_{}", migration.version, migration.name);
+    }
+
+    if pending.is_empty() {
+        tracing::info!("Database schema is up to date");
+    }
+    Ok(pending.len())
+}
+
+/// Roll back the migrations after `to`, newest first; returns how many were rolled back
+async fn migrate_down(db: &Database, to: Option<u32>) -> Result<usize> {
+    let applied = applied(db).await?;
+
+    let revert = to_revert(&applied, to)?;
+    for migration in &revert {
+        db.transaction()
+            .query(migration.down)
+            .query("DELETE type::thing('migration', $version)")
+            .bind(("version", migration.version))
+            .commit()
+            .await?;
+        tracing::info!("Rolled back migration {:04}_{}", migration.version, migration.name);
+    }
+
+    if revert.is_empty() {
+        tracing::info!("Nothing to roll back");
+    }
+    Ok(revert.len())
+}
+
+/// Migrations recorded as applied, oldest first
+async fn applied(db: &Database) -> Result<Vec<AppliedMigration>> {
+    db.client.query(MIGRATION_TABLE).await?.check()?;
+
+    let applied: Vec<AppliedMigration> = db
+        .client
+        .query("SELECT version, name, checksum, applied_at FROM migration ORDER BY version")
+        .await?
+        .take(0)?;
+
+    Ok(applied)
+}
+
+fn warn_on_edited(applied: &[AppliedMigration]) {
+    for a in applied {
+        if let Some(migration) = MIGRATIONS.iter().find(|m| m.version == a.version) {
+            if migration.checksum() != a.checksum {
+                tracing::warn!(
+                    "Migration {:04}_{} was edited after it was applied; put further changes in a new migration",
+                    a.version,
+                    a.name
+                );
+            }
+        }
+    }
+}
+
+/// Migrations not applied yet, oldest first
+///
+/// A database with migrations this build doesn't know was migrated by a
+/// newer build; nothing is applied to it.
+fn pending(applied: &[AppliedMigration]) -> Result<Vec<&'static Migration>> {
+    if let Some(unknown) = applied.iter().find(|a| !MIGRATIONS.iter().any(|m| m.version == a.version)) {
+        bail!(
+            "The database has migration {:04}_{}, which this build doesn't know; is the server out of date?",
+            unknown.version,
+            unknown.name
+        );
+    }
+
+    Ok(MIGRATIONS
+        .iter()
+        .filter(|m| !applied.iter().any(|a| a.version == m.version))
+        .collect())
+}
+
+/// Applied migrations to roll back to get to `to`, newest first
+fn to_revert(applied: &[AppliedMigration], to: Option<u32>) -> Result<Vec<&'static Migration>> {
+    let applied_versions: Vec<u32> = applied.iter().map(|a| a.version).collect();
+    let to = match to {
+        Some(to) => to,
+        // Just the latest: everything up to the one before it stays
+        None => applied_versions.iter().rev().nth(1).copied().unwrap_or(0),
+    };
+
+    let mut revert = Vec::new();
+    for version in applied_versions.into_iter().rev().filter(|v| *v > to) {
+        match MIGRATIONS.iter().find(|m| m.version == version) {
+            Some(migration) => revert.push(migration),
+            None => bail!("Migration {:04} isn't known to this build, so it can't be rolled back", version),
+        }
+    }
+
+    Ok(revert)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn applied(versions: &[u32]) -> Vec<AppliedMigration> {
+        versions
+            .iter()
+            .map(|v| AppliedMigration {
+                version: *v,
+                name: format!("m{}", v),
+                checksum: String::new(),
+                applied_at: Utc::now(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_migrations_are_numbered_in_order() {
+        assert!(!MIGRATIONS.is_empty());
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].version < w[1].version));
+        assert!(MIGRATIONS.iter().all(|m| m.version > 0 && !m.down.trim().is_empty()));
+    }
+
+    #[test]
+    fn test_pending_skips_applied_and_refuses_unknown_versions() {
+        let all: Vec<u32> = MIGRATIONS.iter().map(|m| m.version).collect();
+
+        let versions: Vec<u32> = pending(&[]).unwrap().iter().map(|m| m.version).collect();
+        assert_eq!(versions, all);
+        assert!(pending(&applied(&all)).unwrap().is_empty());
+        assert!(pending(&applied(&[9999])).is_err());
+    }
+
+    #[test]
+    fn test_to_revert_rolls_back_the_latest_or_down_to_a_version() {
+        assert!(to_revert(&[], None).unwrap().is_empty());
+
+        let latest = MIGRATIONS.last().unwrap().version;
+        let all: Vec<u32> = MIGRATIONS.iter().map(|m| m.version).collect();
+
+        let revert = to_revert(&applied(&all), None).unwrap();
+        assert_eq!(revert.len(), 1);
+        assert_eq!(revert[0].version, latest);
+
+        let revert: Vec<u32> = to_revert(&applied(&all), Some(0)).unwrap().iter().map(|m| m.version).collect();
+        assert_eq!(revert, all.iter().rev().copied().collect::<Vec<_>>());
+
+        assert!(to_revert(&applied(&all), Some(latest)).unwrap().is_empty());
+        assert!(to_revert(&applied(&[9999]), None).is_err());
+    }
+
+    #[test]
+    fn test_parse_migrate_command() {
+        let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+
+        assert_eq!(MigrateCommand::parse(&args("")).unwrap(), MigrateCommand::Up);
+        assert_eq!(MigrateCommand::parse(&args("up")).unwrap(), MigrateCommand::Up);
+        assert_eq!(MigrateCommand::parse(&args("down")).unwrap(), MigrateCommand::Down { to: None });
+        assert_eq!(MigrateCommand::parse(&args("down 3")).unwrap(), MigrateCommand::Down { to: Some(3) });
+        assert_eq!(MigrateCommand::parse(&args("status")).unwrap(), MigrateCommand::Status);
+        assert!(MigrateCommand::parse(&args("down three")).is_err());
+        assert!(MigrateCommand::parse(&args("sideways")).is_err());
+    }
+}
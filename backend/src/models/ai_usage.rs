@@ -0,0 +1,64 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+use crate::models::TimeRange;
+
+/// One AI content generation and what it cost
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiUsage {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    /// None for content generated outside a campaign
+    pub campaign: Option<Thing>,
+    /// `email`, `social_posts` or `landing_page`
+    pub content_kind: String,
+    pub provider: String,
+    pub model: String,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub latency_ms: u64,
+    /// Priced when generated, so later price changes don't rewrite history
+    pub cost_usd: f64,
+    /// `YYYY-MM` of `created_at`, for monthly grouping
+    pub month: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Usage summed over a group of generations
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AiUsageTotals {
+    pub generations: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+    pub avg_latency_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AiUsageByCampaign {
+    /// None for content generated outside a campaign
+    pub campaign_id: Option<String>,
+    #[serde(flatten)]
+    pub totals: AiUsageTotals,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AiUsageByMonth {
+    /// `YYYY-MM`
+    pub month: String,
+    #[serde(flatten)]
+    pub totals: AiUsageTotals,
+}
+
+/// What AI content generation cost, per campaign and per month
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AiUsageReport {
+    pub time_range: TimeRange,
+    pub total: AiUsageTotals,
+    /// Most expensive first
+    pub by_campaign: Vec<AiUsageByCampaign>,
+    /// Oldest first
+    pub by_month: Vec<AiUsageByMonth>,
+}
@@ -0,0 +1,345 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::ContactStatus;
+use crate::domain::{WinLossOutcome, WinLossReason};
+use crate::domain::DealStage;
+
+/// Window of time an analytics report covers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum TimeRange {
+    #[serde(rename = "7d")]
+    Last7Days,
+    #[default]
+    #[serde(rename = "30d")]
+    Last30Days,
+    #[serde(rename = "90d")]
+    Last90Days,
+    #[serde(rename = "365d")]
+    Last365Days,
+    #[serde(rename = "all")]
+    AllTime,
+}
+
+impl TimeRange {
+    /// Start of the window; the Unix epoch for all time
+    pub fn since(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let days = match self {
+            TimeRange::Last7Days => 7,
+            TimeRange::Last30Days => 30,
+            TimeRange::Last90Days => 90,
+            TimeRange::Last365Days => 365,
+            TimeRange::AllTime => return DateTime::UNIX_EPOCH,
+        };
+        now - Duration::days(days)
+    }
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AnalyticsQuery {
+    pub time_range: Option<TimeRange>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CampaignAnalytics {
+    pub campaign_id: String,
+    pub time_range: TimeRange,
+    pub total_contacts: u64,
+    pub emails_sent: u64,
+    /// Recipients that wrote back to the campaign's email
+    pub replies: u64,
+    pub emails_opened: u64,
+    pub emails_clicked: u64,
+    pub landing_page_visits: u64,
+    pub conversions: u64,
+    /// Contacts that did anything attributed to the campaign: opened,
+    /// clicked, visited, submitted, registered or attended
+    pub engaged_contacts: u64,
+    pub open_rate: f64,
+    pub click_rate: f64,
+    /// Replies as a percentage of emails sent
+    pub reply_rate: f64,
+    pub conversion_rate: f64,
+    /// Visit to submission conversion of each of the campaign's landing pages
+    pub landing_pages: Vec<LandingPageConversion>,
+    /// Clicks on each link the campaign sent, tracked email links and short
+    /// links alike, most clicked first
+    pub links: Vec<LinkClicks>,
+}
+
+/// Bounces and spam complaints reported for one campaign's email
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DeliverabilityAnalytics {
+    pub campaign_id: String,
+    pub time_range: TimeRange,
+    /// Emails that went out, bounced ones included
+    pub sent: u64,
+    /// Emails sent that didn't bounce for good
+    pub delivered: u64,
+    pub hard_bounces: u64,
+    pub soft_bounces: u64,
+    pub complaints: u64,
+    /// Hard bounces as a percentage of emails sent
+    pub bounce_rate: f64,
+    /// Complaints as a percentage of emails delivered
+    pub complaint_rate: f64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LandingPageConversion {
+    pub landing_page_id: String,
+    /// Unique visitors
+    pub visits: u64,
+    /// Unique contacts that submitted the form
+    pub submissions: u64,
+    /// Submissions as a percentage of visits
+    pub conversion_rate: f64,
+}
+
+/// Clicks on one destination URL of a campaign
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LinkClicks {
+    pub url: String,
+    pub clicks: u64,
+    /// Unique contacts that clicked
+    pub unique_contacts: u64,
+    /// Clicks from campaign email
+    pub email_clicks: u64,
+    /// Clicks on short links shared on social media
+    pub social_clicks: u64,
+}
+
+/// How an event's RSVPs progressed, from invitation to attendance
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EventAnalytics {
+    pub event_id: String,
+    /// Every contact with an RSVP, whatever its status
+    pub invited: u64,
+    /// Registered, attended or no-show
+    pub registered: u64,
+    pub waitlisted: u64,
+    pub cancelled: u64,
+    pub attended: u64,
+    pub no_show: u64,
+    /// Registered as a percentage of invited
+    pub registration_rate: f64,
+    /// Attended as a percentage of registered
+    pub attendance_rate: f64,
+    /// No-shows as a percentage of attendance recorded either way
+    pub no_show_rate: f64,
+    /// Registrations per day, days without any left out
+    pub registrations_over_time: Vec<DailyRegistrations>,
+    pub top_engaged_attendees: Vec<TopEngagedContact>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DailyRegistrations {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub registrations: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ContactsAnalytics {
+    pub time_range: TimeRange,
+    pub total_contacts: u64,
+    pub leads: u64,
+    pub customers: u64,
+    pub partners: u64,
+    pub investors: u64,
+    pub other: u64,
+    pub avg_engagement_score: f64,
+    pub new_this_month: u64,
+    /// Contacts created within the requested time range
+    pub new_in_range: u64,
+    pub top_engaged: Vec<TopEngagedContact>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TopEngagedContact {
+    pub id: String,
+    pub name: String,
+    pub engagement_score: f64,
+}
+
+/// Customers lost over the time range
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChurnAnalytics {
+    pub time_range: TimeRange,
+    /// Estimated from the customers now and the moves during the range
+    pub customers_at_start: u64,
+    pub new_customers: u64,
+    pub churned_customers: u64,
+    pub customers_now: u64,
+    /// Churned customers as a percentage of those at the start
+    pub churn_rate: f64,
+    /// Most given reasons first
+    pub top_reasons: Vec<ChurnReasonCount>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChurnReasonCount {
+    pub reason: String,
+    pub count: u64,
+}
+
+/// Deals and leads won and lost over the time range, and why
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WinLossAnalytics {
+    pub time_range: TimeRange,
+    pub deals_won: u64,
+    pub deals_lost: u64,
+    /// Leads that became customers
+    pub leads_won: u64,
+    /// Leads moved to other
+    pub leads_lost: u64,
+    /// Deals won as a percentage of those closed
+    pub deal_win_rate: f64,
+    /// Over the whole range, most given first
+    pub reasons: Vec<WinLossReasonCount>,
+    /// Oldest month first; months with nothing won or lost are left out
+    pub by_month: Vec<WinLossMonth>,
+}
+
+/// How fast we and our contacts answer each other's email over the time range
+///
+/// Counts replies in the range to email also in the range.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ResponseTimeAnalytics {
+    pub time_range: TimeRange,
+    /// Threads with email in the range
+    pub threads: u64,
+    /// Threads where both sides wrote
+    pub replied_threads: u64,
+    /// Threads whose last email came from the contact
+    pub awaiting_our_reply: u64,
+    pub our_replies: u64,
+    pub our_avg_response_minutes: Option<f64>,
+    pub our_median_response_minutes: Option<f64>,
+    pub their_replies: u64,
+    pub their_avg_response_minutes: Option<f64>,
+    pub their_median_response_minutes: Option<f64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WinLossReasonCount {
+    pub outcome: WinLossOutcome,
+    /// `None` for those closed without a reason
+    pub reason: Option<WinLossReason>,
+    pub deals: u64,
+    pub leads: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WinLossMonth {
+    /// `YYYY-MM`
+    pub month: String,
+    /// Deals and leads won
+    pub won: u64,
+    /// Deals and leads lost
+    pub lost: u64,
+    /// Most given first
+    pub reasons: Vec<WinLossReasonCount>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FunnelAnalytics {
+    pub time_range: TimeRange,
+    pub stages: Vec<FunnelStage>,
+    pub overall_conversion_rate: f64,
+    /// Status changes of the same contacts and how long each took, most
+    /// common first
+    pub stage_conversions: Vec<StageConversion>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FunnelStage {
+    pub name: String,
+    pub count: u64,
+    pub percentage: f64,
+}
+
+/// Contacts moving from one status to another
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StageConversion {
+    pub from_status: ContactStatus,
+    pub to_status: ContactStatus,
+    /// Number of such changes
+    pub conversions: u64,
+    /// Days spent in `from_status` before the change, counted from the
+    /// contact's creation or previous change
+    pub avg_days: f64,
+    pub median_days: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PipelineAnalytics {
+    pub time_range: TimeRange,
+    /// Current open pipeline, one entry per open stage
+    pub stages: Vec<PipelineStageSummary>,
+    pub open_deals: u64,
+    pub open_value: f64,
+    /// Open value weighted by each stage's win probability
+    pub weighted_value: f64,
+    /// Deals closed within the time range
+    pub won_deals: u64,
+    pub won_value: f64,
+    pub lost_deals: u64,
+    /// Won as a percentage of all deals closed in the range
+    pub win_rate: f64,
+}
+
+/// The pipeline by where each deal's company, or else its contact, is
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PipelineRegionAnalytics {
+    pub time_range: TimeRange,
+    /// Largest open value first
+    pub regions: Vec<PipelineRegionSummary>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PipelineRegionSummary {
+    /// ISO 3166-1 alpha-2 code; `None` for deals with no known place
+    pub country: Option<String>,
+    pub region: Option<String>,
+    pub open_deals: u64,
+    pub open_value: f64,
+    /// Open value weighted by each stage's win probability
+    pub weighted_value: f64,
+    /// Deals closed within the time range
+    pub won_deals: u64,
+    pub won_value: f64,
+    pub lost_deals: u64,
+    pub win_rate: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PipelineStageSummary {
+    pub stage: DealStage,
+    pub count: u64,
+    pub total_value: f64,
+    pub weighted_value: f64,
+}
+
+/// Accounts whose contacts came across worse in the last 30 days than in the 60 before
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SentimentAnalytics {
+    /// Biggest drop first
+    pub accounts_turning_negative: Vec<AccountSentiment>,
+}
+
+/// Average sentiment of a company's rated interactions, -1 (negative) to 1 (positive)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccountSentiment {
+    pub company_id: String,
+    pub name: String,
+    /// Last 30 days
+    pub recent_sentiment: f64,
+    /// The 60 days before
+    pub previous_sentiment: f64,
+    /// Negative interactions in the last 30 days
+    pub recent_negative: u64,
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+pub use crate::ai::ai_reply::{ReplyLength, ReplyTone};
+
+/// POST /api/ai/draft-reply
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DraftReplyRequest {
+    /// The contact who sent the email
+    pub contact_id: String,
+    /// Text of the email being answered
+    pub email_text: String,
+    pub subject: Option<String>,
+    #[serde(default)]
+    pub tone: ReplyTone,
+    #[serde(default)]
+    pub length: ReplyLength,
+}
+
+/// A reply draft for the user to review before sending
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DraftReplyResponse {
+    pub contact_id: String,
+    pub subject: String,
+    pub body: String,
+    pub tone: ReplyTone,
+    pub length: ReplyLength,
+    /// Written by the configured AI provider; `false` when it came from the built-in template
+    pub ai_generated: bool,
+}
@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+/// A file attached to a contact, and possibly to one of its timeline entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub contact: Thing,
+    pub timeline_entry: Option<Thing>,
+    pub filename: String,
+    pub content_type: String,
+    /// In bytes
+    pub size: u64,
+    /// Where the object storage keeps the file
+    pub storage_key: String,
+    pub uploaded_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What a timeline entry shows of each file attached to it
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AttachmentSummary {
+    pub id: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size: u64,
+}
+
+/// Query of the upload endpoints; the request body is the file itself
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UploadAttachmentQuery {
+    pub filename: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AttachmentResponse {
+    pub id: String,
+    pub contact_id: String,
+    pub timeline_entry_id: Option<String>,
+    pub filename: String,
+    pub content_type: String,
+    pub size: u64,
+    pub uploaded_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Attachment> for AttachmentResponse {
+    fn from(a: Attachment) -> Self {
+        Self {
+            id: a.id.map(|t| t.id.to_string()).unwrap_or_default(),
+            contact_id: a.contact.id.to_string(),
+            timeline_entry_id: a.timeline_entry.map(|t| t.id.to_string()),
+            filename: a.filename,
+            content_type: a.content_type,
+            size: a.size,
+            uploaded_by: a.uploaded_by,
+            created_at: a.created_at,
+        }
+    }
+}
+
+impl From<&Attachment> for AttachmentSummary {
+    fn from(a: &Attachment) -> Self {
+        Self {
+            id: a.id.as_ref().map(|t| t.id.to_raw()).unwrap_or_default(),
+            filename: a.filename.clone(),
+            content_type: a.content_type.clone(),
+            size: a.size,
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+use crate::domain::{AuditAction, AuditEntity, FieldChange};
+
+/// A record of who changed what
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLog {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub entity_type: AuditEntity,
+    pub entity_id: String,
+    pub action: AuditAction,
+    pub actor: Option<Thing>,
+    pub actor_email: Option<String>,
+    pub changes: Vec<FieldChange>,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditQuery {
+    pub entity_type: Option<AuditEntity>,
+    pub entity_id: Option<String>,
+    pub action: Option<AuditAction>,
+    pub actor_id: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuditLogResponse {
+    pub id: String,
+    pub entity_type: AuditEntity,
+    pub entity_id: String,
+    pub action: AuditAction,
+    pub actor_id: Option<String>,
+    pub actor_email: Option<String>,
+    pub changes: Vec<FieldChange>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl From<AuditLog> for AuditLogResponse {
+    fn from(a: AuditLog) -> Self {
+        Self {
+            id: a.id.map(|t| t.id.to_string()).unwrap_or_default(),
+            entity_type: a.entity_type,
+            entity_id: a.entity_id,
+            action: a.action,
+            actor_id: a.actor.map(|t| t.id.to_string()),
+            actor_email: a.actor_email,
+            changes: a.changes,
+            timestamp: a.timestamp,
+        }
+    }
+}
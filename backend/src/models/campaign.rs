@@ -1,8 +1,11 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use surrealdb::sql::Thing;
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::domain::SendWindow;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum CampaignObjective {
     Awareness,
@@ -12,7 +15,7 @@ pub enum CampaignObjective {
     EarlyAdopters,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum CampaignStatus {
     Draft,
@@ -21,7 +24,7 @@ pub enum CampaignStatus {
     Completed,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum CampaignChannel {
     Email,
@@ -33,17 +36,30 @@ pub enum CampaignChannel {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Campaign {
     pub id: Option<Thing>,
+    pub workspace: Thing,
     pub name: String,
     pub objective: CampaignObjective,
     pub status: CampaignStatus,
     pub channels: Vec<CampaignChannel>,
     pub prompt: Option<String>,
     pub segment_definition: serde_json::Value,
+    /// Saved segment the audience comes from; takes precedence over segment_definition
+    #[serde(default)]
+    pub segment: Option<Thing>,
+    /// Sender identity the campaign's email goes out from
+    #[serde(default)]
+    pub sender: Option<Thing>,
+    /// When a scheduled campaign is executed
+    #[serde(default)]
+    pub scheduled_at: Option<DateTime<Utc>>,
+    /// Local hours recipients may be emailed in; outside them they wait
+    #[serde(default)]
+    pub send_window: Option<SendWindow>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum AssetType {
     Email,
@@ -55,24 +71,48 @@ pub enum AssetType {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CampaignAsset {
     pub id: Option<Thing>,
+    pub workspace: Thing,
     pub campaign: Thing,
     #[serde(rename = "type")]
     pub asset_type: AssetType,
     pub generated_content: serde_json::Value,
     pub url: Option<String>,
+    /// 1 for the first generation of a type, counting up with every
+    /// regeneration or edit
+    #[serde(default = "first_version")]
+    pub version: u32,
+    /// The version this one was regenerated or edited from
+    #[serde(default)]
+    pub parent: Option<Thing>,
+    /// Whether this is the version of its type the campaign uses; earlier
+    /// versions are kept as history
+    #[serde(default = "active_by_default")]
+    pub active: bool,
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+fn first_version() -> u32 {
+    1
+}
+
+fn active_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateCampaignRequest {
     pub name: String,
     pub objective: CampaignObjective,
     pub channels: Vec<CampaignChannel>,
     pub prompt: Option<String>,
     pub segment_definition: Option<serde_json::Value>,
+    /// Use a saved segment as the audience instead of an inline definition
+    pub segment_id: Option<String>,
+    /// Sender identity to send email from; required before the campaign can send
+    pub sender_id: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateCampaignRequest {
     pub name: Option<String>,
     pub objective: Option<CampaignObjective>,
@@ -80,15 +120,50 @@ pub struct UpdateCampaignRequest {
     pub channels: Option<Vec<CampaignChannel>>,
     pub prompt: Option<String>,
     pub segment_definition: Option<serde_json::Value>,
+    pub segment_id: Option<String>,
+    pub sender_id: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+/// POST /api/campaigns/:id/schedule
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ScheduleCampaignRequest {
+    /// When to execute the campaign
+    pub scheduled_at: DateTime<Utc>,
+    /// Only email recipients during these local hours; omit to send to
+    /// everyone at `scheduled_at`
+    pub send_window: Option<SendWindow>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct GenerateAssetsRequest {
     pub prompt: String,
     pub asset_types: Vec<AssetType>,
 }
 
-#[derive(Debug, Serialize)]
+/// POST /api/campaigns/:id/assets/:asset_id/regenerate
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct RegenerateAssetRequest {
+    /// Brief for the new version; defaults to the campaign's prompt
+    pub prompt: Option<String>,
+}
+
+/// PATCH /api/campaigns/:id/assets/:asset_id
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateAssetRequest {
+    /// Replacement content, in the same shape as the generated content;
+    /// an email's bodies are compiled from its `document` when it has one
+    pub generated_content: serde_json::Value,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AssetQuery {
+    /// Include earlier versions, not just the active one of each type
+    pub include_history: Option<bool>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CampaignResponse {
     pub id: String,
     pub name: String,
@@ -97,6 +172,10 @@ pub struct CampaignResponse {
     pub channels: Vec<CampaignChannel>,
     pub prompt: Option<String>,
     pub segment_definition: serde_json::Value,
+    pub segment_id: Option<String>,
+    pub sender_id: Option<String>,
+    pub scheduled_at: Option<DateTime<Utc>>,
+    pub send_window: Option<SendWindow>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -111,13 +190,17 @@ impl From<Campaign> for CampaignResponse {
             channels: c.channels,
             prompt: c.prompt,
             segment_definition: c.segment_definition,
+            segment_id: c.segment.map(|t| t.id.to_string()),
+            sender_id: c.sender.map(|t| t.id.to_string()),
+            scheduled_at: c.scheduled_at,
+            send_window: c.send_window,
             created_at: c.created_at,
             updated_at: c.updated_at,
         }
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CampaignAssetResponse {
     pub id: String,
     pub campaign_id: String,
@@ -125,6 +208,9 @@ pub struct CampaignAssetResponse {
     pub asset_type: AssetType,
     pub generated_content: serde_json::Value,
     pub url: Option<String>,
+    pub version: u32,
+    pub parent_id: Option<String>,
+    pub active: bool,
     pub created_at: DateTime<Utc>,
 }
 
@@ -136,7 +222,101 @@ impl From<CampaignAsset> for CampaignAssetResponse {
             asset_type: a.asset_type,
             generated_content: a.generated_content,
             url: a.url,
+            version: a.version,
+            parent_id: a.parent.map(|p| p.id.to_string()),
+            active: a.active,
             created_at: a.created_at,
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RecipientStatus {
+    Pending,
+    Sent,
+    Bounced,
+    Unsubscribed,
+    /// Sent to, and the contact wrote back
+    Replied,
+}
+
+/// A contact resolved from a campaign's segment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampaignRecipient {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub campaign: Thing,
+    pub contact: Thing,
+    pub email: String,
+    pub status: RecipientStatus,
+    /// When the campaign's email went out to the recipient
+    pub sent_at: Option<DateTime<Utc>>,
+    /// When the contact's first reply came in
+    pub replied_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RecipientQuery {
+    pub status: Option<RecipientStatus>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CampaignRecipientResponse {
+    pub id: String,
+    pub campaign_id: String,
+    pub contact_id: String,
+    pub email: String,
+    pub status: RecipientStatus,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub replied_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<CampaignRecipient> for CampaignRecipientResponse {
+    fn from(r: CampaignRecipient) -> Self {
+        Self {
+            id: r.id.map(|t| t.id.to_string()).unwrap_or_default(),
+            campaign_id: r.campaign.id.to_string(),
+            contact_id: r.contact.id.to_string(),
+            email: r.email,
+            status: r.status,
+            sent_at: r.sent_at,
+            replied_at: r.replied_at,
+            created_at: r.created_at,
+            updated_at: r.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EmailPreviewQuery {
+    /// Contact whose data fills the merge variables
+    pub contact_id: String,
+}
+
+/// The campaign's email as one contact would receive it
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EmailPreviewResponse {
+    pub campaign_id: String,
+    pub contact_id: String,
+    pub to: String,
+    pub subject: String,
+    pub preview_text: String,
+    pub body_html: String,
+    pub body_text: String,
+    pub cta_text: String,
+    pub cta_url: String,
+    /// Variables without a value for this contact, left as written; the
+    /// campaign can't be executed while any recipient has some
+    pub unresolved_variables: Vec<String>,
+}
@@ -0,0 +1,106 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+use super::{AssetType, CampaignAsset, CampaignChannel, CampaignObjective};
+use crate::domain::SendWindow;
+
+/// A reusable campaign: setup, audience and generated assets, without a
+/// status or schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampaignTemplate {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub name: String,
+    pub description: Option<String>,
+    pub objective: CampaignObjective,
+    pub channels: Vec<CampaignChannel>,
+    pub prompt: Option<String>,
+    pub segment_definition: serde_json::Value,
+    #[serde(default)]
+    pub segment: Option<Thing>,
+    #[serde(default)]
+    pub send_window: Option<SendWindow>,
+    #[serde(default)]
+    pub assets: Vec<TemplateAsset>,
+    /// Campaign the template was saved from
+    pub source_campaign: Option<Thing>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Generated content kept with a template, or carried over to a clone
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TemplateAsset {
+    #[serde(rename = "type")]
+    pub asset_type: AssetType,
+    pub generated_content: serde_json::Value,
+}
+
+impl From<CampaignAsset> for TemplateAsset {
+    fn from(a: CampaignAsset) -> Self {
+        Self {
+            asset_type: a.asset_type,
+            generated_content: a.generated_content,
+        }
+    }
+}
+
+/// POST /api/campaigns/:id/clone
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct CloneCampaignRequest {
+    /// Name of the copy; defaults to the original's name with " (copy)"
+    pub name: Option<String>,
+}
+
+/// POST /api/campaigns/:id/template
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SaveTemplateRequest {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// POST /api/campaign-templates/:id/instantiate
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct InstantiateTemplateRequest {
+    /// Name of the new campaign; defaults to the template's name
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CampaignTemplateResponse {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub objective: CampaignObjective,
+    pub channels: Vec<CampaignChannel>,
+    pub prompt: Option<String>,
+    pub segment_definition: serde_json::Value,
+    pub segment_id: Option<String>,
+    pub send_window: Option<SendWindow>,
+    pub assets: Vec<TemplateAsset>,
+    pub source_campaign_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<CampaignTemplate> for CampaignTemplateResponse {
+    fn from(t: CampaignTemplate) -> Self {
+        Self {
+            id: t.id.map(|id| id.id.to_string()).unwrap_or_default(),
+            name: t.name,
+            description: t.description,
+            objective: t.objective,
+            channels: t.channels,
+            prompt: t.prompt,
+            segment_definition: t.segment_definition,
+            segment_id: t.segment.map(|s| s.id.to_string()),
+            send_window: t.send_window,
+            assets: t.assets,
+            source_campaign_id: t.source_campaign.map(|c| c.id.to_string()),
+            created_at: t.created_at,
+            updated_at: t.updated_at,
+        }
+    }
+}
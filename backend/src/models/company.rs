@@ -1,38 +1,64 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use surrealdb::sql::Thing;
+use utoipa::ToSchema;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Company {
     pub id: Option<Thing>,
+    pub workspace: Thing,
     pub name: String,
     pub domain: Option<String>,
     pub industry: Option<String>,
     pub size: Option<String>,
     pub tags: Vec<String>,
+    /// ISO 3166-1 alpha-2 code, upper case
+    #[serde(default)]
+    pub country: Option<String>,
+    /// State, county or sales territory
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub city: Option<String>,
+    /// IANA timezone; campaign email to its contacts without one follows it
+    #[serde(default)]
+    pub timezone: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateCompanyRequest {
     pub name: String,
     pub domain: Option<String>,
     pub industry: Option<String>,
     pub size: Option<String>,
     pub tags: Option<Vec<String>>,
+    /// ISO 3166-1 alpha-2 code, e.g. `SE`
+    pub country: Option<String>,
+    pub region: Option<String>,
+    pub city: Option<String>,
+    /// IANA timezone, e.g. `Europe/Stockholm`
+    pub timezone: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateCompanyRequest {
     pub name: Option<String>,
     pub domain: Option<String>,
     pub industry: Option<String>,
     pub size: Option<String>,
     pub tags: Option<Vec<String>>,
+    /// An empty string clears it, as for the fields below
+    pub country: Option<String>,
+    pub region: Option<String>,
+    pub city: Option<String>,
+    pub timezone: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct CompanyQuery {
     pub search: Option<String>,
     pub industry: Option<String>,
@@ -41,7 +67,7 @@ pub struct CompanyQuery {
     pub offset: Option<u32>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CompanyResponse {
     pub id: String,
     pub name: String,
@@ -49,6 +75,10 @@ pub struct CompanyResponse {
     pub industry: Option<String>,
     pub size: Option<String>,
     pub tags: Vec<String>,
+    pub country: Option<String>,
+    pub region: Option<String>,
+    pub city: Option<String>,
+    pub timezone: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -62,6 +92,10 @@ impl From<Company> for CompanyResponse {
             industry: c.industry,
             size: c.size,
             tags: c.tags,
+            country: c.country,
+            region: c.region,
+            city: c.city,
+            timezone: c.timezone,
             created_at: c.created_at,
             updated_at: c.updated_at,
         }
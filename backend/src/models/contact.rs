@@ -1,9 +1,12 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use surrealdb::sql::Thing;
 use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+use crate::domain::{gravatar_url, DuplicateReason, MergeConflict, WinLossReason};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ContactStatus {
     Lead,
@@ -13,18 +16,64 @@ pub enum ContactStatus {
     Other,
 }
 
+impl From<crate::domain::ContactStatus> for ContactStatus {
+    fn from(status: crate::domain::ContactStatus) -> Self {
+        use crate::domain::ContactStatus as DomainStatus;
+
+        match status {
+            DomainStatus::Lead => ContactStatus::Lead,
+            DomainStatus::Customer => ContactStatus::Customer,
+            DomainStatus::Partner => ContactStatus::Partner,
+            DomainStatus::Investor => ContactStatus::Investor,
+            DomainStatus::Other => ContactStatus::Other,
+        }
+    }
+}
+
+/// Email consent; only subscribed contacts get campaign email
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionStatus {
+    #[default]
+    Subscribed,
+    Unsubscribed,
+    /// The address bounced for good
+    Bounced,
+    /// The contact marked our email as spam
+    Complained,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Contact {
     pub id: Option<Thing>,
+    pub workspace: Thing,
     pub first_name: String,
     pub last_name: String,
     pub email: String,
     pub phone: Option<String>,
     pub linkedin_url: Option<String>,
+    #[serde(default)]
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub city: Option<String>,
+    #[serde(default)]
+    pub avatar_url: Option<String>,
+    #[serde(default)]
+    pub pipeline_position: Option<f64>,
     pub tags: Vec<String>,
     pub status: ContactStatus,
+    #[serde(default)]
+    pub subscription_status: SubscriptionStatus,
+    #[serde(default)]
+    pub custom_fields: BTreeMap<String, String>,
     pub engagement_score: f64,
     pub company: Option<Thing>,
+    #[serde(default)]
+    pub owner: Option<Thing>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -36,9 +85,23 @@ pub struct CreateContactRequest {
     pub email: String,
     pub phone: Option<String>,
     pub linkedin_url: Option<String>,
+    /// IANA timezone, e.g. `Europe/Stockholm`
+    pub timezone: Option<String>,
+    /// ISO 3166-1 alpha-2 code, e.g. `SE`
+    pub country: Option<String>,
+    /// State, county or sales territory
+    pub region: Option<String>,
+    pub city: Option<String>,
     pub tags: Option<Vec<String>>,
     pub status: Option<ContactStatus>,
     pub company_id: Option<String>,
+    /// Workspace-defined fields, usable as `{{name}}` in campaign email
+    pub custom_fields: Option<BTreeMap<String, String>>,
+    /// Added to the contact's timeline as its first note
+    pub note: Option<String>,
+    /// Create the contact even if it looks like an existing one
+    #[serde(default)]
+    pub allow_duplicates: bool,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -48,24 +111,180 @@ pub struct UpdateContactRequest {
     pub email: Option<String>,
     pub phone: Option<String>,
     pub linkedin_url: Option<String>,
+    /// IANA timezone; an empty string clears it
+    pub timezone: Option<String>,
+    /// ISO 3166-1 alpha-2 code; an empty string clears it
+    pub country: Option<String>,
+    /// An empty string clears it
+    pub region: Option<String>,
+    /// An empty string clears it
+    pub city: Option<String>,
     pub tags: Option<Vec<String>>,
     pub status: Option<ContactStatus>,
+    /// Why the status changed; kept in the contact's status history
+    pub status_reason: Option<String>,
+    /// Why a lead was won or lost, when it becomes a customer or is moved to other
+    pub win_loss_reason: Option<WinLossReason>,
     pub engagement_score: Option<f64>,
     pub company_id: Option<String>,
+    /// Fields to set; a null or empty value removes the field, others are kept
+    pub custom_fields: Option<BTreeMap<String, Option<String>>>,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
 #[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ContactQuery {
     pub search: Option<String>,
     pub status: Option<ContactStatus>,
+    /// Comma-separated; a contact must carry every listed tag. Same as `tags_all`
     pub tags: Option<String>,
+    /// Comma-separated; a contact must carry every listed tag
+    pub tags_all: Option<String>,
+    /// Comma-separated; a contact must carry at least one listed tag
+    pub tags_any: Option<String>,
+    /// Comma-separated; a contact must carry none of the listed tags
+    pub exclude_tags: Option<String>,
     pub company_id: Option<String>,
+    /// ISO 3166-1 alpha-2 code, e.g. `SE`
+    pub country: Option<String>,
+    /// Matched ignoring case
+    pub region: Option<String>,
+    /// Matched ignoring case
+    pub city: Option<String>,
+    /// IANA timezone, e.g. `Europe/Stockholm`
+    pub timezone: Option<String>,
+    pub min_engagement: Option<f64>,
+    pub max_engagement: Option<f64>,
+    /// Created at or after this time (RFC 3339)
+    pub created_after: Option<DateTime<Utc>>,
+    /// Created before this time (RFC 3339)
+    pub created_before: Option<DateTime<Utc>>,
+    /// Last changed at or after this time (RFC 3339)
+    pub updated_after: Option<DateTime<Utc>>,
+    /// Last changed before this time (RFC 3339)
+    pub updated_before: Option<DateTime<Utc>>,
+    /// Defaults to `created_at`
+    pub sort: Option<ContactSort>,
+    /// Defaults to `asc` for `last_name`, `desc` otherwise
+    pub order: Option<SortOrder>,
     pub limit: Option<u32>,
     pub offset: Option<u32>,
 }
 
+/// Columns a contact list can be sorted by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ContactSort {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    EngagementScore,
+    LastName,
+}
+
+impl ContactSort {
+    /// The database column; only these fixed names ever reach a query
+    pub fn column(&self) -> &'static str {
+        match self {
+            ContactSort::CreatedAt => "created_at",
+            ContactSort::UpdatedAt => "updated_at",
+            ContactSort::EngagementScore => "engagement_score",
+            ContactSort::LastName => "last_name",
+        }
+    }
+
+    pub fn default_order(&self) -> SortOrder {
+        match self {
+            ContactSort::LastName => SortOrder::Asc,
+            _ => SortOrder::Desc,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// File format for contact exports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Json => "application/json",
+        }
+    }
+
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// Export-specific query params; filters come from ContactQuery
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ContactExportParams {
+    pub format: Option<ExportFormat>,
+}
+
+/// GET /api/contacts/:id/report
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ContactReportQuery {
+    /// Weeks of activity the chart covers; 26 by default, at most 104
+    pub weeks: Option<u32>,
+    /// Serve the report as a file download rather than a page to print
+    #[serde(default)]
+    pub download: bool,
+}
+
+/// GET /api/contacts/count
 #[derive(Debug, Serialize, ToSchema)]
+pub struct ContactCountResponse {
+    pub count: u64,
+}
+
+/// A tag in use, with how many contacts carry it
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AvatarQuery {
+    /// Wanted width in pixels; served at the nearest standard size (64, 128 or 256)
+    pub size: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct ContactResponse {
     pub id: String,
     pub first_name: String,
@@ -73,16 +292,26 @@ pub struct ContactResponse {
     pub email: String,
     pub phone: Option<String>,
     pub linkedin_url: Option<String>,
+    pub timezone: Option<String>,
+    pub country: Option<String>,
+    pub region: Option<String>,
+    pub city: Option<String>,
+    /// The uploaded avatar, or the contact's Gravatar
+    pub avatar_url: String,
     pub tags: Vec<String>,
     pub status: ContactStatus,
+    pub subscription_status: SubscriptionStatus,
+    pub custom_fields: BTreeMap<String, String>,
     pub engagement_score: f64,
     pub company_id: Option<String>,
+    pub owner_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
 impl From<Contact> for ContactResponse {
     fn from(c: Contact) -> Self {
+        let avatar_url = c.avatar_url.unwrap_or_else(|| gravatar_url(&c.email));
         Self {
             id: c.id.map(|t| t.id.to_string()).unwrap_or_default(),
             first_name: c.first_name,
@@ -90,10 +319,18 @@ impl From<Contact> for ContactResponse {
             email: c.email,
             phone: c.phone,
             linkedin_url: c.linkedin_url,
+            timezone: c.timezone,
+            country: c.country,
+            region: c.region,
+            city: c.city,
+            avatar_url,
             tags: c.tags,
             status: c.status,
+            subscription_status: c.subscription_status,
+            custom_fields: c.custom_fields,
             engagement_score: c.engagement_score,
             company_id: c.company.map(|t| t.id.to_string()),
+            owner_id: c.owner.map(|t| t.id.to_string()),
             created_at: c.created_at,
             updated_at: c.updated_at,
         }
@@ -103,17 +340,22 @@ impl From<Contact> for ContactResponse {
 impl ContactResponse {
     /// Create a ContactResponse from a StoredContact (domain + ID)
     pub fn from_stored(stored: crate::repositories::StoredContact) -> Self {
-        use crate::domain::ContactStatus as DomainStatus;
+        use crate::domain::SubscriptionStatus as DomainSubscription;
 
         // Convert domain status to API status
-        let status = match stored.contact.status {
-            DomainStatus::Lead => ContactStatus::Lead,
-            DomainStatus::Customer => ContactStatus::Customer,
-            DomainStatus::Partner => ContactStatus::Partner,
-            DomainStatus::Investor => ContactStatus::Investor,
-            DomainStatus::Other => ContactStatus::Other,
+        let status = ContactStatus::from(stored.contact.status);
+
+        let subscription_status = match stored.contact.subscription_status {
+            DomainSubscription::Subscribed => SubscriptionStatus::Subscribed,
+            DomainSubscription::Unsubscribed => SubscriptionStatus::Unsubscribed,
+            DomainSubscription::Bounced => SubscriptionStatus::Bounced,
+            DomainSubscription::Complained => SubscriptionStatus::Complained,
         };
 
+        let avatar_url = stored
+            .contact
+            .avatar_url
+            .unwrap_or_else(|| gravatar_url(&stored.contact.email));
         Self {
             id: stored.id,
             first_name: stored.contact.first_name,
@@ -121,12 +363,163 @@ impl ContactResponse {
             email: stored.contact.email,
             phone: stored.contact.phone,
             linkedin_url: stored.contact.linkedin_url,
+            timezone: stored.contact.timezone,
+            country: stored.contact.country,
+            region: stored.contact.region,
+            city: stored.contact.city,
+            avatar_url,
             tags: stored.contact.tags,
             status,
+            subscription_status,
+            custom_fields: stored.contact.custom_fields,
             engagement_score: stored.contact.engagement_score,
             company_id: stored.contact.company_id,
+            owner_id: stored.contact.owner_id,
             created_at: stored.contact.created_at,
             updated_at: stored.contact.updated_at,
         }
     }
 }
+
+/// POST /api/contacts/:id/churn
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ChurnContactRequest {
+    /// Why the customer left
+    pub reason: String,
+    /// When they left; defaults to now, and may be neither in the future nor
+    /// before the contact was created or last changed status
+    pub churned_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MergeContactsRequest {
+    /// Contact that survives the merge
+    pub primary_id: String,
+    /// Contact folded into the primary and then deleted
+    pub duplicate_id: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MergeContactsResponse {
+    pub contact: ContactResponse,
+    pub merged_contact_id: String,
+    /// Fields where the duplicate's value was discarded
+    pub conflicts: Vec<MergeConflict>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EnrichContactResponse {
+    pub contact: ContactResponse,
+    pub provider: String,
+    /// Fields written from the profile: `headline`, `company`, `location`
+    pub filled: Vec<String>,
+    /// Fields the profile had a value for, where the contact's own value was kept
+    pub kept: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DuplicateQuery {
+    pub limit: Option<usize>,
+}
+
+/// A pair of contacts that look like the same person
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DuplicateCandidate {
+    pub contact: ContactResponse,
+    pub duplicate: ContactResponse,
+    /// 0-1, higher is more certain
+    pub score: f64,
+    pub reasons: Vec<DuplicateReason>,
+}
+
+/// An existing contact that a contact about to be created may duplicate
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PossibleDuplicate {
+    pub contact: ContactResponse,
+    /// 0-1, higher is more certain
+    pub score: f64,
+    pub reasons: Vec<DuplicateReason>,
+}
+
+/// One change applied by `POST /api/contacts/bulk`, in request order
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BulkOperation {
+    AddTags { tags: Vec<String> },
+    RemoveTags { tags: Vec<String> },
+    SetStatus { status: ContactStatus },
+    /// `null` clears the owner
+    SetOwner { owner_id: Option<String> },
+}
+
+/// POST /api/contacts/batch-get
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BatchGetContactsRequest {
+    /// At most 500; repeated IDs are returned once
+    pub ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchGetContactsResponse {
+    /// In the order requested
+    pub contacts: Vec<ContactResponse>,
+    /// Requested IDs that don't exist, are trashed or belong to another workspace
+    pub not_found: Vec<String>,
+}
+
+/// Give either `contact_ids` or `segment_definition`, not both
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BulkContactsRequest {
+    pub contact_ids: Option<Vec<String>>,
+    /// Same shape as a segment definition; unsubscribed contacts match too
+    pub segment_definition: Option<serde_json::Value>,
+    pub operations: Vec<BulkOperation>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkResultStatus {
+    Updated,
+    /// Every operation was already satisfied
+    Unchanged,
+    /// An operation was refused, e.g. a disallowed status transition
+    Failed,
+    NotFound,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BulkContactResult {
+    pub contact_id: String,
+    pub status: BulkResultStatus,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changed_fields: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BulkContactsResponse {
+    /// Contacts targeted by the request
+    pub matched: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    /// Failed or not found
+    pub failed: usize,
+    pub results: Vec<BulkContactResult>,
+}
+
+impl BulkContactsResponse {
+    pub fn from_results(results: Vec<BulkContactResult>) -> Self {
+        let count = |status: BulkResultStatus| results.iter().filter(|r| r.status == status).count();
+
+        Self {
+            matched: results.len(),
+            updated: count(BulkResultStatus::Updated),
+            unchanged: count(BulkResultStatus::Unchanged),
+            failed: count(BulkResultStatus::Failed) + count(BulkResultStatus::NotFound),
+            results,
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+use crate::domain::{weighted_value, DealStage, WinLossReason};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Deal {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub name: String,
+    pub value: f64,
+    pub stage: DealStage,
+    pub expected_close_date: Option<DateTime<Utc>>,
+    /// Set when the deal is won or lost, cleared when it is reopened
+    pub closed_at: Option<DateTime<Utc>>,
+    /// Why the deal was won or lost, cleared along with `closed_at`
+    #[serde(default)]
+    pub win_loss_reason: Option<WinLossReason>,
+    pub contact: Option<Thing>,
+    pub company: Option<Thing>,
+    pub notes: Option<String>,
+    /// Manual order on the pipeline board; `None` until first moved there
+    #[serde(default)]
+    pub pipeline_position: Option<f64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateDealRequest {
+    pub name: String,
+    pub value: f64,
+    pub stage: Option<DealStage>,
+    pub expected_close_date: Option<DateTime<Utc>>,
+    pub contact_id: Option<String>,
+    pub company_id: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// Stage is changed through `POST /api/deals/:id/stage`, not here
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateDealRequest {
+    pub name: Option<String>,
+    pub value: Option<f64>,
+    pub expected_close_date: Option<DateTime<Utc>>,
+    pub contact_id: Option<String>,
+    pub company_id: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DealStageRequest {
+    pub stage: DealStage,
+    /// Why the deal was won or lost; only for `closed_won` and `closed_lost`
+    pub win_loss_reason: Option<WinLossReason>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DealQuery {
+    pub stage: Option<DealStage>,
+    pub contact_id: Option<String>,
+    pub company_id: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DealResponse {
+    pub id: String,
+    pub name: String,
+    pub value: f64,
+    /// Value weighted by the stage's win probability
+    pub weighted_value: f64,
+    pub stage: DealStage,
+    pub expected_close_date: Option<DateTime<Utc>>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub win_loss_reason: Option<WinLossReason>,
+    pub contact_id: Option<String>,
+    pub company_id: Option<String>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Deal> for DealResponse {
+    fn from(d: Deal) -> Self {
+        Self {
+            id: d.id.map(|t| t.id.to_string()).unwrap_or_default(),
+            name: d.name,
+            value: d.value,
+            weighted_value: weighted_value(d.value, d.stage),
+            stage: d.stage,
+            expected_close_date: d.expected_close_date,
+            closed_at: d.closed_at,
+            win_loss_reason: d.win_loss_reason,
+            contact_id: d.contact.map(|t| t.id.to_string()),
+            company_id: d.company.map(|t| t.id.to_string()),
+            notes: d.notes,
+            created_at: d.created_at,
+            updated_at: d.updated_at,
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+use super::ContactResponse;
+use crate::domain::{DedupeStatus, DuplicateReason};
+
+/// A pair of contacts queued for review; `contact` is the older of the two
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DedupeSuggestion {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub contact: Thing,
+    pub duplicate: Thing,
+    /// Similarity of the two contacts' embeddings, 0-1
+    pub confidence: f64,
+    /// Duplicate rules the pair also matches, if any
+    #[serde(default)]
+    pub reasons: Vec<DuplicateReason>,
+    #[serde(default)]
+    pub status: DedupeStatus,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub resolved_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub resolved_by: Option<Thing>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DedupeSuggestionQuery {
+    /// Defaults to `pending`
+    pub status: Option<DedupeStatus>,
+    pub limit: Option<u32>,
+}
+
+/// POST /api/contacts/dedupe-suggestions/:id/accept
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct AcceptDedupeRequest {
+    /// Contact to keep; the other is merged into it. Defaults to the older contact.
+    pub primary_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DedupeSuggestionResponse {
+    pub id: String,
+    pub contact_id: String,
+    pub duplicate_id: String,
+    /// `None` once the contact is merged away or deleted
+    pub contact: Option<ContactResponse>,
+    pub duplicate: Option<ContactResponse>,
+    /// 0-1, higher is more certain
+    pub confidence: f64,
+    pub reasons: Vec<DuplicateReason>,
+    pub status: DedupeStatus,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,15 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// What one provider callback did
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct EmailEventsResponse {
+    /// Bounces and complaints in the callback
+    pub received: usize,
+    /// Reports stored on a contact's timeline, once per contact with the address
+    pub recorded: usize,
+    /// Reports for addresses no contact has
+    pub unmatched: usize,
+    /// Whether the callback was an SNS subscription confirmation, now confirmed
+    pub confirmed: bool,
+}
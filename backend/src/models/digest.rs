@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+use crate::domain::{DigestPreferences, RenderedDigest};
+
+/// A member's stored digest settings; members who never saved any get the defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestSettings {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub user: Thing,
+    #[serde(default)]
+    pub preferences: DigestPreferences,
+    pub last_sent_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DigestPreferencesResponse {
+    pub preferences: DigestPreferences,
+    pub last_sent_at: Option<DateTime<Utc>>,
+    /// When the next digest goes out; absent while digests are turned off
+    pub next_digest_at: Option<DateTime<Utc>>,
+}
+
+/// GET /api/digest/preview
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DigestPreview {
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+impl From<RenderedDigest> for DigestPreview {
+    fn from(digest: RenderedDigest) -> Self {
+        Self {
+            subject: digest.subject,
+            html: digest.html,
+            text: digest.text,
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::domain::EmailDirection;
+
+/// One email of a conversation
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ThreadMessage {
+    /// The timeline entry recording the email
+    pub entry_id: String,
+    pub direction: EmailDirection,
+    pub subject: Option<String>,
+    /// The synced snippet, or the entry's content when there is none
+    pub preview: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// A conversation with a contact, collapsed into one item
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EmailThreadResponse {
+    /// The timeline entry ID of the thread's first email
+    pub thread_id: String,
+    /// The subject of the thread's first email
+    pub subject: Option<String>,
+    pub message_count: usize,
+    pub sent_count: usize,
+    pub received_count: usize,
+    pub started_at: DateTime<Utc>,
+    pub last_message_at: DateTime<Utc>,
+    /// Received when the contact wrote last, so the thread is waiting on us
+    pub last_direction: EmailDirection,
+    /// Mean time we took to answer the contact
+    pub our_avg_response_minutes: Option<f64>,
+    /// Mean time the contact took to answer us
+    pub their_avg_response_minutes: Option<f64>,
+    /// Oldest first
+    pub messages: Vec<ThreadMessage>,
+}
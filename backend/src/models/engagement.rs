@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::domain::{EngagementLevel, EngagementTrend, InteractionType, ScoreContribution};
+
+pub use crate::ai::ai_summary::{ActivityTrend, SentimentTrend};
+
+/// Result of recomputing one contact's engagement score
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EngagementRecalculationResponse {
+    pub contact_id: String,
+    pub previous_score: f64,
+    pub engagement_score: f64,
+    pub level: EngagementLevel,
+    /// Timeline entries that counted towards the score
+    pub interactions: usize,
+}
+
+/// How much one interaction type contributes to a contact's score
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InteractionContribution {
+    pub interaction_type: InteractionType,
+    /// Time-decayed points before normalization
+    pub score: f64,
+}
+
+/// Engagement overview for one contact
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ContactEngagementResponse {
+    pub contact_id: String,
+    pub engagement_score: f64,
+    pub level: EngagementLevel,
+    pub recommended_action: &'static str,
+    pub trend: EngagementTrend,
+    /// Positive when engagement is accelerating, negative when it is cooling off
+    pub velocity: f64,
+    pub top_interaction_types: Vec<InteractionContribution>,
+}
+
+/// Score contribution per interaction type
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EngagementBreakdownResponse {
+    pub contact_id: String,
+    /// Score recomputed from the timeline; the contributions sum to it
+    pub engagement_score: f64,
+    pub contributions: Vec<ScoreContribution>,
+}
+
+/// What to know about a contact, from their timeline
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ContactSummaryResponse {
+    pub contact_id: String,
+    pub summary: String,
+    pub engagement_score: f64,
+    pub trend: ActivityTrend,
+    pub sentiment_trend: SentimentTrend,
+    pub recommendation: String,
+    pub next_best_action: String,
+    /// Written by the configured AI provider; `false` when it came from the built-in rules
+    pub ai_generated: bool,
+}
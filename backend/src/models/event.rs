@@ -1,8 +1,13 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use surrealdb::sql::Thing;
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::domain::{EventReminders, RegistrationRules};
+
+pub use crate::domain::RsvpStatus;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum EventType {
     Webinar,
@@ -15,6 +20,7 @@ pub enum EventType {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
     pub id: Option<Thing>,
+    pub workspace: Thing,
     pub campaign: Option<Thing>,
     pub name: String,
     #[serde(rename = "type")]
@@ -23,28 +29,42 @@ pub struct Event {
     pub start_time: DateTime<Utc>,
     pub end_time: DateTime<Utc>,
     pub location: String,
+    /// Most contacts that can hold a seat; None for no limit
+    #[serde(default)]
+    pub max_attendees: Option<u32>,
+    /// Registrations after this are refused; None keeps registration open
+    #[serde(default)]
+    pub registration_deadline: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub reminders: EventReminders,
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum RsvpStatus {
-    Invited,
-    Registered,
-    Attended,
-    NoShow,
+impl Event {
+    pub fn registration_rules(&self) -> RegistrationRules {
+        RegistrationRules {
+            max_attendees: self.max_attendees,
+            registration_deadline: self.registration_deadline,
+            start_time: self.start_time,
+            end_time: self.end_time,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Rsvp {
     pub id: Option<Thing>,
+    pub workspace: Thing,
     pub event: Thing,
     pub contact: Thing,
     pub status: RsvpStatus,
     pub timestamp: DateTime<Utc>,
+    /// Keys of the reminder and follow-up emails already sent
+    #[serde(default)]
+    pub emails_sent: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateEventRequest {
     pub campaign_id: Option<String>,
     pub name: String,
@@ -54,20 +74,27 @@ pub struct CreateEventRequest {
     pub start_time: DateTime<Utc>,
     pub end_time: DateTime<Utc>,
     pub location: String,
+    /// Registrations beyond this are waitlisted
+    pub max_attendees: Option<u32>,
+    /// Must be no later than `start_time`
+    pub registration_deadline: Option<DateTime<Utc>>,
+    /// Defaults to reminders a day and an hour before, and a follow-up an hour after
+    pub reminders: Option<EventReminders>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct InviteRequest {
     pub contact_ids: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct RsvpRequest {
     pub contact_id: String,
+    /// `registered` becomes `waitlisted` when the event is full
     pub status: RsvpStatus,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct EventResponse {
     pub id: String,
     pub campaign_id: Option<String>,
@@ -78,6 +105,9 @@ pub struct EventResponse {
     pub start_time: DateTime<Utc>,
     pub end_time: DateTime<Utc>,
     pub location: String,
+    pub max_attendees: Option<u32>,
+    pub registration_deadline: Option<DateTime<Utc>>,
+    pub reminders: EventReminders,
     pub created_at: DateTime<Utc>,
 }
 
@@ -92,12 +122,15 @@ impl From<Event> for EventResponse {
             start_time: e.start_time,
             end_time: e.end_time,
             location: e.location,
+            max_attendees: e.max_attendees,
+            registration_deadline: e.registration_deadline,
+            reminders: e.reminders,
             created_at: e.created_at,
         }
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct RsvpResponse {
     pub id: String,
     pub event_id: String,
@@ -117,3 +150,69 @@ impl From<Rsvp> for RsvpResponse {
         }
     }
 }
+
+/// Identify the contact by ID, or by the signed token from their QR code
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CheckinRequest {
+    pub contact_id: Option<String>,
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BulkCheckinRequest {
+    pub checkins: Vec<CheckinRequest>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckinStatus {
+    CheckedIn,
+    /// The contact had already been checked in
+    Unchanged,
+    /// Refused, e.g. an invalid token or check-in not open yet
+    Failed,
+    NotFound,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CheckinResult {
+    /// `None` when a token couldn't be read
+    pub contact_id: Option<String>,
+    pub status: CheckinStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rsvp: Option<RsvpResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BulkCheckinResponse {
+    pub checked_in: usize,
+    pub unchanged: usize,
+    /// Failed or not found
+    pub failed: usize,
+    /// In request order
+    pub results: Vec<CheckinResult>,
+}
+
+impl BulkCheckinResponse {
+    pub fn from_results(results: Vec<CheckinResult>) -> Self {
+        let count = |status: CheckinStatus| results.iter().filter(|r| r.status == status).count();
+
+        Self {
+            checked_in: count(CheckinStatus::CheckedIn),
+            unchanged: count(CheckinStatus::Unchanged),
+            failed: count(CheckinStatus::Failed) + count(CheckinStatus::NotFound),
+            results,
+        }
+    }
+}
+
+/// What a contact's check-in QR code encodes
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CheckinTokenResponse {
+    pub event_id: String,
+    pub contact_id: String,
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
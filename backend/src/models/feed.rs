@@ -0,0 +1,45 @@
+use serde::Serialize;
+
+use super::{ContactResponse, RsvpResponse, TimelineEntryResponse};
+
+/// What happened to a contact
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeAction {
+    Created,
+    Updated,
+    /// Moved to the trash or removed for good
+    Deleted,
+}
+
+/// A contact change seen by the database live query, whoever made it
+#[derive(Debug, Clone, Serialize)]
+pub struct ContactChange {
+    pub action: ChangeAction,
+    pub contact: ContactResponse,
+}
+
+/// Something that happened in a workspace, pushed to live activity feeds
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum FeedEvent {
+    TimelineEntryCreated(TimelineEntryResponse),
+    ContactCreated(ContactResponse),
+    ContactChanged(ContactChange),
+    RsvpChanged(RsvpResponse),
+    /// Events may have been missed; reload over the REST API
+    Resync,
+}
+
+impl FeedEvent {
+    /// SSE event name, so clients can listen for one kind only
+    pub fn name(&self) -> &'static str {
+        match self {
+            FeedEvent::TimelineEntryCreated(_) => "timeline_entry_created",
+            FeedEvent::ContactCreated(_) => "contact_created",
+            FeedEvent::ContactChanged(_) => "contact_changed",
+            FeedEvent::RsvpChanged(_) => "rsvp_changed",
+            FeedEvent::Resync => "resync",
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+use super::{
+    AttachmentResponse, AuditLogResponse, ContactResponse, DealResponse, StatusHistoryResponse, TimelineEntryResponse,
+};
+
+/// Everything stored about a contact, for a data subject access request
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GdprExport {
+    pub exported_at: DateTime<Utc>,
+    pub contact: ContactResponse,
+    /// Oldest first
+    pub timeline: Vec<TimelineEntryResponse>,
+    pub rsvps: Vec<GdprRsvp>,
+    pub campaigns: Vec<GdprCampaignMembership>,
+    pub sequences: Vec<GdprSequenceEnrollment>,
+    pub deals: Vec<DealResponse>,
+    /// Metadata only; the files download from `/api/attachments/{id}/download`
+    pub attachments: Vec<AttachmentResponse>,
+    pub landing_page_visits: Vec<GdprLandingPageVisit>,
+    pub short_links: Vec<GdprShortLink>,
+    pub status_history: Vec<StatusHistoryResponse>,
+    /// Webhook deliveries carrying the contact's data
+    pub webhook_deliveries: Vec<GdprWebhookDelivery>,
+    /// Responses kept for retried requests that mention the contact
+    pub stored_responses: Vec<GdprStoredResponse>,
+    /// What was embedded for duplicate detection; the vector itself is left out
+    pub embeddings: Vec<GdprEmbedding>,
+    /// Suggestions that the contact and another are the same person
+    pub duplicate_suggestions: Vec<GdprDuplicateSuggestion>,
+    /// Changes made to the contact, oldest first
+    pub history: Vec<AuditLogResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GdprRsvp {
+    pub event_id: String,
+    pub event_name: Option<String>,
+    pub status: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GdprCampaignMembership {
+    pub campaign_id: String,
+    pub campaign_name: Option<String>,
+    /// Address the campaign was sent to
+    pub email: String,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GdprSequenceEnrollment {
+    pub sequence_id: String,
+    pub sequence_name: Option<String>,
+    pub status: String,
+    pub enrolled_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GdprLandingPageVisit {
+    pub campaign_id: String,
+    pub referrer: Option<String>,
+    pub utm: serde_json::Value,
+    pub visited_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GdprShortLink {
+    pub campaign_id: String,
+    pub channel: String,
+    pub url: String,
+    pub clicks: u64,
+    pub last_clicked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GdprWebhookDelivery {
+    pub event: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GdprStoredResponse {
+    /// The request's Idempotency-Key
+    pub key: String,
+    pub status: Option<u16>,
+    pub body: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GdprEmbedding {
+    pub text: String,
+    pub model: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GdprDuplicateSuggestion {
+    /// The contact this one was suggested as a duplicate of
+    pub other_contact_id: String,
+    pub confidence: f64,
+    pub reasons: Vec<String>,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Erasure log entry; holds no personal data itself
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GdprErasure {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub contact: Thing,
+    pub erased_by: Option<Thing>,
+    pub timeline_entries: u64,
+    pub attachments: u64,
+    pub erased_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GdprErasureResponse {
+    pub id: String,
+    pub contact_id: String,
+    /// User who requested the erasure
+    pub erased_by: Option<String>,
+    /// Timeline entries whose content was erased
+    pub timeline_entries: u64,
+    /// Attached files deleted
+    pub attachments: u64,
+    pub erased_at: DateTime<Utc>,
+}
+
+impl From<GdprErasure> for GdprErasureResponse {
+    fn from(e: GdprErasure) -> Self {
+        Self {
+            id: e.id.map(|t| t.id.to_string()).unwrap_or_default(),
+            contact_id: e.contact.id.to_string(),
+            erased_by: e.erased_by.map(|t| t.id.to_string()),
+            timeline_entries: e.timeline_entries,
+            attachments: e.attachments,
+            erased_at: e.erased_at,
+        }
+    }
+}
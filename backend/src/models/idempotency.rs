@@ -0,0 +1,24 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+
+/// A POST seen with an Idempotency-Key, and its response once finished
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    pub id: Option<Thing>,
+    /// Workspace the key belongs to, or `public` for unauthenticated routes
+    pub scope: String,
+    pub key: String,
+    /// Fingerprint of method, path and body; a reused key must match it
+    pub request_hash: String,
+    /// None while the first request is still running
+    pub status: Option<u16>,
+    pub body: Option<String>,
+    pub content_type: Option<String>,
+    /// While unfinished, retries wait until this passes and may then take
+    /// the key over from a request that never finished
+    #[serde(default)]
+    pub locked_until: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
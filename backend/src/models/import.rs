@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    Hubspot,
+    Pipedrive,
+}
+
+impl ImportSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportSource::Hubspot => "hubspot",
+            ImportSource::Pipedrive => "pipedrive",
+        }
+    }
+
+    /// Name to show people
+    pub fn label(&self) -> &'static str {
+        match self {
+            ImportSource::Hubspot => "HubSpot",
+            ImportSource::Pipedrive => "Pipedrive",
+        }
+    }
+}
+
+/// What happens to records that already exist here
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Only create what's missing; existing records are left alone
+    #[default]
+    Create,
+    /// Also update existing records, found by earlier imports, email or domain
+    Upsert,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+/// How far along one kind of record is
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ImportCounts {
+    pub total: u32,
+    pub created: u32,
+    pub updated: u32,
+    /// Already here, and left alone
+    pub skipped: u32,
+    pub failed: u32,
+}
+
+impl ImportCounts {
+    pub fn processed(&self) -> u32 {
+        self.created + self.updated + self.skipped + self.failed
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ImportProgress {
+    pub companies: ImportCounts,
+    pub contacts: ImportCounts,
+    pub deals: ImportCounts,
+    pub notes: ImportCounts,
+}
+
+/// A record that couldn't be imported
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ImportFailure {
+    /// `company`, `contact`, `deal` or `note`
+    pub kind: String,
+    /// Its ID in the other CRM
+    pub external_id: String,
+    pub reason: String,
+}
+
+/// One run of an importer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportJob {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub source: ImportSource,
+    pub mode: ImportMode,
+    pub status: ImportStatus,
+    #[serde(default)]
+    pub progress: ImportProgress,
+    /// The first failures; `progress` has the full counts
+    #[serde(default)]
+    pub failures: Vec<ImportFailure>,
+    /// Why the whole run failed
+    pub error: Option<String>,
+    pub started_by: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Set while running; unique, so a workspace runs one import per source
+    #[serde(default)]
+    pub running_key: Option<String>,
+    /// Renewed while the import runs; a running job whose lease has lapsed
+    /// was abandoned and no longer blocks a new one
+    #[serde(default)]
+    pub lease_expires_at: Option<DateTime<Utc>>,
+}
+
+/// POST /api/import/hubspot
+///
+/// Give either an `api_key` (a private app access token) to read the
+/// portal over the HubSpot API, or an `export` with the same objects.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct HubspotImportRequest {
+    pub api_key: Option<String>,
+    /// `{ contacts, companies, deals, notes }`, each a list of HubSpot CRM
+    /// objects (`{ id, properties, associations? }`)
+    pub export: Option<serde_json::Value>,
+    #[serde(default)]
+    pub mode: ImportMode,
+}
+
+/// POST /api/import/pipedrive
+///
+/// Give either an `api_token` to read the company account over the
+/// Pipedrive API, or an `export` with the same objects.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PipedriveImportRequest {
+    pub api_token: Option<String>,
+    /// `{ persons, organizations, deals, notes }`, each a list of Pipedrive
+    /// objects as the API returns them
+    pub export: Option<serde_json::Value>,
+    #[serde(default)]
+    pub mode: ImportMode,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ImportJobResponse {
+    pub id: String,
+    pub source: ImportSource,
+    pub mode: ImportMode,
+    pub status: ImportStatus,
+    pub progress: ImportProgress,
+    pub failures: Vec<ImportFailure>,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl From<ImportJob> for ImportJobResponse {
+    fn from(j: ImportJob) -> Self {
+        Self {
+            id: j.id.map(|t| t.id.to_string()).unwrap_or_default(),
+            source: j.source,
+            mode: j.mode,
+            status: j.status,
+            progress: j.progress,
+            failures: j.failures,
+            error: j.error,
+            started_at: j.started_at,
+            finished_at: j.finished_at,
+        }
+    }
+}
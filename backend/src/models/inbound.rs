@@ -0,0 +1,74 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+use crate::domain::InboundMapping;
+
+/// An external tool allowed to push JSON into a workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InboundSource {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub name: String,
+    /// Secret part of the ingestion URL; identifies the source and its workspace
+    pub token: String,
+    pub mapping: InboundMapping,
+    pub active: bool,
+    pub last_received_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// POST /api/inbound-sources
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateInboundSourceRequest {
+    /// e.g. "Typeform - demo requests"
+    pub name: String,
+    pub mapping: InboundMapping,
+}
+
+/// PATCH /api/inbound-sources/:id
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateInboundSourceRequest {
+    pub name: Option<String>,
+    pub mapping: Option<InboundMapping>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InboundSourceResponse {
+    pub id: String,
+    pub name: String,
+    /// Payloads are POSTed to /api/inbound/{token}
+    pub token: String,
+    pub mapping: InboundMapping,
+    pub active: bool,
+    pub last_received_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<InboundSource> for InboundSourceResponse {
+    fn from(s: InboundSource) -> Self {
+        Self {
+            id: s.id.map(|t| t.id.to_string()).unwrap_or_default(),
+            name: s.name,
+            token: s.token,
+            mapping: s.mapping,
+            active: s.active,
+            last_received_at: s.last_received_at,
+            created_at: s.created_at,
+            updated_at: s.updated_at,
+        }
+    }
+}
+
+/// What one ingested payload did
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InboundIngestResponse {
+    pub contact_id: String,
+    /// Whether the payload created the contact rather than updating one
+    pub created: bool,
+    pub timeline_entry_id: String,
+}
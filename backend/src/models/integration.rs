@@ -0,0 +1,163 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrationProvider {
+    Imap,
+    Gmail,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrationStatus {
+    /// Waiting for the OAuth consent screen to come back
+    Pending,
+    Active,
+    /// The last sync failed; retried on the next run
+    Error,
+}
+
+/// Login for an IMAP mailbox
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImapSettings {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    pub mailbox: String,
+}
+
+/// OAuth tokens of a connected account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthTokens {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// How far a mailbox has been synced
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncCursor {
+    /// IMAP: UIDs are only comparable within one UIDVALIDITY
+    pub uid_validity: Option<u32>,
+    pub last_uid: Option<u32>,
+    /// Gmail: messages received after this are fetched next
+    pub last_received_at: Option<DateTime<Utc>>,
+    /// Gmail: where listing resumes when a pass didn't finish in one sync
+    #[serde(default)]
+    pub page_token: Option<String>,
+    /// Gmail: the pass in progress lists messages received up to this
+    #[serde(default)]
+    pub pass_until: Option<DateTime<Utc>>,
+}
+
+/// A mailbox connected to a workspace for inbound email sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Integration {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub provider: IntegrationProvider,
+    /// Address of the connected mailbox; empty until OAuth completes
+    pub account: String,
+    pub status: IntegrationStatus,
+    /// Create a lead for senders that match no contact
+    pub create_unknown_contacts: bool,
+    pub imap: Option<ImapSettings>,
+    pub oauth: Option<OAuthTokens>,
+    /// Ties the OAuth callback to this integration
+    pub oauth_state: Option<String>,
+    #[serde(default)]
+    pub cursor: SyncCursor,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// POST /api/integrations/imap
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateImapIntegrationRequest {
+    pub host: String,
+    /// Defaults to 993 (IMAP over TLS)
+    pub port: Option<u16>,
+    pub username: String,
+    pub password: String,
+    /// Defaults to INBOX
+    pub mailbox: Option<String>,
+    #[serde(default)]
+    pub create_unknown_contacts: bool,
+}
+
+/// POST /api/integrations/gmail
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateGmailIntegrationRequest {
+    #[serde(default)]
+    pub create_unknown_contacts: bool,
+}
+
+/// PATCH /api/integrations/:id
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateIntegrationRequest {
+    pub create_unknown_contacts: Option<bool>,
+}
+
+/// Where to send the user to grant access to their Gmail account
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GmailAuthorizationResponse {
+    pub integration_id: String,
+    pub authorization_url: String,
+}
+
+/// Query Google redirects back to the OAuth callback with
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OAuthCallbackQuery {
+    pub state: String,
+    pub code: Option<String>,
+    /// Set when the user declined
+    pub error: Option<String>,
+}
+
+/// Credentials are never returned
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IntegrationResponse {
+    pub id: String,
+    pub provider: IntegrationProvider,
+    pub account: String,
+    pub status: IntegrationStatus,
+    pub create_unknown_contacts: bool,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Integration> for IntegrationResponse {
+    fn from(i: Integration) -> Self {
+        Self {
+            id: i.id.map(|t| t.id.to_string()).unwrap_or_default(),
+            provider: i.provider,
+            account: i.account,
+            status: i.status,
+            create_unknown_contacts: i.create_unknown_contacts,
+            last_synced_at: i.last_synced_at,
+            last_error: i.last_error,
+            created_at: i.created_at,
+        }
+    }
+}
+
+/// What one sync of a mailbox did
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct InboxSyncResponse {
+    /// New messages read from the mailbox
+    pub fetched: usize,
+    /// Messages put on a contact's timeline
+    pub recorded: usize,
+    /// Contacts created for unknown senders
+    pub contacts_created: usize,
+    /// Unknown senders, already-synced messages and our own mail
+    pub skipped: usize,
+}
@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+/// Campaign attribution from a landing page link
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct UtmParams {
+    pub source: Option<String>,
+    pub medium: Option<String>,
+    pub campaign: Option<String>,
+    pub term: Option<String>,
+    pub content: Option<String>,
+}
+
+/// GET /lp/:id?utm_source=...&utm_medium=...
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LandingPageQuery {
+    pub utm_source: Option<String>,
+    pub utm_medium: Option<String>,
+    pub utm_campaign: Option<String>,
+    pub utm_term: Option<String>,
+    pub utm_content: Option<String>,
+}
+
+impl LandingPageQuery {
+    /// The UTM parameters, with blank ones dropped
+    pub fn utm(self) -> UtmParams {
+        let clean = |v: Option<String>| {
+            v.map(|v| v.trim().chars().take(200).collect::<String>())
+                .filter(|v| !v.is_empty())
+        };
+
+        UtmParams {
+            source: clean(self.utm_source),
+            medium: clean(self.utm_medium),
+            campaign: clean(self.utm_campaign),
+            term: clean(self.utm_term),
+            content: clean(self.utm_content),
+        }
+    }
+}
+
+/// A visitor's first view of a landing page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LandingPageVisit {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub campaign: Thing,
+    /// The landing_page asset that was viewed
+    pub page: Thing,
+    /// From the visitor cookie
+    pub visitor_id: String,
+    /// Set once the visitor submits a form, here or on another page
+    pub contact: Option<Thing>,
+    #[serde(default)]
+    pub utm: UtmParams,
+    pub referrer: Option<String>,
+    pub visited_at: DateTime<Utc>,
+}
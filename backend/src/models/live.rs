@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+use super::{ChangeAction, ContactResponse};
+
+/// A message on the /ws/contacts socket
+///
+/// Every message carries a `cursor`; a client that reconnects with the
+/// last one it saw is sent the changes it missed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContactStreamMessage {
+    Change {
+        cursor: String,
+        action: ChangeAction,
+        contact: Box<ContactResponse>,
+    },
+    /// Changes may have been missed; reload contacts over the REST API
+    Resync { cursor: String },
+}
+
+/// Credentials and resume point for opening /ws/contacts
+///
+/// Browsers cannot set headers on a WebSocket handshake, so they pass a
+/// one-time ticket instead of the access token.
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LiveSocketQuery {
+    /// From POST /api/live/tickets; not needed with an Authorization header
+    pub ticket: Option<String>,
+    /// Cursor of the last message received, to resume after it
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LiveTicketResponse {
+    pub ticket: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A one-time ticket for opening a live socket, stored until used or expired
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveTicket {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub user: Thing,
+    pub email: String,
+    pub expires_at: DateTime<Utc>,
+}
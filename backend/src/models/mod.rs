@@ -1,11 +1,79 @@
+pub mod ai_usage;
+pub mod analytics;
+pub mod assistant;
+pub mod attachment;
+pub mod audit;
 pub mod contact;
 pub mod company;
+pub mod deal;
+pub mod dedupe;
+pub mod deliverability;
+pub mod digest;
+pub mod email_thread;
+pub mod engagement;
 pub mod timeline;
 pub mod campaign;
+pub mod campaign_template;
 pub mod event;
+pub mod feed;
+pub mod gdpr;
+pub mod idempotency;
+pub mod import;
+pub mod inbound;
+pub mod integration;
+pub mod landing_page;
+pub mod live;
+pub mod pipeline;
+pub mod recommendation;
+pub mod relationship;
+pub mod search;
+pub mod segment;
+pub mod sender;
+pub mod sequence;
+pub mod short_link;
+pub mod social;
+pub mod status_history;
+pub mod trash;
+pub mod user;
+pub mod webhook;
+pub mod workspace;
 
+pub use ai_usage::*;
+pub use analytics::*;
+pub use assistant::*;
+pub use attachment::*;
+pub use audit::*;
 pub use contact::*;
 pub use company::*;
+pub use deal::*;
+pub use dedupe::*;
+pub use deliverability::*;
+pub use digest::*;
+pub use email_thread::*;
+pub use engagement::*;
 pub use timeline::*;
 pub use campaign::*;
+pub use campaign_template::*;
 pub use event::*;
+pub use feed::*;
+pub use gdpr::*;
+pub use idempotency::*;
+pub use import::*;
+pub use inbound::*;
+pub use integration::*;
+pub use landing_page::*;
+pub use live::*;
+pub use pipeline::*;
+pub use recommendation::*;
+pub use relationship::*;
+pub use search::*;
+pub use segment::*;
+pub use sender::*;
+pub use sequence::*;
+pub use short_link::*;
+pub use social::*;
+pub use status_history::*;
+pub use trash::*;
+pub use user::*;
+pub use webhook::*;
+pub use workspace::*;
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{ContactResponse, DealResponse};
+use crate::domain::WinLossReason;
+
+/// What the pipeline board shows: contacts by status, or deals by stage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PipelineKind {
+    #[default]
+    Contacts,
+    Deals,
+}
+
+impl PipelineKind {
+    /// Table holding this kind of card
+    pub fn table(&self) -> &'static str {
+        match self {
+            PipelineKind::Contacts => "contact",
+            PipelineKind::Deals => "deal",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PipelineBoardQuery {
+    /// Defaults to `contacts`
+    pub kind: Option<PipelineKind>,
+    /// Most cards returned per column, default 50
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PipelineBoard {
+    pub kind: PipelineKind,
+    /// One column per contact status or deal stage, in pipeline order
+    pub columns: Vec<PipelineColumn>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PipelineColumn {
+    /// Contact status or deal stage
+    pub status: String,
+    /// Cards in the column, including those past the limit
+    pub total: u64,
+    /// In board order: cards never moved by hand first, newest first
+    pub cards: Vec<PipelineCard>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum PipelineCard {
+    /// Boxed: a contact is much larger than a deal
+    Contact(Box<ContactResponse>),
+    Deal(DealResponse),
+}
+
+/// A card dragged to a column, and where in it
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PipelineMoveRequest {
+    pub kind: PipelineKind,
+    /// Contact or deal ID
+    pub id: String,
+    /// Target column: a contact status or deal stage
+    pub status: String,
+    /// Place in the target column, not counting the moved card; the end when omitted
+    pub index: Option<usize>,
+    /// Why the deal or lead was won or lost, when the move closes or converts it
+    pub win_loss_reason: Option<WinLossReason>,
+}
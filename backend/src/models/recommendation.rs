@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::domain::{ActionPriority, DealStage, EngagementLevel, EngagementTrend, InteractionType, NextActionKind};
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RecommendationQuery {
+    /// Only actions of this priority
+    pub priority: Option<ActionPriority>,
+    /// Defaults to 50, at most 200
+    pub limit: Option<u32>,
+}
+
+/// The next best action for one contact, with the signals it was picked from
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RecommendationResponse {
+    pub contact_id: String,
+    pub contact_name: String,
+    pub email: String,
+    pub kind: NextActionKind,
+    pub priority: ActionPriority,
+    /// What to do, in a few words
+    pub action: String,
+    pub reason: String,
+    pub engagement_score: f64,
+    pub level: EngagementLevel,
+    pub trend: EngagementTrend,
+    pub velocity: f64,
+    pub last_interaction_type: Option<InteractionType>,
+    pub last_interaction_at: Option<DateTime<Utc>>,
+    /// Task entries not marked completed
+    pub open_tasks: u32,
+    /// Furthest stage among the contact's open deals
+    pub deal_stage: Option<DealStage>,
+    /// Action and reason worded by the AI provider; the kind and priority always come from the rules
+    pub ai_generated: bool,
+}
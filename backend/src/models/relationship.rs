@@ -0,0 +1,78 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+use crate::domain::{NodeType, RelationshipKind};
+
+/// An edge of one of the relationship tables, with the names of both ends
+///
+/// The names are looked up when the edge is read and aren't stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Relationship {
+    pub id: Thing,
+    #[serde(rename = "in")]
+    pub from: Thing,
+    #[serde(rename = "out")]
+    pub to: Thing,
+    pub workspace: Thing,
+    pub note: Option<String>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub from_name: Option<String>,
+    #[serde(default)]
+    pub to_name: Option<String>,
+}
+
+/// Body of POST /api/relationships
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateRelationshipRequest {
+    pub kind: RelationshipKind,
+    pub from_type: NodeType,
+    pub from_id: String,
+    pub to_type: NodeType,
+    pub to_id: String,
+    pub note: Option<String>,
+}
+
+/// One end of a relationship
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RelationshipNodeResponse {
+    #[serde(rename = "type")]
+    pub node_type: NodeType,
+    pub id: String,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RelationshipResponse {
+    pub id: String,
+    pub kind: RelationshipKind,
+    pub from: RelationshipNodeResponse,
+    pub to: RelationshipNodeResponse,
+    pub note: Option<String>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A contact or company in someone's network
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NetworkNodeResponse {
+    #[serde(flatten)]
+    pub node: RelationshipNodeResponse,
+    /// 1 for direct relationships, 2 for warm introductions
+    pub hops: u8,
+    /// For warm introductions, who can make them
+    pub via: Vec<RelationshipNodeResponse>,
+}
+
+/// Response of GET /api/contacts/:id/network
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NetworkResponse {
+    pub contact_id: String,
+    /// Direct relationships first, then warm introductions, best connected first
+    pub nodes: Vec<NetworkNodeResponse>,
+    /// Every relationship between the contact and the nodes, and among them
+    pub relationships: Vec<RelationshipResponse>,
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchQuery {
+    pub q: String,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchResultType {
+    Contact,
+    Company,
+    TimelineEntry,
+}
+
+/// One hit in a unified search result list
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SearchResult {
+    #[serde(rename = "type")]
+    pub result_type: SearchResultType,
+    pub id: String,
+    pub title: String,
+    pub subtitle: Option<String>,
+    /// Set for timeline entries: the contact the entry belongs to
+    pub contact_id: Option<String>,
+    pub score: f64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchResponse {
+    pub query: String,
+    pub results: Vec<SearchResult>,
+    pub total: usize,
+}
@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+use super::ContactResponse;
+
+/// A named, reusable audience definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Segment {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub name: String,
+    pub description: Option<String>,
+    pub definition: serde_json::Value,
+    /// Matching contacts as of `counted_at`; refreshed in the background
+    pub member_count: u64,
+    pub counted_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateSegmentRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub definition: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateSegmentRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub definition: Option<serde_json::Value>,
+}
+
+/// Preview an unsaved definition
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SegmentPreviewRequest {
+    pub definition: serde_json::Value,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SegmentPreviewQuery {
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SegmentPreviewResponse {
+    /// Every matching contact, not just the sample
+    pub total: u64,
+    pub contacts: Vec<ContactResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SegmentResponse {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub definition: serde_json::Value,
+    pub member_count: u64,
+    pub counted_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Segment> for SegmentResponse {
+    fn from(s: Segment) -> Self {
+        Self {
+            id: s.id.map(|t| t.id.to_string()).unwrap_or_default(),
+            name: s.name,
+            description: s.description,
+            definition: s.definition,
+            member_count: s.member_count,
+            counted_at: s.counted_at,
+            created_at: s.created_at,
+            updated_at: s.updated_at,
+        }
+    }
+}
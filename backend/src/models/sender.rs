@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+use crate::domain::{format_mailbox, verification_name, verification_record, DnsCheck};
+
+/// The name, address and signature campaign email goes out with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SenderIdentity {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    /// The user the identity belongs to; shared with the workspace when unset
+    #[serde(default)]
+    pub owner: Option<Thing>,
+    pub from_name: String,
+    pub from_email: String,
+    pub reply_to: Option<String>,
+    pub signature_html: Option<String>,
+    /// Domain of `from_email`, the one SPF and DKIM are checked for
+    pub domain: String,
+    /// Random token the domain publishes to show it is the workspace's
+    #[serde(default)]
+    pub verification_token: String,
+    #[serde(default)]
+    pub ownership: DnsCheck,
+    #[serde(default)]
+    pub spf: DnsCheck,
+    #[serde(default)]
+    pub dkim: DnsCheck,
+    /// When the domain's records were last looked up
+    pub checked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SenderIdentity {
+    /// Whether campaigns may send from the identity: its domain passed all three checks
+    pub fn is_verified(&self) -> bool {
+        self.ownership.passed() && self.spf.passed() && self.dkim.passed()
+    }
+
+    /// The From header, e.g. `Jane Doe <jane@example.com>`
+    pub fn header_from(&self) -> String {
+        format_mailbox(&self.from_name, &self.from_email)
+    }
+}
+
+/// POST /api/senders
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateSenderRequest {
+    pub from_name: String,
+    pub from_email: String,
+    pub reply_to: Option<String>,
+    pub signature_html: Option<String>,
+    /// Keep the identity to yourself rather than share it with the workspace
+    #[serde(default)]
+    pub personal: bool,
+}
+
+/// PATCH /api/senders/:id
+///
+/// Changing the address's domain clears its verification.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateSenderRequest {
+    pub from_name: Option<String>,
+    pub from_email: Option<String>,
+    /// An empty string clears it
+    pub reply_to: Option<String>,
+    /// An empty string clears it
+    pub signature_html: Option<String>,
+}
+
+/// A TXT record to publish in the sender's domain
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VerificationRecord {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SenderResponse {
+    pub id: String,
+    /// Set for personal identities
+    pub owner_id: Option<String>,
+    pub from_name: String,
+    pub from_email: String,
+    pub reply_to: Option<String>,
+    pub signature_html: Option<String>,
+    pub domain: String,
+    /// Ownership, SPF and DKIM all pass; only verified identities can send
+    pub verified: bool,
+    /// The record proving the domain is the workspace's
+    pub verification: VerificationRecord,
+    pub ownership: DnsCheck,
+    pub spf: DnsCheck,
+    pub dkim: DnsCheck,
+    pub checked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<SenderIdentity> for SenderResponse {
+    fn from(s: SenderIdentity) -> Self {
+        Self {
+            id: s.id.as_ref().map(|t| t.id.to_string()).unwrap_or_default(),
+            owner_id: s.owner.as_ref().map(|t| t.id.to_string()),
+            verified: s.is_verified(),
+            verification: VerificationRecord {
+                name: verification_name(&s.domain),
+                value: verification_record(&s.verification_token),
+            },
+            from_name: s.from_name,
+            from_email: s.from_email,
+            reply_to: s.reply_to,
+            signature_html: s.signature_html,
+            domain: s.domain,
+            ownership: s.ownership,
+            spf: s.spf,
+            dkim: s.dkim,
+            checked_at: s.checked_at,
+            created_at: s.created_at,
+            updated_at: s.updated_at,
+        }
+    }
+}
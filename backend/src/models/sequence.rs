@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+use crate::domain::SequenceStep;
+
+/// A drip sequence: emails and waits a contact is walked through
+///
+/// Steps can't be edited once created, since enrollments point into them
+/// by position; create a new sequence instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sequence {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub name: String,
+    pub description: Option<String>,
+    pub steps: Vec<SequenceStep>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateSequenceRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub steps: Vec<SequenceStep>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SequenceResponse {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub steps: Vec<SequenceStep>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Sequence> for SequenceResponse {
+    fn from(s: Sequence) -> Self {
+        Self {
+            id: s.id.map(|t| t.id.to_string()).unwrap_or_default(),
+            name: s.name,
+            description: s.description,
+            steps: s.steps,
+            created_at: s.created_at,
+            updated_at: s.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EnrollmentStatus {
+    Active,
+    Completed,
+    /// Taken out through the API, or because the contact unsubscribed
+    Unenrolled,
+    /// Stopped because the contact wrote back
+    Replied,
+}
+
+/// A contact's progress through a sequence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SequenceEnrollment {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub sequence: Thing,
+    pub contact: Thing,
+    pub status: EnrollmentStatus,
+    /// Index of the next step to run
+    pub position: usize,
+    /// When the runner picks the enrollment up next; unset once it ends
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_email_at: Option<DateTime<Utc>>,
+    pub enrolled_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Contacts to enroll in or take out of a sequence
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct EnrollmentRequest {
+    pub contact_ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EnrollmentQuery {
+    pub status: Option<EnrollmentStatus>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EnrollmentResponse {
+    pub id: String,
+    pub sequence_id: String,
+    pub contact_id: String,
+    pub status: EnrollmentStatus,
+    pub position: usize,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_email_at: Option<DateTime<Utc>>,
+    pub enrolled_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl From<SequenceEnrollment> for EnrollmentResponse {
+    fn from(e: SequenceEnrollment) -> Self {
+        Self {
+            id: e.id.map(|t| t.id.to_string()).unwrap_or_default(),
+            sequence_id: e.sequence.id.to_string(),
+            contact_id: e.contact.id.to_string(),
+            status: e.status,
+            position: e.position,
+            next_run_at: e.next_run_at,
+            last_email_at: e.last_email_at,
+            enrolled_at: e.enrolled_at,
+            ended_at: e.ended_at,
+        }
+    }
+}
+
+/// How many contacts an enroll or unenroll request changed
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EnrollmentChangeResponse {
+    pub sequence_id: String,
+    pub changed: usize,
+    /// Already in (or already out of) the sequence
+    pub unchanged: usize,
+}
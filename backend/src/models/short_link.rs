@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+pub use crate::domain::LinkChannel;
+
+/// A shortened URL sent to one contact for one campaign
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShortLink {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub campaign: Thing,
+    pub contact: Thing,
+    pub channel: LinkChannel,
+    /// Path segment of the short URL, unique across workspaces
+    pub code: String,
+    /// Where the link goes
+    pub url: String,
+    #[serde(default)]
+    pub clicks: u64,
+    pub last_clicked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// POST /api/campaigns/:id/short-links
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateShortLinksRequest {
+    /// Absolute http(s) URL to shorten
+    pub url: String,
+    /// Defaults to email
+    #[serde(default)]
+    pub channel: LinkChannel,
+    /// One link is made for each contact
+    pub contact_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ShortLinkResponse {
+    pub id: String,
+    pub campaign_id: String,
+    pub contact_id: String,
+    pub channel: LinkChannel,
+    pub code: String,
+    /// The URL to share
+    pub short_url: String,
+    pub url: String,
+    pub clicks: u64,
+    pub last_clicked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,140 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+pub use crate::ai::ai_social::SocialPlatform;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SocialPostStatus {
+    Scheduled,
+    /// A request to the platform is out
+    Publishing,
+    Published,
+    /// The request may have gone through; not retried, so it is never posted twice
+    Unconfirmed,
+    Failed,
+}
+
+/// The account a workspace publishes to one platform with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SocialAccount {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub platform: SocialPlatform,
+    pub access_token: String,
+    /// LinkedIn: the member or organization posts are made as
+    pub author_urn: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// PUT /api/integrations/social/:platform
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ConnectSocialAccountRequest {
+    /// OAuth access token; for X an OAuth 2.0 user token
+    pub access_token: String,
+    /// LinkedIn only: `urn:li:person:<id>` or `urn:li:organization:<id>`
+    pub author_urn: Option<String>,
+}
+
+/// Tokens are never returned
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SocialAccountResponse {
+    pub platform: SocialPlatform,
+    pub author_urn: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<SocialAccount> for SocialAccountResponse {
+    fn from(a: SocialAccount) -> Self {
+        Self {
+            platform: a.platform,
+            author_urn: a.author_urn,
+            created_at: a.created_at,
+            updated_at: a.updated_at,
+        }
+    }
+}
+
+/// One generated post queued for, or published to, one platform
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SocialPost {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub campaign: Thing,
+    /// The social_post asset the text came from
+    pub asset: Thing,
+    pub platform: SocialPlatform,
+    pub text: String,
+    /// Contacts the post is aimed at; each gets a social touch once it is live
+    #[serde(default)]
+    pub tagged_contacts: Vec<Thing>,
+    pub status: SocialPostStatus,
+    pub attempts: u32,
+    /// When the post should go out
+    pub publish_at: DateTime<Utc>,
+    /// When it is next tried; later than `publish_at` after a failure
+    pub next_attempt_at: DateTime<Utc>,
+    pub url: Option<String>,
+    pub external_id: Option<String>,
+    pub error: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// POST /api/campaigns/:id/social-posts
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ScheduleSocialPostRequest {
+    /// A social_post asset of the campaign
+    pub asset_id: String,
+    /// Which of the asset's generated posts to publish
+    pub platform: SocialPlatform,
+    /// Replaces the generated text
+    pub text: Option<String>,
+    /// Defaults to now
+    pub publish_at: Option<DateTime<Utc>>,
+    /// Contacts to log a social touch for once the post is live
+    #[serde(default)]
+    pub contact_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SocialPostResponse {
+    pub id: String,
+    pub campaign_id: String,
+    pub asset_id: String,
+    pub platform: SocialPlatform,
+    pub text: String,
+    pub tagged_contact_ids: Vec<String>,
+    pub status: SocialPostStatus,
+    pub attempts: u32,
+    pub publish_at: DateTime<Utc>,
+    pub url: Option<String>,
+    pub error: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<SocialPost> for SocialPostResponse {
+    fn from(p: SocialPost) -> Self {
+        Self {
+            id: p.id.map(|t| t.id.to_string()).unwrap_or_default(),
+            campaign_id: p.campaign.id.to_string(),
+            asset_id: p.asset.id.to_string(),
+            platform: p.platform,
+            text: p.text,
+            tagged_contact_ids: p.tagged_contacts.into_iter().map(|c| c.id.to_string()).collect(),
+            status: p.status,
+            attempts: p.attempts,
+            publish_at: p.publish_at,
+            url: p.url,
+            error: p.error,
+            published_at: p.published_at,
+            created_at: p.created_at,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+use super::ContactStatus;
+use crate::domain::WinLossReason;
+
+/// One change of a contact's lifecycle status
+///
+/// A contact's first status is held from its creation, so only changes are
+/// recorded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusHistoryEntry {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub contact: Thing,
+    pub from_status: ContactStatus,
+    pub to_status: ContactStatus,
+    /// The user who made the change
+    pub actor: Option<Thing>,
+    pub reason: Option<String>,
+    /// Set when a lead was won or lost and a reason was given
+    pub win_loss_reason: Option<WinLossReason>,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StatusHistoryResponse {
+    pub id: String,
+    pub contact_id: String,
+    pub from_status: ContactStatus,
+    pub to_status: ContactStatus,
+    pub actor_id: Option<String>,
+    pub reason: Option<String>,
+    pub win_loss_reason: Option<WinLossReason>,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl From<StatusHistoryEntry> for StatusHistoryResponse {
+    fn from(e: StatusHistoryEntry) -> Self {
+        Self {
+            id: e.id.map(|t| t.id.to_string()).unwrap_or_default(),
+            contact_id: e.contact.id.to_string(),
+            from_status: e.from_status,
+            to_status: e.to_status,
+            actor_id: e.actor.map(|t| t.id.to_string()),
+            reason: e.reason,
+            win_loss_reason: e.win_loss_reason,
+            changed_at: e.changed_at,
+        }
+    }
+}
@@ -1,62 +1,161 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use surrealdb::sql::Thing;
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use super::AttachmentSummary;
+use crate::domain::{CallMetadata, DomainResult, EmailMetadata, MeetingMetadata};
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum TimelineEntryType {
     EmailSent,
     EmailOpen,
     EmailClick,
+    /// Sent to us by the contact, synced from a connected mailbox
+    EmailReceived,
+    /// Pushed in by an external tool through an inbound webhook
+    ExternalEvent,
     SocialTouch,
     Note,
     EventInvite,
+    /// Registered for an event, from an invite or the public RSVP page
+    EventRegistration,
     EventAttend,
     LandingPageVisit,
+    /// Filled in a landing page form
+    FormSubmission,
+    MeetingScheduled,
+    MeetingAttended,
     Task,
     Call,
+    /// Fields filled in from the contact's LinkedIn profile
+    Enrichment,
+    /// The customer left; the reason is in the metadata
+    Churned,
+    /// The email provider reported an email to the contact as bounced
+    EmailBounce,
+    /// The contact marked an email as spam, as reported by the email provider
+    EmailComplaint,
+    /// A "what to know before this call" brief written for the contact
+    PrepBrief,
+    /// The contact unsubscribed from campaign email
+    Unsubscribed,
+}
+
+impl TimelineEntryType {
+    /// Check the metadata keys this type of entry is known by
+    pub fn validate_metadata(&self, metadata: &serde_json::Value) -> DomainResult<()> {
+        match self {
+            Self::Call => CallMetadata::from_metadata(metadata).map(drop),
+            Self::MeetingScheduled | Self::MeetingAttended => MeetingMetadata::from_metadata(metadata).map(drop),
+            Self::EmailSent | Self::EmailOpen | Self::EmailClick | Self::EmailReceived => {
+                EmailMetadata::from_metadata(metadata).map(drop)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// How the contact came across in an interaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Sentiment {
+    Positive,
+    Neutral,
+    Negative,
+}
+
+impl Sentiment {
+    /// 1 for positive, 0 for neutral, -1 for negative
+    pub fn score(&self) -> f64 {
+        match self {
+            Sentiment::Positive => 1.0,
+            Sentiment::Neutral => 0.0,
+            Sentiment::Negative => -1.0,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimelineEntry {
     pub id: Option<Thing>,
+    pub workspace: Thing,
     pub contact: Thing,
     pub company: Option<Thing>,
+    /// The campaign the entry came from: an email it sent, one of its
+    /// landing pages or events
+    #[serde(default)]
+    pub campaign: Option<Thing>,
     #[serde(rename = "type")]
     pub entry_type: TimelineEntryType,
     pub content: String,
     pub metadata: serde_json::Value,
+    /// Files attached through `POST /api/timeline/:id/attachments`
+    #[serde(default)]
+    pub attachments: Vec<AttachmentSummary>,
+    /// Set when logged, or rated later for notes and received email when `ai.infer_sentiment` is on
+    #[serde(default)]
+    pub sentiment: Option<Sentiment>,
     pub timestamp: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateTimelineEntryRequest {
     pub contact_id: String,
     pub company_id: Option<String>,
+    /// The campaign that led to the interaction, for attribution
+    pub campaign_id: Option<String>,
     #[serde(rename = "type")]
     pub entry_type: TimelineEntryType,
     pub content: String,
+    /// Checked for calls (`duration_minutes`, `outcome`), meetings (`attendees`,
+    /// `location`) and email (`subject`, `message_id`, `in_reply_to`, `references`);
+    /// other keys are kept as given
     pub metadata: Option<serde_json::Value>,
+    /// Left out to have it inferred, where that is enabled
+    pub sentiment: Option<Sentiment>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct TimelineQuery {
     pub contact_id: Option<String>,
     pub company_id: Option<String>,
+    /// Entries attributed to the campaign
+    pub campaign_id: Option<String>,
     pub entry_type: Option<TimelineEntryType>,
+    /// Inclusive lower bound on `timestamp`
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on `timestamp`
+    pub to: Option<DateTime<Utc>>,
+    /// Case-insensitive substring match on the entry content
+    pub search: Option<String>,
     pub limit: Option<u32>,
     pub offset: Option<u32>,
 }
 
-#[derive(Debug, Serialize)]
+/// Number of timeline entries on one UTC day, for activity heatmaps
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DailyActivityCount {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct TimelineEntryResponse {
     pub id: String,
     pub contact_id: String,
     pub company_id: Option<String>,
+    pub campaign_id: Option<String>,
     #[serde(rename = "type")]
     pub entry_type: TimelineEntryType,
     pub content: String,
     pub metadata: serde_json::Value,
+    pub attachments: Vec<AttachmentSummary>,
+    pub sentiment: Option<Sentiment>,
     pub timestamp: DateTime<Utc>,
 }
 
@@ -66,10 +165,25 @@ impl From<TimelineEntry> for TimelineEntryResponse {
             id: t.id.map(|th| th.id.to_string()).unwrap_or_default(),
             contact_id: t.contact.id.to_string(),
             company_id: t.company.map(|th| th.id.to_string()),
+            campaign_id: t.campaign.map(|th| th.id.to_string()),
             entry_type: t.entry_type,
             content: t.content,
             metadata: t.metadata,
+            attachments: t.attachments,
+            sentiment: t.sentiment,
             timestamp: t.timestamp,
         }
     }
 }
+
+/// POST /api/contacts/:id/prep-brief
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PrepBriefResponse {
+    pub contact_id: String,
+    /// "What to know before this call", as Markdown
+    pub markdown: String,
+    /// Written by the configured AI provider; `false` when it lists the CRM facts as they are
+    pub ai_generated: bool,
+    /// The entry the brief was saved as on the contact's timeline
+    pub entry: TimelineEntryResponse,
+}
@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::domain::AuditEntity;
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TrashQuery {
+    /// `contact` or `company`; both when absent
+    pub entity_type: Option<AuditEntity>,
+    pub limit: Option<u32>,
+}
+
+/// A deleted contact or company that can still be restored
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TrashItem {
+    pub entity_type: AuditEntity,
+    pub id: String,
+    pub name: String,
+    pub deleted_at: DateTime<Utc>,
+    /// When the purge job removes it for good
+    pub purge_after: DateTime<Utc>,
+}
@@ -0,0 +1,160 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+use crate::domain::WebhookEvent;
+
+/// A URL registered to receive events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub description: Option<String>,
+    /// Shared secret used to sign payloads
+    pub secret: String,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+/// One event queued for one webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub webhook: Thing,
+    pub event: WebhookEvent,
+    pub payload: serde_json::Value,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A single HTTP request made for a delivery
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookAttempt {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub delivery: Thing,
+    pub attempt: u32,
+    pub response_status: Option<u16>,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    pub attempted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeliveryQuery {
+    pub status: Option<DeliveryStatus>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebhookResponse {
+    pub id: String,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub description: Option<String>,
+    /// Only returned when the webhook is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Webhook> for WebhookResponse {
+    fn from(w: Webhook) -> Self {
+        Self {
+            id: w.id.map(|t| t.id.to_string()).unwrap_or_default(),
+            url: w.url,
+            events: w.events,
+            description: w.description,
+            secret: None,
+            active: w.active,
+            created_at: w.created_at,
+            updated_at: w.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebhookDeliveryResponse {
+    pub id: String,
+    pub webhook_id: String,
+    pub event: WebhookEvent,
+    pub payload: serde_json::Value,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<WebhookDelivery> for WebhookDeliveryResponse {
+    fn from(d: WebhookDelivery) -> Self {
+        Self {
+            id: d.id.map(|t| t.id.to_string()).unwrap_or_default(),
+            webhook_id: d.webhook.id.to_string(),
+            event: d.event,
+            payload: d.payload,
+            next_attempt_at: (d.status == DeliveryStatus::Pending).then_some(d.next_attempt_at),
+            status: d.status,
+            attempts: d.attempts,
+            created_at: d.created_at,
+            updated_at: d.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebhookAttemptResponse {
+    pub attempt: u32,
+    pub response_status: Option<u16>,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    pub attempted_at: DateTime<Utc>,
+}
+
+impl From<WebhookAttempt> for WebhookAttemptResponse {
+    fn from(a: WebhookAttempt) -> Self {
+        Self {
+            attempt: a.attempt,
+            response_status: a.response_status,
+            error: a.error,
+            duration_ms: a.duration_ms,
+            attempted_at: a.attempted_at,
+        }
+    }
+}
+
+/// A delivery together with every attempt made for it
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebhookDeliveryDetailResponse {
+    #[serde(flatten)]
+    pub delivery: WebhookDeliveryResponse,
+    pub attempt_log: Vec<WebhookAttemptResponse>,
+}
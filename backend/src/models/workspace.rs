@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+
+/// A tenant - every CRM record belongs to exactly one workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub id: Option<Thing>,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateWorkspaceRequest {
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WorkspaceResponse {
+    pub id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Workspace> for WorkspaceResponse {
+    fn from(w: Workspace) -> Self {
+        Self {
+            id: w.id.map(|t| t.id.to_string()).unwrap_or_default(),
+            name: w.name,
+            created_at: w.created_at,
+            updated_at: w.updated_at,
+        }
+    }
+}
+
+/// What a restore put back, per table
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RestoreResponse {
+    pub workspace_id: String,
+    /// When the backup was taken
+    pub backed_up_at: DateTime<Utc>,
+    pub records: u64,
+    pub tables: Vec<RestoredTable>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RestoredTable {
+    pub table: String,
+    pub records: u64,
+}
@@ -0,0 +1,625 @@
+//! OpenAPI document for the HTTP API
+//!
+//! Served as JSON at `/api/openapi.json` and browsable with Swagger UI at
+//! `/docs`. Every handler carries a `#[utoipa::path]` annotation and must be
+//! listed in `paths` below; every request and response type it references
+//! must be listed in `components`.
+
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::openapi::SecurityRequirement;
+use utoipa::{Modify, OpenApi};
+
+use crate::domain;
+use crate::error;
+use crate::handlers;
+use crate::models;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "CRM.HEY.SH API", description = "Contacts, companies, deals, campaigns and events"),
+    paths(
+        handlers::health::health_check,
+        // Auth
+        handlers::auth::register,
+        handlers::auth::login,
+        handlers::auth::refresh,
+        handlers::auth::me,
+        // Workspace
+        handlers::workspaces::get_workspace,
+        handlers::workspaces::update_workspace,
+        handlers::workspaces::backup_workspace,
+        handlers::workspaces::restore_workspace,
+        // Search
+        handlers::search::search,
+        // Activity feed and live updates
+        handlers::feed::stream_feed,
+        handlers::live::create_live_ticket,
+        handlers::live::contacts_socket,
+        // Contacts
+        handlers::contacts::list_contacts,
+        handlers::contacts::count_contacts,
+        handlers::contacts::list_tags,
+        handlers::contacts::create_contact,
+        handlers::contacts::export_contacts,
+        handlers::contacts::find_duplicates,
+        handlers::contacts::list_dedupe_suggestions,
+        handlers::contacts::accept_dedupe_suggestion,
+        handlers::contacts::dismiss_dedupe_suggestion,
+        handlers::contacts::merge_contacts,
+        handlers::contacts::batch_get_contacts,
+        handlers::contacts::bulk_update_contacts,
+        handlers::contacts::get_contact,
+        handlers::contacts::update_contact,
+        handlers::contacts::delete_contact,
+        handlers::contacts::restore_contact,
+        handlers::contacts::churn_contact,
+        handlers::contacts::get_contact_engagement,
+        handlers::contacts::get_engagement_breakdown,
+        handlers::contacts::get_status_history,
+        handlers::contacts::get_contact_summary,
+        handlers::contacts::get_contact_report,
+        handlers::contacts::create_prep_brief,
+        handlers::ai::draft_reply,
+        handlers::recommendations::list_recommendations,
+        handlers::contacts::recalculate_engagement,
+        handlers::contacts::enrich_contact,
+        handlers::avatars::upload_contact_avatar,
+        handlers::avatars::delete_contact_avatar,
+        handlers::avatars::get_avatar,
+        // GDPR
+        handlers::gdpr::export_contact_data,
+        handlers::gdpr::erase_contact_data,
+        handlers::gdpr::list_erasures,
+        // Companies
+        handlers::companies::list_companies,
+        handlers::companies::create_company,
+        handlers::companies::get_company,
+        handlers::companies::update_company,
+        handlers::companies::delete_company,
+        handlers::companies::restore_company,
+        // Trash
+        handlers::trash::list_trash,
+        // Timeline
+        handlers::timeline::get_contact_timeline,
+        handlers::timeline::get_contact_threads,
+        handlers::timeline::list_timeline,
+        handlers::timeline::create_timeline_entry,
+        handlers::timeline::daily_activity,
+        // Attachments
+        handlers::attachments::list_contact_attachments,
+        handlers::attachments::upload_contact_attachment,
+        handlers::attachments::upload_timeline_attachment,
+        handlers::attachments::download_attachment,
+        handlers::attachments::delete_attachment,
+        handlers::relationships::create_relationship,
+        handlers::relationships::delete_relationship,
+        handlers::relationships::list_contact_relationships,
+        handlers::relationships::list_company_relationships,
+        handlers::relationships::get_contact_network,
+        // Deals
+        handlers::deals::list_deals,
+        handlers::deals::create_deal,
+        handlers::deals::get_deal,
+        handlers::deals::update_deal,
+        handlers::deals::delete_deal,
+        handlers::deals::update_deal_stage,
+        // Pipeline board
+        handlers::pipeline::get_board,
+        handlers::pipeline::move_card,
+        // Digest
+        handlers::digest::get_preferences,
+        handlers::digest::update_preferences,
+        handlers::digest::preview,
+        // Segments
+        handlers::segments::list_segments,
+        handlers::segments::create_segment,
+        handlers::segments::preview_definition,
+        handlers::segments::get_segment,
+        handlers::segments::update_segment,
+        handlers::segments::delete_segment,
+        handlers::segments::preview_segment,
+        // Senders
+        handlers::senders::list_senders,
+        handlers::senders::create_sender,
+        handlers::senders::get_sender,
+        handlers::senders::update_sender,
+        handlers::senders::delete_sender,
+        handlers::senders::verify_sender,
+        handlers::sequences::list_sequences,
+        handlers::sequences::create_sequence,
+        handlers::sequences::get_sequence,
+        handlers::sequences::delete_sequence,
+        handlers::sequences::list_enrollments,
+        handlers::sequences::enroll_contacts,
+        handlers::sequences::unenroll_contacts,
+        // Campaigns
+        handlers::campaigns::list_campaigns,
+        handlers::campaigns::create_campaign,
+        handlers::campaigns::get_campaign,
+        handlers::campaigns::update_campaign,
+        handlers::campaigns::list_campaign_assets,
+        handlers::campaigns::generate_campaign_assets,
+        handlers::campaigns::regenerate_campaign_asset,
+        handlers::campaigns::update_campaign_asset,
+        handlers::campaigns::execute_campaign,
+        handlers::campaigns::schedule_campaign,
+        handlers::campaigns::cancel_campaign,
+        handlers::campaigns::clone_campaign,
+        handlers::campaigns::save_campaign_template,
+        handlers::campaign_templates::list_templates,
+        handlers::campaign_templates::get_template,
+        handlers::campaign_templates::delete_template,
+        handlers::campaign_templates::instantiate_template,
+        handlers::campaigns::list_campaign_recipients,
+        handlers::campaigns::preview_campaign_email,
+        handlers::campaigns::schedule_social_post,
+        handlers::campaigns::list_social_posts,
+        handlers::campaigns::create_short_links,
+        handlers::campaigns::list_short_links,
+        // Landing pages
+        handlers::landing_pages::generate_landing_page,
+        handlers::landing_pages::get_landing_page,
+        handlers::landing_pages::submit_landing_page_form,
+        // Events
+        handlers::events::list_events,
+        handlers::events::create_event,
+        handlers::events::get_event,
+        handlers::events::invite_to_event,
+        handlers::events::rsvp_event,
+        handlers::events::check_in,
+        handlers::events::bulk_check_in,
+        handlers::events::checkin_token,
+        // Email tracking
+        handlers::tracking::track_open,
+        handlers::tracking::track_click,
+        handlers::tracking::unsubscribe_page,
+        handlers::tracking::unsubscribe,
+        handlers::tracking::follow_short_link,
+        // Webhooks
+        handlers::webhooks::list_webhooks,
+        handlers::webhooks::create_webhook,
+        handlers::webhooks::get_webhook,
+        handlers::webhooks::delete_webhook,
+        handlers::webhooks::list_deliveries,
+        handlers::webhooks::get_delivery,
+        // Inbound webhooks
+        handlers::inbound::list_inbound_sources,
+        handlers::inbound::create_inbound_source,
+        handlers::inbound::update_inbound_source,
+        handlers::inbound::delete_inbound_source,
+        handlers::inbound::ingest,
+        // Bounce and complaint callbacks
+        handlers::email_events::receive,
+        // Integrations
+        handlers::integrations::list_integrations,
+        handlers::integrations::create_imap_integration,
+        handlers::integrations::create_gmail_integration,
+        handlers::integrations::gmail_callback,
+        handlers::integrations::update_integration,
+        handlers::integrations::delete_integration,
+        handlers::integrations::sync_integration,
+        handlers::integrations::list_social_accounts,
+        handlers::integrations::connect_social_account,
+        handlers::integrations::disconnect_social_account,
+        // Import
+        handlers::imports::import_hubspot,
+        handlers::imports::import_pipedrive,
+        handlers::imports::list_import_jobs,
+        handlers::imports::get_import_job,
+        // Audit log
+        handlers::audit::list_audit_log,
+        handlers::audit::get_entity_history,
+        // Analytics
+        handlers::analytics::campaign_analytics,
+        handlers::analytics::campaign_deliverability,
+        handlers::analytics::event_analytics,
+        handlers::analytics::contacts_analytics,
+        handlers::analytics::funnel_analytics,
+        handlers::analytics::churn_analytics,
+        handlers::analytics::win_loss_analytics,
+        handlers::analytics::response_time_analytics,
+        handlers::analytics::pipeline_analytics,
+        handlers::analytics::pipeline_region_analytics,
+        handlers::analytics::ai_usage_analytics,
+        handlers::analytics::sentiment_analytics,
+    ),
+    components(
+        schemas(
+            error::ErrorResponse,
+            error::FieldError,
+            handlers::health::HealthResponse,
+            handlers::landing_pages::GenerateLandingPageRequest,
+            handlers::landing_pages::LandingPageResponse,
+            models::LandingPageQuery,
+            models::UtmParams,
+            // Domain
+            domain::ActionPriority,
+            domain::AuditAction,
+            domain::AuditEntity,
+            domain::DealStage,
+            domain::DnsCheck,
+            domain::DnsCheckStatus,
+            domain::DuplicateReason,
+            domain::DedupeStatus,
+            domain::EngagementLevel,
+            domain::EngagementTrend,
+            domain::EventReminders,
+            domain::FieldChange,
+            domain::FormSchema,
+            domain::FormField,
+            domain::FormFieldType,
+            domain::InteractionType,
+            domain::MergeConflict,
+            domain::NextActionKind,
+            domain::ScoreContribution,
+            domain::SendWindow,
+            domain::SequenceStep,
+            domain::BranchCondition,
+            domain::WebhookEvent,
+            domain::WinLossOutcome,
+            domain::WinLossReason,
+            domain::EmailDirection,
+            domain::RelationshipKind,
+            domain::NodeType,
+            // Analytics
+            models::TimeRange,
+            models::AnalyticsQuery,
+            models::CampaignAnalytics,
+            models::LandingPageConversion,
+            models::LinkClicks,
+            models::DeliverabilityAnalytics,
+            models::EventAnalytics,
+            models::DailyRegistrations,
+            models::ContactsAnalytics,
+            models::TopEngagedContact,
+            models::FunnelAnalytics,
+            models::FunnelStage,
+            models::StageConversion,
+            models::ChurnAnalytics,
+            models::ChurnReasonCount,
+            models::ResponseTimeAnalytics,
+            models::WinLossAnalytics,
+            models::WinLossReasonCount,
+            models::WinLossMonth,
+            models::PipelineAnalytics,
+            models::PipelineStageSummary,
+            models::PipelineRegionAnalytics,
+            models::PipelineRegionSummary,
+            models::AiUsageReport,
+            models::SentimentAnalytics,
+            models::AccountSentiment,
+            models::AiUsageTotals,
+            models::AiUsageByCampaign,
+            models::AiUsageByMonth,
+            // Audit
+            models::AuditQuery,
+            models::AuditLogResponse,
+            // Auth and workspace
+            models::RegisterRequest,
+            models::LoginRequest,
+            models::RefreshRequest,
+            models::UserResponse,
+            models::UserRole,
+            models::AuthResponse,
+            models::UpdateWorkspaceRequest,
+            models::WorkspaceResponse,
+            models::RestoreResponse,
+            models::RestoredTable,
+            // Sequences
+            models::CreateSequenceRequest,
+            models::SequenceResponse,
+            models::EnrollmentStatus,
+            models::EnrollmentRequest,
+            models::EnrollmentQuery,
+            models::EnrollmentResponse,
+            models::EnrollmentChangeResponse,
+            // Campaigns
+            models::CampaignObjective,
+            models::CampaignStatus,
+            models::CampaignChannel,
+            models::AssetType,
+            models::CreateCampaignRequest,
+            models::UpdateCampaignRequest,
+            models::GenerateAssetsRequest,
+            models::RegenerateAssetRequest,
+            models::UpdateAssetRequest,
+            domain::EmailDocument,
+            domain::EmailSection,
+            models::AssetQuery,
+            models::CampaignResponse,
+            models::CampaignAssetResponse,
+            models::RecipientStatus,
+            models::RecipientQuery,
+            models::CampaignRecipientResponse,
+            models::EmailPreviewQuery,
+            models::EmailPreviewResponse,
+            models::ScheduleCampaignRequest,
+            models::ScheduleSocialPostRequest,
+            models::SocialPostResponse,
+            models::SocialPostStatus,
+            models::SocialPlatform,
+            models::CreateShortLinksRequest,
+            models::ShortLinkResponse,
+            models::LinkChannel,
+            models::CloneCampaignRequest,
+            models::SaveTemplateRequest,
+            models::InstantiateTemplateRequest,
+            models::TemplateAsset,
+            models::CampaignTemplateResponse,
+            // Companies
+            models::CreateCompanyRequest,
+            models::UpdateCompanyRequest,
+            models::CompanyQuery,
+            models::CompanyResponse,
+            // Contacts
+            models::ContactStatus,
+            models::SubscriptionStatus,
+            models::CreateContactRequest,
+            models::UpdateContactRequest,
+            models::ContactQuery,
+            models::ContactSort,
+            models::SortOrder,
+            models::ExportFormat,
+            models::ContactExportParams,
+            models::ContactReportQuery,
+            models::ContactCountResponse,
+            models::TagCount,
+            models::AvatarQuery,
+            models::ContactResponse,
+            models::ChurnContactRequest,
+            models::MergeContactsRequest,
+            models::MergeContactsResponse,
+            models::EnrichContactResponse,
+            models::DuplicateQuery,
+            models::DuplicateCandidate,
+            models::DedupeSuggestionQuery,
+            models::AcceptDedupeRequest,
+            models::DedupeSuggestionResponse,
+            models::PossibleDuplicate,
+            models::BulkOperation,
+            models::BatchGetContactsRequest,
+            models::BatchGetContactsResponse,
+            models::BulkContactsRequest,
+            models::BulkResultStatus,
+            models::BulkContactResult,
+            models::BulkContactsResponse,
+            models::EngagementRecalculationResponse,
+            models::InteractionContribution,
+            models::ContactEngagementResponse,
+            models::EngagementBreakdownResponse,
+            models::StatusHistoryResponse,
+            models::ContactSummaryResponse,
+            models::ActivityTrend,
+            models::SentimentTrend,
+            models::PrepBriefResponse,
+            models::ReplyTone,
+            models::ReplyLength,
+            models::DraftReplyRequest,
+            models::DraftReplyResponse,
+            models::RecommendationQuery,
+            models::RecommendationResponse,
+            // Deals
+            models::CreateDealRequest,
+            models::UpdateDealRequest,
+            models::DealStageRequest,
+            models::DealQuery,
+            models::DealResponse,
+            // Pipeline board
+            models::PipelineKind,
+            models::PipelineBoardQuery,
+            models::PipelineBoard,
+            models::PipelineColumn,
+            models::PipelineCard,
+            models::PipelineMoveRequest,
+            // Digest
+            domain::DigestPreferences,
+            models::DigestPreferencesResponse,
+            models::DigestPreview,
+            // Events
+            models::EventType,
+            models::RsvpStatus,
+            models::CreateEventRequest,
+            models::InviteRequest,
+            models::RsvpRequest,
+            models::EventResponse,
+            models::CheckinRequest,
+            models::BulkCheckinRequest,
+            models::CheckinStatus,
+            models::CheckinResult,
+            models::BulkCheckinResponse,
+            models::CheckinTokenResponse,
+            models::RsvpResponse,
+            // Live updates
+            models::LiveSocketQuery,
+            models::LiveTicketResponse,
+            // Search
+            models::SearchQuery,
+            models::SearchResultType,
+            models::SearchResult,
+            models::SearchResponse,
+            // Segments
+            models::CreateSegmentRequest,
+            models::UpdateSegmentRequest,
+            models::SegmentPreviewRequest,
+            models::SegmentPreviewQuery,
+            models::SegmentPreviewResponse,
+            models::SegmentResponse,
+            // Senders
+            models::CreateSenderRequest,
+            models::UpdateSenderRequest,
+            models::SenderResponse,
+            models::VerificationRecord,
+            // Timeline
+            models::TimelineEntryType,
+            models::Sentiment,
+            models::CreateTimelineEntryRequest,
+            models::TimelineQuery,
+            models::DailyActivityCount,
+            models::TimelineEntryResponse,
+            models::ThreadMessage,
+            models::EmailThreadResponse,
+            // Attachments
+            models::AttachmentSummary,
+            models::AttachmentResponse,
+            // Relationships
+            models::CreateRelationshipRequest,
+            models::RelationshipNodeResponse,
+            models::RelationshipResponse,
+            models::NetworkNodeResponse,
+            models::NetworkResponse,
+            // GDPR
+            models::GdprExport,
+            models::GdprRsvp,
+            models::GdprCampaignMembership,
+            models::GdprSequenceEnrollment,
+            models::GdprLandingPageVisit,
+            models::GdprShortLink,
+            models::GdprWebhookDelivery,
+            models::GdprStoredResponse,
+            models::GdprEmbedding,
+            models::GdprDuplicateSuggestion,
+            models::GdprErasureResponse,
+            // Trash
+            models::TrashQuery,
+            models::TrashItem,
+            // Webhooks
+            models::DeliveryStatus,
+            models::CreateWebhookRequest,
+            models::DeliveryQuery,
+            models::WebhookResponse,
+            models::WebhookDeliveryResponse,
+            models::WebhookAttemptResponse,
+            models::WebhookDeliveryDetailResponse,
+            // Inbound webhooks
+            domain::InboundMapping,
+            models::CreateInboundSourceRequest,
+            models::UpdateInboundSourceRequest,
+            models::InboundSourceResponse,
+            models::InboundIngestResponse,
+            // Bounce and complaint callbacks
+            domain::EmailProvider,
+            models::EmailEventsResponse,
+            // Integrations
+            models::IntegrationProvider,
+            models::IntegrationStatus,
+            models::CreateImapIntegrationRequest,
+            models::CreateGmailIntegrationRequest,
+            models::UpdateIntegrationRequest,
+            models::GmailAuthorizationResponse,
+            models::IntegrationResponse,
+            models::InboxSyncResponse,
+            models::ConnectSocialAccountRequest,
+            models::SocialAccountResponse,
+            // Import
+            models::ImportSource,
+            models::ImportMode,
+            models::ImportStatus,
+            models::ImportCounts,
+            models::ImportProgress,
+            models::ImportFailure,
+            models::HubspotImportRequest,
+            models::PipedriveImportRequest,
+            models::ImportJobResponse,
+        )
+    ),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "health", description = "Liveness"),
+        (name = "auth", description = "Registration, login and token refresh"),
+        (name = "workspace", description = "The caller's workspace"),
+        (name = "search", description = "Search across contacts, companies and timeline"),
+        (name = "feed", description = "Live workspace activity over server-sent events"),
+        (name = "live", description = "WebSocket change streams"),
+        (name = "contacts", description = "Contacts, duplicates and engagement"),
+        (name = "ai", description = "Drafting help from the AI provider"),
+        (name = "recommendations", description = "Next best actions across contacts"),
+        (name = "companies", description = "Companies"),
+        (name = "trash", description = "Deleted contacts and companies awaiting purge"),
+        (name = "gdpr", description = "Data subject export and erasure"),
+        (name = "timeline", description = "Interaction history"),
+        (name = "attachments", description = "Files attached to contacts and timeline entries"),
+        (name = "relationships", description = "Who knows whom, and warm introductions through them"),
+        (name = "deals", description = "Sales pipeline"),
+        (name = "pipeline", description = "Kanban board of contacts or deals"),
+        (name = "digest", description = "Weekly digest email preferences and preview"),
+        (name = "segments", description = "Saved audience definitions"),
+        (name = "senders", description = "Sender identities campaign email goes out from, and their domain checks"),
+        (name = "sequences", description = "Drip sequences and their enrollments"),
+        (name = "campaigns", description = "Campaigns, generated assets and recipients"),
+        (name = "landing_pages", description = "Generated landing pages and their public forms"),
+        (name = "events", description = "Events, invitations and RSVPs"),
+        (name = "tracking", description = "Open, click and unsubscribe links in sent email, and short links"),
+        (name = "webhooks", description = "Outgoing webhooks and delivery logs"),
+        (name = "inbound", description = "Sources pushing contacts and activity in from external tools"),
+        (name = "email-events", description = "Bounce and complaint callbacks from the email provider"),
+        (name = "integrations", description = "Connected mailboxes synced onto contact timelines"),
+        (name = "import", description = "One-time migration from another CRM"),
+        (name = "audit", description = "Who changed what"),
+        (name = "analytics", description = "Campaign, event, contact, funnel and pipeline reports"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Bearer token auth, required by every operation that does not opt out
+/// with `security(())`
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+        openapi.security = Some(vec![SecurityRequirement::new("bearer_auth", Vec::<String>::new())]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn collect_refs(value: &Value, refs: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(r)) = map.get("$ref") {
+                    refs.push(r.clone());
+                }
+                map.values().for_each(|v| collect_refs(v, refs));
+            }
+            Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_every_schema_reference_resolves() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let schemas = doc["components"]["schemas"].as_object().unwrap();
+
+        let mut refs = Vec::new();
+        collect_refs(&doc, &mut refs);
+        assert!(!refs.is_empty());
+
+        for r in refs {
+            let name = r.strip_prefix("#/components/schemas/").unwrap_or(&r);
+            assert!(schemas.contains_key(name), "unresolved schema reference {}", r);
+        }
+    }
+
+    #[test]
+    fn test_protected_and_public_operations() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+
+        assert!(doc["components"]["securitySchemes"]["bearer_auth"].is_object());
+        assert!(doc["paths"]["/api/contacts"]["get"]["security"].is_null());
+        assert_eq!(doc["paths"]["/api/auth/login"]["post"]["security"], serde_json::json!([{}]));
+        assert!(doc["paths"]["/api/contacts/{id}"]["patch"].is_object());
+    }
+}
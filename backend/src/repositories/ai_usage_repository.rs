@@ -0,0 +1,70 @@
+//! AI Usage Repository - one row per AI content generation
+
+use crate::db::{workspace_thing, Database};
+use crate::error::AppResult;
+use crate::models::AiUsage;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Summed usage for one campaign or month
+#[derive(Debug, Clone, Deserialize)]
+pub struct AiUsageGroup {
+    #[serde(default)]
+    pub campaign: Option<Thing>,
+    #[serde(default)]
+    pub month: Option<String>,
+    pub generations: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+    pub latency_ms: u64,
+}
+
+/// Repository for AiUsage database operations
+#[derive(Clone)]
+pub struct AiUsageRepository {
+    db: Arc<Database>,
+}
+
+impl AiUsageRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    pub async fn insert(&self, usage: AiUsage) -> AppResult<()> {
+        let _: Vec<AiUsage> = self.db.client.create("ai_usage").content(usage).await?;
+        Ok(())
+    }
+
+    /// Usage since `since`, grouped by campaign and by month
+    ///
+    /// `latency_ms` in each group is the sum; the service averages it.
+    pub async fn grouped(
+        &self,
+        workspace_id: &str,
+        since: DateTime<Utc>,
+    ) -> AppResult<(Vec<AiUsageGroup>, Vec<AiUsageGroup>)> {
+        const SUMS: &str = "count() AS generations, math::sum(input_tokens) AS input_tokens, \
+            math::sum(output_tokens) AS output_tokens, math::sum(cost_usd) AS cost_usd, \
+            math::sum(latency_ms) AS latency_ms";
+
+        let mut response = self
+            .db
+            .client
+            .query(format!(
+                "SELECT campaign, {} FROM ai_usage WHERE workspace = $workspace AND created_at >= $since GROUP BY campaign",
+                SUMS
+            ))
+            .query(format!(
+                "SELECT month, {} FROM ai_usage WHERE workspace = $workspace AND created_at >= $since GROUP BY month",
+                SUMS
+            ))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("since", since))
+            .await?;
+
+        Ok((response.take(0)?, response.take(1)?))
+    }
+}
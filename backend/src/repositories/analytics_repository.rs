@@ -0,0 +1,659 @@
+//! Analytics Repository - aggregate counts over contacts, timeline and RSVPs
+//!
+//! Everything here returns raw counts; turning them into rates and funnel
+//! percentages is the AnalyticsService's job.
+
+use crate::db::{workspace_thing, Database};
+use crate::domain::{DealStage, WinLossReason};
+use crate::models::{ContactStatus, Sentiment, TimelineEntryType};
+use crate::error::AppResult;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Timeline entries that show a contact responding to a campaign; sends,
+/// bounces, complaints and unsubscribes don't
+const ENGAGEMENT_TYPES: [TimelineEntryType; 11] = [
+    TimelineEntryType::EmailOpen,
+    TimelineEntryType::EmailClick,
+    TimelineEntryType::EmailReceived,
+    TimelineEntryType::SocialTouch,
+    TimelineEntryType::LandingPageVisit,
+    TimelineEntryType::FormSubmission,
+    TimelineEntryType::EventRegistration,
+    TimelineEntryType::EventAttend,
+    TimelineEntryType::MeetingScheduled,
+    TimelineEntryType::MeetingAttended,
+    TimelineEntryType::Call,
+];
+
+/// Raw counts for one campaign
+#[derive(Debug, Clone, Default)]
+pub struct CampaignCounts {
+    pub recipients: u64,
+    pub emails_sent: u64,
+    /// Recipients sent to that wrote back
+    pub replies: u64,
+    /// Unique contacts that opened
+    pub emails_opened: u64,
+    /// Unique contacts that clicked
+    pub emails_clicked: u64,
+    /// Unique visitors per landing page, summed over the campaign's pages
+    pub landing_page_visits: u64,
+    /// Unique contacts that submitted a campaign form or registered for a campaign event
+    pub conversions: u64,
+    /// Unique contacts that did any of `ENGAGEMENT_TYPES` for the campaign
+    pub engaged_contacts: u64,
+    /// IDs of the campaign's landing pages
+    pub pages: Vec<String>,
+    pub page_visits: Vec<PageVisitCount>,
+    pub page_submissions: Vec<PageSubmissionRow>,
+    pub link_clicks: Vec<LinkClickRow>,
+}
+
+/// Raw bounce and complaint counts for one campaign
+#[derive(Debug, Clone, Default)]
+pub struct DeliverabilityCounts {
+    /// Recipients the campaign's email went out to, bounced ones included
+    pub sent: u64,
+    /// Unique contacts whose email bounced for good
+    pub hard_bounces: u64,
+    /// Unique contacts whose email bounced temporarily
+    pub soft_bounces: u64,
+    /// Unique contacts that marked the email as spam
+    pub complaints: u64,
+}
+
+/// Unique visitors of one landing page
+#[derive(Debug, Clone, Deserialize)]
+pub struct PageVisitCount {
+    pub page: Thing,
+    pub visits: u64,
+}
+
+/// A contact that submitted a landing page's form
+#[derive(Debug, Clone, Deserialize)]
+pub struct PageSubmissionRow {
+    pub landing_page_id: String,
+    pub contact: Thing,
+}
+
+/// A click on a campaign link, through a tracking token or a short link
+#[derive(Debug, Clone, Deserialize)]
+pub struct LinkClickRow {
+    pub url: String,
+    #[serde(rename = "type")]
+    pub entry_type: TimelineEntryType,
+    pub contact: Thing,
+}
+
+/// Number of contacts per status
+#[derive(Debug, Clone, Deserialize)]
+pub struct StatusCount {
+    pub status: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EngagedContactRow {
+    pub id: Thing,
+    pub first_name: String,
+    pub last_name: String,
+    pub engagement_score: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ContactCounts {
+    pub by_status: Vec<StatusCount>,
+    pub avg_engagement_score: f64,
+    pub new_since_month_start: u64,
+    pub new_in_range: u64,
+    pub top_engaged: Vec<EngagedContactRow>,
+}
+
+/// Size of each funnel stage, for contacts created in the range
+#[derive(Debug, Clone, Default)]
+pub struct FunnelCounts {
+    pub new_contacts: u64,
+    pub engaged: u64,
+    pub event_registrants: u64,
+    /// Customers now, or at any point since their creation
+    pub customers: u64,
+    /// When each of the contacts was created
+    pub created: Vec<ContactCreated>,
+    /// Their status changes, oldest first
+    pub status_changes: Vec<StatusChangeRow>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContactCreated {
+    pub id: Thing,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One status change of a contact
+#[derive(Debug, Clone, Deserialize)]
+pub struct StatusChangeRow {
+    pub contact: Thing,
+    pub from_status: ContactStatus,
+    pub to_status: ContactStatus,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// Customers won and lost in the range
+#[derive(Debug, Clone, Default)]
+pub struct ChurnCounts {
+    pub customers_now: u64,
+    /// Contacts that became customers, by a status change or created as one
+    pub new_customers: u64,
+    pub churned: u64,
+    pub reasons: Vec<ReasonCount>,
+}
+
+/// Number of churns given the same reason
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReasonCount {
+    pub reason: String,
+    pub count: u64,
+}
+
+/// Deals closed, and leads won or lost, in the range
+#[derive(Debug, Clone, Default)]
+pub struct WinLossCounts {
+    pub deals: Vec<WinLossRow>,
+    pub leads: Vec<WinLossRow>,
+}
+
+/// Number won or lost in one month for the same reason
+#[derive(Debug, Clone, Deserialize)]
+pub struct WinLossRow {
+    /// `YYYY-MM`
+    pub month: String,
+    pub won: bool,
+    pub reason: Option<WinLossReason>,
+    pub count: u64,
+}
+
+/// RSVP statuses, registrations and top attendees of one event
+#[derive(Debug, Clone, Default)]
+pub struct EventCounts {
+    pub by_status: Vec<StatusCount>,
+    pub registrations_by_day: Vec<DailyCount>,
+    pub top_attendees: Vec<EngagedContactRow>,
+}
+
+/// Number of things that happened on one day
+#[derive(Debug, Clone, Deserialize)]
+pub struct DailyCount {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub count: u64,
+}
+
+/// Number and summed value of deals in one stage
+#[derive(Debug, Clone, Deserialize)]
+pub struct StageTotals {
+    pub stage: DealStage,
+    pub count: u64,
+    pub total_value: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PipelineCounts {
+    /// Every deal, grouped by current stage
+    pub by_stage: Vec<StageTotals>,
+    /// Closed within the range
+    pub won: Option<StageTotals>,
+    pub lost: Option<StageTotals>,
+}
+
+/// Deals of one stage in one place: the company's, or else the contact's
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegionStageTotals {
+    pub country: Option<String>,
+    pub region: Option<String>,
+    pub stage: DealStage,
+    pub count: u64,
+    pub total_value: f64,
+}
+
+/// A timeline entry with a sentiment, by a contact at a company
+#[derive(Debug, Clone, Deserialize)]
+pub struct RatedInteraction {
+    pub company: Thing,
+    pub sentiment: Sentiment,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Company name by ID
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompanyName {
+    pub id: Thing,
+    pub name: String,
+}
+
+/// Repository for analytics aggregations
+pub struct AnalyticsRepository {
+    db: Arc<Database>,
+}
+
+impl AnalyticsRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    pub async fn campaign_counts(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        since: DateTime<Utc>,
+    ) -> AppResult<CampaignCounts> {
+        let mut response = self
+            .db
+            .client
+            .query(
+                "LET $pages = (SELECT VALUE meta::id(id) FROM campaign_asset \
+                    WHERE workspace = $workspace AND campaign = $campaign AND type = 'landing_page')",
+            )
+            .query("LET $events = (SELECT VALUE id FROM event WHERE workspace = $workspace AND campaign = $campaign)")
+            .query(
+                "RETURN array::len((SELECT VALUE id FROM campaign_recipient \
+                    WHERE workspace = $workspace AND campaign = $campaign))",
+            )
+            .query(
+                "RETURN array::len((SELECT VALUE id FROM campaign_recipient \
+                    WHERE workspace = $workspace AND campaign = $campaign \
+                       AND status INSIDE ['sent', 'replied'] AND sent_at >= $since))",
+            )
+            .query(
+                "RETURN array::len((SELECT VALUE id FROM campaign_recipient \
+                    WHERE workspace = $workspace AND campaign = $campaign \
+                       AND status = 'replied' AND sent_at >= $since))",
+            )
+            .query(
+                "RETURN array::len(array::distinct((SELECT VALUE contact FROM timeline_entry \
+                    WHERE workspace = $workspace AND type = 'email_open' \
+                       AND campaign = $campaign AND timestamp >= $since)))",
+            )
+            .query(
+                "RETURN array::len(array::distinct((SELECT VALUE contact FROM timeline_entry \
+                    WHERE workspace = $workspace AND type = 'email_click' \
+                       AND campaign = $campaign AND timestamp >= $since)))",
+            )
+            .query(
+                "RETURN array::len((SELECT VALUE id FROM landing_page_visit \
+                    WHERE workspace = $workspace AND campaign = $campaign AND visited_at >= $since))",
+            )
+            .query(
+                "RETURN array::len(array::union( \
+                    (SELECT VALUE contact FROM timeline_entry \
+                        WHERE workspace = $workspace AND campaign = $campaign \
+                           AND type INSIDE ['form_submission', 'event_registration', 'event_attend'] \
+                           AND timestamp >= $since), \
+                    (SELECT VALUE contact FROM rsvp \
+                        WHERE workspace = $workspace AND event INSIDE $events \
+                           AND status INSIDE ['registered', 'attended'] AND timestamp >= $since)))",
+            )
+            .query(
+                "RETURN array::len(array::distinct((SELECT VALUE contact FROM timeline_entry \
+                    WHERE workspace = $workspace AND campaign = $campaign \
+                       AND type INSIDE $engagement_types AND timestamp >= $since)))",
+            )
+            .query("RETURN $pages")
+            .query(
+                "SELECT page, count() AS visits FROM landing_page_visit \
+                 WHERE workspace = $workspace AND campaign = $campaign AND visited_at >= $since GROUP BY page",
+            )
+            .query(
+                "SELECT metadata.landing_page_id AS landing_page_id, contact FROM timeline_entry \
+                 WHERE workspace = $workspace AND campaign = $campaign AND type = 'form_submission' \
+                    AND metadata.landing_page_id INSIDE $pages AND timestamp >= $since",
+            )
+            .query(
+                "SELECT metadata.url AS url, type, contact FROM timeline_entry \
+                 WHERE workspace = $workspace AND campaign = $campaign AND timestamp >= $since \
+                    AND (type = 'email_click' OR (type = 'social_touch' AND metadata.short_code != NONE)) \
+                    AND metadata.url != NONE",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("campaign", Thing::from(("campaign", campaign_id))))
+            .bind(("since", since))
+            .bind(("engagement_types", ENGAGEMENT_TYPES))
+            .await?;
+
+        Ok(CampaignCounts {
+            recipients: count(response.take(2)?),
+            emails_sent: count(response.take(3)?),
+            replies: count(response.take(4)?),
+            emails_opened: count(response.take(5)?),
+            emails_clicked: count(response.take(6)?),
+            landing_page_visits: count(response.take(7)?),
+            conversions: count(response.take(8)?),
+            engaged_contacts: count(response.take(9)?),
+            pages: response.take(10)?,
+            page_visits: response.take(11)?,
+            page_submissions: response.take(12)?,
+            link_clicks: response.take(13)?,
+        })
+    }
+
+    pub async fn deliverability_counts(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        since: DateTime<Utc>,
+    ) -> AppResult<DeliverabilityCounts> {
+        let mut response = self
+            .db
+            .client
+            .query(
+                "RETURN array::len((SELECT VALUE id FROM campaign_recipient \
+                    WHERE workspace = $workspace AND campaign = $campaign \
+                       AND status INSIDE ['sent', 'replied', 'bounced'] AND sent_at >= $since))",
+            )
+            .query(
+                "RETURN array::len(array::distinct((SELECT VALUE contact FROM timeline_entry \
+                    WHERE workspace = $workspace AND campaign = $campaign AND type = 'email_bounce' \
+                       AND metadata.kind = 'hard_bounce' AND timestamp >= $since)))",
+            )
+            .query(
+                "RETURN array::len(array::distinct((SELECT VALUE contact FROM timeline_entry \
+                    WHERE workspace = $workspace AND campaign = $campaign AND type = 'email_bounce' \
+                       AND metadata.kind = 'soft_bounce' AND timestamp >= $since)))",
+            )
+            .query(
+                "RETURN array::len(array::distinct((SELECT VALUE contact FROM timeline_entry \
+                    WHERE workspace = $workspace AND campaign = $campaign AND type = 'email_complaint' \
+                       AND timestamp >= $since)))",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("campaign", Thing::from(("campaign", campaign_id))))
+            .bind(("since", since))
+            .await?;
+
+        Ok(DeliverabilityCounts {
+            sent: count(response.take(0)?),
+            hard_bounces: count(response.take(1)?),
+            soft_bounces: count(response.take(2)?),
+            complaints: count(response.take(3)?),
+        })
+    }
+
+    pub async fn contact_counts(
+        &self,
+        workspace_id: &str,
+        since: DateTime<Utc>,
+        month_start: DateTime<Utc>,
+        top_limit: u32,
+    ) -> AppResult<ContactCounts> {
+        let mut response = self
+            .db
+            .client
+            .query("SELECT status, count() AS count FROM contact \
+                 WHERE workspace = $workspace AND deleted_at IS NONE GROUP BY status")
+            .query(
+                "RETURN math::mean((SELECT VALUE engagement_score FROM contact \
+                    WHERE workspace = $workspace AND deleted_at IS NONE))",
+            )
+            .query(
+                "RETURN array::len((SELECT VALUE id FROM contact \
+                    WHERE workspace = $workspace AND deleted_at IS NONE AND created_at >= $month_start))",
+            )
+            .query(
+                "RETURN array::len((SELECT VALUE id FROM contact \
+                    WHERE workspace = $workspace AND deleted_at IS NONE AND created_at >= $since))",
+            )
+            .query(
+                "SELECT id, first_name, last_name, engagement_score FROM contact \
+                 WHERE workspace = $workspace AND deleted_at IS NONE \
+                 ORDER BY engagement_score DESC LIMIT $top_limit",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("since", since))
+            .bind(("month_start", month_start))
+            .bind(("top_limit", top_limit))
+            .await?;
+
+        let avg: Option<f64> = response.take(1)?;
+
+        Ok(ContactCounts {
+            by_status: response.take(0)?,
+            avg_engagement_score: avg.filter(|a| a.is_finite()).unwrap_or(0.0),
+            new_since_month_start: count(response.take(2)?),
+            new_in_range: count(response.take(3)?),
+            top_engaged: response.take(4)?,
+        })
+    }
+
+    pub async fn funnel_counts(&self, workspace_id: &str, since: DateTime<Utc>) -> AppResult<FunnelCounts> {
+        let mut response = self
+            .db
+            .client
+            .query(
+                "LET $new = (SELECT VALUE id FROM contact \
+                    WHERE workspace = $workspace AND deleted_at IS NONE AND created_at >= $since)",
+            )
+            .query("RETURN array::len($new)")
+            .query(
+                "RETURN array::len(array::distinct((SELECT VALUE contact FROM timeline_entry \
+                    WHERE workspace = $workspace AND contact INSIDE $new \
+                       AND type INSIDE ['email_open', 'email_click', 'email_received', 'landing_page_visit', \
+                           'form_submission', 'event_registration', 'event_attend', 'meeting_scheduled', \
+                           'meeting_attended'])))",
+            )
+            .query(
+                "RETURN array::len(array::distinct((SELECT VALUE contact FROM rsvp \
+                    WHERE workspace = $workspace AND contact INSIDE $new \
+                       AND status INSIDE ['registered', 'attended'])))",
+            )
+            .query(
+                "RETURN array::len(array::union( \
+                    (SELECT VALUE id FROM contact \
+                        WHERE workspace = $workspace AND id INSIDE $new AND status = 'customer'), \
+                    (SELECT VALUE contact FROM status_history \
+                        WHERE workspace = $workspace AND contact INSIDE $new AND to_status = 'customer')))",
+            )
+            .query("SELECT id, created_at FROM contact WHERE workspace = $workspace AND id INSIDE $new")
+            .query(
+                "SELECT contact, from_status, to_status, changed_at FROM status_history \
+                 WHERE workspace = $workspace AND contact INSIDE $new ORDER BY changed_at ASC",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("since", since))
+            .await?;
+
+        Ok(FunnelCounts {
+            new_contacts: count(response.take(1)?),
+            engaged: count(response.take(2)?),
+            event_registrants: count(response.take(3)?),
+            customers: count(response.take(4)?),
+            created: response.take(5)?,
+            status_changes: response.take(6)?,
+        })
+    }
+
+    pub async fn event_counts(&self, workspace_id: &str, event_id: &str, top_limit: u32) -> AppResult<EventCounts> {
+        let mut response = self
+            .db
+            .client
+            .query(
+                "SELECT status, count() AS count FROM rsvp \
+                 WHERE workspace = $workspace AND event = $event GROUP BY status",
+            )
+            .query(
+                "SELECT time::format(timestamp, '%Y-%m-%d') AS date, count() AS count FROM timeline_entry \
+                 WHERE workspace = $workspace AND type = 'event_registration' \
+                    AND metadata.event_id = $event_id \
+                 GROUP BY date ORDER BY date ASC",
+            )
+            .query(
+                "SELECT contact.id AS id, contact.first_name AS first_name, contact.last_name AS last_name, \
+                    contact.engagement_score AS engagement_score FROM rsvp \
+                 WHERE workspace = $workspace AND event = $event AND status = 'attended' \
+                    AND contact.deleted_at IS NONE \
+                 ORDER BY engagement_score DESC LIMIT $top_limit",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("event", Thing::from(("event", event_id))))
+            .bind(("event_id", event_id.to_string()))
+            .bind(("top_limit", top_limit))
+            .await?;
+
+        Ok(EventCounts {
+            by_status: response.take(0)?,
+            registrations_by_day: response.take(1)?,
+            top_attendees: response.take(2)?,
+        })
+    }
+
+    pub async fn pipeline_counts(&self, workspace_id: &str, since: DateTime<Utc>) -> AppResult<PipelineCounts> {
+        let mut response = self
+            .db
+            .client
+            .query(
+                "SELECT stage, count() AS count, math::sum(value) AS total_value FROM deal \
+                 WHERE workspace = $workspace GROUP BY stage",
+            )
+            .query(
+                "SELECT stage, count() AS count, math::sum(value) AS total_value FROM deal \
+                 WHERE workspace = $workspace AND stage INSIDE ['closed_won', 'closed_lost'] \
+                    AND closed_at >= $since GROUP BY stage",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("since", since))
+            .await?;
+
+        let by_stage: Vec<StageTotals> = response.take(0)?;
+        let closed: Vec<StageTotals> = response.take(1)?;
+
+        Ok(PipelineCounts {
+            by_stage,
+            won: closed.iter().find(|s| s.stage == DealStage::ClosedWon).cloned(),
+            lost: closed.into_iter().find(|s| s.stage == DealStage::ClosedLost),
+        })
+    }
+
+    /// Open deals, and deals closed since `since`, by place and stage
+    pub async fn pipeline_region_counts(
+        &self,
+        workspace_id: &str,
+        since: DateTime<Utc>,
+    ) -> AppResult<Vec<RegionStageTotals>> {
+        let rows: Vec<RegionStageTotals> = self
+            .db
+            .client
+            .query(
+                "SELECT (company.country ?? contact.country) AS country, \
+                    (company.region ?? contact.region) AS region, \
+                    stage, count() AS count, math::sum(value) AS total_value FROM deal \
+                 WHERE workspace = $workspace \
+                    AND (stage NOTINSIDE ['closed_won', 'closed_lost'] OR closed_at >= $since) \
+                 GROUP BY country, region, stage",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("since", since))
+            .await?
+            .take(0)?;
+
+        Ok(rows)
+    }
+
+    pub async fn churn_counts(&self, workspace_id: &str, since: DateTime<Utc>) -> AppResult<ChurnCounts> {
+        let mut response = self
+            .db
+            .client
+            .query(
+                "RETURN array::len((SELECT VALUE id FROM contact \
+                    WHERE workspace = $workspace AND deleted_at IS NONE AND status = 'customer'))",
+            )
+            .query(
+                "RETURN array::len(array::union( \
+                    (SELECT VALUE contact FROM status_history \
+                        WHERE workspace = $workspace AND to_status = 'customer' AND changed_at >= $since), \
+                    (SELECT VALUE id FROM contact \
+                        WHERE workspace = $workspace AND deleted_at IS NONE AND status = 'customer' \
+                           AND created_at >= $since)))",
+            )
+            .query(
+                "RETURN array::len(array::distinct((SELECT VALUE contact FROM timeline_entry \
+                    WHERE workspace = $workspace AND type = 'churned' AND timestamp >= $since)))",
+            )
+            .query(
+                "SELECT metadata.reason AS reason, count() AS count FROM timeline_entry \
+                 WHERE workspace = $workspace AND type = 'churned' AND timestamp >= $since \
+                    AND type::is::string(metadata.reason) \
+                 GROUP BY reason",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("since", since))
+            .await?;
+
+        Ok(ChurnCounts {
+            customers_now: count(response.take(0)?),
+            new_customers: count(response.take(1)?),
+            churned: count(response.take(2)?),
+            reasons: response.take(3)?,
+        })
+    }
+
+    /// Closed deals and leads won or lost since `since`, by month and reason
+    pub async fn win_loss_counts(&self, workspace_id: &str, since: DateTime<Utc>) -> AppResult<WinLossCounts> {
+        let mut response = self
+            .db
+            .client
+            .query(
+                "SELECT time::format(closed_at, '%Y-%m') AS month, (stage = 'closed_won') AS won, \
+                    win_loss_reason AS reason, count() AS count FROM deal \
+                 WHERE workspace = $workspace AND stage INSIDE ['closed_won', 'closed_lost'] \
+                    AND closed_at >= $since \
+                 GROUP BY month, won, reason",
+            )
+            .query(
+                "SELECT time::format(changed_at, '%Y-%m') AS month, (to_status = 'customer') AS won, \
+                    win_loss_reason AS reason, count() AS count FROM status_history \
+                 WHERE workspace = $workspace AND from_status = 'lead' AND to_status INSIDE ['customer', 'other'] \
+                    AND changed_at >= $since \
+                 GROUP BY month, won, reason",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("since", since))
+            .await?;
+
+        Ok(WinLossCounts {
+            deals: response.take(0)?,
+            leads: response.take(1)?,
+        })
+    }
+
+    /// Rated interactions since `since` of contacts that belong to a company,
+    /// and the names of those companies
+    pub async fn rated_interactions(
+        &self,
+        workspace_id: &str,
+        since: DateTime<Utc>,
+    ) -> AppResult<(Vec<RatedInteraction>, Vec<CompanyName>)> {
+        let mut response = self
+            .db
+            .client
+            .query(
+                "SELECT contact.company AS company, sentiment, timestamp FROM timeline_entry \
+                 WHERE workspace = $workspace AND sentiment IS NOT NONE AND timestamp >= $since \
+                    AND contact.company IS NOT NONE",
+            )
+            .query(
+                "SELECT id, name FROM company WHERE workspace = $workspace AND deleted_at IS NONE \
+                    AND id INSIDE (SELECT VALUE contact.company FROM timeline_entry \
+                        WHERE workspace = $workspace AND sentiment IS NOT NONE AND timestamp >= $since)",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("since", since))
+            .await?;
+
+        Ok((response.take(0)?, response.take(1)?))
+    }
+}
+
+fn count(value: Option<u64>) -> u64 {
+    value.unwrap_or(0)
+}
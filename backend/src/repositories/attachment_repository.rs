@@ -0,0 +1,91 @@
+//! Attachment Repository - metadata of attached files
+//!
+//! The files themselves live in object storage; a timeline entry also
+//! carries a summary of each file attached to it.
+
+use crate::db::{new_thing, workspace_thing, Database};
+use crate::error::{AppError, AppResult};
+use crate::models::{Attachment, AttachmentSummary, TimelineEntry};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Repository for Attachment database operations
+pub struct AttachmentRepository {
+    db: Arc<Database>,
+}
+
+impl AttachmentRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Save an attachment, adding its summary to its timeline entry if it has one
+    pub async fn create(&self, mut attachment: Attachment) -> AppResult<Attachment> {
+        let id = new_thing("attachment");
+        attachment.id = Some(id.clone());
+
+        let mut transaction = self
+            .db
+            .transaction()
+            .query("CREATE $attachment CONTENT $record")
+            .bind(("attachment", id.clone()))
+            .bind(("record", attachment.clone()));
+        if let Some(entry) = &attachment.timeline_entry {
+            transaction = transaction
+                .query("UPDATE $entry SET attachments += $summary")
+                .bind(("entry", entry.clone()))
+                .bind(("summary", AttachmentSummary::from(&attachment)));
+        }
+        transaction.commit().await?;
+
+        Ok(attachment)
+    }
+
+    pub async fn find_by_id(&self, workspace_id: &str, id: &str) -> AppResult<Option<Attachment>> {
+        Ok(self.db.select_scoped("attachment", id, workspace_id).await?)
+    }
+
+    /// Every file attached to a contact or one of its timeline entries, newest first
+    pub async fn find_for_contact(&self, workspace_id: &str, contact_id: &str) -> AppResult<Vec<Attachment>> {
+        let attachments: Vec<Attachment> = self
+            .db
+            .client
+            .query(
+                "SELECT * FROM attachment WHERE workspace = $workspace AND contact = $contact \
+                 ORDER BY created_at DESC",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("contact", Thing::from(("contact", contact_id))))
+            .await?
+            .take(0)?;
+
+        Ok(attachments)
+    }
+
+    pub async fn find_timeline_entry(&self, workspace_id: &str, id: &str) -> AppResult<Option<TimelineEntry>> {
+        Ok(self.db.select_scoped("timeline_entry", id, workspace_id).await?)
+    }
+
+    /// Delete an attachment and drop it from its timeline entry
+    pub async fn delete(&self, attachment: &Attachment) -> AppResult<()> {
+        let id = attachment
+            .id
+            .clone()
+            .ok_or_else(|| AppError::Internal("Attachment has no ID".into()))?;
+
+        let mut transaction = self
+            .db
+            .transaction()
+            .query("DELETE $attachment")
+            .bind(("attachment", id.clone()));
+        if let Some(entry) = &attachment.timeline_entry {
+            transaction = transaction
+                .query("UPDATE $entry SET attachments = attachments[WHERE id != $attachment_id]")
+                .bind(("entry", entry.clone()))
+                .bind(("attachment_id", id.id.to_raw()));
+        }
+        transaction.commit().await?;
+
+        Ok(())
+    }
+}
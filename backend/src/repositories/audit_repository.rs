@@ -0,0 +1,61 @@
+//! Audit Repository - append-only storage for audit log entries
+
+use crate::db::{workspace_thing, Database};
+use crate::error::AppResult;
+use crate::models::{AuditLog, AuditQuery};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Repository for AuditLog database operations
+pub struct AuditRepository {
+    db: Arc<Database>,
+}
+
+impl AuditRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    pub async fn insert(&self, entry: AuditLog) -> AppResult<()> {
+        let _: Vec<AuditLog> = self.db.client.create("audit_log").content(entry).await?;
+        Ok(())
+    }
+
+    /// List audit entries, newest first
+    pub async fn find(&self, workspace_id: &str, query: &AuditQuery) -> AppResult<Vec<AuditLog>> {
+        let mut conditions = vec!["workspace = $workspace"];
+        if query.entity_type.is_some() {
+            conditions.push("entity_type = $entity_type");
+        }
+        if query.entity_id.is_some() {
+            conditions.push("entity_id = $entity_id");
+        }
+        if query.action.is_some() {
+            conditions.push("action = $action");
+        }
+        if query.actor_id.is_some() {
+            conditions.push("actor = $actor");
+        }
+
+        let query_str = format!(
+            "SELECT * FROM audit_log WHERE {} ORDER BY timestamp DESC LIMIT $limit START $offset",
+            conditions.join(" AND ")
+        );
+
+        let entries: Vec<AuditLog> = self
+            .db
+            .client
+            .query(query_str)
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("entity_type", query.entity_type))
+            .bind(("entity_id", query.entity_id.clone()))
+            .bind(("action", query.action))
+            .bind(("actor", query.actor_id.as_deref().map(|id| Thing::from(("user", id)))))
+            .bind(("limit", query.limit.unwrap_or(50).min(500)))
+            .bind(("offset", query.offset.unwrap_or(0)))
+            .await?
+            .take(0)?;
+
+        Ok(entries)
+    }
+}
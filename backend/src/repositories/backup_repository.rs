@@ -0,0 +1,236 @@
+//! Backup Repository - a workspace's records, table by table, as raw values
+//!
+//! Backups copy records verbatim, so this repository works with SurrealDB
+//! values rather than models: every field survives a backup and restore,
+//! including ones the models don't know about.
+
+use crate::db::{workspace_thing, Database};
+use crate::domain::RelationshipKind;
+use crate::error::AppResult;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use surrealdb::sql::{Thing, Value};
+
+/// Every table holding a workspace's data, in backup order
+///
+/// The workspace record itself comes first. Everything else carries a
+/// `workspace` link. `idempotency_key` is left out: its records expire
+/// within a day and replaying one after a restore would be wrong anyway.
+pub const BACKUP_TABLES: &[&str] = &[
+    "workspace",
+    "user",
+    "contact",
+    "company",
+    "deal",
+    "timeline_entry",
+    "segment",
+    "sender_identity",
+    "campaign",
+    "campaign_asset",
+    "landing_page_visit",
+    "ai_usage",
+    "campaign_recipient",
+    "campaign_template",
+    "sequence",
+    "sequence_enrollment",
+    "social_account",
+    "social_post",
+    "short_link",
+    "event",
+    "rsvp",
+    "webhook",
+    "webhook_delivery",
+    "webhook_attempt",
+    "integration",
+    "inbound_source",
+    "attachment",
+    "gdpr_erasure",
+    "import_job",
+    "import_ref",
+    "audit_log",
+    "digest_settings",
+    "contact_embedding",
+    "dedupe_suggestion",
+    "introduced_by",
+    "works_with",
+    "invested_in",
+];
+
+/// Repository for workspace backups and restores
+pub struct BackupRepository {
+    db: Arc<Database>,
+}
+
+impl BackupRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Up to `limit` of the workspace's records in `table` after the record `after`, by ID
+    ///
+    /// `table` must be one of `BACKUP_TABLES`. Trashed records are included.
+    pub async fn find_page_after(
+        &self,
+        table: &'static str,
+        workspace_id: &str,
+        after: Option<&Thing>,
+        limit: u32,
+    ) -> AppResult<Vec<Value>> {
+        let scope = if table == "workspace" { "id" } else { "workspace" };
+        let query_str = match after {
+            Some(_) => format!(
+                "SELECT * FROM {} WHERE {} = $workspace AND id > $after ORDER BY id LIMIT $limit",
+                table, scope
+            ),
+            None => format!("SELECT * FROM {} WHERE {} = $workspace ORDER BY id LIMIT $limit", table, scope),
+        };
+        let query_str = &query_str;
+
+        let records: Value = self
+            .db
+            .retry(move || async move {
+                self.db
+                    .client
+                    .query(query_str)
+                    .bind(("workspace", workspace_thing(workspace_id)))
+                    .bind(("after", after.cloned()))
+                    .bind(("limit", limit))
+                    .await?
+                    .take(0)
+            })
+            .await?;
+
+        Ok(match records {
+            Value::Array(records) => records.0,
+            _ => Vec::new(),
+        })
+    }
+
+    /// Every record of the workspace in `table`, which must be one of `BACKUP_TABLES`
+    pub async fn find_all(&self, table: &str, workspace_id: &str) -> AppResult<Vec<Value>> {
+        let records: Value = self
+            .db
+            .client
+            .query(format!("SELECT * FROM {} WHERE workspace = $workspace", table))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(match records {
+            Value::Array(records) => records.0,
+            _ => Vec::new(),
+        })
+    }
+
+    /// Those of `links` that are records of another workspace
+    ///
+    /// Links to records that don't exist are not returned. Links to
+    /// workspace records are not looked up.
+    pub async fn find_outside_workspace(&self, workspace_id: &str, links: Vec<Thing>) -> AppResult<Vec<Thing>> {
+        let foreign: Vec<Thing> = self
+            .db
+            .client
+            .query("SELECT VALUE id FROM $links WHERE workspace != $workspace")
+            .bind(("links", links))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+        Ok(foreign)
+    }
+
+    /// Users of other workspaces with any of `emails`, compared in lower case
+    ///
+    /// `emails` must be lower case. Returns the emails found.
+    pub async fn find_emails_outside_workspace(&self, workspace_id: &str, emails: Vec<String>) -> AppResult<Vec<String>> {
+        let taken: Vec<String> = self
+            .db
+            .client
+            .query("SELECT VALUE email FROM user WHERE string::lowercase(email) INSIDE $emails AND workspace != $workspace")
+            .bind(("emails", emails))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+        Ok(taken)
+    }
+
+    /// Keep one checked batch of a restore's records until the whole archive is read
+    pub async fn stage(&self, restore_id: &str, seq: u64, table: &'static str, records: Vec<Value>) -> AppResult<()> {
+        self.db
+            .client
+            .query(
+                "CREATE backup_staging SET restore = $restore, seq = $seq, table_name = $table, \
+                    records = $records, created_at = time::now()",
+            )
+            .bind(("restore", restore_id.to_string()))
+            .bind(("seq", seq))
+            .bind(("table", table))
+            .bind(("records", Value::from(records)))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Drop what a restore staged
+    pub async fn discard_staged(&self, restore_id: &str) -> AppResult<()> {
+        self.db
+            .client
+            .query("DELETE backup_staging WHERE restore = $restore")
+            .bind(("restore", restore_id.to_string()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Drop batches left behind by restores that never finished, e.g. when
+    /// the server stopped halfway through one
+    pub async fn discard_staged_before(&self, before: DateTime<Utc>) -> AppResult<()> {
+        self.db
+            .client
+            .query("DELETE backup_staging WHERE created_at < $before")
+            .bind(("before", before))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Replace the workspace's records with those a restore staged, in one transaction
+    ///
+    /// Every record of the workspace, the workspace record included, is
+    /// deleted first. The staged records are then inserted as they are, IDs
+    /// included, table by table in `BACKUP_TABLES` order. Relationship edges
+    /// are related again rather than inserted, so they can still be walked.
+    /// The records move from the staging table on the server; none of them
+    /// pass through here.
+    pub async fn replace_workspace(&self, workspace_id: &str, restore_id: &str) -> AppResult<()> {
+        let mut tx = self.db.transaction();
+        for table in BACKUP_TABLES.iter().rev() {
+            tx = match *table {
+                "workspace" => tx.query("DELETE $workspace"),
+                table => tx.query(format!("DELETE {} WHERE workspace = $workspace", table)),
+            };
+        }
+
+        for table in BACKUP_TABLES {
+            let insert = match RelationshipKind::from_table(table) {
+                Some(_) => format!(
+                    "FOR $record IN $records {{ RELATE ($record.in)->{}->($record.out) CONTENT $record; }};",
+                    table
+                ),
+                None => format!("INSERT INTO {} $records;", table),
+            };
+            tx = tx.query(format!(
+                "FOR $batch IN (SELECT seq, records FROM backup_staging \
+                    WHERE restore = $restore AND table_name = '{}' ORDER BY seq) \
+                 {{ LET $records = $batch.records; {} }}",
+                table, insert
+            ));
+        }
+
+        tx.query("DELETE backup_staging WHERE restore = $restore")
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("restore", restore_id.to_string()))
+            .commit()
+            .await?;
+        Ok(())
+    }
+}
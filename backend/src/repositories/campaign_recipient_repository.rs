@@ -0,0 +1,301 @@
+//! Campaign Recipient Repository - the materialized recipient list of a campaign
+
+use crate::db::{workspace_thing, Database};
+use crate::domain::{MergeFields, SubscriptionStatus};
+use crate::error::AppResult;
+use crate::models::{CampaignRecipient, CampaignStatus, RecipientStatus, TimelineEntry};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// A contact selected by a segment, ready to become a recipient
+#[derive(Debug, Clone)]
+pub struct ResolvedContact {
+    pub contact: Thing,
+    pub email: String,
+}
+
+/// A pending recipient joined with the contact's current subscription and
+/// the data its email is personalized with
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct PendingRecipient {
+    pub contact: Thing,
+    pub email: String,
+    pub subscription_status: SubscriptionStatus,
+    #[serde(default)]
+    pub first_name: String,
+    #[serde(default)]
+    pub last_name: String,
+    /// Name of the contact's company
+    #[serde(default)]
+    pub company: Option<String>,
+    #[serde(default)]
+    pub custom_fields: BTreeMap<String, String>,
+    /// IANA timezone for send windows: the contact's, else their company's
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+impl PendingRecipient {
+    pub fn merge_fields(&self) -> MergeFields {
+        MergeFields::new(&self.first_name, &self.last_name, &self.email)
+            .company(self.company.as_deref())
+            .custom_fields(&self.custom_fields)
+    }
+}
+
+/// Repository for CampaignRecipient database operations
+pub struct CampaignRecipientRepository {
+    db: Arc<Database>,
+}
+
+impl CampaignRecipientRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// List recipients of a campaign, optionally filtered by status
+    pub async fn find_by_campaign(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        status: Option<RecipientStatus>,
+        limit: u32,
+        offset: u32,
+    ) -> AppResult<Vec<CampaignRecipient>> {
+        let status_clause = if status.is_some() { "AND status = $status" } else { "" };
+        let query = format!(
+            "SELECT * FROM campaign_recipient \
+             WHERE workspace = $workspace AND campaign = $campaign {} \
+             ORDER BY created_at ASC LIMIT $limit START $offset",
+            status_clause
+        );
+
+        let recipients: Vec<CampaignRecipient> = self
+            .db
+            .client
+            .query(query)
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("campaign", Thing::from(("campaign", campaign_id))))
+            .bind(("status", status))
+            .bind(("limit", limit))
+            .bind(("offset", offset))
+            .await?
+            .take(0)?;
+
+        Ok(recipients)
+    }
+
+    /// Bring the recipient list in line with a freshly resolved segment
+    ///
+    /// Contacts new to the segment are added as pending, and pending
+    /// recipients that no longer match are dropped. Recipients that have
+    /// already been sent to (or bounced/unsubscribed) are history and are
+    /// kept as they are. Returns the number of recipients added.
+    pub async fn sync(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        resolved: Vec<ResolvedContact>,
+    ) -> AppResult<usize> {
+        let workspace = workspace_thing(workspace_id);
+        let campaign = Thing::from(("campaign", campaign_id));
+        let contacts: Vec<Thing> = resolved.iter().map(|r| r.contact.clone()).collect();
+
+        let existing: Vec<Thing> = self
+            .db
+            .client
+            .query(
+                "SELECT VALUE contact FROM campaign_recipient \
+                 WHERE workspace = $workspace AND campaign = $campaign",
+            )
+            .bind(("workspace", workspace.clone()))
+            .bind(("campaign", campaign.clone()))
+            .await?
+            .take(0)?;
+        let existing: HashSet<String> = existing.iter().map(|contact| contact.id.to_raw()).collect();
+
+        let now = Utc::now();
+        let new_recipients: Vec<CampaignRecipient> = resolved
+            .into_iter()
+            .filter(|r| !existing.contains(&r.contact.id.to_raw()))
+            .map(|r| CampaignRecipient {
+                id: None,
+                workspace: workspace.clone(),
+                campaign: campaign.clone(),
+                contact: r.contact,
+                email: r.email,
+                status: RecipientStatus::Pending,
+                sent_at: None,
+                replied_at: None,
+                created_at: now,
+                updated_at: now,
+            })
+            .collect();
+
+        // Dropping stale recipients and adding new ones happen together, so
+        // a failure never leaves the list half synced
+        let added = new_recipients.len();
+        let mut tx = self
+            .db
+            .transaction()
+            .query(
+                "DELETE campaign_recipient \
+                 WHERE workspace = $workspace AND campaign = $campaign \
+                    AND status = 'pending' AND contact NOTINSIDE $contacts",
+            )
+            .bind(("workspace", workspace))
+            .bind(("campaign", campaign))
+            .bind(("contacts", contacts));
+        if added > 0 {
+            tx = tx
+                .query("INSERT INTO campaign_recipient $recipients")
+                .bind(("recipients", new_recipients));
+        }
+        tx.commit().await?;
+
+        Ok(added)
+    }
+
+    /// Load the recipients still waiting to be sent to
+    ///
+    /// The subscription status is read from the contact at send time, not
+    /// copied at materialization, so an unsubscribe in between is honoured;
+    /// the same goes for the names and fields the email is personalized with.
+    /// Recipients whose contact has since been deleted are skipped.
+    pub async fn find_pending(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+    ) -> AppResult<Vec<PendingRecipient>> {
+        let recipients: Vec<PendingRecipient> = self
+            .db
+            .client
+            .query(
+                "SELECT contact, email, contact.subscription_status AS subscription_status, \
+                    contact.first_name AS first_name, contact.last_name AS last_name, \
+                    contact.company.name AS company, contact.custom_fields AS custom_fields, \
+                    (contact.timezone ?? contact.company.timezone) AS timezone \
+                 FROM campaign_recipient \
+                 WHERE workspace = $workspace AND campaign = $campaign \
+                    AND status = 'pending' AND contact.subscription_status != NONE \
+                 ORDER BY created_at ASC",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("campaign", Thing::from(("campaign", campaign_id))))
+            .await?
+            .take(0)?;
+
+        Ok(recipients)
+    }
+
+    /// Mark the recipients a run sent to and skipped, store the timeline
+    /// entries of the emails sent, and set the campaign's status: running
+    /// while recipients wait for their send window, completed otherwise
+    ///
+    /// All happen in one transaction, so a campaign is never shown as
+    /// running or completed with its sent or suppressed recipients still
+    /// pending, or the other way round.
+    pub async fn record_execution(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        sent: Vec<Thing>,
+        suppressed: Vec<Thing>,
+        entries: Vec<TimelineEntry>,
+        status: CampaignStatus,
+    ) -> AppResult<()> {
+        let mut tx = self.db.transaction();
+        if !entries.is_empty() {
+            tx = tx.query("INSERT INTO timeline_entry $entries").bind(("entries", entries));
+        }
+
+        tx.query(
+            "UPDATE campaign_recipient SET status = 'sent', sent_at = $now, updated_at = $now \
+             WHERE workspace = $workspace AND campaign = $campaign \
+                AND status = 'pending' AND contact INSIDE $sent",
+        )
+        .query(
+            "UPDATE campaign_recipient SET status = 'unsubscribed', updated_at = $now \
+             WHERE workspace = $workspace AND campaign = $campaign \
+                AND status = 'pending' AND contact INSIDE $suppressed",
+        )
+        .query("UPDATE $campaign SET status = $status, updated_at = $now WHERE workspace = $workspace")
+        .bind(("workspace", workspace_thing(workspace_id)))
+        .bind(("campaign", Thing::from(("campaign", campaign_id))))
+        .bind(("status", status))
+        .bind(("sent", sent))
+        .bind(("suppressed", suppressed))
+        .bind(("now", Utc::now()))
+        .commit()
+        .await?;
+
+        Ok(())
+    }
+
+    /// Mark pending recipients as unsubscribed
+    ///
+    /// With no campaign, applies to every campaign the contacts are pending in.
+    pub async fn mark_unsubscribed(
+        &self,
+        workspace_id: &str,
+        campaign_id: Option<&str>,
+        contacts: Vec<Thing>,
+    ) -> AppResult<()> {
+        if contacts.is_empty() {
+            return Ok(());
+        }
+
+        let campaign_clause = if campaign_id.is_some() { "AND campaign = $campaign" } else { "" };
+        let query = format!(
+            "UPDATE campaign_recipient SET status = 'unsubscribed', updated_at = $now \
+             WHERE workspace = $workspace {} AND status = 'pending' AND contact INSIDE $contacts",
+            campaign_clause
+        );
+
+        self.db
+            .client
+            .query(query)
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("campaign", campaign_id.map(|id| Thing::from(("campaign", id)))))
+            .bind(("contacts", contacts))
+            .bind(("now", Utc::now()))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Mark a contact's recipients as replied to
+    ///
+    /// Only recipients sent to between `sent_since` and the reply count, so
+    /// an answer to an old email doesn't credit a later campaign. Returns the
+    /// number of recipients marked.
+    pub async fn mark_replied(
+        &self,
+        workspace_id: &str,
+        contact: &Thing,
+        sent_since: DateTime<Utc>,
+        replied_at: DateTime<Utc>,
+    ) -> AppResult<usize> {
+        let replied: Vec<CampaignRecipient> = self
+            .db
+            .client
+            .query(
+                "UPDATE campaign_recipient SET status = 'replied', replied_at = $replied_at, updated_at = $now \
+                 WHERE workspace = $workspace AND contact = $contact AND status = 'sent' \
+                    AND sent_at >= $sent_since AND sent_at <= $replied_at \
+                 RETURN AFTER",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("contact", contact.clone()))
+            .bind(("sent_since", sent_since))
+            .bind(("replied_at", replied_at))
+            .bind(("now", Utc::now()))
+            .await?
+            .take(0)?;
+
+        Ok(replied.len())
+    }
+}
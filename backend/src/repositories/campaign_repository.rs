@@ -0,0 +1,336 @@
+//! Campaign Repository - campaigns, their assets and scheduling state
+//!
+//! Status changes that race with the scheduler (scheduling, cancelling,
+//! claiming a due campaign) are conditional updates on the current status,
+//! so exactly one of them wins.
+
+use crate::ai::ai_email::GeneratedEmail;
+use crate::db::{is_unique_violation, new_thing, workspace_thing, Database};
+use crate::domain::SendWindow;
+use crate::error::{AppError, AppResult};
+use crate::models::{Campaign, CampaignAsset, CampaignStatus, TemplateAsset};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Tries at numbering a new asset version before giving up to concurrent edits
+const VERSION_ATTEMPTS: u32 = 3;
+
+/// Repository for Campaign database operations
+#[derive(Clone)]
+pub struct CampaignRepository {
+    db: Arc<Database>,
+}
+
+impl CampaignRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    pub async fn find_by_id(&self, workspace_id: &str, id: &str) -> AppResult<Option<Campaign>> {
+        Ok(self.db.select_scoped("campaign", id, workspace_id).await?)
+    }
+
+    /// Create a campaign together with its assets
+    ///
+    /// Either everything is written or nothing is.
+    pub async fn create_with_assets(&self, mut campaign: Campaign, assets: &[TemplateAsset]) -> AppResult<Campaign> {
+        let id = new_thing("campaign");
+        campaign.id = Some(id.clone());
+
+        let assets: Vec<CampaignAsset> = assets
+            .iter()
+            .zip(template_versions(assets))
+            .map(|(asset, (version, active))| CampaignAsset {
+                id: None,
+                workspace: campaign.workspace.clone(),
+                campaign: id.clone(),
+                asset_type: asset.asset_type.clone(),
+                generated_content: asset.generated_content.clone(),
+                url: None,
+                version,
+                parent: None,
+                active,
+                created_at: campaign.created_at,
+            })
+            .collect();
+
+        let mut tx = self
+            .db
+            .transaction()
+            .query("CREATE $campaign CONTENT $record")
+            .bind(("campaign", id))
+            .bind(("record", campaign.clone()));
+        if !assets.is_empty() {
+            tx = tx.query("INSERT INTO campaign_asset $assets").bind(("assets", assets));
+        }
+        tx.commit().await?;
+
+        Ok(campaign)
+    }
+
+    /// Assets of a campaign, oldest first
+    ///
+    /// Only the active version of each type unless `include_history` is set.
+    pub async fn find_assets(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        include_history: bool,
+    ) -> AppResult<Vec<CampaignAsset>> {
+        // Assets from before versioning have no `active` field; they count as active
+        let active_clause = if include_history { "" } else { "AND active != false" };
+        let query = format!(
+            "SELECT * FROM campaign_asset WHERE workspace = $workspace AND campaign = $campaign {} \
+             ORDER BY created_at ASC",
+            active_clause
+        );
+
+        let assets: Vec<CampaignAsset> = self
+            .db
+            .client
+            .query(query)
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("campaign", Thing::from(("campaign", campaign_id))))
+            .await?
+            .take(0)?;
+
+        Ok(assets)
+    }
+
+    /// One asset of a campaign, whatever its version
+    pub async fn find_asset(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        asset_id: &str,
+    ) -> AppResult<Option<CampaignAsset>> {
+        let asset: Option<CampaignAsset> = self.db.select_scoped("campaign_asset", asset_id, workspace_id).await?;
+        Ok(asset.filter(|a| a.campaign.id.to_raw() == campaign_id))
+    }
+
+    /// Store a new version of an asset type and make it the active one
+    ///
+    /// The version number follows the highest one of the type so far. The
+    /// previously active version stays as history and, unless `asset.parent`
+    /// says otherwise, becomes the new version's parent. Versions are unique
+    /// per campaign and type, so a version numbered at the same time by
+    /// another request is numbered again.
+    pub async fn create_version(&self, asset: CampaignAsset) -> AppResult<CampaignAsset> {
+        let mut attempt = 1;
+        loop {
+            match self.try_create_version(asset.clone()).await {
+                Ok(created) => return Ok(created),
+                Err(e) if is_unique_violation(&e) && attempt < VERSION_ATTEMPTS => attempt += 1,
+                Err(e) if is_unique_violation(&e) => {
+                    return Err(AppError::Conflict("The asset is being changed by another request".into()));
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    async fn try_create_version(&self, mut asset: CampaignAsset) -> Result<CampaignAsset, surrealdb::Error> {
+        #[derive(serde::Deserialize)]
+        struct Existing {
+            id: Thing,
+            version: Option<u32>,
+            active: Option<bool>,
+        }
+
+        let existing: Vec<Existing> = self
+            .db
+            .client
+            .query(
+                "SELECT id, version, active FROM campaign_asset \
+                 WHERE workspace = $workspace AND campaign = $campaign AND type = $type \
+                 ORDER BY created_at ASC",
+            )
+            .bind(("workspace", asset.workspace.clone()))
+            .bind(("campaign", asset.campaign.clone()))
+            .bind(("type", asset.asset_type.clone()))
+            .await?
+            .take(0)?;
+
+        let id = new_thing("campaign_asset");
+        asset.id = Some(id.clone());
+        asset.version = existing.iter().map(|e| e.version.unwrap_or(1)).max().unwrap_or(0) + 1;
+        asset.active = true;
+        if asset.parent.is_none() {
+            asset.parent = existing
+                .iter()
+                .rev()
+                .find(|e| e.active.unwrap_or(true))
+                .map(|e| e.id.clone());
+        }
+
+        // The CREATE goes first so a clash on the version index is the error the commit reports
+        self.db
+            .transaction()
+            .query("CREATE $asset CONTENT $record")
+            .query(
+                "UPDATE campaign_asset SET active = false \
+                 WHERE workspace = $workspace AND campaign = $campaign AND type = $type \
+                    AND active != false AND id != $asset",
+            )
+            .bind(("workspace", asset.workspace.clone()))
+            .bind(("campaign", asset.campaign.clone()))
+            .bind(("type", asset.asset_type.clone()))
+            .bind(("asset", id))
+            .bind(("record", asset.clone()))
+            .commit()
+            .await?;
+
+        Ok(asset)
+    }
+
+    /// Set status and schedule, if the campaign is currently in one of `from`
+    ///
+    /// Returns `None` when it is not (anymore).
+    pub async fn update_schedule(
+        &self,
+        workspace_id: &str,
+        id: &str,
+        from: &[CampaignStatus],
+        status: CampaignStatus,
+        scheduled_at: Option<DateTime<Utc>>,
+        send_window: Option<SendWindow>,
+    ) -> AppResult<Option<Campaign>> {
+        let updated: Vec<Campaign> = self
+            .db
+            .client
+            .query(
+                "UPDATE $campaign SET status = $status, scheduled_at = $scheduled_at, \
+                    send_window = $send_window, updated_at = $now \
+                 WHERE workspace = $workspace AND status INSIDE $from RETURN AFTER",
+            )
+            .bind(("campaign", Thing::from(("campaign", id))))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("from", from.to_vec()))
+            .bind(("status", status))
+            .bind(("scheduled_at", scheduled_at))
+            .bind(("send_window", send_window))
+            .bind(("now", Utc::now()))
+            .await?
+            .take(0)?;
+
+        Ok(updated.into_iter().next())
+    }
+
+    /// Scheduled campaigns whose time has come, in every workspace
+    pub async fn find_due(&self, now: DateTime<Utc>) -> AppResult<Vec<Campaign>> {
+        let campaigns: Vec<Campaign> = self
+            .db
+            .client
+            .query("SELECT * FROM campaign WHERE status = 'scheduled' AND scheduled_at <= $now ORDER BY scheduled_at ASC")
+            .bind(("now", now))
+            .await?
+            .take(0)?;
+
+        Ok(campaigns)
+    }
+
+    /// Running campaigns with a send window, whose recipients may be waiting
+    /// for it to open
+    pub async fn find_running_windowed(&self) -> AppResult<Vec<Campaign>> {
+        let campaigns: Vec<Campaign> = self
+            .db
+            .client
+            .query("SELECT * FROM campaign WHERE status = 'running' AND send_window != NONE")
+            .await?
+            .take(0)?;
+
+        Ok(campaigns)
+    }
+
+    /// Move a due campaign from scheduled to running
+    ///
+    /// Returns false if it was cancelled or executed in the meantime.
+    pub async fn claim_scheduled(&self, workspace_id: &str, id: &str) -> AppResult<bool> {
+        let claimed: Vec<Campaign> = self
+            .db
+            .client
+            .query(
+                "UPDATE $campaign SET status = 'running', updated_at = $now \
+                 WHERE workspace = $workspace AND status = 'scheduled' RETURN AFTER",
+            )
+            .bind(("campaign", Thing::from(("campaign", id))))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("now", Utc::now()))
+            .await?
+            .take(0)?;
+
+        Ok(!claimed.is_empty())
+    }
+
+    pub async fn set_status(&self, workspace_id: &str, id: &str, status: CampaignStatus) -> AppResult<()> {
+        self.db
+            .client
+            .query("UPDATE $campaign SET status = $status, updated_at = $now WHERE workspace = $workspace")
+            .bind(("campaign", Thing::from(("campaign", id))))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("status", status))
+            .bind(("now", Utc::now()))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// The active email asset of a campaign
+    pub async fn latest_email(&self, workspace_id: &str, campaign_id: &str) -> AppResult<Option<GeneratedEmail>> {
+        let assets: Vec<CampaignAsset> = self
+            .db
+            .client
+            .query(
+                "SELECT * FROM campaign_asset \
+                 WHERE workspace = $workspace AND campaign = $campaign AND type = 'email' AND active != false \
+                 ORDER BY created_at DESC LIMIT 1",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("campaign", Thing::from(("campaign", campaign_id))))
+            .await?
+            .take(0)?;
+
+        assets
+            .into_iter()
+            .next()
+            .map(|a| {
+                serde_json::from_value(a.generated_content)
+                    .map_err(|e| AppError::Internal(format!("Stored email asset is malformed: {}", e)))
+            })
+            .transpose()
+    }
+}
+
+/// Version and active flag of each asset a campaign is created with
+///
+/// Several assets of one type become its versions, the last one active.
+fn template_versions(assets: &[TemplateAsset]) -> Vec<(u32, bool)> {
+    assets
+        .iter()
+        .enumerate()
+        .map(|(i, asset)| {
+            let same_type = |a: &TemplateAsset| a.asset_type == asset.asset_type;
+            let version = assets[..i].iter().filter(|a| same_type(a)).count() as u32 + 1;
+            (version, !assets[i + 1..].iter().any(same_type))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::AssetType;
+
+    #[test]
+    fn test_template_assets_of_one_type_become_versions() {
+        let asset = |asset_type| TemplateAsset {
+            asset_type,
+            generated_content: serde_json::json!({}),
+        };
+        let assets = [asset(AssetType::Email), asset(AssetType::SocialPost), asset(AssetType::Email)];
+
+        assert_eq!(template_versions(&assets), vec![(1, false), (1, true), (2, true)]);
+    }
+}
@@ -0,0 +1,46 @@
+//! Campaign Template Repository - storage for reusable campaign setups
+
+use crate::db::{workspace_thing, Database};
+use crate::error::{AppError, AppResult};
+use crate::models::CampaignTemplate;
+use std::sync::Arc;
+
+/// Repository for CampaignTemplate database operations
+pub struct CampaignTemplateRepository {
+    db: Arc<Database>,
+}
+
+impl CampaignTemplateRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    pub async fn create(&self, template: CampaignTemplate) -> AppResult<CampaignTemplate> {
+        let created: Vec<CampaignTemplate> = self.db.client.create("campaign_template").content(template).await?;
+
+        created
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::Internal("Failed to create campaign template".into()))
+    }
+
+    pub async fn find_all(&self, workspace_id: &str) -> AppResult<Vec<CampaignTemplate>> {
+        let templates: Vec<CampaignTemplate> = self
+            .db
+            .client
+            .query("SELECT * FROM campaign_template WHERE workspace = $workspace ORDER BY name ASC")
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(templates)
+    }
+
+    pub async fn find_by_id(&self, workspace_id: &str, id: &str) -> AppResult<Option<CampaignTemplate>> {
+        Ok(self.db.select_scoped("campaign_template", id, workspace_id).await?)
+    }
+
+    pub async fn delete(&self, workspace_id: &str, id: &str) -> AppResult<bool> {
+        Ok(self.db.delete_scoped("campaign_template", id, workspace_id).await?)
+    }
+}
@@ -5,12 +5,15 @@
 //! - Mapping between domain::Contact and database records
 //! - Query building for filters/search
 //! - Handling database-level constraints (unique email)
+//! - Workspace isolation: every method takes the caller's workspace ID
 
-use crate::db::Database;
-use crate::domain::{Contact as DomainContact, ContactStatus as DomainStatus};
+use crate::db::{workspace_thing, Database};
+use crate::domain::{Contact as DomainContact, ContactStatus as DomainStatus, SubscriptionStatus};
 use crate::error::{AppError, AppResult};
+use crate::models::{ContactSort, SortOrder, StatusHistoryEntry, TagCount, TimelineEntry};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use surrealdb::sql::Thing;
 
@@ -19,28 +22,70 @@ use surrealdb::sql::Thing;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContactRecord {
     pub id: Option<Thing>,
+    pub workspace: Thing,
     pub first_name: String,
     pub last_name: String,
     pub email: String,
     pub phone: Option<String>,
     pub linkedin_url: Option<String>,
+    #[serde(default)]
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub city: Option<String>,
+    #[serde(default)]
+    pub avatar_url: Option<String>,
+    #[serde(default)]
+    pub pipeline_position: Option<f64>,
     pub tags: Vec<String>,
     pub status: String, // Stored as string in DB
+    #[serde(default)]
+    pub subscription_status: SubscriptionStatus,
+    #[serde(default)]
+    pub custom_fields: BTreeMap<String, String>,
     pub engagement_score: f64,
     pub company: Option<Thing>,
+    #[serde(default)]
+    pub owner: Option<Thing>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
 /// Query parameters for listing contacts
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct ContactQuery {
     pub search: Option<String>,
     pub status: Option<DomainStatus>,
-    pub tags: Option<Vec<String>>,
+    /// Contacts carrying every one of these tags
+    pub tags_all: Option<Vec<String>>,
+    /// Contacts carrying at least one of these tags
+    pub tags_any: Option<Vec<String>>,
+    /// Contacts carrying none of these tags
+    pub exclude_tags: Option<Vec<String>>,
     pub company_id: Option<String>,
+    /// Upper case ISO code
+    pub country: Option<String>,
+    /// Matched ignoring case
+    pub region: Option<String>,
+    /// Matched ignoring case
+    pub city: Option<String>,
+    pub timezone: Option<String>,
     pub min_engagement: Option<f64>,
     pub max_engagement: Option<f64>,
+    /// Created at or after
+    pub created_after: Option<DateTime<Utc>>,
+    /// Created before
+    pub created_before: Option<DateTime<Utc>>,
+    /// Last changed at or after
+    pub updated_after: Option<DateTime<Utc>>,
+    /// Last changed before
+    pub updated_before: Option<DateTime<Utc>>,
+    pub sort: ContactSort,
+    /// `None` uses the sort column's default direction
+    pub order: Option<SortOrder>,
     pub limit: u32,
     pub offset: u32,
 }
@@ -73,9 +118,22 @@ impl ContactQuery {
         self.search = Some(search);
         self
     }
+
+    pub fn with_sort(mut self, sort: ContactSort, order: Option<SortOrder>) -> Self {
+        self.sort = sort;
+        self.order = order;
+        self
+    }
+
+    /// `ORDER BY` clause; the ID tiebreak keeps LIMIT/START paging stable
+    fn order_by(&self) -> String {
+        let order = self.order.unwrap_or_else(|| self.sort.default_order());
+        format!("ORDER BY {} {}, id ASC", self.sort.column(), order.as_sql())
+    }
 }
 
 /// Repository for Contact database operations
+#[derive(Clone)]
 pub struct ContactRepository {
     db: Arc<Database>,
 }
@@ -86,124 +144,255 @@ impl ContactRepository {
     }
 
     /// Find a contact by ID
-    pub async fn find_by_id(&self, id: &str) -> AppResult<Option<DomainContact>> {
+    pub async fn find_by_id(&self, workspace_id: &str, id: &str) -> AppResult<Option<DomainContact>> {
         let record: Option<ContactRecord> = self
             .db
-            .client
-            .select(("contact", id))
+            .select_scoped("contact", id, workspace_id)
             .await?;
 
         Ok(record.map(|r| self.to_domain(r)))
     }
 
-    /// Find a contact by email (for uniqueness checks)
-    pub async fn find_by_email(&self, email: &str) -> AppResult<Option<DomainContact>> {
+    /// Find a contact by email
+    ///
+    /// Trashed contacts are left out; uniqueness checks, which must see
+    /// them, use `find_other_id_by_email`.
+    pub async fn find_by_email(&self, workspace_id: &str, email: &str) -> AppResult<Option<DomainContact>> {
         let records: Vec<ContactRecord> = self
             .db
-            .client
-            .query("SELECT * FROM contact WHERE email = $email LIMIT 1")
-            .bind(("email", email.to_lowercase()))
-            .await?
-            .take(0)?;
+            .retry(move || async move {
+                self.db
+                    .client
+                    .query(
+                        "SELECT * FROM contact \
+                         WHERE workspace = $workspace AND email = $email AND deleted_at IS NONE LIMIT 1",
+                    )
+                    .bind(("workspace", workspace_thing(workspace_id)))
+                    .bind(("email", email.to_lowercase()))
+                    .await?
+                    .take(0)
+            })
+            .await?;
 
         Ok(records.into_iter().next().map(|r| self.to_domain(r)))
     }
 
-    /// Check if email exists (excluding a specific contact ID)
-    pub async fn email_exists_for_other(&self, email: &str, exclude_id: &str) -> AppResult<bool> {
+    /// ID of another contact already using this email, if any
+    ///
+    /// Trashed contacts count: they keep their email until purged.
+    pub async fn find_other_id_by_email(
+        &self,
+        workspace_id: &str,
+        email: &str,
+        exclude_id: Option<&str>,
+    ) -> AppResult<Option<String>> {
         let records: Vec<ContactRecord> = self
             .db
             .client
-            .query("SELECT * FROM contact WHERE email = $email AND id != $id LIMIT 1")
+            .query("SELECT * FROM contact WHERE workspace = $workspace AND email = $email AND id != $id LIMIT 1")
+            .bind(("workspace", workspace_thing(workspace_id)))
             .bind(("email", email.to_lowercase()))
-            .bind(("id", Thing::from(("contact", exclude_id))))
+            .bind(("id", exclude_id.map(|id| Thing::from(("contact", id)))))
             .await?
             .take(0)?;
 
-        Ok(!records.is_empty())
+        Ok(records.into_iter().next().and_then(|r| r.id).map(|t| t.id.to_string()))
     }
 
-    /// List contacts with optional filters
-    pub async fn find_all(&self, query: ContactQuery) -> AppResult<Vec<DomainContact>> {
-        let mut conditions = Vec::new();
-        let mut bindings: Vec<(&str, serde_json::Value)> = Vec::new();
+    /// Contacts that could duplicate `contact`, for a closer look in the domain
+    ///
+    /// A cheap prefilter: same last name, email starting the same way, or a
+    /// phone ending in the same digits. Trashed contacts are left out. When
+    /// there are more than `limit`, the same address and then the same phone
+    /// ending are kept first, then the most recently updated.
+    pub async fn find_duplicate_candidates(
+        &self,
+        workspace_id: &str,
+        contact: &DomainContact,
+        limit: u32,
+    ) -> AppResult<Vec<StoredContact>> {
+        let email_prefix: String = contact.email.trim().to_lowercase().chars().take(3).collect();
+        let phone_digits: Vec<char> = contact
+            .phone
+            .as_deref()
+            .unwrap_or_default()
+            .chars()
+            .filter(char::is_ascii_digit)
+            .collect();
+        let phone_tail: Option<String> =
+            (phone_digits.len() >= 7).then(|| phone_digits[phone_digits.len() - 4..].iter().collect());
 
-        // Build WHERE conditions dynamically
-        if let Some(ref status) = query.status {
-            conditions.push("status = $status");
-            bindings.push(("status", serde_json::json!(status_to_string(status))));
-        }
+        let records: Vec<ContactRecord> = self
+            .db
+            .client
+            .query(
+                "SELECT *, \
+                    (IF string::lowercase(email) = $email THEN 2 ELSE 0 END) \
+                    + (IF $phone_tail != NONE AND phone != NONE AND string::contains(phone, $phone_tail) \
+                        THEN 1 ELSE 0 END) AS candidate_rank \
+                 FROM contact WHERE workspace = $workspace AND deleted_at IS NONE AND ( \
+                    string::lowercase(last_name) = $last_name \
+                    OR string::starts_with(email, $email_prefix) \
+                    OR ($phone_tail != NONE AND phone != NONE AND string::contains(phone, $phone_tail)) \
+                 ) ORDER BY candidate_rank DESC, updated_at DESC LIMIT $limit",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("email", contact.email.trim().to_lowercase()))
+            .bind(("last_name", contact.last_name.trim().to_lowercase()))
+            .bind(("email_prefix", email_prefix))
+            .bind(("phone_tail", phone_tail))
+            .bind(("limit", limit))
+            .await?
+            .take(0)?;
 
-        if let Some(ref search) = query.search {
-            conditions.push("(first_name CONTAINS $search OR last_name CONTAINS $search OR email CONTAINS $search)");
-            bindings.push(("search", serde_json::json!(search)));
-        }
+        Ok(records.into_iter().map(|r| self.to_stored(r)).collect())
+    }
 
-        if let Some(min) = query.min_engagement {
-            conditions.push("engagement_score >= $min_engagement");
-            bindings.push(("min_engagement", serde_json::json!(min)));
-        }
+    /// IDs of contacts sharing a mailbox name, phone number or name prefix
+    /// with another contact of the workspace
+    ///
+    /// Contacts that share none of these can't be duplicates of each other,
+    /// so only these need loading when scanning the workspace. Trashed
+    /// contacts are left out, and at most `limit` IDs are returned.
+    pub async fn find_duplicate_group_members(&self, workspace_id: &str, limit: usize) -> AppResult<Vec<String>> {
+        let mut response = self
+            .db
+            .client
+            .query(
+                "SELECT VALUE ids FROM (SELECT key, array::group(id) AS ids FROM ( \
+                    SELECT id, IF domain INSIDE ['gmail.com', 'googlemail.com'] \
+                        THEN string::replace(local, '.', '') ELSE local END AS key \
+                    FROM (SELECT id, string::split(parts[0], '+')[0] AS local, parts[1] AS domain \
+                        FROM (SELECT id, string::split(string::lowercase(string::trim(email)), '@') AS parts \
+                            FROM contact WHERE workspace = $workspace AND deleted_at IS NONE)) \
+                 ) GROUP BY key) WHERE array::len(ids) > 1",
+            )
+            .query(
+                "SELECT VALUE ids FROM (SELECT key, array::group(id) AS ids FROM ( \
+                    SELECT id, string::replace(string::replace(string::replace(string::replace(string::replace( \
+                        string::replace(phone, ' ', ''), '-', ''), '(', ''), ')', ''), '.', ''), '+', '') AS key \
+                    FROM contact WHERE workspace = $workspace AND deleted_at IS NONE AND phone != NONE \
+                 ) WHERE string::len(key) >= 7 GROUP BY key) WHERE array::len(ids) > 1",
+            )
+            .query(
+                "SELECT VALUE ids FROM (SELECT key, array::group(id) AS ids FROM ( \
+                    SELECT id, string::concat(string::slice(string::lowercase(first_name), 0, 1), \
+                        string::slice(string::lowercase(last_name), 0, 3)) AS key \
+                    FROM contact WHERE workspace = $workspace AND deleted_at IS NONE \
+                 ) GROUP BY key) WHERE array::len(ids) > 1",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?;
 
-        if let Some(max) = query.max_engagement {
-            conditions.push("engagement_score <= $max_engagement");
-            bindings.push(("max_engagement", serde_json::json!(max)));
+        let mut seen = std::collections::HashSet::new();
+        let mut ids = Vec::new();
+        for statement in 0..3 {
+            let groups: Vec<Vec<Thing>> = response.take(statement)?;
+            for id in groups.into_iter().flatten() {
+                let id = id.id.to_raw();
+                if seen.insert(id.clone()) {
+                    ids.push(id);
+                }
+            }
         }
 
-        if let Some(ref company_id) = query.company_id {
-            conditions.push("company = $company");
-            bindings.push(("company", serde_json::json!(format!("company:{}", company_id))));
-        }
+        ids.truncate(limit);
+        Ok(ids)
+    }
+
+    /// List contacts with optional filters, each with its ID attached
+    pub async fn find_all_with_ids(&self, workspace_id: &str, query: ContactQuery) -> AppResult<Vec<StoredContact>> {
+        let (conditions, bindings) = filter_conditions(&query);
 
         // Build query string
-        let where_clause = if conditions.is_empty() {
-            String::new()
-        } else {
-            format!("WHERE {}", conditions.join(" AND "))
-        };
+        let where_clause = format!("WHERE {}", conditions.join(" AND "));
 
         let query_str = format!(
-            "SELECT * FROM contact {} ORDER BY created_at DESC LIMIT $limit START $offset",
-            where_clause
+            "SELECT * FROM contact {} {} LIMIT $limit START $offset",
+            where_clause,
+            query.order_by()
         );
 
-        let mut db_query = self.db.client.query(&query_str);
+        let query = &query;
+        let query_str = query_str.as_str();
+        let bindings = &bindings;
 
-        // Bind all parameters
-        for (key, value) in bindings {
-            db_query = db_query.bind((key, value));
-        }
+        let records: Vec<ContactRecord> = self
+            .db
+            .retry(move || async move {
+                let mut db_query = self
+                    .db
+                    .client
+                    .query(query_str)
+                    .bind(("workspace", workspace_thing(workspace_id)));
 
-        db_query = db_query
-            .bind(("limit", query.limit))
-            .bind(("offset", query.offset));
+                // Bind all parameters
+                for (key, value) in bindings {
+                    db_query = db_query.bind((*key, value.clone()));
+                }
 
-        let records: Vec<ContactRecord> = db_query.await?.take(0)?;
+                db_query
+                    .bind(("limit", query.limit))
+                    .bind(("offset", query.offset))
+                    .await?
+                    .take(0)
+            })
+            .await?;
 
-        Ok(records.into_iter().map(|r| self.to_domain(r)).collect())
+        Ok(records.into_iter().map(|r| self.to_stored(r)).collect())
     }
 
-    /// Create a new contact
-    pub async fn create(&self, contact: &DomainContact) -> AppResult<DomainContact> {
-        let record = self.to_record(contact);
+    /// Fetch one page of contacts ordered by ID, starting after `after`
+    ///
+    /// Used for exports: keyset pagination keeps every page query cheap no
+    /// matter how deep into the result set we are, unlike LIMIT/START.
+    /// `limit` and `offset` on the query are ignored.
+    pub async fn find_page_after(
+        &self,
+        workspace_id: &str,
+        query: &ContactQuery,
+        after: Option<&str>,
+        page_size: u32,
+    ) -> AppResult<Vec<StoredContact>> {
+        let (mut conditions, bindings) = filter_conditions(query);
 
-        let created: Vec<ContactRecord> = self
+        if after.is_some() {
+            conditions.push("id > $after");
+        }
+
+        let query_str = format!(
+            "SELECT * FROM contact WHERE {} ORDER BY id ASC LIMIT $page_size",
+            conditions.join(" AND ")
+        );
+
+        let mut db_query = self
             .db
             .client
-            .create("contact")
-            .content(record)
-            .await?;
+            .query(&query_str)
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("page_size", page_size));
 
-        let created = created
-            .into_iter()
-            .next()
-            .ok_or_else(|| AppError::Internal("Failed to create contact".into()))?;
+        for (key, value) in bindings {
+            db_query = db_query.bind((key, value));
+        }
+
+        if let Some(after) = after {
+            db_query = db_query.bind(("after", Thing::from(("contact", after))));
+        }
+
+        let records: Vec<ContactRecord> = db_query.await?.take(0)?;
 
-        Ok(self.to_domain(created))
+        Ok(records.into_iter().map(|r| self.to_stored(r)).collect())
     }
 
     /// Update an existing contact
-    pub async fn update(&self, id: &str, contact: &DomainContact) -> AppResult<DomainContact> {
-        let record = self.to_record(contact);
+    pub async fn update(&self, workspace_id: &str, id: &str, contact: &DomainContact) -> AppResult<DomainContact> {
+        // Refuse to touch a record that belongs to another workspace
+        if self.find_by_id(workspace_id, id).await?.is_none() {
+            return Err(AppError::NotFound(format!("Contact {} not found", id)));
+        }
+
+        let record = self.to_record(workspace_id, contact);
 
         let updated: Option<ContactRecord> = self
             .db
@@ -218,29 +407,145 @@ impl ContactRepository {
         Ok(self.to_domain(updated))
     }
 
-    /// Delete a contact
-    pub async fn delete(&self, id: &str) -> AppResult<bool> {
-        let _: Option<ContactRecord> = self
-            .db
-            .client
-            .delete(("contact", id))
+    /// Update a contact whose status changed, recording the change, and the
+    /// timeline entry about it if there is one, in the same transaction
+    pub async fn update_with_status_change(
+        &self,
+        workspace_id: &str,
+        id: &str,
+        contact: &DomainContact,
+        change: &StatusHistoryEntry,
+        entry: Option<&TimelineEntry>,
+    ) -> AppResult<DomainContact> {
+        // Refuse to touch a record that belongs to another workspace
+        if self.find_by_id(workspace_id, id).await?.is_none() {
+            return Err(AppError::NotFound(format!("Contact {} not found", id)));
+        }
+
+        let thing = Thing::from(("contact", id));
+        let mut record = self.to_record(workspace_id, contact);
+        record.id = Some(thing.clone());
+
+        let mut tx = self.db.transaction();
+        if let Some(entry) = entry {
+            tx = tx
+                .query("CREATE $entry_id CONTENT $entry")
+                .bind(("entry_id", entry.id.clone()))
+                .bind(("entry", entry.clone()));
+        }
+
+        tx.query("UPDATE $contact CONTENT $record WHERE workspace = $workspace")
+            .query("CREATE status_history CONTENT $change")
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("contact", thing))
+            .bind(("record", record))
+            .bind(("change", change.clone()))
+            .commit()
             .await?;
 
-        Ok(true)
+        self.find_by_id(workspace_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Contact {} not found", id)))
     }
 
-    /// Count contacts matching a query
-    pub async fn count(&self, query: ContactQuery) -> AppResult<u64> {
-        // Simplified - in production, reuse query building logic
-        let records: Vec<ContactRecord> = self
+    /// A contact's status changes, oldest first
+    pub async fn status_history(&self, workspace_id: &str, id: &str) -> AppResult<Vec<StatusHistoryEntry>> {
+        let entries: Vec<StatusHistoryEntry> = self
             .db
             .client
-            .query("SELECT count() FROM contact GROUP ALL")
+            .query(
+                "SELECT * FROM status_history WHERE workspace = $workspace AND contact = $contact \
+                 ORDER BY changed_at ASC",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("contact", Thing::from(("contact", id))))
             .await?
             .take(0)?;
 
-        // SurrealDB returns count in a specific format
-        Ok(records.len() as u64)
+        Ok(entries)
+    }
+
+    /// Move a contact to the trash
+    pub async fn delete(&self, workspace_id: &str, id: &str) -> AppResult<bool> {
+        let deleted = self.db.soft_delete_scoped("contact", id, workspace_id).await?;
+
+        Ok(deleted)
+    }
+
+    /// Take a contact back out of the trash
+    pub async fn restore(&self, workspace_id: &str, id: &str) -> AppResult<Option<StoredContact>> {
+        let record: Option<ContactRecord> = self.db.restore_scoped("contact", id, workspace_id).await?;
+
+        Ok(record.map(|r| self.to_stored(r)))
+    }
+
+    /// Count contacts matching a query; `limit`, `offset` and sorting are ignored
+    pub async fn count(&self, workspace_id: &str, query: &ContactQuery) -> AppResult<u64> {
+        let (conditions, bindings) = filter_conditions(query);
+
+        let query_str = format!(
+            "SELECT count() AS count FROM contact WHERE {} GROUP ALL",
+            conditions.join(" AND ")
+        );
+
+        let query_str = query_str.as_str();
+        let bindings = &bindings;
+
+        // GROUP ALL over no rows returns no row rather than a zero count
+        let count: Option<u64> = self
+            .db
+            .retry(move || async move {
+                let mut db_query = self
+                    .db
+                    .client
+                    .query(query_str)
+                    .bind(("workspace", workspace_thing(workspace_id)));
+
+                for (key, value) in bindings {
+                    db_query = db_query.bind((*key, value.clone()));
+                }
+
+                db_query.await?.take((0, "count"))
+            })
+            .await?;
+
+        Ok(count.unwrap_or(0))
+    }
+
+    /// Every tag on the workspace's contacts, most used first
+    pub async fn tag_counts(&self, workspace_id: &str) -> AppResult<Vec<TagCount>> {
+        #[derive(Deserialize)]
+        struct Row {
+            tags: String,
+            count: u64,
+        }
+
+        let rows: Vec<Row> = self
+            .db
+            .retry(move || async move {
+                self.db
+                    .client
+                    .query(
+                        "SELECT tags, count() AS count FROM contact \
+                         WHERE workspace = $workspace AND deleted_at IS NONE AND array::len(tags) > 0 \
+                         SPLIT tags GROUP BY tags",
+                    )
+                    .bind(("workspace", workspace_thing(workspace_id)))
+                    .await?
+                    .take(0)
+            })
+            .await?;
+
+        let mut tags: Vec<TagCount> = rows
+            .into_iter()
+            .map(|r| TagCount {
+                tag: r.tags,
+                count: r.count,
+            })
+            .collect();
+        tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+
+        Ok(tags)
     }
 
     // ---- Mapping Functions ----
@@ -253,28 +558,55 @@ impl ContactRepository {
             email: record.email,
             phone: record.phone,
             linkedin_url: record.linkedin_url,
+            timezone: record.timezone,
+            country: record.country,
+            region: record.region,
+            city: record.city,
+            avatar_url: record.avatar_url,
+            pipeline_position: record.pipeline_position,
             tags: record.tags,
             status: string_to_status(&record.status),
+            subscription_status: record.subscription_status,
+            custom_fields: record.custom_fields,
             engagement_score: record.engagement_score,
             company_id: record.company.map(|t| t.id.to_string()),
+            owner_id: record.owner.map(|t| t.id.to_string()),
             created_at: record.created_at,
             updated_at: record.updated_at,
         }
     }
 
+    /// Convert database record to domain model, keeping the record ID
+    fn to_stored(&self, record: ContactRecord) -> StoredContact {
+        StoredContact {
+            id: record.id.as_ref().map(|t| t.id.to_string()).unwrap_or_default(),
+            contact: self.to_domain(record),
+        }
+    }
+
     /// Convert domain model to database record
-    fn to_record(&self, contact: &DomainContact) -> ContactRecord {
+    fn to_record(&self, workspace_id: &str, contact: &DomainContact) -> ContactRecord {
         ContactRecord {
             id: None, // Let DB generate
+            workspace: workspace_thing(workspace_id),
             first_name: contact.first_name.clone(),
             last_name: contact.last_name.clone(),
             email: contact.email.clone(),
             phone: contact.phone.clone(),
             linkedin_url: contact.linkedin_url.clone(),
+            timezone: contact.timezone.clone(),
+            country: contact.country.clone(),
+            region: contact.region.clone(),
+            city: contact.city.clone(),
+            avatar_url: contact.avatar_url.clone(),
+            pipeline_position: contact.pipeline_position,
             tags: contact.tags.clone(),
             status: status_to_string(&contact.status),
+            subscription_status: contact.subscription_status,
+            custom_fields: contact.custom_fields.clone(),
             engagement_score: contact.engagement_score,
             company: contact.company_id.as_ref().map(|id| Thing::from(("company", id.as_str()))),
+            owner: contact.owner_id.as_ref().map(|id| Thing::from(("user", id.as_str()))),
             created_at: contact.created_at,
             updated_at: contact.updated_at,
         }
@@ -283,6 +615,82 @@ impl ContactRepository {
 
 // ---- Helper Functions ----
 
+/// Build the WHERE conditions and bindings for a contact query
+///
+/// Workspace scoping is not optional - it is always the first condition,
+/// and callers must bind `$workspace`. Trashed contacts never match.
+pub(crate) fn filter_conditions(query: &ContactQuery) -> (Vec<&'static str>, Vec<(&'static str, serde_json::Value)>) {
+    let mut conditions = vec!["workspace = $workspace", "deleted_at IS NONE"];
+    let mut bindings: Vec<(&'static str, serde_json::Value)> = Vec::new();
+
+    if let Some(ref status) = query.status {
+        conditions.push("status = $status");
+        bindings.push(("status", serde_json::json!(status_to_string(status))));
+    }
+
+    if let Some(ref search) = query.search {
+        conditions.push("(first_name CONTAINS $search OR last_name CONTAINS $search OR email CONTAINS $search)");
+        bindings.push(("search", serde_json::json!(search)));
+    }
+
+    if let Some(min) = query.min_engagement {
+        conditions.push("engagement_score >= $min_engagement");
+        bindings.push(("min_engagement", serde_json::json!(min)));
+    }
+
+    if let Some(max) = query.max_engagement {
+        conditions.push("engagement_score <= $max_engagement");
+        bindings.push(("max_engagement", serde_json::json!(max)));
+    }
+
+    // Bound as JSON strings, so cast back to datetimes to compare
+    let date_filters = [
+        (query.created_after, "created_at >= type::datetime($created_after)", "created_after"),
+        (query.created_before, "created_at < type::datetime($created_before)", "created_before"),
+        (query.updated_after, "updated_at >= type::datetime($updated_after)", "updated_after"),
+        (query.updated_before, "updated_at < type::datetime($updated_before)", "updated_before"),
+    ];
+    for (at, condition, name) in date_filters {
+        if let Some(at) = at {
+            conditions.push(condition);
+            bindings.push((name, serde_json::json!(at)));
+        }
+    }
+
+    // An empty tag list is no filter, rather than matching everything or nothing
+    let tag_filters = [
+        (&query.tags_all, "tags CONTAINSALL $tags_all", "tags_all"),
+        (&query.tags_any, "tags CONTAINSANY $tags_any", "tags_any"),
+        (&query.exclude_tags, "tags CONTAINSNONE $exclude_tags", "exclude_tags"),
+    ];
+    for (tags, condition, name) in tag_filters {
+        if let Some(tags) = tags.as_ref().filter(|tags| !tags.is_empty()) {
+            conditions.push(condition);
+            bindings.push((name, serde_json::json!(tags)));
+        }
+    }
+
+    if let Some(ref company_id) = query.company_id {
+        conditions.push("company = type::thing('company', $company_id)");
+        bindings.push(("company_id", serde_json::json!(company_id)));
+    }
+
+    let place_filters = [
+        (&query.country, "country = $country", "country"),
+        (&query.region, "string::lowercase(region ?? '') = string::lowercase($region)", "region"),
+        (&query.city, "string::lowercase(city ?? '') = string::lowercase($city)", "city"),
+        (&query.timezone, "timezone = $timezone", "timezone"),
+    ];
+    for (value, condition, name) in place_filters {
+        if let Some(value) = value {
+            conditions.push(condition);
+            bindings.push((name, serde_json::json!(value.trim())));
+        }
+    }
+
+    (conditions, bindings)
+}
+
 fn status_to_string(status: &DomainStatus) -> String {
     match status {
         DomainStatus::Lead => "lead".to_string(),
@@ -312,22 +720,18 @@ pub struct StoredContact {
 
 impl ContactRepository {
     /// Find by ID and return with ID attached
-    pub async fn find_by_id_with_id(&self, id: &str) -> AppResult<Option<StoredContact>> {
+    pub async fn find_by_id_with_id(&self, workspace_id: &str, id: &str) -> AppResult<Option<StoredContact>> {
         let record: Option<ContactRecord> = self
             .db
-            .client
-            .select(("contact", id))
+            .select_scoped("contact", id, workspace_id)
             .await?;
 
-        Ok(record.map(|r| StoredContact {
-            id: r.id.as_ref().map(|t| t.id.to_string()).unwrap_or_default(),
-            contact: self.to_domain(r),
-        }))
+        Ok(record.map(|r| self.to_stored(r)))
     }
 
     /// Create and return with ID
-    pub async fn create_with_id(&self, contact: &DomainContact) -> AppResult<StoredContact> {
-        let record = self.to_record(contact);
+    pub async fn create_with_id(&self, workspace_id: &str, contact: &DomainContact) -> AppResult<StoredContact> {
+        let record = self.to_record(workspace_id, contact);
 
         let created: Vec<ContactRecord> = self
             .db
@@ -341,9 +745,317 @@ impl ContactRepository {
             .next()
             .ok_or_else(|| AppError::Internal("Failed to create contact".into()))?;
 
-        Ok(StoredContact {
-            id: created.id.as_ref().map(|t| t.id.to_string()).unwrap_or_default(),
-            contact: self.to_domain(created),
-        })
+        Ok(self.to_stored(created))
+    }
+
+    /// Create a contact together with a first timeline entry about it
+    ///
+    /// The contact is created with the ID the entry names. Both are written
+    /// in one transaction, so there is never a contact without its note or
+    /// a note without its contact.
+    pub async fn create_with_note(
+        &self,
+        workspace_id: &str,
+        contact: &DomainContact,
+        entry: &TimelineEntry,
+    ) -> AppResult<StoredContact> {
+        let id = entry.contact.clone();
+        let mut record = self.to_record(workspace_id, contact);
+        record.id = Some(id.clone());
+
+        self.db
+            .transaction()
+            .query("CREATE $contact CONTENT $record")
+            .query("CREATE $entry_id CONTENT $entry")
+            .bind(("contact", id.clone()))
+            .bind(("record", record))
+            .bind(("entry_id", entry.id.clone()))
+            .bind(("entry", entry.clone()))
+            .commit()
+            .await?;
+
+        let stored = self
+            .find_by_id_with_id(workspace_id, &id.id.to_string())
+            .await?
+            .ok_or_else(|| AppError::Internal("Failed to create contact".into()))?;
+
+        Ok(stored)
+    }
+
+    /// Fold the duplicate contact into the primary in one transaction
+    ///
+    /// Stores the merged primary, moves timeline entries, deals, RSVPs,
+    /// campaign recipients, short links and status history over, records a note on the
+    /// primary's timeline, and the primary's status change if it took the
+    /// duplicate's, and deletes the duplicate. Where both contacts have an RSVP for the
+    /// same event the primary's is kept, upgraded if the duplicate had
+    /// registered or attended; shared campaign recipients keep the
+    /// primary's row.
+    pub async fn merge_into(
+        &self,
+        workspace_id: &str,
+        primary_id: &str,
+        merged: &DomainContact,
+        duplicate_id: &str,
+        note: &str,
+        status_change: Option<StatusHistoryEntry>,
+    ) -> AppResult<StoredContact> {
+        let primary = Thing::from(("contact", primary_id));
+        let mut record = self.to_record(workspace_id, merged);
+        record.id = Some(primary.clone());
+
+        let mut tx = self.db.transaction();
+        if let Some(change) = status_change {
+            tx = tx.query("CREATE status_history CONTENT $change").bind(("change", change));
+        }
+
+        tx.query("UPDATE $primary CONTENT $record")
+            .query("UPDATE timeline_entry SET contact = $primary WHERE workspace = $workspace AND contact = $duplicate")
+            .query("UPDATE status_history SET contact = $primary WHERE workspace = $workspace AND contact = $duplicate")
+            .query("UPDATE deal SET contact = $primary WHERE workspace = $workspace AND contact = $duplicate")
+            .query(
+                "UPDATE rsvp SET status = 'attended' \
+                 WHERE workspace = $workspace AND contact = $primary AND event INSIDE \
+                    (SELECT VALUE event FROM rsvp WHERE workspace = $workspace AND contact = $duplicate AND status = 'attended')",
+            )
+            .query(
+                "UPDATE rsvp SET status = 'registered' \
+                 WHERE workspace = $workspace AND contact = $primary AND status = 'invited' AND event INSIDE \
+                    (SELECT VALUE event FROM rsvp WHERE workspace = $workspace AND contact = $duplicate AND status = 'registered')",
+            )
+            .query(
+                "DELETE rsvp WHERE workspace = $workspace AND contact = $duplicate AND event INSIDE \
+                    (SELECT VALUE event FROM rsvp WHERE workspace = $workspace AND contact = $primary)",
+            )
+            .query("UPDATE rsvp SET contact = $primary WHERE workspace = $workspace AND contact = $duplicate")
+            .query(
+                "DELETE campaign_recipient WHERE workspace = $workspace AND contact = $duplicate AND campaign INSIDE \
+                    (SELECT VALUE campaign FROM campaign_recipient WHERE workspace = $workspace AND contact = $primary)",
+            )
+            .query(
+                "UPDATE campaign_recipient SET contact = $primary \
+                 WHERE workspace = $workspace AND contact = $duplicate",
+            )
+            .query("UPDATE short_link SET contact = $primary WHERE workspace = $workspace AND contact = $duplicate")
+            .query(
+                "CREATE timeline_entry CONTENT { \
+                    workspace: $workspace, contact: $primary, type: 'note', content: $note, \
+                    metadata: { merged_contact_id: $duplicate_id }, timestamp: time::now() }",
+            )
+            .query("DELETE $duplicate WHERE workspace = $workspace")
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("primary", primary))
+            .bind(("duplicate", Thing::from(("contact", duplicate_id))))
+            .bind(("duplicate_id", duplicate_id.to_string()))
+            .bind(("record", record))
+            .bind(("note", note.to_string()))
+            .commit()
+            .await?;
+
+        self.find_by_id_with_id(workspace_id, primary_id)
+            .await?
+            .ok_or_else(|| AppError::Internal("Failed to merge contacts".into()))
+    }
+
+    /// Load the listed contacts that exist in the workspace and aren't trashed
+    ///
+    /// IDs that don't match are simply missing from the result.
+    pub async fn find_many_with_ids(&self, workspace_id: &str, ids: &[String]) -> AppResult<Vec<StoredContact>> {
+        let things: Vec<Thing> = ids.iter().map(|id| Thing::from(("contact", id.as_str()))).collect();
+
+        let things = &things;
+
+        let records: Vec<ContactRecord> = self
+            .db
+            .retry(move || async move {
+                self.db
+                    .client
+                    .query("SELECT * FROM contact WHERE id INSIDE $ids AND workspace = $workspace AND deleted_at IS NONE")
+                    .bind(("ids", things.clone()))
+                    .bind(("workspace", workspace_thing(workspace_id)))
+                    .await?
+                    .take(0)
+            })
+            .await?;
+
+        Ok(records.into_iter().map(|r| self.to_stored(r)).collect())
+    }
+
+    /// Store several changed contacts, and their status changes, in one
+    /// transaction
+    ///
+    /// Either every contact is written or none is.
+    pub async fn update_many(
+        &self,
+        workspace_id: &str,
+        contacts: &[StoredContact],
+        status_changes: Vec<StatusHistoryEntry>,
+    ) -> AppResult<()> {
+        let mut tx = self
+            .db
+            .transaction()
+            .bind(("workspace", workspace_thing(workspace_id)));
+        if !status_changes.is_empty() {
+            tx = tx
+                .query("INSERT INTO status_history $status_changes")
+                .bind(("status_changes", status_changes));
+        }
+
+        for (i, stored) in contacts.iter().enumerate() {
+            let thing = Thing::from(("contact", stored.id.as_str()));
+            let mut record = self.to_record(workspace_id, &stored.contact);
+            record.id = Some(thing.clone());
+            tx = tx
+                .query(format!(
+                    "UPDATE $contact{i} CONTENT $record{i} WHERE workspace = $workspace AND deleted_at IS NONE"
+                ))
+                .bind((format!("contact{i}"), thing))
+                .bind((format!("record{i}"), record));
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_conditions_always_scope_workspace() {
+        let (conditions, bindings) = filter_conditions(&ContactQuery::new());
+
+        assert_eq!(conditions, vec!["workspace = $workspace", "deleted_at IS NONE"]);
+        assert!(bindings.is_empty());
+    }
+
+    #[test]
+    fn test_filter_conditions_bind_every_filter() {
+        let query = ContactQuery {
+            search: Some("ada".into()),
+            status: Some(DomainStatus::Customer),
+            tags_all: Some(vec!["vip".into(), "beta".into()]),
+            company_id: Some("acme".into()),
+            min_engagement: Some(20.0),
+            max_engagement: Some(80.0),
+            ..ContactQuery::new()
+        };
+
+        let (conditions, bindings) = filter_conditions(&query);
+        let names: Vec<&str> = bindings.iter().map(|(name, _)| *name).collect();
+
+        assert_eq!(conditions.len(), 8);
+        assert!(conditions.contains(&"tags CONTAINSALL $tags_all"));
+        assert_eq!(
+            names,
+            vec!["status", "search", "min_engagement", "max_engagement", "tags_all", "company_id"]
+        );
+        assert!(bindings.contains(&("tags_all", serde_json::json!(["vip", "beta"]))));
+    }
+
+    #[test]
+    fn test_order_by() {
+        assert_eq!(ContactQuery::new().order_by(), "ORDER BY created_at DESC, id ASC");
+        assert_eq!(
+            ContactQuery::new().with_sort(ContactSort::LastName, None).order_by(),
+            "ORDER BY last_name ASC, id ASC"
+        );
+        assert_eq!(
+            ContactQuery::new()
+                .with_sort(ContactSort::EngagementScore, Some(SortOrder::Asc))
+                .order_by(),
+            "ORDER BY engagement_score ASC, id ASC"
+        );
+    }
+
+    #[test]
+    fn test_empty_tag_list_is_no_filter() {
+        let query = ContactQuery {
+            tags_all: Some(Vec::new()),
+            tags_any: Some(Vec::new()),
+            exclude_tags: Some(Vec::new()),
+            ..ContactQuery::new()
+        };
+
+        assert_eq!(filter_conditions(&query).0.len(), 2);
+    }
+
+    #[test]
+    fn test_date_ranges_include_the_start_and_exclude_the_end() {
+        let week_start = "2024-06-03T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let query = ContactQuery {
+            created_after: Some(week_start),
+            created_before: Some(week_start + chrono::Duration::days(7)),
+            updated_before: Some(week_start),
+            ..ContactQuery::new()
+        };
+
+        let (conditions, bindings) = filter_conditions(&query);
+        assert_eq!(
+            conditions[2..],
+            [
+                "created_at >= type::datetime($created_after)",
+                "created_at < type::datetime($created_before)",
+                "updated_at < type::datetime($updated_before)"
+            ]
+        );
+        assert_eq!(bindings[0], ("created_after", serde_json::json!("2024-06-03T00:00:00Z")));
+        assert_eq!(bindings[1], ("created_before", serde_json::json!("2024-06-10T00:00:00Z")));
+    }
+
+    #[test]
+    fn test_place_filters() {
+        let query = ContactQuery {
+            country: Some("SE".into()),
+            city: Some(" Stockholm ".into()),
+            timezone: Some("Europe/Stockholm".into()),
+            ..ContactQuery::new()
+        };
+
+        let (conditions, bindings) = filter_conditions(&query);
+        assert_eq!(
+            conditions[2..],
+            [
+                "country = $country",
+                "string::lowercase(city ?? '') = string::lowercase($city)",
+                "timezone = $timezone"
+            ]
+        );
+        assert_eq!(bindings[1], ("city", serde_json::json!("Stockholm")));
+    }
+
+    #[test]
+    fn test_tag_filters_combine() {
+        let query = ContactQuery {
+            tags_all: Some(vec!["customer".into()]),
+            tags_any: Some(vec!["vip".into(), "beta".into()]),
+            exclude_tags: Some(vec!["churned".into()]),
+            ..ContactQuery::new()
+        };
+
+        let (conditions, bindings) = filter_conditions(&query);
+        assert_eq!(
+            conditions[2..],
+            [
+                "tags CONTAINSALL $tags_all",
+                "tags CONTAINSANY $tags_any",
+                "tags CONTAINSNONE $exclude_tags"
+            ]
+        );
+        assert_eq!(
+            bindings,
+            vec![
+                ("tags_all", serde_json::json!(["customer"])),
+                ("tags_any", serde_json::json!(["vip", "beta"])),
+                ("exclude_tags", serde_json::json!(["churned"])),
+            ]
+        );
+
+        let only_excluded = ContactQuery {
+            exclude_tags: Some(vec!["churned".into()]),
+            ..ContactQuery::new()
+        };
+        assert_eq!(filter_conditions(&only_excluded).0[2..], ["tags CONTAINSNONE $exclude_tags"]);
     }
 }
@@ -0,0 +1,98 @@
+//! Deal Repository - deals of the sales pipeline
+
+use crate::db::{workspace_thing, Database};
+use crate::error::{AppError, AppResult};
+use crate::models::{Deal, DealQuery};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Repository for Deal database operations
+pub struct DealRepository {
+    db: Arc<Database>,
+}
+
+impl DealRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Deals matching the query's filters, newest first
+    ///
+    /// `limit` is taken as given; the query's own limit is ignored.
+    pub async fn find(&self, workspace_id: &str, query: &DealQuery, limit: u32) -> AppResult<Vec<Deal>> {
+        let mut conditions = vec!["workspace = $workspace"];
+        if query.stage.is_some() {
+            conditions.push("stage = $stage");
+        }
+        if query.contact_id.is_some() {
+            conditions.push("contact = $contact");
+        }
+        if query.company_id.is_some() {
+            conditions.push("company = $company");
+        }
+
+        let query_str = format!(
+            "SELECT * FROM deal WHERE {} ORDER BY created_at DESC LIMIT $limit START $offset",
+            conditions.join(" AND ")
+        );
+
+        let query_str = query_str.as_str();
+
+        let deals: Vec<Deal> = self
+            .db
+            .retry(move || async move {
+                self.db
+                    .client
+                    .query(query_str)
+                    .bind(("workspace", workspace_thing(workspace_id)))
+                    .bind(("stage", query.stage))
+                    .bind(("contact", query.contact_id.as_deref().map(|id| Thing::from(("contact", id)))))
+                    .bind(("company", query.company_id.as_deref().map(|id| Thing::from(("company", id)))))
+                    .bind(("limit", limit))
+                    .bind(("offset", query.offset.unwrap_or(0)))
+                    .await?
+                    .take(0)
+            })
+            .await?;
+
+        Ok(deals)
+    }
+
+    pub async fn find_by_id(&self, workspace_id: &str, id: &str) -> AppResult<Option<Deal>> {
+        Ok(self.db.select_scoped("deal", id, workspace_id).await?)
+    }
+
+    pub async fn create(&self, deal: Deal) -> AppResult<Deal> {
+        let created: Vec<Deal> = self.db.client.create("deal").content(deal).await?;
+        created
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::Internal("Failed to create deal".into()))
+    }
+
+    /// Replace a deal of the workspace; `None` when it is gone
+    pub async fn update(&self, workspace_id: &str, id: &str, deal: Deal) -> AppResult<Option<Deal>> {
+        let updated: Vec<Deal> = self
+            .db
+            .client
+            .query("UPDATE $deal CONTENT $content WHERE workspace = $workspace")
+            .bind(("deal", Thing::from(("deal", id))))
+            .bind(("content", deal))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(updated.into_iter().next())
+    }
+
+    /// Returns whether the deal was found and deleted
+    pub async fn delete(&self, workspace_id: &str, id: &str) -> AppResult<bool> {
+        Ok(self.db.delete_scoped("deal", id, workspace_id).await?)
+    }
+
+    /// Whether a live contact or company of the workspace exists, for linking
+    pub async fn linkable_exists(&self, workspace_id: &str, table: &str, id: &str) -> AppResult<bool> {
+        let record: Option<serde_json::Value> = self.db.select_scoped(table, id, workspace_id).await?;
+        Ok(record.is_some())
+    }
+}
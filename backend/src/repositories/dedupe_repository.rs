@@ -0,0 +1,244 @@
+//! Dedupe Repository - contact embeddings and the duplicate review queue
+//!
+//! Embeddings live at `contact_embedding:<contact id>`. Suggestions are
+//! unique per pair of contacts, whatever their status, so a pair is only
+//! ever suggested once. A pending suggestion whose contacts are gone or
+//! trashed is stale: it is left out of the queue and cleared on the next run.
+
+use crate::db::{workspace_thing, Database};
+use crate::domain::DedupeStatus;
+use crate::error::AppResult;
+use crate::models::DedupeSuggestion;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// A contact's stored embedding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContactEmbedding {
+    pub id: Option<Thing>,
+    pub workspace: Thing,
+    pub contact: Thing,
+    /// What was embedded
+    pub text: String,
+    pub model: String,
+    pub vector: Vec<f32>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompanyName {
+    id: Thing,
+    name: String,
+}
+
+/// Pending suggestions whose contacts are both still there
+const LIVE_PAIR: &str = "contact.id IS NOT NONE AND duplicate.id IS NOT NONE \
+     AND contact.deleted_at IS NONE AND duplicate.deleted_at IS NONE";
+
+/// Repository for contact embeddings and dedupe suggestions
+pub struct DedupeRepository {
+    db: Arc<Database>,
+}
+
+impl DedupeRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Every workspace; used by the background job, which is not tied to one
+    pub async fn find_workspace_ids(&self) -> AppResult<Vec<String>> {
+        let ids: Vec<Thing> = self.db.client.query("SELECT VALUE id FROM workspace").await?.take(0)?;
+        Ok(ids.into_iter().map(|id| id.id.to_raw()).collect())
+    }
+
+    /// Company names by company ID
+    pub async fn find_company_names(&self, workspace_id: &str) -> AppResult<HashMap<String, String>> {
+        let companies: Vec<CompanyName> = self
+            .db
+            .client
+            .query("SELECT id, name FROM company WHERE workspace = $workspace AND deleted_at IS NONE")
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(companies.into_iter().map(|c| (c.id.id.to_string(), c.name)).collect())
+    }
+
+    pub async fn find_embeddings(&self, workspace_id: &str) -> AppResult<Vec<ContactEmbedding>> {
+        let embeddings: Vec<ContactEmbedding> = self
+            .db
+            .client
+            .query("SELECT * FROM contact_embedding WHERE workspace = $workspace")
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(embeddings)
+    }
+
+    /// Replace the embeddings of the given contacts
+    pub async fn save_embeddings(&self, workspace_id: &str, embeddings: Vec<ContactEmbedding>) -> AppResult<()> {
+        if embeddings.is_empty() {
+            return Ok(());
+        }
+
+        let ids: Vec<Thing> = embeddings.iter().filter_map(|e| e.id.clone()).collect();
+        self.db
+            .transaction()
+            .query("DELETE contact_embedding WHERE workspace = $workspace AND id INSIDE $ids")
+            .query("INSERT INTO contact_embedding $embeddings")
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("ids", ids))
+            .bind(("embeddings", embeddings))
+            .commit()
+            .await?;
+
+        Ok(())
+    }
+
+    /// Drop the embeddings of contacts that are gone or no longer compared
+    pub async fn delete_embeddings_except(&self, workspace_id: &str, keep: &[Thing]) -> AppResult<()> {
+        self.db
+            .client
+            .query("DELETE contact_embedding WHERE workspace = $workspace AND contact NOTINSIDE $keep")
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("keep", keep.to_vec()))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Every pair already suggested, whatever its status, as (contact, duplicate) IDs
+    pub async fn find_suggested_pairs(&self, workspace_id: &str) -> AppResult<HashSet<(String, String)>> {
+        #[derive(Deserialize)]
+        struct Pair {
+            contact: Thing,
+            duplicate: Thing,
+        }
+
+        let pairs: Vec<Pair> = self
+            .db
+            .client
+            .query("SELECT contact, duplicate FROM dedupe_suggestion WHERE workspace = $workspace")
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(pairs
+            .into_iter()
+            .map(|p| (p.contact.id.to_raw(), p.duplicate.id.to_raw()))
+            .collect())
+    }
+
+    pub async fn insert_suggestions(&self, suggestions: Vec<DedupeSuggestion>) -> AppResult<()> {
+        if suggestions.is_empty() {
+            return Ok(());
+        }
+
+        self.db
+            .client
+            .query("INSERT INTO dedupe_suggestion $suggestions")
+            .bind(("suggestions", suggestions))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Suggestions with the given status, most certain first
+    pub async fn find_suggestions(
+        &self,
+        workspace_id: &str,
+        status: DedupeStatus,
+        limit: u32,
+    ) -> AppResult<Vec<DedupeSuggestion>> {
+        // Stale pairs would otherwise take up the page ahead of ones that can be merged
+        let live = match status {
+            DedupeStatus::Pending => format!("AND {}", LIVE_PAIR),
+            _ => String::new(),
+        };
+        let suggestions: Vec<DedupeSuggestion> = self
+            .db
+            .client
+            .query(format!(
+                "SELECT * FROM dedupe_suggestion WHERE workspace = $workspace AND status = $status {} \
+                 ORDER BY confidence DESC, created_at DESC LIMIT $limit",
+                live
+            ))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("status", status))
+            .bind(("limit", limit))
+            .await?
+            .take(0)?;
+
+        Ok(suggestions)
+    }
+
+    pub async fn find_suggestion(&self, workspace_id: &str, id: &str) -> AppResult<Option<DedupeSuggestion>> {
+        Ok(self.db.select_scoped("dedupe_suggestion", id, workspace_id).await?)
+    }
+
+    /// Accept or dismiss a pending suggestion
+    ///
+    /// `None` when it isn't pending any more, e.g. another reviewer got to it first.
+    pub async fn resolve(
+        &self,
+        workspace_id: &str,
+        id: &str,
+        status: DedupeStatus,
+        user_id: &str,
+    ) -> AppResult<Option<DedupeSuggestion>> {
+        let resolved: Option<DedupeSuggestion> = self
+            .db
+            .client
+            .query(
+                "UPDATE $suggestion SET status = $status, resolved_at = time::now(), resolved_by = $user \
+                 WHERE workspace = $workspace AND status = 'pending'",
+            )
+            .bind(("suggestion", Thing::from(("dedupe_suggestion", id))))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("status", status))
+            .bind(("user", Thing::from(("user", user_id))))
+            .await?
+            .take(0)?;
+
+        Ok(resolved)
+    }
+
+    /// Put a suggestion resolved by mistake back in the queue
+    pub async fn reopen(&self, workspace_id: &str, id: &str) -> AppResult<()> {
+        self.db
+            .client
+            .query(
+                "UPDATE $suggestion SET status = 'pending', resolved_at = NONE, resolved_by = NONE \
+                 WHERE workspace = $workspace",
+            )
+            .bind(("suggestion", Thing::from(("dedupe_suggestion", id))))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Drop pending suggestions whose contacts are gone or trashed
+    ///
+    /// A restored contact's pairs are then suggested afresh.
+    pub async fn delete_stale(&self, workspace_id: &str) -> AppResult<()> {
+        self.db
+            .client
+            .query(format!(
+                "DELETE dedupe_suggestion WHERE workspace = $workspace AND status = 'pending' AND !({})",
+                LIVE_PAIR
+            ))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+}
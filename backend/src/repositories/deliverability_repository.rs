@@ -0,0 +1,151 @@
+//! Deliverability Repository - applying bounce and complaint reports to contacts and campaigns
+
+use crate::db::{workspace_thing, Database};
+use crate::domain::SubscriptionStatus;
+use crate::error::AppResult;
+use crate::models::TimelineEntry;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Most contacts one report's address is looked up as, across workspaces
+const MAX_CONTACTS_PER_ADDRESS: u32 = 100;
+
+/// A contact a reported address belongs to
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReportedContact {
+    pub id: Thing,
+    pub workspace: Thing,
+    pub company: Option<Thing>,
+}
+
+/// Repository for bounce and complaint database operations
+pub struct DeliverabilityRepository {
+    db: Arc<Database>,
+}
+
+impl DeliverabilityRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Contacts with this address, trashed ones included
+    ///
+    /// In one workspace, or in every workspace when none is given.
+    pub async fn find_contacts(&self, workspace_id: Option<&str>, email: &str) -> AppResult<Vec<ReportedContact>> {
+        let workspace_clause = if workspace_id.is_some() {
+            "AND workspace = $workspace"
+        } else {
+            ""
+        };
+        let contacts: Vec<ReportedContact> = self
+            .db
+            .client
+            .query(format!(
+                "SELECT id, workspace, company FROM contact WHERE email = $email {} LIMIT $limit",
+                workspace_clause
+            ))
+            .bind(("workspace", workspace_id.map(workspace_thing)))
+            .bind(("email", email.to_string()))
+            .bind(("limit", MAX_CONTACTS_PER_ADDRESS))
+            .await?
+            .take(0)?;
+
+        Ok(contacts)
+    }
+
+    /// The campaign last sent to a contact between `since` and `before`
+    pub async fn last_campaign(
+        &self,
+        contact: &ReportedContact,
+        since: DateTime<Utc>,
+        before: DateTime<Utc>,
+    ) -> AppResult<Option<Thing>> {
+        let campaign: Option<Thing> = self
+            .db
+            .client
+            .query(
+                "SELECT VALUE campaign FROM campaign_recipient \
+                 WHERE workspace = $workspace AND contact = $contact \
+                    AND status INSIDE ['sent', 'replied', 'bounced'] AND sent_at >= $since AND sent_at <= $before \
+                 ORDER BY sent_at DESC LIMIT 1",
+            )
+            .bind(("workspace", contact.workspace.clone()))
+            .bind(("contact", contact.id.clone()))
+            .bind(("since", since))
+            .bind(("before", before))
+            .await?
+            .take(0)?;
+
+        Ok(campaign)
+    }
+
+    /// Whether a report like this entry's is already on the contact's timeline
+    ///
+    /// Providers retry callbacks, so the same report can arrive more than once.
+    pub async fn is_recorded(&self, entry: &TimelineEntry) -> AppResult<bool> {
+        let existing: Option<Thing> = self
+            .db
+            .client
+            .query(
+                "SELECT VALUE id FROM timeline_entry \
+                 WHERE workspace = $workspace AND contact = $contact AND type = $type AND timestamp = $timestamp \
+                 LIMIT 1",
+            )
+            .bind(("workspace", entry.workspace.clone()))
+            .bind(("contact", entry.contact.clone()))
+            .bind(("type", entry.entry_type.clone()))
+            .bind(("timestamp", entry.timestamp))
+            .await?
+            .take(0)?;
+
+        Ok(existing.is_some())
+    }
+
+    /// Store a report's timeline entry and act on it
+    ///
+    /// With a `suppression`, a subscribed contact takes that status and is
+    /// dropped from every campaign still pending for them. A hard bounce
+    /// also marks the recipient of the entry's campaign as bounced. All
+    /// happen in one transaction.
+    pub async fn record(
+        &self,
+        entry: TimelineEntry,
+        suppression: Option<SubscriptionStatus>,
+        hard_bounce: bool,
+    ) -> AppResult<()> {
+        let mut tx = self
+            .db
+            .transaction()
+            .query("INSERT INTO timeline_entry $entry")
+            .bind(("workspace", entry.workspace.clone()))
+            .bind(("contact", entry.contact.clone()))
+            .bind(("campaign", entry.campaign.clone()))
+            .bind(("now", Utc::now()))
+            .bind(("entry", entry));
+
+        if let Some(status) = suppression {
+            tx = tx
+                .query(
+                    "UPDATE $contact SET subscription_status = $suppression, updated_at = $now \
+                     WHERE workspace = $workspace AND subscription_status = 'subscribed'",
+                )
+                .query(
+                    "UPDATE campaign_recipient SET status = 'unsubscribed', updated_at = $now \
+                     WHERE workspace = $workspace AND contact = $contact AND status = 'pending'",
+                )
+                .bind(("suppression", status));
+        }
+        if hard_bounce {
+            tx = tx.query(
+                "UPDATE campaign_recipient SET status = 'bounced', updated_at = $now \
+                 WHERE workspace = $workspace AND campaign = $campaign AND contact = $contact \
+                    AND status INSIDE ['sent', 'replied']",
+            );
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
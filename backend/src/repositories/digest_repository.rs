@@ -0,0 +1,243 @@
+//! Digest Repository - digest settings and what goes into a digest
+//!
+//! Settings live at `digest_settings:<user id>`, so saving them is an
+//! UPDATE that creates the record the first time. The content queries only
+//! return raw rows; picking and ordering entries is the DigestService's job.
+
+use crate::db::{workspace_thing, Database};
+use crate::domain::DigestPreferences;
+use crate::error::{AppError, AppResult};
+use crate::models::{DigestSettings, TimelineEntryType};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// A workspace member who may get a digest
+#[derive(Debug, Clone, Deserialize)]
+pub struct DigestRecipient {
+    pub id: Thing,
+    pub workspace: Thing,
+    pub email: String,
+    pub name: String,
+}
+
+/// The contact fields a digest lists
+#[derive(Debug, Clone, Deserialize)]
+pub struct DigestContactRow {
+    pub id: Thing,
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+    pub engagement_score: f64,
+    pub created_at: DateTime<Utc>,
+    /// Latest timeline entry; only filled in by `find_lead_activity`
+    #[serde(default)]
+    pub last_activity: Option<DateTime<Utc>>,
+}
+
+/// Timeline entries of one type for one contact
+#[derive(Debug, Clone, Deserialize)]
+pub struct InteractionCount {
+    pub contact: Thing,
+    #[serde(rename = "type")]
+    pub entry_type: TimelineEntryType,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DigestEventRow {
+    pub name: String,
+    pub start_time: DateTime<Utc>,
+    pub registered: u64,
+}
+
+/// Repository for digest settings and content
+pub struct DigestRepository {
+    db: Arc<Database>,
+}
+
+impl DigestRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    pub async fn find_settings(&self, user_id: &str) -> AppResult<Option<DigestSettings>> {
+        let settings: Option<DigestSettings> = self.db.client.select(("digest_settings", user_id)).await?;
+        Ok(settings)
+    }
+
+    pub async fn save_preferences(
+        &self,
+        workspace_id: &str,
+        user_id: &str,
+        preferences: &DigestPreferences,
+    ) -> AppResult<DigestSettings> {
+        let saved: Option<DigestSettings> = self
+            .db
+            .client
+            .query(
+                "UPDATE $settings SET workspace = $workspace, user = $user, \
+                    preferences = $preferences, updated_at = time::now()",
+            )
+            .bind(("settings", Thing::from(("digest_settings", user_id))))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("user", Thing::from(("user", user_id))))
+            .bind(("preferences", preferences.clone()))
+            .await?
+            .take(0)?;
+
+        saved.ok_or_else(|| AppError::Internal("Failed to save digest preferences".into()))
+    }
+
+    /// Record a sent digest; members still on the defaults get a settings record here
+    pub async fn mark_sent(&self, recipient: &DigestRecipient, sent_at: DateTime<Utc>) -> AppResult<()> {
+        self.db
+            .client
+            .query("UPDATE $settings SET workspace = $workspace, user = $user, last_sent_at = $sent_at")
+            .bind(("settings", Thing::from(("digest_settings", recipient.id.id.to_raw().as_str()))))
+            .bind(("workspace", recipient.workspace.clone()))
+            .bind(("user", recipient.id.clone()))
+            .bind(("sent_at", sent_at))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Every member of every workspace, with their settings if they saved any
+    ///
+    /// Used by the background job, which is not tied to a workspace.
+    pub async fn find_recipients(&self) -> AppResult<Vec<(DigestRecipient, Option<DigestSettings>)>> {
+        let mut response = self
+            .db
+            .client
+            .query("SELECT id, workspace, email, name FROM user")
+            .query("SELECT * FROM digest_settings")
+            .await?;
+
+        let recipients: Vec<DigestRecipient> = response.take(0)?;
+        let settings: Vec<DigestSettings> = response.take(1)?;
+
+        let mut by_user: HashMap<String, DigestSettings> =
+            settings.into_iter().map(|s| (s.user.id.to_raw(), s)).collect();
+        Ok(recipients
+            .into_iter()
+            .map(|recipient| {
+                let settings = by_user.remove(&recipient.id.id.to_raw());
+                (recipient, settings)
+            })
+            .collect())
+    }
+
+    /// Leads created since `since`: how many, and the newest `limit`
+    pub async fn find_new_leads(
+        &self,
+        workspace_id: &str,
+        since: DateTime<Utc>,
+        limit: u32,
+    ) -> AppResult<(u64, Vec<DigestContactRow>)> {
+        const NEW_LEADS: &str =
+            "workspace = $workspace AND deleted_at IS NONE AND status = 'lead' AND created_at >= $since";
+
+        let mut response = self
+            .db
+            .client
+            .query(format!("RETURN array::len((SELECT VALUE id FROM contact WHERE {NEW_LEADS}))"))
+            .query(format!("SELECT * FROM contact WHERE {NEW_LEADS} ORDER BY created_at DESC LIMIT $limit"))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("since", since))
+            .bind(("limit", limit))
+            .await?;
+
+        let total: Option<u64> = response.take(0)?;
+        Ok((total.unwrap_or_default(), response.take(1)?))
+    }
+
+    /// Timeline entries since `since`, counted per contact and type
+    pub async fn find_interaction_counts(&self, workspace_id: &str, since: DateTime<Utc>) -> AppResult<Vec<InteractionCount>> {
+        let counts: Vec<InteractionCount> = self
+            .db
+            .client
+            .query(
+                "SELECT contact, type, count() AS count FROM timeline_entry \
+                 WHERE workspace = $workspace AND timestamp >= $since GROUP BY contact, type",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("since", since))
+            .await?
+            .take(0)?;
+
+        Ok(counts)
+    }
+
+    /// The given contacts, leaving out trashed ones
+    pub async fn find_contacts(&self, workspace_id: &str, ids: &[Thing]) -> AppResult<Vec<DigestContactRow>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let contacts: Vec<DigestContactRow> = self
+            .db
+            .client
+            .query("SELECT * FROM contact WHERE id IN $ids AND workspace = $workspace AND deleted_at IS NONE")
+            .bind(("ids", ids.to_vec()))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(contacts)
+    }
+
+    /// Leads created before `created_before`, each with its latest timeline entry
+    pub async fn find_lead_activity(
+        &self,
+        workspace_id: &str,
+        created_before: DateTime<Utc>,
+    ) -> AppResult<Vec<DigestContactRow>> {
+        let leads: Vec<DigestContactRow> = self
+            .db
+            .client
+            .query(
+                "SELECT *, (SELECT VALUE timestamp FROM timeline_entry WHERE contact = $parent.id \
+                    ORDER BY timestamp DESC LIMIT 1)[0] AS last_activity FROM contact \
+                 WHERE workspace = $workspace AND deleted_at IS NONE AND status = 'lead' \
+                    AND created_at < $created_before",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("created_before", created_before))
+            .await?
+            .take(0)?;
+
+        Ok(leads)
+    }
+
+    /// Events starting in `[from, until)`, soonest first, with their registrations
+    pub async fn find_upcoming_events(
+        &self,
+        workspace_id: &str,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+        limit: u32,
+    ) -> AppResult<Vec<DigestEventRow>> {
+        let events: Vec<DigestEventRow> = self
+            .db
+            .client
+            .query(
+                "SELECT name, start_time, \
+                    array::len((SELECT VALUE id FROM rsvp WHERE event = $parent.id AND status = 'registered')) \
+                    AS registered FROM event \
+                 WHERE workspace = $workspace AND start_time >= $from AND start_time < $until \
+                 ORDER BY start_time ASC LIMIT $limit",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("from", from))
+            .bind(("until", until))
+            .bind(("limit", limit))
+            .await?
+            .take(0)?;
+
+        Ok(events)
+    }
+}
@@ -0,0 +1,112 @@
+//! Engagement Repository - timeline activity in, engagement scores out
+
+use crate::db::{workspace_thing, Database};
+use crate::error::AppResult;
+use crate::models::TimelineEntryType;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// A timeline entry reduced to what scoring needs
+#[derive(Debug, Clone, Deserialize)]
+pub struct TimelineActivity {
+    pub contact: Thing,
+    #[serde(rename = "type")]
+    pub entry_type: TimelineEntryType,
+    pub timestamp: DateTime<Utc>,
+    /// Only the keys that weight a call: `duration_minutes` and `outcome`
+    #[serde(default)]
+    pub metadata: serde_json::Value,
+}
+
+/// A contact's current score
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContactScore {
+    pub id: Thing,
+    pub engagement_score: f64,
+}
+
+/// Repository for engagement score reads and writes
+pub struct EngagementRepository {
+    db: Arc<Database>,
+}
+
+impl EngagementRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    pub async fn find_score(&self, workspace_id: &str, contact_id: &str) -> AppResult<Option<ContactScore>> {
+        let mut scores: Vec<ContactScore> = self
+            .db
+            .client
+            .query("SELECT id, engagement_score FROM $contact WHERE workspace = $workspace AND deleted_at IS NONE")
+            .bind(("contact", Thing::from(("contact", contact_id))))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(scores.pop())
+    }
+
+    /// Page through contacts of every workspace, ordered by ID
+    ///
+    /// Used by the background job, which is not tied to a workspace.
+    pub async fn find_scores_after(&self, after: Option<&Thing>, limit: u32) -> AppResult<Vec<ContactScore>> {
+        let query_str = if after.is_some() {
+            "SELECT id, engagement_score FROM contact \
+             WHERE id > $after AND deleted_at IS NONE ORDER BY id ASC LIMIT $limit"
+        } else {
+            "SELECT id, engagement_score FROM contact WHERE deleted_at IS NONE ORDER BY id ASC LIMIT $limit"
+        };
+
+        let scores: Vec<ContactScore> = self
+            .db
+            .client
+            .query(query_str)
+            .bind(("after", after.cloned()))
+            .bind(("limit", limit))
+            .await?
+            .take(0)?;
+
+        Ok(scores)
+    }
+
+    /// All timeline activity for the given contacts
+    pub async fn find_activity(&self, contacts: &[Thing]) -> AppResult<Vec<TimelineActivity>> {
+        if contacts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let activity: Vec<TimelineActivity> = self
+            .db
+            .client
+            .query(
+                "SELECT contact, type, timestamp, metadata.duration_minutes, metadata.outcome, metadata.import_source \
+                 FROM timeline_entry \
+                 WHERE contact INSIDE $contacts",
+            )
+            .bind(("contacts", contacts.to_vec()))
+            .await?
+            .take(0)?;
+
+        Ok(activity)
+    }
+
+    /// Store a recomputed score
+    ///
+    /// `updated_at` is left alone: the score is derived data, and a
+    /// nightly recompute shouldn't make every contact look recently edited.
+    pub async fn update_score(&self, contact: &Thing, score: f64) -> AppResult<()> {
+        self.db
+            .client
+            .query("UPDATE $contact SET engagement_score = $score")
+            .bind(("contact", contact.clone()))
+            .bind(("score", score))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+//! Enrichment Repository - what earlier enrichments found, and the companies they link to
+
+use crate::db::{workspace_thing, Database};
+use crate::domain::EnrichedProfile;
+use crate::error::{AppError, AppResult};
+use crate::models::Company;
+use chrono::Utc;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Repository for the data contact enrichment reads and writes besides the contact
+pub struct EnrichmentRepository {
+    db: Arc<Database>,
+}
+
+impl EnrichmentRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// What the last enrichment of a contact found, if it was ever enriched
+    pub async fn last_profile(&self, contact: &Thing) -> AppResult<Option<EnrichedProfile>> {
+        let profiles: Vec<EnrichedProfile> = self
+            .db
+            .client
+            .query(
+                "SELECT VALUE metadata.profile FROM timeline_entry \
+                 WHERE contact = $contact AND type = 'enrichment' \
+                 ORDER BY timestamp DESC LIMIT 1",
+            )
+            .bind(("contact", contact.clone()))
+            .await?
+            .take(0)?;
+
+        Ok(profiles.into_iter().next())
+    }
+
+    pub async fn company_name(&self, workspace_id: &str, company_id: &str) -> AppResult<Option<String>> {
+        let company: Option<Company> = self.db.select_scoped("company", company_id, workspace_id).await?;
+        Ok(company.map(|c| c.name))
+    }
+
+    /// ID of the workspace's company with this name, creating it if there is none
+    ///
+    /// Names are matched case-insensitively. Returns whether it was created.
+    pub async fn find_or_create_company(&self, workspace_id: &str, name: &str) -> AppResult<(String, bool)> {
+        let existing: Vec<Thing> = self
+            .db
+            .client
+            .query(
+                "SELECT VALUE id FROM company \
+                 WHERE workspace = $workspace AND string::lowercase(name) = $name AND deleted_at IS NONE \
+                 LIMIT 1",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("name", name.to_lowercase()))
+            .await?
+            .take(0)?;
+        if let Some(id) = existing.into_iter().next() {
+            return Ok((id.id.to_raw(), false));
+        }
+
+        let now = Utc::now();
+        let created: Vec<Company> = self
+            .db
+            .client
+            .create("company")
+            .content(Company {
+                id: None,
+                workspace: workspace_thing(workspace_id),
+                name: name.to_string(),
+                domain: None,
+                industry: None,
+                size: None,
+                tags: Vec::new(),
+                country: None,
+                region: None,
+                city: None,
+                timezone: None,
+                created_at: now,
+                updated_at: now,
+            })
+            .await?;
+
+        created
+            .into_iter()
+            .next()
+            .and_then(|c| c.id)
+            .map(|id| (id.id.to_raw(), true))
+            .ok_or_else(|| AppError::Internal("Failed to create company".into()))
+    }
+}
@@ -0,0 +1,239 @@
+//! Event Repository - events and the RSVPs of their contacts
+
+use crate::db::{is_unique_violation, is_write_conflict, Database};
+use crate::domain::EventReminders;
+use crate::error::AppResult;
+use crate::models::{Event, Rsvp, RsvpStatus, TimelineEntry};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// What the seat check of `save_rsvp` throws when the event filled up first
+const EVENT_FULL: &str = "The event has no seats left";
+
+/// An RSVP to an event that is coming up or just over, joined with what its
+/// reminder and follow-up emails need
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct ReminderCandidate {
+    pub id: Thing,
+    pub workspace: Thing,
+    pub event: Thing,
+    pub contact: Thing,
+    pub status: RsvpStatus,
+    #[serde(default)]
+    pub emails_sent: Vec<String>,
+    pub event_name: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    /// `None` for events created before reminders were configurable
+    pub reminders: Option<EventReminders>,
+    /// `None` when the contact has been deleted
+    pub email: Option<String>,
+}
+
+/// Repository for Event and Rsvp database operations
+pub struct EventRepository {
+    db: Arc<Database>,
+}
+
+impl EventRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    pub async fn find_by_id(&self, workspace_id: &str, id: &str) -> AppResult<Option<Event>> {
+        Ok(self.db.select_scoped("event", id, workspace_id).await?)
+    }
+
+    pub async fn find_rsvp(&self, event: &Thing, contact: &Thing) -> AppResult<Option<Rsvp>> {
+        let rsvps: Vec<Rsvp> = self
+            .db
+            .client
+            .query("SELECT * FROM rsvp WHERE event = $event AND contact = $contact LIMIT 1")
+            .bind(("event", event.clone()))
+            .bind(("contact", contact.clone()))
+            .await?
+            .take(0)?;
+
+        Ok(rsvps.into_iter().next())
+    }
+
+    /// Seats on an event held by contacts other than `except`
+    pub async fn seats_taken(&self, event: &Thing, except: &Thing) -> AppResult<u64> {
+        let count: Option<u64> = self
+            .db
+            .client
+            .query(
+                "RETURN array::len((SELECT VALUE id FROM rsvp \
+                    WHERE event = $event AND contact != $except AND status INSIDE ['registered', 'attended']))",
+            )
+            .bind(("event", event.clone()))
+            .bind(("except", except.clone()))
+            .await?
+            .take(0)?;
+
+        Ok(count.unwrap_or(0))
+    }
+
+    /// Waitlisted RSVPs, longest waiting first; all of them without a limit
+    pub async fn waitlist(&self, event: &Thing, limit: Option<u64>) -> AppResult<Vec<Rsvp>> {
+        let rsvps: Vec<Rsvp> = self
+            .db
+            .client
+            .query(
+                "SELECT * FROM rsvp WHERE event = $event AND status = 'waitlisted' \
+                 ORDER BY timestamp ASC LIMIT $limit",
+            )
+            .bind(("event", event.clone()))
+            .bind(("limit", limit.unwrap_or(u32::MAX as u64)))
+            .await?
+            .take(0)?;
+
+        Ok(rsvps)
+    }
+
+    /// Which of `contacts` already have an RSVP to the event
+    pub async fn rsvp_contacts(&self, event: &Thing, contacts: &[Thing]) -> AppResult<Vec<Thing>> {
+        let invited: Vec<Thing> = self
+            .db
+            .client
+            .query("SELECT VALUE contact FROM rsvp WHERE event = $event AND contact INSIDE $contacts")
+            .bind(("event", event.clone()))
+            .bind(("contacts", contacts.to_vec()))
+            .await?
+            .take(0)?;
+
+        Ok(invited)
+    }
+
+    /// Store new invitations and their timeline entries, all or nothing
+    ///
+    /// Returns false when another request gave one of the contacts an RSVP
+    /// first.
+    pub async fn create_invites(&self, rsvps: Vec<Rsvp>, entries: Vec<TimelineEntry>) -> AppResult<bool> {
+        let committed = self
+            .db
+            .transaction()
+            .query("INSERT INTO rsvp $rsvps")
+            .query("INSERT INTO timeline_entry $entries")
+            .bind(("rsvps", rsvps))
+            .bind(("entries", entries))
+            .commit()
+            .await;
+
+        match committed {
+            Ok(()) => Ok(true),
+            Err(e) if is_unique_violation(&e) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Store an RSVP change, the waitlisted RSVPs it promotes and their
+    /// timeline entries, all or nothing
+    ///
+    /// With a `capacity`, the change only goes through if the seats it
+    /// takes are still free when it is written. Every such change also
+    /// touches the event, so two of them racing for the last seats conflict
+    /// instead of both counting the same free seats. Returns false when the
+    /// seats were taken first; the caller decides again.
+    pub async fn save_rsvp(
+        &self,
+        rsvp: &Rsvp,
+        created: bool,
+        promoted: &[Thing],
+        entries: Vec<TimelineEntry>,
+        capacity: Option<u32>,
+        now: DateTime<Utc>,
+    ) -> AppResult<bool> {
+        let statement = if created {
+            "CREATE $rsvp_id CONTENT $rsvp"
+        } else {
+            "UPDATE $rsvp_id SET status = $status, timestamp = $timestamp"
+        };
+
+        let mut tx = self.db.transaction();
+        if let Some(capacity) = capacity {
+            // First, so a full event is the error the commit reports
+            let seats = promoted.len() + usize::from(rsvp.status.takes_seat());
+            tx = tx
+                .query(
+                    "IF array::len((SELECT VALUE id FROM rsvp \
+                        WHERE event = $event AND contact != $contact AND id NOTINSIDE $promoted \
+                            AND status INSIDE ['registered', 'attended'])) + $seats > $capacity \
+                     { THROW $event_full }",
+                )
+                .query("UPDATE $event SET seats_changed_at = $now")
+                .bind(("event", rsvp.event.clone()))
+                .bind(("contact", rsvp.contact.clone()))
+                .bind(("seats", seats))
+                .bind(("capacity", capacity))
+                .bind(("event_full", EVENT_FULL));
+        }
+        tx = tx
+            .query(statement)
+            .bind(("rsvp_id", rsvp.id.clone()))
+            .bind(("rsvp", rsvp.clone()))
+            .bind(("status", rsvp.status))
+            .bind(("timestamp", rsvp.timestamp))
+            .bind(("promoted", promoted.to_vec()))
+            .bind(("now", now));
+        if !promoted.is_empty() {
+            tx = tx.query("UPDATE $promoted SET status = 'registered', timestamp = $now");
+        }
+        if !entries.is_empty() {
+            tx = tx.query("INSERT INTO timeline_entry $entries").bind(("entries", entries));
+        }
+
+        match tx.commit().await {
+            Ok(()) => Ok(true),
+            Err(e) if capacity.is_some() && (e.to_string().contains(EVENT_FULL) || is_write_conflict(&e)) => {
+                Ok(false)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// RSVPs of events starting before `horizon` or that ended after `ended_since`
+    pub async fn find_reminder_candidates(
+        &self,
+        now: DateTime<Utc>,
+        horizon: DateTime<Utc>,
+        ended_since: DateTime<Utc>,
+    ) -> AppResult<Vec<ReminderCandidate>> {
+        let candidates: Vec<ReminderCandidate> = self
+            .db
+            .client
+            .query(
+                "SELECT id, workspace, event, contact, status, emails_sent, \
+                    event.name AS event_name, event.start_time AS start_time, \
+                    event.end_time AS end_time, event.reminders AS reminders, contact.email AS email \
+                 FROM rsvp \
+                 WHERE status INSIDE ['registered', 'attended', 'no_show'] AND contact.deleted_at IS NONE \
+                    AND ((event.start_time > $now AND event.start_time <= $horizon) \
+                        OR (event.end_time <= $now AND event.end_time > $ended_since))",
+            )
+            .bind(("now", now))
+            .bind(("horizon", horizon))
+            .bind(("ended_since", ended_since))
+            .await?
+            .take(0)?;
+
+        Ok(candidates)
+    }
+
+    /// Remember the emails an RSVP was sent, with the timeline entry of the send
+    pub async fn record_email(&self, rsvp: &Thing, keys: Vec<String>, entry: &TimelineEntry) -> AppResult<()> {
+        self.db
+            .transaction()
+            .query("UPDATE $rsvp SET emails_sent = array::union(emails_sent ?? [], $keys)")
+            .query("CREATE $entry_id CONTENT $entry")
+            .bind(("rsvp", rsvp.clone()))
+            .bind(("keys", keys))
+            .bind(("entry_id", entry.id.clone()))
+            .bind(("entry", entry.clone()))
+            .commit()
+            .await?;
+
+        Ok(())
+    }
+}
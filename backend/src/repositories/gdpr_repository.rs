@@ -0,0 +1,254 @@
+//! GDPR Repository - everything stored about one contact, and its erasure
+//!
+//! Unlike the other repositories this one also sees trashed contacts: they
+//! hold personal data until they are purged.
+
+use crate::db::{new_thing, workspace_thing, Database};
+use crate::domain::{erased_email, ERASED_CONTENT, ERASED_FIRST_NAME, ERASED_LAST_NAME, KEPT_METADATA_KEYS};
+use crate::error::{AppError, AppResult};
+use crate::repositories::workspace_scope;
+use crate::models::{
+    Attachment, AuditLog, Contact, Deal, GdprCampaignMembership, GdprDuplicateSuggestion, GdprEmbedding, GdprErasure,
+    GdprLandingPageVisit, GdprRsvp, GdprSequenceEnrollment, GdprShortLink, GdprStoredResponse, GdprWebhookDelivery,
+    StatusHistoryEntry, TimelineEntry,
+};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// The records linked to a contact, each list oldest first
+pub struct LinkedRecords {
+    pub timeline: Vec<TimelineEntry>,
+    pub rsvps: Vec<GdprRsvp>,
+    pub campaigns: Vec<GdprCampaignMembership>,
+    pub sequences: Vec<GdprSequenceEnrollment>,
+    pub deals: Vec<Deal>,
+    pub attachments: Vec<Attachment>,
+    pub landing_page_visits: Vec<GdprLandingPageVisit>,
+    pub short_links: Vec<GdprShortLink>,
+    pub status_history: Vec<StatusHistoryEntry>,
+    pub webhook_deliveries: Vec<GdprWebhookDelivery>,
+    pub stored_responses: Vec<GdprStoredResponse>,
+    pub embeddings: Vec<GdprEmbedding>,
+    pub duplicate_suggestions: Vec<GdprDuplicateSuggestion>,
+    pub history: Vec<AuditLog>,
+}
+
+/// Webhook deliveries whose data is the contact (`contact.*` events) or
+/// names it (`form.submitted`)
+const CONTACT_DELIVERIES: &str = "workspace = $workspace AND (payload.data.contact_id = $contact_id \
+     OR (payload.data.id = $contact_id AND string::starts_with(event, 'contact.')))";
+
+/// Duplicate suggestions with the contact on either side
+const CONTACT_SUGGESTIONS: &str = "workspace = $workspace AND (contact = $contact OR duplicate = $contact)";
+
+/// Stored idempotent responses of the workspace that mention the contact
+const CONTACT_RESPONSES: &str = "scope = $scope AND string::contains(body ?? '', $contact_id)";
+
+/// Repository for GDPR export and erasure
+pub struct GdprRepository {
+    db: Arc<Database>,
+}
+
+impl GdprRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// A contact of the workspace, trashed or not
+    pub async fn find_contact(&self, workspace_id: &str, id: &str) -> AppResult<Option<Contact>> {
+        let contacts: Vec<Contact> = self
+            .db
+            .client
+            .query("SELECT * FROM contact WHERE id = $contact AND workspace = $workspace")
+            .bind(("contact", Thing::from(("contact", id))))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(contacts.into_iter().next())
+    }
+
+    pub async fn find_records(&self, workspace_id: &str, id: &str) -> AppResult<LinkedRecords> {
+        let mut response = self
+            .db
+            .client
+            .query(
+                "SELECT * FROM timeline_entry WHERE workspace = $workspace AND contact = $contact \
+                 ORDER BY timestamp ASC",
+            )
+            .query(
+                "SELECT meta::id(event) AS event_id, event.name AS event_name, status, timestamp FROM rsvp \
+                 WHERE workspace = $workspace AND contact = $contact ORDER BY timestamp ASC",
+            )
+            .query(
+                "SELECT meta::id(campaign) AS campaign_id, campaign.name AS campaign_name, email, status, created_at \
+                 FROM campaign_recipient WHERE workspace = $workspace AND contact = $contact ORDER BY created_at ASC",
+            )
+            .query(
+                "SELECT meta::id(sequence) AS sequence_id, sequence.name AS sequence_name, status, enrolled_at, ended_at \
+                 FROM sequence_enrollment WHERE workspace = $workspace AND contact = $contact ORDER BY enrolled_at ASC",
+            )
+            .query("SELECT * FROM deal WHERE workspace = $workspace AND contact = $contact ORDER BY created_at ASC")
+            .query("SELECT * FROM attachment WHERE workspace = $workspace AND contact = $contact ORDER BY created_at ASC")
+            .query(
+                "SELECT meta::id(campaign) AS campaign_id, referrer, utm, visited_at FROM landing_page_visit \
+                 WHERE workspace = $workspace AND contact = $contact ORDER BY visited_at ASC",
+            )
+            .query(
+                "SELECT * FROM audit_log \
+                 WHERE workspace = $workspace AND entity_type = 'contact' AND entity_id = $contact_id \
+                 ORDER BY timestamp ASC",
+            )
+            .query(
+                "SELECT meta::id(campaign) AS campaign_id, channel, url, clicks, last_clicked_at, created_at \
+                 FROM short_link WHERE workspace = $workspace AND contact = $contact ORDER BY created_at ASC",
+            )
+            .query(
+                "SELECT * FROM status_history WHERE workspace = $workspace AND contact = $contact \
+                 ORDER BY changed_at ASC",
+            )
+            .query(format!(
+                "SELECT event, payload, status, created_at FROM webhook_delivery WHERE {} ORDER BY created_at ASC",
+                CONTACT_DELIVERIES
+            ))
+            .query(format!(
+                "SELECT key, status, body, created_at FROM idempotency_key WHERE {} ORDER BY created_at ASC",
+                CONTACT_RESPONSES
+            ))
+            .query(
+                "SELECT text, model, updated_at FROM contact_embedding \
+                 WHERE workspace = $workspace AND contact = $contact",
+            )
+            .query(format!(
+                "SELECT meta::id(IF contact = $contact THEN duplicate ELSE contact END) AS other_contact_id, \
+                    confidence, reasons, status, created_at \
+                 FROM dedupe_suggestion WHERE {} ORDER BY created_at ASC",
+                CONTACT_SUGGESTIONS
+            ))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("scope", workspace_scope(workspace_id)))
+            .bind(("contact", Thing::from(("contact", id))))
+            .bind(("contact_id", id.to_string()))
+            .await?;
+
+        Ok(LinkedRecords {
+            timeline: response.take(0)?,
+            rsvps: response.take(1)?,
+            campaigns: response.take(2)?,
+            sequences: response.take(3)?,
+            deals: response.take(4)?,
+            attachments: response.take(5)?,
+            landing_page_visits: response.take(6)?,
+            history: response.take(7)?,
+            short_links: response.take(8)?,
+            status_history: response.take(9)?,
+            webhook_deliveries: response.take(10)?,
+            stored_responses: response.take(11)?,
+            embeddings: response.take(12)?,
+            duplicate_suggestions: response.take(13)?,
+        })
+    }
+
+    /// Anonymize a contact and everything linked to it, and log the erasure
+    ///
+    /// Links stay in place; see `domain::gdpr` for what is erased.
+    pub async fn erase(&self, workspace_id: &str, id: &str, erased_by: &str) -> AppResult<GdprErasure> {
+        let erasure = new_thing("gdpr_erasure");
+        let kept_metadata = KEPT_METADATA_KEYS
+            .iter()
+            .map(|key| format!("{}: metadata.{}", key, key))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        self.db
+            .transaction()
+            // Counted first: the attachments are deleted below
+            .query(
+                "CREATE $erasure CONTENT { \
+                    workspace: $workspace, contact: $contact, erased_by: $erased_by, \
+                    timeline_entries: count(SELECT id FROM timeline_entry WHERE workspace = $workspace AND contact = $contact), \
+                    attachments: count(SELECT id FROM attachment WHERE workspace = $workspace AND contact = $contact), \
+                    erased_at: time::now() }",
+            )
+            .query(
+                "UPDATE $contact SET first_name = $first_name, last_name = $last_name, email = $email, \
+                    phone = NONE, linkedin_url = NONE, timezone = NONE, country = NONE, region = NONE, city = NONE, \
+                    avatar_url = NONE, tags = [], \
+                    custom_fields = {}, subscription_status = 'unsubscribed', updated_at = time::now() \
+                 WHERE workspace = $workspace",
+            )
+            .query(format!(
+                "UPDATE timeline_entry SET content = $erased_content, metadata = {{ {} }}, attachments = [], \
+                    sentiment = NONE \
+                 WHERE workspace = $workspace AND contact = $contact",
+                kept_metadata
+            ))
+            .query("DELETE attachment WHERE workspace = $workspace AND contact = $contact")
+            .query("UPDATE campaign_recipient SET email = $email WHERE workspace = $workspace AND contact = $contact")
+            .query(
+                "UPDATE sequence_enrollment SET status = 'unenrolled', next_run_at = NONE, \
+                    ended_at = time::now(), updated_at = time::now() \
+                 WHERE workspace = $workspace AND contact = $contact AND status = 'active'",
+            )
+            .query("UPDATE landing_page_visit SET referrer = NONE WHERE workspace = $workspace AND contact = $contact")
+            .query("UPDATE status_history SET reason = NONE WHERE workspace = $workspace AND contact = $contact")
+            .query("DELETE short_link WHERE workspace = $workspace AND contact = $contact")
+            .query(format!(
+                "UPDATE webhook_delivery SET payload.data = IF payload.data.contact_id = $contact_id \
+                    THEN {{ contact_id: $contact_id }} ELSE {{ id: $contact_id }} END \
+                 WHERE {}",
+                CONTACT_DELIVERIES
+            ))
+            .query(format!("DELETE idempotency_key WHERE {}", CONTACT_RESPONSES))
+            .query("DELETE contact_embedding WHERE workspace = $workspace AND contact = $contact")
+            .query(format!("DELETE dedupe_suggestion WHERE {}", CONTACT_SUGGESTIONS))
+            .query(
+                "UPDATE introduced_by, works_with, invested_in SET note = NONE \
+                 WHERE workspace = $workspace AND (in = $contact OR out = $contact)",
+            )
+            .query(
+                "UPDATE audit_log SET changes = [] \
+                 WHERE workspace = $workspace AND entity_type = 'contact' AND entity_id = $contact_id",
+            )
+            .query("DELETE import_ref WHERE workspace = $workspace AND kind = 'contact' AND record_id = $contact_id")
+            .bind(("erasure", erasure.clone()))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("scope", workspace_scope(workspace_id)))
+            .bind(("contact", Thing::from(("contact", id))))
+            .bind(("contact_id", id.to_string()))
+            .bind(("erased_by", Thing::from(("user", erased_by))))
+            .bind(("first_name", ERASED_FIRST_NAME))
+            .bind(("last_name", ERASED_LAST_NAME))
+            .bind(("email", erased_email(id)))
+            .bind(("erased_content", ERASED_CONTENT))
+            .commit()
+            .await?;
+
+        let erasures: Vec<GdprErasure> = self
+            .db
+            .client
+            .query("SELECT * FROM $erasure")
+            .bind(("erasure", erasure))
+            .await?
+            .take(0)?;
+
+        erasures
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::Internal("Failed to log erasure".into()))
+    }
+
+    /// The erasure log, newest first
+    pub async fn find_erasures(&self, workspace_id: &str, limit: u32) -> AppResult<Vec<GdprErasure>> {
+        let erasures: Vec<GdprErasure> = self
+            .db
+            .client
+            .query("SELECT * FROM gdpr_erasure WHERE workspace = $workspace ORDER BY erased_at DESC LIMIT $limit")
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("limit", limit))
+            .await?
+            .take(0)?;
+
+        Ok(erasures)
+    }
+}
@@ -0,0 +1,141 @@
+//! Idempotency Repository - stored responses keyed by Idempotency-Key
+
+use crate::db::Database;
+use crate::error::AppResult;
+use crate::models::IdempotencyRecord;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+/// Scope of the keys sent by a workspace's users
+pub fn workspace_scope(workspace_id: &str) -> String {
+    format!("workspace:{}", workspace_id)
+}
+
+/// Repository for IdempotencyRecord database operations
+pub struct IdempotencyRepository {
+    db: Arc<Database>,
+}
+
+impl IdempotencyRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    pub async fn find(&self, scope: &str, key: &str) -> AppResult<Option<IdempotencyRecord>> {
+        let records: Vec<IdempotencyRecord> = self
+            .db
+            .client
+            .query("SELECT * FROM idempotency_key WHERE scope = $scope AND key = $key LIMIT 1")
+            .bind(("scope", scope))
+            .bind(("key", key))
+            .await?
+            .take(0)?;
+
+        Ok(records.into_iter().next())
+    }
+
+    /// Insert a record for a key not seen before
+    ///
+    /// Fails on the unique (scope, key) index if another request got there
+    /// first; callers look the key up again in that case.
+    pub async fn insert(&self, record: IdempotencyRecord) -> AppResult<()> {
+        let _: Vec<IdempotencyRecord> = self.db.client.create("idempotency_key").content(record).await?;
+        Ok(())
+    }
+
+    /// Claim an unfinished key whose lease has run out
+    ///
+    /// Returns false when the key was finished, or taken over by another
+    /// retry, in the meantime.
+    pub async fn take_over(
+        &self,
+        scope: &str,
+        key: &str,
+        now: DateTime<Utc>,
+        locked_until: DateTime<Utc>,
+    ) -> AppResult<bool> {
+        let taken: Vec<IdempotencyRecord> = self
+            .db
+            .client
+            .query(
+                "UPDATE idempotency_key SET locked_until = $locked_until \
+                 WHERE scope = $scope AND key = $key AND status = NONE \
+                 AND (locked_until = NONE OR locked_until <= $now) RETURN AFTER",
+            )
+            .bind(("scope", scope))
+            .bind(("key", key))
+            .bind(("now", now))
+            .bind(("locked_until", locked_until))
+            .await?
+            .take(0)?;
+
+        Ok(!taken.is_empty())
+    }
+
+    /// Push back the lease of a key whose request is still running
+    pub async fn renew(&self, scope: &str, key: &str, locked_until: DateTime<Utc>) -> AppResult<()> {
+        self.db
+            .client
+            .query(
+                "UPDATE idempotency_key SET locked_until = $locked_until \
+                 WHERE scope = $scope AND key = $key AND status = NONE",
+            )
+            .bind(("scope", scope))
+            .bind(("key", key))
+            .bind(("locked_until", locked_until))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Store the finished response
+    pub async fn complete(
+        &self,
+        scope: &str,
+        key: &str,
+        status: u16,
+        body: String,
+        content_type: Option<String>,
+    ) -> AppResult<()> {
+        self.db
+            .client
+            .query(
+                "UPDATE idempotency_key SET status = $status, body = $body, content_type = $content_type \
+                 WHERE scope = $scope AND key = $key",
+            )
+            .bind(("scope", scope))
+            .bind(("key", key))
+            .bind(("status", status))
+            .bind(("body", body))
+            .bind(("content_type", content_type))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Forget a key, so the request can be retried
+    pub async fn delete(&self, scope: &str, key: &str) -> AppResult<()> {
+        self.db
+            .client
+            .query("DELETE idempotency_key WHERE scope = $scope AND key = $key")
+            .bind(("scope", scope))
+            .bind(("key", key))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    pub async fn delete_expired(&self, now: DateTime<Utc>) -> AppResult<()> {
+        self.db
+            .client
+            .query("DELETE idempotency_key WHERE expires_at < $now")
+            .bind(("now", now))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,276 @@
+//! Import Repository - import jobs, and which records they brought over
+//!
+//! An `import_ref` ties a record in another CRM to the record it became
+//! here, so re-running an import updates instead of duplicating.
+//!
+//! A running job holds its workspace and source's unique `running_key` and a
+//! lease it renews while it works. Saves from a job that lost its lease are
+//! refused, and a lapsed lease frees the key for the next import.
+
+use crate::db::{is_unique_violation, workspace_thing, Database};
+use crate::error::{AppError, AppResult};
+use crate::models::{Company, Deal, ImportJob, ImportSource};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Why a job whose lease lapsed was marked failed
+const ABANDONED_ERROR: &str = "The import stopped without finishing";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ImportRef {
+    workspace: Thing,
+    source: ImportSource,
+    kind: String,
+    external_id: String,
+    record_id: String,
+}
+
+/// Repository for import jobs and the companies and deals they write
+#[derive(Clone)]
+pub struct ImportRepository {
+    db: Arc<Database>,
+}
+
+impl ImportRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Create a running job, unless the workspace already runs an import
+    /// from the same source
+    ///
+    /// A running job whose lease lapsed is marked failed first. Returns
+    /// `None` when another import holds the source.
+    pub async fn start_job(&self, mut job: ImportJob, now: DateTime<Utc>) -> AppResult<Option<ImportJob>> {
+        let workspace_id = job.workspace.id.to_raw();
+        job.running_key = Some(running_key(&workspace_id, job.source));
+
+        self.db
+            .client
+            .query(
+                "UPDATE import_job SET status = 'failed', error = $error, finished_at = $now, running_key = NONE \
+                 WHERE workspace = $workspace AND source = $source AND status = 'running' \
+                    AND (lease_expires_at IS NONE OR lease_expires_at < $now)",
+            )
+            .bind(("workspace", job.workspace.clone()))
+            .bind(("source", job.source))
+            .bind(("error", ABANDONED_ERROR))
+            .bind(("now", now))
+            .await?
+            .check()?;
+
+        let created: Result<Vec<ImportJob>, _> = self.db.client.create("import_job").content(job).await;
+        match created {
+            Ok(created) => created
+                .into_iter()
+                .next()
+                .map(Some)
+                .ok_or_else(|| AppError::Internal("Failed to create import job".into())),
+            Err(e) if is_unique_violation(&e) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Save a running job's progress and renew its lease
+    ///
+    /// False when the job lost its lease and must stop.
+    pub async fn save_progress(&self, job: &ImportJob, lease_expires_at: DateTime<Utc>) -> AppResult<bool> {
+        let saved: Vec<Thing> = self
+            .db
+            .client
+            .query(
+                "UPDATE $job SET progress = $progress, failures = $failures, lease_expires_at = $lease \
+                 WHERE status = 'running' RETURN VALUE id",
+            )
+            .bind(("job", job_id(job)?))
+            .bind(("progress", job.progress.clone()))
+            .bind(("failures", job.failures.clone()))
+            .bind(("lease", lease_expires_at))
+            .await?
+            .take(0)?;
+
+        Ok(!saved.is_empty())
+    }
+
+    /// Extend a running job's lease while it works between saves
+    pub async fn renew_lease(&self, job: &Thing, lease_expires_at: DateTime<Utc>) -> AppResult<()> {
+        self.db
+            .client
+            .query("UPDATE $job SET lease_expires_at = $lease WHERE status = 'running'")
+            .bind(("job", job.clone()))
+            .bind(("lease", lease_expires_at))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Record how a running job ended and free its source
+    pub async fn finish_job(&self, job: &ImportJob) -> AppResult<()> {
+        self.db
+            .client
+            .query(
+                "UPDATE $job SET status = $status, progress = $progress, failures = $failures, error = $error, \
+                    finished_at = $finished_at, running_key = NONE, lease_expires_at = NONE \
+                 WHERE status = 'running'",
+            )
+            .bind(("job", job_id(job)?))
+            .bind(("status", job.status))
+            .bind(("progress", job.progress.clone()))
+            .bind(("failures", job.failures.clone()))
+            .bind(("error", job.error.clone()))
+            .bind(("finished_at", job.finished_at))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    pub async fn find_job(&self, workspace_id: &str, id: &str) -> AppResult<Option<ImportJob>> {
+        Ok(self.db.select_scoped("import_job", id, workspace_id).await?)
+    }
+
+    pub async fn find_jobs(&self, workspace_id: &str) -> AppResult<Vec<ImportJob>> {
+        let jobs: Vec<ImportJob> = self
+            .db
+            .client
+            .query("SELECT * FROM import_job WHERE workspace = $workspace ORDER BY started_at DESC LIMIT 50")
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(jobs)
+    }
+
+    /// External ID → ID here, for everything of one kind imported from a source
+    pub async fn refs(&self, workspace_id: &str, source: ImportSource, kind: &str) -> AppResult<HashMap<String, String>> {
+        let refs: Vec<ImportRef> = self
+            .db
+            .client
+            .query("SELECT * FROM import_ref WHERE workspace = $workspace AND source = $source AND kind = $kind")
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("source", source))
+            .bind(("kind", kind.to_string()))
+            .await?
+            .take(0)?;
+
+        Ok(refs.into_iter().map(|r| (r.external_id, r.record_id)).collect())
+    }
+
+    /// Remember what an external record became, replacing any earlier ref
+    pub async fn save_ref(
+        &self,
+        workspace_id: &str,
+        source: ImportSource,
+        kind: &str,
+        external_id: &str,
+        record_id: &str,
+    ) -> AppResult<()> {
+        self.db
+            .client
+            .query(
+                "DELETE import_ref WHERE workspace = $workspace AND source = $source \
+                 AND kind = $kind AND external_id = $external_id; \
+                 CREATE import_ref CONTENT $ref",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("source", source))
+            .bind(("kind", kind.to_string()))
+            .bind(("external_id", external_id.to_string()))
+            .bind((
+                "ref",
+                ImportRef {
+                    workspace: workspace_thing(workspace_id),
+                    source,
+                    kind: kind.to_string(),
+                    external_id: external_id.to_string(),
+                    record_id: record_id.to_string(),
+                },
+            ))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    pub async fn find_company(&self, workspace_id: &str, id: &str) -> AppResult<Option<Company>> {
+        Ok(self.db.select_scoped("company", id, workspace_id).await?)
+    }
+
+    /// ID of the workspace's live company with this domain
+    pub async fn find_company_id_by_domain(&self, workspace_id: &str, domain: &str) -> AppResult<Option<String>> {
+        let ids: Vec<Thing> = self
+            .db
+            .client
+            .query(
+                "SELECT VALUE id FROM company \
+                 WHERE workspace = $workspace AND domain = $domain AND deleted_at IS NONE LIMIT 1",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("domain", domain.to_string()))
+            .await?
+            .take(0)?;
+
+        Ok(ids.into_iter().next().map(|id| id.id.to_raw()))
+    }
+
+    pub async fn create_company(&self, company: Company) -> AppResult<Company> {
+        let created: Vec<Company> = self.db.client.create("company").content(company).await?;
+
+        created
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::Internal("Failed to create company".into()))
+    }
+
+    pub async fn update_company(&self, id: &str, company: Company) -> AppResult<Company> {
+        let updated: Option<Company> = self.db.client.update(("company", id)).content(company).await?;
+
+        updated.ok_or_else(|| AppError::NotFound(format!("Company '{}' not found", id)))
+    }
+
+    pub async fn find_deal(&self, workspace_id: &str, id: &str) -> AppResult<Option<Deal>> {
+        Ok(self.db.select_scoped("deal", id, workspace_id).await?)
+    }
+
+    pub async fn create_deal(&self, deal: Deal) -> AppResult<Deal> {
+        let created: Vec<Deal> = self.db.client.create("deal").content(deal).await?;
+
+        created
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::Internal("Failed to create deal".into()))
+    }
+
+    pub async fn update_deal(&self, id: &str, deal: Deal) -> AppResult<Deal> {
+        let updated: Option<Deal> = self.db.client.update(("deal", id)).content(deal).await?;
+
+        updated.ok_or_else(|| AppError::NotFound(format!("Deal '{}' not found", id)))
+    }
+}
+
+/// Held by the running import of a source in a workspace
+fn running_key(workspace_id: &str, source: ImportSource) -> String {
+    format!("{}:{}", workspace_id, source.as_str())
+}
+
+fn job_id(job: &ImportJob) -> AppResult<Thing> {
+    job.id.clone().ok_or_else(|| AppError::Internal("Import job has no ID".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_running_key_is_per_workspace_and_source() {
+        assert_eq!(running_key("acme", ImportSource::Hubspot), "acme:hubspot");
+        assert_ne!(
+            running_key("acme", ImportSource::Hubspot),
+            running_key("acme", ImportSource::Pipedrive)
+        );
+    }
+}
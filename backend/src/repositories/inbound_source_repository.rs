@@ -0,0 +1,84 @@
+//! Inbound Source Repository - external tools pushing JSON into a workspace
+
+use crate::db::{workspace_thing, Database};
+use crate::error::{AppError, AppResult};
+use crate::models::InboundSource;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Repository for InboundSource database operations
+pub struct InboundSourceRepository {
+    db: Arc<Database>,
+}
+
+impl InboundSourceRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    pub async fn create(&self, source: InboundSource) -> AppResult<InboundSource> {
+        let created: Vec<InboundSource> = self.db.client.create("inbound_source").content(source).await?;
+
+        created
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::Internal("Failed to create inbound source".into()))
+    }
+
+    pub async fn find_all(&self, workspace_id: &str) -> AppResult<Vec<InboundSource>> {
+        let sources: Vec<InboundSource> = self
+            .db
+            .client
+            .query("SELECT * FROM inbound_source WHERE workspace = $workspace ORDER BY created_at DESC")
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(sources)
+    }
+
+    pub async fn find_by_id(&self, workspace_id: &str, id: &str) -> AppResult<Option<InboundSource>> {
+        Ok(self.db.select_scoped("inbound_source", id, workspace_id).await?)
+    }
+
+    /// The source an ingestion URL belongs to, in whichever workspace
+    pub async fn find_by_token(&self, token: &str) -> AppResult<Option<InboundSource>> {
+        let sources: Vec<InboundSource> = self
+            .db
+            .client
+            .query("SELECT * FROM inbound_source WHERE token = $token LIMIT 1")
+            .bind(("token", token.to_string()))
+            .await?
+            .take(0)?;
+
+        Ok(sources.into_iter().next())
+    }
+
+    pub async fn update(&self, id: &str, source: InboundSource) -> AppResult<InboundSource> {
+        let updated: Option<InboundSource> = self
+            .db
+            .client
+            .update(("inbound_source", id))
+            .content(source)
+            .await?;
+
+        updated.ok_or_else(|| AppError::NotFound("Inbound source not found".into()))
+    }
+
+    pub async fn touch(&self, source: &Thing, received_at: DateTime<Utc>) -> AppResult<()> {
+        self.db
+            .client
+            .query("UPDATE $source SET last_received_at = $received_at")
+            .bind(("source", source.clone()))
+            .bind(("received_at", received_at))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    pub async fn delete(&self, workspace_id: &str, id: &str) -> AppResult<bool> {
+        Ok(self.db.delete_scoped("inbound_source", id, workspace_id).await?)
+    }
+}
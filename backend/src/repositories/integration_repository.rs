@@ -0,0 +1,212 @@
+//! Integration Repository - connected mailboxes and what their syncs matched
+
+use crate::db::{workspace_thing, Database};
+use crate::error::{AppError, AppResult};
+use crate::models::Integration;
+use chrono::Utc;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Repository for Integration database operations
+pub struct IntegrationRepository {
+    db: Arc<Database>,
+}
+
+impl IntegrationRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    pub async fn create(&self, integration: Integration) -> AppResult<Integration> {
+        let created: Vec<Integration> = self.db.client.create("integration").content(integration).await?;
+
+        created
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::Internal("Failed to create integration".into()))
+    }
+
+    pub async fn find_all(&self, workspace_id: &str) -> AppResult<Vec<Integration>> {
+        let integrations: Vec<Integration> = self
+            .db
+            .client
+            .query("SELECT * FROM integration WHERE workspace = $workspace ORDER BY created_at DESC")
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(integrations)
+    }
+
+    pub async fn find_by_id(&self, workspace_id: &str, id: &str) -> AppResult<Option<Integration>> {
+        Ok(self.db.select_scoped("integration", id, workspace_id).await?)
+    }
+
+    /// The pending integration an OAuth callback belongs to
+    pub async fn find_by_oauth_state(&self, state: &str) -> AppResult<Option<Integration>> {
+        let integrations: Vec<Integration> = self
+            .db
+            .client
+            .query("SELECT * FROM integration WHERE oauth_state = $state AND status = 'pending' LIMIT 1")
+            .bind(("state", state.to_string()))
+            .await?
+            .take(0)?;
+
+        Ok(integrations.into_iter().next())
+    }
+
+    /// Integrations to sync, across all workspaces
+    pub async fn find_syncable(&self) -> AppResult<Vec<Integration>> {
+        let integrations: Vec<Integration> = self
+            .db
+            .client
+            .query("SELECT * FROM integration WHERE status INSIDE ['active', 'error']")
+            .await?
+            .take(0)?;
+
+        Ok(integrations)
+    }
+
+    /// Change whether unknown senders become contacts, leaving the
+    /// credentials and sync state as they are
+    pub async fn update_settings(
+        &self,
+        workspace_id: &str,
+        id: &str,
+        create_unknown_contacts: bool,
+    ) -> AppResult<Option<Integration>> {
+        let updated: Vec<Integration> = self
+            .db
+            .client
+            .query(
+                "UPDATE $integration SET create_unknown_contacts = $create_unknown_contacts, updated_at = $now \
+                 WHERE workspace = $workspace RETURN AFTER",
+            )
+            .bind(("integration", Thing::from(("integration", id))))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("create_unknown_contacts", create_unknown_contacts))
+            .bind(("now", Utc::now()))
+            .await?
+            .take(0)?;
+
+        Ok(updated.into_iter().next())
+    }
+
+    /// Activate a pending Gmail integration with the account and tokens
+    /// its OAuth callback brought back
+    pub async fn complete_oauth(&self, integration: &Integration) -> AppResult<Integration> {
+        let updated: Vec<Integration> = self
+            .db
+            .client
+            .query(
+                "UPDATE $integration SET account = $account, oauth = $oauth, oauth_state = NONE, \
+                    status = 'active', updated_at = $now \
+                 WHERE status = 'pending' RETURN AFTER",
+            )
+            .bind(("integration", record_id(integration)?))
+            .bind(("account", integration.account.clone()))
+            .bind(("oauth", integration.oauth.clone()))
+            .bind(("now", Utc::now()))
+            .await?
+            .take(0)?;
+
+        updated
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::NotFound("No pending Gmail connection for this request".into()))
+    }
+
+    /// Store the outcome of a sync: cursor, status, error and refreshed
+    /// credentials
+    ///
+    /// Settings changed while the sync ran are left alone, and a mailbox
+    /// disconnected meanwhile stays gone.
+    pub async fn save_sync(&self, integration: &Integration) -> AppResult<()> {
+        self.db
+            .client
+            .query(
+                "UPDATE $integration SET status = $status, cursor = $cursor, imap = $imap, oauth = $oauth, \
+                    last_synced_at = $last_synced_at, last_error = $last_error, updated_at = $now \
+                 WHERE workspace = $workspace",
+            )
+            .bind(("integration", record_id(integration)?))
+            .bind(("workspace", integration.workspace.clone()))
+            .bind(("status", integration.status))
+            .bind(("cursor", integration.cursor.clone()))
+            .bind(("imap", integration.imap.clone()))
+            .bind(("oauth", integration.oauth.clone()))
+            .bind(("last_synced_at", integration.last_synced_at))
+            .bind(("last_error", integration.last_error.clone()))
+            .bind(("now", Utc::now()))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    pub async fn delete(&self, workspace_id: &str, id: &str) -> AppResult<bool> {
+        Ok(self.db.delete_scoped("integration", id, workspace_id).await?)
+    }
+
+    /// Contact IDs by email, for the addresses that belong to a live contact
+    pub async fn contact_ids_by_email(
+        &self,
+        workspace_id: &str,
+        emails: Vec<String>,
+    ) -> AppResult<HashMap<String, String>> {
+        #[derive(Deserialize)]
+        struct Row {
+            id: Thing,
+            email: String,
+        }
+
+        if emails.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let rows: Vec<Row> = self
+            .db
+            .client
+            .query(
+                "SELECT id, email FROM contact \
+                 WHERE workspace = $workspace AND email INSIDE $emails AND deleted_at IS NONE",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("emails", emails))
+            .await?
+            .take(0)?;
+
+        Ok(rows.into_iter().map(|r| (r.email, r.id.id.to_string())).collect())
+    }
+
+    /// Which of these messages are already on a timeline in the workspace
+    pub async fn synced_message_ids(&self, workspace_id: &str, message_ids: Vec<String>) -> AppResult<HashSet<String>> {
+        if message_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let synced: Vec<String> = self
+            .db
+            .client
+            .query(
+                "SELECT VALUE metadata.message_id FROM timeline_entry \
+                 WHERE workspace = $workspace AND type = 'email_received' \
+                    AND metadata.message_id INSIDE $ids",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("ids", message_ids))
+            .await?
+            .take(0)?;
+
+        Ok(synced.into_iter().collect())
+    }
+}
+
+fn record_id(integration: &Integration) -> AppResult<Thing> {
+    integration
+        .id
+        .clone()
+        .ok_or_else(|| AppError::Internal("Integration has no ID".into()))
+}
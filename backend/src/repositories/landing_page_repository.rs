@@ -0,0 +1,153 @@
+//! Landing Page Repository - visits to and form submissions on public
+//! landing pages
+//!
+//! A visitor is identified by the cookie set on their first view; each
+//! visitor is recorded once per page.
+
+use crate::db::Database;
+use crate::error::AppResult;
+use crate::models::{CampaignAsset, Contact, LandingPageVisit, TimelineEntry};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Repository for LandingPageVisit database operations
+pub struct LandingPageRepository {
+    db: Arc<Database>,
+}
+
+impl LandingPageRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Store a visit unless the visitor has already seen the page
+    ///
+    /// Returns whether the visit was new.
+    pub async fn record_visit(&self, visit: LandingPageVisit) -> AppResult<bool> {
+        let seen: Option<u64> = self
+            .db
+            .client
+            .query(
+                "RETURN array::len((SELECT VALUE id FROM landing_page_visit \
+                    WHERE page = $page AND visitor_id = $visitor_id LIMIT 1))",
+            )
+            .bind(("page", visit.page.clone()))
+            .bind(("visitor_id", visit.visitor_id.clone()))
+            .await?
+            .take(0)?;
+        if seen.unwrap_or(0) > 0 {
+            return Ok(false);
+        }
+
+        // The unique index settles two first views racing each other
+        self.db
+            .client
+            .query("CREATE landing_page_visit CONTENT $visit")
+            .bind(("visit", visit))
+            .await?
+            .check()?;
+
+        Ok(true)
+    }
+
+    /// The contact a visitor turned out to be, if they ever submitted a form
+    pub async fn visitor_contact(&self, workspace: &Thing, visitor_id: &str) -> AppResult<Option<Thing>> {
+        let contact: Vec<Thing> = self
+            .db
+            .client
+            .query(
+                "SELECT VALUE contact FROM landing_page_visit \
+                 WHERE workspace = $workspace AND visitor_id = $visitor_id AND contact IS NOT NONE LIMIT 1",
+            )
+            .bind(("workspace", workspace.clone()))
+            .bind(("visitor_id", visitor_id.to_string()))
+            .await?
+            .take(0)?;
+
+        Ok(contact.into_iter().next())
+    }
+
+    /// Attribute a visitor's earlier anonymous visits to a contact
+    pub async fn link_visitor(&self, workspace: &Thing, visitor_id: &str, contact: &Thing) -> AppResult<()> {
+        self.db
+            .client
+            .query(
+                "UPDATE landing_page_visit SET contact = $contact \
+                 WHERE workspace = $workspace AND visitor_id = $visitor_id AND contact IS NONE",
+            )
+            .bind(("workspace", workspace.clone()))
+            .bind(("visitor_id", visitor_id.to_string()))
+            .bind(("contact", contact.clone()))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// A landing page, if it is the version its campaign currently uses
+    pub async fn find_active_page(&self, id: &str) -> AppResult<Option<CampaignAsset>> {
+        let pages: Vec<CampaignAsset> = self
+            .db
+            .client
+            .query("SELECT * FROM $page WHERE type = 'landing_page' AND active = true")
+            .bind(("page", Thing::from(("campaign_asset", id))))
+            .await?
+            .take(0)?;
+
+        Ok(pages.into_iter().next())
+    }
+
+    /// The live contact with an email, and whether a trashed one has it
+    pub async fn find_submitter(&self, workspace: &Thing, email: &str) -> AppResult<(Option<Contact>, bool)> {
+        let mut found = self
+            .db
+            .client
+            .query("SELECT * FROM contact WHERE workspace = $workspace AND email = $email AND deleted_at IS NONE LIMIT 1")
+            .query(
+                "SELECT VALUE id FROM contact \
+                 WHERE workspace = $workspace AND email = $email AND deleted_at IS NOT NONE LIMIT 1",
+            )
+            .bind(("workspace", workspace.clone()))
+            .bind(("email", email.to_string()))
+            .await?;
+        let live: Vec<Contact> = found.take(0)?;
+        let trashed: Vec<Thing> = found.take(1)?;
+
+        Ok((live.into_iter().next(), !trashed.is_empty()))
+    }
+
+    /// Store a submission's timeline entry with the contact it creates, or
+    /// the custom fields it adds to an existing one, all or nothing
+    pub async fn save_submission(
+        &self,
+        contact: &Contact,
+        created: bool,
+        added_fields: BTreeMap<String, String>,
+        entry: &TimelineEntry,
+        now: DateTime<Utc>,
+    ) -> AppResult<()> {
+        let mut tx = self.db.transaction();
+        if created {
+            tx = tx
+                .query("CREATE $contact CONTENT $record")
+                .bind(("contact", contact.id.clone()))
+                .bind(("record", contact.clone()));
+        } else if !added_fields.is_empty() {
+            tx = tx
+                .query("UPDATE $contact MERGE { custom_fields: $custom_fields, updated_at: $now }")
+                .bind(("contact", contact.id.clone()))
+                .bind(("custom_fields", added_fields))
+                .bind(("now", now));
+        }
+
+        tx.query("CREATE $entry_id CONTENT $entry")
+            .bind(("entry_id", entry.id.clone()))
+            .bind(("entry", entry.clone()))
+            .commit()
+            .await?;
+
+        Ok(())
+    }
+}
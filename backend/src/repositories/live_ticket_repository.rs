@@ -0,0 +1,49 @@
+//! Live Ticket Repository - one-time tickets for opening live sockets
+
+use crate::db::Database;
+use crate::error::AppResult;
+use crate::models::LiveTicket;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Repository for LiveTicket database operations
+pub struct LiveTicketRepository {
+    db: Arc<Database>,
+}
+
+impl LiveTicketRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Store a ticket, dropping tickets that expired unused
+    pub async fn create(&self, ticket: LiveTicket, now: DateTime<Utc>) -> AppResult<()> {
+        self.db
+            .client
+            .query("DELETE live_ticket WHERE expires_at <= $now")
+            .query("CREATE $id CONTENT $ticket")
+            .bind(("now", now))
+            .bind(("id", ticket.id.clone()))
+            .bind(("ticket", ticket))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Delete an unexpired ticket and return it
+    ///
+    /// A ticket is returned once at most, however many requests race for it.
+    pub async fn consume(&self, id: Thing, now: DateTime<Utc>) -> AppResult<Option<LiveTicket>> {
+        let consumed: Vec<LiveTicket> = self
+            .db
+            .client
+            .query("DELETE $id WHERE expires_at > $now RETURN BEFORE")
+            .bind(("id", id))
+            .bind(("now", now))
+            .await?
+            .take(0)?;
+
+        Ok(consumed.into_iter().next())
+    }
+}
@@ -6,7 +6,87 @@
 //! - Handle database errors
 //!
 //! Repositories know about SurrealDB. Domain layer does NOT.
+//!
+//! Every tenant-owned table carries a `workspace` record link, and every
+//! query a repository issues must filter on it. Use `Database::select_scoped`
+//! and `Database::delete_scoped` for single-record access by ID.
 
+pub mod ai_usage_repository;
+pub mod analytics_repository;
+pub mod attachment_repository;
+pub mod audit_repository;
+pub mod backup_repository;
+pub mod campaign_recipient_repository;
+pub mod campaign_repository;
+pub mod campaign_template_repository;
 pub mod contact_repository;
+pub mod deal_repository;
+pub mod dedupe_repository;
+pub mod deliverability_repository;
+pub mod digest_repository;
+pub mod engagement_repository;
+pub mod enrichment_repository;
+pub mod event_repository;
+pub mod gdpr_repository;
+pub mod idempotency_repository;
+pub mod import_repository;
+pub mod inbound_source_repository;
+pub mod integration_repository;
+pub mod landing_page_repository;
+pub mod live_ticket_repository;
+pub mod pipeline_repository;
+pub mod recommendation_repository;
+pub mod relationship_repository;
+pub mod search_repository;
+pub mod seed_repository;
+pub mod segment_repository;
+pub mod sender_repository;
+pub mod sequence_repository;
+pub mod short_link_repository;
+pub mod social_account_repository;
+pub mod social_post_repository;
+pub mod timeline_repository;
+pub mod trash_repository;
+pub mod user_repository;
+pub mod webhook_repository;
+pub mod workspace_repository;
 
+pub use ai_usage_repository::*;
+pub use analytics_repository::*;
+pub use attachment_repository::*;
+pub use audit_repository::*;
+pub use backup_repository::*;
+pub use campaign_recipient_repository::*;
+pub use campaign_repository::*;
+pub use campaign_template_repository::*;
 pub use contact_repository::*;
+pub use deal_repository::*;
+pub use dedupe_repository::*;
+pub use deliverability_repository::*;
+pub use digest_repository::*;
+pub use engagement_repository::*;
+pub use enrichment_repository::*;
+pub use event_repository::*;
+pub use gdpr_repository::*;
+pub use idempotency_repository::*;
+pub use import_repository::*;
+pub use inbound_source_repository::*;
+pub use integration_repository::*;
+pub use landing_page_repository::*;
+pub use live_ticket_repository::*;
+pub use pipeline_repository::*;
+pub use recommendation_repository::*;
+pub use relationship_repository::*;
+pub use search_repository::*;
+pub use seed_repository::*;
+pub use segment_repository::*;
+pub use sender_repository::*;
+pub use sequence_repository::*;
+pub use short_link_repository::*;
+pub use social_account_repository::*;
+pub use social_post_repository::*;
+pub use timeline_repository::*;
+pub use trash_repository::*;
+pub use user_repository::*;
+pub use webhook_repository::*;
+pub use workspace_repository::*;
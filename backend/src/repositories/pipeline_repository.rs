@@ -0,0 +1,164 @@
+//! Pipeline Repository - board columns and card positions
+//!
+//! Contacts and deals share the board: a column is a contact status or a
+//! deal stage, and both tables carry a `pipeline_position`. Trashed
+//! contacts never show up.
+
+use crate::db::{workspace_thing, Database, Transaction};
+use crate::error::{AppError, AppResult};
+use crate::models::{Deal, PipelineKind};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Board order: cards never moved by hand sort first (NONE before any
+/// number), newest first among themselves
+const BOARD_ORDER: &str = "ORDER BY pipeline_position ASC, created_at DESC";
+
+/// Where a card sits, enough to work out where a dropped card lands
+#[derive(Debug, Deserialize)]
+pub struct CardPosition {
+    pub id: Thing,
+    pub pipeline_position: Option<f64>,
+}
+
+/// Repository for the pipeline board
+pub struct PipelineRepository {
+    db: Arc<Database>,
+}
+
+impl PipelineRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// The first `limit` cards of each column, with the column's total
+    ///
+    /// Results follow the order of `statuses`.
+    pub async fn find_columns<T: DeserializeOwned>(
+        &self,
+        workspace_id: &str,
+        kind: PipelineKind,
+        statuses: &[&str],
+        limit: u32,
+    ) -> AppResult<Vec<(Vec<T>, u64)>> {
+        let (table, filter) = (kind.table(), column_filter(kind));
+        let status_field = status_field(kind);
+
+        let mut statements = String::new();
+        for i in 0..statuses.len() {
+            statements.push_str(&format!(
+                "SELECT * FROM {table} WHERE {filter} AND {status_field} = $status{i} {BOARD_ORDER} LIMIT $limit; \
+                 RETURN array::len((SELECT VALUE id FROM {table} WHERE {filter} AND {status_field} = $status{i}));"
+            ));
+        }
+
+        let mut query = self
+            .db
+            .client
+            .query(statements)
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("limit", limit));
+        for (i, status) in statuses.iter().enumerate() {
+            query = query.bind((format!("status{i}"), status.to_string()));
+        }
+
+        let mut response = query.await?;
+
+        let mut columns = Vec::with_capacity(statuses.len());
+        for i in 0..statuses.len() {
+            let cards: Vec<T> = response.take(2 * i)?;
+            let total: Option<u64> = response.take(2 * i + 1)?;
+            columns.push((cards, total.unwrap_or_default()));
+        }
+
+        Ok(columns)
+    }
+
+    /// Positions of the cards in a column, in board order, leaving out one card
+    pub async fn find_positions(
+        &self,
+        workspace_id: &str,
+        kind: PipelineKind,
+        status: &str,
+        exclude_id: &str,
+    ) -> AppResult<Vec<CardPosition>> {
+        let (table, filter) = (kind.table(), column_filter(kind));
+        let status_field = status_field(kind);
+
+        // created_at is selected only because ORDER BY needs it in the output
+        let positions: Vec<CardPosition> = self
+            .db
+            .client
+            .query(format!(
+                "SELECT id, pipeline_position, created_at FROM {table} \
+                 WHERE {filter} AND {status_field} = $status AND id != $card {BOARD_ORDER}"
+            ))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("status", status.to_string()))
+            .bind(("card", Thing::from((table, exclude_id))))
+            .await?
+            .take(0)?;
+
+        Ok(positions)
+    }
+
+    /// Store new positions for several cards in one transaction
+    pub async fn set_positions(&self, workspace_id: &str, positions: &[(Thing, f64)]) -> AppResult<()> {
+        position_updates(self.db.transaction(), workspace_id, positions)
+            .commit()
+            .await?;
+        Ok(())
+    }
+
+    pub async fn find_deal(&self, workspace_id: &str, id: &str) -> AppResult<Option<Deal>> {
+        Ok(self.db.select_scoped("deal", id, workspace_id).await?)
+    }
+
+    /// Save a deal moved to another stage, with the positions of other
+    /// cards it needed placed, in one transaction
+    pub async fn move_deal(&self, workspace_id: &str, deal: &Deal, others: &[(Thing, f64)]) -> AppResult<()> {
+        let id = deal
+            .id
+            .clone()
+            .ok_or_else(|| AppError::Internal("A moved deal has no ID".into()))?;
+
+        position_updates(self.db.transaction(), workspace_id, others)
+            .query("UPDATE $deal CONTENT $content WHERE workspace = $workspace")
+            .bind(("deal", id))
+            .bind(("content", deal.clone()))
+            .commit()
+            .await?;
+        Ok(())
+    }
+}
+
+/// Add statements storing card positions to a transaction; binds `$workspace`
+fn position_updates<'a>(mut tx: Transaction<'a>, workspace_id: &str, positions: &[(Thing, f64)]) -> Transaction<'a> {
+    tx = tx.bind(("workspace", workspace_thing(workspace_id)));
+    for (i, (card, position)) in positions.iter().enumerate() {
+        tx = tx
+            .query(format!(
+                "UPDATE $card{i} SET pipeline_position = $position{i} WHERE workspace = $workspace"
+            ))
+            .bind((format!("card{i}"), card.clone()))
+            .bind((format!("position{i}"), *position));
+    }
+    tx
+}
+
+/// Base WHERE clause for a kind of card; binds `$workspace`
+fn column_filter(kind: PipelineKind) -> &'static str {
+    match kind {
+        PipelineKind::Contacts => "workspace = $workspace AND deleted_at IS NONE",
+        PipelineKind::Deals => "workspace = $workspace",
+    }
+}
+
+fn status_field(kind: PipelineKind) -> &'static str {
+    match kind {
+        PipelineKind::Contacts => "status",
+        PipelineKind::Deals => "stage",
+    }
+}
@@ -0,0 +1,120 @@
+//! Recommendation Repository - the signals next best actions are picked from
+//!
+//! Activity comes from `EngagementRepository::find_activity`; this adds the
+//! contacts themselves, their open tasks and their open deals.
+
+use crate::db::{workspace_thing, Database};
+use crate::domain::DealStage;
+use crate::error::AppResult;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// The contact fields a recommendation lists
+#[derive(Debug, Clone, Deserialize)]
+pub struct RecommendationContact {
+    pub id: Thing,
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+    pub engagement_score: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TaskCount {
+    contact: Thing,
+    count: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenDeal {
+    contact: Thing,
+    stage: DealStage,
+}
+
+/// Repository for recommendation signals
+pub struct RecommendationRepository {
+    db: Arc<Database>,
+}
+
+impl RecommendationRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Contacts of the workspace, most engaged first
+    pub async fn find_contacts(&self, workspace_id: &str, limit: u32) -> AppResult<Vec<RecommendationContact>> {
+        let contacts: Vec<RecommendationContact> = self
+            .db
+            .client
+            .query(
+                "SELECT id, first_name, last_name, email, engagement_score FROM contact \
+                 WHERE workspace = $workspace AND deleted_at IS NONE \
+                 ORDER BY engagement_score DESC LIMIT $limit",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("limit", limit))
+            .await?
+            .take(0)?;
+
+        Ok(contacts)
+    }
+
+    /// Task entries not marked completed, counted per contact ID
+    pub async fn open_task_counts(&self, workspace_id: &str, contacts: &[Thing]) -> AppResult<HashMap<String, u32>> {
+        if contacts.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let counts: Vec<TaskCount> = self
+            .db
+            .client
+            .query(
+                "SELECT contact, count() AS count FROM timeline_entry \
+                 WHERE workspace = $workspace AND contact INSIDE $contacts AND type = 'task' \
+                    AND metadata.completed != true \
+                 GROUP BY contact",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("contacts", contacts.to_vec()))
+            .await?
+            .take(0)?;
+
+        Ok(counts.into_iter().map(|c| (c.contact.id.to_raw(), c.count)).collect())
+    }
+
+    /// The furthest stage among each contact's open deals, by contact ID
+    pub async fn open_deal_stages(
+        &self,
+        workspace_id: &str,
+        contacts: &[Thing],
+    ) -> AppResult<HashMap<String, DealStage>> {
+        if contacts.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let deals: Vec<OpenDeal> = self
+            .db
+            .client
+            .query(
+                "SELECT contact, stage FROM deal \
+                 WHERE workspace = $workspace AND contact INSIDE $contacts \
+                    AND stage NOTINSIDE ['closed_won', 'closed_lost']",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("contacts", contacts.to_vec()))
+            .await?
+            .take(0)?;
+
+        let mut stages: HashMap<String, DealStage> = HashMap::new();
+        for deal in deals {
+            let stage = stages.entry(deal.contact.id.to_raw()).or_insert(deal.stage);
+            if deal.stage.win_probability() > stage.win_probability() {
+                *stage = deal.stage;
+            }
+        }
+
+        Ok(stages)
+    }
+}
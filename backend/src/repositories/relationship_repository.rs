@@ -0,0 +1,139 @@
+//! Relationship Repository - graph edges between contacts and companies
+//!
+//! Every kind of relationship has its own edge table, created with RELATE so
+//! the edges can also be walked with `->` and `<-`. Reads go to all the
+//! tables at once and skip edges whose either end is in the trash.
+
+use crate::db::{workspace_thing, Database};
+use crate::domain::RelationshipKind;
+use crate::error::{AppError, AppResult};
+use crate::models::Relationship;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Most edges one read returns, so a hub company can't pull in the whole workspace
+pub const MAX_EDGES_PER_READ: u32 = 2000;
+
+/// Repository for relationship database operations
+pub struct RelationshipRepository {
+    db: Arc<Database>,
+}
+
+impl RelationshipRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Relate `from` to `to`
+    pub async fn create(
+        &self,
+        workspace_id: &str,
+        kind: RelationshipKind,
+        from: Thing,
+        to: Thing,
+        note: Option<String>,
+        created_by: &str,
+    ) -> AppResult<Relationship> {
+        let created: Vec<Relationship> = self
+            .db
+            .client
+            .query(format!(
+                "RELATE $from->{}->$to CONTENT {{ \
+                    workspace: $workspace, note: $note, created_by: $created_by, created_at: time::now() }}",
+                kind.table()
+            ))
+            .bind(("from", from))
+            .bind(("to", to))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("note", note))
+            .bind(("created_by", created_by.to_string()))
+            .await?
+            .take(0)?;
+
+        created
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::Internal("Failed to create relationship".into()))
+    }
+
+    /// The name of a contact or company of the workspace, unless it is missing or trashed
+    pub async fn find_name(&self, workspace_id: &str, node: Thing) -> AppResult<Option<String>> {
+        let names: Vec<String> = self
+            .db
+            .client
+            .query(format!(
+                "SELECT VALUE {} FROM $node WHERE workspace = $workspace AND deleted_at = NONE",
+                name_of("id")
+            ))
+            .bind(("node", node))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(names.into_iter().next())
+    }
+
+    pub async fn exists(
+        &self,
+        workspace_id: &str,
+        kind: RelationshipKind,
+        from: &Thing,
+        to: &Thing,
+    ) -> AppResult<bool> {
+        let ids: Vec<Thing> = self
+            .db
+            .client
+            .query(format!(
+                "SELECT VALUE id FROM {} WHERE workspace = $workspace AND in = $from AND out = $to LIMIT 1",
+                kind.table()
+            ))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("from", from.clone()))
+            .bind(("to", to.clone()))
+            .await?
+            .take(0)?;
+
+        Ok(!ids.is_empty())
+    }
+
+    /// Every relationship of the listed records, either way round, newest first
+    pub async fn find_touching(
+        &self,
+        workspace_id: &str,
+        nodes: Vec<Thing>,
+        limit: u32,
+    ) -> AppResult<Vec<Relationship>> {
+        let tables = RelationshipKind::ALL.map(|kind| kind.table()).join(", ");
+        let relationships: Vec<Relationship> = self
+            .db
+            .client
+            .query(format!(
+                "SELECT *, ({}) AS from_name, ({}) AS to_name FROM {} \
+                 WHERE workspace = $workspace AND (in INSIDE $nodes OR out INSIDE $nodes) \
+                    AND in.deleted_at = NONE AND out.deleted_at = NONE \
+                 ORDER BY created_at DESC LIMIT $limit",
+                name_of("in"),
+                name_of("out"),
+                tables
+            ))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("nodes", nodes))
+            .bind(("limit", limit))
+            .await?
+            .take(0)?;
+
+        Ok(relationships)
+    }
+
+    pub async fn delete(&self, workspace_id: &str, kind: RelationshipKind, id: &str) -> AppResult<bool> {
+        Ok(self.db.delete_scoped(kind.table(), id, workspace_id).await?)
+    }
+}
+
+/// SurrealQL for the name of the contact or company `record` links to
+fn name_of(record: &str) -> String {
+    format!(
+        "IF meta::tb({r}) = 'company' THEN {r}.name ELSE string::join(' ', {r}.first_name, {r}.last_name) END",
+        r = record
+    )
+}
@@ -0,0 +1,118 @@
+//! Search Repository - full-text queries against the search indexes
+//!
+//! Each method hits the BM25 indexes defined in the schema migrations for
+//! one table and returns raw hits with their relevance score. Merging and
+//! ranking across tables is the SearchService's job.
+
+use crate::db::{workspace_thing, Database};
+use crate::error::AppResult;
+use serde::Deserialize;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// A contact matching a search term
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContactHit {
+    pub id: Thing,
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+    pub score: Option<f64>,
+}
+
+/// A company matching a search term
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompanyHit {
+    pub id: Thing,
+    pub name: String,
+    pub domain: Option<String>,
+    pub score: Option<f64>,
+}
+
+/// A timeline entry whose content matches a search term
+#[derive(Debug, Clone, Deserialize)]
+pub struct TimelineHit {
+    pub id: Thing,
+    pub contact: Thing,
+    #[serde(rename = "type")]
+    pub entry_type: String,
+    pub content: String,
+    pub snippet: Option<String>,
+    pub score: Option<f64>,
+}
+
+/// Repository for full-text search queries
+pub struct SearchRepository {
+    db: Arc<Database>,
+}
+
+impl SearchRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Search contact first name, last name and email
+    pub async fn search_contacts(&self, workspace_id: &str, term: &str, limit: u32) -> AppResult<Vec<ContactHit>> {
+        let hits: Vec<ContactHit> = self
+            .db
+            .client
+            .query(
+                "SELECT id, first_name, last_name, email, \
+                    (search::score(0) + search::score(1) + search::score(2)) AS score \
+                 FROM contact \
+                 WHERE workspace = $workspace AND deleted_at IS NONE \
+                    AND (first_name @0@ $term OR last_name @1@ $term OR email @2@ $term) \
+                 ORDER BY score DESC LIMIT $limit",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("term", term))
+            .bind(("limit", limit))
+            .await?
+            .take(0)?;
+
+        Ok(hits)
+    }
+
+    /// Search company name and domain
+    pub async fn search_companies(&self, workspace_id: &str, term: &str, limit: u32) -> AppResult<Vec<CompanyHit>> {
+        let hits: Vec<CompanyHit> = self
+            .db
+            .client
+            .query(
+                "SELECT id, name, domain, (search::score(0) + search::score(1)) AS score \
+                 FROM company \
+                 WHERE workspace = $workspace AND deleted_at IS NONE \
+                    AND (name @0@ $term OR domain @1@ $term) \
+                 ORDER BY score DESC LIMIT $limit",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("term", term))
+            .bind(("limit", limit))
+            .await?
+            .take(0)?;
+
+        Ok(hits)
+    }
+
+    /// Search timeline entry content, with the matched terms highlighted
+    pub async fn search_timeline(&self, workspace_id: &str, term: &str, limit: u32) -> AppResult<Vec<TimelineHit>> {
+        let hits: Vec<TimelineHit> = self
+            .db
+            .client
+            .query(
+                "SELECT id, contact, type, content, \
+                    search::highlight('<mark>', '</mark>', 0) AS snippet, \
+                    search::score(0) AS score \
+                 FROM timeline_entry \
+                 WHERE workspace = $workspace AND content @0@ $term \
+                 ORDER BY score DESC LIMIT $limit",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("term", term))
+            .bind(("limit", limit))
+            .await?
+            .take(0)?;
+
+        Ok(hits)
+    }
+}
@@ -0,0 +1,33 @@
+//! Seed Repository - bulk inserts of generated demo data
+
+use crate::db::Database;
+use crate::error::AppResult;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Records inserted per statement
+const SEED_BATCH_SIZE: usize = 500;
+
+pub struct SeedRepository {
+    db: Arc<Database>,
+}
+
+impl SeedRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Insert records into `table` with the IDs they carry, in batches
+    pub async fn insert<T: Serialize>(&self, table: &'static str, records: &[T]) -> AppResult<()> {
+        for batch in records.chunks(SEED_BATCH_SIZE) {
+            self.db
+                .client
+                .query(format!("INSERT INTO {} $records", table))
+                .bind(("records", batch))
+                .await?
+                .check()?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+//! Segment Repository - storage for saved segments
+
+use crate::db::{is_unique_violation, workspace_thing, Database};
+use crate::error::{AppError, AppResult};
+use crate::models::Segment;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Repository for saved Segment records
+pub struct SegmentRepository {
+    db: Arc<Database>,
+}
+
+impl SegmentRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Fails with `Conflict` when the workspace already has a segment of that name
+    pub async fn create(&self, segment: Segment) -> AppResult<Segment> {
+        let created: Vec<Segment> = self
+            .db
+            .client
+            .create("segment")
+            .content(segment)
+            .await
+            .map_err(name_conflict)?;
+
+        created
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::Internal("Failed to create segment".into()))
+    }
+
+    pub async fn find_all(&self, workspace_id: &str) -> AppResult<Vec<Segment>> {
+        let segments: Vec<Segment> = self
+            .db
+            .client
+            .query("SELECT * FROM segment WHERE workspace = $workspace ORDER BY name ASC")
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(segments)
+    }
+
+    pub async fn find_by_id(&self, workspace_id: &str, id: &str) -> AppResult<Option<Segment>> {
+        Ok(self.db.select_scoped("segment", id, workspace_id).await?)
+    }
+
+    /// Segments of every workspace, for the background count refresh
+    pub async fn find_all_workspaces(&self) -> AppResult<Vec<Segment>> {
+        let segments: Vec<Segment> = self.db.client.query("SELECT * FROM segment").await?.take(0)?;
+        Ok(segments)
+    }
+
+    /// Fails with `Conflict` when another segment of the workspace has the new name
+    pub async fn update(&self, id: &str, segment: Segment) -> AppResult<Segment> {
+        let updated: Option<Segment> = self
+            .db
+            .client
+            .update(("segment", id))
+            .content(segment)
+            .await
+            .map_err(name_conflict)?;
+
+        updated.ok_or_else(|| AppError::NotFound("Segment not found".into()))
+    }
+
+    pub async fn update_count(&self, segment: &Thing, count: u64, counted_at: DateTime<Utc>) -> AppResult<()> {
+        self.db
+            .client
+            .query("UPDATE $segment SET member_count = $count, counted_at = $counted_at")
+            .bind(("segment", segment.clone()))
+            .bind(("count", count))
+            .bind(("counted_at", counted_at))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Whether any campaign or campaign template uses the segment as its audience
+    pub async fn is_referenced(&self, workspace_id: &str, id: &str) -> AppResult<bool> {
+        let campaigns: Option<u64> = self
+            .db
+            .client
+            .query(
+                "RETURN array::len((SELECT VALUE id FROM campaign \
+                    WHERE workspace = $workspace AND segment = $segment)) \
+                 + array::len((SELECT VALUE id FROM campaign_template \
+                    WHERE workspace = $workspace AND segment = $segment))",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("segment", Thing::from(("segment", id))))
+            .await?
+            .take(0)?;
+
+        Ok(campaigns.unwrap_or(0) > 0)
+    }
+
+    pub async fn delete(&self, workspace_id: &str, id: &str) -> AppResult<bool> {
+        Ok(self.db.delete_scoped("segment", id, workspace_id).await?)
+    }
+}
+
+/// The unique index on (workspace, name) rejected the write
+fn name_conflict(error: surrealdb::Error) -> AppError {
+    if is_unique_violation(&error) {
+        AppError::Conflict("A segment with this name already exists".into())
+    } else {
+        error.into()
+    }
+}
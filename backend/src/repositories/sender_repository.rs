@@ -0,0 +1,114 @@
+//! Sender Repository - sender identities campaign email goes out with
+//!
+//! Identities without an owner are shared with the workspace; the rest are
+//! visible to their owner only.
+
+use crate::db::{workspace_thing, Database};
+use crate::domain::DnsCheck;
+use crate::error::{AppError, AppResult};
+use crate::models::SenderIdentity;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Repository for SenderIdentity database operations
+pub struct SenderRepository {
+    db: Arc<Database>,
+}
+
+impl SenderRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    pub async fn create(&self, identity: SenderIdentity) -> AppResult<Option<SenderIdentity>> {
+        let created: Vec<SenderIdentity> = self.db.client.create("sender_identity").content(identity).await?;
+        Ok(created.into_iter().next())
+    }
+
+    /// Any identity of the workspace, whoever owns it
+    pub async fn find_by_id(&self, workspace_id: &str, id: &str) -> AppResult<Option<SenderIdentity>> {
+        Ok(self.db.select_scoped("sender_identity", id, workspace_id).await?)
+    }
+
+    /// The workspace's shared identities and the user's own, by address
+    pub async fn find_visible(&self, workspace_id: &str, user_id: &str) -> AppResult<Vec<SenderIdentity>> {
+        let identities: Vec<SenderIdentity> = self
+            .db
+            .client
+            .query(
+                "SELECT * FROM sender_identity \
+                 WHERE workspace = $workspace AND (owner = NONE OR owner = $user) \
+                 ORDER BY from_email ASC, from_name ASC",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("user", Thing::from(("user", user_id))))
+            .await?
+            .take(0)?;
+
+        Ok(identities)
+    }
+
+    pub async fn update(&self, identity: SenderIdentity) -> AppResult<Option<SenderIdentity>> {
+        let id = identity
+            .id
+            .clone()
+            .ok_or_else(|| AppError::Internal("Sender identity has no ID".into()))?;
+        let updated: Option<SenderIdentity> = self
+            .db
+            .client
+            .update(("sender_identity", id.id.to_raw()))
+            .content(identity)
+            .await?;
+
+        Ok(updated)
+    }
+
+    /// Store the outcome of looking up the domain's records
+    pub async fn record_checks(
+        &self,
+        identity: &Thing,
+        ownership: DnsCheck,
+        spf: DnsCheck,
+        dkim: DnsCheck,
+        now: DateTime<Utc>,
+    ) -> AppResult<Option<SenderIdentity>> {
+        let updated: Vec<SenderIdentity> = self
+            .db
+            .client
+            .query(
+                "UPDATE $identity SET ownership = $ownership, spf = $spf, dkim = $dkim, \
+                 checked_at = $now, updated_at = $now RETURN AFTER",
+            )
+            .bind(("identity", identity.clone()))
+            .bind(("ownership", ownership))
+            .bind(("spf", spf))
+            .bind(("dkim", dkim))
+            .bind(("now", now))
+            .await?
+            .take(0)?;
+
+        Ok(updated.into_iter().next())
+    }
+
+    /// Whether a campaign that hasn't finished sends from the identity
+    pub async fn in_use(&self, workspace_id: &str, identity: &Thing) -> AppResult<bool> {
+        let count: Option<u64> = self
+            .db
+            .client
+            .query(
+                "RETURN array::len((SELECT VALUE id FROM campaign \
+                    WHERE workspace = $workspace AND sender = $identity AND status != 'completed' LIMIT 1))",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("identity", identity.clone()))
+            .await?
+            .take(0)?;
+
+        Ok(count.unwrap_or(0) > 0)
+    }
+
+    pub async fn delete(&self, workspace_id: &str, id: &str) -> AppResult<bool> {
+        Ok(self.db.delete_scoped("sender_identity", id, workspace_id).await?)
+    }
+}
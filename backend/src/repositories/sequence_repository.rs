@@ -0,0 +1,376 @@
+//! Sequence Repository - drip sequences and the contacts enrolled in them
+
+use crate::db::{workspace_thing, Database};
+use crate::domain::{EmailActivity, MergeFields, SubscriptionStatus};
+use crate::error::{AppError, AppResult};
+use crate::models::{EnrollmentStatus, Sequence, SequenceEnrollment, TimelineEntry, TimelineEntryType};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// An active enrollment whose next step is due, joined with the contact
+/// data its emails are personalized with
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct DueEnrollment {
+    pub id: Thing,
+    pub workspace: Thing,
+    pub sequence: Thing,
+    pub contact: Thing,
+    pub position: usize,
+    pub last_email_at: Option<DateTime<Utc>>,
+    /// `None` when the contact has been deleted
+    pub email: Option<String>,
+    pub subscription_status: Option<SubscriptionStatus>,
+    #[serde(default)]
+    pub first_name: String,
+    #[serde(default)]
+    pub last_name: String,
+    #[serde(default)]
+    pub company: Option<String>,
+    #[serde(default)]
+    pub custom_fields: BTreeMap<String, String>,
+}
+
+impl DueEnrollment {
+    pub fn merge_fields(&self) -> MergeFields {
+        MergeFields::new(&self.first_name, &self.last_name, self.email.as_deref().unwrap_or_default())
+            .company(self.company.as_deref())
+            .custom_fields(&self.custom_fields)
+    }
+}
+
+/// Repository for Sequence and SequenceEnrollment database operations
+pub struct SequenceRepository {
+    db: Arc<Database>,
+}
+
+impl SequenceRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    pub async fn create(&self, sequence: Sequence) -> AppResult<Sequence> {
+        let created: Vec<Sequence> = self.db.client.create("sequence").content(sequence).await?;
+
+        created
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::Internal("Failed to create sequence".into()))
+    }
+
+    pub async fn find_all(&self, workspace_id: &str) -> AppResult<Vec<Sequence>> {
+        let sequences: Vec<Sequence> = self
+            .db
+            .client
+            .query("SELECT * FROM sequence WHERE workspace = $workspace ORDER BY name ASC")
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(sequences)
+    }
+
+    pub async fn find_by_id(&self, workspace_id: &str, id: &str) -> AppResult<Option<Sequence>> {
+        Ok(self.db.select_scoped("sequence", id, workspace_id).await?)
+    }
+
+    /// Delete a sequence together with its enrollments
+    pub async fn delete(&self, workspace_id: &str, id: &str) -> AppResult<()> {
+        self.db
+            .transaction()
+            .query("DELETE sequence_enrollment WHERE workspace = $workspace AND sequence = $sequence")
+            .query("DELETE $sequence WHERE workspace = $workspace")
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("sequence", Thing::from(("sequence", id))))
+            .commit()
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn count_active(&self, workspace_id: &str, id: &str) -> AppResult<u64> {
+        let count: Option<u64> = self
+            .db
+            .client
+            .query(
+                "RETURN array::len((SELECT VALUE id FROM sequence_enrollment \
+                    WHERE workspace = $workspace AND sequence = $sequence AND status = 'active'))",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("sequence", Thing::from(("sequence", id))))
+            .await?
+            .take(0)?;
+
+        Ok(count.unwrap_or(0))
+    }
+
+    /// List enrollments of a sequence, optionally filtered by status
+    pub async fn find_enrollments(
+        &self,
+        workspace_id: &str,
+        sequence_id: &str,
+        status: Option<EnrollmentStatus>,
+        limit: u32,
+        offset: u32,
+    ) -> AppResult<Vec<SequenceEnrollment>> {
+        let status_clause = if status.is_some() { "AND status = $status" } else { "" };
+        let query = format!(
+            "SELECT * FROM sequence_enrollment \
+             WHERE workspace = $workspace AND sequence = $sequence {} \
+             ORDER BY enrolled_at ASC LIMIT $limit START $offset",
+            status_clause
+        );
+
+        let enrollments: Vec<SequenceEnrollment> = self
+            .db
+            .client
+            .query(query)
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("sequence", Thing::from(("sequence", sequence_id))))
+            .bind(("status", status))
+            .bind(("limit", limit))
+            .bind(("offset", offset))
+            .await?
+            .take(0)?;
+
+        Ok(enrollments)
+    }
+
+    /// Start contacts at the first step of a sequence
+    ///
+    /// Contacts who completed or left the sequence start over; those still
+    /// active in it are left alone. Returns the number of contacts enrolled.
+    pub async fn enroll(&self, workspace_id: &str, sequence_id: &str, contacts: Vec<Thing>) -> AppResult<usize> {
+        let workspace = workspace_thing(workspace_id);
+        let sequence = Thing::from(("sequence", sequence_id));
+
+        let existing: Vec<SequenceEnrollment> = self
+            .db
+            .client
+            .query(
+                "SELECT * FROM sequence_enrollment \
+                 WHERE workspace = $workspace AND sequence = $sequence AND contact INSIDE $contacts",
+            )
+            .bind(("workspace", workspace.clone()))
+            .bind(("sequence", sequence.clone()))
+            .bind(("contacts", contacts.clone()))
+            .await?
+            .take(0)?;
+
+        let active: HashSet<String> = existing
+            .iter()
+            .filter(|e| e.status == EnrollmentStatus::Active)
+            .map(|e| e.contact.id.to_raw())
+            .collect();
+        let known: HashSet<String> = existing.iter().map(|e| e.contact.id.to_raw()).collect();
+
+        let now = Utc::now();
+        let restart: Vec<Thing> = contacts
+            .iter()
+            .filter(|c| known.contains(&c.id.to_raw()) && !active.contains(&c.id.to_raw()))
+            .cloned()
+            .collect();
+        let new_enrollments: Vec<SequenceEnrollment> = contacts
+            .into_iter()
+            .filter(|c| !known.contains(&c.id.to_raw()))
+            .map(|contact| SequenceEnrollment {
+                id: None,
+                workspace: workspace.clone(),
+                sequence: sequence.clone(),
+                contact,
+                status: EnrollmentStatus::Active,
+                position: 0,
+                next_run_at: Some(now),
+                last_email_at: None,
+                enrolled_at: now,
+                ended_at: None,
+                updated_at: now,
+            })
+            .collect();
+
+        let enrolled = restart.len() + new_enrollments.len();
+        if enrolled == 0 {
+            return Ok(0);
+        }
+
+        let mut tx = self
+            .db
+            .transaction()
+            .query(
+                "UPDATE sequence_enrollment SET status = 'active', position = 0, next_run_at = $now, \
+                    last_email_at = NONE, enrolled_at = $now, ended_at = NONE, updated_at = $now \
+                 WHERE workspace = $workspace AND sequence = $sequence AND contact INSIDE $restart",
+            )
+            .bind(("workspace", workspace))
+            .bind(("sequence", sequence))
+            .bind(("restart", restart))
+            .bind(("now", now));
+        if !new_enrollments.is_empty() {
+            tx = tx
+                .query("INSERT INTO sequence_enrollment $enrollments")
+                .bind(("enrollments", new_enrollments));
+        }
+        tx.commit().await?;
+
+        Ok(enrolled)
+    }
+
+    /// Take contacts out of a sequence
+    ///
+    /// Returns the number of active enrollments ended.
+    pub async fn unenroll(&self, workspace_id: &str, sequence_id: &str, contacts: Vec<Thing>) -> AppResult<usize> {
+        let ended: Vec<SequenceEnrollment> = self
+            .db
+            .client
+            .query(
+                "UPDATE sequence_enrollment SET status = 'unenrolled', next_run_at = NONE, \
+                    ended_at = $now, updated_at = $now \
+                 WHERE workspace = $workspace AND sequence = $sequence \
+                    AND status = 'active' AND contact INSIDE $contacts \
+                 RETURN AFTER",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("sequence", Thing::from(("sequence", sequence_id))))
+            .bind(("contacts", contacts))
+            .bind(("now", Utc::now()))
+            .await?
+            .take(0)?;
+
+        Ok(ended.len())
+    }
+
+    /// End a contact's active enrollments because they wrote back
+    ///
+    /// Enrollments that started after the reply carry on. Returns the number
+    /// of enrollments ended.
+    pub async fn end_replied(
+        &self,
+        workspace_id: &str,
+        contact: &Thing,
+        replied_at: DateTime<Utc>,
+    ) -> AppResult<usize> {
+        let ended: Vec<SequenceEnrollment> = self
+            .db
+            .client
+            .query(
+                "UPDATE sequence_enrollment SET status = 'replied', next_run_at = NONE, \
+                    ended_at = $now, updated_at = $now \
+                 WHERE workspace = $workspace AND contact = $contact \
+                    AND status = 'active' AND enrolled_at <= $replied_at \
+                 RETURN AFTER",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("contact", contact.clone()))
+            .bind(("replied_at", replied_at))
+            .bind(("now", Utc::now()))
+            .await?
+            .take(0)?;
+
+        Ok(ended.len())
+    }
+
+    /// Active enrollments whose next step is due, in every workspace
+    ///
+    /// The contact's subscription status and personalization data are read
+    /// now, so an unsubscribe since enrollment is honoured.
+    pub async fn find_due(&self, now: DateTime<Utc>, limit: u32) -> AppResult<Vec<DueEnrollment>> {
+        let due: Vec<DueEnrollment> = self
+            .db
+            .client
+            .query(
+                "SELECT id, workspace, sequence, contact, position, last_email_at, \
+                    contact.email AS email, contact.subscription_status AS subscription_status, \
+                    contact.first_name AS first_name, contact.last_name AS last_name, \
+                    contact.company.name AS company, contact.custom_fields AS custom_fields \
+                 FROM sequence_enrollment \
+                 WHERE status = 'active' AND next_run_at <= $now \
+                 ORDER BY next_run_at ASC LIMIT $limit",
+            )
+            .bind(("now", now))
+            .bind(("limit", limit))
+            .await?
+            .take(0)?;
+
+        Ok(due)
+    }
+
+    /// Opens and clicks a contact is recorded with since `since`
+    pub async fn email_activity(
+        &self,
+        workspace: &Thing,
+        contact: &Thing,
+        since: DateTime<Utc>,
+    ) -> AppResult<EmailActivity> {
+        let types: Vec<TimelineEntryType> = self
+            .db
+            .client
+            .query(
+                "SELECT VALUE type FROM timeline_entry \
+                 WHERE workspace = $workspace AND contact = $contact \
+                    AND type INSIDE ['email_open', 'email_click'] AND timestamp >= $since",
+            )
+            .bind(("workspace", workspace.clone()))
+            .bind(("contact", contact.clone()))
+            .bind(("since", since))
+            .await?
+            .take(0)?;
+
+        Ok(EmailActivity {
+            opened: types.iter().any(|t| matches!(t, TimelineEntryType::EmailOpen)),
+            clicked: types.iter().any(|t| matches!(t, TimelineEntryType::EmailClick)),
+        })
+    }
+
+    /// Store where an enrollment goes next, with the timeline entries of
+    /// the emails sent on the way
+    ///
+    /// With no `next_run_at` the enrollment ends with `status`. Both are
+    /// written in one transaction, and only while the enrollment is still
+    /// active; returns whether it was.
+    pub async fn update_progress(
+        &self,
+        enrollment: &Thing,
+        status: EnrollmentStatus,
+        position: usize,
+        next_run_at: Option<DateTime<Utc>>,
+        last_email_at: Option<DateTime<Utc>>,
+        entries: Vec<TimelineEntry>,
+    ) -> AppResult<bool> {
+        let now = Utc::now();
+        let ended_at = next_run_at.is_none().then_some(now);
+
+        self.db
+            .transaction()
+            .query(
+                "LET $advanced = (UPDATE $enrollment SET status = $status, position = $position, \
+                    next_run_at = $next_run_at, last_email_at = $last_email_at, ended_at = $ended_at, \
+                    updated_at = $now WHERE status = 'active' RETURN AFTER)",
+            )
+            .query(
+                "IF array::len($advanced) > 0 AND array::len($entries) > 0 \
+                 THEN (INSERT INTO timeline_entry $entries) END",
+            )
+            .bind(("enrollment", enrollment.clone()))
+            .bind(("entries", entries))
+            .bind(("status", status))
+            .bind(("position", position))
+            .bind(("next_run_at", next_run_at))
+            .bind(("last_email_at", last_email_at))
+            .bind(("ended_at", ended_at))
+            .bind(("now", now))
+            .commit()
+            .await?;
+
+        // The batch returns nothing, so check whether the update above took
+        let updated_at: Option<DateTime<Utc>> = self
+            .db
+            .client
+            .query("SELECT VALUE updated_at FROM ONLY $enrollment")
+            .bind(("enrollment", enrollment.clone()))
+            .await?
+            .take(0)?;
+
+        Ok(updated_at == Some(now))
+    }
+}
@@ -0,0 +1,102 @@
+//! Short Link Repository - per-recipient shortened URLs
+//!
+//! Codes are unique across workspaces: `/r/:code` is looked up before the
+//! workspace is known.
+
+use crate::db::{workspace_thing, Database};
+use crate::error::AppResult;
+use crate::models::{LinkChannel, ShortLink};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Repository for ShortLink database operations
+pub struct ShortLinkRepository {
+    db: Arc<Database>,
+}
+
+impl ShortLinkRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    pub async fn create_many(&self, links: Vec<ShortLink>) -> AppResult<Vec<ShortLink>> {
+        if links.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let created: Vec<ShortLink> = self
+            .db
+            .client
+            .query("INSERT INTO short_link $links")
+            .bind(("links", links))
+            .await?
+            .take(0)?;
+
+        Ok(created)
+    }
+
+    /// Links to a URL the contacts already have for the campaign and channel
+    pub async fn find_existing(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        channel: LinkChannel,
+        url: &str,
+        contacts: &[Thing],
+    ) -> AppResult<Vec<ShortLink>> {
+        let links: Vec<ShortLink> = self
+            .db
+            .client
+            .query(
+                "SELECT * FROM short_link \
+                 WHERE workspace = $workspace AND campaign = $campaign AND channel = $channel \
+                    AND url = $url AND contact INSIDE $contacts",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("campaign", Thing::from(("campaign", campaign_id))))
+            .bind(("channel", channel))
+            .bind(("url", url.to_string()))
+            .bind(("contacts", contacts.to_vec()))
+            .await?
+            .take(0)?;
+
+        Ok(links)
+    }
+
+    /// Links of a campaign, oldest first
+    pub async fn find_by_campaign(&self, workspace_id: &str, campaign_id: &str) -> AppResult<Vec<ShortLink>> {
+        let links: Vec<ShortLink> = self
+            .db
+            .client
+            .query(
+                "SELECT * FROM short_link WHERE workspace = $workspace AND campaign = $campaign \
+                 ORDER BY created_at ASC",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("campaign", Thing::from(("campaign", campaign_id))))
+            .await?
+            .take(0)?;
+
+        Ok(links)
+    }
+
+    /// Count a click on the link with the code, in any workspace
+    ///
+    /// Returns the link, or None when no link has the code.
+    pub async fn record_click(&self, code: &str, now: DateTime<Utc>) -> AppResult<Option<ShortLink>> {
+        let updated: Vec<ShortLink> = self
+            .db
+            .client
+            .query(
+                "UPDATE short_link SET clicks += 1, last_clicked_at = $now \
+                 WHERE code = $code RETURN AFTER",
+            )
+            .bind(("code", code.to_string()))
+            .bind(("now", now))
+            .await?
+            .take(0)?;
+
+        Ok(updated.into_iter().next())
+    }
+}
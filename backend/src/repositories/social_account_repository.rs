@@ -0,0 +1,108 @@
+//! Social Account Repository - the account each workspace publishes to a platform with
+//!
+//! A workspace has at most one account per platform, stored at
+//! `social_account:<workspace id>_<platform>`, so connecting again is an
+//! UPDATE that replaces the token.
+
+use crate::db::{workspace_thing, Database};
+use crate::error::{AppError, AppResult};
+use crate::models::{SocialAccount, SocialPlatform};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Repository for SocialAccount database operations
+pub struct SocialAccountRepository {
+    db: Arc<Database>,
+}
+
+impl SocialAccountRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Connect the workspace's account for a platform, replacing any earlier one
+    pub async fn save(
+        &self,
+        workspace_id: &str,
+        platform: SocialPlatform,
+        access_token: &str,
+        author_urn: Option<String>,
+        now: DateTime<Utc>,
+    ) -> AppResult<SocialAccount> {
+        let saved: Option<SocialAccount> = self
+            .db
+            .client
+            .query(
+                "UPDATE $account SET workspace = $workspace, platform = $platform, \
+                    access_token = $access_token, author_urn = $author_urn, updated_at = $now",
+            )
+            .bind(("account", account_thing(workspace_id, platform)))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("platform", platform))
+            .bind(("access_token", access_token.to_string()))
+            .bind(("author_urn", author_urn))
+            .bind(("now", now))
+            .await?
+            .take(0)?;
+
+        saved.ok_or_else(|| AppError::Internal("Failed to save social account".into()))
+    }
+
+    pub async fn find(&self, workspace_id: &str, platform: SocialPlatform) -> AppResult<Option<SocialAccount>> {
+        let account: Option<SocialAccount> = self
+            .db
+            .client
+            .query("SELECT * FROM $account WHERE workspace = $workspace")
+            .bind(("account", account_thing(workspace_id, platform)))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(account)
+    }
+
+    pub async fn find_all(&self, workspace_id: &str) -> AppResult<Vec<SocialAccount>> {
+        let accounts: Vec<SocialAccount> = self
+            .db
+            .client
+            .query("SELECT * FROM social_account WHERE workspace = $workspace ORDER BY platform ASC")
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(accounts)
+    }
+
+    /// Disconnect an account; false if none was connected
+    pub async fn delete(&self, workspace_id: &str, platform: SocialPlatform) -> AppResult<bool> {
+        let deleted: Vec<SocialAccount> = self
+            .db
+            .client
+            .query("DELETE $account WHERE workspace = $workspace RETURN BEFORE")
+            .bind(("account", account_thing(workspace_id, platform)))
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(!deleted.is_empty())
+    }
+}
+
+fn account_thing(workspace_id: &str, platform: SocialPlatform) -> Thing {
+    let key = format!("{}_{}", workspace_id, format!("{:?}", platform).to_lowercase());
+    Thing::from(("social_account", key.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_account_per_workspace_and_platform() {
+        let linkedin = account_thing("ws1", SocialPlatform::LinkedIn);
+        assert_eq!(linkedin.to_string(), "social_account:ws1_linkedin");
+        assert_ne!(linkedin, account_thing("ws2", SocialPlatform::LinkedIn));
+        assert_ne!(linkedin, account_thing("ws1", SocialPlatform::Twitter));
+    }
+}
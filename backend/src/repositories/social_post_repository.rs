@@ -0,0 +1,195 @@
+//! Social Post Repository - campaign posts queued for social platforms
+//!
+//! Due posts are claimed by marking them publishing and pushing
+//! `next_attempt_at` past a lease, so a post is never published twice by
+//! overlapping runs. A post still publishing once its lease is over was cut
+//! off mid-request; it is marked unconfirmed instead of being tried again.
+
+use crate::db::{workspace_thing, Database};
+use crate::error::AppResult;
+use crate::models::{SocialPost, SocialPostStatus};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Repository for SocialPost database operations
+pub struct SocialPostRepository {
+    db: Arc<Database>,
+}
+
+impl SocialPostRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    pub async fn create_many(&self, posts: Vec<SocialPost>) -> AppResult<Vec<SocialPost>> {
+        if posts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let created: Vec<SocialPost> = self
+            .db
+            .client
+            .query("INSERT INTO social_post $posts")
+            .bind(("posts", posts))
+            .await?
+            .take(0)?;
+
+        Ok(created)
+    }
+
+    /// Posts of a campaign, in publishing order
+    pub async fn find_by_campaign(&self, workspace_id: &str, campaign_id: &str) -> AppResult<Vec<SocialPost>> {
+        let posts: Vec<SocialPost> = self
+            .db
+            .client
+            .query(
+                "SELECT * FROM social_post WHERE workspace = $workspace AND campaign = $campaign \
+                 ORDER BY publish_at ASC",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("campaign", Thing::from(("campaign", campaign_id))))
+            .await?
+            .take(0)?;
+
+        Ok(posts)
+    }
+
+    pub async fn has_posts(&self, workspace_id: &str, campaign_id: &str) -> AppResult<bool> {
+        let count: Option<u64> = self
+            .db
+            .client
+            .query(
+                "RETURN array::len((SELECT VALUE id FROM social_post \
+                    WHERE workspace = $workspace AND campaign = $campaign LIMIT 1))",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("campaign", Thing::from(("campaign", campaign_id))))
+            .await?
+            .take(0)?;
+
+        Ok(count.unwrap_or(0) > 0)
+    }
+
+    /// Mark posts whose publishing lease ran out as unconfirmed; returns how many
+    pub async fn expire_in_flight(&self, now: DateTime<Utc>) -> AppResult<usize> {
+        let expired: Vec<SocialPost> = self
+            .db
+            .client
+            .query(
+                "UPDATE social_post SET status = 'unconfirmed', updated_at = $now, \
+                    error = 'Publishing was interrupted; check the platform before scheduling it again' \
+                    WHERE status = 'publishing' AND next_attempt_at <= $now",
+            )
+            .bind(("now", now))
+            .await?
+            .take(0)?;
+
+        Ok(expired.len())
+    }
+
+    /// Claim scheduled posts that are due, in every workspace
+    pub async fn claim_due(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: u32,
+    ) -> AppResult<Vec<SocialPost>> {
+        let mut response = self
+            .db
+            .client
+            .query(
+                "LET $due = (SELECT VALUE id FROM social_post \
+                    WHERE status = 'scheduled' AND next_attempt_at <= $now \
+                    ORDER BY next_attempt_at ASC LIMIT $limit)",
+            )
+            .query(
+                "UPDATE $due SET status = 'publishing', next_attempt_at = $lease_until \
+                    WHERE status = 'scheduled' AND next_attempt_at <= $now RETURN AFTER",
+            )
+            .bind(("now", now))
+            .bind(("lease_until", lease_until))
+            .bind(("limit", limit))
+            .await?;
+
+        let claimed: Vec<SocialPost> = response.take(1)?;
+        Ok(claimed)
+    }
+
+    /// Mark a post live and write its URL back to the asset it came from
+    ///
+    /// An asset that already has a URL, from another platform's post or set
+    /// by hand, keeps it.
+    pub async fn mark_published(
+        &self,
+        post: &Thing,
+        asset: &Thing,
+        url: &str,
+        external_id: &str,
+        attempts: u32,
+        now: DateTime<Utc>,
+    ) -> AppResult<()> {
+        self.db
+            .transaction()
+            .query(
+                "UPDATE $post SET status = 'published', attempts = $attempts, url = $url, \
+                    external_id = $external_id, error = NONE, published_at = $now, updated_at = $now",
+            )
+            .query("UPDATE $asset SET url = $url WHERE url IS NONE")
+            .bind(("post", post.clone()))
+            .bind(("asset", asset.clone()))
+            .bind(("attempts", attempts))
+            .bind(("url", url.to_string()))
+            .bind(("external_id", external_id.to_string()))
+            .bind(("now", now))
+            .commit()
+            .await?;
+
+        Ok(())
+    }
+
+    /// Record an attempt that may have gone live; it is not retried
+    pub async fn record_unconfirmed(&self, post: &Thing, attempts: u32, error: &str) -> AppResult<()> {
+        self.db
+            .client
+            .query(
+                "UPDATE $post SET status = 'unconfirmed', attempts = $attempts, error = $error, \
+                    updated_at = $now",
+            )
+            .bind(("post", post.clone()))
+            .bind(("attempts", attempts))
+            .bind(("error", error.to_string()))
+            .bind(("now", Utc::now()))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Record a failed attempt and when, if ever, to try again
+    pub async fn record_failure(
+        &self,
+        post: &Thing,
+        attempts: u32,
+        status: SocialPostStatus,
+        next_attempt_at: DateTime<Utc>,
+        error: &str,
+    ) -> AppResult<()> {
+        self.db
+            .client
+            .query(
+                "UPDATE $post SET status = $status, attempts = $attempts, \
+                    next_attempt_at = $next_attempt_at, error = $error, updated_at = $now",
+            )
+            .bind(("post", post.clone()))
+            .bind(("status", status))
+            .bind(("attempts", attempts))
+            .bind(("next_attempt_at", next_attempt_at))
+            .bind(("error", error.to_string()))
+            .bind(("now", Utc::now()))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,315 @@
+//! Timeline Repository - storage and filtered queries for timeline entries
+
+use crate::db::{is_unique_violation, workspace_thing, Database};
+use crate::error::{AppError, AppResult};
+use crate::models::{DailyActivityCount, Sentiment, TimelineEntry, TimelineEntryType, TimelineQuery};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use surrealdb::engine::any::Any;
+use surrealdb::method::Query;
+use surrealdb::sql::Thing;
+
+/// Most email entries one thread or response-time read loads
+pub const MAX_EMAILS_READ: u32 = 10_000;
+
+/// An email entry, with only what threading needs
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmailEntry {
+    pub id: Thing,
+    pub contact: Thing,
+    #[serde(rename = "type")]
+    pub entry_type: TimelineEntryType,
+    pub content: String,
+    pub metadata: serde_json::Value,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// An entry as stored, with the fields only storage reads
+#[derive(Serialize)]
+struct StoredEntry {
+    #[serde(flatten)]
+    entry: TimelineEntry,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    synced_message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sentiment_pending: Option<bool>,
+}
+
+impl StoredEntry {
+    fn new(entry: TimelineEntry, synced_message: Option<String>, rate_sentiment: bool) -> Self {
+        Self {
+            entry,
+            synced_message,
+            sentiment_pending: rate_sentiment.then_some(true),
+        }
+    }
+}
+
+/// Repository for TimelineEntry database operations
+pub struct TimelineRepository {
+    db: Arc<Database>,
+}
+
+impl TimelineRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    pub async fn create(&self, entry: TimelineEntry) -> AppResult<TimelineEntry> {
+        self.create_queued(entry, false).await
+    }
+
+    /// Store an entry; `rate_sentiment` queues it for the sentiment job
+    pub async fn create_queued(&self, entry: TimelineEntry, rate_sentiment: bool) -> AppResult<TimelineEntry> {
+        let created: Vec<TimelineEntry> = self
+            .db
+            .client
+            .create("timeline_entry")
+            .content(StoredEntry::new(entry, None, rate_sentiment))
+            .await?;
+
+        created
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::Internal("Failed to create timeline entry".into()))
+    }
+
+    /// Record an email synced from a mailbox
+    ///
+    /// `None` when the message is already on a timeline in the workspace;
+    /// entries carry a key unique per workspace and message, so two syncs
+    /// racing to record one can't both succeed.
+    pub async fn create_synced(
+        &self,
+        entry: TimelineEntry,
+        message_id: &str,
+        rate_sentiment: bool,
+    ) -> AppResult<Option<TimelineEntry>> {
+        let synced_message = synced_message_key(&entry.workspace.id.to_raw(), message_id);
+        let created: Result<Vec<TimelineEntry>, _> = self
+            .db
+            .client
+            .create("timeline_entry")
+            .content(StoredEntry::new(entry, Some(synced_message), rate_sentiment))
+            .await;
+
+        match created {
+            Ok(created) => created
+                .into_iter()
+                .next()
+                .map(Some)
+                .ok_or_else(|| AppError::Internal("Failed to create timeline entry".into())),
+            Err(e) if is_unique_violation(&e) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Entries of any workspace queued for the sentiment job, oldest first
+    pub async fn find_pending_sentiment(&self, limit: u32) -> AppResult<Vec<TimelineEntry>> {
+        let entries: Vec<TimelineEntry> = self
+            .db
+            .client
+            .query("SELECT * FROM timeline_entry WHERE sentiment_pending = true ORDER BY timestamp ASC LIMIT $limit")
+            .bind(("limit", limit))
+            .await?
+            .take(0)?;
+
+        Ok(entries)
+    }
+
+    /// Take an entry off the sentiment queue, with the rating if there is one
+    ///
+    /// A sentiment logged since the entry was queued is kept.
+    pub async fn finish_sentiment(&self, id: &Thing, sentiment: Option<Sentiment>) -> AppResult<()> {
+        self.db
+            .client
+            .query("UPDATE $entry SET sentiment = sentiment ?? $sentiment, sentiment_pending = NONE")
+            .bind(("entry", id.clone()))
+            .bind(("sentiment", sentiment))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// List matching entries, newest first
+    pub async fn find(&self, workspace_id: &str, query: &TimelineQuery) -> AppResult<Vec<TimelineEntry>> {
+        let query_str = format!(
+            "SELECT * FROM timeline_entry WHERE {} ORDER BY timestamp DESC LIMIT $limit START $offset",
+            conditions(query).join(" AND ")
+        );
+
+        let query_str = query_str.as_str();
+
+        let entries: Vec<TimelineEntry> = self
+            .db
+            .retry(move || async move {
+                let db_query = self
+                    .db
+                    .client
+                    .query(query_str)
+                    .bind(("workspace", workspace_thing(workspace_id)))
+                    .bind(("limit", query.limit.unwrap_or(50).min(500)))
+                    .bind(("offset", query.offset.unwrap_or(0)));
+                bind_filters(db_query, query).await?.take(0)
+            })
+            .await?;
+
+        Ok(entries)
+    }
+
+    /// Sent and received email of one contact, or of the whole workspace, oldest first
+    ///
+    /// Only email from `since` on when it is given; at most `MAX_EMAILS_READ`
+    /// entries, the most recent ones.
+    pub async fn find_emails(
+        &self,
+        workspace_id: &str,
+        contact_id: Option<&str>,
+        since: Option<DateTime<Utc>>,
+    ) -> AppResult<Vec<EmailEntry>> {
+        let mut conditions = vec!["workspace = $workspace", "type INSIDE ['email_sent', 'email_received']"];
+        if contact_id.is_some() {
+            conditions.push("contact = $contact");
+        }
+        if since.is_some() {
+            conditions.push("timestamp >= $since");
+        }
+
+        let query_str = format!(
+            "SELECT id, contact, type, content, metadata, timestamp FROM timeline_entry \
+             WHERE {} ORDER BY timestamp DESC LIMIT $limit",
+            conditions.join(" AND ")
+        );
+        let query_str = query_str.as_str();
+
+        let mut emails: Vec<EmailEntry> = self
+            .db
+            .retry(move || async move {
+                self.db
+                    .client
+                    .query(query_str)
+                    .bind(("workspace", workspace_thing(workspace_id)))
+                    .bind(("contact", contact_id.map(|id| Thing::from(("contact", id)))))
+                    .bind(("since", since))
+                    .bind(("limit", MAX_EMAILS_READ))
+                    .await?
+                    .take(0)
+            })
+            .await?;
+
+        emails.reverse();
+        Ok(emails)
+    }
+
+    /// Number of matching entries per UTC day, oldest day first
+    ///
+    /// Days without activity are left out.
+    pub async fn daily_counts(
+        &self,
+        workspace_id: &str,
+        query: &TimelineQuery,
+    ) -> AppResult<Vec<DailyActivityCount>> {
+        let query_str = format!(
+            "SELECT time::format(timestamp, '%Y-%m-%d') AS date, count() AS count FROM timeline_entry \
+             WHERE {} GROUP BY date ORDER BY date ASC",
+            conditions(query).join(" AND ")
+        );
+
+        let query_str = query_str.as_str();
+
+        let counts: Vec<DailyActivityCount> = self
+            .db
+            .retry(move || async move {
+                let db_query = self
+                    .db
+                    .client
+                    .query(query_str)
+                    .bind(("workspace", workspace_thing(workspace_id)));
+                bind_filters(db_query, query).await?.take(0)
+            })
+            .await?;
+
+        Ok(counts)
+    }
+}
+
+fn conditions(query: &TimelineQuery) -> Vec<&'static str> {
+    let mut conditions = vec!["workspace = $workspace"];
+    if query.contact_id.is_some() {
+        conditions.push("contact = $contact");
+    }
+    if query.company_id.is_some() {
+        conditions.push("company = $company");
+    }
+    if query.campaign_id.is_some() {
+        conditions.push("campaign = $campaign");
+    }
+    if query.entry_type.is_some() {
+        conditions.push("type = $entry_type");
+    }
+    if query.from.is_some() {
+        conditions.push("timestamp >= $from");
+    }
+    if query.to.is_some() {
+        conditions.push("timestamp < $to");
+    }
+    if query.search.is_some() {
+        conditions.push("string::contains(string::lowercase(content), $search)");
+    }
+    conditions
+}
+
+/// Bind the filter values under the parameter names `conditions` uses
+fn bind_filters<'a>(db_query: Query<'a, Any>, query: &TimelineQuery) -> Query<'a, Any> {
+    db_query
+        .bind(("contact", query.contact_id.as_deref().map(|id| Thing::from(("contact", id)))))
+        .bind(("company", query.company_id.as_deref().map(|id| Thing::from(("company", id)))))
+        .bind(("campaign", query.campaign_id.as_deref().map(|id| Thing::from(("campaign", id)))))
+        .bind(("entry_type", query.entry_type.clone()))
+        .bind(("from", query.from))
+        .bind(("to", query.to))
+        .bind(("search", query.search.as_deref().map(str::to_lowercase)))
+}
+
+/// Key of an email synced from a mailbox, unique per workspace and message
+fn synced_message_key(workspace_id: &str, message_id: &str) -> String {
+    format!("{}:{}", workspace_id, message_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synced_message_key_is_per_workspace() {
+        assert_eq!(synced_message_key("acme", "<1@mail>"), "acme:<1@mail>");
+        assert_ne!(synced_message_key("acme", "<1@mail>"), synced_message_key("globex", "<1@mail>"));
+    }
+
+    #[test]
+    fn test_stored_entry_is_flagged_only_when_queued_for_sentiment() {
+        let entry = TimelineEntry {
+            id: None,
+            workspace: workspace_thing("acme"),
+            contact: Thing::from(("contact", "ada")),
+            company: None,
+            campaign: None,
+            entry_type: TimelineEntryType::Note,
+            content: "Called about renewal".into(),
+            metadata: serde_json::json!({}),
+            attachments: Vec::new(),
+            sentiment: None,
+            timestamp: Utc::now(),
+        };
+
+        let queued = serde_json::to_value(StoredEntry::new(entry.clone(), None, true)).unwrap();
+        assert_eq!(queued["sentiment_pending"], true);
+        assert!(queued.get("synced_message").is_none());
+
+        let stored = serde_json::to_value(StoredEntry::new(entry, Some("acme:<1@mail>".into()), false)).unwrap();
+        assert!(stored.get("sentiment_pending").is_none());
+        assert_eq!(stored["synced_message"], "acme:<1@mail>");
+    }
+}
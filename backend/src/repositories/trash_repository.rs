@@ -0,0 +1,250 @@
+//! Trash Repository - soft-deleted contacts and companies
+//!
+//! Trashing and restoring single records goes through
+//! `Database::soft_delete_scoped` / `Database::restore_scoped`; this
+//! repository lists what is in the trash and purges it.
+
+use crate::db::{workspace_thing, Database};
+use crate::error::AppResult;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// A trashed record with just enough to show it in a list
+#[derive(Debug, Clone, Deserialize)]
+pub struct TrashedRecord {
+    pub id: Thing,
+    pub name: String,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// Records removed by one purge run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PurgeCounts {
+    pub contacts: usize,
+    pub companies: usize,
+    /// Object storage keys of the purged contacts' attachments
+    pub storage_keys: Vec<String>,
+    /// The purged contacts' uploaded avatars
+    pub avatars: Vec<PurgedAvatar>,
+}
+
+/// Avatar of a purged contact, whose files are still in storage
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PurgedAvatar {
+    pub workspace_id: String,
+    pub avatar_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct StoredFile {
+    contact: Thing,
+    storage_key: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct StoredAvatar {
+    id: Thing,
+    workspace: Thing,
+    avatar_url: String,
+}
+
+/// Repository for the trash
+pub struct TrashRepository {
+    db: Arc<Database>,
+}
+
+impl TrashRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Trashed contacts, most recently deleted first
+    pub async fn find_contacts(&self, workspace_id: &str, limit: u32) -> AppResult<Vec<TrashedRecord>> {
+        let records: Vec<TrashedRecord> = self
+            .db
+            .client
+            .query(
+                "SELECT id, string::concat(first_name, ' ', last_name) AS name, deleted_at FROM contact \
+                 WHERE workspace = $workspace AND deleted_at IS NOT NONE \
+                 ORDER BY deleted_at DESC LIMIT $limit",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("limit", limit))
+            .await?
+            .take(0)?;
+
+        Ok(records)
+    }
+
+    /// Trashed companies, most recently deleted first
+    pub async fn find_companies(&self, workspace_id: &str, limit: u32) -> AppResult<Vec<TrashedRecord>> {
+        let records: Vec<TrashedRecord> = self
+            .db
+            .client
+            .query(
+                "SELECT id, name, deleted_at FROM company \
+                 WHERE workspace = $workspace AND deleted_at IS NOT NONE \
+                 ORDER BY deleted_at DESC LIMIT $limit",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("limit", limit))
+            .await?
+            .take(0)?;
+
+        Ok(records)
+    }
+
+    /// Permanently delete everything trashed before `cutoff`, in every workspace
+    ///
+    /// Rows that only make sense with a purged contact (its timeline,
+    /// deals, RSVPs, links, status history, relationship edges and so on)
+    /// go with it in the same transaction. Contacts, deals and timeline
+    /// entries of a purged company are kept with the link cleared. The
+    /// attachment and avatar files are left for the caller to remove from
+    /// storage.
+    pub async fn purge(&self, cutoff: DateTime<Utc>) -> AppResult<PurgeCounts> {
+        let mut response = self
+            .db
+            .client
+            .query("SELECT VALUE id FROM contact WHERE deleted_at IS NOT NONE AND deleted_at < $cutoff")
+            .query("SELECT VALUE id FROM company WHERE deleted_at IS NOT NONE AND deleted_at < $cutoff")
+            .bind(("cutoff", cutoff))
+            .await?;
+
+        let contacts: Vec<Thing> = response.take(0)?;
+        let companies: Vec<Thing> = response.take(1)?;
+        if contacts.is_empty() && companies.is_empty() {
+            return Ok(PurgeCounts::default());
+        }
+
+        // Looked up first: the attachment rows and contacts go in the transaction
+        let mut response = self
+            .db
+            .client
+            .query("SELECT contact, storage_key FROM attachment WHERE contact INSIDE $contacts")
+            .query("SELECT id, workspace, avatar_url FROM contact WHERE id INSIDE $contacts AND avatar_url IS NOT NONE")
+            .bind(("contacts", contacts.clone()))
+            .await?;
+        let files: Vec<StoredFile> = response.take(0)?;
+        let avatars: Vec<StoredAvatar> = response.take(1)?;
+
+        // Anything restored since the lookup above is left alone
+        let mut tx = self.db.transaction().query(
+            "LET $contacts = (SELECT VALUE id FROM contact \
+             WHERE id INSIDE $candidates AND deleted_at IS NOT NONE AND deleted_at < $cutoff)",
+        );
+        for table in CONTACT_ROWS {
+            tx = tx.query(format!("DELETE {} WHERE contact INSIDE $contacts", table));
+        }
+        tx.query("DELETE dedupe_suggestion WHERE contact INSIDE $contacts OR duplicate INSIDE $contacts")
+            .query("DELETE introduced_by, works_with, invested_in WHERE in INSIDE $contacts OR out INSIDE $contacts")
+            .query("UPDATE landing_page_visit SET contact = NONE WHERE contact INSIDE $contacts")
+            .query(
+                "DELETE import_ref WHERE kind = 'contact' \
+                 AND record_id INSIDE (SELECT VALUE meta::id(id) FROM contact WHERE id INSIDE $contacts)",
+            )
+            .query("DELETE contact WHERE id INSIDE $contacts")
+            .query(
+                "LET $companies = (SELECT VALUE id FROM company \
+                 WHERE id INSIDE $company_candidates AND deleted_at IS NOT NONE AND deleted_at < $cutoff)",
+            )
+            .query("UPDATE contact, deal, timeline_entry SET company = NONE WHERE company INSIDE $companies")
+            .query(
+                "DELETE import_ref WHERE kind = 'company' \
+                 AND record_id INSIDE (SELECT VALUE meta::id(id) FROM company WHERE id INSIDE $companies)",
+            )
+            .query("DELETE company WHERE id INSIDE $companies")
+            .bind(("candidates", contacts.clone()))
+            .bind(("company_candidates", companies.clone()))
+            .bind(("cutoff", cutoff))
+            .commit()
+            .await?;
+
+        // Contacts restored before the transaction keep their files
+        let remaining: Vec<Thing> = self
+            .db
+            .client
+            .query("SELECT VALUE id FROM contact WHERE id INSIDE $contacts")
+            .bind(("contacts", contacts.clone()))
+            .await?
+            .take(0)?;
+
+        Ok(PurgeCounts {
+            contacts: contacts.len() - remaining.len(),
+            companies: companies.len(),
+            storage_keys: purged_files(files, &remaining),
+            avatars: purged_avatars(avatars, &remaining),
+        })
+    }
+}
+
+/// Tables whose rows belong to a single contact and are purged with it
+const CONTACT_ROWS: [&str; 9] = [
+    "timeline_entry",
+    "deal",
+    "rsvp",
+    "short_link",
+    "status_history",
+    "attachment",
+    "campaign_recipient",
+    "sequence_enrollment",
+    "contact_embedding",
+];
+
+/// Storage keys of the files whose contact was purged
+fn purged_files(files: Vec<StoredFile>, remaining: &[Thing]) -> Vec<String> {
+    files
+        .into_iter()
+        .filter(|file| !remaining.contains(&file.contact))
+        .map(|file| file.storage_key)
+        .collect()
+}
+
+/// Avatars whose contact was purged
+fn purged_avatars(avatars: Vec<StoredAvatar>, remaining: &[Thing]) -> Vec<PurgedAvatar> {
+    avatars
+        .into_iter()
+        .filter(|avatar| !remaining.contains(&avatar.id))
+        .map(|avatar| PurgedAvatar {
+            workspace_id: avatar.workspace.id.to_raw(),
+            avatar_url: avatar.avatar_url,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restored_contacts_keep_their_files() {
+        let file = |contact: &str, key: &str| StoredFile {
+            contact: Thing::from(("contact", contact)),
+            storage_key: key.to_string(),
+        };
+        let files = vec![file("c1", "ws1/c1/a"), file("c2", "ws1/c2/b"), file("c1", "ws1/c1/c")];
+
+        let keys = purged_files(files, &[Thing::from(("contact", "c2"))]);
+        assert_eq!(keys, vec!["ws1/c1/a", "ws1/c1/c"]);
+    }
+
+    #[test]
+    fn test_restored_contacts_keep_their_avatars() {
+        let avatar = |contact: &str| StoredAvatar {
+            id: Thing::from(("contact", contact)),
+            workspace: Thing::from(("workspace", "ws1")),
+            avatar_url: format!("https://crm.example.com/avatars/ws1/{}", contact),
+        };
+
+        let avatars = purged_avatars(vec![avatar("c1"), avatar("c2")], &[Thing::from(("contact", "c2"))]);
+        assert_eq!(
+            avatars,
+            vec![PurgedAvatar {
+                workspace_id: "ws1".to_string(),
+                avatar_url: "https://crm.example.com/avatars/ws1/c1".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,53 @@
+//! User Repository - Database operations for users
+//!
+//! Users are the people who log in to the CRM (not contacts).
+//! Emails are stored lowercased so lookups are case-insensitive.
+
+use crate::db::Database;
+use crate::error::{AppError, AppResult};
+use crate::models::User;
+use std::sync::Arc;
+
+/// Repository for User database operations
+pub struct UserRepository {
+    db: Arc<Database>,
+}
+
+impl UserRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Find a user by ID
+    pub async fn find_by_id(&self, id: &str) -> AppResult<Option<User>> {
+        let user: Option<User> = self.db.retry(|| self.db.client.select(("user", id)).into_future()).await?;
+        Ok(user)
+    }
+
+    /// Find a user by email (for login and uniqueness checks)
+    pub async fn find_by_email(&self, email: &str) -> AppResult<Option<User>> {
+        let users: Vec<User> = self
+            .db
+            .retry(move || async move {
+                self.db
+                    .client
+                    .query("SELECT * FROM user WHERE email = $email LIMIT 1")
+                    .bind(("email", email.to_lowercase()))
+                    .await?
+                    .take(0)
+            })
+            .await?;
+
+        Ok(users.into_iter().next())
+    }
+
+    /// Create a new user
+    pub async fn create(&self, user: User) -> AppResult<User> {
+        let created: Vec<User> = self.db.client.create("user").content(user).await?;
+
+        created
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::Internal("Failed to create user".into()))
+    }
+}
@@ -0,0 +1,242 @@
+//! Webhook Repository - subscriptions, queued deliveries and their attempts
+
+use crate::db::{workspace_thing, Database};
+use crate::domain::WebhookEvent;
+use crate::error::{AppError, AppResult};
+use crate::models::{DeliveryStatus, Webhook, WebhookAttempt, WebhookDelivery};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// A delivery claimed by the dispatcher, with what it needs to send it
+#[derive(Debug, Clone, Deserialize)]
+pub struct DueDelivery {
+    pub id: Thing,
+    pub workspace: Thing,
+    pub event: WebhookEvent,
+    pub payload: serde_json::Value,
+    pub attempts: u32,
+    /// None when the webhook has been deleted since the event was queued
+    pub webhook_url: Option<String>,
+    pub webhook_secret: Option<String>,
+}
+
+/// Repository for Webhook database operations
+pub struct WebhookRepository {
+    db: Arc<Database>,
+}
+
+impl WebhookRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    pub async fn create(&self, webhook: Webhook) -> AppResult<Webhook> {
+        let created: Vec<Webhook> = self.db.client.create("webhook").content(webhook).await?;
+
+        created
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::Internal("Failed to create webhook".into()))
+    }
+
+    pub async fn find_all(&self, workspace_id: &str) -> AppResult<Vec<Webhook>> {
+        let webhooks: Vec<Webhook> = self
+            .db
+            .client
+            .query("SELECT * FROM webhook WHERE workspace = $workspace ORDER BY created_at DESC")
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .await?
+            .take(0)?;
+
+        Ok(webhooks)
+    }
+
+    pub async fn find_by_id(&self, workspace_id: &str, id: &str) -> AppResult<Option<Webhook>> {
+        Ok(self.db.select_scoped("webhook", id, workspace_id).await?)
+    }
+
+    /// Active webhooks of a workspace subscribed to an event
+    pub async fn find_subscribed(
+        &self,
+        workspace_id: &str,
+        event: WebhookEvent,
+    ) -> AppResult<Vec<Webhook>> {
+        let webhooks: Vec<Webhook> = self
+            .db
+            .client
+            .query(
+                "SELECT * FROM webhook \
+                 WHERE workspace = $workspace AND active = true AND events CONTAINS $event",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("event", event))
+            .await?
+            .take(0)?;
+
+        Ok(webhooks)
+    }
+
+    /// Delete a webhook along with any deliveries it still had queued
+    ///
+    /// Finished deliveries are kept for inspection.
+    pub async fn delete(&self, workspace_id: &str, id: &str) -> AppResult<bool> {
+        let deleted = self.db.delete_scoped("webhook", id, workspace_id).await?;
+
+        if deleted {
+            self.db
+                .client
+                .query(
+                    "DELETE webhook_delivery \
+                     WHERE workspace = $workspace AND webhook = $webhook AND status = 'pending'",
+                )
+                .bind(("workspace", workspace_thing(workspace_id)))
+                .bind(("webhook", Thing::from(("webhook", id))))
+                .await?
+                .check()?;
+        }
+
+        Ok(deleted)
+    }
+
+    /// Queue deliveries
+    pub async fn enqueue(&self, deliveries: Vec<WebhookDelivery>) -> AppResult<()> {
+        if deliveries.is_empty() {
+            return Ok(());
+        }
+
+        self.db
+            .client
+            .query("INSERT INTO webhook_delivery $deliveries")
+            .bind(("deliveries", deliveries))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Claim up to `limit` deliveries that are due, across all workspaces
+    ///
+    /// Claiming pushes `next_attempt_at` out to `lease_until`, so a second
+    /// dispatcher polling at the same time skips them - and if this one dies
+    /// mid-send they simply become due again once the lease runs out.
+    pub async fn claim_due(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: u32,
+    ) -> AppResult<Vec<DueDelivery>> {
+        let mut response = self
+            .db
+            .client
+            .query(
+                "LET $due = (SELECT VALUE id FROM webhook_delivery \
+                    WHERE status = 'pending' AND next_attempt_at <= $now \
+                    ORDER BY next_attempt_at ASC LIMIT $limit)",
+            )
+            .query(
+                "LET $claimed = (UPDATE $due SET next_attempt_at = $lease_until \
+                    WHERE status = 'pending' AND next_attempt_at <= $now RETURN VALUE id)",
+            )
+            .query(
+                "SELECT id, workspace, event, payload, attempts, \
+                    webhook.url AS webhook_url, webhook.secret AS webhook_secret \
+                 FROM $claimed",
+            )
+            .bind(("now", now))
+            .bind(("lease_until", lease_until))
+            .bind(("limit", limit))
+            .await?;
+
+        let due: Vec<DueDelivery> = response.take(2)?;
+        Ok(due)
+    }
+
+    /// Store an attempt and move the delivery to its next state
+    pub async fn record_attempt(
+        &self,
+        attempt: WebhookAttempt,
+        status: DeliveryStatus,
+        next_attempt_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        self.db
+            .client
+            .query("CREATE webhook_attempt CONTENT $attempt")
+            .query(
+                "UPDATE $delivery SET attempts = $attempts, status = $status, \
+                    next_attempt_at = $next_attempt_at, updated_at = $now",
+            )
+            .bind(("delivery", attempt.delivery.clone()))
+            .bind(("attempts", attempt.attempt))
+            .bind(("status", status))
+            .bind(("next_attempt_at", next_attempt_at))
+            .bind(("now", Utc::now()))
+            .bind(("attempt", attempt))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Deliveries for a webhook, newest first
+    pub async fn find_deliveries(
+        &self,
+        workspace_id: &str,
+        webhook_id: &str,
+        status: Option<DeliveryStatus>,
+        limit: u32,
+        offset: u32,
+    ) -> AppResult<Vec<WebhookDelivery>> {
+        let status_clause = if status.is_some() { "AND status = $status" } else { "" };
+        let query = format!(
+            "SELECT * FROM webhook_delivery \
+             WHERE workspace = $workspace AND webhook = $webhook {} \
+             ORDER BY created_at DESC LIMIT $limit START $offset",
+            status_clause
+        );
+
+        let deliveries: Vec<WebhookDelivery> = self
+            .db
+            .client
+            .query(query)
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("webhook", Thing::from(("webhook", webhook_id))))
+            .bind(("status", status))
+            .bind(("limit", limit))
+            .bind(("offset", offset))
+            .await?
+            .take(0)?;
+
+        Ok(deliveries)
+    }
+
+    pub async fn find_delivery(
+        &self,
+        workspace_id: &str,
+        id: &str,
+    ) -> AppResult<Option<WebhookDelivery>> {
+        Ok(self.db.select_scoped("webhook_delivery", id, workspace_id).await?)
+    }
+
+    /// Every attempt made for a delivery, in order
+    pub async fn find_attempts(
+        &self,
+        workspace_id: &str,
+        delivery_id: &str,
+    ) -> AppResult<Vec<WebhookAttempt>> {
+        let attempts: Vec<WebhookAttempt> = self
+            .db
+            .client
+            .query(
+                "SELECT * FROM webhook_attempt \
+                 WHERE workspace = $workspace AND delivery = $delivery ORDER BY attempt ASC",
+            )
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("delivery", Thing::from(("webhook_delivery", delivery_id))))
+            .await?
+            .take(0)?;
+
+        Ok(attempts)
+    }
+}
@@ -0,0 +1,50 @@
+//! Workspace Repository - Database operations for workspaces (tenants)
+
+use crate::db::Database;
+use crate::error::{AppError, AppResult};
+use crate::models::Workspace;
+use std::sync::Arc;
+
+/// Repository for Workspace database operations
+pub struct WorkspaceRepository {
+    db: Arc<Database>,
+}
+
+impl WorkspaceRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Find a workspace by ID
+    pub async fn find_by_id(&self, id: &str) -> AppResult<Option<Workspace>> {
+        let workspace: Option<Workspace> = self.db.retry(|| self.db.client.select(("workspace", id)).into_future()).await?;
+        Ok(workspace)
+    }
+
+    /// Create a new workspace
+    pub async fn create(&self, workspace: Workspace) -> AppResult<Workspace> {
+        let created: Vec<Workspace> = self
+            .db
+            .client
+            .create("workspace")
+            .content(workspace)
+            .await?;
+
+        created
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::Internal("Failed to create workspace".into()))
+    }
+
+    /// Update an existing workspace
+    pub async fn update(&self, id: &str, workspace: Workspace) -> AppResult<Workspace> {
+        let updated: Option<Workspace> = self
+            .db
+            .client
+            .update(("workspace", id))
+            .content(workspace)
+            .await?;
+
+        updated.ok_or_else(|| AppError::NotFound(format!("Workspace {} not found", id)))
+    }
+}
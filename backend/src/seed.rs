@@ -0,0 +1,112 @@
+//! Demo data from the command line
+//!
+//! Fills an existing workspace (register one first) with generated
+//! companies, contacts, deals, timelines, campaigns and events; see
+//! `services::seed_service`:
+//!
+//! ```text
+//! crm-server seed WORKSPACE_ID [--companies N] [--contacts N] [--campaigns N] [--events N] [--seed N]
+//! ```
+//!
+//! Seeding adds to what is there; run it on a fresh workspace.
+
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+
+use crate::config::Config;
+use crate::db::Database;
+use crate::secrets;
+use crate::services::read_cache::build_read_cache;
+use crate::services::{SeedService, SeedVolume};
+
+const USAGE: &str =
+    "Usage: crm-server seed WORKSPACE_ID [--companies N] [--contacts N] [--campaigns N] [--events N] [--seed N]";
+
+/// What `crm-server seed` was asked to do
+#[derive(Debug, PartialEq, Eq)]
+pub struct SeedCommand {
+    pub workspace_id: String,
+    pub volume: SeedVolume,
+    pub seed: u64,
+}
+
+impl SeedCommand {
+    /// Parse the arguments after `seed`
+    pub fn parse(args: &[String]) -> Result<Self> {
+        let Some((workspace_id, options)) = args.split_first() else {
+            bail!(USAGE);
+        };
+        if workspace_id.starts_with("--") {
+            bail!(USAGE);
+        }
+
+        let mut command = Self {
+            workspace_id: workspace_id.clone(),
+            volume: SeedVolume::default(),
+            seed: 1,
+        };
+        for option in options.chunks(2) {
+            let [name, value] = option else {
+                bail!(USAGE);
+            };
+            let Ok(value) = value.parse::<u64>() else {
+                bail!("'{}' for {} is not a number", value, name);
+            };
+            match name.as_str() {
+                "--companies" => command.volume.companies = value as usize,
+                "--contacts" => command.volume.contacts = value as usize,
+                "--campaigns" => command.volume.campaigns = value as usize,
+                "--events" => command.volume.events = value as usize,
+                "--seed" => command.seed = value,
+                _ => bail!(USAGE),
+            }
+        }
+
+        Ok(command)
+    }
+}
+
+pub async fn run(db: Arc<Database>, config: &Config, command: SeedCommand) -> Result<()> {
+    let read_cache = build_read_cache(&config.cache, &secrets::init_secrets_manager()).await?;
+    let service = SeedService::new(db, Arc::new(read_cache));
+
+    let summary = service
+        .seed(&command.workspace_id, &command.volume, command.seed)
+        .await?;
+
+    println!("Seeded workspace {}:", command.workspace_id);
+    println!("  {} companies", summary.companies);
+    println!("  {} contacts", summary.contacts);
+    println!("  {} deals", summary.deals);
+    println!("  {} timeline entries", summary.timeline_entries);
+    println!("  {} campaigns, {} recipients", summary.campaigns, summary.campaign_recipients);
+    println!("  {} events, {} RSVPs", summary.events, summary.rsvps);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_seed_command() {
+        let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+
+        let command = SeedCommand::parse(&args("acme")).unwrap();
+        assert_eq!(command.workspace_id, "acme");
+        assert_eq!(command.volume, SeedVolume::default());
+
+        let command = SeedCommand::parse(&args("acme --contacts 5000 --seed 9")).unwrap();
+        assert_eq!(command.volume.contacts, 5000);
+        assert_eq!(command.volume.companies, SeedVolume::default().companies);
+        assert_eq!(command.seed, 9);
+
+        assert!(SeedCommand::parse(&args("")).is_err());
+        assert!(SeedCommand::parse(&args("--contacts 10")).is_err());
+        assert!(SeedCommand::parse(&args("acme --contacts")).is_err());
+        assert!(SeedCommand::parse(&args("acme --contacts many")).is_err());
+        assert!(SeedCommand::parse(&args("acme --users 3")).is_err());
+    }
+}
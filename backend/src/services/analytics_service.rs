@@ -0,0 +1,1220 @@
+//! Analytics Service - campaign, event, contact, funnel, churn, win/loss, response time, sentiment and AI usage reports
+//!
+//! The repository supplies raw counts for the requested time range; this
+//! service turns them into rates and funnel percentages, and status
+//! histories into the time contacts take to move between statuses.
+//!
+//! The pipeline report is read on every board and dashboard load, so it is
+//! kept in the read cache until a deal changes.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+
+use crate::ai::ai_summary::{
+    sentiment_trend, sentiment_windows, SentimentTrend, SENTIMENT_BASELINE_DAYS, TREND_WINDOW_DAYS,
+};
+
+use crate::db::Database;
+use crate::error::AppResult;
+use crate::domain::{
+    average_minutes, customers_at_start, median_minutes, weighted_value, DealStage, EmailDirection, ResponseTimes,
+    WinLossOutcome, WinLossReason,
+};
+use crate::models::{
+    AccountSentiment, AiUsageByCampaign, AiUsageByMonth, AiUsageReport, AiUsageTotals, CampaignAnalytics,
+    ChurnAnalytics, ChurnReasonCount, ContactsAnalytics, DailyRegistrations, DeliverabilityAnalytics, EventAnalytics,
+    FunnelAnalytics, FunnelStage, LandingPageConversion, LinkClicks, PipelineAnalytics, PipelineRegionAnalytics,
+    PipelineRegionSummary, PipelineStageSummary, ResponseTimeAnalytics, Sentiment, SentimentAnalytics, StageConversion,
+    TimeRange, TimelineEntryType, TopEngagedContact, WinLossAnalytics, WinLossMonth, WinLossReasonCount,
+};
+use crate::repositories::{
+    AiUsageGroup, AiUsageRepository, AnalyticsRepository, CampaignCounts, ChurnCounts, CompanyName, ContactCreated,
+    DeliverabilityCounts, EmailEntry, EngagedContactRow, EventCounts, PipelineCounts, RatedInteraction,
+    RegionStageTotals, StatusChangeRow, TimelineRepository, WinLossCounts, WinLossRow,
+};
+use crate::services::read_cache::{CacheScope, ReadCache};
+use crate::services::{email_direction, email_threads, round_minutes, thread_response_times};
+
+/// How many contacts the "top engaged" list shows
+const TOP_ENGAGED_LIMIT: u32 = 5;
+
+/// How many churn reasons the churn report lists
+const TOP_CHURN_REASONS: usize = 10;
+
+/// Deals and leads won or lost for one reason
+type ReasonTally = BTreeMap<(WinLossOutcome, Option<WinLossReason>), (u64, u64)>;
+
+pub struct AnalyticsService {
+    repo: AnalyticsRepository,
+    ai_usage: AiUsageRepository,
+    timeline: TimelineRepository,
+    cache: Arc<ReadCache>,
+}
+
+impl AnalyticsService {
+    pub fn new(db: Arc<Database>, cache: Arc<ReadCache>) -> Self {
+        Self {
+            repo: AnalyticsRepository::new(Arc::clone(&db)),
+            ai_usage: AiUsageRepository::new(Arc::clone(&db)),
+            timeline: TimelineRepository::new(db),
+            cache,
+        }
+    }
+
+    pub async fn campaign(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        time_range: TimeRange,
+    ) -> AppResult<CampaignAnalytics> {
+        let since = time_range.since(Utc::now());
+        let counts = self.repo.campaign_counts(workspace_id, campaign_id, since).await?;
+
+        Ok(campaign_report(campaign_id, time_range, counts))
+    }
+
+    /// Bounces and spam complaints reported for one campaign's email
+    pub async fn deliverability(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        time_range: TimeRange,
+    ) -> AppResult<DeliverabilityAnalytics> {
+        let since = time_range.since(Utc::now());
+        let counts = self.repo.deliverability_counts(workspace_id, campaign_id, since).await?;
+
+        Ok(deliverability_report(campaign_id, time_range, counts))
+    }
+
+    /// RSVP funnel, registrations per day and most engaged attendees of one event
+    pub async fn event(&self, workspace_id: &str, event_id: &str) -> AppResult<EventAnalytics> {
+        let counts = self.repo.event_counts(workspace_id, event_id, TOP_ENGAGED_LIMIT).await?;
+
+        Ok(event_report(event_id, counts))
+    }
+
+    pub async fn contacts(&self, workspace_id: &str, time_range: TimeRange) -> AppResult<ContactsAnalytics> {
+        let now = Utc::now();
+        let month_start = Utc
+            .with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+            .single()
+            .unwrap_or(now);
+
+        let counts = self
+            .repo
+            .contact_counts(workspace_id, time_range.since(now), month_start, TOP_ENGAGED_LIMIT)
+            .await?;
+
+        let status_count = |status: &str| {
+            counts
+                .by_status
+                .iter()
+                .find(|s| s.status == status)
+                .map_or(0, |s| s.count)
+        };
+
+        Ok(ContactsAnalytics {
+            time_range,
+            total_contacts: counts.by_status.iter().map(|s| s.count).sum(),
+            leads: status_count("lead"),
+            customers: status_count("customer"),
+            partners: status_count("partner"),
+            investors: status_count("investor"),
+            other: status_count("other"),
+            avg_engagement_score: round2(counts.avg_engagement_score),
+            new_this_month: counts.new_since_month_start,
+            new_in_range: counts.new_in_range,
+            top_engaged: counts.top_engaged.into_iter().map(top_engaged).collect(),
+        })
+    }
+
+    /// Funnel of contacts created in the range: new -> engaged -> event -> customer
+    ///
+    /// Also how long those contacts took over each status change.
+    pub async fn funnel(&self, workspace_id: &str, time_range: TimeRange) -> AppResult<FunnelAnalytics> {
+        let counts = self
+            .repo
+            .funnel_counts(workspace_id, time_range.since(Utc::now()))
+            .await?;
+
+        let mut funnel = build_funnel(
+            time_range,
+            &[
+                ("New contacts", counts.new_contacts),
+                ("Engaged", counts.engaged),
+                ("Event registrants", counts.event_registrants),
+                ("Customers", counts.customers),
+            ],
+        );
+        funnel.stage_conversions = stage_conversions(&counts.created, &counts.status_changes);
+        Ok(funnel)
+    }
+
+    /// Customers lost in the range, against those there were at its start
+    pub async fn churn(&self, workspace_id: &str, time_range: TimeRange) -> AppResult<ChurnAnalytics> {
+        let counts = self
+            .repo
+            .churn_counts(workspace_id, time_range.since(Utc::now()))
+            .await?;
+
+        Ok(churn_report(time_range, counts))
+    }
+
+    /// Deals closed and leads won or lost in the range, by reason and month
+    pub async fn win_loss(&self, workspace_id: &str, time_range: TimeRange) -> AppResult<WinLossAnalytics> {
+        let counts = self
+            .repo
+            .win_loss_counts(workspace_id, time_range.since(Utc::now()))
+            .await?;
+
+        Ok(win_loss_report(time_range, counts))
+    }
+
+    /// How fast we and our contacts answer each other's email in the range
+    pub async fn response_times(&self, workspace_id: &str, time_range: TimeRange) -> AppResult<ResponseTimeAnalytics> {
+        let since = time_range.since(Utc::now());
+        let emails = self.timeline.find_emails(workspace_id, None, Some(since)).await?;
+
+        Ok(response_time_report(time_range, &emails))
+    }
+
+    /// Open pipeline by stage, plus deals won and lost in the range
+    pub async fn pipeline(&self, workspace_id: &str, time_range: TimeRange) -> AppResult<PipelineAnalytics> {
+        let key = format!("pipeline:{:?}", time_range);
+
+        self.cache
+            .get_or_load(workspace_id, CacheScope::Deals, &key, || async {
+                let counts = self
+                    .repo
+                    .pipeline_counts(workspace_id, time_range.since(Utc::now()))
+                    .await?;
+
+                Ok(pipeline_report(time_range, counts))
+            })
+            .await
+    }
+
+    /// The pipeline report broken down by country and region
+    ///
+    /// Not cached: moving a company or contact changes it as much as a deal does.
+    pub async fn pipeline_by_region(
+        &self,
+        workspace_id: &str,
+        time_range: TimeRange,
+    ) -> AppResult<PipelineRegionAnalytics> {
+        let rows = self
+            .repo
+            .pipeline_region_counts(workspace_id, time_range.since(Utc::now()))
+            .await?;
+
+        Ok(PipelineRegionAnalytics {
+            time_range,
+            regions: region_summaries(rows),
+        })
+    }
+
+    /// Companies whose contacts have come across worse lately, from rated interactions
+    pub async fn sentiment(&self, workspace_id: &str) -> AppResult<SentimentAnalytics> {
+        let now = Utc::now();
+        let (rated, companies) = self
+            .repo
+            .rated_interactions(workspace_id, now - Duration::days(SENTIMENT_BASELINE_DAYS))
+            .await?;
+
+        Ok(SentimentAnalytics {
+            accounts_turning_negative: accounts_turning_negative(rated, companies, now),
+        })
+    }
+
+    /// What AI content generation cost in the range, per campaign and per month
+    pub async fn ai_usage(&self, workspace_id: &str, time_range: TimeRange) -> AppResult<AiUsageReport> {
+        let (by_campaign, by_month) = self
+            .ai_usage
+            .grouped(workspace_id, time_range.since(Utc::now()))
+            .await?;
+
+        Ok(ai_usage_report(time_range, by_campaign, by_month))
+    }
+}
+
+/// Companies whose sentiment trend is worsening and whose last 30 days are net negative
+fn accounts_turning_negative(
+    rated: Vec<RatedInteraction>,
+    companies: Vec<CompanyName>,
+    now: DateTime<Utc>,
+) -> Vec<AccountSentiment> {
+    let recent_start = now - Duration::days(TREND_WINDOW_DAYS);
+    let mut ratings: HashMap<String, Vec<(DateTime<Utc>, Sentiment)>> = HashMap::new();
+    for interaction in rated {
+        ratings
+            .entry(interaction.company.id.to_string())
+            .or_default()
+            .push((interaction.timestamp, interaction.sentiment));
+    }
+
+    let mut accounts: Vec<AccountSentiment> = companies
+        .into_iter()
+        .filter_map(|company| {
+            let company_id = company.id.id.to_string();
+            let ratings = ratings.get(&company_id)?;
+            let (recent, previous) = sentiment_windows(ratings.iter().copied(), now);
+            if sentiment_trend(recent, previous) != SentimentTrend::Worsening {
+                return None;
+            }
+            let (recent, previous) = (recent?, previous?);
+            if recent >= 0.0 {
+                return None;
+            }
+
+            Some(AccountSentiment {
+                company_id,
+                name: company.name,
+                recent_sentiment: round2(recent),
+                previous_sentiment: round2(previous),
+                recent_negative: ratings
+                    .iter()
+                    .filter(|(at, sentiment)| *at >= recent_start && *sentiment == Sentiment::Negative)
+                    .count() as u64,
+            })
+        })
+        .collect();
+
+    accounts.sort_by(|a, b| {
+        (a.recent_sentiment - a.previous_sentiment)
+            .total_cmp(&(b.recent_sentiment - b.previous_sentiment))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    accounts
+}
+
+/// `part` as a percentage of `whole`, rounded to two decimals; 0 when `whole` is 0
+fn percentage(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        return 0.0;
+    }
+    round2(part as f64 / whole as f64 * 100.0)
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn campaign_report(campaign_id: &str, time_range: TimeRange, counts: CampaignCounts) -> CampaignAnalytics {
+    CampaignAnalytics {
+        campaign_id: campaign_id.to_string(),
+        time_range,
+        total_contacts: counts.recipients,
+        emails_sent: counts.emails_sent,
+        replies: counts.replies,
+        emails_opened: counts.emails_opened,
+        emails_clicked: counts.emails_clicked,
+        landing_page_visits: counts.landing_page_visits,
+        conversions: counts.conversions,
+        engaged_contacts: counts.engaged_contacts,
+        open_rate: percentage(counts.emails_opened, counts.emails_sent),
+        click_rate: percentage(counts.emails_clicked, counts.emails_sent),
+        reply_rate: percentage(counts.replies, counts.emails_sent),
+        conversion_rate: percentage(counts.conversions, counts.recipients),
+        landing_pages: landing_page_conversions(&counts),
+        links: link_clicks(&counts),
+    }
+}
+
+fn deliverability_report(
+    campaign_id: &str,
+    time_range: TimeRange,
+    counts: DeliverabilityCounts,
+) -> DeliverabilityAnalytics {
+    let delivered = counts.sent.saturating_sub(counts.hard_bounces);
+    DeliverabilityAnalytics {
+        campaign_id: campaign_id.to_string(),
+        time_range,
+        sent: counts.sent,
+        delivered,
+        hard_bounces: counts.hard_bounces,
+        soft_bounces: counts.soft_bounces,
+        complaints: counts.complaints,
+        bounce_rate: percentage(counts.hard_bounces, counts.sent),
+        complaint_rate: percentage(counts.complaints, delivered),
+    }
+}
+
+fn top_engaged(c: EngagedContactRow) -> TopEngagedContact {
+    TopEngagedContact {
+        id: c.id.id.to_string(),
+        name: format!("{} {}", c.first_name, c.last_name),
+        engagement_score: c.engagement_score,
+    }
+}
+
+fn event_report(event_id: &str, counts: EventCounts) -> EventAnalytics {
+    let status_count = |status: &str| {
+        counts
+            .by_status
+            .iter()
+            .find(|s| s.status == status)
+            .map_or(0, |s| s.count)
+    };
+    let attended = status_count("attended");
+    let no_show = status_count("no_show");
+    let registered = status_count("registered") + attended + no_show;
+    let invited = counts.by_status.iter().map(|s| s.count).sum();
+
+    EventAnalytics {
+        event_id: event_id.to_string(),
+        invited,
+        registered,
+        waitlisted: status_count("waitlisted"),
+        cancelled: status_count("cancelled"),
+        attended,
+        no_show,
+        registration_rate: percentage(registered, invited),
+        attendance_rate: percentage(attended, registered),
+        no_show_rate: percentage(no_show, attended + no_show),
+        registrations_over_time: counts
+            .registrations_by_day
+            .into_iter()
+            .map(|d| DailyRegistrations {
+                date: d.date,
+                registrations: d.count,
+            })
+            .collect(),
+        top_engaged_attendees: counts.top_attendees.into_iter().map(top_engaged).collect(),
+    }
+}
+
+/// Visit-to-submission conversion of each of the campaign's landing pages
+fn landing_page_conversions(counts: &CampaignCounts) -> Vec<LandingPageConversion> {
+    let visits: HashMap<String, u64> = counts
+        .page_visits
+        .iter()
+        .map(|v| (v.page.id.to_raw(), v.visits))
+        .collect();
+
+    let mut submitters: HashMap<&str, HashSet<&surrealdb::sql::Thing>> = HashMap::new();
+    for row in &counts.page_submissions {
+        submitters.entry(row.landing_page_id.as_str()).or_default().insert(&row.contact);
+    }
+
+    counts
+        .pages
+        .iter()
+        .map(|page| {
+            let visits = visits.get(page).copied().unwrap_or(0);
+            let submissions = submitters.get(page.as_str()).map_or(0, |c| c.len() as u64);
+
+            LandingPageConversion {
+                landing_page_id: page.clone(),
+                visits,
+                submissions,
+                conversion_rate: percentage(submissions, visits),
+            }
+        })
+        .collect()
+}
+
+/// Clicks per destination URL, most clicked first
+fn link_clicks(counts: &CampaignCounts) -> Vec<LinkClicks> {
+    let mut links: Vec<(LinkClicks, HashSet<&surrealdb::sql::Thing>)> = Vec::new();
+    for row in &counts.link_clicks {
+        let index = match links.iter().position(|(l, _)| l.url == row.url) {
+            Some(index) => index,
+            None => {
+                let link = LinkClicks {
+                    url: row.url.clone(),
+                    clicks: 0,
+                    unique_contacts: 0,
+                    email_clicks: 0,
+                    social_clicks: 0,
+                };
+                links.push((link, HashSet::new()));
+                links.len() - 1
+            }
+        };
+        let (link, contacts) = &mut links[index];
+        link.clicks += 1;
+        match row.entry_type {
+            TimelineEntryType::SocialTouch => link.social_clicks += 1,
+            _ => link.email_clicks += 1,
+        }
+        contacts.insert(&row.contact);
+    }
+
+    let mut links: Vec<LinkClicks> = links
+        .into_iter()
+        .map(|(mut link, contacts)| {
+            link.unique_contacts = contacts.len() as u64;
+            link
+        })
+        .collect();
+    links.sort_by(|a, b| b.clicks.cmp(&a.clicks).then_with(|| a.url.cmp(&b.url)));
+    links
+}
+
+/// Each stage's percentage is relative to the first stage
+fn build_funnel(time_range: TimeRange, stages: &[(&str, u64)]) -> FunnelAnalytics {
+    let top = stages.first().map_or(0, |(_, count)| *count);
+    let bottom = stages.last().map_or(0, |(_, count)| *count);
+
+    FunnelAnalytics {
+        time_range,
+        stages: stages
+            .iter()
+            .map(|(name, count)| FunnelStage {
+                name: name.to_string(),
+                count: *count,
+                percentage: percentage(*count, top),
+            })
+            .collect(),
+        overall_conversion_rate: percentage(bottom, top),
+        stage_conversions: Vec::new(),
+    }
+}
+
+/// Count and time each kind of status change
+///
+/// Time in a status runs from the contact's creation, for its first
+/// change, or from its previous change. `changes` must be oldest first.
+fn stage_conversions(created: &[ContactCreated], changes: &[StatusChangeRow]) -> Vec<StageConversion> {
+    // Keyed by contact ID
+    let mut entered: HashMap<String, DateTime<Utc>> =
+        created.iter().map(|c| (c.id.id.to_raw(), c.created_at)).collect();
+    let mut durations: Vec<(&StatusChangeRow, Vec<f64>)> = Vec::new();
+
+    for change in changes {
+        let since = entered.insert(change.contact.id.to_raw(), change.changed_at);
+        let days = since.map_or(0.0, |since| {
+            (change.changed_at - since).num_seconds().max(0) as f64 / 86_400.0
+        });
+
+        match durations
+            .iter_mut()
+            .find(|(c, _)| c.from_status == change.from_status && c.to_status == change.to_status)
+        {
+            Some((_, days_taken)) => days_taken.push(days),
+            None => durations.push((change, vec![days])),
+        }
+    }
+
+    let mut conversions: Vec<StageConversion> = durations
+        .into_iter()
+        .map(|(change, mut days)| {
+            days.sort_by(f64::total_cmp);
+            let mid = days.len() / 2;
+            let median = if days.len() % 2 == 1 {
+                days[mid]
+            } else {
+                (days[mid - 1] + days[mid]) / 2.0
+            };
+
+            StageConversion {
+                from_status: change.from_status.clone(),
+                to_status: change.to_status.clone(),
+                conversions: days.len() as u64,
+                avg_days: round2(days.iter().sum::<f64>() / days.len() as f64),
+                median_days: round2(median),
+            }
+        })
+        .collect();
+
+    conversions.sort_by_key(|c| Reverse(c.conversions));
+    conversions
+}
+
+/// Reasons are grouped ignoring case and surrounding space
+fn churn_report(time_range: TimeRange, counts: ChurnCounts) -> ChurnAnalytics {
+    let customers_at_start = customers_at_start(counts.customers_now, counts.churned, counts.new_customers);
+
+    let mut reasons: Vec<ChurnReasonCount> = Vec::new();
+    for row in counts.reasons {
+        let reason = row.reason.trim();
+        match reasons.iter_mut().find(|r| r.reason.eq_ignore_ascii_case(reason)) {
+            Some(existing) => existing.count += row.count,
+            None => reasons.push(ChurnReasonCount {
+                reason: reason.to_string(),
+                count: row.count,
+            }),
+        }
+    }
+    reasons.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.reason.cmp(&b.reason)));
+    reasons.truncate(TOP_CHURN_REASONS);
+
+    ChurnAnalytics {
+        time_range,
+        customers_at_start,
+        new_customers: counts.new_customers,
+        churned_customers: counts.churned,
+        customers_now: counts.customers_now,
+        churn_rate: percentage(counts.churned, customers_at_start),
+        top_reasons: reasons,
+    }
+}
+
+fn response_time_report(time_range: TimeRange, emails: &[EmailEntry]) -> ResponseTimeAnalytics {
+    let threads = email_threads(emails);
+    let mut times = ResponseTimes::default();
+    let mut replied_threads = 0;
+    let mut awaiting_our_reply = 0;
+
+    for thread in &threads {
+        let directions: HashSet<EmailDirection> =
+            thread.iter().map(|&i| email_direction(&emails[i].entry_type)).collect();
+        if directions.len() == 2 {
+            replied_threads += 1;
+        }
+        if thread.last().is_some_and(|&i| email_direction(&emails[i].entry_type) == EmailDirection::Received) {
+            awaiting_our_reply += 1;
+        }
+        times.extend(thread_response_times(emails, thread));
+    }
+
+    ResponseTimeAnalytics {
+        time_range,
+        threads: threads.len() as u64,
+        replied_threads,
+        awaiting_our_reply,
+        our_replies: times.ours.len() as u64,
+        our_avg_response_minutes: average_minutes(&times.ours).map(round_minutes),
+        our_median_response_minutes: median_minutes(&times.ours).map(round_minutes),
+        their_replies: times.theirs.len() as u64,
+        their_avg_response_minutes: average_minutes(&times.theirs).map(round_minutes),
+        their_median_response_minutes: median_minutes(&times.theirs).map(round_minutes),
+    }
+}
+
+fn win_loss_report(time_range: TimeRange, counts: WinLossCounts) -> WinLossAnalytics {
+    let outcome = |won: bool| if won { WinLossOutcome::Won } else { WinLossOutcome::Lost };
+    let total =
+        |rows: &[WinLossRow], won: bool| -> u64 { rows.iter().filter(|r| r.won == won).map(|r| r.count).sum() };
+
+    let mut months: BTreeMap<String, ReasonTally> = BTreeMap::new();
+    for row in &counts.deals {
+        let tally = months.entry(row.month.clone()).or_default();
+        tally.entry((outcome(row.won), row.reason)).or_default().0 += row.count;
+    }
+    for row in &counts.leads {
+        let tally = months.entry(row.month.clone()).or_default();
+        tally.entry((outcome(row.won), row.reason)).or_default().1 += row.count;
+    }
+
+    let mut overall = ReasonTally::new();
+    let by_month = months
+        .into_iter()
+        .map(|(month, tally)| {
+            let (mut won, mut lost) = (0, 0);
+            for (&key, &(deals, leads)) in &tally {
+                let sum = overall.entry(key).or_default();
+                sum.0 += deals;
+                sum.1 += leads;
+                match key.0 {
+                    WinLossOutcome::Won => won += deals + leads,
+                    WinLossOutcome::Lost => lost += deals + leads,
+                }
+            }
+            WinLossMonth {
+                month,
+                won,
+                lost,
+                reasons: reason_counts(tally),
+            }
+        })
+        .collect();
+
+    let deals_won = total(&counts.deals, true);
+    let deals_lost = total(&counts.deals, false);
+    WinLossAnalytics {
+        time_range,
+        deals_won,
+        deals_lost,
+        leads_won: total(&counts.leads, true),
+        leads_lost: total(&counts.leads, false),
+        deal_win_rate: percentage(deals_won, deals_won + deals_lost),
+        reasons: reason_counts(overall),
+        by_month,
+    }
+}
+
+/// Most given first; ties keep wins before losses
+fn reason_counts(tally: ReasonTally) -> Vec<WinLossReasonCount> {
+    let mut reasons: Vec<WinLossReasonCount> = tally
+        .into_iter()
+        .map(|((outcome, reason), (deals, leads))| WinLossReasonCount {
+            outcome,
+            reason,
+            deals,
+            leads,
+        })
+        .collect();
+
+    reasons.sort_by_key(|r| Reverse(r.deals + r.leads));
+    reasons
+}
+
+fn pipeline_report(time_range: TimeRange, counts: PipelineCounts) -> PipelineAnalytics {
+    let stages: Vec<PipelineStageSummary> = DealStage::ALL
+        .into_iter()
+        .filter(DealStage::is_open)
+        .map(|stage| {
+            let (count, total_value) = counts
+                .by_stage
+                .iter()
+                .find(|s| s.stage == stage)
+                .map_or((0, 0.0), |s| (s.count, s.total_value));
+
+            PipelineStageSummary {
+                stage,
+                count,
+                total_value: round2(total_value),
+                weighted_value: round2(weighted_value(total_value, stage)),
+            }
+        })
+        .collect();
+
+    let won_deals = counts.won.as_ref().map_or(0, |s| s.count);
+    let lost_deals = counts.lost.as_ref().map_or(0, |s| s.count);
+
+    PipelineAnalytics {
+        time_range,
+        open_deals: stages.iter().map(|s| s.count).sum(),
+        open_value: round2(stages.iter().map(|s| s.total_value).sum()),
+        weighted_value: round2(stages.iter().map(|s| s.weighted_value).sum()),
+        stages,
+        won_deals,
+        won_value: round2(counts.won.as_ref().map_or(0.0, |s| s.total_value)),
+        lost_deals,
+        win_rate: percentage(won_deals, won_deals + lost_deals),
+    }
+}
+
+fn region_summaries(rows: Vec<RegionStageTotals>) -> Vec<PipelineRegionSummary> {
+    let mut regions: Vec<PipelineRegionSummary> = Vec::new();
+    for row in rows {
+        let index = match regions
+            .iter()
+            .position(|r| r.country == row.country && r.region == row.region)
+        {
+            Some(index) => index,
+            None => {
+                regions.push(PipelineRegionSummary {
+                    country: row.country.clone(),
+                    region: row.region.clone(),
+                    open_deals: 0,
+                    open_value: 0.0,
+                    weighted_value: 0.0,
+                    won_deals: 0,
+                    won_value: 0.0,
+                    lost_deals: 0,
+                    win_rate: 0.0,
+                });
+                regions.len() - 1
+            }
+        };
+
+        let summary = &mut regions[index];
+        match row.stage {
+            DealStage::ClosedWon => {
+                summary.won_deals += row.count;
+                summary.won_value += row.total_value;
+            }
+            DealStage::ClosedLost => summary.lost_deals += row.count,
+            stage => {
+                summary.open_deals += row.count;
+                summary.open_value += row.total_value;
+                summary.weighted_value += weighted_value(row.total_value, stage);
+            }
+        }
+    }
+
+    for summary in &mut regions {
+        summary.open_value = round2(summary.open_value);
+        summary.weighted_value = round2(summary.weighted_value);
+        summary.won_value = round2(summary.won_value);
+        summary.win_rate = percentage(summary.won_deals, summary.won_deals + summary.lost_deals);
+    }
+    regions.sort_by(|a, b| b.open_value.total_cmp(&a.open_value));
+    regions
+}
+
+fn round4(value: f64) -> f64 {
+    (value * 10_000.0).round() / 10_000.0
+}
+
+fn usage_totals(groups: &[&AiUsageGroup]) -> AiUsageTotals {
+    let generations: u64 = groups.iter().map(|g| g.generations).sum();
+    let latency_ms: u64 = groups.iter().map(|g| g.latency_ms).sum();
+
+    AiUsageTotals {
+        generations,
+        input_tokens: groups.iter().map(|g| g.input_tokens).sum(),
+        output_tokens: groups.iter().map(|g| g.output_tokens).sum(),
+        cost_usd: round4(groups.iter().map(|g| g.cost_usd).sum()),
+        avg_latency_ms: if generations == 0 {
+            0.0
+        } else {
+            round2(latency_ms as f64 / generations as f64)
+        },
+    }
+}
+
+fn ai_usage_report(time_range: TimeRange, by_campaign: Vec<AiUsageGroup>, by_month: Vec<AiUsageGroup>) -> AiUsageReport {
+    let mut campaigns: Vec<AiUsageByCampaign> = by_campaign
+        .iter()
+        .map(|g| AiUsageByCampaign {
+            campaign_id: g.campaign.as_ref().map(|t| t.id.to_raw()),
+            totals: usage_totals(&[g]),
+        })
+        .collect();
+    campaigns.sort_by(|a, b| b.totals.cost_usd.total_cmp(&a.totals.cost_usd));
+
+    let mut months: Vec<AiUsageByMonth> = by_month
+        .iter()
+        .map(|g| AiUsageByMonth {
+            month: g.month.clone().unwrap_or_default(),
+            totals: usage_totals(&[g]),
+        })
+        .collect();
+    months.sort_by(|a, b| a.month.cmp(&b.month));
+
+    AiUsageReport {
+        time_range,
+        total: usage_totals(&by_month.iter().collect::<Vec<_>>()),
+        by_campaign: campaigns,
+        by_month: months,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::{LinkClickRow, PageSubmissionRow, PageVisitCount, StageTotals, StatusCount};
+
+    #[test]
+    fn test_percentage() {
+        assert_eq!(percentage(1, 3), 33.33);
+        assert_eq!(percentage(5, 0), 0.0);
+        assert_eq!(percentage(10, 10), 100.0);
+    }
+
+    #[test]
+    fn test_campaign_report_rates() {
+        let report = campaign_report(
+            "c1",
+            TimeRange::Last30Days,
+            CampaignCounts {
+                recipients: 200,
+                emails_sent: 100,
+                replies: 6,
+                emails_opened: 40,
+                emails_clicked: 8,
+                landing_page_visits: 12,
+                conversions: 3,
+                engaged_contacts: 45,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(report.open_rate, 40.0);
+        assert_eq!(report.click_rate, 8.0);
+        assert_eq!(report.reply_rate, 6.0);
+        assert_eq!(report.conversion_rate, 1.5);
+        assert_eq!(report.engaged_contacts, 45);
+        assert!(report.landing_pages.is_empty());
+    }
+
+    #[test]
+    fn test_deliverability_report_rates() {
+        let report = deliverability_report(
+            "c1",
+            TimeRange::Last30Days,
+            DeliverabilityCounts {
+                sent: 200,
+                hard_bounces: 10,
+                soft_bounces: 4,
+                complaints: 19,
+            },
+        );
+
+        assert_eq!(report.delivered, 190);
+        assert_eq!(report.bounce_rate, 5.0);
+        assert_eq!(report.complaint_rate, 10.0);
+
+        // Bounces reported for recipients sent to before the range don't go negative
+        let counts = DeliverabilityCounts {
+            hard_bounces: 2,
+            ..Default::default()
+        };
+        let report = deliverability_report("c1", TimeRange::Last7Days, counts);
+        assert_eq!(report.delivered, 0);
+        assert_eq!(report.complaint_rate, 0.0);
+    }
+
+    #[test]
+    fn test_event_report_stages() {
+        let status = |status: &str, count: u64| StatusCount {
+            status: status.to_string(),
+            count,
+        };
+        let report = event_report(
+            "e1",
+            EventCounts {
+                by_status: vec![
+                    status("invited", 10),
+                    status("registered", 2),
+                    status("waitlisted", 3),
+                    status("attended", 6),
+                    status("no_show", 2),
+                    status("cancelled", 1),
+                ],
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(report.invited, 24);
+        assert_eq!(report.registered, 10);
+        assert_eq!(report.registration_rate, 41.67);
+        assert_eq!(report.attendance_rate, 60.0);
+        assert_eq!(report.no_show_rate, 25.0);
+    }
+
+    #[test]
+    fn test_landing_page_conversions_count_unique_submitters() {
+        let contact = |id: &str| surrealdb::sql::Thing::from(("contact", id));
+        let submission = |page: &str, id: &str| PageSubmissionRow {
+            landing_page_id: page.to_string(),
+            contact: contact(id),
+        };
+
+        let counts = CampaignCounts {
+            pages: vec!["lp1".into(), "lp2".into()],
+            page_visits: vec![PageVisitCount {
+                page: surrealdb::sql::Thing::from(("campaign_asset", "lp1")),
+                visits: 8,
+            }],
+            page_submissions: vec![submission("lp1", "a"), submission("lp1", "a"), submission("lp1", "b")],
+            ..Default::default()
+        };
+
+        let pages = landing_page_conversions(&counts);
+
+        assert_eq!(pages[0].visits, 8);
+        assert_eq!(pages[0].submissions, 2);
+        assert_eq!(pages[0].conversion_rate, 25.0);
+        assert_eq!(pages[1].visits, 0);
+        assert_eq!(pages[1].conversion_rate, 0.0);
+    }
+
+    #[test]
+    fn test_link_clicks_group_by_url() {
+        let click = |url: &str, entry_type: TimelineEntryType, id: &str| LinkClickRow {
+            url: url.to_string(),
+            entry_type,
+            contact: surrealdb::sql::Thing::from(("contact", id)),
+        };
+
+        let counts = CampaignCounts {
+            link_clicks: vec![
+                click("https://a.example", TimelineEntryType::EmailClick, "x"),
+                click("https://b.example", TimelineEntryType::EmailClick, "x"),
+                click("https://b.example", TimelineEntryType::EmailClick, "x"),
+                click("https://b.example", TimelineEntryType::SocialTouch, "y"),
+            ],
+            ..Default::default()
+        };
+
+        let links = link_clicks(&counts);
+
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].url, "https://b.example");
+        assert_eq!(links[0].clicks, 3);
+        assert_eq!(links[0].unique_contacts, 2);
+        assert_eq!(links[0].email_clicks, 2);
+        assert_eq!(links[0].social_clicks, 1);
+        assert_eq!(links[1].clicks, 1);
+    }
+
+    #[test]
+    fn test_build_funnel() {
+        let funnel = build_funnel(TimeRange::AllTime, &[("New", 200), ("Engaged", 50), ("Customers", 5)]);
+
+        assert_eq!(funnel.stages[0].percentage, 100.0);
+        assert_eq!(funnel.stages[1].percentage, 25.0);
+        assert_eq!(funnel.overall_conversion_rate, 2.5);
+    }
+
+    #[test]
+    fn test_empty_funnel_has_no_nan() {
+        let funnel = build_funnel(TimeRange::Last7Days, &[("New", 0), ("Customers", 0)]);
+
+        assert_eq!(funnel.overall_conversion_rate, 0.0);
+    }
+
+    #[test]
+    fn test_churn_report_rate_and_reasons() {
+        use crate::repositories::ReasonCount;
+
+        let reason = |reason: &str, count| ReasonCount {
+            reason: reason.into(),
+            count,
+        };
+        let report = churn_report(
+            TimeRange::Last90Days,
+            ChurnCounts {
+                customers_now: 40,
+                new_customers: 15,
+                churned: 7,
+                reasons: vec![reason("Price", 2), reason("Moved to a competitor", 3), reason(" price ", 2)],
+            },
+        );
+
+        assert_eq!(report.customers_at_start, 32);
+        assert_eq!(report.churn_rate, 21.88);
+        assert_eq!(report.top_reasons[0].reason, "Price");
+        assert_eq!(report.top_reasons[0].count, 4);
+        assert_eq!(report.top_reasons.len(), 2);
+        assert_eq!(churn_report(TimeRange::AllTime, ChurnCounts::default()).churn_rate, 0.0);
+    }
+
+    #[test]
+    fn test_region_summaries_split_open_and_closed() {
+        let row = |country: Option<&str>, region: Option<&str>, stage, count, total_value| RegionStageTotals {
+            country: country.map(String::from),
+            region: region.map(String::from),
+            stage,
+            count,
+            total_value,
+        };
+        let regions = region_summaries(vec![
+            row(Some("SE"), Some("Stockholm County"), DealStage::Proposal, 2, 10_000.0),
+            row(Some("US"), Some("California"), DealStage::Negotiation, 1, 50_000.0),
+            row(Some("SE"), Some("Stockholm County"), DealStage::ClosedWon, 3, 9_000.0),
+            row(Some("SE"), Some("Stockholm County"), DealStage::ClosedLost, 1, 2_000.0),
+            row(None, None, DealStage::Prospecting, 4, 1_000.0),
+        ]);
+
+        assert_eq!(regions.len(), 3);
+        assert_eq!(regions[0].country.as_deref(), Some("US"));
+        let stockholm = &regions[1];
+        assert_eq!((stockholm.open_deals, stockholm.open_value), (2, 10_000.0));
+        assert_eq!(stockholm.weighted_value, weighted_value(10_000.0, DealStage::Proposal));
+        assert_eq!((stockholm.won_deals, stockholm.lost_deals), (3, 1));
+        assert_eq!(stockholm.win_rate, 75.0);
+        assert_eq!(regions[2].country, None);
+        assert_eq!(regions[2].win_rate, 0.0);
+    }
+
+    #[test]
+    fn test_response_time_report() {
+        use crate::models::TimelineEntryType::{EmailReceived, EmailSent};
+        let start = Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        let email = |contact: &str, entry_type, minutes, subject: &str| EmailEntry {
+            id: surrealdb::sql::Thing::from(("timeline_entry", format!("{}-{}", contact, minutes).as_str())),
+            contact: surrealdb::sql::Thing::from(("contact", contact)),
+            entry_type,
+            content: String::new(),
+            metadata: serde_json::json!({ "subject": subject }),
+            timestamp: start + Duration::minutes(minutes),
+        };
+        let emails = vec![
+            email("c1", EmailSent, 0, "Pricing"),
+            email("c1", EmailReceived, 60, "Re: Pricing"),
+            email("c1", EmailSent, 70, "Re: Pricing"),
+            email("c2", EmailSent, 0, "Intro"),
+            email("c2", EmailReceived, 240, "Re: Intro"),
+            email("c3", EmailSent, 5, "Welcome"),
+        ];
+
+        let report = response_time_report(TimeRange::Last30Days, &emails);
+        assert_eq!(report.threads, 3);
+        assert_eq!(report.replied_threads, 2);
+        assert_eq!(report.awaiting_our_reply, 1);
+        assert_eq!((report.our_replies, report.our_avg_response_minutes), (1, Some(10.0)));
+        assert_eq!(report.their_replies, 2);
+        assert_eq!(report.their_avg_response_minutes, Some(150.0));
+        assert_eq!(report.their_median_response_minutes, Some(150.0));
+    }
+
+    #[test]
+    fn test_win_loss_report_by_reason_and_month() {
+        let row = |month: &str, won, reason, count| WinLossRow {
+            month: month.into(),
+            won,
+            reason,
+            count,
+        };
+        let report = win_loss_report(
+            TimeRange::Last90Days,
+            WinLossCounts {
+                deals: vec![
+                    row("2026-08", true, Some(WinLossReason::ProductFit), 2),
+                    row("2026-08", false, Some(WinLossReason::Price), 3),
+                    row("2026-09", false, Some(WinLossReason::Price), 1),
+                    row("2026-09", true, None, 1),
+                ],
+                leads: vec![row("2026-09", false, Some(WinLossReason::Price), 2)],
+            },
+        );
+
+        assert_eq!((report.deals_won, report.deals_lost), (3, 4));
+        assert_eq!((report.leads_won, report.leads_lost), (0, 2));
+        assert_eq!(report.deal_win_rate, 42.86);
+
+        let top = &report.reasons[0];
+        assert_eq!((top.outcome, top.reason), (WinLossOutcome::Lost, Some(WinLossReason::Price)));
+        assert_eq!((top.deals, top.leads), (4, 2));
+        assert_eq!(report.reasons.len(), 3);
+
+        assert_eq!(report.by_month.len(), 2);
+        assert_eq!(report.by_month[0].month, "2026-08");
+        assert_eq!((report.by_month[1].won, report.by_month[1].lost), (1, 3));
+        assert_eq!(report.by_month[1].reasons[0].reason, Some(WinLossReason::Price));
+
+        let empty = win_loss_report(TimeRange::AllTime, WinLossCounts::default());
+        assert_eq!(empty.deal_win_rate, 0.0);
+        assert!(empty.by_month.is_empty());
+    }
+
+    #[test]
+    fn test_stage_conversions_time_each_status() {
+        use crate::models::ContactStatus;
+
+        let contact = |id: &str| surrealdb::sql::Thing::from(("contact", id));
+        let day = |d: u32| Utc.with_ymd_and_hms(2026, 3, d, 12, 0, 0).unwrap();
+        let change = |id: &str, from, to, at| StatusChangeRow {
+            contact: contact(id),
+            from_status: from,
+            to_status: to,
+            changed_at: at,
+        };
+        let created = |id: &str, at| ContactCreated {
+            id: contact(id),
+            created_at: at,
+        };
+        let created = vec![created("a", day(1)), created("b", day(1)), created("c", day(2))];
+        let changes = vec![
+            change("a", ContactStatus::Lead, ContactStatus::Customer, day(3)),
+            change("c", ContactStatus::Lead, ContactStatus::Customer, day(5)),
+            change("b", ContactStatus::Lead, ContactStatus::Customer, day(11)),
+            change("a", ContactStatus::Customer, ContactStatus::Partner, day(13)),
+        ];
+
+        let conversions = stage_conversions(&created, &changes);
+
+        assert_eq!(conversions.len(), 2);
+        assert_eq!(conversions[0].to_status, ContactStatus::Customer);
+        assert_eq!(conversions[0].conversions, 3);
+        assert_eq!(conversions[0].avg_days, 5.0);
+        assert_eq!(conversions[0].median_days, 3.0);
+        // Time as a customer counts from becoming one, not from creation
+        assert_eq!(conversions[1].from_status, ContactStatus::Customer);
+        assert_eq!(conversions[1].median_days, 10.0);
+    }
+
+    #[test]
+    fn test_pipeline_report() {
+        let totals = |stage, count, total_value| StageTotals {
+            stage,
+            count,
+            total_value,
+        };
+
+        let report = pipeline_report(
+            TimeRange::Last90Days,
+            PipelineCounts {
+                by_stage: vec![
+                    totals(DealStage::Proposal, 2, 10_000.0),
+                    totals(DealStage::Negotiation, 1, 4_000.0),
+                    totals(DealStage::ClosedWon, 5, 50_000.0),
+                ],
+                won: Some(totals(DealStage::ClosedWon, 3, 30_000.0)),
+                lost: Some(totals(DealStage::ClosedLost, 1, 2_000.0)),
+            },
+        );
+
+        assert_eq!(report.stages.len(), 4);
+        assert_eq!(report.open_deals, 3);
+        assert_eq!(report.open_value, 14_000.0);
+        assert_eq!(report.weighted_value, 8_000.0);
+        assert_eq!(report.won_value, 30_000.0);
+        assert_eq!(report.win_rate, 75.0);
+    }
+
+    #[test]
+    fn test_ai_usage_report_orders_and_averages() {
+        let group = |campaign: Option<&str>, month: Option<&str>, generations, cost_usd, latency_ms| AiUsageGroup {
+            campaign: campaign.map(|id| surrealdb::sql::Thing::from(("campaign", id))),
+            month: month.map(String::from),
+            generations,
+            input_tokens: generations * 1_000,
+            output_tokens: generations * 500,
+            cost_usd,
+            latency_ms,
+        };
+
+        let report = ai_usage_report(
+            TimeRange::AllTime,
+            vec![
+                group(Some("cheap"), None, 1, 0.01, 800),
+                group(None, None, 1, 0.02, 1_200),
+                group(Some("launch"), None, 2, 0.05, 3_000),
+            ],
+            vec![
+                group(None, Some("2026-02"), 3, 0.06, 3_000),
+                group(None, Some("2026-01"), 1, 0.02, 2_000),
+            ],
+        );
+
+        assert_eq!(report.by_campaign[0].campaign_id.as_deref(), Some("launch"));
+        assert_eq!(report.by_campaign[2].campaign_id.as_deref(), Some("cheap"));
+        assert_eq!(report.by_month[0].month, "2026-01");
+        assert_eq!(report.total.generations, 4);
+        assert_eq!(report.total.cost_usd, 0.08);
+        assert_eq!(report.total.avg_latency_ms, 1_250.0);
+        assert_eq!(report.by_campaign[1].totals.avg_latency_ms, 1_200.0);
+    }
+
+    #[test]
+    fn test_time_range_since() {
+        let now = Utc::now();
+
+        assert_eq!(TimeRange::Last7Days.since(now), now - chrono::Duration::days(7));
+        assert_eq!(TimeRange::AllTime.since(now).timestamp(), 0);
+    }
+
+    #[test]
+    fn test_accounts_turning_negative() {
+        let now = Utc::now();
+        let rated = |company: &str, days_ago: i64, sentiment| RatedInteraction {
+            company: surrealdb::sql::Thing::from(("company", company)),
+            sentiment,
+            timestamp: now - Duration::days(days_ago),
+        };
+        let company = |id: &str, name: &str| CompanyName {
+            id: surrealdb::sql::Thing::from(("company", id)),
+            name: name.into(),
+        };
+
+        let interactions = vec![
+            // Was happy, now unhappy
+            rated("acme", 3, Sentiment::Negative),
+            rated("acme", 5, Sentiment::Negative),
+            rated("acme", 45, Sentiment::Positive),
+            // Slipping, but still net positive
+            rated("globex", 3, Sentiment::Positive),
+            rated("globex", 4, Sentiment::Neutral),
+            rated("globex", 40, Sentiment::Positive),
+            // Unhappy all along
+            rated("initech", 2, Sentiment::Negative),
+            rated("initech", 50, Sentiment::Negative),
+        ];
+        let companies = vec![company("acme", "Acme"), company("globex", "Globex"), company("initech", "Initech")];
+
+        let accounts = accounts_turning_negative(interactions, companies, now);
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].company_id, "acme");
+        assert_eq!((accounts[0].recent_sentiment, accounts[0].previous_sentiment), (-1.0, 1.0));
+        assert_eq!(accounts[0].recent_negative, 2);
+    }
+}
@@ -0,0 +1,269 @@
+//! Assistant Service - AI help with individual contacts
+//!
+//! Works without an AI provider too: summaries then come from the
+//! rule-based heuristics in `ai::ai_summary`, prep briefs list the CRM
+//! facts as they are, and reply drafts come from a template.
+//!
+//! Summaries are cached until the workspace's timeline changes, and each
+//! workspace has the provider write only so many of them an hour.
+
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+
+use crate::ai::ai_contact_summary::summarize_contact;
+use crate::ai::ai_prep_brief::{prep_brief, PrepBriefFacts};
+use crate::ai::ai_reply::{reply_brief, template_reply, ReplyFacts};
+use crate::ai::{ContentGenerator, GenerationContext};
+use crate::config::AiConfig;
+use crate::db::Database;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Company, ContactSummaryResponse, DraftReplyRequest, DraftReplyResponse, PrepBriefResponse, TimelineEntry,
+    TimelineEntryType, TimelineQuery,
+};
+use crate::repositories::ContactRepository;
+use crate::services::rate_limiter::RateLimiter;
+use crate::services::read_cache::{CacheScope, ReadCache};
+use crate::services::{new_entry, TimelineService};
+
+/// Most recent timeline entries a summary is based on
+const SUMMARY_TIMELINE_ENTRIES: u32 = 200;
+
+/// Most recent timeline entries listed in a prep brief
+const BRIEF_RECENT_ENTRIES: u32 = 15;
+
+/// Most recent sent and received emails each looked at for a prep brief
+const BRIEF_EMAILS: u32 = 20;
+
+/// Task entries looked at for open ones
+const BRIEF_TASKS: u32 = 50;
+
+/// Most recent timeline entries a reply draft takes into account
+const REPLY_HISTORY_ENTRIES: u32 = 20;
+
+pub struct AssistantService {
+    db: Arc<Database>,
+    contacts: ContactRepository,
+    timeline: Arc<TimelineService>,
+    generator: Arc<ContentGenerator>,
+    cache: Arc<ReadCache>,
+    limiter: Mutex<RateLimiter>,
+    summaries_per_hour: u32,
+}
+
+impl AssistantService {
+    pub fn new(
+        db: Arc<Database>,
+        generator: Arc<ContentGenerator>,
+        timeline: Arc<TimelineService>,
+        cache: Arc<ReadCache>,
+        config: &AiConfig,
+    ) -> Self {
+        Self {
+            contacts: ContactRepository::new(Arc::clone(&db)),
+            db,
+            timeline,
+            generator,
+            cache,
+            limiter: Mutex::new(RateLimiter::default()),
+            summaries_per_hour: config.summaries_per_hour,
+        }
+    }
+
+    /// Summary, activity trend and next best action for a contact
+    pub async fn summary(&self, workspace_id: &str, contact_id: &str) -> AppResult<ContactSummaryResponse> {
+        let key = format!("summary:{}", contact_id);
+        self.cache
+            .get_or_load(workspace_id, CacheScope::Timeline, &key, || {
+                self.write_summary(workspace_id, contact_id)
+            })
+            .await
+    }
+
+    async fn write_summary(&self, workspace_id: &str, contact_id: &str) -> AppResult<ContactSummaryResponse> {
+        let contact = self
+            .contacts
+            .find_by_id(workspace_id, contact_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Contact {} not found", contact_id)))?;
+
+        let entries = self.find_entries(workspace_id, contact_id, None, SUMMARY_TIMELINE_ENTRIES).await?;
+        if !self.generator.uses_templates() && !entries.is_empty() {
+            self.admit_summary(workspace_id)?;
+        }
+
+        let ctx = GenerationContext {
+            workspace_id,
+            campaign_id: None,
+        };
+        let summary = summarize_contact(
+            &self.generator,
+            ctx,
+            &contact.full_name(),
+            &entries,
+            contact.engagement_score,
+        )
+        .await;
+
+        Ok(ContactSummaryResponse {
+            contact_id: contact_id.to_string(),
+            summary: summary.summary,
+            engagement_score: summary.insights.score,
+            trend: summary.insights.trend,
+            sentiment_trend: summary.insights.sentiment_trend,
+            recommendation: summary.insights.recommendation,
+            next_best_action: summary.insights.next_best_action,
+            ai_generated: summary.ai_generated,
+        })
+    }
+
+    /// Count a summary the provider writes against the workspace's hourly limit
+    fn admit_summary(&self, workspace_id: &str) -> AppResult<()> {
+        let admitted = self
+            .limiter
+            .lock()
+            .map_err(|_| AppError::Internal("Rate limiter lock poisoned".into()))?
+            .admit(
+                &[(workspace_id.to_string(), self.summaries_per_hour)],
+                Utc::now(),
+                chrono::Duration::hours(1),
+            );
+        if !admitted {
+            return Err(AppError::TooManyRequests(
+                "Too many contact summaries this hour, please try again later".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Write a "what to know before this call" brief and save it as a note
+    pub async fn prep_brief(&self, workspace_id: &str, contact_id: &str) -> AppResult<PrepBriefResponse> {
+        let contact = self
+            .contacts
+            .find_by_id(workspace_id, contact_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Contact {} not found", contact_id)))?;
+
+        let company: Option<Company> = match &contact.company_id {
+            Some(company_id) => self.db.select_scoped("company", company_id, workspace_id).await?,
+            None => None,
+        };
+
+        let recent_activity = self.find_entries(workspace_id, contact_id, None, BRIEF_RECENT_ENTRIES).await?;
+
+        let open_tasks: Vec<TimelineEntry> = self
+            .find_entries(workspace_id, contact_id, Some(TimelineEntryType::Task), BRIEF_TASKS)
+            .await?
+            .into_iter()
+            .filter(|task| task.metadata["completed"].as_bool() != Some(true))
+            .collect();
+
+        let mut emails = self
+            .find_entries(workspace_id, contact_id, Some(TimelineEntryType::EmailReceived), BRIEF_EMAILS)
+            .await?;
+        emails.extend(
+            self.find_entries(workspace_id, contact_id, Some(TimelineEntryType::EmailSent), BRIEF_EMAILS)
+                .await?,
+        );
+        emails.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+
+        let facts = PrepBriefFacts {
+            contact: &contact,
+            company: company.as_ref(),
+            recent_activity: &recent_activity,
+            open_tasks: &open_tasks,
+            emails: &emails,
+        };
+        let ctx = GenerationContext {
+            workspace_id,
+            campaign_id: None,
+        };
+        let brief = prep_brief(&self.generator, ctx, facts).await;
+
+        let entry = new_entry(
+            workspace_id,
+            contact_id,
+            TimelineEntryType::PrepBrief,
+            brief.markdown.clone(),
+            serde_json::json!({ "ai_generated": brief.ai_generated }),
+        );
+        let entry = self.timeline.record(workspace_id, entry).await?;
+
+        Ok(PrepBriefResponse {
+            contact_id: contact_id.to_string(),
+            markdown: brief.markdown,
+            ai_generated: brief.ai_generated,
+            entry: entry.into(),
+        })
+    }
+
+    /// Draft a reply to an email from a contact, in the light of their history
+    pub async fn draft_reply(&self, workspace_id: &str, req: DraftReplyRequest) -> AppResult<DraftReplyResponse> {
+        if req.email_text.trim().is_empty() {
+            return Err(AppError::BadRequest("email_text is required".into()));
+        }
+
+        let contact = self
+            .contacts
+            .find_by_id(workspace_id, &req.contact_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Contact {} not found", req.contact_id)))?;
+
+        let history = self
+            .find_entries(workspace_id, &req.contact_id, None, REPLY_HISTORY_ENTRIES)
+            .await?;
+
+        let contact_name = contact.full_name();
+        let facts = ReplyFacts {
+            contact_name: &contact_name,
+            subject: req.subject.as_deref(),
+            email_text: &req.email_text,
+            history: &history,
+            tone: req.tone,
+            length: req.length,
+        };
+
+        let (draft, ai_generated) = if self.generator.uses_templates() {
+            (template_reply(facts), false)
+        } else {
+            let ctx = GenerationContext {
+                workspace_id,
+                campaign_id: None,
+            };
+            match self.generator.generate_reply(ctx, &reply_brief(facts)).await {
+                Ok(draft) => (draft, true),
+                Err(e) => {
+                    tracing::warn!("AI reply draft failed, using the template: {}", e);
+                    (template_reply(facts), false)
+                }
+            }
+        };
+
+        Ok(DraftReplyResponse {
+            contact_id: req.contact_id,
+            subject: draft.subject,
+            body: draft.body,
+            tone: req.tone,
+            length: req.length,
+            ai_generated,
+        })
+    }
+
+    /// A contact's newest timeline entries, optionally of one type
+    async fn find_entries(
+        &self,
+        workspace_id: &str,
+        contact_id: &str,
+        entry_type: Option<TimelineEntryType>,
+        limit: u32,
+    ) -> AppResult<Vec<TimelineEntry>> {
+        let query = TimelineQuery {
+            contact_id: Some(contact_id.to_string()),
+            entry_type,
+            limit: Some(limit),
+            ..Default::default()
+        };
+        self.timeline.list(workspace_id, &query).await
+    }
+}
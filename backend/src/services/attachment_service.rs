@@ -0,0 +1,155 @@
+//! Attachment Service - files such as proposals and decks on contacts and timeline entries
+//!
+//! The file goes to object storage first and its metadata is saved after,
+//! so a failed save leaves at worst an unreferenced file, never metadata
+//! pointing at nothing. Deleting works the other way round.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use surrealdb::sql::Thing;
+use uuid::Uuid;
+
+use crate::config::AttachmentsConfig;
+use crate::db::{workspace_thing, Database};
+use crate::domain::validate_upload;
+use crate::error::{AppError, AppResult};
+use crate::models::{Attachment, AttachmentResponse};
+use crate::repositories::{AttachmentRepository, ContactRepository};
+use crate::services::object_storage::ObjectStorage;
+use crate::services::AuthenticatedUser;
+
+pub struct AttachmentService {
+    attachments: AttachmentRepository,
+    contacts: ContactRepository,
+    storage: Arc<dyn ObjectStorage>,
+    max_size_bytes: usize,
+    allowed_content_types: Vec<String>,
+}
+
+impl AttachmentService {
+    pub fn new(db: Arc<Database>, storage: Arc<dyn ObjectStorage>, config: &AttachmentsConfig) -> Self {
+        Self {
+            attachments: AttachmentRepository::new(Arc::clone(&db)),
+            contacts: ContactRepository::new(db),
+            storage,
+            max_size_bytes: config.max_size_bytes,
+            allowed_content_types: config.allowed_content_types.clone(),
+        }
+    }
+
+    pub async fn list_for_contact(&self, workspace_id: &str, contact_id: &str) -> AppResult<Vec<AttachmentResponse>> {
+        self.require_contact(workspace_id, contact_id).await?;
+        let attachments = self.attachments.find_for_contact(workspace_id, contact_id).await?;
+        Ok(attachments.into_iter().map(Into::into).collect())
+    }
+
+    /// Attach a file to a contact
+    pub async fn attach_to_contact(
+        &self,
+        actor: &AuthenticatedUser,
+        contact_id: &str,
+        filename: &str,
+        content_type: &str,
+        data: Vec<u8>,
+    ) -> AppResult<AttachmentResponse> {
+        self.require_contact(&actor.workspace_id, contact_id).await?;
+        let contact = Thing::from(("contact", contact_id));
+        self.store(actor, contact, None, filename, content_type, data).await
+    }
+
+    /// Attach a file to a timeline entry, and so to the entry's contact
+    pub async fn attach_to_entry(
+        &self,
+        actor: &AuthenticatedUser,
+        entry_id: &str,
+        filename: &str,
+        content_type: &str,
+        data: Vec<u8>,
+    ) -> AppResult<AttachmentResponse> {
+        let entry = self
+            .attachments
+            .find_timeline_entry(&actor.workspace_id, entry_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Timeline entry not found".into()))?;
+
+        self.store(actor, entry.contact, entry.id, filename, content_type, data).await
+    }
+
+    /// An attachment with its file's bytes
+    pub async fn download(&self, workspace_id: &str, id: &str) -> AppResult<(Attachment, Vec<u8>)> {
+        let attachment = self.find(workspace_id, id).await?;
+        let data = self
+            .storage
+            .get(&attachment.storage_key)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Attachment file is missing from storage".into()))?;
+
+        Ok((attachment, data))
+    }
+
+    pub async fn delete(&self, workspace_id: &str, id: &str) -> AppResult<()> {
+        let attachment = self.find(workspace_id, id).await?;
+        self.attachments.delete(&attachment).await?;
+
+        if let Err(e) = self.storage.delete(&attachment.storage_key).await {
+            tracing::warn!("Failed to remove attachment file {}: {}", attachment.storage_key, e);
+        }
+        Ok(())
+    }
+
+    async fn store(
+        &self,
+        actor: &AuthenticatedUser,
+        contact: Thing,
+        timeline_entry: Option<Thing>,
+        filename: &str,
+        content_type: &str,
+        data: Vec<u8>,
+    ) -> AppResult<AttachmentResponse> {
+        let (filename, content_type) = validate_upload(
+            filename,
+            content_type,
+            data.len(),
+            self.max_size_bytes,
+            &self.allowed_content_types,
+        )?;
+
+        let storage_key = format!("{}/{}", actor.workspace_id, Uuid::new_v4().simple());
+        let size = data.len() as u64;
+        self.storage.put(&storage_key, &content_type, data).await?;
+
+        let attachment = self
+            .attachments
+            .create(Attachment {
+                id: None,
+                workspace: workspace_thing(&actor.workspace_id),
+                contact,
+                timeline_entry,
+                filename,
+                content_type,
+                size,
+                storage_key,
+                uploaded_by: actor.user_id.clone(),
+                created_at: Utc::now(),
+            })
+            .await?;
+
+        Ok(attachment.into())
+    }
+
+    async fn find(&self, workspace_id: &str, id: &str) -> AppResult<Attachment> {
+        self.attachments
+            .find_by_id(workspace_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Attachment not found".into()))
+    }
+
+    async fn require_contact(&self, workspace_id: &str, contact_id: &str) -> AppResult<()> {
+        self.contacts
+            .find_by_id(workspace_id, contact_id)
+            .await?
+            .map(|_| ())
+            .ok_or_else(|| AppError::NotFound(format!("Contact '{}' not found", contact_id)))
+    }
+}
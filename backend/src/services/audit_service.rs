@@ -0,0 +1,126 @@
+//! Audit Service - records who changed what
+//!
+//! Called after every create, update and delete of a contact, company,
+//! campaign or event. The change itself has already been committed by
+//! then, so a failure to write the audit entry is logged rather than
+//! failing the request.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use serde::Serialize;
+use surrealdb::sql::Thing;
+
+use crate::db::{workspace_thing, Database};
+use crate::domain::{diff_all, AuditAction, AuditEntity, FieldChange};
+use crate::error::AppResult;
+use crate::models::{AuditLog, AuditLogResponse, AuditQuery};
+use crate::repositories::AuditRepository;
+use crate::services::AuthenticatedUser;
+
+pub struct AuditService {
+    repo: AuditRepository,
+}
+
+impl AuditService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            repo: AuditRepository::new(db),
+        }
+    }
+
+    /// Record a change with an explicit list of field changes
+    pub async fn record(
+        &self,
+        actor: &AuthenticatedUser,
+        entity_type: AuditEntity,
+        entity_id: &str,
+        action: AuditAction,
+        changes: Vec<FieldChange>,
+    ) {
+        // An update that changed nothing isn't worth an entry
+        if action == AuditAction::Update && changes.is_empty() {
+            return;
+        }
+
+        let entry = AuditLog {
+            id: None,
+            workspace: workspace_thing(&actor.workspace_id),
+            entity_type,
+            entity_id: entity_id.to_string(),
+            action,
+            actor: Some(Thing::from(("user", actor.user_id.as_str()))),
+            actor_email: Some(actor.email.clone()),
+            changes,
+            timestamp: Utc::now(),
+        };
+
+        if let Err(e) = self.repo.insert(entry).await {
+            tracing::error!(
+                "Failed to write audit log for {} {}: {}",
+                entity_type.as_str(),
+                entity_id,
+                e
+            );
+        }
+    }
+
+    pub async fn record_create<T: Serialize>(
+        &self,
+        actor: &AuthenticatedUser,
+        entity_type: AuditEntity,
+        entity_id: &str,
+        created: &T,
+    ) {
+        let changes = diff_all(&serde_json::Value::Null, &snapshot(created));
+        self.record(actor, entity_type, entity_id, AuditAction::Create, changes)
+            .await;
+    }
+
+    pub async fn record_update<T: Serialize>(
+        &self,
+        actor: &AuthenticatedUser,
+        entity_type: AuditEntity,
+        entity_id: &str,
+        before: &T,
+        after: &T,
+    ) {
+        let changes = diff_all(&snapshot(before), &snapshot(after));
+        self.record(actor, entity_type, entity_id, AuditAction::Update, changes)
+            .await;
+    }
+
+    pub async fn record_delete<T: Serialize>(
+        &self,
+        actor: &AuthenticatedUser,
+        entity_type: AuditEntity,
+        entity_id: &str,
+        deleted: &T,
+    ) {
+        let changes = diff_all(&snapshot(deleted), &serde_json::Value::Null);
+        self.record(actor, entity_type, entity_id, AuditAction::Delete, changes)
+            .await;
+    }
+
+    /// A record restored from the trash; logged like a create
+    pub async fn record_restore<T: Serialize>(
+        &self,
+        actor: &AuthenticatedUser,
+        entity_type: AuditEntity,
+        entity_id: &str,
+        restored: &T,
+    ) {
+        let changes = diff_all(&serde_json::Value::Null, &snapshot(restored));
+        self.record(actor, entity_type, entity_id, AuditAction::Restore, changes)
+            .await;
+    }
+
+    pub async fn list(&self, workspace_id: &str, query: &AuditQuery) -> AppResult<Vec<AuditLogResponse>> {
+        let entries = self.repo.find(workspace_id, query).await?;
+        Ok(entries.into_iter().map(Into::into).collect())
+    }
+}
+
+fn snapshot<T: Serialize>(value: &T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap_or_default()
+}
@@ -0,0 +1,269 @@
+//! Auth Service - Registration, login and JWT issuance
+//!
+//! Passwords are hashed with bcrypt. Successful logins return a pair of
+//! tokens signed with the configured JWT secret:
+//! - an access token (short-lived) sent as `Authorization: Bearer ...`
+//! - a refresh token (long-lived) exchanged at /api/auth/refresh
+//!
+//! The token type is part of the claims so a refresh token can never be
+//! used to call the API directly, and vice versa.
+//!
+//! Each user belongs to one workspace. Registering creates a new workspace;
+//! the workspace ID travels in the token so every request is tenant-scoped.
+
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::config::JwtConfig;
+use crate::db::{workspace_thing, Database};
+use crate::domain::{validate_email, validate_name, validate_password};
+use crate::error::{AppError, AppResult};
+use crate::models::{AuthResponse, User, UserRole, Workspace};
+use crate::repositories::{UserRepository, WorkspaceRepository};
+
+/// Distinguishes access tokens from refresh tokens
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenType {
+    Access,
+    Refresh,
+}
+
+/// JWT claims carried by every token we issue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    /// User ID
+    pub sub: String,
+    pub email: String,
+    /// Workspace ID the user belongs to
+    pub workspace: String,
+    pub typ: TokenType,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+/// The identity of the user making the current request
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser {
+    pub user_id: String,
+    pub email: String,
+    pub workspace_id: String,
+}
+
+impl From<Claims> for AuthenticatedUser {
+    fn from(claims: Claims) -> Self {
+        Self {
+            user_id: claims.sub,
+            email: claims.email,
+            workspace_id: claims.workspace,
+        }
+    }
+}
+
+pub struct AuthService {
+    repo: UserRepository,
+    workspaces: WorkspaceRepository,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    access_ttl: Duration,
+    refresh_ttl: Duration,
+}
+
+impl AuthService {
+    pub fn new(db: Arc<Database>, config: &JwtConfig) -> Self {
+        Self {
+            repo: UserRepository::new(Arc::clone(&db)),
+            workspaces: WorkspaceRepository::new(db),
+            encoding_key: EncodingKey::from_secret(config.secret.as_bytes()),
+            decoding_key: DecodingKey::from_secret(config.secret.as_bytes()),
+            access_ttl: Duration::minutes(config.access_token_ttl_minutes),
+            refresh_ttl: Duration::days(config.refresh_token_ttl_days),
+        }
+    }
+
+    /// Register a new user in a fresh workspace and log them in
+    pub async fn register(
+        &self,
+        email: &str,
+        name: &str,
+        password: &str,
+        workspace_name: Option<&str>,
+    ) -> AppResult<AuthResponse> {
+        let email = email.trim().to_lowercase();
+        validate_email(&email)?;
+        validate_name(name, "name")?;
+        validate_password(password)?;
+
+        if self.repo.find_by_email(&email).await?.is_some() {
+            return Err(AppError::Conflict(format!(
+                "A user with email '{}' already exists",
+                email
+            )));
+        }
+
+        let workspace_name = match workspace_name.map(str::trim) {
+            Some(ws) if !ws.is_empty() => ws.to_string(),
+            _ => format!("{}'s Workspace", name.trim()),
+        };
+        validate_name(&workspace_name, "workspace_name")?;
+
+        let password_hash = hash_password(password.to_string()).await?;
+        let now = Utc::now();
+
+        let workspace = self
+            .workspaces
+            .create(Workspace {
+                id: None,
+                name: workspace_name,
+                created_at: now,
+                updated_at: now,
+            })
+            .await?;
+        let workspace_id = workspace
+            .id
+            .map(|t| t.id.to_string())
+            .ok_or_else(|| AppError::Internal("Workspace has no ID".into()))?;
+
+        let user = self
+            .repo
+            .create(User {
+                id: None,
+                workspace: workspace_thing(&workspace_id),
+                email,
+                name: name.trim().to_string(),
+                password_hash,
+                role: UserRole::Owner,
+                created_at: now,
+                updated_at: now,
+            })
+            .await?;
+
+        self.issue_tokens(user)
+    }
+
+    /// Verify credentials and issue a new token pair
+    pub async fn login(&self, email: &str, password: &str) -> AppResult<AuthResponse> {
+        // Same error for unknown email and bad password - don't leak which one
+        let invalid = || AppError::Unauthorized("Invalid email or password".into());
+
+        let user = self
+            .repo
+            .find_by_email(email.trim())
+            .await?
+            .ok_or_else(invalid)?;
+
+        if user.password_hash.is_empty() || !verify_password(password.to_string(), user.password_hash.clone()).await? {
+            return Err(invalid());
+        }
+
+        self.issue_tokens(user)
+    }
+
+    /// Exchange a refresh token for a new token pair
+    pub async fn refresh(&self, refresh_token: &str) -> AppResult<AuthResponse> {
+        let claims = self.decode_token(refresh_token, TokenType::Refresh)?;
+
+        let user = self
+            .repo
+            .find_by_id(&claims.sub)
+            .await?
+            .ok_or_else(|| AppError::Unauthorized("User no longer exists".into()))?;
+
+        self.issue_tokens(user)
+    }
+
+    /// Look up the user behind an authenticated identity
+    pub async fn current_user(&self, identity: &AuthenticatedUser) -> AppResult<User> {
+        self.repo
+            .find_by_id(&identity.user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("User '{}' not found", identity.user_id)))
+    }
+
+    /// Fail unless the user is an owner or admin of their workspace
+    pub async fn require_admin(&self, identity: &AuthenticatedUser) -> AppResult<()> {
+        let user = self.current_user(identity).await?;
+        if user.workspace.id.to_raw() != identity.workspace_id || !user.role.is_admin() {
+            return Err(AppError::Forbidden("Only workspace owners and admins can do this".into()));
+        }
+        Ok(())
+    }
+
+    /// Validate an access token (used by the auth middleware)
+    pub fn verify_access_token(&self, token: &str) -> AppResult<AuthenticatedUser> {
+        self.decode_token(token, TokenType::Access).map(Into::into)
+    }
+
+    fn decode_token(&self, token: &str, expected: TokenType) -> AppResult<Claims> {
+        let data = decode::<Claims>(token, &self.decoding_key, &Validation::default())
+            .map_err(|e| AppError::Unauthorized(format!("Invalid token: {}", e)))?;
+
+        if data.claims.typ != expected {
+            return Err(AppError::Unauthorized("Wrong token type".into()));
+        }
+
+        Ok(data.claims)
+    }
+
+    fn issue_tokens(&self, user: User) -> AppResult<AuthResponse> {
+        let user_id = user
+            .id
+            .as_ref()
+            .map(|t| t.id.to_string())
+            .ok_or_else(|| AppError::Internal("User has no ID".into()))?;
+
+        let workspace_id = user.workspace.id.to_string();
+
+        let access_token = self.sign(&user_id, &user.email, &workspace_id, TokenType::Access, self.access_ttl)?;
+        let refresh_token = self.sign(&user_id, &user.email, &workspace_id, TokenType::Refresh, self.refresh_ttl)?;
+
+        Ok(AuthResponse {
+            access_token,
+            refresh_token,
+            token_type: "Bearer".to_string(),
+            expires_in: self.access_ttl.num_seconds(),
+            user: user.into(),
+        })
+    }
+
+    fn sign(
+        &self,
+        user_id: &str,
+        email: &str,
+        workspace_id: &str,
+        typ: TokenType,
+        ttl: Duration,
+    ) -> AppResult<String> {
+        let now = Utc::now();
+        let claims = Claims {
+            sub: user_id.to_string(),
+            email: email.to_string(),
+            workspace: workspace_id.to_string(),
+            typ,
+            iat: now.timestamp(),
+            exp: (now + ttl).timestamp(),
+        };
+
+        encode(&Header::default(), &claims, &self.encoding_key)
+            .map_err(|e| AppError::Internal(format!("Failed to sign token: {}", e)))
+    }
+}
+
+// bcrypt is deliberately slow - keep it off the async runtime threads
+
+async fn hash_password(password: String) -> AppResult<String> {
+    tokio::task::spawn_blocking(move || bcrypt::hash(password, bcrypt::DEFAULT_COST))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .map_err(|e| AppError::Internal(format!("Failed to hash password: {}", e)))
+}
+
+async fn verify_password(password: String, hash: String) -> AppResult<bool> {
+    tokio::task::spawn_blocking(move || bcrypt::verify(password, &hash))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .map_err(|e| AppError::Internal(format!("Failed to verify password: {}", e)))
+}
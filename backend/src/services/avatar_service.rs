@@ -0,0 +1,142 @@
+//! Avatar Service - uploaded contact pictures
+//!
+//! An upload is resized to every standard size (see `domain::resize_avatar`)
+//! and stored under a fresh avatar ID, which then becomes part of the
+//! contact's `avatar_url`. Replacing or removing an avatar deletes the old
+//! files. Avatars are served without authentication so they work in `<img>`
+//! tags; the random avatar ID in the URL is what keeps them private.
+
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use crate::config::AvatarsConfig;
+use crate::db::Database;
+use crate::domain::{avatar_size, resize_avatar, AuditEntity, ContactUpdater, DomainError, AVATAR_SIZES};
+use crate::error::{AppError, AppResult};
+use crate::models::ContactResponse;
+use crate::repositories::{ContactRepository, StoredContact};
+use crate::services::object_storage::ObjectStorage;
+use crate::services::{AuditService, AuthenticatedUser};
+
+pub struct AvatarService {
+    contacts: ContactRepository,
+    audit: AuditService,
+    storage: Arc<dyn ObjectStorage>,
+    /// Public base URL avatar URLs start with
+    base_url: String,
+    max_size_bytes: usize,
+}
+
+impl AvatarService {
+    pub fn new(db: Arc<Database>, storage: Arc<dyn ObjectStorage>, base_url: &str, config: &AvatarsConfig) -> Self {
+        Self {
+            contacts: ContactRepository::new(Arc::clone(&db)),
+            audit: AuditService::new(db),
+            storage,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            max_size_bytes: config.max_size_bytes,
+        }
+    }
+
+    /// Replace a contact's avatar with an uploaded image
+    pub async fn upload(&self, actor: &AuthenticatedUser, contact_id: &str, data: Vec<u8>) -> AppResult<ContactResponse> {
+        if data.is_empty() || data.len() > self.max_size_bytes {
+            return Err(DomainError::InvalidField {
+                field: "file".to_string(),
+                reason: format!("Must be between 1 and {} bytes", self.max_size_bytes),
+            }
+            .into());
+        }
+        let stored = self.find_contact(&actor.workspace_id, contact_id).await?;
+
+        // Decoding and resizing is CPU-bound; keep it off the async workers
+        let resized = tokio::task::spawn_blocking(move || resize_avatar(&data))
+            .await
+            .map_err(|e| AppError::Internal(format!("Avatar resizing failed: {}", e)))??;
+
+        let avatar_id = Uuid::new_v4().simple().to_string();
+        for (size, png) in resized {
+            self.storage
+                .put(&storage_key(&actor.workspace_id, &avatar_id, size), "image/png", png)
+                .await?;
+        }
+
+        let url = format!("{}/avatars/{}/{}", self.base_url, actor.workspace_id, avatar_id);
+        self.set_avatar_url(actor, stored, Some(&url)).await
+    }
+
+    /// Remove a contact's uploaded avatar, falling back to its Gravatar
+    pub async fn remove(&self, actor: &AuthenticatedUser, contact_id: &str) -> AppResult<ContactResponse> {
+        let stored = self.find_contact(&actor.workspace_id, contact_id).await?;
+        self.set_avatar_url(actor, stored, None).await
+    }
+
+    /// An avatar's PNG at the standard size closest to `size`
+    pub async fn image(&self, workspace_id: &str, avatar_id: &str, size: Option<u32>) -> AppResult<Vec<u8>> {
+        // Both end up in a storage key; don't let them climb out of it
+        if !is_id(workspace_id) || !is_id(avatar_id) {
+            return Err(AppError::NotFound("Avatar not found".into()));
+        }
+
+        self.storage
+            .get(&storage_key(workspace_id, avatar_id, avatar_size(size)))
+            .await?
+            .ok_or_else(|| AppError::NotFound("Avatar not found".into()))
+    }
+
+    /// Delete the stored files behind an avatar URL; URLs of other sites are left alone
+    pub async fn delete_files(&self, workspace_id: &str, avatar_url: &str) {
+        let prefix = format!("{}/avatars/{}/", self.base_url, workspace_id);
+        let Some(avatar_id) = avatar_url.strip_prefix(&prefix) else {
+            return;
+        };
+
+        for size in AVATAR_SIZES {
+            if let Err(e) = self.storage.delete(&storage_key(workspace_id, avatar_id, size)).await {
+                tracing::warn!("Failed to remove avatar {}: {}", avatar_id, e);
+            }
+        }
+    }
+
+    async fn find_contact(&self, workspace_id: &str, contact_id: &str) -> AppResult<StoredContact> {
+        self.contacts
+            .find_by_id_with_id(workspace_id, contact_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Contact '{}' not found", contact_id)))
+    }
+
+    /// Save the new URL, then delete the files of the avatar it replaces
+    async fn set_avatar_url(
+        &self,
+        actor: &AuthenticatedUser,
+        stored: StoredContact,
+        url: Option<&str>,
+    ) -> AppResult<ContactResponse> {
+        let workspace_id = actor.workspace_id.as_str();
+        let before = stored.contact;
+        let contact = ContactUpdater::new(before.clone()).avatar_url(url).apply()?;
+        let contact = self.contacts.update(workspace_id, &stored.id, &contact).await?;
+        self.audit
+            .record_update(actor, AuditEntity::Contact, &stored.id, &before, &contact)
+            .await;
+
+        // Avatar IDs are never reused, so the old files are always unreferenced now
+        if let Some(old) = &before.avatar_url {
+            self.delete_files(workspace_id, old).await;
+        }
+
+        Ok(ContactResponse::from_stored(StoredContact {
+            id: stored.id,
+            contact,
+        }))
+    }
+}
+
+fn storage_key(workspace_id: &str, avatar_id: &str, size: u32) -> String {
+    format!("{}/avatars/{}/{}", workspace_id, avatar_id, size)
+}
+
+fn is_id(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
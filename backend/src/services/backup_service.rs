@@ -0,0 +1,905 @@
+//! Backup Service - a whole workspace exported to an archive and restored from one
+//!
+//! An archive is newline-delimited JSON. The first line is a header naming
+//! the format, its version and the workspace; then comes one line per
+//! record, `{"table": ..., "record": ...}`, with the record written as
+//! SurrealQL so record links and datetimes come back as they were; the last
+//! line, `{"end": true, "records": N}`, tells a complete archive from a
+//! truncated one.
+//!
+//! Exports stream: each table is read with keyset pagination and sent a
+//! page at a time. Credentials (password hashes, webhook secrets, inbound
+//! tokens, connected accounts' tokens, sender domain verification) are left
+//! out of the archive.
+//!
+//! Restoring replaces the workspace. The archive is read as it streams in
+//! and checked a batch of records at a time: every record must be of a
+//! backed-up table and of the archive's workspace, hold plain data only (no
+//! queries, functions or params to run on insert), and link to no record of
+//! another workspace, and no user may have the email of another
+//! workspace's user. Checked batches are staged in the database, and once
+//! the end line has counted them all, the workspace's records are deleted
+//! and the staged ones inserted with their original IDs, in one transaction
+//! run on the server. A bad archive changes nothing but its own staging.
+//! Credentials are never taken from the archive: records that still exist
+//! keep their own, and the rest come back without them (accounts that
+//! can't sign in, webhooks paused with a new secret, inbound sources with a
+//! new token, connected accounts to reconnect, sender identities with a new
+//! verification token to verify again). A sender identity whose domain
+//! changed is verified again too. User roles are kept the same way; users
+//! the workspace doesn't have come back as members.
+//! Attachment files live in object storage and are not part of the
+//! archive, only their records are.
+
+use std::collections::{BTreeMap, HashMap};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use futures::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Id, Thing, Value};
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::error::{AppError, AppResult};
+use crate::models::{RestoreResponse, RestoredTable};
+use crate::repositories::{BackupRepository, BACKUP_TABLES};
+use crate::services::inbound_service::generate_token;
+use crate::services::read_cache::{CacheScope, ReadCache};
+use crate::services::sender_service::generate_verification_token;
+use crate::services::webhook_service::generate_secret;
+
+/// Name of the archive format, in its header
+const BACKUP_FORMAT: &str = "crm-backup";
+
+/// Version of the archive format; restores read this version and older
+const BACKUP_VERSION: u32 = 1;
+
+/// Records read from the database per chunk of an export
+const BACKUP_PAGE_SIZE: u32 = 500;
+
+/// Records checked and staged together during a restore
+const RESTORE_BATCH_SIZE: usize = 500;
+
+/// Hours after which a restore's staged batches count as abandoned
+const STAGING_TTL_HOURS: i64 = 24;
+
+/// Record links looked up per query when checking a restore's links
+const LINK_CHECK_BATCH_SIZE: usize = 1000;
+
+/// Longest line a restore accepts; records are far smaller
+const MAX_LINE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Largest archive a restore reads; it is staged in the database until checked
+const MAX_ARCHIVE_BYTES: usize = 1024 * 1024 * 1024;
+
+/// Fields holding credentials, or vouching for a domain, per table; `.`
+/// reaches into an object
+const CREDENTIAL_FIELDS: &[(&str, &[&str])] = &[
+    ("user", &["password_hash"]),
+    ("webhook", &["secret"]),
+    ("inbound_source", &["token"]),
+    ("social_account", &["access_token"]),
+    ("integration", &["oauth", "oauth_state", "imap.password"]),
+    ("sender_identity", &["verification_token", "ownership"]),
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupHeader {
+    format: String,
+    version: u32,
+    workspace_id: String,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum BackupLine {
+    Record { table: String, record: String },
+    End { end: bool, records: u64 },
+}
+
+/// Where the export stream is in its lifecycle
+enum Phase {
+    Header,
+    Table { index: usize, after: Option<Thing> },
+    Done,
+}
+
+struct ExportState {
+    repo: BackupRepository,
+    workspace_id: String,
+    records: u64,
+    phase: Phase,
+}
+
+pub struct BackupService {
+    db: Arc<Database>,
+    cache: Arc<ReadCache>,
+}
+
+impl BackupService {
+    pub fn new(db: Arc<Database>, cache: Arc<ReadCache>) -> Self {
+        Self { db, cache }
+    }
+
+    /// Stream the workspace as an archive, in text chunks
+    ///
+    /// The first chunk is the header line, followed by one chunk per page of
+    /// records and the end line. A database error ends the stream without
+    /// the end line, so the partial archive won't restore.
+    pub fn export(&self, workspace_id: &str) -> impl Stream<Item = AppResult<String>> + Send + 'static {
+        let state = ExportState {
+            repo: BackupRepository::new(Arc::clone(&self.db)),
+            workspace_id: workspace_id.to_string(),
+            records: 0,
+            phase: Phase::Header,
+        };
+
+        stream::unfold(state, |mut state| async move {
+            loop {
+                match std::mem::replace(&mut state.phase, Phase::Done) {
+                    Phase::Header => {
+                        state.phase = Phase::Table { index: 0, after: None };
+                        let header = BackupHeader {
+                            format: BACKUP_FORMAT.to_string(),
+                            version: BACKUP_VERSION,
+                            workspace_id: state.workspace_id.clone(),
+                            created_at: Utc::now(),
+                        };
+                        return Some((json_line(&header), state));
+                    }
+                    Phase::Table { index, after } => {
+                        let Some(table) = BACKUP_TABLES.get(index).copied() else {
+                            let end = BackupLine::End { end: true, records: state.records };
+                            return Some((json_line(&end), state));
+                        };
+
+                        let page = state
+                            .repo
+                            .find_page_after(table, &state.workspace_id, after.as_ref(), BACKUP_PAGE_SIZE)
+                            .await;
+                        let page = match page {
+                            Ok(page) => page,
+                            Err(e) => return Some((Err(e), state)),
+                        };
+
+                        let last_id = page.last().and_then(record_id);
+                        state.phase = match last_id {
+                            Some(last_id) if page.len() == BACKUP_PAGE_SIZE as usize => Phase::Table {
+                                index,
+                                after: Some(last_id),
+                            },
+                            _ => Phase::Table { index: index + 1, after: None },
+                        };
+
+                        // Empty tables produce no chunk
+                        if page.is_empty() {
+                            continue;
+                        }
+                        state.records += page.len() as u64;
+                        return Some((encode_page(table, page), state));
+                    }
+                    Phase::Done => return None,
+                }
+            }
+        })
+    }
+
+    /// Replace a workspace with the one in an archive, read from a stream of byte chunks
+    ///
+    /// With `workspace_id`, the archive must be of that workspace; without,
+    /// it is restored into the workspace it was taken from.
+    pub async fn restore<S, B>(&self, workspace_id: Option<&str>, chunks: S) -> AppResult<RestoreResponse>
+    where
+        S: Stream<Item = AppResult<B>> + Send,
+        B: AsRef<[u8]>,
+    {
+        let mut lines = Lines::new(chunks);
+
+        let header = lines
+            .next()
+            .await?
+            .ok_or_else(|| AppError::BadRequest("The backup is empty".into()))?;
+        let header = parse_header(&header, workspace_id)?;
+        let workspace_id = header.workspace_id.as_str();
+
+        let repo = BackupRepository::new(Arc::clone(&self.db));
+        repo.discard_staged_before(Utc::now() - chrono::Duration::hours(STAGING_TTL_HOURS))
+            .await?;
+
+        let restore_id = Uuid::new_v4().to_string();
+        let staged = match self.stage(&repo, &restore_id, workspace_id, &mut lines).await {
+            Ok(counts) => repo.replace_workspace(workspace_id, &restore_id).await.map(|()| counts),
+            Err(e) => Err(e),
+        };
+        let counts = match staged {
+            Ok(counts) => counts,
+            Err(e) => {
+                if let Err(discard) = repo.discard_staged(&restore_id).await {
+                    tracing::warn!("Failed to discard the staged restore {}: {}", restore_id, discard);
+                }
+                return Err(e);
+            }
+        };
+
+        self.cache.invalidate(workspace_id, CacheScope::Contacts).await;
+        self.cache.invalidate(workspace_id, CacheScope::Deals).await;
+        self.cache.invalidate(workspace_id, CacheScope::Timeline).await;
+
+        let total: u64 = counts.values().sum();
+        tracing::info!("Restored workspace {} ({} records)", workspace_id, total);
+        Ok(RestoreResponse {
+            workspace_id: header.workspace_id.clone(),
+            backed_up_at: header.created_at,
+            records: total,
+            tables: counts
+                .into_iter()
+                .map(|(table, records)| RestoredTable {
+                    table: table.to_string(),
+                    records,
+                })
+                .collect(),
+        })
+    }
+
+    /// Read an archive's records, checking and staging them a batch at a
+    /// time, and count them per table
+    async fn stage<S, B>(
+        &self,
+        repo: &BackupRepository,
+        restore_id: &str,
+        workspace_id: &str,
+        lines: &mut Lines<S>,
+    ) -> AppResult<BTreeMap<&'static str, u64>>
+    where
+        S: Stream<Item = AppResult<B>> + Send,
+        B: AsRef<[u8]>,
+    {
+        let mut current: HashMap<String, Value> = HashMap::new();
+        for (table, _) in CREDENTIAL_FIELDS {
+            for record in repo.find_all(table, workspace_id).await? {
+                if let Some(id) = record_id(&record) {
+                    current.insert(id.to_string(), record);
+                }
+            }
+        }
+
+        let mut counts: BTreeMap<&'static str, u64> = BTreeMap::new();
+        let mut batcher = Batcher::default();
+        let mut seq = 0;
+        let mut end = None;
+
+        while let Some(line) = lines.next().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if end.is_some() {
+                return Err(AppError::BadRequest("The backup has lines after its end".into()));
+            }
+
+            let (table, record) = match parse_line(&line)? {
+                BackupLine::Record { table, record } => (table, record),
+                BackupLine::End { records, .. } => {
+                    end = Some(records);
+                    continue;
+                }
+            };
+            let table = backup_table(&table)?;
+            let record = surrealdb::sql::value(&record)
+                .map_err(|e| AppError::BadRequest(format!("Unreadable {} record in the backup: {}", table, e)))?;
+            check_record(table, workspace_id, &record)?;
+            *counts.entry(table).or_default() += 1;
+
+            if let Some((table, records)) = batcher.push(table, record) {
+                stage_batch(repo, restore_id, seq, workspace_id, &current, table, records).await?;
+                seq += 1;
+            }
+        }
+        if let Some((table, records)) = batcher.take() {
+            stage_batch(repo, restore_id, seq, workspace_id, &current, table, records).await?;
+        }
+
+        let total: u64 = counts.values().sum();
+        match end {
+            Some(expected) if expected == total => Ok(counts),
+            Some(expected) => Err(AppError::BadRequest(format!(
+                "The backup lists {} records but has {}; nothing was restored",
+                expected, total
+            ))),
+            None => Err(AppError::BadRequest("The backup is truncated; nothing was restored".into())),
+        }
+    }
+}
+
+/// Check a batch of an archive's records against the database and stage it
+///
+/// Links to the workspace record were checked with each record; any other
+/// link, own IDs included, must not be another workspace's record. Users
+/// may not have the email of another workspace's user: logins look users
+/// up by email alone.
+async fn stage_batch(
+    repo: &BackupRepository,
+    restore_id: &str,
+    seq: u64,
+    workspace_id: &str,
+    current: &HashMap<String, Value>,
+    table: &'static str,
+    mut records: Vec<Value>,
+) -> AppResult<()> {
+    let mut links = HashMap::new();
+    for record in &records {
+        collect_links(record, &mut links);
+    }
+    let links: Vec<Thing> = links.into_values().filter(|link| link.tb != "workspace").collect();
+    for chunk in links.chunks(LINK_CHECK_BATCH_SIZE) {
+        let foreign = repo.find_outside_workspace(workspace_id, chunk.to_vec()).await?;
+        if let Some(link) = foreign.first() {
+            return Err(AppError::BadRequest(format!(
+                "The backup links to {}, a record of another workspace; nothing was restored",
+                link
+            )));
+        }
+    }
+
+    if table == "user" {
+        let emails: Vec<String> = records.iter().filter_map(user_email).collect();
+        let taken = repo.find_emails_outside_workspace(workspace_id, emails).await?;
+        if let Some(email) = taken.first() {
+            return Err(AppError::BadRequest(format!(
+                "The backup has a user {}, who belongs to another workspace; nothing was restored",
+                email
+            )));
+        }
+    }
+
+    for record in &mut records {
+        let existing = record_id(record).and_then(|id| current.get(&id.to_string()));
+        keep_credentials(table, record, existing);
+    }
+
+    repo.stage(restore_id, seq, table, records).await
+}
+
+/// A user record's email, in lower case
+fn user_email(record: &Value) -> Option<String> {
+    match get_field(record, "email") {
+        Some(Value::Strand(email)) => Some(email.as_str().to_lowercase()),
+        _ => None,
+    }
+}
+
+/// Records gathered into batches of one table each, in archive order
+#[derive(Default)]
+struct Batcher {
+    table: Option<&'static str>,
+    records: Vec<Value>,
+}
+
+impl Batcher {
+    /// Add a record, handing back the batch it closed, if any
+    fn push(&mut self, table: &'static str, record: Value) -> Option<(&'static str, Vec<Value>)> {
+        let closed = match self.table {
+            Some(current) if current != table || self.records.len() >= RESTORE_BATCH_SIZE => self.take(),
+            _ => None,
+        };
+        self.table = Some(table);
+        self.records.push(record);
+        closed
+    }
+
+    /// The open batch, if it has records
+    fn take(&mut self) -> Option<(&'static str, Vec<Value>)> {
+        let table = self.table.take()?;
+        let records = std::mem::take(&mut self.records);
+        (!records.is_empty()).then_some((table, records))
+    }
+}
+
+/// Remove a table's credential fields from a record
+fn strip_credentials(table: &str, record: &mut Value) {
+    for field in credential_fields(table) {
+        remove_field(record, field);
+    }
+}
+
+/// Give a restored record credentials and role from the record it replaces, never from the archive
+///
+/// Without an existing record, credentials are left blank or replaced with
+/// new ones, and whatever used them is stopped until someone reconnects it.
+fn keep_credentials(table: &str, record: &mut Value, existing: Option<&Value>) {
+    strip_credentials(table, record);
+
+    if let Some(existing) = existing {
+        let kept = credential_fields(table).iter().chain(if table == "user" { &["role"][..] } else { &[] });
+        for field in kept {
+            match get_field(existing, field) {
+                Some(value) => set_field(record, field, value.clone()),
+                None => remove_field(record, field),
+            }
+        }
+        if table == "sender_identity" && get_field(record, "domain") != get_field(existing, "domain") {
+            remove_field(record, "ownership");
+        }
+        return;
+    }
+
+    match table {
+        "user" => {
+            set_field(record, "password_hash", Value::from(""));
+            set_field(record, "role", Value::from("member"));
+        }
+        "webhook" => {
+            set_field(record, "secret", Value::from(generate_secret()));
+            set_field(record, "active", Value::Bool(false));
+        }
+        "inbound_source" => set_field(record, "token", Value::from(generate_token())),
+        "sender_identity" => set_field(record, "verification_token", Value::from(generate_verification_token())),
+        "social_account" => set_field(record, "access_token", Value::from("")),
+        "integration" => {
+            if get_field(record, "imap").is_some() {
+                set_field(record, "imap.password", Value::from(""));
+            }
+            set_field(record, "status", Value::from("error"));
+            set_field(record, "last_error", Value::from("Restored from a backup; reconnect the account"));
+        }
+        _ => {}
+    }
+}
+
+fn credential_fields(table: &str) -> &'static [&'static str] {
+    CREDENTIAL_FIELDS
+        .iter()
+        .find(|(t, _)| *t == table)
+        .map(|(_, fields)| *fields)
+        .unwrap_or_default()
+}
+
+/// The object a dotted path's last part is in, and that part
+fn parent_mut<'a>(record: &'a mut Value, path: &'a str) -> Option<(&'a mut BTreeMap<String, Value>, &'a str)> {
+    let (parents, name) = match path.rsplit_once('.') {
+        Some((parents, name)) => (Some(parents), name),
+        None => (None, path),
+    };
+
+    let mut object = match record {
+        Value::Object(object) => &mut object.0,
+        _ => return None,
+    };
+    for part in parents.into_iter().flat_map(|p| p.split('.')) {
+        object = match object.get_mut(part) {
+            Some(Value::Object(inner)) => &mut inner.0,
+            _ => return None,
+        };
+    }
+    Some((object, name))
+}
+
+fn get_field<'a>(record: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(record, |value, part| match value {
+        Value::Object(object) => object.get(part),
+        _ => None,
+    })
+}
+
+fn set_field(record: &mut Value, path: &str, value: Value) {
+    if let Some((object, name)) = parent_mut(record, path) {
+        object.insert(name.to_string(), value);
+    }
+}
+
+fn remove_field(record: &mut Value, path: &str) {
+    if let Some((object, name)) = parent_mut(record, path) {
+        object.remove(name);
+    }
+}
+
+fn json_line<T: Serialize>(value: &T) -> AppResult<String> {
+    serde_json::to_string(value)
+        .map(|json| json + "\n")
+        .map_err(|e| AppError::Internal(format!("Failed to encode the backup: {}", e)))
+}
+
+/// One line per record, as a single chunk
+fn encode_page(table: &str, page: Vec<Value>) -> AppResult<String> {
+    let mut chunk = String::new();
+    for mut record in page {
+        strip_credentials(table, &mut record);
+        chunk.push_str(&json_line(&BackupLine::Record {
+            table: table.to_string(),
+            record: record.to_string(),
+        })?);
+    }
+    Ok(chunk)
+}
+
+fn record_id(record: &Value) -> Option<Thing> {
+    match record {
+        Value::Object(fields) => match fields.get("id") {
+            Some(Value::Thing(id)) => Some(id.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn parse_header(line: &str, workspace_id: Option<&str>) -> AppResult<BackupHeader> {
+    let header: BackupHeader = serde_json::from_str(line)
+        .map_err(|_| AppError::BadRequest("This is not a CRM backup: its header is missing".into()))?;
+
+    if header.format != BACKUP_FORMAT {
+        return Err(AppError::BadRequest(format!("This is not a CRM backup but '{}'", header.format)));
+    }
+    if header.version > BACKUP_VERSION {
+        return Err(AppError::BadRequest(format!(
+            "The backup is version {}, newer than this server reads ({}); upgrade the server",
+            header.version, BACKUP_VERSION
+        )));
+    }
+    if let Some(workspace_id) = workspace_id
+        && header.workspace_id != workspace_id
+    {
+        return Err(AppError::BadRequest("The backup is of another workspace".into()));
+    }
+
+    Ok(header)
+}
+
+fn parse_line(line: &str) -> AppResult<BackupLine> {
+    serde_json::from_str(line).map_err(|e| AppError::BadRequest(format!("Unreadable line in the backup: {}", e)))
+}
+
+fn backup_table(table: &str) -> AppResult<&'static str> {
+    BACKUP_TABLES
+        .iter()
+        .find(|t| **t == table)
+        .copied()
+        .ok_or_else(|| AppError::BadRequest(format!("The backup has records of unknown table '{}'", table)))
+}
+
+/// A record belongs in `table` and to the workspace being restored
+fn check_record(table: &str, workspace_id: &str, record: &Value) -> AppResult<()> {
+    let invalid =
+        |reason: &str| Err(AppError::BadRequest(format!("Invalid {} record in the backup: {}", table, reason)));
+
+    let Value::Object(fields) = record else {
+        return invalid("not an object");
+    };
+    let Some(Value::Thing(id)) = fields.get("id") else {
+        return invalid("no ID");
+    };
+    if id.tb != table {
+        return invalid("its ID is of another table");
+    }
+
+    let workspace = match table {
+        "workspace" => Some(id),
+        _ => match fields.get("workspace") {
+            Some(Value::Thing(workspace)) => Some(workspace),
+            _ => None,
+        },
+    };
+    match workspace {
+        Some(workspace) if workspace.tb == "workspace" && workspace.id.to_raw() == workspace_id => {}
+        _ => return invalid("it belongs to another workspace"),
+    }
+
+    if !is_data(record) {
+        return invalid("it holds something other than plain data");
+    }
+    let mut links = HashMap::new();
+    collect_links(record, &mut links);
+    if links.values().any(|link| link.tb == "workspace" && link.id.to_raw() != workspace_id) {
+        return invalid("it links to another workspace");
+    }
+    Ok(())
+}
+
+/// A value is data a restore can insert as it is
+///
+/// Anything SurrealQL would compute on insert (subqueries, functions,
+/// params, futures and the like) is not, and neither is a link to a table
+/// outside the backup or with a computed ID.
+fn is_data(value: &Value) -> bool {
+    match value {
+        Value::None
+        | Value::Null
+        | Value::Bool(_)
+        | Value::Number(_)
+        | Value::Strand(_)
+        | Value::Duration(_)
+        | Value::Datetime(_)
+        | Value::Uuid(_) => true,
+        Value::Array(values) => values.iter().all(is_data),
+        Value::Object(fields) => fields.values().all(is_data),
+        Value::Thing(thing) => {
+            BACKUP_TABLES.contains(&thing.tb.as_str()) && matches!(thing.id, Id::Number(_) | Id::String(_))
+        }
+        _ => false,
+    }
+}
+
+/// Every record link in a value, its own ID and relationship ends
+/// included, keyed by the record ID as text
+fn collect_links(value: &Value, links: &mut HashMap<String, Thing>) {
+    match value {
+        Value::Thing(thing) => {
+            links.insert(thing.to_string(), thing.clone());
+        }
+        Value::Array(values) => values.iter().for_each(|v| collect_links(v, links)),
+        Value::Object(fields) => fields.values().for_each(|v| collect_links(v, links)),
+        _ => {}
+    }
+}
+
+/// Lines of text read from a stream of byte chunks
+struct Lines<S> {
+    chunks: Pin<Box<S>>,
+    buffer: Vec<u8>,
+    /// Bytes of `buffer` already searched for a newline
+    searched: usize,
+    /// Bytes read from the stream so far
+    read: usize,
+    finished: bool,
+}
+
+impl<S, B> Lines<S>
+where
+    S: Stream<Item = AppResult<B>>,
+    B: AsRef<[u8]>,
+{
+    fn new(chunks: S) -> Self {
+        Self {
+            chunks: Box::pin(chunks),
+            buffer: Vec::new(),
+            searched: 0,
+            read: 0,
+            finished: false,
+        }
+    }
+
+    /// The next line without its line ending; `None` once the stream is done
+    async fn next(&mut self) -> AppResult<Option<String>> {
+        loop {
+            if let Some(pos) = self.buffer[self.searched..].iter().position(|b| *b == b'\n') {
+                let end = self.searched + pos;
+                let mut line: Vec<u8> = self.buffer.drain(..=end).collect();
+                line.pop();
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+                self.searched = 0;
+                return decode_line(line).map(Some);
+            }
+            self.searched = self.buffer.len();
+
+            if self.buffer.len() > MAX_LINE_BYTES {
+                return Err(AppError::BadRequest("The backup has a line too long to be a record".into()));
+            }
+            if self.finished {
+                if self.buffer.is_empty() {
+                    return Ok(None);
+                }
+                self.searched = 0;
+                return decode_line(std::mem::take(&mut self.buffer)).map(Some);
+            }
+
+            match self.chunks.next().await {
+                Some(chunk) => {
+                    let chunk = chunk?;
+                    self.read += chunk.as_ref().len();
+                    if self.read > MAX_ARCHIVE_BYTES {
+                        return Err(AppError::BadRequest(format!(
+                            "The backup is over {} MiB, more than a restore reads",
+                            MAX_ARCHIVE_BYTES / (1024 * 1024)
+                        )));
+                    }
+                    self.buffer.extend_from_slice(chunk.as_ref());
+                }
+                None => self.finished = true,
+            }
+        }
+    }
+}
+
+fn decode_line(line: Vec<u8>) -> AppResult<String> {
+    String::from_utf8(line).map_err(|_| AppError::BadRequest("The backup is not UTF-8 text".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(workspace_id: &str) -> String {
+        format!(
+            r#"{{"format":"crm-backup","version":1,"workspace_id":"{}","created_at":"2026-01-02T03:04:05Z"}}"#,
+            workspace_id
+        )
+    }
+
+    #[test]
+    fn test_parse_header_checks_format_version_and_workspace() {
+        assert_eq!(parse_header(&header("acme"), None).unwrap().workspace_id, "acme");
+        assert!(parse_header(&header("acme"), Some("acme")).is_ok());
+        assert!(parse_header(&header("acme"), Some("globex")).is_err());
+
+        let newer = header("acme").replace(r#""version":1"#, r#""version":2"#);
+        assert!(parse_header(&newer, None).is_err());
+        let other = header("acme").replace("crm-backup", "tarball");
+        assert!(parse_header(&other, None).is_err());
+        assert!(parse_header(r#"{"table":"contact","record":"{}"}"#, None).is_err());
+    }
+
+    #[test]
+    fn test_records_round_trip_and_must_belong_to_the_workspace() {
+        let record = surrealdb::sql::value(
+            "{ id: contact:ada, workspace: workspace:acme, email: 'ada@example.com', \
+             notes: s'workspace:globex', created_at: d'2026-01-02T03:04:05Z' }",
+        )
+        .unwrap();
+
+        let line = encode_page("contact", vec![record.clone()]).unwrap();
+        let BackupLine::Record { table, record: text } = parse_line(line.trim_end()).unwrap() else {
+            panic!("expected a record line");
+        };
+        assert_eq!(backup_table(&table).unwrap(), "contact");
+        assert_eq!(surrealdb::sql::value(&text).unwrap(), record);
+        assert_eq!(record_id(&record), Some(Thing::from(("contact", "ada"))));
+
+        assert!(check_record("contact", "acme", &record).is_ok());
+        assert!(check_record("contact", "globex", &record).is_err());
+        assert!(check_record("company", "acme", &record).is_err());
+        assert!(backup_table("migration").is_err());
+
+        let workspace = surrealdb::sql::value("{ id: workspace:acme, name: 'Acme' }").unwrap();
+        assert!(check_record("workspace", "acme", &workspace).is_ok());
+        assert!(check_record("workspace", "globex", &workspace).is_err());
+    }
+
+    #[test]
+    fn test_records_must_hold_plain_data() {
+        let contact = |fields: &str| {
+            surrealdb::sql::value(&format!("{{ id: contact:ada, workspace: workspace:acme, {} }}", fields)).unwrap()
+        };
+
+        let plain = contact(
+            "tags: ['vip'], score: 4.5, active: true, owner: user:bob, seen: d'2026-01-02T03:04:05Z', \
+             meta: { gap: 1h, ref: u'0190f3b2-4c6e-7000-8000-000000000000', none: NONE, null: NULL }",
+        );
+        assert!(check_record("contact", "acme", &plain).is_ok());
+
+        for crafted in [
+            "notes: (SELECT * FROM user)",
+            "notes: string::concat('a', 'b')",
+            "notes: <future> { 1 }",
+            "notes: $auth",
+            "tags: [(SELECT VALUE email FROM user)]",
+            "meta: { inner: (DELETE contact) }",
+            "owner: migration:one",
+            "company: workspace:globex",
+        ] {
+            assert!(check_record("contact", "acme", &contact(crafted)).is_err(), "{}", crafted);
+        }
+    }
+
+    #[test]
+    fn test_links_include_ids_and_relationship_ends() {
+        let edge = surrealdb::sql::value(
+            "{ id: works_with:one, workspace: workspace:acme, in: contact:ada, out: contact:bob, \
+             notes: { by: [user:cy] } }",
+        )
+        .unwrap();
+        let mut links = HashMap::new();
+        collect_links(&edge, &mut links);
+
+        let mut ids: Vec<&str> = links.keys().map(String::as_str).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, ["contact:ada", "contact:bob", "user:cy", "works_with:one", "workspace:acme"]);
+        assert_eq!(links["contact:ada"], Thing::from(("contact", "ada")));
+    }
+
+    #[test]
+    fn test_user_email_is_compared_in_lower_case() {
+        let user = surrealdb::sql::value("{ id: user:ada, workspace: workspace:acme, email: 'Ada@Example.com' }").unwrap();
+        assert_eq!(user_email(&user), Some("ada@example.com".to_string()));
+
+        let no_email = surrealdb::sql::value("{ id: user:bob, workspace: workspace:acme }").unwrap();
+        assert_eq!(user_email(&no_email), None);
+    }
+
+    #[test]
+    fn test_credentials_are_stripped_and_never_restored_from_the_archive() {
+        let archived = surrealdb::sql::value(
+            "{ id: user:ada, workspace: workspace:acme, email: 'ada@example.com', \
+             password_hash: 'crafted', role: 'owner' }",
+        )
+        .unwrap();
+
+        let mut exported = archived.clone();
+        strip_credentials("user", &mut exported);
+        assert!(get_field(&exported, "password_hash").is_none());
+        assert_eq!(get_field(&exported, "email"), get_field(&archived, "email"));
+
+        let existing = surrealdb::sql::value(
+            "{ id: user:ada, workspace: workspace:acme, password_hash: 'real', role: 'member' }",
+        )
+        .unwrap();
+        let mut kept = archived.clone();
+        keep_credentials("user", &mut kept, Some(&existing));
+        assert_eq!(get_field(&kept, "password_hash"), Some(&Value::from("real")));
+        assert_eq!(get_field(&kept, "role"), Some(&Value::from("member")));
+
+        let mut new_user = archived;
+        keep_credentials("user", &mut new_user, None);
+        assert_eq!(get_field(&new_user, "password_hash"), Some(&Value::from("")));
+        assert_eq!(get_field(&new_user, "role"), Some(&Value::from("member")));
+
+        let mut integration = surrealdb::sql::value(
+            "{ id: integration:gmail, workspace: workspace:acme, status: 'active', \
+             imap: { host: 'imap.example.com', password: 'hunter2' }, oauth: { access_token: 'x' } }",
+        )
+        .unwrap();
+        keep_credentials("integration", &mut integration, None);
+        assert_eq!(get_field(&integration, "imap.password"), Some(&Value::from("")));
+        assert_eq!(get_field(&integration, "imap.host"), Some(&Value::from("imap.example.com")));
+        assert!(get_field(&integration, "oauth").is_none());
+        assert_eq!(get_field(&integration, "status"), Some(&Value::from("error")));
+    }
+
+    #[test]
+    fn test_sender_domain_verification_is_never_restored_from_the_archive() {
+        let archived = surrealdb::sql::value(
+            "{ id: sender_identity:s1, workspace: workspace:acme, domain: 'not-ours.com', \
+             verification_token: 'crafted', ownership: { status: 'pass' } }",
+        )
+        .unwrap();
+
+        let mut new_sender = archived.clone();
+        keep_credentials("sender_identity", &mut new_sender, None);
+        assert!(get_field(&new_sender, "ownership").is_none());
+        assert_ne!(get_field(&new_sender, "verification_token"), Some(&Value::from("crafted")));
+
+        let existing = surrealdb::sql::value(
+            "{ id: sender_identity:s1, workspace: workspace:acme, domain: 'ours.com', \
+             verification_token: 'real', ownership: { status: 'pass' } }",
+        )
+        .unwrap();
+        let mut moved = archived;
+        keep_credentials("sender_identity", &mut moved, Some(&existing));
+        assert_eq!(get_field(&moved, "verification_token"), Some(&Value::from("real")));
+        assert!(get_field(&moved, "ownership").is_none());
+
+        let mut same_domain = existing.clone();
+        keep_credentials("sender_identity", &mut same_domain, Some(&existing));
+        assert_eq!(get_field(&same_domain, "ownership"), get_field(&existing, "ownership"));
+    }
+
+    #[test]
+    fn test_batches_hold_one_table_each() {
+        let record = || Value::from("record");
+        let mut records = vec![("contact", record()), ("contact", record()), ("company", record())];
+        records.extend((0..RESTORE_BATCH_SIZE + 1).map(|_| ("deal", record())));
+
+        let mut batcher = Batcher::default();
+        let mut sizes: Vec<(&str, usize)> = records
+            .into_iter()
+            .filter_map(|(table, record)| batcher.push(table, record))
+            .map(|(t, b)| (t, b.len()))
+            .collect();
+        sizes.extend(batcher.take().map(|(t, b)| (t, b.len())));
+        assert_eq!(sizes, [("contact", 2), ("company", 1), ("deal", RESTORE_BATCH_SIZE), ("deal", 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_lines_splits_chunks_on_newlines() {
+        let chunks = stream::iter(["one\r\ntw", "o\n", "", "\nthree"].map(|c| Ok::<_, AppError>(c.as_bytes())));
+        let mut lines = Lines::new(chunks);
+
+        let mut read = Vec::new();
+        while let Some(line) = lines.next().await.unwrap() {
+            read.push(line);
+        }
+        assert_eq!(read, ["one", "two", "", "three"]);
+    }
+}
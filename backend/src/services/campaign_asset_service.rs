@@ -0,0 +1,250 @@
+//! Campaign Asset Service - generating, regenerating and editing assets
+//!
+//! Assets are versioned per type: every generation, regeneration or edit
+//! stores a new version, links it to the one it replaces and makes it the
+//! active one. Earlier versions are never changed, so the history of what
+//! was generated and how it was edited stays intact.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use surrealdb::sql::Thing;
+
+use crate::ai::ai_email::GeneratedEmail;
+use crate::ai::ai_landing_page::GeneratedLandingPage;
+use crate::ai::ai_social::GeneratedPost;
+use crate::ai::{ContentGenerator, GenerationContext};
+use crate::db::{workspace_thing, Database};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    AssetType, Campaign, CampaignAsset, CampaignStatus, GenerateAssetsRequest, RegenerateAssetRequest,
+    UpdateAssetRequest,
+};
+use crate::repositories::CampaignRepository;
+
+pub struct CampaignAssetService {
+    campaigns: CampaignRepository,
+    generator: Arc<ContentGenerator>,
+}
+
+impl CampaignAssetService {
+    pub fn new(db: Arc<Database>, generator: Arc<ContentGenerator>) -> Self {
+        Self {
+            campaigns: CampaignRepository::new(db),
+            generator,
+        }
+    }
+
+    /// Active assets of a campaign, or every version with `include_history`
+    pub async fn list(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        include_history: bool,
+    ) -> AppResult<Vec<CampaignAsset>> {
+        self.find_campaign(workspace_id, campaign_id).await?;
+        self.campaigns.find_assets(workspace_id, campaign_id, include_history).await
+    }
+
+    /// Generate a new version of each requested asset type
+    pub async fn generate(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        req: GenerateAssetsRequest,
+    ) -> AppResult<Vec<CampaignAsset>> {
+        self.find_campaign(workspace_id, campaign_id).await?;
+
+        let mut created = Vec::with_capacity(req.asset_types.len());
+        for asset_type in req.asset_types {
+            let content = self.generate_content(workspace_id, campaign_id, &asset_type, &req.prompt).await?;
+            let asset = new_version(workspace_id, campaign_id, asset_type, content, None);
+            created.push(self.campaigns.create_version(asset).await?);
+        }
+
+        Ok(created)
+    }
+
+    /// Generate a fresh version of an asset, keeping the old one as history
+    pub async fn regenerate(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        asset_id: &str,
+        req: RegenerateAssetRequest,
+    ) -> AppResult<CampaignAsset> {
+        let campaign = self.find_campaign(workspace_id, campaign_id).await?;
+        let asset = self.find_asset(workspace_id, campaign_id, asset_id).await?;
+
+        let prompt = req
+            .prompt
+            .or(campaign.prompt)
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| AppError::Validation("A prompt is required; the campaign has none".into()))?;
+
+        let content = self
+            .generate_content(workspace_id, campaign_id, &asset.asset_type, &prompt)
+            .await?;
+        let version = new_version(workspace_id, campaign_id, asset.asset_type, content, asset.id);
+        self.campaigns.create_version(version).await
+    }
+
+    /// Replace an asset's content by hand, as a new version
+    ///
+    /// Only allowed before the campaign runs. An email given as sections is
+    /// compiled into its HTML and text bodies.
+    pub async fn edit(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        asset_id: &str,
+        req: UpdateAssetRequest,
+    ) -> AppResult<CampaignAsset> {
+        let campaign = self.find_campaign(workspace_id, campaign_id).await?;
+        if !matches!(campaign.status, CampaignStatus::Draft | CampaignStatus::Scheduled) {
+            return Err(AppError::Conflict(
+                "Assets can only be edited before the campaign runs".into(),
+            ));
+        }
+
+        let asset = self.find_asset(workspace_id, campaign_id, asset_id).await?;
+        let content = prepare_content(&asset.asset_type, req.generated_content)?;
+
+        let version = new_version(workspace_id, campaign_id, asset.asset_type, content, asset.id);
+        self.campaigns.create_version(version).await
+    }
+
+    async fn generate_content(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        asset_type: &AssetType,
+        prompt: &str,
+    ) -> AppResult<serde_json::Value> {
+        let ctx = GenerationContext {
+            workspace_id,
+            campaign_id: Some(campaign_id),
+        };
+
+        let content = match asset_type {
+            AssetType::Email => serde_json::to_value(self.generator.generate_email(ctx, prompt).await?),
+            AssetType::SocialPost => serde_json::to_value(self.generator.generate_social_posts(ctx, prompt).await?),
+            AssetType::LandingPage => serde_json::to_value(self.generator.generate_landing_page(ctx, prompt).await?),
+            AssetType::EventInvite => {
+                let brief = format!("Event invitation: {}", prompt);
+                serde_json::to_value(self.generator.generate_email(ctx, &brief).await?)
+            }
+        };
+
+        content.map_err(|e| AppError::Internal(format!("Failed to encode generated content: {}", e)))
+    }
+
+    async fn find_campaign(&self, workspace_id: &str, id: &str) -> AppResult<Campaign> {
+        self.campaigns
+            .find_by_id(workspace_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Campaign not found".into()))
+    }
+
+    async fn find_asset(&self, workspace_id: &str, campaign_id: &str, asset_id: &str) -> AppResult<CampaignAsset> {
+        self.campaigns
+            .find_asset(workspace_id, campaign_id, asset_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Asset not found".into()))
+    }
+}
+
+fn new_version(
+    workspace_id: &str,
+    campaign_id: &str,
+    asset_type: AssetType,
+    generated_content: serde_json::Value,
+    parent: Option<Thing>,
+) -> CampaignAsset {
+    CampaignAsset {
+        id: None,
+        workspace: workspace_thing(workspace_id),
+        campaign: Thing::from(("campaign", campaign_id)),
+        asset_type,
+        generated_content,
+        url: None,
+        // Numbered and activated by the repository
+        version: 0,
+        parent,
+        active: true,
+        created_at: Utc::now(),
+    }
+}
+
+/// Check edited content has the shape generated content of its type has,
+/// compiling an email's sections into its bodies
+fn prepare_content(asset_type: &AssetType, content: serde_json::Value) -> AppResult<serde_json::Value> {
+    fn parse<T: DeserializeOwned>(content: &serde_json::Value) -> AppResult<T> {
+        serde_json::from_value::<T>(content.clone())
+            .map_err(|e| AppError::Validation(format!("generated_content does not match the asset type: {}", e)))
+    }
+
+    match asset_type {
+        AssetType::Email | AssetType::EventInvite => {
+            let email = parse::<GeneratedEmail>(&content)?.compile()?;
+            serde_json::to_value(email)
+                .map_err(|e| AppError::Internal(format!("Failed to encode the compiled email: {}", e)))
+        }
+        AssetType::SocialPost => parse::<Vec<GeneratedPost>>(&content).map(|_| content),
+        AssetType::LandingPage => {
+            // An edited page must keep a form that can take submissions
+            parse::<GeneratedLandingPage>(&content)?.form.validate()?;
+            Ok(content)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prepare_content_checks_shape_per_type() {
+        let email = serde_json::json!({
+            "subject": "Hi {{first_name}}",
+            "preview_text": "",
+            "body_html": "<p>Hello</p>",
+            "body_text": "Hello",
+            "cta_text": "Go",
+            "cta_url": "https://crm.hey.sh"
+        });
+
+        assert!(prepare_content(&AssetType::Email, email.clone()).is_ok());
+        assert!(prepare_content(&AssetType::EventInvite, email.clone()).is_ok());
+        assert!(prepare_content(&AssetType::SocialPost, email).is_err());
+        assert!(prepare_content(&AssetType::Email, serde_json::json!({ "subject": "Hi" })).is_err());
+        assert!(prepare_content(&AssetType::SocialPost, serde_json::json!([])).is_ok());
+    }
+
+    #[test]
+    fn test_prepare_content_compiles_email_sections() {
+        let mut email = serde_json::json!({
+            "subject": "Hi {{first_name}}",
+            "preview_text": "News",
+            "cta_text": "Go",
+            "cta_url": "https://crm.hey.sh",
+            "document": {
+                "sections": [
+                    { "type": "text", "text": "Hello" },
+                    { "type": "button", "text": "Go", "url": "https://crm.hey.sh" }
+                ]
+            }
+        });
+
+        let compiled = prepare_content(&AssetType::Email, email.clone()).unwrap();
+        assert!(compiled["body_html"].as_str().unwrap().contains("<!DOCTYPE html>"));
+        assert_eq!(compiled["body_text"], "Hello\n\nGo: https://crm.hey.sh");
+        assert!(compiled["document"].is_object());
+
+        // Neither sections nor HTML
+        email.as_object_mut().unwrap().remove("document");
+        assert!(prepare_content(&AssetType::Email, email).is_err());
+    }
+}
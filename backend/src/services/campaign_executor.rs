@@ -1,15 +1,60 @@
-use crate::models::{Campaign, CampaignChannel};
+use surrealdb::sql::Thing;
+
+use crate::ai::ai_email::GeneratedEmail;
+use crate::domain::{append_signature_html, append_signature_text};
+use crate::models::{Campaign, CampaignChannel, SenderIdentity};
+use crate::repositories::PendingRecipient;
 
 /// Service responsible for executing campaigns across different channels
 pub struct CampaignExecutor;
 
 impl CampaignExecutor {
-    pub async fn execute(campaign: &Campaign) -> Result<ExecutionResult, ExecutionError> {
+    /// Run every channel of the campaign
+    ///
+    /// `recipients` are the campaign's pending recipients; the email channel
+    /// enforces the suppression list on them and personalizes `email` for
+    /// each, going out from `sender` with its signature. If any deliverable
+    /// recipient has a merge variable without a value, nothing runs and
+    /// every such recipient is reported.
+    pub async fn execute(
+        campaign: &Campaign,
+        email: Option<&GeneratedEmail>,
+        sender: Option<&SenderIdentity>,
+        recipients: &[PendingRecipient],
+    ) -> Result<ExecutionResult, ExecutionError> {
+        Self::execute_channels(campaign, &campaign.channels, email, sender, recipients).await
+    }
+
+    /// Run only the given channels, e.g. just email for recipients whose
+    /// send window opened after the campaign started
+    pub async fn execute_channels(
+        campaign: &Campaign,
+        channels: &[CampaignChannel],
+        email: Option<&GeneratedEmail>,
+        sender: Option<&SenderIdentity>,
+        recipients: &[PendingRecipient],
+    ) -> Result<ExecutionResult, ExecutionError> {
+        // Suppression list: contacts who unsubscribed after the audience
+        // was resolved are dropped here, at the last moment before sending
+        let (deliverable, suppressed): (Vec<&PendingRecipient>, Vec<&PendingRecipient>) = recipients
+            .iter()
+            .partition(|r| !r.subscription_status.is_suppressed());
+
+        let mut messages = match email {
+            Some(email) if channels.iter().any(|c| matches!(c, CampaignChannel::Email)) => {
+                let signed = sign(email, sender);
+                personalize(&signed, sender, &deliverable)?
+            }
+            _ => Vec::new(),
+        };
+
         let mut results = Vec::new();
 
-        for channel in &campaign.channels {
+        for channel in channels {
             let result = match channel {
-                CampaignChannel::Email => Self::execute_email_channel(campaign).await,
+                CampaignChannel::Email => {
+                    Self::execute_email_channel(&deliverable, &suppressed, std::mem::take(&mut messages)).await
+                }
                 CampaignChannel::Social => Self::execute_social_channel(campaign).await,
                 CampaignChannel::LandingPage => Self::execute_landing_page_channel(campaign).await,
                 CampaignChannel::Event => Self::execute_event_channel(campaign).await,
@@ -23,23 +68,36 @@ impl CampaignExecutor {
         })
     }
 
-    async fn execute_email_channel(_campaign: &Campaign) -> ChannelResult {
-        // Stub: In production, this would integrate with email provider
+    async fn execute_email_channel(
+        deliverable: &[&PendingRecipient],
+        suppressed: &[&PendingRecipient],
+        messages: Vec<OutgoingEmail>,
+    ) -> ChannelResult {
+        // Stub: In production, this would hand `messages` to the email provider
         ChannelResult {
             channel: CampaignChannel::Email,
             success: true,
-            message: "Email campaign queued for delivery".to_string(),
-            recipients_count: 0,
+            message: format!(
+                "Email campaign queued for delivery ({} suppressed)",
+                suppressed.len()
+            ),
+            recipients_count: deliverable.len(),
+            delivered: deliverable.iter().map(|r| r.contact.clone()).collect(),
+            suppressed: suppressed.iter().map(|r| r.contact.clone()).collect(),
+            messages,
         }
     }
 
     async fn execute_social_channel(_campaign: &Campaign) -> ChannelResult {
-        // Stub: In production, this would integrate with social APIs
+        // Posts are queued by SocialService and published by its background task
         ChannelResult {
             channel: CampaignChannel::Social,
             success: true,
-            message: "Social posts scheduled".to_string(),
+            message: "Social posts queued for publishing".to_string(),
             recipients_count: 0,
+            delivered: Vec::new(),
+            suppressed: Vec::new(),
+            messages: Vec::new(),
         }
     }
 
@@ -50,6 +108,9 @@ impl CampaignExecutor {
             success: true,
             message: "Landing page published".to_string(),
             recipients_count: 0,
+            delivered: Vec::new(),
+            suppressed: Vec::new(),
+            messages: Vec::new(),
         }
     }
 
@@ -60,6 +121,9 @@ impl CampaignExecutor {
             success: true,
             message: "Event invitations sent".to_string(),
             recipients_count: 0,
+            delivered: Vec::new(),
+            suppressed: Vec::new(),
+            messages: Vec::new(),
         }
     }
 }
@@ -70,12 +134,107 @@ pub struct ExecutionResult {
     pub channel_results: Vec<ChannelResult>,
 }
 
+impl ExecutionResult {
+    /// Contacts any channel sent to
+    pub fn delivered(&self) -> Vec<Thing> {
+        self.channel_results
+            .iter()
+            .flat_map(|r| r.delivered.iter().cloned())
+            .collect()
+    }
+
+    /// Personalized emails the email channel sent
+    pub fn messages(&self) -> impl Iterator<Item = &OutgoingEmail> {
+        self.channel_results.iter().flat_map(|r| r.messages.iter())
+    }
+
+    /// Contacts skipped by any channel because they are suppressed
+    pub fn suppressed(&self) -> Vec<Thing> {
+        self.channel_results
+            .iter()
+            .flat_map(|r| r.suppressed.iter().cloned())
+            .collect()
+    }
+}
+
 #[derive(Debug)]
 pub struct ChannelResult {
     pub channel: CampaignChannel,
     pub success: bool,
     pub message: String,
     pub recipients_count: usize,
+    /// Contacts the channel sent to
+    pub delivered: Vec<Thing>,
+    /// Contacts left out because they unsubscribed
+    pub suppressed: Vec<Thing>,
+    /// Personalized emails, one per deliverable recipient
+    pub messages: Vec<OutgoingEmail>,
+}
+
+/// An email personalized for one recipient
+#[derive(Debug, Clone)]
+pub struct OutgoingEmail {
+    pub contact: Thing,
+    pub to: String,
+    /// From header of the campaign's sender identity
+    pub from: Option<String>,
+    pub reply_to: Option<String>,
+    pub email: GeneratedEmail,
+}
+
+/// A recipient whose email would go out with variables left unfilled
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnresolvedRecipient {
+    pub contact: Thing,
+    pub variables: Vec<String>,
+}
+
+/// `email` with the sender's signature appended, if it has one
+fn sign(email: &GeneratedEmail, sender: Option<&SenderIdentity>) -> GeneratedEmail {
+    let mut signed = email.clone();
+    if let Some(signature) = sender.and_then(|s| s.signature_html.as_deref()) {
+        signed.body_html = append_signature_html(&signed.body_html, signature);
+        signed.body_text = append_signature_text(&signed.body_text, signature);
+    }
+    signed
+}
+
+/// Personalize `email` for every recipient, or report each one it can't be
+/// completed for
+fn personalize(
+    email: &GeneratedEmail,
+    sender: Option<&SenderIdentity>,
+    recipients: &[&PendingRecipient],
+) -> Result<Vec<OutgoingEmail>, ExecutionError> {
+    let from = sender.map(SenderIdentity::header_from);
+    let reply_to = sender.and_then(|s| s.reply_to.clone());
+
+    let mut messages = Vec::with_capacity(recipients.len());
+    let mut unresolved = Vec::new();
+
+    for recipient in recipients {
+        let (personalized, missing) = email.personalize(&recipient.merge_fields());
+        if missing.is_empty() {
+            messages.push(OutgoingEmail {
+                contact: recipient.contact.clone(),
+                to: recipient.email.clone(),
+                from: from.clone(),
+                reply_to: reply_to.clone(),
+                email: personalized,
+            });
+        } else {
+            unresolved.push(UnresolvedRecipient {
+                contact: recipient.contact.clone(),
+                variables: missing,
+            });
+        }
+    }
+
+    if unresolved.is_empty() {
+        Ok(messages)
+    } else {
+        Err(ExecutionError::UnresolvedVariables(unresolved))
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -86,4 +245,140 @@ pub enum ExecutionError {
     NotReady,
     #[error("Channel error: {0}")]
     ChannelError(String),
+    #[error("{} recipient(s) have merge variables without a value", .0.len())]
+    UnresolvedVariables(Vec<UnresolvedRecipient>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::SubscriptionStatus;
+    use crate::models::{CampaignObjective, CampaignStatus};
+    use chrono::Utc;
+
+    fn campaign() -> Campaign {
+        Campaign {
+            id: Some(Thing::from(("campaign", "c1"))),
+            workspace: Thing::from(("workspace", "ws1")),
+            name: "Launch".into(),
+            objective: CampaignObjective::Awareness,
+            status: CampaignStatus::Draft,
+            channels: vec![CampaignChannel::Email],
+            prompt: None,
+            segment_definition: serde_json::json!({}),
+            segment: None,
+            sender: Some(Thing::from(("sender_identity", "s1"))),
+            scheduled_at: None,
+            send_window: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn sender() -> SenderIdentity {
+        SenderIdentity {
+            id: Some(Thing::from(("sender_identity", "s1"))),
+            workspace: Thing::from(("workspace", "ws1")),
+            owner: None,
+            from_name: "Jane at Hey".into(),
+            from_email: "jane@hey.sh".into(),
+            reply_to: Some("replies@hey.sh".into()),
+            signature_html: Some("<p>Jane</p>".into()),
+            domain: "hey.sh".into(),
+            verification_token: "abc123".into(),
+            ownership: Default::default(),
+            spf: Default::default(),
+            dkim: Default::default(),
+            checked_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn recipient(id: &str, subscription_status: SubscriptionStatus) -> PendingRecipient {
+        PendingRecipient {
+            contact: Thing::from(("contact", id)),
+            email: format!("{}@example.com", id),
+            subscription_status,
+            first_name: id.to_uppercase(),
+            last_name: "Example".into(),
+            company: None,
+            custom_fields: Default::default(),
+            timezone: None,
+        }
+    }
+
+    fn email(subject: &str) -> GeneratedEmail {
+        GeneratedEmail {
+            subject: subject.into(),
+            preview_text: String::new(),
+            body_html: "<p>Hi {{first_name}}</p>".into(),
+            body_text: "Hi {{first_name}}".into(),
+            cta_text: "Go".into(),
+            cta_url: "https://crm.hey.sh".into(),
+            document: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_email_channel_skips_suppressed_recipients() {
+        let recipients = vec![
+            recipient("a", SubscriptionStatus::Subscribed),
+            recipient("b", SubscriptionStatus::Unsubscribed),
+            recipient("c", SubscriptionStatus::Subscribed),
+        ];
+
+        let result = CampaignExecutor::execute(&campaign(), None, None, &recipients).await.unwrap();
+
+        assert_eq!(result.channel_results[0].recipients_count, 2);
+        assert_eq!(result.delivered(), vec![Thing::from(("contact", "a")), Thing::from(("contact", "c"))]);
+        assert_eq!(result.suppressed(), vec![Thing::from(("contact", "b"))]);
+    }
+
+    #[tokio::test]
+    async fn test_email_channel_personalizes_each_recipient() {
+        let recipients = vec![
+            recipient("a", SubscriptionStatus::Subscribed),
+            recipient("b", SubscriptionStatus::Unsubscribed),
+        ];
+
+        let email = email("News for {{first_name}}");
+        let result = CampaignExecutor::execute(&campaign(), Some(&email), Some(&sender()), &recipients)
+            .await
+            .unwrap();
+
+        let messages = &result.channel_results[0].messages;
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].to, "a@example.com");
+        assert_eq!(messages[0].from.as_deref(), Some("Jane at Hey <jane@hey.sh>"));
+        assert_eq!(messages[0].reply_to.as_deref(), Some("replies@hey.sh"));
+        assert_eq!(messages[0].email.subject, "News for A");
+        assert_eq!(
+            messages[0].email.body_html,
+            r#"<p>Hi A</p><div class="signature" style="margin-top:24px;"><p>Jane</p></div>"#
+        );
+        assert_eq!(messages[0].email.body_text, "Hi A\n\n-- \nJane");
+    }
+
+    #[tokio::test]
+    async fn test_unresolved_variables_stop_the_run() {
+        let mut with_plan = recipient("a", SubscriptionStatus::Subscribed);
+        with_plan.custom_fields.insert("plan".into(), "pro".into());
+        let recipients = vec![with_plan, recipient("c", SubscriptionStatus::Subscribed)];
+
+        let err = CampaignExecutor::execute(&campaign(), Some(&email("Your {{plan}} plan")), None, &recipients)
+            .await
+            .unwrap_err();
+
+        let ExecutionError::UnresolvedVariables(unresolved) = err else {
+            panic!("expected unresolved variables, got {:?}", err);
+        };
+        assert_eq!(
+            unresolved,
+            vec![UnresolvedRecipient {
+                contact: Thing::from(("contact", "c")),
+                variables: vec!["plan".to_string()],
+            }]
+        );
+    }
 }
@@ -0,0 +1,446 @@
+//! Campaign Scheduler - running campaigns now or at their scheduled time
+//!
+//! Executing a campaign freezes its audience, sends to the recipients who
+//! are due and records the outcome. Without a send window everyone is due
+//! at once; with one, only recipients for whom the window is open in their
+//! own timezone (or their company's) are, and the rest stay pending.
+//!
+//! Campaigns with an email channel only run, or get scheduled, with a
+//! verified sender identity.
+//!
+//! A background task starts scheduled campaigns whose time has come and,
+//! on every tick, emails the waiting recipients of running campaigns whose
+//! window has since opened. A campaign is completed once no recipient is
+//! left waiting.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::task::JoinHandle;
+
+use crate::ai::ai_email::GeneratedEmail;
+use crate::config::SchedulerConfig;
+use crate::db::Database;
+use crate::domain::{validate_scheduled_at, SendWindow, WebhookEvent};
+use crate::error::{AppError, AppResult, FieldError};
+use crate::models::{
+    Campaign, CampaignChannel, CampaignStatus, ScheduleCampaignRequest, SenderIdentity, TimelineEntry,
+    TimelineEntryType,
+};
+use crate::repositories::{CampaignRepository, PendingRecipient};
+use crate::services::campaign_executor::{CampaignExecutor, ExecutionError, OutgoingEmail};
+use crate::services::{new_entry, SegmentService, SenderService, SocialService, TimelineService, WebhookService};
+use crate::shutdown::Shutdown;
+
+/// What one execution did
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionSummary {
+    pub recipients_added: usize,
+    pub recipients_sent: usize,
+    pub recipients_suppressed: usize,
+    /// Pending until their send window opens
+    pub recipients_waiting: usize,
+    /// When the window next opens for one of the waiting recipients
+    pub next_send_at: Option<DateTime<Utc>>,
+    /// Generated posts queued for the social platforms
+    pub social_posts_queued: usize,
+}
+
+pub struct CampaignScheduler {
+    campaigns: CampaignRepository,
+    segments: Arc<SegmentService>,
+    senders: Arc<SenderService>,
+    social: Arc<SocialService>,
+    timeline: Arc<TimelineService>,
+    webhooks: WebhookService,
+}
+
+impl CampaignScheduler {
+    pub fn new(
+        db: Arc<Database>,
+        segments: Arc<SegmentService>,
+        senders: Arc<SenderService>,
+        social: Arc<SocialService>,
+        timeline: Arc<TimelineService>,
+    ) -> Self {
+        Self {
+            campaigns: CampaignRepository::new(Arc::clone(&db)),
+            segments,
+            senders,
+            social,
+            timeline,
+            webhooks: WebhookService::new(db),
+        }
+    }
+
+    /// Schedule a draft campaign, or move an already scheduled one
+    pub async fn schedule(
+        &self,
+        workspace_id: &str,
+        id: &str,
+        req: ScheduleCampaignRequest,
+    ) -> AppResult<Campaign> {
+        validate_scheduled_at(req.scheduled_at, Utc::now())?;
+        if let Some(window) = &req.send_window {
+            window.validate()?;
+        }
+        if let Some(campaign) = self.campaigns.find_by_id(workspace_id, id).await? {
+            self.sender_for(&campaign, &campaign.channels).await?;
+        }
+
+        let updated = self
+            .campaigns
+            .update_schedule(
+                workspace_id,
+                id,
+                &[CampaignStatus::Draft, CampaignStatus::Scheduled],
+                CampaignStatus::Scheduled,
+                Some(req.scheduled_at),
+                req.send_window,
+            )
+            .await?;
+
+        match updated {
+            Some(campaign) => Ok(campaign),
+            None => Err(self.not_in_state(workspace_id, id, "Only draft or scheduled campaigns can be scheduled").await),
+        }
+    }
+
+    /// Take a scheduled campaign back to draft, clearing its schedule
+    pub async fn cancel(&self, workspace_id: &str, id: &str) -> AppResult<Campaign> {
+        let updated = self
+            .campaigns
+            .update_schedule(workspace_id, id, &[CampaignStatus::Scheduled], CampaignStatus::Draft, None, None)
+            .await?;
+
+        match updated {
+            Some(campaign) => Ok(campaign),
+            None => Err(self.not_in_state(workspace_id, id, "Only scheduled campaigns can be cancelled").await),
+        }
+    }
+
+    /// Execute a campaign now
+    ///
+    /// Fails with the offending recipients if any of them would get an
+    /// email with unfilled merge variables; nothing is sent then.
+    pub async fn execute(&self, workspace_id: &str, campaign: &Campaign) -> AppResult<ExecutionSummary> {
+        let sender = self.sender_for(campaign, &campaign.channels).await?;
+
+        // Freeze the audience before anything goes out
+        let recipients_added = self.segments.materialize(workspace_id, campaign).await?;
+
+        let mut summary = self
+            .send_due(workspace_id, campaign, &campaign.channels, sender.as_ref(), Utc::now())
+            .await?;
+        summary.recipients_added = recipients_added;
+
+        if campaign.channels.iter().any(|c| matches!(c, CampaignChannel::Social)) {
+            // Emails are already out, so a failure here doesn't fail the run
+            match self.social.enqueue_campaign(workspace_id, campaign).await {
+                Ok(queued) => summary.social_posts_queued = queued,
+                Err(e) => tracing::error!("Queueing social posts of campaign {} failed: {}", campaign_id(campaign), e),
+            }
+        }
+
+        self.webhooks
+            .notify(
+                workspace_id,
+                WebhookEvent::CampaignExecuted,
+                serde_json::json!({
+                    "campaign_id": campaign_id(campaign),
+                    "name": campaign.name,
+                    "channels": campaign.channels,
+                    "recipients_added": summary.recipients_added,
+                    "recipients_sent": summary.recipients_sent,
+                    "recipients_suppressed": summary.recipients_suppressed,
+                    "recipients_waiting": summary.recipients_waiting,
+                    "next_send_at": summary.next_send_at,
+                    "social_posts_queued": summary.social_posts_queued,
+                }),
+            )
+            .await;
+
+        Ok(summary)
+    }
+
+    /// The email a campaign run sends: its most recently generated one
+    pub async fn latest_email(&self, workspace_id: &str, campaign_id: &str) -> AppResult<Option<GeneratedEmail>> {
+        self.campaigns.latest_email(workspace_id, campaign_id).await
+    }
+
+    /// Start due campaigns and email recipients whose window has opened
+    ///
+    /// One campaign failing doesn't stop the others; its failure is logged.
+    /// Returns the number of campaigns started.
+    pub async fn run_due(&self) -> AppResult<usize> {
+        let now = Utc::now();
+        let mut started = 0;
+
+        for campaign in self.campaigns.find_due(now).await? {
+            let workspace_id = campaign.workspace.id.to_raw();
+            let id = campaign_id(&campaign);
+
+            match self.campaigns.claim_scheduled(&workspace_id, &id).await {
+                Ok(true) => {}
+                // Lost the race against a cancel or a manual execute
+                Ok(false) => continue,
+                Err(e) => {
+                    tracing::error!("Failed to claim scheduled campaign {}: {}", id, e);
+                    continue;
+                }
+            }
+
+            match self.execute(&workspace_id, &campaign).await {
+                Ok(summary) => {
+                    started += 1;
+                    tracing::info!(
+                        "Started scheduled campaign {}: {} sent, {} waiting for their send window",
+                        id,
+                        summary.recipients_sent,
+                        summary.recipients_waiting
+                    );
+                }
+                Err(e) => {
+                    // Back to draft, so it's visible that it didn't go out
+                    tracing::error!("Scheduled campaign {} failed to start: {}", id, e);
+                    if let Err(e) = self.campaigns.set_status(&workspace_id, &id, CampaignStatus::Draft).await {
+                        tracing::error!("Failed to return campaign {} to draft: {}", id, e);
+                    }
+                }
+            }
+        }
+
+        for campaign in self.campaigns.find_running_windowed().await? {
+            let workspace_id = campaign.workspace.id.to_raw();
+            let channels = [CampaignChannel::Email];
+            let sent = match self.sender_for(&campaign, &channels).await {
+                Ok(sender) => self.send_due(&workspace_id, &campaign, &channels, sender.as_ref(), now).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = sent {
+                tracing::error!("Sending to waiting recipients of campaign {} failed: {}", campaign_id(&campaign), e);
+            }
+        }
+
+        Ok(started)
+    }
+
+    /// Run `run_due` on a background task at the configured interval
+    pub fn spawn(self: Arc<Self>, config: &SchedulerConfig, shutdown: Shutdown) -> JoinHandle<()> {
+        let period = Duration::from_secs(config.poll_interval_secs.max(10));
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+
+            while shutdown.tick(&mut interval).await {
+                if let Err(e) = self.run_due().await {
+                    tracing::error!("Campaign scheduler run failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// The verified sender identity email goes out from, when `channels` include email
+    async fn sender_for(&self, campaign: &Campaign, channels: &[CampaignChannel]) -> AppResult<Option<SenderIdentity>> {
+        if !channels.iter().any(|c| matches!(c, CampaignChannel::Email)) {
+            return Ok(None);
+        }
+        self.senders.for_campaign(campaign).await.map(Some)
+    }
+
+    /// Send `channels` to the pending recipients that are due at `now`
+    async fn send_due(
+        &self,
+        workspace_id: &str,
+        campaign: &Campaign,
+        channels: &[CampaignChannel],
+        sender: Option<&SenderIdentity>,
+        now: DateTime<Utc>,
+    ) -> AppResult<ExecutionSummary> {
+        let id = campaign_id(campaign);
+
+        // Suppression is re-checked against the contacts at send time
+        let pending = self.segments.pending_recipients(workspace_id, &id).await?;
+        let Split { due, waiting, next_send_at } = split_due(campaign.send_window.as_ref(), pending, now);
+        let status = if waiting == 0 { CampaignStatus::Completed } else { CampaignStatus::Running };
+        if due.is_empty() && matches!(campaign.status, CampaignStatus::Running) {
+            if waiting == 0 {
+                self.campaigns.set_status(workspace_id, &id, status).await?;
+            }
+            return Ok(ExecutionSummary {
+                recipients_waiting: waiting,
+                next_send_at,
+                ..Default::default()
+            });
+        }
+
+        let email = self.campaigns.latest_email(workspace_id, &id).await?;
+        let result = CampaignExecutor::execute_channels(campaign, channels, email.as_ref(), sender, &due)
+            .await
+            .map_err(execution_error)?;
+
+        let sent = result.delivered();
+        let suppressed = result.suppressed();
+        let summary = ExecutionSummary {
+            recipients_added: 0,
+            recipients_sent: sent.len(),
+            recipients_suppressed: suppressed.len(),
+            recipients_waiting: waiting,
+            next_send_at,
+            social_posts_queued: 0,
+        };
+
+        // Each email shows on the recipient's timeline, attributed to the campaign
+        let entries: Vec<TimelineEntry> =
+            result.messages().map(|message| sent_entry(workspace_id, campaign, message)).collect();
+
+        // Sent and suppressed recipients, their entries and the campaign's status are stored together
+        self.segments
+            .record_execution(workspace_id, &id, sent, suppressed, entries.clone(), status)
+            .await?;
+        self.timeline.recorded(workspace_id, &entries).await;
+
+        Ok(summary)
+    }
+
+    /// The error for a schedule change the campaign's status doesn't allow
+    async fn not_in_state(&self, workspace_id: &str, id: &str, message: &str) -> AppError {
+        match self.campaigns.find_by_id(workspace_id, id).await {
+            Ok(Some(_)) => AppError::Conflict(message.to_string()),
+            Ok(None) => AppError::NotFound("Campaign not found".into()),
+            Err(e) => e,
+        }
+    }
+}
+
+fn campaign_id(campaign: &Campaign) -> String {
+    campaign.id.as_ref().map(|t| t.id.to_raw()).unwrap_or_default()
+}
+
+/// Timeline entry for one campaign email sent
+fn sent_entry(workspace_id: &str, campaign: &Campaign, message: &OutgoingEmail) -> TimelineEntry {
+    let mut entry = new_entry(
+        workspace_id,
+        &message.contact.id.to_raw(),
+        TimelineEntryType::EmailSent,
+        format!("Sent \"{}\"", message.email.subject),
+        serde_json::json!({
+            "campaign_id": campaign_id(campaign),
+            "subject": message.email.subject,
+        }),
+    );
+    entry.campaign = campaign.id.clone();
+    entry
+}
+
+/// Pending recipients split by whether their window is open
+struct Split {
+    due: Vec<PendingRecipient>,
+    /// How many are left pending
+    waiting: usize,
+    /// The earliest time the window opens for one of those left pending
+    next_send_at: Option<DateTime<Utc>>,
+}
+
+/// Split pending recipients into those due now and those still waiting
+/// for their window
+///
+/// Suppressed recipients are always due, so they are marked as skipped
+/// rather than left pending.
+fn split_due(window: Option<&SendWindow>, pending: Vec<PendingRecipient>, now: DateTime<Utc>) -> Split {
+    let Some(window) = window else {
+        return Split {
+            due: pending,
+            waiting: 0,
+            next_send_at: None,
+        };
+    };
+
+    let (due, waiting): (Vec<_>, Vec<_>) = pending.into_iter().partition(|r| {
+        r.subscription_status.is_suppressed() || window.is_open(now, window.timezone_for(r.timezone.as_deref()))
+    });
+    let next_send_at = waiting
+        .iter()
+        .map(|r| window.next_open(now, window.timezone_for(r.timezone.as_deref())))
+        .min();
+
+    Split {
+        due,
+        waiting: waiting.len(),
+        next_send_at,
+    }
+}
+
+/// Unfilled merge variables are the caller's to fix, one error per contact
+fn execution_error(err: ExecutionError) -> AppError {
+    match err {
+        ExecutionError::UnresolvedVariables(recipients) => AppError::InvalidFields(
+            recipients
+                .into_iter()
+                .map(|r| FieldError {
+                    field: format!("recipients.{}", r.contact.id.to_raw()),
+                    code: "unresolved_variables".into(),
+                    message: format!("No value for {}", r.variables.join(", ")),
+                })
+                .collect(),
+        ),
+        e => AppError::Internal(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::SubscriptionStatus;
+    use surrealdb::sql::Thing;
+
+    fn recipient(id: &str, timezone: &str, subscription_status: SubscriptionStatus) -> PendingRecipient {
+        PendingRecipient {
+            contact: Thing::from(("contact", id)),
+            email: format!("{}@example.com", id),
+            subscription_status,
+            first_name: id.into(),
+            last_name: "Example".into(),
+            company: None,
+            custom_fields: Default::default(),
+            timezone: Some(timezone.into()),
+        }
+    }
+
+    #[test]
+    fn test_split_due_follows_recipient_timezones() {
+        let window = SendWindow {
+            start_hour: 9,
+            end_hour: 17,
+            days: Vec::new(),
+            timezone: None,
+        };
+        // 14:00 UTC: office hours in Stockholm, night in Tokyo
+        let now: DateTime<Utc> = "2026-03-18T14:00:00Z".parse().unwrap();
+        let pending = vec![
+            recipient("stockholm", "Europe/Stockholm", SubscriptionStatus::Subscribed),
+            recipient("tokyo", "Asia/Tokyo", SubscriptionStatus::Subscribed),
+            recipient("gone", "Asia/Tokyo", SubscriptionStatus::Unsubscribed),
+        ];
+
+        let split = split_due(Some(&window), pending, now);
+
+        let due: Vec<String> = split.due.iter().map(|r| r.contact.id.to_raw()).collect();
+        assert_eq!(due, vec!["stockholm", "gone"]);
+        assert_eq!(split.waiting, 1);
+        // 09:00 the next morning in Tokyo
+        assert_eq!(split.next_send_at, Some("2026-03-19T00:00:00Z".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_everyone_is_due_without_a_window() {
+        let pending = vec![recipient("tokyo", "Asia/Tokyo", SubscriptionStatus::Subscribed)];
+
+        let split = split_due(None, pending, Utc::now());
+
+        assert_eq!(split.due.len(), 1);
+        assert_eq!(split.waiting, 0);
+        assert_eq!(split.next_send_at, None);
+    }
+}
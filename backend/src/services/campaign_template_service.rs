@@ -0,0 +1,275 @@
+//! Campaign Template Service - cloning campaigns and the template library
+//!
+//! A clone or a campaign made from a template is a new draft: same
+//! objective, channels, prompt, audience, send window and generated
+//! assets, but no schedule and no recipients, so nothing from the original
+//! run carries over.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+
+use crate::db::Database;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Campaign, CampaignStatus, CampaignTemplate, CampaignTemplateResponse, CloneCampaignRequest,
+    InstantiateTemplateRequest, SaveTemplateRequest, TemplateAsset,
+};
+use crate::repositories::{CampaignRepository, CampaignTemplateRepository, SegmentRepository};
+
+pub struct CampaignTemplateService {
+    campaigns: CampaignRepository,
+    templates: CampaignTemplateRepository,
+    segments: SegmentRepository,
+}
+
+impl CampaignTemplateService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            campaigns: CampaignRepository::new(Arc::clone(&db)),
+            templates: CampaignTemplateRepository::new(Arc::clone(&db)),
+            segments: SegmentRepository::new(db),
+        }
+    }
+
+    /// Copy a campaign and its assets into a new draft
+    pub async fn clone_campaign(
+        &self,
+        workspace_id: &str,
+        id: &str,
+        req: CloneCampaignRequest,
+    ) -> AppResult<Campaign> {
+        let source = self.find_campaign(workspace_id, id).await?;
+        let name = match req.name {
+            Some(name) => required_name(&name, "Campaign")?,
+            None => format!("{} (copy)", source.name),
+        };
+
+        let assets: Vec<TemplateAsset> = self
+            .campaigns
+            .find_assets(workspace_id, id, false)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        let copy = draft_campaign(&source, name, Utc::now());
+        self.campaigns.create_with_assets(copy, &assets).await
+    }
+
+    // ---- Templates ----
+
+    /// Save a campaign's setup and assets as a template
+    pub async fn save_template(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        req: SaveTemplateRequest,
+    ) -> AppResult<CampaignTemplateResponse> {
+        let name = required_name(&req.name, "Template")?;
+        let campaign = self.find_campaign(workspace_id, campaign_id).await?;
+        let assets = self
+            .campaigns
+            .find_assets(workspace_id, campaign_id, false)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        let template = template_from(&campaign, assets, name, req.description, Utc::now());
+        Ok(self.templates.create(template).await?.into())
+    }
+
+    pub async fn list(&self, workspace_id: &str) -> AppResult<Vec<CampaignTemplateResponse>> {
+        let templates = self.templates.find_all(workspace_id).await?;
+        Ok(templates.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get(&self, workspace_id: &str, id: &str) -> AppResult<CampaignTemplate> {
+        self.templates
+            .find_by_id(workspace_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Campaign template not found".into()))
+    }
+
+    pub async fn delete(&self, workspace_id: &str, id: &str) -> AppResult<()> {
+        if !self.templates.delete(workspace_id, id).await? {
+            return Err(AppError::NotFound("Campaign template not found".into()));
+        }
+        Ok(())
+    }
+
+    /// Create a draft campaign from a template
+    pub async fn instantiate(
+        &self,
+        workspace_id: &str,
+        id: &str,
+        req: InstantiateTemplateRequest,
+    ) -> AppResult<Campaign> {
+        let template = self.get(workspace_id, id).await?;
+        let name = match req.name {
+            Some(name) => required_name(&name, "Campaign")?,
+            None => template.name.clone(),
+        };
+
+        // The saved segment may have been deleted since the template was made
+        if let Some(segment) = &template.segment {
+            let segment_id = segment.id.to_raw();
+            if self.segments.find_by_id(workspace_id, &segment_id).await?.is_none() {
+                return Err(AppError::BadRequest(format!(
+                    "Segment {} used by the template no longer exists",
+                    segment_id
+                )));
+            }
+        }
+
+        let campaign = campaign_from_template(&template, name, Utc::now());
+        self.campaigns.create_with_assets(campaign, &template.assets).await
+    }
+
+    async fn find_campaign(&self, workspace_id: &str, id: &str) -> AppResult<Campaign> {
+        self.campaigns
+            .find_by_id(workspace_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Campaign not found".into()))
+    }
+}
+
+fn required_name(name: &str, what: &str) -> AppResult<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation(format!("{} name is required", what)));
+    }
+    Ok(name.to_string())
+}
+
+/// A new draft with the source's setup and audience, but no schedule
+fn draft_campaign(source: &Campaign, name: String, now: DateTime<Utc>) -> Campaign {
+    Campaign {
+        id: None,
+        workspace: source.workspace.clone(),
+        name,
+        objective: source.objective.clone(),
+        status: CampaignStatus::Draft,
+        channels: source.channels.clone(),
+        prompt: source.prompt.clone(),
+        segment_definition: source.segment_definition.clone(),
+        segment: source.segment.clone(),
+        sender: source.sender.clone(),
+        scheduled_at: None,
+        send_window: source.send_window.clone(),
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+fn template_from(
+    campaign: &Campaign,
+    assets: Vec<TemplateAsset>,
+    name: String,
+    description: Option<String>,
+    now: DateTime<Utc>,
+) -> CampaignTemplate {
+    CampaignTemplate {
+        id: None,
+        workspace: campaign.workspace.clone(),
+        name,
+        description,
+        objective: campaign.objective.clone(),
+        channels: campaign.channels.clone(),
+        prompt: campaign.prompt.clone(),
+        segment_definition: campaign.segment_definition.clone(),
+        segment: campaign.segment.clone(),
+        send_window: campaign.send_window.clone(),
+        assets,
+        source_campaign: campaign.id.clone(),
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+/// The draft campaign a template turns into
+fn campaign_from_template(template: &CampaignTemplate, name: String, now: DateTime<Utc>) -> Campaign {
+    Campaign {
+        id: None,
+        workspace: template.workspace.clone(),
+        name,
+        objective: template.objective.clone(),
+        status: CampaignStatus::Draft,
+        channels: template.channels.clone(),
+        prompt: template.prompt.clone(),
+        segment_definition: template.segment_definition.clone(),
+        segment: template.segment.clone(),
+        sender: None,
+        scheduled_at: None,
+        send_window: template.send_window.clone(),
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::workspace_thing;
+    use crate::domain::SendWindow;
+    use crate::models::{AssetType, CampaignChannel, CampaignObjective};
+    use surrealdb::sql::Thing;
+
+    fn launched() -> Campaign {
+        let created: DateTime<Utc> = "2026-01-10T09:00:00Z".parse().unwrap();
+        Campaign {
+            id: Some(Thing::from(("campaign", "spring"))),
+            workspace: workspace_thing("acme"),
+            name: "Spring launch".into(),
+            objective: CampaignObjective::LeadGen,
+            status: CampaignStatus::Running,
+            channels: vec![CampaignChannel::Email],
+            prompt: Some("Announce the spring release".into()),
+            segment_definition: serde_json::json!({ "tags": ["beta"] }),
+            segment: None,
+            sender: None,
+            scheduled_at: Some(created),
+            send_window: Some(SendWindow {
+                start_hour: 9,
+                end_hour: 17,
+                days: Vec::new(),
+                timezone: None,
+            }),
+            created_at: created,
+            updated_at: created,
+        }
+    }
+
+    #[test]
+    fn test_draft_campaign_drops_status_and_schedule() {
+        let now = Utc::now();
+        let copy = draft_campaign(&launched(), "Autumn launch".into(), now);
+
+        assert!(copy.id.is_none());
+        assert!(matches!(copy.status, CampaignStatus::Draft));
+        assert!(copy.scheduled_at.is_none());
+        assert_eq!(copy.send_window, launched().send_window);
+        assert_eq!(copy.segment_definition, launched().segment_definition);
+        assert_eq!(copy.created_at, now);
+    }
+
+    #[test]
+    fn test_template_round_trips_campaign_setup() {
+        let assets = vec![TemplateAsset {
+            asset_type: AssetType::Email,
+            generated_content: serde_json::json!({ "subject": "Hi {{first_name|there}}" }),
+        }];
+        let now = Utc::now();
+
+        let template = template_from(&launched(), assets, "Launch".into(), None, now);
+        assert_eq!(template.source_campaign, launched().id);
+        assert_eq!(template.assets.len(), 1);
+
+        let campaign = campaign_from_template(&template, "Summer launch".into(), now);
+        assert_eq!(campaign.name, "Summer launch");
+        assert_eq!(campaign.prompt, launched().prompt);
+        assert!(matches!(campaign.status, CampaignStatus::Draft));
+        assert!(campaign.scheduled_at.is_none());
+    }
+}
@@ -0,0 +1,245 @@
+//! Contact Export - streams contacts out as CSV or JSON
+//!
+//! Exports can cover an entire workspace, so nothing here holds more than
+//! one page of contacts in memory. The repository is read with keyset
+//! pagination and each page is encoded into a single chunk of the
+//! response body.
+
+use futures::stream::{self, Stream};
+
+use crate::error::AppResult;
+use crate::models::{ContactResponse, ExportFormat};
+use crate::repositories::{ContactQuery, ContactRepository, StoredContact};
+
+/// Number of contacts fetched from the database per chunk
+pub const EXPORT_PAGE_SIZE: u32 = 500;
+
+const CSV_COLUMNS: [&str; 13] = [
+    "id",
+    "first_name",
+    "last_name",
+    "email",
+    "phone",
+    "linkedin_url",
+    "tags",
+    "status",
+    "subscription_status",
+    "engagement_score",
+    "company_id",
+    "created_at",
+    "updated_at",
+];
+
+/// Where the export stream is in its lifecycle
+enum Phase {
+    Start,
+    Pages { after: Option<String>, first: bool },
+    Finish,
+    Done,
+}
+
+struct ExportState {
+    repo: ContactRepository,
+    workspace_id: String,
+    query: ContactQuery,
+    format: ExportFormat,
+    phase: Phase,
+}
+
+/// Stream every contact matching `query` as encoded text chunks
+///
+/// The first chunk is the CSV header row (or the opening `[` for JSON),
+/// followed by one chunk per page. A database error ends the stream.
+pub fn export_stream(
+    repo: ContactRepository,
+    workspace_id: String,
+    query: ContactQuery,
+    format: ExportFormat,
+) -> impl Stream<Item = AppResult<String>> + Send + 'static {
+    let state = ExportState {
+        repo,
+        workspace_id,
+        query,
+        format,
+        phase: Phase::Start,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        match std::mem::replace(&mut state.phase, Phase::Done) {
+            Phase::Start => {
+                state.phase = Phase::Pages { after: None, first: true };
+                Some((Ok(prelude(state.format)), state))
+            }
+            Phase::Pages { after, first } => {
+                let page = state
+                    .repo
+                    .find_page_after(&state.workspace_id, &state.query, after.as_deref(), EXPORT_PAGE_SIZE)
+                    .await;
+
+                let page = match page {
+                    Ok(page) => page,
+                    Err(e) => return Some((Err(e), state)),
+                };
+
+                if page.is_empty() {
+                    return Some((Ok(epilogue(state.format)), state));
+                }
+
+                let last_id = page.last().map(|c| c.id.clone());
+                state.phase = if page.len() < EXPORT_PAGE_SIZE as usize {
+                    Phase::Finish
+                } else {
+                    Phase::Pages { after: last_id, first: false }
+                };
+
+                Some((Ok(encode_page(state.format, page, first)), state))
+            }
+            Phase::Finish => Some((Ok(epilogue(state.format)), state)),
+            Phase::Done => None,
+        }
+    })
+}
+
+fn prelude(format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Csv => format!("{}\r\n", CSV_COLUMNS.join(",")),
+        ExportFormat::Json => "[".to_string(),
+    }
+}
+
+fn epilogue(format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Csv => String::new(),
+        ExportFormat::Json => "]\n".to_string(),
+    }
+}
+
+/// Encode one page; `first` tells JSON whether a separating comma is needed
+fn encode_page(format: ExportFormat, page: Vec<StoredContact>, first: bool) -> String {
+    match format {
+        ExportFormat::Csv => page.iter().map(csv_row).collect(),
+        ExportFormat::Json => {
+            let rows: Vec<String> = page
+                .into_iter()
+                .map(|c| {
+                    serde_json::to_string(&ContactResponse::from_stored(c))
+                        .unwrap_or_else(|_| "null".to_string())
+                })
+                .collect();
+            let separator = if first { "" } else { "," };
+            format!("{}{}", separator, rows.join(","))
+        }
+    }
+}
+
+fn csv_row(stored: &StoredContact) -> String {
+    let c = &stored.contact;
+    let fields = [
+        stored.id.clone(),
+        c.first_name.clone(),
+        c.last_name.clone(),
+        c.email.clone(),
+        c.phone.clone().unwrap_or_default(),
+        c.linkedin_url.clone().unwrap_or_default(),
+        c.tags.join(";"),
+        c.status.to_string().to_lowercase(),
+        c.subscription_status.as_str().to_string(),
+        c.engagement_score.to_string(),
+        c.company_id.clone().unwrap_or_default(),
+        c.created_at.to_rfc3339(),
+        c.updated_at.to_rfc3339(),
+    ];
+
+    let mut row = fields
+        .iter()
+        .map(|f| csv_escape(f))
+        .collect::<Vec<_>>()
+        .join(",");
+    row.push_str("\r\n");
+    row
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break (RFC 4180)
+///
+/// Fields starting with a character a spreadsheet reads as the start of a
+/// formula (`=`, `+`, `-`, `@`, tab or carriage return, per OWASP) are
+/// prefixed with `'` and quoted, so user-supplied content isn't evaluated.
+fn csv_escape(field: &str) -> String {
+    if field.starts_with(FORMULA_PREFIXES) {
+        return format!("\"'{}\"", field.replace('"', "\"\""));
+    }
+
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Leading characters that make spreadsheets evaluate a cell
+const FORMULA_PREFIXES: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ContactBuilder;
+
+    fn stored(id: &str) -> StoredContact {
+        StoredContact {
+            id: id.to_string(),
+            contact: ContactBuilder::new()
+                .first_name("Ada")
+                .last_name("Lovelace, Countess")
+                .email("ada@example.com")
+                .tags(vec!["vip".into(), "investor".into()])
+                .build()
+                .unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_csv_escape() {
+        assert_eq!(csv_escape("plain"), "plain");
+        assert_eq!(csv_escape("a,b"), "\"a,b\"");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_escape("line\nbreak"), "\"line\nbreak\"");
+        assert_eq!(csv_escape("=SUM(A1)"), "\"'=SUM(A1)\"");
+        assert_eq!(csv_escape("+1 555"), "\"'+1 555\"");
+        assert_eq!(csv_escape("@cmd|' /C calc'!A0"), "\"'@cmd|' /C calc'!A0\"");
+        assert_eq!(csv_escape("\t=1+1"), "\"'\t=1+1\"");
+        assert_eq!(csv_escape("\r=1+1"), "\"'\r=1+1\"");
+        assert_eq!(csv_escape("-2,\"x\""), "\"'-2,\"\"x\"\"\"");
+    }
+
+    #[test]
+    fn test_csv_row_matches_header() {
+        let row = csv_row(&stored("abc"));
+
+        assert!(row.starts_with("abc,Ada,\"Lovelace, Countess\",ada@example.com,"));
+        assert!(row.contains(",vip;investor,lead,subscribed,"));
+        assert!(row.ends_with("\r\n"));
+    }
+
+    #[test]
+    fn test_json_pages_form_a_valid_array() {
+        let body = [
+            prelude(ExportFormat::Json),
+            encode_page(ExportFormat::Json, vec![stored("a"), stored("b")], true),
+            encode_page(ExportFormat::Json, vec![stored("c")], false),
+            epilogue(ExportFormat::Json),
+        ]
+        .concat();
+
+        let parsed: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed[2]["id"], "c");
+    }
+
+    #[test]
+    fn test_empty_json_export() {
+        let body = [prelude(ExportFormat::Json), epilogue(ExportFormat::Json)].concat();
+
+        let parsed: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+        assert!(parsed.is_empty());
+    }
+}
@@ -0,0 +1,424 @@
+//! Contact Live Service - real-time contact changes from SurrealDB live queries
+//!
+//! One `LIVE SELECT` over the contact table feeds both the activity feed,
+//! as `contact_changed` events, and the /ws/contacts sockets, where each
+//! subscriber keeps only its own workspace's changes. If the live query
+//! ends (connection lost, database restarted) it is started again with
+//! backoff, and every subscriber is told to resync, since changes in
+//! between are gone. A reconnect ends the stream, but a connection that
+//! silently stopped answering may not, so the connection is pinged while
+//! the query runs and the query restarted when a ping fails.
+//!
+//! Socket messages carry a cursor, and the latest changes are kept so a
+//! client that reconnects with its last cursor is sent what it missed.
+//! Cursors are only good on the process that issued them: one from another
+//! instance, an earlier run, or too far back gets a resync instead.
+//!
+//! Sockets are opened with a one-time ticket, since browsers cannot send
+//! the access token with the handshake. Tickets are stored in the
+//! database, so any instance can take one.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use futures::stream::{self, BoxStream, StreamExt};
+use surrealdb::sql::{Thing, Value};
+use surrealdb::{Action, Notification};
+use tokio::sync::{broadcast, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::db::{workspace_thing, Database};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    ChangeAction, Contact, ContactChange, ContactResponse, ContactStreamMessage, FeedEvent, LiveTicket,
+    LiveTicketResponse,
+};
+use crate::repositories::LiveTicketRepository;
+use crate::services::{AuthenticatedUser, FeedService};
+use crate::shutdown::Shutdown;
+
+const MIN_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// How often the live query connection is pinged while the query runs
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Changes buffered per subscriber before the slowest one has to resync
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// Latest socket messages kept for clients resuming after a reconnect
+const HISTORY_SIZE: usize = 4096;
+
+/// How long a ticket for opening a socket can be used
+const TICKET_TTL_SECS: i64 = 30;
+
+/// A socket message, its place in the stream and the workspace it is for;
+/// `None` goes to everyone
+#[derive(Debug, Clone)]
+struct LiveMessage {
+    seq: u64,
+    workspace_id: Option<String>,
+    message: ContactStreamMessage,
+}
+
+impl LiveMessage {
+    fn is_for(&self, workspace_id: &str) -> bool {
+        self.workspace_id.as_deref().is_none_or(|w| w == workspace_id)
+    }
+}
+
+/// The latest socket messages, oldest first
+#[derive(Debug)]
+struct History {
+    /// Tells this process's cursors apart from another's or an earlier run's
+    epoch: String,
+    messages: VecDeque<LiveMessage>,
+    capacity: usize,
+}
+
+impl History {
+    fn new(epoch: String, capacity: usize) -> Self {
+        Self {
+            epoch,
+            messages: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    fn cursor(&self, seq: u64) -> String {
+        format!("{}.{}", self.epoch, seq)
+    }
+
+    fn push(&mut self, message: LiveMessage) {
+        if self.messages.len() == self.capacity {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(message);
+    }
+
+    /// What a client resuming after `cursor` missed in its workspace
+    ///
+    /// A cursor this history can't account for gets a single resync at
+    /// `latest`.
+    fn replay(&self, workspace_id: &str, cursor: &str, latest: u64) -> Vec<ContactStreamMessage> {
+        let after = cursor
+            .split_once('.')
+            .filter(|(epoch, _)| *epoch == self.epoch)
+            .and_then(|(_, seq)| seq.parse::<u64>().ok());
+        let oldest = self.messages.front().map_or(latest + 1, |m| m.seq);
+
+        match after {
+            Some(after) if after <= latest && after + 1 >= oldest => self
+                .messages
+                .iter()
+                .filter(|m| m.seq > after && m.is_for(workspace_id))
+                .map(|m| m.message.clone())
+                .collect(),
+            _ => vec![ContactStreamMessage::Resync {
+                cursor: self.cursor(latest),
+            }],
+        }
+    }
+}
+
+pub struct ContactLiveService {
+    db: Arc<Database>,
+    feed: Arc<FeedService>,
+    tickets: LiveTicketRepository,
+    sender: broadcast::Sender<LiveMessage>,
+    history: Mutex<History>,
+    /// Sequence number of the latest message; 0 before the first
+    latest: Arc<AtomicU64>,
+}
+
+impl ContactLiveService {
+    pub fn new(db: Arc<Database>, feed: Arc<FeedService>) -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        let epoch = Uuid::new_v4().simple().to_string();
+        Self {
+            tickets: LiveTicketRepository::new(Arc::clone(&db)),
+            db,
+            feed,
+            sender,
+            history: Mutex::new(History::new(epoch, HISTORY_SIZE)),
+            latest: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Issue a ticket that opens one socket as `user`, shortly
+    pub async fn issue_ticket(&self, user: &AuthenticatedUser) -> AppResult<LiveTicketResponse> {
+        let now = Utc::now();
+        let ticket = format!("lt_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let expires_at = now + chrono::Duration::seconds(TICKET_TTL_SECS);
+
+        self.tickets
+            .create(
+                LiveTicket {
+                    id: Some(Thing::from(("live_ticket", ticket.as_str()))),
+                    workspace: workspace_thing(&user.workspace_id),
+                    user: Thing::from(("user", user.user_id.as_str())),
+                    email: user.email.clone(),
+                    expires_at,
+                },
+                now,
+            )
+            .await?;
+
+        Ok(LiveTicketResponse { ticket, expires_at })
+    }
+
+    /// The user a ticket was issued to; the ticket can't be used again
+    pub async fn redeem_ticket(&self, ticket: &str) -> AppResult<AuthenticatedUser> {
+        let ticket = self
+            .tickets
+            .consume(Thing::from(("live_ticket", ticket)), Utc::now())
+            .await?
+            .ok_or_else(|| AppError::Unauthorized("Invalid or expired ticket".into()))?;
+
+        Ok(AuthenticatedUser {
+            user_id: ticket.user.id.to_raw(),
+            email: ticket.email,
+            workspace_id: ticket.workspace.id.to_raw(),
+        })
+    }
+
+    /// Changes to contacts in one workspace, from now on or resuming after
+    /// a cursor
+    ///
+    /// A subscriber that falls behind gets a `resync` message in place of
+    /// the changes it missed.
+    pub async fn subscribe(
+        &self,
+        workspace_id: &str,
+        after: Option<&str>,
+    ) -> BoxStream<'static, ContactStreamMessage> {
+        // Publishing holds the lock while sending, so nothing falls between
+        // the replay and the first live message
+        let history = self.history.lock().await;
+        let receiver = self.sender.subscribe();
+        let latest = self.latest.load(Ordering::SeqCst);
+        let replay = after.map(|cursor| history.replay(workspace_id, cursor, latest)).unwrap_or_default();
+        let epoch = history.epoch.clone();
+        drop(history);
+
+        let workspace_id = workspace_id.to_string();
+        let latest = Arc::clone(&self.latest);
+        let live = stream::unfold(receiver, move |mut receiver| {
+            let workspace_id = workspace_id.clone();
+            let latest = Arc::clone(&latest);
+            let epoch = epoch.clone();
+            async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok(live) if live.is_for(&workspace_id) => return Some((live.message, receiver)),
+                        Ok(_) => continue,
+                        Err(broadcast::error::RecvError::Lagged(_)) => {
+                            let cursor = format!("{}.{}", epoch, latest.load(Ordering::SeqCst));
+                            return Some((ContactStreamMessage::Resync { cursor }, receiver));
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            }
+        });
+
+        stream::iter(replay).chain(live).boxed()
+    }
+
+    /// Keep a live query running until shutdown
+    pub fn spawn(self: Arc<Self>, shutdown: Shutdown) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut stopping = Box::pin(shutdown.triggered());
+            let mut delay = MIN_RETRY_DELAY;
+            let mut restarted = false;
+
+            loop {
+                match self.db.live_contacts().await {
+                    Ok(notifications) => {
+                        delay = MIN_RETRY_DELAY;
+                        if restarted {
+                            self.feed.publish_all(FeedEvent::Resync);
+                            self.broadcast(None, |cursor| ContactStreamMessage::Resync { cursor }).await;
+                        }
+
+                        let mut notifications = Box::pin(notifications);
+                        let mut ping = tokio::time::interval_at(Instant::now() + PING_INTERVAL, PING_INTERVAL);
+                        loop {
+                            let notification = tokio::select! {
+                                _ = &mut stopping => return,
+                                _ = ping.tick() => match self.db.ping_live().await {
+                                    Ok(()) => continue,
+                                    Err(e) => {
+                                        tracing::warn!("Live query connection stopped answering: {}", e);
+                                        break;
+                                    }
+                                },
+                                notification = notifications.next() => notification,
+                            };
+                            let Some(notification) = notification else { break };
+                            match notification {
+                                Ok(notification) => {
+                                    if let Some((workspace_id, change)) = contact_change(notification) {
+                                        self.feed.publish(&workspace_id, FeedEvent::ContactChanged(change.clone()));
+                                        self.broadcast(Some(workspace_id), |cursor| ContactStreamMessage::Change {
+                                            cursor,
+                                            action: change.action,
+                                            contact: Box::new(change.contact),
+                                        })
+                                        .await;
+                                    }
+                                }
+                                Err(e) => tracing::warn!("Bad contact live notification: {}", e),
+                            }
+                        }
+                        tracing::warn!("Contact live query ended, restarting");
+                    }
+                    Err(e) => tracing::error!("Failed to start contact live query: {}", e),
+                }
+
+                restarted = true;
+                tokio::select! {
+                    _ = &mut stopping => return,
+                    _ = tokio::time::sleep(delay) => {}
+                }
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+            }
+        })
+    }
+
+    /// Number, keep and send a socket message
+    async fn broadcast(&self, workspace_id: Option<String>, message: impl FnOnce(String) -> ContactStreamMessage) {
+        let mut history = self.history.lock().await;
+        let seq = self.latest.load(Ordering::SeqCst) + 1;
+        let live = LiveMessage {
+            seq,
+            workspace_id,
+            message: message(history.cursor(seq)),
+        };
+
+        history.push(live.clone());
+        self.latest.store(seq, Ordering::SeqCst);
+        // No subscribers is not an error
+        let _ = self.sender.send(live);
+    }
+}
+
+/// Turn a live notification into a change for the contact's workspace
+///
+/// Notifications that carry only a record ID (some hard deletes) cannot be
+/// scoped to a workspace and are dropped.
+fn contact_change(notification: Notification<Value>) -> Option<(String, ContactChange)> {
+    let Value::Object(object) = notification.data else {
+        return None;
+    };
+    let trashed = object.get("deleted_at").is_some_and(|v| !v.is_none_or_null());
+    let action = change_action(notification.action, trashed)?;
+
+    let contact: Contact = match surrealdb::sql::from_value(Value::Object(object)) {
+        Ok(contact) => contact,
+        Err(e) => {
+            tracing::warn!("Unreadable contact in live notification: {:?}", e);
+            return None;
+        }
+    };
+
+    Some((
+        contact.workspace.id.to_string(),
+        ContactChange {
+            action,
+            contact: ContactResponse::from(contact),
+        },
+    ))
+}
+
+/// Moving a contact to the trash is an update in the database but a
+/// delete to anyone watching
+fn change_action(action: Action, trashed: bool) -> Option<ChangeAction> {
+    match action {
+        Action::Create => Some(ChangeAction::Created),
+        Action::Update if trashed => Some(ChangeAction::Deleted),
+        Action::Update => Some(ChangeAction::Updated),
+        Action::Delete => Some(ChangeAction::Deleted),
+        #[allow(unreachable_patterns)]
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(workspaces: &[Option<&str>], capacity: usize) -> History {
+        let mut history = History::new("e1".to_string(), capacity);
+        for (i, workspace_id) in workspaces.iter().enumerate() {
+            let seq = i as u64 + 1;
+            let cursor = history.cursor(seq);
+            history.push(LiveMessage {
+                seq,
+                workspace_id: workspace_id.map(str::to_string),
+                message: ContactStreamMessage::Resync { cursor },
+            });
+        }
+        history
+    }
+
+    fn cursors(messages: &[ContactStreamMessage]) -> Vec<&str> {
+        messages
+            .iter()
+            .map(|m| match m {
+                ContactStreamMessage::Change { cursor, .. } | ContactStreamMessage::Resync { cursor } => cursor.as_str(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_change_action() {
+        assert_eq!(change_action(Action::Create, false), Some(ChangeAction::Created));
+        assert_eq!(change_action(Action::Update, false), Some(ChangeAction::Updated));
+        assert_eq!(change_action(Action::Update, true), Some(ChangeAction::Deleted));
+        assert_eq!(change_action(Action::Delete, false), Some(ChangeAction::Deleted));
+    }
+
+    #[test]
+    fn test_message_routing() {
+        let to = |workspace_id: Option<&str>| LiveMessage {
+            seq: 1,
+            workspace_id: workspace_id.map(str::to_string),
+            message: ContactStreamMessage::Resync { cursor: "e1.1".to_string() },
+        };
+
+        assert!(to(Some("ws1")).is_for("ws1"));
+        assert!(!to(Some("ws2")).is_for("ws1"));
+        assert!(to(None).is_for("ws1"));
+    }
+
+    #[test]
+    fn test_resume_replays_what_the_workspace_missed() {
+        let history = history(&[Some("ws1"), Some("ws2"), Some("ws1"), None], 10);
+
+        assert_eq!(cursors(&history.replay("ws1", "e1.1", 4)), ["e1.3", "e1.4"]);
+        assert_eq!(cursors(&history.replay("ws2", "e1.0", 4)), ["e1.2", "e1.4"]);
+        assert!(history.replay("ws1", "e1.4", 4).is_empty());
+    }
+
+    #[test]
+    fn test_resume_from_an_unknown_cursor_resyncs() {
+        // Seq 1 and 2 have been dropped
+        let history = history(&[Some("ws1"), Some("ws1"), Some("ws1"), Some("ws1")], 2);
+        let resync = |cursor: &str| matches!(
+            history.replay("ws1", cursor, 4).as_slice(),
+            [ContactStreamMessage::Resync { cursor }] if cursor == "e1.4"
+        );
+
+        assert!(resync("e1.1"));
+        assert!(!resync("e1.2"));
+        assert!(resync("e0.3"));
+        assert!(resync("e1.9"));
+        assert!(resync("garbage"));
+    }
+}
@@ -0,0 +1,226 @@
+//! Contact Report Service - gathers what goes into a contact report
+//!
+//! Collects the profile, engagement, weekly activity, timeline highlights
+//! and notes of one contact and renders them with
+//! `domain::render_contact_report`.
+
+use std::sync::Arc;
+
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+
+use crate::db::Database;
+use crate::domain::{
+    render_contact_report, weekly_activity, Contact, ContactReport, ReportEngagement, ReportEntry, ReportProfile,
+};
+use crate::error::{AppError, AppResult};
+use crate::models::{Company, TimelineEntry, TimelineEntryType, TimelineQuery};
+use crate::repositories::{ContactRepository, TimelineRepository, UserRepository, WorkspaceRepository};
+use crate::services::EngagementService;
+
+/// Weeks of activity charted unless asked otherwise
+const DEFAULT_REPORT_WEEKS: u32 = 26;
+const MAX_REPORT_WEEKS: u32 = 104;
+
+/// Most recent timeline entries searched for highlights
+const HIGHLIGHT_SCAN_ENTRIES: u32 = 500;
+
+/// Highlights and notes listed, newest first
+const REPORT_HIGHLIGHTS: usize = 25;
+const REPORT_NOTES: u32 = 25;
+
+pub struct ContactReportService {
+    db: Arc<Database>,
+    contacts: ContactRepository,
+    timeline: TimelineRepository,
+    users: UserRepository,
+    workspaces: WorkspaceRepository,
+    engagement: Arc<EngagementService>,
+}
+
+impl ContactReportService {
+    pub fn new(db: Arc<Database>, engagement: Arc<EngagementService>) -> Self {
+        Self {
+            contacts: ContactRepository::new(Arc::clone(&db)),
+            timeline: TimelineRepository::new(Arc::clone(&db)),
+            users: UserRepository::new(Arc::clone(&db)),
+            workspaces: WorkspaceRepository::new(Arc::clone(&db)),
+            db,
+            engagement,
+        }
+    }
+
+    /// A contact's report as a standalone HTML page
+    ///
+    /// The activity chart covers the last `weeks` weeks, 26 by default.
+    pub async fn render(&self, workspace_id: &str, contact_id: &str, weeks: Option<u32>) -> AppResult<String> {
+        let weeks = weeks.unwrap_or(DEFAULT_REPORT_WEEKS);
+        if weeks == 0 || weeks > MAX_REPORT_WEEKS {
+            return Err(AppError::Validation(format!(
+                "weeks must be between 1 and {}",
+                MAX_REPORT_WEEKS
+            )));
+        }
+
+        let contact = self
+            .contacts
+            .find_by_id(workspace_id, contact_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Contact {} not found", contact_id)))?;
+        let workspace = self
+            .workspaces
+            .find_by_id(workspace_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Workspace not found".into()))?;
+        let engagement = self.engagement.overview(workspace_id, contact_id).await?;
+
+        let now = Utc::now();
+        let today = now.date_naive();
+        let since = today - Duration::weeks(i64::from(weeks));
+        let daily = self
+            .timeline
+            .daily_counts(
+                workspace_id,
+                &TimelineQuery {
+                    contact_id: Some(contact_id.to_string()),
+                    from: since.and_hms_opt(0, 0, 0).map(|t| Utc.from_utc_datetime(&t)),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        let daily: Vec<(NaiveDate, u64)> = daily
+            .into_iter()
+            .filter_map(|d| Some((d.date.parse().ok()?, d.count)))
+            .collect();
+
+        let highlights = self
+            .find_entries(workspace_id, contact_id, None, HIGHLIGHT_SCAN_ENTRIES)
+            .await?
+            .into_iter()
+            .filter_map(|entry| Some(report_entry(highlight_label(&entry.entry_type)?, entry)))
+            .take(REPORT_HIGHLIGHTS)
+            .collect();
+        let notes = self
+            .find_entries(workspace_id, contact_id, Some(TimelineEntryType::Note), REPORT_NOTES)
+            .await?
+            .into_iter()
+            .map(|entry| report_entry("Note", entry))
+            .collect();
+
+        let report = ContactReport {
+            workspace_name: workspace.name,
+            profile: self.profile(workspace_id, &contact).await?,
+            engagement: ReportEngagement {
+                score: engagement.engagement_score,
+                level: engagement.level,
+                trend: engagement.trend,
+            },
+            activity: weekly_activity(&daily, weeks, today),
+            highlights,
+            notes,
+        };
+
+        Ok(render_contact_report(&report, now))
+    }
+
+    async fn profile(&self, workspace_id: &str, contact: &Contact) -> AppResult<ReportProfile> {
+        let company: Option<Company> = match &contact.company_id {
+            Some(company_id) => self.db.select_scoped("company", company_id, workspace_id).await?,
+            None => None,
+        };
+        let owner = match &contact.owner_id {
+            Some(owner_id) => self
+                .users
+                .find_by_id(owner_id)
+                .await?
+                .filter(|user| user.workspace.id.to_raw() == workspace_id)
+                .map(|user| user.name),
+            None => None,
+        };
+        let location: Vec<&str> = [&contact.city, &contact.region, &contact.country]
+            .into_iter()
+            .filter_map(|part| part.as_deref())
+            .collect();
+
+        Ok(ReportProfile {
+            name: contact.full_name(),
+            email: contact.email.clone(),
+            phone: contact.phone.clone(),
+            linkedin_url: contact.linkedin_url.clone(),
+            company: company.map(|c| c.name),
+            status: contact.status.to_string(),
+            location: (!location.is_empty()).then(|| location.join(", ")),
+            tags: contact.tags.clone(),
+            owner,
+            created_at: contact.created_at,
+        })
+    }
+
+    /// A contact's newest timeline entries, optionally of one type
+    async fn find_entries(
+        &self,
+        workspace_id: &str,
+        contact_id: &str,
+        entry_type: Option<TimelineEntryType>,
+        limit: u32,
+    ) -> AppResult<Vec<TimelineEntry>> {
+        let query = TimelineQuery {
+            contact_id: Some(contact_id.to_string()),
+            entry_type,
+            limit: Some(limit),
+            ..Default::default()
+        };
+        self.timeline.find(workspace_id, &query).await
+    }
+}
+
+fn report_entry(label: &str, entry: TimelineEntry) -> ReportEntry {
+    ReportEntry {
+        timestamp: entry.timestamp,
+        label: label.to_string(),
+        content: entry.content,
+    }
+}
+
+/// How a timeline entry worth a place in the report is labelled
+///
+/// Routine entries (campaign sends, opens, clicks, visits) and notes,
+/// which have their own section, are left out.
+fn highlight_label(entry_type: &TimelineEntryType) -> Option<&'static str> {
+    match entry_type {
+        TimelineEntryType::EmailReceived => Some("Email received"),
+        TimelineEntryType::MeetingScheduled => Some("Meeting scheduled"),
+        TimelineEntryType::MeetingAttended => Some("Meeting attended"),
+        TimelineEntryType::Call => Some("Call"),
+        TimelineEntryType::Task => Some("Task"),
+        TimelineEntryType::EventRegistration => Some("Event registration"),
+        TimelineEntryType::EventAttend => Some("Event attended"),
+        TimelineEntryType::FormSubmission => Some("Form submission"),
+        TimelineEntryType::ExternalEvent => Some("External event"),
+        TimelineEntryType::Churned => Some("Churned"),
+        TimelineEntryType::EmailComplaint => Some("Spam complaint"),
+        TimelineEntryType::Unsubscribed => Some("Unsubscribed"),
+        TimelineEntryType::EmailSent
+        | TimelineEntryType::EmailOpen
+        | TimelineEntryType::EmailClick
+        | TimelineEntryType::EmailBounce
+        | TimelineEntryType::SocialTouch
+        | TimelineEntryType::Note
+        | TimelineEntryType::PrepBrief
+        | TimelineEntryType::EventInvite
+        | TimelineEntryType::LandingPageVisit
+        | TimelineEntryType::Enrichment => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlights_leave_out_routine_entries_and_notes() {
+        assert_eq!(highlight_label(&TimelineEntryType::MeetingAttended), Some("Meeting attended"));
+        assert_eq!(highlight_label(&TimelineEntryType::EmailReceived), Some("Email received"));
+        assert_eq!(highlight_label(&TimelineEntryType::EmailOpen), None);
+        assert_eq!(highlight_label(&TimelineEntryType::Note), None);
+    }
+}
@@ -6,14 +6,42 @@
 //! - Repository (data access) - persist to database
 //!
 //! The service enforces business rules that require database access,
-//! like "email must be unique".
+//! like "email must be unique" (within a workspace).
+//!
+//! Every operation is scoped to the caller's workspace.
+//!
+//! Creates, updates and deletes are audited with the acting user, and
+//! creating a contact or changing its status publishes webhook events.
+//! New contacts also go out on the live activity feed. Every status change
+//! is kept in the contact's status history, written with the change itself.
+//!
+//! Bulk updates apply the same per-contact rules and write every changed
+//! contact in a single transaction.
+//!
+//! Every write drops the workspace's cached tag list and segment counts.
 
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 
-use crate::db::Database;
-use crate::domain::{Contact, ContactBuilder, ContactStatus, ContactUpdater};
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use surrealdb::sql::Thing;
+
+use crate::db::{new_thing, workspace_thing, Database};
+use crate::domain::{
+    diff_fields, find_duplicate_pairs, lead_outcome, match_new_contact, merge_contacts, validate_tag, win_loss_reason,
+    AuditAction, AuditEntity, Churn, Contact, ContactBuilder, ContactStatus, ContactUpdater, DomainError, DomainResult,
+    WebhookEvent, WinLossReason,
+};
 use crate::error::{AppError, AppResult};
-use crate::repositories::{ContactQuery, ContactRepository, StoredContact};
+use crate::models::{
+    BulkContactResult, BulkContactsResponse, BulkResultStatus, ContactResponse, DuplicateCandidate, ExportFormat,
+    FeedEvent, MergeContactsResponse, PossibleDuplicate, StatusHistoryEntry, TagCount, TimelineEntryType,
+};
+use crate::repositories::{ContactQuery, ContactRepository, StoredContact, UserRepository};
+use crate::services::contact_export;
+use crate::services::read_cache::{CacheScope, ReadCache};
+use crate::services::{new_entry, AuditService, AuthenticatedUser, FeedService, TimelineService, WebhookService};
 
 /// Request to create a new contact
 #[derive(Debug)]
@@ -23,9 +51,18 @@ pub struct CreateContactInput {
     pub email: String,
     pub phone: Option<String>,
     pub linkedin_url: Option<String>,
+    pub timezone: Option<String>,
+    pub country: Option<String>,
+    pub region: Option<String>,
+    pub city: Option<String>,
     pub tags: Vec<String>,
     pub status: Option<ContactStatus>,
     pub company_id: Option<String>,
+    pub custom_fields: BTreeMap<String, String>,
+    /// Recorded on the new contact's timeline in the same transaction
+    pub note: Option<String>,
+    /// Skip the fuzzy duplicate check; an exact email match is still refused
+    pub allow_duplicates: bool,
 }
 
 /// Request to update an existing contact
@@ -36,21 +73,77 @@ pub struct UpdateContactInput {
     pub email: Option<String>,
     pub phone: Option<String>,
     pub linkedin_url: Option<String>,
+    pub timezone: Option<String>,
+    pub country: Option<String>,
+    pub region: Option<String>,
+    pub city: Option<String>,
     pub tags: Option<Vec<String>>,
     pub status: Option<ContactStatus>,
+    /// Kept in the status history when the status changes
+    pub status_reason: Option<String>,
+    /// Kept in the status history when a lead is won or lost
+    pub win_loss_reason: Option<WinLossReason>,
     pub engagement_score: Option<f64>,
     pub company_id: Option<String>,
+    /// Place on the pipeline board, saved with the rest of the change
+    pub pipeline_position: Option<f64>,
+    /// `None` values remove the field
+    pub custom_fields: Option<BTreeMap<String, Option<String>>>,
+}
+
+/// One change in a bulk update, applied in order to every targeted contact
+#[derive(Debug, Clone)]
+pub enum BulkChange {
+    AddTags(Vec<String>),
+    RemoveTags(Vec<String>),
+    SetStatus(ContactStatus),
+    SetOwner(Option<String>),
 }
 
+/// Most contacts compared when scanning the workspace for duplicates
+const MAX_DUPLICATE_CANDIDATES: usize = 5000;
+
+/// Existing contacts compared against one about to be created
+const NEW_CONTACT_CANDIDATES: u32 = 200;
+
+/// Most likely duplicates reported when creating a contact
+const MAX_POSSIBLE_DUPLICATES: usize = 5;
+
+/// Most contacts one bulk update may target
+pub const MAX_BULK_CONTACTS: usize = 1000;
+
+/// Most contacts one batch get may ask for
+const MAX_BATCH_GET_CONTACTS: usize = 500;
+
+/// Longest reason a status change may be given
+const MAX_STATUS_REASON_LEN: usize = 1000;
+
 /// The Contact Service - your entry point for all contact operations
 pub struct ContactService {
     repo: ContactRepository,
+    users: UserRepository,
+    audit: AuditService,
+    webhooks: WebhookService,
+    feed: Arc<FeedService>,
+    timeline: Arc<TimelineService>,
+    cache: Arc<ReadCache>,
 }
 
 impl ContactService {
-    pub fn new(db: Arc<Database>) -> Self {
+    pub fn new(
+        db: Arc<Database>,
+        feed: Arc<FeedService>,
+        timeline: Arc<TimelineService>,
+        cache: Arc<ReadCache>,
+    ) -> Self {
         Self {
-            repo: ContactRepository::new(db),
+            repo: ContactRepository::new(Arc::clone(&db)),
+            users: UserRepository::new(Arc::clone(&db)),
+            audit: AuditService::new(Arc::clone(&db)),
+            webhooks: WebhookService::new(db),
+            feed,
+            timeline,
+            cache,
         }
     }
 
@@ -60,15 +153,16 @@ impl ContactService {
     /// 1. Validates all input using domain validation
     /// 2. Checks email uniqueness (business rule requiring DB)
     /// 3. Creates the contact using ContactBuilder
-    /// 4. Persists via repository
-    pub async fn create(&self, input: CreateContactInput) -> AppResult<StoredContact> {
+    /// 4. Persists via repository, with the initial note in the same transaction
+    /// 5. Audits the creation
+    pub async fn create(&self, actor: &AuthenticatedUser, input: CreateContactInput) -> AppResult<StoredContact> {
+        let workspace_id = actor.workspace_id.as_str();
+
         // Step 1: Check email uniqueness BEFORE building
         // This is a business rule that requires database access
-        if let Some(_existing) = self.repo.find_by_email(&input.email).await? {
-            return Err(AppError::Conflict(format!(
-                "A contact with email '{}' already exists",
-                input.email
-            )));
+        let email = input.email.trim().to_lowercase();
+        if let Some(existing_id) = self.repo.find_other_id_by_email(workspace_id, &email, None).await? {
+            return Err(DomainError::DuplicateEmail { email, existing_id }.into());
         }
 
         // Step 2: Build the contact using domain layer
@@ -86,6 +180,22 @@ impl ContactService {
             builder = builder.linkedin_url(linkedin);
         }
 
+        if let Some(ref timezone) = input.timezone {
+            builder = builder.timezone(timezone);
+        }
+
+        if let Some(ref country) = input.country {
+            builder = builder.country(country);
+        }
+
+        if let Some(ref region) = input.region {
+            builder = builder.region(region);
+        }
+
+        if let Some(ref city) = input.city {
+            builder = builder.city(city);
+        }
+
         builder = builder.tags(input.tags);
 
         if let Some(status) = input.status {
@@ -96,154 +206,792 @@ impl ContactService {
             builder = builder.company_id(company_id);
         }
 
+        for (key, value) in &input.custom_fields {
+            builder = builder.custom_field(key, value);
+        }
+
         // Build validates everything
         let contact = builder.build()?;
 
-        // Step 3: Persist
-        let stored = self.repo.create_with_id(&contact).await?;
+        // Step 3: Refuse likely duplicates unless the caller insists
+        if !input.allow_duplicates {
+            let duplicates = self.possible_duplicates(workspace_id, &contact).await?;
+            if !duplicates.is_empty() {
+                return Err(AppError::PossibleDuplicates(duplicates));
+            }
+        }
+
+        // Step 4: Persist, together with the initial note if there is one
+        let note = input.note.as_deref().map(str::trim).filter(|n| !n.is_empty());
+        let stored = match note {
+            Some(note) => {
+                let mut entry = new_entry(
+                    workspace_id,
+                    &new_thing("contact").id.to_raw(),
+                    TimelineEntryType::Note,
+                    note,
+                    serde_json::json!({}),
+                );
+                entry.company = contact.company_id.as_deref().map(|c| Thing::from(("company", c)));
+                entry.timestamp = contact.created_at;
+
+                let stored = self.repo.create_with_note(workspace_id, &contact, &entry).await?;
+                self.timeline.recorded(workspace_id, &[entry]).await;
+                stored
+            }
+            None => self.repo.create_with_id(workspace_id, &contact).await?,
+        };
+        self.cache.invalidate(workspace_id, CacheScope::Contacts).await;
+
+        self.audit
+            .record_create(actor, AuditEntity::Contact, &stored.id, &stored.contact)
+            .await;
+        self.webhooks
+            .notify(workspace_id, WebhookEvent::ContactCreated, contact_data(&stored))
+            .await;
+        self.feed.publish(
+            workspace_id,
+            FeedEvent::ContactCreated(ContactResponse::from_stored(stored.clone())),
+        );
 
         Ok(stored)
     }
 
     /// Get a contact by ID
-    pub async fn get(&self, id: &str) -> AppResult<StoredContact> {
+    pub async fn get(&self, workspace_id: &str, id: &str) -> AppResult<StoredContact> {
         self.repo
-            .find_by_id_with_id(id)
+            .find_by_id_with_id(workspace_id, id)
             .await?
             .ok_or_else(|| AppError::NotFound(format!("Contact '{}' not found", id)))
     }
 
     /// List contacts with optional filters
-    pub async fn list(&self, query: ContactQuery) -> AppResult<Vec<StoredContact>> {
-        // Get contacts from repository
-        let contacts = self.repo.find_all(query).await?;
+    pub async fn list(&self, workspace_id: &str, query: ContactQuery) -> AppResult<Vec<StoredContact>> {
+        self.repo.find_all_with_ids(workspace_id, query).await
+    }
 
-        // For now, we don't have IDs in find_all result
-        // This is a simplification - in production you'd want IDs
-        Ok(contacts
-            .into_iter()
-            .map(|c| StoredContact {
-                id: String::new(), // Repository layer should include this
-                contact: c,
-            })
-            .collect())
+    /// Load many contacts in one query, in the order asked for
+    ///
+    /// Returns the contacts found and the IDs that weren't.
+    pub async fn get_many(&self, workspace_id: &str, ids: Vec<String>) -> AppResult<(Vec<StoredContact>, Vec<String>)> {
+        if ids.len() > MAX_BATCH_GET_CONTACTS {
+            return Err(AppError::Validation(format!(
+                "A batch get can ask for at most {} contacts",
+                MAX_BATCH_GET_CONTACTS
+            )));
+        }
+        let unique = unique_ids(ids);
+        if unique.is_empty() {
+            return Ok((Vec::new(), Vec::new()));
+        }
+
+        let found = self.repo.find_many_with_ids(workspace_id, &unique).await?;
+        Ok(in_requested_order(&unique, found))
+    }
+
+    /// Number of contacts matching a query, whatever its page
+    pub async fn count(&self, workspace_id: &str, query: &ContactQuery) -> AppResult<u64> {
+        self.repo.count(workspace_id, query).await
+    }
+
+    /// Every tag in use, most used first
+    pub async fn tags(&self, workspace_id: &str) -> AppResult<Vec<TagCount>> {
+        self.cache
+            .get_or_load(workspace_id, CacheScope::Contacts, "tags", || self.repo.tag_counts(workspace_id))
+            .await
+    }
+
+    /// Stream all contacts matching a query as CSV or JSON chunks
+    ///
+    /// The stream owns its own repository handle, so it can outlive the
+    /// request handler that created it.
+    pub fn export(
+        &self,
+        workspace_id: &str,
+        query: ContactQuery,
+        format: ExportFormat,
+    ) -> impl Stream<Item = AppResult<String>> + Send + 'static {
+        contact_export::export_stream(self.repo.clone(), workspace_id.to_string(), query, format)
     }
 
     /// Update an existing contact
     ///
     /// This method:
     /// 1. Loads the existing contact
-    /// 2. Validates each updated field
-    /// 3. Checks email uniqueness if email changed
-    /// 4. Applies updates using domain rules
-    /// 5. Persists changes
-    pub async fn update(&self, id: &str, input: UpdateContactInput) -> AppResult<StoredContact> {
+    /// 2. Checks email uniqueness if email changed
+    /// 3. Applies updates through ContactUpdater (validation + domain rules)
+    /// 4. Persists changes
+    /// 5. Audits the modified fields
+    pub async fn update(
+        &self,
+        actor: &AuthenticatedUser,
+        id: &str,
+        input: UpdateContactInput,
+    ) -> AppResult<StoredContact> {
+        let workspace_id = actor.workspace_id.as_str();
+        let status_reason = status_reason(input.status_reason.as_deref())?;
+
         // Step 1: Load existing
         let stored = self
             .repo
-            .find_by_id_with_id(id)
+            .find_by_id_with_id(workspace_id, id)
             .await?
             .ok_or_else(|| AppError::NotFound(format!("Contact '{}' not found", id)))?;
 
-        let mut contact = stored.contact;
+        let before = stored.contact;
 
         // Step 2: Check email uniqueness if changing
         if let Some(ref new_email) = input.email {
             let normalized = new_email.trim().to_lowercase();
-            if normalized != contact.email {
-                if self.repo.email_exists_for_other(&normalized, id).await? {
-                    return Err(AppError::Conflict(format!(
-                        "A contact with email '{}' already exists",
-                        normalized
-                    )));
+            if normalized != before.email {
+                if let Some(existing_id) = self
+                    .repo
+                    .find_other_id_by_email(workspace_id, &normalized, Some(id))
+                    .await?
+                {
+                    return Err(DomainError::DuplicateEmail {
+                        email: normalized,
+                        existing_id,
+                    }
+                    .into());
                 }
-                // Validate and update email
-                crate::domain::validate_email(&normalized)?;
-                contact.email = normalized;
             }
         }
 
-        // Step 3: Apply other updates with validation
+        // Step 3: Apply updates with validation
+        let mut updater = ContactUpdater::new(before.clone());
+
+        if let Some(ref email) = input.email {
+            updater = updater.email(email)?;
+        }
         if let Some(ref first_name) = input.first_name {
-            crate::domain::validate_name(first_name, "first_name")?;
-            contact.first_name = first_name.trim().to_string();
+            updater = updater.first_name(first_name)?;
         }
-
         if let Some(ref last_name) = input.last_name {
-            crate::domain::validate_name(last_name, "last_name")?;
-            contact.last_name = last_name.trim().to_string();
+            updater = updater.last_name(last_name)?;
         }
-
         if let Some(ref phone) = input.phone {
-            crate::domain::validate_phone(Some(phone))?;
-            contact.phone = if phone.is_empty() {
-                None
-            } else {
-                Some(phone.clone())
-            };
+            updater = updater.phone(Some(phone))?;
         }
-
         if let Some(ref linkedin) = input.linkedin_url {
-            crate::domain::validate_linkedin_url(Some(linkedin))?;
-            contact.linkedin_url = if linkedin.is_empty() {
-                None
-            } else {
-                Some(linkedin.clone())
-            };
+            updater = updater.linkedin_url(Some(linkedin))?;
+        }
+        if let Some(ref timezone) = input.timezone {
+            updater = updater.timezone(Some(timezone))?;
+        }
+        if let Some(ref country) = input.country {
+            updater = updater.country(Some(country))?;
+        }
+        if let Some(ref region) = input.region {
+            updater = updater.region(Some(region))?;
+        }
+        if let Some(ref city) = input.city {
+            updater = updater.city(Some(city))?;
         }
-
         if let Some(ref tags) = input.tags {
-            contact.tags = crate::domain::validate_tags(tags)?;
+            updater = updater.tags(tags)?;
         }
-
         if let Some(new_status) = input.status {
-            // Use domain method which enforces transition rules
-            contact.transition_status(new_status)?;
+            // Enforces transition rules
+            updater = updater.status(new_status)?;
         }
-
         if let Some(score) = input.engagement_score {
-            contact.update_engagement(score)?;
+            updater = updater.engagement_score(score)?;
         }
-
         if let Some(ref company_id) = input.company_id {
-            contact.company_id = if company_id.is_empty() {
-                None
-            } else {
-                Some(company_id.clone())
-            };
+            updater = updater.company_id(Some(company_id));
+        }
+        if let Some(position) = input.pipeline_position {
+            updater = updater.pipeline_position(position);
+        }
+        if let Some(ref custom_fields) = input.custom_fields {
+            updater = updater.custom_fields(custom_fields)?;
         }
 
-        // Update timestamp
-        contact.updated_at = chrono::Utc::now();
+        let modified_fields = updater.modified_fields().to_vec();
+        let contact = updater.apply()?;
+        let win_loss_reason = win_loss_reason(lead_outcome(before.status, contact.status), input.win_loss_reason)?;
+
+        // Step 4: Persist, with the status change if there is one
+        let change = status_change(actor, id, &before, &contact, status_reason).map(|change| StatusHistoryEntry {
+            win_loss_reason,
+            ..change
+        });
+        let updated = match change {
+            Some(change) => {
+                self.repo
+                    .update_with_status_change(workspace_id, id, &contact, &change, None)
+                    .await?
+            }
+            None => self.repo.update(workspace_id, id, &contact).await?,
+        };
+        self.cache.invalidate(workspace_id, CacheScope::Contacts).await;
+        let stored = StoredContact {
+            id: id.to_string(),
+            contact: updated,
+        };
+
+        // Step 5: Audit and notify
+        let changes = diff_fields(
+            &serde_json::to_value(&before).unwrap_or_default(),
+            &serde_json::to_value(&stored.contact).unwrap_or_default(),
+            &modified_fields,
+        );
+        self.audit
+            .record(actor, AuditEntity::Contact, id, AuditAction::Update, changes)
+            .await;
+
+        if stored.contact.status != before.status {
+            let mut data = contact_data(&stored);
+            data["previous_status"] = serde_json::json!(before.status);
+            self.webhooks
+                .notify(workspace_id, WebhookEvent::ContactStatusChanged, data)
+                .await;
+        }
+
+        Ok(stored)
+    }
+
+    /// Move a contact to the trash
+    ///
+    /// It stays restorable until the purge job removes it for good.
+    pub async fn delete(&self, actor: &AuthenticatedUser, id: &str) -> AppResult<bool> {
+        // Check exists first
+        let existing = self
+            .repo
+            .find_by_id(&actor.workspace_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Contact '{}' not found", id)))?;
 
-        // Step 4: Persist
-        let updated = self.repo.update(id, &contact).await?;
+        let deleted = self.repo.delete(&actor.workspace_id, id).await?;
 
-        Ok(StoredContact {
+        if deleted {
+            self.cache.invalidate(&actor.workspace_id, CacheScope::Contacts).await;
+            self.audit
+                .record_delete(actor, AuditEntity::Contact, id, &existing)
+                .await;
+        }
+
+        Ok(deleted)
+    }
+
+    /// Churn a customer
+    ///
+    /// They go back to Lead, tagged `churned`. The reason goes on their
+    /// timeline, dated the day they left, and into their status history, in
+    /// the same transaction as the change itself, which is audited and
+    /// announced like any other status change.
+    pub async fn churn(
+        &self,
+        actor: &AuthenticatedUser,
+        id: &str,
+        reason: &str,
+        churned_at: Option<DateTime<Utc>>,
+    ) -> AppResult<StoredContact> {
+        let workspace_id = actor.workspace_id.as_str();
+        let before = self.get(workspace_id, id).await?.contact;
+
+        let last_status_change = self
+            .repo
+            .status_history(workspace_id, id)
+            .await?
+            .last()
+            .map(|change| change.changed_at);
+        let not_before = last_status_change.map_or(before.created_at, |at| at.max(before.created_at));
+        let churn = Churn::new(reason, churned_at, not_before, Utc::now())?;
+
+        let updater = ContactUpdater::new(before.clone()).churn()?;
+        let modified_fields = updater.modified_fields().to_vec();
+        let contact = updater.apply()?;
+
+        let mut change = status_change(actor, id, &before, &contact, Some(churn.reason.clone()))
+            .ok_or_else(|| AppError::Internal("Churning left the status unchanged".into()))?;
+        change.changed_at = churn.churned_at;
+
+        let mut entry = new_entry(
+            workspace_id,
+            id,
+            TimelineEntryType::Churned,
+            format!("Churned: {}", churn.reason),
+            serde_json::json!({ "reason": churn.reason }),
+        );
+        entry.company = before.company_id.as_deref().map(|c| Thing::from(("company", c)));
+        entry.timestamp = churn.churned_at;
+
+        let updated = self
+            .repo
+            .update_with_status_change(workspace_id, id, &contact, &change, Some(&entry))
+            .await?;
+        self.cache.invalidate(workspace_id, CacheScope::Contacts).await;
+        self.timeline.recorded(workspace_id, &[entry]).await;
+
+        let stored = StoredContact {
             id: id.to_string(),
             contact: updated,
+        };
+        let changes = diff_fields(
+            &serde_json::to_value(&before).unwrap_or_default(),
+            &serde_json::to_value(&stored.contact).unwrap_or_default(),
+            &modified_fields,
+        );
+        self.audit
+            .record(actor, AuditEntity::Contact, id, AuditAction::Update, changes)
+            .await;
+
+        let mut data = contact_data(&stored);
+        data["previous_status"] = serde_json::json!(before.status);
+        data["churn_reason"] = serde_json::json!(churn.reason);
+        self.webhooks
+            .notify(workspace_id, WebhookEvent::ContactStatusChanged, data)
+            .await;
+
+        Ok(stored)
+    }
+
+    /// A contact's status changes, oldest first
+    pub async fn status_history(&self, workspace_id: &str, id: &str) -> AppResult<Vec<StatusHistoryEntry>> {
+        self.get(workspace_id, id).await?;
+        self.repo.status_history(workspace_id, id).await
+    }
+
+    /// Take a contact back out of the trash
+    pub async fn restore(&self, actor: &AuthenticatedUser, id: &str) -> AppResult<StoredContact> {
+        let restored = self
+            .repo
+            .restore(&actor.workspace_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Contact '{}' not found in trash", id)))?;
+        self.cache.invalidate(&actor.workspace_id, CacheScope::Contacts).await;
+
+        self.audit
+            .record_restore(actor, AuditEntity::Contact, id, &restored.contact)
+            .await;
+
+        Ok(restored)
+    }
+
+    /// Merge a duplicate contact into a primary one
+    ///
+    /// Field conflicts are resolved by the domain merge rules; related
+    /// timeline entries, deals, RSVPs and campaign recipients move to the
+    /// primary, and the duplicate is deleted.
+    pub async fn merge(
+        &self,
+        actor: &AuthenticatedUser,
+        primary_id: &str,
+        duplicate_id: &str,
+    ) -> AppResult<MergeContactsResponse> {
+        let workspace_id = actor.workspace_id.as_str();
+
+        if primary_id == duplicate_id {
+            return Err(AppError::BadRequest("Cannot merge a contact into itself".into()));
+        }
+
+        let primary = self.get(workspace_id, primary_id).await?;
+        let duplicate = self.get(workspace_id, duplicate_id).await?;
+
+        let result = merge_contacts(&primary.contact, &duplicate.contact, chrono::Utc::now());
+        let note = format!(
+            "Merged duplicate contact {} <{}>",
+            duplicate.contact.full_name(),
+            duplicate.contact.email
+        );
+
+        let reason = format!("Merged with duplicate contact {}", duplicate_id);
+        let status_change = status_change(actor, primary_id, &primary.contact, &result.merged, Some(reason));
+        let merged = self
+            .repo
+            .merge_into(workspace_id, primary_id, &result.merged, duplicate_id, &note, status_change)
+            .await?;
+        self.cache.invalidate(workspace_id, CacheScope::Contacts).await;
+
+        self.audit
+            .record_update(actor, AuditEntity::Contact, primary_id, &primary.contact, &merged.contact)
+            .await;
+        self.audit
+            .record_delete(actor, AuditEntity::Contact, duplicate_id, &duplicate.contact)
+            .await;
+
+        Ok(MergeContactsResponse {
+            contact: ContactResponse::from_stored(merged),
+            merged_contact_id: duplicate_id.to_string(),
+            conflicts: result.conflicts,
         })
     }
 
-    /// Delete a contact
-    pub async fn delete(&self, id: &str) -> AppResult<bool> {
-        // Check exists first
-        self.repo
-            .find_by_id(id)
+    /// Apply the same changes to many contacts at once
+    ///
+    /// Each contact goes through ContactUpdater, so the usual rules hold: a
+    /// contact whose status can't make the requested transition is reported
+    /// as failed and left alone, while the rest still go ahead. All changed
+    /// contacts are then written in one transaction, audited and, where the
+    /// status changed, announced by webhook. Results follow the order of
+    /// `ids`, repeats dropped.
+    pub async fn bulk_update(
+        &self,
+        actor: &AuthenticatedUser,
+        ids: Vec<String>,
+        changes: Vec<BulkChange>,
+    ) -> AppResult<BulkContactsResponse> {
+        let workspace_id = actor.workspace_id.as_str();
+
+        if changes.is_empty() {
+            return Err(AppError::Validation("At least one operation is required".into()));
+        }
+
+        if ids.len() > MAX_BULK_CONTACTS {
+            return Err(AppError::Validation(format!(
+                "A bulk update can target at most {} contacts",
+                MAX_BULK_CONTACTS
+            )));
+        }
+        let unique = unique_ids(ids);
+
+        // Bad input would fail every contact the same way, so reject it up front
+        for change in &changes {
+            if let BulkChange::AddTags(tags) = change {
+                for tag in tags {
+                    validate_tag(tag)?;
+                }
+            }
+            if let BulkChange::SetOwner(Some(owner_id)) = change {
+                let owner = self.users.find_by_id(owner_id).await?;
+                if !owner.is_some_and(|u| u.workspace.id.to_string() == workspace_id) {
+                    return Err(AppError::Validation(format!("Owner '{}' is not a user in this workspace", owner_id)));
+                }
+            }
+        }
+
+        let mut found: HashMap<String, Contact> = self
+            .repo
+            .find_many_with_ids(workspace_id, &unique)
             .await?
-            .ok_or_else(|| AppError::NotFound(format!("Contact '{}' not found", id)))?;
+            .into_iter()
+            .map(|s| (s.id, s.contact))
+            .collect();
+
+        let mut results = Vec::with_capacity(unique.len());
+        let mut writes: Vec<(StoredContact, Contact, Vec<String>)> = Vec::new();
 
-        self.repo.delete(id).await
+        for id in unique {
+            let Some(before) = found.remove(&id) else {
+                results.push(bulk_result(id, BulkResultStatus::NotFound, Vec::new(), Some("Contact not found".into())));
+                continue;
+            };
+
+            match apply_bulk_changes(before.clone(), &changes) {
+                Err(e) => results.push(bulk_result(id, BulkResultStatus::Failed, Vec::new(), Some(e.to_string()))),
+                Ok((_, fields)) if fields.is_empty() => {
+                    results.push(bulk_result(id, BulkResultStatus::Unchanged, Vec::new(), None))
+                }
+                Ok((contact, fields)) => {
+                    results.push(bulk_result(id.clone(), BulkResultStatus::Updated, fields.clone(), None));
+                    writes.push((StoredContact { id, contact }, before, fields));
+                }
+            }
+        }
+
+        let stored: Vec<StoredContact> = writes.iter().map(|(s, _, _)| s.clone()).collect();
+        let status_changes = writes
+            .iter()
+            .filter_map(|(s, before, _)| status_change(actor, &s.id, before, &s.contact, None))
+            .collect();
+        self.repo.update_many(workspace_id, &stored, status_changes).await?;
+        if !stored.is_empty() {
+            self.cache.invalidate(workspace_id, CacheScope::Contacts).await;
+        }
+
+        for (stored, before, fields) in &writes {
+            let changes = diff_fields(
+                &serde_json::to_value(before).unwrap_or_default(),
+                &serde_json::to_value(&stored.contact).unwrap_or_default(),
+                fields,
+            );
+            self.audit
+                .record(actor, AuditEntity::Contact, &stored.id, AuditAction::Update, changes)
+                .await;
+
+            if stored.contact.status != before.status {
+                let mut data = contact_data(stored);
+                data["previous_status"] = serde_json::json!(before.status);
+                self.webhooks
+                    .notify(workspace_id, WebhookEvent::ContactStatusChanged, data)
+                    .await;
+            }
+        }
+
+        Ok(BulkContactsResponse::from_results(results))
+    }
+
+    /// Existing contacts that `contact` would likely duplicate, most likely first
+    async fn possible_duplicates(&self, workspace_id: &str, contact: &Contact) -> AppResult<Vec<PossibleDuplicate>> {
+        let candidates = self
+            .repo
+            .find_duplicate_candidates(workspace_id, contact, NEW_CONTACT_CANDIDATES)
+            .await?;
+
+        let mut duplicates: Vec<PossibleDuplicate> = candidates
+            .into_iter()
+            .filter_map(|existing| {
+                let m = match_new_contact(contact, &existing.contact)?;
+                Some(PossibleDuplicate {
+                    contact: ContactResponse::from_stored(existing),
+                    score: m.score,
+                    reasons: m.reasons,
+                })
+            })
+            .collect();
+
+        duplicates.sort_by(|a, b| b.score.total_cmp(&a.score));
+        duplicates.truncate(MAX_POSSIBLE_DUPLICATES);
+        Ok(duplicates)
+    }
+
+    /// Likely duplicate pairs in the workspace, most certain first
+    ///
+    /// Only contacts that share a blocking key with another are loaded and
+    /// compared; the rest of the workspace is never read.
+    pub async fn find_duplicates(&self, workspace_id: &str, limit: usize) -> AppResult<Vec<DuplicateCandidate>> {
+        let ids = self.repo.find_duplicate_group_members(workspace_id, MAX_DUPLICATE_CANDIDATES).await?;
+        let contacts = self.repo.find_many_with_ids(workspace_id, &ids).await?;
+
+        let domain: Vec<Contact> = contacts.iter().map(|c| c.contact.clone()).collect();
+
+        Ok(find_duplicate_pairs(&domain)
+            .into_iter()
+            .take(limit)
+            .map(|(i, j, m)| DuplicateCandidate {
+                contact: ContactResponse::from_stored(contacts[i].clone()),
+                duplicate: ContactResponse::from_stored(contacts[j].clone()),
+                score: m.score,
+                reasons: m.reasons,
+            })
+            .collect())
     }
 
     /// Find a contact by email
-    pub async fn find_by_email(&self, email: &str) -> AppResult<Option<Contact>> {
-        self.repo.find_by_email(email).await
+    pub async fn find_by_email(&self, workspace_id: &str, email: &str) -> AppResult<Option<Contact>> {
+        self.repo.find_by_email(workspace_id, email).await
     }
 }
 
+/// Webhook payload `data` for a contact event
+fn contact_data(stored: &StoredContact) -> serde_json::Value {
+    serde_json::to_value(ContactResponse::from_stored(stored.clone())).unwrap_or_default()
+}
+
+/// The history entry for a contact whose status differs after a change
+fn status_change(
+    actor: &AuthenticatedUser,
+    contact_id: &str,
+    before: &Contact,
+    after: &Contact,
+    reason: Option<String>,
+) -> Option<StatusHistoryEntry> {
+    if before.status == after.status {
+        return None;
+    }
+
+    Some(StatusHistoryEntry {
+        id: None,
+        workspace: workspace_thing(&actor.workspace_id),
+        contact: Thing::from(("contact", contact_id)),
+        from_status: before.status.into(),
+        to_status: after.status.into(),
+        actor: Some(Thing::from(("user", actor.user_id.as_str()))),
+        reason,
+        win_loss_reason: None,
+        changed_at: Utc::now(),
+    })
+}
+
+/// A status change reason, trimmed; blank is none
+fn status_reason(reason: Option<&str>) -> DomainResult<Option<String>> {
+    let Some(reason) = reason.map(str::trim).filter(|r| !r.is_empty()) else {
+        return Ok(None);
+    };
+    if reason.chars().count() > MAX_STATUS_REASON_LEN {
+        return Err(DomainError::InvalidField {
+            field: "status_reason".into(),
+            reason: format!("must be at most {} characters", MAX_STATUS_REASON_LEN),
+        });
+    }
+
+    Ok(Some(reason.to_string()))
+}
+
+/// Run every change against one contact, returning it with the fields that changed
+fn apply_bulk_changes(contact: Contact, changes: &[BulkChange]) -> DomainResult<(Contact, Vec<String>)> {
+    let mut updater = ContactUpdater::new(contact);
+
+    for change in changes {
+        updater = match change {
+            BulkChange::AddTags(tags) => tags.iter().try_fold(updater, |u, tag| u.add_tag(tag))?,
+            BulkChange::RemoveTags(tags) => tags.iter().fold(updater, |u, tag| u.remove_tag(tag)),
+            BulkChange::SetStatus(status) => updater.status(*status)?,
+            BulkChange::SetOwner(owner_id) => updater.owner_id(owner_id.as_deref()),
+        };
+    }
+
+    let fields = updater.modified_fields().to_vec();
+    Ok((updater.apply()?, fields))
+}
+
+fn bulk_result(
+    contact_id: String,
+    status: BulkResultStatus,
+    changed_fields: Vec<String>,
+    error: Option<String>,
+) -> BulkContactResult {
+    BulkContactResult {
+        contact_id,
+        status,
+        changed_fields,
+        error,
+    }
+}
+
+/// IDs with repeats dropped, first occurrence kept
+fn unique_ids(ids: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::with_capacity(ids.len());
+    ids.into_iter().filter(|id| seen.insert(id.clone())).collect()
+}
+
+/// Order loaded contacts as requested, and list the IDs that weren't found
+fn in_requested_order(ids: &[String], found: Vec<StoredContact>) -> (Vec<StoredContact>, Vec<String>) {
+    let mut by_id: HashMap<String, StoredContact> = found.into_iter().map(|s| (s.id.clone(), s)).collect();
+    let mut contacts = Vec::with_capacity(by_id.len());
+    let mut not_found = Vec::new();
+
+    for id in ids {
+        match by_id.remove(id) {
+            Some(stored) => contacts.push(stored),
+            None => not_found.push(id.clone()),
+        }
+    }
+
+    (contacts, not_found)
+}
+
+/// Split a comma-separated tag filter into normalized tags
+///
+/// Tags are stored lowercased, so the filter is too; blanks and repeats
+/// are dropped.
+pub fn parse_tag_filter(raw: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in raw.split(',').map(|t| t.trim().to_lowercase()) {
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
 #[cfg(test)]
 mod tests {
     // Service tests would typically use a mock repository
     // For now, integration tests cover service behavior
+
+    use super::*;
+
+    #[test]
+    fn test_parse_tag_filter() {
+        assert_eq!(parse_tag_filter("VIP, beta ,,vip"), vec!["vip", "beta"]);
+        assert!(parse_tag_filter(" , ").is_empty());
+    }
+
+    fn contact() -> Contact {
+        ContactBuilder::new()
+            .first_name("Ada")
+            .last_name("Lovelace")
+            .email("ada@example.com")
+            .tags(vec!["vip".into(), "beta".into()])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_apply_bulk_changes_reports_changed_fields() {
+        let changes = vec![
+            BulkChange::AddTags(vec!["Investor".into()]),
+            BulkChange::RemoveTags(vec!["BETA".into()]),
+            BulkChange::SetStatus(ContactStatus::Customer),
+            BulkChange::SetOwner(Some("alice".into())),
+        ];
+
+        let (updated, fields) = apply_bulk_changes(contact(), &changes).unwrap();
+
+        assert_eq!(updated.tags, vec!["vip", "investor"]);
+        assert_eq!(updated.status, ContactStatus::Customer);
+        assert_eq!(updated.owner_id.as_deref(), Some("alice"));
+        assert_eq!(fields, vec!["tags", "status", "owner_id"]);
+    }
+
+    #[test]
+    fn test_apply_bulk_changes_unchanged() {
+        let changes = vec![
+            BulkChange::AddTags(vec!["vip".into()]),
+            BulkChange::RemoveTags(vec!["absent".into()]),
+            BulkChange::SetOwner(None),
+        ];
+
+        let (_, fields) = apply_bulk_changes(contact(), &changes).unwrap();
+
+        assert!(fields.is_empty());
+    }
+
+    #[test]
+    fn test_status_change_only_when_status_differs() {
+        let actor = AuthenticatedUser {
+            user_id: "u1".into(),
+            email: "owner@example.com".into(),
+            workspace_id: "ws1".into(),
+        };
+        let before = contact();
+        let (after, _) = apply_bulk_changes(before.clone(), &[BulkChange::SetStatus(ContactStatus::Customer)]).unwrap();
+
+        let change = status_change(&actor, "c1", &before, &after, Some("Signed".into())).unwrap();
+        assert_eq!(change.from_status, crate::models::ContactStatus::Lead);
+        assert_eq!(change.to_status, crate::models::ContactStatus::Customer);
+        assert_eq!(change.actor, Some(Thing::from(("user", "u1"))));
+        assert_eq!(change.contact, Thing::from(("contact", "c1")));
+        assert!(status_change(&actor, "c1", &before, &before, None).is_none());
+    }
+
+    #[test]
+    fn test_status_reason_is_trimmed_and_bounded() {
+        assert_eq!(status_reason(Some("  Signed the contract ")).unwrap().as_deref(), Some("Signed the contract"));
+        assert_eq!(status_reason(Some("   ")).unwrap(), None);
+        assert_eq!(status_reason(None).unwrap(), None);
+        assert!(status_reason(Some(&"x".repeat(MAX_STATUS_REASON_LEN + 1))).is_err());
+    }
+
+    #[test]
+    fn test_unique_ids_keeps_first_occurrences_in_order() {
+        let ids = vec!["c2".to_string(), "c1".into(), "c2".into(), "c3".into(), "c1".into()];
+        assert_eq!(unique_ids(ids), vec!["c2", "c1", "c3"]);
+    }
+
+    #[test]
+    fn test_in_requested_order() {
+        let stored = |id: &str| StoredContact {
+            id: id.into(),
+            contact: contact(),
+        };
+        let ids: Vec<String> = ["c3", "gone", "c1"].into_iter().map(String::from).collect();
+
+        let (contacts, not_found) = in_requested_order(&ids, vec![stored("c1"), stored("c3")]);
+
+        let order: Vec<&str> = contacts.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(order, vec!["c3", "c1"]);
+        assert_eq!(not_found, vec!["gone"]);
+    }
+
+    #[test]
+    fn test_apply_bulk_changes_rejects_invalid_tag() {
+        let result = apply_bulk_changes(contact(), &[BulkChange::AddTags(vec!["not a tag!".into()])]);
+
+        assert!(matches!(result, Err(DomainError::InvalidField { .. })));
+    }
 }
+
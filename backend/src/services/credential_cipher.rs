@@ -0,0 +1,125 @@
+//! Credential Cipher - encrypting account credentials before they are stored
+//!
+//! Mailbox passwords and OAuth and social access tokens are sealed with
+//! AES-256-GCM under a key from the secrets manager. A sealed value is
+//! `enc:v1:` followed by the hex of the nonce and ciphertext. Values without
+//! the prefix were stored before encryption; they are read as they are.
+
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use sha2::{Digest, Sha256};
+
+use crate::config::CredentialsConfig;
+use crate::error::{AppError, AppResult};
+use crate::secrets::SecretsManager;
+
+const SEALED_PREFIX: &str = "enc:v1:";
+
+pub struct CredentialCipher {
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl CredentialCipher {
+    /// The key is the SHA-256 of the secret, so any long random string will do
+    pub fn new(secret: &str) -> AppResult<Self> {
+        let digest = Sha256::digest(secret.as_bytes());
+        let key = UnboundKey::new(&AES_256_GCM, &digest)
+            .map_err(|_| AppError::Internal("Failed to derive the credential key".into()))?;
+
+        Ok(Self {
+            key: LessSafeKey::new(key),
+            rng: SystemRandom::new(),
+        })
+    }
+
+    /// `None` when the secrets manager has no usable key; accounts with
+    /// credentials can't be connected then
+    pub fn from_config(config: &CredentialsConfig, secrets: &SecretsManager) -> Option<Self> {
+        match secrets.get_secret(&config.key_secret) {
+            Ok(secret) if !secret.is_empty() => match Self::new(&secret) {
+                Ok(cipher) => Some(cipher),
+                Err(e) => {
+                    tracing::error!("{}; mailboxes and social accounts can't be connected", e);
+                    None
+                }
+            },
+            _ => {
+                tracing::warn!("No {} secret; mailboxes and social accounts can't be connected", config.key_secret);
+                None
+            }
+        }
+    }
+
+    pub fn seal(&self, plaintext: &str) -> AppResult<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| AppError::Internal("Failed to generate a nonce".into()))?;
+
+        let mut sealed = plaintext.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut sealed)
+            .map_err(|_| AppError::Internal("Failed to encrypt a credential".into()))?;
+
+        Ok(format!("{}{}{}", SEALED_PREFIX, hex::encode(nonce), hex::encode(sealed)))
+    }
+
+    /// The plaintext of a stored value
+    pub fn open(&self, stored: &str) -> AppResult<String> {
+        let Some(sealed) = stored.strip_prefix(SEALED_PREFIX) else {
+            return Ok(stored.to_string());
+        };
+
+        let unreadable = || AppError::Internal("A stored credential can't be decrypted with the current key".into());
+        let bytes = hex::decode(sealed).map_err(|_| unreadable())?;
+        if bytes.len() < NONCE_LEN {
+            return Err(unreadable());
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| unreadable())?;
+
+        let mut ciphertext = ciphertext.to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut ciphertext)
+            .map_err(|_| unreadable())?;
+
+        String::from_utf8(plaintext.to_vec()).map_err(|_| unreadable())
+    }
+}
+
+/// Whether a stored value is already encrypted
+pub fn is_sealed(stored: &str) -> bool {
+    stored.starts_with(SEALED_PREFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_round_trips() {
+        let cipher = CredentialCipher::new("test-key").unwrap();
+        let sealed = cipher.seal("hunter2").unwrap();
+
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.contains("hunter2"));
+        assert_ne!(sealed, cipher.seal("hunter2").unwrap());
+        assert_eq!(cipher.open(&sealed).unwrap(), "hunter2");
+    }
+
+    #[test]
+    fn test_open_rejects_another_key() {
+        let sealed = CredentialCipher::new("test-key").unwrap().seal("hunter2").unwrap();
+
+        assert!(CredentialCipher::new("other-key").unwrap().open(&sealed).is_err());
+        assert!(CredentialCipher::new("test-key").unwrap().open("enc:v1:zz").is_err());
+    }
+
+    #[test]
+    fn test_plaintext_from_before_encryption_is_read_as_is() {
+        assert_eq!(CredentialCipher::new("test-key").unwrap().open("hunter2").unwrap(), "hunter2");
+        assert!(!is_sealed("hunter2"));
+    }
+}
@@ -0,0 +1,222 @@
+//! Deal Service - the deals of the sales pipeline
+//!
+//! Stage changes go through the deal stage state machine in
+//! `domain::deal`: closing stamps `closed_at` and takes the win/loss
+//! reason, reopening clears both. Every write is audited and drops the
+//! workspace's cached deal reads.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use surrealdb::sql::Thing;
+
+use crate::db::{workspace_thing, Database};
+use crate::domain::{closed_at, deal_outcome, validate_deal, win_loss_reason, AuditEntity};
+use crate::error::{AppError, AppResult};
+use crate::models::{CreateDealRequest, Deal, DealQuery, DealResponse, DealStageRequest, UpdateDealRequest};
+use crate::repositories::DealRepository;
+use crate::services::read_cache::{CacheScope, ReadCache};
+use crate::services::{AuditService, AuthenticatedUser};
+
+const DEFAULT_LIMIT: u32 = 50;
+/// Most deals returned per page
+const MAX_LIMIT: u32 = 200;
+
+pub struct DealService {
+    repo: DealRepository,
+    audit: Arc<AuditService>,
+    cache: Arc<ReadCache>,
+}
+
+impl DealService {
+    pub fn new(db: Arc<Database>, audit: Arc<AuditService>, cache: Arc<ReadCache>) -> Self {
+        Self {
+            repo: DealRepository::new(db),
+            audit,
+            cache,
+        }
+    }
+
+    /// One page of the workspace's deals, at most `MAX_LIMIT` long
+    pub async fn list(&self, workspace_id: &str, query: &DealQuery) -> AppResult<Vec<DealResponse>> {
+        let deals = self.repo.find(workspace_id, query, page_limit(query.limit)).await?;
+        Ok(deals.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get(&self, workspace_id: &str, id: &str) -> AppResult<DealResponse> {
+        Ok(self.find(workspace_id, id).await?.into())
+    }
+
+    /// Create a deal; one created closed is stamped as closed now
+    pub async fn create(&self, actor: &AuthenticatedUser, req: CreateDealRequest) -> AppResult<DealResponse> {
+        let workspace_id = actor.workspace_id.as_str();
+        validate_deal(&req.name, req.value)?;
+
+        let contact = self.linked_record(workspace_id, "contact", req.contact_id.as_deref()).await?;
+        let company = self.linked_record(workspace_id, "company", req.company_id.as_deref()).await?;
+
+        let now = Utc::now();
+        let stage = req.stage.unwrap_or_default();
+
+        let deal = self
+            .repo
+            .create(Deal {
+                id: None,
+                workspace: workspace_thing(workspace_id),
+                name: req.name.trim().to_string(),
+                value: req.value,
+                stage,
+                expected_close_date: req.expected_close_date,
+                closed_at: stage.is_closed().then_some(now),
+                win_loss_reason: None,
+                contact,
+                company,
+                notes: req.notes,
+                pipeline_position: None,
+                created_at: now,
+                updated_at: now,
+            })
+            .await?;
+        self.cache.invalidate(workspace_id, CacheScope::Deals).await;
+
+        let response = DealResponse::from(deal.clone());
+        self.audit
+            .record_create(actor, AuditEntity::Deal, &response.id, &deal)
+            .await;
+
+        Ok(response)
+    }
+
+    /// Update a deal's details; its stage is changed with `change_stage`
+    pub async fn update(
+        &self,
+        actor: &AuthenticatedUser,
+        id: &str,
+        req: UpdateDealRequest,
+    ) -> AppResult<DealResponse> {
+        let workspace_id = actor.workspace_id.as_str();
+        let before = self.find(workspace_id, id).await?;
+        let mut deal = before.clone();
+
+        if let Some(name) = req.name {
+            deal.name = name.trim().to_string();
+        }
+        if let Some(value) = req.value {
+            deal.value = value;
+        }
+        if let Some(date) = req.expected_close_date {
+            deal.expected_close_date = Some(date);
+        }
+        if req.contact_id.is_some() {
+            deal.contact = self.linked_record(workspace_id, "contact", req.contact_id.as_deref()).await?;
+        }
+        if req.company_id.is_some() {
+            deal.company = self.linked_record(workspace_id, "company", req.company_id.as_deref()).await?;
+        }
+        if let Some(notes) = req.notes {
+            deal.notes = Some(notes);
+        }
+
+        validate_deal(&deal.name, deal.value)?;
+        deal.updated_at = Utc::now();
+
+        let deal = self.save(actor, id, &before, deal).await?;
+        Ok(deal.into())
+    }
+
+    /// Move a deal to another pipeline stage
+    ///
+    /// Moving to the stage it is already in changes nothing.
+    pub async fn change_stage(
+        &self,
+        actor: &AuthenticatedUser,
+        id: &str,
+        req: DealStageRequest,
+    ) -> AppResult<DealResponse> {
+        let before = self.find(&actor.workspace_id, id).await?;
+        if before.stage == req.stage {
+            return Ok(before.into());
+        }
+
+        let mut deal = before.clone();
+        let now = Utc::now();
+
+        deal.stage = before.stage.transition_to(req.stage)?;
+        deal.closed_at = closed_at(before.stage, deal.stage, before.closed_at, now);
+        deal.win_loss_reason = win_loss_reason(deal_outcome(deal.stage), req.win_loss_reason)?;
+        deal.updated_at = now;
+
+        let deal = self.save(actor, id, &before, deal).await?;
+        Ok(deal.into())
+    }
+
+    pub async fn delete(&self, actor: &AuthenticatedUser, id: &str) -> AppResult<()> {
+        let workspace_id = actor.workspace_id.as_str();
+        let existing = self.find(workspace_id, id).await?;
+
+        if !self.repo.delete(workspace_id, id).await? {
+            return Err(AppError::NotFound("Deal not found".into()));
+        }
+        self.cache.invalidate(workspace_id, CacheScope::Deals).await;
+
+        self.audit
+            .record_delete(actor, AuditEntity::Deal, id, &existing)
+            .await;
+
+        Ok(())
+    }
+
+    async fn find(&self, workspace_id: &str, id: &str) -> AppResult<Deal> {
+        self.repo
+            .find_by_id(workspace_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Deal not found".into()))
+    }
+
+    /// Store an updated deal and audit the change
+    async fn save(&self, actor: &AuthenticatedUser, id: &str, before: &Deal, deal: Deal) -> AppResult<Deal> {
+        let workspace_id = actor.workspace_id.as_str();
+        let deal = self
+            .repo
+            .update(workspace_id, id, deal)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Deal not found".into()))?;
+        self.cache.invalidate(workspace_id, CacheScope::Deals).await;
+
+        self.audit
+            .record_update(actor, AuditEntity::Deal, id, before, &deal)
+            .await;
+
+        Ok(deal)
+    }
+
+    /// Resolve a linked contact or company, making sure it belongs to the workspace
+    async fn linked_record(&self, workspace_id: &str, table: &str, id: Option<&str>) -> AppResult<Option<Thing>> {
+        let Some(id) = id else {
+            return Ok(None);
+        };
+
+        if !self.repo.linkable_exists(workspace_id, table, id).await? {
+            return Err(AppError::BadRequest(format!("Unknown {} '{}'", table, id)));
+        }
+
+        Ok(Some(Thing::from((table, id))))
+    }
+}
+
+fn page_limit(limit: Option<u32>) -> u32 {
+    limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_limit_is_clamped() {
+        assert_eq!(page_limit(None), DEFAULT_LIMIT);
+        assert_eq!(page_limit(Some(0)), 1);
+        assert_eq!(page_limit(Some(20)), 20);
+        assert_eq!(page_limit(Some(100_000)), MAX_LIMIT);
+    }
+}
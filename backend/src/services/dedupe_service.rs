@@ -0,0 +1,401 @@
+//! Dedupe Service - the embedding-based duplicate review queue
+//!
+//! A background task embeds each workspace's contacts, re-embedding only
+//! those whose text or embedding model changed, and queues every pair at
+//! least `min_confidence` alike that hasn't been suggested before. Erased
+//! contacts are left out. The comparison is CPU-bound, so it runs on the
+//! blocking thread pool. Accepting a suggestion claims it, then merges the
+//! pair through ContactService; dismissing it keeps the pair from being
+//! raised again.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use surrealdb::sql::Thing;
+use tokio::task::JoinHandle;
+
+use crate::ai::embedding::Embedder;
+use crate::config::DedupeConfig;
+use crate::db::{workspace_thing, Database};
+use crate::domain::{duplicate_reasons, embedding_text, is_erased, similar_pairs, DedupeStatus};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    AcceptDedupeRequest, ContactResponse, ContactSort, DedupeSuggestion, DedupeSuggestionQuery,
+    DedupeSuggestionResponse, MergeContactsResponse, SortOrder,
+};
+use crate::repositories::{ContactEmbedding, ContactQuery, ContactRepository, DedupeRepository, StoredContact};
+use crate::services::{AuthenticatedUser, ContactService};
+use crate::shutdown::Shutdown;
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 200;
+
+pub struct DedupeService {
+    dedupe: DedupeRepository,
+    contact_repo: ContactRepository,
+    contacts: Arc<ContactService>,
+    embedder: Arc<dyn Embedder>,
+    config: DedupeConfig,
+}
+
+impl DedupeService {
+    pub fn new(
+        db: Arc<Database>,
+        embedder: Arc<dyn Embedder>,
+        contacts: Arc<ContactService>,
+        config: &DedupeConfig,
+    ) -> Self {
+        Self {
+            dedupe: DedupeRepository::new(Arc::clone(&db)),
+            contact_repo: ContactRepository::new(db),
+            contacts,
+            embedder,
+            config: config.clone(),
+        }
+    }
+
+    /// Embed and compare the contacts of every workspace
+    ///
+    /// Returns the number of suggestions queued.
+    pub async fn run(&self) -> AppResult<usize> {
+        let mut queued = 0;
+
+        for workspace_id in self.dedupe.find_workspace_ids().await? {
+            match self.run_workspace(&workspace_id).await {
+                Ok(count) => queued += count,
+                Err(e) => tracing::warn!("Dedupe run failed for workspace {}: {}", workspace_id, e),
+            }
+        }
+
+        Ok(queued)
+    }
+
+    /// Run `run` on a background task at the configured interval
+    pub fn spawn(self: Arc<Self>, shutdown: Shutdown) -> JoinHandle<()> {
+        let period = Duration::from_secs(self.config.check_interval_secs.max(60));
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+
+            while shutdown.tick(&mut interval).await {
+                match self.run().await {
+                    Ok(queued) if queued > 0 => tracing::info!("Queued {} duplicate suggestions", queued),
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Dedupe run failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// The review queue, most certain first; pending suggestions by default
+    pub async fn suggestions(
+        &self,
+        workspace_id: &str,
+        query: DedupeSuggestionQuery,
+    ) -> AppResult<Vec<DedupeSuggestionResponse>> {
+        let status = query.status.unwrap_or_default();
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+        let suggestions = self.dedupe.find_suggestions(workspace_id, status, limit).await?;
+        let responses = self.responses(workspace_id, suggestions).await?;
+
+        // A pending pair with a contact gone can no longer be merged
+        Ok(responses
+            .into_iter()
+            .filter(|r| r.status != DedupeStatus::Pending || (r.contact.is_some() && r.duplicate.is_some()))
+            .collect())
+    }
+
+    /// Merge the pair into one contact
+    ///
+    /// The older contact is kept unless `primary_id` names the other one.
+    pub async fn accept(
+        &self,
+        actor: &AuthenticatedUser,
+        id: &str,
+        req: AcceptDedupeRequest,
+    ) -> AppResult<MergeContactsResponse> {
+        let workspace_id = actor.workspace_id.as_str();
+        let suggestion = self.get(workspace_id, id).await?;
+        suggestion.status.resolve(DedupeStatus::Accepted)?;
+
+        let contact_id = suggestion.contact.id.to_string();
+        let duplicate_id = suggestion.duplicate.id.to_string();
+        let (primary_id, merged_id) = match req.primary_id.as_deref() {
+            None => (contact_id, duplicate_id),
+            Some(primary) if primary == contact_id => (contact_id, duplicate_id),
+            Some(primary) if primary == duplicate_id => (duplicate_id, contact_id),
+            Some(_) => {
+                return Err(AppError::BadRequest(
+                    "primary_id must be one of the two suggested contacts".into(),
+                ));
+            }
+        };
+
+        // Claimed first, so two reviewers accepting at once can't both merge
+        self.dedupe
+            .resolve(workspace_id, id, DedupeStatus::Accepted, &actor.user_id)
+            .await?
+            .ok_or_else(already_resolved)?;
+
+        match self.contacts.merge(actor, &primary_id, &merged_id).await {
+            Ok(merged) => Ok(merged),
+            Err(e) => {
+                if let Err(reopen) = self.dedupe.reopen(workspace_id, id).await {
+                    tracing::error!("Failed to reopen suggestion {} after a failed merge: {}", id, reopen);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Mark the pair as two different people
+    pub async fn dismiss(&self, actor: &AuthenticatedUser, id: &str) -> AppResult<DedupeSuggestionResponse> {
+        let suggestion = self.get(&actor.workspace_id, id).await?;
+        suggestion.status.resolve(DedupeStatus::Dismissed)?;
+
+        let dismissed = self
+            .dedupe
+            .resolve(&actor.workspace_id, id, DedupeStatus::Dismissed, &actor.user_id)
+            .await?
+            .ok_or_else(already_resolved)?;
+
+        self.responses(&actor.workspace_id, vec![dismissed])
+            .await?
+            .pop()
+            .ok_or_else(|| AppError::Internal("Dismissed suggestion has no ID".into()))
+    }
+
+    async fn get(&self, workspace_id: &str, id: &str) -> AppResult<DedupeSuggestion> {
+        self.dedupe
+            .find_suggestion(workspace_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Suggestion {} not found", id)))
+    }
+
+    /// Refresh the workspace's embeddings and queue the new pairs
+    async fn run_workspace(&self, workspace_id: &str) -> AppResult<usize> {
+        let query = ContactQuery::new()
+            .with_sort(ContactSort::UpdatedAt, Some(SortOrder::Desc))
+            .with_limit(self.config.max_contacts);
+        let mut contacts = self.contact_repo.find_all_with_ids(workspace_id, query).await?;
+        // Erased contacts are placeholders; neither embedded nor compared
+        contacts.retain(|c| !is_erased(&c.id, &c.contact.email));
+
+        let vectors = self.refresh_embeddings(workspace_id, &contacts).await?;
+        self.dedupe.delete_stale(workspace_id).await?;
+        if contacts.len() < 2 {
+            return Ok(0);
+        }
+
+        let suggested = self.dedupe.find_suggested_pairs(workspace_id).await?;
+        let (workspace, min_confidence) = (workspace_id.to_string(), self.config.min_confidence);
+        let suggestions = tokio::task::spawn_blocking(move || {
+            new_suggestions(&workspace, &contacts, &vectors, &suggested, min_confidence, Utc::now())
+        })
+        .await
+        .map_err(|e| AppError::Internal(format!("Duplicate comparison failed: {}", e)))?;
+
+        let count = suggestions.len();
+        self.dedupe.insert_suggestions(suggestions).await?;
+        Ok(count)
+    }
+
+    /// One vector per contact, embedding only what changed since the last run
+    async fn refresh_embeddings(&self, workspace_id: &str, contacts: &[StoredContact]) -> AppResult<Vec<Vec<f32>>> {
+        let companies = self.dedupe.find_company_names(workspace_id).await?;
+        let texts: Vec<String> = contacts
+            .iter()
+            .map(|c| {
+                let company = c.contact.company_id.as_ref().and_then(|id| companies.get(id));
+                embedding_text(&c.contact, company.map(String::as_str))
+            })
+            .collect();
+
+        let model = self.embedder.model().to_string();
+        let mut stored: HashMap<String, ContactEmbedding> = self
+            .dedupe
+            .find_embeddings(workspace_id)
+            .await?
+            .into_iter()
+            .map(|e| (e.contact.id.to_string(), e))
+            .collect();
+
+        let stale: Vec<usize> = (0..contacts.len())
+            .filter(|&i| match stored.get(&contacts[i].id) {
+                Some(e) => e.text != texts[i] || e.model != model,
+                None => true,
+            })
+            .collect();
+
+        for chunk in stale.chunks(self.config.batch_size.max(1)) {
+            let batch: Vec<String> = chunk.iter().map(|&i| texts[i].clone()).collect();
+            let vectors = self.embedder.embed(&batch).await?;
+
+            let now = Utc::now();
+            let embeddings: Vec<ContactEmbedding> = chunk
+                .iter()
+                .zip(vectors)
+                .map(|(&i, vector)| ContactEmbedding {
+                    id: Some(Thing::from(("contact_embedding", contacts[i].id.as_str()))),
+                    workspace: workspace_thing(workspace_id),
+                    contact: Thing::from(("contact", contacts[i].id.as_str())),
+                    text: texts[i].clone(),
+                    model: model.clone(),
+                    vector,
+                    updated_at: now,
+                })
+                .collect();
+
+            for embedding in &embeddings {
+                stored.insert(embedding.contact.id.to_string(), embedding.clone());
+            }
+            self.dedupe.save_embeddings(workspace_id, embeddings).await?;
+        }
+
+        let keep: Vec<Thing> = contacts.iter().map(|c| Thing::from(("contact", c.id.as_str()))).collect();
+        self.dedupe.delete_embeddings_except(workspace_id, &keep).await?;
+
+        Ok(contacts
+            .iter()
+            .map(|c| stored.remove(&c.id).map(|e| e.vector).unwrap_or_default())
+            .collect())
+    }
+
+    /// Join suggestions with their contacts, as they are now
+    async fn responses(
+        &self,
+        workspace_id: &str,
+        suggestions: Vec<DedupeSuggestion>,
+    ) -> AppResult<Vec<DedupeSuggestionResponse>> {
+        let ids: Vec<String> = suggestions
+            .iter()
+            .flat_map(|s| [s.contact.id.to_string(), s.duplicate.id.to_string()])
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let contacts: HashMap<String, StoredContact> = self
+            .contact_repo
+            .find_many_with_ids(workspace_id, &ids)
+            .await?
+            .into_iter()
+            .map(|c| (c.id.clone(), c))
+            .collect();
+
+        Ok(suggestions
+            .into_iter()
+            .filter_map(|s| {
+                let contact_id = s.contact.id.to_string();
+                let duplicate_id = s.duplicate.id.to_string();
+                Some(DedupeSuggestionResponse {
+                    id: s.id?.id.to_raw(),
+                    contact: contacts.get(&contact_id).cloned().map(ContactResponse::from_stored),
+                    duplicate: contacts.get(&duplicate_id).cloned().map(ContactResponse::from_stored),
+                    contact_id,
+                    duplicate_id,
+                    confidence: s.confidence,
+                    reasons: s.reasons,
+                    status: s.status,
+                    created_at: s.created_at,
+                    resolved_at: s.resolved_at,
+                })
+            })
+            .collect())
+    }
+}
+
+fn already_resolved() -> AppError {
+    AppError::Conflict("The suggestion has already been resolved".into())
+}
+
+/// Pairs alike enough that haven't been suggested before, older contact first
+///
+/// `vectors` line up with `contacts`; `suggested` holds the contact IDs of
+/// the pairs already in the queue, in either order.
+fn new_suggestions(
+    workspace_id: &str,
+    contacts: &[StoredContact],
+    vectors: &[Vec<f32>],
+    suggested: &HashSet<(String, String)>,
+    min_confidence: f64,
+    now: DateTime<Utc>,
+) -> Vec<DedupeSuggestion> {
+    similar_pairs(vectors, min_confidence)
+        .into_iter()
+        .filter_map(|(i, j, confidence)| {
+            let (older, newer) = if contacts[j].contact.created_at < contacts[i].contact.created_at {
+                (&contacts[j], &contacts[i])
+            } else {
+                (&contacts[i], &contacts[j])
+            };
+            if suggested.contains(&(older.id.clone(), newer.id.clone()))
+                || suggested.contains(&(newer.id.clone(), older.id.clone()))
+            {
+                return None;
+            }
+            let contact = Thing::from(("contact", older.id.as_str()));
+            let duplicate = Thing::from(("contact", newer.id.as_str()));
+
+            Some(DedupeSuggestion {
+                id: None,
+                workspace: workspace_thing(workspace_id),
+                contact,
+                duplicate,
+                confidence,
+                reasons: duplicate_reasons(&older.contact, &newer.contact),
+                status: DedupeStatus::Pending,
+                created_at: now,
+                resolved_at: None,
+                resolved_by: None,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{ContactBuilder, DuplicateReason};
+
+    fn stored(id: &str, email: &str, days_old: i64) -> StoredContact {
+        let mut contact = ContactBuilder::new()
+            .first_name("Jane")
+            .last_name("Doe")
+            .email(email)
+            .build()
+            .unwrap();
+        contact.created_at = Utc::now() - chrono::Duration::days(days_old);
+        StoredContact {
+            id: id.into(),
+            contact,
+        }
+    }
+
+    #[test]
+    fn test_new_suggestions_keep_the_older_contact_and_skip_known_pairs() {
+        let contacts = vec![
+            stored("new", "jane@example.com", 1),
+            stored("old", "jane.doe@example.com", 30),
+            stored("other", "jd@example.org", 10),
+        ];
+        let vectors = vec![vec![1.0, 0.0], vec![1.0, 0.05], vec![0.9, 0.3]];
+        // Already suggested, the other way round
+        let suggested = HashSet::from([("other".to_string(), "new".to_string())]);
+
+        let suggestions = new_suggestions("acme", &contacts, &vectors, &suggested, 0.9, Utc::now());
+
+        let pairs: Vec<(String, String)> = suggestions
+            .iter()
+            .map(|s| (s.contact.id.to_string(), s.duplicate.id.to_string()))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![("old".to_string(), "new".to_string()), ("old".to_string(), "other".to_string())]
+        );
+        assert!(suggestions[0].confidence > suggestions[1].confidence);
+        assert!(suggestions[0].reasons.contains(&DuplicateReason::SameName));
+        assert_eq!(suggestions[0].status, DedupeStatus::Pending);
+    }
+}
@@ -0,0 +1,255 @@
+//! Deliverability Service - bounce and complaint callbacks from the email provider
+//!
+//! SES (through SNS) and SendGrid POST to `/api/email-events/:provider/:token`,
+//! where the token is a shared secret from the secrets manager. Each bounce
+//! or complaint lands on the timeline of every contact with the address,
+//! attributed to the campaign the email carried, or else to the campaign
+//! last sent to the contact. Hard bounces and complaints suppress the
+//! contact: their subscription becomes `bounced` or `complained`, pending
+//! campaign sends to them are dropped, and sequences stop at the next step.
+
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use chrono::{Duration, Utc};
+use sha2::{Digest, Sha256};
+use surrealdb::sql::Thing;
+
+use crate::config::EmailEventsConfig;
+use crate::db::{new_thing, Database};
+use crate::domain::{parse_sendgrid, parse_ses, DeliveryFeedback, EmailProvider, FeedbackKind, SesNotification};
+use crate::error::{AppError, AppResult};
+use crate::models::{EmailEventsResponse, TimelineEntry, TimelineEntryType};
+use crate::repositories::{DeliverabilityRepository, ReportedContact};
+use crate::secrets::SecretsManager;
+use crate::services::TimelineService;
+
+/// How long after a campaign email a report without a campaign is still attributed to it
+const ATTRIBUTION_WINDOW_DAYS: i64 = 30;
+
+pub struct DeliverabilityService {
+    repo: DeliverabilityRepository,
+    timeline: Arc<TimelineService>,
+    http: reqwest::Client,
+    /// Digest of the callback token; unset while callbacks are disabled
+    token_digest: Option<Vec<u8>>,
+}
+
+impl DeliverabilityService {
+    pub fn new(
+        db: Arc<Database>,
+        timeline: Arc<TimelineService>,
+        config: &EmailEventsConfig,
+        secrets: &SecretsManager,
+    ) -> AppResult<Self> {
+        let token_digest = if config.enabled {
+            let token = secrets
+                .get_secret(&config.webhook_token_secret)
+                .map_err(|e| AppError::Internal(format!("Email event callbacks need a token: {}", e)))?;
+            if token.trim().is_empty() {
+                return Err(AppError::Internal("Email event callback token is empty".into()));
+            }
+            tracing::info!("Email provider bounce and complaint callbacks enabled");
+            Some(Sha256::digest(token.as_bytes()).to_vec())
+        } else {
+            None
+        };
+        // Only the checked SNS URL is fetched, never wherever it redirects to
+        let http = reqwest::Client::builder()
+            .timeout(StdDuration::from_secs(config.request_timeout_secs))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| AppError::Internal(format!("Failed to build SNS client: {}", e)))?;
+
+        Ok(Self {
+            repo: DeliverabilityRepository::new(db),
+            timeline,
+            http,
+            token_digest,
+        })
+    }
+
+    /// Apply one provider callback
+    ///
+    /// Answers 404 while callbacks are disabled or the token is wrong, so the
+    /// endpoint gives nothing away.
+    pub async fn ingest(
+        &self,
+        provider: EmailProvider,
+        token: &str,
+        payload: serde_json::Value,
+    ) -> AppResult<EmailEventsResponse> {
+        // Digests have the same length whatever the token, so comparing them doesn't leak its length
+        let authorized = self
+            .token_digest
+            .as_deref()
+            .is_some_and(|digest| Sha256::digest(token.as_bytes()).as_slice() == digest);
+        if !authorized {
+            return Err(AppError::NotFound("Not found".into()));
+        }
+
+        let now = Utc::now();
+        let reports = match provider {
+            EmailProvider::Sendgrid => parse_sendgrid(&payload, now)?,
+            EmailProvider::Ses => match parse_ses(&payload, now)? {
+                SesNotification::SubscriptionConfirmation { subscribe_url } => {
+                    self.confirm_subscription(&subscribe_url).await?;
+                    return Ok(EmailEventsResponse {
+                        confirmed: true,
+                        ..Default::default()
+                    });
+                }
+                SesNotification::Feedback(reports) => reports,
+            },
+        };
+
+        let mut result = EmailEventsResponse {
+            received: reports.len(),
+            ..Default::default()
+        };
+        for report in &reports {
+            let workspace_id = report.workspace_id.as_deref();
+            let contacts = self.repo.find_contacts(workspace_id, &report.email).await?;
+            if contacts.is_empty() {
+                result.unmatched += 1;
+                continue;
+            }
+            for contact in &contacts {
+                if self.apply(provider, report, contact).await? {
+                    result.recorded += 1;
+                }
+            }
+        }
+
+        if result.recorded > 0 {
+            tracing::info!(
+                "{:?} reported {} bounces and complaints; recorded {}, {} for unknown addresses",
+                provider,
+                result.received,
+                result.recorded,
+                result.unmatched
+            );
+        }
+        Ok(result)
+    }
+
+    /// Put a report on one contact's timeline and suppress them if it calls for it;
+    /// false when it was already recorded
+    async fn apply(
+        &self,
+        provider: EmailProvider,
+        report: &DeliveryFeedback,
+        contact: &ReportedContact,
+    ) -> AppResult<bool> {
+        let campaign = match tagged_campaign(report, contact) {
+            Some(campaign) => Some(campaign),
+            None => {
+                let since = report.occurred_at - Duration::days(ATTRIBUTION_WINDOW_DAYS);
+                self.repo.last_campaign(contact, since, report.occurred_at).await?
+            }
+        };
+
+        let entry = TimelineEntry {
+            id: Some(new_thing("timeline_entry")),
+            workspace: contact.workspace.clone(),
+            contact: contact.id.clone(),
+            company: contact.company.clone(),
+            campaign: campaign.clone(),
+            entry_type: if report.kind.is_bounce() {
+                TimelineEntryType::EmailBounce
+            } else {
+                TimelineEntryType::EmailComplaint
+            },
+            content: describe(report),
+            metadata: serde_json::json!({
+                "kind": report.kind,
+                "provider": provider,
+                "email": report.email,
+                "reason": report.reason,
+                "campaign_id": campaign.as_ref().map(|c| c.id.to_raw()),
+            }),
+            attachments: Vec::new(),
+            sentiment: None,
+            timestamp: report.occurred_at,
+        };
+        if self.repo.is_recorded(&entry).await? {
+            return Ok(false);
+        }
+
+        let hard_bounce = report.kind == FeedbackKind::HardBounce;
+        self.repo.record(entry.clone(), report.kind.suppression(), hard_bounce).await?;
+        self.timeline.recorded(&contact.workspace.id.to_raw(), &[entry]).await;
+
+        Ok(true)
+    }
+
+    async fn confirm_subscription(&self, subscribe_url: &str) -> AppResult<()> {
+        let response = self
+            .http
+            .get(subscribe_url)
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to confirm SNS subscription: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::Internal(format!(
+                "SNS refused the subscription confirmation: {}",
+                response.status()
+            )));
+        }
+
+        tracing::info!("Confirmed SNS subscription for SES notifications");
+        Ok(())
+    }
+}
+
+/// The campaign the email carried, when it was sent from the contact's workspace
+fn tagged_campaign(report: &DeliveryFeedback, contact: &ReportedContact) -> Option<Thing> {
+    let workspace_id = report.workspace_id.as_deref()?;
+    let campaign_id = report.campaign_id.as_deref()?;
+    (contact.workspace.id.to_raw() == workspace_id).then(|| Thing::from(("campaign", campaign_id)))
+}
+
+/// Timeline text for a report
+fn describe(report: &DeliveryFeedback) -> String {
+    let what = match report.kind {
+        FeedbackKind::HardBounce => format!("Email to {} bounced", report.email),
+        FeedbackKind::SoftBounce => format!("Email to {} bounced temporarily", report.email),
+        FeedbackKind::Complaint => format!("{} marked an email as spam", report.email),
+    };
+    match &report.reason {
+        Some(reason) => format!("{}: {}", what, reason),
+        None => what,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(kind: FeedbackKind, reason: Option<&str>) -> DeliveryFeedback {
+        DeliveryFeedback {
+            kind,
+            email: "jane@example.com".into(),
+            reason: reason.map(String::from),
+            occurred_at: Utc::now(),
+            workspace_id: None,
+            campaign_id: None,
+        }
+    }
+
+    #[test]
+    fn test_describe_reports() {
+        assert_eq!(
+            describe(&report(FeedbackKind::HardBounce, Some("550 5.1.1 user unknown"))),
+            "Email to jane@example.com bounced: 550 5.1.1 user unknown"
+        );
+        assert_eq!(
+            describe(&report(FeedbackKind::SoftBounce, None)),
+            "Email to jane@example.com bounced temporarily"
+        );
+        assert_eq!(
+            describe(&report(FeedbackKind::Complaint, Some("abuse"))),
+            "jane@example.com marked an email as spam: abuse"
+        );
+    }
+}
@@ -0,0 +1,294 @@
+//! Digest Service - the weekly summary email for workspace members
+//!
+//! A background task looks for members whose digest is due, compiles new
+//! leads, engagement movers, stale leads and upcoming events for their
+//! workspace, and sends the rendered email. Engagement movers are the
+//! contacts with the most inbound interactions in the period, since scores
+//! themselves keep no history to compare against.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use surrealdb::sql::Thing;
+use tokio::task::JoinHandle;
+
+use crate::config::DigestConfig;
+use crate::db::Database;
+use crate::domain::{
+    render_digest, DigestContent, DigestEvent, DigestLead, DigestMover, DigestPreferences, DigestStaleLead,
+};
+use crate::error::{AppError, AppResult};
+use crate::models::{DigestPreferencesResponse, DigestPreview, DigestSettings};
+use crate::repositories::{DigestContactRow, DigestRecipient, DigestRepository, UserRepository, WorkspaceRepository};
+use crate::services::{interaction_type, AuthenticatedUser};
+use crate::shutdown::Shutdown;
+
+pub struct DigestService {
+    digests: DigestRepository,
+    users: UserRepository,
+    workspaces: WorkspaceRepository,
+    config: DigestConfig,
+}
+
+impl DigestService {
+    pub fn new(db: Arc<Database>, config: &DigestConfig) -> Self {
+        Self {
+            digests: DigestRepository::new(Arc::clone(&db)),
+            users: UserRepository::new(Arc::clone(&db)),
+            workspaces: WorkspaceRepository::new(db),
+            config: config.clone(),
+        }
+    }
+
+    pub async fn preferences(&self, user: &AuthenticatedUser) -> AppResult<DigestPreferencesResponse> {
+        let settings = self.digests.find_settings(&user.user_id).await?;
+        Ok(preferences_response(settings, Utc::now()))
+    }
+
+    pub async fn update_preferences(
+        &self,
+        user: &AuthenticatedUser,
+        preferences: DigestPreferences,
+    ) -> AppResult<DigestPreferencesResponse> {
+        preferences.validate()?;
+
+        let settings = self
+            .digests
+            .save_preferences(&user.workspace_id, &user.user_id, &preferences)
+            .await?;
+
+        Ok(preferences_response(Some(settings), Utc::now()))
+    }
+
+    /// The digest the user would get if it went out now
+    pub async fn preview(&self, user: &AuthenticatedUser) -> AppResult<DigestPreview> {
+        let now = Utc::now();
+        let account = self
+            .users
+            .find_by_id(&user.user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".into()))?;
+        let preferences = self
+            .digests
+            .find_settings(&user.user_id)
+            .await?
+            .map(|s| s.preferences)
+            .unwrap_or_default();
+
+        let content = self
+            .compile(&user.workspace_id, &account.name, &preferences, now)
+            .await?;
+
+        Ok(render_digest(&content, now).into())
+    }
+
+    /// Send every digest that is due
+    ///
+    /// Returns the number of digests sent.
+    pub async fn run_due(&self) -> AppResult<usize> {
+        let now = Utc::now();
+        let mut sent = 0;
+
+        for (recipient, settings) in self.digests.find_recipients().await? {
+            let (preferences, last_sent_at) = settings
+                .map(|s| (s.preferences, s.last_sent_at))
+                .unwrap_or_default();
+            if !preferences.is_due(last_sent_at, now) {
+                continue;
+            }
+
+            match self.send(&recipient, &preferences, now).await {
+                Ok(()) => sent += 1,
+                Err(e) => tracing::warn!("Failed to send digest to user {}: {}", recipient.id, e),
+            }
+        }
+
+        Ok(sent)
+    }
+
+    /// Run `run_due` on a background task at the configured interval
+    pub fn spawn(self: Arc<Self>, shutdown: Shutdown) -> JoinHandle<()> {
+        let period = Duration::from_secs(self.config.check_interval_secs.max(60));
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+
+            while shutdown.tick(&mut interval).await {
+                match self.run_due().await {
+                    Ok(sent) if sent > 0 => tracing::info!("Sent {} digests", sent),
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Digest run failed: {}", e),
+                }
+            }
+        })
+    }
+
+    async fn send(
+        &self,
+        recipient: &DigestRecipient,
+        preferences: &DigestPreferences,
+        now: DateTime<Utc>,
+    ) -> AppResult<()> {
+        let workspace_id = recipient.workspace.id.to_raw();
+        let content = self.compile(&workspace_id, &recipient.name, preferences, now).await?;
+        let email = render_digest(&content, now);
+
+        // Stub: In production, this would hand the email to the email provider
+        tracing::info!("Sending digest \"{}\" to {}", email.subject, recipient.email);
+
+        self.digests.mark_sent(recipient, now).await
+    }
+
+    /// Gather the sections the member has turned on
+    async fn compile(
+        &self,
+        workspace_id: &str,
+        recipient_name: &str,
+        preferences: &DigestPreferences,
+        now: DateTime<Utc>,
+    ) -> AppResult<DigestContent> {
+        let workspace = self
+            .workspaces
+            .find_by_id(workspace_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Workspace {} not found", workspace_id)))?;
+
+        let period = chrono::Duration::days(self.config.period_days);
+        let since = now - period;
+        let limit = self.config.section_limit;
+
+        let (new_lead_count, new_leads) = if preferences.new_leads {
+            let (count, leads) = self.digests.find_new_leads(workspace_id, since, limit).await?;
+            let leads = leads
+                .into_iter()
+                .map(|c| DigestLead {
+                    name: full_name(&c),
+                    email: c.email,
+                })
+                .collect();
+            (count, Some(leads))
+        } else {
+            (0, None)
+        };
+
+        let engagement_movers = if preferences.engagement_movers {
+            Some(self.engagement_movers(workspace_id, since).await?)
+        } else {
+            None
+        };
+
+        let stale_leads = if preferences.stale_leads {
+            let stale_before = now - chrono::Duration::days(self.config.stale_after_days);
+            let mut leads: Vec<DigestStaleLead> = self
+                .digests
+                .find_lead_activity(workspace_id, stale_before)
+                .await?
+                .into_iter()
+                .map(|c| DigestStaleLead {
+                    name: full_name(&c),
+                    last_activity: c.last_activity.unwrap_or(c.created_at),
+                    email: c.email,
+                })
+                .filter(|l| l.last_activity < stale_before)
+                .collect();
+            // Coldest first
+            leads.sort_by_key(|l| l.last_activity);
+            leads.truncate(limit as usize);
+            Some(leads)
+        } else {
+            None
+        };
+
+        let upcoming_events = if preferences.upcoming_events {
+            let events = self
+                .digests
+                .find_upcoming_events(workspace_id, now, now + period, limit)
+                .await?
+                .into_iter()
+                .map(|e| DigestEvent {
+                    name: e.name,
+                    start_time: e.start_time,
+                    registered: e.registered,
+                })
+                .collect();
+            Some(events)
+        } else {
+            None
+        };
+
+        Ok(DigestContent {
+            workspace_name: workspace.name,
+            recipient_name: recipient_name.to_string(),
+            period_days: self.config.period_days,
+            new_lead_count,
+            new_leads,
+            engagement_movers,
+            stale_leads,
+            upcoming_events,
+        })
+    }
+
+    /// Contacts with the most inbound interactions since `since`
+    async fn engagement_movers(&self, workspace_id: &str, since: DateTime<Utc>) -> AppResult<Vec<DigestMover>> {
+        // Keyed by contact ID
+        let mut interactions: HashMap<String, u64> = HashMap::new();
+        for row in self.digests.find_interaction_counts(workspace_id, since).await? {
+            if interaction_type(&row.entry_type).is_some_and(|t| t.is_inbound()) {
+                *interactions.entry(row.contact.id.to_raw()).or_default() += row.count;
+            }
+        }
+
+        let mut top: Vec<(String, u64)> = interactions.into_iter().collect();
+        top.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top.truncate(self.config.section_limit as usize);
+
+        let ids: Vec<Thing> = top.iter().map(|(id, _)| Thing::from(("contact", id.as_str()))).collect();
+        let mut contacts: HashMap<String, DigestContactRow> = self
+            .digests
+            .find_contacts(workspace_id, &ids)
+            .await?
+            .into_iter()
+            .map(|c| (c.id.id.to_raw(), c))
+            .collect();
+
+        Ok(top
+            .into_iter()
+            .filter_map(|(id, count)| {
+                let contact = contacts.remove(&id)?;
+                Some(DigestMover {
+                    name: full_name(&contact),
+                    email: contact.email,
+                    interactions: count,
+                    engagement_score: contact.engagement_score,
+                })
+            })
+            .collect())
+    }
+}
+
+fn preferences_response(settings: Option<DigestSettings>, now: DateTime<Utc>) -> DigestPreferencesResponse {
+    let (preferences, last_sent_at) = settings
+        .map(|s| (s.preferences, s.last_sent_at))
+        .unwrap_or_default();
+
+    let next_digest_at = if !preferences.enabled {
+        None
+    } else if preferences.is_due(last_sent_at, now) {
+        // Goes out on the next run
+        Some(preferences.last_slot(now))
+    } else {
+        Some(preferences.next_slot(now))
+    };
+
+    DigestPreferencesResponse {
+        preferences,
+        last_sent_at,
+        next_digest_at,
+    }
+}
+
+fn full_name(contact: &DigestContactRow) -> String {
+    format!("{} {}", contact.first_name, contact.last_name).trim().to_string()
+}
@@ -0,0 +1,144 @@
+//! DNS client - TXT lookups over HTTPS
+//!
+//! Sender verification reads SPF and DKIM records through a DNS-over-HTTPS
+//! resolver's JSON API (Cloudflare and Google both speak it), so lookups
+//! go out over the same HTTP client stack as everything else.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::config::SendersConfig;
+use crate::error::{AppError, AppResult};
+
+/// RR type of TXT records
+const TXT_TYPE: u16 = 16;
+
+/// Response codes meaning the name has no records
+const NO_ERROR: u32 = 0;
+const NXDOMAIN: u32 = 3;
+
+pub struct DnsClient {
+    http: reqwest::Client,
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DnsResponse {
+    #[serde(rename = "Status")]
+    status: u32,
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DnsAnswer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DnsAnswer {
+    #[serde(rename = "type")]
+    record_type: u16,
+    data: String,
+}
+
+impl DnsClient {
+    pub fn new(config: &SendersConfig) -> AppResult<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_secs))
+            .build()
+            .map_err(|e| AppError::Internal(format!("Failed to build DNS client: {}", e)))?;
+
+        Ok(Self {
+            http,
+            url: config.dns_url.clone(),
+        })
+    }
+
+    /// TXT records of a name; empty when it has none or doesn't exist
+    pub async fn txt_records(&self, name: &str) -> AppResult<Vec<String>> {
+        let response = self
+            .http
+            .get(&self.url)
+            .query(&[("name", name), ("type", "TXT")])
+            .header("accept", "application/dns-json")
+            .send()
+            .await
+            .map_err(|e| AppError::Upstream(format!("DNS lookup of {} failed: {}", name, e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::Upstream(format!(
+                "DNS lookup of {} failed: resolver answered {}",
+                name,
+                response.status()
+            )));
+        }
+
+        let body: DnsResponse = response
+            .json()
+            .await
+            .map_err(|e| AppError::Upstream(format!("Unreadable DNS answer for {}: {}", name, e)))?;
+        txt_answers(name, body)
+    }
+}
+
+fn txt_answers(name: &str, response: DnsResponse) -> AppResult<Vec<String>> {
+    match response.status {
+        NO_ERROR => Ok(response
+            .answer
+            .into_iter()
+            .filter(|a| a.record_type == TXT_TYPE)
+            .map(|a| txt_data(&a.data))
+            .collect()),
+        NXDOMAIN => Ok(Vec::new()),
+        code => Err(AppError::Upstream(format!("DNS lookup of {} failed with code {}", name, code))),
+    }
+}
+
+/// A TXT record's text: its quoted character strings joined, or the data
+/// as is when the resolver didn't quote it
+fn txt_data(data: &str) -> String {
+    let data = data.trim();
+    if !data.starts_with('"') {
+        return data.to_string();
+    }
+
+    let mut text = String::with_capacity(data.len());
+    let mut quoted = false;
+    let mut chars = data.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted = !quoted,
+            '\\' if quoted => text.extend(chars.next()),
+            c if quoted => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_txt_data_joins_quoted_strings() {
+        assert_eq!(txt_data(r#""v=spf1 include:sendgrid.net -all""#), "v=spf1 include:sendgrid.net -all");
+        assert_eq!(txt_data(r#""v=DKIM1; k=rsa; " "p=MIGf\"MA0""#), "v=DKIM1; k=rsa; p=MIGf\"MA0");
+        assert_eq!(txt_data("v=spf1 -all"), "v=spf1 -all");
+    }
+
+    #[test]
+    fn test_txt_answers_skip_other_types_and_missing_names() {
+        let response: DnsResponse = serde_json::from_value(serde_json::json!({
+            "Status": 0,
+            "Answer": [
+                { "name": "s1._domainkey.example.com", "type": 5, "data": "s1.domainkey.u1.wl.sendgrid.net." },
+                { "name": "s1.domainkey.u1.wl.sendgrid.net", "type": 16, "data": "\"p=abc\"" }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(txt_answers("s1._domainkey.example.com", response).unwrap(), vec!["p=abc"]);
+
+        let missing: DnsResponse = serde_json::from_value(serde_json::json!({ "Status": 3 })).unwrap();
+        assert!(txt_answers("nope.example.com", missing).unwrap().is_empty());
+
+        let failed: DnsResponse = serde_json::from_value(serde_json::json!({ "Status": 2 })).unwrap();
+        assert!(txt_answers("example.com", failed).is_err());
+    }
+}
@@ -0,0 +1,351 @@
+//! Engagement Service - keeps stored engagement scores in line with activity
+//!
+//! Scores decay with time even when nothing happens, so besides the
+//! on-demand recompute a background job periodically rescores every
+//! contact from its timeline using `domain::calculate_engagement_score`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use surrealdb::sql::Thing;
+use tokio::task::JoinHandle;
+
+use crate::config::EngagementJobConfig;
+use crate::db::Database;
+use crate::domain::{
+    calculate_engagement_score, calculate_engagement_trend, calculate_engagement_velocity, engagement_breakdown,
+    identify_top_interaction_types, CallMetadata, EngagementConfig, EngagementLevel, Interaction, InteractionType,
+    ScoreContribution, IMPORT_SOURCE_KEY,
+};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    ContactEngagementResponse, EngagementBreakdownResponse, EngagementRecalculationResponse,
+    InteractionContribution, TimelineEntryType,
+};
+use crate::repositories::{EngagementRepository, TimelineActivity};
+use crate::shutdown::Shutdown;
+
+/// Changes smaller than this are not written back
+const SCORE_EPSILON: f64 = 0.01;
+
+/// Interaction types listed in the engagement overview
+const TOP_INTERACTION_TYPES: usize = 3;
+
+pub struct EngagementService {
+    repo: EngagementRepository,
+    config: EngagementConfig,
+}
+
+impl EngagementService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            repo: EngagementRepository::new(db),
+            config: EngagementConfig::default(),
+        }
+    }
+
+    /// Level, trend, velocity and main drivers of a contact's engagement
+    pub async fn overview(&self, workspace_id: &str, contact_id: &str) -> AppResult<ContactEngagementResponse> {
+        let current = self
+            .repo
+            .find_score(workspace_id, contact_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Contact not found".into()))?;
+
+        let activity = self.repo.find_activity(std::slice::from_ref(&current.id)).await?;
+        let interactions = to_interactions(&activity);
+        let level = EngagementLevel::from_score(current.engagement_score);
+
+        Ok(ContactEngagementResponse {
+            contact_id: contact_id.to_string(),
+            engagement_score: current.engagement_score,
+            level,
+            recommended_action: level.recommended_action(),
+            trend: calculate_engagement_trend(&interactions, &self.config),
+            velocity: round2(calculate_engagement_velocity(&interactions, &self.config)),
+            top_interaction_types: identify_top_interaction_types(&interactions, &self.config, TOP_INTERACTION_TYPES)
+                .into_iter()
+                .map(|(interaction_type, score)| InteractionContribution {
+                    interaction_type,
+                    score: round2(score),
+                })
+                .collect(),
+        })
+    }
+
+    /// What each interaction type contributes to a contact's score
+    pub async fn breakdown(&self, workspace_id: &str, contact_id: &str) -> AppResult<EngagementBreakdownResponse> {
+        let current = self
+            .repo
+            .find_score(workspace_id, contact_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Contact not found".into()))?;
+
+        let activity = self.repo.find_activity(std::slice::from_ref(&current.id)).await?;
+        let interactions = to_interactions(&activity);
+
+        Ok(EngagementBreakdownResponse {
+            contact_id: contact_id.to_string(),
+            engagement_score: self.score(&interactions),
+            contributions: engagement_breakdown(&interactions, &self.config)
+                .into_iter()
+                .map(|c| ScoreContribution {
+                    points: round2(c.points),
+                    percentage: round2(c.percentage),
+                    ..c
+                })
+                .collect(),
+        })
+    }
+
+    /// Recompute and store one contact's score
+    pub async fn recalculate(
+        &self,
+        workspace_id: &str,
+        contact_id: &str,
+    ) -> AppResult<EngagementRecalculationResponse> {
+        let current = self
+            .repo
+            .find_score(workspace_id, contact_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Contact not found".into()))?;
+
+        let activity = self.repo.find_activity(std::slice::from_ref(&current.id)).await?;
+        let interactions = to_interactions(&activity);
+        let score = self.score(&interactions);
+
+        if (score - current.engagement_score).abs() >= SCORE_EPSILON {
+            self.repo.update_score(&current.id, score).await?;
+        }
+
+        Ok(EngagementRecalculationResponse {
+            contact_id: contact_id.to_string(),
+            previous_score: current.engagement_score,
+            engagement_score: score,
+            level: EngagementLevel::from_score(score),
+            interactions: interactions.len(),
+        })
+    }
+
+    /// Rescore every contact in every workspace
+    ///
+    /// Returns the number of contacts whose score changed.
+    pub async fn recalculate_all(&self, batch_size: u32) -> AppResult<usize> {
+        let mut after: Option<Thing> = None;
+        let mut changed = 0;
+
+        loop {
+            let page = self.repo.find_scores_after(after.as_ref(), batch_size).await?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            after = Some(last.id.clone());
+
+            let ids: Vec<Thing> = page.iter().map(|c| c.id.clone()).collect();
+            let mut by_contact: HashMap<Thing, Vec<Interaction>> = HashMap::new();
+            for a in self.repo.find_activity(&ids).await? {
+                if let Some(interaction) = to_interaction(&a) {
+                    by_contact.entry(a.contact).or_default().push(interaction);
+                }
+            }
+
+            for contact in &page {
+                let interactions = by_contact.remove(&contact.id).unwrap_or_default();
+                let score = self.score(&interactions);
+
+                if (score - contact.engagement_score).abs() >= SCORE_EPSILON {
+                    self.repo.update_score(&contact.id, score).await?;
+                    changed += 1;
+                }
+            }
+
+            if page.len() < batch_size as usize {
+                break;
+            }
+        }
+
+        Ok(changed)
+    }
+
+    /// Run `recalculate_all` on a background task at the configured interval
+    pub fn spawn_recalculation(self: Arc<Self>, config: &EngagementJobConfig, shutdown: Shutdown) -> JoinHandle<()> {
+        let period = Duration::from_secs(config.recalculate_interval_secs.max(60));
+        let batch_size = config.batch_size.max(1);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+
+            while shutdown.tick(&mut interval).await {
+                match self.recalculate_all(batch_size).await {
+                    Ok(changed) => tracing::info!("Engagement recalculation updated {} contacts", changed),
+                    Err(e) => tracing::error!("Engagement recalculation failed: {}", e),
+                }
+            }
+        })
+    }
+
+    fn score(&self, interactions: &[Interaction]) -> f64 {
+        round2(calculate_engagement_score(interactions, &self.config))
+    }
+}
+
+/// The engagement interaction a timeline entry counts as, if any
+pub fn interaction_type(entry_type: &TimelineEntryType) -> Option<InteractionType> {
+    match entry_type {
+        TimelineEntryType::EmailSent => Some(InteractionType::EmailSent),
+        TimelineEntryType::EmailOpen => Some(InteractionType::EmailOpen),
+        TimelineEntryType::EmailClick => Some(InteractionType::EmailClick),
+        TimelineEntryType::EmailReceived => Some(InteractionType::EmailReceived),
+        TimelineEntryType::SocialTouch => Some(InteractionType::SocialInteraction),
+        TimelineEntryType::Note => Some(InteractionType::NoteAdded),
+        TimelineEntryType::EventRegistration => Some(InteractionType::EventRegistration),
+        TimelineEntryType::EventAttend => Some(InteractionType::EventAttendance),
+        TimelineEntryType::LandingPageVisit => Some(InteractionType::LandingPageVisit),
+        TimelineEntryType::FormSubmission => Some(InteractionType::FormSubmission),
+        TimelineEntryType::MeetingScheduled => Some(InteractionType::MeetingScheduled),
+        TimelineEntryType::MeetingAttended => Some(InteractionType::MeetingAttended),
+        TimelineEntryType::Call => Some(InteractionType::CallCompleted),
+        // Outbound invites, internal tasks and briefs, enrichment, churn and delivery records say nothing
+        // about the contact's interest, and what an external tool's event means varies from source to source
+        TimelineEntryType::EventInvite
+        | TimelineEntryType::Task
+        | TimelineEntryType::PrepBrief
+        | TimelineEntryType::Unsubscribed
+        | TimelineEntryType::Enrichment
+        | TimelineEntryType::Churned
+        | TimelineEntryType::EmailBounce
+        | TimelineEntryType::EmailComplaint
+        | TimelineEntryType::ExternalEvent => None,
+    }
+}
+
+/// The interaction a timeline entry counts as, weighted by what its metadata says
+pub fn to_interaction(activity: &TimelineActivity) -> Option<Interaction> {
+    // History brought over from another CRM was engagement there, not here
+    if activity.metadata.get(IMPORT_SOURCE_KEY).is_some_and(|source| !source.is_null()) {
+        return None;
+    }
+
+    let interaction = Interaction::new(interaction_type(&activity.entry_type)?, activity.timestamp);
+
+    match activity.entry_type {
+        // Calls logged before their metadata was checked count as plain calls
+        TimelineEntryType::Call => match CallMetadata::from_metadata(&activity.metadata) {
+            Ok(call) => Some(interaction.with_weight(call.engagement_weight())),
+            Err(_) => Some(interaction),
+        },
+        _ => Some(interaction),
+    }
+}
+
+fn to_interactions(activity: &[TimelineActivity]) -> Vec<Interaction> {
+    activity.iter().filter_map(to_interaction).collect()
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn activity(entry_type: TimelineEntryType) -> TimelineActivity {
+        TimelineActivity {
+            contact: Thing::from(("contact", "c1")),
+            entry_type,
+            timestamp: Utc::now(),
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn test_imported_history_is_not_engagement() {
+        let mut note = activity(TimelineEntryType::Note);
+        assert!(to_interaction(&note).is_some());
+
+        note.metadata = serde_json::json!({ IMPORT_SOURCE_KEY: "hubspot" });
+        assert!(to_interaction(&note).is_none());
+    }
+
+    #[test]
+    fn test_interaction_type_mapping() {
+        assert_eq!(interaction_type(&TimelineEntryType::EmailOpen), Some(InteractionType::EmailOpen));
+        assert_eq!(interaction_type(&TimelineEntryType::Call), Some(InteractionType::CallCompleted));
+        assert_eq!(interaction_type(&TimelineEntryType::Task), None);
+    }
+
+    #[test]
+    fn test_every_interaction_type_has_a_timeline_entry() {
+        let counted: Vec<InteractionType> = [
+            TimelineEntryType::EmailSent,
+            TimelineEntryType::EmailOpen,
+            TimelineEntryType::EmailClick,
+            TimelineEntryType::EmailReceived,
+            TimelineEntryType::ExternalEvent,
+            TimelineEntryType::SocialTouch,
+            TimelineEntryType::Note,
+            TimelineEntryType::EventInvite,
+            TimelineEntryType::EventRegistration,
+            TimelineEntryType::EventAttend,
+            TimelineEntryType::LandingPageVisit,
+            TimelineEntryType::FormSubmission,
+            TimelineEntryType::MeetingScheduled,
+            TimelineEntryType::MeetingAttended,
+            TimelineEntryType::Task,
+            TimelineEntryType::Call,
+            TimelineEntryType::Enrichment,
+            TimelineEntryType::Churned,
+            TimelineEntryType::EmailBounce,
+            TimelineEntryType::EmailComplaint,
+        ]
+        .iter()
+        .filter_map(interaction_type)
+        .collect();
+
+        // Otherwise that interaction could never be recorded, and would never score
+        for t in [
+            InteractionType::EmailSent,
+            InteractionType::EmailOpen,
+            InteractionType::EmailClick,
+            InteractionType::EmailReceived,
+            InteractionType::LandingPageVisit,
+            InteractionType::FormSubmission,
+            InteractionType::EventRegistration,
+            InteractionType::EventAttendance,
+            InteractionType::MeetingScheduled,
+            InteractionType::MeetingAttended,
+            InteractionType::CallCompleted,
+            InteractionType::NoteAdded,
+            InteractionType::SocialInteraction,
+        ] {
+            assert!(counted.contains(&t), "{:?} has no timeline entry type", t);
+        }
+    }
+
+    #[test]
+    fn test_uncounted_entries_are_dropped() {
+        let interactions = to_interactions(&[
+            activity(TimelineEntryType::EmailClick),
+            activity(TimelineEntryType::Task),
+            activity(TimelineEntryType::EventInvite),
+        ]);
+
+        assert_eq!(interactions.len(), 1);
+        assert_eq!(interactions[0].interaction_type, InteractionType::EmailClick);
+    }
+
+    #[test]
+    fn test_calls_are_weighted_by_outcome() {
+        let mut missed = activity(TimelineEntryType::Call);
+        missed.metadata = serde_json::json!({ "outcome": "no_answer" });
+        let mut unreadable = activity(TimelineEntryType::Call);
+        unreadable.metadata = serde_json::json!({ "outcome": "hung_up" });
+
+        assert!(to_interaction(&missed).unwrap().weight < 1.0);
+        assert_eq!(to_interaction(&unreadable).unwrap().weight, 1.0);
+        assert_eq!(to_interaction(&activity(TimelineEntryType::Call)).unwrap().weight, 1.0);
+    }
+}
@@ -0,0 +1,168 @@
+//! Enrichment providers - looking up a contact's LinkedIn profile
+//!
+//! `EnrichmentProvider` turns a LinkedIn profile URL into the headline,
+//! current employer and location it shows. `ProxycurlProvider` calls the
+//! Proxycurl person profile API with a key read from the secrets manager.
+//! With `enrichment.provider: none` there is no provider and enrichment is
+//! refused.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use serde::Deserialize;
+
+use crate::config::EnrichmentConfig;
+use crate::domain::EnrichedProfile;
+use crate::error::{AppError, AppResult};
+use crate::secrets::SecretsManager;
+
+const PROXYCURL_PROFILE_URL: &str = "https://nubela.co/proxycurl/api/v2/linkedin";
+
+pub trait EnrichmentProvider: Send + Sync {
+    /// Short name, e.g. `proxycurl`
+    fn name(&self) -> &'static str;
+
+    /// The profile behind a LinkedIn URL; `None` when there is no such profile
+    fn lookup<'a>(&'a self, linkedin_url: &'a str) -> BoxFuture<'a, AppResult<Option<EnrichedProfile>>>;
+}
+
+/// Build the provider selected in config, if any
+///
+/// The API key comes from the secrets manager under `enrichment.api_key_secret`.
+pub fn build_enrichment_provider(
+    config: &EnrichmentConfig,
+    secrets: &SecretsManager,
+) -> AppResult<Option<Arc<dyn EnrichmentProvider>>> {
+    let provider: Arc<dyn EnrichmentProvider> = match config.provider.as_str() {
+        "none" | "" => return Ok(None),
+        "proxycurl" => Arc::new(ProxycurlProvider {
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(config.request_timeout_secs))
+                .build()
+                .map_err(|e| AppError::Internal(format!("Failed to build enrichment client: {}", e)))?,
+            api_key: secrets.get_secret(&config.api_key_secret).map_err(|e| {
+                AppError::Internal(format!("Enrichment provider 'proxycurl' needs an API key: {}", e))
+            })?,
+        }),
+        other => {
+            return Err(AppError::Internal(format!(
+                "Unknown enrichment provider '{}', expected none or proxycurl",
+                other
+            )));
+        }
+    };
+
+    tracing::info!("Contact enrichment provider: {}", provider.name());
+    Ok(Some(provider))
+}
+
+/// Proxycurl person profile API
+struct ProxycurlProvider {
+    http: reqwest::Client,
+    api_key: String,
+}
+
+#[derive(Deserialize)]
+struct ProxycurlProfile {
+    headline: Option<String>,
+    city: Option<String>,
+    state: Option<String>,
+    country_full_name: Option<String>,
+    #[serde(default)]
+    experiences: Vec<ProxycurlExperience>,
+}
+
+#[derive(Deserialize)]
+struct ProxycurlExperience {
+    company: Option<String>,
+    /// `None` for a position the person still holds
+    ends_at: Option<serde_json::Value>,
+}
+
+impl ProxycurlProvider {
+    async fn fetch(&self, linkedin_url: &str) -> AppResult<Option<EnrichedProfile>> {
+        let response = self
+            .http
+            .get(PROXYCURL_PROFILE_URL)
+            .bearer_auth(&self.api_key)
+            .query(&[("linkedin_profile_url", linkedin_url)])
+            .send()
+            .await
+            .map_err(|e| AppError::Upstream(format!("Enrichment provider unreachable: {}", e)))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(AppError::Upstream(format!("Enrichment provider returned {}: {}", status, text)));
+        }
+
+        let profile: ProxycurlProfile = response
+            .json()
+            .await
+            .map_err(|e| AppError::Upstream(format!("Unreadable enrichment response: {}", e)))?;
+        Ok(Some(to_profile(profile)))
+    }
+}
+
+impl EnrichmentProvider for ProxycurlProvider {
+    fn name(&self) -> &'static str {
+        "proxycurl"
+    }
+
+    fn lookup<'a>(&'a self, linkedin_url: &'a str) -> BoxFuture<'a, AppResult<Option<EnrichedProfile>>> {
+        Box::pin(self.fetch(linkedin_url))
+    }
+}
+
+fn to_profile(profile: ProxycurlProfile) -> EnrichedProfile {
+    let location = [profile.city, profile.state, profile.country_full_name]
+        .into_iter()
+        .flatten()
+        .filter(|part| !part.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    EnrichedProfile {
+        headline: profile.headline,
+        company: profile
+            .experiences
+            .into_iter()
+            .find(|e| e.ends_at.is_none())
+            .and_then(|e| e.company),
+        location: Some(location),
+    }
+    .normalized()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_profile() {
+        let profile: ProxycurlProfile = serde_json::from_value(serde_json::json!({
+            "headline": "VP Sales at Globex",
+            "city": "Stockholm",
+            "state": null,
+            "country_full_name": "Sweden",
+            "experiences": [
+                { "company": "Initech", "ends_at": { "day": 1, "month": 1, "year": 2020 } },
+                { "company": "Globex", "ends_at": null }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            to_profile(profile),
+            EnrichedProfile {
+                headline: Some("VP Sales at Globex".into()),
+                company: Some("Globex".into()),
+                location: Some("Stockholm, Sweden".into()),
+            }
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Enrichment Service - filling in contacts from their LinkedIn profile
+//!
+//! The configured `EnrichmentProvider` looks up the contact's LinkedIn URL.
+//! The headline and location go into the `headline` and `location` custom
+//! fields; the current employer is linked as the contact's company,
+//! created if the workspace doesn't have it yet. Only fields that are
+//! empty, or still hold what the last enrichment found, are written (see
+//! `domain::fields_to_fill`).
+//!
+//! Every enrichment is logged on the contact's timeline together with the
+//! profile it found, which is what the next enrichment compares against.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use surrealdb::sql::Thing;
+
+use crate::db::Database;
+use crate::domain::{
+    diff_fields, fields_to_fill, AuditAction, AuditEntity, ContactUpdater, EnrichedProfile, HEADLINE_FIELD,
+    LOCATION_FIELD,
+};
+use crate::error::{AppError, AppResult};
+use crate::models::{ContactResponse, EnrichContactResponse, TimelineEntryType};
+use crate::repositories::{ContactRepository, EnrichmentRepository, StoredContact};
+use crate::services::enrichment_provider::EnrichmentProvider;
+use crate::services::{new_entry, AuditService, AuthenticatedUser, TimelineService};
+
+pub struct EnrichmentService {
+    contacts: ContactRepository,
+    enrichment: EnrichmentRepository,
+    timeline: Arc<TimelineService>,
+    audit: AuditService,
+    provider: Option<Arc<dyn EnrichmentProvider>>,
+}
+
+impl EnrichmentService {
+    pub fn new(
+        db: Arc<Database>,
+        timeline: Arc<TimelineService>,
+        provider: Option<Arc<dyn EnrichmentProvider>>,
+    ) -> Self {
+        Self {
+            contacts: ContactRepository::new(Arc::clone(&db)),
+            enrichment: EnrichmentRepository::new(Arc::clone(&db)),
+            audit: AuditService::new(db),
+            timeline,
+            provider,
+        }
+    }
+
+    /// Enrich a contact from the LinkedIn profile at its `linkedin_url`
+    pub async fn enrich(&self, actor: &AuthenticatedUser, id: &str) -> AppResult<EnrichContactResponse> {
+        let workspace_id = actor.workspace_id.as_str();
+        let provider = self
+            .provider
+            .as_ref()
+            .ok_or_else(|| AppError::BadRequest("Contact enrichment is not configured".into()))?;
+
+        let stored = self
+            .contacts
+            .find_by_id_with_id(workspace_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Contact '{}' not found", id)))?;
+        let linkedin_url = stored
+            .contact
+            .linkedin_url
+            .clone()
+            .ok_or_else(|| AppError::BadRequest("Contact has no LinkedIn URL to enrich from".into()))?;
+
+        let found = provider
+            .lookup(&linkedin_url)
+            .await?
+            .map(EnrichedProfile::normalized)
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| AppError::NotFound(format!("No LinkedIn profile found at {}", linkedin_url)))?;
+
+        let contact_thing = Thing::from(("contact", id));
+        let previous = self.enrichment.last_profile(&contact_thing).await?.unwrap_or_default();
+        let company_name = match stored.contact.company_id.as_deref() {
+            Some(company_id) => self.enrichment.company_name(workspace_id, company_id).await?,
+            None => None,
+        };
+        let current = EnrichedProfile {
+            headline: stored.contact.custom_fields.get(HEADLINE_FIELD).cloned(),
+            company: company_name,
+            location: stored.contact.custom_fields.get(LOCATION_FIELD).cloned(),
+        };
+        let fill = fields_to_fill(&current, &previous, &found);
+
+        let before = stored.contact.clone();
+        let mut updater = ContactUpdater::new(stored.contact.clone());
+        let mut custom_fields = BTreeMap::new();
+        if let Some(headline) = &fill.headline {
+            custom_fields.insert(HEADLINE_FIELD.to_string(), Some(headline.clone()));
+        }
+        if let Some(location) = &fill.location {
+            custom_fields.insert(LOCATION_FIELD.to_string(), Some(location.clone()));
+        }
+        if !custom_fields.is_empty() {
+            updater = updater.custom_fields(&custom_fields)?;
+        }
+        let mut company_created = false;
+        if let Some(company) = &fill.company {
+            let (company_id, created) = self.enrichment.find_or_create_company(workspace_id, company).await?;
+            company_created = created;
+            updater = updater.company_id(Some(&company_id));
+        }
+
+        let modified_fields = updater.modified_fields().to_vec();
+        let stored = if modified_fields.is_empty() {
+            stored
+        } else {
+            let contact = self.contacts.update(workspace_id, id, &updater.apply()?).await?;
+            StoredContact {
+                id: id.to_string(),
+                contact,
+            }
+        };
+
+        let filled = field_names(&fill);
+        // Fields already matching the profile were neither filled nor kept
+        let kept: Vec<String> = field_names(&found)
+            .into_iter()
+            .filter(|f| !filled.contains(f) && !matches_current(f, &current, &found))
+            .collect();
+
+        let content = if filled.is_empty() {
+            "Enriched from LinkedIn; nothing new".to_string()
+        } else {
+            format!("Enriched from LinkedIn: {}", filled.join(", "))
+        };
+        let metadata = serde_json::json!({
+            "provider": provider.name(),
+            "linkedin_url": linkedin_url,
+            "profile": found,
+            "filled": filled,
+            "kept": kept,
+            "company_created": company_created,
+        });
+        self.timeline
+            .record(workspace_id, new_entry(workspace_id, id, TimelineEntryType::Enrichment, content, metadata))
+            .await?;
+
+        let changes = diff_fields(
+            &serde_json::to_value(&before).unwrap_or_default(),
+            &serde_json::to_value(&stored.contact).unwrap_or_default(),
+            &modified_fields,
+        );
+        self.audit
+            .record(actor, AuditEntity::Contact, id, AuditAction::Update, changes)
+            .await;
+
+        Ok(EnrichContactResponse {
+            contact: ContactResponse::from_stored(stored),
+            provider: provider.name().to_string(),
+            filled,
+            kept,
+        })
+    }
+}
+
+/// Names of the fields a profile has a value for
+fn field_names(profile: &EnrichedProfile) -> Vec<String> {
+    [
+        ("headline", &profile.headline),
+        ("company", &profile.company),
+        ("location", &profile.location),
+    ]
+    .into_iter()
+    .filter(|(_, value)| value.is_some())
+    .map(|(name, _)| name.to_string())
+    .collect()
+}
+
+/// Whether the contact already has exactly what the profile says for a field
+fn matches_current(field: &str, current: &EnrichedProfile, found: &EnrichedProfile) -> bool {
+    match field {
+        "headline" => current.headline == found.headline,
+        "company" => current.company == found.company,
+        "location" => current.location == found.location,
+        _ => false,
+    }
+}
@@ -0,0 +1,702 @@
+//! Event Service - invitations, RSVPs, capacity and the waitlist
+//!
+//! Inviting contacts gives each one an `invited` RSVP; contacts who
+//! already have an RSVP keep it.
+//!
+//! Whether an RSVP request registers, waitlists or is refused is decided by
+//! the event's `RegistrationRules`. When a registered contact gives up
+//! their seat before the event, the longest-waiting contacts on the
+//! waitlist are moved up in the same transaction. Seats are counted again
+//! inside that transaction, so concurrent RSVPs can't overbook the event.
+//!
+//! Contacts are checked in by ID or with the signed token in their QR code.
+//! A check-in moves the RSVP to attended and rescores the contact's
+//! engagement right away.
+//!
+//! A background runner emails reminders to registered contacts ahead of
+//! each event and a follow-up to attendees and no-shows afterwards, at the
+//! offsets the event was created with (see `domain::EventReminders`).
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use tokio::task::JoinHandle;
+
+use crate::config::EventConfig;
+use crate::db::{new_thing, workspace_thing, Database};
+use crate::domain::{EventEmail, EventReminders, FOLLOW_UP_WINDOW_DAYS, MAX_REMINDER_OFFSET_MINUTES};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    BulkCheckinResponse, CheckinRequest, CheckinResult, CheckinStatus, CheckinTokenResponse, Event, FeedEvent, Rsvp,
+    RsvpResponse, RsvpStatus, TimelineEntry, TimelineEntryType,
+};
+use crate::repositories::{ContactRepository, EventRepository, ReminderCandidate};
+use crate::services::{FeedService, TimelineService};
+use crate::shutdown::Shutdown;
+
+/// Most check-ins one bulk request may list
+pub const MAX_BULK_CHECKINS: usize = 1000;
+
+/// Most contacts one invite request may list
+pub const MAX_INVITES: usize = 1000;
+
+/// Days after an event ends its check-in codes still work, for syncing attendance
+const CHECKIN_TOKEN_GRACE_DAYS: i64 = 1;
+
+/// Times an RSVP is decided again after other RSVPs took the seats it counted
+const RSVP_ATTEMPTS: usize = 3;
+
+/// What one reminder run sent
+#[derive(Debug, Clone, Default)]
+pub struct ReminderRunSummary {
+    pub reminders: usize,
+    pub follow_ups: usize,
+}
+
+/// Claims carried by a check-in token
+#[derive(Debug, Serialize, Deserialize)]
+struct CheckinClaims {
+    workspace: String,
+    event: String,
+    contact: String,
+    exp: i64,
+}
+
+/// Signs and verifies the tokens in check-in QR codes
+struct CheckinTokens {
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    validation: Validation,
+}
+
+impl CheckinTokens {
+    fn new(secret: &str) -> Self {
+        Self {
+            encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+            decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+            validation: Validation::new(Algorithm::HS256),
+        }
+    }
+
+    fn sign(&self, claims: &CheckinClaims) -> AppResult<String> {
+        encode(&Header::new(Algorithm::HS256), claims, &self.encoding_key)
+            .map_err(|e| AppError::Internal(format!("Failed to sign check-in token: {}", e)))
+    }
+
+    fn verify(&self, token: &str) -> AppResult<CheckinClaims> {
+        decode::<CheckinClaims>(token, &self.decoding_key, &self.validation)
+            .map(|data| data.claims)
+            .map_err(|_| AppError::BadRequest("Invalid or expired check-in code".into()))
+    }
+}
+
+pub struct EventService {
+    events: EventRepository,
+    contacts: ContactRepository,
+    feed: Arc<FeedService>,
+    timeline: Arc<TimelineService>,
+    tokens: CheckinTokens,
+}
+
+impl EventService {
+    pub fn new(
+        db: Arc<Database>,
+        feed: Arc<FeedService>,
+        timeline: Arc<TimelineService>,
+        config: &EventConfig,
+    ) -> Self {
+        Self {
+            events: EventRepository::new(Arc::clone(&db)),
+            contacts: ContactRepository::new(db),
+            feed,
+            timeline,
+            tokens: CheckinTokens::new(&config.checkin_secret),
+        }
+    }
+
+    async fn event(&self, workspace_id: &str, event_id: &str) -> AppResult<Event> {
+        self.events
+            .find_by_id(workspace_id, event_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Event not found".into()))
+    }
+
+    /// Invite contacts to an event
+    ///
+    /// Contacts that already have an RSVP keep it and are left out of the
+    /// result, as are repeats. If any contact doesn't exist, nobody is
+    /// invited.
+    pub async fn invite(&self, workspace_id: &str, event_id: &str, contact_ids: Vec<String>) -> AppResult<Vec<Rsvp>> {
+        if contact_ids.is_empty() {
+            return Err(AppError::Validation("contact_ids must list at least one contact".into()));
+        }
+        if contact_ids.len() > MAX_INVITES {
+            return Err(AppError::Validation(format!(
+                "At most {} contacts can be invited at once",
+                MAX_INVITES
+            )));
+        }
+
+        let event = self.event(workspace_id, event_id).await?;
+        let ids = unique_ids(contact_ids);
+        let found: HashSet<String> = self
+            .contacts
+            .find_many_with_ids(workspace_id, &ids)
+            .await?
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        if let Some(missing) = ids.iter().find(|id| !found.contains(*id)) {
+            return Err(AppError::NotFound(format!("Contact '{}' not found", missing)));
+        }
+
+        let event_thing = Thing::from(("event", event_id));
+        let contacts: Vec<Thing> = ids.iter().map(|id| Thing::from(("contact", id.as_str()))).collect();
+        let invited: HashSet<Thing> = self
+            .events
+            .rsvp_contacts(&event_thing, &contacts)
+            .await?
+            .into_iter()
+            .collect();
+
+        let now = Utc::now();
+        let rsvps: Vec<Rsvp> = contacts
+            .iter()
+            .filter(|contact| !invited.contains(*contact))
+            .map(|contact| with_status(None, workspace_id, &event_thing, contact, RsvpStatus::Invited, now))
+            .collect();
+        if rsvps.is_empty() {
+            return Ok(rsvps);
+        }
+        let entries: Vec<TimelineEntry> = rsvps
+            .iter()
+            .map(|rsvp| TimelineEntry {
+                id: Some(new_thing("timeline_entry")),
+                workspace: rsvp.workspace.clone(),
+                contact: rsvp.contact.clone(),
+                company: None,
+                campaign: event.campaign.clone(),
+                entry_type: TimelineEntryType::EventInvite,
+                content: format!("Invited to event {}", event_id),
+                metadata: serde_json::json!({ "event_id": event_id }),
+                attachments: Vec::new(),
+                sentiment: None,
+                timestamp: now,
+            })
+            .collect();
+
+        if !self.events.create_invites(rsvps.clone(), entries.clone()).await? {
+            return Err(AppError::Conflict(
+                "Some of these contacts were given an RSVP at the same time; please try again".into(),
+            ));
+        }
+
+        for rsvp in &rsvps {
+            self.feed
+                .publish(workspace_id, FeedEvent::RsvpChanged(RsvpResponse::from(rsvp.clone())));
+        }
+        self.timeline.recorded(workspace_id, &entries).await;
+
+        Ok(rsvps)
+    }
+
+    /// Record a contact's RSVP, waitlisting them if the event is full
+    ///
+    /// Seats are re-checked as the RSVP is written; if other RSVPs took
+    /// them first, the request is decided again against the new count.
+    pub async fn rsvp(
+        &self,
+        workspace_id: &str,
+        event_id: &str,
+        contact_id: &str,
+        requested: RsvpStatus,
+    ) -> AppResult<Rsvp> {
+        let event = self.event(workspace_id, event_id).await?;
+
+        for _ in 0..RSVP_ATTEMPTS {
+            let Some((rsvp, promoted, entries)) =
+                self.try_rsvp(workspace_id, &event, event_id, contact_id, requested).await?
+            else {
+                continue;
+            };
+
+            for changed in std::iter::once(&rsvp).chain(&promoted) {
+                self.feed
+                    .publish(workspace_id, FeedEvent::RsvpChanged(RsvpResponse::from(changed.clone())));
+            }
+            self.timeline.recorded(workspace_id, &entries).await;
+            return Ok(rsvp);
+        }
+
+        Err(AppError::Conflict("The event's seats are changing quickly; please try again".into()))
+    }
+
+    /// Decide and store an RSVP; None when the seats it counted were taken first
+    async fn try_rsvp(
+        &self,
+        workspace_id: &str,
+        event: &Event,
+        event_id: &str,
+        contact_id: &str,
+        requested: RsvpStatus,
+    ) -> AppResult<Option<(Rsvp, Vec<Rsvp>, Vec<TimelineEntry>)>> {
+        let rules = event.registration_rules();
+
+        let event_thing = Thing::from(("event", event_id));
+        let contact_thing = Thing::from(("contact", contact_id));
+        let existing = self.events.find_rsvp(&event_thing, &contact_thing).await?;
+        let previous = existing.as_ref().map(|r| r.status);
+
+        let now = Utc::now();
+        let seats_taken = self.events.seats_taken(&event_thing, &contact_thing).await?;
+        let status = rules.resolve(previous, requested, seats_taken, now)?;
+
+        let created = existing.is_none();
+        let rsvp = with_status(existing, workspace_id, &event_thing, &contact_thing, status, now);
+
+        // A seat given up before the event goes to whoever has waited longest
+        let promoted = if rules.frees_seat(previous, status, now) {
+            match rules.open_seats(seats_taken) {
+                Some(0) => Vec::new(),
+                open => self.events.waitlist(&event_thing, open).await?,
+            }
+        } else {
+            Vec::new()
+        };
+
+        let mut entries = Vec::new();
+        if previous != Some(status) {
+            entries.extend(rsvp_entry(&rsvp, event_id));
+        }
+        let promoted: Vec<Rsvp> = promoted
+            .into_iter()
+            .map(|r| Rsvp {
+                status: RsvpStatus::Registered,
+                timestamp: now,
+                ..r
+            })
+            .collect();
+        for mut entry in promoted.iter().filter_map(|r| rsvp_entry(r, event_id)) {
+            entry.content = format!("Moved off the waitlist for event {}", event_id);
+            entry.metadata["promoted_from"] = serde_json::json!(RsvpStatus::Waitlisted);
+            entries.push(entry);
+        }
+
+        for entry in &mut entries {
+            entry.campaign = event.campaign.clone();
+        }
+
+        // Only changes that take seats need them to still be free
+        let takes_seats = !promoted.is_empty() || (status.takes_seat() && !previous.is_some_and(|p| p.takes_seat()));
+        let capacity = rules.max_attendees.filter(|_| takes_seats);
+
+        let promoted_ids: Vec<Thing> = promoted.iter().filter_map(|r| r.id.clone()).collect();
+        let saved = self
+            .events
+            .save_rsvp(&rsvp, created, &promoted_ids, entries.clone(), capacity, now)
+            .await?;
+
+        Ok(saved.then_some((rsvp, promoted, entries)))
+    }
+
+    // ---- Check-in ----
+
+    /// The token a contact's check-in QR code encodes
+    pub async fn checkin_token(
+        &self,
+        workspace_id: &str,
+        event_id: &str,
+        contact_id: &str,
+    ) -> AppResult<CheckinTokenResponse> {
+        let event = self.event(workspace_id, event_id).await?;
+        self.contacts
+            .find_by_id_with_id(workspace_id, contact_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Contact '{}' not found", contact_id)))?;
+
+        let expires_at = event.end_time + chrono::Duration::days(CHECKIN_TOKEN_GRACE_DAYS);
+        let token = self.tokens.sign(&CheckinClaims {
+            workspace: workspace_id.to_string(),
+            event: event_id.to_string(),
+            contact: contact_id.to_string(),
+            exp: expires_at.timestamp(),
+        })?;
+
+        Ok(CheckinTokenResponse {
+            event_id: event_id.to_string(),
+            contact_id: contact_id.to_string(),
+            token,
+            expires_at,
+        })
+    }
+
+    /// Check a contact in, marking their RSVP attended
+    pub async fn check_in(&self, workspace_id: &str, event_id: &str, req: CheckinRequest) -> AppResult<Rsvp> {
+        let event = self.event(workspace_id, event_id).await?;
+        let (contact_id, method) = self.checkin_contact(workspace_id, event_id, &req)?;
+        self.contacts
+            .find_by_id_with_id(workspace_id, &contact_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Contact '{}' not found", contact_id)))?;
+
+        let (rsvp, _) = self.record_check_in(workspace_id, &event, event_id, &contact_id, method).await?;
+        Ok(rsvp)
+    }
+
+    /// Check in many contacts at once, e.g. an attendance list synced after
+    /// the event; each one succeeds or fails on its own
+    pub async fn check_in_many(
+        &self,
+        workspace_id: &str,
+        event_id: &str,
+        checkins: Vec<CheckinRequest>,
+    ) -> AppResult<BulkCheckinResponse> {
+        if checkins.is_empty() {
+            return Err(AppError::Validation("checkins must list at least one contact".into()));
+        }
+        if checkins.len() > MAX_BULK_CHECKINS {
+            return Err(AppError::Validation(format!(
+                "At most {} contacts can be checked in at once",
+                MAX_BULK_CHECKINS
+            )));
+        }
+
+        // Check-in not being open would fail every contact the same way
+        let event = self.event(workspace_id, event_id).await?;
+        event.registration_rules().check_in(None, Utc::now())?;
+
+        let resolved: Vec<AppResult<(String, &str)>> = checkins
+            .iter()
+            .map(|req| self.checkin_contact(workspace_id, event_id, req))
+            .collect();
+        let ids: Vec<String> = resolved
+            .iter()
+            .filter_map(|r| r.as_ref().ok().map(|(id, _)| id.clone()))
+            .collect();
+        let found: HashSet<String> = self
+            .contacts
+            .find_many_with_ids(workspace_id, &ids)
+            .await?
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+
+        let mut results = Vec::with_capacity(resolved.len());
+        for resolved in resolved {
+            let result = match resolved {
+                Err(e) => checkin_result(None, CheckinStatus::Failed, None, Some(e.to_string())),
+                Ok((contact_id, _)) if !found.contains(&contact_id) => checkin_result(
+                    Some(contact_id),
+                    CheckinStatus::NotFound,
+                    None,
+                    Some("Contact not found".into()),
+                ),
+                Ok((contact_id, method)) => {
+                    match self.record_check_in(workspace_id, &event, event_id, &contact_id, method).await {
+                        Ok((rsvp, changed)) => {
+                            let status = if changed { CheckinStatus::CheckedIn } else { CheckinStatus::Unchanged };
+                            checkin_result(Some(contact_id), status, Some(rsvp.into()), None)
+                        }
+                        Err(e) => checkin_result(Some(contact_id), CheckinStatus::Failed, None, Some(e.to_string())),
+                    }
+                }
+            };
+            results.push(result);
+        }
+
+        Ok(BulkCheckinResponse::from_results(results))
+    }
+
+    /// The contact a check-in is for, and whether it came from a QR code
+    fn checkin_contact(
+        &self,
+        workspace_id: &str,
+        event_id: &str,
+        req: &CheckinRequest,
+    ) -> AppResult<(String, &'static str)> {
+        match (&req.contact_id, &req.token) {
+            (Some(contact_id), None) => Ok((contact_id.clone(), "manual")),
+            (None, Some(token)) => {
+                let claims = self.tokens.verify(token)?;
+                if claims.workspace != workspace_id || claims.event != event_id {
+                    return Err(AppError::BadRequest("Check-in code is for a different event".into()));
+                }
+                Ok((claims.contact, "qr"))
+            }
+            _ => Err(AppError::Validation("Give either contact_id or token".into())),
+        }
+    }
+
+    /// Mark a contact attended, unless they already are
+    async fn record_check_in(
+        &self,
+        workspace_id: &str,
+        event: &Event,
+        event_id: &str,
+        contact_id: &str,
+        method: &str,
+    ) -> AppResult<(Rsvp, bool)> {
+        let event_thing = Thing::from(("event", event_id));
+        let contact_thing = Thing::from(("contact", contact_id));
+        let existing = self.events.find_rsvp(&event_thing, &contact_thing).await?;
+        let previous = existing.as_ref().map(|r| r.status);
+
+        let now = Utc::now();
+        let changed = event.registration_rules().check_in(previous, now)?;
+        let existing = match existing {
+            Some(rsvp) if !changed => return Ok((rsvp, false)),
+            existing => existing,
+        };
+
+        let created = existing.is_none();
+        let rsvp = with_status(existing, workspace_id, &event_thing, &contact_thing, RsvpStatus::Attended, now);
+        let entry = TimelineEntry {
+            id: Some(new_thing("timeline_entry")),
+            workspace: rsvp.workspace.clone(),
+            contact: contact_thing,
+            company: None,
+            campaign: event.campaign.clone(),
+            entry_type: TimelineEntryType::EventAttend,
+            content: format!("Checked in to event {}", event_id),
+            metadata: serde_json::json!({
+                "event_id": event_id,
+                "status": RsvpStatus::Attended,
+                "previous_status": previous,
+                "method": method,
+            }),
+            attachments: Vec::new(),
+            sentiment: None,
+            timestamp: now,
+        };
+
+        self.events
+            .save_rsvp(&rsvp, created, &[], vec![entry.clone()], None, now)
+            .await?;
+
+        self.feed
+            .publish(workspace_id, FeedEvent::RsvpChanged(RsvpResponse::from(rsvp.clone())));
+        // Attendance counts towards engagement, so this rescores the contact
+        self.timeline.recorded(workspace_id, &[entry]).await;
+
+        Ok((rsvp, true))
+    }
+
+    // ---- Reminders ----
+
+    /// Send every reminder and follow-up that is due
+    ///
+    /// One RSVP failing is logged and retried on the next run.
+    pub async fn run_reminders(&self) -> AppResult<ReminderRunSummary> {
+        let now = Utc::now();
+        let horizon = now + chrono::Duration::minutes(i64::from(MAX_REMINDER_OFFSET_MINUTES));
+        let ended_since = now
+            - chrono::Duration::minutes(i64::from(MAX_REMINDER_OFFSET_MINUTES))
+            - chrono::Duration::days(FOLLOW_UP_WINDOW_DAYS);
+        let mut summary = ReminderRunSummary::default();
+
+        for candidate in self.events.find_reminder_candidates(now, horizon, ended_since).await? {
+            if candidate.email.is_none() {
+                continue;
+            }
+            let reminders = candidate.reminders.clone().unwrap_or_default();
+            let Some(email) = reminders.due(
+                candidate.status,
+                candidate.start_time,
+                candidate.end_time,
+                &candidate.emails_sent,
+                now,
+            ) else {
+                continue;
+            };
+
+            match self.send_email(&candidate, &reminders, email, now).await {
+                Ok(()) => match email {
+                    EventEmail::Reminder { .. } => summary.reminders += 1,
+                    EventEmail::FollowUp { .. } => summary.follow_ups += 1,
+                },
+                Err(e) => tracing::warn!("Failed to send event email for RSVP {}: {}", candidate.id, e),
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Run `run_reminders` on a background task at the configured interval
+    pub fn spawn_reminders(self: Arc<Self>, config: &EventConfig, shutdown: Shutdown) -> JoinHandle<()> {
+        let period = Duration::from_secs(config.reminder_interval_secs.max(60));
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+
+            while shutdown.tick(&mut interval).await {
+                match self.run_reminders().await {
+                    Ok(summary) if summary.reminders + summary.follow_ups > 0 => tracing::info!(
+                        "Sent {} event reminders and {} follow-ups",
+                        summary.reminders,
+                        summary.follow_ups
+                    ),
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Event reminder run failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Send one reminder or follow-up and put it on the contact's timeline
+    async fn send_email(
+        &self,
+        candidate: &ReminderCandidate,
+        reminders: &EventReminders,
+        email: EventEmail,
+        now: DateTime<Utc>,
+    ) -> AppResult<()> {
+        // Stub: In production, this would hand the email to the email provider
+        let event_id = candidate.event.id.to_raw();
+        let (subject, kind) = match email {
+            EventEmail::Reminder { minutes_before } => (
+                format!("Reminder: {} starts in {}", candidate.event_name, lead_time(minutes_before)),
+                "reminder",
+            ),
+            EventEmail::FollowUp { attended: true } => {
+                (format!("Thanks for joining {}", candidate.event_name), "follow_up")
+            }
+            EventEmail::FollowUp { attended: false } => {
+                (format!("Sorry we missed you at {}", candidate.event_name), "follow_up")
+            }
+        };
+
+        let entry = TimelineEntry {
+            id: Some(new_thing("timeline_entry")),
+            workspace: candidate.workspace.clone(),
+            contact: candidate.contact.clone(),
+            company: None,
+            campaign: None,
+            entry_type: TimelineEntryType::EmailSent,
+            content: format!("Sent \"{}\" for event {}", subject, event_id),
+            metadata: serde_json::json!({
+                "event_id": event_id,
+                "email": kind,
+                "rsvp_status": candidate.status,
+                "subject": subject,
+            }),
+            attachments: Vec::new(),
+            sentiment: None,
+            timestamp: now,
+        };
+
+        self.events
+            .record_email(&candidate.id, reminders.settled_by(email), &entry)
+            .await?;
+        self.timeline.recorded(&candidate.workspace.id.to_raw(), &[entry]).await;
+
+        Ok(())
+    }
+}
+
+/// "2 days", "1 hour", "30 minutes": how far ahead a reminder goes out
+fn lead_time(minutes: u32) -> String {
+    let (amount, unit) = match minutes {
+        m if m % (24 * 60) == 0 => (m / (24 * 60), "day"),
+        m if m % 60 == 0 => (m / 60, "hour"),
+        m => (m, "minute"),
+    };
+    format!("{} {}{}", amount, unit, if amount == 1 { "" } else { "s" })
+}
+
+/// `existing` moved to `status`, or a new RSVP when there is none
+fn with_status(
+    existing: Option<Rsvp>,
+    workspace_id: &str,
+    event: &Thing,
+    contact: &Thing,
+    status: RsvpStatus,
+    now: DateTime<Utc>,
+) -> Rsvp {
+    match existing {
+        Some(rsvp) => Rsvp {
+            status,
+            timestamp: now,
+            ..rsvp
+        },
+        None => Rsvp {
+            id: Some(new_thing("rsvp")),
+            workspace: workspace_thing(workspace_id),
+            event: event.clone(),
+            contact: contact.clone(),
+            status,
+            timestamp: now,
+            emails_sent: Vec::new(),
+        },
+    }
+}
+
+/// IDs with repeats dropped, first occurrence kept
+fn unique_ids(ids: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::with_capacity(ids.len());
+    ids.into_iter().filter(|id| seen.insert(id.clone())).collect()
+}
+
+fn checkin_result(
+    contact_id: Option<String>,
+    status: CheckinStatus,
+    rsvp: Option<RsvpResponse>,
+    error: Option<String>,
+) -> CheckinResult {
+    CheckinResult {
+        contact_id,
+        status,
+        rsvp,
+        error,
+    }
+}
+
+/// Timeline entry for an RSVP reaching a status worth showing on the timeline
+fn rsvp_entry(rsvp: &Rsvp, event_id: &str) -> Option<TimelineEntry> {
+    let (entry_type, content) = match rsvp.status {
+        RsvpStatus::Registered => (TimelineEntryType::EventRegistration, "RSVP status updated for event"),
+        RsvpStatus::Waitlisted => (TimelineEntryType::EventInvite, "Waitlisted for event"),
+        RsvpStatus::Attended => (TimelineEntryType::EventAttend, "RSVP status updated for event"),
+        _ => return None,
+    };
+
+    Some(TimelineEntry {
+        id: Some(new_thing("timeline_entry")),
+        workspace: rsvp.workspace.clone(),
+        contact: rsvp.contact.clone(),
+        company: None,
+        campaign: None,
+        entry_type,
+        content: format!("{} {}", content, event_id),
+        metadata: serde_json::json!({
+            "event_id": event_id,
+            "status": rsvp.status,
+        }),
+        attachments: Vec::new(),
+        sentiment: None,
+        timestamp: rsvp.timestamp,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unique_ids_keeps_first_occurrence() {
+        let ids = vec!["b".to_string(), "a".to_string(), "b".to_string()];
+        assert_eq!(unique_ids(ids), vec!["b".to_string(), "a".to_string()]);
+    }
+
+    #[test]
+    fn test_lead_time() {
+        assert_eq!(lead_time(24 * 60), "1 day");
+        assert_eq!(lead_time(48 * 60), "2 days");
+        assert_eq!(lead_time(60), "1 hour");
+        assert_eq!(lead_time(90), "90 minutes");
+    }
+}
@@ -0,0 +1,154 @@
+//! Feed Service - in-process broadcast of workspace activity
+//!
+//! Writers publish after a successful write; every open activity stream
+//! subscribes and keeps only its own workspace's events. Nothing is
+//! stored: a client that connects late misses events, and one that falls
+//! too far behind gets a `resync` event; either should reload through the
+//! regular endpoints.
+
+use futures::stream::{self, Stream};
+use tokio::sync::broadcast;
+
+use crate::models::{FeedEvent, TimelineEntry, TimelineEntryResponse};
+
+/// Events buffered per subscriber before the slowest one starts missing some
+const FEED_CHANNEL_CAPACITY: usize = 1024;
+
+/// A published event with the workspace it belongs to; `None` goes to everyone
+#[derive(Debug, Clone)]
+struct FeedMessage {
+    workspace_id: Option<String>,
+    event: FeedEvent,
+}
+
+impl FeedMessage {
+    fn is_for(&self, workspace_id: &str) -> bool {
+        self.workspace_id.as_deref().is_none_or(|w| w == workspace_id)
+    }
+}
+
+pub struct FeedService {
+    sender: broadcast::Sender<FeedMessage>,
+}
+
+impl FeedService {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(FEED_CHANNEL_CAPACITY);
+        Self { sender }
+    }
+
+    /// Send an event to every subscriber of the workspace
+    ///
+    /// Having no subscribers is not an error.
+    pub fn publish(&self, workspace_id: &str, event: FeedEvent) {
+        let _ = self.sender.send(FeedMessage {
+            workspace_id: Some(workspace_id.to_string()),
+            event,
+        });
+    }
+
+    /// Send an event to every subscriber of every workspace
+    pub fn publish_all(&self, event: FeedEvent) {
+        let _ = self.sender.send(FeedMessage {
+            workspace_id: None,
+            event,
+        });
+    }
+
+    pub fn publish_timeline_entry(&self, entry: &TimelineEntry) {
+        self.publish(
+            &entry.workspace.id.to_string(),
+            FeedEvent::TimelineEntryCreated(TimelineEntryResponse::from(entry.clone())),
+        );
+    }
+
+    /// Events of one workspace, from now on
+    ///
+    /// A subscriber that falls behind gets a `Resync` event in place of
+    /// the events it missed.
+    pub fn subscribe(&self, workspace_id: &str) -> impl Stream<Item = FeedEvent> + Send + use<> {
+        let receiver = self.sender.subscribe();
+        let workspace_id = workspace_id.to_string();
+
+        stream::unfold(receiver, move |mut receiver| {
+            let workspace_id = workspace_id.clone();
+            async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok(message) if message.is_for(&workspace_id) => {
+                            return Some((message.event, receiver));
+                        }
+                        Ok(_) => continue,
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            tracing::warn!("Activity feed subscriber fell behind, skipped {} events", skipped);
+                            return Some((FeedEvent::Resync, receiver));
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl Default for FeedService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{RsvpResponse, RsvpStatus};
+    use futures::StreamExt;
+
+    fn rsvp(id: &str) -> FeedEvent {
+        FeedEvent::RsvpChanged(RsvpResponse {
+            id: id.to_string(),
+            event_id: "launch".to_string(),
+            contact_id: "ada".to_string(),
+            status: RsvpStatus::Registered,
+            timestamp: chrono::Utc::now(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_subscriber_only_sees_its_workspace() {
+        let feed = FeedService::new();
+        let mut stream = Box::pin(feed.subscribe("ws1"));
+
+        feed.publish("ws2", rsvp("other"));
+        feed.publish("ws1", rsvp("mine"));
+
+        match stream.next().await {
+            Some(FeedEvent::RsvpChanged(r)) => assert_eq!(r.id, "mine"),
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publish_all_reaches_every_workspace() {
+        let feed = FeedService::new();
+        let mut stream = Box::pin(feed.subscribe("ws1"));
+
+        feed.publish_all(FeedEvent::Resync);
+
+        assert!(matches!(stream.next().await, Some(FeedEvent::Resync)));
+    }
+
+    #[tokio::test]
+    async fn test_publish_without_subscribers_is_fine() {
+        let feed = FeedService::new();
+        feed.publish("ws1", rsvp("nobody-listening"));
+
+        // A later subscriber only sees what is published after it subscribed
+        let mut stream = Box::pin(feed.subscribe("ws1"));
+        feed.publish("ws1", rsvp("listening"));
+
+        match stream.next().await {
+            Some(FeedEvent::RsvpChanged(r)) => assert_eq!(r.id, "listening"),
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+}
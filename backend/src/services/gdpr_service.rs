@@ -0,0 +1,98 @@
+//! GDPR Service - data subject access and erasure requests
+//!
+//! An export bundles everything stored about a contact as JSON. An erasure
+//! anonymizes the contact in place (see `domain::gdpr`), deletes its
+//! attached files and uploaded avatar, and is written to an erasure log.
+//! Both work on trashed contacts too: they hold personal data until purged.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+
+use crate::db::Database;
+use crate::error::{AppError, AppResult};
+use crate::models::{Contact, GdprErasureResponse, GdprExport};
+use crate::repositories::{AttachmentRepository, GdprRepository};
+use crate::services::object_storage::ObjectStorage;
+use crate::services::{AuthenticatedUser, AvatarService};
+
+/// Most erasure log entries listed at once
+const MAX_ERASURES: u32 = 500;
+
+pub struct GdprService {
+    gdpr: GdprRepository,
+    attachments: AttachmentRepository,
+    storage: Arc<dyn ObjectStorage>,
+    avatars: Arc<AvatarService>,
+}
+
+impl GdprService {
+    pub fn new(db: Arc<Database>, storage: Arc<dyn ObjectStorage>, avatars: Arc<AvatarService>) -> Self {
+        Self {
+            gdpr: GdprRepository::new(Arc::clone(&db)),
+            attachments: AttachmentRepository::new(db),
+            storage,
+            avatars,
+        }
+    }
+
+    /// Everything stored about a contact
+    pub async fn export(&self, workspace_id: &str, contact_id: &str) -> AppResult<GdprExport> {
+        let contact = self.find_contact(workspace_id, contact_id).await?;
+        let records = self.gdpr.find_records(workspace_id, contact_id).await?;
+
+        Ok(GdprExport {
+            exported_at: Utc::now(),
+            contact: contact.into(),
+            timeline: records.timeline.into_iter().map(Into::into).collect(),
+            rsvps: records.rsvps,
+            campaigns: records.campaigns,
+            sequences: records.sequences,
+            deals: records.deals.into_iter().map(Into::into).collect(),
+            attachments: records.attachments.into_iter().map(Into::into).collect(),
+            landing_page_visits: records.landing_page_visits,
+            short_links: records.short_links,
+            status_history: records.status_history.into_iter().map(Into::into).collect(),
+            webhook_deliveries: records.webhook_deliveries,
+            stored_responses: records.stored_responses,
+            embeddings: records.embeddings,
+            duplicate_suggestions: records.duplicate_suggestions,
+            history: records.history.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    /// Anonymize a contact and delete its files
+    pub async fn erase(&self, actor: &AuthenticatedUser, contact_id: &str) -> AppResult<GdprErasureResponse> {
+        let workspace_id = actor.workspace_id.as_str();
+        let contact = self.find_contact(workspace_id, contact_id).await?;
+        let attachments = self.attachments.find_for_contact(workspace_id, contact_id).await?;
+
+        let erasure = self.gdpr.erase(workspace_id, contact_id, &actor.user_id).await?;
+
+        // The records are gone; a file left behind is unreachable, so only warn
+        for attachment in &attachments {
+            if let Err(e) = self.storage.delete(&attachment.storage_key).await {
+                tracing::warn!("Failed to remove attachment file {}: {}", attachment.storage_key, e);
+            }
+        }
+        if let Some(avatar_url) = &contact.avatar_url {
+            self.avatars.delete_files(workspace_id, avatar_url).await;
+        }
+
+        tracing::info!("Erased contact {} in workspace {}", contact_id, workspace_id);
+        Ok(erasure.into())
+    }
+
+    /// The erasure log, newest first
+    pub async fn list_erasures(&self, workspace_id: &str) -> AppResult<Vec<GdprErasureResponse>> {
+        let erasures = self.gdpr.find_erasures(workspace_id, MAX_ERASURES).await?;
+        Ok(erasures.into_iter().map(Into::into).collect())
+    }
+
+    async fn find_contact(&self, workspace_id: &str, contact_id: &str) -> AppResult<Contact> {
+        self.gdpr
+            .find_contact(workspace_id, contact_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Contact '{}' not found", contact_id)))
+    }
+}
@@ -0,0 +1,364 @@
+//! HubSpot - reading a portal's CRM objects for the importer
+//!
+//! `HubspotClient` pages through contacts, companies, deals and notes over
+//! the CRM v3 API with a private app access token. `parse_export` reads the
+//! same objects from an uploaded export. Either way they come out as an
+//! `ImportBatch`, keyed by their HubSpot IDs.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Deserialize;
+
+use crate::domain::{
+    normalize_domain, note_text, ContactStatus, DealStage, ImportBatch, ImportedCompany, ImportedContact, ImportedDeal,
+    ImportedNote,
+};
+use crate::error::{AppError, AppResult};
+
+const HUBSPOT_OBJECTS_URL: &str = "https://api.hubapi.com/crm/v3/objects";
+
+/// Largest page the list endpoints return
+const PAGE_SIZE: u32 = 100;
+
+/// Times a rate-limited request is retried before the import gives up
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Default, Deserialize)]
+struct HubspotExport {
+    #[serde(default)]
+    contacts: Vec<HubspotObject>,
+    #[serde(default)]
+    companies: Vec<HubspotObject>,
+    #[serde(default)]
+    deals: Vec<HubspotObject>,
+    #[serde(default)]
+    notes: Vec<HubspotObject>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HubspotObject {
+    id: String,
+    #[serde(default)]
+    properties: HashMap<String, serde_json::Value>,
+    /// Keyed by the associated object type, e.g. `companies`
+    #[serde(default)]
+    associations: HashMap<String, AssociationList>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AssociationList {
+    #[serde(default)]
+    results: Vec<AssociationRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AssociationRef {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct Page {
+    results: Vec<HubspotObject>,
+    paging: Option<Paging>,
+}
+
+#[derive(Deserialize)]
+struct Paging {
+    next: Option<NextPage>,
+}
+
+#[derive(Deserialize)]
+struct NextPage {
+    after: String,
+}
+
+impl HubspotObject {
+    /// A property's value as trimmed text; `None` when blank
+    fn prop(&self, name: &str) -> Option<String> {
+        let value = match self.properties.get(name)? {
+            serde_json::Value::String(s) => s.trim().to_string(),
+            serde_json::Value::Number(n) => n.to_string(),
+            serde_json::Value::Bool(b) => b.to_string(),
+            _ => return None,
+        };
+        (!value.is_empty()).then_some(value)
+    }
+
+    /// IDs of the associated objects of one type
+    fn associated(&self, object_type: &str) -> Vec<String> {
+        self.associations
+            .get(object_type)
+            .map(|list| list.results.iter().map(|r| r.id.clone()).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Read an uploaded export
+pub fn parse_export(export: serde_json::Value) -> AppResult<ImportBatch> {
+    let export: HubspotExport = serde_json::from_value(export)
+        .map_err(|e| AppError::BadRequest(format!("Not a HubSpot export: {}", e)))?;
+    Ok(to_batch(export))
+}
+
+/// HubSpot CRM API, authenticated with a private app access token
+pub struct HubspotClient {
+    http: reqwest::Client,
+    api_key: String,
+}
+
+impl HubspotClient {
+    pub fn new(api_key: &str) -> AppResult<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| AppError::Internal(format!("Failed to build HubSpot client: {}", e)))?;
+
+        Ok(Self {
+            http,
+            api_key: api_key.trim().to_string(),
+        })
+    }
+
+    /// Every contact, company, deal and note in the portal
+    pub async fn fetch_all(&self) -> AppResult<ImportBatch> {
+        let export = HubspotExport {
+            companies: self
+                .list("companies", "name,domain,website,industry,numberofemployees", None)
+                .await?,
+            contacts: self
+                .list(
+                    "contacts",
+                    "email,firstname,lastname,phone,jobtitle,lifecyclestage,associatedcompanyid",
+                    Some("companies"),
+                )
+                .await?,
+            deals: self
+                .list("deals", "dealname,amount,dealstage,closedate", Some("contacts,companies"))
+                .await?,
+            notes: self.list("notes", "hs_note_body,hs_timestamp", Some("contacts")).await?,
+        };
+
+        Ok(to_batch(export))
+    }
+
+    async fn list(&self, object_type: &str, properties: &str, associations: Option<&str>) -> AppResult<Vec<HubspotObject>> {
+        let mut objects = Vec::new();
+        let mut after: Option<String> = None;
+
+        loop {
+            let mut query = vec![("limit", PAGE_SIZE.to_string()), ("properties", properties.to_string())];
+            if let Some(associations) = associations {
+                query.push(("associations", associations.to_string()));
+            }
+            if let Some(after) = &after {
+                query.push(("after", after.clone()));
+            }
+
+            let page = self.get_page(object_type, &query).await?;
+            objects.extend(page.results);
+            match page.paging.and_then(|p| p.next) {
+                Some(next) => after = Some(next.after),
+                None => return Ok(objects),
+            }
+        }
+    }
+
+    async fn get_page(&self, object_type: &str, query: &[(&str, String)]) -> AppResult<Page> {
+        let mut retries = 0;
+        loop {
+            let response = self
+                .http
+                .get(format!("{}/{}", HUBSPOT_OBJECTS_URL, object_type))
+                .bearer_auth(&self.api_key)
+                .query(query)
+                .send()
+                .await
+                .map_err(|e| AppError::Upstream(format!("HubSpot unreachable: {}", e)))?;
+
+            let status = response.status();
+            if status == reqwest::StatusCode::TOO_MANY_REQUESTS && retries < MAX_RATE_LIMIT_RETRIES {
+                // Private apps get 100 requests per 10 seconds
+                retries += 1;
+                tokio::time::sleep(Duration::from_secs(10)).await;
+                continue;
+            }
+            if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+                return Err(AppError::BadRequest(format!(
+                    "HubSpot refused the API key for {} ({})",
+                    object_type, status
+                )));
+            }
+            if !status.is_success() {
+                let text = response.text().await.unwrap_or_default();
+                return Err(AppError::Upstream(format!("HubSpot returned {}: {}", status, text)));
+            }
+
+            return response
+                .json()
+                .await
+                .map_err(|e| AppError::Upstream(format!("Unreadable HubSpot response: {}", e)));
+        }
+    }
+}
+
+fn to_batch(export: HubspotExport) -> ImportBatch {
+    ImportBatch {
+        companies: export.companies.iter().map(to_company).collect(),
+        contacts: export.contacts.iter().map(to_contact).collect(),
+        deals: export.deals.iter().map(to_deal).collect(),
+        notes: export.notes.iter().map(to_note).collect(),
+    }
+}
+
+fn to_company(object: &HubspotObject) -> ImportedCompany {
+    let domain = object
+        .prop("domain")
+        .or_else(|| object.prop("website"))
+        .and_then(|d| normalize_domain(&d));
+
+    ImportedCompany {
+        external_id: object.id.clone(),
+        // Companies need a name; HubSpot's don't
+        name: object.prop("name").or_else(|| domain.clone()).unwrap_or_default(),
+        domain,
+        industry: object.prop("industry"),
+        size: object.prop("numberofemployees"),
+    }
+}
+
+fn to_contact(object: &HubspotObject) -> ImportedContact {
+    ImportedContact {
+        external_id: object.id.clone(),
+        email: object.prop("email").map(|e| e.to_lowercase()),
+        first_name: object.prop("firstname"),
+        last_name: object.prop("lastname"),
+        phone: object.prop("phone"),
+        job_title: object.prop("jobtitle"),
+        status: contact_status(object.prop("lifecyclestage").as_deref()),
+        company_external_id: object
+            .associated("companies")
+            .into_iter()
+            .next()
+            .or_else(|| object.prop("associatedcompanyid")),
+    }
+}
+
+fn to_deal(object: &HubspotObject) -> ImportedDeal {
+    ImportedDeal {
+        external_id: object.id.clone(),
+        name: object
+            .prop("dealname")
+            .unwrap_or_else(|| format!("HubSpot deal {}", object.id)),
+        value: object
+            .prop("amount")
+            .and_then(|a| a.parse::<f64>().ok())
+            .filter(|a| a.is_finite() && *a >= 0.0)
+            .unwrap_or(0.0),
+        stage: deal_stage(object.prop("dealstage").as_deref()),
+        close_date: object.prop("closedate").and_then(|d| parse_timestamp(&d)),
+        contact_external_id: object.associated("contacts").into_iter().next(),
+        company_external_id: object.associated("companies").into_iter().next(),
+    }
+}
+
+fn to_note(object: &HubspotObject) -> ImportedNote {
+    ImportedNote {
+        external_id: object.id.clone(),
+        body: note_text(&object.prop("hs_note_body").unwrap_or_default()),
+        timestamp: object.prop("hs_timestamp").and_then(|t| parse_timestamp(&t)),
+        contact_external_ids: object.associated("contacts"),
+    }
+}
+
+/// Lifecycle stages past the sale count as customers
+fn contact_status(lifecycle_stage: Option<&str>) -> ContactStatus {
+    match lifecycle_stage {
+        Some("customer" | "evangelist") => ContactStatus::Customer,
+        Some("other") => ContactStatus::Other,
+        _ => ContactStatus::Lead,
+    }
+}
+
+/// Stages of HubSpot's default sales pipeline; custom stages start at prospecting
+fn deal_stage(stage: Option<&str>) -> DealStage {
+    match stage {
+        Some("qualifiedtobuy") => DealStage::Qualification,
+        Some("presentationscheduled") => DealStage::Proposal,
+        Some("decisionmakerboughtin" | "contractsent") => DealStage::Negotiation,
+        Some("closedwon") => DealStage::ClosedWon,
+        Some("closedlost") => DealStage::ClosedLost,
+        _ => DealStage::Prospecting,
+    }
+}
+
+/// ISO 8601, or milliseconds since the epoch as in older exports
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(millis) = value.parse::<i64>() {
+        return Utc.timestamp_millis_opt(millis).single();
+    }
+    DateTime::parse_from_rfc3339(value).ok().map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_export() {
+        let batch = parse_export(serde_json::json!({
+            "companies": [
+                { "id": "10", "properties": { "name": null, "website": "https://www.acme.com/" } }
+            ],
+            "contacts": [
+                {
+                    "id": "1",
+                    "properties": { "email": "Ada@Acme.com", "firstname": "Ada", "lifecyclestage": "customer" },
+                    "associations": { "companies": { "results": [{ "id": "10", "type": "contact_to_company" }] } }
+                }
+            ],
+            "deals": [
+                {
+                    "id": "20",
+                    "properties": { "dealname": "Renewal", "amount": "1200.50", "dealstage": "contractsent", "closedate": "2026-03-01T00:00:00Z" },
+                    "associations": { "contacts": { "results": [{ "id": "1" }] } }
+                }
+            ],
+            "notes": [
+                {
+                    "id": "30",
+                    "properties": { "hs_note_body": "<p>Called &amp; left a message</p><p>Follow up</p>", "hs_timestamp": "1767225600000" },
+                    "associations": { "contacts": { "results": [{ "id": "1" }] } }
+                }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(batch.companies[0].name, "acme.com");
+        assert_eq!(batch.companies[0].domain.as_deref(), Some("acme.com"));
+
+        let contact = &batch.contacts[0];
+        assert_eq!(contact.email.as_deref(), Some("ada@acme.com"));
+        assert_eq!(contact.status, ContactStatus::Customer);
+        assert_eq!(contact.company_external_id.as_deref(), Some("10"));
+
+        let deal = &batch.deals[0];
+        assert_eq!(deal.value, 1200.5);
+        assert_eq!(deal.stage, DealStage::Negotiation);
+        assert_eq!(deal.contact_external_id.as_deref(), Some("1"));
+        assert!(deal.close_date.is_some());
+
+        let note = &batch.notes[0];
+        assert_eq!(note.body, "Called & left a message\nFollow up");
+        assert_eq!(note.contact_external_ids, vec!["1".to_string()]);
+        assert_eq!(note.timestamp, Utc.timestamp_millis_opt(1767225600000).single());
+    }
+
+    #[test]
+    fn test_parse_export_rejects_other_shapes() {
+        assert!(parse_export(serde_json::json!({ "contacts": "nope" })).is_err());
+    }
+}
@@ -0,0 +1,317 @@
+//! Idempotency Service - replay the first response for a retried request
+//!
+//! A client that sends `Idempotency-Key` on a POST gets the same status and
+//! body back for every retry with that key, and the handler runs only once.
+//! Keys are scoped per workspace (or to public routes), expire after
+//! `KEY_TTL_HOURS`, and may not be reused for a different request.
+//! Client errors free the key, since the request was turned away before
+//! anything was written. Every other response is stored: a server error
+//! may come after part of the work is done, such as half a campaign send,
+//! and a retry must not do it again.
+//! A running request renews its lease every `RENEW_SECS`; if it dies
+//! without finishing, the next retry after `LEASE_SECS` takes the key over.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use tokio::task::JoinHandle;
+
+use crate::db::Database;
+use crate::error::{AppError, AppResult};
+use crate::models::IdempotencyRecord;
+use crate::repositories::IdempotencyRepository;
+use crate::shutdown::Shutdown;
+
+/// How long a key is remembered
+const KEY_TTL_HOURS: i64 = 24;
+/// How long an unfinished request holds its key before a retry may take over
+const LEASE_SECS: i64 = 90;
+/// How often a running request renews its lease
+const RENEW_SECS: u64 = 30;
+const MAX_KEY_LENGTH: usize = 255;
+const CLEANUP_INTERVAL_SECS: u64 = 3600;
+
+/// A response recorded for a key
+#[derive(Debug, Clone)]
+pub struct StoredResponse {
+    pub status: u16,
+    pub body: String,
+    pub content_type: Option<String>,
+}
+
+/// What to do with a request carrying an Idempotency-Key
+#[derive(Debug)]
+pub enum IdempotencyOutcome {
+    /// First time this key is seen - run the handler, then call `finish`
+    Proceed,
+    /// Already handled - send the stored response
+    Replay(StoredResponse),
+}
+
+pub struct IdempotencyService {
+    repo: IdempotencyRepository,
+}
+
+impl IdempotencyService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            repo: IdempotencyRepository::new(db),
+        }
+    }
+
+    /// Claim a key for a request, or find the response it already got
+    ///
+    /// Fails with Conflict while the first request is still running within
+    /// its lease, and with BadRequest if the key was used for a different
+    /// request.
+    pub async fn begin(&self, scope: &str, key: &str, request_hash: &str) -> AppResult<IdempotencyOutcome> {
+        validate_key(key)?;
+
+        let existing = match self.repo.find(scope, key).await? {
+            Some(record) if record.expires_at <= Utc::now() => {
+                self.repo.delete(scope, key).await?;
+                None
+            }
+            existing => existing,
+        };
+
+        let now = Utc::now();
+        let record = match existing {
+            Some(record) if record.request_hash == request_hash && lease_expired(&record, now) => {
+                if self.repo.take_over(scope, key, now, now + lease()).await? {
+                    return Ok(IdempotencyOutcome::Proceed);
+                }
+                self.repo
+                    .find(scope, key)
+                    .await?
+                    .ok_or_else(|| AppError::Conflict("Idempotency-Key was released; retry the request".into()))?
+            }
+            Some(record) => record,
+            None => {
+                let claim = IdempotencyRecord {
+                    id: None,
+                    scope: scope.to_string(),
+                    key: key.to_string(),
+                    request_hash: request_hash.to_string(),
+                    status: None,
+                    body: None,
+                    content_type: None,
+                    locked_until: Some(now + lease()),
+                    created_at: now,
+                    expires_at: now + chrono::Duration::hours(KEY_TTL_HOURS),
+                };
+
+                match self.repo.insert(claim).await {
+                    Ok(()) => return Ok(IdempotencyOutcome::Proceed),
+                    // Lost the race on the unique index; the winner's record decides
+                    Err(e) => self.repo.find(scope, key).await?.ok_or(e)?,
+                }
+            }
+        };
+
+        outcome(record, request_hash)
+    }
+
+    /// Run the handler for a claimed key, renewing its lease until it returns
+    ///
+    /// However long the handler takes, a retry can only take the key over
+    /// once renewals stop, i.e. when this request has died.
+    pub async fn hold<F: Future>(&self, scope: &str, key: &str, handler: F) -> F::Output {
+        let mut handler = std::pin::pin!(handler);
+        let every = Duration::from_secs(RENEW_SECS);
+        let mut renewal = tokio::time::interval_at(tokio::time::Instant::now() + every, every);
+
+        loop {
+            tokio::select! {
+                output = &mut handler => return output,
+                _ = renewal.tick() => {
+                    if let Err(e) = self.repo.renew(scope, key, Utc::now() + lease()).await {
+                        tracing::warn!("Failed to renew idempotency lease for key {}: {}", key, e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Record the handler's response
+    ///
+    /// Client errors release the key so the client can correct the request
+    /// and retry; anything else is kept for replay.
+    pub async fn finish(&self, scope: &str, key: &str, response: StoredResponse) -> AppResult<()> {
+        if keeps_response(response.status) {
+            self.repo
+                .complete(scope, key, response.status, response.body, response.content_type)
+                .await
+        } else {
+            self.repo.delete(scope, key).await
+        }
+    }
+
+    /// Free a key without storing anything
+    pub async fn release(&self, scope: &str, key: &str) -> AppResult<()> {
+        self.repo.delete(scope, key).await
+    }
+
+    /// Delete expired keys on a background task
+    pub fn spawn_cleanup(self: Arc<Self>, shutdown: Shutdown) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(CLEANUP_INTERVAL_SECS));
+
+            while shutdown.tick(&mut interval).await {
+                if let Err(e) = self.repo.delete_expired(Utc::now()).await {
+                    tracing::error!("Idempotency key cleanup failed: {}", e);
+                }
+            }
+        })
+    }
+}
+
+/// Whether a response is stored rather than freeing its key
+///
+/// Only a client error is known to have changed nothing.
+fn keeps_response(status: u16) -> bool {
+    !(400..500).contains(&status)
+}
+
+fn lease() -> chrono::Duration {
+    chrono::Duration::seconds(LEASE_SECS)
+}
+
+/// Whether an unfinished request has held its key too long
+fn lease_expired(record: &IdempotencyRecord, now: DateTime<Utc>) -> bool {
+    record.status.is_none() && record.locked_until.is_none_or(|until| until <= now)
+}
+
+/// Fingerprint of a request, compared when a key is reused
+pub fn fingerprint(method: &str, path: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_bytes());
+    hasher.update(b" ");
+    hasher.update(path.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+
+    hex::encode(hasher.finalize())
+}
+
+fn validate_key(key: &str) -> AppResult<()> {
+    if key.trim().is_empty() {
+        return Err(AppError::BadRequest("Idempotency-Key must not be empty".into()));
+    }
+    if key.len() > MAX_KEY_LENGTH {
+        return Err(AppError::BadRequest(format!(
+            "Idempotency-Key must be at most {} characters",
+            MAX_KEY_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+fn outcome(record: IdempotencyRecord, request_hash: &str) -> AppResult<IdempotencyOutcome> {
+    if record.request_hash != request_hash {
+        return Err(AppError::BadRequest(
+            "Idempotency-Key was already used for a different request".into(),
+        ));
+    }
+
+    match record.status {
+        Some(status) => Ok(IdempotencyOutcome::Replay(StoredResponse {
+            status,
+            body: record.body.unwrap_or_default(),
+            content_type: record.content_type,
+        })),
+        None => Err(AppError::Conflict(
+            "A request with this Idempotency-Key is still in progress".into(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(request_hash: &str, status: Option<u16>) -> IdempotencyRecord {
+        IdempotencyRecord {
+            id: None,
+            scope: "workspace:acme".into(),
+            key: "retry-1".into(),
+            request_hash: request_hash.into(),
+            status,
+            body: status.map(|_| r#"{"id":"ada"}"#.to_string()),
+            content_type: Some("application/json".into()),
+            locked_until: status.is_none().then(|| Utc::now() + lease()),
+            created_at: Utc::now(),
+            expires_at: Utc::now() + chrono::Duration::hours(KEY_TTL_HOURS),
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_covers_method_path_and_body() {
+        let base = fingerprint("POST", "/api/contacts", br#"{"email":"ada@example.com"}"#);
+
+        assert_eq!(base, fingerprint("POST", "/api/contacts", br#"{"email":"ada@example.com"}"#));
+        assert_eq!(base.len(), 64);
+        assert_ne!(base, fingerprint("POST", "/api/contacts", br#"{"email":"bob@example.com"}"#));
+        assert_ne!(base, fingerprint("POST", "/lp/launch/submit", br#"{"email":"ada@example.com"}"#));
+    }
+
+    #[test]
+    fn test_validate_key() {
+        assert!(validate_key("3f1c9a2e-retry").is_ok());
+        assert!(validate_key("  ").is_err());
+        assert!(validate_key(&"k".repeat(MAX_KEY_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_outcome_replays_completed_request() {
+        match outcome(record("abc", Some(200)), "abc").unwrap() {
+            IdempotencyOutcome::Replay(stored) => {
+                assert_eq!(stored.status, 200);
+                assert_eq!(stored.body, r#"{"id":"ada"}"#);
+            }
+            other => panic!("expected replay, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_outcome_rejects_in_progress_and_mismatched_requests() {
+        assert!(matches!(outcome(record("abc", None), "abc"), Err(AppError::Conflict(_))));
+        assert!(matches!(outcome(record("abc", Some(200)), "xyz"), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_only_client_errors_free_the_key() {
+        assert!(keeps_response(200));
+        assert!(keeps_response(201));
+        assert!(keeps_response(500));
+        assert!(keeps_response(502));
+        assert!(!keeps_response(400));
+        assert!(!keeps_response(404));
+        assert!(!keeps_response(422));
+    }
+
+    #[test]
+    fn test_lease_outlasts_renewals() {
+        assert!(Duration::from_secs(RENEW_SECS * 2) < lease().to_std().unwrap());
+    }
+
+    #[test]
+    fn test_lease_expires_only_for_unfinished_requests() {
+        let now = Utc::now();
+        let mut running = record("abc", None);
+        assert!(!lease_expired(&running, now));
+
+        running.locked_until = Some(now - chrono::Duration::seconds(1));
+        assert!(lease_expired(&running, now));
+
+        running.locked_until = None;
+        assert!(lease_expired(&running, now));
+
+        let mut finished = record("abc", Some(200));
+        finished.locked_until = Some(now - chrono::Duration::seconds(1));
+        assert!(!lease_expired(&finished, now));
+    }
+}
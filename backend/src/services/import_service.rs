@@ -0,0 +1,813 @@
+//! Import Service - one-time migration from another CRM
+//!
+//! Starting an import creates an `import_job` and runs it on a background
+//! task; the job's progress counts are saved as it goes, so clients poll
+//! `GET /api/import/jobs/:id`. Companies are imported first, then contacts
+//! (linked to their company), deals and notes.
+//!
+//! A workspace runs one import per source at a time. The running job holds
+//! a lease it renews while it works; if the server dies mid-import the lease
+//! lapses and the next import marks the job failed and takes over.
+//!
+//! Every imported record is remembered by its ID in the other CRM. A
+//! re-run finds records through those refs, or companies by domain and
+//! contacts by email, and in `create` mode leaves them alone; in `upsert`
+//! mode it updates them with what the other CRM has.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use surrealdb::sql::Thing;
+use tokio::task::JoinHandle;
+
+use crate::db::{workspace_thing, Database};
+use crate::domain::{
+    deal_outcome, sender_name, validate_deal, AuditEntity, ContactBuilder, ContactUpdater, ImportBatch, ImportedCompany,
+    ImportedContact, ImportedDeal, ImportedNote, WebhookEvent, IMPORT_SOURCE_KEY, JOB_TITLE_FIELD,
+};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Company, ContactResponse, Deal, FeedEvent, HubspotImportRequest, ImportCounts, ImportFailure, ImportJob,
+    ImportJobResponse, ImportMode, ImportProgress, ImportSource, ImportStatus, PipedriveImportRequest, TimelineEntry,
+    TimelineEntryType,
+};
+use crate::repositories::{ContactRepository, ImportRepository};
+use crate::services::hubspot::{self, HubspotClient};
+use crate::services::pipedrive::{self, PipedriveClient};
+use crate::services::{AuditService, AuthenticatedUser, FeedService, TimelineService, WebhookService};
+
+/// Progress is saved every this many records
+const PROGRESS_INTERVAL: u32 = 50;
+
+/// Failures kept on a job; the counts include the rest
+const MAX_FAILURES: usize = 100;
+
+/// How long a running job's lease lasts; renewed every fifth of it
+const LEASE_SECS: i64 = 300;
+
+#[derive(Clone, Copy)]
+enum RecordKind {
+    Company,
+    Contact,
+    Deal,
+    Note,
+}
+
+impl RecordKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RecordKind::Company => "company",
+            RecordKind::Contact => "contact",
+            RecordKind::Deal => "deal",
+            RecordKind::Note => "note",
+        }
+    }
+
+    fn counts(self, progress: &mut ImportProgress) -> &mut ImportCounts {
+        match self {
+            RecordKind::Company => &mut progress.companies,
+            RecordKind::Contact => &mut progress.contacts,
+            RecordKind::Deal => &mut progress.deals,
+            RecordKind::Note => &mut progress.notes,
+        }
+    }
+}
+
+/// What importing one record did, with the ID of the record here
+enum Outcome {
+    Created(String),
+    Updated(String),
+    Skipped(String),
+}
+
+/// Where an import's records come from
+enum BatchInput {
+    Hubspot(HubspotClient),
+    Pipedrive(PipedriveClient),
+    Ready(ImportBatch),
+}
+
+/// One running import: the job, who started it, and the refs loaded so far
+struct ImportRun {
+    job: ImportJob,
+    actor: AuthenticatedUser,
+    refs: HashMap<&'static str, HashMap<String, String>>,
+}
+
+impl ImportRun {
+    fn workspace_id(&self) -> &str {
+        &self.actor.workspace_id
+    }
+
+    fn ref_id(&self, kind: RecordKind, external_id: &str) -> Option<&String> {
+        self.refs.get(kind.as_str()).and_then(|refs| refs.get(external_id))
+    }
+}
+
+pub struct ImportService {
+    imports: ImportRepository,
+    contacts: ContactRepository,
+    audit: AuditService,
+    webhooks: WebhookService,
+    feed: Arc<FeedService>,
+    timeline: Arc<TimelineService>,
+}
+
+impl ImportService {
+    pub fn new(db: Arc<Database>, feed: Arc<FeedService>, timeline: Arc<TimelineService>) -> Self {
+        Self {
+            imports: ImportRepository::new(Arc::clone(&db)),
+            contacts: ContactRepository::new(Arc::clone(&db)),
+            audit: AuditService::new(Arc::clone(&db)),
+            webhooks: WebhookService::new(db),
+            feed,
+            timeline,
+        }
+    }
+
+    pub async fn list(&self, workspace_id: &str) -> AppResult<Vec<ImportJobResponse>> {
+        let jobs = self.imports.find_jobs(workspace_id).await?;
+        Ok(jobs.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get(&self, workspace_id: &str, id: &str) -> AppResult<ImportJobResponse> {
+        self.imports
+            .find_job(workspace_id, id)
+            .await?
+            .map(Into::into)
+            .ok_or_else(|| AppError::NotFound("Import job not found".into()))
+    }
+
+    /// Start importing a HubSpot portal, from its API or an export
+    ///
+    /// Returns the job right away; the import runs in the background.
+    pub async fn start_hubspot(
+        self: &Arc<Self>,
+        actor: &AuthenticatedUser,
+        req: HubspotImportRequest,
+    ) -> AppResult<ImportJobResponse> {
+        let input = match (req.api_key.filter(|k| !k.trim().is_empty()), req.export) {
+            (Some(_), Some(_)) => {
+                return Err(AppError::BadRequest("Give either an API key or an export, not both".into()));
+            }
+            (Some(api_key), None) => BatchInput::Hubspot(HubspotClient::new(&api_key)?),
+            (None, Some(export)) => BatchInput::Ready(hubspot::parse_export(export)?),
+            (None, None) => {
+                return Err(AppError::Validation("An API key or an export is required".into()));
+            }
+        };
+
+        self.start(actor, ImportSource::Hubspot, req.mode, input).await
+    }
+
+    /// Start importing a Pipedrive account, from its API or an export
+    ///
+    /// Returns the job right away; the import runs in the background.
+    pub async fn start_pipedrive(
+        self: &Arc<Self>,
+        actor: &AuthenticatedUser,
+        req: PipedriveImportRequest,
+    ) -> AppResult<ImportJobResponse> {
+        let input = match (req.api_token.filter(|t| !t.trim().is_empty()), req.export) {
+            (Some(_), Some(_)) => {
+                return Err(AppError::BadRequest("Give either an API token or an export, not both".into()));
+            }
+            (Some(api_token), None) => BatchInput::Pipedrive(PipedriveClient::new(&api_token)?),
+            (None, Some(export)) => BatchInput::Ready(pipedrive::parse_export(export)?),
+            (None, None) => {
+                return Err(AppError::Validation("An API token or an export is required".into()));
+            }
+        };
+
+        self.start(actor, ImportSource::Pipedrive, req.mode, input).await
+    }
+
+    async fn start(
+        self: &Arc<Self>,
+        actor: &AuthenticatedUser,
+        source: ImportSource,
+        mode: ImportMode,
+        input: BatchInput,
+    ) -> AppResult<ImportJobResponse> {
+        let now = Utc::now();
+        let job = self
+            .imports
+            .start_job(
+                ImportJob {
+                    id: None,
+                    workspace: workspace_thing(&actor.workspace_id),
+                    source,
+                    mode,
+                    status: ImportStatus::Running,
+                    progress: ImportProgress::default(),
+                    failures: Vec::new(),
+                    error: None,
+                    started_by: actor.user_id.clone(),
+                    started_at: now,
+                    finished_at: None,
+                    running_key: None,
+                    lease_expires_at: Some(lease_expiry(now)),
+                },
+                now,
+            )
+            .await?
+            .ok_or_else(|| AppError::Conflict(format!("A {} import is already running", source.label())))?;
+
+        let response = ImportJobResponse::from(job.clone());
+        let service = Arc::clone(self);
+        let run = ImportRun {
+            job,
+            actor: actor.clone(),
+            refs: HashMap::new(),
+        };
+        tokio::spawn(async move { service.run(run, input).await });
+
+        Ok(response)
+    }
+
+    /// Keep a job's lease while it runs, including through long fetches
+    fn spawn_lease_renewal(&self, job: Thing) -> JoinHandle<()> {
+        let imports = self.imports.clone();
+        let period = std::time::Duration::from_secs(LEASE_SECS as u64 / 5);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(e) = imports.renew_lease(&job, lease_expiry(Utc::now())).await {
+                    tracing::warn!("Failed to renew the lease of import job {}: {}", job, e);
+                }
+            }
+        })
+    }
+
+    /// Run an import to the end, recording how it finished on the job
+    async fn run(&self, mut run: ImportRun, input: BatchInput) {
+        let renewal = run.job.id.clone().map(|id| self.spawn_lease_renewal(id));
+        let batch = match input {
+            BatchInput::Hubspot(client) => client.fetch_all().await,
+            BatchInput::Pipedrive(client) => client.fetch_all().await,
+            BatchInput::Ready(batch) => Ok(batch),
+        };
+        let result = match batch {
+            Ok(batch) => self.import_batch(&mut run, batch).await,
+            Err(e) => Err(e),
+        };
+        if let Some(renewal) = renewal {
+            renewal.abort();
+        }
+
+        match result {
+            Ok(()) => run.job.status = ImportStatus::Completed,
+            Err(e) => {
+                tracing::error!("Import for workspace {} failed: {}", run.workspace_id(), e);
+                run.job.status = ImportStatus::Failed;
+                run.job.error = Some(e.to_string());
+            }
+        }
+        run.job.finished_at = Some(Utc::now());
+        if let Err(e) = self.imports.finish_job(&run.job).await {
+            tracing::error!("Failed to save the end of an import job: {}", e);
+        }
+    }
+
+    async fn import_batch(&self, run: &mut ImportRun, batch: ImportBatch) -> AppResult<()> {
+        let progress = &mut run.job.progress;
+        progress.companies.total = batch.companies.len() as u32;
+        progress.contacts.total = batch.contacts.len() as u32;
+        progress.deals.total = batch.deals.len() as u32;
+        progress.notes.total = batch.notes.len() as u32;
+        self.save_progress(run).await?;
+
+        let source = run.job.source;
+        for kind in [RecordKind::Company, RecordKind::Contact, RecordKind::Deal, RecordKind::Note] {
+            let refs = self.imports.refs(run.workspace_id(), source, kind.as_str()).await?;
+            run.refs.insert(kind.as_str(), refs);
+        }
+
+        for company in &batch.companies {
+            let result = self.import_company(run, company).await;
+            self.tally(run, RecordKind::Company, &company.external_id, result).await?;
+        }
+        for contact in &batch.contacts {
+            let result = self.import_contact(run, contact).await;
+            self.tally(run, RecordKind::Contact, &contact.external_id, result).await?;
+        }
+        for deal in &batch.deals {
+            let result = self.import_deal(run, deal).await;
+            self.tally(run, RecordKind::Deal, &deal.external_id, result).await?;
+        }
+        for note in &batch.notes {
+            let result = self.import_note(run, note).await;
+            self.tally(run, RecordKind::Note, &note.external_id, result).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Count one record's outcome, remember its ref and save progress now and then
+    async fn tally(
+        &self,
+        run: &mut ImportRun,
+        kind: RecordKind,
+        external_id: &str,
+        result: AppResult<Outcome>,
+    ) -> AppResult<()> {
+        let counts = kind.counts(&mut run.job.progress);
+        let record_id = match result {
+            Ok(Outcome::Created(id)) => {
+                counts.created += 1;
+                Some(id)
+            }
+            Ok(Outcome::Updated(id)) => {
+                counts.updated += 1;
+                Some(id)
+            }
+            Ok(Outcome::Skipped(id)) => {
+                counts.skipped += 1;
+                Some(id)
+            }
+            Err(e) => {
+                counts.failed += 1;
+                if run.job.failures.len() < MAX_FAILURES {
+                    run.job.failures.push(ImportFailure {
+                        kind: kind.as_str().to_string(),
+                        external_id: external_id.to_string(),
+                        reason: e.to_string(),
+                    });
+                }
+                None
+            }
+        };
+        let processed = kind.counts(&mut run.job.progress).processed();
+
+        if let Some(record_id) = record_id
+            && run.ref_id(kind, external_id) != Some(&record_id)
+        {
+            self.imports
+                .save_ref(run.workspace_id(), run.job.source, kind.as_str(), external_id, &record_id)
+                .await?;
+            run.refs
+                .entry(kind.as_str())
+                .or_default()
+                .insert(external_id.to_string(), record_id);
+        }
+
+        if processed.is_multiple_of(PROGRESS_INTERVAL) {
+            self.save_progress(run).await?;
+        }
+        Ok(())
+    }
+
+    /// Save progress and renew the lease; an import that lost its lease stops
+    async fn save_progress(&self, run: &ImportRun) -> AppResult<()> {
+        if !self.imports.save_progress(&run.job, lease_expiry(Utc::now())).await? {
+            return Err(AppError::Conflict("The import lost its lease and was stopped".into()));
+        }
+        Ok(())
+    }
+
+    async fn import_company(&self, run: &ImportRun, imported: &ImportedCompany) -> AppResult<Outcome> {
+        if imported.name.is_empty() {
+            return Err(AppError::Validation("Company has neither a name nor a domain".into()));
+        }
+        let workspace_id = run.workspace_id();
+
+        let mut existing_id = None;
+        if let Some(id) = run.ref_id(RecordKind::Company, &imported.external_id) {
+            existing_id = self.imports.find_company(workspace_id, id).await?.map(|_| id.clone());
+        }
+        if existing_id.is_none()
+            && let Some(domain) = &imported.domain
+        {
+            existing_id = self.imports.find_company_id_by_domain(workspace_id, domain).await?;
+        }
+
+        let now = Utc::now();
+        let Some(id) = existing_id else {
+            let company = self
+                .imports
+                .create_company(Company {
+                    id: None,
+                    workspace: workspace_thing(workspace_id),
+                    name: imported.name.clone(),
+                    domain: imported.domain.clone(),
+                    industry: imported.industry.clone(),
+                    size: imported.size.clone(),
+                    tags: Vec::new(),
+                    country: None,
+                    region: None,
+                    city: None,
+                    timezone: None,
+                    created_at: now,
+                    updated_at: now,
+                })
+                .await?;
+            let id = company.id.as_ref().map(|t| t.id.to_raw()).unwrap_or_default();
+            self.audit
+                .record_create(&run.actor, AuditEntity::Company, &id, &company)
+                .await;
+            return Ok(Outcome::Created(id));
+        };
+        if run.job.mode == ImportMode::Create {
+            return Ok(Outcome::Skipped(id));
+        }
+
+        let before = self
+            .imports
+            .find_company(workspace_id, &id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Company '{}' not found", id)))?;
+        let Some(company) = merged_company(&before, imported, now) else {
+            return Ok(Outcome::Skipped(id));
+        };
+
+        let company = self.imports.update_company(&id, company).await?;
+        self.audit
+            .record_update(&run.actor, AuditEntity::Company, &id, &before, &company)
+            .await;
+        Ok(Outcome::Updated(id))
+    }
+
+    async fn import_contact(&self, run: &ImportRun, imported: &ImportedContact) -> AppResult<Outcome> {
+        let email = imported
+            .email
+            .as_deref()
+            .ok_or_else(|| AppError::Validation("Contact has no email address".into()))?;
+        let workspace_id = run.workspace_id();
+        let company_id = imported
+            .company_external_id
+            .as_deref()
+            .and_then(|external_id| run.ref_id(RecordKind::Company, external_id));
+
+        let mut existing = None;
+        if let Some(id) = run.ref_id(RecordKind::Contact, &imported.external_id) {
+            existing = self.contacts.find_by_id_with_id(workspace_id, id).await?;
+        }
+        if existing.is_none()
+            && let Some(id) = self.contacts.find_other_id_by_email(workspace_id, email, None).await?
+        {
+            // The email is taken even while its contact sits in the trash
+            existing = Some(
+                self.contacts
+                    .find_by_id_with_id(workspace_id, &id)
+                    .await?
+                    .ok_or_else(|| AppError::Conflict(format!("Contact {} is in the trash", email)))?,
+            );
+        }
+
+        let Some(existing) = existing else {
+            let (first_name, last_name) = sender_name(None, email);
+            let mut builder = ContactBuilder::new()
+                .first_name(imported.first_name.as_deref().unwrap_or(&first_name))
+                .last_name(imported.last_name.as_deref().unwrap_or(&last_name))
+                .email(email)
+                .tag(run.job.source.as_str())
+                .status(imported.status);
+            if let Some(phone) = &imported.phone {
+                builder = builder.phone(phone);
+            }
+            if let Some(job_title) = &imported.job_title {
+                builder = builder.custom_field(JOB_TITLE_FIELD, job_title);
+            }
+            if let Some(company_id) = company_id {
+                builder = builder.company_id(company_id);
+            }
+
+            let stored = self.contacts.create_with_id(workspace_id, &builder.build()?).await?;
+            self.audit
+                .record_create(&run.actor, AuditEntity::Contact, &stored.id, &stored.contact)
+                .await;
+            let response = ContactResponse::from_stored(stored.clone());
+            self.webhooks
+                .notify(
+                    workspace_id,
+                    WebhookEvent::ContactCreated,
+                    serde_json::to_value(&response).unwrap_or_default(),
+                )
+                .await;
+            self.feed.publish(workspace_id, FeedEvent::ContactCreated(response));
+            return Ok(Outcome::Created(stored.id));
+        };
+        if run.job.mode == ImportMode::Create {
+            return Ok(Outcome::Skipped(existing.id));
+        }
+
+        let mut updater = ContactUpdater::new(existing.contact.clone());
+        if let Some(first_name) = &imported.first_name {
+            updater = updater.first_name(first_name)?;
+        }
+        if let Some(last_name) = &imported.last_name {
+            updater = updater.last_name(last_name)?;
+        }
+        if imported.phone.is_some() {
+            updater = updater.phone(imported.phone.as_deref())?;
+        }
+        if let Some(job_title) = &imported.job_title {
+            let changes = BTreeMap::from([(JOB_TITLE_FIELD.to_string(), Some(job_title.clone()))]);
+            updater = updater.custom_fields(&changes)?;
+        }
+        if company_id.is_some() {
+            updater = updater.company_id(company_id.map(String::as_str));
+        }
+        if updater.modified_fields().is_empty() {
+            return Ok(Outcome::Skipped(existing.id));
+        }
+
+        let contact = self.contacts.update(workspace_id, &existing.id, &updater.apply()?).await?;
+        self.audit
+            .record_update(&run.actor, AuditEntity::Contact, &existing.id, &existing.contact, &contact)
+            .await;
+        Ok(Outcome::Updated(existing.id))
+    }
+
+    async fn import_deal(&self, run: &ImportRun, imported: &ImportedDeal) -> AppResult<Outcome> {
+        validate_deal(&imported.name, imported.value)?;
+        let workspace_id = run.workspace_id();
+        let linked = |kind: RecordKind, external_id: &Option<String>| {
+            external_id
+                .as_deref()
+                .and_then(|external_id| run.ref_id(kind, external_id))
+                .map(|id| Thing::from((kind.as_str(), id.as_str())))
+        };
+        let contact = linked(RecordKind::Contact, &imported.contact_external_id);
+        let company = linked(RecordKind::Company, &imported.company_external_id);
+
+        let mut existing = None;
+        if let Some(id) = run.ref_id(RecordKind::Deal, &imported.external_id) {
+            existing = self.imports.find_deal(workspace_id, id).await?.map(|deal| (id.clone(), deal));
+        }
+
+        let now = Utc::now();
+        let Some((id, before)) = existing else {
+            let deal = self
+                .imports
+                .create_deal(Deal {
+                    id: None,
+                    workspace: workspace_thing(workspace_id),
+                    name: imported.name.trim().to_string(),
+                    value: imported.value,
+                    stage: imported.stage,
+                    expected_close_date: imported.close_date,
+                    closed_at: closed_at(imported, None, now),
+                    win_loss_reason: None,
+                    contact,
+                    company,
+                    notes: None,
+                    pipeline_position: None,
+                    created_at: now,
+                    updated_at: now,
+                })
+                .await?;
+            let id = deal.id.as_ref().map(|t| t.id.to_raw()).unwrap_or_default();
+            self.audit.record_create(&run.actor, AuditEntity::Deal, &id, &deal).await;
+            return Ok(Outcome::Created(id));
+        };
+        if run.job.mode == ImportMode::Create {
+            return Ok(Outcome::Skipped(id));
+        }
+
+        let Some(deal) = merged_deal(&before, imported, contact, company, now) else {
+            return Ok(Outcome::Skipped(id));
+        };
+
+        let deal = self.imports.update_deal(&id, deal).await?;
+        self.audit
+            .record_update(&run.actor, AuditEntity::Deal, &id, &before, &deal)
+            .await;
+        Ok(Outcome::Updated(id))
+    }
+
+    /// Put a note on the timeline of each imported contact it's attached to
+    ///
+    /// Notes are history, so they don't go out on the live feed (see
+    /// `TimelineService::import`). A note imported before is skipped in
+    /// either mode.
+    async fn import_note(&self, run: &ImportRun, imported: &ImportedNote) -> AppResult<Outcome> {
+        if let Some(id) = run.ref_id(RecordKind::Note, &imported.external_id) {
+            return Ok(Outcome::Skipped(id.clone()));
+        }
+        if imported.body.is_empty() {
+            return Err(AppError::Validation("Note is empty".into()));
+        }
+        let contact_ids: Vec<&String> = imported
+            .contact_external_ids
+            .iter()
+            .filter_map(|external_id| run.ref_id(RecordKind::Contact, external_id))
+            .collect();
+        if contact_ids.is_empty() {
+            return Err(AppError::Validation("Note isn't attached to an imported contact".into()));
+        }
+
+        let mut first_entry_id = None;
+        for contact_id in contact_ids {
+            let entry = self
+                .timeline
+                .import(run.workspace_id(), note_entry(run.workspace_id(), contact_id, run.job.source, imported))
+                .await?;
+            first_entry_id.get_or_insert(entry.id.map(|t| t.id.to_raw()).unwrap_or_default());
+        }
+
+        Ok(Outcome::Created(first_entry_id.unwrap_or_default()))
+    }
+}
+
+fn lease_expiry(now: DateTime<Utc>) -> DateTime<Utc> {
+    now + chrono::Duration::seconds(LEASE_SECS)
+}
+
+/// A company updated with what the other CRM has; `None` when nothing changes
+fn merged_company(before: &Company, imported: &ImportedCompany, now: DateTime<Utc>) -> Option<Company> {
+    let mut company = before.clone();
+    company.name = imported.name.clone();
+    company.domain = imported.domain.clone().or(company.domain);
+    company.industry = imported.industry.clone().or(company.industry);
+    company.size = imported.size.clone().or(company.size);
+    if (&company.name, &company.domain, &company.industry, &company.size)
+        == (&before.name, &before.domain, &before.industry, &before.size)
+    {
+        return None;
+    }
+
+    company.updated_at = now;
+    Some(company)
+}
+
+/// When an imported deal closed; closed deals keep the time they first closed here
+fn closed_at(
+    imported: &ImportedDeal,
+    previous: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    imported
+        .stage
+        .is_closed()
+        .then(|| previous.or(imported.close_date).unwrap_or(now))
+}
+
+/// A deal updated with what the other CRM has; `None` when nothing changes
+///
+/// A reason given for winning or losing goes when the outcome changes.
+fn merged_deal(
+    before: &Deal,
+    imported: &ImportedDeal,
+    contact: Option<Thing>,
+    company: Option<Thing>,
+    now: DateTime<Utc>,
+) -> Option<Deal> {
+    let mut deal = before.clone();
+    deal.name = imported.name.trim().to_string();
+    deal.value = imported.value;
+    deal.stage = imported.stage;
+    deal.expected_close_date = imported.close_date.or(deal.expected_close_date);
+    deal.closed_at = closed_at(imported, deal.closed_at, now);
+    if deal_outcome(deal.stage) != deal_outcome(before.stage) {
+        deal.win_loss_reason = None;
+    }
+    deal.contact = contact.or(deal.contact);
+    deal.company = company.or(deal.company);
+    if serde_json::to_value(&deal).ok() == serde_json::to_value(before).ok() {
+        return None;
+    }
+
+    deal.updated_at = now;
+    Some(deal)
+}
+
+/// The timeline entry an imported note becomes on one contact's timeline
+fn note_entry(workspace_id: &str, contact_id: &str, source: ImportSource, imported: &ImportedNote) -> TimelineEntry {
+    TimelineEntry {
+        id: None,
+        workspace: workspace_thing(workspace_id),
+        contact: Thing::from(("contact", contact_id)),
+        company: None,
+        campaign: None,
+        entry_type: TimelineEntryType::Note,
+        content: imported.body.clone(),
+        metadata: serde_json::json!({
+            IMPORT_SOURCE_KEY: source,
+            "external_id": imported.external_id,
+        }),
+        attachments: Vec::new(),
+        sentiment: None,
+        timestamp: imported.timestamp.unwrap_or_else(Utc::now),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{DealStage, WinLossReason};
+
+    fn company() -> Company {
+        let created: DateTime<Utc> = "2026-01-10T09:00:00Z".parse().unwrap();
+        Company {
+            id: Some(Thing::from(("company", "acme"))),
+            workspace: workspace_thing("ws1"),
+            name: "Acme".into(),
+            domain: Some("acme.com".into()),
+            industry: Some("Software".into()),
+            size: None,
+            tags: Vec::new(),
+            country: None,
+            region: None,
+            city: None,
+            timezone: None,
+            created_at: created,
+            updated_at: created,
+        }
+    }
+
+    fn imported_deal(stage: DealStage) -> ImportedDeal {
+        ImportedDeal {
+            external_id: "20".into(),
+            name: "Renewal".into(),
+            value: 1200.0,
+            stage,
+            close_date: None,
+            contact_external_id: None,
+            company_external_id: None,
+        }
+    }
+
+    fn deal(stage: DealStage) -> Deal {
+        let created: DateTime<Utc> = "2026-01-10T09:00:00Z".parse().unwrap();
+        Deal {
+            id: Some(Thing::from(("deal", "renewal"))),
+            workspace: workspace_thing("ws1"),
+            name: "Renewal".into(),
+            value: 1200.0,
+            stage,
+            expected_close_date: None,
+            closed_at: stage.is_closed().then_some(created),
+            win_loss_reason: Some(WinLossReason::Price),
+            contact: None,
+            company: None,
+            notes: None,
+            pipeline_position: None,
+            created_at: created,
+            updated_at: created,
+        }
+    }
+
+    #[test]
+    fn test_merged_company_keeps_what_the_import_lacks() {
+        let now = Utc::now();
+        let imported = ImportedCompany {
+            external_id: "10".into(),
+            name: "Acme".into(),
+            domain: None,
+            industry: None,
+            size: Some("50".into()),
+        };
+
+        let merged = merged_company(&company(), &imported, now).unwrap();
+        assert_eq!(merged.domain.as_deref(), Some("acme.com"));
+        assert_eq!(merged.industry.as_deref(), Some("Software"));
+        assert_eq!(merged.size.as_deref(), Some("50"));
+        assert_eq!(merged.updated_at, now);
+
+        let unchanged = ImportedCompany { size: None, ..imported };
+        assert!(merged_company(&company(), &unchanged, now).is_none());
+    }
+
+    #[test]
+    fn test_merged_deal() {
+        let now = Utc::now();
+        let won = deal(DealStage::ClosedWon);
+
+        assert!(merged_deal(&won, &imported_deal(DealStage::ClosedWon), None, None, now).is_none());
+
+        // Reopened: no longer closed, and the reason for winning no longer applies
+        let reopened = merged_deal(&won, &imported_deal(DealStage::Negotiation), None, None, now).unwrap();
+        assert_eq!(reopened.closed_at, None);
+        assert_eq!(reopened.win_loss_reason, None);
+        assert_eq!(reopened.updated_at, now);
+
+        let closed = merged_deal(&deal(DealStage::Proposal), &imported_deal(DealStage::ClosedLost), None, None, now);
+        assert_eq!(closed.unwrap().closed_at, Some(now));
+    }
+
+    #[test]
+    fn test_note_entry_is_marked_as_imported() {
+        let note = ImportedNote {
+            external_id: "30".into(),
+            body: "Called".into(),
+            timestamp: None,
+            contact_external_ids: vec!["1".into()],
+        };
+
+        let entry = note_entry("ws1", "c1", ImportSource::Pipedrive, &note);
+        assert_eq!(entry.contact, Thing::from(("contact", "c1")));
+        assert!(matches!(entry.entry_type, TimelineEntryType::Note));
+        assert_eq!(entry.metadata[IMPORT_SOURCE_KEY], "pipedrive");
+        assert_eq!(entry.metadata["external_id"], "30");
+    }
+
+    #[test]
+    fn test_lease_outlasts_its_renewal_period() {
+        let now = Utc::now();
+        assert!(lease_expiry(now) - now > chrono::Duration::seconds(LEASE_SECS / 5));
+    }
+}
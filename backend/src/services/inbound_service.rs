@@ -0,0 +1,243 @@
+//! Inbound Service - contacts and activity pushed in by external tools
+//!
+//! Tools like Typeform, Calendly or Stripe (directly or through Zapier or
+//! Make) POST JSON to a source's secret URL. The source's mapping picks
+//! the contact fields out of the payload: the email finds the contact or
+//! creates a lead, mapped fields update it, and the payload itself lands
+//! on the contact's timeline as an `external_event` entry.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::db::{workspace_thing, Database};
+use crate::domain::{sender_name, ContactBuilder, ContactStatus, ContactUpdater, DomainError, WebhookEvent};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    ContactResponse, CreateInboundSourceRequest, FeedEvent, InboundIngestResponse, InboundSource,
+    InboundSourceResponse, TimelineEntryType, UpdateInboundSourceRequest,
+};
+use crate::repositories::{ContactRepository, InboundSourceRepository, StoredContact};
+use crate::services::{new_entry, FeedService, TimelineService, WebhookService};
+
+/// Longest name a source may have
+const MAX_NAME_LEN: usize = 100;
+
+pub struct InboundService {
+    sources: InboundSourceRepository,
+    contacts: ContactRepository,
+    webhooks: WebhookService,
+    feed: Arc<FeedService>,
+    timeline: Arc<TimelineService>,
+}
+
+impl InboundService {
+    pub fn new(db: Arc<Database>, feed: Arc<FeedService>, timeline: Arc<TimelineService>) -> Self {
+        Self {
+            sources: InboundSourceRepository::new(Arc::clone(&db)),
+            contacts: ContactRepository::new(Arc::clone(&db)),
+            webhooks: WebhookService::new(db),
+            feed,
+            timeline,
+        }
+    }
+
+    pub async fn list(&self, workspace_id: &str) -> AppResult<Vec<InboundSourceResponse>> {
+        let sources = self.sources.find_all(workspace_id).await?;
+        Ok(sources.into_iter().map(Into::into).collect())
+    }
+
+    /// Create a source with a fresh ingestion token
+    pub async fn create(
+        &self,
+        workspace_id: &str,
+        req: CreateInboundSourceRequest,
+    ) -> AppResult<InboundSourceResponse> {
+        let name = validate_name(&req.name)?;
+        req.mapping.validate()?;
+
+        let now = Utc::now();
+        let source = self
+            .sources
+            .create(InboundSource {
+                id: None,
+                workspace: workspace_thing(workspace_id),
+                name,
+                token: generate_token(),
+                mapping: req.mapping,
+                active: true,
+                last_received_at: None,
+                created_at: now,
+                updated_at: now,
+            })
+            .await?;
+
+        Ok(source.into())
+    }
+
+    pub async fn update(
+        &self,
+        workspace_id: &str,
+        id: &str,
+        req: UpdateInboundSourceRequest,
+    ) -> AppResult<InboundSourceResponse> {
+        let mut source = self
+            .sources
+            .find_by_id(workspace_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Inbound source not found".into()))?;
+
+        if let Some(name) = req.name {
+            source.name = validate_name(&name)?;
+        }
+        if let Some(mapping) = req.mapping {
+            mapping.validate()?;
+            source.mapping = mapping;
+        }
+        if let Some(active) = req.active {
+            source.active = active;
+        }
+        source.updated_at = Utc::now();
+
+        Ok(self.sources.update(id, source).await?.into())
+    }
+
+    /// Delete a source; its URL stops working, what it brought in stays
+    pub async fn delete(&self, workspace_id: &str, id: &str) -> AppResult<()> {
+        if !self.sources.delete(workspace_id, id).await? {
+            return Err(AppError::NotFound("Inbound source not found".into()));
+        }
+        Ok(())
+    }
+
+    /// Take in one payload posted to a source's URL
+    pub async fn ingest(&self, token: &str, payload: serde_json::Value) -> AppResult<InboundIngestResponse> {
+        let source = self
+            .sources
+            .find_by_token(token)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Inbound source not found".into()))?;
+        if !source.active {
+            return Err(AppError::Conflict("Inbound source is disabled".into()));
+        }
+
+        let workspace_id = source.workspace.id.to_raw();
+        let mapped = source.mapping.apply(&payload)?;
+
+        let (stored, created) = match self.contacts.find_other_id_by_email(&workspace_id, &mapped.email, None).await? {
+            Some(id) => {
+                // The email is taken even while its contact sits in the trash
+                let existing = self
+                    .contacts
+                    .find_by_id_with_id(&workspace_id, &id)
+                    .await?
+                    .ok_or_else(|| AppError::Conflict(format!("Contact {} is in the trash", mapped.email)))?;
+
+                let mut updater = ContactUpdater::new(existing.contact.clone());
+                if let Some(first_name) = &mapped.first_name {
+                    updater = updater.first_name(first_name)?;
+                }
+                if let Some(last_name) = &mapped.last_name {
+                    updater = updater.last_name(last_name)?;
+                }
+                if mapped.phone.is_some() {
+                    updater = updater.phone(mapped.phone.as_deref())?;
+                }
+                if !mapped.custom_fields.is_empty() {
+                    let changes: BTreeMap<String, Option<String>> = mapped
+                        .custom_fields
+                        .iter()
+                        .map(|(k, v)| (k.clone(), Some(v.clone())))
+                        .collect();
+                    updater = updater.custom_fields(&changes)?;
+                }
+                updater = source.mapping.tags.iter().try_fold(updater, |u, tag| u.add_tag(tag))?;
+
+                if updater.modified_fields().is_empty() {
+                    (existing, false)
+                } else {
+                    let contact = self.contacts.update(&workspace_id, &id, &updater.apply()?).await?;
+                    (StoredContact { id, contact }, false)
+                }
+            }
+            None => {
+                // Tools don't always send a name; make one up from the address
+                let (first_name, last_name) = sender_name(None, &mapped.email);
+                let mut builder = ContactBuilder::new()
+                    .first_name(mapped.first_name.as_deref().unwrap_or(&first_name))
+                    .last_name(mapped.last_name.as_deref().unwrap_or(&last_name))
+                    .email(&mapped.email)
+                    .tags(source.mapping.tags.clone())
+                    .status(ContactStatus::Lead);
+                if let Some(phone) = &mapped.phone {
+                    builder = builder.phone(phone);
+                }
+                for (key, value) in &mapped.custom_fields {
+                    builder = builder.custom_field(key, value);
+                }
+
+                let stored = self.contacts.create_with_id(&workspace_id, &builder.build()?).await?;
+                let response = ContactResponse::from_stored(stored.clone());
+                self.webhooks
+                    .notify(
+                        &workspace_id,
+                        WebhookEvent::ContactCreated,
+                        serde_json::to_value(&response).unwrap_or_default(),
+                    )
+                    .await;
+                self.feed.publish(&workspace_id, FeedEvent::ContactCreated(response));
+                (stored, true)
+            }
+        };
+
+        let now = Utc::now();
+        let content = match &mapped.summary {
+            Some(summary) => format!("{}: {}", source.name, summary),
+            None => format!("Received from {}", source.name),
+        };
+        let source_id = source.id.as_ref().map(|t| t.id.to_raw()).unwrap_or_default();
+        let metadata = serde_json::json!({
+            "source_id": source_id,
+            "source_name": source.name,
+            "summary": mapped.summary,
+            "payload": payload,
+        });
+        let mut entry = new_entry(&workspace_id, &stored.id, TimelineEntryType::ExternalEvent, content, metadata);
+        entry.timestamp = now;
+        let entry = self.timeline.record(&workspace_id, entry).await?;
+
+        if let Some(source_thing) = &source.id {
+            if let Err(e) = self.sources.touch(source_thing, now).await {
+                tracing::warn!("Failed to stamp inbound source {}: {}", source_id, e);
+            }
+        }
+
+        Ok(InboundIngestResponse {
+            contact_id: stored.id,
+            created,
+            timeline_entry_id: entry.id.map(|t| t.id.to_raw()).unwrap_or_default(),
+        })
+    }
+}
+
+fn validate_name(name: &str) -> AppResult<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(DomainError::RequiredFieldMissing { field: "name".to_string() }.into());
+    }
+    if name.chars().count() > MAX_NAME_LEN {
+        return Err(DomainError::InvalidField {
+            field: "name".to_string(),
+            reason: format!("Must be at most {} characters", MAX_NAME_LEN),
+        }
+        .into());
+    }
+    Ok(name.to_string())
+}
+
+/// A new token for an inbound source's endpoint
+pub fn generate_token() -> String {
+    format!("in_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
@@ -0,0 +1,594 @@
+//! Inbox Service - syncing connected mailboxes onto contact timelines
+//!
+//! Each connected mailbox is polled in the background (and on demand).
+//! Messages from a known contact become `email_received` entries on their
+//! timeline with the subject and a snippet; messages from unknown senders
+//! are skipped, or create a lead when the integration is set up to.
+//!
+//! A message from a contact ends their active sequence enrollments and
+//! marks the campaign emails it answers as replied to.
+//!
+//! Syncs resume from a per-mailbox cursor, and message IDs already on a
+//! timeline are skipped, so overlapping syncs never record a message twice.
+//! Passwords and OAuth tokens are stored encrypted.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::config::InboxConfig;
+use crate::db::{workspace_thing, Database};
+use crate::domain::{sender_name, validate_imap_login, ContactBuilder, ContactStatus, InboundEmail};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    CreateGmailIntegrationRequest, CreateImapIntegrationRequest, GmailAuthorizationResponse, ImapSettings,
+    InboxSyncResponse, Integration, IntegrationProvider, IntegrationResponse, IntegrationStatus,
+    OAuthCallbackQuery, OAuthTokens, TimelineEntry, TimelineEntryType, UpdateIntegrationRequest,
+};
+use crate::repositories::{ContactRepository, IntegrationRepository};
+use crate::services::credential_cipher::{is_sealed, CredentialCipher};
+use crate::services::mailbox::{fetch_imap, FetchedMail, GmailClient};
+use crate::services::TimelineService;
+use crate::shutdown::Shutdown;
+
+/// Tag given to contacts created from an unknown sender
+pub const INBOUND_EMAIL_TAG: &str = "inbound-email";
+
+const DEFAULT_IMAP_PORT: u16 = 993;
+const DEFAULT_MAILBOX: &str = "INBOX";
+
+pub struct InboxService {
+    integrations: IntegrationRepository,
+    contacts: ContactRepository,
+    timeline: Arc<TimelineService>,
+    gmail: Option<GmailClient>,
+    cipher: Option<Arc<CredentialCipher>>,
+    max_messages: u32,
+    initial_sync_days: i64,
+    timeout: Duration,
+}
+
+impl InboxService {
+    pub fn new(
+        db: Arc<Database>,
+        timeline: Arc<TimelineService>,
+        gmail: Option<GmailClient>,
+        cipher: Option<Arc<CredentialCipher>>,
+        config: &InboxConfig,
+    ) -> Self {
+        Self {
+            integrations: IntegrationRepository::new(Arc::clone(&db)),
+            contacts: ContactRepository::new(db),
+            timeline,
+            gmail,
+            cipher,
+            max_messages: config.max_messages_per_sync.max(1),
+            initial_sync_days: config.initial_sync_days.into(),
+            timeout: Duration::from_secs(config.request_timeout_secs),
+        }
+    }
+
+    pub async fn list(&self, workspace_id: &str) -> AppResult<Vec<IntegrationResponse>> {
+        let integrations = self.integrations.find_all(workspace_id).await?;
+        Ok(integrations.into_iter().map(Into::into).collect())
+    }
+
+    /// Connect an IMAP mailbox
+    ///
+    /// The login is checked against the server before it is stored.
+    pub async fn create_imap(
+        &self,
+        workspace_id: &str,
+        req: CreateImapIntegrationRequest,
+    ) -> AppResult<IntegrationResponse> {
+        let port = req.port.unwrap_or(DEFAULT_IMAP_PORT);
+        validate_imap_login(&req.host, port, &req.username, &req.password)?;
+        let cipher = self.cipher()?;
+
+        let mut settings = ImapSettings {
+            host: req.host.trim().to_lowercase(),
+            port,
+            username: req.username.trim().to_string(),
+            password: req.password,
+            mailbox: req
+                .mailbox
+                .map(|m| m.trim().to_string())
+                .filter(|m| !m.is_empty())
+                .unwrap_or_else(|| DEFAULT_MAILBOX.to_string()),
+        };
+        // Reads at most one of today's messages; this only proves the login and mailbox work
+        fetch_imap(&settings, &Default::default(), Utc::now(), 1, self.timeout)
+            .await
+            .map_err(|e| AppError::BadRequest(format!("Could not connect to the mailbox: {}", e)))?;
+        settings.password = cipher.seal(&settings.password)?;
+
+        let now = Utc::now();
+        let integration = self
+            .integrations
+            .create(Integration {
+                id: None,
+                workspace: workspace_thing(workspace_id),
+                provider: IntegrationProvider::Imap,
+                account: settings.username.to_lowercase(),
+                status: IntegrationStatus::Active,
+                create_unknown_contacts: req.create_unknown_contacts,
+                imap: Some(settings),
+                oauth: None,
+                oauth_state: None,
+                cursor: Default::default(),
+                last_synced_at: None,
+                last_error: None,
+                created_at: now,
+                updated_at: now,
+            })
+            .await?;
+
+        Ok(integration.into())
+    }
+
+    /// Start connecting a Gmail account
+    ///
+    /// The integration stays pending until Google redirects back to the
+    /// OAuth callback.
+    pub async fn start_gmail(
+        &self,
+        workspace_id: &str,
+        req: CreateGmailIntegrationRequest,
+    ) -> AppResult<GmailAuthorizationResponse> {
+        let gmail = self.gmail_client()?;
+        self.cipher()?;
+
+        let state = Uuid::new_v4().to_string();
+        let authorization_url = gmail.authorization_url(&state)?;
+
+        let now = Utc::now();
+        let integration = self
+            .integrations
+            .create(Integration {
+                id: None,
+                workspace: workspace_thing(workspace_id),
+                provider: IntegrationProvider::Gmail,
+                account: String::new(),
+                status: IntegrationStatus::Pending,
+                create_unknown_contacts: req.create_unknown_contacts,
+                imap: None,
+                oauth: None,
+                oauth_state: Some(state),
+                cursor: Default::default(),
+                last_synced_at: None,
+                last_error: None,
+                created_at: now,
+                updated_at: now,
+            })
+            .await?;
+
+        Ok(GmailAuthorizationResponse {
+            integration_id: integration.id.map(|t| t.id.to_raw()).unwrap_or_default(),
+            authorization_url,
+        })
+    }
+
+    /// Finish connecting a Gmail account with the code Google redirected back with
+    pub async fn complete_gmail(&self, query: OAuthCallbackQuery) -> AppResult<IntegrationResponse> {
+        let gmail = self.gmail_client()?;
+        let cipher = self.cipher()?;
+        let mut integration = self
+            .integrations
+            .find_by_oauth_state(&query.state)
+            .await?
+            .ok_or_else(|| AppError::NotFound("No pending Gmail connection for this request".into()))?;
+
+        let code = match (query.code, query.error) {
+            (Some(code), None) => code,
+            (_, error) => {
+                let reason = error.unwrap_or_else(|| "no authorization code".to_string());
+                if let Some(id) = integration.id.as_ref() {
+                    let workspace_id = integration.workspace.id.to_raw();
+                    self.integrations.delete(&workspace_id, &id.id.to_raw()).await?;
+                }
+                return Err(AppError::BadRequest(format!("Gmail access was not granted: {}", reason)));
+            }
+        };
+
+        let tokens = gmail.exchange_code(&code).await?;
+        integration.account = gmail.account(&tokens.access_token).await?;
+        integration.oauth = Some(seal_tokens(cipher, &tokens)?);
+
+        Ok(self.integrations.complete_oauth(&integration).await?.into())
+    }
+
+    pub async fn update(
+        &self,
+        workspace_id: &str,
+        id: &str,
+        req: UpdateIntegrationRequest,
+    ) -> AppResult<IntegrationResponse> {
+        let Some(create_unknown_contacts) = req.create_unknown_contacts else {
+            return Ok(self.find(workspace_id, id).await?.into());
+        };
+
+        self.integrations
+            .update_settings(workspace_id, id, create_unknown_contacts)
+            .await?
+            .map(Into::into)
+            .ok_or_else(|| AppError::NotFound("Integration not found".into()))
+    }
+
+    /// Disconnect a mailbox; what it synced stays on the timelines
+    pub async fn delete(&self, workspace_id: &str, id: &str) -> AppResult<()> {
+        if !self.integrations.delete(workspace_id, id).await? {
+            return Err(AppError::NotFound("Integration not found".into()));
+        }
+        Ok(())
+    }
+
+    /// Sync one mailbox now
+    pub async fn sync(&self, workspace_id: &str, id: &str) -> AppResult<InboxSyncResponse> {
+        let integration = self.find(workspace_id, id).await?;
+        if integration.status == IntegrationStatus::Pending {
+            return Err(AppError::Conflict("The mailbox has not finished connecting".into()));
+        }
+
+        self.sync_integration(integration).await
+    }
+
+    /// Sync every connected mailbox
+    ///
+    /// A mailbox that fails is marked as errored and the rest carry on.
+    pub async fn sync_all(&self) -> AppResult<InboxSyncResponse> {
+        let mut total = InboxSyncResponse::default();
+
+        for integration in self.integrations.find_syncable().await? {
+            let id = integration.id.clone();
+            match self.sync_integration(integration).await {
+                Ok(result) => {
+                    total.fetched += result.fetched;
+                    total.recorded += result.recorded;
+                    total.contacts_created += result.contacts_created;
+                    total.skipped += result.skipped;
+                }
+                Err(e) => tracing::warn!("Inbox sync of integration {:?} failed: {}", id, e),
+            }
+        }
+
+        Ok(total)
+    }
+
+    /// Run `sync_all` on a background task at the configured interval
+    pub fn spawn(self: Arc<Self>, config: &InboxConfig, shutdown: Shutdown) -> JoinHandle<()> {
+        let period = Duration::from_secs(config.sync_interval_secs.max(60));
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+
+            while shutdown.tick(&mut interval).await {
+                match self.sync_all().await {
+                    Ok(summary) if summary.recorded + summary.contacts_created > 0 => tracing::info!(
+                        "Inbox sync recorded {} emails and created {} contacts",
+                        summary.recorded,
+                        summary.contacts_created
+                    ),
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Inbox sync run failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Fetch, record and move the cursor; failures are stored on the integration
+    async fn sync_integration(&self, mut integration: Integration) -> AppResult<InboxSyncResponse> {
+        match self.fetch(&mut integration).await {
+            Ok(fetched) => {
+                let cursor = fetched.cursor.clone();
+                let result = self.record(&integration, fetched.messages).await?;
+
+                integration.cursor = cursor;
+                integration.status = IntegrationStatus::Active;
+                integration.last_error = None;
+                integration.last_synced_at = Some(Utc::now());
+                self.integrations.save_sync(&integration).await?;
+
+                Ok(result)
+            }
+            Err(e) => {
+                integration.status = IntegrationStatus::Error;
+                integration.last_error = Some(e.to_string());
+                self.integrations.save_sync(&integration).await?;
+
+                Err(e)
+            }
+        }
+    }
+
+    /// Read new messages from the mailbox, refreshing Gmail tokens first if due
+    ///
+    /// Credentials stored before they were encrypted are sealed on the way.
+    async fn fetch(&self, integration: &mut Integration) -> AppResult<FetchedMail> {
+        let cipher = self.cipher()?;
+        let since = Utc::now() - chrono::Duration::days(self.initial_sync_days);
+
+        match integration.provider {
+            IntegrationProvider::Imap => {
+                let stored = integration
+                    .imap
+                    .as_mut()
+                    .ok_or_else(|| AppError::Internal("IMAP integration has no login".into()))?;
+                let mut settings = stored.clone();
+                settings.password = cipher.open(&stored.password)?;
+                if !is_sealed(&stored.password) {
+                    stored.password = cipher.seal(&settings.password)?;
+                }
+
+                fetch_imap(&settings, &integration.cursor, since, self.max_messages, self.timeout).await
+            }
+            IntegrationProvider::Gmail => {
+                let gmail = self.gmail_client()?;
+                let stored = integration
+                    .oauth
+                    .as_ref()
+                    .ok_or_else(|| AppError::Internal("Gmail integration has no tokens".into()))?;
+                let mut tokens = open_tokens(cipher, stored)?;
+                if let Some(refreshed) = gmail.refresh(&tokens).await? {
+                    tokens = refreshed;
+                }
+                integration.oauth = Some(seal_tokens(cipher, &tokens)?);
+
+                gmail.fetch(&tokens.access_token, &integration.cursor, since, self.max_messages).await
+            }
+        }
+    }
+
+    /// Put fetched messages on their senders' timelines
+    async fn record(&self, integration: &Integration, messages: Vec<InboundEmail>) -> AppResult<InboxSyncResponse> {
+        let workspace_id = integration.workspace.id.to_raw();
+        let integration_id = integration.id.as_ref().map(|t| t.id.to_raw()).unwrap_or_default();
+        let mut result = InboxSyncResponse {
+            fetched: messages.len(),
+            ..Default::default()
+        };
+
+        let synced = self
+            .integrations
+            .synced_message_ids(&workspace_id, messages.iter().map(|m| m.message_id.clone()).collect())
+            .await?;
+        let messages = unsynced(messages, &synced, &integration.account);
+        result.skipped = result.fetched - messages.len();
+
+        let mut senders: Vec<String> = messages.iter().map(|m| m.from_email.clone()).collect();
+        senders.sort();
+        senders.dedup();
+        let mut contacts: HashMap<String, String> =
+            self.integrations.contact_ids_by_email(&workspace_id, senders).await?;
+        let mut recorded = Vec::new();
+
+        for message in messages {
+            let contact_id = match contacts.get(&message.from_email) {
+                Some(id) => id.clone(),
+                None if integration.create_unknown_contacts => {
+                    match self.create_sender(&workspace_id, &message).await? {
+                        Some(id) => {
+                            result.contacts_created += 1;
+                            contacts.insert(message.from_email.clone(), id.clone());
+                            id
+                        }
+                        None => {
+                            result.skipped += 1;
+                            continue;
+                        }
+                    }
+                }
+                None => {
+                    result.skipped += 1;
+                    continue;
+                }
+            };
+
+            let entry = received_entry(integration, &integration_id, &contact_id, &message);
+
+            // Another sync of the mailbox may have recorded it since the check above
+            let Some(entry) = self.timeline.store_synced(&workspace_id, entry, &message.message_id).await? else {
+                result.skipped += 1;
+                continue;
+            };
+            recorded.push(entry);
+        }
+        result.recorded = recorded.len();
+        self.timeline.recorded(&workspace_id, &recorded).await;
+
+        Ok(result)
+    }
+
+    /// Create a lead for an unknown sender
+    ///
+    /// `None` when the address belongs to a trashed contact or isn't one a
+    /// contact can have.
+    async fn create_sender(&self, workspace_id: &str, message: &InboundEmail) -> AppResult<Option<String>> {
+        if self.contacts.find_by_email(workspace_id, &message.from_email).await?.is_some() {
+            return Ok(None);
+        }
+
+        let (first_name, last_name) = sender_name(message.from_name.as_deref(), &message.from_email);
+        let contact = match ContactBuilder::new()
+            .first_name(&first_name)
+            .last_name(&last_name)
+            .email(&message.from_email)
+            .tag(INBOUND_EMAIL_TAG)
+            .status(ContactStatus::Lead)
+            .build()
+        {
+            Ok(contact) => contact,
+            Err(e) => {
+                tracing::debug!("Not creating a contact for sender {}: {}", message.from_email, e);
+                return Ok(None);
+            }
+        };
+
+        let stored = self.contacts.create_with_id(workspace_id, &contact).await?;
+        Ok(Some(stored.id))
+    }
+
+    async fn find(&self, workspace_id: &str, id: &str) -> AppResult<Integration> {
+        self.integrations
+            .find_by_id(workspace_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Integration not found".into()))
+    }
+
+    fn gmail_client(&self) -> AppResult<&GmailClient> {
+        self.gmail
+            .as_ref()
+            .ok_or_else(|| AppError::BadRequest("Gmail sync is not configured".into()))
+    }
+
+    fn cipher(&self) -> AppResult<&CredentialCipher> {
+        self.cipher
+            .as_deref()
+            .ok_or_else(|| AppError::BadRequest("No key is configured to encrypt mailbox credentials".into()))
+    }
+}
+
+/// Messages not synced before; mail the mailbox sent itself isn't contact activity
+fn unsynced(messages: Vec<InboundEmail>, synced: &HashSet<String>, account: &str) -> Vec<InboundEmail> {
+    messages
+        .into_iter()
+        .filter(|m| !synced.contains(&m.message_id) && m.from_email != account)
+        .collect()
+}
+
+/// The `email_received` entry a synced message becomes
+fn received_entry(
+    integration: &Integration,
+    integration_id: &str,
+    contact_id: &str,
+    message: &InboundEmail,
+) -> TimelineEntry {
+    TimelineEntry {
+        id: None,
+        workspace: integration.workspace.clone(),
+        contact: surrealdb::sql::Thing::from(("contact", contact_id)),
+        company: None,
+        campaign: None,
+        entry_type: TimelineEntryType::EmailReceived,
+        content: format!("Received \"{}\"", message.subject),
+        metadata: serde_json::json!({
+            "message_id": message.message_id,
+            "header_message_id": message.header_message_id,
+            "in_reply_to": message.in_reply_to,
+            "references": message.references,
+            "integration_id": integration_id,
+            "subject": message.subject,
+            "snippet": message.snippet,
+            "from": message.from_email,
+        }),
+        attachments: Vec::new(),
+        sentiment: None,
+        timestamp: message.received_at,
+    }
+}
+
+fn seal_tokens(cipher: &CredentialCipher, tokens: &OAuthTokens) -> AppResult<OAuthTokens> {
+    Ok(OAuthTokens {
+        access_token: cipher.seal(&tokens.access_token)?,
+        refresh_token: cipher.seal(&tokens.refresh_token)?,
+        expires_at: tokens.expires_at,
+    })
+}
+
+fn open_tokens(cipher: &CredentialCipher, tokens: &OAuthTokens) -> AppResult<OAuthTokens> {
+    Ok(OAuthTokens {
+        access_token: cipher.open(&tokens.access_token)?,
+        refresh_token: cipher.open(&tokens.refresh_token)?,
+        expires_at: tokens.expires_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::workspace_thing;
+
+    fn message(message_id: &str, from: &str) -> InboundEmail {
+        InboundEmail {
+            message_id: message_id.into(),
+            header_message_id: Some("<1@mail>".into()),
+            in_reply_to: None,
+            references: Vec::new(),
+            from_email: from.into(),
+            from_name: None,
+            subject: "Pricing".into(),
+            snippet: "What does it cost?".into(),
+            received_at: "2026-03-01T09:00:00Z".parse().unwrap(),
+        }
+    }
+
+    fn integration() -> Integration {
+        let now = Utc::now();
+        Integration {
+            id: Some(surrealdb::sql::Thing::from(("integration", "inbox"))),
+            workspace: workspace_thing("acme"),
+            provider: IntegrationProvider::Imap,
+            account: "sales@acme.com".into(),
+            status: IntegrationStatus::Active,
+            create_unknown_contacts: false,
+            imap: None,
+            oauth: None,
+            oauth_state: None,
+            cursor: Default::default(),
+            last_synced_at: None,
+            last_error: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_unsynced_skips_known_and_own_messages() {
+        let synced = HashSet::from(["gmail:1".to_string()]);
+        let messages = vec![
+            message("gmail:1", "jane@example.com"),
+            message("gmail:2", "sales@acme.com"),
+            message("gmail:3", "jane@example.com"),
+        ];
+
+        let ids: Vec<String> = unsynced(messages, &synced, "sales@acme.com")
+            .into_iter()
+            .map(|m| m.message_id)
+            .collect();
+        assert_eq!(ids, vec!["gmail:3"]);
+    }
+
+    #[test]
+    fn test_received_entry() {
+        let entry = received_entry(&integration(), "inbox", "jane", &message("gmail:3", "jane@example.com"));
+
+        assert_eq!(entry.contact, surrealdb::sql::Thing::from(("contact", "jane")));
+        assert!(matches!(entry.entry_type, TimelineEntryType::EmailReceived));
+        assert_eq!(entry.content, "Received \"Pricing\"");
+        assert_eq!(entry.metadata["message_id"], "gmail:3");
+        assert_eq!(entry.metadata["integration_id"], "inbox");
+        assert_eq!(entry.timestamp, message("gmail:3", "jane@example.com").received_at);
+    }
+
+    #[test]
+    fn test_tokens_are_stored_sealed() {
+        let cipher = CredentialCipher::new("test-key").unwrap();
+        let tokens = OAuthTokens {
+            access_token: "access".into(),
+            refresh_token: "refresh".into(),
+            expires_at: Utc::now(),
+        };
+
+        let sealed = seal_tokens(&cipher, &tokens).unwrap();
+        assert!(is_sealed(&sealed.access_token) && is_sealed(&sealed.refresh_token));
+
+        let opened = open_tokens(&cipher, &sealed).unwrap();
+        assert_eq!(opened.access_token, "access");
+        assert_eq!(opened.refresh_token, "refresh");
+        // Tokens stored before encryption still open
+        assert_eq!(open_tokens(&cipher, &tokens).unwrap().refresh_token, "refresh");
+    }
+}
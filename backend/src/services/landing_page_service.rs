@@ -0,0 +1,316 @@
+//! Landing Page Service - visit tracking and form submissions for public
+//! landing pages
+//!
+//! Every view of `/lp/:id` carries a visitor cookie; the first view of a
+//! page by a visitor is recorded with its UTM parameters and referrer.
+//! Once a visitor submits a form they are linked to the contact, so their
+//! earlier visits are attributed and later ones land on the contact's
+//! timeline as `landing_page_visit` entries.
+//!
+//! Forms are only taken on the version of a landing page its campaign
+//! currently uses. A submission creates a lead, or fills gaps in the
+//! custom fields of the contact with its email, and puts the submission on
+//! the contact's timeline in the same transaction.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use surrealdb::sql::Thing;
+use uuid::Uuid;
+
+use crate::db::{new_thing, Database};
+use crate::domain::{FormSchema, FormSubmission, WebhookEvent};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    AssetType, CampaignAsset, Contact, ContactResponse, ContactStatus, FeedEvent, LandingPageVisit, SubscriptionStatus,
+    TimelineEntryType, UtmParams,
+};
+use crate::repositories::LandingPageRepository;
+use crate::services::{new_entry, FeedService, TimelineService, WebhookService};
+
+/// Name of the cookie identifying a landing page visitor
+pub const VISITOR_COOKIE: &str = "hey_lpv";
+
+/// How long the visitor cookie lives, in seconds
+const VISITOR_COOKIE_MAX_AGE_SECS: u32 = 365 * 24 * 60 * 60;
+
+pub struct LandingPageService {
+    visits: LandingPageRepository,
+    timeline: Arc<TimelineService>,
+    feed: Arc<FeedService>,
+    webhooks: Arc<WebhookService>,
+}
+
+impl LandingPageService {
+    pub fn new(
+        db: Arc<Database>,
+        timeline: Arc<TimelineService>,
+        feed: Arc<FeedService>,
+        webhooks: Arc<WebhookService>,
+    ) -> Self {
+        Self {
+            visits: LandingPageRepository::new(db),
+            timeline,
+            feed,
+            webhooks,
+        }
+    }
+
+    /// Record a view of a landing page, once per visitor
+    pub async fn record_visit(
+        &self,
+        page: &CampaignAsset,
+        visitor_id: &str,
+        utm: UtmParams,
+        referrer: Option<String>,
+    ) -> AppResult<()> {
+        let Some(page_id) = page.id.clone() else { return Ok(()) };
+        if !matches!(page.asset_type, AssetType::LandingPage) {
+            return Ok(());
+        }
+
+        let contact = self.visits.visitor_contact(&page.workspace, visitor_id).await?;
+        let now = Utc::now();
+        let visit = LandingPageVisit {
+            id: None,
+            workspace: page.workspace.clone(),
+            campaign: page.campaign.clone(),
+            page: page_id.clone(),
+            visitor_id: visitor_id.to_string(),
+            contact: contact.clone(),
+            utm: utm.clone(),
+            referrer,
+            visited_at: now,
+        };
+        if !self.visits.record_visit(visit).await? {
+            return Ok(());
+        }
+
+        // Known visitors get the visit on their timeline
+        if let Some(contact) = contact {
+            let workspace_id = page.workspace.id.to_raw();
+            let mut entry = new_entry(
+                &workspace_id,
+                &contact.id.to_raw(),
+                TimelineEntryType::LandingPageVisit,
+                format!("Visited landing page {}", page_id.id.to_raw()),
+                serde_json::json!({
+                    "landing_page_id": page_id.id.to_raw(),
+                    "campaign_id": page.campaign.id.to_raw(),
+                    "action": "visit",
+                    "utm": utm,
+                }),
+            );
+            entry.campaign = Some(page.campaign.clone());
+            entry.timestamp = now;
+            self.timeline.record(&workspace_id, entry).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Attribute a visitor's visits to the contact they submitted a form as
+    pub async fn link_visitor(&self, workspace: &Thing, visitor_id: &str, contact: &Thing) -> AppResult<()> {
+        self.visits.link_visitor(workspace, visitor_id, contact).await
+    }
+
+    /// The page a form is submitted on and its form
+    ///
+    /// Only the active version of a landing page takes submissions; pages
+    /// from before forms were configurable use the standard form.
+    pub async fn submission_form(&self, page_id: &str) -> AppResult<(CampaignAsset, FormSchema)> {
+        let page = self
+            .visits
+            .find_active_page(page_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Landing page not found".into()))?;
+
+        let form = match page.generated_content.get("form") {
+            Some(form) => serde_json::from_value::<FormSchema>(form.clone())
+                .map_err(|e| AppError::Internal(format!("Landing page form is malformed: {}", e)))?,
+            None => FormSchema::default(),
+        };
+
+        Ok((page, form))
+    }
+
+    /// Record a screened and validated form submission
+    ///
+    /// Returns the ID of the contact it was recorded on. A trashed contact
+    /// keeps its email, so its submissions are dropped rather than reviving
+    /// or duplicating it, and None is returned.
+    pub async fn submit(
+        &self,
+        page: &CampaignAsset,
+        submission: FormSubmission,
+        visitor_id: Option<String>,
+    ) -> AppResult<Option<String>> {
+        let page_id = page
+            .id
+            .as_ref()
+            .map(|id| id.id.to_raw())
+            .ok_or_else(|| AppError::Internal("Landing page has no ID".into()))?;
+        let workspace = page.workspace.clone();
+        let workspace_id = workspace.id.to_raw();
+
+        let (existing, trashed) = self.visits.find_submitter(&workspace, &submission.email).await?;
+        if existing.is_none() && trashed {
+            tracing::info!("Dropped a landing page {} submission for a trashed contact", page_id);
+            return Ok(None);
+        }
+
+        let created = existing.is_none();
+        let now = Utc::now();
+        let contact = match existing {
+            Some(contact) => contact,
+            None => Contact {
+                id: Some(new_thing("contact")),
+                workspace: workspace.clone(),
+                first_name: submission.first_name.clone(),
+                last_name: submission.last_name.clone(),
+                email: submission.email.clone(),
+                phone: submission.phone.clone(),
+                linkedin_url: None,
+                timezone: None,
+                country: None,
+                region: None,
+                city: None,
+                avatar_url: None,
+                pipeline_position: None,
+                tags: vec!["landing_page_lead".to_string()],
+                status: ContactStatus::Lead,
+                subscription_status: SubscriptionStatus::Subscribed,
+                custom_fields: submission.custom_fields.clone(),
+                engagement_score: 10.0,
+                company: None,
+                owner: None,
+                created_at: now,
+                updated_at: now,
+            },
+        };
+        let contact_id = contact
+            .id
+            .clone()
+            .ok_or_else(|| AppError::Internal("Contact has no ID".into()))?;
+
+        let mut entry = new_entry(
+            &workspace_id,
+            &contact_id.id.to_raw(),
+            TimelineEntryType::FormSubmission,
+            format!("Submitted form on landing page {}", page_id),
+            serde_json::json!({
+                "landing_page_id": page_id,
+                "campaign_id": page.campaign.id.to_raw(),
+                "action": "submit",
+                "visitor_id": visitor_id,
+                "message": submission.message,
+                "company": submission.company,
+                "custom_fields": submission.custom_fields,
+            }),
+        );
+        entry.campaign = Some(page.campaign.clone());
+        entry.timestamp = now;
+        self.timeline.prepare_with(&contact, &mut entry)?;
+
+        // Known contacts only gain fields they don't have; every submitted value is on the timeline entry
+        let added_fields = if created {
+            Default::default()
+        } else {
+            submission.unset_custom_fields(&contact.custom_fields)
+        };
+        self.visits
+            .save_submission(&contact, created, added_fields, &entry, now)
+            .await?;
+
+        // Earlier anonymous visits now belong to the contact
+        if let Some(visitor_id) = &visitor_id {
+            if let Err(e) = self.visits.link_visitor(&workspace, visitor_id, &contact_id).await {
+                tracing::warn!("Failed to link landing page visitor {}: {}", visitor_id, e);
+            }
+        }
+
+        if created {
+            let response = ContactResponse::from(contact);
+            self.webhooks
+                .notify(
+                    &workspace_id,
+                    WebhookEvent::ContactCreated,
+                    serde_json::to_value(&response).unwrap_or_default(),
+                )
+                .await;
+            self.feed.publish(&workspace_id, FeedEvent::ContactCreated(response));
+        }
+        self.timeline.recorded(&workspace_id, &[entry]).await;
+
+        let contact_id = contact_id.id.to_string();
+        self.webhooks
+            .notify(
+                &workspace_id,
+                WebhookEvent::FormSubmitted,
+                serde_json::json!({
+                    "landing_page_id": page_id,
+                    "campaign_id": page.campaign.id.to_string(),
+                    "contact_id": contact_id,
+                    "contact_created": created,
+                    "first_name": submission.first_name,
+                    "last_name": submission.last_name,
+                    "email": submission.email,
+                    "phone": submission.phone,
+                    "company": submission.company,
+                    "message": submission.message,
+                    "custom_fields": submission.custom_fields,
+                }),
+            )
+            .await;
+
+        Ok(Some(contact_id))
+    }
+}
+
+/// The visitor ID in a `Cookie` header, if it holds a well-formed one
+pub fn visitor_from_cookies(header: &str) -> Option<String> {
+    header
+        .split(';')
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == VISITOR_COOKIE)
+        .and_then(|(_, value)| Uuid::parse_str(value.trim()).ok())
+        .map(|id| id.to_string())
+}
+
+/// A new visitor ID
+pub fn new_visitor_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
+/// `Set-Cookie` value giving a visitor their ID
+pub fn visitor_cookie(visitor_id: &str) -> String {
+    format!(
+        "{}={}; Path=/lp; Max-Age={}; HttpOnly; Secure; SameSite=Lax",
+        VISITOR_COOKIE, visitor_id, VISITOR_COOKIE_MAX_AGE_SECS
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visitor_from_cookies() {
+        let id = new_visitor_id();
+
+        assert_eq!(visitor_from_cookies(&format!("theme=dark; {}={}", VISITOR_COOKIE, id)), Some(id.clone()));
+        assert_eq!(visitor_from_cookies(&format!("{}= {} ", VISITOR_COOKIE, id)), Some(id));
+        assert_eq!(visitor_from_cookies(&format!("{}=not-a-uuid", VISITOR_COOKIE)), None);
+        assert_eq!(visitor_from_cookies("theme=dark"), None);
+    }
+
+    #[test]
+    fn test_visitor_cookie_round_trips() {
+        let id = new_visitor_id();
+        let cookie = visitor_cookie(&id);
+
+        assert!(cookie.contains("HttpOnly"));
+        assert!(cookie.contains("Secure"));
+        assert_eq!(cookie.split(';').next().and_then(visitor_from_cookies), Some(id));
+    }
+}
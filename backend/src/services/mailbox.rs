@@ -0,0 +1,597 @@
+//! Mailboxes - reading inbound email from IMAP servers and Gmail
+//!
+//! `fetch_imap` logs in over TLS and reads the messages past the last
+//! synced UID, without marking them read. `GmailClient` runs the OAuth code
+//! flow for a Gmail account and reads its inbox through the Gmail API with
+//! read-only scope.
+//!
+//! Both return messages oldest first, together with the cursor to resume
+//! from on the next sync.
+
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
+use futures::TryStreamExt;
+use mailparse::MailHeaderMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::config::InboxConfig;
+use crate::domain::{header_message_ids, snippet, InboundEmail};
+use crate::error::{AppError, AppResult};
+use crate::models::{ImapSettings, OAuthTokens, SyncCursor};
+use crate::secrets::SecretsManager;
+use crate::services::public_host::resolve_public;
+
+const GOOGLE_AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+const GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const GMAIL_API_URL: &str = "https://gmail.googleapis.com/gmail/v1/users/me";
+const GMAIL_SCOPE: &str = "https://www.googleapis.com/auth/gmail.readonly";
+
+/// Bytes of a message body read over IMAP; enough for a snippet
+const IMAP_BODY_BYTES: u32 = 8192;
+
+/// Most message IDs Gmail lists per page
+const GMAIL_PAGE_SIZE: usize = 500;
+
+/// How far a finished Gmail pass is overlapped by the next, so messages
+/// dated on its boundary aren't missed; ones seen twice are skipped as synced
+const GMAIL_PASS_OVERLAP_SECS: i64 = 60;
+
+static HTML_TAG_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<style.*?</style>|<[^>]*>").unwrap());
+
+/// New messages and where the next sync picks up
+#[derive(Debug, Clone, Default)]
+pub struct FetchedMail {
+    pub messages: Vec<InboundEmail>,
+    pub cursor: SyncCursor,
+}
+
+// ---- IMAP ----
+
+/// Read up to `limit` messages past the cursor; without a usable cursor,
+/// those received since `since`
+pub async fn fetch_imap(
+    settings: &ImapSettings,
+    cursor: &SyncCursor,
+    since: DateTime<Utc>,
+    limit: u32,
+    timeout: Duration,
+) -> AppResult<FetchedMail> {
+    tokio::time::timeout(timeout, fetch_imap_inner(settings, cursor, since, limit))
+        .await
+        .map_err(|_| AppError::Upstream(format!("IMAP server {} timed out", settings.host)))?
+}
+
+async fn fetch_imap_inner(
+    settings: &ImapSettings,
+    cursor: &SyncCursor,
+    since: DateTime<Utc>,
+    limit: u32,
+) -> AppResult<FetchedMail> {
+    let imap_error = |e: async_imap::error::Error| AppError::Upstream(format!("IMAP error: {}", e));
+
+    // Resolved here rather than when saved, so a name can't later point inside the network
+    let addrs = resolve_public(&settings.host, settings.port).await?;
+    let tcp = tokio::net::TcpStream::connect(addrs.as_slice())
+        .await
+        .map_err(|e| AppError::Upstream(format!("IMAP server {} unreachable: {}", settings.host, e)))?;
+    let tls = tokio_native_tls::native_tls::TlsConnector::new()
+        .map_err(|e| AppError::Internal(format!("Failed to set up TLS: {}", e)))?;
+    let stream = tokio_native_tls::TlsConnector::from(tls)
+        .connect(&settings.host, tcp)
+        .await
+        .map_err(|e| AppError::Upstream(format!("TLS handshake with {} failed: {}", settings.host, e)))?;
+
+    let mut session = async_imap::Client::new(stream)
+        .login(&settings.username, &settings.password)
+        .await
+        .map_err(|(e, _)| AppError::Upstream(format!("IMAP login failed: {}", e)))?;
+
+    let mailbox = session.select(&settings.mailbox).await.map_err(imap_error)?;
+    let uid_validity = mailbox.uid_validity;
+
+    // UIDs from before the mailbox was rebuilt mean nothing now
+    let resume_after = cursor.last_uid.filter(|_| cursor.uid_validity == uid_validity);
+    let query = match resume_after {
+        Some(last_uid) => format!("UID {}:*", last_uid + 1),
+        None => format!("SINCE {}", since.format("%d-%b-%Y")),
+    };
+    let mut uids: Vec<u32> = session
+        .uid_search(query)
+        .await
+        .map_err(imap_error)?
+        .into_iter()
+        // "n:*" always matches the newest message, even below n
+        .filter(|uid| resume_after.is_none_or(|last| *uid > last))
+        .collect();
+    uids.sort_unstable();
+    uids.truncate(limit as usize);
+
+    let mut messages = Vec::with_capacity(uids.len());
+    if !uids.is_empty() {
+        let set = uids.iter().map(u32::to_string).collect::<Vec<_>>().join(",");
+        let fetches: Vec<async_imap::types::Fetch> = session
+            .uid_fetch(
+                set,
+                format!("(UID INTERNALDATE BODY.PEEK[HEADER] BODY.PEEK[TEXT]<0.{}>)", IMAP_BODY_BYTES),
+            )
+            .await
+            .map_err(imap_error)?
+            .try_collect()
+            .await
+            .map_err(imap_error)?;
+
+        for fetch in &fetches {
+            let (Some(uid), Some(header)) = (fetch.uid, fetch.header()) else {
+                continue;
+            };
+            let received_at = fetch.internal_date().map(|d| d.with_timezone(&Utc));
+            let fallback_id = format!("imap:{}:{}", uid_validity.unwrap_or_default(), uid);
+            if let Some(email) = parse_message(header, fetch.text().unwrap_or_default(), received_at, fallback_id) {
+                messages.push(email);
+            }
+        }
+    }
+
+    if let Err(e) = session.logout().await {
+        tracing::debug!("IMAP logout from {} failed: {}", settings.host, e);
+    }
+
+    Ok(FetchedMail {
+        messages,
+        cursor: SyncCursor {
+            uid_validity,
+            last_uid: uids.last().copied().or(resume_after),
+            ..SyncCursor::default()
+        },
+    })
+}
+
+/// Read the sender, subject and a snippet out of a raw message
+fn parse_message(
+    header: &[u8],
+    body: &[u8],
+    received_at: Option<DateTime<Utc>>,
+    fallback_id: String,
+) -> Option<InboundEmail> {
+    let (headers, _) = mailparse::parse_headers(header).ok()?;
+    let from = mailparse::addrparse(&headers.get_first_value("From")?)
+        .ok()?
+        .extract_single_info()?;
+
+    let received_at = received_at
+        .or_else(|| {
+            headers
+                .get_first_value("Date")
+                .and_then(|d| mailparse::dateparse(&d).ok())
+                .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+        })
+        .unwrap_or_else(Utc::now);
+
+    let mut raw = header.to_vec();
+    raw.extend_from_slice(body);
+    let text = mailparse::parse_mail(&raw)
+        .ok()
+        .and_then(|mail| body_text(&mail))
+        .unwrap_or_default();
+
+    Some(InboundEmail {
+        message_id: headers
+            .get_first_value("Message-ID")
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .unwrap_or(fallback_id),
+        header_message_id: None,
+        in_reply_to: headers
+            .get_first_value("In-Reply-To")
+            .and_then(|h| header_message_ids(&h).into_iter().next()),
+        references: headers
+            .get_first_value("References")
+            .map(|h| header_message_ids(&h))
+            .unwrap_or_default(),
+        from_email: from.addr.trim().to_lowercase(),
+        from_name: from.display_name,
+        subject: headers.get_first_value("Subject").unwrap_or_default(),
+        snippet: snippet(&text),
+        received_at,
+    })
+}
+
+/// The plain text of a message, or its HTML with the tags stripped
+fn body_text(mail: &mailparse::ParsedMail) -> Option<String> {
+    fn find<'a>(part: &'a mailparse::ParsedMail<'a>, mimetype: &str) -> Option<&'a mailparse::ParsedMail<'a>> {
+        if part.subparts.is_empty() {
+            return (part.ctype.mimetype == mimetype).then_some(part);
+        }
+        part.subparts.iter().find_map(|p| find(p, mimetype))
+    }
+
+    if let Some(text) = find(mail, "text/plain").and_then(|p| p.get_body().ok()) {
+        return Some(text);
+    }
+    find(mail, "text/html")
+        .and_then(|p| p.get_body().ok())
+        .map(|html| unescape_html(&HTML_TAG_REGEX.replace_all(&html, " ")))
+}
+
+fn unescape_html(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+// ---- Gmail ----
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: i64,
+}
+
+#[derive(Deserialize)]
+struct MessageList {
+    #[serde(default)]
+    messages: Vec<MessageRef>,
+    #[serde(rename = "nextPageToken")]
+    next_page_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MessageRef {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct GmailMessage {
+    id: String,
+    #[serde(default)]
+    snippet: String,
+    /// Milliseconds since the epoch, as a string
+    #[serde(rename = "internalDate")]
+    internal_date: String,
+    payload: GmailPayload,
+}
+
+#[derive(Deserialize)]
+struct GmailPayload {
+    #[serde(default)]
+    headers: Vec<GmailHeader>,
+}
+
+#[derive(Deserialize)]
+struct GmailHeader {
+    name: String,
+    value: String,
+}
+
+/// Gmail OAuth and read-only access to an account's inbox
+pub struct GmailClient {
+    http: reqwest::Client,
+    client_id: String,
+    client_secret: String,
+    redirect_uri: String,
+}
+
+impl GmailClient {
+    /// `None` when no OAuth client is configured; the client secret comes
+    /// from the secrets manager
+    pub fn from_config(config: &InboxConfig, secrets: &SecretsManager) -> AppResult<Option<Self>> {
+        if config.gmail.client_id.is_empty() {
+            return Ok(None);
+        }
+
+        let client_secret = secrets
+            .get_secret(&config.gmail.client_secret_secret)
+            .map_err(|e| AppError::Internal(format!("Gmail sync needs an OAuth client secret: {}", e)))?;
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_secs))
+            .build()
+            .map_err(|e| AppError::Internal(format!("Failed to build Gmail client: {}", e)))?;
+
+        tracing::info!("Gmail inbox sync enabled");
+        Ok(Some(Self {
+            http,
+            client_id: config.gmail.client_id.clone(),
+            client_secret,
+            redirect_uri: config.gmail.redirect_uri.clone(),
+        }))
+    }
+
+    /// Google's consent screen; it redirects back with `state` and a code
+    pub fn authorization_url(&self, state: &str) -> AppResult<String> {
+        let url = reqwest::Url::parse_with_params(
+            GOOGLE_AUTH_URL,
+            &[
+                ("client_id", self.client_id.as_str()),
+                ("redirect_uri", self.redirect_uri.as_str()),
+                ("response_type", "code"),
+                ("scope", GMAIL_SCOPE),
+                // A refresh token is only handed out with offline access and fresh consent
+                ("access_type", "offline"),
+                ("prompt", "consent"),
+                ("state", state),
+            ],
+        )
+        .map_err(|e| AppError::Internal(format!("Failed to build Gmail authorization URL: {}", e)))?;
+
+        Ok(url.into())
+    }
+
+    pub async fn exchange_code(&self, code: &str) -> AppResult<OAuthTokens> {
+        let tokens = self
+            .token_request(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", self.redirect_uri.as_str()),
+            ])
+            .await?;
+
+        let refresh_token = tokens
+            .refresh_token
+            .ok_or_else(|| AppError::Upstream("Google returned no refresh token".into()))?;
+        Ok(OAuthTokens {
+            access_token: tokens.access_token,
+            refresh_token,
+            expires_at: Utc::now() + chrono::Duration::seconds(tokens.expires_in),
+        })
+    }
+
+    /// New tokens when the access token is about to expire; `None` when it's still good
+    pub async fn refresh(&self, tokens: &OAuthTokens) -> AppResult<Option<OAuthTokens>> {
+        if tokens.expires_at > Utc::now() + chrono::Duration::minutes(1) {
+            return Ok(None);
+        }
+
+        let refreshed = self
+            .token_request(&[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", tokens.refresh_token.as_str()),
+            ])
+            .await?;
+
+        Ok(Some(OAuthTokens {
+            access_token: refreshed.access_token,
+            // Google only sometimes rotates the refresh token
+            refresh_token: refreshed.refresh_token.unwrap_or_else(|| tokens.refresh_token.clone()),
+            expires_at: Utc::now() + chrono::Duration::seconds(refreshed.expires_in),
+        }))
+    }
+
+    /// Address of the account the access token belongs to
+    pub async fn account(&self, access_token: &str) -> AppResult<String> {
+        #[derive(Deserialize)]
+        struct Profile {
+            #[serde(rename = "emailAddress")]
+            email_address: String,
+        }
+
+        let profile: Profile = self.get_json(&format!("{}/profile", GMAIL_API_URL), access_token, &[]).await?;
+        Ok(profile.email_address.to_lowercase())
+    }
+
+    /// Read about `limit` inbox messages received after the cursor, or
+    /// after `since` on the first sync
+    ///
+    /// Gmail lists newest first, so a pass pages through everything received
+    /// between the cursor and when the pass started. A pass with more than
+    /// `limit` messages stores its page token and the next sync carries on.
+    pub async fn fetch(
+        &self,
+        access_token: &str,
+        cursor: &SyncCursor,
+        since: DateTime<Utc>,
+        limit: u32,
+    ) -> AppResult<FetchedMail> {
+        let after = cursor.last_received_at.unwrap_or(since);
+        let until = cursor.pass_until.unwrap_or_else(Utc::now);
+        let query = format!("in:inbox after:{} before:{}", after.timestamp(), until.timestamp());
+        let page_size = (limit as usize).clamp(1, GMAIL_PAGE_SIZE);
+
+        let mut ids = Vec::new();
+        let mut page_token = cursor.page_token.clone();
+        loop {
+            let mut params = vec![("q", query.clone()), ("maxResults", page_size.to_string())];
+            if let Some(token) = page_token.take() {
+                params.push(("pageToken", token));
+            }
+            let page: MessageList = self
+                .get_json(&format!("{}/messages", GMAIL_API_URL), access_token, &params)
+                .await?;
+            ids.extend(page.messages.into_iter().map(|m| m.id));
+
+            page_token = page.next_page_token;
+            if page_token.is_none() || ids.len() >= limit as usize {
+                break;
+            }
+        }
+        ids.reverse();
+
+        let mut messages = Vec::with_capacity(ids.len());
+        for id in ids {
+            let message: GmailMessage = self
+                .get_json(
+                    &format!("{}/messages/{}", GMAIL_API_URL, id),
+                    access_token,
+                    &[
+                        ("format", "metadata".to_string()),
+                        ("metadataHeaders", "From".to_string()),
+                        ("metadataHeaders", "Subject".to_string()),
+                        ("metadataHeaders", "Message-ID".to_string()),
+                        ("metadataHeaders", "In-Reply-To".to_string()),
+                        ("metadataHeaders", "References".to_string()),
+                    ],
+                )
+                .await?;
+            if let Some(email) = gmail_message(message) {
+                messages.push(email);
+            }
+        }
+        messages.sort_by_key(|m| m.received_at);
+
+        Ok(FetchedMail {
+            messages,
+            cursor: gmail_cursor(after, until, page_token),
+        })
+    }
+
+    async fn token_request(&self, params: &[(&str, &str)]) -> AppResult<TokenResponse> {
+        let mut form = vec![
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+        ];
+        form.extend_from_slice(params);
+
+        let response = self
+            .http
+            .post(GOOGLE_TOKEN_URL)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| AppError::Upstream(format!("Google OAuth unreachable: {}", e)))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(AppError::Upstream(format!("Google OAuth returned {}: {}", status, text)));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| AppError::Upstream(format!("Unreadable Google OAuth response: {}", e)))
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        access_token: &str,
+        query: &[(&str, String)],
+    ) -> AppResult<T> {
+        let response = self
+            .http
+            .get(url)
+            .bearer_auth(access_token)
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| AppError::Upstream(format!("Gmail unreachable: {}", e)))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(AppError::Upstream(format!("Gmail returned {}: {}", status, text)));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| AppError::Upstream(format!("Unreadable Gmail response: {}", e)))
+    }
+}
+
+/// Where the next Gmail sync picks up after listing up to `next_page_token`
+fn gmail_cursor(after: DateTime<Utc>, until: DateTime<Utc>, next_page_token: Option<String>) -> SyncCursor {
+    match next_page_token {
+        // Mid-pass: the same query, so the page token stays valid
+        Some(token) => SyncCursor {
+            last_received_at: Some(after),
+            page_token: Some(token),
+            pass_until: Some(until),
+            ..SyncCursor::default()
+        },
+        None => SyncCursor {
+            last_received_at: Some(until - chrono::Duration::seconds(GMAIL_PASS_OVERLAP_SECS)),
+            ..SyncCursor::default()
+        },
+    }
+}
+
+fn gmail_message(message: GmailMessage) -> Option<InboundEmail> {
+    let header = |name: &str| {
+        message
+            .payload
+            .headers
+            .iter()
+            .find(|h| h.name.eq_ignore_ascii_case(name))
+            .map(|h| h.value.clone())
+    };
+
+    let from = mailparse::addrparse(&header("From")?).ok()?.extract_single_info()?;
+    let received_at = message
+        .internal_date
+        .parse::<i64>()
+        .ok()
+        .and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+        .unwrap_or_else(Utc::now);
+
+    Some(InboundEmail {
+        message_id: format!("gmail:{}", message.id),
+        header_message_id: header("Message-ID").map(|id| id.trim().to_string()).filter(|id| !id.is_empty()),
+        in_reply_to: header("In-Reply-To").and_then(|h| header_message_ids(&h).into_iter().next()),
+        references: header("References").map(|h| header_message_ids(&h)).unwrap_or_default(),
+        from_email: from.addr.trim().to_lowercase(),
+        from_name: from.display_name,
+        subject: header("Subject").unwrap_or_default(),
+        // Gmail's snippets come HTML-escaped
+        snippet: snippet(&unescape_html(&message.snippet)),
+        received_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_message() {
+        let header = b"From: \"Jane Doe\" <Jane@Example.com>\r\n\
+            Subject: =?UTF-8?Q?Caf=C3=A9_meeting?=\r\n\
+            Message-ID: <abc@example.com>\r\n\
+            In-Reply-To: <prev@example.com>\r\n\
+            References: <root@example.com> <prev@example.com>\r\n\
+            Content-Type: multipart/alternative; boundary=\"b1\"\r\n\r\n";
+        let body = b"--b1\r\nContent-Type: text/html\r\n\r\n<p>Hello <b>there</b></p>\r\n\
+            --b1\r\nContent-Type: text/plain\r\n\r\nHello there,\r\nsee you soon\r\n--b1--\r\n";
+
+        let email = parse_message(header, body, None, "imap:1:7".into()).unwrap();
+
+        assert_eq!(email.from_email, "jane@example.com");
+        assert_eq!(email.from_name.as_deref(), Some("Jane Doe"));
+        assert_eq!(email.subject, "Café meeting");
+        assert_eq!(email.message_id, "<abc@example.com>");
+        assert_eq!(email.in_reply_to.as_deref(), Some("<prev@example.com>"));
+        assert_eq!(email.references, vec!["<root@example.com>", "<prev@example.com>"]);
+        assert_eq!(email.snippet, "Hello there, see you soon");
+    }
+
+    #[test]
+    fn test_gmail_cursor_resumes_an_unfinished_pass() {
+        let after: DateTime<Utc> = "2026-03-01T00:00:00Z".parse().unwrap();
+        let until: DateTime<Utc> = "2026-03-02T00:00:00Z".parse().unwrap();
+
+        let resumed = gmail_cursor(after, until, Some("page-2".into()));
+        assert_eq!(resumed.last_received_at, Some(after));
+        assert_eq!(resumed.page_token.as_deref(), Some("page-2"));
+        assert_eq!(resumed.pass_until, Some(until));
+
+        let finished = gmail_cursor(after, until, None);
+        assert_eq!(finished.last_received_at, Some(until - chrono::Duration::seconds(GMAIL_PASS_OVERLAP_SECS)));
+        assert!(finished.page_token.is_none());
+        assert!(finished.pass_until.is_none());
+    }
+
+    #[test]
+    fn test_html_only_body() {
+        let header = b"From: bob@example.com\r\nContent-Type: text/html\r\n\r\n";
+        let body = b"<style>p { color: red }</style><p>Tom &amp; Jerry</p>";
+
+        let email = parse_message(header, body, None, "imap:1:8".into()).unwrap();
+
+        assert_eq!(email.message_id, "imap:1:8");
+        assert_eq!(email.snippet, "Tom & Jerry");
+    }
+}
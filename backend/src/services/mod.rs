@@ -7,8 +7,104 @@
 //!
 //! Handlers call services. Services call domain + repository.
 
+pub mod analytics_service;
+pub mod assistant_service;
+pub mod attachment_service;
+pub mod audit_service;
+pub mod auth_service;
+pub mod backup_service;
+pub mod avatar_service;
+pub mod campaign_asset_service;
 pub mod campaign_executor;
+pub mod campaign_scheduler;
+pub mod campaign_template_service;
+pub mod contact_export;
+pub mod contact_live_service;
+pub mod contact_report_service;
 pub mod contact_service;
+pub mod credential_cipher;
+pub mod deal_service;
+pub mod dedupe_service;
+pub mod deliverability_service;
+pub mod digest_service;
+pub mod dns_client;
+pub mod engagement_service;
+pub mod enrichment_provider;
+pub mod enrichment_service;
+pub mod event_service;
+pub mod feed_service;
+pub mod gdpr_service;
+pub mod hubspot;
+pub mod idempotency_service;
+pub mod import_service;
+pub mod inbound_service;
+pub mod inbox_service;
+pub mod landing_page_service;
+pub mod mailbox;
+pub mod object_storage;
+pub mod pipedrive;
+pub mod pipeline_service;
+pub mod public_host;
+pub mod rate_limiter;
+pub mod read_cache;
+pub mod recommendation_service;
+pub mod relationship_service;
+pub mod reply_detector;
+pub mod search_service;
+pub mod seed_service;
 pub mod segment_builder;
+pub mod segment_service;
+pub mod sender_service;
+pub mod sequence_service;
+pub mod social_publisher;
+pub mod social_service;
+pub mod spam_guard;
+pub mod timeline_service;
+pub mod tracking_service;
+pub mod trash_service;
+pub mod webhook_dispatcher;
+pub mod webhook_service;
 
+pub use analytics_service::*;
+pub use assistant_service::*;
+pub use attachment_service::*;
+pub use audit_service::*;
+pub use auth_service::*;
+pub use backup_service::*;
+pub use avatar_service::*;
+pub use campaign_asset_service::*;
+pub use campaign_scheduler::*;
+pub use campaign_template_service::*;
+pub use contact_live_service::*;
+pub use contact_report_service::*;
 pub use contact_service::*;
+pub use deal_service::*;
+pub use dedupe_service::*;
+pub use deliverability_service::*;
+pub use digest_service::*;
+pub use engagement_service::*;
+pub use enrichment_service::*;
+pub use event_service::*;
+pub use feed_service::*;
+pub use gdpr_service::*;
+pub use idempotency_service::*;
+pub use import_service::*;
+pub use inbound_service::*;
+pub use inbox_service::*;
+pub use landing_page_service::*;
+pub use pipeline_service::*;
+pub use recommendation_service::*;
+pub use relationship_service::*;
+pub use reply_detector::*;
+pub use search_service::*;
+pub use seed_service::*;
+pub use segment_service::*;
+pub use sender_service::*;
+pub use sequence_service::*;
+pub use social_service::*;
+pub use spam_guard::*;
+pub use timeline_service::*;
+pub use tracking_service::*;
+pub use trash_service::*;
+pub use webhook_dispatcher::*;
+pub use webhook_service::*;
@@ -0,0 +1,336 @@
+//! Object storage - where attachment files and avatars are kept
+//!
+//! `ObjectStorage` stores, reads and removes files by key. `LocalDiskStorage`
+//! keeps them under a directory on the server; `S3Storage` keeps them in an
+//! S3 bucket (or an S3-compatible service), signing requests with AWS
+//! Signature Version 4 and an access key pair read from the secrets
+//! manager. Keys start with the workspace ID and contain only IDs, so
+//! they are safe as both paths and URLs.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use futures::future::BoxFuture;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::config::StorageConfig;
+use crate::error::{AppError, AppResult};
+use crate::secrets::SecretsManager;
+
+pub trait ObjectStorage: Send + Sync {
+    /// Short name, e.g. `local`
+    fn name(&self) -> &'static str;
+
+    fn put<'a>(&'a self, key: &'a str, content_type: &'a str, data: Vec<u8>) -> BoxFuture<'a, AppResult<()>>;
+
+    /// The file's bytes; `None` when there is nothing under the key
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, AppResult<Option<Vec<u8>>>>;
+
+    /// Remove a file; removing a missing file is not an error
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, AppResult<()>>;
+}
+
+/// Build the storage backend selected in config
+pub fn build_object_storage(config: &StorageConfig, secrets: &SecretsManager) -> AppResult<Arc<dyn ObjectStorage>> {
+    let storage: Arc<dyn ObjectStorage> = match config.backend.as_str() {
+        "local" | "" => Arc::new(LocalDiskStorage {
+            root: PathBuf::from(&config.local_dir),
+        }),
+        "s3" => {
+            if config.s3_bucket.is_empty() {
+                return Err(AppError::Internal("Attachment storage 's3' needs s3_bucket".into()));
+            }
+            let secret = |name: &str| {
+                secrets
+                    .get_secret(name)
+                    .map_err(|e| AppError::Internal(format!("Attachment storage 's3' needs an access key: {}", e)))
+            };
+            let endpoint = if config.s3_endpoint.is_empty() {
+                format!("https://s3.{}.amazonaws.com", config.s3_region)
+            } else {
+                config.s3_endpoint.trim_end_matches('/').to_string()
+            };
+            let host = reqwest::Url::parse(&endpoint)
+                .ok()
+                .and_then(|url| {
+                    let host = url.host_str()?.to_string();
+                    Some(match url.port() {
+                        Some(port) => format!("{}:{}", host, port),
+                        None => host,
+                    })
+                })
+                .ok_or_else(|| AppError::Internal(format!("Invalid S3 endpoint '{}'", endpoint)))?;
+
+            Arc::new(S3Storage {
+                http: reqwest::Client::builder()
+                    .timeout(Duration::from_secs(config.request_timeout_secs))
+                    .build()
+                    .map_err(|e| AppError::Internal(format!("Failed to build S3 client: {}", e)))?,
+                endpoint,
+                host,
+                bucket: config.s3_bucket.clone(),
+                region: config.s3_region.clone(),
+                access_key: secret(&config.s3_access_key_secret)?,
+                secret_key: secret(&config.s3_secret_key_secret)?,
+            })
+        }
+        other => {
+            return Err(AppError::Internal(format!(
+                "Unknown attachment storage '{}', expected local or s3",
+                other
+            )));
+        }
+    };
+
+    tracing::info!("Attachment storage: {}", storage.name());
+    Ok(storage)
+}
+
+// ---- Local disk ----
+
+/// Files under a directory on the server
+struct LocalDiskStorage {
+    root: PathBuf,
+}
+
+impl LocalDiskStorage {
+    fn path(&self, key: &str) -> PathBuf {
+        self.root.join(key)
+    }
+
+    async fn write(&self, key: &str, data: Vec<u8>) -> AppResult<()> {
+        let path = self.path(key);
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|e| AppError::Internal(format!("Failed to create {}: {}", dir.display(), e)))?;
+        }
+        tokio::fs::write(&path, data)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to write {}: {}", path.display(), e)))
+    }
+
+    async fn read(&self, key: &str) -> AppResult<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path(key)).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(AppError::Internal(format!("Failed to read attachment {}: {}", key, e))),
+        }
+    }
+
+    async fn remove(&self, key: &str) -> AppResult<()> {
+        match tokio::fs::remove_file(self.path(key)).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::Internal(format!("Failed to remove attachment {}: {}", key, e))),
+        }
+    }
+}
+
+impl ObjectStorage for LocalDiskStorage {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    fn put<'a>(&'a self, key: &'a str, _content_type: &'a str, data: Vec<u8>) -> BoxFuture<'a, AppResult<()>> {
+        Box::pin(self.write(key, data))
+    }
+
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, AppResult<Option<Vec<u8>>>> {
+        Box::pin(self.read(key))
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, AppResult<()>> {
+        Box::pin(self.remove(key))
+    }
+}
+
+// ---- S3 ----
+
+/// Objects in an S3 bucket, addressed path-style so S3-compatible services work too
+struct S3Storage {
+    http: reqwest::Client,
+    /// Scheme and host, e.g. `https://s3.eu-north-1.amazonaws.com`
+    endpoint: String,
+    /// Host header value, with the port if there is one
+    host: String,
+    bucket: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+}
+
+impl S3Storage {
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        key: &str,
+        content_type: Option<&str>,
+        body: Vec<u8>,
+    ) -> AppResult<reqwest::Response> {
+        let path = format!("/{}/{}", self.bucket, key);
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+        let mut headers = vec![
+            ("host", self.host.clone()),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(content_type) = content_type {
+            headers.insert(0, ("content-type", content_type.to_string()));
+        }
+        let (signed_headers, signature) =
+            sign_v4(&self.secret_key, &self.region, &amz_date, method.as_str(), &path, &headers, &payload_hash)?;
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}/{}/s3/aws4_request, SignedHeaders={}, Signature={}",
+            self.access_key,
+            &amz_date[..8],
+            self.region,
+            signed_headers,
+            signature
+        );
+
+        let mut request = self
+            .http
+            .request(method, format!("{}{}", self.endpoint, path))
+            .header("authorization", authorization);
+        for (name, value) in headers.into_iter().filter(|(name, _)| *name != "host") {
+            request = request.header(name, value);
+        }
+
+        request
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| AppError::Upstream(format!("S3 unreachable: {}", e)))
+    }
+
+    async fn upload(&self, key: &str, content_type: &str, data: Vec<u8>) -> AppResult<()> {
+        let response = self.send(reqwest::Method::PUT, key, Some(content_type), data).await?;
+        check_status(response).await.map(|_| ())
+    }
+
+    async fn download(&self, key: &str) -> AppResult<Option<Vec<u8>>> {
+        let response = self.send(reqwest::Method::GET, key, None, Vec::new()).await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let response = check_status(response).await?;
+        let data = response
+            .bytes()
+            .await
+            .map_err(|e| AppError::Upstream(format!("Failed to read from S3: {}", e)))?;
+        Ok(Some(data.to_vec()))
+    }
+
+    async fn remove(&self, key: &str) -> AppResult<()> {
+        let response = self.send(reqwest::Method::DELETE, key, None, Vec::new()).await?;
+        check_status(response).await.map(|_| ())
+    }
+}
+
+impl ObjectStorage for S3Storage {
+    fn name(&self) -> &'static str {
+        "s3"
+    }
+
+    fn put<'a>(&'a self, key: &'a str, content_type: &'a str, data: Vec<u8>) -> BoxFuture<'a, AppResult<()>> {
+        Box::pin(self.upload(key, content_type, data))
+    }
+
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, AppResult<Option<Vec<u8>>>> {
+        Box::pin(self.download(key))
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, AppResult<()>> {
+        Box::pin(self.remove(key))
+    }
+}
+
+async fn check_status(response: reqwest::Response) -> AppResult<reqwest::Response> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    Err(AppError::Upstream(format!("S3 returned {}: {}", status, text)))
+}
+
+/// AWS Signature Version 4 for an S3 request without a query string
+///
+/// `headers` are the lowercase names and values to sign, sorted by name.
+/// Returns the signed header list and the signature.
+fn sign_v4(
+    secret_key: &str,
+    region: &str,
+    amz_date: &str,
+    method: &str,
+    path: &str,
+    headers: &[(&str, String)],
+    payload_hash: &str,
+) -> AppResult<(String, String)> {
+    fn hmac(key: &[u8], data: &str) -> AppResult<Vec<u8>> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key)
+            .map_err(|e| AppError::Internal(format!("Failed to sign an S3 request: {}", e)))?;
+        mac.update(data.as_bytes());
+        Ok(mac.finalize().into_bytes().to_vec())
+    }
+
+    let date = &amz_date[..8];
+    let canonical_headers: String = headers.iter().map(|(name, value)| format!("{}:{}\n", name, value.trim())).collect();
+    let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+    let canonical_request = [method, path, "", &canonical_headers, &signed_headers, payload_hash].join("\n");
+
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = [
+        "AWS4-HMAC-SHA256",
+        amz_date,
+        &scope,
+        &hex::encode(Sha256::digest(canonical_request.as_bytes())),
+    ]
+    .join("\n");
+
+    let key = hmac(format!("AWS4{}", secret_key).as_bytes(), date)?;
+    let key = hmac(&key, region)?;
+    let key = hmac(&key, "s3")?;
+    let key = hmac(&key, "aws4_request")?;
+
+    Ok((signed_headers, hex::encode(hmac(&key, &string_to_sign)?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_v4_matches_aws_example() {
+        // "GET Object" example from the AWS Signature Version 4 documentation
+        let empty_hash = hex::encode(Sha256::digest(b""));
+        let headers = [
+            ("host", "examplebucket.s3.amazonaws.com".to_string()),
+            ("range", "bytes=0-9".to_string()),
+            ("x-amz-content-sha256", empty_hash.clone()),
+            ("x-amz-date", "20130524T000000Z".to_string()),
+        ];
+
+        let (signed_headers, signature) = sign_v4(
+            "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY",
+            "us-east-1",
+            "20130524T000000Z",
+            "GET",
+            "/test.txt",
+            &headers,
+            &empty_hash,
+        )
+        .unwrap();
+
+        assert_eq!(signed_headers, "host;range;x-amz-content-sha256;x-amz-date");
+        assert_eq!(signature, "f0e8bdb87c964420e857bd35b5d6ed310bd44f0170aba48dd91039c6036bdb41");
+    }
+}
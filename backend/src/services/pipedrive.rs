@@ -0,0 +1,374 @@
+//! Pipedrive - reading a company account's CRM objects for the importer
+//!
+//! `PipedriveClient` pages through persons, organizations, deals and notes
+//! over the v1 API with an API token. `parse_export` reads the same objects
+//! from an uploaded export. Either way they come out as an `ImportBatch`,
+//! keyed by their Pipedrive IDs; objects without an ID are skipped.
+
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::Deserialize;
+
+use crate::domain::{
+    normalize_domain, note_text, ContactStatus, DealStage, ImportBatch, ImportedCompany, ImportedContact, ImportedDeal,
+    ImportedNote,
+};
+use crate::error::{AppError, AppResult};
+
+const PIPEDRIVE_API_URL: &str = "https://api.pipedrive.com/v1";
+
+/// Largest page the list endpoints return
+const PAGE_SIZE: u32 = 500;
+
+/// Times a rate-limited request is retried before the import gives up
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Default, Deserialize)]
+struct PipedriveExport {
+    #[serde(default)]
+    persons: Vec<PipedriveObject>,
+    #[serde(default)]
+    organizations: Vec<PipedriveObject>,
+    #[serde(default)]
+    deals: Vec<PipedriveObject>,
+    #[serde(default)]
+    notes: Vec<PipedriveObject>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(transparent)]
+struct PipedriveObject(serde_json::Map<String, serde_json::Value>);
+
+#[derive(Deserialize)]
+struct Page {
+    data: Option<Vec<PipedriveObject>>,
+    additional_data: Option<AdditionalData>,
+}
+
+#[derive(Deserialize)]
+struct AdditionalData {
+    pagination: Option<Pagination>,
+}
+
+#[derive(Deserialize)]
+struct Pagination {
+    #[serde(default)]
+    more_items_in_collection: bool,
+    next_start: Option<u32>,
+}
+
+impl PipedriveObject {
+    /// A field's value as trimmed text; `None` when blank
+    fn text(&self, name: &str) -> Option<String> {
+        scalar_text(self.0.get(name)?)
+    }
+
+    /// ID of a linked object; Pipedrive gives either the bare ID or
+    /// `{ value, name, ... }`
+    fn linked_id(&self, name: &str) -> Option<String> {
+        match self.0.get(name)? {
+            serde_json::Value::Object(linked) => linked.get("value").and_then(scalar_text),
+            value => scalar_text(value),
+        }
+    }
+
+    /// The primary of a person's emails or phones, or the first one
+    fn primary(&self, name: &str) -> Option<String> {
+        match self.0.get(name)? {
+            serde_json::Value::Array(items) => {
+                let value = |item: &serde_json::Value| item.get("value").and_then(scalar_text);
+                items
+                    .iter()
+                    .find(|item| item.get("primary").and_then(|p| p.as_bool()) == Some(true))
+                    .and_then(value)
+                    .or_else(|| items.iter().find_map(value))
+            }
+            value => scalar_text(value),
+        }
+    }
+}
+
+fn scalar_text(value: &serde_json::Value) -> Option<String> {
+    let text = match value {
+        serde_json::Value::String(s) => s.trim().to_string(),
+        serde_json::Value::Number(n) => n.to_string(),
+        _ => return None,
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+/// Read an uploaded export
+pub fn parse_export(export: serde_json::Value) -> AppResult<ImportBatch> {
+    let export: PipedriveExport = serde_json::from_value(export)
+        .map_err(|e| AppError::BadRequest(format!("Not a Pipedrive export: {}", e)))?;
+    Ok(to_batch(export))
+}
+
+/// Pipedrive API, authenticated with a user's API token
+pub struct PipedriveClient {
+    http: reqwest::Client,
+    api_token: String,
+}
+
+impl PipedriveClient {
+    pub fn new(api_token: &str) -> AppResult<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| AppError::Internal(format!("Failed to build Pipedrive client: {}", e)))?;
+
+        Ok(Self {
+            http,
+            api_token: api_token.trim().to_string(),
+        })
+    }
+
+    /// Every person, organization, deal and note in the account
+    pub async fn fetch_all(&self) -> AppResult<ImportBatch> {
+        let export = PipedriveExport {
+            organizations: self.list("organizations").await?,
+            persons: self.list("persons").await?,
+            deals: self.list("deals").await?,
+            notes: self.list("notes").await?,
+        };
+
+        Ok(to_batch(export))
+    }
+
+    async fn list(&self, object_type: &str) -> AppResult<Vec<PipedriveObject>> {
+        let mut objects = Vec::new();
+        let mut start = 0;
+
+        loop {
+            let page = self.get_page(object_type, start).await?;
+            objects.extend(page.data.unwrap_or_default());
+            match page.additional_data.and_then(|d| d.pagination) {
+                Some(Pagination {
+                    more_items_in_collection: true,
+                    next_start: Some(next),
+                }) => start = next,
+                _ => return Ok(objects),
+            }
+        }
+    }
+
+    async fn get_page(&self, object_type: &str, start: u32) -> AppResult<Page> {
+        let mut retries = 0;
+        loop {
+            // The token goes in a header so it stays out of URLs in error messages
+            let response = self
+                .http
+                .get(format!("{}/{}", PIPEDRIVE_API_URL, object_type))
+                .header("x-api-token", &self.api_token)
+                .query(&[("start", start), ("limit", PAGE_SIZE)])
+                .send()
+                .await
+                .map_err(|e| AppError::Upstream(format!("Pipedrive unreachable: {}", e)))?;
+
+            let status = response.status();
+            if status == reqwest::StatusCode::TOO_MANY_REQUESTS && retries < MAX_RATE_LIMIT_RETRIES {
+                // Limits are per 2-second window
+                retries += 1;
+                tokio::time::sleep(Duration::from_secs(2)).await;
+                continue;
+            }
+            if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+                return Err(AppError::BadRequest(format!(
+                    "Pipedrive refused the API token for {} ({})",
+                    object_type, status
+                )));
+            }
+            if !status.is_success() {
+                let text = response.text().await.unwrap_or_default();
+                return Err(AppError::Upstream(format!("Pipedrive returned {}: {}", status, text)));
+            }
+
+            return response
+                .json()
+                .await
+                .map_err(|e| AppError::Upstream(format!("Unreadable Pipedrive response: {}", e)));
+        }
+    }
+}
+
+fn to_batch(export: PipedriveExport) -> ImportBatch {
+    ImportBatch {
+        companies: export.organizations.iter().filter_map(to_company).collect(),
+        contacts: export.persons.iter().filter_map(to_contact).collect(),
+        deals: export.deals.iter().filter_map(to_deal).collect(),
+        notes: export.notes.iter().filter_map(to_note).collect(),
+    }
+}
+
+fn to_company(object: &PipedriveObject) -> Option<ImportedCompany> {
+    let domain = object.text("website").and_then(|w| normalize_domain(&w));
+
+    Some(ImportedCompany {
+        external_id: object.text("id")?,
+        name: object.text("name").or_else(|| domain.clone()).unwrap_or_default(),
+        domain,
+        industry: None,
+        size: None,
+    })
+}
+
+fn to_contact(object: &PipedriveObject) -> Option<ImportedContact> {
+    // Older exports only have the full name
+    let full_name = object.text("name");
+    let (first, last) = match full_name.as_deref().map(|n| n.split_once(' ')) {
+        Some(Some((first, last))) => (Some(first.to_string()), Some(last.trim().to_string())),
+        Some(None) => (full_name.clone(), None),
+        None => (None, None),
+    };
+    let won_deals = object.text("won_deals_count").and_then(|c| c.parse::<u32>().ok()).unwrap_or(0);
+
+    Some(ImportedContact {
+        external_id: object.text("id")?,
+        email: object.primary("email").map(|e| e.to_lowercase()),
+        first_name: object.text("first_name").or(first),
+        last_name: object.text("last_name").or(last),
+        phone: object.primary("phone"),
+        job_title: None,
+        // Pipedrive has no lifecycle stage; someone who bought is a customer
+        status: if won_deals > 0 { ContactStatus::Customer } else { ContactStatus::Lead },
+        company_external_id: object.linked_id("org_id"),
+    })
+}
+
+/// Deleted deals are left behind
+fn to_deal(object: &PipedriveObject) -> Option<ImportedDeal> {
+    let external_id = object.text("id")?;
+    let stage = match object.text("status").as_deref() {
+        Some("deleted") => return None,
+        Some("won") => DealStage::ClosedWon,
+        Some("lost") => DealStage::ClosedLost,
+        // Stages are per pipeline and named freely, so open deals start at prospecting
+        _ => DealStage::Prospecting,
+    };
+    let close_date = if stage.is_closed() {
+        ["won_time", "lost_time", "close_time"]
+            .iter()
+            .find_map(|field| object.text(field).and_then(|t| parse_timestamp(&t)))
+    } else {
+        None
+    };
+
+    Some(ImportedDeal {
+        name: object
+            .text("title")
+            .unwrap_or_else(|| format!("Pipedrive deal {}", external_id)),
+        external_id,
+        value: object
+            .text("value")
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| v.is_finite() && *v >= 0.0)
+            .unwrap_or(0.0),
+        stage,
+        close_date: close_date.or_else(|| object.text("expected_close_date").and_then(|d| parse_timestamp(&d))),
+        contact_external_id: object.linked_id("person_id"),
+        company_external_id: object.linked_id("org_id"),
+    })
+}
+
+fn to_note(object: &PipedriveObject) -> Option<ImportedNote> {
+    Some(ImportedNote {
+        external_id: object.text("id")?,
+        body: note_text(&object.text("content").unwrap_or_default()),
+        timestamp: object.text("add_time").and_then(|t| parse_timestamp(&t)),
+        contact_external_ids: object.linked_id("person_id").into_iter().collect(),
+    })
+}
+
+/// `YYYY-MM-DD HH:MM:SS` in UTC, or a bare date
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(time) = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S") {
+        return Some(time.and_utc());
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|time| time.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_export() {
+        let batch = parse_export(serde_json::json!({
+            "organizations": [
+                { "id": 10, "name": "Acme", "website": "https://www.acme.com/" }
+            ],
+            "persons": [
+                {
+                    "id": 1,
+                    "name": "Ada Lovelace",
+                    "email": [
+                        { "value": "ada@work.example", "primary": false },
+                        { "value": "Ada@Acme.com", "primary": true }
+                    ],
+                    "phone": [{ "value": "+44 20 7946 0000", "primary": true }],
+                    "org_id": { "value": 10, "name": "Acme" },
+                    "won_deals_count": 2
+                }
+            ],
+            "deals": [
+                {
+                    "id": 20, "title": "Renewal", "value": 1200.5, "status": "won",
+                    "won_time": "2026-03-01 12:30:00", "person_id": 1, "org_id": { "value": 10 }
+                },
+                { "id": 21, "title": "Gone", "status": "deleted" }
+            ],
+            "notes": [
+                { "id": 30, "content": "<p>Called &amp; left a message</p>", "add_time": "2026-01-01 00:00:00", "person_id": 1 }
+            ]
+        }))
+        .unwrap();
+
+        let company = &batch.companies[0];
+        assert_eq!((company.external_id.as_str(), company.name.as_str()), ("10", "Acme"));
+        assert_eq!(company.domain.as_deref(), Some("acme.com"));
+
+        let contact = &batch.contacts[0];
+        assert_eq!(contact.email.as_deref(), Some("ada@acme.com"));
+        assert_eq!(contact.first_name.as_deref(), Some("Ada"));
+        assert_eq!(contact.last_name.as_deref(), Some("Lovelace"));
+        assert_eq!(contact.phone.as_deref(), Some("+44 20 7946 0000"));
+        assert_eq!(contact.status, ContactStatus::Customer);
+        assert_eq!(contact.company_external_id.as_deref(), Some("10"));
+
+        assert_eq!(batch.deals.len(), 1);
+        let deal = &batch.deals[0];
+        assert_eq!(deal.stage, DealStage::ClosedWon);
+        assert_eq!(deal.value, 1200.5);
+        assert_eq!(deal.close_date, parse_timestamp("2026-03-01 12:30:00"));
+        assert_eq!(deal.contact_external_id.as_deref(), Some("1"));
+        assert_eq!(deal.company_external_id.as_deref(), Some("10"));
+
+        let note = &batch.notes[0];
+        assert_eq!(note.body, "Called & left a message");
+        assert_eq!(note.contact_external_ids, vec!["1".to_string()]);
+        assert!(note.timestamp.is_some());
+    }
+
+    #[test]
+    fn test_objects_without_an_id_are_skipped() {
+        let batch = parse_export(serde_json::json!({ "persons": [{ "name": "Nobody" }] })).unwrap();
+        assert!(batch.contacts.is_empty());
+
+        assert!(parse_export(serde_json::json!({ "persons": "nope" })).is_err());
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(
+            parse_timestamp("2026-03-01"),
+            "2026-03-01T00:00:00Z".parse::<DateTime<Utc>>().ok()
+        );
+        assert_eq!(parse_timestamp("next week"), None);
+    }
+}
@@ -0,0 +1,195 @@
+//! Pipeline Service - the kanban board of contacts or deals
+//!
+//! Moving a card to another column is a status change and follows the same
+//! rules as anywhere else: contact moves go through the contact service (so
+//! they are audited and announced by webhook) and deal moves through the
+//! deal stage state machine. The card's place in the column is worked out
+//! by `domain::pipeline` and saved in the same write as its new status.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use surrealdb::sql::Thing;
+
+use crate::db::Database;
+use crate::domain::{
+    closed_at, deal_outcome, place_card, win_loss_reason, AuditEntity, ContactStatus, DealStage, Placement,
+};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Contact, ContactResponse, Deal, DealResponse, PipelineBoard, PipelineCard, PipelineColumn, PipelineKind,
+    PipelineMoveRequest,
+};
+use crate::repositories::{ContactRepository, PipelineRepository};
+use crate::services::read_cache::{CacheScope, ReadCache};
+use crate::services::{AuditService, AuthenticatedUser, ContactService, UpdateContactInput};
+
+/// Most cards returned per column
+const MAX_COLUMN_CARDS: u32 = 200;
+
+pub struct PipelineService {
+    pipeline: PipelineRepository,
+    contact_repo: ContactRepository,
+    contacts: Arc<ContactService>,
+    audit: Arc<AuditService>,
+    cache: Arc<ReadCache>,
+}
+
+impl PipelineService {
+    pub fn new(
+        db: Arc<Database>,
+        contacts: Arc<ContactService>,
+        audit: Arc<AuditService>,
+        cache: Arc<ReadCache>,
+    ) -> Self {
+        Self {
+            pipeline: PipelineRepository::new(Arc::clone(&db)),
+            contact_repo: ContactRepository::new(db),
+            contacts,
+            audit,
+            cache,
+        }
+    }
+
+    /// Every column of the board, each with its first `limit` cards
+    pub async fn board(&self, workspace_id: &str, kind: PipelineKind, limit: u32) -> AppResult<PipelineBoard> {
+        let limit = limit.min(MAX_COLUMN_CARDS);
+
+        let columns = match kind {
+            PipelineKind::Contacts => {
+                let statuses: Vec<&str> = ContactStatus::ALL.iter().map(ContactStatus::as_str).collect();
+                let columns = self
+                    .pipeline
+                    .find_columns::<Contact>(workspace_id, kind, &statuses, limit)
+                    .await?;
+                columns_of(&statuses, columns, |c| PipelineCard::Contact(Box::new(ContactResponse::from(c))))
+            }
+            PipelineKind::Deals => {
+                let stages: Vec<&str> = DealStage::ALL.iter().map(DealStage::as_str).collect();
+                let columns = self
+                    .pipeline
+                    .find_columns::<Deal>(workspace_id, kind, &stages, limit)
+                    .await?;
+                columns_of(&stages, columns, |d| PipelineCard::Deal(DealResponse::from(d)))
+            }
+        };
+
+        Ok(PipelineBoard { kind, columns })
+    }
+
+    /// Move a card to a column, changing its status if needed, and place it
+    pub async fn move_card(&self, actor: &AuthenticatedUser, req: PipelineMoveRequest) -> AppResult<PipelineCard> {
+        match req.kind {
+            PipelineKind::Contacts => self.move_contact(actor, req).await,
+            PipelineKind::Deals => self.move_deal(actor, req).await,
+        }
+    }
+
+    async fn move_contact(&self, actor: &AuthenticatedUser, req: PipelineMoveRequest) -> AppResult<PipelineCard> {
+        let workspace_id = actor.workspace_id.as_str();
+        let status = ContactStatus::ALL
+            .into_iter()
+            .find(|s| s.as_str() == req.status)
+            .ok_or_else(|| AppError::BadRequest(format!("Unknown contact status '{}'", req.status)))?;
+
+        let mut stored = self
+            .contact_repo
+            .find_by_id_with_id(workspace_id, &req.id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Contact {} not found", req.id)))?;
+
+        let (position, others) = self.placement(workspace_id, req.kind, status.as_str(), &req.id, req.index).await?;
+        if stored.contact.status == status {
+            let card = Thing::from(("contact", req.id.as_str()));
+            let positions: Vec<(Thing, f64)> = others.into_iter().chain([(card, position)]).collect();
+            self.pipeline.set_positions(workspace_id, &positions).await?;
+            stored.contact.pipeline_position = Some(position);
+        } else {
+            // Placing the others first keeps their order whether or not the move succeeds
+            self.pipeline.set_positions(workspace_id, &others).await?;
+            let input = UpdateContactInput {
+                status: Some(status),
+                win_loss_reason: req.win_loss_reason,
+                pipeline_position: Some(position),
+                ..Default::default()
+            };
+            stored = self.contacts.update(actor, &req.id, input).await?;
+        }
+
+        Ok(PipelineCard::Contact(Box::new(ContactResponse::from_stored(stored))))
+    }
+
+    async fn move_deal(&self, actor: &AuthenticatedUser, req: PipelineMoveRequest) -> AppResult<PipelineCard> {
+        let workspace_id = actor.workspace_id.as_str();
+        let stage = DealStage::ALL
+            .into_iter()
+            .find(|s| s.as_str() == req.status)
+            .ok_or_else(|| AppError::BadRequest(format!("Unknown deal stage '{}'", req.status)))?;
+
+        let before = self
+            .pipeline
+            .find_deal(workspace_id, &req.id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Deal not found".into()))?;
+
+        let (position, others) = self.placement(workspace_id, req.kind, stage.as_str(), &req.id, req.index).await?;
+        let mut deal = before.clone();
+        deal.pipeline_position = Some(position);
+        if before.stage == stage {
+            let card = Thing::from(("deal", req.id.as_str()));
+            let positions: Vec<(Thing, f64)> = others.into_iter().chain([(card, position)]).collect();
+            self.pipeline.set_positions(workspace_id, &positions).await?;
+            return Ok(PipelineCard::Deal(deal.into()));
+        }
+
+        let now = Utc::now();
+        deal.stage = before.stage.transition_to(stage)?;
+        deal.closed_at = closed_at(before.stage, deal.stage, before.closed_at, now);
+        deal.win_loss_reason = win_loss_reason(deal_outcome(deal.stage), req.win_loss_reason)?;
+        deal.updated_at = now;
+
+        self.pipeline.move_deal(workspace_id, &deal, &others).await?;
+        self.cache.invalidate(workspace_id, CacheScope::Deals).await;
+        self.audit
+            .record_update(actor, AuditEntity::Deal, &req.id, &before, &deal)
+            .await;
+
+        Ok(PipelineCard::Deal(deal.into()))
+    }
+
+    /// Where a card dropped in a column goes, and the other cards to place with it
+    async fn placement(
+        &self,
+        workspace_id: &str,
+        kind: PipelineKind,
+        status: &str,
+        id: &str,
+        index: Option<usize>,
+    ) -> AppResult<(f64, Vec<(Thing, f64)>)> {
+        let column = self.pipeline.find_positions(workspace_id, kind, status, id).await?;
+        let positions: Vec<Option<f64>> = column.iter().map(|c| c.pipeline_position).collect();
+        let Placement { position, others } = place_card(&positions, index.unwrap_or(column.len()));
+
+        let others = others
+            .into_iter()
+            .map(|(i, position)| (column[i].id.clone(), position))
+            .collect();
+        Ok((position, others))
+    }
+}
+
+fn columns_of<T>(
+    statuses: &[&str],
+    columns: Vec<(Vec<T>, u64)>,
+    card: impl Fn(T) -> PipelineCard,
+) -> Vec<PipelineColumn> {
+    statuses
+        .iter()
+        .zip(columns)
+        .map(|(status, (cards, total))| PipelineColumn {
+            status: status.to_string(),
+            total,
+            cards: cards.into_iter().map(&card).collect(),
+        })
+        .collect()
+}
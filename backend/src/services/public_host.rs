@@ -0,0 +1,55 @@
+//! Public host checks at connect time
+//!
+//! A host name that passed `domain::validate_public_host` when it was saved
+//! can later resolve to an internal address. Connections to hosts a
+//! workspace chose resolve them first and refuse if any address isn't
+//! public.
+
+use std::net::SocketAddr;
+
+use crate::domain::{is_internal_host, is_public_ip};
+use crate::error::{AppError, AppResult};
+
+/// The addresses `host` resolves to, all of them public
+pub async fn resolve_public(host: &str, port: u16) -> AppResult<Vec<SocketAddr>> {
+    if is_internal_host(host) {
+        return Err(AppError::Validation(format!("{} is not a public host", host)));
+    }
+
+    let name = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name, port))
+        .await
+        .map_err(|e| AppError::Upstream(format!("Failed to resolve {}: {}", host, e)))?
+        .collect();
+
+    if addrs.is_empty() {
+        return Err(AppError::Upstream(format!("{} has no addresses", host)));
+    }
+    if let Some(internal) = addrs.iter().find(|addr| !is_public_ip(addr.ip())) {
+        return Err(AppError::Validation(format!(
+            "{} resolves to {}, which is not a public address",
+            host,
+            internal.ip()
+        )));
+    }
+
+    Ok(addrs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_internal_literals_are_refused_without_a_lookup() {
+        assert!(matches!(resolve_public("127.0.0.1", 80).await, Err(AppError::Validation(_))));
+        assert!(matches!(resolve_public("[::1]", 80).await, Err(AppError::Validation(_))));
+        assert!(matches!(resolve_public("localhost", 80).await, Err(AppError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_public_literals_resolve_to_themselves() {
+        let addrs = resolve_public("8.8.8.8", 443).await.unwrap();
+        assert_eq!(addrs, vec!["8.8.8.8:443".parse().unwrap()]);
+    }
+}
@@ -0,0 +1,65 @@
+//! Rate limiter - sliding-window limits on how often something happens
+//!
+//! Hits are counted in memory, so each server instance limits on its own.
+//! Callers keep the limiter behind a lock.
+
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{DateTime, Utc};
+
+/// Tracked keys beyond which idle ones are forgotten
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+/// Sliding-window counts of recent hits per key
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    hits: HashMap<String, VecDeque<DateTime<Utc>>>,
+}
+
+impl RateLimiter {
+    /// Record a hit on every key if none of them is over its limit
+    pub fn admit(&mut self, limits: &[(String, u32)], now: DateTime<Utc>, window: chrono::Duration) -> bool {
+        let cutoff = now - window;
+        if self.hits.len() > MAX_TRACKED_KEYS {
+            self.hits.retain(|_, hits| hits.back().is_some_and(|t| *t > cutoff));
+        }
+
+        for (key, limit) in limits {
+            let hits = self.hits.entry(key.clone()).or_default();
+            while hits.front().is_some_and(|t| *t <= cutoff) {
+                hits.pop_front();
+            }
+            if hits.len() >= *limit as usize {
+                return false;
+            }
+        }
+
+        for (key, _) in limits {
+            self.hits.entry(key.clone()).or_default().push_back(now);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter_admits_within_limits() {
+        let mut limiter = RateLimiter::default();
+        let window = chrono::Duration::minutes(60);
+        let now = Utc::now();
+        let limits = vec![("page:lp1".to_string(), 3), ("client:lp1:1.2.3.4".to_string(), 2)];
+
+        assert!(limiter.admit(&limits, now, window));
+        assert!(limiter.admit(&limits, now, window));
+        // The client is at its limit; the page isn't charged for the rejection
+        assert!(!limiter.admit(&limits, now, window));
+        assert!(limiter.admit(&limits[..1], now, window));
+        assert!(!limiter.admit(&limits[..1], now, window));
+
+        // Hits older than the window no longer count
+        assert!(limiter.admit(&limits, now + window + chrono::Duration::seconds(1), window));
+    }
+}
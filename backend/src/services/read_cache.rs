@@ -0,0 +1,404 @@
+//! Read cache - hot lookups kept for a short while instead of recomputed
+//!
+//! Pipeline summaries, tag lists and segment counts are aggregates over a
+//! whole workspace, and contact summaries are written by the AI provider; computing them on every request is wasteful when they
+//! change far less often than they are read. Values are kept per workspace
+//! and per `CacheScope` (the records they are computed from) as JSON, for
+//! at most the configured TTL. Write paths call `invalidate` for the scope
+//! they touched; writers that don't (imports, background jobs) are caught
+//! up with by the TTL. Invalidating bumps the group's generation, and
+//! values are stored with the generation they were loaded in, so a load
+//! that was already running when its group was invalidated is never read
+//! back.
+//!
+//! The `memory` backend keeps values in this process. The `redis` backend
+//! (built with the `redis` feature) shares them between server instances,
+//! so an invalidation on one instance is seen by all. A failing backend is
+//! logged and bypassed; lookups then compute every time.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::config::CacheConfig;
+use crate::error::{AppError, AppResult};
+use crate::secrets::SecretsManager;
+
+/// The records a cached value is computed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheScope {
+    /// Tag lists and segment counts
+    Contacts,
+    /// Pipeline summaries
+    Deals,
+    /// Contact summaries and recommendations
+    Timeline,
+}
+
+impl CacheScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Contacts => "contacts",
+            Self::Deals => "deals",
+            Self::Timeline => "timeline",
+        }
+    }
+}
+
+pub trait CacheBackend: Send + Sync {
+    /// Short name, e.g. `memory`
+    fn name(&self) -> &'static str;
+
+    fn get<'a>(&'a self, group: &'a str, key: &'a str) -> BoxFuture<'a, AppResult<Option<String>>>;
+
+    fn put<'a>(&'a self, group: &'a str, key: &'a str, value: String) -> BoxFuture<'a, AppResult<()>>;
+
+    /// How many times the group has been invalidated
+    fn generation<'a>(&'a self, group: &'a str) -> BoxFuture<'a, AppResult<u64>>;
+
+    /// Bump the group's generation and drop every value in it
+    fn invalidate<'a>(&'a self, group: &'a str) -> BoxFuture<'a, AppResult<()>>;
+}
+
+/// Build the cache with the backend selected in config
+pub async fn build_read_cache(config: &CacheConfig, secrets: &SecretsManager) -> AppResult<ReadCache> {
+    let ttl = Duration::from_secs(config.ttl_secs);
+
+    let backend: Option<Arc<dyn CacheBackend>> = match config.backend.as_str() {
+        "none" => None,
+        "memory" | "" => Some(Arc::new(MemoryCache::new(config.max_entries, ttl))),
+        #[cfg(feature = "redis")]
+        "redis" => {
+            let url = secrets
+                .get_secret(&config.redis_url_secret)
+                .map_err(|e| AppError::Internal(format!("Read cache 'redis' needs a URL: {}", e)))?;
+            Some(Arc::new(RedisCache::connect(&url, ttl).await?))
+        }
+        #[cfg(not(feature = "redis"))]
+        "redis" => {
+            let _ = secrets;
+            return Err(AppError::Internal(
+                "Read cache 'redis' needs the server built with the `redis` feature".into(),
+            ));
+        }
+        other => return Err(AppError::Internal(format!("Unknown read cache backend '{}'", other))),
+    };
+
+    if let Some(backend) = &backend {
+        tracing::info!("Read cache: {} (TTL {:?})", backend.name(), ttl);
+    }
+    Ok(ReadCache { backend })
+}
+
+/// Cached lookups with explicit invalidation; shared through `AppState`
+pub struct ReadCache {
+    backend: Option<Arc<dyn CacheBackend>>,
+}
+
+impl ReadCache {
+    /// The cached value under `key`, or `load`'s result, which is then cached
+    ///
+    /// Errors from `load` are returned and not cached.
+    pub async fn get_or_load<T, F, Fut>(&self, workspace_id: &str, scope: CacheScope, key: &str, load: F) -> AppResult<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = AppResult<T>>,
+    {
+        let Some(backend) = &self.backend else {
+            return load().await;
+        };
+        let group = group(workspace_id, scope);
+
+        // Taken before loading: a value loaded across an invalidation is stored
+        // under the old generation and never read back
+        let generation = match backend.generation(&group).await {
+            Ok(generation) => generation,
+            Err(e) => {
+                tracing::warn!("Read cache lookup failed: {}", e);
+                return load().await;
+            }
+        };
+
+        match backend.get(&group, key).await {
+            Ok(Some(cached)) => match stored_value(&cached, generation).map(serde_json::from_str) {
+                Some(Ok(value)) => return Ok(value),
+                Some(Err(e)) => tracing::warn!("Unreadable cached {} '{}': {}", group, key, e),
+                None => {}
+            },
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Read cache lookup failed: {}", e),
+        }
+
+        let value = load().await?;
+        match serde_json::to_string(&value) {
+            Ok(json) => {
+                if let Err(e) = backend.put(&group, key, format!("{}:{}", generation, json)).await {
+                    tracing::warn!("Read cache store failed: {}", e);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to serialize {} '{}' for the read cache: {}", group, key, e),
+        }
+
+        Ok(value)
+    }
+
+    /// Forget everything the workspace has cached from the scope's records
+    ///
+    /// Called by write paths once their change is stored.
+    pub async fn invalidate(&self, workspace_id: &str, scope: CacheScope) {
+        if let Some(backend) = &self.backend {
+            if let Err(e) = backend.invalidate(&group(workspace_id, scope)).await {
+                tracing::warn!("Read cache invalidation failed: {}", e);
+            }
+        }
+    }
+}
+
+fn group(workspace_id: &str, scope: CacheScope) -> String {
+    format!("{}:{}", workspace_id, scope.as_str())
+}
+
+/// The JSON of a stored `<generation>:<json>` value, if it is of `generation`
+fn stored_value(stored: &str, generation: u64) -> Option<&str> {
+    let (stored_generation, json) = stored.split_once(':')?;
+    (stored_generation.parse::<u64>().ok()? == generation).then_some(json)
+}
+
+/// Values kept in this process, evicted after the TTL or when full
+pub struct MemoryCache {
+    cache: moka::future::Cache<(String, String), String>,
+    generations: Mutex<HashMap<String, u64>>,
+}
+
+impl MemoryCache {
+    fn new(max_entries: u64, ttl: Duration) -> Self {
+        Self {
+            cache: moka::future::Cache::builder()
+                .max_capacity(max_entries)
+                .time_to_live(ttl)
+                .support_invalidation_closures()
+                .build(),
+            generations: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl CacheBackend for MemoryCache {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn get<'a>(&'a self, group: &'a str, key: &'a str) -> BoxFuture<'a, AppResult<Option<String>>> {
+        Box::pin(async move { Ok(self.cache.get(&(group.to_string(), key.to_string())).await) })
+    }
+
+    fn put<'a>(&'a self, group: &'a str, key: &'a str, value: String) -> BoxFuture<'a, AppResult<()>> {
+        Box::pin(async move {
+            self.cache.insert((group.to_string(), key.to_string()), value).await;
+            Ok(())
+        })
+    }
+
+    fn generation<'a>(&'a self, group: &'a str) -> BoxFuture<'a, AppResult<u64>> {
+        Box::pin(async move {
+            let generations = self.generations.lock().unwrap_or_else(|e| e.into_inner());
+            Ok(generations.get(group).copied().unwrap_or(0))
+        })
+    }
+
+    fn invalidate<'a>(&'a self, group: &'a str) -> BoxFuture<'a, AppResult<()>> {
+        Box::pin(async move {
+            *self
+                .generations
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .entry(group.to_string())
+                .or_insert(0) += 1;
+
+            let owned = group.to_string();
+            self.cache
+                .invalidate_entries_if(move |(g, _), _| *g == owned)
+                .map_err(|e| AppError::Internal(format!("Failed to invalidate cached {}: {}", group, e)))?;
+            Ok(())
+        })
+    }
+}
+
+/// Values kept in Redis, one hash per group that expires a TTL after it was started
+#[cfg(feature = "redis")]
+pub struct RedisCache {
+    connection: redis::aio::ConnectionManager,
+    ttl: Duration,
+}
+
+#[cfg(feature = "redis")]
+impl RedisCache {
+    async fn connect(url: &str, ttl: Duration) -> AppResult<Self> {
+        let client = redis::Client::open(url).map_err(|e| AppError::Internal(format!("Invalid Redis URL: {}", e)))?;
+        let connection = redis::aio::ConnectionManager::new(client)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to connect to Redis: {}", e)))?;
+
+        Ok(Self { connection, ttl })
+    }
+
+    fn hash(group: &str) -> String {
+        format!("crm:cache:{}", group)
+    }
+
+    fn generation_key(group: &str) -> String {
+        format!("crm:cache-generation:{}", group)
+    }
+}
+
+#[cfg(feature = "redis")]
+fn redis_error(e: redis::RedisError) -> AppError {
+    AppError::Upstream(format!("Redis: {}", e))
+}
+
+#[cfg(feature = "redis")]
+impl CacheBackend for RedisCache {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    fn get<'a>(&'a self, group: &'a str, key: &'a str) -> BoxFuture<'a, AppResult<Option<String>>> {
+        Box::pin(async move {
+            redis::cmd("HGET")
+                .arg(Self::hash(group))
+                .arg(key)
+                .query_async(&mut self.connection.clone())
+                .await
+                .map_err(redis_error)
+        })
+    }
+
+    fn put<'a>(&'a self, group: &'a str, key: &'a str, value: String) -> BoxFuture<'a, AppResult<()>> {
+        Box::pin(async move {
+            let hash = Self::hash(group);
+            // NX keeps the first expiry, so a busy group still goes stale after the TTL
+            redis::pipe()
+                .atomic()
+                .cmd("HSET")
+                .arg(&hash)
+                .arg(key)
+                .arg(value)
+                .ignore()
+                .cmd("EXPIRE")
+                .arg(&hash)
+                .arg(self.ttl.as_secs().max(1))
+                .arg("NX")
+                .ignore()
+                .query_async(&mut self.connection.clone())
+                .await
+                .map_err(redis_error)
+        })
+    }
+
+    fn generation<'a>(&'a self, group: &'a str) -> BoxFuture<'a, AppResult<u64>> {
+        Box::pin(async move {
+            let generation: Option<u64> = redis::cmd("GET")
+                .arg(Self::generation_key(group))
+                .query_async(&mut self.connection.clone())
+                .await
+                .map_err(redis_error)?;
+            Ok(generation.unwrap_or(0))
+        })
+    }
+
+    fn invalidate<'a>(&'a self, group: &'a str) -> BoxFuture<'a, AppResult<()>> {
+        Box::pin(async move {
+            redis::pipe()
+                .atomic()
+                .cmd("INCR")
+                .arg(Self::generation_key(group))
+                .ignore()
+                .cmd("DEL")
+                .arg(Self::hash(group))
+                .ignore()
+                .query_async(&mut self.connection.clone())
+                .await
+                .map_err(redis_error)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn memory_cache() -> ReadCache {
+        ReadCache {
+            backend: Some(Arc::new(MemoryCache::new(100, Duration::from_secs(60)))),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_or_load_caches_until_invalidated() {
+        let cache = memory_cache();
+        let loads = &AtomicU32::new(0);
+        let load = move || async move { Ok::<_, AppError>(loads.fetch_add(1, Ordering::SeqCst) + 1) };
+
+        assert_eq!(cache.get_or_load("acme", CacheScope::Deals, "30d", load).await.unwrap(), 1);
+        assert_eq!(cache.get_or_load("acme", CacheScope::Deals, "30d", load).await.unwrap(), 1);
+        // Other workspaces, scopes and keys are cached separately
+        assert_eq!(cache.get_or_load("globex", CacheScope::Deals, "30d", load).await.unwrap(), 2);
+        assert_eq!(cache.get_or_load("acme", CacheScope::Contacts, "30d", load).await.unwrap(), 3);
+
+        cache.invalidate("acme", CacheScope::Deals).await;
+        assert_eq!(cache.get_or_load("acme", CacheScope::Deals, "30d", load).await.unwrap(), 4);
+        assert_eq!(cache.get_or_load("acme", CacheScope::Contacts, "30d", load).await.unwrap(), 3);
+        assert_eq!(cache.get_or_load("globex", CacheScope::Deals, "30d", load).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_load_running_across_an_invalidation_is_not_kept() {
+        let cache = memory_cache();
+
+        let stale = cache
+            .get_or_load("acme", CacheScope::Deals, "30d", || async {
+                // A write lands while the value is being computed
+                cache.invalidate("acme", CacheScope::Deals).await;
+                Ok::<_, AppError>(1u32)
+            })
+            .await
+            .unwrap();
+        assert_eq!(stale, 1);
+
+        let fresh = cache
+            .get_or_load("acme", CacheScope::Deals, "30d", || async { Ok::<_, AppError>(2u32) })
+            .await
+            .unwrap();
+        assert_eq!(fresh, 2);
+    }
+
+    #[test]
+    fn test_stored_value() {
+        assert_eq!(stored_value("3:{\"a\":1}", 3), Some("{\"a\":1}"));
+        assert_eq!(stored_value("2:{\"a\":1}", 3), None);
+        assert_eq!(stored_value("{\"a\":1}", 3), None);
+    }
+
+    #[tokio::test]
+    async fn test_failed_loads_are_not_cached() {
+        let cache = memory_cache();
+
+        let failed: AppResult<u32> = cache
+            .get_or_load("acme", CacheScope::Contacts, "tags", || async { Err(AppError::Internal("down".into())) })
+            .await;
+        assert!(failed.is_err());
+
+        let loaded = cache
+            .get_or_load("acme", CacheScope::Contacts, "tags", || async { Ok(7u32) })
+            .await
+            .unwrap();
+        assert_eq!(loaded, 7);
+    }
+}
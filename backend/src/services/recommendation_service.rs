@@ -0,0 +1,169 @@
+//! Recommendation Service - the next best action for every contact
+//!
+//! Gathers each contact's signals, lets the rules in
+//! `domain::recommendation` pick and prioritize an action, and has the AI
+//! provider reword the most urgent ones when one is configured. Results are
+//! cached until the workspace's timeline changes, and the provider rewords
+//! at most `ai.recommendations_per_hour` lists per workspace; past that, the
+//! rules' wording is returned.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use surrealdb::sql::Thing;
+
+use crate::ai::ai_recommendation::word_recommendations;
+use crate::ai::{ContentGenerator, GenerationContext};
+use crate::config::AiConfig;
+use crate::db::Database;
+use crate::domain::{
+    calculate_engagement_trend, calculate_engagement_velocity, recommend_next_action, ActionPriority,
+    EngagementConfig, EngagementLevel, Interaction, RecommendationSignals,
+};
+use crate::error::{AppError, AppResult};
+use crate::models::{RecommendationQuery, RecommendationResponse};
+use crate::repositories::{EngagementRepository, RecommendationRepository};
+use crate::services::rate_limiter::RateLimiter;
+use crate::services::read_cache::{CacheScope, ReadCache};
+use crate::services::to_interaction;
+
+/// Contacts looked at, most engaged first
+const MAX_CONTACTS: u32 = 2000;
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 200;
+
+pub struct RecommendationService {
+    repo: RecommendationRepository,
+    engagement: EngagementRepository,
+    generator: Arc<ContentGenerator>,
+    cache: Arc<ReadCache>,
+    config: EngagementConfig,
+    limiter: Mutex<RateLimiter>,
+    worded_per_hour: u32,
+}
+
+impl RecommendationService {
+    pub fn new(db: Arc<Database>, generator: Arc<ContentGenerator>, cache: Arc<ReadCache>, config: &AiConfig) -> Self {
+        Self {
+            repo: RecommendationRepository::new(Arc::clone(&db)),
+            engagement: EngagementRepository::new(db),
+            generator,
+            cache,
+            config: EngagementConfig::default(),
+            limiter: Mutex::new(RateLimiter::default()),
+            worded_per_hour: config.recommendations_per_hour,
+        }
+    }
+
+    /// Next best actions across the workspace's contacts, most urgent first
+    ///
+    /// Within a priority, more engaged contacts come first.
+    pub async fn recommendations(
+        &self,
+        workspace_id: &str,
+        query: RecommendationQuery,
+    ) -> AppResult<Vec<RecommendationResponse>> {
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let key = format!("recommendations:{:?}:{}", query.priority, limit);
+        self.cache
+            .get_or_load(workspace_id, CacheScope::Timeline, &key, || {
+                self.recommend(workspace_id, query.priority, limit as usize)
+            })
+            .await
+    }
+
+    async fn recommend(
+        &self,
+        workspace_id: &str,
+        priority: Option<ActionPriority>,
+        limit: usize,
+    ) -> AppResult<Vec<RecommendationResponse>> {
+        let contacts = self.repo.find_contacts(workspace_id, MAX_CONTACTS).await?;
+        let ids: Vec<Thing> = contacts.iter().map(|c| c.id.clone()).collect();
+        let open_tasks = self.repo.open_task_counts(workspace_id, &ids).await?;
+        let deal_stages = self.repo.open_deal_stages(workspace_id, &ids).await?;
+
+        let mut by_contact: HashMap<String, Vec<Interaction>> = HashMap::new();
+        for activity in self.engagement.find_activity(&ids).await? {
+            if let Some(interaction) = to_interaction(&activity) {
+                by_contact.entry(activity.contact.id.to_raw()).or_default().push(interaction);
+            }
+        }
+
+        let now = Utc::now();
+        let mut recommendations: Vec<RecommendationResponse> = contacts
+            .into_iter()
+            .filter_map(|contact| {
+                let contact_id = contact.id.id.to_raw();
+                let interactions = by_contact.remove(&contact_id).unwrap_or_default();
+                let signals = RecommendationSignals {
+                    engagement_score: contact.engagement_score,
+                    trend: calculate_engagement_trend(&interactions, &self.config),
+                    velocity: calculate_engagement_velocity(&interactions, &self.config),
+                    last_interaction: interactions.iter().max_by_key(|i| i.occurred_at).cloned(),
+                    open_tasks: open_tasks.get(&contact_id).copied().unwrap_or_default(),
+                    deal_stage: deal_stages.get(&contact_id).copied(),
+                };
+                let action = recommend_next_action(&signals, now);
+                if priority.is_some_and(|p| p != action.priority) {
+                    return None;
+                }
+
+                Some(RecommendationResponse {
+                    contact_id: contact.id.id.to_string(),
+                    contact_name: format!("{} {}", contact.first_name, contact.last_name),
+                    email: contact.email,
+                    kind: action.kind,
+                    priority: action.priority,
+                    action: action.action,
+                    reason: action.reason,
+                    engagement_score: contact.engagement_score,
+                    level: EngagementLevel::from_score(contact.engagement_score),
+                    trend: signals.trend,
+                    velocity: (signals.velocity * 100.0).round() / 100.0,
+                    last_interaction_type: signals.last_interaction.as_ref().map(|i| i.interaction_type),
+                    last_interaction_at: signals.last_interaction.as_ref().map(|i| i.occurred_at),
+                    open_tasks: signals.open_tasks,
+                    deal_stage: signals.deal_stage,
+                    ai_generated: false,
+                })
+            })
+            .collect();
+
+        recommendations.sort_by(|a, b| {
+            a.priority
+                .cmp(&b.priority)
+                .then_with(|| b.engagement_score.total_cmp(&a.engagement_score))
+        });
+        recommendations.truncate(limit);
+
+        if self.admit_wording(workspace_id)? {
+            let ctx = GenerationContext {
+                workspace_id,
+                campaign_id: None,
+            };
+            word_recommendations(&self.generator, ctx, &mut recommendations).await;
+        }
+
+        Ok(recommendations)
+    }
+
+    /// Whether the workspace may have another list worded by the AI provider this hour
+    fn admit_wording(&self, workspace_id: &str) -> AppResult<bool> {
+        if self.generator.uses_templates() {
+            return Ok(false);
+        }
+
+        Ok(self
+            .limiter
+            .lock()
+            .map_err(|_| AppError::Internal("Rate limiter lock poisoned".into()))?
+            .admit(
+                &[(workspace_id.to_string(), self.worded_per_hour)],
+                Utc::now(),
+                chrono::Duration::hours(1),
+            ))
+    }
+}
@@ -0,0 +1,184 @@
+//! Relationship Service - who knows whom, and who can make a warm introduction
+//!
+//! Relationships are edges between contacts and companies of one workspace;
+//! see `domain::relationship` for the kinds and which way they read. A
+//! network is read in two steps: the relationships of the contact, then
+//! the relationships of everything the contact is related to.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use surrealdb::sql::Thing;
+
+use crate::db::Database;
+use crate::domain::{network_reach, validate_relationship, NodeRef, NodeType, RelationshipKind};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    CreateRelationshipRequest, NetworkNodeResponse, NetworkResponse, Relationship, RelationshipNodeResponse,
+    RelationshipResponse,
+};
+use crate::repositories::{RelationshipRepository, MAX_EDGES_PER_READ};
+use crate::services::AuthenticatedUser;
+
+pub struct RelationshipService {
+    repo: RelationshipRepository,
+}
+
+impl RelationshipService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            repo: RelationshipRepository::new(db),
+        }
+    }
+
+    /// Relate two contacts or companies of the workspace
+    pub async fn create(
+        &self,
+        actor: &AuthenticatedUser,
+        req: CreateRelationshipRequest,
+    ) -> AppResult<RelationshipResponse> {
+        let from = NodeRef::new(req.from_type, req.from_id);
+        let to = NodeRef::new(req.to_type, req.to_id);
+        let note = validate_relationship(req.kind, &from, &to, req.note.as_deref())?;
+
+        let from_name = self.require_name(&actor.workspace_id, &from).await?;
+        let to_name = self.require_name(&actor.workspace_id, &to).await?;
+
+        let (from_thing, to_thing) = (thing(&from), thing(&to));
+        if self.repo.exists(&actor.workspace_id, req.kind, &from_thing, &to_thing).await? {
+            return Err(AppError::Conflict(format!(
+                "{} '{}' is already {} {} '{}'",
+                from.node_type.table(),
+                from.id,
+                req.kind.table(),
+                to.node_type.table(),
+                to.id
+            )));
+        }
+
+        let mut relationship = self
+            .repo
+            .create(&actor.workspace_id, req.kind, from_thing, to_thing, note, &actor.user_id)
+            .await?;
+        relationship.from_name = Some(from_name);
+        relationship.to_name = Some(to_name);
+
+        relationship_response(relationship)
+    }
+
+    /// Every relationship of a contact or company, either way round, newest first
+    pub async fn list(
+        &self,
+        workspace_id: &str,
+        node_type: NodeType,
+        id: &str,
+    ) -> AppResult<Vec<RelationshipResponse>> {
+        let node = NodeRef::new(node_type, id);
+        self.require_name(workspace_id, &node).await?;
+
+        let relationships = self.repo.find_touching(workspace_id, vec![thing(&node)], MAX_EDGES_PER_READ).await?;
+        relationships.into_iter().map(relationship_response).collect()
+    }
+
+    pub async fn delete(&self, workspace_id: &str, kind: RelationshipKind, id: &str) -> AppResult<()> {
+        if !self.repo.delete(workspace_id, kind, id).await? {
+            return Err(AppError::NotFound(format!("Relationship '{}' not found", id)));
+        }
+        Ok(())
+    }
+
+    /// Everything within two hops of a contact, for finding warm introductions
+    pub async fn network(&self, workspace_id: &str, contact_id: &str) -> AppResult<NetworkResponse> {
+        let root = NodeRef::new(NodeType::Contact, contact_id);
+        self.require_name(workspace_id, &root).await?;
+
+        let root_thing = thing(&root);
+        let direct = self.repo.find_touching(workspace_id, vec![root_thing.clone()], MAX_EDGES_PER_READ).await?;
+        let first_hop: Vec<Thing> = direct
+            .iter()
+            .map(|r| if r.from == root_thing { r.to.clone() } else { r.from.clone() })
+            .collect();
+        let onward = if first_hop.is_empty() {
+            Vec::new()
+        } else {
+            self.repo.find_touching(workspace_id, first_hop, MAX_EDGES_PER_READ).await?
+        };
+
+        // Edges among the first hop show up in both reads; edges of different
+        // kinds live in different tables, so they are told apart by table and ID
+        let mut seen: HashSet<String> = direct.iter().map(|r| r.id.to_string()).collect();
+        let mut relationships = direct;
+        relationships.extend(onward.into_iter().filter(|r| seen.insert(r.id.to_string())));
+
+        let mut names = HashMap::new();
+        let mut edges = Vec::with_capacity(relationships.len());
+        for r in &relationships {
+            let (from, to) = (node_ref(&r.from)?, node_ref(&r.to)?);
+            names.insert(from.clone(), r.from_name.clone());
+            names.insert(to.clone(), r.to_name.clone());
+            edges.push((from, to));
+        }
+
+        let node = |node: NodeRef| {
+            let name = names.get(&node).cloned().flatten();
+            node_response(node, name)
+        };
+        let nodes = network_reach(&root, &edges)
+            .into_iter()
+            .map(|reach| NetworkNodeResponse {
+                node: node(reach.node),
+                hops: reach.hops,
+                via: reach.via.into_iter().map(&node).collect(),
+            })
+            .collect();
+
+        Ok(NetworkResponse {
+            contact_id: contact_id.to_string(),
+            nodes,
+            relationships: relationships.into_iter().map(relationship_response).collect::<AppResult<_>>()?,
+        })
+    }
+
+    async fn require_name(&self, workspace_id: &str, node: &NodeRef) -> AppResult<String> {
+        self.repo.find_name(workspace_id, thing(node)).await?.ok_or_else(|| {
+            let kind = match node.node_type {
+                NodeType::Contact => "Contact",
+                NodeType::Company => "Company",
+            };
+            AppError::NotFound(format!("{} '{}' not found", kind, node.id))
+        })
+    }
+}
+
+fn thing(node: &NodeRef) -> Thing {
+    Thing::from((node.node_type.table(), node.id.as_str()))
+}
+
+fn node_ref(thing: &Thing) -> AppResult<NodeRef> {
+    let node_type = NodeType::from_table(&thing.tb)
+        .ok_or_else(|| AppError::Internal(format!("Relationship to unexpected record {}", thing)))?;
+    Ok(NodeRef::new(node_type, thing.id.to_raw()))
+}
+
+fn node_response(node: NodeRef, name: Option<String>) -> RelationshipNodeResponse {
+    RelationshipNodeResponse {
+        node_type: node.node_type,
+        id: node.id,
+        name,
+    }
+}
+
+fn relationship_response(r: Relationship) -> AppResult<RelationshipResponse> {
+    let kind = RelationshipKind::from_table(&r.id.tb)
+        .ok_or_else(|| AppError::Internal(format!("Unexpected relationship record {}", r.id)))?;
+
+    Ok(RelationshipResponse {
+        id: r.id.id.to_raw(),
+        kind,
+        from: node_response(node_ref(&r.from)?, r.from_name),
+        to: node_response(node_ref(&r.to)?, r.to_name),
+        note: r.note,
+        created_by: r.created_by,
+        created_at: r.created_at,
+    })
+}
@@ -0,0 +1,119 @@
+//! Reply Detector - stopping outreach to contacts who wrote back
+//!
+//! When email from a contact lands on their timeline, whether synced from a
+//! mailbox or logged by hand, their active sequence enrollments end as
+//! `replied` and the campaign emails sent to them in the days before are
+//! marked as replied to, which campaign analytics report as the reply rate.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use surrealdb::sql::Thing;
+
+use crate::db::Database;
+use crate::error::AppResult;
+use crate::models::{TimelineEntry, TimelineEntryType};
+use crate::repositories::{CampaignRecipientRepository, SequenceRepository};
+
+/// How long after a campaign email a reply still counts as answering it
+const REPLY_WINDOW_DAYS: i64 = 30;
+
+pub struct ReplyDetector {
+    recipients: CampaignRecipientRepository,
+    sequences: SequenceRepository,
+}
+
+impl ReplyDetector {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            recipients: CampaignRecipientRepository::new(Arc::clone(&db)),
+            sequences: SequenceRepository::new(db),
+        }
+    }
+
+    /// Act on the received emails among newly stored entries
+    ///
+    /// Both updates only touch what is still active or unanswered, so seeing
+    /// the same reply twice changes nothing.
+    pub async fn detect(&self, workspace_id: &str, entries: &[TimelineEntry]) -> AppResult<()> {
+        for (contact, replied_at) in first_replies(entries) {
+            let ended = self.sequences.end_replied(workspace_id, contact, replied_at).await?;
+            let sent_since = replied_at - Duration::days(REPLY_WINDOW_DAYS);
+            let replied = self
+                .recipients
+                .mark_replied(workspace_id, contact, sent_since, replied_at)
+                .await?;
+
+            if ended + replied > 0 {
+                tracing::info!(
+                    "Contact {} replied: ended {} sequence enrollments, marked {} campaign recipients",
+                    contact.id.to_raw(),
+                    ended,
+                    replied
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The earliest received email of each contact among `entries`
+fn first_replies(entries: &[TimelineEntry]) -> Vec<(&Thing, DateTime<Utc>)> {
+    // Keyed by contact ID
+    let mut first: HashMap<String, (&Thing, DateTime<Utc>)> = HashMap::new();
+    for entry in entries {
+        if matches!(entry.entry_type, TimelineEntryType::EmailReceived) {
+            first
+                .entry(entry.contact.id.to_raw())
+                .and_modify(|(_, at)| *at = (*at).min(entry.timestamp))
+                .or_insert((&entry.contact, entry.timestamp));
+        }
+    }
+
+    let mut replies: Vec<_> = first.into_values().collect();
+    replies.sort_by_key(|&(_, at)| at);
+    replies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(contact: &str, entry_type: TimelineEntryType, minutes: i64) -> TimelineEntry {
+        TimelineEntry {
+            id: None,
+            workspace: Thing::from(("workspace", "w1")),
+            contact: Thing::from(("contact", contact)),
+            company: None,
+            campaign: None,
+            entry_type,
+            content: String::new(),
+            metadata: serde_json::json!({}),
+            attachments: Vec::new(),
+            sentiment: None,
+            timestamp: Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap() + Duration::minutes(minutes),
+        }
+    }
+
+    #[test]
+    fn test_first_replies_per_contact() {
+        let entries = vec![
+            entry("a", TimelineEntryType::EmailReceived, 30),
+            entry("b", TimelineEntryType::EmailSent, 0),
+            entry("a", TimelineEntryType::EmailReceived, 10),
+            entry("c", TimelineEntryType::EmailReceived, 20),
+            entry("b", TimelineEntryType::Note, 5),
+        ];
+
+        let replies: Vec<(String, i64)> = first_replies(&entries)
+            .into_iter()
+            .map(|(contact, at)| (contact.id.to_raw(), at.timestamp() % 3600 / 60))
+            .collect();
+
+        // Only received email counts, once per contact, at its earliest
+        assert_eq!(replies, vec![("a".to_string(), 10), ("c".to_string(), 20)]);
+    }
+}
@@ -0,0 +1,136 @@
+//! Search Service - unified full-text search across the CRM
+//!
+//! Queries contacts, companies and timeline entries in parallel and merges
+//! them into a single list ranked by relevance score. Scores come from the
+//! BM25 indexes, so they are comparable across tables.
+
+use std::sync::Arc;
+
+use crate::db::Database;
+use crate::error::{AppError, AppResult};
+use crate::models::{SearchResult, SearchResultType};
+use crate::repositories::{CompanyHit, ContactHit, SearchRepository, TimelineHit};
+
+/// Shortest search term accepted (matches the analyzer's edgengram minimum)
+pub const MIN_QUERY_LENGTH: usize = 2;
+pub const DEFAULT_SEARCH_LIMIT: u32 = 20;
+pub const MAX_SEARCH_LIMIT: u32 = 100;
+
+pub struct SearchService {
+    repo: SearchRepository,
+}
+
+impl SearchService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            repo: SearchRepository::new(db),
+        }
+    }
+
+    /// Search everything in the workspace, best matches first
+    pub async fn search(
+        &self,
+        workspace_id: &str,
+        query: &str,
+        limit: Option<u32>,
+    ) -> AppResult<Vec<SearchResult>> {
+        let term = query.trim();
+        if term.chars().count() < MIN_QUERY_LENGTH {
+            return Err(AppError::Validation(format!(
+                "q: must be at least {} characters",
+                MIN_QUERY_LENGTH
+            )));
+        }
+
+        let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT);
+
+        // Each table is asked for `limit` hits so the merged list can still
+        // be filled entirely from one table if that's where the matches are
+        let (contacts, companies, timeline) = tokio::try_join!(
+            self.repo.search_contacts(workspace_id, term, limit),
+            self.repo.search_companies(workspace_id, term, limit),
+            self.repo.search_timeline(workspace_id, term, limit),
+        )?;
+
+        let results = contacts
+            .into_iter()
+            .map(contact_result)
+            .chain(companies.into_iter().map(company_result))
+            .chain(timeline.into_iter().map(timeline_result))
+            .collect();
+
+        Ok(rank_results(results, limit as usize))
+    }
+}
+
+/// Sort by score (highest first) and keep the top `limit`
+fn rank_results(mut results: Vec<SearchResult>, limit: usize) -> Vec<SearchResult> {
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    results.truncate(limit);
+    results
+}
+
+fn contact_result(hit: ContactHit) -> SearchResult {
+    SearchResult {
+        result_type: SearchResultType::Contact,
+        id: hit.id.id.to_string(),
+        title: format!("{} {}", hit.first_name, hit.last_name),
+        subtitle: Some(hit.email),
+        contact_id: None,
+        score: hit.score.unwrap_or(0.0),
+    }
+}
+
+fn company_result(hit: CompanyHit) -> SearchResult {
+    SearchResult {
+        result_type: SearchResultType::Company,
+        id: hit.id.id.to_string(),
+        title: hit.name,
+        subtitle: hit.domain,
+        contact_id: None,
+        score: hit.score.unwrap_or(0.0),
+    }
+}
+
+fn timeline_result(hit: TimelineHit) -> SearchResult {
+    SearchResult {
+        result_type: SearchResultType::TimelineEntry,
+        id: hit.id.id.to_string(),
+        title: hit.entry_type,
+        subtitle: Some(hit.snippet.unwrap_or(hit.content)),
+        contact_id: Some(hit.contact.id.to_string()),
+        score: hit.score.unwrap_or(0.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(id: &str, score: f64) -> SearchResult {
+        SearchResult {
+            result_type: SearchResultType::Contact,
+            id: id.to_string(),
+            title: id.to_string(),
+            subtitle: None,
+            contact_id: None,
+            score,
+        }
+    }
+
+    #[test]
+    fn test_rank_results_orders_by_score() {
+        let ranked = rank_results(vec![result("low", 0.5), result("high", 3.2), result("mid", 1.0)], 10);
+
+        let ids: Vec<&str> = ranked.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["high", "mid", "low"]);
+    }
+
+    #[test]
+    fn test_rank_results_truncates() {
+        let ranked = rank_results(vec![result("a", 1.0), result("b", 2.0), result("c", 3.0)], 2);
+
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].id, "c");
+    }
+}
@@ -0,0 +1,787 @@
+//! Seed Service - realistic fake data for development and demos
+//!
+//! Fills a workspace with companies, contacts, deals, timelines, campaigns
+//! and events that look like a few months of real use: contacts work at the
+//! companies, campaigns were sent to subscribed contacts and were opened
+//! and clicked, past events have attendees, and deals move through every
+//! stage. The same seed produces the same names, numbers and activity.
+//!
+//! Seeded data can't reach anyone: email addresses and company domains use
+//! the reserved `.example` domain, campaigns are completed or drafts, and
+//! events have no reminder or follow-up emails.
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use surrealdb::sql::Thing;
+
+use crate::db::{new_thing, workspace_thing, Database};
+use crate::domain::{DealStage, EventReminders, RsvpStatus, SubscriptionStatus, WinLossReason};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Campaign, CampaignChannel, CampaignObjective, CampaignRecipient, CampaignStatus, Company, Deal, Event, EventType,
+    RecipientStatus, Rsvp, Sentiment, TimelineEntry, TimelineEntryType,
+};
+use crate::repositories::{ContactRecord, SeedRepository, WorkspaceRepository};
+use crate::services::read_cache::{CacheScope, ReadCache};
+
+/// How much data to generate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedVolume {
+    pub companies: usize,
+    pub contacts: usize,
+    pub campaigns: usize,
+    pub events: usize,
+}
+
+impl Default for SeedVolume {
+    fn default() -> Self {
+        Self {
+            companies: 25,
+            contacts: 200,
+            campaigns: 4,
+            events: 4,
+        }
+    }
+}
+
+/// How many records were generated, per kind
+#[derive(Debug, Default)]
+pub struct SeedSummary {
+    pub companies: usize,
+    pub contacts: usize,
+    pub deals: usize,
+    pub timeline_entries: usize,
+    pub campaigns: usize,
+    pub campaign_recipients: usize,
+    pub events: usize,
+    pub rsvps: usize,
+}
+
+pub struct SeedService {
+    db: Arc<Database>,
+    cache: Arc<ReadCache>,
+}
+
+impl SeedService {
+    pub fn new(db: Arc<Database>, cache: Arc<ReadCache>) -> Self {
+        Self { db, cache }
+    }
+
+    /// Add generated data to an existing workspace
+    pub async fn seed(&self, workspace_id: &str, volume: &SeedVolume, seed: u64) -> AppResult<SeedSummary> {
+        WorkspaceRepository::new(Arc::clone(&self.db))
+            .find_by_id(workspace_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Workspace '{}' not found", workspace_id)))?;
+
+        let data = generate(workspace_id, volume, seed, Utc::now());
+        let summary = data.summary();
+
+        let repo = SeedRepository::new(Arc::clone(&self.db));
+        repo.insert("company", &data.companies).await?;
+        repo.insert("contact", &data.contacts).await?;
+        repo.insert("deal", &data.deals).await?;
+        repo.insert("campaign", &data.campaigns).await?;
+        repo.insert("campaign_recipient", &data.campaign_recipients).await?;
+        repo.insert("event", &data.events).await?;
+        repo.insert("rsvp", &data.rsvps).await?;
+        repo.insert("timeline_entry", &data.timeline).await?;
+
+        self.cache.invalidate(workspace_id, CacheScope::Contacts).await;
+        self.cache.invalidate(workspace_id, CacheScope::Deals).await;
+
+        tracing::info!("Seeded workspace {}: {:?}", workspace_id, summary);
+        Ok(summary)
+    }
+}
+
+/// Everything generated for one workspace, with links between the records
+struct SeedData {
+    companies: Vec<Company>,
+    contacts: Vec<ContactRecord>,
+    deals: Vec<Deal>,
+    timeline: Vec<TimelineEntry>,
+    campaigns: Vec<Campaign>,
+    campaign_recipients: Vec<CampaignRecipient>,
+    events: Vec<Event>,
+    rsvps: Vec<Rsvp>,
+}
+
+impl SeedData {
+    fn summary(&self) -> SeedSummary {
+        SeedSummary {
+            companies: self.companies.len(),
+            contacts: self.contacts.len(),
+            deals: self.deals.len(),
+            timeline_entries: self.timeline.len(),
+            campaigns: self.campaigns.len(),
+            campaign_recipients: self.campaign_recipients.len(),
+            events: self.events.len(),
+            rsvps: self.rsvps.len(),
+        }
+    }
+}
+
+const FIRST_NAMES: &[&str] = &[
+    "Ada", "Alan", "Amara", "Ana", "Ben", "Carlos", "Chen", "Clara", "David", "Elena", "Emil", "Fatima", "Grace",
+    "Hana", "Hugo", "Ingrid", "Isaac", "Jonas", "Julia", "Kai", "Lars", "Layla", "Leo", "Lina", "Marco", "Maya",
+    "Mei", "Nadia", "Noah", "Olivia", "Omar", "Priya", "Rafael", "Sara", "Sofia", "Tariq", "Theo", "Yara", "Yusuf",
+    "Zoe",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Andersson", "Bauer", "Costa", "Dubois", "Eriksen", "Fischer", "Garcia", "Hansen", "Ito", "Jensen", "Kim",
+    "Larsen", "Lindqvist", "Martin", "Moreau", "Nakamura", "Novak", "Okafor", "Patel", "Petrov", "Quinn", "Rossi",
+    "Santos", "Schmidt", "Silva", "Tanaka", "Underwood", "Varga", "Wagner", "Walsh", "Weber", "Xu", "Yilmaz",
+    "Zhang",
+];
+
+const COMPANY_PREFIXES: &[&str] = &[
+    "North", "Blue", "Bright", "Iron", "Silver", "Cedar", "Harbor", "Summit", "Quantum", "Red", "Atlas", "Nova",
+    "Pine", "Orbit", "Lumen", "Granite", "Maple", "Vertex", "Coral", "Aurora",
+];
+
+const COMPANY_SUFFIXES: &[&str] = &[
+    "wind", "stone", "field", "works", "bridge", "point", "gate", "light", "path", "wave",
+];
+
+const COMPANY_KINDS: &[&str] = &[
+    "Labs", "Systems", "Analytics", "Health", "Logistics", "Capital", "Studio", "Robotics",
+];
+
+const INDUSTRIES: &[&str] = &[
+    "Software", "Healthcare", "Logistics", "Finance", "Manufacturing", "Retail", "Education", "Energy", "Media",
+];
+
+const COMPANY_SIZES: &[&str] = &["1-10", "11-50", "51-200", "201-1000", "1000+"];
+
+const TAGS: &[&str] = &[
+    "vip", "newsletter", "webinar", "beta", "enterprise", "smb", "referral", "conference", "churn-risk", "champion",
+];
+
+/// Timezone, country, region and city
+const PLACES: &[(&str, &str, &str, &str)] = &[
+    ("Europe/Stockholm", "SE", "Stockholm County", "Stockholm"),
+    ("Europe/London", "GB", "England", "London"),
+    ("Europe/Berlin", "DE", "Berlin", "Berlin"),
+    ("America/New_York", "US", "New York", "New York"),
+    ("America/Los_Angeles", "US", "California", "San Francisco"),
+    ("America/Sao_Paulo", "BR", "São Paulo", "São Paulo"),
+    ("Asia/Tokyo", "JP", "Tokyo", "Tokyo"),
+    ("Asia/Singapore", "SG", "Singapore", "Singapore"),
+    ("Australia/Sydney", "AU", "New South Wales", "Sydney"),
+];
+
+const SOURCES: &[&str] = &["website", "referral", "webinar", "conference", "linkedin", "import"];
+
+const PLANS: &[&str] = &["starter", "pro", "enterprise"];
+
+const TOPICS: &[&str] = &[
+    "pricing", "the API", "onboarding", "data migration", "SSO", "reporting", "the mobile app", "integrations",
+    "security review", "renewal",
+];
+
+const PRODUCTS: &[&str] = &["Annual plan", "Pilot", "Expansion", "Onboarding package", "Enterprise licence"];
+
+const WIN_LOSS_REASONS: &[WinLossReason] = &[
+    WinLossReason::Competitor,
+    WinLossReason::Price,
+    WinLossReason::Timing,
+    WinLossReason::NoBudget,
+    WinLossReason::ProductFit,
+];
+
+const EVENT_NAMES: &[(&str, EventType)] = &[
+    ("Product walkthrough", EventType::Demo),
+    ("Ask us anything", EventType::Ama),
+    ("Customer meetup", EventType::Meetup),
+    ("Reporting deep dive", EventType::Webinar),
+    ("Roadmap preview", EventType::Webinar),
+    ("Integrations office hours", EventType::Other),
+];
+
+const CAMPAIGN_NAMES: &[(&str, CampaignObjective)] = &[
+    ("Spring product update", CampaignObjective::Awareness),
+    ("Free trial follow-up", CampaignObjective::LeadGen),
+    ("Webinar invitation", CampaignObjective::Event),
+    ("Quarterly investor update", CampaignObjective::Investor),
+    ("Early adopter program", CampaignObjective::EarlyAdopters),
+    ("Customer newsletter", CampaignObjective::Awareness),
+];
+
+/// Generate a workspace's worth of linked records, as of `now`
+fn generate(workspace_id: &str, volume: &SeedVolume, seed: u64, now: DateTime<Utc>) -> SeedData {
+    let mut rng = Rng::new(seed);
+    let workspace = workspace_thing(workspace_id);
+
+    let companies = generate_companies(&mut rng, &workspace, volume.companies, now);
+    let contacts = generate_contacts(&mut rng, &workspace, &companies, volume.contacts, now);
+
+    let mut timeline = Vec::new();
+    for contact in &contacts {
+        generate_activity(&mut rng, &workspace, contact, now, &mut timeline);
+    }
+
+    let deals = generate_deals(&mut rng, &workspace, &contacts, &companies, now);
+
+    let mut campaigns = Vec::new();
+    let mut campaign_recipients = Vec::new();
+    for i in 0..volume.campaigns {
+        let (name, objective) = &CAMPAIGN_NAMES[i % CAMPAIGN_NAMES.len()];
+        // The newest is still a draft; the others went out a few weeks apart
+        let sent_at = (i + 1 < volume.campaigns).then(|| now - Duration::days(((volume.campaigns - i) * 21) as i64));
+        let created_at = sent_at.unwrap_or(now) - Duration::days(rng.range(2, 10));
+        let campaign = Campaign {
+            id: Some(new_thing("campaign")),
+            workspace: workspace.clone(),
+            name: name.to_string(),
+            objective: objective.clone(),
+            status: if sent_at.is_some() { CampaignStatus::Completed } else { CampaignStatus::Draft },
+            channels: vec![CampaignChannel::Email],
+            prompt: None,
+            segment_definition: json!({}),
+            segment: None,
+            sender: None,
+            scheduled_at: None,
+            send_window: None,
+            created_at,
+            updated_at: sent_at.unwrap_or(created_at),
+        };
+        if let Some(sent_at) = sent_at {
+            generate_sends(
+                &mut rng,
+                &workspace,
+                &campaign,
+                &contacts,
+                sent_at,
+                &mut campaign_recipients,
+                &mut timeline,
+            );
+        }
+        campaigns.push(campaign);
+    }
+
+    let mut events = Vec::new();
+    let mut rsvps = Vec::new();
+    for i in 0..volume.events {
+        let (name, event_type) = &EVENT_NAMES[i % EVENT_NAMES.len()];
+        // Half are past, half upcoming, two weeks apart
+        let offset_days = (i as i64 - volume.events as i64 / 2) * 14 + 7;
+        let start_time = now + Duration::days(offset_days) + Duration::hours(rng.range(0, 8));
+        let end_time = start_time + Duration::minutes(*rng.pick(&[45, 60, 90]));
+        let event = Event {
+            id: Some(new_thing("event")),
+            workspace: workspace.clone(),
+            campaign: None,
+            name: name.to_string(),
+            event_type: event_type.clone(),
+            description: format!("{} for customers and prospects, with time for questions.", name),
+            start_time,
+            end_time,
+            location: if matches!(event_type, EventType::Meetup) { "Stockholm office" } else { "Online" }.to_string(),
+            max_attendees: Some(*rng.pick(&[50, 100, 250])),
+            registration_deadline: None,
+            reminders: EventReminders {
+                before_start_minutes: Vec::new(),
+                follow_up_after_minutes: None,
+            },
+            created_at: start_time.min(now) - Duration::days(rng.range(14, 30)),
+        };
+        generate_rsvps(&mut rng, &workspace, &event, &contacts, now, &mut rsvps, &mut timeline);
+        events.push(event);
+    }
+
+    SeedData {
+        companies,
+        contacts,
+        deals,
+        timeline,
+        campaigns,
+        campaign_recipients,
+        events,
+        rsvps,
+    }
+}
+
+fn generate_companies(rng: &mut Rng, workspace: &Thing, count: usize, now: DateTime<Utc>) -> Vec<Company> {
+    let mut names = HashSet::new();
+
+    (0..count)
+        .map(|i| {
+            let prefix = *rng.pick(COMPANY_PREFIXES);
+            let suffix = *rng.pick(COMPANY_SUFFIXES);
+            let mut base = format!("{}{}", prefix, suffix);
+            if !names.insert(base.clone()) {
+                base = format!("{}{}", base, i);
+                names.insert(base.clone());
+            }
+            let created_at = now - Duration::days(rng.range(200, 720));
+            let (timezone, country, region, city) = *rng.pick(PLACES);
+
+            Company {
+                id: Some(new_thing("company")),
+                workspace: workspace.clone(),
+                name: format!("{} {}", base, rng.pick(COMPANY_KINDS)),
+                domain: Some(format!("{}.example", base.to_lowercase())),
+                industry: Some(rng.pick(INDUSTRIES).to_string()),
+                size: Some(rng.pick(COMPANY_SIZES).to_string()),
+                tags: Vec::new(),
+                country: Some(country.to_string()),
+                region: Some(region.to_string()),
+                city: Some(city.to_string()),
+                timezone: Some(timezone.to_string()),
+                created_at,
+                updated_at: created_at,
+            }
+        })
+        .collect()
+}
+
+fn generate_contacts(
+    rng: &mut Rng,
+    workspace: &Thing,
+    companies: &[Company],
+    count: usize,
+    now: DateTime<Utc>,
+) -> Vec<ContactRecord> {
+    let mut emails = HashSet::new();
+
+    (0..count)
+        .map(|i| {
+            let first_name = *rng.pick(FIRST_NAMES);
+            let last_name = *rng.pick(LAST_NAMES);
+            let company = (!companies.is_empty() && rng.chance(0.8)).then(|| rng.pick(companies));
+
+            let domain = company
+                .and_then(|c| c.domain.clone())
+                .unwrap_or_else(|| "mail.example".to_string());
+            let local = format!("{}.{}", first_name, last_name).to_lowercase();
+            let mut email = format!("{}@{}", local, domain);
+            if !emails.insert(email.clone()) {
+                email = format!("{}{}@{}", local, i, domain);
+                emails.insert(email.clone());
+            }
+
+            let status = rng.weighted(&[("lead", 55), ("customer", 25), ("partner", 8), ("investor", 4), ("other", 8)]);
+            let mut custom_fields = BTreeMap::new();
+            custom_fields.insert("source".to_string(), rng.pick(SOURCES).to_string());
+            if status == "customer" {
+                custom_fields.insert("plan".to_string(), rng.pick(PLANS).to_string());
+            }
+
+            let mut tags: Vec<String> = (0..rng.range(0, 3)).map(|_| rng.pick(TAGS).to_string()).collect();
+            tags.sort();
+            tags.dedup();
+
+            let created_at = now - Duration::days(rng.range(1, 540)) - Duration::minutes(rng.range(0, 24 * 60));
+            let (timezone, country, region, city) = *rng.pick(PLACES);
+            ContactRecord {
+                id: Some(new_thing("contact")),
+                workspace: workspace.clone(),
+                first_name: first_name.to_string(),
+                last_name: last_name.to_string(),
+                email,
+                // 555-01xx numbers are reserved for fiction
+                phone: rng.chance(0.5).then(|| format!("+1 555 01{:02}", rng.range(0, 99))),
+                linkedin_url: rng
+                    .chance(0.6)
+                    .then(|| format!("https://www.linkedin.com/in/{}-{}-{}", first_name, last_name, i).to_lowercase()),
+                timezone: Some(timezone.to_string()),
+                country: Some(country.to_string()),
+                region: Some(region.to_string()),
+                city: Some(city.to_string()),
+                avatar_url: None,
+                pipeline_position: None,
+                tags,
+                status: status.to_string(),
+                subscription_status: if rng.chance(0.92) {
+                    SubscriptionStatus::Subscribed
+                } else {
+                    SubscriptionStatus::Unsubscribed
+                },
+                custom_fields,
+                engagement_score: (rng.range(0, 1000) as f64) / 10.0,
+                company: company.and_then(|c| c.id.clone()),
+                owner: None,
+                created_at,
+                updated_at: created_at,
+            }
+        })
+        .collect()
+}
+
+/// Notes, calls, tasks, email and visits between the contact's creation and now
+fn generate_activity(
+    rng: &mut Rng,
+    workspace: &Thing,
+    contact: &ContactRecord,
+    now: DateTime<Utc>,
+    timeline: &mut Vec<TimelineEntry>,
+) {
+    let Some(contact_id) = contact.id.clone() else {
+        return;
+    };
+    let span = (now - contact.created_at).num_minutes().max(1);
+
+    for _ in 0..rng.range(1, 10) {
+        let timestamp = contact.created_at + Duration::minutes(rng.range(0, span));
+        let topic = *rng.pick(TOPICS);
+        let entry_type = rng.weighted(&[
+            (TimelineEntryType::Note, 30),
+            (TimelineEntryType::Call, 15),
+            (TimelineEntryType::EmailReceived, 20),
+            (TimelineEntryType::EmailSent, 15),
+            (TimelineEntryType::Task, 10),
+            (TimelineEntryType::LandingPageVisit, 10),
+        ]);
+
+        let (content, metadata, sentiment) = match entry_type {
+            TimelineEntryType::Note => (
+                format!("{} asked about {}; follow up next week.", contact.first_name, topic),
+                json!({}),
+                Some(rng.sentiment()),
+            ),
+            TimelineEntryType::Call => (
+                format!("Call with {} about {}.", contact.first_name, topic),
+                json!({ "duration_minutes": rng.range(5, 45) }),
+                Some(rng.sentiment()),
+            ),
+            TimelineEntryType::EmailReceived => (
+                format!("Re: Question about {}", topic),
+                json!({ "subject": format!("Re: Question about {}", topic) }),
+                Some(rng.sentiment()),
+            ),
+            TimelineEntryType::EmailSent => (
+                format!("Following up on {}", topic),
+                json!({ "subject": format!("Following up on {}", topic) }),
+                None,
+            ),
+            TimelineEntryType::Task => (
+                format!("Send {} details on {}", contact.first_name, topic),
+                // Tasks in the last two weeks are still open
+                json!({ "completed": timestamp < now - Duration::days(14) }),
+                None,
+            ),
+            _ => (
+                "Visited the pricing page".to_string(),
+                json!({ "url": "/pricing" }),
+                None,
+            ),
+        };
+
+        timeline.push(TimelineEntry {
+            id: Some(new_thing("timeline_entry")),
+            workspace: workspace.clone(),
+            contact: contact_id.clone(),
+            company: contact.company.clone(),
+            campaign: None,
+            entry_type,
+            content,
+            metadata,
+            attachments: Vec::new(),
+            sentiment,
+            timestamp,
+        });
+    }
+}
+
+/// Deals for about a third of the contacts at a company, in every stage
+fn generate_deals(
+    rng: &mut Rng,
+    workspace: &Thing,
+    contacts: &[ContactRecord],
+    companies: &[Company],
+    now: DateTime<Utc>,
+) -> Vec<Deal> {
+    let mut deals = Vec::new();
+
+    for contact in contacts {
+        let Some(company) = companies.iter().find(|c| c.id.is_some() && c.id == contact.company) else {
+            continue;
+        };
+        if !rng.chance(0.35) {
+            continue;
+        }
+
+        let stage = rng.weighted(&[
+            (DealStage::Prospecting, 25),
+            (DealStage::Qualification, 20),
+            (DealStage::Proposal, 15),
+            (DealStage::Negotiation, 10),
+            (DealStage::ClosedWon, 20),
+            (DealStage::ClosedLost, 10),
+        ]);
+        let age = (now - contact.created_at).num_days().max(1);
+        let created_at = now - Duration::days(rng.range(0, age.min(180)));
+        let closed_at = matches!(stage, DealStage::ClosedWon | DealStage::ClosedLost)
+            .then(|| (created_at + Duration::days(rng.range(10, 90))).min(now));
+
+        deals.push(Deal {
+            id: Some(new_thing("deal")),
+            workspace: workspace.clone(),
+            name: format!("{} - {}", company.name, rng.pick(PRODUCTS)),
+            value: (rng.range(8, 400) * 250) as f64,
+            stage,
+            expected_close_date: closed_at.is_none().then(|| created_at + Duration::days(rng.range(30, 120))),
+            closed_at,
+            win_loss_reason: closed_at.is_some().then(|| *rng.pick(WIN_LOSS_REASONS)),
+            contact: contact.id.clone(),
+            company: company.id.clone(),
+            notes: None,
+            pipeline_position: None,
+            created_at,
+            updated_at: closed_at.unwrap_or(created_at),
+        });
+    }
+
+    deals
+}
+
+/// A campaign sent to some of the subscribed contacts, with their opens and clicks
+fn generate_sends(
+    rng: &mut Rng,
+    workspace: &Thing,
+    campaign: &Campaign,
+    contacts: &[ContactRecord],
+    sent_at: DateTime<Utc>,
+    recipients: &mut Vec<CampaignRecipient>,
+    timeline: &mut Vec<TimelineEntry>,
+) {
+    let Some(campaign_id) = campaign.id.clone() else {
+        return;
+    };
+    let metadata = json!({ "campaign_id": campaign_id.id.to_raw() });
+
+    for contact in contacts {
+        let Some(contact_id) = contact.id.clone() else {
+            continue;
+        };
+        if contact.subscription_status != SubscriptionStatus::Subscribed
+            || contact.created_at >= sent_at
+            || !rng.chance(0.4)
+        {
+            continue;
+        }
+
+        let bounced = rng.chance(0.03);
+        recipients.push(CampaignRecipient {
+            id: Some(new_thing("campaign_recipient")),
+            workspace: workspace.clone(),
+            campaign: campaign_id.clone(),
+            contact: contact_id.clone(),
+            email: contact.email.clone(),
+            status: if bounced { RecipientStatus::Bounced } else { RecipientStatus::Sent },
+            sent_at: Some(sent_at),
+            replied_at: None,
+            created_at: sent_at,
+            updated_at: sent_at,
+        });
+        if bounced {
+            continue;
+        }
+
+        let mut entry = |entry_type, content: String, timestamp| {
+            timeline.push(TimelineEntry {
+                id: Some(new_thing("timeline_entry")),
+                workspace: workspace.clone(),
+                contact: contact_id.clone(),
+                company: contact.company.clone(),
+                campaign: Some(campaign_id.clone()),
+                entry_type,
+                content,
+                metadata: metadata.clone(),
+                attachments: Vec::new(),
+                sentiment: None,
+                timestamp,
+            })
+        };
+
+        entry(TimelineEntryType::EmailSent, campaign.name.clone(), sent_at);
+        if rng.chance(0.45) {
+            let opened_at = sent_at + Duration::minutes(rng.range(5, 3 * 24 * 60));
+            entry(TimelineEntryType::EmailOpen, format!("Opened \"{}\"", campaign.name), opened_at);
+            if rng.chance(0.3) {
+                let clicked_at = opened_at + Duration::minutes(rng.range(1, 30));
+                entry(TimelineEntryType::EmailClick, format!("Clicked a link in \"{}\"", campaign.name), clicked_at);
+            }
+        }
+    }
+}
+
+/// Registrations for an upcoming event, attendance for a past one
+fn generate_rsvps(
+    rng: &mut Rng,
+    workspace: &Thing,
+    event: &Event,
+    contacts: &[ContactRecord],
+    now: DateTime<Utc>,
+    rsvps: &mut Vec<Rsvp>,
+    timeline: &mut Vec<TimelineEntry>,
+) {
+    let Some(event_id) = event.id.clone() else {
+        return;
+    };
+    let past = event.end_time < now;
+
+    for contact in contacts {
+        let Some(contact_id) = contact.id.clone() else {
+            continue;
+        };
+        if contact.created_at >= event.created_at || !rng.chance(0.15) {
+            continue;
+        }
+
+        let status = if past {
+            rng.weighted(&[(RsvpStatus::Attended, 60), (RsvpStatus::NoShow, 30), (RsvpStatus::Cancelled, 10)])
+        } else {
+            rng.weighted(&[(RsvpStatus::Registered, 70), (RsvpStatus::Invited, 30)])
+        };
+        let open_for = (event.start_time - event.created_at).num_minutes().max(1);
+        let registered_at = event.created_at + Duration::minutes(rng.range(0, open_for));
+
+        rsvps.push(Rsvp {
+            id: Some(new_thing("rsvp")),
+            workspace: workspace.clone(),
+            event: event_id.clone(),
+            contact: contact_id.clone(),
+            status,
+            timestamp: registered_at.min(now),
+            emails_sent: Vec::new(),
+        });
+
+        if status == RsvpStatus::Attended {
+            timeline.push(TimelineEntry {
+                id: Some(new_thing("timeline_entry")),
+                workspace: workspace.clone(),
+                contact: contact_id,
+                company: contact.company.clone(),
+                campaign: None,
+                entry_type: TimelineEntryType::EventAttend,
+                content: format!("Attended {}", event.name),
+                metadata: json!({ "event_id": event_id.id.to_raw() }),
+                attachments: Vec::new(),
+                sentiment: None,
+                timestamp: event.start_time,
+            });
+        }
+    }
+}
+
+/// SplitMix64: small and good enough for fake data
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `low..=high`
+    fn range(&mut self, low: i64, high: i64) -> i64 {
+        low + (self.next_u64() % (high - low + 1) as u64) as i64
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0, items.len() as i64 - 1) as usize]
+    }
+
+    /// One of the choices, each as likely as its weight
+    fn weighted<T: Clone>(&mut self, choices: &[(T, u32)]) -> T {
+        let total: u32 = choices.iter().map(|(_, w)| w).sum();
+        let mut roll = self.range(0, i64::from(total) - 1) as u32;
+        for (choice, weight) in choices {
+            if roll < *weight {
+                return choice.clone();
+            }
+            roll -= weight;
+        }
+        choices[choices.len() - 1].0.clone()
+    }
+
+    fn sentiment(&mut self) -> Sentiment {
+        self.weighted(&[(Sentiment::Positive, 45), (Sentiment::Neutral, 40), (Sentiment::Negative, 15)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        "2026-06-01T12:00:00Z".parse().unwrap()
+    }
+
+    #[test]
+    fn test_generate_is_repeatable_and_sized_by_volume() {
+        let volume = SeedVolume::default();
+        let a = generate("acme", &volume, 7, now());
+        let b = generate("acme", &volume, 7, now());
+
+        assert_eq!(a.companies.len(), volume.companies);
+        assert_eq!(a.contacts.len(), volume.contacts);
+        assert_eq!(a.campaigns.len(), volume.campaigns);
+        assert_eq!(a.events.len(), volume.events);
+        assert!(!a.deals.is_empty() && !a.campaign_recipients.is_empty() && !a.rsvps.is_empty());
+
+        let emails = |d: &SeedData| d.contacts.iter().map(|c| c.email.clone()).collect::<Vec<_>>();
+        assert_eq!(emails(&a), emails(&b));
+        assert_eq!(a.timeline.len(), b.timeline.len());
+        assert_ne!(emails(&a), emails(&generate("acme", &volume, 8, now())));
+
+        let empty = generate("acme", &SeedVolume { companies: 0, contacts: 0, campaigns: 0, events: 0 }, 7, now());
+        assert_eq!(empty.summary().timeline_entries, 0);
+    }
+
+    #[test]
+    fn test_generated_records_are_linked_unreachable_and_in_the_past() {
+        let data = generate("acme", &SeedVolume::default(), 1, now());
+        let workspace = workspace_thing("acme");
+
+        let emails: HashSet<&str> = data.contacts.iter().map(|c| c.email.as_str()).collect();
+        assert_eq!(emails.len(), data.contacts.len());
+        assert!(data.contacts.iter().all(|c| c.email.ends_with(".example") && c.workspace == workspace));
+
+        let contact_ids: HashSet<String> =
+            data.contacts.iter().filter_map(|c| c.id.as_ref()).map(|id| id.to_string()).collect();
+        assert!(data
+            .timeline
+            .iter()
+            .all(|e| contact_ids.contains(&e.contact.to_string()) && e.workspace == workspace));
+        assert!(data
+            .deals
+            .iter()
+            .all(|d| d.contact.as_ref().is_some_and(|c| contact_ids.contains(&c.to_string()))));
+        assert!(data.timeline.iter().all(|e| e.timestamp <= now()));
+
+        assert!(data.campaigns.iter().all(|c| matches!(c.status, CampaignStatus::Completed | CampaignStatus::Draft)));
+        assert!(data
+            .events
+            .iter()
+            .all(|e| e.reminders.before_start_minutes.is_empty() && e.reminders.follow_up_after_minutes.is_none()));
+    }
+
+    #[test]
+    fn test_rng_stays_in_range() {
+        let mut rng = Rng::new(42);
+        for _ in 0..1000 {
+            assert!((3..=5).contains(&rng.range(3, 5)));
+        }
+        assert_eq!(rng.weighted(&[("only", 1)]), "only");
+        assert!(!rng.chance(0.0));
+        assert!(rng.chance(1.0));
+    }
+}
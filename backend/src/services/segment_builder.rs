@@ -1,4 +1,22 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::domain::{DomainError, DomainResult};
+
+/// Contact fields a segment filter may reference
+pub const SEGMENT_FIELDS: &[&str] = &[
+    "first_name",
+    "last_name",
+    "email",
+    "phone",
+    "linkedin_url",
+    "tags",
+    "status",
+    "engagement_score",
+    "company",
+    "created_at",
+    "updated_at",
+];
 
 /// Service for building contact segments based on filter criteria
 pub struct SegmentBuilder;
@@ -23,34 +41,78 @@ pub enum FilterOperator {
     NotIn,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SegmentDefinition {
     pub filters: Vec<SegmentFilter>,
     pub logic: LogicOperator,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum LogicOperator {
+    #[default]
     And,
     Or,
 }
 
-impl SegmentBuilder {
-    /// Build a SurrealQL WHERE clause from a segment definition
-    pub fn build_query(definition: &SegmentDefinition) -> String {
-        if definition.filters.is_empty() {
-            return String::new();
+/// A segment compiled to SurrealQL: query text plus the values to bind
+///
+/// User-supplied values only ever appear in `bindings`; the query text is
+/// built from field names in `SEGMENT_FIELDS` and fixed operator keywords.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SegmentQuery {
+    /// Condition to AND into a WHERE clause; empty when the segment
+    /// matches every contact
+    pub condition: String,
+    /// Parameter name (without `$`) to value
+    pub bindings: BTreeMap<String, serde_json::Value>,
+}
+
+impl SegmentQuery {
+    /// The condition as a standalone WHERE clause (empty if there is none)
+    pub fn where_clause(&self) -> String {
+        if self.condition.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", self.condition)
         }
+    }
+}
+
+impl SegmentBuilder {
+    /// Compile a segment definition into a parameterized query
+    ///
+    /// Values are returned as bindings named `$seg_0`, `$seg_1`, ... and
+    /// field names must be in `SEGMENT_FIELDS`, so nothing user-supplied
+    /// ends up in the query text.
+    pub fn build(definition: &SegmentDefinition) -> DomainResult<SegmentQuery> {
+        let mut bindings = BTreeMap::new();
+        let mut conditions = Vec::new();
+
+        for (i, filter) in definition.filters.iter().enumerate() {
+            if !SEGMENT_FIELDS.contains(&filter.field.as_str()) {
+                return Err(DomainError::InvalidField {
+                    field: "segment_definition.filters.field".to_string(),
+                    reason: format!("Unknown field '{}'", filter.field),
+                });
+            }
 
-        let conditions: Vec<String> = definition
-            .filters
-            .iter()
-            .filter_map(|f| Self::filter_to_condition(f))
-            .collect();
+            let param = format!("seg_{}", i);
+            let needs_array = matches!(filter.operator, FilterOperator::In | FilterOperator::NotIn);
+            if needs_array && !filter.value.is_array() {
+                return Err(DomainError::InvalidField {
+                    field: "segment_definition.filters.value".to_string(),
+                    reason: format!("'{}' filter on {} needs an array value", operator_keyword(&filter.operator), filter.field),
+                });
+            }
 
-        if conditions.is_empty() {
-            return String::new();
+            conditions.push(format!(
+                "{} {} ${}",
+                filter.field,
+                operator_keyword(&filter.operator),
+                param
+            ));
+            bindings.insert(param, filter.value.clone());
         }
 
         let connector = match definition.logic {
@@ -58,68 +120,105 @@ impl SegmentBuilder {
             LogicOperator::Or => " OR ",
         };
 
-        format!("WHERE {}", conditions.join(connector))
+        let condition = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("({})", conditions.join(connector))
+        };
+
+        Ok(SegmentQuery { condition, bindings })
     }
+}
 
-    fn filter_to_condition(filter: &SegmentFilter) -> Option<String> {
-        let field = &filter.field;
-        let value = &filter.value;
+fn operator_keyword(operator: &FilterOperator) -> &'static str {
+    match operator {
+        FilterOperator::Equals => "=",
+        FilterOperator::NotEquals => "!=",
+        FilterOperator::Contains => "CONTAINS",
+        FilterOperator::NotContains => "CONTAINSNOT",
+        FilterOperator::GreaterThan => ">",
+        FilterOperator::LessThan => "<",
+        FilterOperator::In => "INSIDE",
+        FilterOperator::NotIn => "NOTINSIDE",
+    }
+}
 
-        let condition = match filter.operator {
-            FilterOperator::Equals => {
-                format!("{} = {}", field, Self::value_to_surql(value))
-            }
-            FilterOperator::NotEquals => {
-                format!("{} != {}", field, Self::value_to_surql(value))
-            }
-            FilterOperator::Contains => {
-                if let Some(s) = value.as_str() {
-                    format!("{} CONTAINS '{}'", field, s)
-                } else {
-                    return None;
-                }
-            }
-            FilterOperator::NotContains => {
-                if let Some(s) = value.as_str() {
-                    format!("NOT {} CONTAINS '{}'", field, s)
-                } else {
-                    return None;
-                }
-            }
-            FilterOperator::GreaterThan => {
-                format!("{} > {}", field, Self::value_to_surql(value))
-            }
-            FilterOperator::LessThan => {
-                format!("{} < {}", field, Self::value_to_surql(value))
-            }
-            FilterOperator::In => {
-                if let Some(arr) = value.as_array() {
-                    let items: Vec<String> = arr.iter().map(|v| Self::value_to_surql(v)).collect();
-                    format!("{} IN [{}]", field, items.join(", "))
-                } else {
-                    return None;
-                }
-            }
-            FilterOperator::NotIn => {
-                if let Some(arr) = value.as_array() {
-                    let items: Vec<String> = arr.iter().map(|v| Self::value_to_surql(v)).collect();
-                    format!("{} NOT IN [{}]", field, items.join(", "))
-                } else {
-                    return None;
-                }
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn filter(field: &str, operator: FilterOperator, value: serde_json::Value) -> SegmentFilter {
+        SegmentFilter {
+            field: field.to_string(),
+            operator,
+            value,
+        }
+    }
+
+    #[test]
+    fn test_build_keeps_values_out_of_query() {
+        let definition = SegmentDefinition {
+            filters: vec![
+                filter("status", FilterOperator::Equals, json!("lead' OR 1=1")),
+                filter("engagement_score", FilterOperator::GreaterThan, json!(50)),
+            ],
+            logic: LogicOperator::And,
         };
 
-        Some(condition)
+        let query = SegmentBuilder::build(&definition).unwrap();
+
+        assert_eq!(query.condition, "(status = $seg_0 AND engagement_score > $seg_1)");
+        assert!(!query.condition.contains("1=1"));
+        assert_eq!(query.bindings["seg_0"], json!("lead' OR 1=1"));
+        assert_eq!(query.bindings["seg_1"], json!(50));
     }
 
-    fn value_to_surql(value: &serde_json::Value) -> String {
-        match value {
-            serde_json::Value::String(s) => format!("'{}'", s.replace('\'', "\\'")),
-            serde_json::Value::Number(n) => n.to_string(),
-            serde_json::Value::Bool(b) => b.to_string(),
-            serde_json::Value::Null => "NONE".to_string(),
-            _ => format!("'{}'", value.to_string().replace('\'', "\\'")),
-        }
+    #[test]
+    fn test_build_empty_definition_matches_everything() {
+        let query = SegmentBuilder::build(&SegmentDefinition::default()).unwrap();
+
+        assert!(query.condition.is_empty());
+        assert!(query.bindings.is_empty());
+        assert_eq!(query.where_clause(), "");
+    }
+
+    #[test]
+    fn test_build_operators_use_bound_arrays() {
+        let definition = SegmentDefinition {
+            filters: vec![
+                filter("status", FilterOperator::NotIn, json!(["customer", "partner"])),
+                filter("tags", FilterOperator::Contains, json!("vip")),
+            ],
+            logic: LogicOperator::Or,
+        };
+
+        let query = SegmentBuilder::build(&definition).unwrap();
+
+        assert_eq!(
+            query.where_clause(),
+            "WHERE (status NOTINSIDE $seg_0 OR tags CONTAINS $seg_1)"
+        );
+        assert_eq!(query.bindings["seg_0"], json!(["customer", "partner"]));
+    }
+
+    #[test]
+    fn test_build_rejects_unknown_field() {
+        let definition = SegmentDefinition {
+            filters: vec![filter("1=1 OR status", FilterOperator::Equals, json!("lead"))],
+            logic: LogicOperator::Or,
+        };
+
+        assert!(SegmentBuilder::build(&definition).is_err());
+    }
+
+    #[test]
+    fn test_build_in_requires_array() {
+        let definition = SegmentDefinition {
+            filters: vec![filter("status", FilterOperator::In, json!("lead"))],
+            logic: LogicOperator::And,
+        };
+
+        assert!(SegmentBuilder::build(&definition).is_err());
     }
 }
@@ -0,0 +1,466 @@
+//! Segment Service - audiences, saved segments and campaign recipients
+//!
+//! A campaign's audience is either an inline SegmentDefinition or a saved
+//! segment referenced by ID. Resolving it runs the segment against the
+//! workspace's contacts (with every filter value bound as a parameter)
+//! and materializes the result into the campaign_recipient table, where
+//! per-recipient delivery status lives.
+//!
+//! Saved segments carry a cached member count, refreshed on save and
+//! periodically by a background task. Preview totals are kept in the read
+//! cache until a contact changes.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use surrealdb::engine::any::Any;
+use surrealdb::method::Query;
+use surrealdb::sql::Thing;
+use tokio::task::JoinHandle;
+
+use crate::config::SegmentConfig;
+use crate::db::{workspace_thing, Database};
+use crate::domain::SubscriptionStatus;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Campaign, CampaignRecipient, CampaignStatus, Contact, ContactResponse, CreateSegmentRequest, RecipientStatus, Segment,
+    SegmentPreviewResponse, SegmentResponse, TimelineEntry, UpdateSegmentRequest,
+};
+use crate::repositories::{CampaignRecipientRepository, PendingRecipient, ResolvedContact, SegmentRepository};
+use crate::services::read_cache::{CacheScope, ReadCache};
+use crate::services::segment_builder::{SegmentBuilder, SegmentDefinition};
+use crate::shutdown::Shutdown;
+
+pub struct SegmentService {
+    db: Arc<Database>,
+    recipients: CampaignRecipientRepository,
+    segments: SegmentRepository,
+    cache: Arc<ReadCache>,
+}
+
+impl SegmentService {
+    pub fn new(db: Arc<Database>, cache: Arc<ReadCache>) -> Self {
+        Self {
+            recipients: CampaignRecipientRepository::new(Arc::clone(&db)),
+            segments: SegmentRepository::new(Arc::clone(&db)),
+            db,
+            cache,
+        }
+    }
+
+    /// Find every contact in the workspace that matches a segment
+    ///
+    /// Suppressed (unsubscribed) and trashed contacts are never part of an
+    /// audience, whatever the segment says.
+    pub async fn resolve(
+        &self,
+        workspace_id: &str,
+        definition: &SegmentDefinition,
+    ) -> AppResult<Vec<ResolvedContact>> {
+        #[derive(serde::Deserialize)]
+        struct Row {
+            id: Thing,
+            email: String,
+        }
+
+        let rows: Vec<Row> = self
+            .audience_query(workspace_id, definition, "SELECT id, email FROM contact", "ORDER BY id")?
+            .await?
+            .take(0)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| ResolvedContact {
+                contact: r.id,
+                email: r.email,
+            })
+            .collect())
+    }
+
+    /// Number of contacts in the audience
+    pub async fn count(&self, workspace_id: &str, definition: &SegmentDefinition) -> AppResult<u64> {
+        let count: Option<u64> = self
+            .audience_query(
+                workspace_id,
+                definition,
+                "SELECT count() AS count FROM contact",
+                "GROUP ALL",
+            )?
+            .await?
+            .take((0, "count"))?;
+
+        Ok(count.unwrap_or(0))
+    }
+
+    /// `count`, served from the read cache while no contact has changed
+    async fn cached_count(&self, workspace_id: &str, definition: &SegmentDefinition) -> AppResult<u64> {
+        let key = format!("segment_count:{}", serde_json::to_string(definition).unwrap_or_default());
+
+        self.cache
+            .get_or_load(workspace_id, CacheScope::Contacts, &key, || self.count(workspace_id, definition))
+            .await
+    }
+
+    /// The first `limit` contacts in the audience, plus the total
+    pub async fn preview(
+        &self,
+        workspace_id: &str,
+        definition: &SegmentDefinition,
+        limit: u32,
+    ) -> AppResult<SegmentPreviewResponse> {
+        let contacts: Vec<Contact> = self
+            .audience_query(workspace_id, definition, "SELECT * FROM contact", "ORDER BY id LIMIT $limit")?
+            .bind(("limit", limit))
+            .await?
+            .take(0)?;
+
+        Ok(SegmentPreviewResponse {
+            total: self.cached_count(workspace_id, definition).await?,
+            contacts: contacts.into_iter().map(ContactResponse::from).collect(),
+        })
+    }
+
+    /// IDs of up to `limit` contacts matching a segment, unsubscribed ones included
+    ///
+    /// For bulk edits rather than sends, so suppression doesn't apply;
+    /// trashed contacts still never match.
+    pub async fn matching_ids(
+        &self,
+        workspace_id: &str,
+        definition: &SegmentDefinition,
+        limit: usize,
+    ) -> AppResult<Vec<String>> {
+        let ids: Vec<Thing> = self
+            .contacts_query(
+                workspace_id,
+                definition,
+                "SELECT VALUE id FROM contact",
+                "ORDER BY id LIMIT $limit",
+                false,
+            )?
+            .bind(("limit", limit))
+            .await?
+            .take(0)?;
+
+        Ok(ids.into_iter().map(|t| t.id.to_string()).collect())
+    }
+
+    /// `<select> WHERE <workspace, subscribed, segment> <suffix>` with every binding applied
+    fn audience_query<'a>(
+        &'a self,
+        workspace_id: &str,
+        definition: &SegmentDefinition,
+        select: &str,
+        suffix: &str,
+    ) -> AppResult<Query<'a, Any>> {
+        self.contacts_query(workspace_id, definition, select, suffix, true)
+    }
+
+    /// Like `audience_query`, with the subscription condition optional
+    fn contacts_query<'a>(
+        &'a self,
+        workspace_id: &str,
+        definition: &SegmentDefinition,
+        select: &str,
+        suffix: &str,
+        subscribed_only: bool,
+    ) -> AppResult<Query<'a, Any>> {
+        let segment = SegmentBuilder::build(definition)?;
+
+        let mut conditions = vec!["workspace = $workspace".to_string(), "deleted_at IS NONE".to_string()];
+        if subscribed_only {
+            conditions.insert(1, "subscription_status = $subscribed".to_string());
+        }
+        if !segment.condition.is_empty() {
+            conditions.push(segment.condition);
+        }
+
+        let query = format!("{} WHERE {} {}", select, conditions.join(" AND "), suffix);
+
+        let mut db_query = self
+            .db
+            .client
+            .query(query)
+            .bind(("workspace", workspace_thing(workspace_id)))
+            .bind(("subscribed", SubscriptionStatus::Subscribed));
+        for (name, value) in segment.bindings {
+            db_query = db_query.bind((name, value));
+        }
+
+        Ok(db_query)
+    }
+
+    // ---- Saved segments ----
+
+    pub async fn list_saved(&self, workspace_id: &str) -> AppResult<Vec<SegmentResponse>> {
+        let segments = self.segments.find_all(workspace_id).await?;
+        Ok(segments.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get_saved(&self, workspace_id: &str, id: &str) -> AppResult<Segment> {
+        self.segments
+            .find_by_id(workspace_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Segment not found".into()))
+    }
+
+    /// Save a named segment; its member count is taken right away
+    pub async fn create_saved(&self, workspace_id: &str, req: CreateSegmentRequest) -> AppResult<SegmentResponse> {
+        let name = validate_segment_name(&req.name)?;
+        let definition = parse_definition(&req.definition)?;
+        SegmentBuilder::build(&definition)?;
+
+        let now = Utc::now();
+        let segment = self
+            .segments
+            .create(Segment {
+                id: None,
+                workspace: workspace_thing(workspace_id),
+                name,
+                description: req.description,
+                definition: req.definition,
+                member_count: self.count(workspace_id, &definition).await?,
+                counted_at: Some(now),
+                created_at: now,
+                updated_at: now,
+            })
+            .await?;
+
+        Ok(segment.into())
+    }
+
+    pub async fn update_saved(
+        &self,
+        workspace_id: &str,
+        id: &str,
+        req: UpdateSegmentRequest,
+    ) -> AppResult<SegmentResponse> {
+        let mut segment = self.get_saved(workspace_id, id).await?;
+
+        if let Some(name) = req.name {
+            segment.name = validate_segment_name(&name)?;
+        }
+        if let Some(description) = req.description {
+            segment.description = Some(description);
+        }
+        if let Some(definition) = req.definition {
+            let parsed = parse_definition(&definition)?;
+            SegmentBuilder::build(&parsed)?;
+
+            segment.member_count = self.count(workspace_id, &parsed).await?;
+            segment.counted_at = Some(Utc::now());
+            segment.definition = definition;
+        }
+        segment.updated_at = Utc::now();
+
+        Ok(self.segments.update(id, segment).await?.into())
+    }
+
+    /// Delete a saved segment that no campaign or template uses
+    pub async fn delete_saved(&self, workspace_id: &str, id: &str) -> AppResult<()> {
+        self.get_saved(workspace_id, id).await?;
+
+        if self.segments.is_referenced(workspace_id, id).await? {
+            return Err(AppError::Conflict(
+                "Segment is used by a campaign or template; point it elsewhere first".into(),
+            ));
+        }
+
+        self.segments.delete(workspace_id, id).await?;
+        Ok(())
+    }
+
+    /// Recount every saved segment in every workspace
+    ///
+    /// A segment that fails to count or store its count is logged and
+    /// skipped. Returns the number of segments refreshed.
+    pub async fn refresh_counts(&self) -> AppResult<usize> {
+        let mut refreshed = 0;
+
+        for segment in self.segments.find_all_workspaces().await? {
+            let Some(id) = segment.id.as_ref() else {
+                continue;
+            };
+            let workspace_id = segment.workspace.id.to_string();
+
+            let count = match parse_definition(&segment.definition) {
+                Ok(definition) => self.count(&workspace_id, &definition).await,
+                Err(e) => Err(e),
+            };
+
+            let stored = match count {
+                Ok(count) => self.segments.update_count(id, count, Utc::now()).await,
+                Err(e) => Err(e),
+            };
+
+            match stored {
+                Ok(()) => refreshed += 1,
+                Err(e) => tracing::warn!("Failed to refresh count of segment {}: {}", id, e),
+            }
+        }
+
+        Ok(refreshed)
+    }
+
+    /// Run `refresh_counts` on a background task at the configured interval
+    pub fn spawn_count_refresh(self: Arc<Self>, config: &SegmentConfig, shutdown: Shutdown) -> JoinHandle<()> {
+        let period = Duration::from_secs(config.count_refresh_interval_secs.max(60));
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+
+            while shutdown.tick(&mut interval).await {
+                if let Err(e) = self.refresh_counts().await {
+                    tracing::error!("Segment count refresh failed: {}", e);
+                }
+            }
+        })
+    }
+
+    // ---- Campaign recipients ----
+
+    /// The definition a campaign's audience comes from
+    ///
+    /// A linked saved segment wins over the inline definition, so editing
+    /// the saved segment changes the audience of campaigns not yet sent.
+    pub async fn campaign_definition(&self, workspace_id: &str, campaign: &Campaign) -> AppResult<SegmentDefinition> {
+        match &campaign.segment {
+            Some(segment) => {
+                let saved = self.get_saved(workspace_id, &segment.id.to_string()).await?;
+                parse_definition(&saved.definition)
+            }
+            None => parse_definition(&campaign.segment_definition),
+        }
+    }
+
+    /// Resolve a campaign's segment and store it as its recipient list
+    ///
+    /// Returns the number of recipients added.
+    pub async fn materialize(&self, workspace_id: &str, campaign: &Campaign) -> AppResult<usize> {
+        let campaign_id = campaign
+            .id
+            .as_ref()
+            .map(|t| t.id.to_string())
+            .ok_or_else(|| AppError::Internal("Campaign has no ID".into()))?;
+
+        let definition = self.campaign_definition(workspace_id, campaign).await?;
+        let resolved = self.resolve(workspace_id, &definition).await?;
+
+        self.recipients.sync(workspace_id, &campaign_id, resolved).await
+    }
+
+    /// Recipients of a campaign that have not been sent to yet
+    pub async fn pending_recipients(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+    ) -> AppResult<Vec<PendingRecipient>> {
+        self.recipients.find_pending(workspace_id, campaign_id).await
+    }
+
+    /// Record a campaign run: the contacts it sent to, those it skipped
+    /// because they unsubscribed, the emails on their timelines, and the
+    /// campaign now running
+    pub async fn record_execution(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        sent: Vec<Thing>,
+        suppressed: Vec<Thing>,
+        entries: Vec<TimelineEntry>,
+        status: CampaignStatus,
+    ) -> AppResult<()> {
+        self.recipients
+            .record_execution(workspace_id, campaign_id, sent, suppressed, entries, status)
+            .await
+    }
+
+    /// List the materialized recipients of a campaign
+    pub async fn recipients(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        status: Option<RecipientStatus>,
+        limit: u32,
+        offset: u32,
+    ) -> AppResult<Vec<CampaignRecipient>> {
+        self.recipients
+            .find_by_campaign(workspace_id, campaign_id, status, limit, offset)
+            .await
+    }
+}
+
+/// Parse a stored segment definition; `{}` means "every contact"
+pub fn parse_definition(value: &serde_json::Value) -> AppResult<SegmentDefinition> {
+    if value.is_null() || value.as_object().is_some_and(|o| o.is_empty()) {
+        return Ok(SegmentDefinition::default());
+    }
+
+    serde_json::from_value(value.clone())
+        .map_err(|e| AppError::Validation(format!("segment_definition: {}", e)))
+}
+
+fn validate_segment_name(name: &str) -> AppResult<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation("Segment name is required".into()));
+    }
+    Ok(name.to_string())
+}
+
+/// Check that a segment definition parses and compiles
+///
+/// Used when a campaign is saved, so a bad segment is rejected up front
+/// rather than when the campaign executes.
+pub fn validate_definition(value: &serde_json::Value) -> AppResult<()> {
+    let definition = parse_definition(value)?;
+    SegmentBuilder::build(&definition)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_empty_definition_matches_everyone() {
+        let definition = parse_definition(&json!({})).unwrap();
+
+        assert!(definition.filters.is_empty());
+    }
+
+    #[test]
+    fn test_parse_definition() {
+        let definition = parse_definition(&json!({
+            "filters": [{ "field": "status", "operator": "equals", "value": "lead" }],
+            "logic": "and"
+        }))
+        .unwrap();
+
+        assert_eq!(definition.filters.len(), 1);
+        assert_eq!(definition.filters[0].field, "status");
+    }
+
+    #[test]
+    fn test_parse_malformed_definition() {
+        assert!(parse_definition(&json!({ "filters": "everyone" })).is_err());
+    }
+
+    #[test]
+    fn test_segment_name_is_trimmed_and_required() {
+        assert_eq!(validate_segment_name("  VIP leads ").unwrap(), "VIP leads");
+        assert!(validate_segment_name("   ").is_err());
+    }
+
+    #[test]
+    fn test_validate_definition_rejects_unknown_field() {
+        let result = validate_definition(&json!({
+            "filters": [{ "field": "password_hash", "operator": "equals", "value": "x" }],
+            "logic": "and"
+        }));
+
+        assert!(result.is_err());
+    }
+}
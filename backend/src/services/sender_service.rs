@@ -0,0 +1,340 @@
+//! Sender Service - sender identities and their domain verification
+//!
+//! Identities are shared with the workspace or personal to the user who
+//! created them; personal ones are invisible to everyone else. Each gets a
+//! random token its domain publishes in a TXT record, to show the domain
+//! is the workspace's. Creating an identity, changing its domain or asking
+//! for it looks up the token, SPF and DKIM records; campaigns only send
+//! from verified identities.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use surrealdb::sql::Thing;
+use uuid::Uuid;
+
+use crate::config::SendersConfig;
+use crate::db::{workspace_thing, Database};
+use crate::domain::{
+    check_dkim, check_ownership, check_spf, sender_domain, validate_sender, verification_name, DnsCheck,
+};
+use crate::error::{AppError, AppResult};
+use crate::models::{Campaign, CreateSenderRequest, SenderIdentity, SenderResponse, UpdateSenderRequest};
+use crate::repositories::SenderRepository;
+use crate::services::dns_client::DnsClient;
+
+pub struct SenderService {
+    senders: SenderRepository,
+    dns: DnsClient,
+    spf_include: String,
+    dkim_selectors: Vec<String>,
+}
+
+impl SenderService {
+    pub fn new(db: Arc<Database>, config: &SendersConfig) -> AppResult<Self> {
+        Ok(Self {
+            senders: SenderRepository::new(db),
+            dns: DnsClient::new(config)?,
+            spf_include: config.spf_include.clone(),
+            dkim_selectors: config.dkim_selectors.clone(),
+        })
+    }
+
+    /// The workspace's shared identities and the user's own
+    pub async fn list(&self, workspace_id: &str, user_id: &str) -> AppResult<Vec<SenderResponse>> {
+        let identities = self.senders.find_visible(workspace_id, user_id).await?;
+        Ok(identities.into_iter().map(Into::into).collect())
+    }
+
+    /// An identity the user can see
+    pub async fn get(&self, workspace_id: &str, user_id: &str, id: &str) -> AppResult<SenderIdentity> {
+        self.senders
+            .find_by_id(workspace_id, id)
+            .await?
+            .filter(|identity| visible_to(identity, user_id))
+            .ok_or_else(|| AppError::NotFound("Sender identity not found".into()))
+    }
+
+    /// Create an identity and check its domain right away
+    ///
+    /// A failed lookup leaves the identity unchecked rather than failing
+    /// the create; `verify` can be retried.
+    pub async fn create(
+        &self,
+        workspace_id: &str,
+        user_id: &str,
+        req: CreateSenderRequest,
+    ) -> AppResult<SenderResponse> {
+        let reply_to = non_empty(req.reply_to);
+        let signature_html = non_empty(req.signature_html);
+        validate_sender(&req.from_name, &req.from_email, reply_to.as_deref(), signature_html.as_deref())?;
+        let domain = domain_of(&req.from_email)?;
+
+        let now = Utc::now();
+        let identity = self
+            .senders
+            .create(SenderIdentity {
+                id: None,
+                workspace: workspace_thing(workspace_id),
+                owner: req.personal.then(|| Thing::from(("user", user_id))),
+                from_name: req.from_name.trim().to_string(),
+                from_email: req.from_email.trim().to_lowercase(),
+                reply_to,
+                signature_html,
+                domain,
+                verification_token: generate_verification_token(),
+                ownership: DnsCheck::default(),
+                spf: DnsCheck::default(),
+                dkim: DnsCheck::default(),
+                checked_at: None,
+                created_at: now,
+                updated_at: now,
+            })
+            .await?
+            .ok_or_else(|| AppError::Internal("Failed to create sender identity".into()))?;
+
+        Ok(self.verify_or_keep(identity).await.into())
+    }
+
+    pub async fn update(
+        &self,
+        workspace_id: &str,
+        user_id: &str,
+        id: &str,
+        req: UpdateSenderRequest,
+    ) -> AppResult<SenderResponse> {
+        let mut identity = self.get(workspace_id, user_id, id).await?;
+
+        if let Some(from_name) = req.from_name {
+            identity.from_name = from_name.trim().to_string();
+        }
+        if let Some(from_email) = req.from_email {
+            identity.from_email = from_email.trim().to_lowercase();
+        }
+        if let Some(reply_to) = req.reply_to {
+            identity.reply_to = non_empty(Some(reply_to));
+        }
+        if let Some(signature_html) = req.signature_html {
+            identity.signature_html = non_empty(Some(signature_html));
+        }
+        validate_sender(
+            &identity.from_name,
+            &identity.from_email,
+            identity.reply_to.as_deref(),
+            identity.signature_html.as_deref(),
+        )?;
+
+        let domain = domain_of(&identity.from_email)?;
+        let domain_changed = domain != identity.domain;
+        if domain_changed {
+            identity.domain = domain;
+            identity.ownership = DnsCheck::default();
+            identity.spf = DnsCheck::default();
+            identity.dkim = DnsCheck::default();
+            identity.checked_at = None;
+        }
+        identity.updated_at = Utc::now();
+
+        let identity = self
+            .senders
+            .update(identity)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Sender identity not found".into()))?;
+
+        if domain_changed {
+            return Ok(self.verify_or_keep(identity).await.into());
+        }
+        Ok(identity.into())
+    }
+
+    /// Delete an identity no unfinished campaign sends from
+    pub async fn delete(&self, workspace_id: &str, user_id: &str, id: &str) -> AppResult<()> {
+        let identity = self.get(workspace_id, user_id, id).await?;
+        let Some(thing) = identity.id else {
+            return Err(AppError::NotFound("Sender identity not found".into()));
+        };
+
+        if self.senders.in_use(workspace_id, &thing).await? {
+            return Err(AppError::Conflict(
+                "Sender identity is used by a campaign that hasn't finished; pick another sender first".into(),
+            ));
+        }
+
+        self.senders.delete(workspace_id, id).await?;
+        Ok(())
+    }
+
+    /// Look up the identity's domain records and store the outcome
+    pub async fn verify(&self, workspace_id: &str, user_id: &str, id: &str) -> AppResult<SenderResponse> {
+        let identity = self.get(workspace_id, user_id, id).await?;
+        Ok(self.check_domain(identity).await?.into())
+    }
+
+    /// The identity a campaign is set to send from, as a record link
+    ///
+    /// Any identity the user can see may be selected; verification is
+    /// enforced when the campaign sends.
+    pub async fn selectable(&self, workspace_id: &str, user_id: &str, id: &str) -> AppResult<Thing> {
+        self.get(workspace_id, user_id, id)
+            .await?
+            .id
+            .ok_or_else(|| AppError::NotFound("Sender identity not found".into()))
+    }
+
+    /// The verified identity a campaign's email goes out with
+    pub async fn for_campaign(&self, campaign: &Campaign) -> AppResult<SenderIdentity> {
+        let Some(sender) = campaign.sender.as_ref() else {
+            return Err(AppError::Validation("Campaign email needs a sender identity".into()));
+        };
+
+        let workspace_id = campaign.workspace.id.to_raw();
+        let identity = self
+            .senders
+            .find_by_id(&workspace_id, &sender.id.to_raw())
+            .await?
+            .ok_or_else(|| AppError::Validation("The campaign's sender identity no longer exists".into()))?;
+
+        ensure_verified(&identity)?;
+        Ok(identity)
+    }
+
+    async fn check_domain(&self, identity: SenderIdentity) -> AppResult<SenderIdentity> {
+        let id = identity
+            .id
+            .clone()
+            .ok_or_else(|| AppError::Internal("Sender identity has no ID".into()))?;
+
+        let token_records = self.dns.txt_records(&verification_name(&identity.domain)).await?;
+        let ownership = check_ownership(&token_records, &identity.verification_token);
+
+        let spf_records = self.dns.txt_records(&identity.domain).await?;
+        let spf = check_spf(&spf_records, &self.spf_include);
+
+        let mut lookups = Vec::with_capacity(self.dkim_selectors.len());
+        for name in dkim_names(&identity.domain, &self.dkim_selectors) {
+            let records = self.dns.txt_records(&name).await?;
+            lookups.push((name, records));
+        }
+        let dkim = check_dkim(&lookups);
+
+        self.senders
+            .record_checks(&id, ownership, spf, dkim, Utc::now())
+            .await?
+            .ok_or_else(|| AppError::NotFound("Sender identity not found".into()))
+    }
+
+    /// `check_domain`, keeping the identity as it was when the lookup fails
+    async fn verify_or_keep(&self, identity: SenderIdentity) -> SenderIdentity {
+        match self.check_domain(identity.clone()).await {
+            Ok(checked) => checked,
+            Err(e) => {
+                tracing::warn!("Failed to check sender domain {}: {}", identity.domain, e);
+                identity
+            }
+        }
+    }
+}
+
+/// Check an identity may send: all of its domain checks pass
+fn ensure_verified(identity: &SenderIdentity) -> AppResult<()> {
+    if identity.is_verified() {
+        return Ok(());
+    }
+    Err(AppError::Validation(format!(
+        "Sender domain {} is not verified; publish its verification, SPF and DKIM records and verify it first",
+        identity.domain
+    )))
+}
+
+fn visible_to(identity: &SenderIdentity, user_id: &str) -> bool {
+    identity.owner.as_ref().is_none_or(|owner| owner.id.to_string() == user_id)
+}
+
+fn domain_of(email: &str) -> AppResult<String> {
+    sender_domain(email).ok_or_else(|| AppError::Validation("from_email has no domain".into()))
+}
+
+/// Names the DKIM selectors' keys are published at
+fn dkim_names(domain: &str, selectors: &[String]) -> Vec<String> {
+    selectors
+        .iter()
+        .map(|selector| format!("{}._domainkey.{}", selector, domain))
+        .collect()
+}
+
+/// A new token for an identity's ownership TXT record
+pub fn generate_verification_token() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::DnsCheckStatus;
+
+    fn identity(owner: Option<&str>) -> SenderIdentity {
+        let now = Utc::now();
+        SenderIdentity {
+            id: Some(Thing::from(("sender_identity", "s1"))),
+            workspace: workspace_thing("w1"),
+            owner: owner.map(|o| Thing::from(("user", o))),
+            from_name: "Jane".into(),
+            from_email: "jane@example.com".into(),
+            reply_to: None,
+            signature_html: None,
+            domain: "example.com".into(),
+            verification_token: "abc123".into(),
+            ownership: DnsCheck::default(),
+            spf: DnsCheck::default(),
+            dkim: DnsCheck::default(),
+            checked_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_personal_identities_are_visible_to_their_owner_only() {
+        assert!(visible_to(&identity(None), "u1"));
+        assert!(visible_to(&identity(Some("u1")), "u1"));
+        assert!(!visible_to(&identity(Some("u2")), "u1"));
+    }
+
+    #[test]
+    fn test_ensure_verified_needs_every_check() {
+        let pass = DnsCheck {
+            status: DnsCheckStatus::Pass,
+            detail: None,
+        };
+
+        let mut sender = identity(None);
+        assert!(matches!(ensure_verified(&sender), Err(AppError::Validation(_))));
+
+        sender.spf = pass.clone();
+        sender.dkim = pass.clone();
+        assert!(ensure_verified(&sender).is_err(), "SPF and DKIM don't show the domain is ours");
+
+        sender.ownership = pass;
+        assert!(ensure_verified(&sender).is_ok());
+    }
+
+    #[test]
+    fn test_verification_tokens_differ() {
+        let token = generate_verification_token();
+        assert_eq!(token.len(), 32);
+        assert_ne!(token, generate_verification_token());
+    }
+
+    #[test]
+    fn test_dkim_names() {
+        let selectors = vec!["s1".to_string(), "s2".to_string()];
+        assert_eq!(
+            dkim_names("example.com", &selectors),
+            vec!["s1._domainkey.example.com", "s2._domainkey.example.com"]
+        );
+    }
+}
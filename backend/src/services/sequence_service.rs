@@ -0,0 +1,397 @@
+//! Sequence Service - drip sequences and the runner that advances them
+//!
+//! Contacts are enrolled at the first step. A background runner picks up
+//! every active enrollment whose next step is due, asks the domain where
+//! the contact goes from there (see `domain::sequence::advance`), sends the
+//! emails on the way and stores the new position and resume time.
+//!
+//! Branches look at the contact's timeline: an `email_open` or
+//! `email_click` entry since the last sequence email counts as engagement.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use surrealdb::sql::Thing;
+use tokio::task::JoinHandle;
+
+use crate::config::SequenceConfig;
+use crate::db::{workspace_thing, Database};
+use crate::domain::{advance, render, validate_steps, EmailActivity, Escape, SequenceStep};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    CreateSequenceRequest, EnrollmentChangeResponse, EnrollmentQuery, EnrollmentResponse, EnrollmentStatus, Sequence,
+    SequenceResponse, TimelineEntry, TimelineEntryType,
+};
+use crate::repositories::{ContactRepository, DueEnrollment, SequenceRepository};
+use crate::services::{new_entry, TimelineService};
+use crate::shutdown::Shutdown;
+
+/// Most contacts one enroll or unenroll request may list
+pub const MAX_ENROLLMENT_BATCH: usize = 1000;
+
+/// How long an enrollment waits before retrying an email with merge
+/// variables the contact has no value for
+const UNRESOLVED_RETRY_HOURS: i64 = 24;
+
+/// What one runner pass did
+#[derive(Debug, Clone, Default)]
+pub struct SequenceRunSummary {
+    pub advanced: usize,
+    pub emails_sent: usize,
+    pub completed: usize,
+    pub unenrolled: usize,
+}
+
+pub struct SequenceService {
+    sequences: SequenceRepository,
+    contacts: ContactRepository,
+    timeline: Arc<TimelineService>,
+}
+
+impl SequenceService {
+    pub fn new(db: Arc<Database>, timeline: Arc<TimelineService>) -> Self {
+        Self {
+            sequences: SequenceRepository::new(Arc::clone(&db)),
+            contacts: ContactRepository::new(db),
+            timeline,
+        }
+    }
+
+    // ---- Sequences ----
+
+    pub async fn list(&self, workspace_id: &str) -> AppResult<Vec<SequenceResponse>> {
+        let sequences = self.sequences.find_all(workspace_id).await?;
+        Ok(sequences.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get(&self, workspace_id: &str, id: &str) -> AppResult<Sequence> {
+        self.sequences
+            .find_by_id(workspace_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Sequence not found".into()))
+    }
+
+    pub async fn create(&self, workspace_id: &str, req: CreateSequenceRequest) -> AppResult<SequenceResponse> {
+        let name = req.name.trim();
+        if name.is_empty() {
+            return Err(AppError::Validation("Sequence name is required".into()));
+        }
+        validate_steps(&req.steps)?;
+
+        let now = Utc::now();
+        let sequence = self
+            .sequences
+            .create(Sequence {
+                id: None,
+                workspace: workspace_thing(workspace_id),
+                name: name.to_string(),
+                description: req.description,
+                steps: req.steps,
+                created_at: now,
+                updated_at: now,
+            })
+            .await?;
+
+        Ok(sequence.into())
+    }
+
+    /// Delete a sequence nobody is active in anymore
+    pub async fn delete(&self, workspace_id: &str, id: &str) -> AppResult<()> {
+        self.get(workspace_id, id).await?;
+
+        if self.sequences.count_active(workspace_id, id).await? > 0 {
+            return Err(AppError::Conflict(
+                "Sequence has active enrollments; unenroll them first".into(),
+            ));
+        }
+
+        self.sequences.delete(workspace_id, id).await
+    }
+
+    // ---- Enrollments ----
+
+    pub async fn enrollments(
+        &self,
+        workspace_id: &str,
+        id: &str,
+        query: &EnrollmentQuery,
+    ) -> AppResult<Vec<EnrollmentResponse>> {
+        self.get(workspace_id, id).await?;
+
+        let enrollments = self
+            .sequences
+            .find_enrollments(
+                workspace_id,
+                id,
+                query.status,
+                query.limit.unwrap_or(100).min(1000),
+                query.offset.unwrap_or(0),
+            )
+            .await?;
+
+        Ok(enrollments.into_iter().map(Into::into).collect())
+    }
+
+    /// Enroll contacts at the first step; the runner sends it on its next pass
+    pub async fn enroll(
+        &self,
+        workspace_id: &str,
+        id: &str,
+        contact_ids: Vec<String>,
+    ) -> AppResult<EnrollmentChangeResponse> {
+        self.get(workspace_id, id).await?;
+        let contact_ids = dedup_batch(contact_ids)?;
+
+        let found = self.contacts.find_many_with_ids(workspace_id, &contact_ids).await?;
+        if found.len() < contact_ids.len() {
+            let missing: Vec<&str> = contact_ids
+                .iter()
+                .filter(|id| !found.iter().any(|c| &c.id == *id))
+                .map(String::as_str)
+                .collect();
+            return Err(AppError::NotFound(format!("Contacts not found: {}", missing.join(", "))));
+        }
+
+        let contacts: Vec<Thing> = contact_ids.iter().map(|c| Thing::from(("contact", c.as_str()))).collect();
+        let changed = self.sequences.enroll(workspace_id, id, contacts).await?;
+
+        Ok(EnrollmentChangeResponse {
+            sequence_id: id.to_string(),
+            changed,
+            unchanged: contact_ids.len() - changed,
+        })
+    }
+
+    /// End the active enrollments of the listed contacts
+    pub async fn unenroll(
+        &self,
+        workspace_id: &str,
+        id: &str,
+        contact_ids: Vec<String>,
+    ) -> AppResult<EnrollmentChangeResponse> {
+        self.get(workspace_id, id).await?;
+        let contact_ids = dedup_batch(contact_ids)?;
+
+        let contacts: Vec<Thing> = contact_ids.iter().map(|c| Thing::from(("contact", c.as_str()))).collect();
+        let changed = self.sequences.unenroll(workspace_id, id, contacts).await?;
+
+        Ok(EnrollmentChangeResponse {
+            sequence_id: id.to_string(),
+            changed,
+            unchanged: contact_ids.len() - changed,
+        })
+    }
+
+    // ---- Runner ----
+
+    /// Advance every enrollment whose next step is due
+    ///
+    /// One enrollment failing is logged and retried on the next pass.
+    pub async fn run_due(&self, batch_size: u32) -> AppResult<SequenceRunSummary> {
+        let now = Utc::now();
+        let mut summary = SequenceRunSummary::default();
+        let mut sequences: HashMap<String, Option<Sequence>> = HashMap::new();
+
+        for due in self.sequences.find_due(now, batch_size).await? {
+            let sequence_id = due.sequence.id.to_raw();
+            if !sequences.contains_key(&sequence_id) {
+                let sequence = self.sequences.find_by_id(&due.workspace.id.to_raw(), &sequence_id).await?;
+                sequences.insert(sequence_id.clone(), sequence);
+            }
+            let Some(sequence) = sequences.get(&sequence_id).and_then(Option::as_ref) else {
+                continue;
+            };
+
+            if let Err(e) = self.run_enrollment(sequence, &due, now, &mut summary).await {
+                tracing::warn!("Failed to advance sequence enrollment {}: {}", due.id, e);
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Run `run_due` on a background task at the configured interval
+    pub fn spawn_runner(self: Arc<Self>, config: &SequenceConfig, shutdown: Shutdown) -> JoinHandle<()> {
+        let period = Duration::from_secs(config.run_interval_secs.max(60));
+        let batch_size = config.batch_size.max(1);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+
+            while shutdown.tick(&mut interval).await {
+                match self.run_due(batch_size).await {
+                    Ok(summary) if summary.advanced > 0 => tracing::info!(
+                        "Advanced {} sequence enrollments: {} emails sent, {} completed, {} unenrolled",
+                        summary.advanced,
+                        summary.emails_sent,
+                        summary.completed,
+                        summary.unenrolled
+                    ),
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Sequence run failed: {}", e),
+                }
+            }
+        })
+    }
+
+    async fn run_enrollment(
+        &self,
+        sequence: &Sequence,
+        due: &DueEnrollment,
+        now: DateTime<Utc>,
+        summary: &mut SequenceRunSummary,
+    ) -> AppResult<()> {
+        // Deleted or unsubscribed since enrolling: no more sequence email
+        let deliverable = match (&due.email, &due.subscription_status) {
+            (Some(_), Some(status)) => !status.is_suppressed(),
+            _ => false,
+        };
+        if !deliverable {
+            self.sequences
+                .update_progress(
+                    &due.id,
+                    EnrollmentStatus::Unenrolled,
+                    due.position,
+                    None,
+                    due.last_email_at,
+                    Vec::new(),
+                )
+                .await?;
+            summary.unenrolled += 1;
+            return Ok(());
+        }
+
+        // Only a branch at the current step looks at what happened since
+        // the last email; anything later follows an email sent right now
+        let activity = match (sequence.steps.get(due.position), due.last_email_at) {
+            (Some(SequenceStep::Branch { .. }), Some(since)) => {
+                self.sequences.email_activity(&due.workspace, &due.contact, since).await?
+            }
+            _ => EmailActivity::default(),
+        };
+
+        let step = advance(&sequence.steps, due.position, activity, now);
+
+        let fields = due.merge_fields();
+        let mut emails = Vec::new();
+        for &index in &step.emails {
+            let Some(SequenceStep::Email { subject, body_html }) = sequence.steps.get(index) else {
+                continue;
+            };
+            let subject = render(subject, &fields, Escape::None);
+            let body = render(body_html, &fields, Escape::Html);
+
+            if !subject.is_complete() || !body.is_complete() {
+                // Leave the contact at this step until the data is filled in
+                tracing::warn!(
+                    "Sequence enrollment {} waits for values of {}",
+                    due.id,
+                    [subject.unresolved, body.unresolved].concat().join(", ")
+                );
+                self.sequences
+                    .update_progress(
+                        &due.id,
+                        EnrollmentStatus::Active,
+                        due.position,
+                        Some(now + chrono::Duration::hours(UNRESOLVED_RETRY_HOURS)),
+                        due.last_email_at,
+                        Vec::new(),
+                    )
+                    .await?;
+                return Ok(());
+            }
+            emails.push((index, subject.text));
+        }
+
+        // Stub: In production, this would hand the emails to the email provider
+        let entries: Vec<TimelineEntry> = emails
+            .iter()
+            .map(|(index, subject)| sent_entry(sequence, due, *index, subject, now))
+            .collect();
+
+        let last_email_at = if emails.is_empty() { due.last_email_at } else { Some(now) };
+        let status = if step.is_complete() {
+            EnrollmentStatus::Completed
+        } else {
+            EnrollmentStatus::Active
+        };
+        // The emails' entries are stored with the new position, so a failure
+        // can't leave the contact to be emailed the same step again
+        let advanced = self
+            .sequences
+            .update_progress(&due.id, status, step.position, step.resume_at, last_email_at, entries.clone())
+            .await?;
+        if advanced {
+            self.timeline.recorded(&due.workspace.id.to_raw(), &entries).await;
+        }
+
+        summary.advanced += 1;
+        summary.emails_sent += emails.len();
+        if step.is_complete() {
+            summary.completed += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Timeline entry for a sequence email
+fn sent_entry(
+    sequence: &Sequence,
+    due: &DueEnrollment,
+    step: usize,
+    subject: &str,
+    now: DateTime<Utc>,
+) -> TimelineEntry {
+    let mut entry = new_entry(
+        &due.workspace.id.to_raw(),
+        &due.contact.id.to_raw(),
+        TimelineEntryType::EmailSent,
+        format!("Sent \"{}\" from sequence {}", subject, sequence.name),
+        serde_json::json!({
+            "sequence_id": due.sequence.id.to_raw(),
+            "step": step,
+            "subject": subject,
+        }),
+    );
+    entry.timestamp = now;
+    entry
+}
+
+/// Enforce the batch limit, then drop repeated IDs
+///
+/// The limit applies to the list as sent, so an oversized request is
+/// rejected before any work is done on it.
+fn dedup_batch(contact_ids: Vec<String>) -> AppResult<Vec<String>> {
+    if contact_ids.len() > MAX_ENROLLMENT_BATCH {
+        return Err(AppError::Validation(format!(
+            "At most {} contacts can be enrolled or unenrolled at once",
+            MAX_ENROLLMENT_BATCH
+        )));
+    }
+
+    let mut seen = HashSet::with_capacity(contact_ids.len());
+    let unique: Vec<String> = contact_ids.into_iter().filter(|id| seen.insert(id.clone())).collect();
+
+    if unique.is_empty() {
+        return Err(AppError::Validation("contact_ids must list at least one contact".into()));
+    }
+    Ok(unique)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedup_batch() {
+        let ids = dedup_batch(vec!["a".into(), "b".into(), "a".into()]).unwrap();
+        assert_eq!(ids, vec!["a", "b"]);
+
+        assert!(dedup_batch(Vec::new()).is_err());
+        assert!(dedup_batch((0..=MAX_ENROLLMENT_BATCH).map(|i| i.to_string()).collect()).is_err());
+        // Counted before duplicates are dropped
+        assert!(dedup_batch(vec!["a".to_string(); MAX_ENROLLMENT_BATCH + 1]).is_err());
+    }
+}
@@ -0,0 +1,377 @@
+//! Social publishers - posting campaign content to social networks
+//!
+//! `SocialPublisher` turns post text into a live post. `LinkedInPublisher`
+//! and `TwitterPublisher` call the platforms' HTTP APIs with the access
+//! token of the workspace's connected account; only platforms enabled in
+//! config get a publisher.
+//!
+//! Neither API takes an idempotency key, so a failed publish says whether
+//! the post could have gone live anyway. Only posts that certainly didn't
+//! are retried.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+use crate::ai::ai_social::{GeneratedPost, SocialPlatform};
+use crate::config::SocialConfig;
+use crate::error::{AppError, AppResult};
+use crate::models::SocialAccount;
+
+/// Longest post X accepts
+pub const TWITTER_MAX_CHARS: usize = 280;
+
+/// Longest post LinkedIn accepts
+pub const LINKEDIN_MAX_CHARS: usize = 3000;
+
+/// A post that went live
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublishedPost {
+    /// The platform's ID for the post
+    pub external_id: String,
+    pub url: String,
+}
+
+/// Why a publish failed
+#[derive(Debug, Clone, PartialEq)]
+pub enum PublishError {
+    /// The platform refused the post or was never reached; safe to retry
+    Failed(String),
+    /// The request may have gone through; retrying could post twice
+    Unconfirmed(String),
+}
+
+impl fmt::Display for PublishError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PublishError::Failed(msg) => f.write_str(msg),
+            PublishError::Unconfirmed(msg) => write!(f, "{} (the post may be live)", msg),
+        }
+    }
+}
+
+pub trait SocialPublisher: Send + Sync {
+    fn platform(&self) -> SocialPlatform;
+
+    fn publish<'a>(
+        &'a self,
+        account: &'a SocialAccount,
+        text: &'a str,
+    ) -> BoxFuture<'a, Result<PublishedPost, PublishError>>;
+}
+
+/// Publishers by platform
+pub type Publishers = HashMap<SocialPlatform, Arc<dyn SocialPublisher>>;
+
+/// Build a publisher for every platform enabled in config
+pub fn build_publishers(config: &SocialConfig) -> AppResult<Publishers> {
+    let mut publishers: Publishers = HashMap::new();
+
+    if config.linkedin.enabled {
+        let publisher = LinkedInPublisher { http: http_client(config)? };
+        publishers.insert(SocialPlatform::LinkedIn, Arc::new(publisher));
+    }
+
+    if config.twitter.enabled {
+        let publisher = TwitterPublisher { http: http_client(config)? };
+        publishers.insert(SocialPlatform::Twitter, Arc::new(publisher));
+    }
+
+    if !publishers.is_empty() {
+        let platforms: Vec<_> = publishers.keys().collect();
+        tracing::info!("Social publishing enabled for {:?}", platforms);
+    }
+    Ok(publishers)
+}
+
+fn http_client(config: &SocialConfig) -> AppResult<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.request_timeout_secs))
+        .build()
+        .map_err(|e| AppError::Internal(format!("Failed to build social client: {}", e)))
+}
+
+/// The text a generated post is published with: its content, followed by
+/// any hashtags the content doesn't already mention
+pub fn post_text(post: &GeneratedPost) -> String {
+    let content = post.content.trim();
+    let lowered = content.to_lowercase();
+
+    let tags: Vec<String> = post
+        .hashtags
+        .iter()
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .map(|t| if t.starts_with('#') { t.to_string() } else { format!("#{}", t) })
+        .filter(|t| !lowered.contains(&t.to_lowercase()))
+        .collect();
+
+    if tags.is_empty() {
+        content.to_string()
+    } else {
+        format!("{}\n\n{}", content, tags.join(" "))
+    }
+}
+
+/// Check an account has what its platform needs to publish
+pub fn validate_account(platform: SocialPlatform, access_token: &str, author_urn: Option<&str>) -> AppResult<()> {
+    if access_token.trim().is_empty() {
+        return Err(AppError::Validation("Access token cannot be empty".into()));
+    }
+    match platform {
+        SocialPlatform::LinkedIn => match author_urn.map(str::trim) {
+            Some(urn) if urn.starts_with("urn:li:person:") || urn.starts_with("urn:li:organization:") => Ok(()),
+            _ => Err(AppError::Validation(
+                "LinkedIn needs author_urn: urn:li:person:<id> or urn:li:organization:<id>".into(),
+            )),
+        },
+        SocialPlatform::Twitter => Ok(()),
+        _ => Err(AppError::BadRequest(format!("Publishing to {:?} is not supported", platform))),
+    }
+}
+
+/// Check the text fits the platform's length limit
+pub fn validate_post_length(platform: SocialPlatform, text: &str) -> AppResult<()> {
+    let limit = match platform {
+        SocialPlatform::Twitter => TWITTER_MAX_CHARS,
+        SocialPlatform::LinkedIn => LINKEDIN_MAX_CHARS,
+        _ => return Ok(()),
+    };
+
+    let length = text.chars().count();
+    if length > limit {
+        return Err(AppError::Validation(format!(
+            "Post is {} characters; {:?} allows {}",
+            length, platform, limit
+        )));
+    }
+    Ok(())
+}
+
+/// POST `body` with a bearer token and return the JSON response
+async fn post_json(
+    http: &reqwest::Client,
+    url: &str,
+    access_token: &str,
+    headers: &[(&str, &str)],
+    body: &Value,
+) -> Result<Value, PublishError> {
+    let mut request = http.post(url).bearer_auth(access_token).json(body);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+
+    let response = request.send().await.map_err(|e| {
+        let msg = format!("Social platform unreachable: {}", e);
+        // Only a failed connection is sure not to have delivered the request
+        if e.is_connect() {
+            PublishError::Failed(msg)
+        } else {
+            PublishError::Unconfirmed(msg)
+        }
+    })?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(rejected(status, &text));
+    }
+
+    // LinkedIn answers 201 with the post ID in a header and may omit the body
+    let id_header = response
+        .headers()
+        .get("x-restli-id")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let text = response.text().await.unwrap_or_default();
+    let mut body: Value = if text.trim().is_empty() {
+        json!({})
+    } else {
+        serde_json::from_str(&text)
+            .map_err(|e| PublishError::Unconfirmed(format!("Unreadable social platform response: {}", e)))?
+    };
+    if let (Some(id), Some(object)) = (id_header, body.as_object_mut()) {
+        object.entry("id").or_insert(Value::String(id));
+    }
+
+    Ok(body)
+}
+
+/// A platform's error response; a gateway timeout may hide a post that went live
+fn rejected(status: StatusCode, text: &str) -> PublishError {
+    let msg = format!("Social platform returned {}: {}", status, text);
+    if status == StatusCode::GATEWAY_TIMEOUT {
+        PublishError::Unconfirmed(msg)
+    } else {
+        PublishError::Failed(msg)
+    }
+}
+
+/// LinkedIn UGC Posts API
+pub struct LinkedInPublisher {
+    http: reqwest::Client,
+}
+
+impl SocialPublisher for LinkedInPublisher {
+    fn platform(&self) -> SocialPlatform {
+        SocialPlatform::LinkedIn
+    }
+
+    fn publish<'a>(
+        &'a self,
+        account: &'a SocialAccount,
+        text: &'a str,
+    ) -> BoxFuture<'a, Result<PublishedPost, PublishError>> {
+        Box::pin(async move {
+            let author = account
+                .author_urn
+                .as_deref()
+                .ok_or_else(|| PublishError::Failed("The LinkedIn account has no author_urn".into()))?;
+            let body = json!({
+                "author": author,
+                "lifecycleState": "PUBLISHED",
+                "specificContent": {
+                    "com.linkedin.ugc.ShareContent": {
+                        "shareCommentary": { "text": text },
+                        "shareMediaCategory": "NONE",
+                    },
+                },
+                "visibility": { "com.linkedin.ugc.MemberNetworkVisibility": "PUBLIC" },
+            });
+            let headers = [("X-Restli-Protocol-Version", "2.0.0")];
+            let response = post_json(
+                &self.http,
+                "https://api.linkedin.com/v2/ugcPosts",
+                &account.access_token,
+                &headers,
+                &body,
+            )
+            .await?;
+
+            parse_linkedin_response(&response)
+        })
+    }
+}
+
+fn parse_linkedin_response(response: &Value) -> Result<PublishedPost, PublishError> {
+    let id = response["id"]
+        .as_str()
+        .filter(|id| !id.is_empty())
+        .ok_or_else(|| PublishError::Unconfirmed("LinkedIn returned no post ID".into()))?;
+
+    Ok(PublishedPost {
+        external_id: id.to_string(),
+        url: format!("https://www.linkedin.com/feed/update/{}", id),
+    })
+}
+
+/// X API v2 manage-tweets endpoint
+pub struct TwitterPublisher {
+    http: reqwest::Client,
+}
+
+impl SocialPublisher for TwitterPublisher {
+    fn platform(&self) -> SocialPlatform {
+        SocialPlatform::Twitter
+    }
+
+    fn publish<'a>(
+        &'a self,
+        account: &'a SocialAccount,
+        text: &'a str,
+    ) -> BoxFuture<'a, Result<PublishedPost, PublishError>> {
+        Box::pin(async move {
+            let body = json!({ "text": text });
+            let response = post_json(
+                &self.http,
+                "https://api.twitter.com/2/tweets",
+                &account.access_token,
+                &[],
+                &body,
+            )
+            .await?;
+
+            parse_twitter_response(&response)
+        })
+    }
+}
+
+fn parse_twitter_response(response: &Value) -> Result<PublishedPost, PublishError> {
+    let id = response["data"]["id"]
+        .as_str()
+        .filter(|id| !id.is_empty())
+        .ok_or_else(|| PublishError::Unconfirmed("X returned no post ID".into()))?;
+
+    Ok(PublishedPost {
+        external_id: id.to_string(),
+        url: format!("https://x.com/i/web/status/{}", id),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(content: &str, hashtags: &[&str]) -> GeneratedPost {
+        GeneratedPost {
+            platform: SocialPlatform::Twitter,
+            content: content.into(),
+            hashtags: hashtags.iter().map(|t| t.to_string()).collect(),
+            suggested_image_prompt: String::new(),
+            character_count: content.len(),
+        }
+    }
+
+    #[test]
+    fn test_post_text_appends_missing_hashtags() {
+        let text = post_text(&post("Launching today with #CRM for founders ", &["#crm", "startup", " "]));
+        assert_eq!(text, "Launching today with #CRM for founders\n\n#startup");
+
+        assert_eq!(post_text(&post("No tags", &[])), "No tags");
+    }
+
+    #[test]
+    fn test_validate_post_length_counts_characters() {
+        let exactly = "é".repeat(TWITTER_MAX_CHARS);
+        assert!(validate_post_length(SocialPlatform::Twitter, &exactly).is_ok());
+        assert!(validate_post_length(SocialPlatform::Twitter, &format!("{}!", exactly)).is_err());
+        assert!(validate_post_length(SocialPlatform::LinkedIn, &format!("{}!", exactly)).is_ok());
+    }
+
+    #[test]
+    fn test_parse_publish_responses() {
+        let linkedin = parse_linkedin_response(&json!({ "id": "urn:li:share:7001" })).unwrap();
+        assert_eq!(linkedin.url, "https://www.linkedin.com/feed/update/urn:li:share:7001");
+
+        let twitter = parse_twitter_response(&json!({ "data": { "id": "1790", "text": "Hi" } })).unwrap();
+        assert_eq!(twitter.external_id, "1790");
+        assert_eq!(twitter.url, "https://x.com/i/web/status/1790");
+
+        assert!(matches!(
+            parse_twitter_response(&json!({ "errors": [] })),
+            Err(PublishError::Unconfirmed(_))
+        ));
+    }
+
+    #[test]
+    fn test_only_ambiguous_rejections_are_unconfirmed() {
+        assert!(matches!(rejected(StatusCode::UNAUTHORIZED, ""), PublishError::Failed(_)));
+        assert!(matches!(rejected(StatusCode::SERVICE_UNAVAILABLE, ""), PublishError::Failed(_)));
+        assert!(matches!(rejected(StatusCode::GATEWAY_TIMEOUT, ""), PublishError::Unconfirmed(_)));
+    }
+
+    #[test]
+    fn test_validate_account() {
+        assert!(validate_account(SocialPlatform::Twitter, "token", None).is_ok());
+        assert!(validate_account(SocialPlatform::Twitter, " ", None).is_err());
+        assert!(validate_account(SocialPlatform::LinkedIn, "token", Some("urn:li:organization:42")).is_ok());
+        assert!(validate_account(SocialPlatform::LinkedIn, "token", None).is_err());
+        assert!(validate_account(SocialPlatform::LinkedIn, "token", Some("42")).is_err());
+        assert!(validate_account(SocialPlatform::Facebook, "token", None).is_err());
+    }
+}
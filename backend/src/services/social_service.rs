@@ -0,0 +1,461 @@
+//! Social Service - scheduling and publishing campaign social posts
+//!
+//! Each workspace connects its own account per platform; access tokens are
+//! stored encrypted. When a campaign
+//! with the social channel runs, every post of its active social_post asset
+//! is queued for the platforms that have a publisher and a connected account.
+//! Posts can also be scheduled one by one, with a publish time and the
+//! contacts they are aimed at.
+//!
+//! A background task publishes due posts. A published post's URL is written
+//! back to its asset and every tagged contact gets a `social_touch` timeline
+//! entry. Failures are retried with exponential backoff until
+//! `max_attempts`, after which the post is marked failed. A post that may
+//! have gone live despite the error is marked unconfirmed and not retried.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use surrealdb::sql::Thing;
+use tokio::task::JoinHandle;
+
+use crate::ai::ai_social::{GeneratedPost, SocialPlatform};
+use crate::config::SocialConfig;
+use crate::db::Database;
+use crate::domain::retry_delay;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    AssetType, Campaign, CampaignAsset, ConnectSocialAccountRequest, ScheduleSocialPostRequest, SocialAccount,
+    SocialAccountResponse, SocialPost, SocialPostResponse, SocialPostStatus, TimelineEntryType,
+};
+use crate::repositories::{CampaignRepository, ContactRepository, SocialAccountRepository, SocialPostRepository};
+use crate::services::credential_cipher::CredentialCipher;
+use crate::services::social_publisher::{
+    post_text, validate_account, validate_post_length, PublishError, PublishedPost, Publishers,
+};
+use crate::services::{new_entry, TimelineService};
+use crate::shutdown::Shutdown;
+
+/// Posts claimed per run
+const BATCH_SIZE: u32 = 20;
+
+/// Most contacts one post may be aimed at
+pub const MAX_TAGGED_CONTACTS: usize = 500;
+
+pub struct SocialService {
+    accounts: SocialAccountRepository,
+    posts: SocialPostRepository,
+    campaigns: CampaignRepository,
+    contacts: ContactRepository,
+    timeline: Arc<TimelineService>,
+    publishers: Publishers,
+    cipher: Option<Arc<CredentialCipher>>,
+    config: SocialConfig,
+}
+
+impl SocialService {
+    pub fn new(
+        db: Arc<Database>,
+        timeline: Arc<TimelineService>,
+        publishers: Publishers,
+        cipher: Option<Arc<CredentialCipher>>,
+        config: &SocialConfig,
+    ) -> Self {
+        Self {
+            accounts: SocialAccountRepository::new(Arc::clone(&db)),
+            posts: SocialPostRepository::new(Arc::clone(&db)),
+            campaigns: CampaignRepository::new(Arc::clone(&db)),
+            contacts: ContactRepository::new(db),
+            timeline,
+            publishers,
+            cipher,
+            config: config.clone(),
+        }
+    }
+
+    /// Connect the workspace's account for a platform, replacing any earlier one
+    pub async fn connect_account(
+        &self,
+        workspace_id: &str,
+        platform: SocialPlatform,
+        req: ConnectSocialAccountRequest,
+    ) -> AppResult<SocialAccountResponse> {
+        if !self.publishers.contains_key(&platform) {
+            return Err(AppError::BadRequest(format!("Publishing to {:?} is not configured", platform)));
+        }
+        let author_urn = match platform {
+            SocialPlatform::LinkedIn => req.author_urn.map(|urn| urn.trim().to_string()),
+            _ => None,
+        };
+        validate_account(platform, &req.access_token, author_urn.as_deref())?;
+        let access_token = self.cipher()?.seal(req.access_token.trim())?;
+
+        let account = self
+            .accounts
+            .save(workspace_id, platform, &access_token, author_urn, Utc::now())
+            .await?;
+        Ok(account.into())
+    }
+
+    pub async fn list_accounts(&self, workspace_id: &str) -> AppResult<Vec<SocialAccountResponse>> {
+        let accounts = self.accounts.find_all(workspace_id).await?;
+        Ok(accounts.into_iter().map(Into::into).collect())
+    }
+
+    /// Disconnect an account; its scheduled posts fail when they come due
+    pub async fn disconnect_account(&self, workspace_id: &str, platform: SocialPlatform) -> AppResult<()> {
+        if !self.accounts.delete(workspace_id, platform).await? {
+            return Err(AppError::NotFound(format!("No {:?} account is connected", platform)));
+        }
+        Ok(())
+    }
+
+    /// Queue a running campaign's generated posts for publishing now
+    ///
+    /// Posts for platforms without a publisher or a connected account, or
+    /// too long for theirs, are skipped. A campaign that already has posts is left alone. Returns
+    /// the number of posts queued.
+    pub async fn enqueue_campaign(&self, workspace_id: &str, campaign: &Campaign) -> AppResult<usize> {
+        let campaign_id = campaign.id.as_ref().map(|t| t.id.to_raw()).unwrap_or_default();
+        if self.posts.has_posts(workspace_id, &campaign_id).await? {
+            return Ok(0);
+        }
+
+        let Some(asset) = self.active_social_asset(workspace_id, &campaign_id).await? else {
+            return Ok(0);
+        };
+        let Some(asset_id) = asset.id.clone() else {
+            return Ok(0);
+        };
+
+        let connected: HashSet<SocialPlatform> = self
+            .accounts
+            .find_all(workspace_id)
+            .await?
+            .into_iter()
+            .map(|a| a.platform)
+            .collect();
+
+        let now = Utc::now();
+        let mut queued = Vec::new();
+        for post in generated_posts(&asset)? {
+            if !self.publishers.contains_key(&post.platform) || !connected.contains(&post.platform) {
+                continue;
+            }
+            let text = post_text(&post);
+            if let Err(e) = validate_post_length(post.platform, &text) {
+                tracing::warn!("Skipping {:?} post of campaign {}: {}", post.platform, campaign_id, e);
+                continue;
+            }
+            queued.push(new_post(&asset, asset_id.clone(), post.platform, text, Vec::new(), now, now));
+        }
+
+        Ok(self.posts.create_many(queued).await?.len())
+    }
+
+    /// Schedule one of an asset's generated posts
+    pub async fn schedule(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        req: ScheduleSocialPostRequest,
+    ) -> AppResult<SocialPostResponse> {
+        self.find_campaign(workspace_id, campaign_id).await?;
+
+        if !self.publishers.contains_key(&req.platform) {
+            return Err(AppError::BadRequest(format!(
+                "Publishing to {:?} is not configured",
+                req.platform
+            )));
+        }
+        if self.accounts.find(workspace_id, req.platform).await?.is_none() {
+            return Err(AppError::BadRequest(format!("No {:?} account is connected", req.platform)));
+        }
+
+        let (asset, asset_id) = self
+            .campaigns
+            .find_assets(workspace_id, campaign_id, false)
+            .await?
+            .into_iter()
+            .filter(|a| matches!(a.asset_type, AssetType::SocialPost))
+            .find_map(|a| {
+                let id = a.id.clone().filter(|id| id.id.to_raw() == req.asset_id)?;
+                Some((a, id))
+            })
+            .ok_or_else(|| AppError::NotFound("Social post asset not found".into()))?;
+
+        let text = match req.text.as_deref().map(str::trim) {
+            Some("") => return Err(AppError::Validation("Post text cannot be empty".into())),
+            Some(text) => text.to_string(),
+            None => generated_posts(&asset)?
+                .iter()
+                .find(|p| p.platform == req.platform)
+                .map(post_text)
+                .ok_or_else(|| AppError::BadRequest(format!("Asset has no {:?} post", req.platform)))?,
+        };
+        validate_post_length(req.platform, &text)?;
+
+        let tagged = self.tagged_contacts(workspace_id, req.contact_ids).await?;
+        let now = Utc::now();
+        let publish_at = req.publish_at.unwrap_or(now).max(now);
+
+        let post = new_post(&asset, asset_id, req.platform, text, tagged, publish_at, now);
+        let created = self
+            .posts
+            .create_many(vec![post])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::Internal("Failed to schedule social post".into()))?;
+
+        Ok(created.into())
+    }
+
+    pub async fn list(&self, workspace_id: &str, campaign_id: &str) -> AppResult<Vec<SocialPostResponse>> {
+        self.find_campaign(workspace_id, campaign_id).await?;
+        let posts = self.posts.find_by_campaign(workspace_id, campaign_id).await?;
+        Ok(posts.into_iter().map(Into::into).collect())
+    }
+
+    /// Publish every post that is due
+    ///
+    /// Returns the number of posts attempted.
+    pub async fn publish_due(&self) -> AppResult<usize> {
+        let now = Utc::now();
+        // Long enough to cover the request itself plus recording it
+        let lease = chrono::Duration::seconds(self.config.request_timeout_secs as i64 + 30);
+
+        let interrupted = self.posts.expire_in_flight(now).await?;
+        if interrupted > 0 {
+            tracing::warn!("{} social posts were cut off mid-publish and are now unconfirmed", interrupted);
+        }
+
+        let due = self.posts.claim_due(now, now + lease, BATCH_SIZE).await?;
+        let attempted = due.len();
+
+        futures::future::join_all(due.into_iter().map(|post| self.publish(post))).await;
+
+        Ok(attempted)
+    }
+
+    /// Run `publish_due` on a background task at the configured interval
+    pub fn spawn(self: Arc<Self>, shutdown: Shutdown) -> JoinHandle<()> {
+        let period = Duration::from_secs(self.config.publish_interval_secs.max(10));
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+
+            while shutdown.tick(&mut interval).await {
+                if let Err(e) = self.publish_due().await {
+                    tracing::error!("Social publishing run failed: {}", e);
+                }
+            }
+        })
+    }
+
+    async fn publish(&self, post: SocialPost) {
+        let Some(id) = post.id.clone() else { return };
+        let attempt = post.attempts + 1;
+
+        let account = match self.accounts.find(&post.workspace.id.to_raw(), post.platform).await {
+            Ok(account) => account,
+            Err(e) => {
+                // Nothing was sent; the lease expires and the post is tried again
+                tracing::error!("Failed to load the account for social post {}: {}", id, e);
+                return;
+            }
+        };
+
+        let result = match (self.publishers.get(&post.platform), account) {
+            (Some(publisher), Some(account)) => match self.open_account(account) {
+                Ok(account) => publisher.publish(&account, &post.text).await,
+                Err(e) => Err(PublishError::Failed(e.to_string())),
+            },
+            (None, _) => Err(PublishError::Failed(format!(
+                "Publishing to {:?} is no longer configured",
+                post.platform
+            ))),
+            (_, None) => Err(PublishError::Failed(format!("No {:?} account is connected", post.platform))),
+        };
+
+        let recorded = match result {
+            Ok(published) => self.record_published(&post, &published, attempt).await,
+            Err(PublishError::Failed(e)) => {
+                tracing::warn!("Publishing social post {} failed: {}", id, e);
+                let (status, next_attempt_at) = after_failure(attempt, &self.config, Utc::now());
+                self.posts.record_failure(&id, attempt, status, next_attempt_at, &e).await
+            }
+            Err(e @ PublishError::Unconfirmed(_)) => {
+                tracing::warn!("Social post {} may have been published: {}", id, e);
+                self.posts.record_unconfirmed(&id, attempt, &e.to_string()).await
+            }
+        };
+
+        if let Err(e) = recorded {
+            // The lease expires and the post is marked unconfirmed
+            tracing::error!("Failed to record publishing of social post {}: {}", id, e);
+        }
+    }
+
+    /// Store the live post and log a social touch for every tagged contact
+    async fn record_published(&self, post: &SocialPost, published: &PublishedPost, attempt: u32) -> AppResult<()> {
+        let Some(id) = &post.id else { return Ok(()) };
+        let now = Utc::now();
+        self.posts
+            .mark_published(id, &post.asset, &published.url, &published.external_id, attempt, now)
+            .await?;
+
+        let workspace_id = post.workspace.id.to_raw();
+        for contact in &post.tagged_contacts {
+            let mut entry = new_entry(
+                &workspace_id,
+                &contact.id.to_raw(),
+                TimelineEntryType::SocialTouch,
+                format!("Tagged in a {:?} post", post.platform),
+                serde_json::json!({
+                    "campaign_id": post.campaign.id.to_raw(),
+                    "social_post_id": id.id.to_raw(),
+                    "platform": post.platform,
+                    "url": published.url,
+                }),
+            );
+            entry.campaign = Some(post.campaign.clone());
+            entry.timestamp = now;
+
+            // A contact deleted since the post was scheduled has no timeline
+            match self.timeline.record(&workspace_id, entry).await {
+                Ok(_) | Err(AppError::NotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn active_social_asset(&self, workspace_id: &str, campaign_id: &str) -> AppResult<Option<CampaignAsset>> {
+        let assets = self.campaigns.find_assets(workspace_id, campaign_id, false).await?;
+        Ok(assets
+            .into_iter()
+            .rev()
+            .find(|a| matches!(a.asset_type, AssetType::SocialPost)))
+    }
+
+    async fn tagged_contacts(&self, workspace_id: &str, contact_ids: Vec<String>) -> AppResult<Vec<Thing>> {
+        let mut unique: Vec<String> = Vec::with_capacity(contact_ids.len());
+        for id in contact_ids {
+            if !unique.contains(&id) {
+                unique.push(id);
+            }
+        }
+        if unique.len() > MAX_TAGGED_CONTACTS {
+            return Err(AppError::Validation(format!(
+                "At most {} contacts can be tagged in a post",
+                MAX_TAGGED_CONTACTS
+            )));
+        }
+        if unique.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let found = self.contacts.find_many_with_ids(workspace_id, &unique).await?;
+        if found.len() < unique.len() {
+            let missing: Vec<&str> = unique
+                .iter()
+                .filter(|id| !found.iter().any(|c| &c.id == *id))
+                .map(String::as_str)
+                .collect();
+            return Err(AppError::NotFound(format!("Contacts not found: {}", missing.join(", "))));
+        }
+
+        Ok(unique.iter().map(|c| Thing::from(("contact", c.as_str()))).collect())
+    }
+
+    async fn find_campaign(&self, workspace_id: &str, id: &str) -> AppResult<Campaign> {
+        self.campaigns
+            .find_by_id(workspace_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Campaign not found".into()))
+    }
+
+    /// The account with its access token decrypted
+    fn open_account(&self, mut account: SocialAccount) -> AppResult<SocialAccount> {
+        account.access_token = self.cipher()?.open(&account.access_token)?;
+        Ok(account)
+    }
+
+    fn cipher(&self) -> AppResult<&CredentialCipher> {
+        self.cipher
+            .as_deref()
+            .ok_or_else(|| AppError::BadRequest("No key is configured to encrypt account credentials".into()))
+    }
+}
+
+/// The posts generated into a social_post asset
+fn generated_posts(asset: &CampaignAsset) -> AppResult<Vec<GeneratedPost>> {
+    serde_json::from_value(asset.generated_content.clone())
+        .map_err(|e| AppError::Internal(format!("Stored social post asset is malformed: {}", e)))
+}
+
+fn new_post(
+    asset: &CampaignAsset,
+    asset_id: Thing,
+    platform: SocialPlatform,
+    text: String,
+    tagged_contacts: Vec<Thing>,
+    publish_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> SocialPost {
+    SocialPost {
+        id: None,
+        workspace: asset.workspace.clone(),
+        campaign: asset.campaign.clone(),
+        asset: asset_id,
+        platform,
+        text,
+        tagged_contacts,
+        status: SocialPostStatus::Scheduled,
+        attempts: 0,
+        publish_at,
+        next_attempt_at: publish_at,
+        url: None,
+        external_id: None,
+        error: None,
+        published_at: None,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+/// Where a post goes after a failed attempt
+fn after_failure(attempt: u32, config: &SocialConfig, now: DateTime<Utc>) -> (SocialPostStatus, DateTime<Utc>) {
+    if attempt >= config.max_attempts {
+        (SocialPostStatus::Failed, now)
+    } else {
+        (
+            SocialPostStatus::Scheduled,
+            now + retry_delay(attempt, config.initial_backoff_secs),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failed_posts_back_off_then_fail() {
+        let config = SocialConfig::default();
+        let now = Utc::now();
+
+        let (status, next) = after_failure(1, &config, now);
+        assert_eq!(status, SocialPostStatus::Scheduled);
+        assert_eq!(next, now + chrono::Duration::seconds(300));
+
+        let (_, next) = after_failure(2, &config, now);
+        assert_eq!(next, now + chrono::Duration::seconds(600));
+
+        assert_eq!(after_failure(config.max_attempts, &config, now).0, SocialPostStatus::Failed);
+    }
+}
@@ -0,0 +1,253 @@
+//! Spam Guard - keeping junk out of landing page form submissions
+//!
+//! Submissions are screened before they touch the CRM:
+//! - a filled-in honeypot field drops the submission without telling the bot
+//! - per-client and per-page rate limits over a sliding window
+//! - a CAPTCHA token, verified with hCaptcha or Turnstile when configured
+//! - emails from throwaway or blocked domains are rejected
+//!
+//! Rate limits are counted in memory, so each server instance limits on
+//! its own.
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use axum::http::HeaderMap;
+use chrono::Utc;
+use serde::Deserialize;
+
+use crate::config::{CaptchaConfig, LandingPageConfig};
+use crate::domain::{validate_email_domain, FormSchema};
+use crate::error::{AppError, AppResult};
+use crate::secrets::SecretsManager;
+use crate::services::rate_limiter::RateLimiter;
+
+/// Submission keys a CAPTCHA token is read from: ours, then each widget's own
+const CAPTCHA_TOKEN_FIELDS: [&str; 3] = ["captcha_token", "h-captcha-response", "cf-turnstile-response"];
+
+/// What to do with a submission that passed screening
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Screening {
+    Accept,
+    /// Caught by the honeypot: answer as if it worked, store nothing
+    Discard,
+}
+
+pub struct SpamGuard {
+    config: LandingPageConfig,
+    captcha: Option<CaptchaVerifier>,
+    limiter: Mutex<RateLimiter>,
+}
+
+impl SpamGuard {
+    /// The CAPTCHA secret key comes from the secrets manager
+    pub fn new(config: &LandingPageConfig, secrets: &SecretsManager) -> AppResult<Self> {
+        Ok(Self {
+            config: config.clone(),
+            captcha: CaptchaVerifier::from_config(&config.captcha, secrets)?,
+            limiter: Mutex::new(RateLimiter::default()),
+        })
+    }
+
+    /// What a page needs to render the CAPTCHA widget, when one is required
+    pub fn captcha_widget(&self) -> Option<serde_json::Value> {
+        self.captcha.as_ref().map(|c| {
+            serde_json::json!({
+                "provider": c.provider.name(),
+                "site_key": c.site_key,
+            })
+        })
+    }
+
+    /// The address submissions from a request are rate limited by
+    pub fn client_ip(&self, headers: &HeaderMap, peer: SocketAddr) -> String {
+        let forwarded: Vec<&str> = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .collect();
+
+        forwarded_client(&forwarded, self.config.trusted_proxies)
+            .unwrap_or_else(|| peer.ip())
+            .to_string()
+    }
+
+    /// Screen a submission before it is validated against its form
+    pub async fn screen(
+        &self,
+        page_id: &str,
+        form: &FormSchema,
+        values: &serde_json::Map<String, serde_json::Value>,
+        client_ip: &str,
+    ) -> AppResult<Screening> {
+        if form.honeypot_filled(values) {
+            tracing::info!("Dropped honeypot submission to landing page {}", page_id);
+            return Ok(Screening::Discard);
+        }
+
+        let page_limit = form.max_submissions.unwrap_or(self.config.max_submissions_per_page);
+        let limits = [
+            (format!("page:{}", page_id), page_limit),
+            (format!("client:{}:{}", page_id, client_ip), self.config.max_submissions_per_client),
+        ];
+        let window = chrono::Duration::seconds(self.config.rate_limit_window_secs as i64);
+        let admitted = self
+            .limiter
+            .lock()
+            .map_err(|_| AppError::Internal("Rate limiter lock poisoned".into()))?
+            .admit(&limits, Utc::now(), window);
+        if !admitted {
+            return Err(AppError::TooManyRequests(
+                "Too many submissions, please try again later".into(),
+            ));
+        }
+
+        if let Some(captcha) = &self.captcha {
+            let token = CAPTCHA_TOKEN_FIELDS
+                .iter()
+                .find_map(|key| values.get(*key).and_then(|v| v.as_str()))
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .ok_or_else(|| AppError::BadRequest("CAPTCHA token is required".into()))?;
+
+            if !captcha.verify(token, client_ip).await? {
+                return Err(AppError::BadRequest("CAPTCHA verification failed".into()));
+            }
+        }
+
+        Ok(Screening::Accept)
+    }
+
+    /// Reject emails from throwaway or blocked domains
+    pub fn check_email(&self, email: &str) -> AppResult<()> {
+        validate_email_domain(
+            email,
+            self.config.block_disposable_emails,
+            &self.config.blocked_email_domains,
+        )?;
+        Ok(())
+    }
+}
+
+/// The client address in `X-Forwarded-For` hops, given how many trusted proxies appended to them
+///
+/// Every proxy appends the address it was connected from, so only the
+/// right-most `trusted_proxies` hops can be believed; anything to their
+/// left came from the client. None when the hops can't be trusted.
+fn forwarded_client(hops: &[&str], trusted_proxies: usize) -> Option<IpAddr> {
+    let hop = hops.len().checked_sub(trusted_proxies).filter(|_| trusted_proxies > 0)?;
+    hops[hop].trim().parse().ok()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CaptchaProvider {
+    HCaptcha,
+    Turnstile,
+}
+
+impl CaptchaProvider {
+    fn name(self) -> &'static str {
+        match self {
+            CaptchaProvider::HCaptcha => "hcaptcha",
+            CaptchaProvider::Turnstile => "turnstile",
+        }
+    }
+
+    fn verify_url(self) -> &'static str {
+        match self {
+            CaptchaProvider::HCaptcha => "https://api.hcaptcha.com/siteverify",
+            CaptchaProvider::Turnstile => "https://challenges.cloudflare.com/turnstile/v0/siteverify",
+        }
+    }
+}
+
+/// Checks CAPTCHA tokens with the provider; both speak the same siteverify protocol
+struct CaptchaVerifier {
+    http: reqwest::Client,
+    provider: CaptchaProvider,
+    site_key: String,
+    secret_key: String,
+}
+
+#[derive(Deserialize)]
+struct SiteverifyResponse {
+    success: bool,
+    #[serde(default, rename = "error-codes")]
+    error_codes: Vec<String>,
+}
+
+impl CaptchaVerifier {
+    fn from_config(config: &CaptchaConfig, secrets: &SecretsManager) -> AppResult<Option<Self>> {
+        let provider = match config.provider.as_str() {
+            "none" | "" => return Ok(None),
+            "hcaptcha" => CaptchaProvider::HCaptcha,
+            "turnstile" => CaptchaProvider::Turnstile,
+            other => {
+                return Err(AppError::Internal(format!(
+                    "Unknown CAPTCHA provider '{}', expected none, hcaptcha or turnstile",
+                    other
+                )))
+            }
+        };
+
+        let secret_key = secrets
+            .get_secret(&config.secret_key_secret)
+            .map_err(|e| AppError::Internal(format!("CAPTCHA verification needs a secret key: {}", e)))?;
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_secs))
+            .build()
+            .map_err(|e| AppError::Internal(format!("Failed to build HTTP client: {}", e)))?;
+
+        tracing::info!("Landing page CAPTCHA: {}", provider.name());
+        Ok(Some(Self {
+            http,
+            provider,
+            site_key: config.site_key.clone(),
+            secret_key,
+        }))
+    }
+
+    async fn verify(&self, token: &str, client_ip: &str) -> AppResult<bool> {
+        let form = [("secret", self.secret_key.as_str()), ("response", token), ("remoteip", client_ip)];
+
+        let response = self
+            .http
+            .post(self.provider.verify_url())
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| AppError::Upstream(format!("CAPTCHA provider unreachable: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::Upstream(format!("CAPTCHA provider returned {}", response.status())));
+        }
+
+        let result: SiteverifyResponse = response
+            .json()
+            .await
+            .map_err(|e| AppError::Upstream(format!("Unexpected CAPTCHA provider response: {}", e)))?;
+        if !result.success {
+            tracing::debug!("CAPTCHA rejected: {:?}", result.error_codes);
+        }
+        Ok(result.success)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forwarded_client_is_the_outermost_trusted_hop() {
+        let hops = ["6.6.6.6", "203.0.113.7", "10.0.0.2"];
+
+        assert_eq!(forwarded_client(&hops, 1), Some("10.0.0.2".parse().unwrap()));
+        assert_eq!(forwarded_client(&hops, 2), Some("203.0.113.7".parse().unwrap()));
+        // The client's own entries never count unless that many proxies are trusted
+        assert_eq!(forwarded_client(&hops, 0), None);
+        assert_eq!(forwarded_client(&hops, 4), None);
+        assert_eq!(forwarded_client(&[" not-an-ip "], 1), None);
+        assert_eq!(forwarded_client(&[], 1), None);
+    }
+}
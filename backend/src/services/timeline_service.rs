@@ -0,0 +1,448 @@
+//! Timeline Service - creating and listing timeline entries
+//!
+//! Recording an entry checks that its contact is in the workspace, fills in
+//! what the caller left out (the contact's company, empty metadata), stores
+//! it, publishes it to the activity feed and, when it counts as an
+//! engagement interaction, rescores the contact. A received email also stops
+//! the outreach the contact answered. Notes and received email left without
+//! a sentiment are queued, and a background job has the AI provider rate them.
+//!
+//! Entries that must be written in one transaction with other records are
+//! built with `new_entry` and handed to `recorded` once committed, which
+//! does the publishing and rescoring.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use surrealdb::sql::Thing;
+use tokio::task::JoinHandle;
+
+use crate::ai::ai_sentiment::{awaits_sentiment, infer_sentiment};
+use crate::ai::ContentGenerator;
+use crate::config::AiConfig;
+use crate::db::{new_thing, workspace_thing, Database};
+use crate::domain::{
+    average_minutes, group_threads, DomainResult, EmailDirection, EmailMetadata, ResponseTimes, ThreadEmail,
+};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Contact, DailyActivityCount, EmailThreadResponse, ThreadMessage, TimelineEntry, TimelineEntryType, TimelineQuery,
+};
+use crate::repositories::{EmailEntry, TimelineRepository};
+use crate::services::read_cache::{CacheScope, ReadCache};
+use crate::services::{interaction_type, EngagementService, FeedService, ReplyDetector};
+use crate::shutdown::Shutdown;
+
+/// A new entry on a contact's timeline, timestamped now
+pub fn new_entry(
+    workspace_id: &str,
+    contact_id: &str,
+    entry_type: TimelineEntryType,
+    content: impl Into<String>,
+    metadata: serde_json::Value,
+) -> TimelineEntry {
+    TimelineEntry {
+        id: Some(new_thing("timeline_entry")),
+        workspace: workspace_thing(workspace_id),
+        contact: Thing::from(("contact", contact_id)),
+        company: None,
+        campaign: None,
+        entry_type,
+        content: content.into(),
+        metadata,
+        attachments: Vec::new(),
+        sentiment: None,
+        timestamp: Utc::now(),
+    }
+}
+
+pub struct TimelineService {
+    db: Arc<Database>,
+    repo: TimelineRepository,
+    content_generator: Arc<ContentGenerator>,
+    feed: Arc<FeedService>,
+    engagement: Arc<EngagementService>,
+    replies: ReplyDetector,
+    cache: Arc<ReadCache>,
+}
+
+impl TimelineService {
+    pub fn new(
+        db: Arc<Database>,
+        content_generator: Arc<ContentGenerator>,
+        feed: Arc<FeedService>,
+        engagement: Arc<EngagementService>,
+        cache: Arc<ReadCache>,
+    ) -> Self {
+        Self {
+            repo: TimelineRepository::new(Arc::clone(&db)),
+            replies: ReplyDetector::new(Arc::clone(&db)),
+            db,
+            content_generator,
+            feed,
+            engagement,
+            cache,
+        }
+    }
+
+    /// Matching entries, newest first
+    pub async fn list(&self, workspace_id: &str, query: &TimelineQuery) -> AppResult<Vec<TimelineEntry>> {
+        self.repo.find(workspace_id, query).await
+    }
+
+    /// A contact's email collapsed into conversations, most recent activity first
+    pub async fn threads(&self, workspace_id: &str, contact_id: &str) -> AppResult<Vec<EmailThreadResponse>> {
+        let contact: Option<Contact> = self.db.select_scoped("contact", contact_id, workspace_id).await?;
+        if contact.is_none() {
+            return Err(AppError::NotFound("Contact not found".into()));
+        }
+
+        let emails = self.repo.find_emails(workspace_id, Some(contact_id), None).await?;
+        let headers: Vec<EmailMetadata> = emails.iter().map(|e| EmailMetadata::read_leniently(&e.metadata)).collect();
+
+        Ok(email_threads(&emails)
+            .into_iter()
+            .map(|thread| thread_response(&emails, &headers, &thread))
+            .collect())
+    }
+
+    /// Matching entries per UTC day, oldest day first
+    pub async fn daily_counts(&self, workspace_id: &str, query: &TimelineQuery) -> AppResult<Vec<DailyActivityCount>> {
+        self.repo.daily_counts(workspace_id, query).await
+    }
+
+    /// Store an entry on its contact's timeline
+    pub async fn record(&self, workspace_id: &str, mut entry: TimelineEntry) -> AppResult<TimelineEntry> {
+        self.prepare(workspace_id, &mut entry).await?;
+        validate_metadata(&entry)?;
+
+        let rate_sentiment = awaits_sentiment(&self.content_generator, &entry);
+        let entry = self.repo.create_queued(entry, rate_sentiment).await?;
+        self.recorded(workspace_id, std::slice::from_ref(&entry)).await;
+
+        Ok(entry)
+    }
+
+    /// Store an entry brought over from another CRM
+    ///
+    /// History doesn't go out on the live feed or to the sentiment job; the
+    /// contact is rescored as for any other entry.
+    pub async fn import(&self, workspace_id: &str, mut entry: TimelineEntry) -> AppResult<TimelineEntry> {
+        self.prepare(workspace_id, &mut entry).await?;
+        validate_metadata(&entry)?;
+
+        let entry = self.repo.create(entry).await?;
+        self.cache.invalidate(workspace_id, CacheScope::Timeline).await;
+        if interaction_type(&entry.entry_type).is_some() {
+            self.rescore(workspace_id, &entry.contact.id.to_raw()).await;
+        }
+
+        Ok(entry)
+    }
+
+    /// Store a message synced from a mailbox, unless one with its ID already is
+    ///
+    /// Mail from outside isn't held to the metadata checks: its headers are
+    /// read leniently. Hand what was stored to `recorded` once the sync is done.
+    pub async fn store_synced(
+        &self,
+        workspace_id: &str,
+        mut entry: TimelineEntry,
+        message_id: &str,
+    ) -> AppResult<Option<TimelineEntry>> {
+        self.prepare(workspace_id, &mut entry).await?;
+
+        let rate_sentiment = awaits_sentiment(&self.content_generator, &entry);
+        self.repo.create_synced(entry, message_id, rate_sentiment).await
+    }
+
+    /// Publish entries already stored, rescore their contacts and act on replies
+    ///
+    /// Each contact is rescored once, however many of the entries are theirs.
+    /// A failed rescore is logged: the recalculation job catches up later.
+    /// So is failed reply detection, which the contact's next reply retries.
+    pub async fn recorded(&self, workspace_id: &str, entries: &[TimelineEntry]) {
+        self.cache.invalidate(workspace_id, CacheScope::Timeline).await;
+
+        let mut rescore = BTreeSet::new();
+        for entry in entries {
+            self.feed.publish_timeline_entry(entry);
+            if interaction_type(&entry.entry_type).is_some() {
+                rescore.insert(entry.contact.id.to_raw());
+            }
+        }
+
+        for contact_id in rescore {
+            self.rescore(workspace_id, &contact_id).await;
+        }
+
+        if let Err(e) = self.replies.detect(workspace_id, entries).await {
+            tracing::warn!("Failed to act on replies after a timeline entry: {}", e);
+        }
+    }
+
+    async fn rescore(&self, workspace_id: &str, contact_id: &str) {
+        if let Err(e) = self.engagement.recalculate(workspace_id, contact_id).await {
+            tracing::warn!("Failed to rescore contact {} after a timeline entry: {}", contact_id, e);
+        }
+    }
+
+    /// Check and fill in an entry written in the same transaction as a
+    /// change to its contact, as `record` would before storing it
+    ///
+    /// `contact` is the contact as the caller loaded it, or as it is about
+    /// to be created.
+    pub fn prepare_with(&self, contact: &Contact, entry: &mut TimelineEntry) -> AppResult<()> {
+        if contact.workspace != entry.workspace || contact.id.as_ref() != Some(&entry.contact) {
+            return Err(AppError::NotFound("Contact not found".into()));
+        }
+
+        enrich(entry, contact.company.clone());
+        validate_metadata(entry)?;
+        Ok(())
+    }
+
+    /// Check the entry's contact is in the workspace and fill in the rest
+    async fn prepare(&self, workspace_id: &str, entry: &mut TimelineEntry) -> AppResult<()> {
+        let contact: Option<Contact> = self
+            .db
+            .select_scoped("contact", &entry.contact.id.to_raw(), workspace_id)
+            .await?;
+        let contact = contact.ok_or_else(|| AppError::NotFound("Contact not found".into()))?;
+
+        enrich(entry, contact.company);
+        Ok(())
+    }
+
+    /// Rate a batch of the entries queued for sentiment
+    ///
+    /// Every entry is taken off the queue, rated or not: one the provider
+    /// fails on stays unrated. Returns the number of entries rated.
+    pub async fn rate_pending(&self, batch_size: u32) -> AppResult<usize> {
+        let mut rated = 0;
+        for mut entry in self.repo.find_pending_sentiment(batch_size).await? {
+            let Some(id) = entry.id.clone() else { continue };
+            infer_sentiment(&self.content_generator, &mut entry).await;
+            self.repo.finish_sentiment(&id, entry.sentiment).await?;
+
+            if entry.sentiment.is_some() {
+                rated += 1;
+                self.cache.invalidate(&entry.workspace.id.to_raw(), CacheScope::Timeline).await;
+            }
+        }
+        Ok(rated)
+    }
+
+    /// Start the background task that rates queued entries
+    pub fn spawn_sentiment(self: Arc<Self>, config: &AiConfig, shutdown: Shutdown) -> JoinHandle<()> {
+        let period = Duration::from_secs(config.sentiment_interval_secs.max(5));
+        let batch_size = config.sentiment_batch_size.max(1);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+
+            while shutdown.tick(&mut interval).await {
+                match self.rate_pending(batch_size).await {
+                    Ok(rated) if rated > 0 => tracing::info!("Rated the sentiment of {} timeline entries", rated),
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Sentiment rating failed: {}", e),
+                }
+            }
+        })
+    }
+}
+
+/// Which way an email entry went; anything but a received email counts as sent
+pub fn email_direction(entry_type: &TimelineEntryType) -> EmailDirection {
+    match entry_type {
+        TimelineEntryType::EmailReceived => EmailDirection::Received,
+        _ => EmailDirection::Sent,
+    }
+}
+
+/// Email entries threaded into conversations, as indexes into `emails`
+///
+/// Threads never span contacts. Each lists its emails oldest first, and the
+/// threads come most recent activity first.
+pub fn email_threads(emails: &[EmailEntry]) -> Vec<Vec<usize>> {
+    let headers: Vec<EmailMetadata> = emails.iter().map(|e| EmailMetadata::read_leniently(&e.metadata)).collect();
+    let mut by_contact: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, email) in emails.iter().enumerate() {
+        by_contact.entry(email.contact.id.to_raw()).or_default().push(i);
+    }
+
+    let mut threads: Vec<Vec<usize>> = Vec::new();
+    for indexes in by_contact.into_values() {
+        let thread_emails: Vec<ThreadEmail> = indexes
+            .iter()
+            .map(|&i| ThreadEmail {
+                ids: headers[i].message_id.iter().chain(&headers[i].header_message_id).map(String::as_str).collect(),
+                parents: headers[i].in_reply_to.iter().chain(&headers[i].references).map(String::as_str).collect(),
+                subject: headers[i].subject.as_deref().unwrap_or_default(),
+                timestamp: emails[i].timestamp,
+            })
+            .collect();
+
+        threads.extend(
+            group_threads(&thread_emails)
+                .into_iter()
+                .map(|thread| thread.into_iter().map(|j| indexes[j]).collect()),
+        );
+    }
+
+    threads.sort_by_key(|thread| Reverse(thread.last().map(|&i| (emails[i].timestamp, i))));
+    threads
+}
+
+/// The response times of one thread from `email_threads`
+pub fn thread_response_times(emails: &[EmailEntry], thread: &[usize]) -> ResponseTimes {
+    let sequence: Vec<_> = thread
+        .iter()
+        .map(|&i| (email_direction(&emails[i].entry_type), emails[i].timestamp))
+        .collect();
+    ResponseTimes::of_thread(&sequence)
+}
+
+fn thread_response(emails: &[EmailEntry], headers: &[EmailMetadata], thread: &[usize]) -> EmailThreadResponse {
+    let messages: Vec<ThreadMessage> = thread
+        .iter()
+        .map(|&i| ThreadMessage {
+            entry_id: emails[i].id.id.to_raw(),
+            direction: email_direction(&emails[i].entry_type),
+            subject: headers[i].subject.clone(),
+            preview: emails[i]
+                .metadata
+                .get("snippet")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or(&emails[i].content)
+                .to_string(),
+            timestamp: emails[i].timestamp,
+        })
+        .collect();
+
+    let times = thread_response_times(emails, thread);
+    let sent_count = messages.iter().filter(|m| m.direction == EmailDirection::Sent).count();
+    let (first, last) = (&messages[0], &messages[messages.len() - 1]);
+
+    EmailThreadResponse {
+        thread_id: first.entry_id.clone(),
+        subject: first.subject.clone(),
+        message_count: messages.len(),
+        sent_count,
+        received_count: messages.len() - sent_count,
+        started_at: first.timestamp,
+        last_message_at: last.timestamp,
+        last_direction: last.direction,
+        our_avg_response_minutes: average_minutes(&times.ours).map(round_minutes),
+        their_avg_response_minutes: average_minutes(&times.theirs).map(round_minutes),
+        messages,
+    }
+}
+
+/// Response times are shown to the tenth of a minute
+pub fn round_minutes(minutes: f64) -> f64 {
+    (minutes * 10.0).round() / 10.0
+}
+
+
+/// Fill in what the caller left out of an entry
+fn enrich(entry: &mut TimelineEntry, contact_company: Option<Thing>) {
+    if entry.company.is_none() {
+        entry.company = contact_company;
+    }
+    if entry.metadata.is_null() {
+        entry.metadata = serde_json::json!({});
+    }
+}
+
+/// Check the metadata keys the entry's type is known by
+fn validate_metadata(entry: &TimelineEntry) -> DomainResult<()> {
+    entry.entry_type.validate_metadata(&entry.metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_entry_links_workspace_and_contact() {
+        let entry = new_entry("ws1", "c1", TimelineEntryType::Note, "Met at the conference", serde_json::json!({}));
+
+        assert_eq!(entry.workspace, Thing::from(("workspace", "ws1")));
+        assert_eq!(entry.contact, Thing::from(("contact", "c1")));
+        assert_eq!(entry.id.unwrap().tb, "timeline_entry");
+        assert!(entry.company.is_none());
+        assert!(entry.sentiment.is_none());
+    }
+
+    #[test]
+    fn test_enrich_fills_company_and_metadata_only_when_unset() {
+        let acme = Thing::from(("company", "acme"));
+        let globex = Thing::from(("company", "globex"));
+
+        let mut entry = new_entry("ws1", "c1", TimelineEntryType::Call, "Intro call", serde_json::Value::Null);
+        enrich(&mut entry, Some(acme.clone()));
+        assert_eq!(entry.company, Some(acme.clone()));
+        assert_eq!(entry.metadata, serde_json::json!({}));
+
+        let metadata = serde_json::json!({ "minutes": 30 });
+        let mut entry = new_entry("ws1", "c1", TimelineEntryType::Call, "Intro call", metadata);
+        entry.company = Some(globex.clone());
+        enrich(&mut entry, Some(acme));
+        assert_eq!(entry.company, Some(globex));
+        assert_eq!(entry.metadata["minutes"], 30);
+    }
+
+    fn email(contact: &str, entry_type: TimelineEntryType, minutes: i64, metadata: serde_json::Value) -> EmailEntry {
+        EmailEntry {
+            id: new_thing("timeline_entry"),
+            contact: Thing::from(("contact", contact)),
+            entry_type,
+            content: "Email".into(),
+            metadata,
+            timestamp: Utc::now() - chrono::Duration::days(1) + chrono::Duration::minutes(minutes),
+        }
+    }
+
+    #[test]
+    fn test_email_threads_stay_within_a_contact() {
+        use serde_json::json;
+        let emails = vec![
+            email("c1", TimelineEntryType::EmailSent, 0, json!({ "subject": "Pricing", "message_id": "<1@us>" })),
+            email("c2", TimelineEntryType::EmailSent, 5, json!({ "subject": "Pricing" })),
+            email(
+                "c1",
+                TimelineEntryType::EmailReceived,
+                45,
+                json!({ "in_reply_to": "<1@us>", "snippet": "Sounds good" }),
+            ),
+            email("c2", TimelineEntryType::EmailReceived, 10, json!({ "subject": "Re: Pricing" })),
+        ];
+
+        let threads = email_threads(&emails);
+        assert_eq!(threads, vec![vec![0, 2], vec![1, 3]]);
+
+        let headers: Vec<EmailMetadata> = emails.iter().map(|e| EmailMetadata::read_leniently(&e.metadata)).collect();
+        let thread = thread_response(&emails, &headers, &threads[0]);
+        assert_eq!(thread.subject.as_deref(), Some("Pricing"));
+        assert_eq!((thread.sent_count, thread.received_count), (1, 1));
+        assert_eq!(thread.last_direction, EmailDirection::Received);
+        assert_eq!(thread.their_avg_response_minutes, Some(45.0));
+        assert_eq!(thread.our_avg_response_minutes, None);
+        assert_eq!(thread.messages[1].preview, "Sounds good");
+    }
+
+    #[test]
+    fn test_metadata_is_checked_by_entry_type() {
+        let bad = serde_json::json!({ "duration_minutes": "long", "attendees": "everyone" });
+
+        assert!(validate_metadata(&new_entry("ws1", "c1", TimelineEntryType::Call, "Call", bad.clone())).is_err());
+        let meeting = new_entry("ws1", "c1", TimelineEntryType::MeetingAttended, "Demo", bad.clone());
+        assert!(validate_metadata(&meeting).is_err());
+        // Notes carry whatever metadata they are given
+        assert!(validate_metadata(&new_entry("ws1", "c1", TimelineEntryType::Note, "Note", bad)).is_ok());
+    }
+}
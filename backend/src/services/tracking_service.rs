@@ -0,0 +1,502 @@
+//! Tracking Service - email open and click tracking, and short links
+//!
+//! Outgoing campaign emails are instrumented with links back to us:
+//! - /t/open/:token  - a 1x1 pixel, loaded when the email is opened
+//! - /t/click/:token - a redirect to the original link
+//! - /unsubscribe/:token - opts the recipient out of campaign email
+//!
+//! Tokens are JWTs signed with the tracking secret. They carry the
+//! workspace, contact and campaign (and for clicks, the destination URL),
+//! so the public endpoints need no lookup to know who did what - and the
+//! redirect target can't be tampered with to turn us into an open redirect.
+//! Tracking links never expire; old emails keep working.
+//!
+//! Where a token makes a link too long - social posts, direct messages -
+//! a short link does the same job: /r/:code redirects to a URL stored for
+//! one contact and campaign, and records an email click or a social
+//! interaction depending on where the link was shared.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use chrono::Utc;
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use uuid::Uuid;
+
+use crate::config::TrackingConfig;
+use crate::db::{workspace_thing, Database};
+use crate::domain::{
+    append_tracking_pixel, append_unsubscribe_link, is_short_code, is_trackable_link, rewrite_links, short_code,
+    LinkChannel, SubscriptionStatus,
+};
+use crate::error::{AppError, AppResult};
+use crate::models::{CreateShortLinksRequest, ShortLink, ShortLinkResponse, TimelineEntryType};
+use crate::repositories::{CampaignRecipientRepository, CampaignRepository, ContactRepository, ShortLinkRepository};
+use crate::services::{new_entry, TimelineService};
+
+/// Longest URL that can be shortened
+const MAX_SHORT_LINK_URL_LEN: usize = 2048;
+
+/// Most contacts one URL may be shortened for at once
+const MAX_SHORT_LINK_CONTACTS: usize = 500;
+
+/// What a tracking token records when it is hit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackingKind {
+    Open,
+    Click,
+    Unsubscribe,
+}
+
+/// Claims carried by a tracking token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackingClaims {
+    pub kind: TrackingKind,
+    pub workspace: String,
+    pub contact: String,
+    pub campaign: String,
+    /// Destination URL (click tokens only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// The recipient and campaign an email is being sent for
+#[derive(Debug, Clone)]
+pub struct TrackingTarget {
+    pub workspace_id: String,
+    pub contact_id: String,
+    pub campaign_id: String,
+}
+
+/// Signs and verifies tracking tokens
+pub struct TrackingTokens {
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    validation: Validation,
+}
+
+impl TrackingTokens {
+    pub fn new(secret: &str) -> Self {
+        let mut validation = Validation::new(Algorithm::HS256);
+        // Tracking links live as long as the email does
+        validation.required_spec_claims = HashSet::new();
+        validation.validate_exp = false;
+
+        Self {
+            encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+            decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+            validation,
+        }
+    }
+
+    pub fn sign(&self, claims: &TrackingClaims) -> AppResult<String> {
+        encode(&Header::new(Algorithm::HS256), claims, &self.encoding_key)
+            .map_err(|e| AppError::Internal(format!("Failed to sign tracking token: {}", e)))
+    }
+
+    /// Verify a token and check it is of the expected kind
+    pub fn verify(&self, token: &str, expected: TrackingKind) -> AppResult<TrackingClaims> {
+        let claims = decode::<TrackingClaims>(token, &self.decoding_key, &self.validation)
+            .map_err(|_| AppError::NotFound("Unknown tracking link".into()))?
+            .claims;
+
+        if claims.kind != expected {
+            return Err(AppError::NotFound("Unknown tracking link".into()));
+        }
+
+        Ok(claims)
+    }
+}
+
+pub struct TrackingService {
+    db: Arc<Database>,
+    recipients: CampaignRecipientRepository,
+    campaigns: CampaignRepository,
+    contacts: ContactRepository,
+    links: ShortLinkRepository,
+    timeline: Arc<TimelineService>,
+    tokens: TrackingTokens,
+    base_url: String,
+}
+
+impl TrackingService {
+    pub fn new(db: Arc<Database>, config: &TrackingConfig, timeline: Arc<TimelineService>) -> Self {
+        Self {
+            recipients: CampaignRecipientRepository::new(Arc::clone(&db)),
+            campaigns: CampaignRepository::new(Arc::clone(&db)),
+            contacts: ContactRepository::new(Arc::clone(&db)),
+            links: ShortLinkRepository::new(Arc::clone(&db)),
+            db,
+            timeline,
+            tokens: TrackingTokens::new(&config.secret),
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Rewrite links, add the unsubscribe footer and the open pixel to an
+    /// outgoing email body
+    pub fn instrument_email(&self, html: &str, target: &TrackingTarget) -> AppResult<String> {
+        // Sign up front so a signing failure surfaces as an error rather
+        // than a half-instrumented email
+        let open_url = self.open_url(target)?;
+        let unsubscribe_url = self.unsubscribe_url(target)?;
+
+        let mut failure = None;
+        let rewritten = rewrite_links(html, |url| match self.click_url(target, url) {
+            Ok(tracked) => tracked,
+            Err(e) => {
+                failure.get_or_insert(e);
+                url.to_string()
+            }
+        });
+        if let Some(e) = failure {
+            return Err(e);
+        }
+
+        let with_footer = append_unsubscribe_link(&rewritten, &unsubscribe_url);
+        Ok(append_tracking_pixel(&with_footer, &open_url))
+    }
+
+    pub fn open_url(&self, target: &TrackingTarget) -> AppResult<String> {
+        let token = self.tokens.sign(&claims(TrackingKind::Open, target, None))?;
+        Ok(format!("{}/t/open/{}", self.base_url, token))
+    }
+
+    pub fn click_url(&self, target: &TrackingTarget, url: &str) -> AppResult<String> {
+        let token = self
+            .tokens
+            .sign(&claims(TrackingKind::Click, target, Some(url.to_string())))?;
+        Ok(format!("{}/t/click/{}", self.base_url, token))
+    }
+
+    pub fn unsubscribe_url(&self, target: &TrackingTarget) -> AppResult<String> {
+        let token = self.tokens.sign(&claims(TrackingKind::Unsubscribe, target, None))?;
+        Ok(format!("{}/unsubscribe/{}", self.base_url, token))
+    }
+
+    /// Check an unsubscribe token without acting on it
+    pub fn check_unsubscribe_token(&self, token: &str) -> AppResult<()> {
+        self.tokens.verify(token, TrackingKind::Unsubscribe)?;
+        Ok(())
+    }
+
+    /// Opt the recipient out of campaign email
+    ///
+    /// Idempotent: following the link again is not an error. Pending
+    /// recipients of every campaign in the workspace are marked unsubscribed
+    /// so nothing already queued goes out.
+    pub async fn unsubscribe(&self, token: &str) -> AppResult<()> {
+        let claims = self.tokens.verify(token, TrackingKind::Unsubscribe)?;
+        let workspace = workspace_thing(&claims.workspace);
+        let contact = Thing::from(("contact", claims.contact.as_str()));
+
+        let updated: Vec<serde_json::Value> = self
+            .db
+            .client
+            .query(
+                "UPDATE $contact SET subscription_status = $unsubscribed, updated_at = $now \
+                 WHERE workspace = $workspace AND subscription_status != $unsubscribed",
+            )
+            .bind(("contact", contact.clone()))
+            .bind(("workspace", workspace.clone()))
+            .bind(("unsubscribed", SubscriptionStatus::Unsubscribed))
+            .bind(("now", Utc::now()))
+            .await?
+            .take(0)?;
+
+        self.recipients
+            .mark_unsubscribed(&claims.workspace, None, vec![contact.clone()])
+            .await?;
+
+        // Only the first unsubscribe goes on the timeline
+        if updated.is_empty() {
+            return Ok(());
+        }
+
+        let mut entry = new_entry(
+            &claims.workspace,
+            &claims.contact,
+            TimelineEntryType::Unsubscribed,
+            "Unsubscribed from campaign email",
+            serde_json::json!({ "campaign_id": claims.campaign }),
+        );
+        entry.campaign = Some(Thing::from(("campaign", claims.campaign.as_str())));
+        self.timeline.record(&claims.workspace, entry).await?;
+
+        Ok(())
+    }
+
+    /// Record an email open
+    pub async fn record_open(&self, token: &str) -> AppResult<()> {
+        let claims = self.tokens.verify(token, TrackingKind::Open)?;
+
+        self.record(&claims, TimelineEntryType::EmailOpen, None).await
+    }
+
+    /// Record a link click, returning the URL to redirect to
+    ///
+    /// Only a bad token is an error. If recording fails the click is lost
+    /// but the recipient still gets where they were going.
+    pub async fn record_click(&self, token: &str) -> AppResult<String> {
+        let claims = self.tokens.verify(token, TrackingKind::Click)?;
+        let url = claims
+            .url
+            .clone()
+            .ok_or_else(|| AppError::NotFound("Unknown tracking link".into()))?;
+
+        if let Err(e) = self.record(&claims, TimelineEntryType::EmailClick, None).await {
+            tracing::warn!("Failed to record email click: {}", e);
+        }
+
+        Ok(url)
+    }
+
+    /// Shorten a URL for each of the contacts
+    ///
+    /// A contact that already has a link to the URL for the campaign and
+    /// channel gets that one back, so sharing it again doesn't split its
+    /// clicks.
+    pub async fn shorten(
+        &self,
+        workspace_id: &str,
+        campaign_id: &str,
+        req: CreateShortLinksRequest,
+    ) -> AppResult<Vec<ShortLinkResponse>> {
+        let url = req.url.trim().to_string();
+        if !is_trackable_link(&url) {
+            return Err(AppError::Validation("Only absolute http(s) URLs can be shortened".into()));
+        }
+        if url.len() > MAX_SHORT_LINK_URL_LEN {
+            return Err(AppError::Validation(format!(
+                "URLs longer than {} characters can't be shortened",
+                MAX_SHORT_LINK_URL_LEN
+            )));
+        }
+
+        let mut contact_ids: Vec<String> = Vec::with_capacity(req.contact_ids.len());
+        for id in req.contact_ids {
+            if !contact_ids.contains(&id) {
+                contact_ids.push(id);
+            }
+        }
+        if contact_ids.is_empty() {
+            return Err(AppError::Validation("At least one contact is required".into()));
+        }
+        if contact_ids.len() > MAX_SHORT_LINK_CONTACTS {
+            return Err(AppError::Validation(format!(
+                "A URL can be shortened for at most {} contacts at once",
+                MAX_SHORT_LINK_CONTACTS
+            )));
+        }
+
+        self.find_campaign(workspace_id, campaign_id).await?;
+        let found = self.contacts.find_many_with_ids(workspace_id, &contact_ids).await?;
+        if found.len() < contact_ids.len() {
+            let missing: Vec<&str> = contact_ids
+                .iter()
+                .filter(|id| !found.iter().any(|c| &c.id == *id))
+                .map(String::as_str)
+                .collect();
+            return Err(AppError::NotFound(format!("Contacts not found: {}", missing.join(", "))));
+        }
+
+        let contacts: Vec<Thing> = contact_ids.iter().map(|id| Thing::from(("contact", id.as_str()))).collect();
+        let mut links = self
+            .links
+            .find_existing(workspace_id, campaign_id, req.channel, &url, &contacts)
+            .await?;
+
+        let now = Utc::now();
+        let new_links: Vec<ShortLink> = contacts
+            .iter()
+            .filter(|contact| !links.iter().any(|l| &l.contact == *contact))
+            .map(|contact| ShortLink {
+                id: None,
+                workspace: workspace_thing(workspace_id),
+                campaign: Thing::from(("campaign", campaign_id)),
+                contact: contact.clone(),
+                channel: req.channel,
+                code: short_code(Uuid::new_v4().as_u128()),
+                url: url.clone(),
+                clicks: 0,
+                last_clicked_at: None,
+                created_at: now,
+            })
+            .collect();
+        links.extend(self.links.create_many(new_links).await?);
+
+        // In the order the contacts were asked for
+        links.sort_by_key(|l| contacts.iter().position(|c| c == &l.contact));
+        Ok(links.into_iter().map(|l| self.short_link_response(l)).collect())
+    }
+
+    /// A campaign's short links with their click counts
+    pub async fn short_links(&self, workspace_id: &str, campaign_id: &str) -> AppResult<Vec<ShortLinkResponse>> {
+        self.find_campaign(workspace_id, campaign_id).await?;
+        let links = self.links.find_by_campaign(workspace_id, campaign_id).await?;
+        Ok(links.into_iter().map(|l| self.short_link_response(l)).collect())
+    }
+
+    /// Record a click through a short link, returning the URL to redirect to
+    ///
+    /// Like `record_click`, only an unknown code is an error.
+    pub async fn follow_short_link(&self, code: &str) -> AppResult<String> {
+        if !is_short_code(code) {
+            return Err(AppError::NotFound("Unknown link".into()));
+        }
+        let link = self
+            .links
+            .record_click(code, Utc::now())
+            .await?
+            .ok_or_else(|| AppError::NotFound("Unknown link".into()))?;
+
+        let claims = TrackingClaims {
+            kind: TrackingKind::Click,
+            workspace: link.workspace.id.to_raw(),
+            contact: link.contact.id.to_raw(),
+            campaign: link.campaign.id.to_raw(),
+            url: Some(link.url.clone()),
+        };
+        if let Err(e) = self
+            .record(&claims, click_entry_type(link.channel), Some(code))
+            .await
+        {
+            tracing::warn!("Failed to record short link click: {}", e);
+        }
+
+        Ok(link.url)
+    }
+
+    fn short_link_response(&self, link: ShortLink) -> ShortLinkResponse {
+        ShortLinkResponse {
+            id: link.id.map(|t| t.id.to_raw()).unwrap_or_default(),
+            campaign_id: link.campaign.id.to_raw(),
+            contact_id: link.contact.id.to_raw(),
+            channel: link.channel,
+            short_url: format!("{}/r/{}", self.base_url, link.code),
+            code: link.code,
+            url: link.url,
+            clicks: link.clicks,
+            last_clicked_at: link.last_clicked_at,
+            created_at: link.created_at,
+        }
+    }
+
+    async fn find_campaign(&self, workspace_id: &str, campaign_id: &str) -> AppResult<()> {
+        self.campaigns
+            .find_by_id(workspace_id, campaign_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Campaign not found".into()))?;
+        Ok(())
+    }
+
+    /// Put the open or click on the contact's timeline, which rescores them
+    ///
+    /// Clicks through a short link carry its code in the entry's metadata.
+    async fn record(
+        &self,
+        claims: &TrackingClaims,
+        entry_type: TimelineEntryType,
+        short_code: Option<&str>,
+    ) -> AppResult<()> {
+        let content = match (&claims.url, &entry_type) {
+            (Some(url), TimelineEntryType::SocialTouch) => format!("Clicked {} shared on social media", url),
+            (Some(url), _) => format!("Clicked {} in campaign email", url),
+            (None, _) => "Opened campaign email".to_string(),
+        };
+        let mut metadata = serde_json::json!({
+            "campaign_id": claims.campaign,
+            "url": claims.url,
+        });
+        if let Some(code) = short_code {
+            metadata["short_code"] = serde_json::json!(code);
+        }
+
+        let mut entry = new_entry(&claims.workspace, &claims.contact, entry_type, content, metadata);
+        entry.campaign = Some(Thing::from(("campaign", claims.campaign.as_str())));
+
+        // The contact may have been deleted since the email went out
+        match self.timeline.record(&claims.workspace, entry).await {
+            Ok(_) | Err(AppError::NotFound(_)) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// What a click through a short link shared on the channel goes on the timeline as
+fn click_entry_type(channel: LinkChannel) -> TimelineEntryType {
+    match channel {
+        LinkChannel::Email => TimelineEntryType::EmailClick,
+        LinkChannel::Social => TimelineEntryType::SocialTouch,
+    }
+}
+
+fn claims(kind: TrackingKind, target: &TrackingTarget, url: Option<String>) -> TrackingClaims {
+    TrackingClaims {
+        kind,
+        workspace: target.workspace_id.clone(),
+        contact: target.contact_id.clone(),
+        campaign: target.campaign_id.clone(),
+        url,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target() -> TrackingTarget {
+        TrackingTarget {
+            workspace_id: "ws1".into(),
+            contact_id: "c1".into(),
+            campaign_id: "camp1".into(),
+        }
+    }
+
+    #[test]
+    fn test_token_round_trip() {
+        let tokens = TrackingTokens::new("secret");
+        let token = tokens
+            .sign(&claims(TrackingKind::Click, &target(), Some("https://example.com".into())))
+            .unwrap();
+
+        let verified = tokens.verify(&token, TrackingKind::Click).unwrap();
+        assert_eq!(verified.contact, "c1");
+        assert_eq!(verified.campaign, "camp1");
+        assert_eq!(verified.url.as_deref(), Some("https://example.com"));
+    }
+
+    #[test]
+    fn test_token_kind_must_match() {
+        let tokens = TrackingTokens::new("secret");
+        let token = tokens.sign(&claims(TrackingKind::Open, &target(), None)).unwrap();
+
+        assert!(tokens.verify(&token, TrackingKind::Click).is_err());
+    }
+
+    #[test]
+    fn test_open_token_cannot_unsubscribe() {
+        let tokens = TrackingTokens::new("secret");
+        let token = tokens.sign(&claims(TrackingKind::Open, &target(), None)).unwrap();
+
+        assert!(tokens.verify(&token, TrackingKind::Unsubscribe).is_err());
+    }
+
+    #[test]
+    fn test_token_signed_with_other_secret_is_rejected() {
+        let token = TrackingTokens::new("secret")
+            .sign(&claims(TrackingKind::Open, &target(), None))
+            .unwrap();
+
+        assert!(TrackingTokens::new("other").verify(&token, TrackingKind::Open).is_err());
+    }
+
+    #[test]
+    fn test_short_link_clicks_go_on_the_timeline_by_channel() {
+        assert!(matches!(click_entry_type(LinkChannel::Email), TimelineEntryType::EmailClick));
+        assert!(matches!(click_entry_type(LinkChannel::Social), TimelineEntryType::SocialTouch));
+    }
+}
@@ -0,0 +1,193 @@
+//! Trash Service - what has been deleted, and for how long it stays
+//!
+//! Deleting a contact or company only stamps `deleted_at`; the record is
+//! hidden everywhere but can be restored. A background task purges
+//! records whose retention window has passed, and their attachment and
+//! avatar files.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::task::JoinHandle;
+
+use crate::config::TrashConfig;
+use crate::db::Database;
+use crate::domain::AuditEntity;
+use crate::error::{AppError, AppResult};
+use crate::models::{TrashItem, TrashQuery};
+use crate::repositories::{PurgeCounts, TrashRepository, TrashedRecord};
+use crate::services::object_storage::ObjectStorage;
+use crate::services::AvatarService;
+use crate::shutdown::Shutdown;
+
+const DEFAULT_LIST_LIMIT: u32 = 50;
+const MAX_LIST_LIMIT: u32 = 500;
+
+pub struct TrashService {
+    repo: TrashRepository,
+    storage: Arc<dyn ObjectStorage>,
+    avatars: Arc<AvatarService>,
+    retention: chrono::Duration,
+}
+
+impl TrashService {
+    pub fn new(
+        db: Arc<Database>,
+        storage: Arc<dyn ObjectStorage>,
+        avatars: Arc<AvatarService>,
+        config: &TrashConfig,
+    ) -> Self {
+        Self {
+            repo: TrashRepository::new(db),
+            storage,
+            avatars,
+            retention: chrono::Duration::days(i64::from(config.retention_days)),
+        }
+    }
+
+    /// Trashed contacts and companies, most recently deleted first
+    pub async fn list(&self, workspace_id: &str, query: &TrashQuery) -> AppResult<Vec<TrashItem>> {
+        let limit = query.limit.unwrap_or(DEFAULT_LIST_LIMIT).min(MAX_LIST_LIMIT);
+
+        let (contacts, companies) = match query.entity_type {
+            None => (
+                self.repo.find_contacts(workspace_id, limit).await?,
+                self.repo.find_companies(workspace_id, limit).await?,
+            ),
+            Some(AuditEntity::Contact) => (self.repo.find_contacts(workspace_id, limit).await?, Vec::new()),
+            Some(AuditEntity::Company) => (Vec::new(), self.repo.find_companies(workspace_id, limit).await?),
+            Some(other) => {
+                return Err(AppError::BadRequest(format!(
+                    "Only contacts and companies go to the trash, not {}",
+                    other.as_str()
+                )))
+            }
+        };
+
+        Ok(merge_trash(contacts, companies, self.retention, limit as usize))
+    }
+
+    /// Permanently delete everything past the retention window
+    pub async fn purge_expired(&self) -> AppResult<PurgeCounts> {
+        let counts = self.repo.purge(purge_cutoff(Utc::now(), self.retention)).await?;
+
+        // The records are gone; a file left behind is unreachable, so only warn
+        for key in &counts.storage_keys {
+            if let Err(e) = self.storage.delete(key).await {
+                tracing::warn!("Failed to remove attachment file {}: {}", key, e);
+            }
+        }
+        // Avatars are served publicly until their files are gone
+        for avatar in &counts.avatars {
+            self.avatars.delete_files(&avatar.workspace_id, &avatar.avatar_url).await;
+        }
+
+        Ok(counts)
+    }
+
+    /// Run `purge_expired` on a background task at the configured interval
+    pub fn spawn_purge(self: Arc<Self>, config: &TrashConfig, shutdown: Shutdown) -> JoinHandle<()> {
+        let period = Duration::from_secs(config.purge_interval_secs.max(60));
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+
+            while shutdown.tick(&mut interval).await {
+                match self.purge_expired().await {
+                    Ok(counts) => tracing::info!(
+                        "Trash purge removed {} contacts, {} companies, {} attachment files and {} avatars",
+                        counts.contacts,
+                        counts.companies,
+                        counts.storage_keys.len(),
+                        counts.avatars.len()
+                    ),
+                    Err(e) => tracing::error!("Trash purge failed: {}", e),
+                }
+            }
+        })
+    }
+}
+
+/// Interleave both lists by deletion time, newest first
+fn merge_trash(
+    contacts: Vec<TrashedRecord>,
+    companies: Vec<TrashedRecord>,
+    retention: chrono::Duration,
+    limit: usize,
+) -> Vec<TrashItem> {
+    let item = |entity_type, record: TrashedRecord| TrashItem {
+        entity_type,
+        id: record.id.id.to_string(),
+        name: record.name,
+        purge_after: purge_after(record.deleted_at, retention),
+        deleted_at: record.deleted_at,
+    };
+
+    let mut items: Vec<TrashItem> = contacts
+        .into_iter()
+        .map(|r| item(AuditEntity::Contact, r))
+        .chain(companies.into_iter().map(|r| item(AuditEntity::Company, r)))
+        .collect();
+    items.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+    items.truncate(limit);
+    items
+}
+
+fn purge_after(deleted_at: DateTime<Utc>, retention: chrono::Duration) -> DateTime<Utc> {
+    deleted_at + retention
+}
+
+/// Records deleted before this are past their retention window at `now`
+fn purge_cutoff(now: DateTime<Utc>, retention: chrono::Duration) -> DateTime<Utc> {
+    now - retention
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use surrealdb::sql::Thing;
+
+    fn trashed(table: &str, id: &str, hours_ago: i64) -> TrashedRecord {
+        TrashedRecord {
+            id: Thing::from((table, id)),
+            name: id.to_string(),
+            deleted_at: Utc::now() - chrono::Duration::hours(hours_ago),
+        }
+    }
+
+    #[test]
+    fn test_merge_trash_orders_newest_first_and_limits() {
+        let items = merge_trash(
+            vec![trashed("contact", "ada", 5), trashed("contact", "bob", 1)],
+            vec![trashed("company", "acme", 3)],
+            chrono::Duration::days(30),
+            2,
+        );
+
+        let ids: Vec<&str> = items.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, vec!["bob", "acme"]);
+        assert_eq!(items[1].entity_type, AuditEntity::Company);
+    }
+
+    #[test]
+    fn test_purge_takes_records_once_the_listed_date_has_passed() {
+        let retention = chrono::Duration::days(30);
+        let deleted_at: DateTime<Utc> = "2026-03-01T12:00:00Z".parse().unwrap();
+        let record = TrashedRecord {
+            id: Thing::from(("contact", "ada")),
+            name: "Ada".to_string(),
+            deleted_at,
+        };
+
+        let items = merge_trash(vec![record], Vec::new(), retention, 10);
+        let listed: DateTime<Utc> = "2026-03-31T12:00:00Z".parse().unwrap();
+        assert_eq!(items[0].purge_after, listed);
+
+        // The purge deletes records trashed before the cutoff
+        let second = chrono::Duration::seconds(1);
+        assert_eq!(purge_cutoff(listed, retention), deleted_at);
+        assert!(deleted_at >= purge_cutoff(listed - second, retention));
+        assert!(deleted_at < purge_cutoff(listed + second, retention));
+    }
+}
@@ -0,0 +1,305 @@
+//! Webhook Dispatcher - background delivery of queued webhook events
+//!
+//! Polls for due deliveries, POSTs each one, and records the attempt.
+//! Failures are retried with exponential backoff until `max_attempts`,
+//! after which the delivery is marked failed.
+//!
+//! Every request is signed: `X-Webhook-Signature: sha256=<hex>` is an
+//! HMAC-SHA256 over `"<timestamp>.<body>"` with the webhook's secret, and
+//! the timestamp is sent in `X-Webhook-Timestamp` so receivers can reject
+//! replays.
+//!
+//! The URL's host is resolved before every attempt, and an attempt is
+//! failed when it resolves to an internal address. The request connects
+//! only to the addresses checked, so the host can't resolve elsewhere in
+//! between. Redirects aren't followed.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tokio::task::JoinHandle;
+
+use crate::config::WebhookConfig;
+use crate::db::Database;
+use crate::domain::retry_delay;
+use crate::error::{AppError, AppResult};
+use crate::models::{DeliveryStatus, WebhookAttempt};
+use crate::repositories::{DueDelivery, WebhookRepository};
+use crate::services::public_host::resolve_public;
+use crate::shutdown::Shutdown;
+
+/// Deliveries claimed per poll
+const BATCH_SIZE: u32 = 50;
+
+pub struct WebhookDispatcher {
+    repo: WebhookRepository,
+    config: WebhookConfig,
+}
+
+impl WebhookDispatcher {
+    pub fn new(db: Arc<Database>, config: &WebhookConfig) -> AppResult<Self> {
+        Ok(Self {
+            repo: WebhookRepository::new(db),
+            config: config.clone(),
+        })
+    }
+
+    /// Run the dispatcher loop on a background task until `shutdown`
+    pub fn spawn(self, shutdown: Shutdown) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(self.config.poll_interval_secs.max(1)));
+
+            while shutdown.tick(&mut interval).await {
+                if let Err(e) = self.run_once().await {
+                    tracing::error!("Webhook dispatch failed: {}", e);
+                }
+            }
+
+            // Deliver what the last requests queued before exiting; failures
+            // are rescheduled, so this ends once nothing is due
+            loop {
+                match self.run_once().await {
+                    Ok(0) => break,
+                    Ok(attempted) => tracing::info!("Attempted {} webhook deliveries while shutting down", attempted),
+                    Err(e) => {
+                        tracing::error!("Webhook dispatch failed while shutting down: {}", e);
+                        break;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Deliver everything that is currently due
+    ///
+    /// Returns the number of deliveries attempted.
+    pub async fn run_once(&self) -> AppResult<usize> {
+        let now = Utc::now();
+        // Long enough to cover the request itself plus recording it
+        let lease = chrono::Duration::seconds(self.config.request_timeout_secs as i64 + 30);
+
+        let due = self.repo.claim_due(now, now + lease, BATCH_SIZE).await?;
+        let attempted = due.len();
+
+        futures::future::join_all(due.into_iter().map(|d| self.deliver(d))).await;
+
+        Ok(attempted)
+    }
+
+    async fn deliver(&self, delivery: DueDelivery) {
+        let attempt = delivery.attempts + 1;
+        let started = Instant::now();
+
+        let (response_status, error) = match (&delivery.webhook_url, &delivery.webhook_secret) {
+            (Some(url), Some(secret)) => self.send(&delivery, url, secret).await,
+            _ => (None, Some("Webhook no longer exists".to_string())),
+        };
+
+        let succeeded = response_status.is_some_and(|s| (200..300).contains(&s));
+        let (status, next_attempt_at) = next_state(
+            attempt,
+            succeeded,
+            delivery.webhook_url.is_some(),
+            &self.config,
+            Utc::now(),
+        );
+
+        let record = WebhookAttempt {
+            id: None,
+            workspace: delivery.workspace.clone(),
+            delivery: delivery.id.clone(),
+            attempt,
+            response_status,
+            error,
+            duration_ms: started.elapsed().as_millis() as u64,
+            attempted_at: Utc::now(),
+        };
+
+        if let Err(e) = self.repo.record_attempt(record, status, next_attempt_at).await {
+            // The lease expires and the delivery is retried
+            tracing::error!("Failed to record webhook attempt for {}: {}", delivery.id, e);
+        }
+    }
+
+    /// POST the payload, returning the response status or the error
+    async fn send(
+        &self,
+        delivery: &DueDelivery,
+        url: &str,
+        secret: &str,
+    ) -> (Option<u16>, Option<String>) {
+        let http = match check_destination(url).await {
+            Ok((host, addrs)) => http_client(&self.config, &host, &addrs),
+            Err(e) => Err(e),
+        };
+        let http = match http {
+            Ok(http) => http,
+            Err(e) => return (None, Some(e.to_string())),
+        };
+
+        let body = match serde_json::to_string(&delivery.payload) {
+            Ok(body) => body,
+            Err(e) => return (None, Some(format!("Failed to encode payload: {}", e))),
+        };
+        let timestamp = Utc::now().timestamp();
+        let signature = match sign_payload(secret, timestamp, &body) {
+            Ok(signature) => signature,
+            Err(e) => return (None, Some(e.to_string())),
+        };
+
+        let result = http
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header("User-Agent", "crm.hey.sh-webhooks/1")
+            .header("X-Webhook-Event", delivery.event.as_str())
+            .header("X-Webhook-Delivery", delivery.id.id.to_string())
+            .header("X-Webhook-Timestamp", timestamp.to_string())
+            .header("X-Webhook-Signature", signature)
+            .body(body)
+            .send()
+            .await;
+
+        match result {
+            Ok(response) if response.status().is_success() => (Some(response.status().as_u16()), None),
+            Ok(response) => (
+                Some(response.status().as_u16()),
+                Some(format!("Receiver responded with {}", response.status())),
+            ),
+            Err(e) => (None, Some(e.to_string())),
+        }
+    }
+}
+
+/// The host of a webhook URL and its addresses, refused when any of them
+/// is internal
+///
+/// The URL is parsed as reqwest parses it for the request, so the host
+/// checked and pinned is the one connected to.
+async fn check_destination(url: &str) -> AppResult<(String, Vec<SocketAddr>)> {
+    let parsed =
+        reqwest::Url::parse(url).map_err(|e| AppError::Validation(format!("{} is not a valid URL: {}", url, e)))?;
+    let (Some(host), Some(port)) = (parsed.host_str(), parsed.port_or_known_default()) else {
+        return Err(AppError::Validation(format!("{} has no host", url)));
+    };
+    let addrs = resolve_public(host, port).await?;
+    Ok((host.to_lowercase(), addrs))
+}
+
+/// A client that connects to `host` only at `addrs`
+///
+/// Built per delivery: a shared client would resolve the host again when
+/// connecting. Address literals aren't resolved at all.
+fn http_client(config: &WebhookConfig, host: &str, addrs: &[SocketAddr]) -> AppResult<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.request_timeout_secs))
+        .redirect(reqwest::redirect::Policy::none())
+        .resolve_to_addrs(host, addrs)
+        .build()
+        .map_err(|e| AppError::Internal(format!("Failed to build webhook client: {}", e)))
+}
+
+/// Signature header value for a payload
+pub fn sign_payload(secret: &str, timestamp: i64, body: &str) -> AppResult<String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| AppError::Internal(format!("Failed to sign webhook payload: {}", e)))?;
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+
+    Ok(format!("sha256={}", hex::encode(mac.finalize().into_bytes())))
+}
+
+/// Where a delivery goes after an attempt
+///
+/// A delivery whose webhook is gone is failed straight away - there is
+/// nothing to retry against.
+fn next_state(
+    attempt: u32,
+    succeeded: bool,
+    webhook_exists: bool,
+    config: &WebhookConfig,
+    now: DateTime<Utc>,
+) -> (DeliveryStatus, DateTime<Utc>) {
+    if succeeded {
+        (DeliveryStatus::Succeeded, now)
+    } else if !webhook_exists || attempt >= config.max_attempts {
+        (DeliveryStatus::Failed, now)
+    } else {
+        (
+            DeliveryStatus::Pending,
+            now + retry_delay(attempt, config.initial_backoff_secs),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_payload_is_stable() {
+        let a = sign_payload("whsec_test", 1_700_000_000, r#"{"event":"contact.created"}"#).unwrap();
+        let b = sign_payload("whsec_test", 1_700_000_000, r#"{"event":"contact.created"}"#).unwrap();
+
+        assert_eq!(a, b);
+        assert!(a.starts_with("sha256="));
+        assert_eq!(a.len(), "sha256=".len() + 64);
+    }
+
+    #[test]
+    fn test_sign_payload_covers_timestamp_and_secret() {
+        let body = r#"{"event":"contact.created"}"#;
+        let base = sign_payload("whsec_test", 1_700_000_000, body).unwrap();
+
+        assert_ne!(base, sign_payload("whsec_test", 1_700_000_001, body).unwrap());
+        assert_ne!(base, sign_payload("whsec_other", 1_700_000_000, body).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_destination_is_checked_and_pinned() {
+        assert!(check_destination("http://127.0.0.1/hook").await.is_err());
+        assert!(check_destination("https://169.254.169.254/latest").await.is_err());
+        assert!(check_destination("http://169.254.169.254\\@example.com/").await.is_err());
+
+        let (host, addrs) = check_destination("https://8.8.8.8/hook").await.unwrap();
+        assert_eq!(host, "8.8.8.8");
+        assert_eq!(addrs, vec!["8.8.8.8:443".parse().unwrap()]);
+        assert!(http_client(&WebhookConfig::default(), &host, &addrs).is_ok());
+    }
+
+    #[test]
+    fn test_next_state_success() {
+        let now = Utc::now();
+        let (status, _) = next_state(1, true, true, &WebhookConfig::default(), now);
+
+        assert_eq!(status, DeliveryStatus::Succeeded);
+    }
+
+    #[test]
+    fn test_next_state_retries_with_backoff() {
+        let now = Utc::now();
+        let config = WebhookConfig::default();
+
+        let (status, next) = next_state(2, false, true, &config, now);
+
+        assert_eq!(status, DeliveryStatus::Pending);
+        assert_eq!(next, now + retry_delay(2, config.initial_backoff_secs));
+    }
+
+    #[test]
+    fn test_next_state_gives_up() {
+        let now = Utc::now();
+        let config = WebhookConfig::default();
+
+        let (status, _) = next_state(config.max_attempts, false, true, &config, now);
+        assert_eq!(status, DeliveryStatus::Failed);
+
+        let (status, _) = next_state(1, false, false, &config, now);
+        assert_eq!(status, DeliveryStatus::Failed);
+    }
+}
@@ -0,0 +1,226 @@
+//! Webhook Service - registering webhooks and publishing events to them
+//!
+//! Publishing only queues a delivery per subscribed webhook; the HTTP
+//! requests are made later by the WebhookDispatcher, so a slow or broken
+//! receiver never holds up the request that triggered the event.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::db::{workspace_thing, Database};
+use crate::domain::{validate_webhook_events, validate_webhook_url, WebhookEvent};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    CreateWebhookRequest, DeliveryStatus, Webhook, WebhookDelivery, WebhookDeliveryDetailResponse,
+    WebhookDeliveryResponse, WebhookResponse,
+};
+use crate::repositories::WebhookRepository;
+
+pub struct WebhookService {
+    repo: WebhookRepository,
+}
+
+impl WebhookService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            repo: WebhookRepository::new(db),
+        }
+    }
+
+    /// Register a webhook
+    ///
+    /// The signing secret is generated here and returned only this once.
+    pub async fn register(
+        &self,
+        workspace_id: &str,
+        req: CreateWebhookRequest,
+    ) -> AppResult<WebhookResponse> {
+        let url = req.url.trim().to_string();
+        validate_webhook_url(&url)?;
+
+        let mut events: Vec<WebhookEvent> = Vec::new();
+        for event in req.events {
+            if !events.contains(&event) {
+                events.push(event);
+            }
+        }
+        validate_webhook_events(&events)?;
+
+        let now = Utc::now();
+        let webhook = self
+            .repo
+            .create(Webhook {
+                id: None,
+                workspace: workspace_thing(workspace_id),
+                url,
+                events,
+                description: req.description,
+                secret: generate_secret(),
+                active: true,
+                created_at: now,
+                updated_at: now,
+            })
+            .await?;
+
+        let secret = webhook.secret.clone();
+        let mut response = WebhookResponse::from(webhook);
+        response.secret = Some(secret);
+        Ok(response)
+    }
+
+    pub async fn list(&self, workspace_id: &str) -> AppResult<Vec<WebhookResponse>> {
+        let webhooks = self.repo.find_all(workspace_id).await?;
+        Ok(webhooks.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get(&self, workspace_id: &str, id: &str) -> AppResult<WebhookResponse> {
+        self.repo
+            .find_by_id(workspace_id, id)
+            .await?
+            .map(Into::into)
+            .ok_or_else(|| AppError::NotFound("Webhook not found".into()))
+    }
+
+    pub async fn delete(&self, workspace_id: &str, id: &str) -> AppResult<()> {
+        if !self.repo.delete(workspace_id, id).await? {
+            return Err(AppError::NotFound("Webhook not found".into()));
+        }
+        Ok(())
+    }
+
+    /// Deliveries made for a webhook, newest first
+    pub async fn deliveries(
+        &self,
+        workspace_id: &str,
+        webhook_id: &str,
+        status: Option<DeliveryStatus>,
+        limit: u32,
+        offset: u32,
+    ) -> AppResult<Vec<WebhookDeliveryResponse>> {
+        // 404 for someone else's webhook rather than an empty list
+        self.get(workspace_id, webhook_id).await?;
+
+        let deliveries = self
+            .repo
+            .find_deliveries(workspace_id, webhook_id, status, limit, offset)
+            .await?;
+        Ok(deliveries.into_iter().map(Into::into).collect())
+    }
+
+    /// A single delivery with its full attempt log
+    pub async fn delivery(
+        &self,
+        workspace_id: &str,
+        webhook_id: &str,
+        delivery_id: &str,
+    ) -> AppResult<WebhookDeliveryDetailResponse> {
+        let delivery = self
+            .repo
+            .find_delivery(workspace_id, delivery_id)
+            .await?
+            .filter(|d| d.webhook.id.to_string() == webhook_id)
+            .ok_or_else(|| AppError::NotFound("Delivery not found".into()))?;
+
+        let attempts = self.repo.find_attempts(workspace_id, delivery_id).await?;
+
+        Ok(WebhookDeliveryDetailResponse {
+            delivery: delivery.into(),
+            attempt_log: attempts.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    /// Queue an event for every webhook subscribed to it
+    ///
+    /// Returns the number of deliveries queued.
+    pub async fn publish(
+        &self,
+        workspace_id: &str,
+        event: WebhookEvent,
+        data: serde_json::Value,
+    ) -> AppResult<usize> {
+        let webhooks = self.repo.find_subscribed(workspace_id, event).await?;
+        if webhooks.is_empty() {
+            return Ok(0);
+        }
+
+        let now = Utc::now();
+        let payload = event_payload(workspace_id, event, data);
+
+        let deliveries: Vec<WebhookDelivery> = webhooks
+            .into_iter()
+            .filter_map(|w| w.id)
+            .map(|webhook| WebhookDelivery {
+                id: None,
+                workspace: workspace_thing(workspace_id),
+                webhook,
+                event,
+                payload: payload.clone(),
+                status: DeliveryStatus::Pending,
+                attempts: 0,
+                next_attempt_at: now,
+                created_at: now,
+                updated_at: now,
+            })
+            .collect();
+
+        let queued = deliveries.len();
+        self.repo.enqueue(deliveries).await?;
+        Ok(queued)
+    }
+
+    /// Publish an event without failing the caller
+    ///
+    /// The event is a side effect of something that already happened;
+    /// failing to queue it is logged, not surfaced.
+    pub async fn notify(&self, workspace_id: &str, event: WebhookEvent, data: serde_json::Value) {
+        if let Err(e) = self.publish(workspace_id, event, data).await {
+            tracing::warn!("Failed to queue {} webhook: {}", event, e);
+        }
+    }
+}
+
+/// The JSON body every receiver gets
+fn event_payload(workspace_id: &str, event: WebhookEvent, data: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "id": format!("evt_{}", Uuid::new_v4().simple()),
+        "event": event,
+        "created_at": Utc::now(),
+        "workspace_id": workspace_id,
+        "data": data,
+    })
+}
+
+/// A new signing secret for a webhook
+pub fn generate_secret() -> String {
+    format!("whsec_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_payload_shape() {
+        let payload = event_payload(
+            "ws1",
+            WebhookEvent::ContactCreated,
+            serde_json::json!({ "id": "c1" }),
+        );
+
+        assert_eq!(payload["event"], "contact.created");
+        assert_eq!(payload["workspace_id"], "ws1");
+        assert_eq!(payload["data"]["id"], "c1");
+        assert!(payload["id"].as_str().unwrap().starts_with("evt_"));
+    }
+
+    #[test]
+    fn test_generated_secrets_are_unique() {
+        let a = generate_secret();
+        let b = generate_secret();
+
+        assert!(a.starts_with("whsec_"));
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,125 @@
+//! Graceful shutdown
+//!
+//! On SIGTERM or Ctrl-C the server stops accepting connections, ends the
+//! live streams and lets in-flight requests finish. Then the background
+//! jobs are told to stop: each finishes the pass it is in (every pass
+//! leaves its work recorded, so that is a safe place to stop) and exits,
+//! and the webhook dispatcher delivers what is still due first. Each phase
+//! gets `server.shutdown_timeout_secs`.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::Interval;
+
+/// A flag that is set once and waited on by whoever has a clone
+#[derive(Clone)]
+pub struct Shutdown {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        let (sender, _) = watch::channel(false);
+        Self { sender: Arc::new(sender) }
+    }
+}
+
+impl Shutdown {
+    pub fn trigger(&self) {
+        self.sender.send_replace(true);
+    }
+
+    /// Resolves once `trigger` has been called, or right away if it was
+    pub fn triggered(&self) -> impl Future<Output = ()> + Send + use<> {
+        let mut receiver = self.sender.subscribe();
+        async move {
+            let _ = receiver.wait_for(|triggered| *triggered).await;
+        }
+    }
+
+    /// Wait for the next tick of a job's interval
+    ///
+    /// Returns `false` instead once shut down, so a job loop reads
+    /// `while shutdown.tick(&mut interval).await { ... }`.
+    pub async fn tick(&self, interval: &mut Interval) -> bool {
+        tokio::select! {
+            biased;
+            _ = self.triggered() => false,
+            _ = interval.tick() => true,
+        }
+    }
+}
+
+/// Resolves on SIGTERM or Ctrl-C
+pub async fn signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => tracing::info!("Received Ctrl-C, shutting down"),
+        _ = terminate => tracing::info!("Received SIGTERM, shutting down"),
+    }
+}
+
+/// Wait up to `grace` for the background jobs to exit
+///
+/// Returns whether they all did; any still running are dropped with the
+/// runtime.
+pub async fn join(jobs: Vec<JoinHandle<()>>, grace: Duration) -> bool {
+    tokio::time::timeout(grace, futures::future::join_all(jobs)).await.is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tick_stops_once_triggered() {
+        let shutdown = Shutdown::default();
+        let mut interval = tokio::time::interval(Duration::from_millis(1));
+
+        assert!(shutdown.tick(&mut interval).await);
+
+        shutdown.clone().trigger();
+        assert!(!shutdown.tick(&mut interval).await);
+        // Later waiters see it too
+        shutdown.triggered().await;
+    }
+
+    #[tokio::test]
+    async fn test_join_gives_up_after_grace() {
+        let shutdown = Shutdown::default();
+        let stops = {
+            let shutdown = shutdown.clone();
+            tokio::spawn(async move { shutdown.triggered().await })
+        };
+        let never_stops = tokio::spawn(std::future::pending::<()>());
+
+        shutdown.trigger();
+        assert!(join(vec![stops], Duration::from_secs(5)).await);
+        assert!(!join(vec![never_stops], Duration::from_millis(10)).await);
+    }
+}
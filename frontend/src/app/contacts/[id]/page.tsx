@@ -20,13 +20,25 @@ const timelineTypeColors: Record<string, string> = {
   email_sent: 'bg-blue-100 text-blue-600',
   email_open: 'bg-green-100 text-green-600',
   email_click: 'bg-green-100 text-green-600',
+  email_received: 'bg-teal-100 text-teal-600',
+  external_event: 'bg-slate-100 text-slate-600',
   note: 'bg-yellow-100 text-yellow-600',
   call: 'bg-purple-100 text-purple-600',
   event_invite: 'bg-pink-100 text-pink-600',
+  event_registration: 'bg-pink-100 text-pink-600',
   event_attend: 'bg-pink-100 text-pink-600',
   landing_page_visit: 'bg-orange-100 text-orange-600',
+  form_submission: 'bg-orange-100 text-orange-600',
+  meeting_scheduled: 'bg-purple-100 text-purple-600',
+  meeting_attended: 'bg-purple-100 text-purple-600',
   task: 'bg-gray-100 text-gray-600',
   social_touch: 'bg-indigo-100 text-indigo-600',
+  enrichment: 'bg-cyan-100 text-cyan-600',
+  churned: 'bg-red-100 text-red-600',
+  email_bounce: 'bg-red-100 text-red-600',
+  email_complaint: 'bg-red-100 text-red-600',
+  prep_brief: 'bg-gray-100 text-gray-600',
+  unsubscribed: 'bg-red-100 text-red-600',
 }
 
 export default function ContactDetailPage() {
@@ -15,6 +15,10 @@ export interface Contact {
   email: string
   phone?: string
   linkedin_url?: string
+  timezone?: string
+  country?: string
+  region?: string
+  city?: string
   tags: string[]
   status: 'lead' | 'customer' | 'partner' | 'investor' | 'other'
   engagement_score: number
@@ -30,6 +34,10 @@ export interface Company {
   industry?: string
   size?: string
   tags: string[]
+  country?: string
+  region?: string
+  city?: string
+  timezone?: string
   created_at: string
   updated_at: string
 }
@@ -62,6 +70,7 @@ export interface TimelineEntry {
   id: string
   contact_id: string
   company_id?: string
+  campaign_id?: string
   type: string
   content: string
   metadata: Record<string, unknown>
@@ -84,18 +93,48 @@ export interface CampaignAnalytics {
   campaign_id: string
   total_contacts: number
   emails_sent: number
+  replies: number
   emails_opened: number
   emails_clicked: number
   landing_page_visits: number
   conversions: number
+  engaged_contacts: number
   open_rate: number
   click_rate: number
+  reply_rate: number
   conversion_rate: number
+  links: LinkClicks[]
+}
+
+export interface LinkClicks {
+  url: string
+  clicks: number
+  unique_contacts: number
+  email_clicks: number
+  social_clicks: number
+}
+
+export interface DeliverabilityAnalytics {
+  campaign_id: string
+  sent: number
+  delivered: number
+  hard_bounces: number
+  soft_bounces: number
+  complaints: number
+  bounce_rate: number
+  complaint_rate: number
 }
 
 export interface FunnelAnalytics {
   stages: { name: string; count: number; percentage: number }[]
   overall_conversion_rate: number
+  stage_conversions: {
+    from_status: string
+    to_status: string
+    conversions: number
+    avg_days: number
+    median_days: number
+  }[]
 }
 
 // API functions
@@ -225,6 +264,10 @@ export const api = {
       const { data } = await client.get<CampaignAnalytics>(`/analytics/campaign/${id}`)
       return data
     },
+    deliverability: async (id: string) => {
+      const { data } = await client.get<DeliverabilityAnalytics>(`/analytics/campaign/${id}/deliverability`)
+      return data
+    },
     funnel: async () => {
       const { data } = await client.get<FunnelAnalytics>('/analytics/funnel')
       return data